@@ -2,8 +2,7 @@
 
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use flux_core::archive::{pack_with_strategy, PackOptions};
-use std::fs::{self, File};
-use std::io::Write;
+use std::fs;
 use std::process::Command;
 use tempfile::TempDir;
 
@@ -114,6 +113,7 @@ fn bench_flux_vs_tar(c: &mut Criterion) {
                     threads: None,
                     force_compress: false,
                     follow_symlinks: false,
+                    ..Default::default()
                 };
 
                 pack_with_strategy(
@@ -180,6 +180,7 @@ fn bench_compression_ratio(c: &mut Criterion) {
                     threads: None,
                     force_compress: false,
                     follow_symlinks: false,
+                    ..Default::default()
                 };
 
                 pack_with_strategy(input_dir.path(), &output, None, options).unwrap();
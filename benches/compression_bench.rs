@@ -6,7 +6,6 @@ use flux_core::strategy::Algorithm;
 use rand::{Rng, SeedableRng};
 use std::fs::{self, File};
 use std::io::Write;
-use std::path::PathBuf;
 use tempfile::TempDir;
 
 /// Generate test data with specified characteristics
@@ -65,6 +64,7 @@ fn bench_small_files(c: &mut Criterion) {
                             threads: Some(4),
                             force_compress: false,
                             follow_symlinks: false,
+                            ..Default::default()
                         };
 
                         pack_with_strategy(
@@ -114,6 +114,7 @@ fn bench_large_file(c: &mut Criterion) {
                             threads: Some(4),
                             force_compress: false,
                             follow_symlinks: false,
+                            ..Default::default()
                         };
 
                         pack_with_strategy(
@@ -156,6 +157,7 @@ fn bench_compression_levels(c: &mut Criterion) {
                         threads: Some(4),
                         force_compress: false,
                         follow_symlinks: false,
+                        ..Default::default()
                     };
 
                     pack_with_strategy(
@@ -188,8 +190,7 @@ fn bench_smart_strategy(c: &mut Criterion) {
                     let text_file = temp_dir.path().join(format!("doc_{}.txt", i));
                     fs::write(&text_file, "This is a text document.\n".repeat(1000)).unwrap();
                 }
-                for i in 0..5 {
-                    let binary_file = temp_dir.path().join(format!("data_{}.bin", i));
+                for _ in 0..5 {
                     generate_test_data(&temp_dir, 1, 1024 * 1024, false);
                 }
                 (temp_dir, TempDir::new().unwrap())
@@ -203,6 +204,7 @@ fn bench_smart_strategy(c: &mut Criterion) {
                     threads: None,
                     force_compress: false,
                     follow_symlinks: false,
+                    ..Default::default()
                 };
 
                 pack_with_strategy(
@@ -225,8 +227,7 @@ fn bench_smart_strategy(c: &mut Criterion) {
                     let text_file = temp_dir.path().join(format!("doc_{}.txt", i));
                     fs::write(&text_file, "This is a text document.\n".repeat(1000)).unwrap();
                 }
-                for i in 0..5 {
-                    let binary_file = temp_dir.path().join(format!("data_{}.bin", i));
+                for _ in 0..5 {
                     generate_test_data(&temp_dir, 1, 1024 * 1024, false);
                 }
                 (temp_dir, TempDir::new().unwrap())
@@ -240,6 +241,7 @@ fn bench_smart_strategy(c: &mut Criterion) {
                     threads: Some(4),
                     force_compress: false,
                     follow_symlinks: false,
+                    ..Default::default()
                 };
 
                 pack_with_strategy(
@@ -0,0 +1,98 @@
+//! Pack and extract benchmarks over the shared flux-testing datasets
+//!
+//! Every machine generates byte-identical input, so results can be compared
+//! across runs and hosts. Use `FLUX_BENCH_SCALE=0.1` for a quick pass.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use flux_core::archive::{extract, pack_with_strategy, PackOptions};
+use flux_core::strategy::Algorithm;
+use flux_testing::bench::{machine_summary, DatasetSpec, DatasetStats};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn datasets() -> Vec<DatasetSpec> {
+    vec![
+        DatasetSpec::many_small_files(),
+        DatasetSpec::few_huge_files(),
+        DatasetSpec::media_mix(),
+    ]
+}
+
+fn pack_options(algorithm: Algorithm) -> PackOptions {
+    PackOptions {
+        smart: false,
+        algorithm: Some(algorithm.to_string()),
+        level: Some(3),
+        threads: Some(4),
+        force_compress: false,
+        follow_symlinks: false,
+        ..Default::default()
+    }
+}
+
+/// Generates a dataset once and keeps it alive for the whole group
+fn prepare(spec: &DatasetSpec) -> (TempDir, PathBuf, DatasetStats) {
+    let dir = TempDir::new().unwrap();
+    let input = dir.path().join(spec.name);
+    let stats = spec.generate(&input).unwrap();
+    (dir, input, stats)
+}
+
+fn pack_to(input: &Path, archive: &Path, algorithm: Algorithm) {
+    pack_with_strategy(input, archive, None, pack_options(algorithm)).unwrap();
+}
+
+/// Benchmark packing each dataset with zstd
+fn bench_pack_datasets(c: &mut Criterion) {
+    eprintln!("dataset_bench: {}", machine_summary());
+
+    let mut group = c.benchmark_group("pack_datasets");
+    group.sample_size(10);
+
+    for spec in datasets() {
+        let (_dir, input, stats) = prepare(&spec);
+        group.throughput(Throughput::Bytes(stats.bytes));
+        group.bench_with_input(BenchmarkId::new("zstd", spec.name), &input, |b, input| {
+            b.iter_with_setup(
+                || TempDir::new().unwrap(),
+                |output_dir| {
+                    let archive = output_dir.path().join("bench.tar.zst");
+                    pack_to(black_box(input), &archive, Algorithm::Zstd);
+                },
+            );
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark extracting each dataset from a zstd archive
+fn bench_extract_datasets(c: &mut Criterion) {
+    let mut group = c.benchmark_group("extract_datasets");
+    group.sample_size(10);
+
+    for spec in datasets() {
+        let (dir, input, stats) = prepare(&spec);
+        let archive = dir.path().join("bench.tar.zst");
+        pack_to(&input, &archive, Algorithm::Zstd);
+
+        group.throughput(Throughput::Bytes(stats.bytes));
+        group.bench_with_input(
+            BenchmarkId::new("zstd", spec.name),
+            &archive,
+            |b, archive| {
+                b.iter_with_setup(
+                    || TempDir::new().unwrap(),
+                    |output_dir| {
+                        extract(black_box(archive), black_box(output_dir.path())).unwrap();
+                    },
+                );
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_pack_datasets, bench_extract_datasets);
+criterion_main!(benches);
@@ -0,0 +1,591 @@
+//! Batch files for `flux batch`
+//!
+//! A batch file lists pack, extract and sync jobs with the same options as
+//! the matching commands, in YAML or TOML:
+//!
+//! ```yaml
+//! parallel: false
+//! jobs:
+//!   - name: nightly docs
+//!     op: pack
+//!     input: ~/docs
+//!     output: backups/docs.tar.zst
+//!     level: 9
+//!     exclude_vcs: true
+//!   - op: sync
+//!     source: ~/projects
+//!     target: backups/projects.tar.gz
+//! ```
+//!
+//! Relative paths are resolved against the directory holding the batch file,
+//! so the same file works from any working directory.
+
+use crate::sync::{manifest_check, sync_directory};
+use crate::{archive_metadata, check_outcome, exclude_filter, named_format, source_date_epoch};
+use anyhow::{Context, Result};
+use flux_core::archive::extractor::ExtractEntryOptions;
+use flux_core::archive::{
+    CaseCollisions, ExtractOptions, NameNormalization, PackOptions, PackOrder, TamperPolicy,
+};
+use flux_core::ops::{ExtractJob, PackJob};
+use flux_core::progress::NoProgress;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tracing::{error, info};
+use yaml_rust2::{Yaml, YamlLoader};
+
+/// A parsed batch file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchFile {
+    /// Run all jobs at the same time instead of one after another
+    #[serde(default)]
+    pub parallel: bool,
+    /// Skip the remaining jobs once one fails; only applies to sequential runs
+    #[serde(default)]
+    pub stop_on_error: bool,
+    pub jobs: Vec<Job>,
+}
+
+/// One job of a batch file
+///
+/// Its name is read along with the operation's options, so that a misspelled
+/// key is rejected rather than ignored.
+#[derive(Debug, Deserialize)]
+#[serde(from = "Operation")]
+pub struct Job {
+    /// Name shown in the report
+    pub name: Option<String>,
+    pub operation: Operation,
+}
+
+/// What a job does, with the options of the matching command
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase", deny_unknown_fields)]
+pub enum Operation {
+    Pack {
+        /// Moved into [`Job::name`] once read
+        name: Option<String>,
+        input: PathBuf,
+        output: PathBuf,
+        format: Option<String>,
+        #[serde(default)]
+        smart: bool,
+        algo: Option<String>,
+        level: Option<u32>,
+        threads: Option<usize>,
+        #[serde(default)]
+        follow_symlinks: bool,
+        max_depth: Option<usize>,
+        #[serde(default)]
+        force_compress: bool,
+        #[serde(default)]
+        exclude_vcs: bool,
+        /// Values for the archive's metadata, as with `--meta`
+        #[serde(default)]
+        meta: BTreeMap<String, String>,
+        /// Latest timestamp to store, as with `--mtime`; defaults to
+        /// `SOURCE_DATE_EPOCH`
+        mtime: Option<u64>,
+        /// As with `--normalize-names`
+        #[serde(default)]
+        normalize_names: NameNormalization,
+        /// As with `--mmap`
+        #[serde(default)]
+        mmap: bool,
+        /// As with `--order`
+        #[serde(default)]
+        order: PackOrder,
+    },
+    Extract {
+        name: Option<String>,
+        archive: PathBuf,
+        output: PathBuf,
+        #[serde(default)]
+        overwrite: bool,
+        #[serde(default)]
+        skip: bool,
+        #[serde(default)]
+        rename: bool,
+        strip_components: Option<usize>,
+        #[serde(default)]
+        hoist: bool,
+        /// As with `--case-collisions`
+        #[serde(default)]
+        case_collisions: CaseCollisions,
+        /// As with `--normalize-names`
+        #[serde(default)]
+        normalize_names: NameNormalization,
+        /// As with `--manifest`
+        manifest: Option<PathBuf>,
+        /// As with `--on-tampered`
+        #[serde(default)]
+        on_tampered: TamperPolicy,
+    },
+    Sync {
+        name: Option<String>,
+        source: PathBuf,
+        target: PathBuf,
+        algo: Option<String>,
+        level: Option<u32>,
+        threads: Option<usize>,
+        #[serde(default)]
+        follow_symlinks: bool,
+        #[serde(default)]
+        full: bool,
+        #[serde(default)]
+        paranoid: bool,
+        #[serde(default)]
+        exclude_vcs: bool,
+        #[serde(default)]
+        encrypt_manifest: bool,
+    },
+}
+
+impl Operation {
+    fn kind(&self) -> &'static str {
+        match self {
+            Operation::Pack { .. } => "pack",
+            Operation::Extract { .. } => "extract",
+            Operation::Sync { .. } => "sync",
+        }
+    }
+
+    /// The path the job is best known by
+    fn subject(&self) -> &Path {
+        match self {
+            Operation::Pack { output, .. } => output,
+            Operation::Extract { archive, .. } => archive,
+            Operation::Sync { target, .. } => target,
+        }
+    }
+
+    fn paths_mut(&mut self) -> Vec<&mut PathBuf> {
+        match self {
+            Operation::Pack { input, output, .. } => vec![input, output],
+            Operation::Extract {
+                archive, output, ..
+            } => vec![archive, output],
+            Operation::Sync { source, target, .. } => vec![source, target],
+        }
+    }
+
+    fn run(&self) -> Result<()> {
+        match self {
+            Operation::Pack {
+                name: _,
+                input,
+                output,
+                format,
+                smart,
+                algo,
+                level,
+                threads,
+                follow_symlinks,
+                max_depth,
+                force_compress,
+                exclude_vcs,
+                meta,
+                mtime,
+                normalize_names,
+                mmap,
+                order,
+            } => {
+                let options = PackOptions {
+                    smart: *smart,
+                    algorithm: algo.clone(),
+                    level: *level,
+                    threads: *threads,
+                    force_compress: *force_compress,
+                    follow_symlinks: *follow_symlinks,
+                    max_depth: *max_depth,
+                    filter: exclude_filter(*exclude_vcs)?,
+                    metadata: archive_metadata(meta.clone()),
+                    clamp_mtime: match mtime {
+                        Some(mtime) => Some(*mtime),
+                        None => source_date_epoch()?,
+                    },
+                    normalize_names: *normalize_names,
+                    strip_prefix: None,
+                    prefix: None,
+                    use_mmap: *mmap,
+                    transform: None,
+                    time_budget: None,
+                    paranoid: false,
+                    hashes: None,
+                    entry_order: *order,
+                };
+                // As `flux pack` does, without a format the output's name
+                // must call for one
+                let format = match format {
+                    Some(format) => format.clone(),
+                    None => named_format(output)?,
+                };
+                PackJob {
+                    format: Some(format),
+                    ..PackJob::new(vec![input.clone()], output, options)
+                }
+                .run(&NoProgress)?;
+            }
+            Operation::Extract {
+                name: _,
+                archive,
+                output,
+                overwrite,
+                skip,
+                rename,
+                strip_components,
+                hoist,
+                case_collisions,
+                normalize_names,
+                manifest,
+                on_tampered,
+            } => {
+                let options = ExtractOptions {
+                    overwrite: *overwrite,
+                    skip: *skip,
+                    rename: *rename,
+                    strip_components: *strip_components,
+                    hoist: *hoist,
+                    case_collisions: *case_collisions,
+                    fold_case: None,
+                    normalize_names: *normalize_names,
+                    manifest_check: manifest
+                        .as_deref()
+                        .map(|path| manifest_check(path, *on_tampered))
+                        .transpose()?,
+                    entry_filter: None,
+                    ..ExtractOptions::default()
+                };
+
+                // Renaming entries and checking a manifest need the whole
+                // archive extraction; the rest is what the job does
+                let per_entry = !options.rename
+                    && options.case_collisions == CaseCollisions::default()
+                    && options.normalize_names == NameNormalization::Keep
+                    && options.manifest_check.is_none();
+                if per_entry {
+                    let job = ExtractJob {
+                        hoist: options.hoist,
+                        ..ExtractJob::new(
+                            archive,
+                            output,
+                            ExtractEntryOptions {
+                                // `skip` keeps existing files even with `overwrite`
+                                overwrite: options.overwrite && !options.skip,
+                                preserve_permissions: true,
+                                preserve_timestamps: true,
+                                follow_symlinks: false,
+                                password: None,
+                                symlink_fallback: Default::default(),
+                                strip_components: options.strip_components,
+                            },
+                        )
+                    };
+                    check_outcome(job.run(&NoProgress, |_, _| None)?, false)?;
+                    return Ok(());
+                }
+
+                let outcome = flux_core::archive::extract_with_options(archive, output, options)?;
+                check_outcome(outcome, false)?;
+            }
+            Operation::Sync {
+                name: _,
+                source,
+                target,
+                algo,
+                level,
+                threads,
+                follow_symlinks,
+                full,
+                paranoid,
+                exclude_vcs,
+                encrypt_manifest,
+            } => {
+                let options = PackOptions {
+                    smart: false,
+                    algorithm: algo.clone(),
+                    level: *level,
+                    threads: *threads,
+                    force_compress: false,
+                    follow_symlinks: *follow_symlinks,
+                    max_depth: None,
+                    filter: exclude_filter(*exclude_vcs)?,
+                    metadata: None,
+                    clamp_mtime: None,
+                    normalize_names: NameNormalization::Keep,
+                    strip_prefix: None,
+                    prefix: None,
+                    use_mmap: false,
+                    transform: None,
+                    time_budget: None,
+                    paranoid: *paranoid,
+                    hashes: None,
+                    entry_order: PackOrder::Walk,
+                };
+                sync_directory(source, target, options, *full, *encrypt_manifest)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl From<Operation> for Job {
+    fn from(mut operation: Operation) -> Self {
+        let name = match &mut operation {
+            Operation::Pack { name, .. }
+            | Operation::Extract { name, .. }
+            | Operation::Sync { name, .. } => name.take(),
+        };
+        Job { name, operation }
+    }
+}
+
+impl Job {
+    /// The job's name, or its operation and main path
+    pub fn label(&self) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            format!(
+                "{} {}",
+                self.operation.kind(),
+                self.operation.subject().display()
+            )
+        })
+    }
+}
+
+/// How a job ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Succeeded,
+    Failed,
+    /// Not run because an earlier job failed
+    Skipped,
+}
+
+/// Outcome of one job
+#[derive(Debug, Serialize)]
+pub struct JobReport {
+    pub name: String,
+    pub operation: &'static str,
+    pub status: JobStatus,
+    /// Wall-clock time in seconds
+    pub seconds: f64,
+    pub error: Option<String>,
+}
+
+/// Outcome of a whole batch, in the order the jobs were listed
+#[derive(Debug, Serialize)]
+pub struct BatchReport {
+    pub jobs: Vec<JobReport>,
+    /// Wall-clock time in seconds
+    pub seconds: f64,
+}
+
+impl BatchReport {
+    /// Number of jobs that failed
+    pub fn failed(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|job| job.status == JobStatus::Failed)
+            .count()
+    }
+
+    /// Print the report as a table
+    pub fn print(&self) {
+        println!(
+            "{:<40} {:<8} {:<10} {:>10}  Error",
+            "Job", "Op", "Status", "Seconds"
+        );
+        println!("{}", "-".repeat(100));
+        for job in &self.jobs {
+            let status = match job.status {
+                JobStatus::Succeeded => "ok",
+                JobStatus::Failed => "FAILED",
+                JobStatus::Skipped => "skipped",
+            };
+            println!(
+                "{:<40} {:<8} {:<10} {:>10.1}  {}",
+                job.name,
+                job.operation,
+                status,
+                job.seconds,
+                job.error.as_deref().unwrap_or("")
+            );
+        }
+        println!(
+            "{} jobs, {} failed, {:.1}s",
+            self.jobs.len(),
+            self.failed(),
+            self.seconds
+        );
+    }
+}
+
+/// Read a batch file, resolving its relative paths against its directory
+///
+/// Files ending in `.toml` are read as TOML, anything else as YAML.
+pub fn load(path: &Path) -> Result<BatchFile> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read batch file {}", path.display()))?;
+    let is_toml = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    let parsed = if is_toml {
+        toml::from_str(&text).map_err(anyhow::Error::from)
+    } else {
+        parse_yaml(&text)
+    };
+    // The cause names the offending key, so keep it in the message
+    let mut batch: BatchFile =
+        parsed.map_err(|e| anyhow::anyhow!("Invalid batch file {}: {:#}", path.display(), e))?;
+
+    let base = path.parent().unwrap_or(Path::new(""));
+    for job in &mut batch.jobs {
+        for path in job.operation.paths_mut() {
+            *path = base.join(expand_home(path));
+        }
+    }
+    Ok(batch)
+}
+
+/// Run every job of `batch` and report how each went
+pub fn run(batch: &BatchFile) -> BatchReport {
+    let start = Instant::now();
+    let jobs = if batch.parallel {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = batch
+                .jobs
+                .iter()
+                .map(|job| scope.spawn(move || run_job(job)))
+                .collect();
+            handles
+                .into_iter()
+                .zip(&batch.jobs)
+                .map(|(handle, job)| {
+                    handle.join().unwrap_or_else(|_| JobReport {
+                        name: job.label(),
+                        operation: job.operation.kind(),
+                        status: JobStatus::Failed,
+                        seconds: 0.0,
+                        error: Some("The job panicked".to_string()),
+                    })
+                })
+                .collect()
+        })
+    } else {
+        let mut reports: Vec<JobReport> = Vec::with_capacity(batch.jobs.len());
+        for job in &batch.jobs {
+            let failed = reports.iter().any(|r| r.status == JobStatus::Failed);
+            reports.push(if batch.stop_on_error && failed {
+                JobReport {
+                    name: job.label(),
+                    operation: job.operation.kind(),
+                    status: JobStatus::Skipped,
+                    seconds: 0.0,
+                    error: None,
+                }
+            } else {
+                run_job(job)
+            });
+        }
+        reports
+    };
+
+    BatchReport {
+        jobs,
+        seconds: start.elapsed().as_secs_f64(),
+    }
+}
+
+fn run_job(job: &Job) -> JobReport {
+    let name = job.label();
+    info!("Starting job: {}", name);
+    let start = Instant::now();
+    let result = job.operation.run();
+    let seconds = start.elapsed().as_secs_f64();
+
+    match result {
+        Ok(()) => {
+            info!("Finished job: {} ({:.1}s)", name, seconds);
+            JobReport {
+                name,
+                operation: job.operation.kind(),
+                status: JobStatus::Succeeded,
+                seconds,
+                error: None,
+            }
+        }
+        Err(e) => {
+            error!("Job failed: {}: {:#}", name, e);
+            JobReport {
+                name,
+                operation: job.operation.kind(),
+                status: JobStatus::Failed,
+                seconds,
+                error: Some(format!("{:#}", e)),
+            }
+        }
+    }
+}
+
+/// Replace a leading `~` with the home directory
+fn expand_home(path: &Path) -> PathBuf {
+    match path.strip_prefix("~") {
+        Ok(rest) => home_dir().map_or_else(|| path.to_path_buf(), |home| home.join(rest)),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os(if cfg!(windows) { "USERPROFILE" } else { "HOME" }).map(PathBuf::from)
+}
+
+/// Parse a YAML document into a [`BatchFile`]
+fn parse_yaml(text: &str) -> Result<BatchFile> {
+    let documents = YamlLoader::load_from_str(text)?;
+    let document = documents
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("The file is empty"))?;
+    Ok(serde_json::from_value(yaml_to_json(document)?)?)
+}
+
+/// Convert a YAML value so serde can deserialize it
+fn yaml_to_json(yaml: Yaml) -> Result<serde_json::Value> {
+    use serde_json::Value;
+
+    Ok(match yaml {
+        Yaml::Null => Value::Null,
+        Yaml::Boolean(b) => Value::Bool(b),
+        Yaml::Integer(i) => Value::from(i),
+        Yaml::Real(s) => s
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(|| anyhow::anyhow!("Invalid number: {}", s))?,
+        Yaml::String(s) => Value::String(s),
+        Yaml::Array(items) => {
+            Value::Array(items.into_iter().map(yaml_to_json).collect::<Result<_>>()?)
+        }
+        Yaml::Hash(hash) => {
+            let mut map = serde_json::Map::new();
+            for (key, value) in hash {
+                let key = match key {
+                    Yaml::String(s) => s,
+                    Yaml::Integer(i) => i.to_string(),
+                    Yaml::Boolean(b) => b.to_string(),
+                    other => anyhow::bail!("Unsupported key: {:?}", other),
+                };
+                map.insert(key, yaml_to_json(value)?);
+            }
+            Value::Object(map)
+        }
+        Yaml::Alias(_) | Yaml::BadValue => anyhow::bail!("Unsupported YAML value"),
+    })
+}
@@ -3,10 +3,26 @@
 //! This module provides cloud storage integration, allowing flux to work with
 //! S3, Google Cloud Storage, and Azure Blob Storage.
 
-
+use crate::progress::{Phase, ProgressDisplay};
 use anyhow::{Context, Result};
-use flux_cloud::{CloudPath, CloudReader, CloudWriter};
-use std::io::{Read, Seek, Write};
+use chrono::{DateTime, Utc};
+use flux_cloud::chain::{DELETED_EXTENSION, MANIFEST_EXTENSION};
+use flux_cloud::encryption::is_encrypted;
+use flux_cloud::parts::{self, split_parts, PartsReader, SplitKind};
+use flux_cloud::uploads::{abort_upload, is_marker, pending_uploads, PendingUpload};
+use flux_cloud::{
+    CloudPath, CloudReader, CloudStore, CloudWriter, CloudWriterGuard, DecryptingReader,
+    EncryptingWriter, ObjectMeta, ObjectPath,
+};
+use flux_core::config::Config;
+use flux_core::keys::Key;
+use flux_core::space::TempResource;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tracing::info;
 
 /// Check if a path is a cloud URL
 pub fn is_cloud_path(path: &str) -> bool {
@@ -22,18 +38,173 @@ pub trait CloudReadSeek: Read + Seek + Send {}
 /// Implement CloudReadSeek for CloudReader
 impl CloudReadSeek for CloudReader {}
 
-/// Create a reader for cloud storage
-pub fn create_cloud_reader(url: &str) -> Result<Box<dyn CloudReadSeek>> {
-    let reader = CloudReader::new(url)
-        .with_context(|| format!("Failed to create cloud reader for {}", url))?;
+/// Download the object at `url` into the file `name` in `scratch`, once
+/// its quota has room for it, showing the bytes received on `display`
+///
+/// An object encrypted client-side is decrypted with the key of the
+/// profile `url` falls under.
+pub fn download(
+    url: &str,
+    scratch: &TempResource,
+    name: &str,
+    display: &ProgressDisplay,
+) -> Result<PathBuf> {
+    let (store, cloud_path) = connect(url)?;
+    let reader = create_cloud_reader_from_store(
+        store.with_metrics(display.transfer_metrics()),
+        &cloud_path,
+    )?;
+    let (mut reader, size) = open_object(reader, url, || profile_key(url))?;
+    scratch.reserve(size)?;
+
+    display.phase(Phase::Download, Some(size));
+    display.status(url);
+    let dest = scratch.path().join(name);
+    let mut file = File::create(&dest)?;
+    io::copy(&mut reader, &mut file)?;
+    Ok(dest)
+}
+
+/// Download the archive at `url` into `scratch`, returning the file to
+/// open
+///
+/// An archive split into parts or spanned volumes is fetched whole, several
+/// objects at a time: parts are joined into one file, and the volumes of a
+/// spanned zip are kept side by side under their own names so the zip can
+/// be read across them. Nothing is fetched unless all of it fits in the
+/// quota of `scratch`.
+pub fn download_archive(
+    url: &str,
+    scratch: &TempResource,
+    display: &ProgressDisplay,
+) -> Result<PathBuf> {
+    let (store, cloud_path) = connect(url)?;
+    let store = store.with_metrics(display.transfer_metrics());
+    let split = split_parts(&store, &cloud_path.path)
+        .with_context(|| format!("Failed to look for the parts of {}", url))?;
+    let Some(split) = split else {
+        return download(url, scratch, "cloud_archive.tar", display);
+    };
+    scratch.reserve(split.size())?;
+    let dir = scratch.path();
+
+    info!(
+        "Downloading {} objects of {} ({} at a time)",
+        split.parts.len(),
+        url,
+        parts::DEFAULT_WINDOW
+    );
+    display.phase(Phase::Download, Some(split.size()));
+    display.status(url);
+    let paths: Vec<_> = split
+        .parts
+        .iter()
+        .map(|part| part.location.clone())
+        .collect();
+    let dest = dir.join(split.name());
+    match split.kind {
+        SplitKind::Parts => {
+            let mut reader = PartsReader::new(store, paths, parts::DEFAULT_WINDOW);
+            let mut file = File::create(&dest)?;
+            io::copy(&mut reader, &mut file)
+                .with_context(|| format!("Failed to download the parts of {}", url))?;
+        }
+        SplitKind::Spanned => {
+            parts::download_parts(&store, &paths, dir, parts::DEFAULT_WINDOW)
+                .with_context(|| format!("Failed to download the volumes of {}", url))?;
+        }
+    }
+    Ok(dest)
+}
+
+/// Reader of the plaintext of `reader`, an object at `url`, with its size
+///
+/// An object encrypted client-side is decrypted with the key `key` gives,
+/// which is only asked for then.
+pub fn open_object(
+    mut reader: Box<dyn CloudReadSeek>,
+    url: &str,
+    key: impl FnOnce() -> Result<Option<Key>>,
+) -> Result<(Box<dyn Read>, u64)> {
+    let mut head = Vec::new();
+    reader.by_ref().take(16).read_to_end(&mut head)?;
+    reader.seek(SeekFrom::Start(0))?;
+    if !is_encrypted(&head) {
+        let size = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(0))?;
+        return Ok((Box::new(reader), size));
+    }
+
+    let key = key()?.ok_or_else(|| {
+        anyhow::anyhow!(
+            "{} is encrypted client-side; set encryption_key in the cloud profile it lies under",
+            url
+        )
+    })?;
+    let reader = DecryptingReader::new(reader, &key)
+        .with_context(|| format!("Failed to decrypt {}", url))?;
+    let size = reader.size();
+    Ok((Box::new(reader), size))
+}
+
+/// Create a reader for an object in an already connected store
+pub fn create_cloud_reader_from_store(
+    store: CloudStore,
+    cloud_path: &CloudPath,
+) -> Result<Box<dyn CloudReadSeek>> {
+    let reader = CloudReader::from_store(store, cloud_path.path.clone())
+        .with_context(|| format!("Failed to create cloud reader for {}", cloud_path.path))?;
     Ok(Box::new(reader))
 }
 
-/// Create a writer for cloud storage
-pub fn create_cloud_writer(url: &str) -> Result<Box<dyn Write + Send>> {
-    let writer = CloudWriter::new(url)
-        .with_context(|| format!("Failed to create cloud writer for {}", url))?;
-    Ok(Box::new(writer))
+/// Upload the file `source` to `url`, showing the bytes sent on `display`
+///
+/// When the profile `url` falls under names an encryption key, the file
+/// is encrypted with it on the way. A failed upload is aborted, so no
+/// partial object is left behind.
+pub fn upload(source: &Path, url: &str, display: &ProgressDisplay) -> Result<()> {
+    // The passphrase of the key is asked for before anything is sent
+    let key = profile_key(url)?;
+    let (store, cloud_path) = connect(url)?;
+    let writer = create_cloud_writer_from_store(
+        store.with_metrics(display.transfer_metrics()),
+        &cloud_path,
+    )?;
+    let mut file = File::open(source)?;
+
+    display.phase(Phase::Upload, Some(file.metadata()?.len()));
+    display.status(url);
+    send(&mut file, writer, key.as_ref())
+}
+
+/// Write all of `source` through `writer` and complete the upload,
+/// encrypting it with `key` when there is one
+pub fn send(source: &mut impl Read, mut writer: CloudWriterGuard, key: Option<&Key>) -> Result<()> {
+    match key {
+        Some(key) => {
+            let mut writer = EncryptingWriter::new(writer, key)?;
+            io::copy(source, &mut writer)?;
+            writer.finish()?.finish()?;
+        }
+        None => {
+            io::copy(source, &mut writer)?;
+            writer.finish()?;
+        }
+    }
+    Ok(())
+}
+
+/// Create a writer for an object in an already connected store
+///
+/// The upload only completes when [`CloudWriterGuard::finish`] is called;
+/// dropping the writer after a failure aborts it.
+pub fn create_cloud_writer_from_store(
+    store: CloudStore,
+    cloud_path: &CloudPath,
+) -> Result<CloudWriterGuard> {
+    let writer = CloudWriter::from_store(store, cloud_path.path.clone())
+        .with_context(|| format!("Failed to create cloud writer for {}", cloud_path.path))?;
+    Ok(CloudWriterGuard::new(writer))
 }
 
 /// Parse and validate a cloud path
@@ -41,6 +212,217 @@ pub fn parse_cloud_path(url: &str) -> Result<CloudPath> {
     CloudPath::parse(url).with_context(|| format!("Failed to parse cloud URL: {}", url))
 }
 
+/// An object or folder found by `flux ls`
+#[derive(Debug, Clone, Serialize)]
+pub struct ListedObject {
+    /// Last component of the key, with a trailing `/` for folders
+    pub name: String,
+    /// Full key within the bucket
+    pub key: String,
+    pub is_dir: bool,
+    /// Size in bytes, 0 for folders
+    pub size: u64,
+    /// Last modification time, unknown for folders
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// Endpoint of the configured cloud profile `url` falls under, if any
+pub fn profile_endpoint(url: &str) -> Option<String> {
+    Config::load_or_default()
+        .cloud
+        .endpoint_for(url)
+        .map(str::to_string)
+}
+
+/// Key of the configured cloud profile `url` falls under, which its
+/// archives are encrypted with, if the profile names one
+pub fn profile_key(url: &str) -> Result<Option<Key>> {
+    let config = Config::load_or_default();
+    match config.cloud.encryption_key_for(url) {
+        Some(name) => Ok(Some(crate::keys::load(name)?)),
+        None => Ok(None),
+    }
+}
+
+/// Connect to the bucket of `url`, through the endpoint of its profile
+pub fn connect(url: &str) -> Result<(CloudStore, CloudPath)> {
+    let cloud_path = parse_cloud_path(url)?;
+    let store = CloudStore::with_endpoint(&cloud_path, profile_endpoint(url).as_deref())
+        .with_context(|| format!("Failed to connect to cloud storage for {}", url))?;
+    Ok((store, cloud_path))
+}
+
+/// Connect to the bucket of `url` and list what is directly below it
+pub fn list_location(url: &str) -> Result<Vec<ListedObject>> {
+    let (store, cloud_path) = connect(url)?;
+    list_objects(&store, &cloud_path)
+}
+
+/// List the objects and folders directly below `cloud_path`
+///
+/// Folders come first, then objects, each sorted by name.
+pub fn list_objects(store: &CloudStore, cloud_path: &CloudPath) -> Result<Vec<ListedObject>> {
+    let prefix = (!cloud_path.path.as_ref().is_empty()).then_some(&cloud_path.path);
+    let listing = store
+        .list(prefix)
+        .with_context(|| format!("Failed to list {}", cloud_path))?;
+
+    let mut folders: Vec<ListedObject> = listing
+        .common_prefixes
+        .iter()
+        .map(|prefix| ListedObject {
+            name: format!("{}/", prefix.filename().unwrap_or_default()),
+            key: prefix.to_string(),
+            is_dir: true,
+            size: 0,
+            modified: None,
+        })
+        .collect();
+    let mut objects: Vec<ListedObject> = listing
+        .objects
+        .iter()
+        .map(|object| ListedObject {
+            name: object.location.filename().unwrap_or_default().to_string(),
+            key: object.location.to_string(),
+            is_dir: false,
+            size: object.size as u64,
+            modified: Some(object.last_modified),
+        })
+        .collect();
+    folders.sort_by(|a, b| a.name.cmp(&b.name));
+    objects.sort_by(|a, b| a.name.cmp(&b.name));
+    folders.extend(objects);
+    Ok(folders)
+}
+
+/// Objects `flux rm-remote` would delete for `url`
+///
+/// A URL ending in `/` names a prefix and needs `recursive`. Below a prefix
+/// only archives are picked, so other data sharing it is left alone; upload
+/// markers are left for [`stale_uploads`] to clean up with their uploads.
+/// The manifests and deleted lists kept next to an archive, as by
+/// `flux sync` and `flux pack --hashes`, go with it.
+pub fn removal_targets(
+    store: &CloudStore,
+    cloud_path: &CloudPath,
+    url: &str,
+    recursive: bool,
+) -> Result<Vec<ObjectMeta>> {
+    if !url.ends_with('/') {
+        let object = store
+            .head(&cloud_path.path)
+            .with_context(|| format!("Cannot find {}", url))?;
+        let sidecars = sidecar_paths(&object.location);
+        let parts = object.location.parts().count();
+        let folder: ObjectPath = object.location.parts().take(parts - 1).collect();
+        let prefix = (!folder.as_ref().is_empty()).then_some(&folder);
+        let mut objects: Vec<ObjectMeta> = store
+            .list(prefix)
+            .with_context(|| format!("Failed to list the folder of {}", url))?
+            .objects
+            .into_iter()
+            .filter(|sibling| sidecars.contains(&sibling.location))
+            .collect();
+        objects.sort_by(|a, b| a.location.cmp(&b.location));
+        objects.insert(0, object);
+        return Ok(objects);
+    }
+    if !recursive {
+        anyhow::bail!(
+            "{} is a prefix; pass --recursive to delete the archives below it",
+            url
+        );
+    }
+
+    let prefix = (!cloud_path.path.as_ref().is_empty()).then_some(&cloud_path.path);
+    let listed = store
+        .list_recursive(prefix)
+        .with_context(|| format!("Failed to list {}", url))?;
+    let is_archive = |object: &ObjectMeta| {
+        !is_marker(&object.location)
+            && flux_core::archive::archive_format(Path::new(object.location.as_ref())).is_some()
+    };
+    let sidecars: HashSet<ObjectPath> = listed
+        .iter()
+        .filter(|object| is_archive(object))
+        .flat_map(|object| sidecar_paths(&object.location))
+        .collect();
+    let mut objects: Vec<ObjectMeta> = listed
+        .into_iter()
+        .filter(|object| is_archive(object) || sidecars.contains(&object.location))
+        .collect();
+    objects.sort_by(|a, b| a.location.cmp(&b.location));
+    Ok(objects)
+}
+
+/// Where the objects describing the archive at `path` would be kept: the
+/// manifest and deleted list of a backup chain link or of `flux sync`, and
+/// the manifest `flux pack --hashes` writes
+///
+/// A chain names them after the archive without its extension, as
+/// `photos.0001.fluxmanifest` for `photos.0001.tar.gz`; the others replace
+/// only the last extension, as `photos.tar.manifest.json`.
+fn sidecar_paths(path: &ObjectPath) -> Vec<ObjectPath> {
+    let key = path.as_ref();
+    let name = path.filename().unwrap_or_default();
+    let folder = &key[..key.len() - name.len()];
+    let stems = [name.find(".tar."), name.rfind('.')]
+        .into_iter()
+        .flatten()
+        .filter(|&dot| dot > 0)
+        .map(|dot| &name[..dot]);
+
+    let mut sidecars = Vec::new();
+    for stem in stems {
+        for extension in [MANIFEST_EXTENSION, DELETED_EXTENSION, "manifest.json"] {
+            let sidecar = ObjectPath::from(format!("{}{}.{}", folder, stem, extension));
+            if sidecar != *path && !sidecars.contains(&sidecar) {
+                sidecars.push(sidecar);
+            }
+        }
+    }
+    sidecars
+}
+
+/// Delete `objects`, stopping at the first failure
+pub fn remove_objects(store: &CloudStore, objects: &[ObjectMeta]) -> Result<()> {
+    for object in objects {
+        store
+            .delete(&object.location)
+            .with_context(|| format!("Failed to delete {}", object.location))?;
+    }
+    Ok(())
+}
+
+/// Uploads flux started below `cloud_path` more than `min_age` ago and
+/// never finished
+///
+/// Younger uploads may still be running in another process.
+pub fn stale_uploads(
+    store: &CloudStore,
+    cloud_path: &CloudPath,
+    min_age: chrono::Duration,
+) -> Result<Vec<PendingUpload>> {
+    let prefix = (!cloud_path.path.as_ref().is_empty()).then_some(&cloud_path.path);
+    let cutoff = Utc::now() - min_age;
+    let mut stale: Vec<PendingUpload> = pending_uploads(store, prefix)
+        .with_context(|| format!("Failed to look for uploads below {}", cloud_path))?
+        .into_iter()
+        .filter(|upload| upload.marker.last_modified <= cutoff)
+        .collect();
+    stale.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(stale)
+}
+
+/// Abort `uploads`, discarding the parts they already sent
+pub fn abort_uploads(store: &CloudStore, uploads: &[PendingUpload]) -> Result<()> {
+    for upload in uploads {
+        abort_upload(store, upload)
+            .with_context(|| format!("Failed to abort the upload to {}", upload.path))?;
+    }
+    Ok(())
+}
+
 /// Get a human-readable description of the cloud location
 pub fn describe_cloud_location(url: &str) -> String {
     match CloudPath::parse(url) {
@@ -68,7 +450,8 @@ pub fn check_cloud_credentials(url: &str) -> Result<()> {
                 || std::env::var("AWS_SECRET_ACCESS_KEY").is_err()
             {
                 anyhow::bail!(
-                    "AWS credentials not found. Please set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY environment variables."
+                    "AWS credentials not found. Please set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY environment variables. Run `flux cloud doctor {}` to check the rest of the setup.",
+                    url
                 );
             }
         }
@@ -77,7 +460,8 @@ pub fn check_cloud_credentials(url: &str) -> Result<()> {
                 && std::env::var("GOOGLE_SERVICE_ACCOUNT").is_err()
             {
                 anyhow::bail!(
-                    "Google Cloud credentials not found. Please set GOOGLE_APPLICATION_CREDENTIALS or GOOGLE_SERVICE_ACCOUNT environment variable."
+                    "Google Cloud credentials not found. Please set GOOGLE_APPLICATION_CREDENTIALS or GOOGLE_SERVICE_ACCOUNT environment variable. Run `flux cloud doctor {}` to check the rest of the setup.",
+                    url
                 );
             }
         }
@@ -87,7 +471,8 @@ pub fn check_cloud_credentials(url: &str) -> Result<()> {
                     && std::env::var("AZURE_STORAGE_SAS_TOKEN").is_err())
             {
                 anyhow::bail!(
-                    "Azure credentials not found. Please set AZURE_STORAGE_ACCOUNT_NAME and either AZURE_STORAGE_ACCOUNT_KEY or AZURE_STORAGE_SAS_TOKEN."
+                    "Azure credentials not found. Please set AZURE_STORAGE_ACCOUNT_NAME and either AZURE_STORAGE_ACCOUNT_KEY or AZURE_STORAGE_SAS_TOKEN. Run `flux cloud doctor {}` to check the rest of the setup.",
+                    url
                 );
             }
         }
@@ -100,6 +485,7 @@ pub fn check_cloud_credentials(url: &str) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::io::Write;
 
     #[test]
     fn test_is_cloud_path() {
@@ -121,4 +507,196 @@ mod tests {
         assert!(desc.contains("Google Cloud Storage"));
         assert!(desc.contains("gcs-bucket"));
     }
+
+    #[test]
+    fn test_reader_from_memory_store() {
+        let store = flux_testing::cloud::MemoryStore::new().with_object("backups/a.tar", "abc");
+        let cloud_path = parse_cloud_path("s3://bucket/backups/a.tar").unwrap();
+
+        let mut reader =
+            create_cloud_reader_from_store(store.cloud_store().unwrap(), &cloud_path).unwrap();
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "abc");
+    }
+
+    #[test]
+    fn test_writer_to_memory_store() {
+        let store = flux_testing::cloud::MemoryStore::new();
+        let cloud_path = parse_cloud_path("s3://bucket/out.tar.zst").unwrap();
+
+        let mut writer =
+            create_cloud_writer_from_store(store.cloud_store().unwrap(), &cloud_path).unwrap();
+        writer.write_all(b"payload").unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(store.uploads(), vec!["out.tar.zst".to_string()]);
+    }
+
+    #[test]
+    fn test_encrypted_upload_reads_back_with_its_key() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let keys = flux_core::keys::KeyStore::new(temp_dir.path());
+        keys.generate("offsite", None).unwrap();
+        let key = keys.load("offsite", None).unwrap();
+        let store = flux_testing::cloud::MemoryStore::new();
+        let url = "s3://bucket/out.tar.zst";
+        let cloud_path = parse_cloud_path(url).unwrap();
+        let open = || create_cloud_reader_from_store(store.cloud_store().unwrap(), &cloud_path);
+
+        let writer =
+            create_cloud_writer_from_store(store.cloud_store().unwrap(), &cloud_path).unwrap();
+        send(&mut &b"payload"[..], writer, Some(&key)).unwrap();
+        let mut stored = Vec::new();
+        open().unwrap().read_to_end(&mut stored).unwrap();
+        assert!(is_encrypted(&stored));
+
+        let (mut reader, size) = open_object(open().unwrap(), url, || Ok(Some(key))).unwrap();
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        assert_eq!((content.as_str(), size), ("payload", 7));
+        // Without a key the object is refused rather than read as is
+        assert!(open_object(open().unwrap(), url, || Ok(None)).is_err());
+
+        let writer =
+            create_cloud_writer_from_store(store.cloud_store().unwrap(), &cloud_path).unwrap();
+        send(&mut &b"plain"[..], writer, None).unwrap();
+        let (mut reader, size) = open_object(open().unwrap(), url, || unreachable!()).unwrap();
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        assert_eq!((content.as_str(), size), ("plain", 5));
+    }
+
+    #[test]
+    fn test_list_objects_from_memory_store() {
+        let store = flux_testing::cloud::MemoryStore::new()
+            .with_object("backups/b.tar.zst", "bb")
+            .with_object("backups/a.tar.zst", "a")
+            .with_object("backups/2024/old.tar", "old")
+            .with_object("other.txt", "x");
+        let cloud_path = parse_cloud_path("s3://bucket/backups/").unwrap();
+
+        let listed = list_objects(&store.cloud_store().unwrap(), &cloud_path).unwrap();
+        let names: Vec<&str> = listed.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, ["2024/", "a.tar.zst", "b.tar.zst"]);
+
+        assert!(listed[0].is_dir);
+        assert_eq!(listed[0].key, "backups/2024");
+        assert!(listed[0].modified.is_none());
+        assert_eq!(listed[2].key, "backups/b.tar.zst");
+        assert_eq!(listed[2].size, 2);
+        assert!(listed[2].modified.is_some());
+    }
+
+    #[test]
+    fn test_removal_targets() {
+        let store = flux_testing::cloud::MemoryStore::new()
+            .with_object("backups/a.tar.zst", "a")
+            .with_object("backups/2024/b.zip", "b")
+            .with_object("backups/notes.txt", "keep")
+            .with_object("backups/c.tar.flux-upload", "1");
+        let cloud_store = store.cloud_store().unwrap();
+
+        let url = "s3://bucket/backups/";
+        let prefix = parse_cloud_path(url).unwrap();
+        assert!(removal_targets(&cloud_store, &prefix, url, false).is_err());
+
+        let targets = removal_targets(&cloud_store, &prefix, url, true).unwrap();
+        let keys: Vec<String> = targets.iter().map(|o| o.location.to_string()).collect();
+        assert_eq!(keys, ["backups/2024/b.zip", "backups/a.tar.zst"]);
+
+        remove_objects(&cloud_store, &targets).unwrap();
+        assert!(!store.contains("backups/a.tar.zst"));
+        assert!(store.contains("backups/notes.txt"));
+
+        let url = "s3://bucket/backups/notes.txt";
+        let single = parse_cloud_path(url).unwrap();
+        assert_eq!(
+            removal_targets(&cloud_store, &single, url, false)
+                .unwrap()
+                .len(),
+            1
+        );
+        let url = "s3://bucket/backups/missing.tar";
+        let missing = parse_cloud_path(url).unwrap();
+        assert!(removal_targets(&cloud_store, &missing, url, false).is_err());
+    }
+
+    #[test]
+    fn test_list_objects_hides_upload_markers() {
+        let store = flux_testing::cloud::MemoryStore::new()
+            .with_object("backups/a.tar.zst", "a")
+            .with_object("backups/b.tar.flux-upload", "1");
+        let cloud_path = parse_cloud_path("s3://bucket/backups/").unwrap();
+
+        let listed = list_objects(&store.cloud_store().unwrap(), &cloud_path).unwrap();
+        let names: Vec<&str> = listed.iter().map(|o| o.name.as_str()).collect();
+        assert_eq!(names, ["a.tar.zst"]);
+    }
+
+    #[test]
+    fn test_removal_targets_take_sidecars_along() {
+        let store = flux_testing::cloud::MemoryStore::new()
+            .with_object("backups/photos.0001.tar.gz", "a")
+            .with_object("backups/photos.0001.fluxmanifest", "m")
+            .with_object("backups/photos.0001.deleted.txt", "d")
+            .with_object("backups/photos.0002.fluxmanifest", "m")
+            .with_object("backups/data.tar.zst", "a")
+            .with_object("backups/data.tar.manifest.json", "m")
+            .with_object("backups/data.txt", "keep");
+        let cloud_store = store.cloud_store().unwrap();
+
+        let url = "s3://bucket/backups/photos.0001.tar.gz";
+        let single = parse_cloud_path(url).unwrap();
+        let targets = removal_targets(&cloud_store, &single, url, false).unwrap();
+        let keys: Vec<String> = targets.iter().map(|o| o.location.to_string()).collect();
+        assert_eq!(
+            keys,
+            [
+                "backups/photos.0001.tar.gz",
+                "backups/photos.0001.deleted.txt",
+                "backups/photos.0001.fluxmanifest",
+            ]
+        );
+
+        let url = "s3://bucket/backups/";
+        let prefix = parse_cloud_path(url).unwrap();
+        let targets = removal_targets(&cloud_store, &prefix, url, true).unwrap();
+        let keys: Vec<String> = targets.iter().map(|o| o.location.to_string()).collect();
+        assert_eq!(
+            keys,
+            [
+                "backups/data.tar.manifest.json",
+                "backups/data.tar.zst",
+                "backups/photos.0001.deleted.txt",
+                "backups/photos.0001.fluxmanifest",
+                "backups/photos.0001.tar.gz",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stale_uploads() {
+        let store =
+            flux_testing::cloud::MemoryStore::new().with_object("other/b.tar.flux-upload", "2");
+        let cloud_store = store.cloud_store().unwrap();
+
+        // An upload whose process died before completing it
+        let mut writer = store.writer("backups/a.tar").unwrap();
+        writer.write_all(&vec![0u8; 20 * 1024 * 1024]).unwrap();
+        std::mem::forget(writer);
+
+        let prefix = parse_cloud_path("s3://bucket/backups/").unwrap();
+
+        let young = stale_uploads(&cloud_store, &prefix, chrono::Duration::hours(1)).unwrap();
+        assert!(young.is_empty());
+
+        let stale = stale_uploads(&cloud_store, &prefix, chrono::Duration::zero()).unwrap();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].path.as_ref(), "backups/a.tar");
+
+        abort_uploads(&cloud_store, &stale).unwrap();
+        assert!(!store.contains("backups/a.tar.flux-upload"));
+        assert!(store.contains("other/b.tar.flux-upload"));
+    }
 }
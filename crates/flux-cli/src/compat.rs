@@ -0,0 +1,589 @@
+//! `flux tar` and `flux unzip`: the options of GNU tar and Info-ZIP unzip
+//! that scripts use most, translated into flux operations
+//!
+//! Only what maps cleanly onto flux is accepted; anything else is an error
+//! rather than being silently ignored. Archives are read by their file name
+//! as everywhere else in flux, so `-z` and friends only matter when creating.
+
+use anyhow::Result;
+use flux_core::archive::detect::output_format;
+use flux_core::archive::{
+    extractor::ExtractEntryOptions, Archive, ArchiveEntry, ExtractOptions, PackFilter, PackOptions,
+    PackRoot,
+};
+use flux_core::ops::ExtractJob;
+use flux_core::progress::{NoProgress, ProgressSink};
+use flux_core::space::TempResource;
+use std::fs::File;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// An error in the options given, which exits like other invalid arguments
+fn usage(message: impl Into<String>) -> anyhow::Error {
+    flux_core::Error::InvalidArgument(message.into()).into()
+}
+
+/// What a `flux tar` run does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TarMode {
+    Create,
+    Extract,
+    List,
+}
+
+/// Parsed `flux tar` command line
+#[derive(Debug, Default, PartialEq)]
+pub struct TarArgs {
+    pub mode: Option<TarMode>,
+    /// `-f`
+    pub file: Option<PathBuf>,
+    /// `-C`
+    pub directory: Option<PathBuf>,
+    /// Archive format chosen by `-z`, `-J` or `--zstd`
+    pub format: Option<&'static str>,
+    /// `-a`: choose the format from the archive name
+    pub auto_compress: bool,
+    pub verbose: bool,
+    /// `-k` or `--skip-old-files`: leave existing files alone
+    pub keep_old_files: bool,
+    pub strip_components: Option<usize>,
+    pub excludes: Vec<String>,
+    pub exclude_vcs: bool,
+    /// Files to pack, or entries to extract or list
+    pub members: Vec<PathBuf>,
+}
+
+/// Parse tar options, in bundled (`-xzf a.tgz`), old (`xzf a.tgz`) or long
+/// (`--extract --file=a.tgz`) form
+pub fn parse_tar(args: &[String]) -> Result<TarArgs> {
+    let mut parsed = TarArgs::default();
+    let mut args = args.iter();
+    let mut operands_only = false;
+    let mut first = true;
+
+    while let Some(arg) = args.next() {
+        if operands_only {
+            parsed.members.push(PathBuf::from(arg));
+        } else if arg == "--" {
+            operands_only = true;
+        } else if let Some(long) = arg.strip_prefix("--") {
+            let (name, inline) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next().cloned())
+                    .ok_or_else(|| usage(format!("tar: option --{} requires an argument", name)))
+            };
+            match name {
+                "create" => set_mode(&mut parsed, TarMode::Create)?,
+                "extract" | "get" => set_mode(&mut parsed, TarMode::Extract)?,
+                "list" => set_mode(&mut parsed, TarMode::List)?,
+                "file" => parsed.file = Some(PathBuf::from(value()?)),
+                "directory" => parsed.directory = Some(PathBuf::from(value()?)),
+                "exclude" => parsed.excludes.push(value()?),
+                "strip-components" => {
+                    let count = value()?;
+                    parsed.strip_components = Some(count.parse().map_err(|_| {
+                        usage(format!("tar: invalid --strip-components value: {}", count))
+                    })?)
+                }
+                _ if inline.is_some() => {
+                    return Err(usage(format!("tar: unsupported option --{}", name)))
+                }
+                _ => apply_flag(&mut parsed, name)?,
+            }
+        } else if (arg.starts_with('-') && arg.len() > 1) || (first && !arg.starts_with('-')) {
+            // Old-style options take their values from the following
+            // arguments in order; bundled ones can also have it attached
+            let old_style = !arg.starts_with('-');
+            let letters = arg.trim_start_matches('-');
+            for (index, letter) in letters.char_indices() {
+                let takes_value = matches!(letter, 'f' | 'C');
+                if !takes_value {
+                    apply_flag(&mut parsed, &letter.to_string())?;
+                    continue;
+                }
+                let rest = &letters[index + 1..];
+                let value = if !old_style && !rest.is_empty() {
+                    rest.to_string()
+                } else {
+                    args.next().cloned().ok_or_else(|| {
+                        usage(format!("tar: option -{} requires an argument", letter))
+                    })?
+                };
+                match letter {
+                    'f' => parsed.file = Some(PathBuf::from(value)),
+                    _ => parsed.directory = Some(PathBuf::from(value)),
+                }
+                if !old_style {
+                    break;
+                }
+            }
+        } else {
+            parsed.members.push(PathBuf::from(arg));
+        }
+        first = false;
+    }
+
+    Ok(parsed)
+}
+
+/// Apply a tar option that takes no value, by its short letter or long name
+fn apply_flag(parsed: &mut TarArgs, flag: &str) -> Result<()> {
+    match flag {
+        "c" => set_mode(parsed, TarMode::Create)?,
+        "x" => set_mode(parsed, TarMode::Extract)?,
+        "t" => set_mode(parsed, TarMode::List)?,
+        "z" | "gzip" | "gunzip" => parsed.format = Some("tar.gz"),
+        "J" | "xz" => parsed.format = Some("tar.xz"),
+        "zstd" => parsed.format = Some("tar.zst"),
+        "a" | "auto-compress" => parsed.auto_compress = true,
+        "v" | "verbose" => parsed.verbose = true,
+        "k" | "keep-old-files" | "skip-old-files" => parsed.keep_old_files = true,
+        "overwrite" => parsed.keep_old_files = false,
+        "exclude-vcs" => parsed.exclude_vcs = true,
+        // flux always keeps permissions
+        "p" | "preserve-permissions" => {}
+        "j" | "bzip2" => return Err(usage("tar: bzip2 is not supported; use -z, -J or --zstd")),
+        _ if flag.len() == 1 => return Err(usage(format!("tar: unsupported option -{}", flag))),
+        _ => return Err(usage(format!("tar: unsupported option --{}", flag))),
+    }
+    Ok(())
+}
+
+fn set_mode(parsed: &mut TarArgs, mode: TarMode) -> Result<()> {
+    match parsed.mode {
+        Some(existing) if existing != mode => {
+            Err(usage("tar: only one of -c, -x and -t can be given"))
+        }
+        _ => {
+            parsed.mode = Some(mode);
+            Ok(())
+        }
+    }
+}
+
+/// Run a parsed `flux tar` command
+pub fn run_tar(args: TarArgs) -> Result<()> {
+    let mode = args
+        .mode
+        .ok_or_else(|| usage("tar: one of -c, -x or -t is required"))?;
+    let file = args
+        .file
+        .clone()
+        .ok_or_else(|| usage("tar: an archive must be given with -f"))?;
+    // `-f -` writes the archive to standard output or reads it from
+    // standard input, which is copied to a scratch file first
+    let piped = file == Path::new("-");
+    let staged = match mode {
+        TarMode::Extract | TarMode::List if piped => Some(stage_stdin()?),
+        _ => None,
+    };
+    let file = match &staged {
+        Some((_, path)) => path.clone(),
+        None => file,
+    };
+
+    match mode {
+        TarMode::Create if piped => create_to_stdout(&args),
+        TarMode::Create => create(&file, &args),
+        TarMode::Extract => extract_members(
+            &file,
+            args.directory.as_deref().unwrap_or(Path::new(".")),
+            &args.members,
+            !args.keep_old_files,
+            args.strip_components,
+            args.verbose,
+        ),
+        TarMode::List => list(&file, &args.members, args.verbose),
+    }
+}
+
+/// Standard input copied into a scratch file, for reading with `-f -`
+///
+/// The format is then detected from the content alone.
+fn stage_stdin() -> Result<(TempResource, PathBuf)> {
+    let scratch = TempResource::new()?;
+    let path = scratch.path().join("archive");
+    io::copy(&mut io::stdin().lock(), &mut File::create(&path)?)?;
+    scratch.check()?;
+    Ok((scratch, path))
+}
+
+/// Inputs of a create, as named by the members below `-C`, and the options
+/// to pack them with
+fn create_inputs(args: &TarArgs) -> Result<(PathBuf, Vec<PathBuf>, PackOptions)> {
+    if args.members.is_empty() {
+        return Err(usage("tar: refusing to create an empty archive"));
+    }
+    let base_dir = match &args.directory {
+        Some(directory) => directory.clone(),
+        None => PathBuf::from("."),
+    };
+    let inputs = args
+        .members
+        .iter()
+        .map(|member| base_dir.join(member))
+        .collect();
+    let options = PackOptions {
+        filter: PackFilter::new(&args.excludes)?.ignore_vcs(args.exclude_vcs),
+        ..PackOptions::default()
+    };
+    Ok((base_dir, inputs, options))
+}
+
+/// Write the archive to standard output, as `tar -cf -` does, listing
+/// packed files on standard error instead with `-v`
+fn create_to_stdout(args: &TarArgs) -> Result<()> {
+    let (base_dir, inputs, options) = create_inputs(args)?;
+    if io::stdout().is_terminal() {
+        return Err(usage(
+            "tar: refusing to write archive data to a terminal; redirect stdout",
+        ));
+    }
+    let roots: Vec<PackRoot> = inputs
+        .iter()
+        .map(|input| PackRoot::named_in(input, Some(&base_dir)))
+        .collect();
+    let listing = Listing {
+        base_dir: &base_dir,
+        enabled: args.verbose,
+        to_stderr: true,
+    };
+
+    let mut stdout = io::stdout().lock();
+    let outcome = flux_core::archive::pack_roots_to_writer(
+        &roots,
+        &mut stdout,
+        args.format.unwrap_or("tar"),
+        &options,
+        &listing,
+    )?;
+    stdout.flush()?;
+    crate::check_outcome(outcome, false)
+}
+
+fn create(file: &Path, args: &TarArgs) -> Result<()> {
+    let (base_dir, inputs, options) = create_inputs(args)?;
+
+    // Like GNU tar, the archive name only picks the format with -a
+    let format = match args.format {
+        Some(format) => format,
+        None if args.auto_compress => format_from_name(file),
+        None => "tar",
+    };
+
+    info!(
+        "Packing {} inputs into {:?} as {}",
+        inputs.len(),
+        file,
+        format
+    );
+    let listing = Listing {
+        base_dir: &base_dir,
+        enabled: args.verbose,
+        to_stderr: false,
+    };
+    let outcome = flux_core::archive::pack_multiple(
+        &inputs,
+        file,
+        format,
+        Some(&base_dir),
+        &options,
+        &listing,
+    )?;
+    crate::check_outcome(outcome, false)
+}
+
+/// The format GNU tar's `-a` would pick for `file`
+fn format_from_name(file: &Path) -> &'static str {
+    output_format(file).unwrap_or("tar")
+}
+
+/// Prints each packed file, as `tar -cv` does
+struct Listing<'a> {
+    base_dir: &'a Path,
+    enabled: bool,
+    /// Print on standard error, when standard output carries the archive
+    to_stderr: bool,
+}
+
+impl ProgressSink for Listing<'_> {
+    fn file(&self, path: &Path) {
+        if self.enabled {
+            let name = path.strip_prefix(self.base_dir).unwrap_or(path);
+            if self.to_stderr {
+                eprintln!("{}", name.display());
+            } else {
+                println!("{}", name.display());
+            }
+        }
+    }
+}
+
+/// Whether `path` is one of `members` or inside one of them
+fn selected(path: &Path, members: &[PathBuf]) -> bool {
+    members.is_empty() || members.iter().any(|member| path.starts_with(member))
+}
+
+/// Extract `archive` into `output_dir`, or only `members` of it when given
+fn extract_members(
+    archive: &Path,
+    output_dir: &Path,
+    members: &[PathBuf],
+    overwrite: bool,
+    strip_components: Option<usize>,
+    verbose: bool,
+) -> Result<()> {
+    // Listed once for both the names printed and the members picked
+    let opened = Archive::open(archive)?;
+    if verbose {
+        for entry in opened.entries()? {
+            if selected(&entry.path, members) {
+                println!("{}", entry.path.display());
+            }
+        }
+    }
+
+    if members.is_empty() {
+        let options = ExtractOptions {
+            overwrite,
+            skip: !overwrite,
+            rename: false,
+            strip_components,
+            hoist: false,
+            ..ExtractOptions::default()
+        };
+        let outcome = opened.extract_all(output_dir, options)?;
+        return crate::check_outcome(outcome, false);
+    }
+
+    if strip_components.is_some() {
+        return Err(usage(
+            "--strip-components cannot be combined with extracting selected members",
+        ));
+    }
+    let mut found = vec![false; members.len()];
+    let mut entries = Vec::new();
+    for entry in opened.entries()? {
+        if let Some(index) = members
+            .iter()
+            .position(|member| entry.path.starts_with(member))
+        {
+            found[index] = true;
+            entries.push(entry.clone());
+        }
+    }
+    if let Some(index) = found.iter().position(|matched| !matched) {
+        return Err(flux_core::Error::NotFound(format!(
+            "{} is not in the archive",
+            members[index].display()
+        ))
+        .into());
+    }
+
+    let job = ExtractJob {
+        archive: archive.to_path_buf(),
+        output_dir: output_dir.to_path_buf(),
+        entries: Some(entries),
+        options: ExtractEntryOptions {
+            overwrite,
+            preserve_permissions: true,
+            preserve_timestamps: true,
+            follow_symlinks: false,
+            password: None,
+            symlink_fallback: Default::default(),
+            strip_components: None,
+        },
+        hoist: false,
+        durability: Default::default(),
+        resume: false,
+    };
+    // Like tar, an entry that cannot be written stops the extraction
+    Ok(job.run(&NoProgress, |_, _| None)?.into_result()?)
+}
+
+/// Print the entries of `archive`, or the selected `members`, as their
+/// paths or, when `verbose`, as a table
+fn list(archive: &Path, members: &[PathBuf], verbose: bool) -> Result<()> {
+    let entries: Vec<ArchiveEntry> = flux_core::inspect(archive)?
+        .into_iter()
+        .filter(|entry| selected(&entry.path, members))
+        .collect();
+    if verbose {
+        print!(
+            "{}",
+            crate::table::render(&entries, crate::table::DEFAULT_FIELDS, false)
+        );
+    } else {
+        for entry in &entries {
+            println!("{}", entry.path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Parsed `flux unzip` command line
+#[derive(Debug, Default, PartialEq)]
+pub struct UnzipArgs {
+    pub archive: Option<PathBuf>,
+    /// `-d`
+    pub directory: Option<PathBuf>,
+    /// `-l`
+    pub list: bool,
+    /// `-o`: overwrite existing files; the default, like `-n`, keeps them
+    pub overwrite: bool,
+    pub members: Vec<PathBuf>,
+}
+
+/// Parse unzip options; the archive is the first operand and the rest name
+/// entries to extract
+pub fn parse_unzip(args: &[String]) -> Result<UnzipArgs> {
+    let mut parsed = UnzipArgs::default();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let Some(letters) = arg.strip_prefix('-').filter(|letters| !letters.is_empty()) else {
+            match parsed.archive {
+                None => parsed.archive = Some(PathBuf::from(arg)),
+                Some(_) => parsed.members.push(PathBuf::from(arg)),
+            }
+            continue;
+        };
+        for (index, letter) in letters.char_indices() {
+            match letter {
+                'l' => parsed.list = true,
+                'o' => parsed.overwrite = true,
+                'n' => parsed.overwrite = false,
+                // Logging is already quiet enough for scripts
+                'q' => {}
+                'd' => {
+                    let rest = &letters[index + 1..];
+                    let directory = if rest.is_empty() {
+                        args.next()
+                            .cloned()
+                            .ok_or_else(|| usage("unzip: option -d requires a directory"))?
+                    } else {
+                        rest.to_string()
+                    };
+                    parsed.directory = Some(PathBuf::from(directory));
+                    break;
+                }
+                _ => return Err(usage(format!("unzip: unsupported option -{}", letter))),
+            }
+        }
+    }
+
+    Ok(parsed)
+}
+
+/// Run a parsed `flux unzip` command
+pub fn run_unzip(args: UnzipArgs, verbose: bool) -> Result<()> {
+    let archive = args
+        .archive
+        .ok_or_else(|| usage("unzip: an archive to extract is required"))?;
+    if args.list {
+        return list(&archive, &args.members, true);
+    }
+    extract_members(
+        &archive,
+        args.directory.as_deref().unwrap_or(Path::new(".")),
+        &args.members,
+        args.overwrite,
+        None,
+        verbose,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_tar_forms() {
+        let expected = TarArgs {
+            mode: Some(TarMode::Extract),
+            file: Some(PathBuf::from("a.tgz")),
+            directory: Some(PathBuf::from("out")),
+            format: Some("tar.gz"),
+            ..TarArgs::default()
+        };
+        for args in [
+            &["-xzf", "a.tgz", "-C", "out"][..],
+            &["xzfC", "a.tgz", "out"],
+            &["-x", "-z", "-fa.tgz", "-Cout"],
+            &["--extract", "--gzip", "--file=a.tgz", "--directory", "out"],
+        ] {
+            assert_eq!(parse_tar(&strings(args)).unwrap(), expected, "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_parse_tar_members_and_options() {
+        let parsed = parse_tar(&strings(&[
+            "-cvJf",
+            "out.tar.xz",
+            "--exclude=*.log",
+            "src",
+            "--",
+            "-odd",
+        ]))
+        .unwrap();
+        assert_eq!(parsed.mode, Some(TarMode::Create));
+        assert!(parsed.verbose);
+        assert_eq!(parsed.format, Some("tar.xz"));
+        assert_eq!(parsed.excludes, ["*.log"]);
+        assert_eq!(
+            parsed.members,
+            [PathBuf::from("src"), PathBuf::from("-odd")]
+        );
+    }
+
+    #[test]
+    fn test_parse_tar_rejects() {
+        for args in [
+            &["-xjf", "a.tar.bz2"][..],
+            &["-cxf", "a.tar"],
+            &["-xf"],
+            &["--no-such-option"],
+        ] {
+            assert!(parse_tar(&strings(args)).is_err(), "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_format_from_name() {
+        assert_eq!(format_from_name(Path::new("a.tgz")), "tar.gz");
+        assert_eq!(format_from_name(Path::new("a.TAR.ZST")), "tar.zst");
+        assert_eq!(format_from_name(Path::new("a.zip")), "zip");
+        assert_eq!(format_from_name(Path::new("a.tar")), "tar");
+        assert_eq!(format_from_name(Path::new("a.txz")), "tar.xz");
+        assert_eq!(format_from_name(Path::new("a.tar.zstd")), "tar.zst");
+        assert_eq!(format_from_name(Path::new("a.gz")), "tar.gz");
+        assert_eq!(format_from_name(Path::new("archive")), "tar");
+    }
+
+    #[test]
+    fn test_parse_unzip() {
+        let parsed = parse_unzip(&strings(&["-oq", "a.zip", "docs/x.txt", "-d", "out"])).unwrap();
+        assert_eq!(
+            parsed,
+            UnzipArgs {
+                archive: Some(PathBuf::from("a.zip")),
+                directory: Some(PathBuf::from("out")),
+                list: false,
+                overwrite: true,
+                members: vec![PathBuf::from("docs/x.txt")],
+            }
+        );
+        assert!(parse_unzip(&strings(&["-j", "a.zip"])).is_err());
+    }
+}
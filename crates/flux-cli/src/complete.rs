@@ -0,0 +1,78 @@
+//! Entry paths for shell completion
+//!
+//! Shells call the hidden `flux __complete-entries ARCHIVE PREFIX` while
+//! completing an argument that names something inside an archive, such as
+//! `flux mv` or `extract --first`. It prints the paths below PREFIX one
+//! level at a time, as file name completion does: a folder is offered as
+//! `docs/`, and its contents once that has been typed. Listings come from
+//! the index cache when the archive has not changed, so a large archive is
+//! only read through the first time.
+
+use flux_core::archive::extractor::ArchiveEntry;
+use flux_core::archive::{Archive, IndexCache};
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+use std::path::Path;
+use tracing::debug;
+
+/// Print the completions of `prefix` among the entries of `archive`
+///
+/// Completion runs on every key press, so an archive that cannot be read
+/// completes nothing rather than printing an error into the command line.
+pub fn run(archive: &Path, prefix: &str) -> anyhow::Result<()> {
+    let Some(entries) = entries(archive) else {
+        return Ok(());
+    };
+    let mut out = io::stdout().lock();
+    for candidate in completions(&entries, prefix) {
+        writeln!(out, "{}", candidate)?;
+    }
+    Ok(())
+}
+
+/// Entries of `archive`, from the index cache when it has them
+fn entries(archive: &Path) -> Option<Vec<ArchiveEntry>> {
+    let cache = IndexCache::open_default()
+        .map_err(|e| debug!("No index cache: {}", e))
+        .ok();
+    if let Some(entries) = cache.as_ref().and_then(|cache| cache.load(archive)) {
+        return Some(entries);
+    }
+
+    let opened = Archive::open(archive)
+        .and_then(|opened| opened.entries().map(<[_]>::to_vec))
+        .map_err(|e| debug!("Cannot list {:?}: {}", archive, e))
+        .ok()?;
+    if let Some(cache) = &cache {
+        if let Err(e) = cache.store(archive, &opened) {
+            debug!("Could not cache the listing of {:?}: {}", archive, e);
+        }
+    }
+    Some(opened)
+}
+
+/// Paths of `entries` starting with `prefix`, cut after the component the
+/// prefix ends in; folders end in `/`
+fn completions(entries: &[ArchiveEntry], prefix: &str) -> BTreeSet<String> {
+    entries
+        .iter()
+        .filter_map(|entry| {
+            let path = slash_path(&entry.path);
+            let rest = path.strip_prefix(prefix)?;
+            Some(match rest.find('/') {
+                // Something below a folder offers the folder
+                Some(end) => format!("{}{}/", prefix, &rest[..end]),
+                None if entry.is_dir => format!("{}/", path),
+                None => path,
+            })
+        })
+        .collect()
+}
+
+/// `path` with its components joined by `/`, as typed on any platform
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -1,11 +1,11 @@
 //! Extract command implementation with interactive support
 
+use crate::progress::{Phase, ProgressDisplay};
 use anyhow::Result;
 use dialoguer::Select;
 use flux_core::archive::extractor::{ConflictAction, ConflictHandler, ExtractEntryOptions};
-use flux_core::archive::{create_extractor, ExtractOptions};
+use flux_core::archive::{Archive, ExtractOptions, RecursiveOptions, SymlinkFallback};
 use flux_core::Error as FluxError;
-use indicatif::{ProgressBar, ProgressStyle};
 // use std::fs;
 use std::path::Path;
 use tracing::{debug, info, warn};
@@ -77,16 +77,15 @@ impl ConflictHandler for InteractiveConflictHandler {
 
 /// Extract with interactive conflict handling
 pub fn extract_interactive(
-    archive: &Path,
+    archive: &Archive,
     output_dir: &Path,
     strip_components: Option<usize>,
     show_progress: bool,
     hoist: bool,
+    symlink_fallback: SymlinkFallback,
 ) -> Result<()> {
     // Check if it's a 7z archive (which doesn't support interactive extraction)
-    let ext = archive.extension().and_then(|e| e.to_str()).unwrap_or("");
-
-    if ext == "7z" {
+    if archive.format() == "7z" {
         warn!(
             "Interactive extraction is not supported for 7z archives. Using standard extraction."
         );
@@ -96,34 +95,26 @@ pub fn extract_interactive(
             rename: false,
             strip_components,
             hoist,
+            symlink_fallback,
+            ..ExtractOptions::default()
         };
         return extract_with_options(archive, output_dir, options, show_progress, false);
     }
 
-    // Create the extractor
-    let extractor = create_extractor(archive)?;
-
     // Get all entries first to show progress
-    let entries: Vec<_> = extractor.entries(archive)?.collect::<Result<Vec<_>, _>>()?;
+    let entries = archive.entries()?;
 
     let total_entries = entries.len();
     info!("Found {} entries in archive", total_entries);
 
-    // Create progress bar if requested
-    let progress = if show_progress {
-        let pb = ProgressBar::new(total_entries as u64);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template(
-                    "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}",
-                )
-                .unwrap()
-                .progress_chars("#>-"),
-        );
-        Some(pb)
-    } else {
-        None
-    };
+    // Bytes of file data over all entries, for the ETA
+    let total_bytes: u64 = entries
+        .iter()
+        .filter(|entry| !entry.is_dir && !entry.is_symlink)
+        .map(|entry| entry.size)
+        .sum();
+    let display = ProgressDisplay::new(show_progress);
+    display.phase(Phase::Extract, Some(total_bytes));
 
     // Create conflict handler
     let mut conflict_handler = InteractiveConflictHandler::new();
@@ -134,11 +125,15 @@ pub fn extract_interactive(
     let mut failed = 0;
 
     // Process each entry
-    for (idx, entry) in entries.iter().enumerate() {
-        if let Some(ref pb) = progress {
-            pb.set_position(idx as u64);
-            pb.set_message(format!("{}", entry.path.display()));
-        }
+    for entry in entries {
+        display.status(entry.path.display().to_string());
+        // File data of the entry; skipped entries count it too, so the ETA
+        // only covers what is left
+        let data = if entry.is_dir || entry.is_symlink {
+            0
+        } else {
+            entry.size
+        };
 
         // Calculate destination path
         let mut dest_path = entry.path.clone();
@@ -154,15 +149,17 @@ pub fn extract_interactive(
                     entry.path
                 );
                 skipped += 1;
+                display.inc(data);
                 continue;
             }
         }
 
         let full_dest = output_dir.join(&dest_path);
 
-        // Check if destination exists
+        // Check if destination exists; the prompt is drawn with the bars
+        // out of the way
         let action = if full_dest.exists() {
-            conflict_handler.handle_conflict(entry, &full_dest)
+            display.suspend(|| conflict_handler.handle_conflict(entry, &full_dest))
         } else {
             ConflictAction::Overwrite // No conflict, proceed
         };
@@ -181,6 +178,7 @@ pub fn extract_interactive(
             ConflictAction::Skip | ConflictAction::SkipAll => {
                 debug!("Skipping: {:?}", dest_path);
                 skipped += 1;
+                display.inc(data);
                 continue;
             }
             ConflictAction::Rename => {
@@ -203,8 +201,8 @@ pub fn extract_interactive(
                 }
 
                 // Extract to renamed path
-                match extractor.extract_entry(
-                    archive,
+                display.start_file(data);
+                match archive.extract_entry(
                     entry,
                     renamed_path.parent().unwrap_or(output_dir),
                     ExtractEntryOptions {
@@ -212,7 +210,11 @@ pub fn extract_interactive(
                         preserve_permissions: true,
                         preserve_timestamps: true,
                         follow_symlinks: false,
+                        password: None,
+                        symlink_fallback,
+                        strip_components: None,
                     },
+                    &display,
                 ) {
                     Ok(_) => {
                         info!(
@@ -229,8 +231,8 @@ pub fn extract_interactive(
             }
             ConflictAction::Overwrite | ConflictAction::OverwriteAll => {
                 // Extract with overwrite
-                match extractor.extract_entry(
-                    archive,
+                display.start_file(data);
+                match archive.extract_entry(
                     entry,
                     output_dir,
                     ExtractEntryOptions {
@@ -238,7 +240,11 @@ pub fn extract_interactive(
                         preserve_permissions: true,
                         preserve_timestamps: true,
                         follow_symlinks: false,
+                        password: None,
+                        symlink_fallback,
+                        strip_components: None,
                     },
+                    &display,
                 ) {
                     Ok(_) => {
                         debug!("Extracted: {:?}", dest_path);
@@ -251,11 +257,10 @@ pub fn extract_interactive(
                 }
             }
         }
+        display.end_file();
     }
 
-    if let Some(pb) = progress {
-        pb.finish_with_message("Extraction complete");
-    }
+    display.finish();
 
     info!(
         "Extraction summary: {} extracted, {} skipped, {} failed",
@@ -280,22 +285,52 @@ pub fn extract_interactive(
     }
 }
 
-/// Extract with non-interactive options
+/// Extract with non-interactive options; `strict` fails on entries of
+/// types that are not extracted
 pub fn extract_with_options(
-    archive: &Path,
+    archive: &Archive,
     output_dir: &Path,
     options: ExtractOptions,
     show_progress: bool,
-    _hoist: bool,
+    strict: bool,
 ) -> Result<()> {
-    // Note: The hoist option is already included in ExtractOptions,
-    // so we don't need the separate _hoist parameter
+    // Extraction reports no byte counts, so this is a spinner
+    let display = ProgressDisplay::new(show_progress);
+    display.phase(Phase::Extract, None);
+    display.status(archive.path().display().to_string());
 
-    // TODO: Implement progress bar for non-interactive extraction
-    if show_progress {
-        info!("Progress bar not yet implemented for non-interactive extraction");
+    let outcome = archive.extract_all(output_dir, options)?;
+    // The spinner shows no bytes, but the phase counts the archive as read
+    if let Ok(metadata) = std::fs::metadata(archive.path()) {
+        display.inc(metadata.len());
     }
+    crate::check_outcome(outcome, strict)
+}
+
+/// Extract an archive and the archives inside it within `limits`, printing
+/// what was found inside
+pub fn extract_recursive(
+    archive: &Path,
+    output_dir: &Path,
+    options: ExtractOptions,
+    limits: &RecursiveOptions,
+    show_progress: bool,
+) -> Result<()> {
+    let display = ProgressDisplay::new(show_progress);
+    display.phase(Phase::Extract, None);
+    display.status(archive.display().to_string());
 
-    flux_core::archive::extract_with_options(archive, output_dir, options)?;
-    Ok(())
+    let report = flux_core::archive::extract_recursive(archive, output_dir, options, limits)?;
+    if let Ok(metadata) = std::fs::metadata(archive) {
+        display.inc(metadata.len());
+    }
+    display.finish();
+    print!("{}", report);
+    if report.is_complete() {
+        return Ok(());
+    }
+    Err(FluxError::PartialFailure {
+        count: report.failures() as u32,
+    }
+    .into())
 }
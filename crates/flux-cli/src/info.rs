@@ -0,0 +1,134 @@
+//! `flux info`: what this build supports and how it is set up
+
+use crate::table::format_size;
+use anyhow::Result;
+use flux_core::archive::Capability;
+use flux_core::info::{BuildInfo, Library};
+use serde::Serialize;
+
+/// [`BuildInfo`] with the features only the CLI is built with
+#[derive(Serialize)]
+struct Info {
+    /// Whether cloud locations can be read and written
+    cloud: bool,
+    #[serde(flatten)]
+    build: BuildInfo,
+}
+
+/// Print the build's features, formats, algorithms and settings, as JSON
+/// when `json`
+pub fn print(json: bool) -> Result<()> {
+    let info = Info {
+        cloud: cfg!(feature = "cloud"),
+        build: BuildInfo::collect(),
+    };
+    if json {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    let build = &info.build;
+    println!("Flux {} ({} {})", build.version, build.os, build.arch);
+
+    println!();
+    println!("Features");
+    let yes_no = |on: bool| if on { "yes" } else { "no" };
+    println!("  cloud        {}", yes_no(info.cloud));
+    println!("  telemetry    {}", yes_no(build.telemetry));
+    let sevenz = build.format("7z");
+    println!(
+        "  7z           {}",
+        match sevenz {
+            Some(format) if format.pack => "yes",
+            Some(_) => "read only",
+            None => "no",
+        }
+    );
+    let encrypted: Vec<&str> = build
+        .formats
+        .iter()
+        .filter(|format| format.capabilities.encryption)
+        .map(|format| format.name)
+        .collect();
+    if encrypted.is_empty() {
+        println!("  encryption   no");
+    } else {
+        println!("  encryption   yes ({})", encrypted.join(", "));
+    }
+
+    println!();
+    println!("Formats");
+    let width = build
+        .formats
+        .iter()
+        .map(|format| format.name.len())
+        .max()
+        .unwrap_or(0);
+    for format in &build.formats {
+        let mode = if format.pack { "read, write" } else { "read" };
+        print!(
+            "  {:width$}  {:11}  {}",
+            format.name,
+            mode,
+            library(&format.library)
+        );
+        if !format.aliases.is_empty() {
+            print!("  (also {})", format.aliases.join(", "));
+        }
+        println!();
+        let holds: Vec<String> = Capability::ALL
+            .into_iter()
+            .filter(|capability| format.capabilities.supports(*capability))
+            .map(|capability| capability.to_string())
+            .collect();
+        println!("  {:width$}  holds {}", "", holds.join(", "));
+    }
+
+    println!();
+    println!("Compression");
+    for algorithm in &build.algorithms {
+        let mut line = format!("  {:8}", algorithm.name);
+        if let Some(lib) = &algorithm.library {
+            line.push_str(&format!("  {}", library(lib)));
+        }
+        if let Some(native) = algorithm.native_version {
+            line.push_str(&format!(" (C library {})", native));
+        }
+        println!("{}", line.trim_end());
+    }
+
+    println!();
+    let configured = match build.configured_threads {
+        0 => "auto".to_string(),
+        threads => threads.to_string(),
+    };
+    println!(
+        "Threads      {} by default (performance.threads: {})",
+        build.default_threads, configured
+    );
+    println!("Buffers      {}", format_size(build.buffer_size as u64));
+    let path = |path: &Option<std::path::PathBuf>| {
+        path.as_ref()
+            .map_or("unavailable".to_string(), |path| path.display().to_string())
+    };
+    println!("Config       {}", path(&build.config_path));
+    println!("Temp dir     {}", path(&build.temp_dir));
+    println!(
+        "Scratch      {}",
+        build
+            .scratch_quota
+            .map_or("unlimited".to_string(), |quota| format!(
+                "{} per operation",
+                format_size(quota)
+            ))
+    );
+    Ok(())
+}
+
+fn library(library: &Library) -> String {
+    format!(
+        "{} {}",
+        library.name,
+        library.version.unwrap_or("(unknown)")
+    )
+}
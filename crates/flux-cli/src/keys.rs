@@ -0,0 +1,132 @@
+//! Named keys for `flux key`, and for the manifests and cloud archives
+//! encrypted with one
+
+use anyhow::Result;
+use flux_core::config::Config;
+use flux_core::keys::{Key, KeyInfo, KeyStore};
+use std::io::{self, IsTerminal};
+use std::path::Path;
+
+/// Environment variable holding the passphrase of protected keys
+pub const PASSPHRASE_ENV: &str = "FLUX_KEY_PASSPHRASE";
+
+/// Passphrase for the key `name`, from `FLUX_KEY_PASSPHRASE` or else asked
+/// for on a terminal, twice when `confirm` as for a new key
+pub fn passphrase(name: &str, confirm: bool) -> Result<String> {
+    if let Some(passphrase) = std::env::var(PASSPHRASE_ENV).ok().filter(|p| !p.is_empty()) {
+        return Ok(passphrase);
+    }
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(flux_core::Error::PasswordRequired(format!(
+            "set {} to the passphrase of key {}",
+            PASSPHRASE_ENV, name
+        ))
+        .into());
+    }
+
+    let prompt = dialoguer::Password::new().with_prompt(format!("Passphrase for key {}", name));
+    let prompt = if confirm {
+        prompt.with_confirmation("Repeat passphrase", "Passphrases do not match")
+    } else {
+        prompt
+    };
+    Ok(prompt.interact()?)
+}
+
+/// The key `name` from the key store, unlocked with its passphrase when it
+/// is protected
+pub fn load(name: &str) -> Result<Key> {
+    let store = KeyStore::open_default()?;
+    let passphrase = if store.info(name)?.protected {
+        Some(passphrase(name, false)?)
+    } else {
+        None
+    };
+    Ok(store.load(name, passphrase.as_deref())?)
+}
+
+/// Password of the key named by `manifest_key` in the configuration, if
+/// one is
+pub fn configured_manifest_key() -> Result<Option<String>> {
+    match Config::load_or_default().manifest_key {
+        Some(name) => Ok(Some(load(&name)?.password())),
+        None => Ok(None),
+    }
+}
+
+/// `flux key generate`
+pub fn generate(name: &str, no_passphrase: bool) -> Result<()> {
+    let store = KeyStore::open_default()?;
+    let passphrase = if no_passphrase {
+        None
+    } else {
+        Some(passphrase(name, true)?)
+    };
+    let info = store.generate(name, passphrase.as_deref())?;
+    println!(
+        "Generated {} key {} in {}",
+        protection(&info),
+        info.name,
+        store.path(name).display()
+    );
+    println!("Export it and keep a copy elsewhere: without it, what it encrypted cannot be read");
+    Ok(())
+}
+
+/// `flux key export`, to `output` or else stdout
+pub fn export(name: &str, output: Option<&Path>) -> Result<()> {
+    let store = KeyStore::open_default()?;
+    match output {
+        Some(output) => {
+            store.export(name, output)?;
+            println!("Exported key {} to {}", name, output.display());
+        }
+        None => print!("{}", store.export_string(name)?),
+    }
+    Ok(())
+}
+
+/// `flux key import`
+pub fn import(file: &Path, name: Option<&str>, force: bool) -> Result<()> {
+    let store = KeyStore::open_default()?;
+    let info = store.import(file, name, force)?;
+    println!("Imported {} key {}", protection(&info), info.name);
+    Ok(())
+}
+
+/// `flux key list`
+pub fn list() -> Result<()> {
+    let store = KeyStore::open_default()?;
+    let keys = store.list()?;
+    if keys.is_empty() {
+        println!("No keys (generate one with flux key generate <NAME>)");
+        return Ok(());
+    }
+    let configured = Config::load_or_default().manifest_key;
+    for info in keys {
+        let created = chrono::DateTime::from_timestamp(info.created, 0)
+            .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+        let marker = if configured.as_deref() == Some(info.name.as_str()) {
+            " (manifest_key)"
+        } else {
+            ""
+        };
+        println!(
+            "{:<24} {:<16} {}{}",
+            info.name,
+            created,
+            protection(&info),
+            marker
+        );
+    }
+    Ok(())
+}
+
+fn protection(info: &KeyInfo) -> &'static str {
+    if info.protected {
+        "protected"
+    } else {
+        "unprotected"
+    }
+}
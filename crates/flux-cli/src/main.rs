@@ -1,12 +1,28 @@
 use anyhow::Result;
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use flux_core::archive::{file_list, Archive, OperationOutcome, PackRoot};
+use flux_core::ops::PackJob;
+use flux_core::{ErrorCode, ErrorReport};
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use tracing_subscriber::EnvFilter;
 
+mod batch;
+mod compat;
+mod complete;
 mod extract;
+mod info;
+mod keys;
+mod progress;
+mod sync;
+mod table;
 mod tui;
+mod watch;
+mod wizard;
 
 #[cfg(feature = "cloud")]
 mod cloud_handler;
@@ -23,12 +39,49 @@ struct Cli {
     #[arg(short, long, global = true)]
     quiet: bool,
 
-    /// Show progress bar
+    /// Show a progress bar with the current phase and file
     #[arg(long, global = true)]
     progress: bool,
 
+    /// Run at lower CPU priority, adding LEVEL (1-19, default 10) to the niceness
+    // `=` is required so `flux --nice pack ...` does not read `pack` as the level
+    #[arg(
+        long,
+        global = true,
+        value_name = "LEVEL",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "10",
+        value_parser = clap::value_parser!(u8).range(1..=19)
+    )]
+    nice: Option<u8>,
+
+    /// Run at the lowest CPU priority and, on Linux, in the idle I/O class
+    #[arg(long, global = true, conflicts_with = "nice")]
+    background: bool,
+
+    /// Append the time and bytes of each phase to FILE, one JSON object per line
+    #[cfg(feature = "telemetry")]
+    #[arg(long, global = true, value_name = "FILE", env = "FLUX_METRICS_FILE")]
+    metrics_file: Option<PathBuf>,
+
+    /// Write temporary files, such as archives staged for the cloud or stdout, under DIR
+    #[arg(long, global = true, value_name = "DIR")]
+    temp_dir: Option<PathBuf>,
+
+    /// Fail an operation rather than stage more than SIZE of temporary files, e.g. 20GiB
+    #[arg(long, global = true, value_name = "SIZE", value_parser = parse_size)]
+    scratch_quota: Option<u64>,
+
+    /// Override a configuration value for this run, e.g. compression.default_level=19 (repeatable)
+    // Copied onto every subcommand by `parse_cli` rather than global, as clap
+    // would drop the values given before the subcommand whenever more follow it
+    #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_override)]
+    overrides: Vec<(String, String)>,
+
+    /// Without one, a terminal gets a guided prompt instead
     #[command(subcommand)]
-    command: Commands,
+    command: Option<Commands>,
 }
 
 #[derive(Subcommand)]
@@ -68,14 +121,135 @@ enum Commands {
             help = "If the archive contains a single folder, hoist its contents to the output directory"
         )]
         hoist: bool,
+
+        /// Recover what is still readable from a damaged archive and report what was lost
+        #[arg(long, conflicts_with_all = ["interactive", "hoist"])]
+        salvage: bool,
+
+        /// What to do with files whose names differ only in letter case when the
+        /// output directory cannot tell them apart (error, rename, last-wins)
+        #[arg(long, default_value = "rename", conflicts_with_all = ["interactive", "salvage"])]
+        case_collisions: flux_core::archive::CaseCollisions,
+
+        /// Write entry names in this Unicode form (keep, nfc, nfd)
+        #[arg(long, value_name = "FORM", default_value = "keep", conflicts_with_all = ["interactive", "salvage"])]
+        normalize_names: flux_core::archive::NameNormalization,
+
+        /// Check each extracted file against the hashes in this manifest, as saved by `flux pack`
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["interactive", "salvage"])]
+        manifest: Option<PathBuf>,
+
+        /// What to do with files that do not match --manifest (fail, flag)
+        #[arg(
+            long,
+            value_name = "POLICY",
+            default_value = "fail",
+            requires = "manifest"
+        )]
+        on_tampered: flux_core::archive::TamperPolicy,
+
+        /// Order to extract entries in (archive, smallest-first); honored for zip and
+        /// uncompressed tar archives
+        #[arg(long, default_value = "archive", conflicts_with_all = ["interactive", "salvage"])]
+        order: flux_core::archive::ExtractOrder,
+
+        /// Extract this archive path, or everything below it, before the rest; may be repeated
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["interactive", "salvage"])]
+        first: Vec<PathBuf>,
+
+        /// Move files that --overwrite replaces into .flux-backup in the output directory
+        /// and journal the extraction, so `flux undo` can take it back
+        #[arg(long, conflicts_with_all = ["interactive", "salvage"])]
+        backup: bool,
+
+        /// Read the archive as this format (tar, tar.gz, tar.zst, tar.xz, tar.br, zip, 7z)
+        /// instead of telling it from the archive's content
+        #[arg(long)]
+        format: Option<String>,
+
+        /// What to write for a symlink the system will not create, as on Windows
+        /// without the privilege (skip, copy, junction)
+        #[arg(long, value_name = "MODE", default_value = "skip")]
+        symlink_fallback: flux_core::archive::SymlinkFallback,
+
+        /// Give extracted files default permissions less the umask, as tar does,
+        /// instead of the modes stored in the archive
+        #[arg(long, conflicts_with = "interactive")]
+        no_same_permissions: bool,
+
+        /// Also extract archives found inside the archive, each into a folder in
+        /// its place
+        #[arg(long, conflicts_with_all = ["interactive", "salvage", "backup"])]
+        recursive: bool,
+
+        /// Levels of archives within archives --recursive opens
+        #[arg(long, value_name = "N", default_value_t = flux_core::archive::DEFAULT_RECURSION_DEPTH, requires = "recursive")]
+        max_depth: usize,
+
+        /// Fail on entries of types that are not extracted, such as devices and FIFOs,
+        /// instead of skipping them with a warning
+        #[arg(long, conflicts_with_all = ["interactive", "salvage", "recursive"])]
+        strict: bool,
+
+        /// When extracted files are synced to disk (none, per-file, final)
+        #[arg(long, value_name = "POLICY", default_value = "none", conflicts_with_all = ["interactive", "salvage"])]
+        durability: flux_core::durability::Durability,
+
+        /// Journal progress in .flux-resume.json in the output directory and carry on
+        /// an interrupted extraction of the same archive where it stopped
+        #[arg(long, conflicts_with_all = ["interactive", "salvage", "recursive"])]
+        resume: bool,
+    },
+
+    /// Take back the last extraction made with --backup into a directory
+    Undo {
+        /// Directory the archive was extracted into
+        #[arg(default_value = ".")]
+        directory: PathBuf,
+    },
+
+    /// Watch a directory and extract archives into their own folders as they arrive
+    WatchExtract {
+        /// Directory archives arrive in
+        directory: PathBuf,
+
+        /// Directory to extract into, one folder per archive
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Seconds between scans; an archive is taken once unchanged for one
+        #[arg(long, value_name = "SECS", default_value_t = 2)]
+        interval: u64,
+
+        /// When an archive's folder already exists
+        #[arg(long, value_enum, default_value_t = watch::OnConflict::Rename)]
+        on_conflict: watch::OnConflict,
+
+        /// Directory to move archives that fail to extract into (default: .quarantine in the watched directory)
+        #[arg(long)]
+        quarantine: Option<PathBuf>,
+
+        /// Delete archives once extracted, instead of moving them into .processed
+        #[arg(long)]
+        delete: bool,
+
+        /// Extract the archives there now and exit, instead of watching
+        #[arg(long)]
+        once: bool,
+
+        /// Print a JSON line for each archive
+        #[arg(long)]
+        json: bool,
     },
 
     /// Pack files into an archive
     Pack {
-        /// Input file or directory
-        input: PathBuf,
+        /// Input file or directory; with --files-from, the directory the listed paths
+        /// are read from (default: the current directory)
+        #[arg(required_unless_present = "files_from")]
+        input: Option<PathBuf>,
 
-        /// Output archive file
+        /// Output archive file, or - to write the archive to stdout
         #[arg(short, long)]
         output: PathBuf,
 
@@ -103,13 +277,84 @@ enum Commands {
         #[arg(long)]
         follow_symlinks: bool,
 
+        /// Levels of nested directories to pack; deeper ones are left out
+        #[arg(long, value_name = "N")]
+        max_depth: Option<usize>,
+
         /// Force compression on already compressed files
         #[arg(long)]
         force_compress: bool,
 
+        /// Skip version control and dependency directories (.git, node_modules, ...)
+        #[arg(long)]
+        exclude_vcs: bool,
+
         /// Previous manifest file for incremental backup
         #[arg(long)]
         incremental: Option<PathBuf>,
+
+        /// Split a zip into volumes of at most SIZE (e.g. 100M), named .z01, .z02, ... and .zip
+        #[arg(long, value_name = "SIZE", value_parser = parse_volume_size, conflicts_with = "incremental")]
+        volume_size: Option<u64>,
+
+        /// Encrypt the manifest with the password in FLUX_MANIFEST_PASSWORD, or one asked for
+        #[arg(long)]
+        encrypt_manifest: bool,
+
+        /// Record who made the archive, where and when, with this value (repeatable)
+        #[arg(long = "meta", value_name = "KEY=VALUE", value_parser = parse_override)]
+        meta: Vec<(String, String)>,
+
+        /// Store no timestamp later than this (Unix seconds or an RFC 3339 date)
+        #[arg(long, value_name = "TIME", env = SOURCE_DATE_EPOCH, value_parser = parse_mtime)]
+        mtime: Option<u64>,
+
+        /// Store entry names in this Unicode form (keep, nfc, nfd)
+        #[arg(long, value_name = "FORM", default_value = "keep")]
+        normalize_names: flux_core::archive::NameNormalization,
+
+        /// Take this leading folder off the names of the entries below it
+        #[arg(long, value_name = "DIR")]
+        strip_prefix: Option<PathBuf>,
+
+        /// Store every entry in this folder, after --strip-prefix
+        #[arg(long, value_name = "DIR")]
+        prefix: Option<PathBuf>,
+
+        /// Memory-map large input files on local disks instead of reading them
+        #[arg(long)]
+        mmap: bool,
+
+        /// Order to store each directory's entries in (walk, extension, similar); the
+        /// sorted orders group alike files for the compressor of a tar archive
+        #[arg(long, value_name = "ORDER", default_value = "walk")]
+        order: flux_core::archive::PackOrder,
+
+        /// Pack exactly the paths listed in FILE, one per line, or in stdin for -
+        #[arg(short = 'T', long, value_name = "FILE", conflicts_with = "incremental")]
+        files_from: Option<PathBuf>,
+
+        /// Paths in the --files-from list end with NUL bytes, as `find -print0` writes them
+        #[arg(long, requires = "files_from")]
+        null: bool,
+
+        /// Pick the highest compression level expected to finish within this time (e.g. 5m, 1h30m)
+        #[arg(long, value_name = "DURATION", value_parser = parse_target_time, conflicts_with = "level")]
+        target_time: Option<std::time::Duration>,
+
+        /// Report progress, including uploads, and errors as JSON lines on stderr
+        #[arg(long)]
+        json: bool,
+
+        /// Fail on files of types the format cannot store, such as sockets and devices,
+        /// instead of skipping them with a warning
+        #[arg(long)]
+        strict: bool,
+
+        /// Whether the archive and manifest are synced to disk once written
+        /// (none, per-file, final)
+        #[arg(long, value_name = "POLICY", default_value = "none")]
+        durability: flux_core::durability::Durability,
     },
 
     /// Inspect archive contents
@@ -121,6 +366,15 @@ enum Commands {
         #[arg(long)]
         json: bool,
 
+        /// Show the archive's metadata instead of its entries
+        #[arg(long, conflicts_with_all = ["interactive", "tree"])]
+        meta: bool,
+
+        /// Read the archive as this format (tar, tar.gz, tar.zst, tar.xz, tar.br, zip, 7z)
+        /// instead of telling it from the archive's content
+        #[arg(long, conflicts_with = "meta")]
+        format: Option<String>,
+
         /// Interactive TUI mode
         #[arg(short, long)]
         interactive: bool,
@@ -128,6 +382,145 @@ enum Commands {
         /// Show as tree structure
         #[arg(long)]
         tree: bool,
+
+        /// Sort entries by size, name or modification time
+        #[arg(long, value_enum)]
+        sort: Option<table::SortKey>,
+
+        /// Reverse the order, e.g. largest first with --sort size
+        #[arg(long)]
+        reverse: bool,
+
+        /// Columns to show, comma separated (path, size, compressed, ratio, mode, mtime)
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            conflicts_with_all = ["json", "tree", "interactive"]
+        )]
+        fields: Vec<table::Field>,
+
+        /// Show sizes in bytes instead of KB, MB, ...
+        #[arg(long, conflicts_with_all = ["json", "tree", "interactive"])]
+        bytes: bool,
+    },
+
+    /// Rename or move an entry inside an archive without extracting it
+    Mv {
+        /// Archive to modify (zip or tar, compressed or not); a compressed
+        /// tar is compressed again at the default level of its compression,
+        /// not the level it was packed at
+        archive: PathBuf,
+
+        /// Entry or directory to move
+        from: String,
+
+        /// New path inside the archive
+        to: String,
+    },
+
+    /// Print a checksum for every file in an archive, in `sha256sum` format
+    Checksum {
+        /// Archive file
+        archive: PathBuf,
+
+        /// Hash algorithm (md5, sha1, sha256, sha512, blake3, xxh3)
+        #[arg(long, default_value = "sha256")]
+        algo: String,
+    },
+
+    /// Find files stored more than once in an archive and what storing them once saves
+    DedupeReport {
+        /// Archive file
+        archive: PathBuf,
+
+        /// Rewrite the archive storing each duplicate once, as hard links (tar archives only)
+        #[arg(long)]
+        rewrite: bool,
+
+        /// With --rewrite, write the new archive here instead of replacing the original
+        #[arg(short, long, requires = "rewrite")]
+        output: Option<PathBuf>,
+
+        /// Output format as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// List archives and folders in cloud storage
+    Ls {
+        /// Cloud location such as s3://bucket/backups/
+        location: String,
+
+        /// Output format as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Delete archives, and the manifests stored next to them, from cloud
+    /// storage
+    RmRemote {
+        /// Cloud objects to delete, or prefixes ending in / with --recursive
+        #[arg(required = true)]
+        urls: Vec<String>,
+
+        /// Delete every archive below a prefix
+        #[arg(short, long)]
+        recursive: bool,
+
+        /// Only show what would be deleted
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Do not ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Cloud storage tools
+    Cloud {
+        #[command(subcommand)]
+        command: CloudCommand,
+    },
+
+    /// Abort unfinished uploads flux left in cloud storage
+    CleanupUploads {
+        /// Cloud prefix to search, such as s3://bucket/backups/
+        prefix: String,
+
+        /// Only abort uploads started at least this many hours ago
+        #[arg(long, default_value_t = 24)]
+        older_than: u64,
+
+        /// Only show what would be aborted
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Run with GNU tar options, e.g. `flux tar -xzf backup.tar.gz -C out`
+    // -v and -q given first are taken by the global flags, so they are
+    // passed back in from there
+    #[command(disable_help_flag = true)]
+    Tar {
+        /// Options and operands, as for tar
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "ARGS"
+        )]
+        args: Vec<String>,
+    },
+
+    /// Run with unzip options, e.g. `flux unzip -o photos.zip -d photos`
+    #[command(disable_help_flag = true)]
+    Unzip {
+        /// Options and operands, as for unzip
+        #[arg(
+            trailing_var_arg = true,
+            allow_hyphen_values = true,
+            value_name = "ARGS"
+        )]
+        args: Vec<String>,
     },
 
     /// Show or edit configuration
@@ -145,12 +538,36 @@ enum Commands {
         path: bool,
     },
 
+    /// Show the features, formats and compression libraries this build has, and where it
+    /// keeps its configuration and temporary files
+    Info {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Add "Extract with Flux" and "Compress with Flux" to the file manager context menu
+    Integrate {
+        /// Remove the context menu entries
+        #[arg(long, conflicts_with = "status")]
+        uninstall: bool,
+
+        /// Show whether the context menu entries are installed
+        #[arg(long)]
+        status: bool,
+
+        /// Flux GUI executable to launch (default: flux-gui next to this binary)
+        #[arg(long)]
+        gui: Option<PathBuf>,
+    },
+
     /// Synchronize directory with incremental backup
     Sync {
         /// Source directory to backup
         source: PathBuf,
 
-        /// Target archive file
+        /// Target archive file, or a cloud URL (s3://, gs://, az://) to keep a
+        /// numbered chain of backups under
         target: PathBuf,
 
         /// Compression algorithm (zstd, xz, brotli, gzip)
@@ -172,16 +589,372 @@ enum Commands {
         /// Force full backup (ignore previous manifest)
         #[arg(long)]
         full: bool,
+
+        /// Hash every file, instead of trusting the manifest for files whose size, times and inode are unchanged
+        #[arg(long)]
+        paranoid: bool,
+
+        /// Skip version control and dependency directories (.git, node_modules, ...)
+        #[arg(long)]
+        exclude_vcs: bool,
+
+        /// Encrypt the manifest with the password in FLUX_MANIFEST_PASSWORD, or one asked for
+        #[arg(long)]
+        encrypt_manifest: bool,
+
+        /// Only check, by size and modification time, whether the target has fallen behind; fails when it has
+        #[arg(long, conflicts_with_all = ["full", "paranoid", "encrypt_manifest"])]
+        check: bool,
+
+        /// Output the check as JSON
+        #[arg(long, requires = "check")]
+        json: bool,
+    },
+
+    /// Show what a sync would back up, compared with the target's last manifest
+    Status {
+        /// Source directory
+        source: PathBuf,
+
+        /// Archive the directory is backed up into
+        target: PathBuf,
+
+        /// Skip version control and dependency directories, as the backup did
+        #[arg(long)]
+        exclude_vcs: bool,
+
+        /// Hash every file, instead of trusting the manifest for files whose size, times and inode are unchanged
+        #[arg(long)]
+        paranoid: bool,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Tag a file in a backup manifest or set its note, and show what it has
+    Tag {
+        /// Manifest saved by `flux pack` or `flux sync`
+        manifest: PathBuf,
+
+        /// File as the manifest lists it, relative to the backed up directory
+        path: PathBuf,
+
+        /// Tags to set
+        #[arg(value_name = "KEY=VALUE", value_parser = parse_override)]
+        tags: Vec<(String, String)>,
+
+        /// Remove this tag (repeatable)
+        #[arg(long, value_name = "KEY")]
+        remove: Vec<String>,
+
+        /// Set the file's note
+        #[arg(long, conflicts_with = "clear_note")]
+        note: Option<String>,
+
+        /// Remove the file's note
+        #[arg(long)]
+        clear_note: bool,
+    },
+
+    /// Manage the keys that encrypt manifests
+    Key {
+        #[command(subcommand)]
+        command: KeyCommand,
+    },
+
+    /// Run the pack, extract and sync jobs listed in a YAML or TOML file
+    Batch {
+        /// Batch file (.yaml, .yml or .toml)
+        file: PathBuf,
+
+        /// Run all jobs at the same time, whatever the file says
+        #[arg(long)]
+        parallel: bool,
+
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
     },
+
+    /// Print the entry paths of an archive that complete PREFIX, for shell completion
+    #[command(name = "__complete-entries", hide = true)]
+    CompleteEntries {
+        /// Archive the entries are in
+        archive: PathBuf,
+
+        /// Start of the entry path typed so far
+        #[arg(default_value = "")]
+        prefix: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum CloudCommand {
+    /// Check that a cloud location can be listed, written, read and deleted from, and say what to fix
+    Doctor {
+        /// Cloud location such as s3://bucket/backups/
+        url: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum KeyCommand {
+    /// Generate a random key, protected by a passphrase unless told not to
+    Generate {
+        /// Name to keep the key under, as `manifest_key` in the configuration names it
+        name: String,
+
+        /// Store the key without a passphrase
+        #[arg(long)]
+        no_passphrase: bool,
+    },
+
+    /// Write a key out to keep elsewhere, still protected by its passphrase
+    Export {
+        /// Name of the key
+        name: String,
+
+        /// File to write (default: stdout)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Add an exported key to the key store
+    Import {
+        /// File written by `flux key export`
+        file: PathBuf,
+
+        /// Name to keep the key under instead of the one it was exported with
+        #[arg(long)]
+        name: Option<String>,
+
+        /// Replace a key by the same name
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// List the keys in the key store
+    List,
+}
+
+/// Apply `--nice` or `--background` before any worker thread starts, so
+/// every thread inherits it
+fn lower_priority(nice: Option<u8>, background: bool) {
+    use flux_core::priority::Priority;
+
+    let priority = match (nice, background) {
+        (_, true) => Priority::Background,
+        (Some(level), false) => Priority::Nice(level),
+        (None, false) => return,
+    };
+    // Running at normal priority beats not running at all
+    if let Err(e) = flux_core::priority::lower_priority(priority) {
+        warn!("Could not lower process priority: {}", e);
+    }
+}
+
+fn parse_override(assignment: &str) -> std::result::Result<(String, String), String> {
+    flux_core::config::parse_override(assignment).map_err(|e| e.to_string())
+}
+
+fn parse_mtime(time: &str) -> std::result::Result<u64, String> {
+    if let Ok(secs) = time.parse() {
+        return Ok(secs);
+    }
+    let time = chrono::DateTime::parse_from_rfc3339(time)
+        .map(|time| time.timestamp())
+        .or_else(|_| {
+            chrono::NaiveDate::parse_from_str(time, "%Y-%m-%d")
+                .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc().timestamp())
+        })
+        .map_err(|_| {
+            format!(
+                "expected Unix seconds or a date like 2024-01-31, got {:?}",
+                time
+            )
+        })?;
+    u64::try_from(time).map_err(|_| "times before 1970 cannot be stored".to_string())
+}
+
+/// Variable holding the latest timestamp to store, for reproducible builds
+const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+/// The timestamp limit from `SOURCE_DATE_EPOCH`, if it is set
+fn source_date_epoch() -> Result<Option<u64>> {
+    match std::env::var(SOURCE_DATE_EPOCH) {
+        Ok(value) if !value.is_empty() => value.parse().map(Some).map_err(|_| {
+            flux_core::Error::InvalidArgument(format!(
+                "{} must be a number of seconds, got {:?}",
+                SOURCE_DATE_EPOCH, value
+            ))
+            .into()
+        }),
+        _ => Ok(None),
+    }
+}
+
+fn parse_target_time(time: &str) -> std::result::Result<std::time::Duration, String> {
+    let time = flux_core::config::parse_duration(time).map_err(|e| e.to_string())?;
+    if time.is_zero() {
+        return Err("the target time must be more than zero".to_string());
+    }
+    Ok(time)
+}
+
+fn parse_size(size: &str) -> std::result::Result<u64, String> {
+    flux_core::config::parse_size(size).map_err(|e| e.to_string())
+}
+
+fn parse_volume_size(size: &str) -> std::result::Result<u64, String> {
+    let bytes = flux_core::config::parse_size(size).map_err(|e| e.to_string())?;
+    if bytes < flux_core::archive::spanned::MIN_VOLUME_SIZE {
+        return Err(format!(
+            "volumes must be at least 64KiB ({} bytes)",
+            flux_core::archive::spanned::MIN_VOLUME_SIZE
+        ));
+    }
+    Ok(bytes)
+}
+
+/// Metadata for `--meta`, none unless a value was given
+fn archive_metadata(
+    values: impl IntoIterator<Item = (String, String)>,
+) -> Option<flux_core::archive::ArchiveMetadata> {
+    let mut values = values.into_iter().peekable();
+    values.peek()?;
+    Some(values.fold(
+        flux_core::archive::ArchiveMetadata::current(),
+        |metadata, (key, value)| metadata.with_value(key, value),
+    ))
+}
+
+/// Filter for `--exclude-vcs`, with the patterns from the configuration file
+fn exclude_filter(exclude_vcs: bool) -> Result<flux_core::archive::PackFilter> {
+    let filter = flux_core::archive::PackFilter::default();
+    if !exclude_vcs {
+        return Ok(filter);
+    }
+    let config = flux_core::config::Config::load_or_default();
+    Ok(filter.exclude(&config.exclude.vcs_patterns)?)
 }
 
-fn setup_logging(verbose: bool, quiet: bool) {
+/// The inputs listed in `list`, or in stdin for `-`, named by their paths
+/// below `base`
+fn listed_roots(list: &Path, null: bool, base: &Path) -> Result<Vec<PackRoot>> {
+    let paths = if list == Path::new("-") {
+        file_list::read_file_list(io::stdin().lock(), null)?
+    } else {
+        file_list::read_file_list(fs::File::open(list)?, null)?
+    };
+    let roots = file_list::listed_roots(base, &paths)?;
+    if roots.is_empty() {
+        return Err(flux_core::Error::InvalidArgument(format!(
+            "No paths to pack are listed in {}",
+            list.display()
+        ))
+        .into());
+    }
+    Ok(roots)
+}
+
+/// Format an archive packed to `output` without `--format` is written in:
+/// the one its name calls for
+///
+/// Unlike the library, which packs such an output as a tar compressed as
+/// the strategy chose, the CLI refuses a name that calls for no format.
+fn named_format(output: &Path) -> Result<String> {
+    match flux_core::archive::detect::output_format(output) {
+        Some(format) => Ok(format.to_string()),
+        None => Err(flux_core::Error::UnsupportedFormat(format!(
+            "{} is not named for an archive format; give --format",
+            output.display()
+        ))
+        .into()),
+    }
+}
+
+/// Warn about what of `inputs` an archive in `format` will not keep
+///
+/// An unknown format is left for packing to report.
+fn warn_dropped_metadata(
+    inputs: &[PathBuf],
+    format: &str,
+    options: &flux_core::archive::PackOptions,
+) {
+    let Ok(dropped) = flux_core::archive::metadata_loss(inputs, format, options) else {
+        return;
+    };
+    for found in dropped {
+        warn!(
+            "{} archives do not keep {}: {}",
+            format, found.capability, found
+        );
+    }
+}
+
+/// Log how many entries a pack or extract handled, failing with
+/// `PartialFailure` when any of them could not be
+///
+/// The entries that failed were already logged as they did. Entries left
+/// out for their type, such as sockets and devices, are summed up in a
+/// warning, or with `strict` fail the command.
+fn check_outcome(outcome: OperationOutcome, strict: bool) -> Result<()> {
+    info!("Entries: {}", outcome);
+    let unsupported: Vec<&Path> = outcome.unsupported().collect();
+    if !unsupported.is_empty() {
+        let summary = unsupported_summary(&unsupported);
+        if strict {
+            return Err(flux_core::Error::UnsupportedOperation(summary).into());
+        }
+        warn!("{}", summary);
+    }
+    if outcome.is_complete() {
+        return Ok(());
+    }
+    Err(flux_core::Error::PartialFailure {
+        count: outcome.failed.len() as u32,
+    }
+    .into())
+}
+
+/// A line such as `3 entries skipped: a.sock, b.sock, tty0 (unsupported
+/// file type)`, naming the first few entries
+fn unsupported_summary(paths: &[&Path]) -> String {
+    const SHOWN: usize = 5;
+    let mut names: Vec<String> = paths
+        .iter()
+        .take(SHOWN)
+        .map(|path| path.display().to_string())
+        .collect();
+    if paths.len() > SHOWN {
+        names.push(format!("and {} more", paths.len() - SHOWN));
+    }
+    format!(
+        "{} {} skipped: {} ({})",
+        paths.len(),
+        if paths.len() == 1 { "entry" } else { "entries" },
+        names.join(", "),
+        flux_core::archive::SkipReason::Unsupported
+    )
+}
+
+/// Whether the command writes an archive to stdout instead of a file
+fn writes_to_stdout(command: &Commands) -> bool {
+    matches!(command, Commands::Pack { output, .. } if output == Path::new("-"))
+}
+
+fn setup_logging(verbose: bool, quiet: bool, terse: bool) {
     if quiet {
         return;
     }
 
     let filter = if verbose {
         EnvFilter::new("debug")
+    } else if terse {
+        // Info lines would scroll the progress bars away, clutter the
+        // terminal of a pipeline fed from stdout, or bury a JSON error
+        EnvFilter::new("warn")
     } else {
         EnvFilter::new("info")
     };
@@ -195,27 +968,118 @@ fn setup_logging(verbose: bool, quiet: bool) {
         .init();
 }
 
+/// Whether the command prints JSON, in which case errors are reported as
+/// JSON too
+fn wants_json(command: &Commands) -> bool {
+    matches!(
+        command,
+        Commands::Inspect { json: true, .. }
+            | Commands::Ls { json: true, .. }
+            | Commands::Info { json: true }
+            | Commands::Status { json: true, .. }
+            | Commands::Sync { json: true, .. }
+            | Commands::DedupeReport { json: true, .. }
+            | Commands::Batch { json: true, .. }
+            | Commands::Pack { json: true, .. }
+            | Commands::WatchExtract { json: true, .. }
+    )
+}
+
 fn main() {
-    let result = run();
+    let cli = parse_cli();
+    let json = cli.command.as_ref().is_some_and(wants_json);
 
-    match result {
+    match run(cli) {
         Ok(_) => process::exit(0),
         Err(e) => {
-            error!("Error: {}", e);
+            let report = error_report(&e);
+            if json {
+                // Stderr rather than stdout, which may already hold results
+                eprintln!("{}", serde_json::json!({ "error": report }));
+            } else {
+                error!("Error: {}", e);
+            }
+            process::exit(exit_code(report.code));
+        }
+    }
+}
+
+/// Parse the command line, accepting `--set` on either side of the subcommand
+///
+/// The values are merged in the order given.
+fn parse_cli() -> Cli {
+    fn add_set(command: clap::Command, set: &clap::Arg) -> clap::Command {
+        command.mut_subcommands(|sub| add_set(sub.arg(set.clone()), set))
+    }
 
-            // Map errors to exit codes based on requirements
-            let exit_code = map_error_to_exit_code(&e);
-            process::exit(exit_code);
+    let top = Cli::command();
+    let set = top
+        .get_arguments()
+        .find(|arg| arg.get_id() == "overrides")
+        .cloned()
+        .expect("--set is declared on Cli");
+    let mut matches = add_set(top, &set).get_matches();
+
+    let mut overrides = Vec::new();
+    let mut level = Some(&matches);
+    while let Some(current) = level {
+        if let Some(values) = current.get_many::<(String, String)>("overrides") {
+            overrides.extend(values.cloned());
         }
+        level = current.subcommand().map(|(_, sub)| sub);
     }
+
+    let mut cli = Cli::from_arg_matches_mut(&mut matches).unwrap_or_else(|e| e.exit());
+    cli.overrides = overrides;
+    cli
 }
 
-fn run() -> Result<()> {
-    let cli = Cli::parse();
+fn run(cli: Cli) -> Result<()> {
+    setup_logging(
+        cli.verbose,
+        // Anything but the completions would end up on the command line
+        cli.quiet || matches!(cli.command, Some(Commands::CompleteEntries { .. })),
+        cli.progress
+            || cli
+                .command
+                .as_ref()
+                .is_some_and(|command| writes_to_stdout(command) || wants_json(command)),
+    );
+    flux_core::config::set_overrides(cli.overrides)?;
+    if let Some(dir) = cli.temp_dir {
+        flux_core::space::set_temp_dir(dir);
+    }
+    if let Some(quota) = cli.scratch_quota {
+        flux_core::space::set_scratch_quota(quota);
+    }
+    lower_priority(cli.nice, cli.background);
+    // `performance.buffer_size` is in KiB
+    let buffer_size = flux_core::config::Config::load_or_default()
+        .performance
+        .buffer_size;
+    flux_core::buffer::BufferPool::global().set_chunk_size(buffer_size as usize * 1024);
+    #[cfg(feature = "telemetry")]
+    if let Some(path) = &cli.metrics_file {
+        let exporter = flux_core::telemetry::JsonLinesExporter::open(path)
+            .map_err(|e| flux_core::Error::from(e).with_path(path))?;
+        flux_core::telemetry::set_exporter(std::sync::Arc::new(exporter));
+    }
 
-    setup_logging(cli.verbose, cli.quiet);
+    let command = match cli.command {
+        Some(command) => command,
+        None if io::stdin().is_terminal() && io::stderr().is_terminal() => match wizard::run()? {
+            Some(command) => command,
+            None => return Ok(()),
+        },
+        None => Cli::command()
+            .error(
+                ErrorKind::MissingSubcommand,
+                "a subcommand is required when not run from a terminal",
+            )
+            .exit(),
+    };
 
-    match cli.command {
+    match command {
         Commands::Extract {
             archive,
             output,
@@ -225,10 +1089,33 @@ fn run() -> Result<()> {
             strip_components,
             interactive,
             hoist,
+            salvage,
+            case_collisions,
+            normalize_names,
+            manifest,
+            on_tampered,
+            order,
+            first,
+            backup,
+            format,
+            symlink_fallback,
+            no_same_permissions,
+            recursive,
+            max_depth,
+            strict,
+            durability,
+            resume,
         } => {
             let archive_str = archive.to_string_lossy();
             info!("Extracting archive: {}", archive_str);
             let output_dir = output.unwrap_or_else(|| PathBuf::from("."));
+            let manifest_check = manifest
+                .map(|path| sync::manifest_check(&path, on_tampered))
+                .transpose()?;
+            let nested = recursive.then(|| flux_core::archive::RecursiveOptions {
+                max_depth,
+                ..Default::default()
+            });
 
             // Check if the archive is a cloud path
             #[cfg(feature = "cloud")]
@@ -238,31 +1125,37 @@ fn run() -> Result<()> {
                     cloud_handler::describe_cloud_location(&archive_str)
                 );
 
+                if salvage {
+                    return Err(flux_core::Error::InvalidArgument(
+                        "--salvage only works on local archives".to_string(),
+                    )
+                    .into());
+                }
+
                 // Check credentials
                 cloud_handler::check_cloud_credentials(&archive_str)?;
 
-                // Create cloud reader
-                let mut reader = cloud_handler::create_cloud_reader(&archive_str)?;
-
-                // Create a temporary file to store the archive
-                let temp_dir = tempfile::tempdir()?;
-                let temp_archive = temp_dir.path().join("cloud_archive.tar");
-                let mut temp_file = std::fs::File::create(&temp_archive)?;
-
-                // Download the archive to temp file
+                // Download the archive, with all its parts, to a temporary
+                // directory
+                let scratch = flux_core::space::TempResource::new()?;
                 info!("Downloading archive from cloud storage...");
-                std::io::copy(&mut reader, &mut temp_file)?;
-                drop(temp_file);
+                let display = progress::ProgressDisplay::new(cli.progress);
+                let temp_archive =
+                    cloud_handler::download_archive(&archive_str, &scratch, &display)?;
+                display.finish();
+                let opened = Archive::open_as(&temp_archive, format.as_deref())?;
+                flux_core::space::check_extract(&opened, &output_dir)?;
 
                 // Extract from the temporary file
                 if interactive {
                     info!("Interactive mode enabled - prompting for file conflicts");
                     extract::extract_interactive(
-                        &temp_archive,
+                        &opened,
                         &output_dir,
                         strip_components,
                         cli.progress,
                         hoist,
+                        symlink_fallback,
                     )?;
                 } else {
                     let options = flux_core::archive::ExtractOptions {
@@ -271,9 +1164,38 @@ fn run() -> Result<()> {
                         rename,
                         strip_components,
                         hoist,
+                        case_collisions,
+                        fold_case: None,
+                        normalize_names,
+                        manifest_check,
+                        entry_filter: None,
+                        order,
+                        priority: first,
+                        undoable: backup,
+                        format,
+                        symlink_fallback,
+                        apply_umask: no_same_permissions,
+                        durability,
+                        resume,
                     };
 
-                    flux_core::archive::extract_with_options(&temp_archive, &output_dir, options)?;
+                    if let Some(limits) = &nested {
+                        extract::extract_recursive(
+                            &temp_archive,
+                            &output_dir,
+                            options,
+                            limits,
+                            cli.progress,
+                        )?;
+                    } else {
+                        extract::extract_with_options(
+                            &opened,
+                            &output_dir,
+                            options,
+                            cli.progress,
+                            strict,
+                        )?;
+                    }
                 }
 
                 info!("Extraction complete");
@@ -281,14 +1203,54 @@ fn run() -> Result<()> {
             }
 
             // Regular local file extraction
+            if salvage {
+                let options = flux_core::archive::ExtractOptions {
+                    overwrite,
+                    skip,
+                    rename,
+                    strip_components,
+                    hoist: false,
+                    format,
+                    symlink_fallback,
+                    apply_umask: no_same_permissions,
+                    ..Default::default()
+                };
+
+                let report = flux_core::archive::salvage(&archive, &output_dir, &options)?;
+                print!("{}", report);
+                if !report.is_complete() {
+                    // Damage that cost no named entry still counts as one loss
+                    return Err(flux_core::Error::PartialFailure {
+                        count: report.lost.len().max(1) as u32,
+                    }
+                    .into());
+                }
+                return Ok(());
+            }
+
+            // Opened once for the space check and the extraction; a damaged
+            // archive may not list, so salvaging goes unchecked
+            let opened = Archive::open_as(&archive, format.as_deref())?;
+            flux_core::space::check_extract(&opened, &output_dir)?;
+            if !resume {
+                if let Ok(Some(journal)) = flux_core::archive::resumable(&archive, &output_dir) {
+                    warn!(
+                        "An interrupted extraction of this archive into {} finished {} files; \
+                         run again with --resume to carry on from there",
+                        output_dir.display(),
+                        journal.completed.len()
+                    );
+                }
+            }
             if interactive {
                 info!("Interactive mode enabled - prompting for file conflicts");
                 extract::extract_interactive(
-                    &archive,
+                    &opened,
                     &output_dir,
                     strip_components,
                     cli.progress,
                     hoist,
+                    symlink_fallback,
                 )?;
             } else {
                 let options = flux_core::archive::ExtractOptions {
@@ -297,9 +1259,38 @@ fn run() -> Result<()> {
                     rename,
                     strip_components,
                     hoist,
+                    case_collisions,
+                    fold_case: None,
+                    normalize_names,
+                    manifest_check,
+                    entry_filter: None,
+                    order,
+                    priority: first,
+                    undoable: backup,
+                    format,
+                    symlink_fallback,
+                    apply_umask: no_same_permissions,
+                    durability,
+                    resume,
                 };
 
-                flux_core::archive::extract_with_options(&archive, &output_dir, options)?;
+                if let Some(limits) = &nested {
+                    extract::extract_recursive(
+                        &archive,
+                        &output_dir,
+                        options,
+                        limits,
+                        cli.progress,
+                    )?;
+                } else {
+                    extract::extract_with_options(
+                        &opened,
+                        &output_dir,
+                        options,
+                        cli.progress,
+                        strict,
+                    )?;
+                }
                 info!("Extraction complete");
             }
         }
@@ -313,12 +1304,81 @@ fn run() -> Result<()> {
             level,
             threads,
             follow_symlinks,
+            max_depth,
             force_compress,
+            exclude_vcs,
             incremental,
+            volume_size,
+            encrypt_manifest,
+            meta,
+            mtime,
+            normalize_names,
+            strip_prefix,
+            prefix,
+            mmap,
+            order,
+            files_from,
+            null,
+            target_time,
+            json,
+            strict,
+            durability,
         } => {
+            let input = input.unwrap_or_else(|| PathBuf::from("."));
+            // Exactly the listed paths, named by their paths below the input
+            let roots = match &files_from {
+                Some(list) => listed_roots(list, null, &input)?,
+                None => Vec::new(),
+            };
+            // What the archive is made of, to measure it by
+            let measured: Vec<PathBuf> = if roots.is_empty() {
+                vec![input.clone()]
+            } else {
+                roots
+                    .iter()
+                    .filter(|root| !root.path.is_dir())
+                    .map(|root| root.path.clone())
+                    .collect()
+            };
+            let filter = exclude_filter(exclude_vcs)?;
+            let metadata = archive_metadata(meta);
             let output_str = output.to_string_lossy();
             info!("Packing {:?} into {}", input, output_str);
 
+            // Volumes are named after the .zip, and only zips are split
+            let format = match volume_size {
+                Some(_) => {
+                    let named_zip = output
+                        .extension()
+                        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"));
+                    if !named_zip || format.as_deref().is_some_and(|format| format != "zip") {
+                        return Err(flux_core::Error::InvalidPath(
+                            "--volume-size needs a zip archive named .zip".to_string(),
+                        )
+                        .into());
+                    }
+                    Some("zip".to_string())
+                }
+                None => format,
+            };
+
+            // Without --format the archive is what the output is named for,
+            // where the library would fall back to a tar; stdout gets a tar
+            let format = match format {
+                None if output != Path::new("-") => Some(named_format(&output)?),
+                format => format,
+            };
+            warn_dropped_metadata(
+                &measured,
+                format.as_deref().unwrap_or("tar"),
+                &flux_core::archive::PackOptions {
+                    follow_symlinks,
+                    max_depth,
+                    filter: filter.clone(),
+                    ..Default::default()
+                },
+            );
+
             // Warn about XZ thread limitations
             if let Some(ref algorithm) = algo {
                 if algorithm.to_lowercase() == "xz" && threads.unwrap_or(2) > 1 {
@@ -334,21 +1394,98 @@ fn run() -> Result<()> {
                     cloud_handler::describe_cloud_location(&output_str)
                 );
 
-                // Check credentials
-                cloud_handler::check_cloud_credentials(&output_str)?;
+                // Check credentials
+                cloud_handler::check_cloud_credentials(&output_str)?;
+
+                if volume_size.is_some() {
+                    return Err(flux_core::Error::UnsupportedOperation(
+                        "Split archives can only be written to local disk".to_string(),
+                    )
+                    .into());
+                }
+
+                if incremental.is_some() {
+                    error!("Incremental backup to cloud storage is not yet supported");
+                    return Err(flux_core::Error::UnsupportedOperation(
+                        "Incremental backup to cloud storage is not yet supported".to_string(),
+                    )
+                    .into());
+                }
+
+                // Create a temporary file for the archive
+                let scratch = flux_core::space::TempResource::new()?;
+                let temp_archive = scratch.path().join("archive");
+
+                // Pack to temporary file
+                let options = flux_core::archive::PackOptions {
+                    smart,
+                    algorithm: algo,
+                    level,
+                    threads,
+                    force_compress,
+                    follow_symlinks,
+                    max_depth,
+                    filter: filter.clone(),
+                    metadata: metadata.clone(),
+                    clamp_mtime: mtime,
+                    normalize_names,
+                    strip_prefix: strip_prefix.clone(),
+                    prefix: prefix.clone(),
+                    use_mmap: mmap,
+                    transform: None,
+                    time_budget: target_time,
+                    paranoid: false,
+                    hashes: None,
+                    entry_order: order,
+                };
+
+                // The temporary file is not named for the archive, the URL is
+                flux_core::space::check_pack(
+                    &measured,
+                    &temp_archive,
+                    format.as_deref(),
+                    &options,
+                )?;
+
+                let display = progress::ProgressDisplay::new(cli.progress).with_events(json);
+                display.phase(progress::Phase::Scan, None);
+                display.status(input.display().to_string());
+                let outcome = PackJob {
+                    inputs: vec![input.clone()],
+                    roots: roots.clone(),
+                    output: temp_archive.clone(),
+                    format: format.clone(),
+                    options,
+                    // Only kept until it is sent on
+                    durability: Default::default(),
+                }
+                .run(&display)?;
+                scratch.check()?;
+
+                // Upload to cloud
+                info!("Uploading archive to cloud storage...");
+                cloud_handler::upload(&temp_archive, &output_str, &display)?;
+                display.finish();
+
+                info!("Packing complete - archive uploaded to cloud");
+                return check_outcome(outcome, strict);
+            }
 
+            if output == Path::new("-") {
                 if incremental.is_some() {
-                    error!("Incremental backup to cloud storage is not yet supported");
-                    return Err(anyhow::anyhow!(
-                        "Incremental backup to cloud storage is not yet supported"
-                    ));
+                    return Err(flux_core::Error::InvalidArgument(
+                        "Incremental backup cannot write to stdout".to_string(),
+                    )
+                    .into());
+                }
+                if io::stdout().is_terminal() {
+                    return Err(flux_core::Error::InvalidArgument(
+                        "Refusing to write archive data to a terminal; redirect stdout or use -o FILE"
+                            .to_string(),
+                    )
+                    .into());
                 }
 
-                // Create a temporary file for the archive
-                let temp_dir = tempfile::tempdir()?;
-                let temp_archive = temp_dir.path().join("temp_archive.tar");
-
-                // Pack to temporary file
                 let options = flux_core::archive::PackOptions {
                     smart,
                     algorithm: algo,
@@ -356,24 +1493,91 @@ fn run() -> Result<()> {
                     threads,
                     force_compress,
                     follow_symlinks,
+                    max_depth,
+                    filter,
+                    metadata: metadata.clone(),
+                    clamp_mtime: mtime,
+                    normalize_names,
+                    strip_prefix: strip_prefix.clone(),
+                    prefix: prefix.clone(),
+                    use_mmap: mmap,
+                    transform: None,
+                    time_budget: target_time,
+                    paranoid: false,
+                    hashes: None,
+                    entry_order: order,
                 };
 
-                flux_core::archive::pack_with_strategy(
-                    &input,
-                    &temp_archive,
-                    format.as_deref(),
-                    options,
-                )?;
-
-                // Upload to cloud
-                info!("Uploading archive to cloud storage...");
-                let mut cloud_writer = cloud_handler::create_cloud_writer(&output_str)?;
-                let mut temp_file = std::fs::File::open(&temp_archive)?;
-                std::io::copy(&mut temp_file, &mut cloud_writer)?;
-                cloud_writer.flush()?;
+                let display = progress::ProgressDisplay::new(cli.progress).with_events(json);
+                display.phase(progress::Phase::Scan, None);
+                display.status(input.display().to_string());
+
+                // Zip and 7z need to seek back into what they wrote, so
+                // those are packed to a temporary file first; tars are
+                // written straight out
+                let staged = format
+                    .as_deref()
+                    .and_then(flux_core::archive::parse_format)
+                    .is_some_and(|format| matches!(format, "zip" | "7z"));
+                let outcome = if staged {
+                    let scratch = flux_core::space::TempResource::new()?;
+                    let temp_archive = scratch.path().join("archive");
+                    flux_core::space::check_pack(
+                        &measured,
+                        &temp_archive,
+                        format.as_deref(),
+                        &options,
+                    )?;
+                    let outcome = PackJob {
+                        inputs: vec![input.clone()],
+                        roots: roots.clone(),
+                        output: temp_archive.clone(),
+                        format: format.clone(),
+                        options,
+                        // Only kept until it is sent on
+                        durability: Default::default(),
+                    }
+                    .run(&display)?;
+                    scratch.check()?;
+
+                    display.phase(progress::Phase::Write, None);
+                    display.status("stdout");
+                    let mut stdout = io::stdout().lock();
+                    let written = io::copy(&mut fs::File::open(&temp_archive)?, &mut stdout)?;
+                    stdout.flush()?;
+                    display.inc(written);
+                    outcome
+                } else {
+                    let mut stdout = io::stdout().lock();
+                    let outcome = if roots.is_empty() {
+                        flux_core::archive::pack_to_writer(
+                            &input,
+                            &mut stdout,
+                            format.as_deref(),
+                            options,
+                            &display,
+                        )?
+                    } else {
+                        let format = format.as_deref().ok_or_else(|| {
+                            flux_core::Error::InvalidArgument(
+                                "Give --format to write a file list to stdout".to_string(),
+                            )
+                        })?;
+                        flux_core::archive::pack_roots_to_writer(
+                            &roots,
+                            &mut stdout,
+                            format,
+                            &options,
+                            &display,
+                        )?
+                    };
+                    stdout.flush()?;
+                    outcome
+                };
+                display.finish();
 
-                info!("Packing complete - archive uploaded to cloud");
-                return Ok(());
+                info!("Packing complete - archive written to stdout");
+                return check_outcome(outcome, strict);
             }
 
             // Regular local file packing
@@ -386,24 +1590,46 @@ fn run() -> Result<()> {
 
                 if !input.is_dir() {
                     error!("Incremental backup requires a directory as input");
-                    return Err(anyhow::anyhow!(
-                        "Incremental backup requires a directory as input"
-                    ));
+                    return Err(flux_core::Error::InvalidPath(
+                        "Incremental backup requires a directory as input".to_string(),
+                    )
+                    .into());
                 }
 
-                let (new_manifest_path, diff) = flux_core::archive::incremental::pack_incremental(
-                    &input,
-                    &output,
-                    &manifest_path,
-                    flux_core::archive::PackOptions {
-                        smart,
-                        algorithm: algo,
-                        level,
-                        threads,
-                        force_compress,
-                        follow_symlinks,
-                    },
-                )?;
+                let password = sync::manifest_password(encrypt_manifest, &manifest_path)?;
+                let (new_manifest_path, diff) =
+                    flux_core::archive::incremental::pack_incremental_with_password(
+                        &input,
+                        &output,
+                        &manifest_path,
+                        flux_core::archive::PackOptions {
+                            smart,
+                            algorithm: algo,
+                            level,
+                            threads,
+                            force_compress,
+                            follow_symlinks,
+                            max_depth,
+                            filter: filter.clone(),
+                            metadata,
+                            clamp_mtime: mtime,
+                            normalize_names,
+                            strip_prefix: strip_prefix.clone(),
+                            prefix: prefix.clone(),
+                            use_mmap: mmap,
+                            transform: None,
+                            time_budget: target_time,
+                            paranoid: false,
+                            hashes: None,
+                            entry_order: order,
+                        },
+                        password.as_deref(),
+                    )?;
+                // Nothing is written when nothing changed
+                if diff.has_changes() {
+                    durability.sync_output(&output)?;
+                    durability.sync_output(&new_manifest_path)?;
+                }
 
                 info!("Incremental backup complete");
                 info!(
@@ -414,7 +1640,10 @@ fn run() -> Result<()> {
                 );
                 info!("New manifest saved to: {:?}", new_manifest_path);
             } else {
-                // Regular packing mode
+                // Regular packing mode; a directory's files are hashed for
+                // its manifest as they are packed
+                let hashes =
+                    (input.is_dir() && roots.is_empty()).then(flux_core::archive::PackHashes::new);
                 let options = flux_core::archive::PackOptions {
                     smart,
                     algorithm: algo,
@@ -422,20 +1651,63 @@ fn run() -> Result<()> {
                     threads,
                     force_compress,
                     follow_symlinks,
+                    max_depth,
+                    filter: filter.clone(),
+                    metadata,
+                    clamp_mtime: mtime,
+                    normalize_names,
+                    strip_prefix: strip_prefix.clone(),
+                    prefix: prefix.clone(),
+                    use_mmap: mmap,
+                    transform: None,
+                    time_budget: target_time,
+                    paranoid: false,
+                    hashes: hashes.clone(),
+                    entry_order: order,
                 };
 
-                flux_core::archive::pack_with_strategy(
-                    &input,
-                    &output,
-                    format.as_deref(),
+                // Asked before packing rather than after it
+                let manifest_path = output.with_extension("manifest.json");
+                let password = if hashes.is_some() {
+                    sync::manifest_password(encrypt_manifest, &manifest_path)?
+                } else {
+                    None
+                };
+
+                flux_core::space::check_pack(&measured, &output, format.as_deref(), &options)?;
+
+                let display = progress::ProgressDisplay::new(cli.progress).with_events(json);
+                display.phase(progress::Phase::Scan, None);
+                display.status(input.display().to_string());
+                let outcome = PackJob {
+                    inputs: vec![input.clone()],
+                    roots: roots.clone(),
+                    output: output.clone(),
+                    format: format.clone(),
                     options,
-                )?;
+                    durability,
+                }
+                .run(&display)?;
+
+                if let Some(volume_size) = volume_size {
+                    let size = fs::metadata(&output)?.len();
+                    display.phase(progress::Phase::Write, Some(size));
+                    display.status(output.display().to_string());
+                    let volumes = flux_core::archive::split_zip(&output, volume_size)?;
+                    for volume in &volumes {
+                        durability.sync_output(volume)?;
+                    }
+                    display.inc(size);
+                    info!("Split into {} volumes", volumes.len());
+                }
+                display.finish();
 
                 // Generate manifest for future incremental backups
-                if input.is_dir() {
-                    let manifest = flux_core::manifest::Manifest::from_directory(&input)?;
-                    let manifest_path = output.with_extension("manifest.json");
-                    manifest.save(&manifest_path)?;
+                if let Some(hashes) = &hashes {
+                    let manifest =
+                        flux_core::manifest::Manifest::from_packed(&input, &filter, hashes)?;
+                    manifest.save_with_password(&manifest_path, password.as_deref())?;
+                    durability.sync_output(&manifest_path)?;
                     info!(
                         "Manifest saved to: {:?} (use with --incremental for future backups)",
                         manifest_path
@@ -443,19 +1715,38 @@ fn run() -> Result<()> {
                 }
 
                 info!("Packing complete");
+                check_outcome(outcome, strict)?;
             }
         }
 
         Commands::Inspect {
             archive,
             json,
+            meta,
+            format,
             interactive,
             tree,
+            sort,
+            reverse,
+            fields,
+            bytes,
         } => {
             let archive_str = archive.to_string_lossy();
             info!("Inspecting archive: {}", archive_str);
 
-            let entries = {
+            // Reads the entries, or with --meta only the metadata
+            let read = |path: &Path| -> Result<_> {
+                if meta {
+                    Ok((Vec::new(), flux_core::archive::read_metadata(path)?))
+                } else {
+                    Ok((
+                        flux_core::archive::inspect_as(path, format.as_deref())?,
+                        None,
+                    ))
+                }
+            };
+
+            let (mut entries, metadata) = {
                 #[cfg(feature = "cloud")]
                 {
                     if cloud_handler::is_cloud_path(&archive_str) {
@@ -467,30 +1758,37 @@ fn run() -> Result<()> {
                         // Check credentials
                         cloud_handler::check_cloud_credentials(&archive_str)?;
 
-                        // Create cloud reader
-                        let mut reader = cloud_handler::create_cloud_reader(&archive_str)?;
-
-                        // Create a temporary file to store the archive
-                        let temp_dir = tempfile::tempdir()?;
-                        let temp_archive = temp_dir.path().join("cloud_archive.tar");
-                        let mut temp_file = std::fs::File::create(&temp_archive)?;
-
-                        // Download the archive to temp file
+                        // Download the archive, with all its parts, to a
+                        // temporary directory
+                        let scratch = flux_core::space::TempResource::new()?;
                         info!("Downloading archive from cloud storage...");
-                        std::io::copy(&mut reader, &mut temp_file)?;
-                        drop(temp_file);
+                        let display = progress::ProgressDisplay::new(cli.progress);
+                        let temp_archive =
+                            cloud_handler::download_archive(&archive_str, &scratch, &display)?;
+                        display.finish();
 
                         // Inspect the temporary file
-                        flux_core::inspect(&temp_archive)?
+                        read(&temp_archive)?
                     } else {
-                        flux_core::inspect(&archive)?
+                        read(&archive)?
                     }
                 }
 
                 #[cfg(not(feature = "cloud"))]
-                flux_core::inspect(&archive)?
+                read(&archive)?
             };
 
+            if meta {
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&metadata)?);
+                } else {
+                    print_metadata(metadata.as_ref());
+                }
+                return Ok(());
+            }
+
+            table::sort_entries(&mut entries, sort, reverse);
+
             if interactive {
                 // Interactive TUI mode
                 info!("Launching interactive browser...");
@@ -503,47 +1801,225 @@ fn run() -> Result<()> {
                 // Tree view
                 print_tree(&entries);
             } else {
-                // Output as human-readable table
-                println!(
-                    "{:<50} {:>15} {:>15} {:>10} {:>20}",
-                    "Path", "Size", "Compressed", "Mode", "Modified"
+                let fields = if fields.is_empty() {
+                    table::DEFAULT_FIELDS
+                } else {
+                    &fields
+                };
+                print!("{}", table::render(&entries, fields, bytes));
+            }
+
+            info!("Inspection complete");
+        }
+
+        Commands::Tar { args } => {
+            let mut args = compat::parse_tar(&args)?;
+            args.verbose |= cli.verbose;
+            compat::run_tar(args)?;
+        }
+
+        Commands::Unzip { args } => {
+            compat::run_unzip(compat::parse_unzip(&args)?, cli.verbose)?;
+        }
+
+        Commands::Mv { archive, from, to } => {
+            info!("Renaming {} to {} in {:?}", from, to, archive);
+            let renamed = flux_core::archive::rename_entry(&archive, &from, &to)?;
+            info!("Renamed {} entries", renamed);
+        }
+
+        Commands::Checksum { archive, algo } => {
+            use std::io::Write;
+
+            let algorithm: flux_core::archive::HashAlgorithm = algo.parse()?;
+            info!("Computing {} checksums for {:?}", algorithm, archive);
+            let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+            flux_core::archive::checksums_with(&archive, algorithm, |entry| {
+                writeln!(out, "{}", entry.line())?;
+                Ok(())
+            })?;
+            out.flush()?;
+        }
+
+        Commands::DedupeReport {
+            archive,
+            rewrite,
+            output,
+            json,
+        } => {
+            let report = if rewrite {
+                flux_core::archive::deduplicate(&archive, output.as_deref())?
+            } else {
+                flux_core::archive::dedupe_report(&archive)?
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+            } else {
+                print_dedupe_report(&report);
+                if rewrite && !report.groups.is_empty() {
+                    let written = output.as_deref().unwrap_or(&archive);
+                    println!("Rewrote {} storing each duplicate once", written.display());
+                }
+            }
+        }
+
+        Commands::Ls { location, json } => {
+            #[cfg(feature = "cloud")]
+            {
+                if !cloud_handler::is_cloud_path(&location) {
+                    return Err(not_cloud_location(&location));
+                }
+                info!(
+                    "Listing {}",
+                    cloud_handler::describe_cloud_location(&location)
                 );
-                println!("{}", "-".repeat(120));
 
-                for entry in entries {
-                    let mode_str = if let Some(mode) = entry.mode {
-                        format!("{:o}", mode)
-                    } else {
-                        "-".to_string()
-                    };
+                cloud_handler::check_cloud_credentials(&location)?;
+                let listed = cloud_handler::list_location(&location)?;
 
-                    let mtime_str = if let Some(mtime) = entry.mtime {
-                        let datetime = chrono::DateTime::<chrono::Utc>::from_timestamp(mtime, 0)
-                            .unwrap_or_default();
-                        datetime.format("%Y-%m-%d %H:%M:%S").to_string()
-                    } else {
-                        "-".to_string()
-                    };
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&listed)?);
+                } else {
+                    print_listing(&listed);
+                }
+            }
+
+            #[cfg(not(feature = "cloud"))]
+            {
+                let _ = json;
+                return Err(no_cloud_support(&location));
+            }
+        }
+
+        Commands::RmRemote {
+            urls,
+            recursive,
+            dry_run,
+            yes,
+        } => {
+            #[cfg(feature = "cloud")]
+            {
+                let mut batches = Vec::new();
+                for url in &urls {
+                    if !cloud_handler::is_cloud_path(url) {
+                        return Err(not_cloud_location(url));
+                    }
+                    cloud_handler::check_cloud_credentials(url)?;
+                    let (store, cloud_path) = cloud_handler::connect(url)?;
+                    let targets =
+                        cloud_handler::removal_targets(&store, &cloud_path, url, recursive)?;
+                    for object in &targets {
+                        println!(
+                            "{}://{}/{} ({} bytes)",
+                            cloud_path.scheme, cloud_path.bucket, object.location, object.size
+                        );
+                    }
+                    batches.push((store, targets));
+                }
+
+                let count: usize = batches.iter().map(|(_, targets)| targets.len()).sum();
+                if count == 0 {
+                    info!("No archives to delete");
+                    return Ok(());
+                }
+                if dry_run {
+                    info!("{} objects would be deleted", count);
+                    return Ok(());
+                }
+                if !yes
+                    && !dialoguer::Confirm::new()
+                        .with_prompt(format!("Delete {} objects?", count))
+                        .default(false)
+                        .interact()?
+                {
+                    info!("Nothing deleted");
+                    return Ok(());
+                }
+
+                for (store, targets) in &batches {
+                    cloud_handler::remove_objects(store, targets)?;
+                }
+                info!("Deleted {} objects", count);
+            }
+
+            #[cfg(not(feature = "cloud"))]
+            {
+                let _ = (recursive, dry_run, yes);
+                return Err(no_cloud_support(&urls[0]));
+            }
+        }
 
-                    let compressed_str = entry
-                        .compressed_size
-                        .map(|s| format!("{}", s))
-                        .unwrap_or_else(|| "-".to_string());
+        Commands::Cloud {
+            command: CloudCommand::Doctor { url },
+        } => {
+            #[cfg(feature = "cloud")]
+            {
+                if !cloud_handler::is_cloud_path(&url) {
+                    return Err(not_cloud_location(&url));
+                }
+                let cloud_path = cloud_handler::parse_cloud_path(&url)?;
+                println!("Checking {}", cloud_handler::describe_cloud_location(&url));
+
+                let endpoint = cloud_handler::profile_endpoint(&url);
+                let checks = flux_cloud::doctor::check_location(&cloud_path, endpoint.as_deref());
+                print_checks(&checks);
+                let failed = checks
+                    .iter()
+                    .filter(|check| check.status == flux_cloud::doctor::Status::Fail)
+                    .count();
+                if failed > 0 {
+                    anyhow::bail!("{} of {} checks failed for {}", failed, checks.len(), url);
+                }
+            }
+
+            #[cfg(not(feature = "cloud"))]
+            return Err(no_cloud_support(&url));
+        }
+
+        Commands::CleanupUploads {
+            prefix,
+            older_than,
+            dry_run,
+        } => {
+            #[cfg(feature = "cloud")]
+            {
+                if !cloud_handler::is_cloud_path(&prefix) {
+                    return Err(not_cloud_location(&prefix));
+                }
+                cloud_handler::check_cloud_credentials(&prefix)?;
+                let (store, cloud_path) = cloud_handler::connect(&prefix)?;
 
+                let min_age = chrono::Duration::hours(older_than.try_into().unwrap_or(i64::MAX));
+                let stale = cloud_handler::stale_uploads(&store, &cloud_path, min_age)?;
+                for upload in &stale {
                     println!(
-                        "{:<50} {:>15} {:>15} {:>10} {:>20}",
-                        entry.path.display(),
-                        entry.size,
-                        compressed_str,
-                        mode_str,
-                        mtime_str
+                        "{}://{}/{} (started {})",
+                        cloud_path.scheme,
+                        cloud_path.bucket,
+                        upload.path,
+                        upload.marker.last_modified.format("%Y-%m-%d %H:%M:%S")
                     );
                 }
+
+                if stale.is_empty() {
+                    info!("No unfinished uploads older than {} hours", older_than);
+                } else if dry_run {
+                    info!("{} uploads would be aborted", stale.len());
+                } else {
+                    cloud_handler::abort_uploads(&store, &stale)?;
+                    info!("Aborted {} uploads", stale.len());
+                }
             }
 
-            info!("Inspection complete");
+            #[cfg(not(feature = "cloud"))]
+            {
+                let _ = (older_than, dry_run);
+                return Err(no_cloud_support(&prefix));
+            }
         }
 
+        Commands::Info { json } => info::print(json)?,
+
         Commands::Config { show, edit, path } => {
             use flux_core::config::Config;
 
@@ -596,6 +2072,39 @@ fn run() -> Result<()> {
             }
         }
 
+        Commands::Integrate {
+            uninstall,
+            status,
+            gui,
+        } => {
+            use flux_core::shell_integration;
+
+            if status {
+                if shell_integration::is_installed() {
+                    println!("Context menu entries are installed");
+                } else {
+                    println!("Context menu entries are not installed");
+                }
+            } else if uninstall {
+                let removed = shell_integration::uninstall()?;
+                if removed.is_empty() {
+                    info!("No context menu entries to remove");
+                }
+                for location in removed {
+                    info!("Removed {}", location);
+                }
+            } else {
+                let gui = match gui {
+                    Some(gui) => gui,
+                    None => default_gui_path()?,
+                };
+                for location in shell_integration::install(&gui)? {
+                    info!("Registered {}", location);
+                }
+                info!("Context menu entries now launch {}", gui.display());
+            }
+        }
+
         Commands::Sync {
             source,
             target,
@@ -604,82 +2113,226 @@ fn run() -> Result<()> {
             threads,
             follow_symlinks,
             full,
+            paranoid,
+            exclude_vcs,
+            encrypt_manifest,
+            check,
+            json,
         } => {
-            info!("Synchronizing {:?} to {:?}", source, target);
-
-            if !source.is_dir() {
-                error!("Source must be a directory");
-                return Err(anyhow::anyhow!("Source must be a directory"));
+            if check {
+                let filter = exclude_filter(exclude_vcs)?;
+                let (manifest_path, drift) = sync::check(&source, &target, &filter)?;
+                if json {
+                    println!("{}", serde_json::to_string_pretty(&drift)?);
+                } else {
+                    print_status(&manifest_path, &drift.diff);
+                    if drift.diff.has_changes() {
+                        println!(
+                            "Behind by {}s, the manifest is {}s old",
+                            drift.drift_secs, drift.manifest_age_secs
+                        );
+                    }
+                }
+                if drift.diff.has_changes() {
+                    return Err(anyhow::anyhow!(
+                        "{} changes not backed up, the oldest {}s ago",
+                        drift.diff.change_count(),
+                        drift.drift_secs
+                    ));
+                }
+                return Ok(());
             }
 
-            // Determine manifest path
-            let manifest_path = target.with_extension("fluxmanifest");
-
-            if full || !manifest_path.exists() {
-                // Full backup
-                info!("Performing full backup (no previous manifest found or --full specified)");
+            let options = flux_core::archive::PackOptions {
+                smart: false,
+                algorithm: algo,
+                level,
+                threads,
+                force_compress: false,
+                follow_symlinks,
+                max_depth: None,
+                filter: exclude_filter(exclude_vcs)?,
+                metadata: None,
+                clamp_mtime: None,
+                normalize_names: flux_core::archive::NameNormalization::Keep,
+                strip_prefix: None,
+                prefix: None,
+                use_mmap: false,
+                transform: None,
+                time_budget: None,
+                paranoid,
+                hashes: None,
+                entry_order: flux_core::archive::PackOrder::Walk,
+            };
+            #[cfg(feature = "cloud")]
+            if let Some(url) = target
+                .to_str()
+                .filter(|target| cloud_handler::is_cloud_path(target))
+            {
+                cloud_handler::check_cloud_credentials(url)?;
+                return sync::sync_to_cloud(&source, url, options, full, encrypt_manifest);
+            }
 
-                let options = flux_core::archive::PackOptions {
-                    smart: false,
-                    algorithm: algo,
-                    level,
-                    threads,
-                    force_compress: false,
-                    follow_symlinks,
-                };
+            sync::sync_directory(&source, &target, options, full, encrypt_manifest)?;
+        }
+        Commands::WatchExtract {
+            directory,
+            output,
+            interval,
+            on_conflict,
+            quarantine,
+            delete,
+            once,
+            json,
+        } => {
+            let options = watch::WatchOptions {
+                destination: output,
+                interval: std::time::Duration::from_secs(interval.max(1)),
+                on_conflict,
+                quarantine,
+                delete,
+                once,
+                json,
+            };
+            watch::watch(&directory, &options)?;
+        }
+        Commands::Undo { directory } => {
+            let report = flux_core::archive::undo_extraction(&directory)?;
+            println!("Undid extraction into {}: {}", directory.display(), report);
+            for kept in &report.kept {
+                println!("  kept {} (not empty)", kept.display());
+            }
+        }
+        Commands::Status {
+            source,
+            target,
+            exclude_vcs,
+            paranoid,
+            json,
+        } => {
+            let filter = exclude_filter(exclude_vcs)?;
+            let (manifest_path, diff) = sync::status(&source, &target, &filter, paranoid)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+            } else {
+                print_status(&manifest_path, &diff);
+            }
+        }
+        Commands::Tag {
+            manifest,
+            path,
+            tags,
+            remove,
+            note,
+            clear_note,
+        } => {
+            use flux_core::manifest::Manifest;
 
-                // Use tar.gz as default format for sync
-                let format = Some("tar.gz");
-                flux_core::archive::pack_with_strategy(&source, &target, format, options)?;
+            let password = sync::manifest_password(false, &manifest)?;
+            let mut loaded = Manifest::load_with_password(&manifest, password.as_deref())?;
+            for (key, value) in &tags {
+                loaded.set_tag(&path, key, value)?;
+            }
+            for key in &remove {
+                if !loaded.remove_tag(&path, key)? {
+                    warn!("{} has no tag {}", path.display(), key);
+                }
+            }
+            if note.is_some() || clear_note {
+                loaded.set_note(&path, note.clone())?;
+            }
+            if !tags.is_empty() || !remove.is_empty() || note.is_some() || clear_note {
+                loaded.save_with_password(&manifest, password.as_deref())?;
+            }
 
-                // Generate and save manifest
-                let manifest = flux_core::manifest::Manifest::from_directory(&source)?;
-                manifest.save(&manifest_path)?;
+            let entry = loaded.entry(&path).ok_or_else(|| {
+                flux_core::Error::NotFound(format!("{} is not in the manifest", path.display()))
+            })?;
+            for (key, value) in &entry.tags {
+                println!("{}={}", key, value);
+            }
+            if let Some(note) = &entry.note {
+                println!("Note: {}", note);
+            }
+        }
+        Commands::Key { command } => match command {
+            KeyCommand::Generate {
+                name,
+                no_passphrase,
+            } => keys::generate(&name, no_passphrase)?,
+            KeyCommand::Export { name, output } => keys::export(&name, output.as_deref())?,
+            KeyCommand::Import { file, name, force } => {
+                keys::import(&file, name.as_deref(), force)?
+            }
+            KeyCommand::List => keys::list()?,
+        },
+        Commands::Batch {
+            file,
+            parallel,
+            json,
+        } => {
+            let mut jobs = batch::load(&file)?;
+            jobs.parallel |= parallel;
+            info!("Running {} jobs from {:?}", jobs.jobs.len(), file);
 
-                info!(
-                    "Full backup complete. Manifest saved to: {:?}",
-                    manifest_path
-                );
+            let report = batch::run(&jobs);
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report)?);
             } else {
-                // Incremental backup
-                info!(
-                    "Performing incremental backup using manifest: {:?}",
-                    manifest_path
-                );
-
-                let (new_manifest_path, diff) = flux_core::archive::incremental::pack_incremental(
-                    &source,
-                    &target,
-                    &manifest_path,
-                    flux_core::archive::PackOptions {
-                        smart: false,
-                        algorithm: algo,
-                        level,
-                        threads,
-                        force_compress: false,
-                        follow_symlinks,
-                    },
-                )?;
+                report.print();
+            }
 
-                if diff.has_changes() {
-                    info!("Incremental backup complete");
-                    info!(
-                        "Changes: {} added, {} modified, {} deleted",
-                        diff.added.len(),
-                        diff.modified.len(),
-                        diff.deleted.len()
-                    );
-                    info!("Updated manifest: {:?}", new_manifest_path);
-                } else {
-                    info!("No changes detected since last backup");
+            let failed = report.failed();
+            if failed > 0 {
+                return Err(flux_core::Error::PartialFailure {
+                    count: failed as u32,
                 }
+                .into());
             }
         }
+
+        Commands::CompleteEntries { archive, prefix } => complete::run(&archive, &prefix)?,
     }
 
     Ok(())
 }
 
+/// Print the metadata of `inspect --meta`
+fn print_metadata(metadata: Option<&flux_core::archive::ArchiveMetadata>) {
+    let Some(metadata) = metadata else {
+        println!("No metadata");
+        return;
+    };
+    let mut rows = Vec::new();
+    if let Some(creator) = &metadata.creator {
+        rows.push(("creator".to_string(), creator.clone()));
+    }
+    if let Some(hostname) = &metadata.hostname {
+        rows.push(("hostname".to_string(), hostname.clone()));
+    }
+    if let Some(created) = metadata.created {
+        let created = chrono::DateTime::<chrono::Utc>::from_timestamp(created, 0)
+            .unwrap_or_default()
+            .format("%Y-%m-%d %H:%M:%S UTC")
+            .to_string();
+        rows.push(("created".to_string(), created));
+    }
+    if let Some(version) = &metadata.flux_version {
+        rows.push(("flux version".to_string(), version.clone()));
+    }
+    rows.extend(metadata.values.clone());
+
+    let width = rows.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    for (key, value) in rows {
+        println!(
+            "{:<width$}  {}",
+            format!("{}:", key),
+            value,
+            width = width + 1
+        );
+    }
+}
+
 /// Print entries as a tree structure
 fn print_tree(entries: &[flux_core::archive::ArchiveEntry]) {
     // Simple tree printing
@@ -704,36 +2357,169 @@ fn print_tree(entries: &[flux_core::archive::ArchiveEntry]) {
     }
 }
 
-/// Map errors to exit codes according to requirements:
+/// Print pending backup changes in the style of `git status`
+fn print_status(manifest_path: &Path, diff: &flux_core::manifest::ManifestDiff) {
+    println!("Compared with {}", manifest_path.display());
+    if !diff.has_changes() {
+        println!("Nothing to back up, the target is up to date");
+        return;
+    }
+
+    println!();
+    for (label, paths) in [
+        ("added:   ", &diff.added),
+        ("modified:", &diff.modified),
+        ("deleted: ", &diff.deleted),
+    ] {
+        for path in paths {
+            println!("    {} {}", label, path.display());
+        }
+    }
+    println!();
+    println!(
+        "{} added, {} modified, {} deleted",
+        diff.added.len(),
+        diff.modified.len(),
+        diff.deleted.len()
+    );
+}
+
+/// Print the groups of duplicate files in an archive, those saving the
+/// most first
+fn print_dedupe_report(report: &flux_core::archive::DedupeReport) {
+    for group in &report.groups {
+        println!(
+            "{} copies of {} (saves {})",
+            group.paths.len(),
+            table::format_size(group.size),
+            table::format_size(group.savings())
+        );
+        for path in &group.paths {
+            println!("    {}", path.display());
+        }
+    }
+    if !report.groups.is_empty() {
+        println!();
+    }
+    println!(
+        "{} files, {} duplicates, {} of {} could be saved",
+        report.files,
+        report.duplicates(),
+        table::format_size(report.savings()),
+        table::format_size(report.size)
+    );
+}
+
+/// Print a cloud listing as a table
+#[cfg(feature = "cloud")]
+fn print_listing(listed: &[cloud_handler::ListedObject]) {
+    println!("{:<50} {:>15} {:>20}", "Name", "Size", "Modified");
+    println!("{}", "-".repeat(87));
+
+    for object in listed {
+        let size_str = if object.is_dir {
+            "-".to_string()
+        } else {
+            object.size.to_string()
+        };
+
+        let mtime_str = object
+            .modified
+            .map(|datetime| datetime.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "-".to_string());
+
+        println!("{:<50} {:>15} {:>20}", object.name, size_str, mtime_str);
+    }
+}
+
+/// Print the results of `flux cloud doctor`, with the advice for each
+/// check that did not pass
+#[cfg(feature = "cloud")]
+fn print_checks(checks: &[flux_cloud::doctor::Check]) {
+    for check in checks {
+        println!("[{:>4}] {:<12} {}", check.status, check.name, check.detail);
+        if let Some(hint) = &check.hint {
+            println!("{:>20}{}", "", hint);
+        }
+    }
+}
+
+/// Error for a cloud command given something other than a cloud URL
+#[cfg(feature = "cloud")]
+fn not_cloud_location(location: &str) -> anyhow::Error {
+    flux_core::Error::InvalidArgument(format!(
+        "Not a cloud location: {} (use s3://, gs:// or az://)",
+        location
+    ))
+    .into()
+}
+
+/// Error for cloud commands in a build without the cloud feature
+#[cfg(not(feature = "cloud"))]
+fn no_cloud_support(location: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "Cannot use {}: flux was built without cloud support (rebuild with --features cloud)",
+        location
+    )
+}
+
+/// The GUI binary installed alongside the CLI
+fn default_gui_path() -> Result<PathBuf> {
+    let exe = std::env::current_exe()?;
+    let dir = exe
+        .parent()
+        .ok_or_else(|| anyhow::anyhow!("Cannot locate the directory of {}", exe.display()))?;
+    Ok(dir.join(format!("flux-gui{}", std::env::consts::EXE_SUFFIX)))
+}
+
+/// What is known about a failed command, for the exit code and `--json`
+///
+/// Errors from flux-core carry their own code; a bare I/O error is coded
+/// by its kind, and anything else is `other`.
+fn error_report(err: &anyhow::Error) -> ErrorReport {
+    if let Some(flux_err) = err.downcast_ref::<flux_core::Error>() {
+        return ErrorReport {
+            message: err.to_string(),
+            ..flux_err.report()
+        };
+    }
+    if let Some(io_err) = err.downcast_ref::<io::Error>() {
+        let error = flux_core::Error::Io(io::Error::new(io_err.kind(), io_err.to_string()));
+        return ErrorReport {
+            message: err.to_string(),
+            ..error.report()
+        };
+    }
+    ErrorReport {
+        code: ErrorCode::Other,
+        message: err.to_string(),
+        path: None,
+        entry: None,
+        retryable: false,
+    }
+}
+
+/// Exit code for an error:
 /// - 0: Success
 /// - 1: General error
 /// - 2: IO error
 /// - 3: Invalid arguments
 /// - 4: Partial failure
-fn map_error_to_exit_code(err: &anyhow::Error) -> i32 {
-    // Check if it's a flux_core error
-    if let Some(flux_err) = err.downcast_ref::<flux_core::Error>() {
-        match flux_err {
-            flux_core::Error::Io(_) => 2,
-            flux_core::Error::InvalidPath(_) => 3,
-            flux_core::Error::UnsupportedFormat(_) => 3,
-            flux_core::Error::Archive(_) => 4,
-            flux_core::Error::Compression(_) => 4,
-            flux_core::Error::Config(_) | flux_core::Error::ConfigError(_) => 1,
-            flux_core::Error::Other(_) => 1,
-            flux_core::Error::Zip(_) => 4,
-            flux_core::Error::ArchiveError(_) => 4,
-            flux_core::Error::FileExists(_) => 3,
-            flux_core::Error::UnsupportedOperation(_) => 3,
-            flux_core::Error::PartialFailure { .. } => 4,
-            flux_core::Error::NotFound(_) => 2,
-            flux_core::Error::SecurityError(_) => 3,
-        }
-    } else if err.is::<std::io::Error>() {
-        2
-    } else if err.to_string().contains("argument") || err.to_string().contains("invalid") {
-        3
-    } else {
-        1
+fn exit_code(code: ErrorCode) -> i32 {
+    match code {
+        ErrorCode::Io
+        | ErrorCode::NotFound
+        | ErrorCode::PermissionDenied
+        | ErrorCode::InsufficientSpace => 2,
+        ErrorCode::InvalidPath
+        | ErrorCode::InvalidArgument
+        | ErrorCode::UnsupportedFormat
+        | ErrorCode::UnsupportedOperation
+        | ErrorCode::FileExists
+        | ErrorCode::Security
+        | ErrorCode::PasswordRequired
+        | ErrorCode::InvalidPassword => 3,
+        ErrorCode::Archive | ErrorCode::Compression | ErrorCode::PartialFailure => 4,
+        ErrorCode::Config | ErrorCode::Other => 1,
     }
 }
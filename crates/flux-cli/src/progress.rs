@@ -0,0 +1,390 @@
+//! Progress display for `--progress`
+//!
+//! Two lines on stderr: a bar counting the bytes of the current phase, and
+//! below it the phase and the file being worked on. Packing feeds it through
+//! [`ProgressSink`]; cloud transfers feed it through the store's transfer
+//! metrics, see [`ProgressDisplay::transfer_metrics`]. Interactive
+//! extraction adds a third line with a bar for the file being extracted,
+//! see [`ProgressDisplay::start_file`], and hides the display while it asks
+//! about conflicts, see [`ProgressDisplay::suspend`].
+//!
+//! With `--json` the same progress is also printed on stderr as JSON lines,
+//! see [`ProgressDisplay::with_events`].
+//!
+//! With the `telemetry` feature each phase is also timed, together with the
+//! bytes it read and produced, and reported through
+//! [`flux_core::telemetry`] when the next phase starts or the display
+//! finishes.
+
+#[cfg(feature = "cloud")]
+use flux_cloud::metrics::{Operation, TransferMetrics};
+use flux_core::progress::ProgressSink;
+#[cfg(feature = "telemetry")]
+use flux_core::telemetry::PhaseTimer;
+use indicatif::{HumanBytes, MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde_json::json;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Shortest time between two `progress` events
+const EVENT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// What the command is doing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Measuring the inputs
+    Scan,
+    Compress,
+    /// Copying the finished archive, such as to stdout or into volumes
+    Write,
+    #[cfg(feature = "cloud")]
+    Upload,
+    #[cfg(feature = "cloud")]
+    Download,
+    Extract,
+}
+
+impl Phase {
+    fn label(&self) -> &'static str {
+        match self {
+            Phase::Scan => "scan",
+            Phase::Compress => "compress",
+            Phase::Write => "write",
+            #[cfg(feature = "cloud")]
+            Phase::Upload => "upload",
+            #[cfg(feature = "cloud")]
+            Phase::Download => "download",
+            Phase::Extract => "extract",
+        }
+    }
+
+    #[cfg(feature = "telemetry")]
+    fn telemetry(&self) -> flux_core::telemetry::Phase {
+        use flux_core::telemetry::Phase as Telemetry;
+        match self {
+            Phase::Scan => Telemetry::Scan,
+            Phase::Compress => Telemetry::Compress,
+            Phase::Write => Telemetry::Write,
+            #[cfg(feature = "cloud")]
+            Phase::Upload => Telemetry::Upload,
+            #[cfg(feature = "cloud")]
+            Phase::Download => Telemetry::Download,
+            Phase::Extract => Telemetry::Extract,
+        }
+    }
+}
+
+/// The timer of the current phase, shared with transfer callbacks
+#[cfg(feature = "telemetry")]
+type SharedTimer = Arc<Mutex<Option<PhaseTimer>>>;
+
+/// Count `bytes` that went by in the current phase: what an upload or a
+/// write sends counts as output, everything else as input
+#[cfg(feature = "telemetry")]
+fn count(timer: &SharedTimer, bytes: u64) {
+    use flux_core::telemetry::Phase as Telemetry;
+    if let Some(timer) = timer.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        match timer.phase() {
+            Telemetry::Upload | Telemetry::Write => timer.add_out(bytes),
+            _ => timer.add_in(bytes),
+        }
+    }
+}
+
+/// Progress events printed on stderr, one JSON object per line
+///
+/// A `phase` event starts each phase, `progress` events follow while its
+/// bytes go by and a last one is printed when the display finishes. Speed
+/// and ETA come from the bar, which keeps counting when hidden.
+#[derive(Clone)]
+struct Events {
+    bar: ProgressBar,
+    state: Arc<Mutex<EventState>>,
+}
+
+struct EventState {
+    phase: Phase,
+    total: Option<u64>,
+    last: Option<Instant>,
+    finished: bool,
+}
+
+impl Events {
+    fn new(bar: ProgressBar) -> Self {
+        Self {
+            bar,
+            state: Arc::new(Mutex::new(EventState {
+                phase: Phase::Scan,
+                total: None,
+                last: None,
+                finished: false,
+            })),
+        }
+    }
+
+    fn phase(&self, phase: Phase, total: Option<u64>) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.last.is_some() {
+            self.progress_locked(&mut state);
+        }
+        state.phase = phase;
+        state.total = total;
+        state.last = Some(Instant::now());
+        eprintln!(
+            "{}",
+            json!({ "event": "phase", "phase": phase.label(), "total": total })
+        );
+    }
+
+    /// Print a `progress` event, unless one was printed within
+    /// [`EVENT_INTERVAL`] and `force` is not set
+    fn progress(&self, force: bool) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.finished
+            || !force
+                && state
+                    .last
+                    .is_some_and(|last| last.elapsed() < EVENT_INTERVAL)
+        {
+            return;
+        }
+        self.progress_locked(&mut state);
+    }
+
+    fn progress_locked(&self, state: &mut EventState) {
+        let bytes = self.bar.position();
+        let eta = state
+            .total
+            .filter(|&total| bytes < total)
+            .map(|_| self.bar.eta().as_secs());
+        state.last = Some(Instant::now());
+        eprintln!(
+            "{}",
+            json!({
+                "event": "progress",
+                "phase": state.phase.label(),
+                "bytes": bytes,
+                "total": state.total,
+                "bytes_per_sec": self.bar.per_sec().round() as u64,
+                "eta_secs": eta,
+            })
+        );
+    }
+
+    fn finish(&self) {
+        self.progress(true);
+        self.state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .finished = true;
+    }
+}
+
+/// The bytes bar and status line; draws nothing unless enabled
+pub struct ProgressDisplay {
+    // Keeps the bars drawn together
+    multi: MultiProgress,
+    bar: ProgressBar,
+    status: ProgressBar,
+    /// Bar of the file from [`ProgressDisplay::start_file`]
+    file: Mutex<Option<ProgressBar>>,
+    events: Option<Events>,
+    #[cfg(feature = "telemetry")]
+    timer: SharedTimer,
+}
+
+impl ProgressDisplay {
+    /// Create the display, hidden unless `enabled`
+    ///
+    /// Nothing is drawn either when stderr is not a terminal.
+    pub fn new(enabled: bool) -> Self {
+        let target = if enabled {
+            ProgressDrawTarget::stderr()
+        } else {
+            ProgressDrawTarget::hidden()
+        };
+        let multi = MultiProgress::with_draw_target(target);
+        let bar = multi.add(ProgressBar::new_spinner());
+        let status = multi.add(ProgressBar::new_spinner());
+        status
+            .set_style(ProgressStyle::with_template("  {prefix:>8.bold.dim} {wide_msg}").unwrap());
+        if enabled {
+            bar.enable_steady_tick(Duration::from_millis(100));
+        }
+        Self {
+            multi,
+            bar,
+            status,
+            file: Mutex::new(None),
+            events: None,
+            #[cfg(feature = "telemetry")]
+            timer: SharedTimer::default(),
+        }
+    }
+
+    /// Also print progress on stderr as JSON lines when `enabled`
+    pub fn with_events(mut self, enabled: bool) -> Self {
+        self.events = enabled.then(|| Events::new(self.bar.clone()));
+        self
+    }
+
+    /// Start `phase`, with a bar when its size is known and a spinner
+    /// otherwise
+    pub fn phase(&self, phase: Phase, total: Option<u64>) {
+        // Before the bar is reset, so the phase before ends with its count
+        if let Some(events) = &self.events {
+            events.phase(phase, total);
+        }
+        let style = match total {
+            Some(_) => ProgressStyle::with_template(
+                "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta}) {msg}",
+            )
+            .unwrap()
+            .progress_chars("#>-"),
+            None => ProgressStyle::with_template("{spinner:.green} [{elapsed_precise}] {msg}")
+                .unwrap(),
+        };
+        self.bar.set_style(style);
+        self.bar.set_length(total.unwrap_or(0));
+        self.bar.set_position(0);
+        self.bar.set_message("");
+        self.status.set_prefix(phase.label());
+        self.status.set_message("");
+
+        // Replacing the timer reports the phase before
+        #[cfg(feature = "telemetry")]
+        {
+            *self.timer.lock().unwrap_or_else(|e| e.into_inner()) =
+                Some(PhaseTimer::start(phase.telemetry()));
+        }
+    }
+
+    /// Show `message` on the status line
+    pub fn status(&self, message: impl Into<String>) {
+        self.status.set_message(message.into());
+    }
+
+    /// Show a bar for the `size` bytes of the file starting now below the
+    /// status line, replacing the one before; it counts along with
+    /// [`ProgressDisplay::inc`] until [`ProgressDisplay::end_file`]
+    pub fn start_file(&self, size: u64) {
+        let bar = self.multi.add(ProgressBar::new(size));
+        bar.set_style(
+            ProgressStyle::with_template(
+                "  {bar:30.yellow/blue} {bytes}/{total_bytes} ({bytes_per_sec}, {eta})",
+            )
+            .unwrap()
+            .progress_chars("=>-"),
+        );
+        if let Some(previous) = self
+            .file
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .replace(bar)
+        {
+            previous.finish_and_clear();
+        }
+    }
+
+    /// Remove the bar of the current file
+    pub fn end_file(&self) {
+        if let Some(bar) = self.file.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            bar.finish_and_clear();
+        }
+    }
+
+    /// Run `f` with the display cleared from the terminal, drawing it again
+    /// afterwards, so a prompt in `f` is not drawn over
+    pub fn suspend<T>(&self, f: impl FnOnce() -> T) -> T {
+        self.multi.suspend(f)
+    }
+
+    /// Count `bytes` more towards the current phase
+    pub fn inc(&self, bytes: u64) {
+        self.bar.inc(bytes);
+        if let Some(file) = self.file.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            file.inc(bytes);
+        }
+        if let Some(events) = &self.events {
+            events.progress(false);
+        }
+        #[cfg(feature = "telemetry")]
+        count(&self.timer, bytes);
+    }
+
+    /// Remove the display from the terminal
+    pub fn finish(&self) {
+        if let Some(events) = &self.events {
+            events.finish();
+        }
+        self.end_file();
+        self.bar.finish_and_clear();
+        self.status.finish_and_clear();
+        #[cfg(feature = "telemetry")]
+        if let Some(timer) = self.timer.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            timer.finish();
+        }
+    }
+
+    /// Metrics for a cloud store that count transferred bytes on the bar
+    #[cfg(feature = "cloud")]
+    pub fn transfer_metrics(&self) -> std::sync::Arc<TransferMetrics> {
+        let bar = self.bar.clone();
+        let events = self.events.clone();
+        #[cfg(feature = "telemetry")]
+        let timer = self.timer.clone();
+        std::sync::Arc::new(TransferMetrics::with_callback(std::sync::Arc::new(
+            move |event| {
+                let transfer = matches!(
+                    event.operation,
+                    Operation::Get | Operation::Put | Operation::PutPart
+                );
+                if transfer && event.success {
+                    bar.inc(event.bytes);
+                    if let Some(events) = &events {
+                        events.progress(false);
+                    }
+                    #[cfg(feature = "telemetry")]
+                    count(&timer, event.bytes);
+                }
+            },
+        )))
+    }
+}
+
+impl Drop for ProgressDisplay {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+impl ProgressSink for ProgressDisplay {
+    fn start(&self, total: u64) {
+        // What the scan measured
+        #[cfg(feature = "telemetry")]
+        count(&self.timer, total);
+        self.phase(Phase::Compress, Some(total));
+    }
+
+    fn file(&self, path: &Path) {
+        self.status(path.display().to_string());
+    }
+
+    fn advance(&self, bytes: u64) {
+        self.inc(bytes);
+    }
+
+    fn written(&self, bytes: u64) {
+        self.bar.set_message(format!("-> {}", HumanBytes(bytes)));
+        #[cfg(feature = "telemetry")]
+        if let Some(timer) = self
+            .timer
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+        {
+            timer.set_out(bytes);
+        }
+    }
+}
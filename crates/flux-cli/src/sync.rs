@@ -0,0 +1,352 @@
+//! Incremental directory backups for `flux sync` and `flux status`
+
+use anyhow::Result;
+use flux_core::archive::{ManifestCheck, PackFilter, PackHashes, PackOptions, TamperPolicy};
+use flux_core::manifest::{Drift, Manifest, ManifestDiff};
+use flux_core::ops::PackJob;
+use flux_core::progress::NoProgress;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use tracing::{error, info};
+
+/// Environment variable holding the password of encrypted manifests
+pub const PASSWORD_ENV: &str = "FLUX_MANIFEST_PASSWORD";
+
+/// Manifest `flux sync` keeps next to `target`
+pub fn manifest_path(target: &Path) -> PathBuf {
+    target.with_extension("fluxmanifest")
+}
+
+/// Manifest describing the last backup into `target`: the one kept by
+/// `flux sync`, or else the one `flux pack` writes when packing a directory
+pub fn find_manifest(target: &Path) -> Option<PathBuf> {
+    [
+        manifest_path(target),
+        target.with_extension("manifest.json"),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+}
+
+/// Password for a manifest saved to, or already at, `path`: `None` unless
+/// `encrypt` asks for one or the manifest there is already encrypted, so an
+/// encrypted backup never goes back to a plain manifest
+///
+/// Taken from `FLUX_MANIFEST_PASSWORD`, or the key named by `manifest_key`
+/// in the configuration, or else asked for on a terminal.
+pub fn manifest_password(encrypt: bool, path: &Path) -> Result<Option<String>> {
+    let encrypted = path.is_file() && Manifest::is_encrypted(path)?;
+    if !encrypt && !encrypted {
+        return Ok(None);
+    }
+
+    if let Some(password) = std::env::var(PASSWORD_ENV).ok().filter(|p| !p.is_empty()) {
+        return Ok(Some(password));
+    }
+    if let Some(password) = crate::keys::configured_manifest_key()? {
+        return Ok(Some(password));
+    }
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return Err(flux_core::Error::PasswordRequired(format!(
+            "set {} to the password for {}",
+            PASSWORD_ENV,
+            path.display()
+        ))
+        .into());
+    }
+
+    let prompt = dialoguer::Password::new().with_prompt("Manifest password");
+    // A typo in a new password would lock the manifest for good
+    let prompt = if encrypted {
+        prompt
+    } else {
+        prompt.with_confirmation("Repeat password", "Passwords do not match")
+    };
+    Ok(Some(prompt.interact()?))
+}
+
+/// Check of extracted files against the manifest at `path`, asking for its
+/// password as [`manifest_password`] does if it is encrypted
+pub fn manifest_check(path: &Path, policy: TamperPolicy) -> Result<ManifestCheck> {
+    let password = manifest_password(false, path)?;
+    let manifest = Manifest::load_with_password(path, password.as_deref())?;
+    Ok(ManifestCheck::new(manifest, policy))
+}
+
+/// `options` collecting the hashes of the files packed into `hashes`
+fn hashing(options: PackOptions, hashes: &PackHashes) -> PackOptions {
+    PackOptions {
+        hashes: Some(hashes.clone()),
+        ..options
+    }
+}
+
+/// Manifest of `source` just packed with `filter`, its hashes taken from
+/// `hashes` as the pack collected them, with the tags and notes of the
+/// manifest at `previous` if there is one
+fn packed_manifest(
+    source: &Path,
+    filter: &PackFilter,
+    hashes: &PackHashes,
+    previous: &Path,
+    password: Option<&str>,
+) -> Result<Manifest> {
+    let mut manifest = Manifest::from_packed(source, filter, hashes)?;
+    if previous.is_file() {
+        manifest.keep_annotations(&Manifest::load_with_password(previous, password)?);
+    }
+    Ok(manifest)
+}
+
+/// The manifest of the last backup of `source` into `target`, and where
+/// it was found
+fn last_manifest(source: &Path, target: &Path) -> Result<(PathBuf, Manifest)> {
+    if !source.is_dir() {
+        return Err(anyhow::anyhow!("Source must be a directory"));
+    }
+    let manifest_path = find_manifest(target).ok_or_else(|| {
+        flux_core::Error::NotFound(format!(
+            "No manifest for {} (back it up with flux sync first)",
+            target.display()
+        ))
+    })?;
+
+    let password = manifest_password(false, &manifest_path)?;
+    let manifest = Manifest::load_with_password(&manifest_path, password.as_deref())?;
+    Ok((manifest_path, manifest))
+}
+
+/// What syncing `source` into `target` now would pick up, along with the
+/// manifest it was compared against; nothing is written
+///
+/// Directories whose only change is a newer modification time, which they
+/// get whenever a file inside them changes, are not listed as modified.
+/// Unless `paranoid`, files whose metadata is unchanged are not read again.
+pub fn status(
+    source: &Path,
+    target: &Path,
+    filter: &PackFilter,
+    paranoid: bool,
+) -> Result<(PathBuf, ManifestDiff)> {
+    let (manifest_path, previous) = last_manifest(source, target)?;
+    let cached = (!paranoid).then_some(&previous);
+    let current = Manifest::scan_with_algorithm(
+        source,
+        filter,
+        cached,
+        previous.hash_algorithm,
+        &NoProgress,
+    )?;
+    let mut diff = previous.diff(&current);
+    diff.modified
+        .retain(|path| !current.files.get(path).is_some_and(|entry| entry.is_dir));
+    diff.added.sort();
+    diff.modified.sort();
+    diff.deleted.sort();
+
+    Ok((manifest_path, diff))
+}
+
+/// How far `source` has drifted from the last backup into `target`, by
+/// size and modification time alone, along with the manifest it was
+/// compared against; no file is read and nothing is written
+pub fn check(source: &Path, target: &Path, filter: &PackFilter) -> Result<(PathBuf, Drift)> {
+    let (manifest_path, previous) = last_manifest(source, target)?;
+    Ok((manifest_path, previous.quick_check(source, filter)?))
+}
+
+/// Back `source` up into `target`
+///
+/// The first run, or any run with `full`, packs everything and saves a
+/// manifest next to the archive; later runs only pack what changed since.
+/// With `encrypt_manifest`, or once the manifest is encrypted, manifests are
+/// saved encrypted.
+pub fn sync_directory(
+    source: &Path,
+    target: &Path,
+    options: PackOptions,
+    full: bool,
+    encrypt_manifest: bool,
+) -> Result<()> {
+    info!("Synchronizing {:?} to {:?}", source, target);
+
+    if !source.is_dir() {
+        error!("Source must be a directory");
+        return Err(anyhow::anyhow!("Source must be a directory"));
+    }
+
+    let manifest_path = manifest_path(target);
+    let password = manifest_password(encrypt_manifest, &manifest_path)?;
+
+    if full || !manifest_path.exists() {
+        // Full backup
+        info!("Performing full backup (no previous manifest found or --full specified)");
+
+        // Use tar.gz as default format for sync
+        let filter = options.filter.clone();
+        let hashes = PackHashes::new();
+        PackJob {
+            format: Some("tar.gz".to_string()),
+            ..PackJob::new(
+                vec![source.to_path_buf()],
+                target,
+                hashing(options, &hashes),
+            )
+        }
+        .run(&NoProgress)?;
+
+        // Generate and save manifest from the hashes taken while packing
+        let manifest = packed_manifest(
+            source,
+            &filter,
+            &hashes,
+            &manifest_path,
+            password.as_deref(),
+        )?;
+        manifest.save_with_password(&manifest_path, password.as_deref())?;
+
+        info!(
+            "Full backup complete. Manifest saved to: {:?}",
+            manifest_path
+        );
+    } else {
+        // Incremental backup
+        info!(
+            "Performing incremental backup using manifest: {:?}",
+            manifest_path
+        );
+
+        let (new_manifest_path, diff) =
+            flux_core::archive::incremental::pack_incremental_with_password(
+                source,
+                target,
+                &manifest_path,
+                options,
+                password.as_deref(),
+            )?;
+
+        if diff.has_changes() {
+            // Saved as `<target>.manifest.json`; move it to where the next
+            // sync and `flux status` look first, replacing the old one
+            std::fs::rename(&new_manifest_path, &manifest_path)?;
+            info!("Incremental backup complete");
+            info!(
+                "Changes: {} added, {} modified, {} deleted",
+                diff.added.len(),
+                diff.modified.len(),
+                diff.deleted.len()
+            );
+            info!("Updated manifest: {:?}", manifest_path);
+        } else {
+            info!("No changes detected since last backup");
+        }
+    }
+
+    Ok(())
+}
+
+/// Back `source` up into the chain of backups at the cloud URL `target`
+///
+/// The newest manifest in the chain is downloaded and diffed against
+/// `source` the way a local sync uses the manifest next to its archive. The
+/// archive, and the manifest describing `source` after it, are then uploaded
+/// as the next link, the manifest last; see [`flux_cloud::chain`].
+#[cfg(feature = "cloud")]
+pub fn sync_to_cloud(
+    source: &Path,
+    target: &str,
+    options: PackOptions,
+    full: bool,
+    encrypt_manifest: bool,
+) -> Result<()> {
+    use anyhow::Context;
+    use flux_cloud::chain::{fetch, push_link};
+    use flux_cloud::BackupChain;
+    use flux_core::space::TempResource;
+
+    info!("Synchronizing {:?} to {}", source, target);
+
+    if !source.is_dir() {
+        error!("Source must be a directory");
+        return Err(anyhow::anyhow!("Source must be a directory"));
+    }
+
+    let (store, cloud_path) = crate::cloud_handler::connect(target)?;
+    let chain = BackupChain::new(&cloud_path.path)?;
+    let latest = chain
+        .latest(&store)
+        .with_context(|| format!("Failed to look for earlier backups in {}", target))?;
+    let next = chain.next_link(&store)?;
+
+    let scratch = TempResource::new()?;
+    let archive = scratch.path().join("archive.tar.gz");
+    let previous = scratch.path().join("previous.fluxmanifest");
+    // Fetched even for a full backup, so an encrypted chain stays encrypted
+    if let Some(link) = &latest {
+        fetch(&store, &link.manifest, &previous)
+            .with_context(|| format!("Failed to download {}", link.manifest))?;
+    }
+    let password = manifest_password(encrypt_manifest, &previous)?;
+
+    let manifest = match latest.filter(|_| !full) {
+        None => {
+            info!("Performing full backup (no previous manifest found or --full specified)");
+
+            let filter = options.filter.clone();
+            let hashes = PackHashes::new();
+            let options = hashing(options, &hashes);
+            PackJob {
+                format: Some("tar.gz".to_string()),
+                ..PackJob::new(vec![source.to_path_buf()], &archive, options)
+            }
+            .run(&NoProgress)?;
+            let manifest = scratch.path().join("next.fluxmanifest");
+            packed_manifest(source, &filter, &hashes, &previous, password.as_deref())?
+                .save_with_password(&manifest, password.as_deref())?;
+            manifest
+        }
+        Some(link) => {
+            info!(
+                "Performing incremental backup using manifest: {}",
+                link.manifest
+            );
+
+            let (manifest, diff) = flux_core::archive::incremental::pack_incremental_with_password(
+                source,
+                &archive,
+                &previous,
+                options,
+                password.as_deref(),
+            )?;
+            if !diff.has_changes() {
+                info!("No changes detected since last backup");
+                return Ok(());
+            }
+            info!(
+                "Changes: {} added, {} modified, {} deleted",
+                diff.added.len(),
+                diff.modified.len(),
+                diff.deleted.len()
+            );
+            manifest
+        }
+    };
+
+    scratch.check()?;
+
+    let deleted = archive.with_extension("deleted.txt");
+    push_link(
+        &store,
+        &next,
+        &archive,
+        deleted.is_file().then_some(deleted.as_path()),
+        &manifest,
+    )
+    .with_context(|| format!("Failed to upload backup {} to {}", next.sequence, target))?;
+
+    info!(
+        "Backup {} uploaded: {} with manifest {}",
+        next.sequence, next.archive, next.manifest
+    );
+    Ok(())
+}
@@ -0,0 +1,253 @@
+//! Table output for `flux inspect`
+
+use clap::ValueEnum;
+use flux_core::archive::ArchiveEntry;
+
+/// Order to list entries in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    /// Largest last
+    Size,
+    /// By path
+    Name,
+    /// Oldest first; entries without a time come before all others
+    Mtime,
+}
+
+/// A column of the table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Field {
+    Path,
+    Size,
+    Compressed,
+    /// Compressed size as a share of the original size
+    Ratio,
+    Mode,
+    Mtime,
+    /// Extra fields of zip entries, such as Info-ZIP timestamps (`UT`)
+    /// and ownership (`ux`)
+    Extra,
+}
+
+/// Columns shown when `--fields` is not given
+pub const DEFAULT_FIELDS: &[Field] = &[
+    Field::Path,
+    Field::Size,
+    Field::Compressed,
+    Field::Mode,
+    Field::Mtime,
+];
+
+impl Field {
+    fn header(&self) -> &'static str {
+        match self {
+            Field::Path => "Path",
+            Field::Size => "Size",
+            Field::Compressed => "Compressed",
+            Field::Ratio => "Ratio",
+            Field::Mode => "Mode",
+            Field::Mtime => "Modified",
+            Field::Extra => "Extra",
+        }
+    }
+
+    /// Everything but the path and extra fields lines up on the right
+    fn right_aligned(&self) -> bool {
+        !matches!(self, Field::Path | Field::Extra)
+    }
+
+    fn cell(&self, entry: &ArchiveEntry, exact_bytes: bool) -> String {
+        let bytes = |size: u64| {
+            if exact_bytes {
+                size.to_string()
+            } else {
+                format_size(size)
+            }
+        };
+        match self {
+            Field::Path => entry.path.display().to_string(),
+            Field::Size => bytes(entry.size),
+            Field::Compressed => entry.compressed_size.map(bytes).unwrap_or_else(dash),
+            Field::Ratio => match entry.compressed_size {
+                Some(compressed) if entry.size > 0 => {
+                    format!("{:.1}%", compressed as f64 * 100.0 / entry.size as f64)
+                }
+                _ => dash(),
+            },
+            Field::Mode => entry
+                .mode
+                .map(|mode| format!("{:o}", mode))
+                .unwrap_or_else(dash),
+            Field::Mtime => entry
+                .mtime
+                .map(|mtime| {
+                    chrono::DateTime::<chrono::Utc>::from_timestamp(mtime, 0)
+                        .unwrap_or_default()
+                        .format("%Y-%m-%d %H:%M:%S")
+                        .to_string()
+                })
+                .unwrap_or_else(dash),
+            Field::Extra if entry.extra_fields.is_empty() => dash(),
+            Field::Extra => entry.extra_fields.join(", "),
+        }
+    }
+}
+
+fn dash() -> String {
+    "-".to_string()
+}
+
+/// Sort `entries` by `key`, keeping archive order between equal entries;
+/// `reverse` flips the result, so it also works without a key
+pub fn sort_entries(entries: &mut [ArchiveEntry], key: Option<SortKey>, reverse: bool) {
+    match key {
+        Some(SortKey::Size) => entries.sort_by_key(|entry| entry.size),
+        Some(SortKey::Name) => entries.sort_by(|a, b| a.path.cmp(&b.path)),
+        Some(SortKey::Mtime) => entries.sort_by_key(|entry| entry.mtime),
+        None => {}
+    }
+    if reverse {
+        entries.reverse();
+    }
+}
+
+/// Lay out `entries` as a table with a header, each column as wide as its
+/// widest cell
+pub fn render(entries: &[ArchiveEntry], fields: &[Field], exact_bytes: bool) -> String {
+    let rows: Vec<Vec<String>> = entries
+        .iter()
+        .map(|entry| {
+            fields
+                .iter()
+                .map(|field| field.cell(entry, exact_bytes))
+                .collect()
+        })
+        .collect();
+
+    let widths: Vec<usize> = fields
+        .iter()
+        .enumerate()
+        .map(|(column, field)| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .chain([field.header().len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let header: Vec<String> = fields
+        .iter()
+        .map(|field| field.header().to_string())
+        .collect();
+    let rule_width = widths.iter().sum::<usize>() + 2 * widths.len().saturating_sub(1);
+
+    let mut out = String::new();
+    push_row(&mut out, &header, fields, &widths);
+    out.push_str(&"-".repeat(rule_width));
+    out.push('\n');
+    for row in &rows {
+        push_row(&mut out, row, fields, &widths);
+    }
+    out
+}
+
+fn push_row(out: &mut String, cells: &[String], fields: &[Field], widths: &[usize]) {
+    let mut line = String::new();
+    for (column, (cell, field)) in cells.iter().zip(fields).enumerate() {
+        if column > 0 {
+            line.push_str("  ");
+        }
+        let padding = " ".repeat(widths[column].saturating_sub(cell.chars().count()));
+        if field.right_aligned() {
+            line.push_str(&padding);
+            line.push_str(cell);
+        } else {
+            line.push_str(cell);
+            line.push_str(&padding);
+        }
+    }
+    out.push_str(line.trim_end());
+    out.push('\n');
+}
+
+/// Format file size in human-readable form
+pub fn format_size(size: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = size as f64;
+    let mut unit_idx = 0;
+
+    while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit_idx += 1;
+    }
+
+    if unit_idx == 0 {
+        format!("{} {}", size as u64, UNITS[unit_idx])
+    } else {
+        format!("{:.2} {}", size, UNITS[unit_idx])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entry(path: &str, size: u64, compressed: Option<u64>, mtime: Option<i64>) -> ArchiveEntry {
+        ArchiveEntry {
+            path: PathBuf::from(path),
+            size,
+            compressed_size: compressed,
+            mode: None,
+            mtime,
+            is_dir: false,
+            is_symlink: false,
+            link_target: None,
+            extra_fields: Vec::new(),
+        }
+    }
+
+    fn paths(entries: &[ArchiveEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .map(|entry| entry.path.display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_sort_entries() {
+        let mut entries = vec![
+            entry("b", 300, None, Some(20)),
+            entry("c", 100, None, None),
+            entry("a", 200, None, Some(10)),
+        ];
+
+        sort_entries(&mut entries, Some(SortKey::Size), false);
+        assert_eq!(paths(&entries), ["c", "a", "b"]);
+        sort_entries(&mut entries, Some(SortKey::Name), true);
+        assert_eq!(paths(&entries), ["c", "b", "a"]);
+        sort_entries(&mut entries, Some(SortKey::Mtime), false);
+        assert_eq!(paths(&entries), ["c", "a", "b"]);
+        sort_entries(&mut entries, None, true);
+        assert_eq!(paths(&entries), ["b", "a", "c"]);
+    }
+
+    #[test]
+    fn test_render_fits_columns() {
+        let entries = vec![
+            entry("short", 2048, Some(512), None),
+            entry("a/much/longer/path.txt", 10, None, None),
+        ];
+
+        let table = render(&entries, &[Field::Path, Field::Size, Field::Ratio], false);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines[0], "Path                       Size  Ratio");
+        assert_eq!(lines[1], "-".repeat(38));
+        assert_eq!(lines[2], "short                   2.00 KB  25.0%");
+        assert_eq!(lines[3], "a/much/longer/path.txt     10 B      -");
+
+        let table = render(&entries, &[Field::Size, Field::Path], true);
+        assert_eq!(table.lines().nth(2).unwrap(), "2048  short");
+    }
+}
@@ -1,5 +1,6 @@
 //! Terminal User Interface for archive browsing
 
+use crate::table::format_size;
 use anyhow::Result;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
@@ -189,11 +190,9 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
                             app.list_state.select(Some(0));
                         }
                     }
-                    KeyCode::End => {
-                        if !app.filtered_entries.is_empty() {
-                            app.selected = app.filtered_entries.len() - 1;
-                            app.list_state.select(Some(app.selected));
-                        }
+                    KeyCode::End if !app.filtered_entries.is_empty() => {
+                        app.selected = app.filtered_entries.len() - 1;
+                        app.list_state.select(Some(app.selected));
                     }
                     KeyCode::Char('/') => {
                         app.clear_search();
@@ -205,17 +204,14 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<()>
                             app.show_help = false;
                         }
                     }
-                    KeyCode::Backspace => {
-                        if !app.search_query.is_empty() {
-                            app.backspace_search();
-                        }
+                    KeyCode::Backspace if !app.search_query.is_empty() => {
+                        app.backspace_search();
                     }
-                    KeyCode::Char(c) => {
+                    KeyCode::Char(c)
                         if (!app.search_query.is_empty() || key.code == KeyCode::Char('/'))
-                            && c != '/'
-                        {
-                            app.update_search(c);
-                        }
+                            && c != '/' =>
+                    {
+                        app.update_search(c);
                     }
                     _ => {}
                 }
@@ -475,21 +471,3 @@ fn render_help(f: &mut Frame, area: Rect) {
 
     f.render_widget(help, area);
 }
-
-/// Format file size in human-readable form
-fn format_size(size: u64) -> String {
-    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-    let mut size = size as f64;
-    let mut unit_idx = 0;
-
-    while size >= 1024.0 && unit_idx < UNITS.len() - 1 {
-        size /= 1024.0;
-        unit_idx += 1;
-    }
-
-    if unit_idx == 0 {
-        format!("{} {}", size as u64, UNITS[unit_idx])
-    } else {
-        format!("{:.2} {}", size, UNITS[unit_idx])
-    }
-}
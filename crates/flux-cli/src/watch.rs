@@ -0,0 +1,281 @@
+//! Extracting archives as they arrive in a folder, for `flux watch-extract`
+//!
+//! The folder is scanned every interval. An archive is taken once its size
+//! and modification time are the same on two scans in a row, so one still
+//! being copied in is left alone until it is complete. Each archive is
+//! extracted into a folder of its own named after it, then moved into
+//! `.processed` in the watched folder, or deleted. One that fails to
+//! extract is moved into the quarantine folder instead, with the error
+//! written next to it, and whatever it had extracted is removed.
+
+use crate::check_outcome;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use flux_core::archive::detect::strip_archive_extension;
+use flux_core::archive::extractor::ExtractEntryOptions;
+use flux_core::archive::{archive_format, ExtractOptions};
+use flux_core::ops::ExtractJob;
+use flux_core::progress::NoProgress;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use tracing::{error, info, warn};
+
+/// Folder in the watched folder that extracted archives are moved to
+pub const PROCESSED_DIR: &str = ".processed";
+
+/// Folder in the watched folder that archives failing to extract are moved
+/// to, unless another is given
+pub const QUARANTINE_DIR: &str = ".quarantine";
+
+/// Suffixes of files still being downloaded or copied
+const PARTIAL_SUFFIXES: &[&str] = &[".part", ".partial", ".tmp", ".crdownload", ".download"];
+
+/// What to do when an archive's folder already exists in the destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OnConflict {
+    /// Extract into a new folder, `name-1`, `name-2`, ...
+    #[default]
+    Rename,
+    /// Extract into the existing folder, replacing files
+    Overwrite,
+    /// Extract into the existing folder, keeping files already there
+    Skip,
+}
+
+/// How to watch a folder
+#[derive(Debug, Clone)]
+pub struct WatchOptions {
+    pub destination: PathBuf,
+    pub interval: Duration,
+    pub on_conflict: OnConflict,
+    /// Quarantine folder; `.quarantine` in the watched folder if unset
+    pub quarantine: Option<PathBuf>,
+    /// Delete archives once extracted instead of keeping them in
+    /// `.processed`
+    pub delete: bool,
+    /// Take every archive there now, without waiting for it to settle, and
+    /// return
+    pub once: bool,
+    /// Print a JSON line for each archive taken
+    pub json: bool,
+}
+
+/// What happened to one archive
+#[derive(Debug, Serialize)]
+pub struct Ingested {
+    pub archive: PathBuf,
+    /// Folder it was extracted into
+    pub output: PathBuf,
+    /// Where the archive was moved, if it was not deleted
+    pub moved_to: Option<PathBuf>,
+    /// Why extraction failed; the archive is then in quarantine
+    pub error: Option<String>,
+}
+
+/// Size and modification time of a file, compared between scans
+type Snapshot = (u64, Option<SystemTime>);
+
+/// Watch `folder`, extracting archives as they arrive, until an error
+/// stops it or, with `once`, the archives there now are done
+///
+/// With `once`, fails if any archive failed to extract.
+pub fn watch(folder: &Path, options: &WatchOptions) -> Result<()> {
+    if !folder.is_dir() {
+        anyhow::bail!("{} is not a directory", folder.display());
+    }
+    fs::create_dir_all(&options.destination)
+        .with_context(|| format!("Cannot create {}", options.destination.display()))?;
+    let quarantine = options
+        .quarantine
+        .clone()
+        .unwrap_or_else(|| folder.join(QUARANTINE_DIR));
+
+    if options.once {
+        let mut failed = 0;
+        for archive in arrivals(folder)? {
+            if ingest(folder, &archive, &quarantine, options)?
+                .error
+                .is_some()
+            {
+                failed += 1;
+            }
+        }
+        if failed > 0 {
+            anyhow::bail!("{} archives failed to extract", failed);
+        }
+        return Ok(());
+    }
+
+    info!(
+        "Watching {} for archives, extracting into {}",
+        folder.display(),
+        options.destination.display()
+    );
+    let mut seen: HashMap<PathBuf, Snapshot> = HashMap::new();
+    loop {
+        let mut current = HashMap::new();
+        for archive in arrivals(folder)? {
+            let Ok(snapshot) = snapshot(&archive) else {
+                continue;
+            };
+            if seen.get(&archive) == Some(&snapshot) {
+                ingest(folder, &archive, &quarantine, options)?;
+            } else {
+                current.insert(archive, snapshot);
+            }
+        }
+        seen = current;
+        thread::sleep(options.interval);
+    }
+}
+
+/// Archives directly in `folder`, skipping hidden and partial files
+fn arrivals(folder: &Path) -> Result<Vec<PathBuf>> {
+    let mut archives = Vec::new();
+    for entry in
+        fs::read_dir(folder).with_context(|| format!("Cannot read {}", folder.display()))?
+    {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        if name.starts_with('.') || PARTIAL_SUFFIXES.iter().any(|s| name.ends_with(s)) {
+            continue;
+        }
+        let path = entry.path();
+        if entry.file_type()?.is_file() && archive_format(&path).is_some() {
+            archives.push(path);
+        }
+    }
+    archives.sort();
+    Ok(archives)
+}
+
+fn snapshot(path: &Path) -> std::io::Result<Snapshot> {
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.len(), metadata.modified().ok()))
+}
+
+/// Extract `archive` and move it out of the watched folder
+///
+/// A failed extraction is not an error; only failing to move the archive
+/// away is, since it would otherwise be taken again on the next scan.
+fn ingest(
+    folder: &Path,
+    archive: &Path,
+    quarantine: &Path,
+    options: &WatchOptions,
+) -> Result<Ingested> {
+    let name = archive
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let stem = strip_archive_extension(&name)
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or(&name);
+    let (output, created) = output_dir(&options.destination, stem, options.on_conflict);
+
+    let extract_options = ExtractOptions {
+        overwrite: options.on_conflict == OnConflict::Overwrite,
+        skip: options.on_conflict != OnConflict::Overwrite,
+        ..ExtractOptions::default()
+    };
+    let job = ExtractJob::new(
+        archive,
+        &output,
+        ExtractEntryOptions {
+            // `skip` keeps existing files even with `overwrite`
+            overwrite: extract_options.overwrite && !extract_options.skip,
+            preserve_permissions: true,
+            preserve_timestamps: true,
+            follow_symlinks: false,
+            password: None,
+            symlink_fallback: Default::default(),
+            strip_components: None,
+        },
+    );
+    let result = job
+        .run(&NoProgress, |_, _| None)
+        .map_err(anyhow::Error::from)
+        .and_then(|outcome| check_outcome(outcome, false));
+
+    let ingested = match result {
+        Ok(()) => {
+            info!("Extracted {} to {}", archive.display(), output.display());
+            let moved_to = if options.delete {
+                fs::remove_file(archive)
+                    .with_context(|| format!("Cannot delete {}", archive.display()))?;
+                None
+            } else {
+                Some(move_into(archive, &folder.join(PROCESSED_DIR), &name)?)
+            };
+            Ingested {
+                archive: archive.to_path_buf(),
+                output,
+                moved_to,
+                error: None,
+            }
+        }
+        Err(e) => {
+            let message = format!("{:#}", e);
+            error!("Failed to extract {}: {}", archive.display(), message);
+            // Leave nothing half-extracted behind in a folder of our own
+            if created && output.exists() {
+                if let Err(e) = fs::remove_dir_all(&output) {
+                    warn!("Cannot remove {}: {}", output.display(), e);
+                }
+            }
+            let moved = move_into(archive, quarantine, &name)?;
+            let note = moved.with_file_name(format!(
+                "{}.error.txt",
+                moved.file_name().unwrap_or_default().to_string_lossy()
+            ));
+            fs::write(&note, format!("{}\n", message))
+                .with_context(|| format!("Cannot write {}", note.display()))?;
+            Ingested {
+                archive: archive.to_path_buf(),
+                output,
+                moved_to: Some(moved),
+                error: Some(message),
+            }
+        }
+    };
+
+    if options.json {
+        println!("{}", serde_json::to_string(&ingested)?);
+    }
+    Ok(ingested)
+}
+
+/// Folder to extract an archive named `stem` into, and whether it is a new
+/// one
+fn output_dir(destination: &Path, stem: &str, on_conflict: OnConflict) -> (PathBuf, bool) {
+    let output = destination.join(stem);
+    if !output.exists() {
+        return (output, true);
+    }
+    if on_conflict != OnConflict::Rename {
+        return (output, false);
+    }
+    let renamed = (1..)
+        .map(|n| destination.join(format!("{}-{}", stem, n)))
+        .find(|path| !path.exists())
+        .expect("unbounded range");
+    (renamed, true)
+}
+
+/// Move `archive` into `dir` as `name`, numbering it if the name is taken
+fn move_into(archive: &Path, dir: &Path, name: &str) -> Result<PathBuf> {
+    fs::create_dir_all(dir).with_context(|| format!("Cannot create {}", dir.display()))?;
+    let mut target = dir.join(name);
+    let mut n = 1;
+    while target.exists() {
+        target = dir.join(format!("{}.{}", name, n));
+        n += 1;
+    }
+    flux_core::utils::move_path(archive, &target)
+        .with_context(|| format!("Cannot move {} to {}", archive.display(), dir.display()))?;
+    Ok(target)
+}
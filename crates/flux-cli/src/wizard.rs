@@ -0,0 +1,228 @@
+//! Guided prompts for running `flux` with no subcommand
+//!
+//! The wizard only builds a [`Commands`] value; running it goes through the
+//! same code as the command line, which is also shown before confirming so
+//! it can be reused.
+
+use crate::Commands;
+use anyhow::Result;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Confirm, Input, Select};
+use flux_core::archive::{
+    CaseCollisions, ExtractOrder, NameNormalization, PackOrder, SymlinkFallback, TamperPolicy,
+};
+use flux_core::config::Config;
+use flux_core::durability::Durability;
+use std::path::{Path, PathBuf};
+
+/// Formats offered when packing, in the order shown
+const PACK_FORMATS: &[&str] = &["tar.zst", "tar.gz", "tar.xz", "tar.br", "tar", "zip"];
+
+/// Ask what to do, or `None` if the user backs out at the confirmation
+pub fn run() -> Result<Option<Commands>> {
+    let theme = ColorfulTheme::default();
+    let action = Select::with_theme(&theme)
+        .with_prompt("What do you want to do?")
+        .items(&["Pack files into an archive", "Extract an archive"])
+        .default(0)
+        .interact()?;
+
+    let (command, line) = match action {
+        0 => pack(&theme)?,
+        _ => extract(&theme)?,
+    };
+
+    eprintln!("\nThis runs: {}\n", line);
+    let confirmed = Confirm::with_theme(&theme)
+        .with_prompt("Go ahead?")
+        .default(true)
+        .interact()?;
+    Ok(confirmed.then_some(command))
+}
+
+fn pack(theme: &ColorfulTheme) -> Result<(Commands, String)> {
+    let input = existing_path(theme, "File or folder to pack", false)?;
+
+    let config = Config::load_or_default();
+    let default_format = PACK_FORMATS
+        .iter()
+        .position(|format| *format == config.archive.default_format)
+        .unwrap_or(0);
+    let format = PACK_FORMATS[Select::with_theme(theme)
+        .with_prompt("Archive format")
+        .items(PACK_FORMATS)
+        .default(default_format)
+        .interact()?];
+
+    let name = input
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "archive".to_string());
+    let output: String = Input::with_theme(theme)
+        .with_prompt("Save the archive as")
+        .default(format!("{}.{}", name, format))
+        .interact_text()?;
+
+    let level: String = Input::with_theme(theme)
+        .with_prompt("Compression level (empty for the default)")
+        .allow_empty(true)
+        .validate_with(|text: &String| -> Result<(), String> {
+            if text.is_empty() || text.parse::<u32>().is_ok() {
+                Ok(())
+            } else {
+                Err("Enter a number".to_string())
+            }
+        })
+        .interact_text()?;
+    let level = level.parse::<u32>().ok();
+
+    let exclude_vcs = input.is_dir()
+        && Confirm::with_theme(theme)
+            .with_prompt("Skip version control and dependency folders (.git, node_modules, ...)?")
+            .default(true)
+            .interact()?;
+
+    let mut line = format!(
+        "flux pack {} -o {} --format {}",
+        quote(&input.to_string_lossy()),
+        quote(&output),
+        format
+    );
+    if let Some(level) = level {
+        line.push_str(&format!(" --level {}", level));
+    }
+    if exclude_vcs {
+        line.push_str(" --exclude-vcs");
+    }
+
+    let command = Commands::Pack {
+        input: Some(input),
+        output: PathBuf::from(output),
+        format: Some(format.to_string()),
+        smart: false,
+        algo: None,
+        level,
+        threads: None,
+        follow_symlinks: false,
+        max_depth: None,
+        force_compress: false,
+        exclude_vcs,
+        incremental: None,
+        volume_size: None,
+        encrypt_manifest: false,
+        meta: Vec::new(),
+        mtime: None,
+        normalize_names: NameNormalization::default(),
+        strip_prefix: None,
+        prefix: None,
+        mmap: false,
+        order: PackOrder::default(),
+        files_from: None,
+        null: false,
+        target_time: None,
+        json: false,
+        strict: false,
+        durability: Durability::default(),
+    };
+    Ok((command, line))
+}
+
+fn extract(theme: &ColorfulTheme) -> Result<(Commands, String)> {
+    let archive = existing_path(theme, "Archive to extract", true)?;
+
+    let output: String = Input::with_theme(theme)
+        .with_prompt("Extract into")
+        .default(".".to_string())
+        .interact_text()?;
+
+    let conflict = Select::with_theme(theme)
+        .with_prompt("When a file already exists")
+        .items(&[
+            "Skip it",
+            "Overwrite it",
+            "Keep both (rename)",
+            "Ask each time",
+        ])
+        .default(0)
+        .interact()?;
+
+    let hoist = Confirm::with_theme(theme)
+        .with_prompt("If everything is in one folder, extract its contents directly?")
+        .default(false)
+        .interact()?;
+
+    let mut line = format!(
+        "flux extract {} -o {}",
+        quote(&archive.to_string_lossy()),
+        quote(&output)
+    );
+    line.push_str(match conflict {
+        0 => " --skip",
+        1 => " --overwrite",
+        2 => " --rename",
+        _ => " --interactive",
+    });
+    if hoist {
+        line.push_str(" --hoist");
+    }
+
+    let command = Commands::Extract {
+        archive,
+        output: Some(PathBuf::from(output)),
+        skip: conflict == 0,
+        overwrite: conflict == 1,
+        rename: conflict == 2,
+        interactive: conflict == 3,
+        strip_components: None,
+        hoist,
+        salvage: false,
+        case_collisions: CaseCollisions::default(),
+        normalize_names: NameNormalization::default(),
+        manifest: None,
+        on_tampered: TamperPolicy::default(),
+        order: ExtractOrder::default(),
+        first: Vec::new(),
+        backup: false,
+        format: None,
+        symlink_fallback: SymlinkFallback::default(),
+        no_same_permissions: false,
+        recursive: false,
+        max_depth: flux_core::archive::DEFAULT_RECURSION_DEPTH,
+        strict: false,
+        durability: Durability::default(),
+        resume: false,
+    };
+    Ok((command, line))
+}
+
+/// Ask for a path until it names an existing file, or a file or folder
+/// unless `file_only`
+fn existing_path(theme: &ColorfulTheme, prompt: &str, file_only: bool) -> Result<PathBuf> {
+    let text: String = Input::with_theme(theme)
+        .with_prompt(prompt)
+        .validate_with(|text: &String| -> Result<(), String> {
+            let path = Path::new(text.trim());
+            if !path.exists() {
+                Err(format!("{} does not exist", text.trim()))
+            } else if file_only && !path.is_file() {
+                Err(format!("{} is not a file", text.trim()))
+            } else {
+                Ok(())
+            }
+        })
+        .interact_text()?;
+    Ok(PathBuf::from(text.trim()))
+}
+
+/// Quote `text` for a POSIX shell when it needs it
+fn quote(text: &str) -> String {
+    let plain = !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:@%+=,".contains(c));
+    if plain {
+        text.to_string()
+    } else {
+        format!("'{}'", text.replace('\'', r"'\''"))
+    }
+}
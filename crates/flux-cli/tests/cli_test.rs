@@ -21,6 +21,37 @@ fn test_cli_help() {
         .stdout(predicate::str::contains("A cross-platform file archiver"));
 }
 
+#[test]
+fn test_info_lists_formats() {
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("info")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("tar.zst"))
+        .stdout(predicate::str::contains("zstd"))
+        .stdout(predicate::str::contains("Temp dir"));
+}
+
+#[test]
+fn test_info_json() {
+    let output = Command::cargo_bin("flux")
+        .unwrap()
+        .args(["info", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let info: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let formats = info["formats"].as_array().unwrap();
+    let sevenz = formats
+        .iter()
+        .find(|format| format["name"] == "7z")
+        .unwrap();
+    assert_eq!(sevenz["pack"], false);
+    assert!(info["cloud"].is_boolean());
+    assert!(info["default_threads"].as_u64().unwrap() > 0);
+}
+
 #[test]
 fn test_pack_extract_basic() {
     let temp_dir = TempDir::new().unwrap();
@@ -81,6 +112,37 @@ fn test_pack_with_format() {
     assert!(archive_path.exists());
 }
 
+#[test]
+fn test_pack_format_follows_output_name() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_dir = temp_dir.path().join("input");
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::write(input_dir.join("test.txt"), "Content").unwrap();
+    let archive_path = temp_dir.path().join("output.zip");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&archive_path)
+        .assert()
+        .success();
+    assert!(fs::read(&archive_path).unwrap().starts_with(b"PK"));
+
+    // A name no format goes by is refused rather than guessed at
+    let unnamed = temp_dir.path().join("output.archive");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&unnamed)
+        .assert()
+        .failure();
+    assert!(!unnamed.exists());
+}
+
 #[test]
 fn test_pack_with_smart_strategy() {
     let temp_dir = TempDir::new().unwrap();
@@ -207,6 +269,283 @@ fn test_extract_with_overwrite() {
     );
 }
 
+#[test]
+fn test_extract_backup_and_undo() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_file = temp_dir.path().join("test.txt");
+    let archive_path = temp_dir.path().join("test.tar");
+    let output_dir = temp_dir.path().join("output");
+
+    fs::write(&input_file, "From the archive").unwrap();
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_file)
+        .arg("-o")
+        .arg(&archive_path)
+        .assert()
+        .success();
+
+    fs::create_dir_all(&output_dir).unwrap();
+    fs::write(output_dir.join("test.txt"), "Mine").unwrap();
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("extract")
+        .arg(&archive_path)
+        .arg("-o")
+        .arg(&output_dir)
+        .args(["--overwrite", "--backup"])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(output_dir.join("test.txt")).unwrap(),
+        "From the archive"
+    );
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("undo")
+        .arg(&output_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 restored"));
+    assert_eq!(
+        fs::read_to_string(output_dir.join("test.txt")).unwrap(),
+        "Mine"
+    );
+
+    // Nothing left to undo
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("undo")
+        .arg(&output_dir)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No undoable extraction"));
+}
+
+#[test]
+fn test_extract_resume_removes_journal_when_done() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_file = temp_dir.path().join("test.txt");
+    let archive_path = temp_dir.path().join("test.zip");
+    let output_dir = temp_dir.path().join("output");
+
+    fs::write(&input_file, "From the archive").unwrap();
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_file)
+        .arg("-o")
+        .arg(&archive_path)
+        .assert()
+        .success();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("extract")
+        .arg(&archive_path)
+        .arg("-o")
+        .arg(&output_dir)
+        .arg("--resume")
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(output_dir.join("test.txt")).unwrap(),
+        "From the archive"
+    );
+    assert!(!output_dir.join(".flux-resume.json").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pack_warns_about_dropped_symlinks() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_dir = temp_dir.path().join("input");
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::write(input_dir.join("file.txt"), "content").unwrap();
+    std::os::unix::fs::symlink("file.txt", input_dir.join("link")).unwrap();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(temp_dir.path().join("out.zip"))
+        .args(["--format", "zip"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "zip archives do not keep symlinks: 1 symlink, such as",
+        ));
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(temp_dir.path().join("out.tar"))
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("do not keep").not());
+}
+
+#[test]
+fn test_extract_misnamed_archive() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_file = temp_dir.path().join("input.txt");
+    let packed = temp_dir.path().join("packed.tar.gz");
+    let archive_path = temp_dir.path().join("download(1)");
+    let output_dir = temp_dir.path().join("output");
+    fs::write(&input_file, "downloaded").unwrap();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_file)
+        .arg("-o")
+        .arg(&packed)
+        .args(["--format", "tar.gz"])
+        .assert()
+        .success();
+    fs::rename(&packed, &archive_path).unwrap();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("extract")
+        .arg(&archive_path)
+        .arg("-o")
+        .arg(&output_dir)
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(output_dir.join("input.txt")).unwrap(),
+        "downloaded"
+    );
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("inspect")
+        .arg(&archive_path)
+        .args(["--format", "zip"])
+        .assert()
+        .failure();
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("inspect")
+        .arg(&archive_path)
+        .args(["--format", "tgz"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("input.txt"));
+}
+
+#[test]
+fn test_extract_recursive() {
+    let temp_dir = TempDir::new().unwrap();
+    let inner_dir = temp_dir.path().join("inner");
+    let outer_dir = temp_dir.path().join("outer");
+    let archive_path = temp_dir.path().join("outer.tar.gz");
+    let output_dir = temp_dir.path().join("output");
+    fs::create_dir_all(&inner_dir).unwrap();
+    fs::create_dir_all(&outer_dir).unwrap();
+    fs::write(inner_dir.join("data.txt"), "nested data").unwrap();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&inner_dir)
+        .arg("-o")
+        .arg(outer_dir.join("inner.zip"))
+        .args(["--format", "zip"])
+        .assert()
+        .success();
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&outer_dir)
+        .arg("-o")
+        .arg(&archive_path)
+        .args(["--format", "tar.gz"])
+        .assert()
+        .success();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("extract")
+        .arg(&archive_path)
+        .arg("-o")
+        .arg(&output_dir)
+        .args(["--recursive", "--max-depth", "2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "outer/inner.zip (zip) -> outer/inner",
+        ))
+        .stdout(predicate::str::contains(
+            "1 nested archives extracted, 0 skipped",
+        ));
+    assert_eq!(
+        fs::read_to_string(output_dir.join("outer/inner/data.txt")).unwrap(),
+        "nested data"
+    );
+    assert!(!output_dir.join("outer/inner.zip").exists());
+}
+
+#[test]
+fn test_dedupe_report_and_rewrite() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_dir = temp_dir.path().join("input");
+    let archive_path = temp_dir.path().join("dupes.tar.gz");
+    let deduped_path = temp_dir.path().join("deduped.tar.gz");
+    fs::create_dir_all(input_dir.join("copy")).unwrap();
+    fs::write(input_dir.join("data.bin"), "the same data".repeat(500)).unwrap();
+    fs::write(input_dir.join("copy/data.bin"), "the same data".repeat(500)).unwrap();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&archive_path)
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("flux")
+        .unwrap()
+        .arg("dedupe-report")
+        .arg(&archive_path)
+        .arg("--json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(report["files"], 2);
+    assert_eq!(report["groups"][0]["size"], 6500);
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("dedupe-report")
+        .arg(&archive_path)
+        .arg("--rewrite")
+        .arg("-o")
+        .arg(&deduped_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 duplicates"))
+        .stdout(predicate::str::contains("storing each duplicate once"));
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("dedupe-report")
+        .arg(&deduped_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("0 duplicates"));
+}
+
 #[test]
 fn test_verbose_and_quiet_flags() {
     let temp_dir = TempDir::new().unwrap();
@@ -274,3 +613,1704 @@ fn test_error_handling() {
     let mut cmd = Command::cargo_bin("flux").unwrap();
     cmd.arg("extract").arg(&bad_archive).assert().failure();
 }
+
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_integrate_install_and_uninstall() {
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    let gui = temp_dir.path().join("flux-gui");
+    fs::write(&gui, "").unwrap();
+
+    let status = |expected: &str| {
+        Command::cargo_bin("flux")
+            .unwrap()
+            .env("XDG_DATA_HOME", &data_dir)
+            .args(["integrate", "--status"])
+            .assert()
+            .success()
+            .stdout(predicate::str::contains(expected));
+    };
+
+    status("not installed");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .env("XDG_DATA_HOME", &data_dir)
+        .arg("integrate")
+        .arg("--gui")
+        .arg(&gui)
+        .assert()
+        .success();
+    assert!(data_dir.join("nautilus/scripts/Extract with Flux").exists());
+    assert!(data_dir
+        .join("kio/servicemenus/flux-compress.desktop")
+        .exists());
+    status("are installed");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .env("XDG_DATA_HOME", &data_dir)
+        .args(["integrate", "--uninstall"])
+        .assert()
+        .success();
+    assert!(!data_dir.join("nautilus/scripts/Extract with Flux").exists());
+    status("not installed");
+}
+
+#[test]
+fn test_integrate_missing_gui() {
+    let temp_dir = TempDir::new().unwrap();
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("integrate")
+        .arg("--gui")
+        .arg(temp_dir.path().join("missing"))
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_mv_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_dir = temp_dir.path().join("input");
+    fs::create_dir_all(input_dir.join("old")).unwrap();
+    fs::write(input_dir.join("old/file.txt"), "moved").unwrap();
+    let archive_path = temp_dir.path().join("test.zip");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&archive_path)
+        .arg("--format")
+        .arg("zip")
+        .assert()
+        .success();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("mv")
+        .arg(&archive_path)
+        .arg("old")
+        .arg("new")
+        .assert()
+        .success();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("inspect")
+        .arg(&archive_path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("new/file.txt"))
+        .stdout(predicate::str::contains("old/file.txt").not());
+
+    // Moving something that is not there fails with an I/O-class exit code
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("mv")
+        .arg(&archive_path)
+        .arg("old")
+        .arg("other")
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_complete_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_dir = temp_dir.path().join("photos");
+    fs::create_dir_all(input_dir.join("2023/raw")).unwrap();
+    fs::write(input_dir.join("2023/raw/a.cr2"), "raw").unwrap();
+    fs::write(input_dir.join("2023/notes.txt"), "notes").unwrap();
+    fs::write(input_dir.join("readme.txt"), "read me").unwrap();
+    let archive_path = temp_dir.path().join("photos.tar.gz");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&archive_path)
+        .assert()
+        .success();
+
+    let cache_dir = temp_dir.path().join("cache");
+    let complete = |prefix: &str| {
+        let output = Command::cargo_bin("flux")
+            .unwrap()
+            .env("XDG_CACHE_HOME", &cache_dir)
+            .arg("__complete-entries")
+            .arg(&archive_path)
+            .arg(prefix)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert!(output.stderr.is_empty());
+        String::from_utf8(output.stdout).unwrap()
+    };
+
+    assert_eq!(complete(""), "photos/\n");
+    assert_eq!(complete("photos/"), "photos/2023/\nphotos/readme.txt\n");
+    assert_eq!(complete("photos/2023/r"), "photos/2023/raw/\n");
+    assert_eq!(
+        complete("photos/2023/"),
+        "photos/2023/notes.txt\nphotos/2023/raw/\n"
+    );
+    // Completed again from the cached listing
+    assert_eq!(complete("photos/2023/raw/"), "photos/2023/raw/a.cr2\n");
+    assert_eq!(complete("nothing/"), "");
+
+    // Hidden from help, and quiet about archives it cannot read
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("__complete-entries").not());
+    Command::cargo_bin("flux")
+        .unwrap()
+        .env("XDG_CACHE_HOME", &cache_dir)
+        .arg("__complete-entries")
+        .arg(temp_dir.path().join("missing.zip"))
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn test_batch_command() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("docs")).unwrap();
+    fs::write(temp_dir.path().join("docs/notes.txt"), "nightly").unwrap();
+
+    // Relative paths are resolved against the batch file, not the working directory
+    let batch_file = temp_dir.path().join("jobs.yaml");
+    fs::write(
+        &batch_file,
+        r#"
+jobs:
+  - name: pack docs
+    op: pack
+    input: docs
+    output: out/docs.tar.gz
+    format: tar.gz
+    level: 9
+  - op: extract
+    archive: out/docs.tar.gz
+    output: restored
+  - op: sync
+    source: docs
+    target: out/docs-sync.tar.gz
+"#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("batch")
+        .arg(&batch_file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pack docs"))
+        .stdout(predicate::str::contains("3 jobs, 0 failed"));
+
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("restored/docs/notes.txt")).unwrap(),
+        "nightly"
+    );
+    assert!(temp_dir.path().join("out/docs-sync.tar.gz").exists());
+    assert!(temp_dir
+        .path()
+        .join("out/docs-sync.tar.fluxmanifest")
+        .exists());
+}
+
+/// Extract jobs treat files already there the same whether they run entry
+/// by entry or as a whole-archive extraction
+#[test]
+fn test_batch_extract_keeps_existing_files() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("docs")).unwrap();
+    fs::write(temp_dir.path().join("docs/notes.txt"), "nightly").unwrap();
+    fs::write(temp_dir.path().join("docs/todo.txt"), "todo").unwrap();
+    for restored in ["entries", "whole", "skipped", "replaced"] {
+        let dir = temp_dir.path().join(restored).join("docs");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("notes.txt"), "kept").unwrap();
+    }
+
+    // Normalizing names needs the whole-archive extraction
+    let batch_file = temp_dir.path().join("jobs.toml");
+    fs::write(
+        &batch_file,
+        r#"
+[[jobs]]
+op = "pack"
+input = "docs"
+output = "docs.tar"
+
+[[jobs]]
+op = "extract"
+archive = "docs.tar"
+output = "entries"
+skip = false
+overwrite = false
+
+[[jobs]]
+op = "extract"
+archive = "docs.tar"
+output = "whole"
+skip = false
+overwrite = false
+normalize_names = "nfc"
+
+[[jobs]]
+op = "extract"
+archive = "docs.tar"
+output = "skipped"
+skip = true
+overwrite = true
+
+[[jobs]]
+op = "extract"
+archive = "docs.tar"
+output = "replaced"
+overwrite = true
+"#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("batch")
+        .arg(&batch_file)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("5 jobs, 0 failed"));
+
+    for (restored, notes) in [
+        ("entries", "kept"),
+        ("whole", "kept"),
+        ("skipped", "kept"),
+        ("replaced", "nightly"),
+    ] {
+        let dir = temp_dir.path().join(restored).join("docs");
+        assert_eq!(
+            fs::read_to_string(dir.join("notes.txt")).unwrap(),
+            notes,
+            "{}",
+            restored
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("todo.txt")).unwrap(),
+            "todo",
+            "{}",
+            restored
+        );
+    }
+}
+
+#[test]
+fn test_batch_command_reports_failures() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir_all(temp_dir.path().join("docs")).unwrap();
+    fs::write(temp_dir.path().join("docs/notes.txt"), "nightly").unwrap();
+
+    let batch_file = temp_dir.path().join("jobs.toml");
+    fs::write(
+        &batch_file,
+        r#"
+[[jobs]]
+op = "pack"
+input = "docs"
+output = "docs.tar.zst"
+
+[[jobs]]
+name = "missing"
+op = "extract"
+archive = "missing.tar.zst"
+output = "restored"
+
+[[jobs]]
+name = "gone"
+op = "pack"
+input = "gone"
+output = "two.tar"
+"#,
+    )
+    .unwrap();
+
+    let output = Command::cargo_bin("flux")
+        .unwrap()
+        .arg("batch")
+        .arg(&batch_file)
+        .arg("--parallel")
+        .arg("--json")
+        .assert()
+        .code(4)
+        .get_output()
+        .stdout
+        .clone();
+
+    let report: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let jobs = report["jobs"].as_array().unwrap();
+    assert_eq!(jobs.len(), 3);
+    assert_eq!(jobs[0]["status"], "succeeded");
+    assert_eq!(jobs[1]["name"], "missing");
+    assert_eq!(jobs[1]["status"], "failed");
+    assert!(jobs[1]["error"].is_string());
+    assert_eq!(jobs[2]["status"], "failed");
+    assert!(temp_dir.path().join("docs.tar.zst").exists());
+    // A failed pack leaves no partial archive behind
+    assert!(!temp_dir.path().join("two.tar").exists());
+
+    // Unknown operations are rejected before anything runs
+    fs::write(&batch_file, "[[jobs]]\nop = \"delete\"\n").unwrap();
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("batch")
+        .arg(&batch_file)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid batch file"));
+
+    // So are misspelled options, in YAML as in TOML
+    let yaml_file = temp_dir.path().join("jobs.yaml");
+    fs::write(
+        &yaml_file,
+        "jobs:\n  - name: docs\n    op: pack\n    input: docs\n    output: docs.tar\n    exclude_vsc: true\n",
+    )
+    .unwrap();
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("batch")
+        .arg(&yaml_file)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("exclude_vsc"));
+    assert!(!temp_dir.path().join("docs.tar").exists());
+
+    fs::write(
+        &batch_file,
+        "[[jobs]]\nop = \"pack\"\ninput = \"docs\"\noutput = \"docs.tar\"\nlvl = 3\n",
+    )
+    .unwrap();
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("batch")
+        .arg(&batch_file)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("lvl"));
+}
+
+#[test]
+fn test_exclude_vcs_flag() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_dir = temp_dir.path().join("project");
+    fs::create_dir_all(input_dir.join(".git")).unwrap();
+    fs::create_dir_all(input_dir.join("web/node_modules/left-pad")).unwrap();
+    fs::write(input_dir.join(".git/HEAD"), "ref: refs/heads/main").unwrap();
+    fs::write(input_dir.join("web/node_modules/left-pad/index.js"), "pad").unwrap();
+    fs::write(input_dir.join("web/app.js"), "app").unwrap();
+    let archive_path = temp_dir.path().join("project.tar.zst");
+    let sync_path = temp_dir.path().join("sync.tar.gz");
+    // Keep the user's configuration out of the test
+    let config_dir = temp_dir.path().join("config");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_dir)
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&archive_path)
+        .arg("--exclude-vcs")
+        .assert()
+        .success();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_dir)
+        .arg("sync")
+        .arg(&input_dir)
+        .arg(&sync_path)
+        .arg("--exclude-vcs")
+        .assert()
+        .success();
+
+    for archive in [&archive_path, &sync_path] {
+        Command::cargo_bin("flux")
+            .unwrap()
+            .arg("inspect")
+            .arg(archive)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("app.js"))
+            .stdout(predicate::str::contains(".git").not())
+            .stdout(predicate::str::contains("node_modules").not());
+    }
+}
+
+#[test]
+fn test_status_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("docs");
+    fs::create_dir_all(source.join("notes")).unwrap();
+    fs::write(source.join("notes/keep.txt"), "keep").unwrap();
+    fs::write(source.join("notes/edit.txt"), "before").unwrap();
+    fs::write(source.join("gone.txt"), "gone").unwrap();
+    let target = temp_dir.path().join("docs.tar.gz");
+
+    // Without a previous backup there is nothing to compare with
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("status")
+        .arg(&source)
+        .arg(&target)
+        .assert()
+        .code(2);
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("sync")
+        .arg(&source)
+        .arg(&target)
+        .assert()
+        .success();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("status")
+        .arg(&source)
+        .arg(&target)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("up to date"));
+
+    fs::write(source.join("notes/edit.txt"), "after").unwrap();
+    fs::write(source.join("new.txt"), "new").unwrap();
+    fs::remove_file(source.join("gone.txt")).unwrap();
+    let archive = fs::read(&target).unwrap();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("status")
+        .arg(&source)
+        .arg(&target)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("added:    new.txt"))
+        .stdout(predicate::str::contains("modified: notes/edit.txt"))
+        .stdout(predicate::str::contains("deleted:  gone.txt"))
+        .stdout(predicate::str::contains("1 added, 1 modified, 1 deleted"));
+
+    let output = Command::cargo_bin("flux")
+        .unwrap()
+        .arg("status")
+        .arg(&source)
+        .arg(&target)
+        .arg("--json")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let diff: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(diff["added"], serde_json::json!(["new.txt"]));
+    assert_eq!(diff["modified"], serde_json::json!(["notes/edit.txt"]));
+    assert_eq!(diff["deleted"], serde_json::json!(["gone.txt"]));
+
+    // Status only looks; the backup is left as it was
+    assert_eq!(fs::read(&target).unwrap(), archive);
+
+    // An incremental sync updates the manifest status compares against
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("sync")
+        .arg(&source)
+        .arg(&target)
+        .assert()
+        .success();
+    assert!(!target.with_extension("manifest.json").exists());
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("status")
+        .arg(&source)
+        .arg(&target)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("up to date"));
+}
+
+#[test]
+fn test_sync_check() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("docs");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join("keep.txt"), "keep").unwrap();
+    let target = temp_dir.path().join("docs.tar.gz");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("sync")
+        .arg(&source)
+        .arg(&target)
+        .assert()
+        .success();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("sync")
+        .arg(&source)
+        .arg(&target)
+        .arg("--check")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("up to date"));
+
+    fs::write(source.join("new.txt"), "new").unwrap();
+    let archive = fs::read(&target).unwrap();
+
+    let output = Command::cargo_bin("flux")
+        .unwrap()
+        .arg("sync")
+        .arg(&source)
+        .arg(&target)
+        .arg("--check")
+        .arg("--json")
+        .assert()
+        .code(1)
+        .get_output()
+        .stdout
+        .clone();
+    let drift: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(drift["added"], serde_json::json!(["new.txt"]));
+    assert!(drift["drift_secs"].is_u64());
+
+    // Checking does not back anything up
+    assert_eq!(fs::read(&target).unwrap(), archive);
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("sync")
+        .arg(&source)
+        .arg(&target)
+        .arg("--check")
+        .arg("--full")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_sync_encrypted_manifest() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("private");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join("salaries.csv"), "alice,100").unwrap();
+    let target = temp_dir.path().join("private.tar.gz");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .env("FLUX_MANIFEST_PASSWORD", "hunter2")
+        .arg("sync")
+        .arg(&source)
+        .arg(&target)
+        .arg("--encrypt-manifest")
+        .assert()
+        .success();
+
+    let manifest = fs::read(target.with_extension("fluxmanifest")).unwrap();
+    assert!(!manifest.windows(8).any(|w| w == b"salaries"));
+
+    // Reading it back takes the password
+    Command::cargo_bin("flux")
+        .unwrap()
+        .env_remove("FLUX_MANIFEST_PASSWORD")
+        .arg("status")
+        .arg(&source)
+        .arg(&target)
+        .arg("--json")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("password_required"));
+
+    fs::write(source.join("bonus.csv"), "bob,5").unwrap();
+    Command::cargo_bin("flux")
+        .unwrap()
+        .env("FLUX_MANIFEST_PASSWORD", "hunter2")
+        .arg("status")
+        .arg(&source)
+        .arg(&target)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("added:    bonus.csv"));
+}
+
+#[test]
+fn test_tag_manifest_entry() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("docs");
+    fs::create_dir_all(source.join("taxes")).unwrap();
+    fs::write(source.join("taxes/2024.pdf"), "return").unwrap();
+    fs::write(source.join("todo.txt"), "call the bank").unwrap();
+    let target = temp_dir.path().join("docs.tar.gz");
+    let manifest = target.with_extension("fluxmanifest");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("sync")
+        .arg(&source)
+        .arg(&target)
+        .assert()
+        .success();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("tag")
+        .arg(&manifest)
+        .arg("taxes/2024.pdf")
+        .args(["retention=7y", "owner=me"])
+        .args(["--note", "Filed in April"])
+        .assert()
+        .success()
+        .stdout("owner=me\nretention=7y\nNote: Filed in April\n");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("tag")
+        .arg(&manifest)
+        .arg("taxes/2024.pdf")
+        .args(["--remove", "owner"])
+        .assert()
+        .success()
+        .stdout("retention=7y\nNote: Filed in April\n");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("tag")
+        .arg(&manifest)
+        .arg("missing.pdf")
+        .arg("retention=1y")
+        .assert()
+        .failure();
+
+    // The next backup's manifest keeps the tags
+    fs::write(source.join("todo.txt"), "done").unwrap();
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("sync")
+        .arg(&source)
+        .arg(&target)
+        .assert()
+        .success();
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("tag")
+        .arg(&manifest)
+        .arg("taxes/2024.pdf")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("retention=7y"));
+}
+
+#[test]
+fn test_checksum_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_dir = temp_dir.path().join("input");
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::write(input_dir.join("hello.txt"), "hello").unwrap();
+    let archive_path = temp_dir.path().join("test.tar.zst");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&archive_path)
+        .assert()
+        .success();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("checksum")
+        .arg(&archive_path)
+        .assert()
+        .success()
+        .stdout(
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  input/hello.txt\n",
+        );
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("checksum")
+        .arg(&archive_path)
+        .arg("--algo")
+        .arg("md5")
+        .assert()
+        .success()
+        .stdout("5d41402abc4b2a76b9719d911017c592  input/hello.txt\n");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("checksum")
+        .arg(&archive_path)
+        .arg("--algo")
+        .arg("crc32")
+        .assert()
+        .code(3);
+}
+
+#[test]
+fn test_extract_salvage() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_dir = temp_dir.path().join("input");
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::write(input_dir.join("first.txt"), "1".repeat(2000)).unwrap();
+    fs::write(input_dir.join("second.txt"), "2".repeat(2000)).unwrap();
+    let archive_path = temp_dir.path().join("test.tar");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&archive_path)
+        .arg("-f")
+        .arg("tar")
+        .assert()
+        .success();
+
+    let output_dir = temp_dir.path().join("intact");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("extract")
+        .arg(&archive_path)
+        .arg("-o")
+        .arg(&output_dir)
+        .arg("--salvage")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("0 lost"));
+    assert!(output_dir.join("input/second.txt").exists());
+
+    // Cut the archive in the middle of whichever file comes last
+    let bytes = fs::read(&archive_path).unwrap();
+    let data = |byte: u8| {
+        bytes
+            .windows(2000)
+            .position(|window| window.iter().all(|&b| b == byte))
+            .unwrap()
+    };
+    let (kept, cut, cut_at) = if data(b'1') < data(b'2') {
+        ("first.txt", "second.txt", data(b'2'))
+    } else {
+        ("second.txt", "first.txt", data(b'1'))
+    };
+    fs::write(&archive_path, &bytes[..cut_at + 1000]).unwrap();
+
+    let output_dir = temp_dir.path().join("damaged");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("extract")
+        .arg(&archive_path)
+        .arg("-o")
+        .arg(&output_dir)
+        .arg("--salvage")
+        .assert()
+        .code(4)
+        .stdout(predicate::str::contains(format!("LOST input/{}", cut)));
+    assert!(output_dir.join("input").join(kept).exists());
+    assert!(!output_dir.join("input").join(cut).exists());
+}
+
+#[test]
+fn test_set_overrides_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = temp_dir.path().join("config");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_dir)
+        .arg("--set")
+        .arg("compression.default_level=19")
+        .arg("--set")
+        .arg("archive.default_format=zip")
+        .arg("config")
+        .arg("--show")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("default_level = 19"))
+        .stdout(predicate::str::contains("default_format = \"zip\""));
+
+    // Nothing is written back to the file
+    Command::cargo_bin("flux")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_dir)
+        .arg("config")
+        .arg("--show")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("default_level = 3"));
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_dir)
+        .arg("--set")
+        .arg("strategy.default_level=19")
+        .arg("config")
+        .arg("--show")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Unknown config key"));
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("--set")
+        .arg("compression.default_level")
+        .arg("config")
+        .arg("--show")
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_set_accepted_after_the_command() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = temp_dir.path().join("config");
+
+    // Values from both sides of the command are kept
+    Command::cargo_bin("flux")
+        .unwrap()
+        .env("XDG_CONFIG_HOME", &config_dir)
+        .arg("--set")
+        .arg("performance.threads=2")
+        .arg("config")
+        .arg("--show")
+        .arg("--set")
+        .arg("archive.default_format=zip")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("threads = 2"))
+        .stdout(predicate::str::contains("default_format = \"zip\""));
+}
+
+#[test]
+fn test_set_default_level_applies_to_pack() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_dir = temp_dir.path().join("config");
+    let input_dir = temp_dir.path().join("input");
+    fs::create_dir_all(&input_dir).unwrap();
+    let words = ["alpha", "beta", "gamma", "delta", "flux", "archive"];
+    let mut seed = 1u64;
+    let text: Vec<String> = (0..100_000)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let n = (seed >> 33) as usize;
+            format!("{}{}", words[n % words.len()], n % 997)
+        })
+        .collect();
+    fs::write(input_dir.join("words.txt"), text.join(" ")).unwrap();
+
+    let pack = |level: u32| {
+        let archive = temp_dir.path().join(format!("level{}.tar.zst", level));
+        Command::cargo_bin("flux")
+            .unwrap()
+            .env("XDG_CONFIG_HOME", &config_dir)
+            .arg("pack")
+            .arg(&input_dir)
+            .arg("-o")
+            .arg(&archive)
+            .arg("--set")
+            .arg(format!("compression.default_level={}", level))
+            .assert()
+            .success();
+        fs::metadata(&archive).unwrap().len()
+    };
+
+    assert!(pack(19) < pack(1));
+}
+
+#[test]
+fn test_priority_flags() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_dir = temp_dir.path().join("input");
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::write(input_dir.join("hello.txt"), "hello").unwrap();
+    let archive_path = temp_dir.path().join("test.tar.zst");
+
+    // A bare --nice must not swallow the subcommand as its level
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("--nice")
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&archive_path)
+        .assert()
+        .success();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("inspect")
+        .arg(&archive_path)
+        .arg("--background")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hello.txt"));
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("--nice=20")
+        .arg("inspect")
+        .arg(&archive_path)
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_progress_pack_and_extract() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_dir = temp_dir.path().join("input");
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::write(input_dir.join("hello.txt"), "hello").unwrap();
+    let archive_path = temp_dir.path().join("test.tar.zst");
+    let output_dir = temp_dir.path().join("output");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("--progress")
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&archive_path)
+        .assert()
+        .success();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("--progress")
+        .arg("extract")
+        .arg(&archive_path)
+        .arg("-o")
+        .arg(&output_dir)
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(output_dir.join("input/hello.txt")).unwrap(),
+        "hello"
+    );
+}
+
+#[test]
+fn test_pack_to_stdout() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_dir = temp_dir.path().join("input");
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::write(input_dir.join("hello.txt"), "hello").unwrap();
+
+    for format in ["tar.zst", "zip"] {
+        let output = Command::cargo_bin("flux")
+            .unwrap()
+            .arg("pack")
+            .arg(&input_dir)
+            .arg("-o")
+            .arg("-")
+            .arg("--format")
+            .arg(format)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", format);
+        // Nothing but the archive goes to stdout, and nothing chatty to stderr
+        assert!(output.stderr.is_empty(), "{}", format);
+
+        let archive_path = temp_dir.path().join(format!("piped.{}", format));
+        fs::write(&archive_path, &output.stdout).unwrap();
+        Command::cargo_bin("flux")
+            .unwrap()
+            .arg("inspect")
+            .arg(&archive_path)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("hello.txt"));
+    }
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg("-")
+        .arg("--incremental")
+        .arg(temp_dir.path().join("previous.manifest.json"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("stdout"));
+}
+
+#[test]
+fn test_scratch_quota_stops_staging() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_dir = temp_dir.path().join("input");
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::write(input_dir.join("hello.txt"), "hello ".repeat(1000)).unwrap();
+    let scratch = temp_dir.path().join("scratch");
+
+    // Zip is staged before it goes to stdout
+    let output = Command::cargo_bin("flux")
+        .unwrap()
+        .arg("--temp-dir")
+        .arg(&scratch)
+        .args(["--scratch-quota", "64"])
+        .arg("pack")
+        .arg(&input_dir)
+        .args(["-o", "-", "--format", "zip"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+    assert_eq!(fs::read_dir(&scratch).unwrap().count(), 0);
+
+    // Tars are streamed, so nothing is staged to count against the quota
+    let output = Command::cargo_bin("flux")
+        .unwrap()
+        .arg("--temp-dir")
+        .arg(&scratch)
+        .args(["--scratch-quota", "64"])
+        .arg("pack")
+        .arg(&input_dir)
+        .args(["-o", "-", "--format", "tar.zst"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]));
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .args(["--scratch-quota", "lots"])
+        .arg("info")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_no_subcommand_outside_terminal() {
+    // The wizard needs a terminal; piped runs keep the usage error
+    Command::cargo_bin("flux")
+        .unwrap()
+        .write_stdin("")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("subcommand is required"));
+}
+
+#[test]
+fn test_inspect_sort_and_fields() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_dir = temp_dir.path().join("input");
+    fs::create_dir_all(&input_dir).unwrap();
+    fs::write(input_dir.join("big.txt"), "b".repeat(5000)).unwrap();
+    fs::write(input_dir.join("medium.txt"), "m".repeat(300)).unwrap();
+    fs::write(input_dir.join("small.txt"), "s").unwrap();
+    let archive_path = temp_dir.path().join("test.zip");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&archive_path)
+        .args(["--format", "zip"])
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("flux")
+        .unwrap()
+        .arg("inspect")
+        .arg(&archive_path)
+        .args([
+            "--sort",
+            "size",
+            "--reverse",
+            "--fields",
+            "size,path",
+            "--bytes",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "Size  Path");
+    assert!(lines[2].starts_with("5000  "), "{}", stdout);
+    assert!(lines[2].ends_with("big.txt"), "{}", stdout);
+    assert!(lines[3].ends_with("medium.txt"), "{}", stdout);
+    assert!(lines[4].ends_with("small.txt"), "{}", stdout);
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("inspect")
+        .arg(&archive_path)
+        .args(["--fields", "path,bogus"])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_tar_and_unzip_compat() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path().join("project");
+    fs::create_dir_all(project.join("docs")).unwrap();
+    fs::write(project.join("readme.txt"), "read me").unwrap();
+    fs::write(project.join("docs/guide.txt"), "guide").unwrap();
+    let tarball = temp_dir.path().join("project.tgz");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["tar", "-czf", "project.tgz", "project"])
+        .assert()
+        .success();
+    assert!(tarball.exists());
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .args(["tar", "tf"])
+        .arg(&tarball)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("project/docs/guide.txt"));
+
+    // Only the named member is extracted
+    let out = temp_dir.path().join("out");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .args(["tar", "-x", "-f"])
+        .arg(&tarball)
+        .arg("-C")
+        .arg(&out)
+        .arg("project/docs")
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(out.join("project/docs/guide.txt")).unwrap(),
+        "guide"
+    );
+    assert!(!out.join("project/readme.txt").exists());
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .args(["tar", "-xjf"])
+        .arg(&tarball)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("bzip2"));
+
+    let zip = temp_dir.path().join("docs.zip");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .args(["tar", "-caf"])
+        .arg(&zip)
+        .arg("-C")
+        .arg(&project)
+        .arg("docs")
+        .assert()
+        .success();
+
+    let unzipped = temp_dir.path().join("unzipped");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .args(["unzip", "-o"])
+        .arg(&zip)
+        .arg("-d")
+        .arg(&unzipped)
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(unzipped.join("docs/guide.txt")).unwrap(),
+        "guide"
+    );
+}
+
+#[test]
+fn test_tar_compat_through_a_pipe() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = temp_dir.path().join("project");
+    fs::create_dir_all(project.join("docs")).unwrap();
+    fs::write(project.join("readme.txt"), "read me").unwrap();
+    fs::write(project.join("docs/guide.txt"), "guide").unwrap();
+
+    // With the archive on stdout, -v lists files on stderr
+    let output = Command::cargo_bin("flux")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .args(["tar", "-czvf", "-", "project"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.starts_with(&[0x1f, 0x8b]));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("project/docs/guide.txt"));
+    let tarball = output.stdout;
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .args(["tar", "-tf", "-"])
+        .write_stdin(tarball.clone())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("project/readme.txt"));
+
+    let out = temp_dir.path().join("out");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .args(["tar", "-xf", "-", "-C"])
+        .arg(&out)
+        .write_stdin(tarball)
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(out.join("project/docs/guide.txt")).unwrap(),
+        "guide"
+    );
+}
+
+#[test]
+fn test_pack_volume_size() {
+    let temp_dir = TempDir::new().unwrap();
+    let input_dir = temp_dir.path().join("input");
+    fs::create_dir_all(&input_dir).unwrap();
+    // Incompressible, so the archive needs several 64 KiB volumes
+    let mut state = 0x2545_f491_u32;
+    let data: Vec<u8> = (0..200_000)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect();
+    fs::write(input_dir.join("data.bin"), &data).unwrap();
+
+    let archive = temp_dir.path().join("parts.zip");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&archive)
+        .arg("--volume-size")
+        .arg("64KiB")
+        .assert()
+        .success();
+    for volume in ["parts.z01", "parts.z02", "parts.z03", "parts.zip"] {
+        let len = fs::metadata(temp_dir.path().join(volume)).unwrap().len();
+        assert!(len <= 64 * 1024, "{} is {} bytes", volume, len);
+    }
+
+    let output_dir = temp_dir.path().join("output");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("extract")
+        .arg(&archive)
+        .arg("-o")
+        .arg(&output_dir)
+        .assert()
+        .success();
+    assert_eq!(fs::read(output_dir.join("data.bin")).unwrap(), data);
+
+    // Only zips can be split, and volumes below 64 KiB are refused
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(temp_dir.path().join("parts.tar.zst"))
+        .arg("--volume-size")
+        .arg("1M")
+        .assert()
+        .code(3);
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input_dir)
+        .arg("-o")
+        .arg(&archive)
+        .arg("--volume-size")
+        .arg("1K")
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn test_pack_with_meta() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("release");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join("app.bin"), "binary").unwrap();
+
+    for format in ["tar.gz", "zip"] {
+        let archive = temp_dir.path().join(format!("release.{}", format));
+        Command::cargo_bin("flux")
+            .unwrap()
+            .arg("pack")
+            .arg(&source)
+            .arg("-o")
+            .arg(&archive)
+            .arg("-f")
+            .arg(format)
+            .arg("--meta")
+            .arg("ticket=OPS-12")
+            .arg("--meta")
+            .arg("channel=beta")
+            .assert()
+            .success();
+
+        Command::cargo_bin("flux")
+            .unwrap()
+            .arg("inspect")
+            .arg(&archive)
+            .arg("--meta")
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("ticket:"))
+            .stdout(predicate::str::contains("OPS-12"))
+            .stdout(predicate::str::contains("flux version:"));
+
+        let output = Command::cargo_bin("flux")
+            .unwrap()
+            .arg("inspect")
+            .arg(&archive)
+            .arg("--meta")
+            .arg("--json")
+            .output()
+            .unwrap();
+        let metadata: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(metadata["values"]["channel"], "beta");
+        assert_eq!(metadata["flux_version"], env!("CARGO_PKG_VERSION"));
+        assert!(metadata["created"].is_i64());
+
+        // The metadata is not listed as an entry
+        Command::cargo_bin("flux")
+            .unwrap()
+            .arg("inspect")
+            .arg(&archive)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("app.bin"))
+            .stdout(predicate::str::contains("pax_global_header").not());
+    }
+
+    // Archives packed without --meta have none
+    let plain = temp_dir.path().join("plain.tar.gz");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&source)
+        .arg("-o")
+        .arg(&plain)
+        .assert()
+        .success();
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("inspect")
+        .arg(&plain)
+        .arg("--meta")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No metadata"));
+}
+
+#[test]
+fn test_pack_with_source_date_epoch() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("pkg");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join("bin"), "binary").unwrap();
+
+    let pack = |archive: &std::path::Path, args: &[&str], epoch: Option<&str>| {
+        let mut command = Command::cargo_bin("flux").unwrap();
+        command.env_remove("SOURCE_DATE_EPOCH");
+        if let Some(epoch) = epoch {
+            command.env("SOURCE_DATE_EPOCH", epoch);
+        }
+        command
+            .arg("pack")
+            .arg(&source)
+            .arg("-o")
+            .arg(archive)
+            .args(args)
+            .assert()
+            .success();
+    };
+    let mtime = |archive: &std::path::Path| {
+        let output = Command::cargo_bin("flux")
+            .unwrap()
+            .arg("inspect")
+            .arg(archive)
+            .arg("--json")
+            .output()
+            .unwrap();
+        let entries: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        entries
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|entry| entry["path"].as_str().unwrap().ends_with("bin"))
+            .unwrap()["mtime"]
+            .as_i64()
+            .unwrap()
+    };
+
+    let from_env = temp_dir.path().join("env.tar.gz");
+    pack(&from_env, &[], Some("1700000000"));
+    assert_eq!(mtime(&from_env), 1_700_000_000);
+
+    // --mtime takes dates too, and wins over the variable
+    let from_flag = temp_dir.path().join("flag.tar.gz");
+    pack(&from_flag, &["--mtime", "2020-01-01"], Some("1700000000"));
+    assert_eq!(mtime(&from_flag), 1_577_836_800);
+
+    let unclamped = temp_dir.path().join("plain.tar.gz");
+    pack(&unclamped, &[], None);
+    assert!(mtime(&unclamped) > 1_700_000_000);
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .env("SOURCE_DATE_EPOCH", "yesterday")
+        .arg("pack")
+        .arg(&source)
+        .arg("-o")
+        .arg(temp_dir.path().join("bad.tar.gz"))
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_pack_with_strip_prefix_and_prefix() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("project");
+    fs::create_dir_all(source.join("src")).unwrap();
+    fs::write(source.join("src/lib.rs"), "pub fn new() {}").unwrap();
+    let archive = temp_dir.path().join("release.tar.gz");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&source)
+        .arg("-o")
+        .arg(&archive)
+        .args(["--strip-prefix", "project", "--prefix", "release/v2"])
+        .assert()
+        .success();
+
+    let output = temp_dir.path().join("out");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("extract")
+        .arg(&archive)
+        .arg("-o")
+        .arg(&output)
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(output.join("release/v2/src/lib.rs")).unwrap(),
+        "pub fn new() {}"
+    );
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&source)
+        .arg("-o")
+        .arg(temp_dir.path().join("bad.tar.gz"))
+        .args(["--prefix", "../outside"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_pack_files_from() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("project");
+    fs::create_dir_all(source.join("src")).unwrap();
+    fs::write(source.join("src/lib.rs"), "pub fn new() {}").unwrap();
+    fs::write(source.join("src/skip.rs"), "left out").unwrap();
+    fs::write(source.join("odd\nname.txt"), "odd").unwrap();
+
+    // A list read from stdin, NUL-separated as `find -print0` writes it
+    let archive = temp_dir.path().join("listed.tar.gz");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&source)
+        .arg("-o")
+        .arg(&archive)
+        .args(["-T", "-", "--null"])
+        .write_stdin("src/lib.rs\0odd\nname.txt\0")
+        .assert()
+        .success();
+
+    let output = temp_dir.path().join("out");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("extract")
+        .arg(&archive)
+        .arg("-o")
+        .arg(&output)
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(output.join("src/lib.rs")).unwrap(),
+        "pub fn new() {}"
+    );
+    assert!(output.join("odd\nname.txt").exists());
+    assert!(!output.join("src/skip.rs").exists());
+
+    // A list file of paths relative to the current directory
+    let list = temp_dir.path().join("list.txt");
+    fs::write(&list, "project/src/lib.rs\n").unwrap();
+    let from_file = temp_dir.path().join("from-file.zip");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .current_dir(temp_dir.path())
+        .arg("pack")
+        .arg("--files-from")
+        .arg(&list)
+        .arg("-o")
+        .arg(&from_file)
+        .assert()
+        .success();
+    let inspected = Command::cargo_bin("flux")
+        .unwrap()
+        .arg("inspect")
+        .arg(&from_file)
+        .arg("--json")
+        .output()
+        .unwrap();
+    let entries: serde_json::Value = serde_json::from_slice(&inspected.stdout).unwrap();
+    let names: Vec<&str> = entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["project/src/lib.rs"]);
+}
+
+#[test]
+fn test_pack_json_progress_events() {
+    let temp_dir = TempDir::new().unwrap();
+    let input = temp_dir.path().join("input.txt");
+    fs::write(&input, "progress ".repeat(1000)).unwrap();
+
+    let output = Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input)
+        .arg("-o")
+        .arg(temp_dir.path().join("out.tar.gz"))
+        .arg("--json")
+        .assert()
+        .success()
+        .get_output()
+        .stderr
+        .clone();
+
+    let events: Vec<serde_json::Value> = String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let phases: Vec<_> = events
+        .iter()
+        .filter(|event| event["event"] == "phase")
+        .map(|event| event["phase"].clone())
+        .collect();
+    assert_eq!(phases, ["scan", "compress"]);
+
+    let last = events.last().unwrap();
+    assert_eq!(last["event"], "progress");
+    assert_eq!(last["phase"], "compress");
+    assert_eq!(last["bytes"], 9000);
+    assert_eq!(last["total"], 9000);
+    assert!(last["eta_secs"].is_null());
+}
+
+/// Pack a small directory into `archive`
+fn pack_sample(temp_dir: &TempDir, archive: &std::path::Path) {
+    let input = temp_dir.path().join("input");
+    fs::create_dir_all(&input).unwrap();
+    fs::write(input.join("file.txt"), "hello").unwrap();
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&input)
+        .arg("-o")
+        .arg(archive)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_watch_extract_once() {
+    let temp_dir = TempDir::new().unwrap();
+    let incoming = temp_dir.path().join("incoming");
+    let dest = temp_dir.path().join("dest");
+    fs::create_dir_all(&incoming).unwrap();
+    pack_sample(&temp_dir, &incoming.join("docs.tar.gz"));
+    fs::write(incoming.join("bad.zip"), "not a zip").unwrap();
+    fs::write(incoming.join("late.tar.gz.part"), "still copying").unwrap();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("watch-extract")
+        .arg(&incoming)
+        .arg("-o")
+        .arg(&dest)
+        .args(["--once", "--json"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("\"error\":null"));
+
+    assert_eq!(
+        fs::read_to_string(dest.join("docs/input/file.txt")).unwrap(),
+        "hello"
+    );
+    assert!(incoming.join(".processed/docs.tar.gz").is_file());
+    assert!(!incoming.join("docs.tar.gz").exists());
+
+    // The broken archive is set aside with its error, leaving nothing behind
+    assert!(incoming.join(".quarantine/bad.zip").is_file());
+    assert!(incoming.join(".quarantine/bad.zip.error.txt").is_file());
+    assert!(!dest.join("bad").exists());
+    assert!(incoming.join("late.tar.gz.part").is_file());
+}
+
+#[test]
+fn test_watch_extract_renames_on_conflict() {
+    let temp_dir = TempDir::new().unwrap();
+    let incoming = temp_dir.path().join("incoming");
+    let dest = temp_dir.path().join("dest");
+    fs::create_dir_all(dest.join("docs")).unwrap();
+    fs::create_dir_all(&incoming).unwrap();
+    pack_sample(&temp_dir, &incoming.join("docs.tar.gz"));
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("watch-extract")
+        .arg(&incoming)
+        .arg("-o")
+        .arg(&dest)
+        .args(["--once", "--delete"])
+        .assert()
+        .success();
+
+    assert!(dest.join("docs-1/input/file.txt").is_file());
+    assert!(!incoming.join("docs.tar.gz").exists());
+    assert!(!incoming.join(".processed").exists());
+}
@@ -15,13 +15,13 @@ fn test_cloud_url_detection() {
     let mut cmd = Command::cargo_bin("flux").unwrap();
 
     // Test that cloud URLs are recognized (will fail due to missing credentials)
-    cmd.args(&["extract", "s3://bucket/file.tar", "-o", "/tmp/out"])
+    cmd.args(["extract", "s3://bucket/file.tar", "-o", "/tmp/out"])
         .assert()
         .failure()
         .stderr(predicate::str::contains("AWS credentials not found"));
 
     let mut cmd = Command::cargo_bin("flux").unwrap();
-    cmd.args(&["extract", "gs://bucket/file.tar", "-o", "/tmp/out"])
+    cmd.args(["extract", "gs://bucket/file.tar", "-o", "/tmp/out"])
         .assert()
         .failure()
         .stderr(predicate::str::contains(
@@ -29,7 +29,7 @@ fn test_cloud_url_detection() {
         ));
 
     let mut cmd = Command::cargo_bin("flux").unwrap();
-    cmd.args(&["extract", "az://container/file.tar", "-o", "/tmp/out"])
+    cmd.args(["extract", "az://container/file.tar", "-o", "/tmp/out"])
         .assert()
         .failure()
         .stderr(predicate::str::contains("Azure credentials not found"));
@@ -44,7 +44,7 @@ fn test_pack_to_cloud_url() {
     let mut cmd = Command::cargo_bin("flux").unwrap();
 
     // Test that packing to cloud URL is recognized
-    cmd.args(&[
+    cmd.args([
         "pack",
         test_file.to_str().unwrap(),
         "-o",
@@ -55,12 +55,32 @@ fn test_pack_to_cloud_url() {
     .stderr(predicate::str::contains("AWS credentials not found"));
 }
 
+#[test]
+fn test_sync_to_cloud_url() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("test.txt"), "test content").unwrap();
+
+    let mut cmd = Command::cargo_bin("flux").unwrap();
+    cmd.env_remove("AWS_ACCESS_KEY_ID")
+        .args([
+            "sync",
+            temp_dir.path().to_str().unwrap(),
+            "s3://bucket/backups/photos.tar.gz",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("AWS credentials not found"));
+
+    // Nothing named after the URL is written locally
+    assert!(!std::path::Path::new("s3:").exists());
+}
+
 #[test]
 fn test_inspect_cloud_archive() {
     let mut cmd = Command::cargo_bin("flux").unwrap();
 
     // Test that inspect command recognizes cloud URLs
-    cmd.args(&["inspect", "s3://bucket/archive.tar"])
+    cmd.args(["inspect", "s3://bucket/archive.tar"])
         .assert()
         .failure()
         .stderr(predicate::str::contains("AWS credentials not found"));
@@ -79,13 +99,87 @@ fn test_cloud_url_formats() {
 
     for url in &urls {
         let mut cmd = Command::cargo_bin("flux").unwrap();
-        cmd.args(&["inspect", url])
+        cmd.args(["inspect", url])
             .assert()
             .failure()
             .stderr(predicate::str::contains("credentials not found"));
     }
 }
 
+#[test]
+fn test_ls_cloud_location() {
+    let mut cmd = Command::cargo_bin("flux").unwrap();
+    cmd.env_remove("AWS_ACCESS_KEY_ID")
+        .args(["ls", "s3://bucket/backups/"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("AWS credentials not found"));
+
+    let mut cmd = Command::cargo_bin("flux").unwrap();
+    cmd.args(["ls", "./backups", "--json"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Not a cloud location"));
+}
+
+#[test]
+fn test_profile_endpoint_applies_to_its_urls() {
+    let temp_dir = TempDir::new().unwrap();
+
+    // The listing goes to the profile's endpoint, where nothing answers
+    let mut cmd = Command::cargo_bin("flux").unwrap();
+    cmd.env("XDG_CONFIG_HOME", temp_dir.path())
+        .env("AWS_ACCESS_KEY_ID", "test")
+        .env("AWS_SECRET_ACCESS_KEY", "test")
+        .env("AWS_REGION", "us-east-1")
+        .env("AWS_ALLOW_HTTP", "true")
+        .args(["--set", "cloud.profiles.work.url=s3://bucket/work"])
+        .args(["ls", "s3://bucket/work/"])
+        .args(["--set", "cloud.profiles.work.endpoint=http://127.0.0.1:9"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("http://127.0.0.1:9/bucket"));
+}
+
+#[test]
+fn test_remote_cleanup_commands() {
+    let mut cmd = Command::cargo_bin("flux").unwrap();
+    cmd.env_remove("AWS_ACCESS_KEY_ID")
+        .args(["rm-remote", "s3://bucket/backups/", "--recursive", "--yes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("AWS credentials not found"));
+
+    let mut cmd = Command::cargo_bin("flux").unwrap();
+    cmd.env_remove("AWS_ACCESS_KEY_ID")
+        .args(["cleanup-uploads", "s3://bucket/backups/", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("AWS credentials not found"));
+
+    let mut cmd = Command::cargo_bin("flux").unwrap();
+    cmd.args(["rm-remote"]).assert().failure();
+}
+
+#[test]
+fn test_cloud_doctor() {
+    let mut cmd = Command::cargo_bin("flux").unwrap();
+    cmd.args(["cloud", "doctor", "./backups"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Not a cloud location"));
+
+    // Missing credentials point at the checks
+    let mut cmd = Command::cargo_bin("flux").unwrap();
+    cmd.env_remove("AWS_ACCESS_KEY_ID")
+        .args(["ls", "s3://bucket/backups/"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "flux cloud doctor s3://bucket/backups/",
+        ));
+}
+
 #[test]
 #[ignore = "Requires AWS credentials and test bucket"]
 fn test_e2e_s3_pack_extract() {
@@ -103,14 +197,14 @@ fn test_e2e_s3_pack_extract() {
 
     // Pack to S3
     let mut cmd = Command::cargo_bin("flux").unwrap();
-    cmd.args(&["pack", "-i", input_dir.to_str().unwrap(), "-o", &s3_url])
+    cmd.args(["pack", "-i", input_dir.to_str().unwrap(), "-o", &s3_url])
         .assert()
         .success();
 
     // Extract from S3
     let output_dir = temp_dir.path().join("output");
     let mut cmd = Command::cargo_bin("flux").unwrap();
-    cmd.args(&["extract", &s3_url, "-o", output_dir.to_str().unwrap()])
+    cmd.args(["extract", &s3_url, "-o", output_dir.to_str().unwrap()])
         .assert()
         .success();
 
@@ -0,0 +1,31 @@
+//! Compatibility of archives and manifests with published releases
+
+use flux_testing::compat::{self, Release, RELEASES_ENV};
+use tempfile::TempDir;
+
+fn current() -> Release {
+    Release::new("current", env!("CARGO_BIN_EXE_flux"))
+}
+
+#[test]
+fn test_current_build_reads_its_own_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let checks = compat::check_release(&current(), &current(), temp_dir.path()).unwrap();
+    assert_eq!(checks.len(), 2 * (compat::FORMATS.len() + 1));
+    compat::assert_compatible(&checks);
+}
+
+#[test]
+fn test_published_releases() {
+    let releases = compat::releases_from_env().unwrap();
+    if releases.is_empty() {
+        eprintln!("{} is not set, no releases to check against", RELEASES_ENV);
+        return;
+    }
+    let temp_dir = TempDir::new().unwrap();
+    let checks = compat::check_releases(&current(), &releases, temp_dir.path()).unwrap();
+    for check in &checks {
+        eprintln!("{}", check);
+    }
+    compat::assert_compatible(&checks);
+}
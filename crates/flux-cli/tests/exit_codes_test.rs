@@ -118,3 +118,213 @@ fn test_inspect_invalid_archive() {
         .failure()
         .code(2); // IO error when reading invalid tar
 }
+
+#[test]
+fn test_json_error_report() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("missing.zip");
+
+    let output = Command::cargo_bin("flux")
+        .unwrap()
+        .arg("inspect")
+        .arg(&archive)
+        .arg("--json")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(output.stdout.is_empty());
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let report: serde_json::Value = serde_json::from_str(stderr.lines().last().unwrap()).unwrap();
+    assert_eq!(report["error"]["code"], "not_found");
+    assert_eq!(report["error"]["path"], archive.to_str().unwrap());
+    assert_eq!(report["error"]["retryable"], false);
+}
+
+#[test]
+fn test_usage_error_exit_code() {
+    // Rejected by flux rather than by the argument parser
+    Command::cargo_bin("flux")
+        .unwrap()
+        .args(["tar", "-xjf", "archive.tar.bz2"])
+        .assert()
+        .failure()
+        .code(3);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_partial_failure_exit_code() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("source");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join("kept.txt"), "kept").unwrap();
+    // Followed, a dangling link cannot be packed
+    std::os::unix::fs::symlink("missing.txt", source.join("dangling")).unwrap();
+    let archive = temp_dir.path().join("out.tar.gz");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&source)
+        .arg("-o")
+        .arg(&archive)
+        .arg("--follow-symlinks")
+        .assert()
+        .failure()
+        .code(4)
+        .stderr(predicates::str::contains("dangling"));
+
+    // The rest was still packed
+    let output = Command::cargo_bin("flux")
+        .unwrap()
+        .arg("inspect")
+        .arg(&archive)
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&output.stdout).contains("kept.txt"));
+}
+
+#[test]
+fn test_tampered_entry_exit_code() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("source");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join("config.txt"), "trusted").unwrap();
+    let archive = temp_dir.path().join("backup.tar.gz");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&source)
+        .arg("-o")
+        .arg(&archive)
+        .assert()
+        .success();
+    let manifest = archive.with_extension("manifest.json");
+    assert!(manifest.exists());
+
+    // An archive of the same folder after someone changed it
+    fs::write(source.join("config.txt"), "altered").unwrap();
+    let tampered = temp_dir.path().join("tampered.tar.gz");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&source)
+        .arg("-o")
+        .arg(&tampered)
+        .assert()
+        .success();
+
+    let output = temp_dir.path().join("restored");
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("extract")
+        .arg(&tampered)
+        .arg("-o")
+        .arg(&output)
+        .arg("--manifest")
+        .arg(&manifest)
+        .assert()
+        .failure()
+        .code(4)
+        .stderr(predicates::str::contains("does not match the manifest"));
+    assert!(!output.join("source/config.txt").exists());
+
+    // Flagged, the file is kept and the extraction succeeds
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("extract")
+        .arg(&tampered)
+        .arg("-o")
+        .arg(&output)
+        .arg("--manifest")
+        .arg(&manifest)
+        .args(["--on-tampered", "flag"])
+        .assert()
+        .success();
+    assert!(output.join("source/config.txt").exists());
+
+    // The untouched archive passes
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("extract")
+        .arg(&archive)
+        .arg("-o")
+        .arg(temp_dir.path().join("clean"))
+        .arg("--manifest")
+        .arg(&manifest)
+        .assert()
+        .success();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_strict_unsupported_entry_exit_code() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("source");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join("kept.txt"), "kept").unwrap();
+    let _listener = std::os::unix::net::UnixListener::bind(source.join("agent.sock")).unwrap();
+    let archive = temp_dir.path().join("out.tar");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&source)
+        .arg("-o")
+        .arg(&archive)
+        .assert()
+        .success()
+        .stderr(predicates::str::contains(
+            "1 entry skipped: source/agent.sock (unsupported file type)",
+        ));
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("pack")
+        .arg(&source)
+        .arg("-o")
+        .arg(temp_dir.path().join("strict.tar"))
+        .arg("--strict")
+        .assert()
+        .failure()
+        .code(3)
+        .stderr(predicates::str::contains("agent.sock"));
+}
+
+#[test]
+fn test_strict_extract_of_device_exit_code() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("device.tar");
+    let mut builder = tar::Builder::new(fs::File::create(&archive).unwrap());
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Char);
+    header.set_size(0);
+    header.set_mode(0o600);
+    builder
+        .append_data(&mut header, "dev/tty0", std::io::empty())
+        .unwrap();
+    builder.into_inner().unwrap();
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("extract")
+        .arg(&archive)
+        .arg("-o")
+        .arg(temp_dir.path().join("out"))
+        .assert()
+        .success()
+        .stderr(predicates::str::contains("1 entry skipped: dev/tty0"));
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("extract")
+        .arg(&archive)
+        .arg("-o")
+        .arg(temp_dir.path().join("strict"))
+        .arg("--strict")
+        .assert()
+        .failure()
+        .code(3);
+}
@@ -1,4 +1,5 @@
 use assert_cmd::Command;
+use flux_testing::assertions::assert_tree_matches;
 use predicates::prelude::*;
 use std::fs;
 use tempfile::TempDir;
@@ -57,6 +58,36 @@ fn test_pack_extract_roundtrip() {
     assert_eq!(content2, b"Content 2");
 }
 
+#[test]
+fn test_pack_extract_roundtrip_keeps_the_tree() {
+    let temp_dir = TempDir::new().unwrap();
+    let test_dir = temp_dir.path().join("test_data");
+    let archive = temp_dir.path().join("test.tar.zst");
+    let extract_dir = temp_dir.path().join("extracted");
+
+    fs::create_dir_all(test_dir.join("nested/empty")).unwrap();
+    fs::write(test_dir.join("file1.txt"), b"Content 1").unwrap();
+    fs::write(test_dir.join("nested/file2.txt"), b"Content 2").unwrap();
+
+    let mut cmd = Command::cargo_bin("flux").unwrap();
+    cmd.arg("pack")
+        .arg(&test_dir)
+        .arg("-o")
+        .arg(&archive)
+        .assert()
+        .success();
+    let mut cmd = Command::cargo_bin("flux").unwrap();
+    cmd.arg("extract")
+        .arg(&archive)
+        .arg("-o")
+        .arg(&extract_dir)
+        .assert()
+        .success();
+
+    // Names, types, contents, permissions and mtimes all come back
+    assert_tree_matches(&test_dir, &extract_dir.join("test_data")).unwrap();
+}
+
 #[test]
 fn test_pack_single_file() {
     let temp_dir = TempDir::new().unwrap();
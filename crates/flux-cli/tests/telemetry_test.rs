@@ -0,0 +1,93 @@
+//! Tests for `--metrics-file`, built with the telemetry feature
+
+#![cfg(feature = "telemetry")]
+
+use assert_cmd::Command;
+use std::fs;
+use tempfile::TempDir;
+
+fn read_records(path: &std::path::Path) -> Vec<serde_json::Value> {
+    fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_metrics_file_records_phases() {
+    let temp_dir = TempDir::new().unwrap();
+    let input = temp_dir.path().join("input.txt");
+    fs::write(&input, "metrics ".repeat(1000)).unwrap();
+    let archive = temp_dir.path().join("out.tar.gz");
+    let metrics = temp_dir.path().join("metrics.jsonl");
+
+    Command::cargo_bin("flux")
+        .unwrap()
+        .arg("--metrics-file")
+        .arg(&metrics)
+        .arg("pack")
+        .arg(&input)
+        .arg("-o")
+        .arg(&archive)
+        .assert()
+        .success();
+
+    let records = read_records(&metrics);
+    let phases: Vec<_> = records.iter().map(|r| r["phase"].clone()).collect();
+    assert_eq!(phases, ["scan", "compress"]);
+    assert_eq!(records[0]["bytes_in"], 8000);
+    assert_eq!(records[1]["bytes_in"], 8000);
+    assert_eq!(
+        records[1]["bytes_out"],
+        fs::metadata(&archive).unwrap().len()
+    );
+
+    // The environment works too, and later runs append
+    Command::cargo_bin("flux")
+        .unwrap()
+        .env("FLUX_METRICS_FILE", &metrics)
+        .arg("extract")
+        .arg(&archive)
+        .arg("-o")
+        .arg(temp_dir.path().join("out"))
+        .assert()
+        .success();
+
+    let records = read_records(&metrics);
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[2]["phase"], "extract");
+    assert_eq!(
+        records[2]["bytes_in"],
+        fs::metadata(&archive).unwrap().len()
+    );
+}
+
+#[test]
+fn test_metrics_file_records_write_to_stdout() {
+    let temp_dir = TempDir::new().unwrap();
+    let input = temp_dir.path().join("input.txt");
+    fs::write(&input, "stdout").unwrap();
+
+    // Tars go out as they are compressed; zips are staged, then written
+    for (format, phase) in [("tar", "compress"), ("zip", "write")] {
+        let metrics = temp_dir.path().join(format!("{}.jsonl", format));
+        let output = Command::cargo_bin("flux")
+            .unwrap()
+            .arg("--metrics-file")
+            .arg(&metrics)
+            .arg("pack")
+            .arg(&input)
+            .arg("-o")
+            .arg("-")
+            .arg("--format")
+            .arg(format)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", format);
+
+        let records = read_records(&metrics);
+        let write = records.iter().find(|r| r["phase"] == phase).unwrap();
+        assert_eq!(write["bytes_out"], output.stdout.len() as u64, "{}", format);
+    }
+}
@@ -0,0 +1,108 @@
+//! Upload and download benchmarks against an in-memory object store
+//!
+//! A fixed per-request latency stands in for the network, so the numbers
+//! reflect request batching and buffering rather than the local connection.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use flux_testing::bench::{machine_summary, DatasetSpec};
+use flux_testing::cloud::{FaultyStore, MemoryStore};
+use std::fs;
+use std::io::{Read, Write};
+use std::time::Duration;
+use tempfile::TempDir;
+
+const LATENCY: Duration = Duration::from_millis(2);
+
+/// Concatenates a generated dataset into one payload, like a packed archive
+fn payload(spec: &DatasetSpec) -> Vec<u8> {
+    let dir = TempDir::new().unwrap();
+    spec.generate(dir.path()).unwrap();
+
+    let mut files: Vec<_> = walkdir::WalkDir::new(dir.path())
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .collect();
+    files.sort();
+
+    let mut data = Vec::new();
+    for file in files {
+        data.extend_from_slice(&fs::read(file).unwrap());
+    }
+    data
+}
+
+fn payloads() -> Vec<(&'static str, Vec<u8>)> {
+    // Uploads hold the whole payload in memory, so keep it modest
+    [
+        DatasetSpec::media_mix().scaled(0.1),
+        DatasetSpec::many_small_files(),
+    ]
+    .iter()
+    .map(|spec| (spec.name, payload(spec)))
+    .collect()
+}
+
+/// Benchmark streaming uploads through `CloudWriter`
+fn bench_upload(c: &mut Criterion) {
+    eprintln!("transfer_bench: {}", machine_summary());
+
+    let mut group = c.benchmark_group("cloud_upload");
+    group.sample_size(10);
+
+    for (name, data) in payloads() {
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &data, |b, data| {
+            b.iter_with_setup(
+                || {
+                    FaultyStore::new(MemoryStore::new())
+                        .with_latency(LATENCY)
+                        .cloud_store()
+                        .unwrap()
+                },
+                |store| {
+                    let mut writer =
+                        flux_cloud::CloudWriter::from_store(store, "bench.bin".into()).unwrap();
+                    writer.write_all(black_box(data)).unwrap();
+                    writer.flush().unwrap();
+                },
+            );
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark sequential downloads through `CloudReader`
+fn bench_download(c: &mut Criterion) {
+    let mut group = c.benchmark_group("cloud_download");
+    group.sample_size(10);
+
+    for (name, data) in payloads() {
+        let memory = MemoryStore::new().with_object("bench.bin", data.clone());
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter_with_setup(
+                || {
+                    FaultyStore::new(memory.clone())
+                        .with_latency(LATENCY)
+                        .cloud_store()
+                        .unwrap()
+                },
+                |store| {
+                    let mut reader =
+                        flux_cloud::CloudReader::from_store(store, "bench.bin".into()).unwrap();
+                    let mut buf = Vec::new();
+                    reader.read_to_end(&mut buf).unwrap();
+                    black_box(buf);
+                },
+            );
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_upload, bench_download);
+criterion_main!(benches);
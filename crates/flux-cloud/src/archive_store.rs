@@ -0,0 +1,480 @@
+//! The files inside an archive, served as an object store
+//!
+//! [`ArchiveStore`] lists an archive once, local or in a bucket, and then
+//! answers [`ObjectStore`] requests from that listing, so tools that read
+//! through `object_store`, such as DataFusion or delta-rs, can query files
+//! in a flux archive without it being extracted first. Every file in the
+//! archive is an object under its path there; folders only show up as
+//! common prefixes, as they do in a bucket.
+//!
+//! The store is read-only. Getting an object reads the archive up to the
+//! entry on a blocking thread and returns the requested range of it, so a
+//! compressed tar is decompressed from the start each time; zip and 7z
+//! entries are found through the archive's index.
+
+use crate::{CloudReader, CloudStore, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::{DateTime, TimeZone, Utc};
+use flux_core::archive::extractor::ArchiveEntry;
+use flux_core::archive::{
+    archive_format, detect_format, list_from_reader, read_entry_from_reader, sniff_bytes,
+};
+use futures_util::stream::{self, BoxStream, StreamExt};
+use object_store::path::Path;
+use object_store::{
+    Attributes, GetOptions, GetRange, GetResult, GetResultPayload, ListResult, MultipartUpload,
+    ObjectMeta, ObjectStore, PutMultipartOpts, PutOptions, PutPayload, PutResult,
+};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Component, PathBuf};
+use tracing::debug;
+
+/// Name the store gives in its errors
+const STORE: &str = "ArchiveStore";
+
+/// Bytes read from a cloud archive to find its format by signature
+const SNIFF_LEN: usize = 512;
+
+/// Where the archive is read from
+#[derive(Clone)]
+enum Source {
+    Local(PathBuf),
+    Cloud { store: CloudStore, path: Path },
+}
+
+/// A file in the archive
+struct Entry {
+    meta: ObjectMeta,
+    /// Path of the entry as stored in the archive
+    archive_path: PathBuf,
+}
+
+/// An archive whose files can be read through [`ObjectStore`]
+///
+/// The archive is taken not to change while the store is in use; entries
+/// carry an ETag made of the archive's own and their position in it, so
+/// caches keyed on it are invalidated when the store is opened on a new
+/// version.
+///
+/// A store opened on a [`CloudStore`] holds that store's runtime, which
+/// may not be dropped from inside an async context; keep the last clone
+/// of it outside one.
+///
+/// # Example
+///
+/// ```no_run
+/// use flux_cloud::ArchiveStore;
+/// use flux_cloud::ObjectStore;
+/// use object_store::path::Path;
+///
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let store = ArchiveStore::open("sales.tar.zst")?;
+/// let csv = store.get(&Path::from("2024/q1.csv")).await?.bytes().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ArchiveStore {
+    source: Source,
+    format: &'static str,
+    files: BTreeMap<Path, Entry>,
+}
+
+impl ArchiveStore {
+    /// Open the local archive at `path`, in the format its extension or
+    /// signature shows
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let format = detect_format(&path)?;
+        let metadata = std::fs::metadata(&path)?;
+        let modified = metadata
+            .modified()
+            .map(DateTime::<Utc>::from)
+            .unwrap_or_default();
+        let tag = format!("{:x}-{:x}", modified.timestamp(), metadata.len());
+
+        let reader = BufReader::new(File::open(&path)?);
+        let listed = list_from_reader(reader, format, |_| {})?;
+        Ok(Self::from_listing(
+            Source::Local(path),
+            format,
+            listed,
+            modified,
+            &tag,
+        ))
+    }
+
+    /// Open the archive at `path` in `store`, in the format its name or
+    /// signature shows
+    pub fn open_cloud(store: CloudStore, path: Path) -> Result<Self> {
+        let head = store.head(&path)?;
+        let mut reader = CloudReader::from_store(store.clone(), path.clone())?;
+        let format = match archive_format(std::path::Path::new(path.as_ref())) {
+            Some(format) => format,
+            None => {
+                let mut start = Vec::with_capacity(SNIFF_LEN);
+                reader
+                    .by_ref()
+                    .take(SNIFF_LEN as u64)
+                    .read_to_end(&mut start)?;
+                reader.seek(SeekFrom::Start(0))?;
+                sniff_bytes(&start).ok_or_else(|| {
+                    flux_core::Error::UnsupportedFormat(format!("{} is not an archive", path))
+                })?
+            }
+        };
+        let tag = head
+            .e_tag
+            .clone()
+            .unwrap_or_else(|| format!("{:x}", head.last_modified.timestamp()));
+
+        let listed = list_from_reader(reader, format, |_| {})?;
+        Ok(Self::from_listing(
+            Source::Cloud { store, path },
+            format,
+            listed,
+            head.last_modified,
+            &tag,
+        ))
+    }
+
+    fn from_listing(
+        source: Source,
+        format: &'static str,
+        listed: Vec<ArchiveEntry>,
+        modified: DateTime<Utc>,
+        tag: &str,
+    ) -> Self {
+        let mut files = BTreeMap::new();
+        for (index, entry) in listed.into_iter().enumerate() {
+            if entry.is_dir || entry.is_symlink {
+                continue;
+            }
+            let Some(location) = object_path(&entry.path) else {
+                debug!("Not serving entry {:?}", entry.path);
+                continue;
+            };
+            let meta = ObjectMeta {
+                location: location.clone(),
+                last_modified: entry
+                    .mtime
+                    .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+                    .unwrap_or(modified),
+                size: entry.size as usize,
+                e_tag: Some(format!("{}:{}", tag, index)),
+                version: None,
+            };
+            // A later entry of the same path replaces the earlier one, as
+            // it would when extracting
+            files.insert(
+                location,
+                Entry {
+                    meta,
+                    archive_path: entry.path,
+                },
+            );
+        }
+        debug!("Serving {} files from {} archive", files.len(), format);
+        Self {
+            source,
+            format,
+            files,
+        }
+    }
+
+    /// Format the archive is read as, such as `tar.zst` or `zip`
+    pub fn format(&self) -> &'static str {
+        self.format
+    }
+
+    /// Number of files served
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    fn entry(&self, location: &Path) -> object_store::Result<&Entry> {
+        self.files
+            .get(location)
+            .ok_or_else(|| object_store::Error::NotFound {
+                path: location.to_string(),
+                source: "not a file in the archive".into(),
+            })
+    }
+
+    /// Objects at or below `prefix`
+    fn below<'a>(&'a self, prefix: Option<&'a Path>) -> impl Iterator<Item = &'a Entry> + 'a {
+        self.files
+            .values()
+            .filter(move |entry| prefix.is_none_or(|p| entry.meta.location.prefix_matches(p)))
+    }
+}
+
+/// Object path of an archive entry; entries whose paths leave the archive
+/// have none
+fn object_path(path: &std::path::Path) -> Option<Path> {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!parts.is_empty()).then(|| Path::from_iter(parts))
+}
+
+/// Bytes of `range` in an object of `len` bytes
+fn byte_range(
+    range: Option<&GetRange>,
+    len: usize,
+    location: &Path,
+) -> object_store::Result<Range<usize>> {
+    let invalid = |reason: String| object_store::Error::Generic {
+        store: STORE,
+        source: format!("Invalid range for {}: {}", location, reason).into(),
+    };
+    match range {
+        None => Ok(0..len),
+        Some(GetRange::Bounded(r)) if r.end <= r.start => {
+            Err(invalid(format!("{}..{} is empty", r.start, r.end)))
+        }
+        Some(GetRange::Bounded(r)) if r.start >= len => {
+            Err(invalid(format!("{} is past the end at {}", r.start, len)))
+        }
+        Some(GetRange::Bounded(r)) => Ok(r.start..r.end.min(len)),
+        Some(GetRange::Offset(start)) if *start >= len => {
+            Err(invalid(format!("{} is past the end at {}", start, len)))
+        }
+        Some(GetRange::Offset(start)) => Ok(*start..len),
+        Some(GetRange::Suffix(n)) => Ok(len.saturating_sub(*n)..len),
+    }
+}
+
+/// Check the conditions of a get against the entry's metadata, as stores
+/// holding the object itself do
+fn check_preconditions(options: &GetOptions, meta: &ObjectMeta) -> object_store::Result<()> {
+    let path = meta.location.to_string();
+    let etag = meta.e_tag.as_deref().unwrap_or_default();
+    let matches = |wanted: &str| {
+        wanted
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag == etag)
+    };
+
+    if let Some(wanted) = &options.if_match {
+        if !matches(wanted) {
+            return Err(object_store::Error::Precondition {
+                path,
+                source: format!("{} does not match {}", etag, wanted).into(),
+            });
+        }
+    } else if let Some(date) = options.if_unmodified_since {
+        if meta.last_modified > date {
+            return Err(object_store::Error::Precondition {
+                path,
+                source: format!("{} < {}", date, meta.last_modified).into(),
+            });
+        }
+    }
+    if let Some(wanted) = &options.if_none_match {
+        if matches(wanted) {
+            return Err(object_store::Error::NotModified {
+                path,
+                source: format!("{} matches {}", etag, wanted).into(),
+            });
+        }
+    } else if let Some(date) = options.if_modified_since {
+        if meta.last_modified <= date {
+            return Err(object_store::Error::NotModified {
+                path,
+                source: format!("{} >= {}", date, meta.last_modified).into(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Keeps the bytes of one range of what is written to it
+struct RangeWriter {
+    range: Range<u64>,
+    position: u64,
+    kept: Vec<u8>,
+}
+
+impl Write for RangeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let start = self.position;
+        let end = start + buf.len() as u64;
+        let from = self.range.start.clamp(start, end);
+        let to = self.range.end.clamp(start, end);
+        self.kept
+            .extend_from_slice(&buf[(from - start) as usize..(to - start) as usize]);
+        self.position = end;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Read `range` of the entry at `archive_path` from `source`
+fn read_range(
+    source: &Source,
+    format: &str,
+    archive_path: &std::path::Path,
+    range: Range<usize>,
+) -> Result<Bytes> {
+    let mut writer = RangeWriter {
+        range: range.start as u64..range.end as u64,
+        position: 0,
+        kept: Vec::with_capacity(range.len()),
+    };
+    match source {
+        Source::Local(path) => {
+            let reader = BufReader::new(File::open(path)?);
+            read_entry_from_reader(reader, format, archive_path, &mut writer)?;
+        }
+        Source::Cloud { store, path } => {
+            let reader = CloudReader::from_store(store.clone(), path.clone())?;
+            read_entry_from_reader(reader, format, archive_path, &mut writer)?;
+        }
+    }
+    Ok(Bytes::from(writer.kept))
+}
+
+fn read_only(operation: &str) -> object_store::Error {
+    object_store::Error::NotSupported {
+        source: format!("{} is read-only; cannot {}", STORE, operation).into(),
+    }
+}
+
+#[async_trait]
+impl ObjectStore for ArchiveStore {
+    async fn put_opts(
+        &self,
+        _location: &Path,
+        _payload: PutPayload,
+        _opts: PutOptions,
+    ) -> object_store::Result<PutResult> {
+        Err(read_only("put"))
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        _location: &Path,
+        _opts: PutMultipartOpts,
+    ) -> object_store::Result<Box<dyn MultipartUpload>> {
+        Err(read_only("put"))
+    }
+
+    async fn get_opts(
+        &self,
+        location: &Path,
+        options: GetOptions,
+    ) -> object_store::Result<GetResult> {
+        let entry = self.entry(location)?;
+        check_preconditions(&options, &entry.meta)?;
+        if options.version.is_some() {
+            return Err(object_store::Error::NotSupported {
+                source: format!("{} has no object versions", STORE).into(),
+            });
+        }
+        let range = byte_range(options.range.as_ref(), entry.meta.size, location)?;
+
+        let data = if options.head {
+            Bytes::new()
+        } else {
+            let source = self.source.clone();
+            let format = self.format;
+            let archive_path = entry.archive_path.clone();
+            let wanted = range.clone();
+            tokio::task::spawn_blocking(move || read_range(&source, format, &archive_path, wanted))
+                .await
+                .map_err(|e| object_store::Error::JoinError { source: e })?
+                .map_err(|e| object_store::Error::Generic {
+                    store: STORE,
+                    source: Box::new(e),
+                })?
+        };
+
+        Ok(GetResult {
+            payload: GetResultPayload::Stream(stream::once(async move { Ok(data) }).boxed()),
+            meta: entry.meta.clone(),
+            range,
+            attributes: Attributes::default(),
+        })
+    }
+
+    async fn delete(&self, _location: &Path) -> object_store::Result<()> {
+        Err(read_only("delete"))
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+        let metas: Vec<_> = self
+            .below(prefix)
+            .map(|entry| Ok(entry.meta.clone()))
+            .collect();
+        stream::iter(metas).boxed()
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> object_store::Result<ListResult> {
+        let root = Path::default();
+        let prefix_path = prefix.unwrap_or(&root);
+        let mut common_prefixes = BTreeSet::new();
+        let mut objects = Vec::new();
+        for entry in self.below(prefix) {
+            let Some(mut parts) = entry.meta.location.prefix_match(prefix_path) else {
+                continue;
+            };
+            let Some(first) = parts.next() else {
+                continue;
+            };
+            if parts.next().is_some() {
+                common_prefixes.insert(prefix_path.child(first));
+            } else {
+                objects.push(entry.meta.clone());
+            }
+        }
+        Ok(ListResult {
+            common_prefixes: common_prefixes.into_iter().collect(),
+            objects,
+        })
+    }
+
+    async fn copy(&self, _from: &Path, _to: &Path) -> object_store::Result<()> {
+        Err(read_only("copy"))
+    }
+
+    async fn copy_if_not_exists(&self, _from: &Path, _to: &Path) -> object_store::Result<()> {
+        Err(read_only("copy"))
+    }
+}
+
+impl fmt::Display for ArchiveStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            Source::Local(path) => write!(f, "{}({})", STORE, path.display()),
+            Source::Cloud { store, path } => write!(f, "{}({}/{})", STORE, store.store(), path),
+        }
+    }
+}
+
+impl fmt::Debug for ArchiveStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchiveStore")
+            .field("archive", &self.to_string())
+            .field("format", &self.format)
+            .field("files", &self.files.len())
+            .finish()
+    }
+}
@@ -0,0 +1,232 @@
+//! Chains of incremental backups kept in a bucket
+//!
+//! Syncing to a cloud target cannot keep a manifest on local disk, so each
+//! backup is stored next to the manifest it was taken against. For a target
+//! `backups/photos.tar.gz` the links of the chain are numbered:
+//!
+//! ```text
+//! backups/photos.0001.tar.gz        backups/photos.0001.fluxmanifest
+//! backups/photos.0002.tar.gz        backups/photos.0002.fluxmanifest
+//! backups/photos.0002.deleted.txt
+//! ```
+//!
+//! The manifest of a link is uploaded last, once its archive is complete, so
+//! a link only counts when its manifest exists. A run that fails halfway
+//! leaves at most an archive without a manifest, which the next run skips
+//! past rather than building on.
+
+use crate::{CloudError, CloudReader, CloudStore, CloudWriter, CloudWriterGuard, Result};
+use object_store::path::Path;
+use std::fs::File;
+use std::io;
+
+/// Extension of the manifests in a chain
+pub const MANIFEST_EXTENSION: &str = "fluxmanifest";
+
+/// Extension of the list of files deleted since the previous link
+pub const DELETED_EXTENSION: &str = "deleted.txt";
+
+/// One backup of a chain and the objects it is stored as
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainLink {
+    /// Position in the chain, starting at 1
+    pub sequence: u32,
+    pub archive: Path,
+    pub manifest: Path,
+    /// Files deleted since the previous link; only written when there are any
+    pub deleted: Path,
+}
+
+/// The naming of a chain for one target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupChain {
+    /// Folder holding the chain, empty at the top of the bucket
+    folder: Path,
+    /// File name of the target up to its archive extension
+    stem: String,
+    /// Archive extension without the leading dot, such as `tar.gz`
+    extension: String,
+}
+
+impl BackupChain {
+    /// The chain for backups into `target`, an archive name such as
+    /// `backups/photos.tar.gz`
+    pub fn new(target: &Path) -> Result<Self> {
+        let name = target.filename().unwrap_or_default();
+        let split = name
+            .find(".tar.")
+            .or_else(|| name.rfind('.'))
+            .filter(|&dot| dot > 0 && dot + 1 < name.len());
+        let Some(dot) = split else {
+            return Err(CloudError::InvalidPath(format!(
+                "{} needs an archive name with an extension, such as backups/photos.tar.gz",
+                target
+            )));
+        };
+
+        let mut parts: Vec<_> = target.parts().collect();
+        parts.pop();
+        Ok(Self {
+            folder: parts.into_iter().collect(),
+            stem: name[..dot].to_string(),
+            extension: name[dot + 1..].to_string(),
+        })
+    }
+
+    /// Objects of the link numbered `sequence`
+    pub fn link(&self, sequence: u32) -> ChainLink {
+        ChainLink {
+            sequence,
+            archive: self.object(sequence, &self.extension),
+            manifest: self.object(sequence, MANIFEST_EXTENSION),
+            deleted: self.object(sequence, DELETED_EXTENSION),
+        }
+    }
+
+    /// Complete links found in `store`, oldest first
+    pub fn links(&self, store: &CloudStore) -> Result<Vec<ChainLink>> {
+        let mut sequences: Vec<u32> = self
+            .listed(store)?
+            .into_iter()
+            .filter(|(_, extension)| extension == MANIFEST_EXTENSION)
+            .map(|(sequence, _)| sequence)
+            .collect();
+        sequences.sort_unstable();
+        sequences.dedup();
+        Ok(sequences.into_iter().map(|s| self.link(s)).collect())
+    }
+
+    /// The newest complete link, whose manifest the next backup is diffed
+    /// against
+    pub fn latest(&self, store: &CloudStore) -> Result<Option<ChainLink>> {
+        Ok(self.links(store)?.pop())
+    }
+
+    /// The link the next backup is stored as
+    ///
+    /// Numbered past every object of the chain, complete or not, so a link
+    /// left behind by a failed run is never overwritten.
+    pub fn next_link(&self, store: &CloudStore) -> Result<ChainLink> {
+        let last = self
+            .listed(store)?
+            .into_iter()
+            .map(|(sequence, _)| sequence)
+            .max()
+            .unwrap_or(0);
+        Ok(self.link(last + 1))
+    }
+
+    fn object(&self, sequence: u32, extension: &str) -> Path {
+        let name = format!("{}.{:04}.{}", self.stem, sequence, extension);
+        self.folder.child(name)
+    }
+
+    /// Sequence numbers and extensions of the chain's objects in `store`
+    fn listed(&self, store: &CloudStore) -> Result<Vec<(u32, String)>> {
+        let prefix = (!self.folder.as_ref().is_empty()).then_some(&self.folder);
+        let listing = store.list(prefix)?;
+        Ok(listing
+            .objects
+            .iter()
+            .filter_map(|object| self.parse(object.location.filename()?))
+            .collect())
+    }
+
+    /// Split a file name of this chain into its sequence and extension
+    fn parse(&self, name: &str) -> Option<(u32, String)> {
+        let rest = name.strip_prefix(&self.stem)?.strip_prefix('.')?;
+        let (digits, extension) = rest.split_once('.')?;
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let known = [
+            self.extension.as_str(),
+            MANIFEST_EXTENSION,
+            DELETED_EXTENSION,
+        ];
+        if !known.contains(&extension) {
+            return None;
+        }
+        Some((digits.parse().ok()?, extension.to_string()))
+    }
+}
+
+/// Upload the files of a new link: the archive and deleted list first, then
+/// the manifest that completes it
+///
+/// If any upload fails, whatever was already uploaded for the link is
+/// deleted again, best effort.
+pub fn push_link(
+    store: &CloudStore,
+    link: &ChainLink,
+    archive: &std::path::Path,
+    deleted: Option<&std::path::Path>,
+    manifest: &std::path::Path,
+) -> Result<()> {
+    let mut uploaded = Vec::new();
+    let mut files = vec![(archive, &link.archive)];
+    files.extend(deleted.map(|deleted| (deleted, &link.deleted)));
+    files.push((manifest, &link.manifest));
+
+    for (file, path) in files {
+        if let Err(e) = upload_file(store, file, path) {
+            for path in uploaded {
+                let _ = store.delete(path);
+            }
+            return Err(e);
+        }
+        uploaded.push(path);
+    }
+    Ok(())
+}
+
+/// Download the object at `path` into the file `dest`
+pub fn fetch(store: &CloudStore, path: &Path, dest: &std::path::Path) -> Result<()> {
+    let mut reader = CloudReader::from_store(store.clone(), path.clone())?;
+    let mut file = File::create(dest)?;
+    io::copy(&mut reader, &mut file)?;
+    Ok(())
+}
+
+fn upload_file(store: &CloudStore, file: &std::path::Path, path: &Path) -> Result<()> {
+    let mut writer = CloudWriterGuard::new(CloudWriter::from_store(store.clone(), path.clone())?);
+    io::copy(&mut File::open(file)?, &mut writer)?;
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_names() {
+        let chain = BackupChain::new(&Path::from("backups/photos.tar.gz")).unwrap();
+        let link = chain.link(12);
+        assert_eq!(link.archive.as_ref(), "backups/photos.0012.tar.gz");
+        assert_eq!(link.manifest.as_ref(), "backups/photos.0012.fluxmanifest");
+        assert_eq!(link.deleted.as_ref(), "backups/photos.0012.deleted.txt");
+
+        let chain = BackupChain::new(&Path::from("my.photos.zip")).unwrap();
+        assert_eq!(chain.link(1).archive.as_ref(), "my.photos.0001.zip");
+
+        assert!(BackupChain::new(&Path::from("backups/photos")).is_err());
+        assert!(BackupChain::new(&Path::from("backups/.tar")).is_err());
+    }
+
+    #[test]
+    fn test_parse_only_matches_the_chain() {
+        let chain = BackupChain::new(&Path::from("photos.tar.gz")).unwrap();
+        assert_eq!(
+            chain.parse("photos.0003.fluxmanifest"),
+            Some((3, MANIFEST_EXTENSION.to_string()))
+        );
+        assert_eq!(
+            chain.parse("photos.10000.tar.gz"),
+            Some((10000, "tar.gz".to_string()))
+        );
+        assert_eq!(chain.parse("photos.tar.gz"), None);
+        assert_eq!(chain.parse("photos.0003.tar.gz.flux-upload"), None);
+        assert_eq!(chain.parse("photos-old.0003.tar.gz"), None);
+        assert_eq!(chain.parse("photos.x003.tar.gz"), None);
+    }
+}
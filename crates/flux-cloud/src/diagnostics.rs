@@ -0,0 +1,147 @@
+//! Explanations for failed cloud requests
+//!
+//! Object store errors differ between providers and rarely say what to do
+//! about them. [`diagnose`] sorts a [`CloudError`] into a few causes a user
+//! can act on and, for credential problems, names the environment variables
+//! the provider reads.
+
+use crate::CloudError;
+use std::env;
+use std::io;
+
+/// Broad reason a cloud request failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cause {
+    /// No credentials were found in the environment
+    MissingCredentials,
+    /// Credentials were found but the provider rejected them
+    InvalidCredentials,
+    /// The credentials do not grant access to the bucket or object
+    PermissionDenied,
+    /// The bucket or object does not exist
+    NotFound,
+    /// The URL is not a supported cloud location
+    InvalidPath,
+    /// Anything else, such as a network failure
+    Other,
+}
+
+/// What went wrong with a cloud request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis {
+    /// Broad reason for the failure
+    pub cause: Cause,
+    /// Provider scheme, such as `s3`
+    pub scheme: String,
+    /// Environment variables the provider reads credentials from
+    pub credential_vars: &'static [&'static str],
+}
+
+impl Diagnosis {
+    /// Whether setting up credentials is likely to fix the failure
+    pub fn is_credentials(&self) -> bool {
+        matches!(
+            self.cause,
+            Cause::MissingCredentials | Cause::InvalidCredentials | Cause::PermissionDenied
+        )
+    }
+}
+
+/// Environment variables a provider reads credentials from
+pub fn credential_vars(scheme: &str) -> &'static [&'static str] {
+    match scheme {
+        "s3" => &["AWS_ACCESS_KEY_ID", "AWS_SECRET_ACCESS_KEY"],
+        "gs" => &["GOOGLE_APPLICATION_CREDENTIALS", "GOOGLE_SERVICE_ACCOUNT"],
+        "az" | "azblob" => &[
+            "AZURE_STORAGE_ACCOUNT_NAME",
+            "AZURE_STORAGE_ACCOUNT_KEY",
+            "AZURE_STORAGE_SAS_TOKEN",
+        ],
+        _ => &[],
+    }
+}
+
+/// Whether the environment holds credentials for `scheme`
+///
+/// Providers can also find credentials elsewhere, such as an instance
+/// metadata service, so `false` only means none were set explicitly.
+pub fn credentials_configured(scheme: &str) -> bool {
+    let set = |name: &str| env::var_os(name).is_some();
+    match scheme {
+        "s3" => set("AWS_ACCESS_KEY_ID") && set("AWS_SECRET_ACCESS_KEY"),
+        "gs" => set("GOOGLE_APPLICATION_CREDENTIALS") || set("GOOGLE_SERVICE_ACCOUNT"),
+        "az" | "azblob" => {
+            set("AZURE_STORAGE_ACCOUNT_NAME")
+                && (set("AZURE_STORAGE_ACCOUNT_KEY") || set("AZURE_STORAGE_SAS_TOKEN"))
+        }
+        _ => true,
+    }
+}
+
+/// Work out why a request to a `scheme` location failed
+pub fn diagnose(error: &CloudError, scheme: &str) -> Diagnosis {
+    let configured = credentials_configured(scheme);
+    let auth_failure = if configured {
+        Cause::InvalidCredentials
+    } else {
+        Cause::MissingCredentials
+    };
+
+    let cause = match error {
+        CloudError::ObjectStore(error) => match error {
+            object_store::Error::Unauthenticated { .. } => auth_failure,
+            object_store::Error::PermissionDenied { .. } if !configured => {
+                Cause::MissingCredentials
+            }
+            object_store::Error::PermissionDenied { .. } => Cause::PermissionDenied,
+            object_store::Error::NotFound { .. } => Cause::NotFound,
+            object_store::Error::Generic { source, .. } if mentions_auth(&source.to_string()) => {
+                auth_failure
+            }
+            _ => Cause::Other,
+        },
+        CloudError::InvalidPath(_) => Cause::InvalidPath,
+        CloudError::Io(error) => return diagnose_io(error, scheme),
+        _ => Cause::Other,
+    };
+
+    Diagnosis {
+        cause,
+        scheme: scheme.to_string(),
+        credential_vars: credential_vars(scheme),
+    }
+}
+
+/// Like [`diagnose`], for an I/O error from a [`crate::CloudReader`] or
+/// [`crate::CloudWriter`]
+pub fn diagnose_io(error: &io::Error, scheme: &str) -> Diagnosis {
+    match error
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<CloudError>())
+    {
+        Some(inner) => diagnose(inner, scheme),
+        None => Diagnosis {
+            cause: Cause::Other,
+            scheme: scheme.to_string(),
+            credential_vars: credential_vars(scheme),
+        },
+    }
+}
+
+/// Whether a provider's error text points at credentials
+///
+/// Token and signature failures from the providers end up as generic errors.
+fn mentions_auth(message: &str) -> bool {
+    let message = message.to_lowercase();
+    [
+        "credential",
+        "token",
+        "signature",
+        "403",
+        "401",
+        "unauthorized",
+        "forbidden",
+    ]
+    .iter()
+    .any(|needle| message.contains(needle))
+}
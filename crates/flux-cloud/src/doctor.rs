@@ -0,0 +1,278 @@
+//! Checks that a cloud location works for flux
+//!
+//! When a cloud operation fails, the error rarely says which part of the
+//! setup is wrong. [`check_location`] makes the requests flux relies on one
+//! at a time against a location: connecting, listing, writing, reading and
+//! deleting a small probe object, and a multipart upload of it. Each step is
+//! reported as a [`Check`] with advice on what to fix when it fails.
+
+use crate::diagnostics::{credential_vars, credentials_configured, diagnose, Cause};
+use crate::metrics::Operation;
+use crate::{CloudError, CloudPath, CloudStore};
+use object_store::path::Path;
+use object_store::PutPayload;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Name of the probe objects, below the checked prefix
+const PROBE_NAME: &str = ".flux-doctor";
+
+/// How a check went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    /// Worked, or might work, but something deserves attention
+    Warn,
+    Fail,
+    /// Not run, because a check it depends on failed
+    Skip,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Status::Pass => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "FAIL",
+            Status::Skip => "skip",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The result of one check
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    /// What was checked, such as `write`
+    pub name: &'static str,
+    pub status: Status,
+    /// What happened
+    pub detail: String,
+    /// What to do about a failure or warning
+    pub hint: Option<String>,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: Status::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn skip(name: &'static str, detail: impl Into<String>) -> Self {
+        Check {
+            name,
+            status: Status::Skip,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    /// A failed check, with advice from what the error says about the cause
+    fn fail(name: &'static str, error: &CloudError, scheme: &str) -> Self {
+        Check {
+            name,
+            status: Status::Fail,
+            detail: error.to_string(),
+            hint: hint(diagnose(error, scheme).cause, scheme),
+        }
+    }
+}
+
+/// Connect to `location`, through `endpoint` if given, and run every check
+/// against it
+pub fn check_location(location: &CloudPath, endpoint: Option<&str>) -> Vec<Check> {
+    let mut checks = vec![check_credentials(&location.scheme)];
+    match CloudStore::with_endpoint(location, endpoint) {
+        Ok(store) => {
+            checks.push(Check::pass("connect", format!("Configured {}", location)));
+            checks.extend(check_store(&store, location));
+        }
+        Err(e) => checks.push(Check::fail("connect", &e, &location.scheme)),
+    }
+    checks
+}
+
+/// Run the checks that need requests against an already connected store
+///
+/// The probe objects are written below `location.path` and deleted again.
+pub fn check_store(store: &CloudStore, location: &CloudPath) -> Vec<Check> {
+    let scheme = location.scheme.as_str();
+    let mut checks = Vec::new();
+
+    let prefix = (!location.path.as_ref().is_empty()).then_some(&location.path);
+    checks.push(match store.list(prefix) {
+        Ok(listing) => Check::pass(
+            "list",
+            format!(
+                "Found {} objects and {} folders",
+                listing.objects.len(),
+                listing.common_prefixes.len()
+            ),
+        ),
+        Err(e) => {
+            let mut check = Check::fail("list", &e, scheme);
+            check.hint.get_or_insert_with(|| {
+                "Check the network, proxy settings and the endpoint or region variables".to_string()
+            });
+            check
+        }
+    });
+
+    let probe = probe_path(&location.path);
+    let written = put(store, &probe);
+    checks.push(match &written {
+        Ok(()) => Check::pass("write", format!("Wrote {}", probe)),
+        Err(e) => Check::fail("write", e, scheme),
+    });
+
+    if written.is_ok() {
+        checks.push(match store.head(&probe) {
+            Ok(meta) => Check::pass("read", format!("Read back {} bytes", meta.size)),
+            Err(e) => Check::fail("read", &e, scheme),
+        });
+        checks.push(check_delete(store, &probe, scheme));
+    } else {
+        checks.push(Check::skip("read", "Nothing was written to read"));
+        checks.push(Check::skip("delete", "Nothing was written to delete"));
+    }
+
+    checks.push(if written.is_ok() {
+        check_multipart(store, &probe, scheme)
+    } else {
+        Check::skip("multipart", "Uploads need write access")
+    });
+    checks
+}
+
+/// Whether credentials are set where the provider looks first
+fn check_credentials(scheme: &str) -> Check {
+    let vars = credential_vars(scheme).join(", ");
+    if credentials_configured(scheme) {
+        return Check::pass("credentials", format!("Found {}", vars));
+    }
+    Check {
+        name: "credentials",
+        status: Status::Warn,
+        detail: format!("None set of {}", vars),
+        hint: Some(
+            "The provider may still find credentials in its config files or an instance \
+             metadata service; if the checks below fail, set these variables"
+                .to_string(),
+        ),
+    }
+}
+
+fn check_delete(store: &CloudStore, probe: &Path, scheme: &str) -> Check {
+    match store.delete(probe) {
+        Ok(()) => Check::pass("delete", format!("Deleted {}", probe)),
+        Err(e) => {
+            let mut check = Check::fail("delete", &e, scheme);
+            let leftover = format!("Remove {} by hand", probe);
+            check.hint = Some(match check.hint {
+                Some(hint) => format!("{}. {}", hint, leftover),
+                None => leftover,
+            });
+            check
+        }
+    }
+}
+
+/// Upload the probe again in one part through the multipart API, which
+/// archives larger than a single request are sent with
+fn check_multipart(store: &CloudStore, probe: &Path, scheme: &str) -> Check {
+    let Some(multipart) = store.multipart_store() else {
+        return Check {
+            name: "multipart",
+            status: Status::Warn,
+            detail: "The store cannot track uploads by id".to_string(),
+            hint: Some(
+                "Large archives still upload, but an interrupted upload cannot be found \
+                 and aborted later"
+                    .to_string(),
+            ),
+        };
+    };
+
+    let result = store
+        .block_on_instrumented(Operation::StartMultipart, probe, 0, async {
+            let id = multipart.create_multipart(probe).await?;
+            let payload = PutPayload::from_static(b"flux doctor multipart probe");
+            let uploaded = async {
+                let part = multipart.put_part(probe, &id, 0, payload).await?;
+                multipart.complete_multipart(probe, &id, vec![part]).await
+            }
+            .await;
+            if uploaded.is_err() {
+                let _ = multipart.abort_multipart(probe, &id).await;
+            }
+            uploaded
+        })
+        .map_err(CloudError::ObjectStore);
+
+    match result {
+        Ok(_) => {
+            let _ = store.delete(probe);
+            Check::pass("multipart", "Started, sent and completed an upload")
+        }
+        Err(e) => {
+            let mut check = Check::fail("multipart", &e, scheme);
+            check.hint.get_or_insert_with(|| {
+                "Archives over 16 MiB are uploaded in parts; allow multipart uploads for \
+                 these credentials"
+                    .to_string()
+            });
+            check
+        }
+    }
+}
+
+fn put(store: &CloudStore, probe: &Path) -> crate::Result<()> {
+    let payload = PutPayload::from_static(b"flux doctor probe");
+    store
+        .block_on_instrumented(
+            Operation::Put,
+            probe,
+            payload.content_length() as u64,
+            store.store().put(probe, payload),
+        )
+        .map(|_| ())
+        .map_err(CloudError::ObjectStore)
+}
+
+/// A key below `prefix` that no other run is using
+fn probe_path(prefix: &Path) -> Path {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_nanos())
+        .unwrap_or_default();
+    let name = format!("{}-{}-{}", PROBE_NAME, std::process::id(), nanos);
+    if prefix.as_ref().is_empty() {
+        Path::from(name)
+    } else {
+        prefix.child(name)
+    }
+}
+
+/// Advice for a failure with `cause`
+fn hint(cause: Cause, scheme: &str) -> Option<String> {
+    let vars = credential_vars(scheme).join(", ");
+    match cause {
+        Cause::MissingCredentials => Some(format!("No credentials were found; set {}", vars)),
+        Cause::InvalidCredentials => Some(format!(
+            "The provider rejected the credentials; check {} and that they have not expired",
+            vars
+        )),
+        Cause::PermissionDenied => Some(
+            "The credentials are valid but not allowed this request; grant it in the bucket \
+             or IAM policy"
+                .to_string(),
+        ),
+        Cause::NotFound => Some("Check the bucket name and that it exists".to_string()),
+        Cause::InvalidPath => Some("Use a URL such as s3://bucket/prefix/".to_string()),
+        Cause::Other => None,
+    }
+}
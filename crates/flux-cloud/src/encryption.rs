@@ -0,0 +1,278 @@
+//! Client-side encryption of objects
+//!
+//! [`EncryptingWriter`] encrypts what is written through it before it
+//! reaches the inner writer, usually a [`crate::CloudWriter`], so an archive
+//! leaves the machine encrypted whatever the provider does at rest.
+//! [`DecryptingReader`] reads it back with random access, so a zip can
+//! still be listed from its end. Both take a key from the key store,
+//! [`flux_core::keys`].
+//!
+//! The plaintext is cut into chunks, each sealed with AES-256-GCM under a
+//! key derived for the object from the store key and a random salt. The
+//! nonce of a chunk is its index and whether it is the last one, so chunks
+//! cannot be reordered, and a stream cut short is told apart from a
+//! shorter one. The layout is
+//!
+//! ```text
+//! magic (8) | chunk size (4, big endian) | salt (16) | chunk | ... | last chunk
+//! ```
+//!
+//! where every chunk is its ciphertext followed by a 16-byte tag, all but
+//! the last holding a full chunk of plaintext, and the header is
+//! authenticated along with each of them.
+
+use crate::{CloudError, Result};
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use flux_core::keys::Key;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+const MAGIC: &[u8; 8] = b"FLUXCSE1";
+/// Plaintext bytes in every chunk but the last
+const CHUNK_SIZE: usize = 64 * 1024;
+const SALT_LEN: usize = 16;
+const TAG_LEN: usize = 16;
+const HEADER_LEN: usize = MAGIC.len() + 4 + SALT_LEN;
+/// Mixed into the object key so it differs from keys the store key is
+/// used for elsewhere
+const KEY_CONTEXT: &[u8] = b"flux-cloud object encryption";
+
+/// Whether `head`, the start of an object, was written by
+/// [`EncryptingWriter`]
+pub fn is_encrypted(head: &[u8]) -> bool {
+    head.starts_with(MAGIC)
+}
+
+/// Cipher for the object with `salt`, keyed from `key`
+fn object_cipher(key: &Key, salt: &[u8]) -> Aes256Gcm {
+    let mut mac =
+        <Hmac<Sha256> as Mac>::new_from_slice(key.secret()).expect("HMAC takes any key length");
+    mac.update(KEY_CONTEXT);
+    mac.update(salt);
+    Aes256Gcm::new(&mac.finalize().into_bytes())
+}
+
+/// Nonce of chunk `index`
+fn chunk_nonce(index: u64, last: bool) -> Result<[u8; 12]> {
+    let index = u32::try_from(index)
+        .map_err(|_| CloudError::Encryption("object too large to encrypt".to_string()))?;
+    let mut nonce = [0u8; 12];
+    nonce[7..11].copy_from_slice(&index.to_be_bytes());
+    nonce[11] = u8::from(last);
+    Ok(nonce)
+}
+
+/// A writer that encrypts everything written to it
+///
+/// [`EncryptingWriter::finish`] seals the last chunk and hands back the
+/// inner writer, which still has to be finished itself. Dropped without
+/// it, the stream has no last chunk and fails to decrypt rather than
+/// reading as a shorter object.
+pub struct EncryptingWriter<W: Write> {
+    inner: W,
+    cipher: Aes256Gcm,
+    header: [u8; HEADER_LEN],
+    /// Plaintext not sealed yet
+    buffer: Vec<u8>,
+    /// Index of the next chunk
+    chunk: u64,
+}
+
+impl<W: Write> EncryptingWriter<W> {
+    /// Start an encrypted object in `inner`, keyed from `key`
+    pub fn new(mut inner: W, key: &Key) -> Result<Self> {
+        let mut salt = [0u8; SALT_LEN];
+        getrandom::fill(&mut salt)
+            .map_err(|e| CloudError::Encryption(format!("No randomness for a salt: {}", e)))?;
+
+        let mut header = [0u8; HEADER_LEN];
+        header[..8].copy_from_slice(MAGIC);
+        header[8..12].copy_from_slice(&(CHUNK_SIZE as u32).to_be_bytes());
+        header[12..].copy_from_slice(&salt);
+        inner.write_all(&header)?;
+
+        Ok(Self {
+            inner,
+            cipher: object_cipher(key, &salt),
+            header,
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+            chunk: 0,
+        })
+    }
+
+    /// Seal the last chunk and return the inner writer
+    pub fn finish(mut self) -> Result<W> {
+        let rest = std::mem::take(&mut self.buffer);
+        self.seal(&rest, true)?;
+        Ok(self.inner)
+    }
+
+    fn seal(&mut self, plaintext: &[u8], last: bool) -> Result<()> {
+        let nonce = chunk_nonce(self.chunk, last)?;
+        let payload = Payload {
+            msg: plaintext,
+            aad: &self.header,
+        };
+        let sealed = self
+            .cipher
+            .encrypt(&Nonce::from(nonce), payload)
+            .map_err(|_| CloudError::Encryption("Failed to encrypt a chunk".to_string()))?;
+        self.inner.write_all(&sealed)?;
+        self.chunk += 1;
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for EncryptingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        // A full chunk is only sealed once more follows, since the last
+        // chunk is sealed differently
+        while self.buffer.len() > CHUNK_SIZE {
+            let rest = self.buffer.split_off(CHUNK_SIZE);
+            let full = std::mem::replace(&mut self.buffer, rest);
+            self.seal(&full, false)?;
+        }
+        Ok(buf.len())
+    }
+
+    /// Flush what has been sealed; the chunk being filled stays buffered
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A reader of objects written by [`EncryptingWriter`], with seeking
+///
+/// Every chunk is checked as it is read, so reading a changed or truncated
+/// object, or one encrypted with another key, fails with
+/// [`CloudError::Encryption`].
+pub struct DecryptingReader<R: Read + Seek> {
+    inner: R,
+    cipher: Aes256Gcm,
+    header: [u8; HEADER_LEN],
+    chunk_size: u64,
+    chunks: u64,
+    /// Plaintext size
+    size: u64,
+    position: u64,
+    /// The chunk last decrypted, by index
+    current: Option<(u64, Vec<u8>)>,
+}
+
+impl<R: Read + Seek> DecryptingReader<R> {
+    /// Read the encrypted object in `inner` with `key`
+    pub fn new(mut inner: R, key: &Key) -> Result<Self> {
+        let invalid = || CloudError::Encryption("Not an encrypted object".to_string());
+
+        let len = inner.seek(SeekFrom::End(0))?;
+        inner.seek(SeekFrom::Start(0))?;
+        let mut header = [0u8; HEADER_LEN];
+        inner.read_exact(&mut header).map_err(|_| invalid())?;
+        if !is_encrypted(&header) {
+            return Err(invalid());
+        }
+        let chunk_size = u64::from(u32::from_be_bytes(header[8..12].try_into().unwrap()));
+        if chunk_size == 0 {
+            return Err(invalid());
+        }
+
+        // Every chunk carries a tag, and there is always a last chunk
+        let body = len - HEADER_LEN as u64;
+        let sealed_chunk = chunk_size + TAG_LEN as u64;
+        let chunks = body.div_ceil(sealed_chunk).max(1);
+        let sealed_last = body
+            .checked_sub((chunks - 1) * sealed_chunk)
+            .filter(|&last| last >= TAG_LEN as u64)
+            .ok_or_else(|| CloudError::Encryption("Encrypted object is truncated".to_string()))?;
+        let size = (chunks - 1) * chunk_size + sealed_last - TAG_LEN as u64;
+
+        Ok(Self {
+            cipher: object_cipher(key, &header[12..]),
+            inner,
+            header,
+            chunk_size,
+            chunks,
+            size,
+            position: 0,
+            current: None,
+        })
+    }
+
+    /// Size of the plaintext in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Decrypt chunk `index` unless it is the current one
+    fn load(&mut self, index: u64) -> Result<&[u8]> {
+        if self
+            .current
+            .as_ref()
+            .is_none_or(|(current, _)| *current != index)
+        {
+            let sealed_chunk = self.chunk_size + TAG_LEN as u64;
+            let start = HEADER_LEN as u64 + index * sealed_chunk;
+            let last = index + 1 == self.chunks;
+            let len = if last {
+                self.size - index * self.chunk_size + TAG_LEN as u64
+            } else {
+                sealed_chunk
+            };
+
+            let mut sealed = vec![0u8; len as usize];
+            self.inner.seek(SeekFrom::Start(start))?;
+            self.inner.read_exact(&mut sealed)?;
+            let nonce = chunk_nonce(index, last)?;
+            let payload = Payload {
+                msg: &sealed,
+                aad: &self.header,
+            };
+            let plaintext = self
+                .cipher
+                .decrypt(&Nonce::from(nonce), payload)
+                .map_err(|_| {
+                    CloudError::Encryption(
+                        "Cannot decrypt: wrong key, or the object was changed".to_string(),
+                    )
+                })?;
+            self.current = Some((index, plaintext));
+        }
+        Ok(self
+            .current
+            .as_ref()
+            .map(|(_, data)| data.as_slice())
+            .unwrap())
+    }
+}
+
+impl<R: Read + Seek> Read for DecryptingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.size || buf.is_empty() {
+            return Ok(0);
+        }
+        let index = self.position / self.chunk_size;
+        let offset = (self.position % self.chunk_size) as usize;
+        let chunk = self.load(index)?;
+        let n = buf.len().min(chunk.len() - offset);
+        buf[..n].copy_from_slice(&chunk[offset..offset + n]);
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Read + Seek> Seek for DecryptingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.size.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        self.position = target.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "Cannot seek before start")
+        })?;
+        Ok(self.position)
+    }
+}
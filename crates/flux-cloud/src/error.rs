@@ -16,6 +16,15 @@ pub enum CloudError {
 
     #[error("Buffer size exceeded: {0} bytes")]
     BufferSizeExceeded(usize),
+
+    #[error("Object {0} changed while it was being read")]
+    ObjectChanged(String),
+
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
+    #[error("Archive error: {0}")]
+    Archive(#[from] flux_core::Error),
 }
 
 pub type Result<T> = std::result::Result<T, CloudError>;
@@ -4,15 +4,31 @@
 //! appear as standard `Read`, `Write`, and `Seek` implementations that can be used
 //! directly with flux-core's synchronous APIs.
 
+pub mod archive_store;
+pub mod chain;
+pub mod diagnostics;
+pub mod doctor;
+pub mod encryption;
 pub mod error;
+pub mod metrics;
+pub mod parts;
 pub mod reader;
 pub mod store;
+pub mod uploads;
 pub mod writer;
 
+pub use archive_store::ArchiveStore;
+pub use chain::{BackupChain, ChainLink};
+pub use diagnostics::{diagnose, Cause, Diagnosis};
+pub use encryption::{DecryptingReader, EncryptingWriter};
 pub use error::{CloudError, Result};
+pub use metrics::{AccessPattern, MetricsSnapshot, TransferMetrics};
+pub use parts::{split_parts, PartsReader, SplitArchive, SplitKind};
 pub use reader::CloudReader;
 pub use store::{CloudPath, CloudStore};
-pub use writer::CloudWriter;
+pub use uploads::{abort_upload, pending_uploads, PendingUpload};
+pub use writer::{CloudWriter, CloudWriterGuard};
 
 // Re-export commonly used types
-pub use object_store::{ObjectMeta, ObjectStore};
+pub use object_store::path::Path as ObjectPath;
+pub use object_store::{ListResult, ObjectMeta, ObjectStore};
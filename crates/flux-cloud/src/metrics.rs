@@ -0,0 +1,276 @@
+//! Transfer metrics for cloud storage operations
+//!
+//! Every request issued through a [`CloudStore`](crate::CloudStore) is timed and
+//! recorded here, and wrapped in a `cloud_request` tracing span. Operators can read
+//! aggregate counters via [`TransferMetrics::snapshot`] or register a callback to
+//! forward individual events to their own monitoring system.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Maximum number of latency samples kept for percentile calculation
+const MAX_LATENCY_SAMPLES: usize = 4096;
+
+/// Kind of request sent to the object store
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Head,
+    Get,
+    List,
+    Put,
+    Delete,
+    StartMultipart,
+    PutPart,
+    CompleteMultipart,
+    AbortMultipart,
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Operation::Head => "head",
+            Operation::Get => "get",
+            Operation::List => "list",
+            Operation::Put => "put",
+            Operation::Delete => "delete",
+            Operation::StartMultipart => "start_multipart",
+            Operation::PutPart => "put_part",
+            Operation::CompleteMultipart => "complete_multipart",
+            Operation::AbortMultipart => "abort_multipart",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// How a read reached the range it requested
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccessPattern {
+    /// Carrying on where the previous range ended
+    Sequential,
+    /// After a seek somewhere else
+    Random,
+}
+
+impl fmt::Display for AccessPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccessPattern::Sequential => write!(f, "sequential"),
+            AccessPattern::Random => write!(f, "random"),
+        }
+    }
+}
+
+/// A single completed request, passed to the metrics callback
+#[derive(Debug, Clone)]
+pub struct TransferEvent {
+    pub operation: Operation,
+    /// Payload bytes moved by this request
+    pub bytes: u64,
+    /// Wall-clock time spent waiting on the request
+    pub duration: Duration,
+    pub success: bool,
+}
+
+/// Callback invoked for every recorded request
+pub type MetricsCallback = Arc<dyn Fn(&TransferEvent) + Send + Sync>;
+
+/// Thread-safe accumulator for cloud transfer statistics
+#[derive(Default)]
+pub struct TransferMetrics {
+    requests: AtomicU64,
+    failures: AtomicU64,
+    retries: AtomicU64,
+    bytes_downloaded: AtomicU64,
+    bytes_uploaded: AtomicU64,
+    sequential_reads: AtomicU64,
+    random_reads: AtomicU64,
+    latencies: Mutex<VecDeque<Duration>>,
+    callback: Option<MetricsCallback>,
+}
+
+/// Point-in-time copy of the collected metrics
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    pub requests: u64,
+    pub failures: u64,
+    pub retries: u64,
+    pub bytes_downloaded: u64,
+    pub bytes_uploaded: u64,
+    /// Ranges read carrying on from the previous one
+    pub sequential_reads: u64,
+    /// Ranges read after a seek
+    pub random_reads: u64,
+    pub latency_p50: Option<Duration>,
+    pub latency_p95: Option<Duration>,
+    pub latency_p99: Option<Duration>,
+}
+
+impl TransferMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create metrics that also forward every event to `callback`
+    pub fn with_callback(callback: MetricsCallback) -> Self {
+        TransferMetrics {
+            callback: Some(callback),
+            ..Self::default()
+        }
+    }
+
+    /// Record a completed request
+    pub fn record(&self, operation: Operation, bytes: u64, duration: Duration, success: bool) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if success {
+            match operation {
+                Operation::Get => {
+                    self.bytes_downloaded.fetch_add(bytes, Ordering::Relaxed);
+                }
+                Operation::Put | Operation::PutPart => {
+                    self.bytes_uploaded.fetch_add(bytes, Ordering::Relaxed);
+                }
+                _ => {}
+            }
+        } else {
+            self.failures.fetch_add(1, Ordering::Relaxed);
+        }
+
+        if let Ok(mut latencies) = self.latencies.lock() {
+            if latencies.len() == MAX_LATENCY_SAMPLES {
+                latencies.pop_front();
+            }
+            latencies.push_back(duration);
+        }
+
+        tracing::debug!(
+            op = %operation,
+            bytes,
+            latency_ms = duration.as_millis() as u64,
+            success,
+            "cloud request finished"
+        );
+
+        if let Some(ref callback) = self.callback {
+            callback(&TransferEvent {
+                operation,
+                bytes,
+                duration,
+                success,
+            });
+        }
+    }
+
+    /// Record that a request is being retried
+    pub fn record_retry(&self, operation: Operation) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+        tracing::debug!(op = %operation, "retrying cloud request");
+    }
+
+    /// Record how a reader reached the range it is about to request
+    pub fn record_access(&self, pattern: AccessPattern) {
+        let counter = match pattern {
+            AccessPattern::Sequential => &self.sequential_reads,
+            AccessPattern::Random => &self.random_reads,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Take a consistent-enough copy of the current counters
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let mut samples: Vec<Duration> = self
+            .latencies
+            .lock()
+            .map(|l| l.iter().copied().collect())
+            .unwrap_or_default();
+        samples.sort_unstable();
+
+        MetricsSnapshot {
+            requests: self.requests.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            bytes_downloaded: self.bytes_downloaded.load(Ordering::Relaxed),
+            bytes_uploaded: self.bytes_uploaded.load(Ordering::Relaxed),
+            sequential_reads: self.sequential_reads.load(Ordering::Relaxed),
+            random_reads: self.random_reads.load(Ordering::Relaxed),
+            latency_p50: percentile(&samples, 50),
+            latency_p95: percentile(&samples, 95),
+            latency_p99: percentile(&samples, 99),
+        }
+    }
+}
+
+impl fmt::Debug for TransferMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TransferMetrics")
+            .field("snapshot", &self.snapshot())
+            .field("callback", &self.callback.is_some())
+            .finish()
+    }
+}
+
+/// Nearest-rank percentile over sorted samples
+fn percentile(sorted: &[Duration], pct: usize) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (pct * sorted.len()).div_ceil(100).max(1);
+    Some(sorted[rank - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn test_counters() {
+        let metrics = TransferMetrics::new();
+        metrics.record(Operation::Get, 100, Duration::from_millis(5), true);
+        metrics.record(Operation::PutPart, 40, Duration::from_millis(5), true);
+        metrics.record(Operation::Get, 100, Duration::from_millis(5), false);
+        metrics.record_retry(Operation::Get);
+        metrics.record_access(AccessPattern::Sequential);
+        metrics.record_access(AccessPattern::Random);
+        metrics.record_access(AccessPattern::Random);
+
+        let snap = metrics.snapshot();
+        assert_eq!(snap.requests, 3);
+        assert_eq!(snap.failures, 1);
+        assert_eq!(snap.retries, 1);
+        assert_eq!(snap.bytes_downloaded, 100);
+        assert_eq!(snap.bytes_uploaded, 40);
+        assert_eq!(snap.sequential_reads, 1);
+        assert_eq!(snap.random_reads, 2);
+    }
+
+    #[test]
+    fn test_latency_percentiles() {
+        let metrics = TransferMetrics::new();
+        assert_eq!(metrics.snapshot().latency_p50, None);
+
+        for ms in 1..=100 {
+            metrics.record(Operation::Head, 0, Duration::from_millis(ms), true);
+        }
+
+        let snap = metrics.snapshot();
+        assert_eq!(snap.latency_p50, Some(Duration::from_millis(50)));
+        assert_eq!(snap.latency_p95, Some(Duration::from_millis(95)));
+        assert_eq!(snap.latency_p99, Some(Duration::from_millis(99)));
+    }
+
+    #[test]
+    fn test_callback_invoked() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let calls_clone = calls.clone();
+        let metrics = TransferMetrics::with_callback(Arc::new(move |event| {
+            assert_eq!(event.operation, Operation::Put);
+            calls_clone.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        metrics.record(Operation::Put, 10, Duration::from_millis(1), true);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
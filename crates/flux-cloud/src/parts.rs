@@ -0,0 +1,252 @@
+//! Archives stored as several objects
+//!
+//! Two kinds are found next to the object a URL names:
+//!
+//! ```text
+//! backups/photos.tar.001  backups/photos.tar.002  ...   parts of one file
+//! backups/photos.z01      backups/photos.z02      backups/photos.zip
+//! ```
+//!
+//! Parts are a file cut into pieces and read back to back; the volumes of a
+//! spanned zip stay separate files, read by flux-core as one archive. Either
+//! way the objects are fetched several at a time rather than one after the
+//! other: [`PartsReader`] keeps a bounded window of parts downloading ahead
+//! of what is read, and [`download_parts`] writes up to that many at once.
+
+use crate::metrics::Operation;
+use crate::{CloudError, CloudStore, Result};
+use bytes::Bytes;
+use futures_util::{StreamExt, TryStreamExt};
+use object_store::path::Path;
+use object_store::ObjectMeta;
+use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::time::Instant;
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+/// Parts downloaded at once unless told otherwise
+pub const DEFAULT_WINDOW: usize = 4;
+
+/// How the objects of a split archive make up the archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitKind {
+    /// `name.001`, `name.002`, ..., joined back to back
+    Parts,
+    /// `name.z01`, `name.z02`, ..., `name.zip`, volumes of one zip
+    Spanned,
+}
+
+/// The objects of a split archive, in order
+#[derive(Debug, Clone)]
+pub struct SplitArchive {
+    pub kind: SplitKind,
+    pub parts: Vec<ObjectMeta>,
+}
+
+impl SplitArchive {
+    /// Bytes in all the parts together
+    pub fn size(&self) -> u64 {
+        self.parts.iter().map(|part| part.size as u64).sum()
+    }
+
+    /// Name of the archive the parts make up, such as `photos.tar` for
+    /// parts or `photos.zip` for a spanned zip
+    pub fn name(&self) -> String {
+        let last = self
+            .parts
+            .last()
+            .and_then(|part| part.location.filename())
+            .unwrap_or_default();
+        match self.kind {
+            SplitKind::Parts => {
+                let first = self.parts[0].location.filename().unwrap_or_default();
+                first
+                    .rsplit_once('.')
+                    .map_or(first, |(name, _)| name)
+                    .to_string()
+            }
+            SplitKind::Spanned => last.to_string(),
+        }
+    }
+}
+
+/// The split archive `path` belongs to, or `None` when it stands alone
+///
+/// `path` is the first part, `name.001`, or the last volume of a spanned
+/// zip, `name.zip`. The parts are the objects next to it numbered on from
+/// there without a gap.
+pub fn split_parts(store: &CloudStore, path: &Path) -> Result<Option<SplitArchive>> {
+    let Some(name) = path.filename() else {
+        return Ok(None);
+    };
+    let Some((stem, extension)) = name.rsplit_once('.') else {
+        return Ok(None);
+    };
+    let kind = if extension == "001" {
+        SplitKind::Parts
+    } else if extension.eq_ignore_ascii_case("zip") {
+        SplitKind::Spanned
+    } else {
+        return Ok(None);
+    };
+
+    let folder: Path = path.parts().take(path.parts().count() - 1).collect();
+    let prefix = (folder.as_ref() != "").then_some(&folder);
+    let siblings = store.list(prefix)?.objects;
+    let sibling = |name: &str| {
+        siblings
+            .iter()
+            .find(|meta| meta.location.filename() == Some(name))
+            .cloned()
+    };
+
+    let mut parts = Vec::new();
+    match kind {
+        SplitKind::Parts => {
+            for number in 1.. {
+                match sibling(&format!("{}.{:03}", stem, number)) {
+                    Some(meta) => parts.push(meta),
+                    None => break,
+                }
+            }
+        }
+        SplitKind::Spanned => {
+            let z = if extension == "ZIP" { "Z" } else { "z" };
+            for number in 1.. {
+                match sibling(&format!("{}.{}{:02}", stem, z, number)) {
+                    Some(meta) => parts.push(meta),
+                    None => break,
+                }
+            }
+            if parts.is_empty() {
+                return Ok(None);
+            }
+            parts.push(store.head(path)?);
+        }
+    }
+    debug!("{} is split into {} objects", path, parts.len());
+    Ok(Some(SplitArchive { kind, parts }))
+}
+
+/// Download `path` whole, recording it in the store's metrics
+async fn fetch(store: CloudStore, path: Path) -> std::result::Result<Bytes, object_store::Error> {
+    let start = Instant::now();
+    let result = match store.store().get(&path).await {
+        Ok(got) => got.bytes().await,
+        Err(e) => Err(e),
+    };
+    let bytes = result.as_ref().map_or(0, |data| data.len() as u64);
+    store
+        .metrics()
+        .record(Operation::Get, bytes, start.elapsed(), result.is_ok());
+    result
+}
+
+/// The parts of an archive read back to back as one stream
+///
+/// Up to `window` parts download at once, ahead of the one being read, and
+/// only those are held in memory, so a slow part does not hold up the ones
+/// after it and memory stays bounded by the window whatever the number of
+/// parts.
+pub struct PartsReader {
+    store: CloudStore,
+    /// Parts not asked for yet
+    pending: VecDeque<Path>,
+    /// Parts downloading, in order
+    downloading: VecDeque<JoinHandle<std::result::Result<Bytes, object_store::Error>>>,
+    current: Bytes,
+    window: usize,
+}
+
+impl PartsReader {
+    /// Read `parts` in order, downloading up to `window` at once
+    pub fn new(store: CloudStore, parts: Vec<Path>, window: usize) -> Self {
+        let mut reader = Self {
+            store,
+            pending: parts.into(),
+            downloading: VecDeque::new(),
+            current: Bytes::new(),
+            window: window.max(1),
+        };
+        reader.fill_window();
+        reader
+    }
+
+    fn fill_window(&mut self) {
+        while self.downloading.len() < self.window {
+            let Some(path) = self.pending.pop_front() else {
+                break;
+            };
+            let fetching = fetch(self.store.clone(), path);
+            self.downloading
+                .push_back(self.store.runtime().spawn(fetching));
+        }
+    }
+
+    /// Wait for the next part, or `None` after the last
+    fn next_part(&mut self) -> Result<Option<Bytes>> {
+        let Some(handle) = self.downloading.pop_front() else {
+            return Ok(None);
+        };
+        self.fill_window();
+        let data = self
+            .store
+            .runtime()
+            .block_on(handle)
+            .map_err(|e| CloudError::Runtime(format!("Part download failed: {}", e)))??;
+        Ok(Some(data))
+    }
+}
+
+impl Read for PartsReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.current.is_empty() {
+            match self.next_part()? {
+                Some(data) => self.current = data,
+                None => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.current.len());
+        buf[..n].copy_from_slice(&self.current.split_to(n));
+        Ok(n)
+    }
+}
+
+impl Drop for PartsReader {
+    fn drop(&mut self) {
+        for handle in &self.downloading {
+            handle.abort();
+        }
+    }
+}
+
+/// Download each of `parts` into `dir` under its own file name, up to
+/// `window` at once, returning the files in the order of `parts`
+pub fn download_parts(
+    store: &CloudStore,
+    parts: &[Path],
+    dir: &std::path::Path,
+    window: usize,
+) -> Result<Vec<PathBuf>> {
+    let files: Vec<PathBuf> = parts
+        .iter()
+        .map(|part| dir.join(part.filename().unwrap_or("part")))
+        .collect();
+    let downloads = parts.iter().zip(&files).map(|(part, file)| {
+        let fetching = fetch(store.clone(), part.clone());
+        async move {
+            let data = fetching.await.map_err(CloudError::ObjectStore)?;
+            fs::write(file, &data)?;
+            Ok::<_, CloudError>(())
+        }
+    });
+    store.runtime().block_on(
+        futures_util::stream::iter(downloads)
+            .buffer_unordered(window.max(1))
+            .try_collect::<()>(),
+    )?;
+    Ok(files)
+}
@@ -1,11 +1,25 @@
+use crate::metrics::{AccessPattern, Operation};
 use crate::{CloudError, CloudPath, CloudStore, Result};
 use bytes::Bytes;
+use chrono::{DateTime, Utc};
 use object_store::path::Path;
+use object_store::GetOptions;
 use std::io::{Read, Seek, SeekFrom};
 
-const DEFAULT_BUFFER_SIZE: usize = 8 * 1024 * 1024; // 8MB buffer
+/// Size of the first range requested
+const INITIAL_REQUEST_SIZE: usize = 1024 * 1024; // 1MB
+/// Smallest range requested, for reads that keep jumping around
+const MIN_REQUEST_SIZE: usize = 64 * 1024; // 64KB
+/// Largest range requested, for reads straight through the object
+const MAX_REQUEST_SIZE: usize = 8 * 1024 * 1024; // 8MB
 
 /// A reader that adapts cloud storage objects to implement `std::io::Read` and `Seek`
+///
+/// The size of the ranges it requests follows how it is read: each request
+/// that carries on where the last one ended doubles it, up to 8MB, so a
+/// sequential scan makes few round trips, and each one after a seek halves
+/// it, down to 64KB, so looking up entries of a zip does not download
+/// megabytes it never reads.
 pub struct CloudReader {
     store: CloudStore,
     path: Path,
@@ -13,8 +27,16 @@ pub struct CloudReader {
     position: u64,
     /// Total size of the object
     size: u64,
+    /// ETag of the version being read, when the store gives one
+    e_tag: Option<String>,
+    /// Modification time of the version being read
+    last_modified: DateTime<Utc>,
     /// Buffer for cached data
     buffer: Option<Buffer>,
+    /// Size of the next range requested
+    request_size: usize,
+    /// Where the last range requested ended
+    fetched_to: Option<u64>,
 }
 
 struct Buffer {
@@ -31,20 +53,7 @@ impl CloudReader {
     pub fn new(url: &str) -> Result<Self> {
         let cloud_path = CloudPath::parse(url)?;
         let store = CloudStore::new(&cloud_path)?;
-
-        // Get object metadata to know the size
-        let meta = store
-            .runtime()
-            .block_on(async { store.store().head(&cloud_path.path).await })
-            .map_err(CloudError::ObjectStore)?;
-
-        Ok(CloudReader {
-            store,
-            path: cloud_path.path,
-            position: 0,
-            size: meta.size as u64,
-            buffer: None,
-        })
+        Self::from_store(store, cloud_path.path)
     }
 
     /// Create a `CloudReader` from an existing `CloudStore` and path
@@ -52,10 +61,11 @@ impl CloudReader {
     /// # Errors
     /// Returns an error if the object metadata cannot be retrieved
     pub fn from_store(store: CloudStore, path: Path) -> Result<Self> {
-        // Get object metadata to know the size
+        // Get object metadata to know the size and which version is read
         let meta = store
-            .runtime()
-            .block_on(async { store.store().head(&path).await })
+            .block_on_instrumented(Operation::Head, &path, 0, async {
+                store.store().head(&path).await
+            })
             .map_err(CloudError::ObjectStore)?;
 
         Ok(CloudReader {
@@ -63,26 +73,64 @@ impl CloudReader {
             path,
             position: 0,
             size: meta.size as u64,
+            e_tag: meta.e_tag,
+            last_modified: meta.last_modified,
             buffer: None,
+            request_size: INITIAL_REQUEST_SIZE,
+            fetched_to: None,
         })
     }
 
+    /// Total size of the object in bytes
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Size of the next range this reader will request
+    pub fn request_size(&self) -> usize {
+        self.request_size
+    }
+
     /// Download a chunk of data from the cloud
+    ///
+    /// The request is conditional on the object still being the version
+    /// the reader was opened on, by ETag or else by modification time, so
+    /// an object replaced mid-read fails with [`CloudError::ObjectChanged`]
+    /// instead of bytes of two versions being stitched together.
     fn fetch_chunk(&mut self, start: u64, len: usize) -> Result<Bytes> {
         let end = (start + len as u64).min(self.size);
+        let options = GetOptions {
+            range: Some((start as usize..end as usize).into()),
+            if_match: self.e_tag.clone(),
+            if_unmodified_since: self.e_tag.is_none().then_some(self.last_modified),
+            ..GetOptions::default()
+        };
 
-        let data = self
+        let store = self.store.store();
+        let path = &self.path;
+        let fetched = self
             .store
-            .runtime()
-            .block_on(async {
-                self.store
-                    .store()
-                    .get_range(&self.path, start as usize..end as usize)
-                    .await
-            })
-            .map_err(CloudError::ObjectStore)?;
+            .block_on_retried(Operation::Get, path, end - start, || {
+                let options = options.clone();
+                async move {
+                    let result = store.get_opts(path, options).await?;
+                    let e_tag = result.meta.e_tag.clone();
+                    Ok((e_tag, result.bytes().await?))
+                }
+            });
 
-        Ok(data)
+        match fetched {
+            Err(object_store::Error::Precondition { .. }) => {
+                Err(CloudError::ObjectChanged(self.path.to_string()))
+            }
+            Err(e) => Err(CloudError::ObjectStore(e)),
+            // Not every store checks the condition; the ETag it returns
+            // still tells
+            Ok((e_tag, _)) if self.e_tag.is_some() && e_tag.is_some() && e_tag != self.e_tag => {
+                Err(CloudError::ObjectChanged(self.path.to_string()))
+            }
+            Ok((_, data)) => Ok(data),
+        }
     }
 
     /// Ensure we have buffered data at the current position
@@ -101,8 +149,12 @@ impl CloudReader {
             return Ok(());
         }
 
-        let chunk_size = DEFAULT_BUFFER_SIZE.min((self.size - self.position) as usize);
+        let pattern = self.adapt_request_size();
+        self.store.metrics().record_access(pattern);
+
+        let chunk_size = self.request_size.min((self.size - self.position) as usize);
         let data = self.fetch_chunk(self.position, chunk_size)?;
+        self.fetched_to = Some(self.position + data.len() as u64);
 
         self.buffer = Some(Buffer {
             data,
@@ -113,6 +165,26 @@ impl CloudReader {
     }
 }
 
+impl CloudReader {
+    /// Tell whether the range about to be requested carries on from the
+    /// last one, and grow or shrink the request size to match
+    fn adapt_request_size(&mut self) -> AccessPattern {
+        match self.fetched_to {
+            Some(end) if end == self.position => {
+                self.request_size = (self.request_size * 2).min(MAX_REQUEST_SIZE);
+                AccessPattern::Sequential
+            }
+            Some(_) => {
+                self.request_size = (self.request_size / 2).max(MIN_REQUEST_SIZE);
+                AccessPattern::Random
+            }
+            // Nothing to go by yet but where the first read is
+            None if self.position == 0 => AccessPattern::Sequential,
+            None => AccessPattern::Random,
+        }
+    }
+}
+
 impl Read for CloudReader {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         if self.position >= self.size {
@@ -1,9 +1,23 @@
+use crate::metrics::{Operation, TransferMetrics};
+use crate::uploads::is_marker;
 use crate::{CloudError, Result};
+use futures_util::TryStreamExt;
+use object_store::multipart::MultipartStore;
 use object_store::path::Path;
-use object_store::DynObjectStore;
+use object_store::{DynObjectStore, ListResult, ObjectMeta, ObjectStore};
+use std::fmt;
+use std::future::Future;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::Instrument;
 use url::Url;
 
+/// Attempts made at a read before a transient failure is given up on
+pub const READ_ATTEMPTS: u32 = 3;
+
+/// Wait before the first retry of a read, doubled for each one after it
+const RETRY_BACKOFF: Duration = Duration::from_millis(100);
+
 /// Represents a path in cloud storage
 #[derive(Debug, Clone)]
 pub struct CloudPath {
@@ -44,61 +58,247 @@ impl CloudPath {
     }
 }
 
+impl fmt::Display for CloudPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}://{}/{}", self.scheme, self.bucket, self.path)
+    }
+}
+
 /// Manages the object store instance and Tokio runtime
 #[derive(Clone)]
 pub struct CloudStore {
     store: Arc<DynObjectStore>,
+    /// The same store, when it lets uploads be tracked by id
+    multipart: Option<Arc<dyn MultipartStore>>,
     runtime: Arc<tokio::runtime::Runtime>,
+    metrics: Arc<TransferMetrics>,
 }
 
 impl CloudStore {
-    /// Create a new CloudStore for the given cloud path
+    /// Create a new CloudStore for the given cloud path, configured from
+    /// the environment
     pub fn new(path: &CloudPath) -> Result<Self> {
+        Self::with_endpoint(path, None)
+    }
+
+    /// Create a new CloudStore for the given cloud path that sends its
+    /// requests to `endpoint`, such as an S3-compatible service, rather
+    /// than the one the environment names
+    pub fn with_endpoint(path: &CloudPath, endpoint: Option<&str>) -> Result<Self> {
         let runtime = tokio::runtime::Runtime::new()
             .map_err(|e| CloudError::Runtime(format!("Failed to create Tokio runtime: {}", e)))?;
 
-        let store =
-            runtime.block_on(async { create_object_store(&path.scheme, &path.bucket).await })?;
+        let (store, multipart) = runtime
+            .block_on(async { create_object_store(&path.scheme, &path.bucket, endpoint).await })?;
 
         Ok(CloudStore {
-            store: Arc::new(store),
+            store,
+            multipart: Some(multipart),
             runtime: Arc::new(runtime),
+            metrics: Arc::new(TransferMetrics::new()),
         })
     }
 
+    /// Wrap an already constructed object store, e.g. an in-memory store in tests
+    pub fn from_object_store(store: Arc<DynObjectStore>) -> Result<Self> {
+        let runtime = tokio::runtime::Runtime::new()
+            .map_err(|e| CloudError::Runtime(format!("Failed to create Tokio runtime: {}", e)))?;
+
+        Ok(CloudStore {
+            store,
+            multipart: None,
+            runtime: Arc::new(runtime),
+            metrics: Arc::new(TransferMetrics::new()),
+        })
+    }
+
+    /// Wrap a store that also supports [`MultipartStore`]
+    ///
+    /// Large uploads to such a store are tracked by id, so they can be
+    /// aborted by a later run if this one is interrupted.
+    pub fn from_multipart_store<S>(store: Arc<S>) -> Result<Self>
+    where
+        S: ObjectStore + MultipartStore,
+    {
+        let multipart: Arc<dyn MultipartStore> = store.clone();
+        let mut cloud_store = Self::from_object_store(store)?;
+        cloud_store.multipart = Some(multipart);
+        Ok(cloud_store)
+    }
+
+    /// Replace the metrics sink, e.g. to share one across several stores
+    /// or to attach a callback
+    pub fn with_metrics(mut self, metrics: Arc<TransferMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
     /// Get the object store instance
     pub fn store(&self) -> &Arc<DynObjectStore> {
         &self.store
     }
 
+    /// Get the store as a [`MultipartStore`], if it is one
+    pub fn multipart_store(&self) -> Option<&Arc<dyn MultipartStore>> {
+        self.multipart.as_ref()
+    }
+
     /// Get the Tokio runtime
     pub fn runtime(&self) -> &Arc<tokio::runtime::Runtime> {
         &self.runtime
     }
+
+    /// Get the transfer metrics collected for this store
+    pub fn metrics(&self) -> &Arc<TransferMetrics> {
+        &self.metrics
+    }
+
+    /// List the objects and "directories" directly below `prefix`
+    ///
+    /// Keys are split on `/`, so nested objects show up as common prefixes
+    /// rather than being returned individually. The markers of unfinished
+    /// uploads are left out, as they are flux's bookkeeping rather than
+    /// data; [`crate::pending_uploads`] finds them.
+    pub fn list(&self, prefix: Option<&Path>) -> Result<ListResult> {
+        let root = Path::default();
+        let mut listing = self
+            .block_on_instrumented(
+                Operation::List,
+                prefix.unwrap_or(&root),
+                0,
+                self.store.list_with_delimiter(prefix),
+            )
+            .map_err(CloudError::ObjectStore)?;
+        listing
+            .objects
+            .retain(|object| !is_marker(&object.location));
+        Ok(listing)
+    }
+
+    /// List every object below `prefix`, however deeply nested
+    pub fn list_recursive(&self, prefix: Option<&Path>) -> Result<Vec<ObjectMeta>> {
+        let root = Path::default();
+        self.block_on_instrumented(
+            Operation::List,
+            prefix.unwrap_or(&root),
+            0,
+            self.store.list(prefix).try_collect(),
+        )
+        .map_err(CloudError::ObjectStore)
+    }
+
+    /// Get the metadata of the object at `path`
+    pub fn head(&self, path: &Path) -> Result<ObjectMeta> {
+        self.block_on_instrumented(Operation::Head, path, 0, self.store.head(path))
+            .map_err(CloudError::ObjectStore)
+    }
+
+    /// Delete the object at `path`
+    pub fn delete(&self, path: &Path) -> Result<()> {
+        self.block_on_instrumented(Operation::Delete, path, 0, self.store.delete(path))
+            .map_err(CloudError::ObjectStore)
+    }
+
+    /// Run a request on the runtime, timing it and recording the outcome
+    pub(crate) fn block_on_instrumented<T, F>(
+        &self,
+        operation: Operation,
+        path: &Path,
+        bytes: u64,
+        fut: F,
+    ) -> std::result::Result<T, object_store::Error>
+    where
+        F: Future<Output = std::result::Result<T, object_store::Error>>,
+    {
+        let span = tracing::debug_span!("cloud_request", op = %operation, path = %path, bytes);
+        let start = Instant::now();
+        let result = self.runtime.block_on(fut.instrument(span));
+        self.metrics
+            .record(operation, bytes, start.elapsed(), result.is_ok());
+        result
+    }
+
+    /// Like [`block_on_instrumented`](Self::block_on_instrumented), but
+    /// retrying transient failures up to [`READ_ATTEMPTS`] times
+    ///
+    /// Only for requests that change nothing, so sending one twice is
+    /// harmless. The object store client retries requests that fail
+    /// outright, but not a response body that breaks off partway through.
+    pub(crate) fn block_on_retried<T, F, Fut>(
+        &self,
+        operation: Operation,
+        path: &Path,
+        bytes: u64,
+        mut request: F,
+    ) -> std::result::Result<T, object_store::Error>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = std::result::Result<T, object_store::Error>>,
+    {
+        let mut backoff = RETRY_BACKOFF;
+        for _ in 1..READ_ATTEMPTS {
+            match self.block_on_instrumented(operation, path, bytes, request()) {
+                Err(e) if is_transient(&e) => {
+                    tracing::warn!(op = %operation, path = %path, "{}, retrying", e);
+                    self.metrics.record_retry(operation);
+                    std::thread::sleep(backoff);
+                    backoff *= 2;
+                }
+                result => return result,
+            }
+        }
+        self.block_on_instrumented(operation, path, bytes, request())
+    }
+}
+
+/// Whether a request failing with `error` may succeed if sent again
+///
+/// Errors the store answered with, such as a missing object or a failed
+/// precondition, will not change; network failures and throttling may.
+fn is_transient(error: &object_store::Error) -> bool {
+    matches!(error, object_store::Error::Generic { .. })
 }
 
-async fn create_object_store(scheme: &str, bucket: &str) -> Result<Box<DynObjectStore>> {
+async fn create_object_store(
+    scheme: &str,
+    bucket: &str,
+    endpoint: Option<&str>,
+) -> Result<(Arc<DynObjectStore>, Arc<dyn MultipartStore>)> {
     match scheme {
         "s3" => {
-            let store = object_store::aws::AmazonS3Builder::from_env()
+            let mut builder = object_store::aws::AmazonS3Builder::from_env();
+            if let Some(endpoint) = endpoint {
+                builder = builder.with_endpoint(endpoint);
+            }
+            let store = builder
                 .with_bucket_name(bucket)
                 .build()
                 .map_err(CloudError::ObjectStore)?;
-            Ok(Box::new(store))
+            let store = Arc::new(store);
+            Ok((store.clone(), store))
         }
+        "gs" if endpoint.is_some() => Err(CloudError::InvalidPath(
+            "Custom endpoints are not supported for gs://".to_string(),
+        )),
         "gs" => {
             let store = object_store::gcp::GoogleCloudStorageBuilder::from_env()
                 .with_bucket_name(bucket)
                 .build()
                 .map_err(CloudError::ObjectStore)?;
-            Ok(Box::new(store))
+            let store = Arc::new(store);
+            Ok((store.clone(), store))
         }
         "az" | "azblob" => {
-            let store = object_store::azure::MicrosoftAzureBuilder::from_env()
+            let mut builder = object_store::azure::MicrosoftAzureBuilder::from_env();
+            if let Some(endpoint) = endpoint {
+                builder = builder.with_endpoint(endpoint.to_string());
+            }
+            let store = builder
                 .with_container_name(bucket)
                 .build()
                 .map_err(CloudError::ObjectStore)?;
-            Ok(Box::new(store))
+            let store = Arc::new(store);
+            Ok((store.clone(), store))
         }
         _ => Err(CloudError::InvalidPath(format!(
             "Unsupported scheme: {}",
@@ -0,0 +1,123 @@
+//! Multipart uploads left behind by interrupted runs
+//!
+//! A multipart upload that is never completed or aborted stays in the bucket,
+//! invisible to listings but billed for every part already sent. Providers do
+//! not let `object_store` list these uploads, so [`crate::CloudWriter`] keeps a
+//! small marker object next to each one it starts, holding the upload id, and
+//! removes it once the upload finishes. [`pending_uploads`] finds the markers
+//! a crashed or killed run left behind and [`abort_upload`] discards the
+//! upload they point to.
+
+use crate::metrics::Operation;
+use crate::{CloudError, CloudStore, Result};
+use object_store::path::Path;
+use object_store::{ObjectMeta, PutPayload};
+
+/// Appended to an object's key to name the marker of its upload
+pub const MARKER_SUFFIX: &str = ".flux-upload";
+
+/// A multipart upload started by flux and not finished
+#[derive(Debug, Clone)]
+pub struct PendingUpload {
+    /// Key the upload would have created
+    pub path: Path,
+    /// Provider id of the upload
+    pub upload_id: String,
+    /// The marker object; its modification time is when the upload started
+    pub marker: ObjectMeta,
+}
+
+/// Key of the marker for an upload to `path`
+pub fn marker_path(path: &Path) -> Path {
+    Path::from(format!("{}{}", path, MARKER_SUFFIX))
+}
+
+/// Whether `path` is an upload marker rather than user data
+pub fn is_marker(path: &Path) -> bool {
+    path.as_ref().ends_with(MARKER_SUFFIX)
+}
+
+/// Record that upload `upload_id` to `path` has started
+pub(crate) fn write_marker(store: &CloudStore, path: &Path, upload_id: &str) -> Result<()> {
+    let marker = marker_path(path);
+    let payload = PutPayload::from(upload_id.to_string());
+    store
+        .block_on_instrumented(
+            Operation::Put,
+            &marker,
+            upload_id.len() as u64,
+            store.store().put(&marker, payload),
+        )
+        .map(|_| ())
+        .map_err(CloudError::ObjectStore)
+}
+
+/// Forget the upload to `path` once it has completed or been aborted
+pub(crate) fn remove_marker(store: &CloudStore, path: &Path) -> Result<()> {
+    match store.delete(&marker_path(path)) {
+        Err(CloudError::ObjectStore(object_store::Error::NotFound { .. })) => Ok(()),
+        result => result,
+    }
+}
+
+/// Uploads below `prefix` that were started by flux and never finished
+///
+/// This includes uploads still running in another process; compare
+/// `marker.last_modified` against the current time to leave those alone.
+pub fn pending_uploads(store: &CloudStore, prefix: Option<&Path>) -> Result<Vec<PendingUpload>> {
+    let mut pending = Vec::new();
+    for marker in store.list_recursive(prefix)? {
+        if !is_marker(&marker.location) {
+            continue;
+        }
+        let key = marker.location.as_ref();
+        let path = Path::from(&key[..key.len() - MARKER_SUFFIX.len()]);
+
+        let upload_id = store
+            .block_on_instrumented(
+                Operation::Get,
+                &marker.location,
+                marker.size as u64,
+                async { store.store().get(&marker.location).await?.bytes().await },
+            )
+            .map_err(CloudError::ObjectStore)?;
+
+        pending.push(PendingUpload {
+            path,
+            upload_id: String::from_utf8_lossy(&upload_id).trim().to_string(),
+            marker,
+        });
+    }
+    Ok(pending)
+}
+
+/// Discard `upload` and the parts already sent, then remove its marker
+///
+/// An upload that completed before its marker could be removed only has the
+/// marker removed.
+pub fn abort_upload(store: &CloudStore, upload: &PendingUpload) -> Result<()> {
+    let multipart = store
+        .multipart_store()
+        .ok_or_else(|| CloudError::Runtime("This store cannot abort uploads by id".to_string()))?;
+
+    let result = store.block_on_instrumented(
+        Operation::AbortMultipart,
+        &upload.path,
+        0,
+        multipart.abort_multipart(&upload.path, &upload.upload_id),
+    );
+    match result {
+        Ok(()) | Err(object_store::Error::NotFound { .. }) => {}
+        Err(_) if completed(store, upload) => {}
+        Err(e) => return Err(CloudError::ObjectStore(e)),
+    }
+
+    remove_marker(store, &upload.path)
+}
+
+/// Whether the object `upload` was creating exists and is newer than its marker
+fn completed(store: &CloudStore, upload: &PendingUpload) -> bool {
+    store
+        .head(&upload.path)
+        .is_ok_and(|meta| meta.last_modified >= upload.marker.last_modified)
+}
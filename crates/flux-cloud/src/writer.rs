@@ -1,7 +1,10 @@
+use crate::metrics::Operation;
+use crate::uploads::{remove_marker, write_marker};
 use crate::{CloudError, CloudPath, CloudStore, Result};
 use bytes::{BufMut, BytesMut};
+use object_store::multipart::PartId;
 use object_store::path::Path;
-use object_store::MultipartUpload;
+use object_store::{MultipartId, MultipartUpload};
 use std::io::Write;
 
 const DEFAULT_BUFFER_SIZE: usize = 8 * 1024 * 1024; // 8MB buffer
@@ -17,12 +20,21 @@ pub struct CloudWriter {
     buffer_size: usize,
     /// Total bytes written
     total_written: u64,
-    /// Multipart upload handle (for large files)
-    multipart: Option<Box<dyn MultipartUpload>>,
+    /// Multipart upload in progress (for large files)
+    multipart: Option<Upload>,
     /// Part number for multipart uploads
     part_number: usize,
 }
 
+/// A multipart upload in progress
+enum Upload {
+    /// Upload through `object_store`'s handle, which only this writer can abort
+    Handle(Box<dyn MultipartUpload>),
+    /// Upload by id, recorded in a marker object until it completes so that
+    /// [`crate::uploads::abort_upload`] can clean it up after a crash
+    Tracked { id: MultipartId, parts: Vec<PartId> },
+}
+
 impl CloudWriter {
     /// Create a new CloudWriter for the given cloud URL
     pub fn new(url: &str) -> Result<Self> {
@@ -84,11 +96,31 @@ impl CloudWriter {
 
     /// Start a multipart upload
     fn start_multipart(&mut self) -> Result<()> {
-        let upload = self
-            .store
-            .runtime()
-            .block_on(async { self.store.store().put_multipart(&self.path).await })
-            .map_err(CloudError::ObjectStore)?;
+        let upload = match self.store.multipart_store() {
+            Some(multipart) => {
+                let id = self
+                    .store
+                    .block_on_instrumented(
+                        Operation::StartMultipart,
+                        &self.path,
+                        0,
+                        multipart.create_multipart(&self.path),
+                    )
+                    .map_err(CloudError::ObjectStore)?;
+                write_marker(&self.store, &self.path, &id)?;
+                Upload::Tracked {
+                    id,
+                    parts: Vec::new(),
+                }
+            }
+            None => Upload::Handle(
+                self.store
+                    .block_on_instrumented(Operation::StartMultipart, &self.path, 0, async {
+                        self.store.store().put_multipart(&self.path).await
+                    })
+                    .map_err(CloudError::ObjectStore)?,
+            ),
+        };
 
         self.multipart = Some(upload);
         self.part_number = 0;
@@ -97,37 +129,112 @@ impl CloudWriter {
 
     /// Upload a part in multipart upload
     fn upload_part(&mut self, data: bytes::Bytes) -> Result<()> {
-        if let Some(ref mut upload) = self.multipart {
-            self.store
-                .runtime()
-                .block_on(async { upload.put_part(data.into()).await })
-                .map_err(CloudError::ObjectStore)?;
-            self.part_number += 1;
+        let len = data.len() as u64;
+        match self.multipart {
+            Some(Upload::Handle(ref mut upload)) => {
+                self.store
+                    .block_on_instrumented(Operation::PutPart, &self.path, len, async {
+                        upload.put_part(data.into()).await
+                    })
+                    .map_err(CloudError::ObjectStore)?;
+            }
+            Some(Upload::Tracked {
+                ref id,
+                ref mut parts,
+            }) => {
+                let multipart = self.store.multipart_store().ok_or_else(|| {
+                    CloudError::Runtime("Store no longer supports tracked uploads".to_string())
+                })?;
+                let part = self
+                    .store
+                    .block_on_instrumented(
+                        Operation::PutPart,
+                        &self.path,
+                        len,
+                        multipart.put_part(&self.path, id, self.part_number, data.into()),
+                    )
+                    .map_err(CloudError::ObjectStore)?;
+                parts.push(part);
+            }
+            None => return Ok(()),
         }
+        self.part_number += 1;
         Ok(())
     }
 
     /// Complete the upload (called on drop or explicit finish)
     fn finish_upload(&mut self) -> Result<()> {
-        if let Some(mut upload) = self.multipart.take() {
-            // Complete multipart upload
+        if self.multipart.is_some() {
+            // Send what is left as the last part, then complete the upload
             self.flush_buffer()?;
-            self.store
-                .runtime()
-                .block_on(async { upload.complete().await })
-                .map_err(CloudError::ObjectStore)?;
+
+            match self.multipart.take() {
+                Some(Upload::Handle(mut upload)) => {
+                    self.store
+                        .block_on_instrumented(Operation::CompleteMultipart, &self.path, 0, async {
+                            upload.complete().await
+                        })
+                        .map_err(CloudError::ObjectStore)?;
+                }
+                Some(Upload::Tracked { id, parts }) => {
+                    if let Some(multipart) = self.store.multipart_store() {
+                        self.store
+                            .block_on_instrumented(
+                                Operation::CompleteMultipart,
+                                &self.path,
+                                0,
+                                multipart.complete_multipart(&self.path, &id, parts),
+                            )
+                            .map_err(CloudError::ObjectStore)?;
+                    }
+                    remove_marker(&self.store, &self.path)?;
+                }
+                None => {}
+            }
         } else {
             // Simple put for small files
             let data = self.buffer.split().freeze();
             if !data.is_empty() {
+                let len = data.len() as u64;
                 self.store
-                    .runtime()
-                    .block_on(async { self.store.store().put(&self.path, data.into()).await })
+                    .block_on_instrumented(Operation::Put, &self.path, len, async {
+                        self.store.store().put(&self.path, data.into()).await
+                    })
                     .map_err(CloudError::ObjectStore)?;
             }
         }
         Ok(())
     }
+
+    /// Give up on the upload, discarding everything written so far
+    ///
+    /// Parts already sent are deleted by the provider and no object is
+    /// created at the destination.
+    pub fn abort(mut self) -> Result<()> {
+        self.buffer.clear();
+        match self.multipart.take() {
+            Some(Upload::Handle(mut upload)) => self
+                .store
+                .block_on_instrumented(Operation::AbortMultipart, &self.path, 0, async {
+                    upload.abort().await
+                })
+                .map_err(CloudError::ObjectStore),
+            Some(Upload::Tracked { id, .. }) => {
+                if let Some(multipart) = self.store.multipart_store() {
+                    self.store
+                        .block_on_instrumented(
+                            Operation::AbortMultipart,
+                            &self.path,
+                            0,
+                            multipart.abort_multipart(&self.path, &id),
+                        )
+                        .map_err(CloudError::ObjectStore)?;
+                }
+                remove_marker(&self.store, &self.path)
+            }
+            None => Ok(()),
+        }
+    }
 }
 
 impl Write for CloudWriter {
@@ -171,7 +278,11 @@ impl Drop for CloudWriter {
     }
 }
 
-/// A CloudWriter that completes the upload when explicitly finished
+/// A CloudWriter that completes the upload only when explicitly finished
+///
+/// Dropping the guard without calling [`CloudWriterGuard::finish`], for
+/// example because an error cut the copy short, aborts the upload instead of
+/// leaving a truncated object behind.
 pub struct CloudWriterGuard {
     writer: Option<CloudWriter>,
 }
@@ -186,10 +297,22 @@ impl CloudWriterGuard {
     /// Finish the upload and consume the writer
     pub fn finish(mut self) -> Result<()> {
         if let Some(mut writer) = self.writer.take() {
-            writer.finish_upload()?;
+            let result = writer.finish_upload();
+            if result.is_err() {
+                let _ = writer.abort();
+            }
+            result?;
         }
         Ok(())
     }
+
+    /// Abort the upload and consume the writer
+    pub fn abort(mut self) -> Result<()> {
+        match self.writer.take() {
+            Some(writer) => writer.abort(),
+            None => Ok(()),
+        }
+    }
 }
 
 impl Write for CloudWriterGuard {
@@ -211,7 +334,10 @@ impl Write for CloudWriterGuard {
 impl Drop for CloudWriterGuard {
     fn drop(&mut self) {
         if let Some(writer) = self.writer.take() {
-            drop(writer); // Will call finish_upload in CloudWriter's drop
+            let path = writer.path.clone();
+            if let Err(e) = writer.abort() {
+                tracing::warn!(path = %path, error = %e, "Failed to abort unfinished upload");
+            }
         }
     }
 }
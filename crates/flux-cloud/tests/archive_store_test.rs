@@ -0,0 +1,147 @@
+//! Tests for reading files inside archives through `ObjectStore`
+
+use flux_cloud::{ArchiveStore, ObjectStore};
+use flux_core::archive::{pack_multiple, PackOptions};
+use flux_core::progress::NoProgress;
+use flux_testing::cloud::MemoryStore;
+use futures_util::TryStreamExt;
+use object_store::path::Path;
+use object_store::{GetOptions, GetRange};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+/// An archive of a folder with a nested file and one at its top
+fn create_archive(dir: &std::path::Path, name: &str, format: &str) -> PathBuf {
+    let source = dir.join("project");
+    fs::create_dir_all(source.join("data/2024")).unwrap();
+    fs::write(source.join("data/2024/q1.csv"), "month,total\njan,3\n").unwrap();
+    fs::write(source.join("readme.txt"), "read me").unwrap();
+    let archive = dir.join(name);
+    pack_multiple(
+        &[&source],
+        &archive,
+        format,
+        None,
+        &PackOptions::default(),
+        &NoProgress,
+    )
+    .unwrap();
+    archive
+}
+
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    tokio::runtime::Runtime::new().unwrap().block_on(future)
+}
+
+#[test]
+fn test_get_files_from_local_archives() {
+    let temp_dir = TempDir::new().unwrap();
+    for (name, format) in [("project.tar.gz", "tar.gz"), ("project.zip", "zip")] {
+        let archive = create_archive(temp_dir.path(), name, format);
+        let store = ArchiveStore::open(&archive).unwrap();
+        assert_eq!(store.format(), format);
+        assert_eq!(store.len(), 2, "{}", name);
+
+        block_on(async {
+            let location = Path::from("project/data/2024/q1.csv");
+            let got = store.get(&location).await.unwrap();
+            assert_eq!(got.meta.size, 18);
+            assert_eq!(got.bytes().await.unwrap(), "month,total\njan,3\n");
+
+            let part = store.get_range(&location, 6..11).await.unwrap();
+            assert_eq!(part, "total");
+
+            let options = GetOptions {
+                range: Some(GetRange::Suffix(2)),
+                ..Default::default()
+            };
+            let tail = store.get_opts(&location, options).await.unwrap();
+            assert_eq!(tail.range, 16..18);
+            assert_eq!(tail.bytes().await.unwrap(), "3\n");
+
+            let head = store.head(&location).await.unwrap();
+            assert!(head.e_tag.is_some());
+
+            let missing = store.get(&Path::from("project/data")).await.unwrap_err();
+            assert!(matches!(missing, object_store::Error::NotFound { .. }));
+        });
+    }
+}
+
+#[test]
+fn test_list_like_a_bucket() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), "project.tar", "tar");
+    let store = ArchiveStore::open(&archive).unwrap();
+
+    block_on(async {
+        let all: Vec<_> = store.list(None).try_collect().await.unwrap();
+        let mut locations: Vec<_> = all.iter().map(|meta| meta.location.to_string()).collect();
+        locations.sort();
+        assert_eq!(
+            locations,
+            ["project/data/2024/q1.csv", "project/readme.txt"]
+        );
+
+        let listed = store
+            .list_with_delimiter(Some(&Path::from("project")))
+            .await
+            .unwrap();
+        assert_eq!(listed.common_prefixes, [Path::from("project/data")]);
+        assert_eq!(listed.objects.len(), 1);
+        assert_eq!(listed.objects[0].location, Path::from("project/readme.txt"));
+
+        // Prefixes match whole segments only
+        let none: Vec<_> = store
+            .list(Some(&Path::from("proj")))
+            .try_collect()
+            .await
+            .unwrap();
+        assert!(none.is_empty());
+    });
+}
+
+#[test]
+fn test_read_only() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), "project.tar.zst", "tar.zst");
+    let store = ArchiveStore::open(&archive).unwrap();
+
+    block_on(async {
+        let err = store
+            .put(&Path::from("new.txt"), "data".into())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, object_store::Error::NotSupported { .. }));
+        let err = store
+            .delete(&Path::from("project/readme.txt"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, object_store::Error::NotSupported { .. }));
+    });
+}
+
+#[test]
+fn test_archive_in_a_bucket() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), "project.tar.xz", "tar.xz");
+    // Without an archive extension the format is found by signature
+    let memory = MemoryStore::new().with_object("backups/latest", fs::read(&archive).unwrap());
+
+    let store =
+        ArchiveStore::open_cloud(memory.cloud_store().unwrap(), Path::from("backups/latest"))
+            .unwrap();
+    assert_eq!(store.format(), "tar.xz");
+
+    let got = block_on(async {
+        store
+            .get(&Path::from("project/readme.txt"))
+            .await
+            .unwrap()
+            .bytes()
+            .await
+            .unwrap()
+    });
+    assert_eq!(got, "read me");
+}
@@ -0,0 +1,98 @@
+//! Tests for finding and extending backup chains in a bucket
+
+use flux_cloud::chain::{fetch, push_link};
+use flux_cloud::BackupChain;
+use flux_testing::cloud::{Fault, FaultyStore, MemoryStore};
+use object_store::path::Path;
+use std::fs;
+use tempfile::TempDir;
+
+fn chain() -> BackupChain {
+    BackupChain::new(&Path::from("backups/photos.tar.gz")).unwrap()
+}
+
+#[test]
+fn test_links_need_a_manifest() {
+    let store = MemoryStore::new()
+        .with_object("backups/photos.0001.tar.gz", "full")
+        .with_object("backups/photos.0001.fluxmanifest", "one")
+        .with_object("backups/photos.0002.tar.gz", "changes")
+        .with_object("backups/photos.0002.fluxmanifest", "two")
+        // Left by a run that failed before its manifest was uploaded
+        .with_object("backups/photos.0003.tar.gz", "unfinished")
+        .with_object("backups/videos.0009.fluxmanifest", "another chain");
+    let cloud_store = store.cloud_store().unwrap();
+
+    let links = chain().links(&cloud_store).unwrap();
+    assert_eq!(links.iter().map(|l| l.sequence).collect::<Vec<_>>(), [1, 2]);
+    let latest = chain().latest(&cloud_store).unwrap().unwrap();
+    assert_eq!(latest.manifest.as_ref(), "backups/photos.0002.fluxmanifest");
+    assert_eq!(chain().next_link(&cloud_store).unwrap().sequence, 4);
+}
+
+#[test]
+fn test_empty_chain() {
+    let store = MemoryStore::new().with_object("backups/photos.tar.gz", "not a link");
+    let cloud_store = store.cloud_store().unwrap();
+
+    assert_eq!(chain().latest(&cloud_store).unwrap(), None);
+    let first = chain().next_link(&cloud_store).unwrap();
+    assert_eq!(first.archive.as_ref(), "backups/photos.0001.tar.gz");
+}
+
+#[test]
+fn test_push_link_uploads_manifest_last() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("archive");
+    let deleted = temp_dir.path().join("deleted");
+    let manifest = temp_dir.path().join("manifest");
+    fs::write(&archive, "archive").unwrap();
+    fs::write(&deleted, "old.txt").unwrap();
+    fs::write(&manifest, "manifest").unwrap();
+
+    let store = MemoryStore::new();
+    let cloud_store = store.cloud_store().unwrap();
+    let link = chain().next_link(&cloud_store).unwrap();
+    push_link(&cloud_store, &link, &archive, Some(&deleted), &manifest).unwrap();
+
+    assert_eq!(
+        store.uploads(),
+        [
+            "backups/photos.0001.tar.gz",
+            "backups/photos.0001.deleted.txt",
+            "backups/photos.0001.fluxmanifest"
+        ]
+    );
+    assert_eq!(chain().latest(&cloud_store).unwrap(), Some(link.clone()));
+
+    let fetched = temp_dir.path().join("fetched");
+    fetch(&cloud_store, &link.manifest, &fetched).unwrap();
+    assert_eq!(fs::read_to_string(fetched).unwrap(), "manifest");
+}
+
+#[test]
+fn test_failed_push_leaves_no_link() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("archive");
+    let manifest = temp_dir.path().join("manifest");
+    fs::write(&archive, "archive").unwrap();
+    fs::write(&manifest, "manifest").unwrap();
+
+    let memory = MemoryStore::new();
+    // The archive goes up in one request and the manifest's fails
+    let faulty = FaultyStore::new(memory.clone()).fail_every(2, Fault::Throttle);
+    let link = chain().link(1);
+    push_link(
+        &faulty.cloud_store().unwrap(),
+        &link,
+        &archive,
+        None,
+        &manifest,
+    )
+    .unwrap_err();
+
+    assert!(!memory.contains("backups/photos.0001.fluxmanifest"));
+    assert!(!memory.contains("backups/photos.0001.tar.gz"));
+    let cloud_store = memory.cloud_store().unwrap();
+    assert_eq!(chain().latest(&cloud_store).unwrap(), None);
+}
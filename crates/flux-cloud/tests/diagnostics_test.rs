@@ -0,0 +1,68 @@
+//! Tests for sorting cloud errors into causes
+
+use flux_cloud::diagnostics::{credential_vars, credentials_configured, diagnose_io};
+use flux_cloud::{diagnose, Cause, CloudError, CloudPath};
+use std::io;
+
+fn store_error(kind: &str) -> CloudError {
+    let source: Box<dyn std::error::Error + Send + Sync> = "denied".into();
+    let path = "bucket/key".to_string();
+    CloudError::ObjectStore(match kind {
+        "unauthenticated" => object_store::Error::Unauthenticated { path, source },
+        "permission" => object_store::Error::PermissionDenied { path, source },
+        "not-found" => object_store::Error::NotFound { path, source },
+        "token" => object_store::Error::Generic {
+            store: "S3",
+            source: "Error performing token request".into(),
+        },
+        _ => object_store::Error::Generic {
+            store: "S3",
+            source: "connection reset".into(),
+        },
+    })
+}
+
+#[test]
+fn test_credential_failures() {
+    let expected = if credentials_configured("s3") {
+        Cause::InvalidCredentials
+    } else {
+        Cause::MissingCredentials
+    };
+
+    for kind in ["unauthenticated", "token"] {
+        let diagnosis = diagnose(&store_error(kind), "s3");
+        assert_eq!(diagnosis.cause, expected, "{}", kind);
+        assert!(diagnosis.is_credentials());
+        assert!(diagnosis.credential_vars.contains(&"AWS_ACCESS_KEY_ID"));
+    }
+}
+
+#[test]
+fn test_other_causes() {
+    assert_eq!(
+        diagnose(&store_error("not-found"), "gs").cause,
+        Cause::NotFound
+    );
+    assert_eq!(diagnose(&store_error("reset"), "gs").cause, Cause::Other);
+    assert!(!diagnose(&store_error("reset"), "gs").is_credentials());
+
+    let invalid = CloudPath::parse("ftp://bucket/key").unwrap_err();
+    assert_eq!(diagnose(&invalid, "ftp").cause, Cause::InvalidPath);
+}
+
+#[test]
+fn test_errors_wrapped_in_io() {
+    let wrapped: io::Error = store_error("not-found").into();
+    assert_eq!(diagnose_io(&wrapped, "az").cause, Cause::NotFound);
+
+    let plain = io::Error::new(io::ErrorKind::TimedOut, "timed out");
+    assert_eq!(diagnose_io(&plain, "az").cause, Cause::Other);
+}
+
+#[test]
+fn test_credential_vars_per_provider() {
+    assert!(credential_vars("gs").contains(&"GOOGLE_APPLICATION_CREDENTIALS"));
+    assert!(credential_vars("azblob").contains(&"AZURE_STORAGE_SAS_TOKEN"));
+    assert!(credential_vars("ftp").is_empty());
+}
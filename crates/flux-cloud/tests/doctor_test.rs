@@ -0,0 +1,66 @@
+//! Tests for checking a cloud location with `flux cloud doctor`
+
+use flux_cloud::doctor::{check_store, Check, Status};
+use flux_cloud::CloudPath;
+use flux_testing::cloud::{Fault, FaultyStore, MemoryStore};
+
+fn location() -> CloudPath {
+    CloudPath::parse("s3://bucket/backups/").unwrap()
+}
+
+fn status(checks: &[Check], name: &str) -> Status {
+    checks
+        .iter()
+        .find(|check| check.name == name)
+        .unwrap_or_else(|| panic!("no {} check in {:?}", name, checks))
+        .status
+}
+
+#[test]
+fn test_working_location_passes() {
+    let store = MemoryStore::new().with_object("backups/full.tar", "data");
+    let checks = check_store(&store.cloud_store().unwrap(), &location());
+
+    let names: Vec<_> = checks.iter().map(|check| check.name).collect();
+    assert_eq!(names, ["list", "write", "read", "delete", "multipart"]);
+    assert!(
+        checks.iter().all(|check| check.status == Status::Pass),
+        "{:?}",
+        checks
+    );
+    assert_eq!(checks[0].detail, "Found 1 objects and 0 folders");
+
+    // The probes are cleaned up, and went below the prefix
+    let uploads = store.uploads();
+    assert_eq!(uploads.len(), 2);
+    assert!(uploads
+        .iter()
+        .all(|path| path.starts_with("backups/.flux-doctor-")));
+    assert!(uploads.iter().all(|path| !store.contains(path)));
+    assert!(store.contains("backups/full.tar"));
+}
+
+#[test]
+fn test_failures_come_with_advice() {
+    let store = FaultyStore::new(MemoryStore::new());
+    store.fail_next(Fault::Timeout, 2);
+    let checks = check_store(&store.cloud_store().unwrap(), &location());
+
+    assert_eq!(status(&checks, "list"), Status::Fail);
+    assert!(checks[0].hint.as_deref().unwrap().contains("network"));
+    assert_eq!(status(&checks, "write"), Status::Fail);
+    assert_eq!(status(&checks, "read"), Status::Skip);
+    assert_eq!(status(&checks, "delete"), Status::Skip);
+    assert_eq!(status(&checks, "multipart"), Status::Skip);
+}
+
+#[test]
+fn test_store_without_upload_ids_warns() {
+    let store = FaultyStore::new(MemoryStore::new());
+    let checks = check_store(&store.cloud_store().unwrap(), &location());
+
+    assert_eq!(status(&checks, "delete"), Status::Pass);
+    let multipart = checks.last().unwrap();
+    assert_eq!(multipart.status, Status::Warn);
+    assert!(multipart.hint.is_some());
+}
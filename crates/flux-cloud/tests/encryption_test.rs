@@ -0,0 +1,112 @@
+//! Tests for client-side encryption of objects
+
+use flux_cloud::encryption::is_encrypted;
+use flux_cloud::{CloudError, CloudWriterGuard, DecryptingReader, EncryptingWriter};
+use flux_core::keys::{Key, KeyStore};
+use flux_testing::cloud::MemoryStore;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use tempfile::TempDir;
+
+fn load_key(dir: &TempDir, name: &str) -> Key {
+    let keys = KeyStore::new(dir.path());
+    keys.generate(name, None).unwrap();
+    keys.load(name, None).unwrap()
+}
+
+fn encrypt(data: &[u8], key: &Key) -> Vec<u8> {
+    let mut writer = EncryptingWriter::new(Vec::new(), key).unwrap();
+    writer.write_all(data).unwrap();
+    writer.finish().unwrap()
+}
+
+fn encryption_error(err: &std::io::Error) -> bool {
+    err.get_ref()
+        .and_then(|inner| inner.downcast_ref::<CloudError>())
+        .is_some_and(|e| matches!(e, CloudError::Encryption(_)))
+}
+
+#[test]
+fn test_round_trip_through_store() {
+    let temp_dir = TempDir::new().unwrap();
+    let key = load_key(&temp_dir, "offsite");
+    let store = MemoryStore::new();
+    let data: Vec<u8> = (0..=255u8).cycle().take(300 * 1024).collect();
+
+    let guard = CloudWriterGuard::new(store.writer("backups/data.tar").unwrap());
+    let mut writer = EncryptingWriter::new(guard, &key).unwrap();
+    for piece in data.chunks(10_000) {
+        writer.write_all(piece).unwrap();
+    }
+    writer.finish().unwrap().finish().unwrap();
+
+    let stored = store.object("backups/data.tar").unwrap();
+    assert!(is_encrypted(&stored));
+    assert!(!stored.windows(64).any(|w| w == &data[..64]));
+
+    let mut reader =
+        DecryptingReader::new(store.reader("backups/data.tar").unwrap(), &key).unwrap();
+    assert_eq!(reader.size(), data.len() as u64);
+    let mut read_back = Vec::new();
+    reader.read_to_end(&mut read_back).unwrap();
+    assert_eq!(read_back, data);
+
+    // Seeking lands inside chunks, across their boundaries and at the end
+    let mut buf = [0u8; 100];
+    reader.seek(SeekFrom::Start(65_500)).unwrap();
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(buf[..], data[65_500..65_600]);
+    reader.seek(SeekFrom::End(-10)).unwrap();
+    let mut tail = Vec::new();
+    reader.read_to_end(&mut tail).unwrap();
+    assert_eq!(tail, data[data.len() - 10..]);
+}
+
+#[test]
+fn test_sizes_at_chunk_boundaries() {
+    let temp_dir = TempDir::new().unwrap();
+    let key = load_key(&temp_dir, "k");
+    for len in [0, 1, 64 * 1024 - 1, 64 * 1024, 64 * 1024 + 1, 128 * 1024] {
+        let data = vec![7u8; len];
+        let sealed = encrypt(&data, &key);
+        let mut reader = DecryptingReader::new(Cursor::new(sealed), &key).unwrap();
+        let mut read_back = Vec::new();
+        reader.read_to_end(&mut read_back).unwrap();
+        assert_eq!(read_back.len(), len);
+    }
+}
+
+#[test]
+fn test_wrong_key_tampering_and_truncation_fail() {
+    let temp_dir = TempDir::new().unwrap();
+    let key = load_key(&temp_dir, "right");
+    let other = load_key(&temp_dir, "other");
+    let data = vec![3u8; 100 * 1024];
+    let sealed = encrypt(&data, &key);
+    let mut read_back = Vec::new();
+
+    let mut reader = DecryptingReader::new(Cursor::new(sealed.clone()), &other).unwrap();
+    assert!(encryption_error(
+        &reader.read_to_end(&mut read_back).unwrap_err()
+    ));
+
+    let mut tampered = sealed.clone();
+    tampered[100] ^= 1;
+    let mut reader = DecryptingReader::new(Cursor::new(tampered), &key).unwrap();
+    assert!(encryption_error(
+        &reader.read_to_end(&mut read_back).unwrap_err()
+    ));
+
+    // Cut after the first chunk, what is left looks like a whole object
+    // whose last chunk was sealed as not the last
+    let first_chunk = 28 + 64 * 1024 + 16;
+    let truncated = sealed[..first_chunk].to_vec();
+    let mut reader = DecryptingReader::new(Cursor::new(truncated), &key).unwrap();
+    assert!(encryption_error(
+        &reader.read_to_end(&mut read_back).unwrap_err()
+    ));
+
+    assert!(matches!(
+        DecryptingReader::new(Cursor::new(b"plain text".to_vec()), &key),
+        Err(CloudError::Encryption(_))
+    ));
+}
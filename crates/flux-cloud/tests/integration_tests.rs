@@ -30,6 +30,20 @@ fn test_cloud_path_parsing() {
     assert_eq!(path.bucket, "container");
 }
 
+#[test]
+fn test_cloud_path_display_round_trips() {
+    for url in [
+        "s3://my-bucket/path/to/file.tar",
+        "gs://gcs-bucket/archive.tar.gz",
+    ] {
+        assert_eq!(CloudPath::parse(url).unwrap().to_string(), url);
+    }
+    assert_eq!(
+        CloudPath::parse("s3://my-bucket").unwrap().to_string(),
+        "s3://my-bucket/"
+    );
+}
+
 #[test]
 fn test_invalid_cloud_paths() {
     assert!(CloudPath::parse("http://not-cloud/file").is_err());
@@ -175,7 +189,7 @@ fn test_multipart_threshold() {
     // Test that large writes trigger multipart upload logic
     // This is a unit test that doesn't require credentials
 
-    let large_data = vec![0u8; 20 * 1024 * 1024]; // 20MB
+    let _large_data = vec![0u8; 20 * 1024 * 1024]; // 20MB
 
     // We can't test actual upload without credentials, but we can verify
     // the writer accepts large data
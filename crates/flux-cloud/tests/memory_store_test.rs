@@ -0,0 +1,179 @@
+//! Reader/writer tests against an in-memory object store
+//!
+//! Unlike `integration_tests.rs`, these run without cloud credentials.
+
+use flux_cloud::store::READ_ATTEMPTS;
+use flux_testing::cloud::{Fault, FaultyStore, MemoryStore};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+#[test]
+fn test_memory_round_trip_with_seek() {
+    let store = MemoryStore::new();
+    let data: Vec<u8> = (0..=255u8).cycle().take(64 * 1024).collect();
+
+    let mut writer = store.writer("archives/round_trip.bin").unwrap();
+    writer.write_all(&data).unwrap();
+    drop(writer);
+
+    let mut reader = store.reader("archives/round_trip.bin").unwrap();
+    reader.seek(SeekFrom::Start(1000)).unwrap();
+    let mut buf = [0u8; 16];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf[..], &data[1000..1016]);
+
+    reader.seek(SeekFrom::Start(0)).unwrap();
+    let mut read_back = Vec::new();
+    reader.read_to_end(&mut read_back).unwrap();
+    assert_eq!(read_back, data);
+}
+
+#[test]
+fn test_memory_multipart_upload() {
+    let store = MemoryStore::new();
+    let data = vec![7u8; 20 * 1024 * 1024];
+
+    let mut writer = store.writer("large.bin").unwrap();
+    writer.write_all(&data).unwrap();
+    drop(writer);
+
+    assert_eq!(store.uploads(), vec!["large.bin".to_string()]);
+    assert_eq!(store.object("large.bin").unwrap().len(), data.len());
+}
+
+#[test]
+fn test_memory_store_records_metrics() {
+    let store = MemoryStore::new().with_object("small.txt", "twelve bytes");
+    let cloud_store = store.cloud_store().unwrap();
+    let metrics = cloud_store.metrics().clone();
+
+    let mut reader = flux_cloud::CloudReader::from_store(cloud_store, "small.txt".into()).unwrap();
+    let mut content = String::new();
+    reader.read_to_string(&mut content).unwrap();
+
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.requests, 2); // head + get
+    assert_eq!(snapshot.bytes_downloaded, 12);
+    assert_eq!(snapshot.failures, 0);
+}
+
+#[test]
+fn test_request_size_follows_access_pattern() {
+    let data: Vec<u8> = (0..=255u8).cycle().take(32 * 1024 * 1024).collect();
+    let store = MemoryStore::new().with_object("scan.bin", data.clone());
+    let cloud_store = store.cloud_store().unwrap();
+    let metrics = cloud_store.metrics().clone();
+
+    // Reading straight through grows the requests: 1, 2, 4, 8, 8, 8, 1 MB
+    let mut reader =
+        flux_cloud::CloudReader::from_store(cloud_store.clone(), "scan.bin".into()).unwrap();
+    let mut read_back = Vec::new();
+    reader.read_to_end(&mut read_back).unwrap();
+    assert_eq!(read_back, data);
+    assert_eq!(reader.request_size(), 8 * 1024 * 1024);
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.sequential_reads, 7);
+    assert_eq!(snapshot.random_reads, 0);
+
+    // Jumping around shrinks them, so little is downloaded per lookup
+    let mut reader = flux_cloud::CloudReader::from_store(cloud_store, "scan.bin".into()).unwrap();
+    let before = metrics.snapshot().bytes_downloaded;
+    let mut buf = [0u8; 16];
+    for i in 0..10u64 {
+        reader
+            .seek(SeekFrom::Start((i * 7 + 3) % 32 * 1024 * 1024))
+            .unwrap();
+        reader.read_exact(&mut buf).unwrap();
+    }
+    assert_eq!(reader.request_size(), 64 * 1024);
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.random_reads, 10);
+    assert!(snapshot.bytes_downloaded - before < 4 * 1024 * 1024);
+}
+
+#[test]
+fn test_object_replaced_mid_read_fails() {
+    let store = MemoryStore::new().with_object("live.tar", vec![1u8; 3 * 1024 * 1024]);
+    let mut reader = store.reader("live.tar").unwrap();
+    let mut buf = [0u8; 16];
+    reader.read_exact(&mut buf).unwrap();
+
+    store.insert("live.tar", vec![2u8; 3 * 1024 * 1024]);
+    reader.seek(SeekFrom::Start(2 * 1024 * 1024)).unwrap();
+    let err = reader.read_exact(&mut buf).unwrap_err();
+    let cloud_error = err
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<flux_cloud::CloudError>());
+    assert!(matches!(
+        cloud_error,
+        Some(flux_cloud::CloudError::ObjectChanged(path)) if path == "live.tar"
+    ));
+
+    // A reader opened afterwards reads the new version
+    let mut reader = store.reader("live.tar").unwrap();
+    reader.seek(SeekFrom::Start(2 * 1024 * 1024)).unwrap();
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [2u8; 16]);
+}
+
+#[test]
+fn test_throttled_read_surfaces_error() {
+    let store = FaultyStore::new(MemoryStore::new().with_object("data.bin", vec![0u8; 1024]));
+    let cloud_store = store.cloud_store().unwrap();
+    let metrics = cloud_store.metrics().clone();
+
+    let mut reader = flux_cloud::CloudReader::from_store(cloud_store, "data.bin".into()).unwrap();
+    store.fail_next(Fault::Throttle, READ_ATTEMPTS as usize);
+
+    let mut buf = Vec::new();
+    let err = reader.read_to_end(&mut buf).unwrap_err();
+    assert!(err.to_string().contains("503"));
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.failures, READ_ATTEMPTS as u64);
+    assert_eq!(snapshot.retries, READ_ATTEMPTS as u64 - 1);
+}
+
+#[test]
+fn test_dropped_read_is_retried() {
+    let data: Vec<u8> = (0..=255u8).cycle().take(4096).collect();
+    let store = FaultyStore::new(MemoryStore::new().with_object("data.bin", data.clone()));
+    let cloud_store = store.cloud_store().unwrap();
+    let metrics = cloud_store.metrics().clone();
+
+    let mut reader = flux_cloud::CloudReader::from_store(cloud_store, "data.bin".into()).unwrap();
+    store.fail_next(Fault::Disconnect, 1);
+
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf).unwrap();
+    assert_eq!(buf, data);
+    let snapshot = metrics.snapshot();
+    assert_eq!(snapshot.failures, 1);
+    assert_eq!(snapshot.retries, 1);
+}
+
+#[test]
+fn test_memory_list_splits_on_delimiter() {
+    let store = MemoryStore::new()
+        .with_object("backups/2024/full.tar.zst", "full")
+        .with_object("backups/2024/daily/mon.tar.zst", "mon")
+        .with_object("backups/readme.txt", "hello")
+        .with_object("other.zip", "zip");
+    let cloud_store = store.cloud_store().unwrap();
+
+    let root = cloud_store.list(None).unwrap();
+    let prefixes: Vec<_> = root.common_prefixes.iter().map(|p| p.as_ref()).collect();
+    let objects: Vec<_> = root.objects.iter().map(|o| o.location.as_ref()).collect();
+    assert_eq!(prefixes, vec!["backups"]);
+    assert_eq!(objects, vec!["other.zip"]);
+
+    let nested = cloud_store.list(Some(&"backups/2024".into())).unwrap();
+    let prefixes: Vec<_> = nested.common_prefixes.iter().map(|p| p.as_ref()).collect();
+    assert_eq!(prefixes, vec!["backups/2024/daily"]);
+    assert_eq!(nested.objects.len(), 1);
+    assert_eq!(
+        nested.objects[0].location.as_ref(),
+        "backups/2024/full.tar.zst"
+    );
+    assert_eq!(nested.objects[0].size, 4);
+
+    assert_eq!(cloud_store.metrics().snapshot().requests, 2);
+}
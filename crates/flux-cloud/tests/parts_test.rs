@@ -0,0 +1,102 @@
+//! Tests for finding and downloading archives stored as several objects
+
+use flux_cloud::parts::download_parts;
+use flux_cloud::{split_parts, PartsReader, SplitKind};
+use flux_testing::cloud::MemoryStore;
+use object_store::path::Path;
+use std::fs;
+use std::io::Read;
+use tempfile::TempDir;
+
+#[test]
+fn test_parts_are_found_and_joined_in_order() {
+    let data: Vec<u8> = (0..=255u8).cycle().take(10_000).collect();
+    let store = MemoryStore::new().with_object("backups/other.bin", "unrelated");
+    for (number, piece) in data.chunks(3_000).enumerate() {
+        store.insert(
+            &format!("backups/photos.tar.{:03}", number + 1),
+            piece.to_vec(),
+        );
+    }
+    let cloud_store = store.cloud_store().unwrap();
+
+    let split = split_parts(&cloud_store, &Path::from("backups/photos.tar.001"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(split.kind, SplitKind::Parts);
+    assert_eq!(split.parts.len(), 4);
+    assert_eq!(split.size(), 10_000);
+    assert_eq!(split.name(), "photos.tar");
+
+    // A window smaller than the number of parts still yields them in order
+    let paths = split
+        .parts
+        .iter()
+        .map(|part| part.location.clone())
+        .collect();
+    let mut reader = PartsReader::new(cloud_store.clone(), paths, 2);
+    let mut joined = Vec::new();
+    reader.read_to_end(&mut joined).unwrap();
+    assert_eq!(joined, data);
+    assert_eq!(cloud_store.metrics().snapshot().bytes_downloaded, 10_000);
+}
+
+#[test]
+fn test_spanned_zip_volumes_are_downloaded_side_by_side() {
+    let store = MemoryStore::new()
+        .with_object("set.z01", "first")
+        .with_object("set.z02", "second")
+        .with_object("set.zip", "last")
+        .with_object("set.z04", "not next in line");
+    let cloud_store = store.cloud_store().unwrap();
+
+    let split = split_parts(&cloud_store, &Path::from("set.zip"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(split.kind, SplitKind::Spanned);
+    assert_eq!(split.name(), "set.zip");
+    let paths: Vec<_> = split
+        .parts
+        .iter()
+        .map(|part| part.location.clone())
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            Path::from("set.z01"),
+            Path::from("set.z02"),
+            Path::from("set.zip")
+        ]
+    );
+
+    let temp_dir = TempDir::new().unwrap();
+    let files = download_parts(&cloud_store, &paths, temp_dir.path(), 2).unwrap();
+    assert_eq!(files[2], temp_dir.path().join("set.zip"));
+    assert_eq!(fs::read_to_string(&files[0]).unwrap(), "first");
+    assert_eq!(fs::read_to_string(&files[1]).unwrap(), "second");
+}
+
+#[test]
+fn test_single_objects_are_not_split() {
+    let store = MemoryStore::new()
+        .with_object("plain.zip", "zip")
+        .with_object("data.tar.gz", "tar");
+    let cloud_store = store.cloud_store().unwrap();
+
+    for path in ["plain.zip", "data.tar.gz"] {
+        assert!(split_parts(&cloud_store, &Path::from(path))
+            .unwrap()
+            .is_none());
+    }
+}
+
+#[test]
+fn test_missing_part_fails_the_read() {
+    let store = MemoryStore::new().with_object("a.001", "one");
+    let cloud_store = store.cloud_store().unwrap();
+
+    let paths = vec![Path::from("a.001"), Path::from("a.002")];
+    let mut reader = PartsReader::new(cloud_store, paths, 4);
+    let mut joined = Vec::new();
+    assert!(reader.read_to_end(&mut joined).is_err());
+}
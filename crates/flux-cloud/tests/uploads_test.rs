@@ -0,0 +1,96 @@
+//! Tests for tracking and cleaning up multipart uploads
+
+use flux_cloud::uploads::marker_path;
+use flux_cloud::{abort_upload, pending_uploads, CloudWriterGuard};
+use flux_testing::cloud::MemoryStore;
+use std::io::Write;
+
+/// Larger than the multipart threshold, so uploads are sent in parts
+const LARGE: usize = 20 * 1024 * 1024;
+
+#[test]
+fn test_completed_upload_removes_marker() {
+    let store = MemoryStore::new();
+
+    let mut writer = CloudWriterGuard::new(store.writer("backups/full.tar").unwrap());
+    writer.write_all(&vec![1u8; LARGE]).unwrap();
+    assert!(store.contains("backups/full.tar.flux-upload"));
+    writer.finish().unwrap();
+
+    assert_eq!(store.object("backups/full.tar").unwrap().len(), LARGE);
+    assert!(!store.contains("backups/full.tar.flux-upload"));
+}
+
+#[test]
+fn test_dropped_guard_aborts_upload() {
+    let store = MemoryStore::new();
+
+    let mut writer = CloudWriterGuard::new(store.writer("backups/cut.tar").unwrap());
+    writer.write_all(&vec![2u8; LARGE]).unwrap();
+    writer.write_all(b"tail").unwrap();
+    drop(writer);
+
+    assert!(!store.contains("backups/cut.tar"));
+    assert!(!store.contains("backups/cut.tar.flux-upload"));
+    assert!(store.uploads().is_empty());
+}
+
+#[test]
+fn test_interrupted_upload_can_be_aborted() {
+    let store = MemoryStore::new().with_object("backups/keep.tar", "done");
+    let cloud_store = store.cloud_store().unwrap();
+
+    // A run that dies mid-upload never completes or aborts it
+    let mut writer = store.writer("backups/crashed.tar").unwrap();
+    writer.write_all(&vec![3u8; LARGE]).unwrap();
+    std::mem::forget(writer);
+
+    let pending = pending_uploads(&cloud_store, Some(&"backups".into())).unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].path.as_ref(), "backups/crashed.tar");
+    assert_eq!(pending[0].marker.location, marker_path(&pending[0].path));
+    assert!(!pending[0].upload_id.is_empty());
+
+    abort_upload(&cloud_store, &pending[0]).unwrap();
+    assert!(pending_uploads(&cloud_store, None).unwrap().is_empty());
+    assert!(!store.contains("backups/crashed.tar"));
+    assert!(store.contains("backups/keep.tar"));
+}
+
+#[test]
+fn test_abort_clears_marker_of_completed_upload() {
+    // A run that completed its upload but died before removing the marker
+    let store = MemoryStore::new().with_object("done.tar.flux-upload", "42");
+    store.insert("done.tar", "archive");
+    let cloud_store = store.cloud_store().unwrap();
+
+    let pending = pending_uploads(&cloud_store, None).unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].upload_id, "42");
+
+    abort_upload(&cloud_store, &pending[0]).unwrap();
+    assert!(!store.contains("done.tar.flux-upload"));
+    assert_eq!(store.object("done.tar").unwrap(), "archive");
+}
+
+#[test]
+fn test_delete_and_list_recursive() {
+    let store = MemoryStore::new()
+        .with_object("backups/a.tar", "a")
+        .with_object("backups/2024/b.tar", "b")
+        .with_object("other/c.tar", "c");
+    let cloud_store = store.cloud_store().unwrap();
+
+    let mut found: Vec<String> = cloud_store
+        .list_recursive(Some(&"backups".into()))
+        .unwrap()
+        .into_iter()
+        .map(|meta| meta.location.to_string())
+        .collect();
+    found.sort();
+    assert_eq!(found, ["backups/2024/b.tar", "backups/a.tar"]);
+
+    cloud_store.delete(&"backups/a.tar".into()).unwrap();
+    assert!(!store.contains("backups/a.tar"));
+    assert!(store.contains("backups/2024/b.tar"));
+}
@@ -0,0 +1,53 @@
+//! Build script for flux-core
+//! Records the versions of the archive and compression libraries the build
+//! locked, for [`info`](src/info.rs) to report
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Libraries whose versions are recorded
+const LIBRARIES: &[&str] = &[
+    "tar",
+    "zip",
+    "sevenz-rust",
+    "flate2",
+    "zstd",
+    "xz2",
+    "brotli",
+];
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let lock = manifest_dir
+        .ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|lock| lock.exists());
+
+    let mut versions = Vec::new();
+    if let Some(lock) = &lock {
+        println!("cargo:rerun-if-changed={}", lock.display());
+        let text = fs::read_to_string(lock).unwrap_or_default();
+        let mut name = None;
+        for line in text.lines() {
+            if let Some(value) = line.strip_prefix("name = ") {
+                name = Some(value.trim_matches('"').to_string());
+            } else if let Some(value) = line.strip_prefix("version = ") {
+                if let Some(name) = name
+                    .take()
+                    .filter(|name| LIBRARIES.contains(&name.as_str()))
+                {
+                    versions.push(format!("{}={}", name, value.trim_matches('"')));
+                }
+            }
+        }
+    }
+
+    // Without a lock file, as when built from a published crate, the
+    // versions are reported as unknown
+    println!(
+        "cargo:rustc-env=FLUX_LIBRARY_VERSIONS={}",
+        versions.join(",")
+    );
+    println!("cargo:rerun-if-changed=build.rs");
+}
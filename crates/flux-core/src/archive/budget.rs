@@ -0,0 +1,256 @@
+//! Choosing a compression level to fit a time budget
+//!
+//! With [`PackOptions::time_budget`] set, a sample of the inputs is
+//! compressed at rising levels, each timed and scaled up to the whole
+//! input, and the highest level expected to finish in time is used. The
+//! sample cannot know about the disk or the rest of the machine, so a zstd
+//! stream keeps checking its pace as it goes and moves to a lower or higher
+//! level when the estimate drifts, starting a new frame each time; zstd
+//! decoders read consecutive frames as one stream. Other algorithms keep
+//! the level they started with.
+
+use super::estimate::{compress_chunks, sample_chunks};
+use super::PackOptions;
+use crate::strategy::Algorithm;
+use crate::Result;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use zstd::stream::write::Encoder as ZstdEncoder;
+
+/// Input a level has to compress before its pace is judged
+const CHECK_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Expected time past the budget, as a fraction of it, that moves a zstd
+/// stream to a lower level
+const BEHIND: f64 = 1.1;
+
+/// Expected time below the budget, as a fraction of it, that moves a zstd
+/// stream to a higher level
+const AHEAD: f64 = 0.5;
+
+/// Share of the budget the sample may take before tuning settles for what
+/// it has measured
+const SAMPLING_SHARE: u32 = 10;
+
+/// Levels tried for `algorithm`, from the fastest up
+fn levels(algorithm: Algorithm) -> &'static [u32] {
+    match algorithm {
+        Algorithm::Store => &[0],
+        Algorithm::Gzip => &[1, 3, 6, 9],
+        Algorithm::Zstd => &[1, 3, 6, 9, 12, 15, 19],
+        Algorithm::Xz => &[0, 3, 6, 9],
+        Algorithm::Brotli => &[1, 4, 6, 9, 11],
+    }
+}
+
+/// The highest level of `algorithm` expected to compress `inputs` within
+/// `budget`, or the fastest when none is
+pub(crate) fn tune_level<P: AsRef<Path>>(
+    inputs: &[P],
+    algorithm: Algorithm,
+    budget: Duration,
+    options: &PackOptions,
+) -> Result<u32> {
+    let started = Instant::now();
+    let levels = levels(algorithm);
+    let (chunks, total) = sample_chunks(inputs, options);
+    let sampled: u64 = chunks.iter().map(|chunk| chunk.len() as u64).sum();
+    if sampled == 0 {
+        return Ok(levels[0]);
+    }
+    let scale = total as f64 / sampled as f64;
+
+    let mut chosen = levels[0];
+    for &level in levels {
+        let timed = Instant::now();
+        compress_chunks(&chunks, algorithm, level)?;
+        let expected = timed.elapsed().mul_f64(scale);
+        debug!("Level {} is expected to take {:?}", level, expected);
+
+        if started.elapsed() + expected > budget {
+            if level == levels[0] {
+                warn!(
+                    "Even level {} is expected to take {:?}, over the budget of {:?}",
+                    level, expected, budget
+                );
+            }
+            break;
+        }
+        chosen = level;
+        if started.elapsed() > budget / SAMPLING_SHARE {
+            break;
+        }
+    }
+
+    info!(
+        "Compressing at level {} to finish within {:?}",
+        chosen, budget
+    );
+    Ok(chosen)
+}
+
+/// Zstd writer that keeps a pack within its time budget, moving to another
+/// level when the pace drifts from the estimate
+pub(crate) struct BudgetedZstd<W: Write> {
+    encoder: Option<ZstdEncoder<'static, W>>,
+    /// Index of the level in use among [`levels`]
+    level: usize,
+    budget: Duration,
+    started: Instant,
+    /// Input expected in all
+    total: u64,
+    /// Input compressed so far
+    consumed: u64,
+    /// When the level in use took over, and the input compressed by then
+    level_started: (Instant, u64),
+}
+
+impl<W: Write> BudgetedZstd<W> {
+    /// Compress to `writer` from `level` on, aiming to have compressed
+    /// `total` bytes within `budget`
+    pub(crate) fn new(writer: W, level: u32, budget: Duration, total: u64) -> io::Result<Self> {
+        let levels = levels(Algorithm::Zstd);
+        let index = levels
+            .iter()
+            .rposition(|&candidate| candidate <= level)
+            .unwrap_or(0);
+        Ok(Self {
+            encoder: Some(new_encoder(writer, levels[index])?),
+            level: index,
+            budget,
+            started: Instant::now(),
+            total,
+            consumed: 0,
+            level_started: (Instant::now(), 0),
+        })
+    }
+
+    /// The level in use
+    pub(crate) fn level(&self) -> u32 {
+        levels(Algorithm::Zstd)[self.level]
+    }
+
+    /// Finish the last frame and return the inner writer
+    pub(crate) fn finish(mut self) -> io::Result<W> {
+        debug!("Finished compressing at level {}", self.level());
+        self.encoder
+            .take()
+            .expect("the encoder is only taken to replace it")
+            .finish()
+    }
+
+    fn encoder_mut(&mut self) -> &mut ZstdEncoder<'static, W> {
+        self.encoder
+            .as_mut()
+            .expect("the encoder is only taken to replace it")
+    }
+
+    /// Move to the next lower or higher level when the pace of the level in
+    /// use would miss the budget or leave most of it unused
+    fn check_pace(&mut self) -> io::Result<()> {
+        let (since, from) = self.level_started;
+        let compressed = self.consumed - from;
+        if compressed < CHECK_BYTES {
+            return Ok(());
+        }
+
+        let rate = compressed as f64 / since.elapsed().as_secs_f64().max(f64::EPSILON);
+        let remaining = self.total.saturating_sub(self.consumed) as f64 / rate;
+        let expected = self.started.elapsed().as_secs_f64() + remaining;
+        let budget = self.budget.as_secs_f64();
+        let levels = levels(Algorithm::Zstd);
+
+        let next = if expected > budget * BEHIND && self.level > 0 {
+            self.level - 1
+        } else if expected < budget * AHEAD && self.level + 1 < levels.len() && remaining > 0.0 {
+            self.level + 1
+        } else {
+            return Ok(());
+        };
+
+        debug!(
+            "Expecting to finish after {:.1}s of {:.1}s, moving from level {} to {}",
+            expected, budget, levels[self.level], levels[next]
+        );
+        let writer = self
+            .encoder
+            .take()
+            .expect("the encoder is only taken to replace it")
+            .finish()?;
+        self.encoder = Some(new_encoder(writer, levels[next])?);
+        self.level = next;
+        self.level_started = (Instant::now(), self.consumed);
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for BudgetedZstd<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.encoder_mut().write(buf)?;
+        self.consumed += n as u64;
+        self.check_pace()?;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.encoder_mut().flush()
+    }
+}
+
+/// A zstd frame at `level`, checksummed like the one of an unbudgeted pack
+fn new_encoder<W: Write>(writer: W, level: u32) -> io::Result<ZstdEncoder<'static, W>> {
+    let mut encoder = ZstdEncoder::new(writer, level as i32)?;
+    encoder.include_checksum(true)?;
+    Ok(encoder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn data(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 7 % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_falls_behind_to_lower_levels() {
+        let input = data(2 * CHECK_BYTES as usize);
+        let mut writer =
+            BudgetedZstd::new(Vec::new(), 3, Duration::ZERO, input.len() as u64).unwrap();
+        assert_eq!(writer.level(), 3);
+
+        for chunk in input.chunks(64 * 1024) {
+            writer.write_all(chunk).unwrap();
+        }
+        assert_eq!(writer.level(), 1);
+
+        // The frames decode as one stream
+        let compressed = writer.finish().unwrap();
+        let mut decoded = Vec::new();
+        zstd::stream::read::Decoder::new(compressed.as_slice())
+            .unwrap()
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, input);
+    }
+
+    #[test]
+    fn test_moves_up_when_far_ahead() {
+        let input = data(2 * CHECK_BYTES as usize);
+        let budget = Duration::from_secs(3_600);
+        let mut writer =
+            BudgetedZstd::new(Vec::new(), 1, budget, 100 * input.len() as u64).unwrap();
+
+        writer.write_all(&input).unwrap();
+        assert!(writer.level() > 1);
+    }
+
+    #[test]
+    fn test_level_snaps_to_a_known_one() {
+        let writer = BudgetedZstd::new(Vec::new(), 10, Duration::ZERO, 0).unwrap();
+        assert_eq!(writer.level(), 9);
+    }
+}
@@ -0,0 +1,332 @@
+//! What each archive format can hold
+//!
+//! [`FormatCapabilities`] answers for a format as Flux reads and writes it,
+//! which is not always all the format allows: Flux stores no extended
+//! attributes in tars, and writes zips without the Zip64 extension. Before
+//! packing, [`scan_metadata`] finds what the inputs carry, so that a caller
+//! can say up front what the chosen format would drop rather than leave it
+//! to warnings in the middle of the job.
+
+use super::walk::WalkLimits;
+use super::PackOptions;
+use crate::{Error, Result};
+use serde::Serialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use walkdir::{DirEntry, WalkDir};
+
+/// Largest file a format without 64-bit sizes holds
+const SMALL_FILE_LIMIT: u64 = u32::MAX as u64;
+
+/// Something a format may or may not be able to hold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Symbolic links, stored as links rather than left out
+    Symlinks,
+    /// Unix permission bits
+    Permissions,
+    /// Extended attributes
+    Xattrs,
+    /// Password protected entries
+    Encryption,
+    /// Adding entries without rewriting those already stored
+    Append,
+    /// Files of 4 GiB and more
+    LargeFiles,
+    /// Names outside ASCII, kept as UTF-8
+    UnicodeNames,
+}
+
+impl Capability {
+    /// Every capability, in the order they are listed
+    pub const ALL: [Capability; 7] = [
+        Capability::Symlinks,
+        Capability::Permissions,
+        Capability::Xattrs,
+        Capability::Encryption,
+        Capability::Append,
+        Capability::LargeFiles,
+        Capability::UnicodeNames,
+    ];
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Capability::Symlinks => "symlinks",
+            Capability::Permissions => "permissions",
+            Capability::Xattrs => "extended attributes",
+            Capability::Encryption => "encryption",
+            Capability::Append => "appending",
+            Capability::LargeFiles => "files over 4 GiB",
+            Capability::UnicodeNames => "Unicode names",
+        })
+    }
+}
+
+/// What an archive format can hold, as Flux handles it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FormatCapabilities {
+    /// Symlinks are stored as links; zip leaves them out unless followed
+    pub symlinks: bool,
+    /// Unix permission bits are stored and restored
+    pub permissions: bool,
+    /// Extended attributes are stored and restored
+    pub xattrs: bool,
+    /// Password protected archives can be read
+    pub encryption: bool,
+    /// Entries can be added to the end without rewriting the archive
+    pub append: bool,
+    /// Files of 4 GiB and more fit
+    pub large_files: bool,
+    /// Names outside ASCII are kept as UTF-8
+    pub unicode_names: bool,
+}
+
+impl FormatCapabilities {
+    /// Capabilities of `format`, such as `tar.zst`, `tgz` or `zip`
+    ///
+    /// `None` for a format Flux does not know.
+    pub fn of(format: &str) -> Option<Self> {
+        let tar = FormatCapabilities {
+            symlinks: true,
+            permissions: true,
+            xattrs: false,
+            encryption: false,
+            append: false,
+            large_files: true,
+            unicode_names: true,
+        };
+        Some(match super::detect::parse_format(format)? {
+            // The end marker of a plain tar can be written over; a
+            // compressed one would have to be decompressed first
+            "tar" => FormatCapabilities {
+                append: true,
+                ..tar
+            },
+            "tar.gz" | "tar.zst" | "tar.xz" | "tar.br" => tar,
+            "zip" => FormatCapabilities {
+                symlinks: false,
+                permissions: true,
+                xattrs: false,
+                encryption: true,
+                append: true,
+                large_files: false,
+                unicode_names: true,
+            },
+            "7z" => FormatCapabilities {
+                symlinks: false,
+                permissions: false,
+                xattrs: false,
+                encryption: true,
+                append: false,
+                large_files: true,
+                unicode_names: true,
+            },
+            _ => return None,
+        })
+    }
+
+    /// Capabilities of the format `archive` is named for
+    pub fn for_path(archive: &Path) -> Option<Self> {
+        super::detect::archive_format(archive).and_then(Self::of)
+    }
+
+    /// Whether the format has `capability`
+    pub fn supports(&self, capability: Capability) -> bool {
+        match capability {
+            Capability::Symlinks => self.symlinks,
+            Capability::Permissions => self.permissions,
+            Capability::Xattrs => self.xattrs,
+            Capability::Encryption => self.encryption,
+            Capability::Append => self.append,
+            Capability::LargeFiles => self.large_files,
+            Capability::UnicodeNames => self.unicode_names,
+        }
+    }
+
+    /// The entries of `found` this format would drop
+    pub fn dropped<'a>(&self, found: &'a [FoundMetadata]) -> Vec<&'a FoundMetadata> {
+        found
+            .iter()
+            .filter(|found| !self.supports(found.capability))
+            .collect()
+    }
+}
+
+/// Inputs carrying something only a format with `capability` keeps
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FoundMetadata {
+    /// What a format needs to keep it
+    pub capability: Capability,
+    /// How many inputs carry it
+    pub count: usize,
+    /// The first of them
+    pub example: PathBuf,
+}
+
+impl fmt::Display for FoundMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (one, many) = match self.capability {
+            Capability::Symlinks => ("symlink", "symlinks"),
+            Capability::Permissions => ("executable file", "executable files"),
+            Capability::Xattrs => (
+                "file with extended attributes",
+                "files with extended attributes",
+            ),
+            Capability::LargeFiles => ("file over 4 GiB", "files over 4 GiB"),
+            Capability::UnicodeNames => ("name outside ASCII", "names outside ASCII"),
+            Capability::Encryption | Capability::Append => ("entry", "entries"),
+        };
+        let what = if self.count == 1 { one } else { many };
+        write!(
+            f,
+            "{} {}, such as {}",
+            self.count,
+            what,
+            self.example.display()
+        )
+    }
+}
+
+/// Find the metadata of `inputs` that only some formats keep
+///
+/// Walks the inputs as packing with `options` would, leaving out what its
+/// filter excludes. Symlinks count only when they are not followed.
+/// Results are in the order of [`Capability::ALL`].
+pub fn scan_metadata(inputs: &[PathBuf], options: &PackOptions) -> Vec<FoundMetadata> {
+    let mut found: Vec<FoundMetadata> = Vec::new();
+
+    for input in inputs {
+        let mut limits = WalkLimits::new(options.follow_symlinks, options.max_depth);
+        let mut walker = WalkDir::new(input)
+            .follow_links(options.follow_symlinks)
+            .into_iter();
+        while let Some(entry) = walker.next() {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            if options.filter.excludes_entry(input, &entry) || limits.leave_out(&entry).is_some() {
+                if entry.file_type().is_dir() {
+                    walker.skip_current_dir();
+                }
+                continue;
+            }
+            for capability in entry_needs(&entry) {
+                match found.iter_mut().find(|f| f.capability == capability) {
+                    Some(found) => found.count += 1,
+                    None => found.push(FoundMetadata {
+                        capability,
+                        count: 1,
+                        example: entry.path().to_path_buf(),
+                    }),
+                }
+            }
+        }
+    }
+
+    found.sort_by_key(|found| {
+        Capability::ALL
+            .iter()
+            .position(|capability| *capability == found.capability)
+    });
+    found
+}
+
+/// What of `inputs` the format `format` would drop, as [`scan_metadata`]
+/// finds it
+pub fn metadata_loss(
+    inputs: &[PathBuf],
+    format: &str,
+    options: &PackOptions,
+) -> Result<Vec<FoundMetadata>> {
+    let capabilities = FormatCapabilities::of(format)
+        .ok_or_else(|| Error::UnsupportedFormat(format.to_string()))?;
+    let found = scan_metadata(inputs, options);
+    Ok(capabilities.dropped(&found).into_iter().cloned().collect())
+}
+
+/// The capabilities a format needs to keep everything about `entry`
+fn entry_needs(entry: &DirEntry) -> Vec<Capability> {
+    let mut needs = Vec::new();
+    let file_type = entry.file_type();
+    if file_type.is_symlink() {
+        needs.push(Capability::Symlinks);
+    }
+    if entry
+        .file_name()
+        .to_str()
+        .is_none_or(|name| !name.is_ascii())
+    {
+        needs.push(Capability::UnicodeNames);
+    }
+    if !file_type.is_file() {
+        return needs;
+    }
+
+    let Ok(metadata) = entry.metadata() else {
+        return needs;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o111 != 0 {
+            needs.push(Capability::Permissions);
+        }
+    }
+    if has_xattrs(entry.path()) {
+        needs.push(Capability::Xattrs);
+    }
+    if metadata.len() > SMALL_FILE_LIMIT {
+        needs.push(Capability::LargeFiles);
+    }
+    needs
+}
+
+/// Whether `path` has extended attributes worth keeping
+///
+/// Security labels such as SELinux contexts are given to every file by the
+/// system the file is on, and are not counted.
+#[cfg(unix)]
+fn has_xattrs(path: &Path) -> bool {
+    xattr::list(path)
+        .is_ok_and(|mut names| names.any(|name| !name.to_string_lossy().starts_with("security.")))
+}
+
+#[cfg(not(unix))]
+fn has_xattrs(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases_share_capabilities() {
+        assert_eq!(
+            FormatCapabilities::of("tgz"),
+            FormatCapabilities::of("tar.gz")
+        );
+        assert_eq!(
+            FormatCapabilities::for_path(Path::new("backup.TAR.ZST")),
+            FormatCapabilities::of("tar.zst")
+        );
+        assert_eq!(FormatCapabilities::of("rar"), None);
+    }
+
+    #[test]
+    fn test_supports_matches_fields() {
+        let zip = FormatCapabilities::of("zip").unwrap();
+        assert!(!zip.supports(Capability::Symlinks));
+        assert!(zip.supports(Capability::Permissions));
+        assert!(!zip.supports(Capability::LargeFiles));
+        assert!(FormatCapabilities::of("tar")
+            .unwrap()
+            .supports(Capability::Append));
+        assert!(!FormatCapabilities::of("tar.xz")
+            .unwrap()
+            .supports(Capability::Append));
+    }
+}
@@ -0,0 +1,234 @@
+//! Entry names that differ only in letter case
+//!
+//! On a case-insensitive file system, such as the defaults on Windows and
+//! macOS, `README` and `readme` name the same file, so extracting both
+//! would leave one of them in place of the other. Extraction checks each
+//! file name against the ones before it, folded to lower case, and applies
+//! [`CaseCollisions`] to any that clash.
+
+use crate::archive::normalize::comparable;
+use crate::archive::{ArchiveEntry, OperationOutcome, SkipReason};
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::warn;
+
+/// What extraction does with a file whose name differs from an earlier
+/// one only in letter case
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CaseCollisions {
+    /// Refuse to extract the archive
+    Error,
+    /// Extract the later file under a numbered name, as `readme (1)`
+    #[default]
+    Rename,
+    /// Let the later file replace the earlier one
+    LastWins,
+}
+
+impl CaseCollisions {
+    /// Lower-case name, as accepted by [`FromStr`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            CaseCollisions::Error => "error",
+            CaseCollisions::Rename => "rename",
+            CaseCollisions::LastWins => "last-wins",
+        }
+    }
+}
+
+impl FromStr for CaseCollisions {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "error" => Ok(CaseCollisions::Error),
+            "rename" => Ok(CaseCollisions::Rename),
+            "last-wins" => Ok(CaseCollisions::LastWins),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unknown case collision policy: {} (expected error, rename or last-wins)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for CaseCollisions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A file whose name differs from an earlier one only in letter case
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CaseCollision {
+    /// Name of the later entry
+    pub path: PathBuf,
+    /// Name of the earlier entry it clashes with
+    pub earlier: PathBuf,
+    /// Where the later entry was written instead, when it was renamed
+    pub renamed_to: Option<PathBuf>,
+}
+
+impl CaseCollision {
+    fn error(&self) -> Error {
+        Error::InvalidPath(format!(
+            "{} and {} differ only in letter case",
+            self.earlier.display(),
+            self.path.display()
+        ))
+    }
+}
+
+/// Every file in `entries` whose name differs from an earlier one only in
+/// letter case
+pub fn find_case_collisions<'a>(
+    entries: impl IntoIterator<Item = &'a ArchiveEntry>,
+) -> Vec<CaseCollision> {
+    let mut seen = HashMap::new();
+    entries
+        .into_iter()
+        .filter(|entry| !entry.is_dir)
+        .filter_map(|entry| {
+            let earlier = seen.entry(fold(&entry.path)).or_insert(&entry.path);
+            (*earlier != &entry.path).then(|| CaseCollision {
+                path: entry.path.clone(),
+                earlier: earlier.to_path_buf(),
+                renamed_to: None,
+            })
+        })
+        .collect()
+}
+
+/// Whether `dir`, which must exist, treats names differing only in case
+/// as the same
+///
+/// Found by creating a file with a lower-case name and looking for it
+/// under the upper-case one; a directory that cannot be written to is
+/// taken to be case-sensitive.
+pub(crate) fn folds_case(dir: &Path) -> bool {
+    let name = format!(".flux-case-probe-{}", std::process::id());
+    let probe = dir.join(&name);
+    if File::create(&probe).is_err() {
+        return false;
+    }
+    let folds = dir.join(name.to_uppercase()).exists();
+    let _ = fs::remove_file(&probe);
+    folds
+}
+
+/// Whether extraction into `output_dir` has to look for case collisions
+pub(crate) fn checks_case(fold_case: Option<bool>, output_dir: &Path) -> bool {
+    fold_case.unwrap_or_else(|| folds_case(output_dir))
+}
+
+/// Tracks the file names extracted so far, applying a [`CaseCollisions`]
+/// policy to the ones that clash
+#[derive(Debug)]
+pub(crate) struct CaseFolder {
+    policy: CaseCollisions,
+    /// File names by their folded form, `None` when the output directory
+    /// is case-sensitive and nothing needs tracking
+    seen: Option<HashMap<String, PathBuf>>,
+}
+
+impl CaseFolder {
+    pub(crate) fn new(policy: CaseCollisions, fold_case: Option<bool>, output_dir: &Path) -> Self {
+        Self {
+            policy,
+            seen: checks_case(fold_case, output_dir).then(HashMap::new),
+        }
+    }
+
+    /// The name to write the file entry `name` under, recording any
+    /// collision in `outcome`
+    ///
+    /// Under [`CaseCollisions::LastWins`] the earlier file is removed from
+    /// `output_dir` and listed as skipped instead of done. Under
+    /// [`CaseCollisions::Error`] the clash is returned as an error for the
+    /// entry.
+    pub(crate) fn place(
+        &mut self,
+        name: &Path,
+        output_dir: &Path,
+        outcome: &mut OperationOutcome,
+    ) -> Result<PathBuf> {
+        let Some(seen) = self.seen.as_mut() else {
+            return Ok(name.to_path_buf());
+        };
+        let key = fold(name);
+        let earlier = match seen.get(&key) {
+            Some(earlier) if earlier != name => earlier.clone(),
+            _ => {
+                seen.insert(key, name.to_path_buf());
+                return Ok(name.to_path_buf());
+            }
+        };
+
+        let mut collision = CaseCollision {
+            path: name.to_path_buf(),
+            earlier,
+            renamed_to: None,
+        };
+        let placed = match self.policy {
+            CaseCollisions::Error => return Err(collision.error()),
+            CaseCollisions::Rename => {
+                let renamed = unused_name(name, seen, output_dir);
+                warn!(
+                    "Extracting {:?} as {:?}: its name differs from {:?} only in case",
+                    name, renamed, collision.earlier
+                );
+                seen.insert(fold(&renamed), renamed.clone());
+                collision.renamed_to = Some(renamed.clone());
+                renamed
+            }
+            CaseCollisions::LastWins => {
+                warn!(
+                    "{:?} replaces {:?}: their names differ only in case",
+                    name, collision.earlier
+                );
+                // Only a file this extraction wrote is removed
+                if let Some(index) = outcome.done.iter().position(|p| p == &collision.earlier) {
+                    outcome.done.remove(index);
+                    let _ = fs::remove_file(output_dir.join(&collision.earlier));
+                    outcome.skip(&collision.earlier, SkipReason::CaseCollision);
+                }
+                seen.insert(key, name.to_path_buf());
+                name.to_path_buf()
+            }
+        };
+        outcome.case_collisions.push(collision);
+        Ok(placed)
+    }
+}
+
+/// `name` folded for comparison
+///
+/// Names that differ only in Unicode normalization clash as well, as they
+/// do on macOS file systems.
+fn fold(name: &Path) -> String {
+    comparable(name).to_string_lossy().to_lowercase()
+}
+
+/// `name` with a number added, as `readme (1).md`, clashing with neither
+/// the names seen so far nor the files in `output_dir`
+fn unused_name(name: &Path, seen: &HashMap<String, PathBuf>, output_dir: &Path) -> PathBuf {
+    let stem = name.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = name.extension().map(|ext| ext.to_string_lossy());
+    (1..)
+        .map(|counter| {
+            name.with_file_name(match &extension {
+                Some(ext) => format!("{} ({}).{}", stem, counter, ext),
+                None => format!("{} ({})", stem, counter),
+            })
+        })
+        .find(|candidate| {
+            !seen.contains_key(&fold(candidate)) && !output_dir.join(candidate).exists()
+        })
+        .expect("some numbered name is unused")
+}
@@ -0,0 +1,327 @@
+//! Checksums of the files inside an archive
+//!
+//! Every file entry is hashed straight from the archive stream, so nothing
+//! is extracted. [`EntryChecksum::line`] formats a result the way
+//! `sha256sum` and friends do, so the output can be saved as a `SHA256SUMS`
+//! file and checked with `sha256sum -c` inside the extracted tree.
+
+use super::detect::detect_format;
+use crate::buffer::BufferPool;
+use crate::config::Config;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use sevenz_rust::{Password, SevenZReader};
+use sha2::Digest;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::debug;
+use zip::ZipArchive;
+
+/// Hash function used for checksums, manifests and finding duplicates
+///
+/// Blake3, the default, is what manifests were always hashed with. SHA-256
+/// suits checksums other tools must check, and xxh3 is much faster still
+/// but only tells files apart, with no protection against tampering.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    #[default]
+    #[serde(alias = "b3")]
+    Blake3,
+    Xxh3,
+}
+
+impl HashAlgorithm {
+    /// Lower-case name, as accepted by [`FromStr`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Md5 => "md5",
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Sha512 => "sha512",
+            HashAlgorithm::Blake3 => "blake3",
+            HashAlgorithm::Xxh3 => "xxh3",
+        }
+    }
+
+    /// Hash for new manifests and the hashes taken while packing for them:
+    /// `hashing.manifest` from the configuration
+    pub fn for_manifests() -> Self {
+        Config::load_or_default().hashing.manifest
+    }
+
+    /// Hash for finding files with the same content:
+    /// `hashing.dedupe` from the configuration
+    pub fn for_dedupe() -> Self {
+        Config::load_or_default().hashing.dedupe
+    }
+
+    pub(crate) fn hasher(&self) -> Hasher {
+        match self {
+            HashAlgorithm::Md5 => Hasher::Md5(md5::Md5::new()),
+            HashAlgorithm::Sha1 => Hasher::Sha1(sha1::Sha1::new()),
+            HashAlgorithm::Sha256 => Hasher::Sha256(sha2::Sha256::new()),
+            HashAlgorithm::Sha512 => Hasher::Sha512(sha2::Sha512::new()),
+            HashAlgorithm::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+            HashAlgorithm::Xxh3 => Hasher::Xxh3(Box::new(xxhash_rust::xxh3::Xxh3::new())),
+        }
+    }
+}
+
+impl FromStr for HashAlgorithm {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().replace('-', "").as_str() {
+            "md5" => Ok(HashAlgorithm::Md5),
+            "sha1" => Ok(HashAlgorithm::Sha1),
+            "sha256" => Ok(HashAlgorithm::Sha256),
+            "sha512" => Ok(HashAlgorithm::Sha512),
+            "blake3" | "b3" => Ok(HashAlgorithm::Blake3),
+            "xxh3" => Ok(HashAlgorithm::Xxh3),
+            _ => Err(Error::UnsupportedFormat(format!(
+                "Unknown checksum algorithm: {} (expected md5, sha1, sha256, sha512, blake3 or xxh3)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Checksum of one file in an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryChecksum {
+    /// Path within the archive, without a leading `./`
+    pub path: PathBuf,
+    /// Uncompressed size
+    pub size: u64,
+    /// Lower-case hex digest
+    pub digest: String,
+}
+
+impl EntryChecksum {
+    /// The entry as a line of a coreutils checksum file, without the newline
+    ///
+    /// Names holding a backslash or a line break are escaped and the line
+    /// starts with a backslash, as `sha256sum` does.
+    pub fn line(&self) -> String {
+        let name = self.path.to_string_lossy();
+        if name.contains(['\\', '\n', '\r']) {
+            let escaped = name
+                .replace('\\', "\\\\")
+                .replace('\n', "\\n")
+                .replace('\r', "\\r");
+            format!("\\{}  {}", self.digest, escaped)
+        } else {
+            format!("{}  {}", self.digest, name)
+        }
+    }
+}
+
+/// Hash every file in `archive`, in archive order
+///
+/// Directories, symlinks and other entries without data of their own are
+/// left out.
+pub fn checksums<P: AsRef<Path>>(
+    archive: P,
+    algorithm: HashAlgorithm,
+) -> Result<Vec<EntryChecksum>> {
+    let mut entries = Vec::new();
+    checksums_with(archive, algorithm, |entry| {
+        entries.push(entry);
+        Ok(())
+    })?;
+    Ok(entries)
+}
+
+/// Like [`checksums`], handing each checksum to `on_entry` as soon as the
+/// entry has been read
+///
+/// An error from `on_entry` stops the scan and is returned.
+pub fn checksums_with<P, F>(archive: P, algorithm: HashAlgorithm, mut on_entry: F) -> Result<()>
+where
+    P: AsRef<Path>,
+    F: FnMut(EntryChecksum) -> Result<()>,
+{
+    let archive = archive.as_ref();
+    debug!("Computing {} checksums for {:?}", algorithm, archive);
+
+    let format = detect_format(archive)?;
+    match format {
+        "zip" => checksums_zip(archive, algorithm, &mut on_entry),
+        "7z" => checksums_7z(archive, algorithm, &mut on_entry),
+        _ => checksums_tar(archive, format, algorithm, &mut on_entry),
+    }
+}
+
+fn checksums_zip(
+    archive: &Path,
+    algorithm: HashAlgorithm,
+    on_entry: &mut dyn FnMut(EntryChecksum) -> Result<()>,
+) -> Result<()> {
+    let mut zip = ZipArchive::new(File::open(archive)?)?;
+
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i)?;
+        if entry.is_dir() || entry.is_symlink() {
+            continue;
+        }
+        let (digest, size) = hash(algorithm, &mut entry)?;
+        on_entry(EntryChecksum {
+            path: entry_path(entry.name()),
+            size,
+            digest,
+        })?;
+    }
+
+    Ok(())
+}
+
+fn checksums_7z(
+    archive: &Path,
+    algorithm: HashAlgorithm,
+    on_entry: &mut dyn FnMut(EntryChecksum) -> Result<()>,
+) -> Result<()> {
+    let mut reader = SevenZReader::open(archive, Password::empty())
+        .map_err(|e| Error::ArchiveError(format!("Failed to read 7z archive: {}", e)))?;
+
+    // Errors from `on_entry` cannot cross the reader's callback, so the
+    // first one is kept here and the scan is stopped
+    let mut failure = None;
+    let result = reader.for_each_entries(|entry, data| {
+        if entry.is_directory || entry.is_anti_item {
+            return Ok(true);
+        }
+        let (digest, size) = hash(algorithm, data)?;
+        match on_entry(EntryChecksum {
+            path: entry_path(&entry.name),
+            size,
+            digest,
+        }) {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                failure = Some(e);
+                Ok(false)
+            }
+        }
+    });
+    if let Some(e) = failure {
+        return Err(e);
+    }
+    result.map_err(|e| Error::ArchiveError(format!("Failed to read 7z archive: {}", e)))
+}
+
+fn checksums_tar(
+    archive: &Path,
+    format: &str,
+    algorithm: HashAlgorithm,
+    on_entry: &mut dyn FnMut(EntryChecksum) -> Result<()>,
+) -> Result<()> {
+    let file = File::open(archive)?;
+    let reader: Box<dyn Read> = match format {
+        "tar.gz" => Box::new(flate2::read::GzDecoder::new(file)),
+        "tar.xz" => Box::new(xz2::read::XzDecoder::new(file)),
+        "tar.zst" => Box::new(zstd::stream::read::Decoder::new(file)?),
+        "tar.br" => Box::new(brotli::Decompressor::new(file, 4096)),
+        _ => Box::new(file),
+    };
+    let mut tar = tar::Archive::new(reader);
+
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        let entry_type = entry.header().entry_type();
+        if !entry_type.is_file() && !entry_type.is_contiguous() {
+            continue;
+        }
+        let path = entry_path(&String::from_utf8_lossy(&entry.path_bytes()));
+        let (digest, size) = hash(algorithm, &mut entry)?;
+        on_entry(EntryChecksum { path, size, digest })?;
+    }
+
+    Ok(())
+}
+
+/// Archive name as a relative path, the way it is extracted
+fn entry_path(name: &str) -> PathBuf {
+    let mut name = name;
+    while let Some(rest) = name.strip_prefix("./") {
+        name = rest;
+    }
+    PathBuf::from(name)
+}
+
+/// Digest of everything `reader` yields, with the number of bytes read
+fn hash(algorithm: HashAlgorithm, reader: &mut dyn Read) -> io::Result<(String, u64)> {
+    let mut hasher = algorithm.hasher();
+    let mut buffer = BufferPool::global().get();
+    let mut size = 0u64;
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+        size += n as u64;
+    }
+    Ok((hasher.hex(), size))
+}
+
+/// A hash of one [`HashAlgorithm`] being taken
+pub(crate) enum Hasher {
+    Md5(md5::Md5),
+    Sha1(sha1::Sha1),
+    Sha256(sha2::Sha256),
+    Sha512(sha2::Sha512),
+    Blake3(Box<blake3::Hasher>),
+    Xxh3(Box<xxhash_rust::xxh3::Xxh3>),
+}
+
+impl Hasher {
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Md5(h) => h.update(data),
+            Hasher::Sha1(h) => h.update(data),
+            Hasher::Sha256(h) => h.update(data),
+            Hasher::Sha512(h) => h.update(data),
+            Hasher::Blake3(h) => {
+                h.update(data);
+            }
+            Hasher::Xxh3(h) => h.update(data),
+        }
+    }
+
+    /// Lower-case hex digest of everything hashed so far
+    pub(crate) fn hex(&self) -> String {
+        match self {
+            Hasher::Md5(h) => format!("{:x}", h.clone().finalize()),
+            Hasher::Sha1(h) => format!("{:x}", h.clone().finalize()),
+            Hasher::Sha256(h) => format!("{:x}", h.clone().finalize()),
+            Hasher::Sha512(h) => format!("{:x}", h.clone().finalize()),
+            Hasher::Blake3(h) => h.finalize().to_hex().to_string(),
+            Hasher::Xxh3(h) => format!("{:016x}", h.digest()),
+        }
+    }
+}
+
+impl io::Write for Hasher {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.update(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,291 @@
+//! Files stored more than once in an archive
+//!
+//! [`dedupe_report`] hashes every file as it streams out of the archive,
+//! with [`HashAlgorithm::for_dedupe`], and groups those with the same
+//! content, with what storing each group once would save. [`deduplicate`] does that for a tar archive: every copy after
+//! the first becomes a hard link entry naming the first, so its data is
+//! stored once. Extracting such an archive links the copies together, so
+//! they share their content on disk as well. Zip and 7z archives have no
+//! entry that refers to another and can only be reported on.
+
+use super::checksum::{checksums_with, HashAlgorithm};
+use super::detect::detect_format;
+use super::meta::is_global_header;
+use super::modify::{tar_reader, temp_path, write_tar, TarBuilder};
+use crate::{Error, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Files in an archive that hold the same content
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DuplicateGroup {
+    /// Size of each copy
+    pub size: u64,
+    /// Digest of the content, taken with the report's hash
+    pub digest: String,
+    /// Every copy, in archive order; the first is the one kept by
+    /// [`deduplicate`]
+    pub paths: Vec<PathBuf>,
+}
+
+impl DuplicateGroup {
+    /// Bytes saved by storing the content once
+    pub fn savings(&self) -> u64 {
+        self.size * (self.paths.len() as u64 - 1)
+    }
+}
+
+/// Duplicate content found in an archive
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DedupeReport {
+    /// Hash the files were told apart by
+    pub algorithm: HashAlgorithm,
+    /// Files looked at
+    pub files: usize,
+    /// Their total uncompressed size
+    pub size: u64,
+    /// Groups of two or more files with the same content, those saving the
+    /// most first
+    pub groups: Vec<DuplicateGroup>,
+}
+
+impl DedupeReport {
+    /// Copies that could be dropped, not counting the one kept of each group
+    pub fn duplicates(&self) -> usize {
+        self.groups.iter().map(|group| group.paths.len() - 1).sum()
+    }
+
+    /// Uncompressed bytes saved by storing every group once
+    pub fn savings(&self) -> u64 {
+        self.groups.iter().map(DuplicateGroup::savings).sum()
+    }
+
+    /// Group files by content; empty files are left out, as they cost
+    /// nothing to store
+    fn from_files(
+        algorithm: HashAlgorithm,
+        files: impl IntoIterator<Item = (PathBuf, u64, String)>,
+    ) -> Self {
+        let mut report = DedupeReport {
+            algorithm,
+            ..DedupeReport::default()
+        };
+        let mut groups: Vec<DuplicateGroup> = Vec::new();
+        let mut index: HashMap<(u64, String), usize> = HashMap::new();
+        for (path, size, digest) in files {
+            report.files += 1;
+            report.size += size;
+            if size == 0 {
+                continue;
+            }
+            match index.get(&(size, digest.clone())) {
+                Some(&i) => groups[i].paths.push(path),
+                None => {
+                    index.insert((size, digest.clone()), groups.len());
+                    groups.push(DuplicateGroup {
+                        size,
+                        digest,
+                        paths: vec![path],
+                    });
+                }
+            }
+        }
+
+        groups.retain(|group| group.paths.len() > 1);
+        groups.sort_by(|a, b| {
+            b.savings()
+                .cmp(&a.savings())
+                .then_with(|| a.paths[0].cmp(&b.paths[0]))
+        });
+        report.groups = groups;
+        report
+    }
+}
+
+/// Find the files of `archive` whose content is stored more than once
+pub fn dedupe_report<P: AsRef<Path>>(archive: P) -> Result<DedupeReport> {
+    let algorithm = HashAlgorithm::for_dedupe();
+    let mut files = Vec::new();
+    checksums_with(archive, algorithm, |entry| {
+        files.push((entry.path, entry.size, entry.digest));
+        Ok(())
+    })?;
+    Ok(DedupeReport::from_files(algorithm, files))
+}
+
+/// Rewrite the tar archive `archive` storing each duplicate once, into
+/// `output`, or in place when it is `None`
+///
+/// The archive is read twice: once to find the duplicates and once to copy
+/// it. It is written compressed as before, and an archive without
+/// duplicates is left as it is. Returns what was found.
+pub fn deduplicate<P: AsRef<Path>>(archive: P, output: Option<&Path>) -> Result<DedupeReport> {
+    let archive = archive.as_ref();
+    let format = detect_format(archive)?;
+    if matches!(format, "zip" | "7z") {
+        return Err(Error::UnsupportedOperation(format!(
+            "Storing duplicates once in {} archives, which cannot link one entry to another",
+            format
+        )));
+    }
+
+    let algorithm = HashAlgorithm::for_dedupe();
+    let files = tar_files(archive, format, algorithm)?;
+    // Named the way they are extracted, as `dedupe_report` does
+    let report = DedupeReport::from_files(
+        algorithm,
+        files.iter().map(|(path, size, digest)| {
+            let path = path.strip_prefix(".").unwrap_or(path).to_path_buf();
+            (path, *size, digest.clone())
+        }),
+    );
+    if report.groups.is_empty() && output.is_none() {
+        return Ok(report);
+    }
+
+    // Each file after the first copy names the entry it links to
+    let mut first: HashMap<(u64, &str), &Path> = HashMap::new();
+    let links: Vec<Option<PathBuf>> = files
+        .iter()
+        .map(|(path, size, digest)| {
+            if *size == 0 {
+                return None;
+            }
+            let kept = *first.entry((*size, digest.as_str())).or_insert(path);
+            (kept != path.as_path()).then(|| kept.to_path_buf())
+        })
+        .collect();
+
+    let destination = output.map_or_else(|| temp_path(archive), Path::to_path_buf);
+    let reader = tar_reader(archive, format)?;
+    let result = write_tar(format, &destination, |builder| {
+        copy_linking(reader, builder, &links)
+    });
+    match result {
+        Ok(()) if output.is_none() => fs::rename(&destination, archive)?,
+        Ok(()) => {}
+        Err(e) => {
+            let _ = fs::remove_file(&destination);
+            return Err(e);
+        }
+    }
+
+    info!(
+        "Stored {} duplicates of {:?} once, saving {} bytes",
+        report.duplicates(),
+        archive,
+        report.savings()
+    );
+    Ok(report)
+}
+
+/// Path, size and `algorithm` digest of every regular file in a tar
+/// archive, by the name it is stored under
+fn tar_files(
+    archive: &Path,
+    format: &str,
+    algorithm: HashAlgorithm,
+) -> Result<Vec<(PathBuf, u64, String)>> {
+    let mut tar = tar::Archive::new(tar_reader(archive, format)?);
+    let mut files = Vec::new();
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if !is_file(&entry) {
+            continue;
+        }
+        let path = entry.path()?.into_owned();
+        let mut hasher = algorithm.hasher();
+        let size = std::io::copy(&mut entry, &mut hasher)?;
+        files.push((path, size, hasher.hex()));
+    }
+    Ok(files)
+}
+
+/// Copy a tar stream into `builder`, writing the `i`th regular file as a
+/// hard link to `links[i]` when that is set
+fn copy_linking<R: Read>(
+    input: R,
+    builder: &mut TarBuilder<'_>,
+    links: &[Option<PathBuf>],
+) -> Result<()> {
+    let mut archive = tar::Archive::new(input);
+    let mut files = links.iter();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let mut header = entry.header().clone();
+        let path = entry.path()?.into_owned();
+
+        let link = if is_file(&entry) {
+            files.next().cloned().flatten()
+        } else {
+            None
+        };
+        if let Some(target) = link {
+            header.set_entry_type(tar::EntryType::Link);
+            header.set_size(0);
+            builder.append_link(&mut header, &path, &target)?;
+            continue;
+        }
+
+        let entry_type = header.entry_type();
+        if entry_type.is_symlink() || entry_type.is_hard_link() {
+            let target = entry.link_name()?.unwrap_or_default().into_owned();
+            builder.append_link(&mut header, &path, &target)?;
+        } else if is_global_header(&header) {
+            builder.append(&header, &mut entry)?;
+        } else {
+            builder.append_data(&mut header, &path, &mut entry)?;
+        }
+    }
+    Ok(())
+}
+
+fn is_file<R: Read>(entry: &tar::Entry<'_, R>) -> bool {
+    let entry_type = entry.header().entry_type();
+    entry_type.is_file() || entry_type.is_contiguous()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, size: u64, digest: &str) -> (PathBuf, u64, String) {
+        (PathBuf::from(path), size, digest.to_string())
+    }
+
+    #[test]
+    fn test_groups_by_content() {
+        let report = DedupeReport::from_files(
+            HashAlgorithm::Blake3,
+            [
+                file("a.txt", 10, "aa"),
+                file("b.bin", 100, "bb"),
+                file("copy/a.txt", 10, "aa"),
+                file("empty", 0, "00"),
+                file("also-empty", 0, "00"),
+                file("copy/b.bin", 100, "bb"),
+                file("again/a.txt", 10, "aa"),
+            ],
+        );
+
+        assert_eq!(report.files, 7);
+        assert_eq!(report.size, 230);
+        assert_eq!(report.groups.len(), 2);
+        assert_eq!(report.groups[0].digest, "bb");
+        assert_eq!(
+            report.groups[1].paths,
+            [
+                PathBuf::from("a.txt"),
+                PathBuf::from("copy/a.txt"),
+                PathBuf::from("again/a.txt")
+            ]
+        );
+        assert_eq!(report.duplicates(), 3);
+        assert_eq!(report.savings(), 120);
+    }
+}
@@ -0,0 +1,290 @@
+//! Telling an archive's format from its content
+//!
+//! Downloads often lose their extension or get one that does not match
+//! (`download(1)`, `release.zip` that is really a tarball), so archives are
+//! read by the signature at the start of the file. The name is only
+//! consulted when the content has none Flux knows: brotli streams carry no
+//! magic number, and a damaged archive may have lost its first bytes.
+//!
+//! Every extension and format name Flux knows is in [`EXTENSIONS`] and
+//! [`COMPRESSION_NAMES`]; code that goes by a name asks [`archive_format`]
+//! or [`parse_format`] rather than matching extensions itself.
+
+use crate::strategy::Algorithm;
+use crate::{Error, Result};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use tracing::debug;
+
+/// Bytes read to tell the format: one tar header block
+const SNIFF_LEN: usize = 512;
+
+/// Leading bytes of each format with a signature, and the format they mean
+///
+/// A compressed stream is taken to hold a tar, as that is the only thing
+/// Flux compresses that way.
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (&[0x1f, 0x8b], "tar.gz"),
+    (&[0x28, 0xb5, 0x2f, 0xfd], "tar.zst"),
+    (&[0xfd, b'7', b'z', b'X', b'Z', 0x00], "tar.xz"),
+    (b"PK\x03\x04", "zip"),
+    // An empty zip is only its end of central directory
+    (b"PK\x05\x06", "zip"),
+    // First volume of a split zip
+    (b"PK\x07\x08", "zip"),
+    (&[b'7', b'z', 0xbc, 0xaf, 0x27, 0x1c], "7z"),
+];
+
+/// Archive extensions and the format each names, longest first where one
+/// ends another
+pub const EXTENSIONS: &[(&str, &str)] = &[
+    ("tar.gz", "tar.gz"),
+    ("tgz", "tar.gz"),
+    ("tar.zst", "tar.zst"),
+    ("tar.zstd", "tar.zst"),
+    ("tzst", "tar.zst"),
+    ("tar.xz", "tar.xz"),
+    ("txz", "tar.xz"),
+    ("tar.br", "tar.br"),
+    ("tar", "tar"),
+    ("zip", "zip"),
+    ("7z", "7z"),
+];
+
+/// Names of the compressions alone, taken as a tar compressed so when
+/// given as a format or as the extension of an archive being written
+///
+/// They are not archive extensions: a `.gz` found on disk or in an archive
+/// is as likely to be a single compressed file.
+pub const COMPRESSION_NAMES: &[(&str, &str)] = &[
+    ("gz", "tar.gz"),
+    ("zst", "tar.zst"),
+    ("zstd", "tar.zst"),
+    ("xz", "tar.xz"),
+    ("br", "tar.br"),
+];
+
+/// Last extension of every name in [`EXTENSIONS`] and
+/// [`COMPRESSION_NAMES`], for file pickers and file managers that filter
+/// by it
+pub const FILE_EXTENSIONS: &[&str] = &[
+    "zip", "tar", "gz", "tgz", "zst", "zstd", "tzst", "xz", "txz", "br", "7z",
+];
+
+/// Extensions of tar archives compressed with bzip2, which Flux recognises
+/// but cannot read or write
+const BZIP2_EXTENSIONS: &[&str] = &["tar.bz2", "tbz2", "tbz", "tb2"];
+
+/// Leading bytes of a bzip2 stream, before its block size digit
+const BZIP2_SIGNATURE: &[u8] = b"BZh";
+
+/// Format of `archive` according to its extension
+pub fn archive_format(archive: &Path) -> Option<&'static str> {
+    let name = archive.file_name()?.to_string_lossy().to_lowercase();
+    EXTENSIONS
+        .iter()
+        .find(|(ext, _)| has_extension(&name, ext))
+        .map(|(_, format)| *format)
+}
+
+/// Format an archive written to `output` gets from its name: as
+/// [`archive_format`], or a tar of the compression a bare `.gz`, `.zst`,
+/// `.xz` or `.br` names
+pub fn output_format(output: &Path) -> Option<&'static str> {
+    archive_format(output).or_else(|| {
+        let name = output.file_name()?.to_string_lossy().to_lowercase();
+        COMPRESSION_NAMES
+            .iter()
+            .find(|(ext, _)| has_extension(&name, ext))
+            .map(|(_, format)| *format)
+    })
+}
+
+/// `name` without the archive extension [`archive_format`] goes by, or
+/// `None` when it has none or would be left empty
+pub fn strip_archive_extension(name: &str) -> Option<&str> {
+    let lower = name.to_lowercase();
+    EXTENSIONS
+        .iter()
+        .find(|(ext, _)| has_extension(&lower, ext))
+        .and_then(|(ext, _)| name.get(..name.len() - ext.len() - 1))
+        .filter(|stem| !stem.is_empty())
+}
+
+/// Format of `archive` as named by Flux (`tar`, `tar.gz`, `tar.zst`,
+/// `tar.xz`, `tar.br`, `zip` or `7z`), from its content or else its name
+///
+/// Fails with [`Error::UnsupportedFormat`] when neither tells.
+pub fn detect_format(archive: &Path) -> Result<&'static str> {
+    if let Some(format) = sniff_format(archive) {
+        if archive_format(archive).is_some_and(|named| named != format) {
+            debug!("{:?} holds a {} archive despite its name", archive, format);
+        }
+        return Ok(format);
+    }
+    archive_format(archive).ok_or_else(|| {
+        let name = archive.file_name().map_or_else(
+            || archive.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        if has_bzip2_signature(archive) {
+            return Error::UnsupportedFormat(format!("{} (bzip2 is not supported)", name));
+        }
+        unsupported_format(&name)
+    })
+}
+
+/// Format of `archive` when it is given, as by `--format`, or else as
+/// [`detect_format`] finds it
+pub fn resolve_format(archive: &Path, format: Option<&str>) -> Result<&'static str> {
+    match format {
+        Some(name) => parse_format(name).ok_or_else(|| unsupported_format(name)),
+        None => detect_format(archive),
+    }
+}
+
+/// Format a signature at the start of `archive` shows, if any
+///
+/// An archive that cannot be read shows none, leaving the error to
+/// whatever opens it next.
+pub fn sniff_format(archive: &Path) -> Option<&'static str> {
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    File::open(archive)
+        .and_then(|file| file.take(SNIFF_LEN as u64).read_to_end(&mut head))
+        .ok()?;
+    sniff_bytes(&head)
+}
+
+/// Format the leading bytes `head` of an archive show, if any
+pub fn sniff_bytes(head: &[u8]) -> Option<&'static str> {
+    SIGNATURES
+        .iter()
+        .find(|(magic, _)| head.starts_with(magic))
+        .map(|(_, format)| *format)
+        .or_else(|| is_tar_header(head).then_some("tar"))
+}
+
+/// Canonical name of the format `name`, such as `tar.gz` for `tgz` or
+/// `gz`
+pub fn parse_format(name: &str) -> Option<&'static str> {
+    let name = name.to_ascii_lowercase();
+    EXTENSIONS
+        .iter()
+        .chain(COMPRESSION_NAMES)
+        .find(|(alias, _)| *alias == name)
+        .map(|(_, format)| *format)
+}
+
+/// Compression around the tar of the format `format`, `None` for zip and 7z
+pub(crate) fn tar_compression(format: &str) -> Option<Algorithm> {
+    match format {
+        "tar" => Some(Algorithm::Store),
+        "tar.gz" => Some(Algorithm::Gzip),
+        "tar.zst" => Some(Algorithm::Zstd),
+        "tar.xz" => Some(Algorithm::Xz),
+        "tar.br" => Some(Algorithm::Brotli),
+        _ => None,
+    }
+}
+
+/// Whether `name`, in lower case, ends in the extension `ext`
+fn has_extension(name: &str, ext: &str) -> bool {
+    name.strip_suffix(ext)
+        .is_some_and(|stem| stem.ends_with('.'))
+}
+
+/// Error for the format or file name `name` that Flux cannot read or
+/// write, saying so plainly for bzip2
+pub(crate) fn unsupported_format(name: &str) -> Error {
+    let lower = name.to_lowercase();
+    let bzip2 = BZIP2_EXTENSIONS
+        .iter()
+        .chain(&["bz2"])
+        .any(|ext| lower == *ext || has_extension(&lower, ext));
+    if bzip2 {
+        Error::UnsupportedFormat(format!("{} (bzip2 is not supported)", name))
+    } else {
+        Error::UnsupportedFormat(name.to_string())
+    }
+}
+
+/// Whether `archive` starts as a bzip2 stream does
+fn has_bzip2_signature(archive: &Path) -> bool {
+    let mut head = [0; 4];
+    File::open(archive)
+        .and_then(|mut file| file.read_exact(&mut head))
+        .is_ok_and(|()| head.starts_with(BZIP2_SIGNATURE) && head[3].is_ascii_digit())
+}
+
+/// Whether `head` starts with a tar header: a POSIX one names itself, an
+/// old one only has its checksum to go by
+fn is_tar_header(head: &[u8]) -> bool {
+    if head.len() < SNIFF_LEN || head[0] == 0 {
+        return false;
+    }
+    if &head[257..262] == b"ustar" {
+        return true;
+    }
+
+    let stored = std::str::from_utf8(&head[148..156])
+        .ok()
+        .map(|field| field.trim_matches(|c: char| c == '\0' || c == ' '))
+        .and_then(|field| u32::from_str_radix(field, 8).ok());
+    // The checksum is taken with its own field counted as spaces
+    let actual: u32 = head[..148]
+        .iter()
+        .chain(&[b' '; 8])
+        .chain(&head[156..SNIFF_LEN])
+        .map(|&byte| u32::from(byte))
+        .sum();
+    stored == Some(actual)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_signatures() {
+        assert_eq!(sniff_bytes(&[0x1f, 0x8b, 0x08, 0x00]), Some("tar.gz"));
+        assert_eq!(sniff_bytes(b"PK\x03\x04rest"), Some("zip"));
+        assert_eq!(sniff_bytes(b"7z\xbc\xaf\x27\x1c\x00\x04"), Some("7z"));
+        assert_eq!(sniff_bytes(b"plain text, not an archive"), None);
+        assert_eq!(sniff_bytes(&[0; SNIFF_LEN]), None);
+    }
+
+    #[test]
+    fn test_sniff_old_tar_by_checksum() {
+        let mut header = tar::Header::new_old();
+        header.set_path("file.txt").unwrap();
+        header.set_size(4);
+        header.set_cksum();
+        assert_eq!(sniff_bytes(header.as_bytes()), Some("tar"));
+
+        let mut damaged = *header.as_bytes();
+        damaged[0] = b'g';
+        assert_eq!(sniff_bytes(&damaged), None);
+    }
+
+    #[test]
+    fn test_parse_format_aliases() {
+        assert_eq!(parse_format("TGZ"), Some("tar.gz"));
+        assert_eq!(parse_format("tar.zst"), Some("tar.zst"));
+        assert_eq!(parse_format("zstd"), Some("tar.zst"));
+        assert_eq!(parse_format("rar"), None);
+    }
+
+    #[test]
+    fn test_extension_tables() {
+        for (i, (ext, format)) in EXTENSIONS.iter().chain(COMPRESSION_NAMES).enumerate() {
+            let last = ext.rsplit('.').next().unwrap();
+            assert!(FILE_EXTENSIONS.contains(&last), "{} not offered", ext);
+            assert_eq!(parse_format(format), Some(*format));
+            // No earlier extension ends this one and so hides it
+            for (earlier, _) in &EXTENSIONS[..i.min(EXTENSIONS.len())] {
+                assert!(!has_extension(ext, earlier), "{} hides {}", earlier, ext);
+            }
+        }
+    }
+}
@@ -0,0 +1,358 @@
+//! Estimate of an archive's size and packing time
+//!
+//! [`estimate_pack`] compresses a sample of the inputs with the chosen
+//! format and extrapolates to the whole job. Samples are taken at evenly
+//! spaced byte offsets across all files, so large files weigh in by their
+//! size, and from the middle of each file rather than its header.
+
+use super::detect::{parse_format, tar_compression};
+use super::walk::WalkLimits;
+use super::PackOptions;
+use crate::strategy::Algorithm;
+use crate::{Error, Result};
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::debug;
+use walkdir::WalkDir;
+
+/// Most files read for a sample
+const SAMPLE_FILES: usize = 64;
+
+/// Bytes read from each sampled file
+const SAMPLE_CHUNK: u64 = 64 * 1024;
+
+/// Level used when none is given, as in [`super::pack_with_strategy`]
+const DEFAULT_LEVEL: u32 = 3;
+
+/// Level of the deflate compression used for zip entries
+const ZIP_LEVEL: u32 = 6;
+
+/// Size of a tar header block and of the padding unit
+const TAR_BLOCK: u64 = 512;
+
+/// Least a compressed tar header takes, as its name, size, time and
+/// checksum differ from file to file
+const TAR_HEADER_MIN: u64 = 16;
+
+/// Local header, central directory record and data descriptor of a zip
+/// entry, not counting its name which appears twice
+const ZIP_ENTRY_OVERHEAD: u64 = 30 + 46 + 16;
+
+/// Expected outcome of a pack job
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct PackEstimate {
+    /// Files that would be packed
+    pub files: u64,
+    /// Total size of those files
+    pub input_bytes: u64,
+    /// Bytes compressed to make the estimate
+    pub sampled_bytes: u64,
+    /// Expected size of the archive
+    pub archive_size: u64,
+    /// Expected time spent compressing
+    pub duration: Duration,
+}
+
+impl PackEstimate {
+    /// Archive size as a fraction of the input size
+    pub fn ratio(&self) -> Option<f64> {
+        (self.input_bytes > 0).then(|| self.archive_size as f64 / self.input_bytes as f64)
+    }
+
+    /// Whether every input byte was compressed, making the size exact up
+    /// to archive metadata
+    pub fn is_exact(&self) -> bool {
+        self.sampled_bytes == self.input_bytes
+    }
+}
+
+/// How the file contents are compressed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Codec {
+    /// One compressed stream around the whole tar
+    Tar(Algorithm),
+    /// Raw deflate per zip entry
+    Deflate,
+}
+
+/// A regular file that would be packed
+struct Input {
+    path: PathBuf,
+    size: u64,
+    /// Length of its name inside the archive
+    name_len: u64,
+}
+
+/// Estimate the archive that packing `inputs` into `format` would produce
+///
+/// `format` is a concrete format such as `tar.zst` or `zip`; the level,
+/// filter and symlink handling come from `options`.
+pub fn estimate_pack(
+    inputs: &[PathBuf],
+    format: &str,
+    options: &PackOptions,
+) -> Result<PackEstimate> {
+    let codec = match parse_format(format) {
+        Some("zip") => Codec::Deflate,
+        parsed => match parsed.and_then(tar_compression) {
+            Some(algorithm) => Codec::Tar(algorithm),
+            None => return Err(Error::UnsupportedFormat(format.to_string())),
+        },
+    };
+    let zip = codec == Codec::Deflate;
+    let level = if zip {
+        ZIP_LEVEL
+    } else {
+        options.level.unwrap_or(DEFAULT_LEVEL)
+    };
+
+    let files = collect_inputs(inputs, options);
+    let input_bytes: u64 = files.iter().map(|file| file.size).sum();
+    let samples = pick_samples(&files, input_bytes);
+
+    let started = Instant::now();
+    let (sampled_bytes, compressed_bytes) = if codec == Codec::Tar(Algorithm::Store) {
+        let sampled = samples.iter().map(|file| file.size.min(SAMPLE_CHUNK)).sum();
+        (sampled, sampled)
+    } else if zip {
+        // Every zip entry is compressed on its own
+        samples
+            .iter()
+            .try_fold((0, 0), |(sampled, compressed), file| {
+                let (read, written) = compress_samples(&[*file], codec, level)?;
+                Ok::<_, Error>((sampled + read, compressed + written))
+            })?
+    } else {
+        // A compressed tar is one stream across all files
+        compress_samples(&samples, codec, level)?
+    };
+    let elapsed = started.elapsed();
+
+    let ratio = if sampled_bytes > 0 {
+        compressed_bytes as f64 / sampled_bytes as f64
+    } else {
+        1.0
+    };
+    let scale = if sampled_bytes > 0 {
+        input_bytes as f64 / sampled_bytes as f64
+    } else {
+        0.0
+    };
+
+    let overhead: u64 = if zip {
+        files
+            .iter()
+            .map(|file| ZIP_ENTRY_OVERHEAD + 2 * file.name_len)
+            .sum::<u64>()
+            + 22
+    } else {
+        // Header plus half a block of padding per file, and the end marker;
+        // the zeros compress to almost nothing, the headers partly
+        let raw = files.len() as u64 * (TAR_BLOCK + TAR_BLOCK / 2) + 2 * TAR_BLOCK;
+        ((raw as f64 * ratio) as u64).max(files.len() as u64 * TAR_HEADER_MIN)
+    };
+
+    let estimate = PackEstimate {
+        files: files.len() as u64,
+        input_bytes,
+        sampled_bytes,
+        archive_size: (input_bytes as f64 * ratio) as u64 + overhead,
+        duration: elapsed.mul_f64(scale),
+    };
+    debug!(
+        "Estimated {} for {} files: {:?}",
+        format,
+        files.len(),
+        estimate
+    );
+
+    Ok(estimate)
+}
+
+/// Total size of the regular files below `inputs` that packing with
+/// `options` keeps
+pub(crate) fn input_size<P: AsRef<Path>>(inputs: &[P], options: &PackOptions) -> u64 {
+    let inputs: Vec<PathBuf> = inputs.iter().map(|p| p.as_ref().to_path_buf()).collect();
+    collect_inputs(&inputs, options)
+        .iter()
+        .map(|file| file.size)
+        .sum()
+}
+
+/// Regular files below `inputs` that packing with `options` keeps
+fn collect_inputs(inputs: &[PathBuf], options: &PackOptions) -> Vec<Input> {
+    let mut files = Vec::new();
+
+    for input in inputs {
+        if input.is_file() {
+            files.push(Input {
+                path: input.clone(),
+                size: input.metadata().map(|m| m.len()).unwrap_or(0),
+                name_len: input.file_name().map_or(0, |n| n.len() as u64),
+            });
+            continue;
+        }
+
+        let base = input.parent().unwrap_or(input);
+        let mut limits = WalkLimits::new(options.follow_symlinks, options.max_depth);
+        let mut walker = WalkDir::new(input)
+            .follow_links(options.follow_symlinks)
+            .into_iter();
+        while let Some(entry) = walker.next() {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            if options.filter.excludes_entry(input, &entry) || limits.leave_out(&entry).is_some() {
+                if entry.file_type().is_dir() {
+                    walker.skip_current_dir();
+                }
+                continue;
+            }
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            files.push(Input {
+                path: entry.path().to_path_buf(),
+                size: entry.metadata().map(|m| m.len()).unwrap_or(0),
+                name_len: entry
+                    .path()
+                    .strip_prefix(base)
+                    .map_or(0, |p| p.as_os_str().len() as u64),
+            });
+        }
+    }
+
+    files
+}
+
+/// Files holding evenly spaced byte offsets across all inputs
+fn pick_samples(files: &[Input], total: u64) -> Vec<&Input> {
+    if files.len() <= SAMPLE_FILES || total == 0 {
+        return files.iter().collect();
+    }
+
+    let step = total / SAMPLE_FILES as u64;
+    let mut samples: Vec<&Input> = Vec::with_capacity(SAMPLE_FILES);
+    let mut offset = 0;
+    let mut next = 0;
+    for file in files {
+        offset += file.size;
+        if offset > next {
+            samples.push(file);
+            while next < offset {
+                next += step.max(1);
+            }
+        }
+    }
+
+    samples
+}
+
+/// Compress a chunk of each file as one stream, returning the bytes read
+/// and written
+fn compress_samples(samples: &[&Input], codec: Codec, level: u32) -> Result<(u64, u64)> {
+    let mut counter = CountingWriter::default();
+    let mut read = 0;
+
+    {
+        let mut encoder = encoder(&mut counter, codec, level)?;
+        for sample in samples {
+            match read_chunk(&sample.path, sample.size) {
+                Ok(chunk) => {
+                    read += chunk.len() as u64;
+                    encoder.write_all(&chunk)?;
+                }
+                Err(e) => debug!("Skipping sample {:?}: {}", sample.path, e),
+            }
+        }
+        // Dropping the encoder finishes its stream
+    }
+
+    Ok((read, counter.0))
+}
+
+/// Chunks of the regular files below `inputs` that packing with `options`
+/// keeps, taken as for an estimate, and the total size of those files
+pub(super) fn sample_chunks<P: AsRef<Path>>(
+    inputs: &[P],
+    options: &PackOptions,
+) -> (Vec<Vec<u8>>, u64) {
+    let inputs: Vec<PathBuf> = inputs.iter().map(|p| p.as_ref().to_path_buf()).collect();
+    let files = collect_inputs(&inputs, options);
+    let total = files.iter().map(|file| file.size).sum();
+    let chunks = pick_samples(&files, total)
+        .into_iter()
+        .filter_map(|sample| match read_chunk(&sample.path, sample.size) {
+            Ok(chunk) => Some(chunk),
+            Err(e) => {
+                debug!("Skipping sample {:?}: {}", sample.path, e);
+                None
+            }
+        })
+        .collect();
+    (chunks, total)
+}
+
+/// Compress `chunks` as one tar stream would, returning the bytes written
+pub(super) fn compress_chunks(chunks: &[Vec<u8>], algorithm: Algorithm, level: u32) -> Result<u64> {
+    let mut counter = CountingWriter::default();
+    {
+        let mut encoder = encoder(&mut counter, Codec::Tar(algorithm), level)?;
+        for chunk in chunks {
+            encoder.write_all(chunk)?;
+        }
+        // Dropping the encoder finishes its stream
+    }
+    Ok(counter.0)
+}
+
+/// An encoder for `codec` writing to `counter`, which finishes its stream
+/// when dropped
+fn encoder(counter: &mut CountingWriter, codec: Codec, level: u32) -> Result<Box<dyn Write + '_>> {
+    Ok(match codec {
+        Codec::Deflate => Box::new(flate2::write::DeflateEncoder::new(
+            counter,
+            flate2::Compression::new(level),
+        )),
+        Codec::Tar(Algorithm::Store) => Box::new(counter),
+        Codec::Tar(Algorithm::Gzip) => Box::new(flate2::write::GzEncoder::new(
+            counter,
+            flate2::Compression::new(level),
+        )),
+        Codec::Tar(Algorithm::Zstd) => {
+            Box::new(zstd::stream::write::Encoder::new(counter, level as i32)?.auto_finish())
+        }
+        Codec::Tar(Algorithm::Xz) => Box::new(xz2::write::XzEncoder::new(counter, level)),
+        Codec::Tar(Algorithm::Brotli) => {
+            Box::new(brotli::CompressorWriter::new(counter, 4096, level, 22))
+        }
+    })
+}
+
+/// Up to [`SAMPLE_CHUNK`] bytes from the middle of a file
+fn read_chunk(path: &Path, size: u64) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let len = size.min(SAMPLE_CHUNK);
+    file.seek(SeekFrom::Start((size - len) / 2))?;
+
+    let mut chunk = Vec::with_capacity(len as usize);
+    file.take(len).read_to_end(&mut chunk)?;
+    Ok(chunk)
+}
+
+/// Writer that only counts what is written to it
+#[derive(Default)]
+struct CountingWriter(u64);
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
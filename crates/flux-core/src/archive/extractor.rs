@@ -1,10 +1,17 @@
 //! Extractor trait for archive operations
 
-use crate::Result;
+use super::links::SymlinkFallback;
+use super::paths;
+use crate::progress::ProgressSink;
+use crate::security::entry_output_path;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
 
 /// Entry in an archive
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArchiveEntry {
     /// Path within the archive (relative to archive root)
     pub path: PathBuf,
@@ -39,6 +46,33 @@ pub struct ExtractEntryOptions {
     pub preserve_timestamps: bool,
     /// Whether to follow symlinks when extracting
     pub follow_symlinks: bool,
+    /// Password for encrypted entries
+    pub password: Option<String>,
+    /// What to write for a symlink entry where the platform refuses to
+    /// create one
+    pub symlink_fallback: SymlinkFallback,
+    /// Number of leading path components to strip from the entry's name;
+    /// an entry with no more components than that is not written
+    pub strip_components: Option<usize>,
+}
+
+impl ExtractEntryOptions {
+    /// Where the entry named `entry_path` is written under `destination`,
+    /// or `None` when `strip_components` removes all of it
+    pub(crate) fn output_path(
+        &self,
+        destination: &Path,
+        entry_path: &Path,
+    ) -> Result<Option<PathBuf>> {
+        let name = match self.strip_components {
+            Some(count) => match paths::strip_components(entry_path, count) {
+                Some(name) => name,
+                None => return Ok(None),
+            },
+            None => entry_path.to_path_buf(),
+        };
+        entry_output_path(destination, &name).map(Some)
+    }
 }
 
 /// Trait for archive extractors
@@ -55,8 +89,41 @@ pub trait Extractor: Send + Sync {
         options: ExtractEntryOptions,
     ) -> Result<()>;
 
+    /// Extract a single entry like [`Extractor::extract_entry`], reporting
+    /// its bytes to `progress` as they are written
+    ///
+    /// Extractors that stream an entry to disk report it chunk by chunk and
+    /// stop when `progress` is cancelled; the default reports the whole
+    /// entry once it is extracted.
+    fn extract_entry_with_progress(
+        &self,
+        source: &Path,
+        entry: &ArchiveEntry,
+        destination: &Path,
+        options: ExtractEntryOptions,
+        progress: &dyn ProgressSink,
+    ) -> Result<()> {
+        self.extract_entry(source, entry, destination, options)?;
+        if !entry.is_dir && !entry.is_symlink {
+            progress.advance(entry.size);
+        }
+        Ok(())
+    }
+
     /// Get the format name for this extractor
     fn format_name(&self) -> &'static str;
+
+    /// Open a file entry for reading without extracting it to disk
+    ///
+    /// Data is decoded as the returned reader is consumed, so previewing the
+    /// start of a large entry does not decompress all of it.
+    fn open_entry(&self, source: &Path, entry: &ArchiveEntry) -> Result<Box<dyn Read + Send>> {
+        let _ = (source, entry);
+        Err(Error::UnsupportedOperation(format!(
+            "Reading single entries from {} archives is not supported",
+            self.format_name()
+        )))
+    }
 }
 
 /// Conflict resolution action
@@ -99,3 +166,88 @@ impl ConflictHandler for OverwriteConflictHandler {
         ConflictAction::Overwrite
     }
 }
+
+/// Number of chunks buffered between an entry decoder and its reader
+const PIPE_CAPACITY: usize = 4;
+
+/// Run `produce` on a background thread and return a reader for what it writes
+///
+/// Archive readers borrow their archive, so an entry cannot be returned as a
+/// standalone reader. Instead the entry is decoded on its own thread and
+/// passed over a bounded channel. Errors before the first chunk, such as a
+/// missing entry, are returned directly; dropping the reader makes the next
+/// write fail, which stops the producer.
+pub(crate) fn stream_entry<F>(produce: F) -> Result<Box<dyn Read + Send>>
+where
+    F: FnOnce(&mut dyn Write) -> Result<()> + Send + 'static,
+{
+    let (sender, receiver) = sync_channel(PIPE_CAPACITY);
+
+    std::thread::spawn(move || {
+        let mut writer = PipeWriter {
+            sender: sender.clone(),
+        };
+        if let Err(e) = produce(&mut writer) {
+            let _ = sender.send(Err(e));
+        }
+    });
+
+    let buffer = match receiver.recv() {
+        Ok(Ok(chunk)) => chunk,
+        Ok(Err(e)) => return Err(e),
+        // Finished without writing anything: an empty entry
+        Err(_) => Vec::new(),
+    };
+
+    Ok(Box::new(PipeReader {
+        receiver,
+        buffer,
+        position: 0,
+    }))
+}
+
+struct PipeWriter {
+    sender: SyncSender<Result<Vec<u8>>>,
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        self.sender
+            .send(Ok(buf.to_vec()))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Entry reader was dropped"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+struct PipeReader {
+    receiver: Receiver<Result<Vec<u8>>>,
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.buffer.len() {
+            match self.receiver.recv() {
+                Ok(Ok(chunk)) => {
+                    self.buffer = chunk;
+                    self.position = 0;
+                }
+                Ok(Err(e)) => return Err(io::Error::other(e)),
+                Err(_) => return Ok(0),
+            }
+        }
+
+        let n = buf.len().min(self.buffer.len() - self.position);
+        buf[..n].copy_from_slice(&self.buffer[self.position..self.position + n]);
+        self.position += n;
+        Ok(n)
+    }
+}
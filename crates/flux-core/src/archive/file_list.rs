@@ -0,0 +1,82 @@
+//! Lists of paths to pack, as `tar -T` reads them
+//!
+//! A list names each path on a line of its own, or ends each with a NUL
+//! byte as `find -print0` writes them, for names holding newlines. The
+//! archive holds exactly the listed paths: a listed directory is stored as
+//! an entry of its own, without its contents, so that the output of `find`
+//! packs each file once. Relative paths are taken from a base directory
+//! and keep the folders they name below it.
+
+use crate::archive::paths;
+use crate::archive::PackRoot;
+use crate::{Error, Result};
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// The paths listed in `reader`, one per line, or each ended by a NUL
+/// byte when `null_separated`
+///
+/// Empty lines are skipped, and so is the `\r` of a line ended `\r\n`.
+pub fn read_file_list(mut reader: impl Read, null_separated: bool) -> Result<Vec<PathBuf>> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data)?;
+
+    let separator = if null_separated { b'\0' } else { b'\n' };
+    let paths = data
+        .split(|&byte| byte == separator)
+        .map(|name| {
+            if null_separated {
+                name
+            } else {
+                name.strip_suffix(b"\r").unwrap_or(name)
+            }
+        })
+        .filter(|name| !name.is_empty())
+        .map(path_from_bytes)
+        .collect();
+    Ok(paths)
+}
+
+/// A [`PackRoot`] for each of `paths`, read from `base` when relative and
+/// named by their path below it
+///
+/// Absolute paths must lie below `base`. The base directory itself, as the
+/// `.` that `find .` starts with, and paths listed again are left out.
+pub fn listed_roots<P: AsRef<Path>>(base: &Path, paths: &[P]) -> Result<Vec<PackRoot>> {
+    let mut seen = HashSet::new();
+    let mut roots = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        let (source, name) = if path.is_absolute() {
+            let canonical = base.canonicalize().ok();
+            let name = path
+                .strip_prefix(base)
+                .ok()
+                .or_else(|| path.strip_prefix(canonical.as_deref()?).ok())
+                .ok_or_else(|| Error::InvalidPath(format!("{:?} is not below {:?}", path, base)))?;
+            (path.to_path_buf(), paths::relative(name).to_path_buf())
+        } else {
+            (base.join(path), paths::relative(path).to_path_buf())
+        };
+        if name.as_os_str().is_empty() || !seen.insert(name.clone()) {
+            continue;
+        }
+        roots.push(PackRoot {
+            recursive: false,
+            ..PackRoot::new(source, name)
+        });
+    }
+    Ok(roots)
+}
+
+#[cfg(unix)]
+fn path_from_bytes(name: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(name))
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(name: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(name).into_owned())
+}
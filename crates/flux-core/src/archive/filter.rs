@@ -0,0 +1,213 @@
+//! Exclusion filters applied while packing directories
+//!
+//! Filters only look inside directories: an input named explicitly is
+//! always packed, even if it would match a pattern.
+
+use crate::{Error, Result};
+use glob::Pattern;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Version control metadata directories skipped by [`PackFilter::ignore_vcs`]
+pub const VCS_DIRS: &[&str] = &[".git", ".hg", ".svn", ".bzr", "_darcs", "CVS"];
+
+/// Default patterns for `--exclude-vcs`: version control metadata plus the
+/// dependency and cache directories a checkout can rebuild on its own
+///
+/// The list used by the CLI comes from `[exclude] vcs_patterns` in the
+/// configuration file, which starts out as this one.
+pub const DEFAULT_VCS_PATTERNS: &[&str] = &[
+    ".git",
+    ".hg",
+    ".svn",
+    ".bzr",
+    "_darcs",
+    "CVS",
+    "node_modules",
+    "bower_components",
+    "__pycache__",
+    ".venv",
+    ".tox",
+];
+
+/// Rules deciding which files inside a directory are left out of an archive
+///
+/// # Example
+///
+/// ```
+/// use flux_core::archive::PackFilter;
+/// use std::path::Path;
+///
+/// let filter = PackFilter::new(&["*.log", "target"])?.ignore_vcs(true);
+/// assert!(filter.excludes(Path::new("logs/app.log"), false, 10));
+/// assert!(filter.excludes(Path::new("target"), true, 0));
+/// assert!(filter.excludes(Path::new(".git"), true, 0));
+/// assert!(!filter.excludes(Path::new("src/main.rs"), false, 10));
+/// # Ok::<(), flux_core::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PackFilter {
+    patterns: Vec<Pattern>,
+    ignore_vcs: bool,
+    max_file_size: Option<u64>,
+}
+
+/// Outcome of applying a filter to a set of inputs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FilterStats {
+    /// Files that will be packed
+    pub included_files: u64,
+    /// Total size of the packed files
+    pub included_bytes: u64,
+    /// Files left out
+    pub skipped_files: u64,
+    /// Total size of the files left out
+    pub skipped_bytes: u64,
+}
+
+impl PackFilter {
+    /// Filter excluding paths that match any of the glob `patterns`
+    ///
+    /// A pattern matches either the path relative to the packed directory
+    /// (`build/*.o`) or any single component of it (`*.log`,
+    /// `node_modules`).
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self> {
+        Self::default().exclude(patterns)
+    }
+
+    /// Also exclude paths matching `patterns`, in the same way as
+    /// [`PackFilter::new`]
+    pub fn exclude<S: AsRef<str>>(mut self, patterns: &[S]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| pattern.as_ref().trim())
+            .filter(|pattern| !pattern.is_empty())
+            .map(|pattern| {
+                Pattern::new(pattern.trim_end_matches('/')).map_err(|e| {
+                    Error::Config(format!("Invalid exclude pattern '{}': {}", pattern, e))
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        self.patterns.extend(patterns);
+        Ok(self)
+    }
+
+    /// Skip version control directories such as `.git`
+    pub fn ignore_vcs(mut self, ignore_vcs: bool) -> Self {
+        self.ignore_vcs = ignore_vcs;
+        self
+    }
+
+    /// Skip files larger than `max_file_size` bytes
+    pub fn max_file_size(mut self, max_file_size: Option<u64>) -> Self {
+        self.max_file_size = max_file_size;
+        self
+    }
+
+    /// Whether the filter lets everything through
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty() && !self.ignore_vcs && self.max_file_size.is_none()
+    }
+
+    /// Whether an entry at `relative_path` inside a packed directory is
+    /// left out; excluding a directory excludes everything below it
+    pub fn excludes(&self, relative_path: &Path, is_dir: bool, size: u64) -> bool {
+        if self.ignore_vcs
+            && relative_path
+                .components()
+                .any(|c| VCS_DIRS.iter().any(|vcs| c.as_os_str() == *vcs))
+        {
+            return true;
+        }
+
+        if !is_dir && self.max_file_size.is_some_and(|max| size > max) {
+            return true;
+        }
+
+        self.patterns.iter().any(|pattern| {
+            pattern.matches_path(relative_path)
+                || relative_path
+                    .components()
+                    .any(|c| pattern.matches(&c.as_os_str().to_string_lossy()))
+        })
+    }
+
+    /// Whether a directory walk entry below `root` is left out
+    pub(crate) fn excludes_entry(&self, root: &Path, entry: &walkdir::DirEntry) -> bool {
+        if self.is_empty() || entry.path() == root {
+            return false;
+        }
+        let relative = entry.path().strip_prefix(root).unwrap_or(entry.path());
+        let is_dir = entry.file_type().is_dir();
+        let size = if is_dir {
+            0
+        } else {
+            entry.metadata().map(|m| m.len()).unwrap_or(0)
+        };
+        self.excludes(relative, is_dir, size)
+    }
+
+    /// Count what packing `inputs` with this filter would include and skip
+    pub fn scan(&self, inputs: &[PathBuf], follow_symlinks: bool) -> FilterStats {
+        let mut stats = FilterStats::default();
+
+        for input in inputs {
+            if !input.is_dir() {
+                stats.included_files += 1;
+                stats.included_bytes += input.metadata().map(|m| m.len()).unwrap_or(0);
+                continue;
+            }
+
+            let mut walker = WalkDir::new(input)
+                .follow_links(follow_symlinks)
+                .into_iter();
+            while let Some(entry) = walker.next() {
+                let Ok(entry) = entry else {
+                    continue;
+                };
+                if entry.path() == input.as_path() {
+                    continue;
+                }
+                let is_dir = entry.file_type().is_dir();
+                let relative = entry.path().strip_prefix(input).unwrap_or(entry.path());
+                let size = if is_dir {
+                    0
+                } else {
+                    entry.metadata().map(|m| m.len()).unwrap_or(0)
+                };
+
+                if self.excludes(relative, is_dir, size) {
+                    if is_dir {
+                        walker.skip_current_dir();
+                        let (files, bytes) = tree_size(entry.path());
+                        stats.skipped_files += files;
+                        stats.skipped_bytes += bytes;
+                    } else {
+                        stats.skipped_files += 1;
+                        stats.skipped_bytes += size;
+                    }
+                } else if !is_dir {
+                    stats.included_files += 1;
+                    stats.included_bytes += size;
+                }
+            }
+        }
+
+        stats
+    }
+}
+
+/// Number and total size of the files below `dir`
+fn tree_size(dir: &Path) -> (u64, u64) {
+    WalkDir::new(dir)
+        .into_iter()
+        .flatten()
+        .filter(|entry| !entry.file_type().is_dir())
+        .fold((0, 0), |(files, bytes), entry| {
+            (
+                files + 1,
+                bytes + entry.metadata().map(|m| m.len()).unwrap_or(0),
+            )
+        })
+}
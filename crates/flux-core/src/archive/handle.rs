@@ -0,0 +1,223 @@
+//! An archive opened once and read as often as needed
+//!
+//! The path-based functions each find the format and list the archive
+//! again, so a front end that checks the free space, hoists a top folder
+//! and then extracts reads a compressed tar through several times before
+//! writing anything. An [`Archive`] finds the format and picks the
+//! extractor when it is opened, lists the entries the first time they are
+//! asked for, and answers every later question from what it kept.
+
+use super::extractor::{ArchiveEntry, ExtractEntryOptions, Extractor};
+use super::{
+    create_extractor_inner, extract_opened, resolve_format, ExtractOptions, FormatCapabilities,
+    OperationOutcome,
+};
+use crate::progress::ProgressSink;
+use crate::{Error, Result};
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::debug;
+
+/// A local archive, with its format, capabilities and entries
+///
+/// Reading is all it does; the archive is taken not to change while the
+/// handle is held. It reads through the secure extractor, as
+/// [`crate::ops::ExtractJob`] does, so entries that would land outside the
+/// destination or look like a compression bomb are refused.
+///
+/// # Example
+///
+/// ```no_run
+/// use flux_core::archive::{Archive, ExtractOptions};
+///
+/// let archive = Archive::open("backup.tar.zst")?;
+/// println!("{} entries", archive.entries()?.len());
+/// archive.extract_all("restored", ExtractOptions::default())?;
+/// # Ok::<(), flux_core::Error>(())
+/// ```
+pub struct Archive {
+    path: PathBuf,
+    format: &'static str,
+    capabilities: FormatCapabilities,
+    extractor: Box<dyn Extractor>,
+    /// Entries, once listed
+    index: OnceLock<Vec<ArchiveEntry>>,
+}
+
+impl Archive {
+    /// Open the archive at `path`, in the format [`super::detect_format`]
+    /// finds
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::open_as(path, None)
+    }
+
+    /// Open the archive at `path` as `format`, such as `tar.gz` or `zip`,
+    /// or as the format [`super::detect_format`] finds when it is `None`
+    pub fn open_as(path: impl AsRef<Path>, format: Option<&str>) -> Result<Self> {
+        let path = path.as_ref();
+        let opened = (|| -> Result<Self> {
+            // A missing archive is reported as such rather than as one of
+            // no known format
+            std::fs::metadata(path)?;
+            let format = resolve_format(path, format)?;
+            let capabilities = FormatCapabilities::of(format)
+                .ok_or_else(|| Error::UnsupportedFormat(format.to_string()))?;
+            Ok(Self {
+                path: path.to_path_buf(),
+                format,
+                capabilities,
+                extractor: create_extractor_inner(path, Some(format), true)?,
+                index: OnceLock::new(),
+            })
+        })();
+        opened.map_err(|e| e.with_path(path))
+    }
+
+    /// Where the archive is
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Format the archive is read as, such as `tar.zst` or `zip`
+    pub fn format(&self) -> &'static str {
+        self.format
+    }
+
+    /// What the archive's format can hold
+    pub fn capabilities(&self) -> FormatCapabilities {
+        self.capabilities
+    }
+
+    /// Entries of the archive in stored order, listed on the first call
+    ///
+    /// An entry that cannot be read fails the listing, and a later call
+    /// tries again.
+    pub fn entries(&self) -> Result<&[ArchiveEntry]> {
+        if let Some(entries) = self.index.get() {
+            return Ok(entries);
+        }
+        let entries = self
+            .extractor
+            .entries(&self.path)
+            .and_then(|entries| entries.collect::<Result<Vec<_>>>())
+            .map_err(|e| e.with_path(&self.path))?;
+        debug!("Listed {} entries of {:?}", entries.len(), self.path);
+        Ok(self.index.get_or_init(|| entries))
+    }
+
+    /// Open `entry` for reading without extracting it to disk
+    pub fn open_entry(&self, entry: &ArchiveEntry) -> Result<Box<dyn Read + Send>> {
+        self.extractor
+            .open_entry(&self.path, entry)
+            .map_err(|e| e.with_path(&self.path))
+    }
+
+    /// Write `entry` below `destination`, under its path in the archive,
+    /// reporting its bytes to `progress`
+    pub fn extract_entry(
+        &self,
+        entry: &ArchiveEntry,
+        destination: &Path,
+        options: ExtractEntryOptions,
+        progress: &dyn ProgressSink,
+    ) -> Result<()> {
+        self.extractor.extract_entry_with_progress(
+            &self.path,
+            entry,
+            destination,
+            options,
+            progress,
+        )
+    }
+
+    /// Extract the archive into `output_dir`, as
+    /// [`super::extract_with_options`] does
+    ///
+    /// The format the archive was opened as is used whatever
+    /// `options.format` says. Hoisting and the case collision check take
+    /// their entries from [`Archive::entries`], so an archive already
+    /// listed is not listed again for them.
+    pub fn extract_all(
+        &self,
+        output_dir: impl AsRef<Path>,
+        options: ExtractOptions,
+    ) -> Result<OperationOutcome> {
+        extract_opened(self, output_dir.as_ref(), options)
+    }
+}
+
+impl fmt::Debug for Archive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Archive")
+            .field("path", &self.path)
+            .field("format", &self.format)
+            .field("entries", &self.index.get().map(Vec::len))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::CaseCollisions;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// An extractor for a format that cannot be listed
+    struct Unlisted;
+
+    impl Extractor for Unlisted {
+        fn entries(
+            &self,
+            _source: &Path,
+        ) -> Result<Box<dyn Iterator<Item = Result<ArchiveEntry>>>> {
+            Err(Error::UnsupportedOperation("Listing".to_string()))
+        }
+
+        fn extract_entry(
+            &self,
+            _source: &Path,
+            _entry: &ArchiveEntry,
+            _destination: &Path,
+            _options: ExtractEntryOptions,
+        ) -> Result<()> {
+            unreachable!("extract_all does not extract entry by entry")
+        }
+
+        fn format_name(&self) -> &'static str {
+            "unlisted"
+        }
+    }
+
+    #[test]
+    fn test_archives_that_cannot_be_listed_are_hoisted_on_disk() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("file.txt"), "content").unwrap();
+        let path = temp_dir.path().join("test.tar");
+        crate::archive::pack(&source, &path, Some("tar")).unwrap();
+
+        let opened = Archive::open(&path).unwrap();
+        let archive = Archive {
+            extractor: Box::new(Unlisted),
+            ..opened
+        };
+        let output = temp_dir.path().join("output");
+        let options = ExtractOptions {
+            hoist: true,
+            case_collisions: CaseCollisions::Error,
+            fold_case: Some(true),
+            ..ExtractOptions::default()
+        };
+        archive.extract_all(&output, options).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(output.join("file.txt")).unwrap(),
+            "content"
+        );
+        assert!(!output.join("source").exists());
+    }
+}
@@ -0,0 +1,188 @@
+//! Hashes of input files taken while they are packed
+//!
+//! A manifest lists a hash of every file, and hashing the source tree after
+//! packing it reads every file a second time. A [`PackHashes`] set as
+//! [`PackOptions::hashes`] is filled by the tar and zip packers instead, as
+//! each file's data goes to the compressor, so
+//! [`Manifest::from_packed`](crate::manifest::Manifest::from_packed) only
+//! has to read what was not packed. [`PackHashes::checksums`] lists the same
+//! hashes by entry name, as a `b3sum` checksum file would. Files are hashed
+//! with the [`HashAlgorithm`] the manifest will be made with.
+//!
+//! A hash is only kept for a file read whole at the size it had when it was
+//! opened; one that changed meanwhile is left for the manifest to read.
+
+use super::checksum::{EntryChecksum, HashAlgorithm, Hasher};
+use super::PackOptions;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::SystemTime;
+
+/// Hash of one packed file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedHash {
+    /// Name the file is stored under in the archive
+    pub name: PathBuf,
+    /// Size of the file as packed
+    pub size: u64,
+    /// Modification time of the file when it was opened (Unix timestamp)
+    pub mtime: i64,
+    /// Lower-case hex digest, as manifests store it
+    pub hash: String,
+}
+
+/// Hashes of the files a pack read, by their path on disk
+///
+/// Clones share the same hashes, so one can be kept while another goes into
+/// the options of the pack.
+#[derive(Clone)]
+pub struct PackHashes {
+    hashes: Arc<Mutex<HashMap<PathBuf, PackedHash>>>,
+    algorithm: HashAlgorithm,
+}
+
+impl PackHashes {
+    /// An empty set, to pass in [`PackOptions::hashes`], hashing with
+    /// [`HashAlgorithm::for_manifests`]
+    pub fn new() -> Self {
+        Self::with_algorithm(HashAlgorithm::for_manifests())
+    }
+
+    /// An empty set hashing with `algorithm`
+    pub fn with_algorithm(algorithm: HashAlgorithm) -> Self {
+        Self {
+            hashes: Arc::default(),
+            algorithm,
+        }
+    }
+
+    /// The hash the files are taken with
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    /// Hash of the file packed from `path`, as the packer was given it
+    pub fn get(&self, path: &Path) -> Option<PackedHash> {
+        self.lock().get(path).cloned()
+    }
+
+    /// Number of files hashed
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no file was hashed
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The hashes as checksums of the archive's entries, by name
+    pub fn checksums(&self) -> Vec<EntryChecksum> {
+        let mut checksums: Vec<EntryChecksum> = self
+            .lock()
+            .values()
+            .map(|packed| EntryChecksum {
+                path: packed.name.clone(),
+                size: packed.size,
+                digest: packed.hash.clone(),
+            })
+            .collect();
+        checksums.sort_by(|a, b| a.path.cmp(&b.path));
+        checksums
+    }
+
+    fn record(&self, path: PathBuf, packed: PackedHash) {
+        self.lock().insert(path, packed);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<PathBuf, PackedHash>> {
+        // Each insert is complete on its own, so a panic elsewhere leaves
+        // the map usable
+        self.hashes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for PackHashes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for PackHashes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PackHashes({} files, {})", self.len(), self.algorithm)
+    }
+}
+
+/// A file being hashed as its data is packed
+pub(crate) struct HashingInput {
+    hashes: PackHashes,
+    path: PathBuf,
+    name: PathBuf,
+    size: u64,
+    mtime: i64,
+    read: u64,
+    hasher: Hasher,
+}
+
+impl HashingInput {
+    /// Start hashing the file at `path`, described by `metadata` and stored
+    /// as `name`, if `options` collect hashes
+    pub(crate) fn new(
+        path: &Path,
+        name: &Path,
+        metadata: &Metadata,
+        options: &PackOptions,
+    ) -> Option<Self> {
+        let hashes = options.hashes.clone()?;
+        Some(Self {
+            hasher: hashes.algorithm.hasher(),
+            hashes,
+            path: path.to_path_buf(),
+            name: name.to_path_buf(),
+            size: metadata.len(),
+            mtime: mtime(metadata),
+            read: 0,
+        })
+    }
+
+    /// Hash the next `data` of the file
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        self.hasher.update(data);
+        self.read += data.len() as u64;
+    }
+
+    /// All of the file has been read; keep its hash unless its size
+    /// changed while it was
+    pub(crate) fn finish(self) {
+        if self.read != self.size {
+            return;
+        }
+        self.hashes.record(
+            self.path,
+            PackedHash {
+                name: self.name,
+                size: self.size,
+                mtime: self.mtime,
+                hash: self.hasher.hex(),
+            },
+        );
+    }
+}
+
+/// Modification time of `metadata` in whole seconds, as manifests record it
+fn mtime(metadata: &Metadata) -> i64 {
+    metadata
+        .modified()
+        .map(|t| {
+            t.duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs() as i64
+        })
+        .unwrap_or(0)
+}
@@ -2,6 +2,8 @@
 
 use crate::archive::{tar, PackOptions};
 use crate::manifest::{Manifest, ManifestDiff};
+use crate::progress::NoProgress;
+use crate::strategy::Algorithm;
 use crate::Result;
 use std::path::{Path, PathBuf};
 use tracing::info;
@@ -12,18 +14,40 @@ pub fn pack_incremental<P: AsRef<Path>, Q: AsRef<Path>, R: AsRef<Path>>(
     output: Q,
     old_manifest_path: R,
     options: PackOptions,
+) -> Result<(PathBuf, ManifestDiff)> {
+    pack_incremental_with_password(input_dir, output, old_manifest_path, options, None)
+}
+
+/// Pack files incrementally based on manifest, reading the old manifest and
+/// saving the new one encrypted with `password` if one is given
+pub fn pack_incremental_with_password<P: AsRef<Path>, Q: AsRef<Path>, R: AsRef<Path>>(
+    input_dir: P,
+    output: Q,
+    old_manifest_path: R,
+    options: PackOptions,
+    password: Option<&str>,
 ) -> Result<(PathBuf, ManifestDiff)> {
     let input_dir = input_dir.as_ref();
     let output = output.as_ref();
     let old_manifest_path = old_manifest_path.as_ref();
 
     info!("Starting incremental backup from {:?}", input_dir);
+    options.check_paths()?;
 
     // Load old manifest
-    let old_manifest = Manifest::load(old_manifest_path)?;
-
-    // Create new manifest
-    let new_manifest = Manifest::from_directory(input_dir)?;
+    let old_manifest = Manifest::load_with_password(old_manifest_path, password)?;
+
+    // Create new manifest, hashed as the old one was, keeping the user's
+    // tags and the hashes of files that look unchanged
+    let cached = (!options.paranoid).then_some(&old_manifest);
+    let mut new_manifest = Manifest::scan_with_algorithm(
+        input_dir,
+        &options.filter,
+        cached,
+        old_manifest.hash_algorithm,
+        &NoProgress,
+    )?;
+    new_manifest.keep_annotations(&old_manifest);
 
     // Calculate differences
     let diff = old_manifest.diff(&new_manifest);
@@ -69,17 +93,27 @@ pub fn pack_incremental<P: AsRef<Path>, Q: AsRef<Path>, R: AsRef<Path>>(
     info!("Packing {} changed files", files_to_pack.len());
 
     // For incremental backup, we'll create a tar archive with the changed files
-    // The tar will preserve the directory structure
-    tar::pack_multiple_files(
+    // The tar will preserve the directory structure, moved as the options ask
+    let pack_options = PackOptions {
+        follow_symlinks: options.follow_symlinks,
+        filter: options.filter.clone(),
+        strip_prefix: options.strip_prefix.clone(),
+        prefix: options.prefix.clone(),
+        ..PackOptions::default()
+    };
+    tar::pack_multiple_files_with_progress(
         &files_to_pack,
         output,
         Some(input_dir),
-        options.follow_symlinks,
-    )?;
+        Algorithm::Store,
+        &pack_options,
+        &NoProgress,
+    )?
+    .into_result()?;
 
     // Save new manifest
     let new_manifest_path = output.with_extension("manifest.json");
-    new_manifest.save(&new_manifest_path)?;
+    new_manifest.save_with_password(&new_manifest_path, password)?;
 
     info!("Incremental backup completed: {:?}", output);
     info!("New manifest saved: {:?}", new_manifest_path);
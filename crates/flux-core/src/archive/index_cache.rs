@@ -0,0 +1,215 @@
+//! On-disk cache of archive listings
+//!
+//! Listing a multi-gigabyte archive means decompressing all of it, so
+//! browsers keep the entries they read in an [`IndexCache`] and show them
+//! straight away the next time the same archive is opened. Each listing is
+//! stored zstd-compressed in a file named for the archive's path, alongside
+//! the archive's size, modification time and a hash of its first and last
+//! blocks; a listing whose archive no longer matches all three is dropped.
+
+use super::extractor::ArchiveEntry;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::debug;
+
+/// Listings kept before the least recently written are removed
+pub const MAX_CACHED: usize = 64;
+
+/// Bytes hashed at each end of an archive to tell it apart from another
+/// of the same size and modification time
+const FINGERPRINT_BLOCK: u64 = 64 * 1024;
+
+/// Version of the cache file layout; files of another are ignored
+const VERSION: u32 = 1;
+
+/// What an archive looked like when it was listed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Fingerprint {
+    size: u64,
+    /// Modification time in nanoseconds since the epoch
+    mtime: u128,
+    /// Blake3 of the first and last [`FINGERPRINT_BLOCK`] bytes
+    hash: String,
+}
+
+impl Fingerprint {
+    fn of(archive: &Path) -> Result<Self> {
+        let mut file = File::open(archive).map_err(|e| Error::from(e).with_path(archive))?;
+        let metadata = file.metadata()?;
+        let size = metadata.len();
+        let mtime = metadata
+            .modified()?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+
+        let mut hasher = blake3::Hasher::new();
+        let mut block = Vec::with_capacity(FINGERPRINT_BLOCK as usize);
+        (&mut file)
+            .take(FINGERPRINT_BLOCK)
+            .read_to_end(&mut block)?;
+        hasher.update(&block);
+        if size > FINGERPRINT_BLOCK {
+            block.clear();
+            file.seek(SeekFrom::Start(
+                size.saturating_sub(FINGERPRINT_BLOCK)
+                    .max(FINGERPRINT_BLOCK),
+            ))?;
+            file.take(FINGERPRINT_BLOCK).read_to_end(&mut block)?;
+            hasher.update(&block);
+        }
+
+        Ok(Self {
+            size,
+            mtime,
+            hash: hasher.finalize().to_hex().to_string(),
+        })
+    }
+}
+
+/// A cache file
+#[derive(Serialize, Deserialize)]
+struct CachedIndex {
+    version: u32,
+    archive: PathBuf,
+    fingerprint: Fingerprint,
+    entries: Vec<ArchiveEntry>,
+}
+
+/// Directory of cached archive listings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexCache {
+    dir: PathBuf,
+}
+
+impl IndexCache {
+    /// The cache in `dir`, created when a listing is first stored
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The cache in the user's cache directory
+    pub fn open_default() -> Result<Self> {
+        let cache_dir = dirs::cache_dir()
+            .ok_or_else(|| Error::ConfigError("Could not determine cache directory".to_string()))?;
+        Ok(Self::new(cache_dir.join("flux").join("index")))
+    }
+
+    /// Entries of `archive` as last stored, if it has not changed since
+    pub fn load(&self, archive: &Path) -> Option<Vec<ArchiveEntry>> {
+        let archive = archive.canonicalize().ok()?;
+        let path = self.path(&archive);
+        let file = File::open(&path).ok()?;
+        let read = zstd::Decoder::new(file)
+            .map_err(|e| e.to_string())
+            .and_then(|decoder| {
+                serde_json::from_reader::<_, CachedIndex>(BufReader::new(decoder))
+                    .map_err(|e| e.to_string())
+            });
+        let cached = match read {
+            Ok(cached) => cached,
+            Err(e) => {
+                debug!("Dropping unreadable index cache {:?}: {}", path, e);
+                let _ = fs::remove_file(&path);
+                return None;
+            }
+        };
+
+        let current = Fingerprint::of(&archive).ok()?;
+        if cached.version != VERSION || cached.archive != archive || cached.fingerprint != current {
+            debug!("Index cache of {:?} is stale", archive);
+            let _ = fs::remove_file(&path);
+            return None;
+        }
+        debug!(
+            "Read {} entries of {:?} from the index cache",
+            cached.entries.len(),
+            archive
+        );
+        Some(cached.entries)
+    }
+
+    /// Keep `entries` as the listing of `archive`
+    ///
+    /// The archive is fingerprinted now, so store a listing right after
+    /// reading it. Beyond [`MAX_CACHED`] listings, the oldest are removed.
+    pub fn store(&self, archive: &Path, entries: &[ArchiveEntry]) -> Result<()> {
+        let archive = archive
+            .canonicalize()
+            .map_err(|e| Error::from(e).with_path(archive))?;
+        let cached = CachedIndex {
+            version: VERSION,
+            fingerprint: Fingerprint::of(&archive)?,
+            archive,
+            entries: entries.to_vec(),
+        };
+
+        fs::create_dir_all(&self.dir).map_err(|e| Error::from(e).with_path(&self.dir))?;
+        let path = self.path(&cached.archive);
+        // Written aside and renamed, so a reader never sees half a file
+        let partial = path.with_extension("partial");
+        let mut encoder = zstd::Encoder::new(BufWriter::new(File::create(&partial)?), 3)?;
+        serde_json::to_writer(&mut encoder, &cached)
+            .map_err(|e| Error::Other(format!("Failed to write index cache: {}", e)))?;
+        encoder.finish()?.flush()?;
+        fs::rename(&partial, &path)?;
+        debug!(
+            "Cached {} entries of {:?} in {:?}",
+            entries.len(),
+            cached.archive,
+            path
+        );
+
+        self.prune()
+    }
+
+    /// Remove every cached listing
+    pub fn clear(&self) -> Result<()> {
+        for (path, _) in self.files()? {
+            fs::remove_file(path)?;
+        }
+        Ok(())
+    }
+
+    fn path(&self, archive: &Path) -> PathBuf {
+        let name = blake3::hash(archive.as_os_str().as_encoded_bytes()).to_hex();
+        self.dir.join(format!("{}.index.zst", &name[..32]))
+    }
+
+    /// Cache files with when they were written
+    fn files(&self) -> Result<Vec<(PathBuf, SystemTime)>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if path.to_string_lossy().ends_with(".index.zst") {
+                let written = entry
+                    .metadata()
+                    .and_then(|m| m.modified())
+                    .unwrap_or(SystemTime::UNIX_EPOCH);
+                files.push((path, written));
+            }
+        }
+        Ok(files)
+    }
+
+    fn prune(&self) -> Result<()> {
+        let mut files = self.files()?;
+        if files.len() <= MAX_CACHED {
+            return Ok(());
+        }
+        files.sort_by_key(|(_, written)| std::cmp::Reverse(*written));
+        for (path, _) in files.drain(MAX_CACHED..) {
+            debug!("Removing old index cache {:?}", path);
+            let _ = fs::remove_file(path);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+//! Writing symlink entries where the platform may refuse them
+//!
+//! Windows only lets administrators, and users in developer mode, create
+//! symlinks. Without that privilege a symlink entry is written as
+//! [`SymlinkFallback`] says instead: left out with a warning, replaced by a
+//! copy of what it points to, or for a directory replaced by a junction,
+//! which any user may create. Copies and junctions are only made of targets
+//! inside the output directory, and only once they have been extracted.
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use tracing::{debug, warn};
+use walkdir::WalkDir;
+
+/// What extraction writes for a symlink entry it is not allowed to create
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SymlinkFallback {
+    /// Leave the link out, with a warning
+    #[default]
+    Skip,
+    /// Copy the file or directory the link points to in its place
+    Copy,
+    /// Link directories with a junction, and copy files
+    Junction,
+}
+
+impl SymlinkFallback {
+    /// Lower-case name, as accepted by [`FromStr`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            SymlinkFallback::Skip => "skip",
+            SymlinkFallback::Copy => "copy",
+            SymlinkFallback::Junction => "junction",
+        }
+    }
+}
+
+impl FromStr for SymlinkFallback {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "skip" => Ok(SymlinkFallback::Skip),
+            "copy" => Ok(SymlinkFallback::Copy),
+            "junction" => Ok(SymlinkFallback::Junction),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unknown symlink fallback: {} (expected skip, copy or junction)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for SymlinkFallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Whether symlinks can be created in `dir`, found by creating one
+///
+/// Always true on Unix. Elsewhere a probe link is made and removed again,
+/// so `dir` has to exist and be writable.
+pub fn symlinks_supported(dir: &Path) -> bool {
+    if cfg!(unix) {
+        return true;
+    }
+    let probe = dir.join(format!(".flux-link-probe-{}", std::process::id()));
+    let supported = make_symlink(Path::new("."), &probe, true).is_ok();
+    let _ = fs::remove_dir(&probe).or_else(|_| fs::remove_file(&probe));
+    supported
+}
+
+/// Create a symlink at `dest` pointing to `target`, or what `fallback`
+/// says where that is not allowed, replacing a file, link or empty
+/// directory at `dest`
+///
+/// A directory with anything in it is left alone and the link fails.
+/// `root` is the output directory, which copies and junctions may not
+/// reach outside of. Returns whether anything was written; a link left out
+/// has been warned about.
+pub(crate) fn create_symlink(
+    root: &Path,
+    dest: &Path,
+    target: &Path,
+    fallback: SymlinkFallback,
+) -> Result<bool> {
+    match fs::symlink_metadata(dest) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir(dest).map_err(|e| {
+            Error::Io(io::Error::new(
+                e.kind(),
+                format!("Cannot replace directory {:?} with a symlink: {}", dest, e),
+            ))
+        })?,
+        // Links to directories are removed as directories on Windows
+        Ok(_) => fs::remove_file(dest).or_else(|_| fs::remove_dir(dest))?,
+        Err(_) => {}
+    }
+
+    let resolved = dest.parent().unwrap_or(Path::new("")).join(target);
+    debug!("Creating symlink: {:?} -> {:?}", dest, target);
+    match make_symlink(target, dest, resolved.is_dir()) {
+        Ok(()) => return Ok(true),
+        Err(e) if !is_refused(&e) => return Err(e.into()),
+        Err(e) => debug!("Symlink {:?} refused: {}", dest, e),
+    }
+
+    if fallback == SymlinkFallback::Skip {
+        warn!(
+            "Not allowed to create symlinks here, skipping: {:?} -> {:?}",
+            dest, target
+        );
+        return Ok(false);
+    }
+    let Some(source) = contained(root, &resolved) else {
+        warn!(
+            "Symlink target {:?} is outside the output directory or not extracted yet, skipping: {:?}",
+            target, dest
+        );
+        return Ok(false);
+    };
+
+    if source.is_dir() && fallback == SymlinkFallback::Junction {
+        debug!("Creating junction: {:?} -> {:?}", dest, source);
+        create_junction(dest, &source)?;
+    } else {
+        debug!("Copying symlink target: {:?} -> {:?}", source, dest);
+        copy_target(&source, dest)?;
+    }
+    Ok(true)
+}
+
+#[cfg(unix)]
+fn make_symlink(target: &Path, dest: &Path, _is_dir: bool) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, dest)
+}
+
+#[cfg(windows)]
+fn make_symlink(target: &Path, dest: &Path, is_dir: bool) -> std::io::Result<()> {
+    if is_dir {
+        std::os::windows::fs::symlink_dir(target, dest)
+    } else {
+        std::os::windows::fs::symlink_file(target, dest)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn make_symlink(_target: &Path, _dest: &Path, _is_dir: bool) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "symlinks are not supported on this platform",
+    ))
+}
+
+/// Whether creating a symlink failed for want of the privilege or of
+/// support, rather than for a reason a fallback would run into as well
+fn is_refused(error: &std::io::Error) -> bool {
+    /// `ERROR_PRIVILEGE_NOT_HELD`
+    const PRIVILEGE_NOT_HELD: i32 = 1314;
+
+    error.kind() == std::io::ErrorKind::Unsupported
+        || (cfg!(windows) && error.raw_os_error() == Some(PRIVILEGE_NOT_HELD))
+}
+
+/// `path` made absolute, if it exists and lies inside `root`
+fn contained(root: &Path, path: &Path) -> Option<PathBuf> {
+    let root = root.canonicalize().ok()?;
+    let path = path.canonicalize().ok()?;
+    path.starts_with(&root).then_some(path)
+}
+
+#[cfg(windows)]
+fn create_junction(dest: &Path, target: &Path) -> Result<()> {
+    // `mklink /J` needs no privilege, unlike a symlink
+    let status = std::process::Command::new("cmd")
+        .arg("/C")
+        .arg("mklink")
+        .arg("/J")
+        .arg(dest)
+        .arg(target)
+        .stdout(std::process::Stdio::null())
+        .status()?;
+    if !status.success() {
+        return Err(Error::Other(format!(
+            "Failed to create a junction from {} to {}",
+            dest.display(),
+            target.display()
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn create_junction(dest: &Path, target: &Path) -> Result<()> {
+    // Junctions are a Windows thing; a copy is the nearest elsewhere
+    copy_target(target, dest)
+}
+
+/// Copy the file or directory tree `source` to `dest`
+fn copy_target(source: &Path, dest: &Path) -> Result<()> {
+    if !source.is_dir() {
+        fs::copy(source, dest)?;
+        return Ok(());
+    }
+    for entry in WalkDir::new(source) {
+        let entry = entry?;
+        let relative = entry.path().strip_prefix(source).unwrap_or(entry.path());
+        let to = dest.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&to)?;
+        } else {
+            fs::copy(entry.path(), &to)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_names_round_trip() {
+        for fallback in [
+            SymlinkFallback::Skip,
+            SymlinkFallback::Copy,
+            SymlinkFallback::Junction,
+        ] {
+            assert_eq!(
+                fallback.name().parse::<SymlinkFallback>().unwrap(),
+                fallback
+            );
+        }
+        assert!("hardlink".parse::<SymlinkFallback>().is_err());
+    }
+
+    #[test]
+    fn test_copy_target_copies_directory_trees() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        fs::create_dir_all(source.join("nested")).unwrap();
+        fs::write(source.join("nested/file.txt"), "copied").unwrap();
+
+        let dest = temp_dir.path().join("dest");
+        copy_target(&source, &dest).unwrap();
+        assert_eq!(
+            fs::read_to_string(dest.join("nested/file.txt")).unwrap(),
+            "copied"
+        );
+    }
+
+    #[test]
+    fn test_contained_rejects_paths_outside_the_root() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().join("out");
+        fs::create_dir_all(root.join("inside")).unwrap();
+
+        assert!(contained(&root, &root.join("inside")).is_some());
+        assert!(contained(&root, &root.join("inside/../..")).is_none());
+        assert!(contained(&root, &root.join("missing")).is_none());
+    }
+}
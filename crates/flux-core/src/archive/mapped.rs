@@ -0,0 +1,210 @@
+//! Memory-mapped reading of input files
+//!
+//! A large file on a local disk can be mapped and handed to the compressor
+//! a slice at a time, rather than read into a buffer first. Mapping is
+//! opt-in through [`PackOptions::use_mmap`]: a file that is truncated while
+//! mapped makes the process fault instead of failing a read, and network
+//! file systems give no guarantee about that at all, so files on them are
+//! always read normally.
+
+use super::hashes::HashingInput;
+use crate::archive::PackOptions;
+use crate::buffer::{self, BufferPool};
+use crate::progress::{ProgressRead, ProgressSink};
+use memmap2::Mmap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use tracing::debug;
+
+/// Smallest file worth mapping; below this a read costs no more than
+/// setting up the mapping
+pub const MMAP_MIN_SIZE: u64 = 8 * 1024 * 1024;
+
+/// The data of one input file, mapped or read, hashed on the way if the
+/// pack collects hashes
+pub(crate) struct InputData {
+    source: Source,
+    hashing: Option<HashingInput>,
+}
+
+enum Source {
+    Mapped { map: Mmap, position: usize },
+    Read(File),
+}
+
+impl InputData {
+    /// `file`, opened from `path` to be stored as `name`, mapped if
+    /// `options` ask for it and the file is large and local; read otherwise
+    pub(crate) fn new(file: File, path: &Path, name: &Path, options: &PackOptions) -> Self {
+        let hashing = match &options.hashes {
+            Some(_) => file
+                .metadata()
+                .ok()
+                .and_then(|metadata| HashingInput::new(path, name, &metadata, options)),
+            None => None,
+        };
+        let source = if !options.use_mmap {
+            Source::Read(file)
+        } else {
+            match map(&file, path) {
+                Some(map) => Source::Mapped { map, position: 0 },
+                None => Source::Read(file),
+            }
+        };
+        Self { source, hashing }
+    }
+
+    /// Write the rest of the data to `writer`, reporting it to `progress`
+    pub(crate) fn copy_to<W: Write + ?Sized>(
+        mut self,
+        writer: &mut W,
+        progress: &dyn ProgressSink,
+    ) -> io::Result<u64> {
+        let Source::Mapped { map, position } = &mut self.source else {
+            return buffer::copy(&mut ProgressRead::new(&mut self, progress), writer);
+        };
+        let rest = &map[*position..];
+        for chunk in rest.chunks(BufferPool::global().chunk_size()) {
+            if progress.is_cancelled() {
+                return Err(io::Error::other("Operation cancelled"));
+            }
+            writer.write_all(chunk)?;
+            if let Some(hashing) = &mut self.hashing {
+                hashing.update(chunk);
+            }
+            progress.advance(chunk.len() as u64);
+        }
+        let copied = rest.len() as u64;
+        *position = map.len();
+        if let Some(hashing) = self.hashing.take() {
+            hashing.finish();
+        }
+        Ok(copied)
+    }
+}
+
+impl Read for InputData {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = match &mut self.source {
+            Source::Mapped { map, position } => {
+                let rest = &map[*position..];
+                let n = rest.len().min(buf.len());
+                buf[..n].copy_from_slice(&rest[..n]);
+                *position += n;
+                n
+            }
+            Source::Read(file) => file.read(buf)?,
+        };
+        if n > 0 {
+            if let Some(hashing) = &mut self.hashing {
+                hashing.update(&buf[..n]);
+            }
+        } else if !buf.is_empty() {
+            // The end of the file
+            if let Some(hashing) = self.hashing.take() {
+                hashing.finish();
+            }
+        }
+        Ok(n)
+    }
+}
+
+/// A read-only mapping of `file`, or `None` when it should be read instead
+fn map(file: &File, path: &Path) -> Option<Mmap> {
+    let len = file.metadata().ok()?.len();
+    if len < MMAP_MIN_SIZE || !is_local(file, path) {
+        return None;
+    }
+
+    // SAFETY: the mapping is only read, and only while `file` is open. A
+    // file changed by another process meanwhile gives torn data, as a read
+    // would; one truncated faults, which is why mapping is opt-in.
+    match unsafe { Mmap::map(file) } {
+        Ok(map) => {
+            #[cfg(unix)]
+            let _ = map.advise(memmap2::Advice::Sequential);
+            Some(map)
+        }
+        Err(e) => {
+            debug!("Reading {:?} instead of mapping it: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Whether `file` lives on a local file system
+///
+/// Where that cannot be told, the file is taken to be remote.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn is_local(file: &File, _path: &Path) -> bool {
+    use std::os::unix::io::AsRawFd;
+
+    // File system magic numbers from statfs(2)
+    const REMOTE: [u32; 9] = [
+        0x6969,      // NFS
+        0x517b,      // SMB
+        0xff53_4d42, // CIFS
+        0xfe53_4d42, // SMB2
+        0x6573_5546, // FUSE
+        0x0102_1997, // 9P
+        0x5346_414f, // AFS
+        0x00c3_6400, // Ceph
+        0x0bd0_0bd0, // Lustre
+    ];
+
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::fstatfs(file.as_raw_fd(), &mut stat) } != 0 {
+        return false;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    let kind = stat.f_type as u32;
+    !REMOTE.contains(&kind)
+}
+
+#[cfg(target_os = "macos")]
+fn is_local(file: &File, _path: &Path) -> bool {
+    use std::os::unix::io::AsRawFd;
+
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::fstatfs(file.as_raw_fd(), &mut stat) } != 0 {
+        return false;
+    }
+    stat.f_flags & libc::MNT_LOCAL as u32 != 0
+}
+
+#[cfg(windows)]
+fn is_local(_file: &File, path: &Path) -> bool {
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::{Component, Prefix};
+    use winapi::um::fileapi::GetDriveTypeW;
+    use winapi::um::winbase::DRIVE_REMOTE;
+
+    let Ok(path) = path.canonicalize() else {
+        return false;
+    };
+    let letter = match path.components().next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => letter,
+            // UNC shares and anything else unusual
+            _ => return false,
+        },
+        _ => return false,
+    };
+    // Mapped network drives have letters too
+    let root: Vec<u16> = std::ffi::OsStr::new(&format!("{}:\\", letter as char))
+        .encode_wide()
+        .chain(Some(0))
+        .collect();
+    unsafe { GetDriveTypeW(root.as_ptr()) != DRIVE_REMOTE }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    windows
+)))]
+fn is_local(_file: &File, _path: &Path) -> bool {
+    false
+}
@@ -0,0 +1,233 @@
+//! Archive-level metadata: who made an archive, on which host, when and
+//! with which version of flux, plus any key/value pairs of the user's
+//!
+//! Tar archives carry it as a pax global header at the start of the
+//! archive, with `FLUX.` keys that other tools ignore. Zip archives carry it
+//! in the archive comment, as a marker line followed by one line of JSON, so
+//! it stays readable with `unzip -z`.
+
+use super::detect::detect_format;
+use super::zip::open_zip;
+use crate::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// First line of a zip comment holding metadata
+const COMMENT_MARKER: &str = "flux-metadata";
+/// Prefix of the pax keys flux writes
+const PAX_PREFIX: &str = "FLUX.";
+/// Prefix of the pax keys holding user values, after [`PAX_PREFIX`]
+const PAX_VALUE_PREFIX: &str = "meta.";
+
+/// Where an archive came from
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveMetadata {
+    /// User who made the archive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// When the archive was made, as a Unix timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flux_version: Option<String>,
+    /// Values given by the user, such as `--meta ticket=OPS-12`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub values: BTreeMap<String, String>,
+}
+
+impl ArchiveMetadata {
+    /// Metadata for an archive made now, by this user on this host
+    pub fn current() -> Self {
+        Self {
+            creator: ["USER", "USERNAME"]
+                .iter()
+                .find_map(|var| std::env::var(var).ok().filter(|user| !user.is_empty())),
+            hostname: hostname(),
+            created: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .ok()
+                .map(|time| time.as_secs() as i64),
+            flux_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Add the user value `key`, replacing any value it had
+    pub fn with_value(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.values.insert(key.into(), value.into());
+        self
+    }
+
+    /// The records of a pax global header holding the metadata
+    pub(crate) fn to_pax(&self) -> Vec<u8> {
+        let mut records = Vec::new();
+        let mut push = |key: &str, value: &str| {
+            records.extend_from_slice(&pax_record(
+                format!("{}{}", PAX_PREFIX, key).as_bytes(),
+                value.as_bytes(),
+            ))
+        };
+        if let Some(creator) = &self.creator {
+            push("creator", creator);
+        }
+        if let Some(hostname) = &self.hostname {
+            push("hostname", hostname);
+        }
+        if let Some(created) = self.created {
+            push("created", &created.to_string());
+        }
+        if let Some(version) = &self.flux_version {
+            push("version", version);
+        }
+        for (key, value) in &self.values {
+            push(&format!("{}{}", PAX_VALUE_PREFIX, key), value);
+        }
+        records
+    }
+
+    /// Metadata from the `FLUX.` keys of a pax global header, if it has any
+    pub(crate) fn from_pax(extensions: tar::PaxExtensions<'_>) -> Option<Self> {
+        let mut metadata = Self::default();
+        let mut found = false;
+        for extension in extensions.flatten() {
+            let (Ok(key), Ok(value)) = (extension.key(), extension.value()) else {
+                continue;
+            };
+            let Some(key) = key.strip_prefix(PAX_PREFIX) else {
+                continue;
+            };
+            found = true;
+            let value = value.to_string();
+            match key {
+                "creator" => metadata.creator = Some(value),
+                "hostname" => metadata.hostname = Some(value),
+                "created" => metadata.created = value.parse().ok(),
+                "version" => metadata.flux_version = Some(value),
+                _ => {
+                    if let Some(key) = key.strip_prefix(PAX_VALUE_PREFIX) {
+                        metadata.values.insert(key.to_string(), value);
+                    }
+                }
+            }
+        }
+        found.then_some(metadata)
+    }
+
+    /// A zip comment holding the metadata
+    pub(crate) fn to_comment(&self) -> String {
+        format!(
+            "{}\n{}",
+            COMMENT_MARKER,
+            serde_json::to_string(self).unwrap_or_default()
+        )
+    }
+
+    /// Metadata from a zip comment written by [`Self::to_comment`]
+    pub(crate) fn from_comment(comment: &[u8]) -> Option<Self> {
+        let comment = std::str::from_utf8(comment).ok()?;
+        let json = comment.strip_prefix(COMMENT_MARKER)?.trim_start();
+        serde_json::from_str(json).ok()
+    }
+}
+
+/// One `length key=value\n` pax record, the length counting itself
+pub(crate) fn pax_record(key: &[u8], value: &[u8]) -> Vec<u8> {
+    let rest = key.len() + value.len() + 3;
+    let mut len = rest + 1;
+    while len != rest + len.to_string().len() {
+        len = rest + len.to_string().len();
+    }
+    let mut record = format!("{} ", len).into_bytes();
+    record.extend_from_slice(key);
+    record.push(b'=');
+    record.extend_from_slice(value);
+    record.push(b'\n');
+    record
+}
+
+#[cfg(unix)]
+fn hostname() -> Option<String> {
+    let mut buffer = [0u8; 256];
+    // SAFETY: the buffer is valid for its whole length
+    let result = unsafe { libc::gethostname(buffer.as_mut_ptr().cast(), buffer.len()) };
+    if result != 0 {
+        return None;
+    }
+    let len = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    let name = String::from_utf8_lossy(&buffer[..len]).into_owned();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(not(unix))]
+fn hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME")
+        .ok()
+        .filter(|name| !name.is_empty())
+}
+
+/// Whether a tar entry is a pax global header rather than a file
+pub(crate) fn is_global_header(header: &tar::Header) -> bool {
+    header.entry_type().is_pax_global_extensions()
+}
+
+/// Add a pax global header holding `metadata` to a tar archive; must come
+/// before any entry
+pub(crate) fn append_tar_metadata<W: std::io::Write>(
+    builder: &mut tar::Builder<W>,
+    metadata: &ArchiveMetadata,
+) -> Result<()> {
+    let records = metadata.to_pax();
+    let mut header = tar::Header::new_ustar();
+    header.set_entry_type(tar::EntryType::XGlobalHeader);
+    header.set_size(records.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(metadata.created.unwrap_or(0).max(0) as u64);
+    builder.append_data(&mut header, "pax_global_header", records.as_slice())?;
+    Ok(())
+}
+
+/// Add a pax extended header holding `records`, which apply to the entry
+/// appended after it
+pub(crate) fn append_pax_header<W: std::io::Write>(
+    builder: &mut tar::Builder<W>,
+    records: &[u8],
+) -> Result<()> {
+    let mut header = tar::Header::new_ustar();
+    header.set_entry_type(tar::EntryType::XHeader);
+    header.set_size(records.len() as u64);
+    header.set_mode(0o644);
+    builder.append_data(&mut header, "PaxHeader", records)?;
+    Ok(())
+}
+
+/// Metadata of a tar stream, from a global header before the first entry
+pub(crate) fn read_tar_metadata<R: Read>(reader: R) -> Result<Option<ArchiveMetadata>> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !is_global_header(entry.header()) {
+            break;
+        }
+        if let Some(metadata) = entry.pax_extensions()?.and_then(ArchiveMetadata::from_pax) {
+            return Ok(Some(metadata));
+        }
+    }
+    Ok(None)
+}
+
+/// Read the metadata of `archive`, `None` if it has none
+///
+/// Only tar and zip archives can carry metadata.
+pub fn read_metadata<P: AsRef<Path>>(archive: P) -> Result<Option<ArchiveMetadata>> {
+    let archive = archive.as_ref();
+    match detect_format(archive)? {
+        "zip" => Ok(ArchiveMetadata::from_comment(open_zip(archive)?.comment())),
+        "7z" => Ok(None),
+        format => read_tar_metadata(super::modify::tar_reader(archive, format)?),
+    }
+}
@@ -1,20 +1,99 @@
 //! Archive operations module
 
+mod budget;
+mod capabilities;
+mod case_fold;
+pub mod checksum;
+pub mod dedupe;
+pub mod detect;
+pub mod estimate;
 pub mod extractor;
+pub mod file_list;
+pub mod filter;
+mod handle;
+mod hashes;
 pub mod incremental;
+pub mod index_cache;
+mod links;
+mod mapped;
+pub mod meta;
+pub mod modify;
+mod nested;
+pub(crate) mod normalize;
+mod order;
+pub mod outcome;
+pub mod paths;
+mod permissions;
+pub mod resume;
+mod roots;
+pub mod salvage;
 pub mod secure_extractor;
 pub mod sevenz;
 pub mod sevenz_extractor;
+pub mod spanned;
+pub mod stats;
+pub mod stream;
+mod tamper;
 pub mod tar;
 pub mod tar_extractor;
+mod undo;
+pub mod verify;
+mod walk;
 pub mod zip;
+pub mod zip_extra;
 pub mod zip_extractor;
 
+pub use capabilities::{
+    metadata_loss, scan_metadata, Capability, FormatCapabilities, FoundMetadata,
+};
+pub use case_fold::{find_case_collisions, CaseCollision, CaseCollisions};
+pub use checksum::{checksums, checksums_with, EntryChecksum, HashAlgorithm};
+pub use dedupe::{dedupe_report, deduplicate, DedupeReport, DuplicateGroup};
+pub use detect::{
+    archive_format, detect_format, parse_format, resolve_format, sniff_bytes, sniff_format,
+};
+pub use estimate::{estimate_pack, PackEstimate};
+pub use filter::{FilterStats, PackFilter};
+pub use handle::Archive;
+pub use hashes::{PackHashes, PackedHash};
+pub use index_cache::IndexCache;
+pub use links::{symlinks_supported, SymlinkFallback};
+pub use mapped::MMAP_MIN_SIZE;
+pub use meta::{read_metadata, ArchiveMetadata};
+pub use modify::{rename_entry, Modifier};
+pub use nested::{
+    extract_recursive, NestedArchive, NestedSkipReason, RecursiveOptions, RecursiveReport,
+    SkippedArchive, DEFAULT_RECURSION_DEPTH,
+};
+pub use normalize::NameNormalization;
+pub use order::{ExtractOrder, PackOrder};
+pub use outcome::{EntryFailure, OperationOutcome, OperationReport, SkipReason, Skipped};
+pub use paths::common_base;
+#[cfg(unix)]
+pub use permissions::umask;
+pub use resume::{read_resume_journal, resumable, ResumeJournal, RESUME_FILE};
+pub use roots::PackRoot;
+pub use salvage::{salvage, LostEntry, SalvageReport};
+pub use spanned::split_zip;
+pub use stats::{inspect_stats, ArchiveStats};
+pub use stream::{extract_from_reader, list_from_reader, read_entry_from_reader};
+pub use tamper::{ManifestCheck, TamperKind, TamperPolicy, Tampered};
+pub use undo::{undo_extraction, UndoReport, BACKUP_DIR};
+pub use verify::{verify, verify_with_progress, EntryCheck, EntryStatus, VerifyReport};
+
+use crate::config::Config;
+use crate::durability::Durability;
+use crate::progress::{NoProgress, ProgressSink};
 use crate::strategy::{Algorithm, CompressionStrategy};
-use crate::{Error, Result};
+use crate::{Error, ErrorCode, Result};
+use detect::tar_compression;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::io::Write;
 use std::path::{Path, PathBuf};
-use tracing::info;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
 
 /// Archive entry information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,6 +114,26 @@ pub struct ArchiveEntry {
     pub is_symlink: bool,
     /// Link target (for symlinks)
     pub link_target: Option<PathBuf>,
+    /// Extra fields of a zip entry, as [`zip_extra::ExtraField::describe`]
+    /// shows them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_fields: Vec<String>,
+}
+
+impl From<extractor::ArchiveEntry> for ArchiveEntry {
+    fn from(entry: extractor::ArchiveEntry) -> Self {
+        Self {
+            path: entry.path,
+            size: entry.size,
+            compressed_size: entry.compressed_size,
+            mode: entry.mode,
+            mtime: entry.mtime,
+            is_dir: entry.is_dir,
+            is_symlink: entry.is_symlink,
+            link_target: entry.link_target,
+            extra_fields: Vec::new(),
+        }
+    }
 }
 
 /// Pack files into an archive
@@ -57,145 +156,76 @@ pub fn pack<P: AsRef<Path>, Q: AsRef<Path>>(
     }
 }
 
-/// Extract files from an archive
+/// Extract files from an archive, of the format [`detect_format`] finds
 pub fn extract<P: AsRef<Path>, Q: AsRef<Path>>(archive: P, output_dir: Q) -> Result<()> {
     let archive = archive.as_ref();
     let output_dir = output_dir.as_ref();
 
-    // Detect format by extension
-    let ext = archive
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or("");
-
-    // Check for double extensions
-    let stem = archive.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-    let double_ext = if stem.ends_with(".tar") {
-        format!("tar.{}", ext)
-    } else {
-        ext.to_string()
-    };
-
-    match double_ext.as_str() {
-        "tar" => tar::extract_tar(archive, output_dir),
-        "tar.gz" | "tgz" => tar::extract_tar_compressed(archive, output_dir, Algorithm::Gzip),
-        "tar.zst" | "tzst" => tar::extract_tar_compressed(archive, output_dir, Algorithm::Zstd),
-        "tar.xz" | "txz" => tar::extract_tar_compressed(archive, output_dir, Algorithm::Xz),
-        "tar.br" => tar::extract_tar_compressed(archive, output_dir, Algorithm::Brotli),
-        _ => match ext {
-            "tar" => tar::extract_tar(archive, output_dir),
-            "gz" if stem.ends_with(".tar") => {
-                tar::extract_tar_compressed(archive, output_dir, Algorithm::Gzip)
-            }
-            "zst" if stem.ends_with(".tar") => {
-                tar::extract_tar_compressed(archive, output_dir, Algorithm::Zstd)
-            }
-            "xz" if stem.ends_with(".tar") => {
-                tar::extract_tar_compressed(archive, output_dir, Algorithm::Xz)
-            }
-            "br" if stem.ends_with(".tar") => {
-                tar::extract_tar_compressed(archive, output_dir, Algorithm::Brotli)
-            }
-            "zip" => zip::extract_zip(archive, output_dir),
-            "7z" => sevenz::extract_7z(archive, output_dir),
-            _ => Err(Error::UnsupportedFormat(ext.to_string())),
+    match detect_format(archive)? {
+        "zip" => zip::extract_zip(archive, output_dir),
+        "7z" => sevenz::extract_7z(archive, output_dir),
+        format => match tar_compression(format) {
+            Some(Algorithm::Store) => tar::extract_tar(archive, output_dir),
+            Some(algorithm) => tar::extract_tar_compressed(archive, output_dir, algorithm),
+            None => Err(Error::UnsupportedFormat(format.to_string())),
         },
     }
 }
 
 /// Inspect archive contents without extracting
 pub fn inspect<P: AsRef<Path>>(archive: P) -> Result<Vec<ArchiveEntry>> {
-    let archive = archive.as_ref();
-
-    // Detect format by extension
-    let ext = archive
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or("");
+    inspect_as(archive, None)
+}
 
-    // Check for double extensions
-    let stem = archive.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-    let double_ext = if stem.ends_with(".tar") {
-        format!("tar.{}", ext)
-    } else {
-        ext.to_string()
-    };
+/// Inspect archive contents as `format`, or as the format
+/// [`detect_format`] finds when it is `None`
+pub fn inspect_as<P: AsRef<Path>>(archive: P, format: Option<&str>) -> Result<Vec<ArchiveEntry>> {
+    let archive = archive.as_ref();
 
-    match double_ext.as_str() {
-        "tar" => tar::inspect_tar(archive),
-        "tar.gz" | "tgz" => tar::inspect_tar_compressed(archive, Algorithm::Gzip),
-        "tar.zst" | "tzst" => tar::inspect_tar_compressed(archive, Algorithm::Zstd),
-        "tar.xz" | "txz" => tar::inspect_tar_compressed(archive, Algorithm::Xz),
-        "tar.br" => tar::inspect_tar_compressed(archive, Algorithm::Brotli),
-        _ => match ext {
-            "tar" => tar::inspect_tar(archive),
-            "gz" if stem.ends_with(".tar") => tar::inspect_tar_compressed(archive, Algorithm::Gzip),
-            "zst" if stem.ends_with(".tar") => {
-                tar::inspect_tar_compressed(archive, Algorithm::Zstd)
-            }
-            "xz" if stem.ends_with(".tar") => tar::inspect_tar_compressed(archive, Algorithm::Xz),
-            "br" if stem.ends_with(".tar") => {
-                tar::inspect_tar_compressed(archive, Algorithm::Brotli)
-            }
-            "zip" => zip::inspect_zip(archive),
-            "7z" => sevenz::inspect_7z(archive),
-            _ => Err(Error::UnsupportedFormat(ext.to_string())),
+    let result = resolve_format(archive, format).and_then(|format| match format {
+        "zip" => zip::inspect_zip(archive),
+        "7z" => sevenz::inspect_7z(archive),
+        format => match tar_compression(format) {
+            Some(Algorithm::Store) => tar::inspect_tar(archive),
+            Some(algorithm) => tar::inspect_tar_compressed(archive, algorithm),
+            None => Err(Error::UnsupportedFormat(format.to_string())),
         },
-    }
+    });
+    result.map_err(|e| e.with_path(archive))
 }
 
 /// Create an extractor for the given archive file
 pub fn create_extractor(path: &Path) -> Result<Box<dyn extractor::Extractor>> {
-    create_extractor_inner(path, false)
+    create_extractor_inner(path, None, false)
+}
+
+/// Create an extractor reading the archive as `format`, or as the format
+/// [`detect_format`] finds when it is `None`
+pub fn create_extractor_as(
+    path: &Path,
+    format: Option<&str>,
+) -> Result<Box<dyn extractor::Extractor>> {
+    create_extractor_inner(path, format, false)
 }
 
 /// Create a secure extractor for the given archive file with security checks
 pub fn create_secure_extractor(path: &Path) -> Result<Box<dyn extractor::Extractor>> {
-    create_extractor_inner(path, true)
+    create_extractor_inner(path, None, true)
 }
 
 /// Internal function to create extractor with optional security wrapper
-fn create_extractor_inner(path: &Path, secure: bool) -> Result<Box<dyn extractor::Extractor>> {
-    // Detect format by extension
-    let ext = path.extension().and_then(|ext| ext.to_str()).unwrap_or("");
-
-    // Check for double extensions
-    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-    let double_ext = if stem.ends_with(".tar") {
-        format!("tar.{}", ext)
-    } else {
-        ext.to_string()
-    };
-
-    let base_extractor: Box<dyn extractor::Extractor> = match double_ext.as_str() {
-        "tar" => Box::new(tar_extractor::TarExtractor::new()),
-        "tar.gz" | "tgz" => Box::new(tar_extractor::TarExtractor::with_compression(
-            Algorithm::Gzip,
-        )),
-        "tar.zst" | "tzst" => Box::new(tar_extractor::TarExtractor::with_compression(
-            Algorithm::Zstd,
-        )),
-        "tar.xz" | "txz" => Box::new(tar_extractor::TarExtractor::with_compression(Algorithm::Xz)),
-        "tar.br" => Box::new(tar_extractor::TarExtractor::with_compression(
-            Algorithm::Brotli,
-        )),
-        _ => match ext {
-            "tar" => Box::new(tar_extractor::TarExtractor::new()),
-            "gz" if stem.ends_with(".tar") => Box::new(
-                tar_extractor::TarExtractor::with_compression(Algorithm::Gzip),
-            ),
-            "zst" if stem.ends_with(".tar") => Box::new(
-                tar_extractor::TarExtractor::with_compression(Algorithm::Zstd),
-            ),
-            "xz" if stem.ends_with(".tar") => {
-                Box::new(tar_extractor::TarExtractor::with_compression(Algorithm::Xz))
-            }
-            "br" if stem.ends_with(".tar") => Box::new(
-                tar_extractor::TarExtractor::with_compression(Algorithm::Brotli),
-            ),
-            "zip" => Box::new(zip_extractor::ZipExtractor::new()),
-            "7z" => Box::new(sevenz_extractor::SevenZExtractor::new()),
-            _ => return Err(Error::UnsupportedFormat(ext.to_string())),
+fn create_extractor_inner(
+    path: &Path,
+    format: Option<&str>,
+    secure: bool,
+) -> Result<Box<dyn extractor::Extractor>> {
+    let base_extractor: Box<dyn extractor::Extractor> = match resolve_format(path, format)? {
+        "zip" => Box::new(zip_extractor::ZipExtractor::new()),
+        "7z" => Box::new(sevenz_extractor::SevenZExtractor::new()),
+        format => match tar_compression(format) {
+            Some(Algorithm::Store) => Box::new(tar_extractor::TarExtractor::new()),
+            Some(algorithm) => Box::new(tar_extractor::TarExtractor::with_compression(algorithm)),
+            None => return Err(Error::UnsupportedFormat(format.to_string())),
         },
     };
 
@@ -257,6 +287,84 @@ pub struct PackOptions {
     pub force_compress: bool,
     /// Follow symlinks (pack link targets instead of links)
     pub follow_symlinks: bool,
+    /// Levels of nested directories to pack below each input directory;
+    /// deeper ones are left out with everything in them
+    pub max_depth: Option<usize>,
+    /// Files inside packed directories to leave out
+    pub filter: PackFilter,
+    /// Record of where the archive came from, for tar and zip archives
+    pub metadata: Option<ArchiveMetadata>,
+    /// Latest modification time to store, in seconds since the Unix epoch;
+    /// newer timestamps are stored as this, as with `SOURCE_DATE_EPOCH`
+    pub clamp_mtime: Option<u64>,
+    /// Unicode form to store entry names in
+    pub normalize_names: NameNormalization,
+    /// Leading folder to take off the names of the entries below it, as
+    /// [`paths::rebase`] does; other entries keep their names
+    pub strip_prefix: Option<PathBuf>,
+    /// Folder to store every entry in, after `strip_prefix`
+    pub prefix: Option<PathBuf>,
+    /// Memory-map input files of at least [`MMAP_MIN_SIZE`] on local file
+    /// systems instead of reading them; others are read as usual
+    pub use_mmap: bool,
+    /// Callback rewriting each entry before it is written; 7z archives
+    /// cannot be packed, so this covers every writer
+    pub transform: Option<EntryTransform>,
+    /// Time the compression should take at most; the level is chosen to
+    /// fit it instead of taken from `level`, for compressed tar archives
+    pub time_budget: Option<Duration>,
+    /// Hash every file for an incremental pack, instead of trusting the
+    /// earlier manifest's hashes of files whose metadata is unchanged
+    pub paranoid: bool,
+    /// Where to keep a hash of each file as it is read, for tar and zip
+    /// archives, so a manifest of the input needs no second read
+    pub hashes: Option<PackHashes>,
+    /// Order to store the entries of each packed directory in, for tar
+    /// archives, where grouping alike files helps the compressor
+    pub entry_order: PackOrder,
+}
+
+impl PackOptions {
+    /// The modification time to store for an entry last modified at `mtime`
+    pub(crate) fn stored_mtime(&self, mtime: u64) -> u64 {
+        self.clamp_mtime.map_or(mtime, |limit| mtime.min(limit))
+    }
+
+    /// The metadata record to store, its creation time clamped like the
+    /// timestamps of the entries
+    pub(crate) fn stored_metadata(&self) -> Option<ArchiveMetadata> {
+        let mut metadata = self.metadata.clone()?;
+        if let (Some(created), Some(limit)) = (metadata.created, self.clamp_mtime) {
+            metadata.created = Some(created.min(limit.try_into().unwrap_or(i64::MAX)));
+        }
+        Some(metadata)
+    }
+
+    /// The name to store the entry `name` under, after `strip_prefix`,
+    /// `prefix` and name normalization
+    ///
+    /// `None` is the folder `strip_prefix` names, which has no entry of its
+    /// own once its contents are moved up; a file named the whole prefix
+    /// keeps its file name.
+    pub(crate) fn stored_name<'a>(&self, name: &'a Path, is_dir: bool) -> Option<Cow<'a, Path>> {
+        if self.strip_prefix.is_none() && self.prefix.is_none() {
+            return Some(self.normalize_names.apply_path(name));
+        }
+        let rebased = paths::rebase(name, self.strip_prefix.as_deref(), self.prefix.as_deref())
+            .or_else(|| name.file_name().filter(|_| !is_dir).map(PathBuf::from))?;
+        Some(Cow::Owned(
+            self.normalize_names.apply_path(&rebased).into_owned(),
+        ))
+    }
+
+    /// Refuse a `strip_prefix` or `prefix` that is not a relative folder
+    /// inside the archive
+    pub(crate) fn check_paths(&self) -> Result<()> {
+        for folder in [&self.strip_prefix, &self.prefix].into_iter().flatten() {
+            paths::check_prefix(folder)?;
+        }
+        Ok(())
+    }
 }
 
 impl Default for PackOptions {
@@ -268,6 +376,19 @@ impl Default for PackOptions {
             threads: None,
             force_compress: false,
             follow_symlinks: false,
+            max_depth: None,
+            filter: PackFilter::default(),
+            metadata: None,
+            clamp_mtime: None,
+            normalize_names: NameNormalization::default(),
+            strip_prefix: None,
+            prefix: None,
+            use_mmap: false,
+            transform: None,
+            time_budget: None,
+            paranoid: false,
+            hashes: None,
+            entry_order: PackOrder::default(),
         }
     }
 }
@@ -284,7 +405,63 @@ pub struct ExtractOptions {
     /// Remove the specified number of leading path elements
     pub strip_components: Option<usize>,
     /// If the archive contains a single folder, hoist its contents to the output directory
+    ///
+    /// The archive is listed first to find the folder, which is then
+    /// stripped from entry names as they are written.
     pub hoist: bool,
+    /// What to do with files whose names differ only in letter case
+    pub case_collisions: CaseCollisions,
+    /// Whether the output directory treats names differing only in case
+    /// as the same; `None` finds out by writing a probe file to it
+    pub fold_case: Option<bool>,
+    /// Unicode form to write entry names in
+    pub normalize_names: NameNormalization,
+    /// Manifest whose hashes extracted files are checked against as they
+    /// are written
+    pub manifest_check: Option<ManifestCheck>,
+    /// Which entries to extract; the others are skipped as excluded
+    pub entry_filter: Option<EntryFilter>,
+    /// Order to extract entries in, after those in `priority`; followed by
+    /// zip archives and plain tars only
+    pub order: ExtractOrder,
+    /// Archive paths to extract before everything else, in this order; a
+    /// directory brings everything below it along
+    pub priority: Vec<PathBuf>,
+    /// Journal the extraction so [`undo_extraction`] can take it back,
+    /// moving files that `overwrite` replaces into [`BACKUP_DIR`] rather
+    /// than losing them; tar, zip and 7z archives only
+    pub undoable: bool,
+    /// Format to read the archive as, such as `tar.gz` or `zip`, instead of
+    /// the one [`detect_format`] finds
+    pub format: Option<String>,
+    /// What to write for a symlink entry where the platform refuses to
+    /// create one, as on Windows without the privilege
+    pub symlink_fallback: SymlinkFallback,
+    /// Give extracted files default permissions less the process umask
+    /// instead of the modes the archive stored, keeping only whether a file
+    /// is executable; tar and zip archives on Unix
+    pub apply_umask: bool,
+    /// When extracted files are synced to disk; tar, zip and 7z archives
+    pub durability: Durability,
+    /// Keep a journal in [`RESUME_FILE`] of what has been extracted, and
+    /// pick up the one an interrupted extraction of the same archive left,
+    /// skipping the files it finished; tar, zip and 7z archives
+    pub resume: bool,
+}
+
+impl ExtractOptions {
+    /// Whether `entry_filter` lets `entry` through; without a filter every
+    /// entry does
+    pub(crate) fn accepts(&self, entry: &ArchiveEntry) -> bool {
+        self.entry_filter
+            .as_ref()
+            .is_none_or(|filter| filter.accepts(entry))
+    }
+
+    /// Whether entries are extracted in any other order than stored
+    pub(crate) fn is_ordered(&self) -> bool {
+        self.order != ExtractOrder::Archive || !self.priority.is_empty()
+    }
 }
 
 impl Default for ExtractOptions {
@@ -295,10 +472,159 @@ impl Default for ExtractOptions {
             rename: false,
             strip_components: None,
             hoist: false,
+            case_collisions: CaseCollisions::default(),
+            fold_case: None,
+            normalize_names: NameNormalization::default(),
+            manifest_check: None,
+            entry_filter: None,
+            order: ExtractOrder::default(),
+            priority: Vec::new(),
+            undoable: false,
+            format: None,
+            symlink_fallback: SymlinkFallback::default(),
+            apply_umask: false,
+            durability: Durability::default(),
+            resume: false,
         }
     }
 }
 
+/// A caller's test of which entries to extract
+///
+/// It sees each entry as it is stored in the archive, before
+/// `strip_components` or name normalization apply. Rejecting a directory
+/// does not reject the files inside it.
+///
+/// # Example
+///
+/// ```
+/// use flux_core::archive::{EntryFilter, ExtractOptions};
+///
+/// // Only files up to 1 MiB outside of docs/
+/// let options = ExtractOptions {
+///     entry_filter: Some(EntryFilter::new(|entry| {
+///         entry.size <= 1 << 20 && !entry.path.starts_with("docs")
+///     })),
+///     ..ExtractOptions::default()
+/// };
+/// # let _ = options;
+/// ```
+#[derive(Clone)]
+pub struct EntryFilter(Arc<dyn Fn(&ArchiveEntry) -> bool + Send + Sync>);
+
+impl EntryFilter {
+    /// A filter extracting the entries `accepts` returns `true` for
+    pub fn new(accepts: impl Fn(&ArchiveEntry) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(accepts))
+    }
+
+    /// Whether `entry` is extracted
+    pub fn accepts(&self, entry: &ArchiveEntry) -> bool {
+        (self.0)(entry)
+    }
+}
+
+impl std::fmt::Debug for EntryFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EntryFilter(..)")
+    }
+}
+
+/// One entry about to be added to an archive, as an [`EntryTransform`]
+/// sees it
+///
+/// The name has already been normalized and the modification time clamped
+/// as the [`PackOptions`] ask.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackEntry {
+    /// Name within the archive
+    pub path: PathBuf,
+    /// Unix permissions (if available)
+    pub mode: Option<u32>,
+    /// Owner user id (if available; zip archives do not store it)
+    pub uid: Option<u64>,
+    /// Owner group id (if available; zip archives do not store it)
+    pub gid: Option<u64>,
+    /// Modification time (Unix timestamp)
+    pub mtime: Option<u64>,
+    source: PathBuf,
+    is_dir: bool,
+    is_symlink: bool,
+}
+
+impl PackEntry {
+    pub(crate) fn new(source: &Path, path: PathBuf, is_dir: bool, is_symlink: bool) -> Self {
+        Self {
+            path,
+            mode: None,
+            uid: None,
+            gid: None,
+            mtime: None,
+            source: source.to_path_buf(),
+            is_dir,
+            is_symlink,
+        }
+    }
+
+    /// The file or directory on disk the entry is made from
+    pub fn source(&self) -> &Path {
+        &self.source
+    }
+
+    /// Whether this is a directory
+    pub fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+
+    /// Whether this is a symlink
+    pub fn is_symlink(&self) -> bool {
+        self.is_symlink
+    }
+}
+
+/// Callback rewriting the name, permissions, owner or modification time of
+/// every entry as it is packed, set in [`PackOptions::transform`]
+///
+/// It is applied the same way to tar and zip archives, to files, symlinks
+/// and directories alike.
+///
+/// ```
+/// use flux_core::archive::{EntryTransform, PackOptions};
+/// use std::path::Path;
+///
+/// // Everything below release/, owned by root and not writable by others
+/// let options = PackOptions {
+///     transform: Some(EntryTransform::new(|entry| {
+///         entry.path = Path::new("release").join(&entry.path);
+///         entry.mode = entry.mode.map(|mode| mode & !0o022);
+///         entry.uid = Some(0);
+///         entry.gid = Some(0);
+///     })),
+///     ..PackOptions::default()
+/// };
+/// # let _ = options;
+/// ```
+#[derive(Clone)]
+pub struct EntryTransform(Arc<dyn Fn(&mut PackEntry) + Send + Sync>);
+
+impl EntryTransform {
+    /// A transform calling `transform` on every entry
+    pub fn new(transform: impl Fn(&mut PackEntry) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(transform))
+    }
+
+    /// Rewrite `entry`
+    pub fn apply(&self, entry: &mut PackEntry) {
+        (self.0)(entry)
+    }
+}
+
+impl std::fmt::Debug for EntryTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("EntryTransform(..)")
+    }
+}
+
 /// Pack files with compression strategy
 pub fn pack_with_strategy<P: AsRef<Path>, Q: AsRef<Path>>(
     input: P,
@@ -306,10 +632,105 @@ pub fn pack_with_strategy<P: AsRef<Path>, Q: AsRef<Path>>(
     format: Option<&str>,
     options: PackOptions,
 ) -> Result<()> {
+    pack_with_progress(input, output, format, options, &NoProgress)?.into_result()
+}
+
+/// Like [`pack_with_strategy`], reporting progress to `progress`
+///
+/// [`ProgressSink::start`] is called once the inputs have been measured,
+/// which for a smart strategy comes after the inputs were sampled. 7z
+/// archives report no progress.
+///
+/// Unlike [`pack_with_strategy`], files that cannot be read do not fail the
+/// call; they are listed in the returned outcome and the rest are packed.
+pub fn pack_with_progress<P: AsRef<Path>, Q: AsRef<Path>>(
+    input: P,
+    output: Q,
+    format: Option<&str>,
+    options: PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
     let input = input.as_ref();
     let output = output.as_ref();
+    options.check_paths()?;
+    let strategy = compression_strategy(input, &options)?;
 
-    // Determine compression strategy
+    // Determine output format: as given, else the tar the output's name
+    // calls for, else a tar compressed as the strategy chose. Zip and 7z
+    // are only written when asked for.
+    let format = match format {
+        Some(name) => resolve_format(output, Some(name))?,
+        None => match detect::output_format(output) {
+            Some(format) if tar_compression(format).is_some() => format,
+            // A tar of a compression Flux does not know is not written as
+            // one it does
+            _ if output
+                .file_stem()
+                .is_some_and(|stem| stem.to_string_lossy().to_lowercase().ends_with(".tar")) =>
+            {
+                let name = output.file_name().unwrap_or_default().to_string_lossy();
+                return Err(detect::unsupported_format(&name));
+            }
+            _ => strategy_format(&strategy),
+        },
+    };
+
+    match format {
+        "zip" => zip::pack_zip_with_progress(input, output, &options, progress),
+        "7z" => sevenz::pack_7z(input, output).map(|()| OperationOutcome::default()), // Note: 7z packing not yet supported
+        format => match tar_compression(format) {
+            Some(Algorithm::Store) => {
+                tar::pack_tar_with_progress(input, output, &options, progress)
+            }
+            Some(algorithm) => tar::pack_tar_compressed_with_progress(
+                input,
+                output,
+                algorithm,
+                strategy.level,
+                &options,
+                progress,
+            ),
+            None => Err(Error::UnsupportedFormat(format.to_string())),
+        },
+    }
+}
+
+/// Pack `input` into `writer`, which need not be seekable, as
+/// [`pack_with_progress`] packs it into a file
+///
+/// Only tars can be written this way, since zip and 7z writers seek back
+/// into what they wrote; `format` defaults to a tar compressed as the
+/// strategy chose.
+pub fn pack_to_writer<P: AsRef<Path>, W: Write>(
+    input: P,
+    writer: W,
+    format: Option<&str>,
+    options: PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
+    let input = input.as_ref();
+    options.check_paths()?;
+    let strategy = compression_strategy(input, &options)?;
+    let format = match format {
+        Some(name) => parse_format(name).ok_or_else(|| detect::unsupported_format(name))?,
+        None => strategy_format(&strategy),
+    };
+    let algorithm = tar_compression(format).ok_or_else(|| unseekable(format))?;
+    tar::pack_tar_to_writer(input, writer, algorithm, strategy.level, &options, progress)
+}
+
+/// `compression.default_level` from the configuration, for when neither the
+/// options nor the smart strategy choose a level
+fn default_level() -> u32 {
+    if std::env::var("FLUX_NO_CONFIG").is_err() {
+        Config::load_or_default().compression.default_level
+    } else {
+        CompressionStrategy::default().level
+    }
+}
+
+/// Compression strategy for packing `input` with `options`
+fn compression_strategy(input: &Path, options: &PackOptions) -> Result<CompressionStrategy> {
     let mut strategy = if options.smart && options.algorithm.is_none() {
         // Use smart strategy
         if input.is_dir() {
@@ -324,14 +745,17 @@ pub fn pack_with_strategy<P: AsRef<Path>, Q: AsRef<Path>>(
             .map_err(|_| Error::UnsupportedFormat(format!("Unknown algorithm: {}", algo_str)))?;
         CompressionStrategy {
             algorithm,
-            level: options.level.unwrap_or(3),
+            level: options.level.unwrap_or_else(default_level),
             threads: options.threads.unwrap_or_else(rayon::current_num_threads),
             force_compress: options.force_compress,
             long_mode: false,
         }
     } else {
         // Use default strategy
-        CompressionStrategy::default()
+        CompressionStrategy {
+            level: options.level.unwrap_or_else(default_level),
+            ..CompressionStrategy::default()
+        }
     };
 
     strategy.force_compress = options.force_compress;
@@ -349,160 +773,161 @@ pub fn pack_with_strategy<P: AsRef<Path>, Q: AsRef<Path>>(
     strategy.adjust_for_parallel(file_size);
 
     info!("Using compression strategy: {:?}", strategy);
+    Ok(strategy)
+}
 
-    // Determine output format
-    let format = if let Some(fmt) = format {
-        fmt.to_string()
-    } else {
-        // Infer from output filename
-        let ext = output
-            .extension()
-            .and_then(|ext| ext.to_str())
-            .unwrap_or("");
-
-        // Check for double extensions
-        if let Some(stem) = output.file_stem().and_then(|s| s.to_str()) {
-            if stem.ends_with(".tar") {
-                format!("tar.{}", ext)
-            } else if matches!(ext, "gz" | "zst" | "xz" | "br") {
-                // These are compression extensions, assume tar
-                format!("tar.{}", ext)
-            } else if ext == "tar" {
-                "tar".to_string()
-            } else {
-                // No clear format, use smart default based on algorithm
-                match strategy.algorithm {
-                    Algorithm::Gzip => "tar.gz",
-                    Algorithm::Zstd => "tar.zst",
-                    Algorithm::Xz => "tar.xz",
-                    Algorithm::Brotli => "tar.br",
-                    Algorithm::Store => "tar",
-                }
-                .to_string()
-            }
-        } else {
-            // No clear format, use smart default based on algorithm
-            match strategy.algorithm {
-                Algorithm::Gzip => "tar.gz",
-                Algorithm::Zstd => "tar.zst",
-                Algorithm::Xz => "tar.xz",
-                Algorithm::Brotli => "tar.br",
-                Algorithm::Store => "tar",
-            }
-            .to_string()
-        }
+/// The tar compressed as `strategy` chose
+fn strategy_format(strategy: &CompressionStrategy) -> &'static str {
+    match strategy.algorithm {
+        Algorithm::Gzip => "tar.gz",
+        Algorithm::Zstd => "tar.zst",
+        Algorithm::Xz => "tar.xz",
+        Algorithm::Brotli => "tar.br",
+        Algorithm::Store => "tar",
+    }
+}
+
+/// Error for writing a `format` archive somewhere it cannot seek
+fn unseekable(format: &str) -> Error {
+    Error::UnsupportedOperation(format!(
+        "{} archives need a seekable output; only tars can be streamed",
+        format
+    ))
+}
+
+/// Pack several files and directories into one archive, reporting
+/// progress to `progress`
+///
+/// `format` names the archive format, such as `tar.zst` or `zip`. Files are
+/// named relative to `base_dir`, or by their file name without one, and
+/// directories keep their own name as the top folder. The level defaults
+/// to 3 when `options` sets none; `smart` and `algorithm` are not used.
+/// Files that cannot be read are listed in the outcome, as by
+/// [`pack_with_progress`].
+pub fn pack_multiple<P: AsRef<Path>>(
+    inputs: &[P],
+    output: &Path,
+    format: &str,
+    base_dir: Option<&Path>,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
+    let roots: Vec<_> = inputs
+        .iter()
+        .map(|input| PackRoot::named_in(input.as_ref(), base_dir))
+        .collect();
+    pack_roots(&roots, output, format, options, progress)
+}
+
+/// Pack each of `roots` into one archive under the name it gives,
+/// reporting progress to `progress`
+///
+/// Inputs are read where they are; nothing is staged. Use
+/// [`PackRoot::below_common_base`] to name inputs from several folders by
+/// their paths below the folder they share. Options are used as by
+/// [`pack_multiple`]. A name, `strip_prefix` or `prefix` that is absolute
+/// or leaves the archive with `..` is an error before anything is written.
+pub fn pack_roots(
+    roots: &[PackRoot],
+    output: &Path,
+    format: &str,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
+    for root in roots {
+        root.check()?;
+    }
+    options.check_paths()?;
+    let algorithm = match parse_format(format) {
+        Some("zip") => return zip::pack_roots_zip(roots, output, options, progress),
+        parsed => match parsed.and_then(tar_compression) {
+            Some(algorithm) => algorithm,
+            None => return Err(detect::unsupported_format(format)),
+        },
     };
+    tar::pack_roots_tar(roots, output, algorithm, options, progress)
+}
 
-    // Support both tar and zip formats
-    match format.as_str() {
-        "tar" => tar::pack_tar_with_options(input, output, options.follow_symlinks),
-        "tar.gz" | "tgz" => tar::pack_tar_compressed_with_options(
-            input,
-            output,
-            Algorithm::Gzip,
-            strategy.level,
-            options.follow_symlinks,
-        ),
-        "tar.zst" | "tzst" => tar::pack_tar_compressed_with_options(
-            input,
-            output,
-            Algorithm::Zstd,
-            strategy.level,
-            options.follow_symlinks,
-        ),
-        "tar.xz" | "txz" => tar::pack_tar_compressed_with_options(
-            input,
-            output,
-            Algorithm::Xz,
-            strategy.level,
-            options.follow_symlinks,
-        ),
-        "tar.br" => tar::pack_tar_compressed_with_options(
-            input,
-            output,
-            Algorithm::Brotli,
-            strategy.level,
-            options.follow_symlinks,
-        ),
-        "zip" => zip::pack_zip_with_options(input, output, options.follow_symlinks),
-        "7z" => sevenz::pack_7z(input, output), // Note: 7z packing not yet supported
-        _ => Err(Error::UnsupportedFormat(format)),
+/// Pack each of `roots` into `writer`, which need not be seekable, as
+/// [`pack_roots`] packs them into a file
+///
+/// `format` must name a tar, as for [`pack_to_writer`].
+pub fn pack_roots_to_writer<W: Write>(
+    roots: &[PackRoot],
+    writer: W,
+    format: &str,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
+    for root in roots {
+        root.check()?;
     }
+    options.check_paths()?;
+    let format = parse_format(format).ok_or_else(|| detect::unsupported_format(format))?;
+    let algorithm = tar_compression(format).ok_or_else(|| unseekable(format))?;
+    tar::pack_roots_tar_to_writer(roots, writer, algorithm, options, progress)
 }
 
 /// Extract files from an archive with options
+///
+/// Entries that cannot be written, and those left out by `options` or for
+/// safety, are listed in the returned outcome; extraction goes on past
+/// them. An unreadable archive is still an error.
+///
+/// With [`CaseCollisions::Error`] and an output directory that folds case,
+/// the archive is listed first and any collision is an error before
+/// anything is written.
+///
+/// To extract an archive that has already been listed, as after showing
+/// its entries, use [`Archive::extract_all`], which lists it only once.
 pub fn extract_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
     archive: P,
     output_dir: Q,
     options: ExtractOptions,
-) -> Result<()> {
-    let archive = archive.as_ref();
-    let output_dir = output_dir.as_ref();
+) -> Result<OperationOutcome> {
+    Archive::open_as(archive, options.format.as_deref())?.extract_all(output_dir, options)
+}
 
-    // Store whether hoist is enabled before moving options
-    let should_hoist = options.hoist;
+/// Extract `archive` as [`extract_with_options`] does
+pub(crate) fn extract_opened(
+    archive: &Archive,
+    output_dir: &Path,
+    options: ExtractOptions,
+) -> Result<OperationOutcome> {
+    let path = archive.path();
+    let mut options = options;
 
-    // Detect format by extension
-    let ext = archive
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .unwrap_or("");
+    // The single top folder is stripped as entries are written; archives
+    // that cannot be listed are hoisted on disk afterwards instead
+    let mut hoisted = None;
+    let mut should_hoist = false;
+    if options.hoist {
+        match single_top_directory(archive, &options) {
+            Ok(Some(top)) => {
+                info!("Hoisting the contents of {:?}", top);
+                options.strip_components = Some(options.strip_components.unwrap_or(0) + 1);
+                hoisted = Some(top);
+            }
+            Ok(None) => {}
+            Err(e) if e.code() == ErrorCode::UnsupportedOperation => should_hoist = true,
+            Err(e) => return Err(e),
+        }
+    }
 
-    // Check for double extensions
-    let stem = archive.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-    let double_ext = if stem.ends_with(".tar") {
-        format!("tar.{}", ext)
-    } else {
-        ext.to_string()
-    };
+    if options.case_collisions == CaseCollisions::Error {
+        check_case_collisions(archive, output_dir, &options)?;
+    }
 
     // Perform the extraction
-    let result = match double_ext.as_str() {
-        "tar" => tar::extract_tar_with_options(archive, output_dir, options),
-        "tar.gz" | "tgz" => {
-            tar::extract_tar_compressed_with_options(archive, output_dir, Algorithm::Gzip, options)
-        }
-        "tar.zst" | "tzst" => {
-            tar::extract_tar_compressed_with_options(archive, output_dir, Algorithm::Zstd, options)
-        }
-        "tar.xz" | "txz" => {
-            tar::extract_tar_compressed_with_options(archive, output_dir, Algorithm::Xz, options)
-        }
-        "tar.br" => tar::extract_tar_compressed_with_options(
-            archive,
-            output_dir,
-            Algorithm::Brotli,
-            options,
-        ),
-        _ => match ext {
-            "tar" => tar::extract_tar_with_options(archive, output_dir, options),
-            "gz" if stem.ends_with(".tar") => tar::extract_tar_compressed_with_options(
-                archive,
-                output_dir,
-                Algorithm::Gzip,
-                options,
-            ),
-            "zst" if stem.ends_with(".tar") => tar::extract_tar_compressed_with_options(
-                archive,
-                output_dir,
-                Algorithm::Zstd,
-                options,
-            ),
-            "xz" if stem.ends_with(".tar") => tar::extract_tar_compressed_with_options(
-                archive,
-                output_dir,
-                Algorithm::Xz,
-                options,
-            ),
-            "br" if stem.ends_with(".tar") => tar::extract_tar_compressed_with_options(
-                archive,
-                output_dir,
-                Algorithm::Brotli,
-                options,
-            ),
-            "zip" => zip::extract_zip_with_options(archive, output_dir, options),
-            "7z" => sevenz::extract_7z_with_options(archive, output_dir, options),
-            _ => Err(Error::UnsupportedFormat(ext.to_string())),
+    let result = match archive.format() {
+        "zip" => zip::extract_zip_with_options(path, output_dir, options),
+        "7z" => sevenz::extract_7z_with_options(path, output_dir, options),
+        format => match tar_compression(format) {
+            Some(Algorithm::Store) => tar::extract_tar_with_options(path, output_dir, options),
+            Some(algorithm) => {
+                tar::extract_tar_compressed_with_options(path, output_dir, algorithm, options)
+            }
+            None => Err(Error::UnsupportedFormat(format.to_string())),
         },
     };
 
@@ -515,6 +940,110 @@ pub fn extract_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
     }
 
     result
+        .map(|mut outcome| {
+            // The folder hoisted out of is not an entry left out
+            if let Some(top) = &hoisted {
+                outcome.skipped.retain(|skipped| {
+                    skipped.reason != SkipReason::Stripped || !skipped.path.ends_with(top)
+                });
+            }
+            outcome.with_path(path)
+        })
+        .map_err(|e| e.with_path(path))
+}
+
+/// Entries of `archive` as [`ExtractOptions::accepts`] sees them, or
+/// [`Error::UnsupportedOperation`] for an archive that cannot be listed
+fn listed_entries(archive: &Archive) -> Result<Vec<ArchiveEntry>> {
+    Ok(archive
+        .entries()?
+        .iter()
+        .cloned()
+        .map(ArchiveEntry::from)
+        .collect())
+}
+
+/// The folder that every entry `options` extracts from `archive` lies in,
+/// after `strip_components`, if there is one with anything in it
+fn single_top_directory(archive: &Archive, options: &ExtractOptions) -> Result<Option<PathBuf>> {
+    let entries = listed_entries(archive)?;
+    Ok(top_directory(
+        entries
+            .iter()
+            .filter(|entry| options.accepts(entry))
+            .map(|entry| (entry.path.as_path(), entry.is_dir)),
+        options.strip_components.unwrap_or(0),
+    ))
+}
+
+/// The folder that all of `entries`, given by name and whether they are
+/// directories, lie in once `strip` leading components are removed from
+/// their names, if there is one with anything in it
+pub(crate) fn top_directory<'a>(
+    entries: impl IntoIterator<Item = (&'a Path, bool)>,
+    strip: usize,
+) -> Option<PathBuf> {
+    let mut top: Option<PathBuf> = None;
+    let mut anything_below = false;
+    for (path, is_dir) in entries {
+        let mut components = path.components().skip(strip);
+        let Some(first) = components.next() else {
+            continue;
+        };
+        let below = components.next().is_some();
+        // A file or link next to the folder keeps everything in place
+        if !below && !is_dir {
+            return None;
+        }
+        anything_below |= below;
+        match &top {
+            Some(top) if top.as_os_str() != first.as_os_str() => return None,
+            Some(_) => {}
+            None => top = Some(PathBuf::from(first.as_os_str())),
+        }
+    }
+    top.filter(|_| anything_below)
+}
+
+/// Fail if the files of `archive` would collide in `output_dir` because
+/// their names differ only in letter case
+fn check_case_collisions(
+    archive: &Archive,
+    output_dir: &Path,
+    options: &ExtractOptions,
+) -> Result<()> {
+    std::fs::create_dir_all(output_dir)?;
+    if !case_fold::checks_case(options.fold_case, output_dir) {
+        return Ok(());
+    }
+    let mut entries = match listed_entries(archive) {
+        Ok(entries) => entries,
+        Err(e) if e.code() == ErrorCode::UnsupportedOperation => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    entries.retain(|entry| options.accepts(entry));
+    // Names are compared as they will be written
+    let strip = options.strip_components.unwrap_or(0);
+    entries.retain_mut(|entry| {
+        let stripped = paths::strip_components(&entry.path, strip).unwrap_or_default();
+        entry.path = options.normalize_names.apply_path(&stripped).into_owned();
+        !entry.path.as_os_str().is_empty()
+    });
+    let collisions = find_case_collisions(&entries);
+    for collision in &collisions {
+        warn!(
+            "{:?} and {:?} differ only in case",
+            collision.earlier, collision.path
+        );
+    }
+    match collisions.len() {
+        0 => Ok(()),
+        count => Err(Error::InvalidPath(format!(
+            "{} file names differ from others only in letter case, starting with {}",
+            count,
+            collisions[0].path.display()
+        ))),
+    }
 }
 
 /// Hoist the contents of a single subdirectory to the parent directory
@@ -522,6 +1051,11 @@ pub fn extract_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
 /// This function checks if the output directory contains exactly one subdirectory,
 /// and if so, moves all contents of that subdirectory up one level and removes
 /// the now-empty subdirectory.
+///
+/// [`extract_with_options`] only falls back to this for archives it cannot
+/// list up front, as moving is slow for large trees. Where the folder is
+/// on another file system than the output directory, as when it is a mount
+/// point, its contents are copied up as by [`crate::utils::move_path`].
 pub fn hoist_single_directory(output_dir: &Path) -> Result<()> {
     use std::fs;
 
@@ -553,7 +1087,7 @@ pub fn hoist_single_directory(output_dir: &Path) -> Result<()> {
                 let dest = output_dir.join(dest_name);
 
                 info!("Moving {:?} to {:?}", source, dest);
-                fs::rename(&source, &dest)?;
+                crate::utils::move_path(&source, &dest)?;
             }
 
             // Remove the now-empty directory
@@ -0,0 +1,503 @@
+//! Renaming, deleting and adding entries of an existing archive
+//!
+//! Zip entries are copied into the new archive with their compressed data
+//! untouched; only the names in the local headers and the central directory
+//! change, and extra fields such as Info-ZIP timestamps and ownership are
+//! kept. Tar archives are streamed entry by entry with rewritten headers
+//! and the pax records of the source, so nothing is extracted, although a
+//! compressed tar is decompressed and compressed again on the way, at the
+//! default level of its compression rather than the one it was packed at.
+//! Deleted entries are left out of the copy
+//! and added files are written after it. The rewritten archive is written
+//! next to the original and only replaces it once complete.
+
+use super::detect::detect_format;
+use super::meta::{append_pax_header, is_global_header, pax_record};
+use super::paths::entry_key;
+use super::zip::entry_options;
+use super::zip_extra::{copy_zip, EntryCopy};
+use super::PackOptions;
+use crate::{Error, Result};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use tracing::info;
+use walkdir::WalkDir;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Changes to apply to the entries of an archive
+///
+/// Renaming a directory moves everything below it, and deleting one
+/// removes everything below it. An entry that is deleted is not renamed.
+///
+/// ```no_run
+/// use flux_core::archive::modify::Modifier;
+///
+/// let changed = Modifier::new("backup.zip")
+///     .rename("docs/old", "docs/new")
+///     .delete("drafts")
+///     .add("notes.txt", "docs/notes.txt")
+///     .apply()?;
+/// # Ok::<(), flux_core::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Modifier {
+    archive: PathBuf,
+    renames: Vec<(String, String)>,
+    deletes: Vec<String>,
+    /// Files and directories on disk, with the entry name each is added as
+    additions: Vec<(PathBuf, String)>,
+}
+
+impl Modifier {
+    /// Start modifying `archive`
+    pub fn new<P: Into<PathBuf>>(archive: P) -> Self {
+        Self {
+            archive: archive.into(),
+            renames: Vec::new(),
+            deletes: Vec::new(),
+            additions: Vec::new(),
+        }
+    }
+
+    /// Rename the entry `from`, or move the directory `from` with its contents,
+    /// to `to`
+    pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(mut self, from: P, to: Q) -> Self {
+        self.renames
+            .push((entry_key(from.as_ref()), entry_key(to.as_ref())));
+        self
+    }
+
+    /// Remove the entry `path`, or the directory `path` with its contents
+    pub fn delete<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.deletes.push(entry_key(path.as_ref()));
+        self
+    }
+
+    /// Add the file `source`, or the directory `source` with its contents,
+    /// as the entry `name`
+    pub fn add<P: Into<PathBuf>, Q: AsRef<Path>>(mut self, source: P, name: Q) -> Self {
+        self.additions
+            .push((source.into(), entry_key(name.as_ref())));
+        self
+    }
+
+    /// Rewrite the archive, returning how many entries were renamed,
+    /// deleted or added
+    ///
+    /// Fails without touching the archive if a renamed or deleted path
+    /// matches no entry, a file to add is missing, or a new name would clash
+    /// with an entry that is already there.
+    pub fn apply(self) -> Result<usize> {
+        for (from, to) in &self.renames {
+            check_entry_name(from)?;
+            check_entry_name(to)?;
+        }
+        for name in &self.deletes {
+            check_entry_name(name)?;
+        }
+        let mut added = Vec::new();
+        for (source, name) in &self.additions {
+            check_entry_name(name)?;
+            added.extend(added_entries(source, name)?);
+        }
+
+        let format = detect_format(&self.archive)?;
+        info!(
+            "Changing entries in {:?}: {} renames, {} deletions, {} additions",
+            self.archive,
+            self.renames.len(),
+            self.deletes.len(),
+            self.additions.len()
+        );
+
+        let names = match format {
+            "zip" => zip_names(&self.archive)?,
+            "7z" => {
+                return Err(Error::UnsupportedOperation(
+                    "Changing entries in 7z archives".to_string(),
+                ))
+            }
+            _ => tar_names(&self.archive, format)?,
+        };
+        let changed = self.plan(&names, &added)? + added.len();
+        if changed == 0 {
+            return Ok(0);
+        }
+
+        let temp = temp_path(&self.archive);
+        let result = match format {
+            "zip" => self.rewrite_zip(&temp, &added),
+            _ => self.rewrite_tar(format, &temp, &added),
+        };
+        // The rewritten archive replaces the original with its permissions
+        let result = result.and_then(|()| {
+            let permissions = fs::metadata(&self.archive)?.permissions();
+            Ok(fs::set_permissions(&temp, permissions)?)
+        });
+        match result {
+            Ok(()) => fs::rename(&temp, &self.archive)?,
+            Err(e) => {
+                let _ = fs::remove_file(&temp);
+                return Err(e);
+            }
+        }
+
+        info!("Changed {} entries", changed);
+        Ok(changed)
+    }
+
+    /// Whether the entry `name` is deleted
+    fn is_deleted(&self, name: &str) -> bool {
+        self.deletes.iter().any(|path| lies_in(name, path))
+    }
+
+    /// What becomes of the entry `name` in the rewritten archive
+    fn copy(&self, name: &str) -> EntryCopy {
+        if self.is_deleted(name) {
+            return EntryCopy::Drop;
+        }
+        match self.new_name(name) {
+            Some(new_name) => EntryCopy::Rename(new_name),
+            None => EntryCopy::Keep,
+        }
+    }
+
+    /// New name of the entry `name`, if one of the renames applies to it
+    ///
+    /// Directory entries keep their trailing `/`.
+    fn new_name(&self, name: &str) -> Option<String> {
+        self.renames
+            .iter()
+            .find_map(|(from, to)| renamed(name, from, to))
+    }
+
+    /// Check the renames and deletions against the archive's entries, and
+    /// the entries to add against the ones kept, and count the entries
+    /// renamed or deleted
+    fn plan<'a>(&self, names: &'a [String], added: &[AddedEntry]) -> Result<usize> {
+        let missing =
+            |path: &str| Error::NotFound(format!("{} in {}", path, self.archive.display()));
+        for (from, to) in &self.renames {
+            if !names.iter().any(|name| renamed(name, from, to).is_some()) {
+                return Err(missing(from));
+            }
+        }
+        for path in &self.deletes {
+            if !names.iter().any(|name| lies_in(name, path)) {
+                return Err(missing(path));
+            }
+        }
+
+        let key = |name: &'a str| name.trim_start_matches("./").trim_end_matches('/');
+        let mut kept = HashSet::new();
+        let mut moved = Vec::new();
+        let mut deleted = 0;
+        for name in names {
+            match self.copy(name) {
+                EntryCopy::Drop => deleted += 1,
+                EntryCopy::Rename(new_name) => moved.push((key(name), new_name)),
+                EntryCopy::Keep => {
+                    kept.insert(key(name));
+                }
+            }
+        }
+        // Nor may two renames land on one name, unless the archive held
+        // that entry twice already
+        let mut targets = HashMap::new();
+        for (name, new_name) in &moved {
+            let target = new_name.trim_end_matches('/');
+            if kept.contains(target)
+                || targets
+                    .insert(target, *name)
+                    .is_some_and(|other| other != *name)
+            {
+                return Err(Error::FileExists(PathBuf::from(target)));
+            }
+        }
+        kept.extend(targets.into_keys());
+        // A folder that is there already can take more files
+        if let Some(clash) = added
+            .iter()
+            .find(|entry| !entry.is_dir && kept.contains(entry.name.as_str()))
+        {
+            return Err(Error::FileExists(PathBuf::from(&clash.name)));
+        }
+
+        Ok(moved.len() + deleted)
+    }
+
+    fn rewrite_zip(&self, output: &Path, added: &[AddedEntry]) -> Result<()> {
+        copy_zip(&self.archive, output, |name| self.copy(name))?;
+        if added.is_empty() {
+            return Ok(());
+        }
+
+        let file = fs::OpenOptions::new().read(true).write(true).open(output)?;
+        let mut zip = ZipWriter::new_append(file)?;
+        let pack_options = PackOptions::default();
+        for entry in added {
+            let metadata = fs::symlink_metadata(&entry.source)?;
+            let base = FileOptions::<'static, ()>::default()
+                .compression_method(CompressionMethod::Deflated);
+            let (_, file_options) =
+                entry_options(&entry.source, &entry.name, base, &metadata, &pack_options);
+            if entry.is_dir {
+                zip.add_directory(&entry.name, file_options)?;
+            } else {
+                zip.start_file(&entry.name, file_options)?;
+                std::io::copy(&mut File::open(&entry.source)?, &mut zip)?;
+            }
+        }
+        zip.finish()?;
+        Ok(())
+    }
+
+    fn rewrite_tar(&self, format: &str, output: &Path, added: &[AddedEntry]) -> Result<()> {
+        let reader = tar_reader(&self.archive, format)?;
+        write_tar(format, output, |builder| {
+            self.copy_tar(reader, builder)?;
+            builder.follow_symlinks(false);
+            for entry in added {
+                if entry.is_dir {
+                    builder.append_dir(&entry.name, &entry.source)?;
+                } else {
+                    builder.append_path_with_name(&entry.source, &entry.name)?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Copy every entry of a tar stream into `builder`, renaming as planned
+    fn copy_tar<R: Read>(&self, input: R, builder: &mut TarBuilder<'_>) -> Result<()> {
+        let mut archive = tar::Archive::new(input);
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let mut header = entry.header().clone();
+            let name = entry_name(&entry)?;
+            if self.is_deleted(&name) {
+                continue;
+            }
+            let path = self.new_name(&name).unwrap_or(name);
+
+            // The name is written anew; the rest of the entry's pax records,
+            // such as times, owners and extended attributes, are kept
+            if !is_global_header(&header) {
+                if let Some(extensions) = entry.pax_extensions()? {
+                    let mut records = Vec::new();
+                    for extension in extensions {
+                        let extension = extension?;
+                        if !matches!(extension.key_bytes(), b"path" | b"linkpath") {
+                            records
+                                .extend(pax_record(extension.key_bytes(), extension.value_bytes()));
+                        }
+                    }
+                    if !records.is_empty() {
+                        append_pax_header(builder, &records)?;
+                    }
+                }
+            }
+
+            let entry_type = header.entry_type();
+            if entry_type.is_symlink() || entry_type.is_hard_link() {
+                let target = entry
+                    .link_name()?
+                    .map(|target| target.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                // Hard links name another entry, which may have moved too
+                let target = if entry_type.is_hard_link() {
+                    self.new_name(&target).unwrap_or(target)
+                } else {
+                    target
+                };
+                builder.append_link(&mut header, &path, &target)?;
+            } else {
+                builder.append_data(&mut header, &path, &mut entry)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Tar builder writing through the compressor of the archive's format
+pub(crate) type TarBuilder<'a> = tar::Builder<&'a mut dyn Write>;
+
+/// Write a tar archive compressed as `format` to `output`, with `append`
+/// adding its entries
+pub(crate) fn write_tar<F>(format: &str, output: &Path, append: F) -> Result<()>
+where
+    F: FnOnce(&mut TarBuilder<'_>) -> Result<()>,
+{
+    let file = BufWriter::new(File::create(output)?);
+    match format {
+        "tar.gz" => {
+            let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            append_entries(&mut encoder, append)?;
+            encoder.finish()?.flush()?;
+        }
+        "tar.zst" => {
+            let mut encoder = zstd::stream::write::Encoder::new(file, 3)?;
+            encoder.include_checksum(true)?;
+            append_entries(&mut encoder, append)?;
+            encoder.finish()?.flush()?;
+        }
+        "tar.xz" => {
+            let mut encoder = xz2::write::XzEncoder::new(file, 6);
+            append_entries(&mut encoder, append)?;
+            encoder.finish()?.flush()?;
+        }
+        "tar.br" => {
+            let mut encoder = brotli::CompressorWriter::new(file, 4096, 6, 22);
+            append_entries(&mut encoder, append)?;
+            // The stream is finished when the encoder is dropped
+            encoder.flush()?;
+        }
+        _ => {
+            let mut file = file;
+            append_entries(&mut file, append)?;
+            file.flush()?;
+        }
+    }
+    Ok(())
+}
+
+fn append_entries<F>(output: &mut dyn Write, append: F) -> Result<()>
+where
+    F: FnOnce(&mut TarBuilder<'_>) -> Result<()>,
+{
+    let mut builder = tar::Builder::new(output);
+    append(&mut builder)?;
+    builder.finish()?;
+    Ok(())
+}
+
+/// Rename a single entry or directory of `archive`
+///
+/// Shorthand for [`Modifier::rename`] followed by [`Modifier::apply`].
+pub fn rename_entry<P, Q, R>(archive: P, from: Q, to: R) -> Result<usize>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    R: AsRef<Path>,
+{
+    Modifier::new(archive.as_ref()).rename(from, to).apply()
+}
+
+/// A file or directory on disk to add, with its entry name
+#[derive(Debug)]
+struct AddedEntry {
+    source: PathBuf,
+    name: String,
+    is_dir: bool,
+}
+
+/// `source` and everything below it as entries under `name`, directories
+/// before their contents
+fn added_entries(source: &Path, name: &str) -> Result<Vec<AddedEntry>> {
+    if fs::symlink_metadata(source).is_err() {
+        return Err(Error::NotFound(format!("{}", source.display())));
+    }
+    let mut entries = Vec::new();
+    for item in WalkDir::new(source).sort_by_file_name() {
+        let item = item.map_err(|e| Error::Io(e.into()))?;
+        let below = item
+            .path()
+            .strip_prefix(source)
+            .map_err(|_| Error::InvalidPath(format!("{:?}", item.path())))?;
+        let mut entry_name = name.to_string();
+        for component in below.components() {
+            entry_name.push('/');
+            entry_name.push_str(&component.as_os_str().to_string_lossy());
+        }
+        entries.push(AddedEntry {
+            source: item.path().to_path_buf(),
+            name: entry_name,
+            is_dir: item.file_type().is_dir(),
+        });
+    }
+    Ok(entries)
+}
+
+/// `name` with its `from` part replaced by `to`, if it is `from` or lies
+/// below it
+fn renamed(name: &str, from: &str, to: &str) -> Option<String> {
+    let name = name.trim_start_matches("./");
+    let (key, suffix) = match name.strip_suffix('/') {
+        Some(key) => (key, "/"),
+        None => (name, ""),
+    };
+    let rest = key.strip_prefix(from)?;
+    (rest.is_empty() || rest.starts_with('/')).then(|| format!("{}{}{}", to, rest, suffix))
+}
+
+/// Whether the entry `name` is `path` or lies below it
+fn lies_in(name: &str, path: &str) -> bool {
+    renamed(name, path, path).is_some()
+}
+
+/// Reject names that could not be extracted safely
+fn check_entry_name(name: &str) -> Result<()> {
+    let path = Path::new(name);
+    let safe = !name.is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    if safe {
+        Ok(())
+    } else {
+        Err(Error::InvalidPath(format!(
+            "{:?} is not a relative path inside the archive",
+            name
+        )))
+    }
+}
+
+/// Full name of a tar entry, with a trailing `/` for directories
+fn entry_name<R: Read>(entry: &tar::Entry<'_, R>) -> Result<String> {
+    let mut name = entry.path()?.to_string_lossy().replace('\\', "/");
+    if entry.header().entry_type().is_dir() && !name.ends_with('/') {
+        name.push('/');
+    }
+    Ok(name)
+}
+
+fn zip_names(archive: &Path) -> Result<Vec<String>> {
+    let source = ZipArchive::new(BufReader::new(File::open(archive)?))?;
+    Ok(source.file_names().map(str::to_string).collect())
+}
+
+fn tar_names(archive: &Path, format: &str) -> Result<Vec<String>> {
+    let mut archive = tar::Archive::new(tar_reader(archive, format)?);
+    let mut names = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !is_global_header(entry.header()) {
+            names.push(entry_name(&entry)?);
+        }
+    }
+    Ok(names)
+}
+
+pub(crate) fn tar_reader(archive: &Path, format: &str) -> Result<Box<dyn Read>> {
+    let file = BufReader::new(File::open(archive)?);
+    Ok(match format {
+        "tar.gz" => Box::new(flate2::read::GzDecoder::new(file)),
+        "tar.zst" => Box::new(zstd::stream::read::Decoder::with_buffer(file)?),
+        "tar.xz" => Box::new(xz2::read::XzDecoder::new(file)),
+        "tar.br" => Box::new(brotli::Decompressor::new(file, 4096)),
+        _ => Box::new(file),
+    })
+}
+
+/// Where the rewritten archive is built before it replaces `archive`
+pub(crate) fn temp_path(archive: &Path) -> PathBuf {
+    let name = archive
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    archive.with_file_name(format!(".{}.{}.tmp", name, std::process::id()))
+}
@@ -0,0 +1,471 @@
+//! Extracting archives found inside an archive
+//!
+//! [`extract_recursive`] extracts an archive as usual, then looks through
+//! what it wrote for files named as archives, such as a zip in a tarball or
+//! a tar.gz in a zip. Each is extracted into a folder next to it, named
+//! after it without its extension, and removed once all of it is out. The
+//! new folders are searched the same way, down to
+//! [`RecursiveOptions::max_depth`] levels.
+//!
+//! Nested archives are where decompression bombs hide, so each is inspected
+//! before anything is written: an archive holding the same bytes as one it
+//! was found in would never end and is left alone, as is one expanding past
+//! the usual compression ratio limit, and all nested archives together are
+//! held to a size and entry limit. Whatever is left alone stays as it was
+//! found and is listed in the report.
+
+use super::detect::detect_format;
+use super::detect::{archive_format, strip_archive_extension};
+use super::paths;
+use super::{extract_with_options, inspect_as, ArchiveEntry, ExtractOptions, OperationOutcome};
+use crate::security::{
+    DEFAULT_MAX_COMPRESSION_RATIO, DEFAULT_MAX_ENTRIES, DEFAULT_MAX_EXTRACTION_SIZE,
+};
+use crate::{Error, Result};
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+use walkdir::WalkDir;
+
+/// Levels of nested archives opened unless told otherwise
+pub const DEFAULT_RECURSION_DEPTH: usize = 3;
+
+/// Uncompressed size below which a nested archive's compression ratio is
+/// not judged; a little repeated text compresses past any bomb threshold
+const RATIO_FLOOR: u64 = 1024 * 1024;
+
+/// Limits on extracting the archives found inside an archive
+#[derive(Debug, Clone)]
+pub struct RecursiveOptions {
+    /// Levels of archives within archives to open, 1 for only those
+    /// directly inside the outer one; 0 opens none
+    pub max_depth: usize,
+    /// Uncompressed bytes all nested archives together may hold
+    pub max_total_size: u64,
+    /// Entries all nested archives together may hold
+    pub max_entries: u64,
+    /// Largest ratio of uncompressed to compressed size a nested archive
+    /// may have
+    pub max_ratio: f64,
+}
+
+impl Default for RecursiveOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_RECURSION_DEPTH,
+            max_total_size: DEFAULT_MAX_EXTRACTION_SIZE,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            max_ratio: DEFAULT_MAX_COMPRESSION_RATIO,
+        }
+    }
+}
+
+/// A nested archive that was extracted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NestedArchive {
+    /// Where the archive was, relative to the output directory
+    pub path: PathBuf,
+    /// Folder it was extracted into, relative to the output directory
+    pub output: PathBuf,
+    /// Format such as `tar.gz` or `zip`
+    pub format: String,
+    /// Levels below the outer archive, 1 for one directly inside it
+    pub depth: usize,
+    /// Entries extracted from it
+    pub extracted: usize,
+    /// Entries that failed; the archive is kept when any did
+    pub failed: usize,
+}
+
+/// Why a nested archive was left as it was found
+#[derive(Debug, Clone, PartialEq)]
+pub enum NestedSkipReason {
+    /// It lies deeper than [`RecursiveOptions::max_depth`]
+    TooDeep,
+    /// It holds the same bytes as `same_as`, an archive it was found in
+    Cycle { same_as: PathBuf },
+    /// It expands more than [`RecursiveOptions::max_ratio`] times
+    Ratio(f64),
+    /// It would take the nested archives past
+    /// [`RecursiveOptions::max_total_size`] or
+    /// [`RecursiveOptions::max_entries`]
+    OverLimit,
+    /// It could not be read or extracted
+    Failed(String),
+}
+
+impl From<Error> for NestedSkipReason {
+    fn from(error: Error) -> Self {
+        NestedSkipReason::Failed(error.to_string())
+    }
+}
+
+impl fmt::Display for NestedSkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NestedSkipReason::TooDeep => write!(f, "nested deeper than the depth limit"),
+            NestedSkipReason::Cycle { same_as } => {
+                write!(f, "same content as {}, which holds it", same_as.display())
+            }
+            NestedSkipReason::Ratio(ratio) => {
+                write!(f, "suspicious compression ratio {:.1}:1", ratio)
+            }
+            NestedSkipReason::OverLimit => {
+                write!(f, "would pass the size or entry limit for nested archives")
+            }
+            NestedSkipReason::Failed(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+/// A nested archive that was left as it was found
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedArchive {
+    /// Where the archive is, relative to the output directory
+    pub path: PathBuf,
+    /// Levels below the outer archive
+    pub depth: usize,
+    pub reason: NestedSkipReason,
+}
+
+/// Outcome of extracting an archive and the archives inside it
+#[derive(Debug, Default)]
+pub struct RecursiveReport {
+    /// Entries of the outer archive
+    pub outcome: OperationOutcome,
+    /// Nested archives extracted, in the order they were found
+    pub extracted: Vec<NestedArchive>,
+    /// Nested archives left as they were
+    pub skipped: Vec<SkippedArchive>,
+}
+
+impl RecursiveReport {
+    /// Entries and nested archives that failed to extract; those left
+    /// alone by a limit do not count
+    pub fn failures(&self) -> usize {
+        let nested: usize = self.extracted.iter().map(|archive| archive.failed).sum();
+        let unreadable = self
+            .skipped
+            .iter()
+            .filter(|skipped| matches!(skipped.reason, NestedSkipReason::Failed(_)))
+            .count();
+        self.outcome.failed.len() + nested + unreadable
+    }
+
+    /// Whether everything extracted that was not left alone by a limit
+    pub fn is_complete(&self) -> bool {
+        self.failures() == 0
+    }
+}
+
+impl fmt::Display for RecursiveReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for archive in &self.extracted {
+            write!(
+                f,
+                "{} ({}) -> {}: {} entries",
+                archive.path.display(),
+                archive.format,
+                archive.output.display(),
+                archive.extracted
+            )?;
+            if archive.failed > 0 {
+                write!(f, ", {} failed", archive.failed)?;
+            }
+            writeln!(f)?;
+        }
+        for skipped in &self.skipped {
+            writeln!(f, "SKIPPED {}: {}", skipped.path.display(), skipped.reason)?;
+        }
+        writeln!(
+            f,
+            "{} nested archives extracted, {} skipped",
+            self.extracted.len(),
+            self.skipped.len()
+        )
+    }
+}
+
+/// Extract `archive` into `output_dir` with `options`, then extract the
+/// archives it holds in place, and those they hold, within `limits`
+///
+/// Only files the outer archive wrote are looked at, so archives already in
+/// the output directory are left alone. A nested archive that fails to
+/// extract is kept and reported; only the outer archive failing is an
+/// error.
+pub fn extract_recursive<P: AsRef<Path>, Q: AsRef<Path>>(
+    archive: P,
+    output_dir: Q,
+    options: ExtractOptions,
+    limits: &RecursiveOptions,
+) -> Result<RecursiveReport> {
+    let archive = archive.as_ref();
+    let output_dir = output_dir.as_ref();
+
+    let entries = inspect_as(archive, options.format.as_deref())?;
+    let outcome = extract_with_options(archive, output_dir, options.clone())?;
+
+    let mut nested = Nested {
+        output_dir,
+        limits,
+        options: ExtractOptions {
+            normalize_names: options.normalize_names,
+            ..ExtractOptions::default()
+        },
+        total_size: 0,
+        total_entries: 0,
+        report: RecursiveReport {
+            outcome,
+            ..RecursiveReport::default()
+        },
+    };
+    let outer = vec![(digest(archive)?, archive.to_path_buf())];
+    let mut queue: VecDeque<Found> = written_archives(&entries, output_dir, &options)
+        .into_iter()
+        .map(|path| Found {
+            path,
+            depth: 1,
+            ancestors: outer.clone(),
+        })
+        .collect();
+    while let Some(found) = queue.pop_front() {
+        nested.open(found, &mut queue);
+    }
+
+    info!(
+        "Extracted {} nested archives from {:?}, skipped {}",
+        nested.report.extracted.len(),
+        archive,
+        nested.report.skipped.len()
+    );
+    Ok(nested.report)
+}
+
+/// A file named as an archive, waiting to be opened
+struct Found {
+    path: PathBuf,
+    depth: usize,
+    /// Digest and report path of every archive it is inside, outermost first
+    ancestors: Vec<(blake3::Hash, PathBuf)>,
+}
+
+/// The state of opening nested archives
+struct Nested<'a> {
+    output_dir: &'a Path,
+    limits: &'a RecursiveOptions,
+    /// Options each nested archive is extracted with
+    options: ExtractOptions,
+    total_size: u64,
+    total_entries: u64,
+    report: RecursiveReport,
+}
+
+impl Nested<'_> {
+    /// Extract `found` next to itself unless a limit stops it, queueing the
+    /// archives it holds
+    fn open(&mut self, found: Found, queue: &mut VecDeque<Found>) {
+        let path = self.relative(&found.path);
+        match self.extract(&found, &path) {
+            Ok((digest, folder)) => {
+                let mut ancestors = found.ancestors;
+                ancestors.push((digest, path));
+                for entry in WalkDir::new(&folder)
+                    .sort_by_file_name()
+                    .into_iter()
+                    .filter_map(|entry| entry.ok())
+                {
+                    if entry.file_type().is_file() && archive_format(entry.path()).is_some() {
+                        queue.push_back(Found {
+                            path: entry.into_path(),
+                            depth: found.depth + 1,
+                            ancestors: ancestors.clone(),
+                        });
+                    }
+                }
+            }
+            Err(reason) => {
+                warn!("Left nested archive {:?} as it is: {}", found.path, reason);
+                self.report.skipped.push(SkippedArchive {
+                    path,
+                    depth: found.depth,
+                    reason,
+                });
+            }
+        }
+    }
+
+    /// Check `found` against the limits and extract it, giving its digest
+    /// and the folder it went into
+    fn extract(
+        &mut self,
+        found: &Found,
+        path: &Path,
+    ) -> std::result::Result<(blake3::Hash, PathBuf), NestedSkipReason> {
+        if found.depth > self.limits.max_depth {
+            return Err(NestedSkipReason::TooDeep);
+        }
+        let digest = digest(&found.path)?;
+        if let Some((_, same_as)) = found.ancestors.iter().find(|(hash, _)| *hash == digest) {
+            return Err(NestedSkipReason::Cycle {
+                same_as: same_as.clone(),
+            });
+        }
+
+        let format = detect_format(&found.path)?;
+        let entries = inspect_as(&found.path, Some(format))?;
+        let size: u64 = entries.iter().map(|entry| entry.size).sum();
+        let compressed = fs::metadata(&found.path).map_err(Error::from)?.len();
+        if size > RATIO_FLOOR && compressed > 0 {
+            let ratio = size as f64 / compressed as f64;
+            if ratio > self.limits.max_ratio {
+                return Err(NestedSkipReason::Ratio(ratio));
+            }
+        }
+        if self.total_size.saturating_add(size) > self.limits.max_total_size
+            || self.total_entries + entries.len() as u64 > self.limits.max_entries
+        {
+            return Err(NestedSkipReason::OverLimit);
+        }
+        self.total_size += size;
+        self.total_entries += entries.len() as u64;
+
+        let folder = target_dir(&found.path);
+        let options = ExtractOptions {
+            format: Some(format.to_string()),
+            ..self.options.clone()
+        };
+        let outcome = match extract_with_options(&found.path, &folder, options) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                let _ = fs::remove_dir_all(&folder);
+                return Err(e.into());
+            }
+        };
+        if outcome.is_complete() {
+            fs::remove_file(&found.path).map_err(Error::from)?;
+        }
+
+        self.report.extracted.push(NestedArchive {
+            path: path.to_path_buf(),
+            output: self.relative(&folder),
+            format: format.to_string(),
+            depth: found.depth,
+            extracted: outcome.done.len(),
+            failed: outcome.failed.len(),
+        });
+        Ok((digest, folder))
+    }
+
+    /// `path` as reported: relative to the output directory
+    fn relative(&self, path: &Path) -> PathBuf {
+        path.strip_prefix(self.output_dir)
+            .unwrap_or(path)
+            .to_path_buf()
+    }
+}
+
+/// Files named as archives that extracting `entries` with `options` wrote
+/// into `output_dir`
+///
+/// A file already there that the extraction skipped is only taken for one
+/// it wrote when it has the same size.
+fn written_archives(
+    entries: &[ArchiveEntry],
+    output_dir: &Path,
+    options: &ExtractOptions,
+) -> Vec<PathBuf> {
+    let strip = options.strip_components.unwrap_or(0);
+    entries
+        .iter()
+        .filter(|entry| !entry.is_dir && !entry.is_symlink && options.accepts(entry))
+        .filter_map(|entry| {
+            let stripped = paths::strip_components(&entry.path, strip).unwrap_or_default();
+            let name = options.normalize_names.apply_path(&stripped);
+            let mut path = output_dir.join(&name);
+            // Hoisting moves everything up out of the single top folder
+            if options.hoist && !path.exists() {
+                path = output_dir.join(name.components().skip(1).collect::<PathBuf>());
+            }
+            let written = fs::symlink_metadata(&path)
+                .is_ok_and(|metadata| metadata.is_file() && metadata.len() == entry.size);
+            (written && archive_format(&path).is_some()).then_some(path)
+        })
+        .collect()
+}
+
+/// Folder to extract `archive` into: next to it and named after it without
+/// its extension, with a number added when that name is taken
+fn target_dir(archive: &Path) -> PathBuf {
+    let parent = archive.parent().unwrap_or(Path::new(""));
+    let name = archive
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned();
+    let stem = strip_archive_extension(&name).unwrap_or(&name);
+
+    let mut target = parent.join(stem);
+    let mut counter = 1;
+    while target.exists() {
+        target = parent.join(format!("{} ({})", stem, counter));
+        counter += 1;
+    }
+    target
+}
+
+fn digest(path: &Path) -> Result<blake3::Hash> {
+    let mut hasher = blake3::Hasher::new();
+    std::io::copy(&mut File::open(path)?, &mut hasher)?;
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_target_dir_drops_extension() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path();
+        assert_eq!(target_dir(&dir.join("libs.tar.gz")), dir.join("libs"));
+        assert_eq!(target_dir(&dir.join("Photos.ZIP")), dir.join("Photos"));
+        assert_eq!(target_dir(&dir.join("v1.2.tgz")), dir.join("v1.2"));
+
+        fs::create_dir(dir.join("libs")).unwrap();
+        assert_eq!(target_dir(&dir.join("libs.zip")), dir.join("libs (1)"));
+    }
+
+    #[test]
+    fn test_archive_holding_itself_is_a_cycle() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = temp_dir.path().join("quine.zip");
+        fs::write(&archive, b"PK\x05\x06").unwrap();
+
+        let limits = RecursiveOptions::default();
+        let mut nested = Nested {
+            output_dir: temp_dir.path(),
+            limits: &limits,
+            options: ExtractOptions::default(),
+            total_size: 0,
+            total_entries: 0,
+            report: RecursiveReport::default(),
+        };
+        let found = Found {
+            path: archive.clone(),
+            depth: 2,
+            ancestors: vec![(digest(&archive).unwrap(), PathBuf::from("outer.zip"))],
+        };
+        nested.open(found, &mut VecDeque::new());
+
+        assert_eq!(
+            nested.report.skipped[0].reason,
+            NestedSkipReason::Cycle {
+                same_as: PathBuf::from("outer.zip")
+            }
+        );
+        assert!(archive.is_file());
+    }
+}
@@ -0,0 +1,84 @@
+//! Unicode normalization of entry names
+//!
+//! The same name can be spelled two ways in Unicode: `é` as one code point
+//! (NFC, what Linux and Windows tools write) or as `e` followed by a
+//! combining accent (NFD, what older macOS file systems store). Both look
+//! alike but compare unequal, so an archive from one system can seem to
+//! lack a file the other has, or to hold it twice.
+
+use crate::{Error, Result};
+use icu_normalizer::{ComposingNormalizerBorrowed, DecomposingNormalizerBorrowed};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// How entry names are normalized when packing or extracting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NameNormalization {
+    /// Names are used as they are
+    #[default]
+    Keep,
+    /// Composed form, as Linux and Windows tools write names
+    Nfc,
+    /// Decomposed form, as macOS HFS+ stores names
+    Nfd,
+}
+
+impl NameNormalization {
+    /// Lower-case name, as accepted by [`FromStr`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            NameNormalization::Keep => "keep",
+            NameNormalization::Nfc => "nfc",
+            NameNormalization::Nfd => "nfd",
+        }
+    }
+
+    /// `name` in this form
+    pub fn apply<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        match self {
+            NameNormalization::Keep => Cow::Borrowed(name),
+            NameNormalization::Nfc => ComposingNormalizerBorrowed::new_nfc().normalize(name),
+            NameNormalization::Nfd => DecomposingNormalizerBorrowed::new_nfd().normalize(name),
+        }
+    }
+
+    /// `path` in this form; a path that is not valid Unicode is left as it is
+    pub fn apply_path<'a>(&self, path: &'a Path) -> Cow<'a, Path> {
+        match path.to_str().map(|name| self.apply(name)) {
+            Some(Cow::Owned(name)) => Cow::Owned(PathBuf::from(name)),
+            _ => Cow::Borrowed(path),
+        }
+    }
+}
+
+impl FromStr for NameNormalization {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "keep" | "none" => Ok(NameNormalization::Keep),
+            "nfc" => Ok(NameNormalization::Nfc),
+            "nfd" => Ok(NameNormalization::Nfd),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unknown name normalization: {} (expected keep, nfc or nfd)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for NameNormalization {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// `path` in the form used to compare names, so that differently
+/// normalized spellings of one name are equal
+pub(crate) fn comparable(path: &Path) -> Cow<'_, Path> {
+    NameNormalization::Nfc.apply_path(path)
+}
@@ -0,0 +1,290 @@
+//! The order entries are packed and extracted in
+//!
+//! Archives are extracted in the order they store their entries unless
+//! asked otherwise. A viewer that shows files as they land, or a caller
+//! waiting on a few particular entries, is better served by writing those
+//! first. That needs entries to be read out of order, which zip archives
+//! and plain tars allow; compressed tars and 7z archives are read in one
+//! pass and always extracted as stored.
+//!
+//! A compressed tar is one stream, so the compressor does better when
+//! files that look alike sit next to each other. [`PackOrder`] can store
+//! the files of each packed directory grouped by extension instead of in
+//! the order the directory walk finds them.
+
+use crate::archive::{ArchiveEntry, ExtractOptions};
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::fmt;
+use std::str::FromStr;
+use walkdir::DirEntry;
+
+/// The order the entries of a packed directory are stored in
+///
+/// The walk order is whatever order the file system lists directories in,
+/// which can differ between machines holding the same tree. The sorted
+/// orders only depend on the entries' paths, so with a fixed
+/// `SOURCE_DATE_EPOCH` they pack the same tree into the same bytes
+/// anywhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PackOrder {
+    /// As the directory walk finds them
+    #[default]
+    Walk,
+    /// Directories first, then files by extension and path
+    Extension,
+    /// Directories first, then files by extension and file name, so files
+    /// of the same name in different folders, often near copies, are
+    /// stored together
+    Similar,
+}
+
+impl PackOrder {
+    /// Name as accepted by [`FromStr`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            PackOrder::Walk => "walk",
+            PackOrder::Extension => "extension",
+            PackOrder::Similar => "similar",
+        }
+    }
+}
+
+impl FromStr for PackOrder {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "walk" => Ok(PackOrder::Walk),
+            "extension" | "ext" => Ok(PackOrder::Extension),
+            "similar" => Ok(PackOrder::Similar),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unknown packing order: {} (expected walk, extension or similar)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for PackOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Sort the walked entries of a directory into `order`
+///
+/// Directories come first, by path, so each is stored before what it
+/// holds; files and links follow, sorted by the key of `order` and then
+/// by path.
+pub(crate) fn sort_for_packing(entries: &mut [DirEntry], order: PackOrder) {
+    if order == PackOrder::Walk {
+        return;
+    }
+    entries.sort_by_cached_key(|entry| {
+        let path = entry.path();
+        if entry.file_type().is_dir() {
+            return (false, String::new(), String::new(), path.to_path_buf());
+        }
+        let name = match order {
+            PackOrder::Similar => lossy(path.file_name()),
+            PackOrder::Walk | PackOrder::Extension => String::new(),
+        };
+        (true, lossy(path.extension()), name, path.to_path_buf())
+    });
+}
+
+fn lossy(part: Option<&OsStr>) -> String {
+    part.map(|part| part.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// The order entries without priority are extracted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExtractOrder {
+    /// As stored in the archive
+    #[default]
+    Archive,
+    /// Directories first, then files from the smallest up
+    SmallestFirst,
+}
+
+impl ExtractOrder {
+    /// Name as accepted by [`FromStr`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExtractOrder::Archive => "archive",
+            ExtractOrder::SmallestFirst => "smallest-first",
+        }
+    }
+}
+
+impl FromStr for ExtractOrder {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "archive" => Ok(ExtractOrder::Archive),
+            "smallest-first" | "smallest" => Ok(ExtractOrder::SmallestFirst),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unknown extraction order: {} (expected archive or smallest-first)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ExtractOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The order to extract `entries` in as `options` ask, as indices into
+/// `entries`
+///
+/// Entries under a path of `options.priority` come first, by the position
+/// of that path; the rest follow in `options.order`. Entries that tie keep
+/// their archive order.
+pub(crate) fn extraction_order(entries: &[ArchiveEntry], options: &ExtractOptions) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..entries.len()).collect();
+    order.sort_by_cached_key(|&i| {
+        let entry = &entries[i];
+        let priority = options
+            .priority
+            .iter()
+            .position(|first| entry.path.starts_with(first))
+            .unwrap_or(options.priority.len());
+        let size = match options.order {
+            ExtractOrder::Archive => None,
+            ExtractOrder::SmallestFirst => Some((!entry.is_dir, entry.size)),
+        };
+        (priority, size)
+    });
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+    use walkdir::WalkDir;
+
+    fn entry(path: &str, size: u64, is_dir: bool) -> ArchiveEntry {
+        ArchiveEntry {
+            path: PathBuf::from(path),
+            size,
+            compressed_size: None,
+            mode: None,
+            mtime: None,
+            is_dir,
+            is_symlink: false,
+            link_target: None,
+            extra_fields: Vec::new(),
+        }
+    }
+
+    fn entries() -> Vec<ArchiveEntry> {
+        vec![
+            entry("big.bin", 900, false),
+            entry("docs", 0, true),
+            entry("docs/guide.md", 40, false),
+            entry("small.txt", 5, false),
+            entry("docs/index.md", 5, false),
+        ]
+    }
+
+    #[test]
+    fn test_archive_order_is_kept() {
+        let order = extraction_order(&entries(), &ExtractOptions::default());
+        assert_eq!(order, vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_smallest_first() {
+        let options = ExtractOptions {
+            order: ExtractOrder::SmallestFirst,
+            ..ExtractOptions::default()
+        };
+        assert_eq!(extraction_order(&entries(), &options), vec![1, 3, 4, 2, 0]);
+    }
+
+    #[test]
+    fn test_priority_comes_first() {
+        let options = ExtractOptions {
+            order: ExtractOrder::SmallestFirst,
+            priority: vec![PathBuf::from("big.bin"), PathBuf::from("docs")],
+            ..ExtractOptions::default()
+        };
+        assert_eq!(extraction_order(&entries(), &options), vec![0, 1, 4, 2, 3]);
+    }
+
+    fn packing_order(order: PackOrder) -> Vec<String> {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("b/src")).unwrap();
+        fs::create_dir_all(root.join("a")).unwrap();
+        for file in [
+            "b/src/mod.rs",
+            "b/Notes.TXT",
+            "a/mod.rs",
+            "a/z.txt",
+            "README",
+        ] {
+            fs::write(root.join(file), file).unwrap();
+        }
+
+        let mut entries: Vec<DirEntry> = WalkDir::new(root)
+            .min_depth(1)
+            .into_iter()
+            .map(|entry| entry.unwrap())
+            .collect();
+        sort_for_packing(&mut entries, order);
+        entries
+            .iter()
+            .map(|entry| {
+                let path = entry.path().strip_prefix(root).unwrap();
+                path.to_string_lossy().replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_extension_order() {
+        assert_eq!(
+            packing_order(PackOrder::Extension),
+            [
+                "a",
+                "b",
+                "b/src",
+                "README",
+                "a/mod.rs",
+                "b/src/mod.rs",
+                "a/z.txt",
+                "b/Notes.TXT"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_similar_order() {
+        assert_eq!(
+            packing_order(PackOrder::Similar),
+            [
+                "a",
+                "b",
+                "b/src",
+                "README",
+                "a/mod.rs",
+                "b/src/mod.rs",
+                "b/Notes.TXT",
+                "a/z.txt"
+            ]
+        );
+    }
+}
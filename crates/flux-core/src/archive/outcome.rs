@@ -0,0 +1,252 @@
+//! What happened to each entry of a pack or extract
+//!
+//! An entry that cannot be read or written is recorded and the operation
+//! goes on with the next one, so a single unreadable file does not cost the
+//! rest of the archive. Whether that makes the whole operation a failure is
+//! up to the caller; [`OperationOutcome::into_result`] gives the old answer.
+//! [`OperationOutcome::report`] sums an outcome up into an
+//! [`OperationReport`], small enough to keep once the operation is over.
+
+use crate::archive::{CaseCollision, Tampered};
+use crate::error::ErrorReport;
+use crate::{Error, Result};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Why an entry was left out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// The destination exists and the conflict mode keeps it
+    Exists,
+    /// Matched an exclude pattern, in which case for a directory so did
+    /// everything in it, or was rejected by an extraction entry filter
+    Excluded,
+    /// The path would end up outside the output directory
+    UnsafePath,
+    /// `strip_components` left nothing of the path
+    Stripped,
+    /// Not a regular file, directory or symlink, such as a socket, or a
+    /// symlink the format cannot store
+    Unsupported,
+    /// A directory already packed under another path, reached again
+    /// through a followed symlink
+    Revisited,
+    /// A directory nested deeper than the depth limit
+    TooDeep,
+    /// Replaced by a later file whose name differs only in letter case
+    CaseCollision,
+    /// Extracted by an earlier run that was interrupted, as its resume
+    /// journal records
+    Resumed,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SkipReason::Exists => "already exists",
+            SkipReason::Excluded => "excluded",
+            SkipReason::UnsafePath => "unsafe path",
+            SkipReason::Stripped => "stripped away",
+            SkipReason::Unsupported => "unsupported file type",
+            SkipReason::Revisited => "already packed under another path",
+            SkipReason::TooDeep => "nested too deeply",
+            SkipReason::CaseCollision => "replaced by a name differing only in case",
+            SkipReason::Resumed => "extracted by an earlier run",
+        })
+    }
+}
+
+/// An entry that was left out on purpose
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Skipped {
+    pub path: PathBuf,
+    pub reason: SkipReason,
+}
+
+/// An entry that could not be packed or extracted
+#[derive(Debug)]
+pub struct EntryFailure {
+    pub path: PathBuf,
+    pub error: Error,
+}
+
+impl Serialize for EntryFailure {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("EntryFailure", 2)?;
+        state.serialize_field("path", &self.path)?;
+        state.serialize_field::<ErrorReport>("error", &self.error.report())?;
+        state.end()
+    }
+}
+
+/// Every entry of a pack or extract, sorted by what happened to it
+///
+/// Paths are entry names within the archive.
+#[derive(Debug, Default, Serialize)]
+pub struct OperationOutcome {
+    /// Entries packed or extracted
+    pub done: Vec<PathBuf>,
+    pub skipped: Vec<Skipped>,
+    pub failed: Vec<EntryFailure>,
+    /// Extracted files whose names differ from earlier ones only in case,
+    /// found when the output directory folds case
+    pub case_collisions: Vec<CaseCollision>,
+    /// Extracted files that did not match the manifest they were checked
+    /// against; under [`TamperPolicy::Fail`](crate::archive::TamperPolicy)
+    /// they are also failed
+    pub tampered: Vec<Tampered>,
+}
+
+impl OperationOutcome {
+    /// Record an entry as packed or extracted
+    pub fn done(&mut self, path: impl Into<PathBuf>) {
+        self.done.push(path.into());
+    }
+
+    /// Record an entry as left out for `reason`
+    pub fn skip(&mut self, path: impl Into<PathBuf>, reason: SkipReason) {
+        self.skipped.push(Skipped {
+            path: path.into(),
+            reason,
+        });
+    }
+
+    /// Record an entry as failed, logging the error
+    pub fn fail(&mut self, path: impl Into<PathBuf>, error: Error) {
+        let path = path.into();
+        tracing::warn!("Failed on {:?}: {}", path, error);
+        self.failed.push(EntryFailure {
+            error: error.with_entry(&path),
+            path,
+        });
+    }
+
+    /// Record `path` as the file each failure concerns, unless one is
+    /// already known
+    pub(crate) fn with_path(mut self, path: &Path) -> Self {
+        self.failed = self
+            .failed
+            .into_iter()
+            .map(|failure| EntryFailure {
+                error: failure.error.with_path(path),
+                path: failure.path,
+            })
+            .collect();
+        self
+    }
+
+    /// Entries left out because their type cannot be stored or written,
+    /// such as sockets and devices
+    pub fn unsupported(&self) -> impl Iterator<Item = &Path> {
+        self.skipped
+            .iter()
+            .filter(|skipped| skipped.reason == SkipReason::Unsupported)
+            .map(|skipped| skipped.path.as_path())
+    }
+
+    /// Whether no entry failed
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Counts of this outcome, with the failures
+    pub fn report(&self) -> OperationReport {
+        let mut skipped_by_reason: Vec<(SkipReason, usize)> = Vec::new();
+        for skipped in &self.skipped {
+            match skipped_by_reason
+                .iter_mut()
+                .find(|(reason, _)| *reason == skipped.reason)
+            {
+                Some((_, count)) => *count += 1,
+                None => skipped_by_reason.push((skipped.reason, 1)),
+            }
+        }
+        OperationReport {
+            done: self.done.len(),
+            skipped: self.skipped.len(),
+            skipped_by_reason,
+            failed: self
+                .failed
+                .iter()
+                .map(|failure| failure.error.report())
+                .collect(),
+            case_collisions: self.case_collisions.len(),
+            tampered: self.tampered.len(),
+        }
+    }
+
+    /// `Ok` if no entry failed, otherwise the error of the first one that
+    /// did, as if the operation had stopped there
+    pub fn into_result(self) -> Result<()> {
+        match self.failed.into_iter().next() {
+            Some(failure) => Err(failure.error),
+            None => Ok(()),
+        }
+    }
+}
+
+/// What an operation did, counted rather than listed entry by entry
+///
+/// Only failures are kept in full, as the entries someone reading the
+/// report would want to look at.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct OperationReport {
+    pub done: usize,
+    pub skipped: usize,
+    /// Skipped entries by reason, in the order the reasons first came up
+    pub skipped_by_reason: Vec<(SkipReason, usize)>,
+    pub failed: Vec<ErrorReport>,
+    pub case_collisions: usize,
+    pub tampered: usize,
+}
+
+impl OperationReport {
+    /// Whether no entry failed
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+impl fmt::Display for OperationReport {
+    /// The same one-line count as the outcome it was made from
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} done, {} skipped, {} failed",
+            self.done,
+            self.skipped,
+            self.failed.len()
+        )?;
+        if self.case_collisions > 0 {
+            write!(f, ", {} case collisions", self.case_collisions)?;
+        }
+        if self.tampered > 0 {
+            write!(f, ", {} tampered", self.tampered)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for OperationOutcome {
+    /// A one-line count, such as `12 done, 2 skipped, 1 failed`, followed
+    /// by the numbers of case collisions and tampered files if there were any
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} done, {} skipped, {} failed",
+            self.done.len(),
+            self.skipped.len(),
+            self.failed.len()
+        )?;
+        if !self.case_collisions.is_empty() {
+            write!(f, ", {} case collisions", self.case_collisions.len())?;
+        }
+        if !self.tampered.is_empty() {
+            write!(f, ", {} tampered", self.tampered.len())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+//! Entry names and how they are worked out, shared by every writer and
+//! extractor
+//!
+//! Names inside an archive are relative and `/`-separated whatever the
+//! platform. Packing takes them from the inputs' paths below a base
+//! directory, optionally moved by [`rebase`] as `--strip-prefix` and
+//! `--prefix` ask; extracting takes leading folders off with
+//! [`strip_components`] and refuses names [`is_enclosed_path`] rejects.
+//! Names are compared component by component, so `docs` is not a prefix of
+//! `docs2/a.txt` and `./docs/` is the same as `docs`.
+
+use crate::{Error, Result};
+use std::path::{Component, Path, PathBuf};
+
+/// Whether an entry path stays inside the output directory once joined to it
+///
+/// Mirrors `ZipFile::enclosed_name`: absolute paths and `..` components are
+/// rejected rather than normalized.
+pub fn is_enclosed_path(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+/// `path` as it is named inside an archive: `/`-separated, without a
+/// leading `./` or a trailing `/`
+pub fn entry_key(path: &Path) -> String {
+    let key = path.to_string_lossy().replace('\\', "/");
+    let mut key = key.as_str();
+    while let Some(rest) = key.strip_prefix("./") {
+        key = rest;
+    }
+    key.trim_end_matches('/').to_string()
+}
+
+/// `path` without its first `count` components, or `None` when that
+/// leaves nothing
+pub fn strip_components(path: &Path, count: usize) -> Option<PathBuf> {
+    let stripped: PathBuf = path.components().skip(count).collect();
+    (!stripped.as_os_str().is_empty()).then_some(stripped)
+}
+
+/// The entry name `name` with `strip_prefix` taken off the front and
+/// `prefix` put in its place
+///
+/// A name not below `strip_prefix` is only given `prefix`. `None` is the
+/// name `strip_prefix` itself with no `prefix` to stand for it, which is
+/// left with no name at all.
+pub fn rebase(name: &Path, strip_prefix: Option<&Path>, prefix: Option<&Path>) -> Option<PathBuf> {
+    let name = relative(name);
+    let rest = strip_prefix
+        .and_then(|strip| name.strip_prefix(relative(strip)).ok())
+        .unwrap_or(name);
+    match prefix.map(relative) {
+        Some(prefix) => Some(prefix.join(rest)),
+        None if rest.as_os_str().is_empty() => None,
+        None => Some(rest.to_path_buf()),
+    }
+}
+
+/// Refuse a folder given to [`rebase`] that is empty or could not be part
+/// of a name extracted inside the output directory
+pub fn check_prefix(prefix: &Path) -> Result<()> {
+    if relative(prefix).as_os_str().is_empty() || !is_enclosed_path(prefix) {
+        return Err(Error::InvalidPath(format!(
+            "{:?} is not a relative folder inside the archive",
+            prefix
+        )));
+    }
+    Ok(())
+}
+
+/// The deepest directory holding all of `paths`, or `None` when they have
+/// none in common, such as paths on different Windows drives
+pub fn common_base<P: AsRef<Path>>(paths: &[P]) -> Option<PathBuf> {
+    let mut parents = paths.iter().map(|path| path.as_ref().parent());
+    let mut base = parents.next()??.to_path_buf();
+    for parent in parents {
+        let parent = parent?;
+        while !parent.starts_with(&base) {
+            if !base.pop() {
+                return None;
+            }
+        }
+    }
+    Some(base)
+}
+
+/// `path` without `.` components or a trailing separator
+pub(crate) fn relative(path: &Path) -> &Path {
+    let mut components = path.components();
+    while components.as_path().starts_with(".") {
+        components.next();
+    }
+    components.as_path()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_common_base() {
+        assert_eq!(
+            common_base(&["/data/a/one.txt", "/data/a/two.txt"]),
+            Some(PathBuf::from("/data/a"))
+        );
+        assert_eq!(
+            common_base(&["/data/a/one.txt", "/data/b/deep/two.txt"]),
+            Some(PathBuf::from("/data"))
+        );
+        assert_eq!(
+            common_base(&["/data/a", "/srv/b"]),
+            Some(PathBuf::from("/"))
+        );
+        assert_eq!(common_base::<&str>(&[]), None);
+    }
+
+    #[test]
+    fn test_entry_key() {
+        assert_eq!(entry_key(Path::new("./docs/a.txt")), "docs/a.txt");
+        assert_eq!(entry_key(Path::new("docs/")), "docs");
+        assert_eq!(entry_key(Path::new("docs\\sub\\a.txt")), "docs/sub/a.txt");
+    }
+
+    #[test]
+    fn test_strip_components() {
+        assert_eq!(
+            strip_components(Path::new("project/src/lib.rs"), 1),
+            Some(PathBuf::from("src/lib.rs"))
+        );
+        assert_eq!(strip_components(Path::new("project"), 1), None);
+        assert_eq!(strip_components(Path::new("project/src"), 3), None);
+    }
+}
@@ -0,0 +1,58 @@
+//! Permissions given to extracted files
+//!
+//! Extraction gives each file the mode its archive stored, as `tar
+//! --same-permissions` does. With [`ExtractOptions::apply_umask`], as with
+//! `tar --no-same-permissions`, the stored mode is not trusted: files get
+//! read and write for everyone, directories and files stored as executable
+//! also execute, and the process umask takes away from that as it does for
+//! any new file. Setuid, setgid and sticky bits from the archive never
+//! reach the disk then, which matters on servers many users share.
+//!
+//! [`ExtractOptions::apply_umask`]: super::ExtractOptions::apply_umask
+
+#[cfg(unix)]
+use std::sync::OnceLock;
+
+/// Mode to give an extracted file, or directory with `is_dir`, for which
+/// the archive stored `stored`
+#[cfg(unix)]
+pub(crate) fn extracted_mode(stored: u32, is_dir: bool, apply_umask: bool) -> u32 {
+    if !apply_umask {
+        return stored;
+    }
+    let base = if is_dir || stored & 0o111 != 0 {
+        0o777
+    } else {
+        0o666
+    };
+    base & !umask()
+}
+
+/// The umask of this process, as it was when first asked for
+#[cfg(unix)]
+#[allow(clippy::useless_conversion)] // mode_t is narrower on macOS
+pub fn umask() -> u32 {
+    static UMASK: OnceLock<u32> = OnceLock::new();
+    *UMASK.get_or_init(|| {
+        // Linux tells it without changing it
+        let reported = std::fs::read_to_string("/proc/self/status")
+            .ok()
+            .and_then(|status| {
+                status
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Umask:"))
+                    .and_then(|mask| u32::from_str_radix(mask.trim(), 8).ok())
+            });
+        if let Some(mask) = reported {
+            return mask;
+        }
+        // Elsewhere it can only be read by setting it, so it is put back
+        // straight away
+        // SAFETY: umask cannot fail and only touches the process's mask
+        unsafe {
+            let mask = libc::umask(0o022);
+            libc::umask(mask);
+            u32::from(mask)
+        }
+    })
+}
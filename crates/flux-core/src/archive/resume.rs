@@ -0,0 +1,435 @@
+//! Picking up an interrupted extraction where it stopped
+//!
+//! With [`ExtractOptions::resume`], an extraction keeps a journal,
+//! [`RESUME_FILE`], in the output directory. It names the archive being
+//! read, lists the files finished so far and, while a large file is being
+//! written, how many of its bytes are on disk along with their hash. Run
+//! again over the same archive and directory after being interrupted, the
+//! extraction skips the finished files and carries on the large file from
+//! the last offset whose bytes still hash the same. The journal is the same
+//! for tar, zip and 7z archives and is written alike by the CLI and the
+//! GUI, so either can resume what the other started; it is removed once an
+//! extraction finishes without a failed entry.
+//!
+//! [`ExtractOptions::resume`]: crate::archive::ExtractOptions::resume
+
+use crate::buffer::BufferPool;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
+
+/// File in the output directory the journal is kept in
+pub const RESUME_FILE: &str = ".flux-resume.json";
+
+/// Version of the journal format; journals of another version are ignored
+pub const RESUME_VERSION: u32 = 1;
+
+/// Bytes written to a file between two checkpoints of its offset
+pub const CHECKPOINT_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Bytes at each end of the archive hashed to recognise it
+const IDENTITY_BLOCK: u64 = 64 * 1024;
+
+/// Least time between two saves of the journal as files are finished
+const SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Which archive a journal belongs to
+///
+/// Only the size and content are compared, not the path or modification
+/// time, so an archive downloaded again to another temporary file is still
+/// recognised.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveIdentity {
+    pub size: u64,
+    /// Blake3 of the first and last 64 KiB
+    pub hash: String,
+}
+
+impl ArchiveIdentity {
+    /// The identity of the archive at `path`
+    pub fn of(path: &Path) -> Result<Self> {
+        let mut file = File::open(path).map_err(|e| Error::from(e).with_path(path))?;
+        let size = file.metadata()?.len();
+
+        let mut hasher = blake3::Hasher::new();
+        let mut block = Vec::with_capacity(IDENTITY_BLOCK as usize);
+        (&mut file).take(IDENTITY_BLOCK).read_to_end(&mut block)?;
+        hasher.update(&block);
+        if size > IDENTITY_BLOCK {
+            block.clear();
+            file.seek(SeekFrom::Start(
+                size.saturating_sub(IDENTITY_BLOCK).max(IDENTITY_BLOCK),
+            ))?;
+            file.take(IDENTITY_BLOCK).read_to_end(&mut block)?;
+            hasher.update(&block);
+        }
+
+        Ok(Self {
+            size,
+            hash: hasher.finalize().to_hex().to_string(),
+        })
+    }
+}
+
+/// A file cut off while being written
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PartialFile {
+    /// Path relative to the output directory
+    pub path: PathBuf,
+    /// Bytes known to be on disk
+    pub offset: u64,
+    /// Blake3 of those bytes
+    pub hash: String,
+}
+
+/// What an extraction has done so far, with paths relative to the output
+/// directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResumeJournal {
+    pub version: u32,
+    pub archive: ArchiveIdentity,
+    /// Milliseconds since the Unix epoch when the first run started
+    pub started: u64,
+    /// Files and links finished, with the size each was written at
+    pub completed: BTreeMap<PathBuf, u64>,
+    /// The file being written when the journal was last saved, if one was
+    /// large enough to be checkpointed
+    pub partial: Option<PartialFile>,
+}
+
+impl ResumeJournal {
+    fn new(archive: ArchiveIdentity) -> Self {
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        Self {
+            version: RESUME_VERSION,
+            archive,
+            started,
+            completed: BTreeMap::new(),
+            partial: None,
+        }
+    }
+}
+
+/// The journal kept in `output_dir`, if there is one this version can read
+pub fn read_resume_journal<P: AsRef<Path>>(output_dir: P) -> Result<Option<ResumeJournal>> {
+    let path = output_dir.as_ref().join(RESUME_FILE);
+    let json = match fs::read(&path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(Error::from(e).with_path(&path)),
+    };
+    match serde_json::from_slice::<ResumeJournal>(&json) {
+        Ok(journal) if journal.version == RESUME_VERSION => Ok(Some(journal)),
+        Ok(journal) => {
+            debug!(
+                "Ignoring {:?}, written in version {} of the format",
+                path, journal.version
+            );
+            Ok(None)
+        }
+        Err(e) => {
+            warn!("Ignoring unreadable resume journal {:?}: {}", path, e);
+            Ok(None)
+        }
+    }
+}
+
+/// The journal an interrupted extraction of `archive` left in
+/// `output_dir`, which extracting it again with
+/// [`ExtractOptions::resume`](crate::archive::ExtractOptions::resume)
+/// would pick up
+pub fn resumable<P: AsRef<Path>, Q: AsRef<Path>>(
+    archive: P,
+    output_dir: Q,
+) -> Result<Option<ResumeJournal>> {
+    let Some(journal) = read_resume_journal(output_dir)? else {
+        return Ok(None);
+    };
+    let identity = ArchiveIdentity::of(archive.as_ref())?;
+    Ok((journal.archive == identity).then_some(journal))
+}
+
+/// What is on disk already for a destination
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Prior {
+    /// Nothing this extraction knows about; the conflict mode decides
+    Unknown,
+    /// Finished by an earlier run
+    Done,
+    /// Written by an earlier run that was interrupted before finishing
+    /// it; overwritten, or carried on, whatever the conflict mode
+    Leftover,
+}
+
+/// The journal of an extraction in progress
+///
+/// The journal is saved when a new one is started, at most every
+/// [`SAVE_INTERVAL`] as files are finished, at each checkpoint of a large
+/// file, and when dropped after the extraction failed part way.
+pub(crate) struct Resume {
+    output_dir: PathBuf,
+    journal: ResumeJournal,
+    /// Whether the journal was picked up from an earlier run
+    resumed: bool,
+    last_saved: Instant,
+    finished: bool,
+}
+
+impl Resume {
+    /// Pick up the journal of an earlier extraction of `archive` into
+    /// `output_dir`, or start one
+    pub(crate) fn open(archive: &Path, output_dir: &Path) -> Result<Self> {
+        let identity = ArchiveIdentity::of(archive)?;
+        let (journal, resumed) = match read_resume_journal(output_dir)? {
+            Some(journal) if journal.archive == identity => {
+                info!(
+                    "Resuming the extraction into {:?}: {} files already extracted",
+                    output_dir,
+                    journal.completed.len()
+                );
+                (journal, true)
+            }
+            Some(_) => {
+                warn!(
+                    "Replacing the resume journal in {:?}, which is for another archive",
+                    output_dir
+                );
+                (ResumeJournal::new(identity), false)
+            }
+            None => (ResumeJournal::new(identity), false),
+        };
+        let mut resume = Self {
+            output_dir: output_dir.to_path_buf(),
+            journal,
+            resumed,
+            last_saved: Instant::now(),
+            finished: false,
+        };
+        // Saved before anything is written, so a run killed before its
+        // first save still leaves the start time its torn files are told by
+        if !resumed {
+            fs::create_dir_all(output_dir)?;
+            resume.save()?;
+            // Taken from the file system's clock, which those files' times
+            // come from and which can lag the system's by a few milliseconds
+            let saved = fs::metadata(output_dir.join(RESUME_FILE))?;
+            if let Some(started) = modified_millis(&saved) {
+                if started < resume.journal.started {
+                    resume.journal.started = started;
+                    resume.save()?;
+                }
+            }
+        }
+        Ok(resume)
+    }
+
+    fn relative<'p>(&self, dest: &'p Path) -> Option<&'p Path> {
+        dest.strip_prefix(&self.output_dir)
+            .ok()
+            .filter(|relative| !relative.as_os_str().is_empty())
+    }
+
+    /// What an earlier run left at `dest`
+    pub(crate) fn prior(&self, dest: &Path) -> Prior {
+        let Some(relative) = self.relative(dest) else {
+            return Prior::Unknown;
+        };
+        let Ok(metadata) = dest.symlink_metadata() else {
+            return Prior::Unknown;
+        };
+        if let Some(&size) = self.journal.completed.get(relative) {
+            if metadata.is_symlink() || metadata.len() == size {
+                return Prior::Done;
+            }
+        }
+        // Written since the first run started, yet never finished
+        let modified = modified_millis(&metadata);
+        if self.resumed && !metadata.is_dir() && modified >= Some(self.journal.started) {
+            return Prior::Leftover;
+        }
+        Prior::Unknown
+    }
+
+    /// Write the content `reader` yields to the file `dest`, carrying on
+    /// from the checkpoint an earlier run left for it when its bytes on
+    /// disk still match
+    ///
+    /// The bytes already on disk are still read from `reader`, to get past
+    /// them, but not written again.
+    pub(crate) fn write<R: Read + ?Sized>(&mut self, reader: &mut R, dest: &Path) -> Result<()> {
+        let relative = self.relative(dest).map(Path::to_path_buf);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut hasher = blake3::Hasher::new();
+        let partial = match &self.journal.partial {
+            Some(partial) if Some(&partial.path) == relative.as_ref() => {
+                self.journal.partial.take()
+            }
+            _ => None,
+        };
+        let mut written = match partial {
+            Some(partial) if matches_prefix(dest, &partial, &mut hasher)? => {
+                let skipped = io::copy(&mut reader.take(partial.offset), &mut io::sink())?;
+                if skipped != partial.offset {
+                    return Err(Error::ArchiveError(format!(
+                        "{:?} ended before the {} bytes extracted earlier",
+                        dest, partial.offset
+                    )));
+                }
+                info!("Carrying on {:?} from byte {}", dest, partial.offset);
+                partial.offset
+            }
+            _ => {
+                hasher = blake3::Hasher::new();
+                0
+            }
+        };
+
+        let mut file = if written > 0 {
+            let mut file = OpenOptions::new().write(true).open(dest)?;
+            file.set_len(written)?;
+            file.seek(SeekFrom::End(0))?;
+            file
+        } else {
+            // Replace what is there rather than write through a symlink or
+            // into a file hard linked elsewhere
+            if dest.symlink_metadata().is_ok_and(|meta| !meta.is_dir()) {
+                fs::remove_file(dest)?;
+            }
+            File::create(dest)?
+        };
+
+        let mut buffer = BufferPool::global().get();
+        let mut checkpoint = written;
+        loop {
+            let n = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            file.write_all(&buffer[..n])?;
+            hasher.update(&buffer[..n]);
+            written += n as u64;
+
+            if let Some(path) = relative.as_ref() {
+                if written - checkpoint >= CHECKPOINT_BYTES {
+                    file.sync_data()?;
+                    self.journal.partial = Some(PartialFile {
+                        path: path.clone(),
+                        offset: written,
+                        hash: hasher.finalize().to_hex().to_string(),
+                    });
+                    self.save()?;
+                    checkpoint = written;
+                }
+            }
+        }
+        if self.journal.partial.as_ref().map(|partial| &partial.path) == relative.as_ref() {
+            self.journal.partial = None;
+        }
+        Ok(())
+    }
+
+    /// Note the file or link at `dest` as finished
+    pub(crate) fn done(&mut self, dest: &Path) {
+        let Some(relative) = self.relative(dest).map(Path::to_path_buf) else {
+            return;
+        };
+        let Ok(metadata) = dest.symlink_metadata() else {
+            return;
+        };
+        if metadata.is_dir() {
+            return;
+        }
+        self.journal.completed.insert(relative, metadata.len());
+        if self.last_saved.elapsed() >= SAVE_INTERVAL {
+            if let Err(e) = self.save() {
+                warn!("Could not save the resume journal: {}", e);
+            }
+        }
+    }
+
+    /// Remove the journal once nothing failed, or else save it for the
+    /// next run
+    pub(crate) fn finish(mut self, failed: bool) -> Result<()> {
+        self.finished = true;
+        if failed {
+            return self.save();
+        }
+        match fs::remove_file(self.output_dir.join(RESUME_FILE)) {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Write the journal, replacing the one on disk in one step
+    fn save(&mut self) -> Result<()> {
+        let json = serde_json::to_vec_pretty(&self.journal)
+            .map_err(|e| Error::Other(format!("Failed to write resume journal: {}", e)))?;
+        let path = self.output_dir.join(RESUME_FILE);
+        let temporary = path.with_extension("json.tmp");
+        fs::write(&temporary, json)?;
+        fs::rename(&temporary, &path)?;
+        self.last_saved = Instant::now();
+        Ok(())
+    }
+}
+
+impl Drop for Resume {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        if let Err(e) = self.save() {
+            warn!(
+                "Could not save the resume journal in {:?}: {}",
+                self.output_dir, e
+            );
+        }
+    }
+}
+
+/// Modification time of a file in milliseconds since the Unix epoch
+fn modified_millis(metadata: &fs::Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|elapsed| elapsed.as_millis() as u64)
+}
+
+/// Whether the first `partial.offset` bytes of `dest` hash as recorded,
+/// leaving them hashed in `hasher`
+fn matches_prefix(dest: &Path, partial: &PartialFile, hasher: &mut blake3::Hasher) -> Result<bool> {
+    let Ok(file) = File::open(dest) else {
+        return Ok(false);
+    };
+    if !file.metadata()?.is_file() || file.metadata()?.len() < partial.offset {
+        return Ok(false);
+    }
+    let read = io::copy(&mut file.take(partial.offset), hasher)?;
+    Ok(read == partial.offset && hasher.finalize().to_hex().as_str() == partial.hash)
+}
+
+/// [`Resume::prior`], or [`Prior::Unknown`] when no journal is kept
+pub(crate) fn prior(resume: Option<&Resume>, dest: &Path) -> Prior {
+    resume.map_or(Prior::Unknown, |resume| resume.prior(dest))
+}
+
+/// [`Resume::done`], when a journal is kept
+pub(crate) fn done(resume: Option<&mut Resume>, dest: &Path) {
+    if let Some(resume) = resume {
+        resume.done(dest);
+    }
+}
@@ -0,0 +1,139 @@
+//! Inputs of a multi-root pack and their names in the archive
+//!
+//! Each input is packed straight from where it is, under the name its
+//! [`PackRoot`] gives it: a file is stored as that name, a directory with
+//! its contents below that name. Names can be chosen one by one, or taken
+//! relative to the deepest directory the inputs share, which keeps inputs
+//! from different folders apart.
+
+use crate::archive::paths::{common_base, is_enclosed_path};
+use crate::{Error, Result};
+use std::path::{Path, PathBuf};
+
+/// One input of a pack and the name it gets in the archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackRoot {
+    /// File or directory to pack
+    pub path: PathBuf,
+    /// Name of the file, or folder of the directory's contents, in the
+    /// archive; empty to put a directory's contents at the top
+    pub name: PathBuf,
+    /// Whether a directory is packed with everything in it, rather than
+    /// as an entry of its own, as a [file list](crate::archive::file_list)
+    /// names it
+    pub recursive: bool,
+}
+
+impl PackRoot {
+    /// Pack `path` as `name`
+    pub fn new(path: impl Into<PathBuf>, name: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            name: name.into(),
+            recursive: true,
+        }
+    }
+
+    /// Pack `path` under its own name, the way [`pack_multiple`] names its
+    /// inputs: a file relative to `base_dir` when it is below it, and
+    /// otherwise, as for every directory, by its file name
+    ///
+    /// [`pack_multiple`]: crate::archive::pack_multiple
+    pub fn named_in(path: impl Into<PathBuf>, base_dir: Option<&Path>) -> Self {
+        let path = path.into();
+        let relative = base_dir
+            .filter(|_| !path.is_dir())
+            .and_then(|base| path.strip_prefix(base).ok());
+        let name = match relative {
+            Some(relative) => relative.to_path_buf(),
+            None => path
+                .strip_prefix(path.parent().unwrap_or(Path::new("")))
+                .unwrap_or(&path)
+                .to_path_buf(),
+        };
+        Self::new(path, name)
+    }
+
+    /// Pack each of `paths` under its path relative to [`common_base`], so
+    /// that inputs keep the folders that tell them apart
+    pub fn below_common_base<P: AsRef<Path>>(paths: &[P]) -> Vec<Self> {
+        let base = common_base(paths);
+        paths
+            .iter()
+            .map(|path| {
+                let path = path.as_ref();
+                match base
+                    .as_deref()
+                    .and_then(|base| path.strip_prefix(base).ok())
+                {
+                    Some(name) => Self::new(path, name),
+                    None => Self::named_in(path, None),
+                }
+            })
+            .collect()
+    }
+
+    /// The paths of `roots` whose files are read, leaving out directories
+    /// packed as entries of their own
+    pub(crate) fn read_paths(roots: &[Self]) -> Vec<&Path> {
+        roots
+            .iter()
+            .filter(|root| root.recursive || !root.path.is_dir())
+            .map(|root| root.path.as_path())
+            .collect()
+    }
+
+    /// Refuse a name that would be extracted outside the output directory,
+    /// and an empty name for a file
+    pub(crate) fn check(&self) -> Result<()> {
+        let unnamed_file = self.name.as_os_str().is_empty() && !self.path.is_dir();
+        if !is_enclosed_path(&self.name) || unnamed_file {
+            return Err(Error::InvalidPath(format!(
+                "Cannot pack {:?} as {:?}",
+                self.path, self.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Name in the archive of `path`, which is this root or below it
+    pub(crate) fn entry_name(&self, path: &Path) -> PathBuf {
+        match path.strip_prefix(&self.path) {
+            Ok(relative) if relative.as_os_str().is_empty() => self.name.clone(),
+            Ok(relative) => self.name.join(relative),
+            Err(_) => path.to_path_buf(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_name() {
+        let root = PackRoot::new("/data/photos", "backup/photos");
+        assert_eq!(
+            root.entry_name(Path::new("/data/photos")),
+            Path::new("backup/photos")
+        );
+        assert_eq!(
+            root.entry_name(Path::new("/data/photos/2024/a.jpg")),
+            Path::new("backup/photos/2024/a.jpg")
+        );
+
+        let contents = PackRoot::new("/data/photos", "");
+        assert_eq!(
+            contents.entry_name(Path::new("/data/photos/a.jpg")),
+            Path::new("a.jpg")
+        );
+    }
+
+    #[test]
+    fn test_names_must_stay_inside() {
+        assert!(PackRoot::new("/data/a.txt", "../a.txt").check().is_err());
+        assert!(PackRoot::new("/data/a.txt", "/a.txt").check().is_err());
+        assert!(PackRoot::new("/data/a.txt", "").check().is_err());
+        assert!(PackRoot::new("/data/a.txt", "docs/a.txt").check().is_ok());
+    }
+}
@@ -0,0 +1,815 @@
+//! Recovery of what is still readable in a damaged archive
+//!
+//! [`salvage`] does not trust any index. Zip local file headers are found by
+//! scanning the raw bytes for their signature, so a missing central
+//! directory or garbage between entries does not hide the entries around
+//! it, and every entry is checked against its CRC before it is kept. Tar
+//! archives are read block by block and a block only counts as a header if
+//! its checksum matches, so after a damaged region reading resumes at the
+//! next intact header. A compressed tar is decompressed as far as the stream
+//! allows; whatever follows the point where it breaks is lost.
+//!
+//! Only a zip's central directory marks symlinks, so zip symlinks come back
+//! as small files holding the link target.
+
+use super::detect::resolve_format;
+use super::links;
+use super::tar::get_unique_filename;
+use super::ExtractOptions;
+use crate::security::{entry_output_path, validate_symlink};
+use crate::{Error, Result};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Size of a tar block
+const BLOCK: usize = 512;
+
+/// Signature of a zip local file header
+const ZIP_LOCAL_HEADER: [u8; 4] = [0x50, 0x4b, 0x03, 0x04];
+
+/// Optional signature of a zip data descriptor
+const ZIP_DATA_DESCRIPTOR: [u8; 4] = [0x50, 0x4b, 0x07, 0x08];
+
+/// Zip flag: the entry is encrypted
+const ZIP_FLAG_ENCRYPTED: u16 = 1;
+
+/// Zip flag: CRC and sizes follow the data instead of the header
+const ZIP_FLAG_DATA_DESCRIPTOR: u16 = 1 << 3;
+
+/// An entry that was found but could not be recovered
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LostEntry {
+    /// Path within the archive, when a header naming it was readable
+    pub path: Option<PathBuf>,
+    /// Why the entry could not be recovered
+    pub reason: String,
+}
+
+/// Outcome of salvaging an archive
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SalvageReport {
+    /// The damaged archive
+    pub archive: PathBuf,
+    /// Format such as `tar.zst` or `zip`
+    pub format: String,
+    /// Files, directories and links written to the output directory, by
+    /// their path within the archive
+    pub recovered: Vec<PathBuf>,
+    /// Entries found but not recovered
+    pub lost: Vec<LostEntry>,
+    /// Bytes skipped because no entry could be read from them
+    pub damaged_bytes: u64,
+    /// Why reading stopped before the end of the archive, if it did
+    pub error: Option<String>,
+}
+
+impl SalvageReport {
+    /// Whether the archive turned out to be intact
+    pub fn is_complete(&self) -> bool {
+        self.lost.is_empty() && self.damaged_bytes == 0 && self.error.is_none()
+    }
+}
+
+/// Plain-text summary listing every lost entry
+impl fmt::Display for SalvageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Archive: {}", self.archive.display())?;
+        writeln!(f, "Format: {}", self.format)?;
+        if !self.lost.is_empty() {
+            writeln!(f)?;
+            for entry in &self.lost {
+                match &entry.path {
+                    Some(path) => writeln!(f, "LOST {}: {}", path.display(), entry.reason)?,
+                    None => writeln!(f, "LOST (unnamed): {}", entry.reason)?,
+                }
+            }
+        }
+        writeln!(f)?;
+        if self.damaged_bytes > 0 {
+            writeln!(f, "Skipped {} damaged bytes", self.damaged_bytes)?;
+        }
+        if let Some(error) = &self.error {
+            writeln!(f, "Error: {}", error)?;
+        }
+        writeln!(
+            f,
+            "{} entries recovered, {} lost",
+            self.recovered.len(),
+            self.lost.len()
+        )
+    }
+}
+
+/// Extract everything that can still be read from a damaged `archive`
+///
+/// Existing files are only replaced with `options.overwrite`, or written
+/// under a new name with `options.rename`; `strip_components` applies as
+/// for a normal extraction. Entries with unsafe paths are reported as lost.
+pub fn salvage<P: AsRef<Path>, Q: AsRef<Path>>(
+    archive: P,
+    output_dir: Q,
+    options: &ExtractOptions,
+) -> Result<SalvageReport> {
+    let archive = archive.as_ref();
+    let output_dir = output_dir.as_ref();
+    info!("Salvaging {:?} into {:?}", archive, output_dir);
+
+    let format = resolve_format(archive, options.format.as_deref())?;
+    if format == "7z" {
+        return Err(Error::UnsupportedOperation(
+            "Salvaging 7z archives".to_string(),
+        ));
+    }
+
+    fs::create_dir_all(output_dir)?;
+    let output = Output {
+        root: output_dir.canonicalize()?,
+        options,
+    };
+    let mut report = SalvageReport {
+        archive: archive.to_path_buf(),
+        format: format.to_string(),
+        ..SalvageReport::default()
+    };
+    match format {
+        "zip" => salvage_zip(archive, &output, &mut report)?,
+        _ => salvage_tar(archive, format, &output, &mut report)?,
+    }
+
+    info!(
+        "Recovered {} entries, lost {}",
+        report.recovered.len(),
+        report.lost.len()
+    );
+    Ok(report)
+}
+
+/// Where recovered entries go
+struct Output<'a> {
+    /// Canonical output directory
+    root: PathBuf,
+    options: &'a ExtractOptions,
+}
+
+impl Output<'_> {
+    /// Destination of the entry named `name`, or `None` when
+    /// `strip_components` removes all of it
+    ///
+    /// Symlinks recovered earlier are followed only while they stay inside
+    /// the output.
+    fn destination(&self, name: &str) -> std::result::Result<Option<PathBuf>, String> {
+        let strip = self.options.strip_components.unwrap_or(0);
+        let stripped: PathBuf = Path::new(name)
+            .components()
+            .filter(|component| !matches!(component, Component::CurDir))
+            .skip(strip)
+            .collect();
+        if stripped.as_os_str().is_empty() {
+            return Ok(None);
+        }
+        entry_output_path(&self.root, &stripped)
+            .map(Some)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Create the file for `dest`, or `None` if an existing file is kept
+    fn create(&self, dest: &Path) -> io::Result<Option<(File, PathBuf)>> {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let dest = if dest.symlink_metadata().is_err() || self.options.overwrite {
+            dest.to_path_buf()
+        } else if self.options.rename {
+            get_unique_filename(dest)
+        } else {
+            info!("Skipping existing file: {:?}", dest);
+            return Ok(None);
+        };
+        if self.options.overwrite && dest.is_symlink() {
+            fs::remove_file(&dest)?;
+        }
+        Ok(Some((File::create(&dest)?, dest)))
+    }
+
+    fn create_dir(&self, dest: &Path) -> io::Result<()> {
+        fs::create_dir_all(dest)
+    }
+
+    /// Create a symlink at `dest` if its target stays inside the output
+    fn create_symlink(&self, dest: &Path, target: &Path) -> std::result::Result<(), String> {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        // The target is relative to where the link really ends up, which a
+        // symlinked parent moves
+        let placed = match (dest.parent().map(Path::canonicalize), dest.file_name()) {
+            (Some(Ok(parent)), Some(name)) => parent.join(name),
+            _ => dest.to_path_buf(),
+        };
+        validate_symlink(&self.root, &placed, target, false).map_err(|e| e.to_string())?;
+        if dest.symlink_metadata().is_ok() {
+            if !self.options.overwrite {
+                info!("Skipping existing file: {:?}", dest);
+                return Ok(());
+            }
+            fs::remove_file(dest).map_err(|e| e.to_string())?;
+        }
+        match links::create_symlink(&self.root, dest, target, self.options.symlink_fallback) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err("Not allowed to create symlinks here".to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+/// Note that the entry `name` could not be recovered
+fn lose(report: &mut SalvageReport, name: Option<&str>, reason: impl Into<String>) {
+    let reason = reason.into();
+    warn!("Lost {}: {}", name.unwrap_or("an entry"), reason);
+    report.lost.push(LostEntry {
+        path: name.map(PathBuf::from),
+        reason,
+    });
+}
+
+fn salvage_zip(archive: &Path, output: &Output<'_>, report: &mut SalvageReport) -> Result<()> {
+    let mut reader = BufReader::new(File::open(archive)?);
+    // End of the last entry that could be read, and where to look next
+    let mut resume = 0u64;
+    let mut search_from = 0u64;
+
+    while let Some(start) = find_signature(&mut reader, search_from, &ZIP_LOCAL_HEADER)? {
+        if start > resume {
+            debug!("Skipping {} bytes at offset {}", start - resume, resume);
+            report.damaged_bytes += start - resume;
+        }
+        match salvage_zip_entry(&mut reader, start, output, report)? {
+            Some(end) => (resume, search_from) = (end, end),
+            // The next header may lie inside what this one claimed as data
+            None => (resume, search_from) = (start, start + 1),
+        }
+    }
+
+    Ok(())
+}
+
+/// Local header fields needed to read an entry
+struct ZipHeader {
+    name: String,
+    flags: u16,
+    method: u16,
+    crc: u32,
+    compressed_size: u64,
+    size: u64,
+    data_start: u64,
+}
+
+/// Recover the zip entry whose local header is at `start`, returning where
+/// it ends, or `None` if it is damaged
+fn salvage_zip_entry<R: BufRead + Seek>(
+    reader: &mut R,
+    start: u64,
+    output: &Output<'_>,
+    report: &mut SalvageReport,
+) -> Result<Option<u64>> {
+    let Some(header) = read_zip_header(reader, start)? else {
+        lose(report, None, "The archive ends inside a file header");
+        return Ok(None);
+    };
+    let name = header.name.as_str();
+    let descriptor = header.flags & ZIP_FLAG_DATA_DESCRIPTOR != 0;
+    // With a data descriptor the header may leave the sizes at zero
+    let sized = !descriptor || header.compressed_size > 0;
+
+    if header.flags & ZIP_FLAG_ENCRYPTED != 0 {
+        lose(report, Some(name), "The entry is encrypted");
+        return Ok(sized.then_some(header.data_start + header.compressed_size));
+    }
+
+    let dest = match output.destination(name) {
+        Ok(dest) => dest,
+        Err(reason) => {
+            lose(report, Some(name), reason);
+            return Ok(sized.then_some(header.data_start + header.compressed_size));
+        }
+    };
+
+    if name.ends_with('/') {
+        if let Some(dest) = dest {
+            output.create_dir(&dest)?;
+        }
+        report.recovered.push(PathBuf::from(name));
+        return Ok(Some(header.data_start + header.compressed_size));
+    }
+
+    if !matches!(header.method, 0 | 8 | 93) {
+        lose(
+            report,
+            Some(name),
+            format!("Unsupported compression method {}", header.method),
+        );
+        return Ok(sized.then_some(header.data_start + header.compressed_size));
+    }
+    if !sized && header.method != 8 {
+        // Only a deflate stream tells where it ends
+        lose(report, Some(name), "The entry's size is not recorded");
+        return Ok(None);
+    }
+
+    // Entries already on disk are still read, so damage is noticed
+    let (mut file, written_to) = match dest.map(|dest| output.create(&dest)).transpose() {
+        Ok(Some(Some((file, path)))) => (Some(file), Some(path)),
+        Ok(_) => (None, None),
+        Err(e) => {
+            lose(
+                report,
+                Some(name),
+                format!("Could not create the file: {}", e),
+            );
+            return Ok(sized.then_some(header.data_start + header.compressed_size));
+        }
+    };
+    let mut sink = CrcWriter::new(file.as_mut());
+
+    reader.seek(SeekFrom::Start(header.data_start))?;
+    let copied = if sized {
+        let mut data = reader.by_ref().take(header.compressed_size);
+        match header.method {
+            0 => io::copy(&mut data, &mut sink).map(|_| header.compressed_size),
+            8 => io::copy(&mut flate2::read::DeflateDecoder::new(data), &mut sink)
+                .map(|_| header.compressed_size),
+            _ => zstd::stream::read::Decoder::new(data)
+                .and_then(|mut decoder| io::copy(&mut decoder, &mut sink))
+                .map(|_| header.compressed_size),
+        }
+    } else {
+        let mut decoder = flate2::bufread::DeflateDecoder::new(reader.by_ref());
+        io::copy(&mut decoder, &mut sink).map(|_| decoder.total_in())
+    };
+
+    let CrcWriter { crc, size, .. } = sink;
+    let crc = crc.finalize();
+    let failure = match copied {
+        Err(e) => format!("The data is damaged: {}", e),
+        Ok(compressed) => {
+            let mut end = header.data_start + compressed;
+            let (expected_crc, expected_size) = if descriptor {
+                match read_data_descriptor(reader, end)? {
+                    Some((crc, size, length)) => {
+                        end += length;
+                        (crc, size)
+                    }
+                    None => (header.crc, header.size),
+                }
+            } else {
+                (header.crc, header.size)
+            };
+            if crc == expected_crc && size == expected_size {
+                report.recovered.push(PathBuf::from(name));
+                return Ok(Some(end));
+            }
+            "The data does not match its checksum".to_string()
+        }
+    };
+
+    drop(file);
+    if let Some(path) = written_to {
+        let _ = fs::remove_file(path);
+    }
+    lose(report, Some(name), failure);
+    Ok(None)
+}
+
+/// Read the local file header at `start`, or `None` if the file ends first
+fn read_zip_header<R: Read + Seek>(reader: &mut R, start: u64) -> io::Result<Option<ZipHeader>> {
+    reader.seek(SeekFrom::Start(start))?;
+    let mut fixed = [0u8; 30];
+    if fill(reader, &mut fixed)? < fixed.len() {
+        return Ok(None);
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([fixed[i], fixed[i + 1]]);
+    let u32_at =
+        |i: usize| u32::from_le_bytes([fixed[i], fixed[i + 1], fixed[i + 2], fixed[i + 3]]);
+
+    let mut name = vec![0u8; u16_at(26) as usize];
+    let mut extra = vec![0u8; u16_at(28) as usize];
+    if fill(reader, &mut name)? < name.len() || fill(reader, &mut extra)? < extra.len() {
+        return Ok(None);
+    }
+
+    let mut size = u64::from(u32_at(22));
+    let mut compressed_size = u64::from(u32_at(18));
+    if size == u64::from(u32::MAX) || compressed_size == u64::from(u32::MAX) {
+        if let Some((zip64_size, zip64_compressed)) = zip64_sizes(&extra) {
+            size = zip64_size;
+            compressed_size = zip64_compressed;
+        }
+    }
+
+    Ok(Some(ZipHeader {
+        name: String::from_utf8_lossy(&name).into_owned(),
+        flags: u16_at(6),
+        method: u16_at(8),
+        crc: u32_at(14),
+        compressed_size,
+        size,
+        data_start: start + 30 + name.len() as u64 + extra.len() as u64,
+    }))
+}
+
+/// Sizes from the zip64 extra field of a local header
+fn zip64_sizes(mut extra: &[u8]) -> Option<(u64, u64)> {
+    while extra.len() >= 4 {
+        let id = u16::from_le_bytes([extra[0], extra[1]]);
+        let len = u16::from_le_bytes([extra[2], extra[3]]) as usize;
+        let data = extra.get(4..4 + len)?;
+        if id == 0x0001 && data.len() >= 16 {
+            let size = u64::from_le_bytes(data[0..8].try_into().ok()?);
+            let compressed = u64::from_le_bytes(data[8..16].try_into().ok()?);
+            return Some((size, compressed));
+        }
+        extra = &extra[4 + len..];
+    }
+    None
+}
+
+/// CRC and size from the data descriptor at `at`, with the descriptor's
+/// length
+fn read_data_descriptor<R: Read + Seek>(
+    reader: &mut R,
+    at: u64,
+) -> io::Result<Option<(u32, u64, u64)>> {
+    reader.seek(SeekFrom::Start(at))?;
+    let mut bytes = [0u8; 16];
+    let n = fill(reader, &mut bytes)?;
+    let (fields, signature_len) = if bytes[..4] == ZIP_DATA_DESCRIPTOR {
+        (&bytes[4..n.max(4)], 4)
+    } else {
+        (&bytes[..n], 0)
+    };
+    if fields.len() < 12 {
+        return Ok(None);
+    }
+    let crc = u32::from_le_bytes(fields[0..4].try_into().unwrap());
+    let size = u32::from_le_bytes(fields[8..12].try_into().unwrap());
+    Ok(Some((crc, u64::from(size), signature_len + 12)))
+}
+
+/// Offset of the next `signature` at or after `from`
+fn find_signature<R: Read + Seek>(
+    reader: &mut R,
+    from: u64,
+    signature: &[u8; 4],
+) -> io::Result<Option<u64>> {
+    reader.seek(SeekFrom::Start(from))?;
+    let mut buffer = vec![0u8; 64 * 1024];
+    // Bytes carried over so a signature split between reads is found
+    let mut carried = 0;
+    let mut base = from;
+
+    loop {
+        let n = fill(reader, &mut buffer[carried..])?;
+        let len = carried + n;
+        if let Some(i) = buffer[..len]
+            .windows(signature.len())
+            .position(|window| window == signature)
+        {
+            return Ok(Some(base + i as u64));
+        }
+        if n == 0 {
+            return Ok(None);
+        }
+        let keep = len.min(signature.len() - 1);
+        buffer.copy_within(len - keep..len, 0);
+        base += (len - keep) as u64;
+        carried = keep;
+    }
+}
+
+fn salvage_tar(
+    archive: &Path,
+    format: &str,
+    output: &Output<'_>,
+    report: &mut SalvageReport,
+) -> Result<()> {
+    let file = BufReader::new(File::open(archive)?);
+    let reader: Box<dyn Read> = match format {
+        "tar.gz" => Box::new(flate2::read::GzDecoder::new(file)),
+        "tar.xz" => Box::new(xz2::read::XzDecoder::new(file)),
+        "tar.zst" => Box::new(zstd::stream::read::Decoder::with_buffer(file)?),
+        "tar.br" => Box::new(brotli::Decompressor::new(file, 4096)),
+        _ => Box::new(file),
+    };
+    let mut stream = TarStream { reader, offset: 0 };
+
+    // Names from GNU long-name and pax headers, for the next entry
+    let mut long_name: Option<String> = None;
+    let mut long_link: Option<String> = None;
+    let mut pax_name: Option<String> = None;
+    // Start of the damaged region being skipped
+    let mut damaged_from: Option<u64> = None;
+
+    loop {
+        let at = stream.offset;
+        let mut block = [0u8; BLOCK];
+        match fill(&mut stream, &mut block) {
+            Ok(BLOCK) => {}
+            Ok(0) => break,
+            Ok(_) => {
+                report.error = Some(format!(
+                    "The archive ends in the middle of a block at {}",
+                    at
+                ));
+                break;
+            }
+            Err(e) => {
+                report.error = Some(format!(
+                    "The compressed stream is damaged after {} bytes: {}",
+                    at, e
+                ));
+                break;
+            }
+        }
+
+        if block.iter().all(|&byte| byte == 0) {
+            if let Some(from) = damaged_from.take() {
+                report.damaged_bytes += at - from;
+            }
+            continue;
+        }
+        if !valid_tar_header(&block) {
+            damaged_from.get_or_insert(at);
+            continue;
+        }
+        if let Some(from) = damaged_from.take() {
+            debug!("Skipping {} bytes at offset {}", at - from, from);
+            report.damaged_bytes += at - from;
+        }
+
+        let header = tar::Header::from_byte_slice(&block);
+        let size = header.entry_size().unwrap_or(0);
+        let entry_type = header.entry_type();
+
+        // Extension headers carry data for the entry that follows
+        if entry_type.is_gnu_longname()
+            || entry_type.is_gnu_longlink()
+            || entry_type.is_pax_local_extensions()
+            || entry_type.is_pax_global_extensions()
+        {
+            let data = match read_tar_data(&mut stream, size) {
+                Ok(Some(data)) => data,
+                result => {
+                    tar_stream_failed(report, result.err(), at);
+                    break;
+                }
+            };
+            let text = || {
+                let text = data.split(|&byte| byte == 0).next().unwrap_or(&[]);
+                String::from_utf8_lossy(text).into_owned()
+            };
+            if entry_type.is_gnu_longname() {
+                long_name = Some(text());
+            } else if entry_type.is_gnu_longlink() {
+                long_link = Some(text());
+            } else if entry_type.is_pax_local_extensions() {
+                pax_name = pax_path(&data);
+            }
+            continue;
+        }
+
+        let name = pax_name
+            .take()
+            .or(long_name.take())
+            .unwrap_or_else(|| String::from_utf8_lossy(&header.path_bytes()).into_owned());
+        let link = long_link.take();
+        let dest = match output.destination(&name) {
+            Ok(dest) => dest,
+            Err(reason) => {
+                lose(report, Some(&name), reason);
+                if !skip_tar_data(&mut stream, size)? {
+                    break;
+                }
+                continue;
+            }
+        };
+
+        if entry_type.is_dir() {
+            if let Some(dest) = dest {
+                output.create_dir(&dest)?;
+            }
+            report.recovered.push(PathBuf::from(&name));
+            continue;
+        }
+
+        if entry_type.is_symlink() || entry_type.is_hard_link() {
+            let target = link.unwrap_or_else(|| {
+                header
+                    .link_name_bytes()
+                    .map(|target| String::from_utf8_lossy(&target).into_owned())
+                    .unwrap_or_default()
+            });
+            let result = match dest {
+                None => Ok(()),
+                Some(dest) if entry_type.is_symlink() => {
+                    output.create_symlink(&dest, Path::new(&target))
+                }
+                Some(dest) => output.destination(&target).and_then(|original| {
+                    let original = original.ok_or("The link target was stripped")?;
+                    fs::hard_link(original, dest).map_err(|e| e.to_string())
+                }),
+            };
+            match result {
+                Ok(()) => report.recovered.push(PathBuf::from(&name)),
+                Err(reason) => lose(report, Some(&name), reason),
+            }
+            continue;
+        }
+
+        if !entry_type.is_file() && !entry_type.is_contiguous() {
+            lose(report, Some(&name), "Special files are not restored");
+            if !skip_tar_data(&mut stream, size)? {
+                break;
+            }
+            continue;
+        }
+
+        let (mut file, path) = match dest.map(|dest| output.create(&dest)).transpose() {
+            Ok(Some(Some((file, path)))) => (Some(file), Some(path)),
+            Ok(_) => (None, None),
+            Err(e) => {
+                lose(
+                    report,
+                    Some(&name),
+                    format!("Could not create the file: {}", e),
+                );
+                if !skip_tar_data(&mut stream, size)? {
+                    break;
+                }
+                continue;
+            }
+        };
+        let copied = copy_tar_data(&mut stream, size, file.as_mut());
+        drop(file);
+        match copied {
+            Ok(true) => report.recovered.push(PathBuf::from(&name)),
+            result => {
+                if let Some(path) = path {
+                    let _ = fs::remove_file(path);
+                }
+                let reason = match &result {
+                    Err(e) => format!("The data is damaged: {}", e),
+                    Ok(_) => "The archive ends inside this file".to_string(),
+                };
+                lose(report, Some(&name), reason);
+                tar_stream_failed(report, result.err(), at);
+                break;
+            }
+        }
+    }
+
+    if let Some(from) = damaged_from {
+        report.damaged_bytes += stream.offset - from;
+    }
+    Ok(())
+}
+
+/// Record why reading a tar stream had to stop
+fn tar_stream_failed(report: &mut SalvageReport, error: Option<io::Error>, at: u64) {
+    if report.error.is_none() {
+        report.error = Some(match error {
+            Some(e) => format!("The compressed stream is damaged after {} bytes: {}", at, e),
+            None => format!("The archive is truncated after {} bytes", at),
+        });
+    }
+}
+
+/// A decompressed tar stream that counts the bytes read from it
+struct TarStream<R> {
+    reader: R,
+    offset: u64,
+}
+
+impl<R: Read> Read for TarStream<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.offset += n as u64;
+        Ok(n)
+    }
+}
+
+/// Whether `block` is a tar header with a matching checksum
+fn valid_tar_header(block: &[u8; BLOCK]) -> bool {
+    let header = tar::Header::from_byte_slice(block);
+    let Ok(stored) = header.cksum() else {
+        return false;
+    };
+    // The checksum field itself is summed as if it held spaces
+    let sum: u32 = block[..148]
+        .iter()
+        .chain(&[b' '; 8])
+        .chain(&block[156..])
+        .map(|&byte| u32::from(byte))
+        .sum();
+    // Some old writers summed signed bytes
+    let signed: i32 = block[..148]
+        .iter()
+        .chain(&[b' '; 8])
+        .chain(&block[156..])
+        .map(|&byte| i32::from(byte as i8))
+        .sum();
+    stored == sum || i64::from(stored) == i64::from(signed)
+}
+
+/// Path from the records of a pax extended header
+fn pax_path(data: &[u8]) -> Option<String> {
+    tar::PaxExtensions::new(data)
+        .flatten()
+        .find(|extension| extension.key() == Ok("path"))
+        .and_then(|extension| extension.value().ok().map(str::to_string))
+}
+
+/// Number of padding bytes after `size` bytes of entry data
+fn padding(size: u64) -> u64 {
+    (BLOCK as u64 - size % BLOCK as u64) % BLOCK as u64
+}
+
+/// Read an extension header's data, or `None` if the stream ends first
+fn read_tar_data<R: Read>(stream: &mut R, size: u64) -> io::Result<Option<Vec<u8>>> {
+    let mut data = Vec::new();
+    let read = stream.take(size).read_to_end(&mut data)? as u64;
+    if read < size || !skip(stream, padding(size))? {
+        return Ok(None);
+    }
+    Ok(Some(data))
+}
+
+/// Skip an entry's data, returning whether the stream held all of it
+fn skip_tar_data<R: Read>(stream: &mut R, size: u64) -> io::Result<bool> {
+    skip(stream, size + padding(size))
+}
+
+/// Copy an entry's data into `file`, returning whether the stream held all
+/// of it
+fn copy_tar_data<R: Read>(stream: &mut R, size: u64, file: Option<&mut File>) -> io::Result<bool> {
+    let copied = match file {
+        Some(file) => io::copy(&mut stream.take(size), file)?,
+        None => io::copy(&mut stream.take(size), &mut io::sink())?,
+    };
+    Ok(copied == size && skip(stream, padding(size))?)
+}
+
+fn skip<R: Read>(stream: &mut R, len: u64) -> io::Result<bool> {
+    Ok(io::copy(&mut stream.take(len), &mut io::sink())? == len)
+}
+
+/// Read until `buf` is full or the input ends, returning the bytes read
+fn fill<R: Read + ?Sized>(reader: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    let mut total = 0;
+    while total < buf.len() {
+        match reader.read(&mut buf[total..]) {
+            Ok(0) => break,
+            Ok(n) => total += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(total)
+}
+
+/// Writer that computes the CRC-32 and size of what passes through it
+struct CrcWriter<'a> {
+    file: Option<&'a mut File>,
+    crc: crc32fast::Hasher,
+    size: u64,
+}
+
+impl<'a> CrcWriter<'a> {
+    fn new(file: Option<&'a mut File>) -> Self {
+        Self {
+            file,
+            crc: crc32fast::Hasher::new(),
+            size: 0,
+        }
+    }
+}
+
+impl Write for CrcWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(file) = self.file.as_mut() {
+            file.write_all(buf)?;
+        }
+        self.crc.update(buf);
+        self.size += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
@@ -1,11 +1,14 @@
 //! Secure wrapper for archive extractors with security checks
 
 use super::extractor::{ArchiveEntry, ExtractEntryOptions, Extractor};
+use super::paths;
+use crate::progress::{NoProgress, ProgressSink};
 use crate::security::{
-    check_compression_ratio, check_disk_space, check_extraction_size, sanitize_path,
-    validate_symlink, SecurityOptions,
+    check_compression_ratio, check_disk_space, check_entry_count, check_extraction_size,
+    sanitize_path, validate_symlink, SecurityOptions,
 };
 use crate::{Error, Result};
+use std::io::Read;
 use std::path::Path;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
@@ -33,13 +36,35 @@ impl SecureExtractor {
             security_options,
         }
     }
+
+    /// Refuse an entry whose sizes look like a compression bomb
+    ///
+    /// Listing only reads metadata, so this is checked when an entry's data is
+    /// read rather than when it is listed.
+    fn check_compression_ratio(&self, entry: &ArchiveEntry) -> Result<()> {
+        match entry.compressed_size {
+            Some(compressed_size) if compressed_size > 0 => check_compression_ratio(
+                compressed_size,
+                entry.size,
+                self.security_options.max_compression_ratio,
+            )
+            .inspect_err(|_| {
+                warn!(
+                    path = ?entry.path,
+                    compressed_size,
+                    uncompressed_size = entry.size,
+                    "Suspicious compression ratio"
+                );
+            }),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl Extractor for SecureExtractor {
     fn entries(&self, source: &Path) -> Result<Box<dyn Iterator<Item = Result<ArchiveEntry>>>> {
         // Get entries from inner extractor
         let entries = self.inner.entries(source)?;
-        let security_options = self.security_options.clone();
 
         // Wrap the iterator to add security checks
         let secure_entries = entries.map(move |entry_result| {
@@ -51,25 +76,6 @@ impl Extractor for SecureExtractor {
                         return Err(e);
                     }
 
-                    // Check compression ratio if both sizes are available
-                    if let Some(compressed_size) = entry.compressed_size {
-                        if compressed_size > 0 {
-                            if let Err(e) = check_compression_ratio(
-                                compressed_size,
-                                entry.size,
-                                security_options.max_compression_ratio,
-                            ) {
-                                warn!(
-                                    path = ?entry.path,
-                                    compressed_size,
-                                    uncompressed_size = entry.size,
-                                    "Suspicious compression ratio"
-                                );
-                                return Err(e);
-                            }
-                        }
-                    }
-
                     Ok(entry)
                 }
                 Err(e) => Err(e),
@@ -85,6 +91,17 @@ impl Extractor for SecureExtractor {
         entry: &ArchiveEntry,
         destination: &Path,
         options: ExtractEntryOptions,
+    ) -> Result<()> {
+        self.extract_entry_with_progress(source, entry, destination, options, &NoProgress)
+    }
+
+    fn extract_entry_with_progress(
+        &self,
+        source: &Path,
+        entry: &ArchiveEntry,
+        destination: &Path,
+        options: ExtractEntryOptions,
+        progress: &dyn ProgressSink,
     ) -> Result<()> {
         // Sanitize the destination path
         let safe_path = sanitize_path(destination, &entry.path)?;
@@ -95,18 +112,27 @@ impl Extractor for SecureExtractor {
             "Extracting entry with sanitized path"
         );
 
-        // If it's a symlink, validate the target
+        // If it's a symlink, validate the target from where it is written
         if entry.is_symlink {
             if let Some(ref target) = entry.link_target {
-                validate_symlink(
-                    destination,
-                    &safe_path,
-                    target,
-                    self.security_options.allow_external_symlinks,
-                )?;
+                let link_path = match options.strip_components {
+                    Some(count) => paths::strip_components(&entry.path, count)
+                        .map(|name| destination.join(name)),
+                    None => Some(safe_path.clone()),
+                };
+                if let Some(link_path) = link_path {
+                    validate_symlink(
+                        destination,
+                        &link_path,
+                        target,
+                        self.security_options.allow_external_symlinks,
+                    )?;
+                }
             }
         }
 
+        self.check_compression_ratio(entry)?;
+
         // Create a modified entry with the sanitized path
         let safe_entry = ArchiveEntry {
             path: safe_path
@@ -118,7 +144,13 @@ impl Extractor for SecureExtractor {
 
         // Extract using the inner extractor
         self.inner
-            .extract_entry(source, &safe_entry, destination, options)
+            .extract_entry_with_progress(source, &safe_entry, destination, options, progress)
+    }
+
+    fn open_entry(&self, source: &Path, entry: &ArchiveEntry) -> Result<Box<dyn Read + Send>> {
+        // Nothing is written to disk, so there is no path to sanitize
+        self.check_compression_ratio(entry)?;
+        self.inner.open_entry(source, entry)
     }
 
     fn format_name(&self) -> &'static str {
@@ -199,6 +231,11 @@ pub fn extract_archive_secure(
     let mut error_count = 0;
 
     for entry in secure_extractor.entries(source)? {
+        check_entry_count(
+            (extracted_count + error_count) as u64,
+            security_options.max_entries,
+        )?;
+
         match entry {
             Ok(entry) => {
                 // Check if extraction would exceed size limit
@@ -215,6 +252,9 @@ pub fn extract_archive_secure(
                     preserve_permissions: true,
                     preserve_timestamps: true,
                     follow_symlinks: false,
+                    password: None,
+                    symlink_fallback: Default::default(),
+                    strip_components: None,
                 };
 
                 match secure_extractor.extract_entry(source, &entry, destination, options) {
@@ -356,7 +396,21 @@ mod tests {
         let secure = SecureExtractor::new(Box::new(mock));
         let entries: Vec<_> = secure.entries(Path::new("test.zip")).unwrap().collect();
 
+        // Listing reads no data, so only extracting should fail
         assert_eq!(entries.len(), 1);
-        assert!(entries[0].is_err()); // Should fail due to high compression ratio
+        let entry = entries.into_iter().next().unwrap().unwrap();
+        let destination = tempfile::TempDir::new().unwrap();
+        assert!(secure
+            .extract_entry(
+                Path::new("test.zip"),
+                &entry,
+                destination.path(),
+                ExtractEntryOptions::default(),
+            )
+            .is_err());
+        assert!(matches!(
+            secure.open_entry(Path::new("test.zip"), &entry),
+            Err(Error::SecurityError(_))
+        ));
     }
 }
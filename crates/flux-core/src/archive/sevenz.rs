@@ -1,12 +1,19 @@
 //! 7z archive support module
 
-use crate::archive::ArchiveEntry;
-use crate::{Error, Result};
+use crate::archive::case_fold::CaseFolder;
+use crate::archive::paths::{self, is_enclosed_path};
+use crate::archive::resume::{self, Prior, Resume};
+use crate::archive::sevenz_extractor::sevenz_entry;
+use crate::archive::tamper::HashLookup;
+use crate::archive::undo::{self, UndoJournal};
+use crate::archive::{ArchiveEntry, OperationOutcome, SkipReason};
+use crate::durability::Syncer;
+use crate::{buffer, Error, Result};
 use sevenz_rust::{Password, SevenZReader};
 use std::fs::{self, File};
 use std::io;
 use std::path::{Path, PathBuf};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// Pack files into a 7z archive
 pub fn pack_7z<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q) -> Result<()> {
@@ -55,7 +62,7 @@ pub fn extract_7z<P: AsRef<Path>, Q: AsRef<Path>>(archive: P, output_dir: Q) ->
 
             // Extract the file
             let mut output_file = File::create(&path)?;
-            io::copy(reader, &mut output_file)?;
+            buffer::copy(reader, &mut output_file)?;
         }
 
         Ok(true) // Continue extraction
@@ -67,15 +74,24 @@ pub fn extract_7z<P: AsRef<Path>, Q: AsRef<Path>>(archive: P, output_dir: Q) ->
 }
 
 /// Extract files from a 7z archive with options
+///
+/// Entries that cannot be written are recorded in the outcome and the rest
+/// are still extracted.
 pub fn extract_7z_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
     archive: P,
     output_dir: Q,
     options: crate::archive::ExtractOptions,
-) -> Result<()> {
+) -> Result<OperationOutcome> {
     let archive_path = archive.as_ref();
     let output_dir = output_dir.as_ref();
 
     info!("Extracting 7z archive with options: {:?}", archive_path);
+    if options.is_ordered() {
+        warn!(
+            "Extracting {:?} in stored order, as 7z archives are read in one pass",
+            archive_path
+        );
+    }
 
     // Create output directory if it doesn't exist
     fs::create_dir_all(output_dir)?;
@@ -84,27 +100,74 @@ pub fn extract_7z_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
     let mut sz = SevenZReader::open(archive_path, Password::empty())
         .map_err(|e| Error::ArchiveError(format!("Failed to open 7z archive: {}", e)))?;
 
+    let mut outcome = OperationOutcome::default();
+    let mut case_folder = CaseFolder::new(options.case_collisions, options.fold_case, output_dir);
+    let hashes = HashLookup::new(options.manifest_check.as_ref());
+    let mut journal = options.undoable.then(|| UndoJournal::new(output_dir));
+    let mut resume = options
+        .resume
+        .then(|| Resume::open(archive_path, output_dir))
+        .transpose()?;
+    let mut syncer = Syncer::new(options.durability);
+
     // Extract all entries
     sz.for_each_entries(|entry, reader| {
         let entry_path = PathBuf::from(&entry.name);
+        if !is_enclosed_path(&entry_path) {
+            warn!("Skipping entry with unsafe path: {:?}", entry_path);
+            outcome.skip(entry_path, SkipReason::UnsafePath);
+            return Ok(true);
+        }
+        if options.entry_filter.is_some() && !options.accepts(&sevenz_entry(entry).into()) {
+            outcome.skip(entry_path, SkipReason::Excluded);
+            return Ok(true);
+        }
 
         // Handle strip components
-        let final_path = if let Some(strip) = options.strip_components {
-            let components: Vec<_> = entry_path.components().collect();
-            if components.len() <= strip {
-                return Ok(true); // Skip this entry
+        let name = match options.strip_components {
+            Some(strip) => match paths::strip_components(&entry_path, strip) {
+                Some(stripped) => stripped,
+                None => {
+                    outcome.skip(entry_path, SkipReason::Stripped);
+                    return Ok(true); // Skip this entry
+                }
+            },
+            None => entry_path.clone(),
+        };
+        let name = options.normalize_names.apply_path(&name).into_owned();
+        let mut final_path = output_dir.join(&name);
+
+        let mut expected = None;
+        if !entry.is_directory {
+            match hashes.expected(&entry_path, &name, &mut outcome) {
+                Ok(hash) => expected = hash,
+                Err(e) => {
+                    outcome.fail(name, e);
+                    return Ok(true);
+                }
+            }
+            match case_folder.place(&name, output_dir, &mut outcome) {
+                Ok(placed) => final_path = output_dir.join(placed),
+                Err(e) => {
+                    outcome.fail(name, e);
+                    return Ok(true);
+                }
             }
-            output_dir.join(components[strip..].iter().collect::<PathBuf>())
+        }
+
+        let prior = if entry.is_directory {
+            Prior::Unknown
         } else {
-            output_dir.join(&entry_path)
+            resume::prior(resume.as_ref(), &final_path)
         };
+        if prior == Prior::Done {
+            outcome.skip(name, SkipReason::Resumed);
+            return Ok(true);
+        }
 
         // Handle existing files
-        if final_path.exists() {
-            if options.skip {
-                debug!("Skipping existing file: {:?}", final_path);
-                return Ok(true);
-            } else if options.rename {
+        if final_path.exists() && !entry.is_directory && prior != Prior::Leftover {
+            if options.rename && !options.skip {
                 let mut counter = 1;
                 let mut new_path = final_path.clone();
                 while new_path.exists() {
@@ -124,35 +187,78 @@ pub fn extract_7z_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
                     counter += 1;
                 }
                 debug!("Renaming to: {:?}", new_path);
-            } else if !options.overwrite {
-                return Err(io::Error::new(
-                    io::ErrorKind::AlreadyExists,
-                    format!("File exists: {:?}", final_path),
-                )
-                .into());
+                final_path = new_path;
+            } else if !options.overwrite || options.skip {
+                debug!("Skipping existing file: {:?}", final_path);
+                outcome.skip(name, SkipReason::Exists);
+                return Ok(true);
             }
         }
 
-        if entry.is_directory {
-            fs::create_dir_all(&final_path)?;
-        } else {
-            // Create parent directories
-            if let Some(parent) = final_path.parent() {
-                fs::create_dir_all(parent)?;
+        // What the interrupted run left is its own, not the user's
+        if prior != Prior::Leftover {
+            if let Err(e) = undo::prepare(journal.as_mut(), &final_path) {
+                outcome.fail(name, e);
+                return Ok(true);
             }
-
-            debug!("Extracting: {:?}", entry.name);
-
-            // Extract the file
-            let mut output_file = File::create(&final_path)?;
-            io::copy(reader, &mut output_file)?;
+        }
+        debug!("Extracting: {:?}", entry.name);
+        let result = match expected {
+            None => extract_7z_entry(entry.is_directory, reader, &final_path, resume.as_mut()),
+            Some(expected) => {
+                let mut reader = hashes.hashing(reader);
+                extract_7z_entry(false, &mut reader, &final_path, resume.as_mut()).and_then(|()| {
+                    let actual = reader.hash();
+                    hashes.verify(&name, &final_path, expected, &actual, &mut outcome)
+                })
+            }
+        };
+        let result = result.and_then(|()| syncer.written(&final_path));
+        match result {
+            Ok(()) => {
+                resume::done(resume.as_mut(), &final_path);
+                outcome.done(name)
+            }
+            Err(e) => outcome.fail(name, e),
         }
 
         Ok(true) // Continue extraction
     })
     .map_err(|e| Error::ArchiveError(format!("Failed to extract 7z archive: {}", e)))?;
 
-    info!("7z extraction complete");
+    syncer.finish()?;
+    if let Some(journal) = &mut journal {
+        journal.save()?;
+    }
+    if let Some(resume) = resume {
+        resume.finish(!outcome.failed.is_empty())?;
+    }
+    info!("7z extraction complete: {}", outcome);
+    Ok(outcome)
+}
+
+/// Write one entry of a 7z archive to `path`, through the resume journal
+/// when one is kept
+fn extract_7z_entry(
+    is_directory: bool,
+    reader: &mut dyn io::Read,
+    path: &Path,
+    resume: Option<&mut Resume>,
+) -> Result<()> {
+    if is_directory {
+        fs::create_dir_all(path)?;
+    } else if let Some(resume) = resume {
+        resume.write(reader, path)?;
+    } else {
+        // Create parent directories
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Extract the file
+        let mut output_file = File::create(path)?;
+        buffer::copy(reader, &mut output_file)?;
+    }
     Ok(())
 }
 
@@ -1,15 +1,32 @@
 //! 7z extractor implementation
+//!
+//! Entries of a solid 7z archive share one compressed block, which can only
+//! be decoded from its start. Reading one entry therefore decodes every
+//! entry before it in the block. To keep selective extraction from decoding
+//! the same block once per entry, a block small enough to hold in memory is
+//! decoded whole the first time one of its entries is asked for and kept
+//! until an entry of another block is. Larger blocks are decoded up to the
+//! wanted entry each time.
 
-use super::extractor::{ArchiveEntry, ExtractEntryOptions, Extractor};
-use crate::{Error, Result};
-use sevenz_rust::{Password, SevenZReader};
+use super::extractor::{stream_entry, ArchiveEntry, ExtractEntryOptions, Extractor};
+use crate::{buffer, Error, Result};
+use sevenz_rust::{Archive, BlockDecoder, Password, SevenZArchiveEntry, SevenZReader};
 use std::fs::{self, File};
-use std::io;
-use std::path::Path;
-// use tracing::{debug, info, warn};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tracing::debug;
+
+/// Largest block, once decoded, kept in memory for the entries after the
+/// first one read from it
+const BLOCK_CACHE_LIMIT: u64 = 64 * 1024 * 1024;
 
 /// 7z extractor
-pub struct SevenZExtractor;
+pub struct SevenZExtractor {
+    /// The block decoded last
+    cache: Mutex<Option<Arc<DecodedBlock>>>,
+}
 
 impl Default for SevenZExtractor {
     fn default() -> Self {
@@ -20,30 +37,283 @@ impl Default for SevenZExtractor {
 impl SevenZExtractor {
     /// Create a new 7z extractor
     pub fn new() -> Self {
-        Self
+        Self {
+            cache: Mutex::new(None),
+        }
+    }
+
+    /// The decoded block `block` of `archive`, decoding it first unless it
+    /// is too large to keep
+    fn decoded_block(
+        &self,
+        source: &Path,
+        archive: &Archive,
+        block: usize,
+        password: &Password,
+    ) -> Result<Option<Arc<DecodedBlock>>> {
+        let version = ArchiveVersion::of(source)?;
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(decoded) = cache
+            .as_ref()
+            .filter(|decoded| decoded.version == version && decoded.block == block)
+        {
+            return Ok(Some(Arc::clone(decoded)));
+        }
+        if archive.folders[block].get_unpack_size() > BLOCK_CACHE_LIMIT {
+            return Ok(None);
+        }
+
+        debug!("Decoding 7z block {} of {:?}", block, source);
+        let mut data = Vec::new();
+        let mut offsets = vec![0];
+        let mut file = File::open(source)?;
+        BlockDecoder::new(block, archive, password.as_slice(), &mut file)
+            .for_each_entries(&mut |_, reader| {
+                reader.read_to_end(&mut data)?;
+                offsets.push(data.len());
+                Ok(true)
+            })
+            .map_err(|e| sevenz_error(e, source))?;
+
+        let decoded = Arc::new(DecodedBlock {
+            version,
+            block,
+            first_file: archive.stream_map.folder_first_file_index[block],
+            data,
+            offsets,
+        });
+        *cache = Some(Arc::clone(&decoded));
+        Ok(Some(decoded))
+    }
+
+    /// Write the data of the file at `index` in `archive` to `writer`
+    fn copy_entry(
+        &self,
+        source: &Path,
+        archive: &Archive,
+        index: usize,
+        password: &Password,
+        writer: &mut dyn Write,
+    ) -> Result<()> {
+        // Empty files and directories have no block
+        let Some(block) = archive.stream_map.file_folder_index[index] else {
+            return Ok(());
+        };
+        if let Some(decoded) = self.decoded_block(source, archive, block, password)? {
+            writer.write_all(decoded.entry(index))?;
+            return Ok(());
+        }
+        copy_from_block(source, archive, block, index, password, writer)
+    }
+}
+
+/// One block of an archive, decoded
+struct DecodedBlock {
+    version: ArchiveVersion,
+    block: usize,
+    /// Index of the first file of the block among the files of the archive
+    first_file: usize,
+    /// The files of the block, one after another
+    data: Vec<u8>,
+    /// Where each file starts in `data`, and where the last one ends
+    offsets: Vec<usize>,
+}
+
+impl DecodedBlock {
+    /// The data of the file at `index` among the files of the archive
+    fn entry(&self, index: usize) -> &[u8] {
+        let i = index - self.first_file;
+        &self.data[self.offsets[i]..self.offsets[i + 1]]
+    }
+}
+
+/// What tells a changed archive file from the one a block was decoded from
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ArchiveVersion {
+    path: PathBuf,
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+impl ArchiveVersion {
+    fn of(path: &Path) -> Result<Self> {
+        let metadata = fs::metadata(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            len: metadata.len(),
+            modified: metadata.modified().ok(),
+        })
+    }
+}
+
+/// Decode `block` up to the file at `index` and write that file to `writer`
+fn copy_from_block(
+    source: &Path,
+    archive: &Archive,
+    block: usize,
+    index: usize,
+    password: &Password,
+    writer: &mut dyn Write,
+) -> Result<()> {
+    debug!(
+        "Decoding 7z block {} of {:?} up to file {}",
+        block, source, index
+    );
+    let mut current = archive.stream_map.folder_first_file_index[block];
+    let mut result = Ok(());
+    let mut file = File::open(source)?;
+    BlockDecoder::new(block, archive, password.as_slice(), &mut file)
+        .for_each_entries(&mut |_, reader| {
+            if current < index {
+                // Earlier files have to be decoded to get past them
+                io::copy(reader, &mut io::sink())?;
+                current += 1;
+                return Ok(true);
+            }
+            result = buffer::copy(reader, writer).map(|_| ());
+            Ok(false)
+        })
+        .map_err(|e| sevenz_error(e, source))?;
+    result.map_err(Error::Io)
+}
+
+/// Read the header of the archive at `source`
+fn open_archive(source: &Path, password: &Password) -> Result<Archive> {
+    let mut file = File::open(source)?;
+    let len = file.metadata()?.len();
+    Archive::read(&mut file, len, password.as_slice()).map_err(|e| sevenz_error(e, source))
+}
+
+/// Index of the file at `path` among the files of `archive`
+fn find_file(archive: &Archive, path: &Path) -> Result<usize> {
+    archive
+        .files
+        .iter()
+        .position(|file| Path::new(&file.name) == path)
+        .ok_or_else(|| Error::NotFound(format!("Entry not found in archive: {:?}", path)))
+}
+
+/// Password to decode with, from the extract options
+fn password(options: &ExtractEntryOptions) -> Password {
+    options
+        .password
+        .as_deref()
+        .map(Password::from)
+        .unwrap_or_else(Password::empty)
+}
+
+/// Turn an error of the 7z reader into ours
+fn sevenz_error(error: sevenz_rust::Error, source: &Path) -> Error {
+    match error {
+        sevenz_rust::Error::PasswordRequired => {
+            Error::PasswordRequired(source.display().to_string())
+        }
+        sevenz_rust::Error::MaybeBadPassword(_) => {
+            Error::InvalidPassword(source.display().to_string())
+        }
+        sevenz_rust::Error::Io(e, _) | sevenz_rust::Error::FileOpen(e, _) => Error::Io(e),
+        other => Error::ArchiveError(format!("Failed to read 7z archive: {}", other)),
+    }
+}
+
+/// Describe one 7z entry from its header
+pub(crate) fn sevenz_entry(entry: &SevenZArchiveEntry) -> ArchiveEntry {
+    ArchiveEntry {
+        path: PathBuf::from(&entry.name),
+        size: entry.size,
+        compressed_size: Some(entry.compressed_size),
+        mode: None,
+        mtime: entry
+            .has_last_modified_date
+            .then(|| entry.last_modified_date.to_unix_time()),
+        is_dir: entry.is_directory,
+        is_symlink: false,
+        link_target: None,
+        uid: None,
+        gid: None,
     }
 }
 
 impl Extractor for SevenZExtractor {
-    fn entries(&self, _source: &Path) -> Result<Box<dyn Iterator<Item = Result<ArchiveEntry>>>> {
-        // Note: 7z doesn't provide a way to list entries without extracting
-        // For now, we'll return an error indicating this operation is not supported
-        Err(Error::UnsupportedOperation(
-            "Listing entries for 7z archives is not currently supported".to_string(),
-        ))
+    fn entries(&self, source: &Path) -> Result<Box<dyn Iterator<Item = Result<ArchiveEntry>>>> {
+        let archive = open_archive(source, &Password::empty())?;
+        let entries: Vec<_> = archive
+            .files
+            .iter()
+            .map(|file| Ok(sevenz_entry(file)))
+            .collect();
+        Ok(Box::new(entries.into_iter()))
     }
 
     fn extract_entry(
         &self,
-        _source: &Path,
-        _entry: &ArchiveEntry,
-        _destination: &Path,
-        _options: ExtractEntryOptions,
+        source: &Path,
+        entry: &ArchiveEntry,
+        destination: &Path,
+        options: ExtractEntryOptions,
     ) -> Result<()> {
-        // Note: 7z doesn't support extracting individual entries
-        Err(Error::UnsupportedOperation(
-            "Extracting individual entries from 7z archives is not currently supported".to_string(),
-        ))
+        let password = password(&options);
+        let archive = open_archive(source, &password)?;
+        let index = find_file(&archive, &entry.path)?;
+        let Some(full_path) = options.output_path(destination, &entry.path)? else {
+            return Ok(());
+        };
+
+        if archive.files[index].is_directory {
+            fs::create_dir_all(&full_path)?;
+            return Ok(());
+        }
+
+        // Check if file exists and handle according to options
+        if full_path.exists() && !options.overwrite {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("File already exists: {:?}", full_path),
+            )));
+        }
+
+        // Create parent directory if needed
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut output_file = File::create(&full_path)?;
+        self.copy_entry(source, &archive, index, &password, &mut output_file)?;
+
+        // Set timestamps if requested
+        if options.preserve_timestamps {
+            if let Some(mtime) = entry.mtime {
+                let mtime = filetime::FileTime::from_unix_time(mtime, 0);
+                filetime::set_file_mtime(&full_path, mtime)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn open_entry(&self, source: &Path, entry: &ArchiveEntry) -> Result<Box<dyn Read + Send>> {
+        let password = Password::empty();
+        let archive = open_archive(source, &password)?;
+        let index = find_file(&archive, &entry.path)?;
+        if archive.files[index].is_directory {
+            return Err(Error::InvalidPath(format!(
+                "Not a regular file: {}",
+                entry.path.display()
+            )));
+        }
+
+        let Some(block) = archive.stream_map.file_folder_index[index] else {
+            return Ok(Box::new(io::empty()));
+        };
+        if let Some(decoded) = self.decoded_block(source, &archive, block, &password)? {
+            return Ok(Box::new(io::Cursor::new(decoded.entry(index).to_vec())));
+        }
+
+        // Too large to keep: decode it up to the entry as it is read
+        let source = source.to_path_buf();
+        stream_entry(move |writer| {
+            copy_from_block(&source, &archive, block, index, &password, writer)
+        })
     }
 
     fn format_name(&self) -> &'static str {
@@ -79,7 +349,7 @@ pub fn extract_7z_fallback<P: AsRef<Path>, Q: AsRef<Path>>(
                 }
 
                 let mut output_file = File::create(&path)?;
-                io::copy(reader, &mut output_file)?;
+                buffer::copy(reader, &mut output_file)?;
             }
 
             Ok(true) // Continue extraction
@@ -88,3 +358,41 @@ pub fn extract_7z_fallback<P: AsRef<Path>, Q: AsRef<Path>>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sevenz_rust::{SeqReader, SevenZWriter, SourceReader};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_copy_from_block_skips_earlier_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("solid.7z");
+        let files: [&[u8]; 3] = [b"one", b"two", b"three"];
+
+        let mut writer = SevenZWriter::create(&source).unwrap();
+        let entries = (0..files.len())
+            .map(|i| {
+                let mut entry = SevenZArchiveEntry::new();
+                entry.name = format!("file{}", i);
+                entry.has_stream = true;
+                entry
+            })
+            .collect();
+        let readers = files.iter().map(|data| SourceReader::new(*data)).collect();
+        writer
+            .push_archive_entries(entries, SeqReader::new(readers))
+            .unwrap();
+        writer.finish().unwrap();
+
+        let password = Password::empty();
+        let archive = open_archive(&source, &password).unwrap();
+        assert_eq!(archive.folders.len(), 1);
+        for (index, data) in files.iter().enumerate().rev() {
+            let mut copied = Vec::new();
+            copy_from_block(&source, &archive, 0, index, &password, &mut copied).unwrap();
+            assert_eq!(&copied, data);
+        }
+    }
+}
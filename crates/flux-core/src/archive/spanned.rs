@@ -0,0 +1,700 @@
+//! Split zip archives, stored as volumes `name.z01`, `name.z02`, ...,
+//! `name.zip`
+//!
+//! This is the zip format's own splitting, not a file cut into pieces: the
+//! central directory records which volume ("disk") each entry starts on and
+//! its offset within that volume, and headers are never cut in two, so other
+//! zip tools can read the volumes. The zip crate only reads single-volume
+//! archives, so [`SpannedReader`] presents the volumes as one archive with a
+//! central directory rewritten to plain offsets.
+
+use crate::{buffer, Error, Result};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+/// Smallest volume size [`split_zip`] accepts, as with Info-ZIP
+pub const MIN_VOLUME_SIZE: u64 = 64 * 1024;
+
+/// Marks the first volume of a split archive
+const SPLIT_SIGNATURE: u32 = 0x0807_4b50;
+/// Marks an archive written for splitting that ended up in one volume
+const SINGLE_SEGMENT_SIGNATURE: u32 = 0x3030_4b50;
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+const ZIP64_EOCD_SIGNATURE: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const ZIP64_EXTRA_ID: u16 = 0x0001;
+
+const EOCD_LEN: usize = 22;
+const ZIP64_EOCD_LEN: usize = 56;
+const ZIP64_LOCATOR_LEN: usize = 20;
+
+/// Volumes of the split archive whose last volume is `path`, in order, or
+/// `None` when `path` is an ordinary zip
+///
+/// An archive is split when its end of central directory is on a later
+/// volume than the first; the volumes before it are `.z01` onwards, and
+/// any others next to it are ignored.
+pub fn volumes(path: &Path) -> Option<Vec<PathBuf>> {
+    let extension = path.extension()?.to_str()?;
+    if !extension.eq_ignore_ascii_case("zip") {
+        return None;
+    }
+    let prefix = if extension == "ZIP" { "Z" } else { "z" };
+    let volume = |number: u32| path.with_extension(format!("{}{:02}", prefix, number));
+
+    // Cheap check first, as most zips are not split
+    if !volume(1).is_file() {
+        return None;
+    }
+    let mut file = File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let last_disk = last_disk(&mut file, len).ok()?;
+    if last_disk == 0 {
+        return None;
+    }
+    let mut volumes: Vec<PathBuf> = (1..=last_disk).map(volume).collect();
+    volumes.push(path.to_path_buf());
+    Some(volumes)
+}
+
+fn u16_at(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn u64_at(bytes: &[u8], at: usize) -> u64 {
+    u64::from_le_bytes(bytes[at..at + 8].try_into().unwrap())
+}
+
+fn corrupt(message: impl Into<String>) -> Error {
+    Error::Zip(format!("invalid split archive: {}", message.into()))
+}
+
+/// The end of `reader`, which is `len` bytes long, and where the end of
+/// central directory record starts in it
+fn read_tail<R: Read + Seek>(reader: &mut R, len: u64) -> Result<(Vec<u8>, usize)> {
+    // The record is followed by at most a 64 KiB comment
+    let tail_len = len.min((EOCD_LEN + u16::MAX as usize) as u64);
+    let mut tail = vec![0; tail_len as usize];
+    reader.seek(SeekFrom::Start(len - tail_len))?;
+    reader.read_exact(&mut tail)?;
+
+    let at = (0..=tail.len().saturating_sub(EOCD_LEN))
+        .rev()
+        .find(|&at| u32_at(&tail, at) == EOCD_SIGNATURE)
+        .ok_or_else(|| corrupt("no end of central directory record"))?;
+    Ok((tail, at))
+}
+
+/// Number of the volume `reader` is, counting from 0, as its end of central
+/// directory records it
+fn last_disk<R: Read + Seek>(reader: &mut R, len: u64) -> Result<u32> {
+    let (tail, at) = read_tail(reader, len)?;
+    match u16_at(&tail, at + 4) {
+        // Saturated; the zip64 locator holds the number of volumes
+        u16::MAX
+            if at >= ZIP64_LOCATOR_LEN
+                && u32_at(&tail, at - ZIP64_LOCATOR_LEN) == ZIP64_LOCATOR_SIGNATURE =>
+        {
+            let total = u32_at(&tail, at - ZIP64_LOCATOR_LEN + 16);
+            Ok(total.saturating_sub(1))
+        }
+        disk => Ok(u32::from(disk)),
+    }
+}
+
+/// What the end of central directory says, taken from the zip64 record
+/// when there is one
+#[derive(Debug)]
+struct Directory {
+    /// Volume holding the start of the central directory
+    disk: u32,
+    entries: u64,
+    size: u64,
+    /// Offset of the central directory within `disk`
+    offset: u64,
+    comment: Vec<u8>,
+    /// Version made by and needed to extract, when there is a zip64 record
+    zip64_versions: Option<[u8; 4]>,
+}
+
+impl Directory {
+    /// Find the end of central directory at the end of `reader`, which is
+    /// `len` bytes long; `locate` turns a volume and offset into a position
+    /// in `reader`
+    fn read<R: Read + Seek>(
+        reader: &mut R,
+        len: u64,
+        locate: impl Fn(u32, u64) -> Result<u64>,
+    ) -> Result<Self> {
+        let (tail, at) = read_tail(reader, len)?;
+        let record = &tail[at..];
+        let comment_len = u16_at(record, 20) as usize;
+        let mut directory = Directory {
+            disk: u32::from(u16_at(record, 6)),
+            entries: u64::from(u16_at(record, 10)),
+            size: u64::from(u32_at(record, 12)),
+            offset: u64::from(u32_at(record, 16)),
+            comment: record[EOCD_LEN..(EOCD_LEN + comment_len).min(record.len())].to_vec(),
+            zip64_versions: None,
+        };
+
+        if at >= ZIP64_LOCATOR_LEN
+            && u32_at(&tail, at - ZIP64_LOCATOR_LEN) == ZIP64_LOCATOR_SIGNATURE
+        {
+            let locator = &tail[at - ZIP64_LOCATOR_LEN..at];
+            let position = locate(u32_at(locator, 4), u64_at(locator, 8))?;
+            let mut record = [0; ZIP64_EOCD_LEN];
+            reader.seek(SeekFrom::Start(position))?;
+            reader.read_exact(&mut record)?;
+            if u32_at(&record, 0) != ZIP64_EOCD_SIGNATURE {
+                return Err(corrupt("zip64 end of central directory not found"));
+            }
+            directory.disk = u32_at(&record, 20);
+            directory.entries = u64_at(&record, 32);
+            directory.size = u64_at(&record, 40);
+            directory.offset = u64_at(&record, 48);
+            directory.zip64_versions = Some(record[12..16].try_into().unwrap());
+        }
+        Ok(directory)
+    }
+
+    /// The records that end an archive whose central directory starts on
+    /// `disk` at `offset`, written to the last volume `last_disk` at
+    /// `position`, with `entries_here` directory entries on that volume
+    fn encode(
+        &self,
+        disk: u32,
+        offset: u64,
+        last_disk: u32,
+        position: u64,
+        entries_here: u64,
+    ) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        let clamp16 = |value: u64| value.min(u64::from(u16::MAX)) as u16;
+        let clamp32 = |value: u64| value.min(u64::from(u32::MAX)) as u32;
+
+        if let Some(versions) = self.zip64_versions {
+            out.extend_from_slice(&ZIP64_EOCD_SIGNATURE.to_le_bytes());
+            out.extend_from_slice(&((ZIP64_EOCD_LEN - 12) as u64).to_le_bytes());
+            out.extend_from_slice(&versions);
+            out.extend_from_slice(&last_disk.to_le_bytes());
+            out.extend_from_slice(&disk.to_le_bytes());
+            out.extend_from_slice(&entries_here.to_le_bytes());
+            out.extend_from_slice(&self.entries.to_le_bytes());
+            out.extend_from_slice(&self.size.to_le_bytes());
+            out.extend_from_slice(&offset.to_le_bytes());
+
+            out.extend_from_slice(&ZIP64_LOCATOR_SIGNATURE.to_le_bytes());
+            out.extend_from_slice(&last_disk.to_le_bytes());
+            out.extend_from_slice(&position.to_le_bytes());
+            out.extend_from_slice(&(last_disk + 1).to_le_bytes());
+        } else if last_disk >= u32::from(u16::MAX)
+            || self.entries >= u64::from(u16::MAX)
+            || offset >= u64::from(u32::MAX)
+        {
+            return Err(Error::UnsupportedOperation(
+                "Split archive too large for a zip without zip64 records".to_string(),
+            ));
+        }
+
+        out.extend_from_slice(&EOCD_SIGNATURE.to_le_bytes());
+        out.extend_from_slice(&clamp16(u64::from(last_disk)).to_le_bytes());
+        out.extend_from_slice(&clamp16(u64::from(disk)).to_le_bytes());
+        out.extend_from_slice(&clamp16(entries_here).to_le_bytes());
+        out.extend_from_slice(&clamp16(self.entries).to_le_bytes());
+        out.extend_from_slice(&clamp32(self.size).to_le_bytes());
+        out.extend_from_slice(&clamp32(offset).to_le_bytes());
+        out.extend_from_slice(&(self.comment.len() as u16).to_le_bytes());
+        out.extend_from_slice(&self.comment);
+        Ok(out)
+    }
+}
+
+/// A central directory header, with its name, extra field and comment
+struct CentralHeader {
+    raw: Vec<u8>,
+}
+
+impl CentralHeader {
+    fn read<R: Read>(reader: &mut R) -> Result<Self> {
+        let mut raw = vec![0; 46];
+        reader.read_exact(&mut raw)?;
+        if u32_at(&raw, 0) != CENTRAL_HEADER_SIGNATURE {
+            return Err(corrupt("bad central directory header"));
+        }
+        let variable =
+            u16_at(&raw, 28) as usize + u16_at(&raw, 30) as usize + u16_at(&raw, 32) as usize;
+        raw.resize(46 + variable, 0);
+        reader.read_exact(&mut raw[46..])?;
+        Ok(Self { raw })
+    }
+
+    /// Positions within `raw` of the zip64 offset and disk values, for the
+    /// fields that are too large for the fixed header
+    fn zip64_fields(&self) -> (Option<usize>, Option<usize>) {
+        let name_len = u16_at(&self.raw, 28) as usize;
+        let extra_len = u16_at(&self.raw, 30) as usize;
+        let mut at = 46 + name_len;
+        let end = at + extra_len;
+        while at + 4 <= end {
+            let id = u16_at(&self.raw, at);
+            let size = u16_at(&self.raw, at + 2) as usize;
+            if id == ZIP64_EXTRA_ID {
+                // Only the values saturated in the fixed header are present,
+                // in this order
+                let mut field = at + 4;
+                for at in [24, 20] {
+                    if u32_at(&self.raw, at) == u32::MAX {
+                        field += 8;
+                    }
+                }
+                let offset = (u32_at(&self.raw, 42) == u32::MAX).then(|| {
+                    field += 8;
+                    field - 8
+                });
+                let disk = (u16_at(&self.raw, 34) == u16::MAX).then_some(field);
+                return (offset, disk);
+            }
+            at += 4 + size;
+        }
+        (None, None)
+    }
+
+    fn disk(&self) -> u32 {
+        match self.zip64_fields().1 {
+            Some(at) => u32_at(&self.raw, at),
+            None => u32::from(u16_at(&self.raw, 34)),
+        }
+    }
+
+    fn offset(&self) -> u64 {
+        match self.zip64_fields().0 {
+            Some(at) => u64_at(&self.raw, at),
+            None => u64::from(u32_at(&self.raw, 42)),
+        }
+    }
+
+    fn set_location(&mut self, disk: u32, offset: u64) -> Result<()> {
+        let (offset_at, disk_at) = self.zip64_fields();
+        match disk_at {
+            Some(at) => self.raw[at..at + 4].copy_from_slice(&disk.to_le_bytes()),
+            None => {
+                let disk = u16::try_from(disk)
+                    .ok()
+                    .filter(|&disk| disk != u16::MAX)
+                    .ok_or_else(|| {
+                        Error::UnsupportedOperation("Too many volumes for this entry".to_string())
+                    })?;
+                self.raw[34..36].copy_from_slice(&disk.to_le_bytes());
+            }
+        }
+        match offset_at {
+            Some(at) => self.raw[at..at + 8].copy_from_slice(&offset.to_le_bytes()),
+            None => {
+                let offset = u32::try_from(offset)
+                    .ok()
+                    .filter(|&offset| offset != u32::MAX)
+                    .ok_or_else(|| {
+                        Error::UnsupportedOperation(
+                            "Entry offset needs zip64, which the archive does not use".to_string(),
+                        )
+                    })?;
+                self.raw[42..46].copy_from_slice(&offset.to_le_bytes());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Volume files read back to back
+struct Concatenated {
+    files: Vec<File>,
+    /// Where each volume starts
+    starts: Vec<u64>,
+    len: u64,
+    position: u64,
+}
+
+impl Concatenated {
+    fn open(paths: &[PathBuf]) -> Result<Self> {
+        let mut files = Vec::with_capacity(paths.len());
+        let mut starts = Vec::with_capacity(paths.len());
+        let mut len = 0;
+        for path in paths {
+            let file = File::open(path)?;
+            starts.push(len);
+            len += file.metadata()?.len();
+            files.push(file);
+        }
+        Ok(Self {
+            files,
+            starts,
+            len,
+            position: 0,
+        })
+    }
+
+    /// Position of `offset` within volume `disk`
+    fn locate(starts: &[u64], disk: u32, offset: u64) -> Result<u64> {
+        starts
+            .get(disk as usize)
+            .map(|start| start + offset)
+            .ok_or_else(|| corrupt(format!("volume {} is missing", disk + 1)))
+    }
+}
+
+impl Read for Concatenated {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+        let volume = self.starts.partition_point(|&start| start <= self.position) - 1;
+        let end = self.starts.get(volume + 1).copied().unwrap_or(self.len);
+        let available = (end - self.position).min(buf.len() as u64) as usize;
+        let file = &mut self.files[volume];
+        file.seek(SeekFrom::Start(self.position - self.starts[volume]))?;
+        let read = file.read(&mut buf[..available])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for Concatenated {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = seek_position(pos, self.position, self.len)?;
+        Ok(self.position)
+    }
+}
+
+fn seek_position(pos: SeekFrom, current: u64, len: u64) -> io::Result<u64> {
+    let target = match pos {
+        SeekFrom::Start(offset) => Some(offset),
+        SeekFrom::End(delta) => len.checked_add_signed(delta),
+        SeekFrom::Current(delta) => current.checked_add_signed(delta),
+    };
+    target.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "seek before the start of the archive",
+        )
+    })
+}
+
+/// The volumes of a split zip read as one single-volume archive
+///
+/// Entry data is read from the volumes in place; only the central
+/// directory, rewritten to offsets in the joined archive, is held in memory.
+pub struct SpannedReader {
+    volumes: Concatenated,
+    /// Bytes of the first volume before the first entry, the split marker
+    skip: u64,
+    /// Length of the entry data, where the rewritten directory starts
+    data_len: u64,
+    directory: Vec<u8>,
+    position: u64,
+}
+
+impl SpannedReader {
+    /// Open the split archive made of `volumes`, as listed by [`volumes`]
+    pub fn open(volumes: &[PathBuf]) -> Result<Self> {
+        let mut concatenated = Concatenated::open(volumes)?;
+        let starts = concatenated.starts.clone();
+        let len = concatenated.len;
+
+        let mut marker = [0; 4];
+        concatenated.seek(SeekFrom::Start(0))?;
+        concatenated.read_exact(&mut marker)?;
+        let skip = match u32::from_le_bytes(marker) {
+            SPLIT_SIGNATURE | SINGLE_SEGMENT_SIGNATURE => 4,
+            _ => 0,
+        };
+        // Position in the joined archive of an offset within a volume
+        let joined = |disk: u32, offset: u64| -> Result<u64> {
+            Concatenated::locate(&starts, disk, offset)?
+                .checked_sub(skip)
+                .ok_or_else(|| corrupt("entry inside the split marker"))
+        };
+
+        let directory = Directory::read(&mut concatenated, len, |disk, offset| {
+            Concatenated::locate(&starts, disk, offset)
+        })?;
+        let data_len = joined(directory.disk, directory.offset)?;
+
+        concatenated.seek(SeekFrom::Start(data_len + skip))?;
+        let mut reader = io::BufReader::new(&mut concatenated);
+        let mut headers = Vec::new();
+        for _ in 0..directory.entries {
+            let mut header = CentralHeader::read(&mut reader)?;
+            let offset = joined(header.disk(), header.offset())?;
+            header.set_location(0, offset)?;
+            headers.extend_from_slice(&header.raw);
+        }
+        drop(reader);
+        let end = directory.encode(
+            0,
+            data_len,
+            0,
+            data_len + headers.len() as u64,
+            directory.entries,
+        )?;
+        headers.extend_from_slice(&end);
+
+        debug!(
+            "Joined {} volumes: {} entries, {} bytes of data",
+            volumes.len(),
+            directory.entries,
+            data_len
+        );
+        Ok(Self {
+            volumes: concatenated,
+            skip,
+            data_len,
+            directory: headers,
+            position: 0,
+        })
+    }
+
+    fn len(&self) -> u64 {
+        self.data_len + self.directory.len() as u64
+    }
+}
+
+impl Read for SpannedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = if self.position < self.data_len {
+            let available = (self.data_len - self.position).min(buf.len() as u64) as usize;
+            self.volumes
+                .seek(SeekFrom::Start(self.position + self.skip))?;
+            self.volumes.read(&mut buf[..available])?
+        } else {
+            let start = ((self.position - self.data_len) as usize).min(self.directory.len());
+            let mut rest = &self.directory[start..];
+            rest.read(buf)?
+        };
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for SpannedReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = seek_position(pos, self.position, self.len())?;
+        Ok(self.position)
+    }
+}
+
+/// Writes volumes, starting a new one when the current one is full
+struct VolumeWriter {
+    base: PathBuf,
+    volume_size: u64,
+    disk: u32,
+    written: u64,
+    file: BufWriter<File>,
+    paths: Vec<PathBuf>,
+}
+
+impl VolumeWriter {
+    fn create(base: &Path, volume_size: u64) -> Result<Self> {
+        let path = Self::volume_path(base, 0);
+        Ok(Self {
+            base: base.to_path_buf(),
+            volume_size,
+            disk: 0,
+            written: 0,
+            file: BufWriter::new(File::create(&path)?),
+            paths: vec![path],
+        })
+    }
+
+    fn volume_path(base: &Path, disk: u32) -> PathBuf {
+        base.with_extension(format!("z{:02}", disk + 1))
+    }
+
+    fn next_volume(&mut self) -> Result<()> {
+        self.file.flush()?;
+        self.disk += 1;
+        let path = Self::volume_path(&self.base, self.disk);
+        self.file = BufWriter::new(File::create(&path)?);
+        self.paths.push(path);
+        self.written = 0;
+        Ok(())
+    }
+
+    /// Make room for `len` bytes that must stay in one volume and return
+    /// where they will start
+    fn reserve(&mut self, len: u64) -> Result<(u32, u64)> {
+        if len > self.volume_size {
+            return Err(Error::Other(format!(
+                "A {} byte zip header does not fit in {} byte volumes",
+                len, self.volume_size
+            )));
+        }
+        if self.written + len > self.volume_size {
+            self.next_volume()?;
+        }
+        Ok((self.disk, self.written))
+    }
+
+    /// Flush the last volume and give it the archive's own name
+    fn finish(mut self) -> Result<Vec<PathBuf>> {
+        self.file.flush()?;
+        drop(self.file);
+        let last = self.paths.pop().expect("at least one volume is written");
+        fs::rename(&last, &self.base)?;
+        self.paths.push(self.base);
+        Ok(self.paths)
+    }
+}
+
+impl Write for VolumeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.written == self.volume_size {
+            self.next_volume().map_err(io::Error::other)?;
+        }
+        let room = (self.volume_size - self.written).min(buf.len() as u64) as usize;
+        let written = self.file.write(&buf[..room])?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Split the zip `archive` into volumes of at most `volume_size` bytes
+///
+/// The volumes are written next to `archive` as `.z01`, `.z02`, ... and the
+/// last one replaces `archive`, which must be named `.zip`. An archive that
+/// already fits is left alone. Returns the volumes in order.
+pub fn split_zip(archive: &Path, volume_size: u64) -> Result<Vec<PathBuf>> {
+    if volume_size < MIN_VOLUME_SIZE {
+        return Err(Error::Other(format!(
+            "Volume size must be at least {} bytes, got {}",
+            MIN_VOLUME_SIZE, volume_size
+        )));
+    }
+    let is_zip = archive
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"));
+    if !is_zip {
+        return Err(Error::InvalidPath(format!(
+            "Split archives must be named .zip: {}",
+            archive.display()
+        )));
+    }
+
+    let mut source = File::open(archive)?;
+    let len = source.metadata()?.len();
+    if len <= volume_size {
+        remove_volumes(archive, 0)?;
+        return Ok(vec![archive.to_path_buf()]);
+    }
+
+    let directory = Directory::read(&mut source, len, |_, offset| Ok(offset))?;
+    source.seek(SeekFrom::Start(directory.offset))?;
+    let mut headers = Vec::new();
+    {
+        let mut reader = io::BufReader::new(&mut source);
+        for _ in 0..directory.entries {
+            headers.push(CentralHeader::read(&mut reader)?);
+        }
+    }
+    // Entries are copied in the order they are stored
+    let mut order: Vec<usize> = (0..headers.len()).collect();
+    order.sort_by_key(|&index| headers[index].offset());
+
+    let mut writer = VolumeWriter::create(archive, volume_size)?;
+    writer.write_all(&SPLIT_SIGNATURE.to_le_bytes())?;
+
+    for (position, &index) in order.iter().enumerate() {
+        let start = headers[index].offset();
+        let end = order
+            .get(position + 1)
+            .map(|&next| headers[next].offset())
+            .unwrap_or(directory.offset);
+
+        let mut local = [0; 30];
+        source.seek(SeekFrom::Start(start))?;
+        source.read_exact(&mut local)?;
+        if u32_at(&local, 0) != LOCAL_HEADER_SIGNATURE {
+            return Err(corrupt("bad local header"));
+        }
+        let header_len = 30 + u64::from(u16_at(&local, 26)) + u64::from(u16_at(&local, 28));
+        if start + header_len > end {
+            return Err(corrupt("overlapping entries"));
+        }
+
+        let (disk, offset) = writer.reserve(header_len)?;
+        headers[index].set_location(disk, offset)?;
+        source.seek(SeekFrom::Start(start))?;
+        buffer::copy(&mut (&mut source).take(header_len), &mut writer.file)?;
+        writer.written += header_len;
+        // Entry data may run on into the next volume
+        buffer::copy(
+            &mut (&mut source).take(end - start - header_len),
+            &mut writer,
+        )?;
+    }
+
+    // The volume each directory header ends up on
+    let mut directory_disks = Vec::with_capacity(headers.len());
+    let mut directory_start = None;
+    for header in &headers {
+        let (disk, offset) = writer.reserve(header.raw.len() as u64)?;
+        directory_start.get_or_insert((disk, offset));
+        directory_disks.push(disk);
+        writer.file.write_all(&header.raw)?;
+        writer.written += header.raw.len() as u64;
+    }
+    let (directory_disk, directory_offset) =
+        directory_start.unwrap_or((writer.disk, writer.written));
+
+    // Reserve the worst case so the end records land on a known volume
+    let end_len = (ZIP64_EOCD_LEN + ZIP64_LOCATOR_LEN + EOCD_LEN + directory.comment.len()) as u64;
+    let (last_disk, position) = writer.reserve(end_len)?;
+    let entries_here = directory_disks
+        .iter()
+        .filter(|&&disk| disk == last_disk)
+        .count() as u64;
+    let end = directory.encode(
+        directory_disk,
+        directory_offset,
+        last_disk,
+        position,
+        entries_here,
+    )?;
+    writer.file.write_all(&end)?;
+    writer.written += end.len() as u64;
+
+    drop(source);
+    let paths = writer.finish()?;
+    // The last volume was renamed, so the next number up starts the stale
+    // ones
+    remove_volumes(archive, paths.len() as u32)?;
+
+    info!("Split {:?} into {} volumes", archive, paths.len());
+    Ok(paths)
+}
+
+/// Remove the volumes of `archive` from volume index `first` on, left over
+/// from an earlier split into more volumes
+fn remove_volumes(archive: &Path, first: u32) -> Result<()> {
+    let stale: Vec<PathBuf> = (first..)
+        .map(|disk| VolumeWriter::volume_path(archive, disk))
+        .take_while(|path| path.is_file())
+        .collect();
+    for path in stale {
+        debug!("Removing stale volume {:?}", path);
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,224 @@
+//! Summary statistics of an archive
+//!
+//! [`inspect_stats`] reads only the archive's metadata. It never decrypts
+//! anything, so encrypted zip entries and 7z archives can be described
+//! without a password.
+
+use super::detect::detect_format;
+use super::inspect;
+use super::meta::{read_tar_metadata, ArchiveMetadata};
+use super::modify::tar_reader;
+use crate::{Error, Result};
+use sevenz_rust::SevenZMethod;
+use std::fs::File;
+use std::io::{Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+use zip::ZipArchive;
+
+/// Extensions of detached signatures looked for next to an archive
+pub const SIGNATURE_EXTENSIONS: &[&str] = &["sig", "asc", "minisig"];
+
+/// Overview of an archive's contents
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArchiveStats {
+    /// Format such as `tar.zst`, `zip` or `7z`
+    pub format: String,
+    /// Compression methods used, e.g. `Deflated` or `LZMA2`
+    pub methods: Vec<String>,
+    /// Size of the archive file
+    pub archive_size: u64,
+    /// Total size of the entries once extracted
+    pub total_size: u64,
+    /// Regular files
+    pub files: u64,
+    /// Directories
+    pub directories: u64,
+    /// Symbolic links
+    pub symlinks: u64,
+    /// Entries that need a password to read
+    pub encrypted_entries: u64,
+    /// The entry list itself is encrypted, so no entries could be counted
+    pub encrypted_headers: bool,
+    /// Archive comment, if it has a non-empty one other than the metadata
+    pub comment: Option<String>,
+    /// Where the archive came from, if it says
+    pub metadata: Option<ArchiveMetadata>,
+    /// Detached signature found next to the archive (not verified)
+    pub signature: Option<PathBuf>,
+}
+
+impl ArchiveStats {
+    /// Total number of entries
+    pub fn entries(&self) -> u64 {
+        self.files + self.directories + self.symlinks
+    }
+
+    /// Archive size as a fraction of the extracted size
+    ///
+    /// `None` when nothing was counted, e.g. for an empty archive.
+    pub fn ratio(&self) -> Option<f64> {
+        (self.total_size > 0).then(|| self.archive_size as f64 / self.total_size as f64)
+    }
+
+    /// Whether any part of the archive needs a password
+    pub fn is_encrypted(&self) -> bool {
+        self.encrypted_headers || self.encrypted_entries > 0
+    }
+}
+
+/// Collect statistics about `archive` without extracting it
+pub fn inspect_stats<P: AsRef<Path>>(archive: P) -> Result<ArchiveStats> {
+    let archive = archive.as_ref();
+    debug!("Collecting statistics for {:?}", archive);
+
+    let format = detect_format(archive)?;
+
+    let mut stats = match format {
+        "zip" => zip_stats(archive)?,
+        "7z" => sevenz_stats(archive)?,
+        _ => tar_stats(archive, format)?,
+    };
+    stats.format = format.to_string();
+    stats.archive_size = archive.metadata()?.len();
+    stats.signature = SIGNATURE_EXTENSIONS.iter().find_map(|ext| {
+        let mut name = archive.as_os_str().to_owned();
+        name.push(format!(".{}", ext));
+        let path = PathBuf::from(name);
+        path.is_file().then_some(path)
+    });
+
+    Ok(stats)
+}
+
+fn tar_stats(archive: &Path, format: &str) -> Result<ArchiveStats> {
+    let mut stats = ArchiveStats {
+        methods: vec![match format {
+            "tar.gz" => "Gzip",
+            "tar.zst" => "Zstd",
+            "tar.xz" => "Xz",
+            "tar.br" => "Brotli",
+            _ => "Stored",
+        }
+        .to_string()],
+        metadata: read_tar_metadata(tar_reader(archive, format)?)?,
+        ..ArchiveStats::default()
+    };
+
+    for entry in inspect(archive)? {
+        if entry.is_dir {
+            stats.directories += 1;
+        } else if entry.is_symlink {
+            stats.symlinks += 1;
+        } else {
+            stats.files += 1;
+            stats.total_size += entry.size;
+        }
+    }
+
+    Ok(stats)
+}
+
+fn zip_stats(archive: &Path) -> Result<ArchiveStats> {
+    let mut zip = ZipArchive::new(File::open(archive)?)?;
+    let metadata = ArchiveMetadata::from_comment(zip.comment());
+    let mut stats = ArchiveStats {
+        comment: comment(zip.comment()).filter(|_| metadata.is_none()),
+        metadata,
+        ..ArchiveStats::default()
+    };
+
+    for i in 0..zip.len() {
+        // Raw access reads metadata without decrypting encrypted entries
+        let entry = zip.by_index_raw(i)?;
+        if entry.is_dir() {
+            stats.directories += 1;
+        } else if entry.is_symlink() {
+            stats.symlinks += 1;
+        } else {
+            stats.files += 1;
+            stats.total_size += entry.size();
+        }
+        if entry.encrypted() {
+            stats.encrypted_entries += 1;
+        }
+
+        let method = format!("{:?}", entry.compression());
+        if !entry.is_dir() && !stats.methods.contains(&method) {
+            stats.methods.push(method);
+        }
+    }
+
+    Ok(stats)
+}
+
+fn sevenz_stats(archive: &Path) -> Result<ArchiveStats> {
+    let mut file = File::open(archive)?;
+    let len = file.seek(SeekFrom::End(0))?;
+    file.seek(SeekFrom::Start(0))?;
+
+    let sz = match sevenz_rust::Archive::read(&mut file, len, &[]) {
+        Ok(sz) => sz,
+        Err(sevenz_rust::Error::PasswordRequired) => {
+            return Ok(ArchiveStats {
+                encrypted_headers: true,
+                ..ArchiveStats::default()
+            })
+        }
+        Err(e) => {
+            return Err(Error::ArchiveError(format!(
+                "Failed to read 7z archive: {}",
+                e
+            )))
+        }
+    };
+
+    let mut stats = ArchiveStats::default();
+    let mut encrypted_folders = Vec::new();
+    for folder in &sz.folders {
+        let mut encrypted = false;
+        for coder in &folder.coders {
+            let id = coder.decompression_method_id();
+            if id == SevenZMethod::ID_AES256SHA256 {
+                encrypted = true;
+                continue;
+            }
+            let name = SevenZMethod::by_id(id)
+                .map(|method| method.name().to_string())
+                .unwrap_or_else(|| format!("{:02x?}", id));
+            if !stats.methods.contains(&name) {
+                stats.methods.push(name);
+            }
+        }
+        encrypted_folders.push(encrypted);
+    }
+
+    for (index, entry) in sz.files.iter().enumerate() {
+        if entry.is_anti_item {
+            continue;
+        }
+        if entry.is_directory {
+            stats.directories += 1;
+            continue;
+        }
+        stats.files += 1;
+        stats.total_size += entry.size;
+
+        let folder = sz
+            .stream_map
+            .file_folder_index
+            .get(index)
+            .copied()
+            .flatten();
+        if folder.is_some_and(|folder| encrypted_folders.get(folder) == Some(&true)) {
+            stats.encrypted_entries += 1;
+        }
+    }
+
+    Ok(stats)
+}
+
+fn comment(bytes: &[u8]) -> Option<String> {
+    let comment = String::from_utf8_lossy(bytes).trim().to_string();
+    (!comment.is_empty()).then_some(comment)
+}
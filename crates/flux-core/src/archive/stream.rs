@@ -0,0 +1,669 @@
+//! Listing and extraction from readers instead of archive files
+//!
+//! [`extract_from_reader`] is meant for untrusted input that is already in
+//! memory or arriving over a stream. Unlike the path-based extractors, every
+//! entry goes through the checks in [`crate::security`]: paths must stay inside
+//! the output directory, symlinks may not point outside it or be written
+//! through, and the number of entries and bytes actually written are capped.
+
+use super::detect::parse_format;
+use super::extractor::ArchiveEntry;
+use super::links::{create_symlink, SymlinkFallback};
+use super::meta::is_global_header;
+use super::tar_extractor::tar_entry;
+use super::zip_extractor::zip_entry;
+use crate::security::{
+    check_entry_count, check_extraction_size, validate_symlink, SecurityOptions,
+};
+use crate::{buffer, Error, Result};
+use sevenz_rust::{Password, SevenZReader};
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use tracing::{debug, info, warn};
+use zip::ZipArchive;
+
+/// Longest symlink target read from a zip entry
+const MAX_LINK_TARGET_LEN: u64 = 4096;
+
+/// Extract an archive of the given format from `reader` into `output_dir`
+///
+/// `format` is an extension such as `"tar.zst"`, `"zip"` or `"7z"`. Entries
+/// with unsafe paths, hard links and special files are skipped; limit
+/// violations and escaping symlinks abort the extraction with
+/// [`Error::SecurityError`].
+pub fn extract_from_reader<R: Read + Seek>(
+    reader: R,
+    format: &str,
+    output_dir: &Path,
+    options: &SecurityOptions,
+) -> Result<()> {
+    info!("Extracting {} stream to {:?}", format, output_dir);
+
+    fs::create_dir_all(output_dir)?;
+    let mut sink = EntrySink::new(output_dir, options);
+
+    match parse_format(format).unwrap_or(format) {
+        "tar" => extract_tar_stream(reader, &mut sink),
+        "tar.gz" => extract_tar_stream(flate2::read::GzDecoder::new(reader), &mut sink),
+        "tar.zst" => extract_tar_stream(zstd::stream::read::Decoder::new(reader)?, &mut sink),
+        "tar.xz" => extract_tar_stream(xz2::read::XzDecoder::new(reader), &mut sink),
+        "tar.br" => extract_tar_stream(brotli::Decompressor::new(reader, 4096), &mut sink),
+        "zip" => extract_zip_stream(reader, &mut sink),
+        "7z" => extract_7z_stream(reader, &mut sink),
+        _ => Err(Error::UnsupportedFormat(format.to_string())),
+    }?;
+
+    info!(
+        entries = sink.entries,
+        bytes = sink.written,
+        "Stream extraction complete"
+    );
+    Ok(())
+}
+
+/// List the entries of an archive of the given format read from `reader`,
+/// calling `on_entry` as each one is found
+///
+/// Zip and 7z are listed from the index at the end of the archive, so
+/// little more than that is read. A tar has to be read through, though the
+/// file data is skipped rather than decompressed into memory.
+pub fn list_from_reader<R, F>(reader: R, format: &str, mut on_entry: F) -> Result<Vec<ArchiveEntry>>
+where
+    R: Read + Seek,
+    F: FnMut(&ArchiveEntry),
+{
+    debug!("Listing {} stream", format);
+
+    let mut entries = Vec::new();
+    let mut push = |entry: ArchiveEntry| {
+        on_entry(&entry);
+        entries.push(entry);
+    };
+
+    match parse_format(format).unwrap_or(format) {
+        "tar" => list_tar_stream(reader, &mut push),
+        "tar.gz" => list_tar_stream(flate2::read::GzDecoder::new(reader), &mut push),
+        "tar.zst" => list_tar_stream(zstd::stream::read::Decoder::new(reader)?, &mut push),
+        "tar.xz" => list_tar_stream(xz2::read::XzDecoder::new(reader), &mut push),
+        "tar.br" => list_tar_stream(brotli::Decompressor::new(reader, 4096), &mut push),
+        "zip" => list_zip_stream(reader, &mut push),
+        "7z" => list_7z_stream(reader, &mut push),
+        _ => Err(Error::UnsupportedFormat(format.to_string())),
+    }?;
+
+    Ok(entries)
+}
+
+/// Write the file at `path` inside an archive of the given format read from
+/// `reader` to `writer`, returning the number of bytes written
+///
+/// Zip and 7z entries are found through the index at the end of the
+/// archive; a tar is read up to the entry. `path` is compared by its
+/// components, so `./docs/a.txt` in the archive is found as `docs/a.txt`.
+/// Fails with [`Error::NotFound`] when no file has that path.
+pub fn read_entry_from_reader<R: Read + Seek>(
+    reader: R,
+    format: &str,
+    path: &Path,
+    writer: &mut dyn Write,
+) -> Result<u64> {
+    debug!("Reading {:?} from {} stream", path, format);
+
+    let copied = match parse_format(format).unwrap_or(format) {
+        "tar" => read_tar_entry(reader, path, writer),
+        "tar.gz" => read_tar_entry(flate2::read::GzDecoder::new(reader), path, writer),
+        "tar.zst" => read_tar_entry(zstd::stream::read::Decoder::new(reader)?, path, writer),
+        "tar.xz" => read_tar_entry(xz2::read::XzDecoder::new(reader), path, writer),
+        "tar.br" => read_tar_entry(brotli::Decompressor::new(reader, 4096), path, writer),
+        "zip" => read_zip_entry(reader, path, writer),
+        "7z" => read_7z_entry(reader, path, writer),
+        _ => Err(Error::UnsupportedFormat(format.to_string())),
+    }?;
+
+    copied.ok_or_else(|| Error::NotFound(format!("{:?} is not a file in the archive", path)))
+}
+
+/// Whether the archive path `stored` names `wanted`, ignoring `.`
+/// components
+fn same_entry(stored: &Path, wanted: &Path) -> bool {
+    let normal = |path: &Path| {
+        path.components()
+            .filter(|component| !matches!(component, Component::CurDir | Component::RootDir))
+            .map(|component| component.as_os_str().to_owned())
+            .collect::<Vec<_>>()
+    };
+    normal(stored) == normal(wanted)
+}
+
+fn read_tar_entry<R: Read>(reader: R, path: &Path, writer: &mut dyn Write) -> Result<Option<u64>> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let entry_type = entry.header().entry_type();
+        if !(entry_type.is_file() || entry_type == tar::EntryType::Continuous) {
+            continue;
+        }
+        if same_entry(&entry.path()?, path) {
+            return Ok(Some(buffer::copy(&mut entry, writer)?));
+        }
+    }
+    Ok(None)
+}
+
+fn read_zip_entry<R: Read + Seek>(
+    reader: R,
+    path: &Path,
+    writer: &mut dyn Write,
+) -> Result<Option<u64>> {
+    let mut archive = ZipArchive::new(reader)?;
+    let found = (0..archive.len()).find(|&i| {
+        archive.by_index_raw(i).is_ok_and(|file| {
+            !file.is_dir() && !file.is_symlink() && same_entry(Path::new(file.name()), path)
+        })
+    });
+    let Some(index) = found else {
+        return Ok(None);
+    };
+    if archive.by_index_raw(index)?.encrypted() {
+        return Err(Error::PasswordRequired(path.display().to_string()));
+    }
+    let mut file = archive.by_index(index)?;
+    Ok(Some(buffer::copy(&mut file, writer)?))
+}
+
+fn read_7z_entry<R: Read + Seek>(
+    mut reader: R,
+    path: &Path,
+    writer: &mut dyn Write,
+) -> Result<Option<u64>> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+
+    let mut archive = SevenZReader::new(reader, len, Password::empty())
+        .map_err(|e| Error::ArchiveError(format!("Failed to open 7z archive: {}", e)))?;
+
+    // As when extracting, our errors are parked while the callback stops
+    let mut copied = None;
+    let mut failure = None;
+    archive
+        .for_each_entries(|entry, data| {
+            if entry.is_anti_item()
+                || entry.is_directory()
+                || !same_entry(Path::new(&entry.name), path)
+            {
+                return Ok(true);
+            }
+            match buffer::copy(data, writer) {
+                Ok(n) => copied = Some(n),
+                Err(e) => failure = Some(e),
+            }
+            Ok(false)
+        })
+        .map_err(|e| Error::ArchiveError(format!("Failed to read 7z archive: {}", e)))?;
+
+    match failure {
+        Some(e) => Err(e.into()),
+        None => Ok(copied),
+    }
+}
+
+fn list_tar_stream<R: Read>(reader: R, push: &mut dyn FnMut(ArchiveEntry)) -> Result<()> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries()? {
+        let entry = entry?;
+        if !is_global_header(entry.header()) {
+            push(tar_entry(&entry)?);
+        }
+    }
+    Ok(())
+}
+
+fn list_zip_stream<R: Read + Seek>(reader: R, push: &mut dyn FnMut(ArchiveEntry)) -> Result<()> {
+    let mut archive = ZipArchive::new(reader)?;
+    for i in 0..archive.len() {
+        // Raw access reads metadata without decrypting encrypted entries
+        push(zip_entry(&archive.by_index_raw(i)?));
+    }
+    Ok(())
+}
+
+fn list_7z_stream<R: Read + Seek>(mut reader: R, push: &mut dyn FnMut(ArchiveEntry)) -> Result<()> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+
+    let archive = SevenZReader::new(reader, len, Password::empty())
+        .map_err(|e| Error::ArchiveError(format!("Failed to open 7z archive: {}", e)))?;
+
+    for file in &archive.archive().files {
+        if file.is_anti_item {
+            continue;
+        }
+        push(ArchiveEntry {
+            path: PathBuf::from(&file.name),
+            size: file.size,
+            compressed_size: Some(file.compressed_size),
+            mode: None,
+            mtime: file
+                .has_last_modified_date
+                .then(|| file.last_modified_date.to_unix_time()),
+            is_dir: file.is_directory,
+            is_symlink: false,
+            link_target: None,
+            uid: None,
+            gid: None,
+        });
+    }
+    Ok(())
+}
+
+fn extract_tar_stream<R: Read>(reader: R, sink: &mut EntrySink) -> Result<()> {
+    let mut archive = tar::Archive::new(reader);
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if is_global_header(entry.header()) {
+            continue;
+        }
+        let path = entry.path()?.into_owned();
+        let entry_type = entry.header().entry_type();
+
+        if entry_type.is_dir() {
+            sink.dir(&path)?;
+        } else if entry_type.is_symlink() {
+            match entry.link_name()? {
+                Some(target) => sink.symlink(&path, &target)?,
+                None => warn!("Skipping symlink without target: {:?}", path),
+            }
+        } else if entry_type.is_file() || entry_type == tar::EntryType::Continuous {
+            let mode = entry.header().mode().ok();
+            sink.file(&path, &mut entry, mode)?;
+        } else {
+            warn!("Skipping unsupported {:?} entry: {:?}", entry_type, path);
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_zip_stream<R: Read + Seek>(reader: R, sink: &mut EntrySink) -> Result<()> {
+    let mut archive = ZipArchive::new(reader)?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let path = match file.enclosed_name() {
+            Some(path) => path,
+            None => {
+                warn!("Skipping entry with unsafe path: {:?}", file.name());
+                continue;
+            }
+        };
+
+        if file.is_dir() {
+            sink.dir(&path)?;
+        } else if file.is_symlink() {
+            let mut target = String::new();
+            file.by_ref()
+                .take(MAX_LINK_TARGET_LEN)
+                .read_to_string(&mut target)?;
+            sink.symlink(&path, Path::new(&target))?;
+        } else {
+            let mode = file.unix_mode();
+            sink.file(&path, &mut file, mode)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn extract_7z_stream<R: Read + Seek>(mut reader: R, sink: &mut EntrySink) -> Result<()> {
+    let len = reader.seek(SeekFrom::End(0))?;
+    reader.seek(SeekFrom::Start(0))?;
+
+    let mut archive = SevenZReader::new(reader, len, Password::empty())
+        .map_err(|e| Error::ArchiveError(format!("Failed to open 7z archive: {}", e)))?;
+
+    // The callback can only return 7z errors, so park ours and stop iterating
+    let mut failure = None;
+    archive
+        .for_each_entries(|entry, data| {
+            if entry.is_anti_item() {
+                return Ok(true);
+            }
+            let path = PathBuf::from(&entry.name);
+            let result = if entry.is_directory() {
+                sink.dir(&path)
+            } else {
+                sink.file(&path, data, None)
+            };
+            match result {
+                Ok(()) => Ok(true),
+                Err(e) => {
+                    failure = Some(e);
+                    Ok(false)
+                }
+            }
+        })
+        .map_err(|e| Error::ArchiveError(format!("Failed to extract 7z archive: {}", e)))?;
+
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Writes entries below `root` while enforcing the security options
+struct EntrySink<'a> {
+    root: &'a Path,
+    options: &'a SecurityOptions,
+    entries: u64,
+    written: u64,
+}
+
+impl<'a> EntrySink<'a> {
+    fn new(root: &'a Path, options: &'a SecurityOptions) -> Self {
+        Self {
+            root,
+            options,
+            entries: 0,
+            written: 0,
+        }
+    }
+
+    /// Counts the entry and maps it to a destination, or `None` to skip it
+    fn resolve(&mut self, path: &Path) -> Result<Option<PathBuf>> {
+        check_entry_count(self.entries, self.options.max_entries)?;
+        self.entries += 1;
+
+        let mut relative = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::Normal(name) => relative.push(name),
+                Component::CurDir => {}
+                _ => {
+                    warn!("Skipping entry with unsafe path: {:?}", path);
+                    return Ok(None);
+                }
+            }
+        }
+        if relative.as_os_str().is_empty() {
+            return Ok(None);
+        }
+
+        // An earlier entry may have planted a symlink where a directory is
+        // expected; writing through it could land anywhere
+        let mut current = self.root.to_path_buf();
+        for component in relative.parent().into_iter().flat_map(Path::components) {
+            current.push(component);
+            if fs::symlink_metadata(&current).is_ok_and(|meta| meta.file_type().is_symlink()) {
+                return Err(Error::SecurityError(format!(
+                    "Entry would be written through symlink {:?}: {:?}",
+                    current, path
+                )));
+            }
+        }
+
+        Ok(Some(self.root.join(relative)))
+    }
+
+    fn dir(&mut self, path: &Path) -> Result<()> {
+        if let Some(dest) = self.resolve(path)? {
+            debug!("Creating directory: {:?}", dest);
+            fs::create_dir_all(dest)?;
+        }
+        Ok(())
+    }
+
+    fn file(&mut self, path: &Path, data: &mut dyn Read, mode: Option<u32>) -> Result<()> {
+        let Some(dest) = self.resolve(path)? else {
+            return Ok(());
+        };
+        debug!("Extracting: {:?}", dest);
+
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if fs::symlink_metadata(&dest).is_ok() {
+            fs::remove_file(&dest).or_else(|_| fs::remove_dir_all(&dest))?;
+        }
+
+        // Read one byte past the budget so overruns are detected without
+        // trusting the size recorded in the archive
+        let remaining = self
+            .options
+            .max_extraction_size
+            .saturating_sub(self.written);
+        let mut output = File::create(&dest)?;
+        let copied = buffer::copy(&mut data.take(remaining.saturating_add(1)), &mut output)?;
+        if let Err(e) =
+            check_extraction_size(self.written, copied, self.options.max_extraction_size)
+        {
+            drop(output);
+            fs::remove_file(&dest).ok();
+            return Err(e);
+        }
+        self.written += copied;
+
+        #[cfg(unix)]
+        if let Some(mode) = mode {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&dest, fs::Permissions::from_mode(mode & 0o777))?;
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+
+        Ok(())
+    }
+
+    fn symlink(&mut self, path: &Path, target: &Path) -> Result<()> {
+        let Some(dest) = self.resolve(path)? else {
+            return Ok(());
+        };
+        validate_symlink(
+            self.root,
+            &dest,
+            target,
+            self.options.allow_external_symlinks,
+        )?;
+
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        create_symlink(self.root, &dest, target, SymlinkFallback::default())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Write};
+    use tempfile::TempDir;
+
+    fn tar_with(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, data) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_extract_tar_from_reader() {
+        let temp_dir = TempDir::new().unwrap();
+        let data = tar_with(&[("a.txt", b"hello"), ("sub/b.txt", b"world")]);
+
+        extract_from_reader(
+            Cursor::new(data),
+            "tar",
+            temp_dir.path(),
+            &SecurityOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(fs::read(temp_dir.path().join("a.txt")).unwrap(), b"hello");
+        assert_eq!(
+            fs::read(temp_dir.path().join("sub/b.txt")).unwrap(),
+            b"world"
+        );
+    }
+
+    #[test]
+    fn test_extract_zip_from_reader() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("z.txt", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"zipped").unwrap();
+        let data = zip.finish().unwrap().into_inner();
+
+        extract_from_reader(
+            Cursor::new(data),
+            "zip",
+            temp_dir.path(),
+            &SecurityOptions::default(),
+        )
+        .unwrap();
+
+        assert_eq!(fs::read(temp_dir.path().join("z.txt")).unwrap(), b"zipped");
+    }
+
+    #[test]
+    fn test_list_tar_from_reader() {
+        let data = tar_with(&[("a.txt", b"hello"), ("sub/b.txt", b"world!")]);
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), Default::default());
+        encoder.write_all(&data).unwrap();
+        let data = encoder.finish().unwrap();
+
+        let mut seen = 0;
+        let entries = list_from_reader(Cursor::new(data), "tar.gz", |_| seen += 1).unwrap();
+
+        assert_eq!(seen, 2);
+        assert_eq!(entries[0].path, PathBuf::from("a.txt"));
+        assert_eq!(entries[1].path, PathBuf::from("sub/b.txt"));
+        assert_eq!(entries[1].size, 6);
+    }
+
+    #[test]
+    fn test_list_zip_from_reader() {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default();
+        zip.add_directory("docs/", options).unwrap();
+        zip.start_file("docs/z.txt", options).unwrap();
+        zip.write_all(b"zipped").unwrap();
+        let data = zip.finish().unwrap().into_inner();
+
+        let entries = list_from_reader(Cursor::new(data), "zip", |_| {}).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].is_dir);
+        assert_eq!(entries[1].path, PathBuf::from("docs/z.txt"));
+        assert_eq!(entries[1].size, 6);
+    }
+
+    #[test]
+    fn test_read_entry_from_reader() {
+        let data = tar_with(&[("./a.txt", b"hello"), ("sub/b.txt", b"world")]);
+        let mut out = Vec::new();
+        let n = read_entry_from_reader(Cursor::new(&data), "tar", Path::new("sub/b.txt"), &mut out)
+            .unwrap();
+        assert_eq!((n, out.as_slice()), (5, &b"world"[..]));
+
+        out.clear();
+        read_entry_from_reader(Cursor::new(&data), "tar", Path::new("a.txt"), &mut out).unwrap();
+        assert_eq!(out, b"hello");
+
+        let err = read_entry_from_reader(Cursor::new(&data), "tar", Path::new("sub"), &mut out)
+            .unwrap_err();
+        assert!(matches!(err, Error::NotFound(_)));
+
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("docs/z.txt", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(b"zipped").unwrap();
+        let data = zip.finish().unwrap().into_inner();
+        out.clear();
+        read_entry_from_reader(Cursor::new(data), "zip", Path::new("docs/z.txt"), &mut out)
+            .unwrap();
+        assert_eq!(out, b"zipped");
+    }
+
+    #[test]
+    fn test_size_limit_counts_written_bytes() {
+        let temp_dir = TempDir::new().unwrap();
+        let data = tar_with(&[("a.txt", &[1u8; 100]), ("b.txt", &[2u8; 100])]);
+        let options = SecurityOptions {
+            max_extraction_size: 150,
+            ..Default::default()
+        };
+
+        let err =
+            extract_from_reader(Cursor::new(data), "tar", temp_dir.path(), &options).unwrap_err();
+
+        assert!(matches!(err, Error::SecurityError(_)));
+        assert!(temp_dir.path().join("a.txt").exists());
+        assert!(!temp_dir.path().join("b.txt").exists());
+    }
+
+    #[test]
+    fn test_entry_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let data = tar_with(&[("a", b"1"), ("b", b"2"), ("c", b"3")]);
+        let options = SecurityOptions {
+            max_entries: 2,
+            ..Default::default()
+        };
+
+        let err =
+            extract_from_reader(Cursor::new(data), "tar", temp_dir.path(), &options).unwrap_err();
+        assert!(matches!(err, Error::SecurityError(_)));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rejects_write_through_symlink() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("out");
+        let mut builder = tar::Builder::new(Vec::new());
+
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        builder
+            .append_link(&mut header, "link", "../outside")
+            .unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_size(4);
+        builder
+            .append_data(&mut header, "link/pwned", &b"evil"[..])
+            .unwrap();
+        let data = builder.into_inner().unwrap();
+
+        let options = SecurityOptions {
+            allow_external_symlinks: true,
+            ..Default::default()
+        };
+        fs::create_dir_all(temp_dir.path().join("outside")).unwrap();
+        let err = extract_from_reader(Cursor::new(data), "tar", &output, &options).unwrap_err();
+
+        assert!(matches!(err, Error::SecurityError(_)));
+        assert!(!temp_dir.path().join("outside/pwned").exists());
+    }
+
+    #[test]
+    fn test_unsupported_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let result = extract_from_reader(
+            Cursor::new(Vec::new()),
+            "rar",
+            temp_dir.path(),
+            &SecurityOptions::default(),
+        );
+        assert!(matches!(result, Err(Error::UnsupportedFormat(_))));
+        let result = list_from_reader(Cursor::new(Vec::new()), "rar", |_| {});
+        assert!(matches!(result, Err(Error::UnsupportedFormat(_))));
+    }
+}
@@ -0,0 +1,279 @@
+//! Checking extracted files against the hashes of a manifest
+//!
+//! A manifest saved when an archive was packed lists the hash of every
+//! file. Given one, extraction hashes each file as it is written, with the
+//! hash function the manifest was made with, and compares the result, so a
+//! restore shows whether the archive was altered since: a file whose
+//! content changed, or one the manifest never listed.
+//! Files the manifest lists but the archive lacks are not reported, as an
+//! incremental archive holds only what changed.
+
+use crate::archive::checksum::{HashAlgorithm, Hasher};
+use crate::archive::normalize::comparable;
+use crate::archive::OperationOutcome;
+use crate::manifest::Manifest;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::warn;
+
+/// What to do with a file that does not match the manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TamperPolicy {
+    /// Remove the file, or do not write it, and count the entry as failed
+    #[default]
+    Fail,
+    /// Keep the file and only report it
+    Flag,
+}
+
+impl TamperPolicy {
+    /// Lower-case name, as accepted by [`FromStr`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            TamperPolicy::Fail => "fail",
+            TamperPolicy::Flag => "flag",
+        }
+    }
+}
+
+impl FromStr for TamperPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "fail" => Ok(TamperPolicy::Fail),
+            "flag" => Ok(TamperPolicy::Flag),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unknown tamper policy: {} (expected fail or flag)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for TamperPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How an extracted file differs from the manifest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TamperKind {
+    /// Its content hashes to something else
+    Modified,
+    /// The manifest does not list it
+    Unlisted,
+}
+
+impl fmt::Display for TamperKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TamperKind::Modified => "content differs from the manifest",
+            TamperKind::Unlisted => "not listed in the manifest",
+        })
+    }
+}
+
+/// An entry that did not match the manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Tampered {
+    /// Entry name as extracted
+    pub path: PathBuf,
+    pub kind: TamperKind,
+}
+
+/// A manifest to check extracted files against, and what to do when one
+/// does not match
+#[derive(Clone)]
+pub struct ManifestCheck {
+    manifest: Arc<Manifest>,
+    policy: TamperPolicy,
+}
+
+impl ManifestCheck {
+    /// Check against `manifest`, handling files that do not match as
+    /// `policy` says
+    pub fn new(manifest: Manifest, policy: TamperPolicy) -> Self {
+        Self {
+            manifest: Arc::new(manifest),
+            policy,
+        }
+    }
+
+    /// The manifest checked against
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    /// What happens to files that do not match
+    pub fn policy(&self) -> TamperPolicy {
+        self.policy
+    }
+}
+
+/// Counts rather than every file, as options are logged
+impl fmt::Debug for ManifestCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ManifestCheck")
+            .field("files", &self.manifest.files.len())
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+/// The hashes of one [`ManifestCheck`], looked up by entry name
+pub(crate) struct HashLookup<'a> {
+    hashes: HashMap<Cow<'a, Path>, &'a str>,
+    /// Name of the directory the manifest was made from, which a tar of
+    /// that directory puts in front of every entry
+    root: Option<&'a OsStr>,
+    policy: TamperPolicy,
+    algorithm: HashAlgorithm,
+    enabled: bool,
+}
+
+impl<'a> HashLookup<'a> {
+    /// A lookup for `check`; without one nothing is checked
+    pub(crate) fn new(check: Option<&'a ManifestCheck>) -> Self {
+        let Some(check) = check else {
+            return Self {
+                hashes: HashMap::new(),
+                root: None,
+                policy: TamperPolicy::default(),
+                algorithm: HashAlgorithm::default(),
+                enabled: false,
+            };
+        };
+        let hashes = check
+            .manifest
+            .files
+            .iter()
+            .filter(|(_, entry)| !entry.is_dir && !entry.is_symlink)
+            .map(|(path, entry)| (comparable(path), entry.hash.as_str()))
+            .collect();
+        Self {
+            hashes,
+            root: check.manifest.base_dir.file_name(),
+            policy: check.policy,
+            algorithm: check.manifest.hash_algorithm,
+            enabled: true,
+        }
+    }
+
+    /// The hash the regular file `entry_path` of the archive should have,
+    /// `None` when it is not checked
+    ///
+    /// A file the manifest does not list is recorded in `outcome` under
+    /// `name`, and is an error when such files fail.
+    pub(crate) fn expected(
+        &self,
+        entry_path: &Path,
+        name: &Path,
+        outcome: &mut OperationOutcome,
+    ) -> Result<Option<&'a str>> {
+        if !self.enabled {
+            return Ok(None);
+        }
+        if let Some(hash) = self.lookup(entry_path) {
+            return Ok(Some(hash));
+        }
+
+        warn!("{:?} is not listed in the manifest", name);
+        outcome.tampered.push(Tampered {
+            path: name.to_path_buf(),
+            kind: TamperKind::Unlisted,
+        });
+        match self.policy {
+            TamperPolicy::Fail => Err(Error::SecurityError(format!(
+                "{} is not listed in the manifest",
+                name.display()
+            ))),
+            TamperPolicy::Flag => Ok(None),
+        }
+    }
+
+    /// A reader hashing `inner` the way the manifest's files were
+    pub(crate) fn hashing<R>(&self, inner: R) -> HashingRead<R> {
+        HashingRead {
+            inner,
+            hasher: self.algorithm.hasher(),
+        }
+    }
+
+    fn lookup(&self, entry_path: &Path) -> Option<&'a str> {
+        if let Some(hash) = self.hashes.get(&comparable(entry_path)) {
+            return Some(hash);
+        }
+        let root = self.root?;
+        let rest = entry_path.strip_prefix(root).ok()?;
+        self.hashes.get(&comparable(rest)).copied()
+    }
+
+    /// Compare the hash of the file written to `dest_path` with the one
+    /// `expected`, recording a mismatch in `outcome` under `name`
+    ///
+    /// A mismatching file is removed again when such files fail.
+    pub(crate) fn verify(
+        &self,
+        name: &Path,
+        dest_path: &Path,
+        expected: &str,
+        actual: &str,
+        outcome: &mut OperationOutcome,
+    ) -> Result<()> {
+        if expected.eq_ignore_ascii_case(actual) {
+            return Ok(());
+        }
+
+        warn!("{:?} does not match the manifest", name);
+        outcome.tampered.push(Tampered {
+            path: name.to_path_buf(),
+            kind: TamperKind::Modified,
+        });
+        match self.policy {
+            TamperPolicy::Fail => {
+                fs::remove_file(dest_path)?;
+                Err(Error::SecurityError(format!(
+                    "{} does not match the manifest (expected {}, got {})",
+                    name.display(),
+                    expected,
+                    actual
+                )))
+            }
+            TamperPolicy::Flag => Ok(()),
+        }
+    }
+}
+
+/// Reader that hashes what passes through it
+pub(crate) struct HashingRead<R> {
+    inner: R,
+    hasher: Hasher,
+}
+
+impl<R> HashingRead<R> {
+    /// Hex hash of everything read, as manifests store it
+    pub(crate) fn hash(&self) -> String {
+        self.hasher.hex()
+    }
+}
+
+impl<R: Read> Read for HashingRead<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
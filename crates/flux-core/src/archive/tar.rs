@@ -1,23 +1,46 @@
 //! Tar archive operations
 
-use crate::archive::{ArchiveEntry, ExtractOptions};
+use crate::archive::budget::{tune_level, BudgetedZstd};
+use crate::archive::case_fold::CaseFolder;
+use crate::archive::estimate::input_size;
+use crate::archive::links::create_symlink;
+use crate::archive::mapped::InputData;
+use crate::archive::meta::{append_tar_metadata, is_global_header};
+use crate::archive::order::{self, extraction_order};
+use crate::archive::paths::{self, is_enclosed_path};
+#[cfg(unix)]
+use crate::archive::permissions;
+use crate::archive::resume::{self, Prior, Resume};
+use crate::archive::tamper::HashLookup;
+use crate::archive::tar_extractor::tar_entry;
+use crate::archive::undo::{self, UndoJournal};
+use crate::archive::walk::WalkLimits;
+use crate::archive::{
+    ArchiveEntry, ExtractOptions, OperationOutcome, PackEntry, PackFilter, PackOptions, PackOrder,
+    PackRoot, SkipReason,
+};
+use crate::durability::Syncer;
 use crate::metadata::FileMetadata;
+use crate::progress::{NoProgress, ProgressRead, ProgressSink, ProgressWrite};
+use crate::security::{entry_output_path, validate_symlink};
 use crate::strategy::Algorithm;
-use crate::{Error, Result};
+use crate::{buffer, Error, Result};
 use flate2::write::GzEncoder;
 use flate2::Compression as GzCompression;
+use std::borrow::Cow;
 use std::fs::{self, File};
-use std::io::{Read, Write};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::time::Instant;
 use tar::{Archive, Builder};
 use tracing::{debug, info, warn};
-use walkdir::WalkDir;
+use walkdir::{DirEntry, WalkDir};
 use xz2::write::XzEncoder;
 use zstd::stream::write::Encoder as ZstdEncoder;
 
 /// Pack files into a tar archive
 pub fn pack_tar<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q) -> Result<()> {
-    pack_tar_with_options(input, output, false)
+    pack_tar_with_options(input, output, false, &PackFilter::default())
 }
 
 /// Pack files into a tar archive with options
@@ -25,36 +48,87 @@ pub fn pack_tar_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
     input: P,
     output: Q,
     follow_symlinks: bool,
+    filter: &PackFilter,
 ) -> Result<()> {
+    let options = PackOptions {
+        follow_symlinks,
+        filter: filter.clone(),
+        ..PackOptions::default()
+    };
+    pack_tar_with_progress(input, output, &options, &NoProgress)?.into_result()
+}
+
+/// Pack files into a tar archive with options, reporting progress to
+/// `progress`
+///
+/// Symlink handling, the filter, the metadata record and the timestamp
+/// limit come from `options`. Files that cannot be read are recorded in
+/// the outcome and left out.
+pub fn pack_tar_with_progress<P: AsRef<Path>, Q: AsRef<Path>>(
+    input: P,
+    output: Q,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
     let input = input.as_ref();
     let output = output.as_ref();
+    let follow_symlinks = options.follow_symlinks;
 
     info!(
         "Packing {:?} into {:?} (follow_symlinks: {})",
         input, output, follow_symlinks
     );
+    progress.start(input_size(&[input], options));
 
     // Create output directory if it doesn't exist
     if let Some(parent) = output.parent() {
         fs::create_dir_all(parent)?;
     }
 
-    let file = File::create(output)?;
-    let mut builder = Builder::new(file);
+    let file = ProgressWrite::new(File::create(output)?, progress);
+    let mut builder = new_builder(file, options)?;
+    let outcome = pack_input(&mut builder, input, options, progress)?;
+
+    builder.finish()?;
+    info!("Packed archive {:?}: {}", output, outcome);
+
+    Ok(outcome)
+}
+
+/// A tar builder writing to `writer`, starting with the metadata record of
+/// `options` if it has one
+fn new_builder<W: Write>(writer: W, options: &PackOptions) -> Result<Builder<W>> {
+    let mut builder = Builder::new(writer);
+    if let Some(metadata) = options.stored_metadata() {
+        append_tar_metadata(&mut builder, &metadata)?;
+    }
+    Ok(builder)
+}
+
+/// Pack a file, or a directory recursively, into the tar builder
+fn pack_input<W: Write>(
+    builder: &mut Builder<W>,
+    input: &Path,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
+    let mut outcome = OperationOutcome::default();
 
     if input.is_file() {
-        // Pack single file
+        let name = input
+            .file_name()
+            .ok_or_else(|| Error::InvalidPath(format!("Invalid file name: {:?}", input)))?;
         pack_file(
-            &mut builder,
+            builder,
             input,
-            Path::new(input.file_name().ok_or_else(|| {
-                Error::InvalidPath(format!("Invalid file name: {:?}", input))
-            })?),
-            follow_symlinks,
+            Path::new(name),
+            options,
+            progress,
+            &mut outcome,
         )?;
     } else if input.is_dir() {
-        // Pack directory recursively
-        pack_directory_with_options(&mut builder, input, follow_symlinks)?;
+        let root = PackRoot::named_in(input, None);
+        pack_directory_with_options(builder, &root, options, progress, &mut outcome)?;
     } else {
         return Err(Error::InvalidPath(format!(
             "{:?} is neither a file nor a directory",
@@ -62,26 +136,110 @@ pub fn pack_tar_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
         )));
     }
 
-    builder.finish()?;
-    info!("Successfully packed archive: {:?}", output);
-
-    Ok(())
+    Ok(outcome)
 }
 
 /// Pack a single file into the tar builder
+///
+/// A file that cannot be opened or described is recorded as failed and
+/// nothing is written for it; only errors writing the archive itself are
+/// returned.
 fn pack_file<W: Write>(
     builder: &mut Builder<W>,
     path: &Path,
     archive_path: &Path,
-    follow_symlinks: bool,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+    outcome: &mut OperationOutcome,
 ) -> Result<()> {
+    let Some(archive_path) = options.stored_name(archive_path, false) else {
+        return Ok(());
+    };
+    let archive_path = &*archive_path;
     debug!("Adding file: {:?} as {:?}", path, archive_path);
 
+    let prepared = file_header(path, archive_path, options).and_then(|(mut header, file)| {
+        let name = transform_header(&mut header, path, archive_path, options)?;
+        Ok((header, file, name))
+    });
+    let (header, file, name) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            outcome.fail(archive_path, e.with_path(path));
+            return Ok(());
+        }
+    };
+
+    match file {
+        None => builder.append(&header, &mut std::io::empty())?,
+        Some(file) => {
+            progress.file(path);
+            let mut data = InputData::new(file, path, &name, options);
+            builder.append(&header, &mut ProgressRead::new(&mut data, progress))?;
+        }
+    }
+
+    outcome.done(name);
+    Ok(())
+}
+
+/// Run the entry transform of `options`, if any, over `header` of the
+/// entry for `path`, returning the name it is now stored under
+fn transform_header<'a>(
+    header: &mut tar::Header,
+    path: &Path,
+    archive_path: &'a Path,
+    options: &PackOptions,
+) -> Result<Cow<'a, Path>> {
+    let Some(transform) = &options.transform else {
+        return Ok(Cow::Borrowed(archive_path));
+    };
+
+    let entry_type = header.entry_type();
+    let mut entry = PackEntry::new(
+        path,
+        archive_path.to_path_buf(),
+        entry_type.is_dir(),
+        entry_type.is_symlink(),
+    );
+    entry.mode = header.mode().ok();
+    entry.uid = header.uid().ok();
+    entry.gid = header.gid().ok();
+    entry.mtime = header.mtime().ok();
+    transform.apply(&mut entry);
+
+    header.set_path(&entry.path)?;
+    if let Some(mode) = entry.mode {
+        header.set_mode(mode);
+    }
+    if let Some(uid) = entry.uid {
+        header.set_uid(uid);
+    }
+    if let Some(gid) = entry.gid {
+        header.set_gid(gid);
+    }
+    if let Some(mtime) = entry.mtime {
+        header.set_mtime(mtime);
+    }
+    header.set_cksum();
+
+    Ok(Cow::Owned(entry.path))
+}
+
+/// The tar header for `path`, and the open file when there is data to copy
+///
+/// Symlinks are described rather than followed unless `follow_symlinks` is
+/// set in `options`.
+fn file_header(
+    path: &Path,
+    archive_path: &Path,
+    options: &PackOptions,
+) -> Result<(tar::Header, Option<File>)> {
     let file_metadata = path.symlink_metadata()?;
 
     // Check if it's a symlink
     #[cfg(unix)]
-    if file_metadata.file_type().is_symlink() && !follow_symlinks {
+    if file_metadata.file_type().is_symlink() && !options.follow_symlinks {
         // Pack the symlink itself
         let link_target = fs::read_link(path)?;
         debug!("Adding symlink: {:?} -> {:?}", path, link_target);
@@ -111,18 +269,17 @@ fn pack_file<W: Write>(
         // Set timestamps
         if let Some(mtime) = metadata.modified {
             if let Ok(duration) = mtime.duration_since(std::time::UNIX_EPOCH) {
-                header.set_mtime(duration.as_secs());
+                header.set_mtime(options.stored_mtime(duration.as_secs()));
             }
         }
 
         header.set_cksum();
-        builder.append(&header, &mut std::io::empty())?;
-        return Ok(());
+        return Ok((header, None));
     }
 
     // Regular file handling
     let metadata = FileMetadata::from_path(path)?;
-    let mut file = File::open(path)?;
+    let file = File::open(path)?;
     let mut header = tar::Header::new_ustar();
 
     // Set basic metadata
@@ -146,100 +303,171 @@ fn pack_file<W: Write>(
     // Set timestamps
     if let Some(mtime) = metadata.modified {
         if let Ok(duration) = mtime.duration_since(std::time::UNIX_EPOCH) {
-            header.set_mtime(duration.as_secs());
+            header.set_mtime(options.stored_mtime(duration.as_secs()));
         }
     }
 
     // Calculate and set checksum
     header.set_cksum();
 
-    builder.append(&header, &mut file)?;
-    Ok(())
+    Ok((header, Some(file)))
+}
+
+/// The tar header for the directory at `path`
+fn directory_header(path: &Path, archive_path: &Path) -> Result<tar::Header> {
+    let metadata = FileMetadata::from_path(path)?;
+    let mut header = tar::Header::new_ustar();
+
+    header.set_entry_type(tar::EntryType::Directory);
+    header.set_path(archive_path)?;
+    header.set_size(0);
+
+    #[cfg(unix)]
+    {
+        if let Some(mode) = metadata.mode {
+            header.set_mode(mode);
+        }
+        if let Some(uid) = metadata.uid {
+            header.set_uid(uid as u64);
+        }
+        if let Some(gid) = metadata.gid {
+            header.set_gid(gid as u64);
+        }
+    }
+
+    // Calculate and set checksum
+    header.set_cksum();
+
+    Ok(header)
 }
 
 /// Pack a directory recursively into the tar builder with options
+///
+/// Entries excluded by the filter are skipped, including the whole subtree
+/// of an excluded directory, and so are directories left out by
+/// [`WalkLimits`]. Entries that cannot be read are recorded as failed.
 fn pack_directory_with_options<W: Write>(
     builder: &mut Builder<W>,
-    dir: &Path,
-    follow_symlinks: bool,
+    root: &PackRoot,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+    outcome: &mut OperationOutcome,
 ) -> Result<()> {
-    let base_path = dir.parent().unwrap_or(Path::new(""));
-
-    let walker = if follow_symlinks {
-        WalkDir::new(dir).follow_links(true).max_depth(100) // Prevent infinite recursion
-    } else {
-        WalkDir::new(dir).follow_links(false)
-    };
-
-    for entry in walker {
+    let dir = root.path.as_path();
+    let mut limits = WalkLimits::new(options.follow_symlinks, options.max_depth);
+
+    let mut left_out = Vec::new();
+    let mut walked = Vec::new();
+    let walker = WalkDir::new(dir).follow_links(options.follow_symlinks);
+    for entry in walker.into_iter().filter_entry(|entry| {
+        let reason = if options.filter.excludes_entry(dir, entry) {
+            Some(SkipReason::Excluded)
+        } else {
+            limits.leave_out(entry)
+        };
+        if let Some(reason) = reason {
+            left_out.push((entry.path().to_path_buf(), reason));
+        }
+        reason.is_none()
+    }) {
         let entry = match entry {
             Ok(e) => e,
             Err(e) => {
-                // Handle WalkDir errors (e.g., symlink loops)
-                warn!("Error walking directory: {}", e);
-                // Check if it's a loop error
-                if let Some(path) = e.path() {
-                    if e.loop_ancestor().is_some() {
-                        return Err(Error::Archive(format!(
-                            "Symlink loop detected at {:?}",
-                            path
-                        )));
-                    }
+                let path = e.path().unwrap_or(dir).to_path_buf();
+                let name = root.entry_name(&path);
+                // A link back to a directory being packed
+                if e.loop_ancestor().is_some() {
+                    warn!("Skipping {:?}: {}", path, SkipReason::Revisited);
+                    outcome.skip(name, SkipReason::Revisited);
+                } else {
+                    outcome.fail(name, e.into());
                 }
-                continue; // Skip this entry
+                continue;
             }
         };
-        let path = entry.path();
-
         // Skip the directory itself
-        if path == dir {
+        if entry.path() == dir {
             continue;
         }
 
-        // Calculate relative path for the archive
-        let relative_path = path
-            .strip_prefix(base_path)
-            .map_err(|_| Error::InvalidPath(format!("Failed to strip prefix from {:?}", path)))?;
+        if options.entry_order == PackOrder::Walk {
+            pack_walked_entry(builder, root, &entry, options, progress, outcome)?;
+        } else {
+            walked.push(entry);
+        }
+    }
 
-        let file_type = entry.file_type();
+    // Sorted once the whole tree is known
+    order::sort_for_packing(&mut walked, options.entry_order);
+    for entry in &walked {
+        pack_walked_entry(builder, root, entry, options, progress, outcome)?;
+    }
 
-        if file_type.is_file() || (file_type.is_symlink() && follow_symlinks) {
-            pack_file(builder, path, relative_path, follow_symlinks)?;
-        } else if file_type.is_dir() {
-            // Add directory entry
-            debug!("Adding directory: {:?}", relative_path);
-            let metadata = FileMetadata::from_path(path)?;
-            let mut header = tar::Header::new_ustar();
+    for (path, reason) in left_out {
+        if reason != SkipReason::Excluded {
+            warn!("Skipping {:?}: {}", path, reason);
+        }
+        outcome.skip(root.entry_name(&path), reason);
+    }
 
-            header.set_entry_type(tar::EntryType::Directory);
-            header.set_path(relative_path)?;
-            header.set_size(0);
+    Ok(())
+}
 
-            #[cfg(unix)]
-            {
-                if let Some(mode) = metadata.mode {
-                    header.set_mode(mode);
-                }
-                if let Some(uid) = metadata.uid {
-                    header.set_uid(uid as u64);
-                }
-                if let Some(gid) = metadata.gid {
-                    header.set_gid(gid as u64);
-                }
-            }
+/// Add an entry found walking the directory of `root`
+fn pack_walked_entry<W: Write>(
+    builder: &mut Builder<W>,
+    root: &PackRoot,
+    entry: &DirEntry,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+    outcome: &mut OperationOutcome,
+) -> Result<()> {
+    let path = entry.path();
+
+    // Calculate relative path for the archive
+    let relative_path = &*root.entry_name(path);
 
-            // Calculate and set checksum
-            header.set_cksum();
+    let file_type = entry.file_type();
+
+    if file_type.is_file() || file_type.is_symlink() {
+        pack_file(builder, path, relative_path, options, progress, outcome)?;
+    } else if file_type.is_dir() {
+        pack_directory_entry(builder, path, relative_path, options, outcome)?;
+    } else {
+        warn!("Skipping special file: {:?}", path);
+        outcome.skip(relative_path, SkipReason::Unsupported);
+    }
+    Ok(())
+}
 
+/// Add the entry of the directory `path`, without its contents, named
+/// `archive_path`
+///
+/// Nothing is added for the folder `strip_prefix` takes off. A directory
+/// that cannot be described is recorded as failed.
+fn pack_directory_entry<W: Write>(
+    builder: &mut Builder<W>,
+    path: &Path,
+    archive_path: &Path,
+    options: &PackOptions,
+    outcome: &mut OperationOutcome,
+) -> Result<()> {
+    let Some(archive_path) = options.stored_name(archive_path, true) else {
+        return Ok(());
+    };
+    let archive_path = &*archive_path;
+    debug!("Adding directory: {:?}", archive_path);
+    let prepared = directory_header(path, archive_path).and_then(|mut header| {
+        let name = transform_header(&mut header, path, archive_path, options)?;
+        Ok((header, name))
+    });
+    match prepared {
+        Ok((header, name)) => {
             builder.append(&header, &mut std::io::empty())?;
-        } else if file_type.is_symlink() && !follow_symlinks {
-            // Handle symlinks when not following them
-            pack_file(builder, path, relative_path, follow_symlinks)?;
-        } else {
-            warn!("Skipping special file: {:?}", path);
+            outcome.done(name);
         }
+        Err(e) => outcome.fail(archive_path, e.with_path(path)),
     }
-
     Ok(())
 }
 
@@ -255,45 +483,33 @@ pub fn extract_tar<P: AsRef<Path>, Q: AsRef<Path>>(archive_path: P, output_dir:
 
     let file = File::open(archive_path)?;
     let mut archive = Archive::new(file);
+    extract_archive_entries(&mut archive, output_dir)
+}
 
-    // Extract all entries
-    for entry in archive.entries()? {
-        let mut entry = entry?;
-        let path = entry.path()?;
-        let dest_path = output_dir.join(&path);
-
-        debug!("Extracting: {:?}", path);
-
-        // Create parent directories if needed
-        if let Some(parent) = dest_path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-
-        // Extract the entry
-        entry.unpack(&dest_path)?;
-
-        // Try to preserve metadata
-        let header = entry.header().clone();
-        apply_tar_metadata(&dest_path, &header);
+/// Apply metadata from tar header to extracted file, with default
+/// permissions instead of the stored mode under `apply_umask`
+fn apply_tar_metadata(path: &Path, header: &tar::Header, apply_umask: bool) {
+    // set_permissions and set_file_mtime follow links, which would clobber
+    // the metadata of the link target
+    if header.entry_type().is_symlink() {
+        return;
     }
 
-    info!("Successfully extracted archive");
-    Ok(())
-}
-
-/// Apply metadata from tar header to extracted file
-fn apply_tar_metadata(path: &Path, header: &tar::Header) {
     #[cfg(unix)]
     {
         use std::os::unix::fs::PermissionsExt;
 
         // Set permissions
         if let Ok(mode) = header.mode() {
+            let is_dir = header.entry_type().is_dir();
+            let mode = permissions::extracted_mode(mode, is_dir, apply_umask);
             if let Err(e) = fs::set_permissions(path, fs::Permissions::from_mode(mode)) {
                 debug!("Failed to set permissions on {:?}: {}", path, e);
             }
         }
     }
+    #[cfg(not(unix))]
+    let _ = apply_umask;
 
     // Set modification time
     if let Ok(mtime) = header.mtime() {
@@ -318,6 +534,9 @@ pub fn inspect_tar<P: AsRef<Path>>(archive_path: P) -> Result<Vec<ArchiveEntry>>
     for entry in archive.entries()? {
         let entry = entry?;
         let header = entry.header();
+        if is_global_header(header) {
+            continue;
+        }
         let path = entry.path()?;
 
         let archive_entry = ArchiveEntry {
@@ -333,6 +552,7 @@ pub fn inspect_tar<P: AsRef<Path>>(archive_path: P) -> Result<Vec<ArchiveEntry>>
             } else {
                 None
             },
+            extra_fields: Vec::new(),
         };
 
         entries.push(archive_entry);
@@ -342,109 +562,6 @@ pub fn inspect_tar<P: AsRef<Path>>(archive_path: P) -> Result<Vec<ArchiveEntry>>
     Ok(entries)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    use tempfile::TempDir;
-
-    #[test]
-    fn test_pack_single_file() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        let test_file = temp_dir.path().join("test.txt");
-        let archive_path = temp_dir.path().join("test.tar");
-
-        // Create test file
-        fs::write(&test_file, b"Hello, World!")?;
-
-        // Pack the file
-        pack_tar(&test_file, &archive_path)?;
-
-        // Verify archive exists
-        assert!(archive_path.exists());
-        assert!(archive_path.metadata()?.len() > 0);
-
-        Ok(())
-    }
-
-    #[test]
-    fn test_pack_directory() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        let test_dir = temp_dir.path().join("test_dir");
-        let archive_path = temp_dir.path().join("test.tar");
-
-        // Create test directory structure
-        fs::create_dir_all(&test_dir)?;
-        fs::write(test_dir.join("file1.txt"), b"File 1")?;
-        fs::write(test_dir.join("file2.txt"), b"File 2")?;
-        fs::create_dir(test_dir.join("subdir"))?;
-        fs::write(test_dir.join("subdir").join("file3.txt"), b"File 3")?;
-
-        // Pack the directory
-        pack_tar(&test_dir, &archive_path)?;
-
-        // Verify archive exists
-        assert!(archive_path.exists());
-        assert!(archive_path.metadata()?.len() > 0);
-
-        Ok(())
-    }
-
-    #[test]
-    fn test_extract_archive() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        let test_dir = temp_dir.path().join("test_dir");
-        let archive_path = temp_dir.path().join("test.tar");
-        let extract_dir = temp_dir.path().join("extracted");
-
-        // Create test directory structure
-        fs::create_dir_all(&test_dir)?;
-        fs::write(test_dir.join("file1.txt"), b"File 1")?;
-        fs::write(test_dir.join("file2.txt"), b"File 2")?;
-
-        // Pack and then extract
-        pack_tar(&test_dir, &archive_path)?;
-        extract_tar(&archive_path, &extract_dir)?;
-
-        // Verify extracted files
-        assert!(extract_dir.join("test_dir").join("file1.txt").exists());
-        assert!(extract_dir.join("test_dir").join("file2.txt").exists());
-
-        // Verify content
-        let content1 = fs::read(extract_dir.join("test_dir").join("file1.txt"))?;
-        assert_eq!(content1, b"File 1");
-
-        let content2 = fs::read(extract_dir.join("test_dir").join("file2.txt"))?;
-        assert_eq!(content2, b"File 2");
-
-        Ok(())
-    }
-
-    #[test]
-    fn test_pack_extract_preserves_content() -> Result<()> {
-        let temp_dir = TempDir::new()?;
-        let test_file = temp_dir.path().join("test.txt");
-        let archive_path = temp_dir.path().join("test.tar");
-        let extract_dir = temp_dir.path().join("extracted");
-
-        // Create test file with specific content
-        let original_content = b"This is a test file with some content!";
-        fs::write(&test_file, original_content)?;
-
-        // Pack, extract, and verify
-        pack_tar(&test_file, &archive_path)?;
-        extract_tar(&archive_path, &extract_dir)?;
-
-        let extracted_file = extract_dir.join("test.txt");
-        assert!(extracted_file.exists());
-
-        let extracted_content = fs::read(&extracted_file)?;
-        assert_eq!(original_content, &extracted_content[..]);
-
-        Ok(())
-    }
-}
-
 /// Pack files into a compressed tar archive
 pub fn pack_tar_compressed<P: AsRef<Path>, Q: AsRef<Path>>(
     input: P,
@@ -452,7 +569,14 @@ pub fn pack_tar_compressed<P: AsRef<Path>, Q: AsRef<Path>>(
     algorithm: Algorithm,
     level: u32,
 ) -> Result<()> {
-    pack_tar_compressed_with_options(input, output, algorithm, level, false)
+    pack_tar_compressed_with_options(
+        input,
+        output,
+        algorithm,
+        level,
+        false,
+        &PackFilter::default(),
+    )
 }
 
 /// Pack files into a compressed tar archive with options
@@ -462,10 +586,39 @@ pub fn pack_tar_compressed_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
     algorithm: Algorithm,
     level: u32,
     follow_symlinks: bool,
+    filter: &PackFilter,
 ) -> Result<()> {
+    let options = PackOptions {
+        follow_symlinks,
+        filter: filter.clone(),
+        ..PackOptions::default()
+    };
+    pack_tar_compressed_with_progress(input, output, algorithm, level, &options, &NoProgress)?
+        .into_result()
+}
+
+/// Pack files into a compressed tar archive with options, reporting
+/// progress to `progress`
+///
+/// Everything but the compression comes from `options`, as for
+/// [`pack_tar_with_progress`]. Files that cannot be read are recorded in
+/// the outcome and left out.
+pub fn pack_tar_compressed_with_progress<P: AsRef<Path>, Q: AsRef<Path>>(
+    input: P,
+    output: Q,
+    algorithm: Algorithm,
+    level: u32,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
     let input = input.as_ref();
     let output = output.as_ref();
 
+    if algorithm == Algorithm::Store {
+        // No compression, just create tar
+        return pack_tar_with_progress(input, output, options, progress);
+    }
+
     info!(
         "Packing {:?} into {:?} with {:?} compression",
         input, output, algorithm
@@ -476,111 +629,98 @@ pub fn pack_tar_compressed_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
         fs::create_dir_all(parent)?;
     }
 
-    let file = File::create(output)?;
+    let outcome = pack_tar_to_writer(
+        input,
+        File::create(output)?,
+        algorithm,
+        level,
+        options,
+        progress,
+    )?;
+    info!("Packed compressed archive {:?}: {}", output, outcome);
+    Ok(outcome)
+}
+
+/// Pack `input` as a tar compressed with `algorithm` into `writer`, which
+/// need not be seekable, reporting progress to `progress`
+///
+/// Options are used as by [`pack_tar_compressed_with_progress`]; the writer
+/// is flushed but not closed.
+pub fn pack_tar_to_writer<W: Write>(
+    input: &Path,
+    writer: W,
+    algorithm: Algorithm,
+    level: u32,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
+    let started = Instant::now();
+    let total = input_size(&[input], options);
+    progress.start(total);
+    let level = match options.time_budget {
+        Some(budget) if algorithm != Algorithm::Store => {
+            tune_level(&[input], algorithm, budget, options)?
+        }
+        _ => level,
+    };
+
+    let writer = ProgressWrite::new(writer, progress);
 
     match algorithm {
         Algorithm::Store => {
-            // No compression, just create tar
-            pack_tar_with_options(input, output, follow_symlinks)
+            let mut builder = new_builder(writer, options)?;
+            let outcome = pack_input(&mut builder, input, options, progress)?;
+            builder.into_inner()?.flush()?;
+            Ok(outcome)
         }
         Algorithm::Gzip => {
-            let encoder = GzEncoder::new(file, GzCompression::new(level));
-            let mut builder = Builder::new(encoder);
-
-            if input.is_file() {
-                pack_file(
-                    &mut builder,
-                    input,
-                    Path::new(input.file_name().unwrap()),
-                    follow_symlinks,
-                )?;
-            } else if input.is_dir() {
-                pack_directory_with_options(&mut builder, input, follow_symlinks)?;
-            } else {
-                return Err(Error::InvalidPath(format!(
-                    "{:?} is neither a file nor a directory",
-                    input
-                )));
-            }
+            let encoder = GzEncoder::new(writer, GzCompression::new(level));
+            let mut builder = new_builder(encoder, options)?;
 
-            let encoder = builder.into_inner()?;
-            encoder.finish()?;
-            info!("Successfully packed compressed archive: {:?}", output);
-            Ok(())
+            let outcome = pack_input(&mut builder, input, options, progress)?;
+            builder.into_inner()?.finish()?.flush()?;
+            Ok(outcome)
+        }
+        Algorithm::Zstd if options.time_budget.is_some() => {
+            let budget = options.time_budget.unwrap_or_default();
+            let encoder = BudgetedZstd::new(
+                writer,
+                level,
+                budget.saturating_sub(started.elapsed()),
+                total,
+            )?;
+            let mut builder = new_builder(encoder, options)?;
+
+            let outcome = pack_input(&mut builder, input, options, progress)?;
+            builder.into_inner()?.finish()?.flush()?;
+            Ok(outcome)
         }
         Algorithm::Zstd => {
-            let encoder = ZstdEncoder::new(file, level as i32)?;
-            let mut builder = Builder::new(encoder);
-
-            if input.is_file() {
-                pack_file(
-                    &mut builder,
-                    input,
-                    Path::new(input.file_name().unwrap()),
-                    follow_symlinks,
-                )?;
-            } else if input.is_dir() {
-                pack_directory_with_options(&mut builder, input, follow_symlinks)?;
-            } else {
-                return Err(Error::InvalidPath(format!(
-                    "{:?} is neither a file nor a directory",
-                    input
-                )));
-            }
-
-            let encoder = builder.into_inner()?;
-            encoder.finish()?;
-            info!("Successfully packed compressed archive: {:?}", output);
-            Ok(())
+            let mut encoder = ZstdEncoder::new(writer, level as i32)?;
+            // Lets `verify` detect corrupted data, for four bytes per frame
+            encoder.include_checksum(true)?;
+            let mut builder = new_builder(encoder, options)?;
+
+            let outcome = pack_input(&mut builder, input, options, progress)?;
+            builder.into_inner()?.finish()?.flush()?;
+            Ok(outcome)
         }
         Algorithm::Xz => {
-            let encoder = XzEncoder::new(file, level);
-            let mut builder = Builder::new(encoder);
-
-            if input.is_file() {
-                pack_file(
-                    &mut builder,
-                    input,
-                    Path::new(input.file_name().unwrap()),
-                    follow_symlinks,
-                )?;
-            } else if input.is_dir() {
-                pack_directory_with_options(&mut builder, input, follow_symlinks)?;
-            } else {
-                return Err(Error::InvalidPath(format!(
-                    "{:?} is neither a file nor a directory",
-                    input
-                )));
-            }
+            let encoder = XzEncoder::new(writer, level);
+            let mut builder = new_builder(encoder, options)?;
 
-            let encoder = builder.into_inner()?;
-            encoder.finish()?;
-            info!("Successfully packed compressed archive: {:?}", output);
-            Ok(())
+            let outcome = pack_input(&mut builder, input, options, progress)?;
+            builder.into_inner()?.finish()?.flush()?;
+            Ok(outcome)
         }
         Algorithm::Brotli => {
-            let encoder = brotli::CompressorWriter::new(file, 4096, level, 22);
-            let mut builder = Builder::new(encoder);
-
-            if input.is_file() {
-                pack_file(
-                    &mut builder,
-                    input,
-                    Path::new(input.file_name().unwrap()),
-                    follow_symlinks,
-                )?;
-            } else if input.is_dir() {
-                pack_directory_with_options(&mut builder, input, follow_symlinks)?;
-            } else {
-                return Err(Error::InvalidPath(format!(
-                    "{:?} is neither a file nor a directory",
-                    input
-                )));
-            }
+            let encoder = brotli::CompressorWriter::new(writer, 4096, level, 22);
+            let mut builder = new_builder(encoder, options)?;
 
-            builder.finish()?;
-            info!("Successfully packed compressed archive: {:?}", output);
-            Ok(())
+            let outcome = pack_input(&mut builder, input, options, progress)?;
+            // The stream is finished when the encoder is dropped
+            builder.into_inner()?.flush()?;
+            Ok(outcome)
         }
     }
 }
@@ -637,7 +777,14 @@ fn extract_archive_entries<R: Read>(archive: &mut Archive<R>, output_dir: &Path)
     // Extract all entries
     for entry in archive.entries()? {
         let mut entry = entry?;
+        if is_global_header(entry.header()) {
+            continue;
+        }
         let path = entry.path()?;
+        if !is_enclosed_path(&path) {
+            warn!("Skipping entry with unsafe path: {:?}", path);
+            continue;
+        }
         let dest_path = output_dir.join(&path);
 
         debug!("Extracting: {:?}", path);
@@ -648,11 +795,20 @@ fn extract_archive_entries<R: Read>(archive: &mut Archive<R>, output_dir: &Path)
         }
 
         // Extract the entry
+        if entry.header().entry_type().is_hard_link() {
+            extract_hard_link(
+                &mut entry,
+                &dest_path,
+                output_dir,
+                &ExtractOptions::default(),
+            )?;
+            continue;
+        }
         entry.unpack(&dest_path)?;
 
         // Try to preserve metadata
         let header = entry.header().clone();
-        apply_tar_metadata(&dest_path, &header);
+        apply_tar_metadata(&dest_path, &header, false);
     }
 
     info!("Successfully extracted archive");
@@ -714,6 +870,9 @@ fn read_archive_entries<R: Read>(
     for entry in archive.entries()? {
         let entry = entry?;
         let header = entry.header();
+        if is_global_header(header) {
+            continue;
+        }
         let path = entry.path()?;
 
         let archive_entry = ArchiveEntry {
@@ -729,6 +888,7 @@ fn read_archive_entries<R: Read>(
             } else {
                 None
             },
+            extra_fields: Vec::new(),
         };
 
         entries.push(archive_entry);
@@ -739,11 +899,14 @@ fn read_archive_entries<R: Read>(
 }
 
 /// Extract tar archive with options
+///
+/// Entries that cannot be written are recorded in the outcome and the rest
+/// are still extracted; a damaged archive stops extraction.
 pub fn extract_tar_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
     archive_path: P,
     output_dir: Q,
     options: ExtractOptions,
-) -> Result<()> {
+) -> Result<OperationOutcome> {
     let archive_path = archive_path.as_ref();
     let output_dir = output_dir.as_ref();
 
@@ -756,56 +919,22 @@ pub fn extract_tar_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
     fs::create_dir_all(output_dir)?;
 
     let file = File::open(archive_path)?;
-    let mut archive = Archive::new(file);
-
-    // Extract all entries
-    for entry in archive.entries()? {
-        let mut entry = entry?;
-        let path = entry.path()?;
-
-        // Apply strip components
-        let path = if let Some(strip) = options.strip_components {
-            let components: Vec<_> = path.components().collect();
-            if components.len() <= strip {
-                // Skip this entry if we're stripping more components than it has
-                continue;
-            }
-            PathBuf::from_iter(components.into_iter().skip(strip))
-        } else {
-            path.to_path_buf()
-        };
-
-        let dest_path = output_dir.join(&path);
-
-        // Handle existing files
-        if dest_path.exists() && !entry.header().entry_type().is_dir() {
-            if options.skip {
-                info!("Skipping existing file: {:?}", dest_path);
-                continue;
-            } else if options.rename {
-                let dest_path = get_unique_filename(&dest_path);
-                info!("Renaming to avoid conflict: {:?}", dest_path);
-                extract_entry(&mut entry, &dest_path)?;
-            } else if options.overwrite {
-                info!("Overwriting existing file: {:?}", dest_path);
-                extract_entry(&mut entry, &dest_path)?;
-            }
-        } else {
-            extract_entry(&mut entry, &dest_path)?;
-        }
+    if options.is_ordered() {
+        return extract_tar_ordered(file, archive_path, output_dir, options);
     }
-
-    info!("Successfully extracted archive");
-    Ok(())
+    let mut archive = Archive::new(file);
+    extract_archive_entries_with_options(&mut archive, archive_path, output_dir, options)
 }
 
 /// Extract compressed tar archive with options
+///
+/// See [`extract_tar_with_options`].
 pub fn extract_tar_compressed_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
     archive_path: P,
     output_dir: Q,
     algorithm: Algorithm,
     options: ExtractOptions,
-) -> Result<()> {
+) -> Result<OperationOutcome> {
     let archive_path = archive_path.as_ref();
     let output_dir = output_dir.as_ref();
 
@@ -818,6 +947,12 @@ pub fn extract_tar_compressed_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
     fs::create_dir_all(output_dir)?;
 
     let file = File::open(archive_path)?;
+    if options.is_ordered() && algorithm != Algorithm::Store {
+        warn!(
+            "Extracting {:?} in stored order, as compressed tars are read in one pass",
+            archive_path
+        );
+    }
 
     match algorithm {
         Algorithm::Store => {
@@ -827,22 +962,22 @@ pub fn extract_tar_compressed_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
         Algorithm::Gzip => {
             let decoder = flate2::read::GzDecoder::new(file);
             let mut archive = Archive::new(decoder);
-            extract_archive_entries_with_options(&mut archive, output_dir, options)
+            extract_archive_entries_with_options(&mut archive, archive_path, output_dir, options)
         }
         Algorithm::Zstd => {
             let decoder = zstd::stream::read::Decoder::new(file)?;
             let mut archive = Archive::new(decoder);
-            extract_archive_entries_with_options(&mut archive, output_dir, options)
+            extract_archive_entries_with_options(&mut archive, archive_path, output_dir, options)
         }
         Algorithm::Xz => {
             let decoder = xz2::read::XzDecoder::new(file);
             let mut archive = Archive::new(decoder);
-            extract_archive_entries_with_options(&mut archive, output_dir, options)
+            extract_archive_entries_with_options(&mut archive, archive_path, output_dir, options)
         }
         Algorithm::Brotli => {
             let decoder = brotli::Decompressor::new(file, 4096);
             let mut archive = Archive::new(decoder);
-            extract_archive_entries_with_options(&mut archive, output_dir, options)
+            extract_archive_entries_with_options(&mut archive, archive_path, output_dir, options)
         }
     }
 }
@@ -850,52 +985,249 @@ pub fn extract_tar_compressed_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
 /// Extract entries from a tar archive reader with options
 fn extract_archive_entries_with_options<R: Read>(
     archive: &mut Archive<R>,
+    archive_path: &Path,
     output_dir: &Path,
     options: ExtractOptions,
-) -> Result<()> {
+) -> Result<OperationOutcome> {
+    let mut extraction = TarExtraction::new(archive_path, output_dir, &options)?;
+
     // Extract all entries
     for entry in archive.entries()? {
         let mut entry = entry?;
-        let path = entry.path()?;
+        if is_global_header(entry.header()) {
+            continue;
+        }
+        extraction.extract(&mut entry)?;
+    }
+
+    extraction.finish()
+}
+
+/// Extract the entries of the plain tar `file` in the order `options` ask
+/// for, seeking to each in turn
+fn extract_tar_ordered(
+    mut file: File,
+    archive_path: &Path,
+    output_dir: &Path,
+    options: ExtractOptions,
+) -> Result<OperationOutcome> {
+    // Where each entry starts, counting the extension headers before it,
+    // which begin where the data of the entry before ends
+    let mut starts = Vec::new();
+    let mut entries = Vec::new();
+    let mut next_start = 0;
+    for entry in Archive::new(&file).entries_with_seek()? {
+        let entry = entry?;
+        let start = next_start;
+        next_start = entry.raw_file_position() + entry.header().entry_size()?.div_ceil(512) * 512;
+        if is_global_header(entry.header()) {
+            continue;
+        }
+        starts.push(start);
+        entries.push(ArchiveEntry::from(tar_entry(&entry)?));
+    }
+
+    let mut extraction = TarExtraction::new(archive_path, output_dir, &options)?;
+    for i in extraction_order(&entries, &options) {
+        file.seek(SeekFrom::Start(starts[i]))?;
+        let mut archive = Archive::new(&file);
+        let mut entry = archive
+            .entries()?
+            .next()
+            .ok_or_else(|| Error::ArchiveError(format!("Entry {:?} is gone", entries[i].path)))??;
+        extraction.extract(&mut entry)?;
+    }
+
+    extraction.finish()
+}
+
+/// What extracting the entries of one tar archive keeps between them
+struct TarExtraction<'a> {
+    output_dir: &'a Path,
+    options: &'a ExtractOptions,
+    case_folder: CaseFolder,
+    hashes: HashLookup<'a>,
+    journal: Option<UndoJournal>,
+    resume: Option<Resume>,
+    syncer: Syncer,
+    outcome: OperationOutcome,
+}
+
+impl<'a> TarExtraction<'a> {
+    fn new(archive_path: &Path, output_dir: &'a Path, options: &'a ExtractOptions) -> Result<Self> {
+        Ok(Self {
+            output_dir,
+            options,
+            case_folder: CaseFolder::new(options.case_collisions, options.fold_case, output_dir),
+            hashes: HashLookup::new(options.manifest_check.as_ref()),
+            journal: options.undoable.then(|| UndoJournal::new(output_dir)),
+            resume: options
+                .resume
+                .then(|| Resume::open(archive_path, output_dir))
+                .transpose()?,
+            syncer: Syncer::new(options.durability),
+            outcome: OperationOutcome::default(),
+        })
+    }
+
+    /// Sync what is still unsynced, save the undo journal and settle the
+    /// resume journal, if they are kept, and return what happened
+    fn finish(mut self) -> Result<OperationOutcome> {
+        self.syncer.finish()?;
+        if let Some(journal) = &mut self.journal {
+            journal.save()?;
+        }
+        if let Some(resume) = self.resume.take() {
+            resume.finish(!self.outcome.failed.is_empty())?;
+        }
+        info!("Extracted archive: {}", self.outcome);
+        Ok(self.outcome)
+    }
+
+    /// Extract `entry`, recording in the outcome how that went
+    ///
+    /// Only an entry that cannot be read is an error.
+    fn extract<R: Read>(&mut self, entry: &mut tar::Entry<R>) -> Result<()> {
+        let options = self.options;
+        let output_dir = self.output_dir;
+        let outcome = &mut self.outcome;
+
+        let path = entry.path()?.into_owned();
+        if !is_enclosed_path(&path) {
+            warn!("Skipping entry with unsafe path: {:?}", path);
+            outcome.skip(path, SkipReason::UnsafePath);
+            return Ok(());
+        }
+        if options.entry_filter.is_some() && !options.accepts(&tar_entry(entry)?.into()) {
+            outcome.skip(path, SkipReason::Excluded);
+            return Ok(());
+        }
+        let entry_path = path.clone();
 
         // Apply strip components
-        let path = if let Some(strip) = options.strip_components {
-            let components: Vec<_> = path.components().collect();
-            if components.len() <= strip {
+        let path = match options.strip_components {
+            Some(strip) => match paths::strip_components(&path, strip) {
+                Some(stripped) => stripped,
                 // Skip this entry if we're stripping more components than it has
-                continue;
+                None => {
+                    outcome.skip(path, SkipReason::Stripped);
+                    return Ok(());
+                }
+            },
+            None => path,
+        };
+        let path = options.normalize_names.apply_path(&path).into_owned();
+
+        let expected = match entry.header().entry_type() {
+            tar::EntryType::Regular | tar::EntryType::Continuous => {
+                match self.hashes.expected(&entry_path, &path, outcome) {
+                    Ok(expected) => expected,
+                    Err(e) => {
+                        outcome.fail(path, e);
+                        return Ok(());
+                    }
+                }
             }
-            PathBuf::from_iter(components.into_iter().skip(strip))
+            _ => None,
+        };
+
+        // Nor may it be written through a symlink, as one extracted
+        // earlier, that leads out of the output directory
+        if let Err(e) = entry_output_path(output_dir, &path) {
+            outcome.fail(path, e);
+            return Ok(());
+        }
+
+        let is_dir = entry.header().entry_type().is_dir();
+        let mut dest_path = if is_dir {
+            output_dir.join(&path)
         } else {
-            path.to_path_buf()
+            match self.case_folder.place(&path, output_dir, outcome) {
+                Ok(name) => output_dir.join(name),
+                Err(e) => {
+                    outcome.fail(path, e);
+                    return Ok(());
+                }
+            }
         };
 
-        let dest_path = output_dir.join(&path);
+        let prior = if is_dir {
+            Prior::Unknown
+        } else {
+            resume::prior(self.resume.as_ref(), &dest_path)
+        };
+        if prior == Prior::Done {
+            outcome.skip(path, SkipReason::Resumed);
+            return Ok(());
+        }
 
         // Handle existing files
-        if dest_path.exists() && !entry.header().entry_type().is_dir() {
-            if options.skip {
-                info!("Skipping existing file: {:?}", dest_path);
-                continue;
-            } else if options.rename {
-                let dest_path = get_unique_filename(&dest_path);
+        if dest_path.exists() && !is_dir && prior != Prior::Leftover {
+            if options.rename && !options.skip {
+                dest_path = get_unique_filename(&dest_path);
                 info!("Renaming to avoid conflict: {:?}", dest_path);
-                extract_entry(&mut entry, &dest_path)?;
-            } else if options.overwrite {
+            } else if options.overwrite && !options.skip {
                 info!("Overwriting existing file: {:?}", dest_path);
-                extract_entry(&mut entry, &dest_path)?;
+            } else {
+                info!("Skipping existing file: {:?}", dest_path);
+                outcome.skip(path, SkipReason::Exists);
+                return Ok(());
             }
-        } else {
-            extract_entry(&mut entry, &dest_path)?;
         }
-    }
 
-    info!("Successfully extracted archive");
-    Ok(())
+        // What the interrupted run left is its own, not the user's
+        if prior != Prior::Leftover {
+            if let Err(e) = undo::prepare(self.journal.as_mut(), &dest_path) {
+                outcome.fail(path, e);
+                return Ok(());
+            }
+        }
+        let resume = self.resume.as_mut();
+        let result = match expected {
+            None if entry.header().entry_type().is_hard_link() => {
+                extract_hard_link(entry, &dest_path, output_dir, options).map(|()| true)
+            }
+            None => extract_entry(entry, &dest_path, output_dir, options, resume),
+            Some(expected) => {
+                extract_hashed_file(entry, &dest_path, &self.hashes, options.apply_umask, resume)
+                    .and_then(|actual| {
+                        self.hashes
+                            .verify(&path, &dest_path, expected, &actual, outcome)
+                    })
+                    .map(|()| true)
+            }
+        };
+        let syncer = &mut self.syncer;
+        let result = result.and_then(|done| {
+            if done {
+                syncer.written(&dest_path)?;
+            }
+            Ok(done)
+        });
+        match result {
+            Ok(true) => {
+                resume::done(self.resume.as_mut(), &dest_path);
+                outcome.done(path)
+            }
+            // A device or FIFO, or a symlink the platform would not create
+            Ok(false) => outcome.skip(path, SkipReason::Unsupported),
+            Err(e) => outcome.fail(path, e),
+        }
+        Ok(())
+    }
 }
 
-/// Extract a single entry to a destination path
-fn extract_entry<R: Read>(entry: &mut tar::Entry<R>, dest_path: &Path) -> Result<()> {
+/// Extract a single entry to a destination path, returning false for a
+/// symlink left out as `options.symlink_fallback` says
+///
+/// Regular files are written through the resume journal when one is kept.
+fn extract_entry<R: Read>(
+    entry: &mut tar::Entry<R>,
+    dest_path: &Path,
+    output_dir: &Path,
+    options: &ExtractOptions,
+    resume: Option<&mut Resume>,
+) -> Result<bool> {
     debug!("Extracting: {:?}", dest_path);
 
     let header = entry.header();
@@ -906,48 +1238,135 @@ fn extract_entry<R: Read>(entry: &mut tar::Entry<R>, dest_path: &Path) -> Result
         fs::create_dir_all(parent)?;
     }
 
+    if let (Some(resume), tar::EntryType::Regular | tar::EntryType::Continuous) =
+        (resume, entry_type)
+    {
+        resume.write(entry, dest_path)?;
+        let header = entry.header().clone();
+        apply_tar_metadata(dest_path, &header, options.apply_umask);
+        return Ok(true);
+    }
+
     // Handle different entry types
     match entry_type {
-        tar::EntryType::Symlink => {
-            // Extract symlink
-            if let Some(link_target) = header.link_name()? {
-                debug!("Creating symlink: {:?} -> {:?}", dest_path, link_target);
-
-                // Remove existing file if it exists
-                if dest_path.exists() {
-                    fs::remove_file(dest_path).ok();
-                }
-
-                #[cfg(unix)]
-                {
-                    use std::os::unix::fs;
-                    fs::symlink(&link_target, dest_path)?;
-                }
-
-                #[cfg(not(unix))]
-                {
-                    warn!(
-                        "Symlinks are not supported on this platform, skipping: {:?}",
-                        dest_path
-                    );
-                }
-            }
+        // The tar crate would write these as empty regular files
+        tar::EntryType::Char | tar::EntryType::Block | tar::EntryType::Fifo => {
+            warn!("Skipping special file: {:?}", dest_path);
+            Ok(false)
         }
+        tar::EntryType::Symlink => match header.link_name()? {
+            Some(link_target) => {
+                // Later entries could be written through a link that leads
+                // out of the output directory
+                validate_symlink(output_dir, dest_path, &link_target, false)?;
+                create_symlink(
+                    output_dir,
+                    dest_path,
+                    &link_target,
+                    options.symlink_fallback,
+                )
+            }
+            None => Ok(true),
+        },
         _ => {
             // Regular file or directory
             entry.unpack(dest_path)?;
 
             // Try to preserve metadata
             let header = entry.header().clone();
-            apply_tar_metadata(dest_path, &header);
+            apply_tar_metadata(dest_path, &header, options.apply_umask);
+            Ok(true)
         }
     }
+}
 
+/// Extract a hard link entry as a link to the file extracted for the entry
+/// it names, or as a copy where the file system has no hard links
+///
+/// The target is found under the name `options` give it, so it must have
+/// been extracted before. Neither it nor the link may be reached through a
+/// symlink that leads out of `output_dir`.
+fn extract_hard_link<R: Read>(
+    entry: &mut tar::Entry<R>,
+    dest_path: &Path,
+    output_dir: &Path,
+    options: &ExtractOptions,
+) -> Result<()> {
+    let target = entry
+        .link_name()?
+        .ok_or_else(|| Error::ArchiveError(format!("Hard link {:?} has no target", dest_path)))?;
+    let stripped = paths::strip_components(&target, options.strip_components.unwrap_or(0));
+    let Some(stripped) = stripped.filter(|_| is_enclosed_path(&target)) else {
+        return Err(Error::InvalidPath(format!(
+            "Hard link {:?} points outside the archive: {:?}",
+            dest_path, target
+        )));
+    };
+    let target = entry_output_path(output_dir, &options.normalize_names.apply_path(&stripped))?;
+    let dest_path = entry_output_path(
+        output_dir,
+        dest_path.strip_prefix(output_dir).unwrap_or(dest_path),
+    )?;
+    let dest_path = dest_path.as_path();
+    if !target.is_file() {
+        return Err(Error::NotFound(format!(
+            "{:?}, which {:?} is a hard link to",
+            target, dest_path
+        )));
+    }
+
+    debug!("Linking {:?} to {:?}", dest_path, target);
+    if let Some(parent) = dest_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    if dest_path
+        .symlink_metadata()
+        .is_ok_and(|metadata| !metadata.is_dir())
+    {
+        fs::remove_file(dest_path)?;
+    }
+    if fs::hard_link(&target, dest_path).is_err() {
+        fs::copy(&target, dest_path)?;
+    }
     Ok(())
 }
 
+/// Extract a regular file entry, returning the hash of its content taken
+/// as `hashes` takes them
+fn extract_hashed_file<R: Read>(
+    entry: &mut tar::Entry<R>,
+    dest_path: &Path,
+    hashes: &HashLookup<'_>,
+    apply_umask: bool,
+    resume: Option<&mut Resume>,
+) -> Result<String> {
+    debug!("Extracting: {:?}", dest_path);
+
+    if let Some(parent) = dest_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut reader = hashes.hashing(&mut *entry);
+    match resume {
+        Some(resume) => resume.write(&mut reader, dest_path)?,
+        None => {
+            // Replace what is there, as `unpack` does, rather than write
+            // through a symlink
+            if dest_path
+                .symlink_metadata()
+                .is_ok_and(|metadata| !metadata.is_dir())
+            {
+                fs::remove_file(dest_path)?;
+            }
+            buffer::copy(&mut reader, &mut File::create(dest_path)?)?;
+        }
+    }
+    let hash = reader.hash();
+    apply_tar_metadata(dest_path, entry.header(), apply_umask);
+    Ok(hash)
+}
+
 /// Get a unique filename by appending a number
-fn get_unique_filename(path: &Path) -> PathBuf {
+pub(crate) fn get_unique_filename(path: &Path) -> PathBuf {
     let mut counter = 1;
     let stem = path.file_stem().unwrap_or_default();
     let extension = path.extension();
@@ -979,6 +1398,7 @@ pub fn pack_multiple_files<P: AsRef<Path>, Q: AsRef<Path>>(
     output: Q,
     base_dir: Option<&Path>,
     follow_symlinks: bool,
+    filter: &PackFilter,
 ) -> Result<()> {
     let output = output.as_ref();
 
@@ -989,28 +1409,285 @@ pub fn pack_multiple_files<P: AsRef<Path>, Q: AsRef<Path>>(
         fs::create_dir_all(parent)?;
     }
 
+    let options = PackOptions {
+        follow_symlinks,
+        filter: filter.clone(),
+        ..PackOptions::default()
+    };
+    let roots = named_roots(files, base_dir);
     let file = File::create(output)?;
     let mut builder = Builder::new(file);
+    let outcome = append_files(&mut builder, &roots, &options, &NoProgress)?;
 
-    for file_path in files {
-        let file_path = file_path.as_ref();
+    builder.finish()?;
+    info!("Packed {} files: {}", files.len(), outcome);
 
-        // Calculate the archive path
-        let archive_path = if let Some(base) = base_dir {
-            file_path.strip_prefix(base).unwrap_or(file_path)
-        } else {
-            file_path
-        };
+    outcome.into_result()
+}
+
+/// Pack multiple files into a tar archive compressed with `algorithm`,
+/// reporting progress to `progress`
+///
+/// Entries are named as by [`pack_multiple_files`]. The level, symlink
+/// handling and filter come from `options`.
+pub fn pack_multiple_files_with_progress<P: AsRef<Path>, Q: AsRef<Path>>(
+    files: &[P],
+    output: Q,
+    base_dir: Option<&Path>,
+    algorithm: Algorithm,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
+    let roots = named_roots(files, base_dir);
+    pack_roots_tar(&roots, output, algorithm, options, progress)
+}
+
+/// Pack each of `roots` under its name into a tar archive compressed with
+/// `algorithm`, reporting progress to `progress`
+pub fn pack_roots_tar<Q: AsRef<Path>>(
+    roots: &[PackRoot],
+    output: Q,
+    algorithm: Algorithm,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
+    let output = output.as_ref();
+
+    info!(
+        "Packing {} inputs into {:?} with {:?} compression",
+        roots.len(),
+        output,
+        algorithm
+    );
+
+    // Create output directory if it doesn't exist
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    pack_roots_tar_to_writer(roots, File::create(output)?, algorithm, options, progress)
+}
+
+/// Pack each of `roots` under its name as a tar compressed with
+/// `algorithm` into `writer`, which need not be seekable
+///
+/// Options are used as by [`pack_roots_tar`]; the writer is flushed but not
+/// closed.
+pub fn pack_roots_tar_to_writer<W: Write>(
+    roots: &[PackRoot],
+    writer: W,
+    algorithm: Algorithm,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
+    let files = PackRoot::read_paths(roots);
+    let started = Instant::now();
+    let total = input_size(&files, options);
+    progress.start(total);
+    let level = match options.time_budget {
+        Some(budget) if algorithm != Algorithm::Store => {
+            tune_level(&files, algorithm, budget, options)?
+        }
+        _ => options.level.unwrap_or(3),
+    };
+
+    let file = ProgressWrite::new(writer, progress);
+
+    let outcome = match algorithm {
+        Algorithm::Store => {
+            let mut builder = new_builder(file, options)?;
+            let outcome = append_files(&mut builder, roots, options, progress)?;
+            builder.into_inner()?.flush()?;
+            outcome
+        }
+        Algorithm::Gzip => {
+            let mut builder =
+                new_builder(GzEncoder::new(file, GzCompression::new(level)), options)?;
+            let outcome = append_files(&mut builder, roots, options, progress)?;
+            builder.into_inner()?.finish()?.flush()?;
+            outcome
+        }
+        Algorithm::Zstd if options.time_budget.is_some() => {
+            let budget = options.time_budget.unwrap_or_default();
+            let encoder =
+                BudgetedZstd::new(file, level, budget.saturating_sub(started.elapsed()), total)?;
+            let mut builder = new_builder(encoder, options)?;
+            let outcome = append_files(&mut builder, roots, options, progress)?;
+            builder.into_inner()?.finish()?.flush()?;
+            outcome
+        }
+        Algorithm::Zstd => {
+            let mut encoder = ZstdEncoder::new(file, level as i32)?;
+            encoder.include_checksum(true)?;
+            let mut builder = new_builder(encoder, options)?;
+            let outcome = append_files(&mut builder, roots, options, progress)?;
+            builder.into_inner()?.finish()?.flush()?;
+            outcome
+        }
+        Algorithm::Xz => {
+            let mut builder = new_builder(XzEncoder::new(file, level), options)?;
+            let outcome = append_files(&mut builder, roots, options, progress)?;
+            builder.into_inner()?.finish()?.flush()?;
+            outcome
+        }
+        Algorithm::Brotli => {
+            let encoder = brotli::CompressorWriter::new(file, 4096, level, 22);
+            let mut builder = new_builder(encoder, options)?;
+            let outcome = append_files(&mut builder, roots, options, progress)?;
+            // The stream is finished when the encoder is dropped
+            builder.into_inner()?.flush()?;
+            outcome
+        }
+    };
+
+    info!("Packed {} inputs: {}", roots.len(), outcome);
+    Ok(outcome)
+}
+
+/// Files named relative to `base_dir` or by their file name, directories
+/// with their own name as the top folder
+fn named_roots<P: AsRef<Path>>(files: &[P], base_dir: Option<&Path>) -> Vec<PackRoot> {
+    files
+        .iter()
+        .map(|file| PackRoot::named_in(file.as_ref(), base_dir))
+        .collect()
+}
+
+/// Add each of `roots` to the builder under its name
+fn append_files<W: Write>(
+    builder: &mut Builder<W>,
+    roots: &[PackRoot],
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
+    let mut outcome = OperationOutcome::default();
+
+    for root in roots {
+        let file_path = root.path.as_path();
+        let archive_path = root.name.as_path();
 
         if file_path.is_file() {
-            pack_file(&mut builder, file_path, archive_path, follow_symlinks)?;
+            pack_file(
+                builder,
+                file_path,
+                archive_path,
+                options,
+                progress,
+                &mut outcome,
+            )?;
+        } else if file_path.is_dir() && !root.recursive {
+            pack_directory_entry(builder, file_path, archive_path, options, &mut outcome)?;
         } else if file_path.is_dir() {
-            pack_directory_with_options(&mut builder, file_path, follow_symlinks)?;
+            pack_directory_with_options(builder, root, options, progress, &mut outcome)?;
+        } else {
+            let error =
+                Error::NotFound(format!("{:?} is neither a file nor a directory", file_path));
+            outcome.fail(archive_path, error.with_path(file_path));
         }
     }
 
-    builder.finish()?;
-    info!("Successfully packed {} files", files.len());
+    Ok(outcome)
+}
 
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pack_single_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("test.txt");
+        let archive_path = temp_dir.path().join("test.tar");
+
+        // Create test file
+        fs::write(&test_file, b"Hello, World!")?;
+
+        // Pack the file
+        pack_tar(&test_file, &archive_path)?;
+
+        // Verify archive exists
+        assert!(archive_path.exists());
+        assert!(archive_path.metadata()?.len() > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pack_directory() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_dir = temp_dir.path().join("test_dir");
+        let archive_path = temp_dir.path().join("test.tar");
+
+        // Create test directory structure
+        fs::create_dir_all(&test_dir)?;
+        fs::write(test_dir.join("file1.txt"), b"File 1")?;
+        fs::write(test_dir.join("file2.txt"), b"File 2")?;
+        fs::create_dir(test_dir.join("subdir"))?;
+        fs::write(test_dir.join("subdir").join("file3.txt"), b"File 3")?;
+
+        // Pack the directory
+        pack_tar(&test_dir, &archive_path)?;
+
+        // Verify archive exists
+        assert!(archive_path.exists());
+        assert!(archive_path.metadata()?.len() > 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_archive() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_dir = temp_dir.path().join("test_dir");
+        let archive_path = temp_dir.path().join("test.tar");
+        let extract_dir = temp_dir.path().join("extracted");
+
+        // Create test directory structure
+        fs::create_dir_all(&test_dir)?;
+        fs::write(test_dir.join("file1.txt"), b"File 1")?;
+        fs::write(test_dir.join("file2.txt"), b"File 2")?;
+
+        // Pack and then extract
+        pack_tar(&test_dir, &archive_path)?;
+        extract_tar(&archive_path, &extract_dir)?;
+
+        // Verify extracted files
+        assert!(extract_dir.join("test_dir").join("file1.txt").exists());
+        assert!(extract_dir.join("test_dir").join("file2.txt").exists());
+
+        // Verify content
+        let content1 = fs::read(extract_dir.join("test_dir").join("file1.txt"))?;
+        assert_eq!(content1, b"File 1");
+
+        let content2 = fs::read(extract_dir.join("test_dir").join("file2.txt"))?;
+        assert_eq!(content2, b"File 2");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pack_extract_preserves_content() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let test_file = temp_dir.path().join("test.txt");
+        let archive_path = temp_dir.path().join("test.tar");
+        let extract_dir = temp_dir.path().join("extracted");
+
+        // Create test file with specific content
+        let original_content = b"This is a test file with some content!";
+        fs::write(&test_file, original_content)?;
+
+        // Pack, extract, and verify
+        pack_tar(&test_file, &archive_path)?;
+        extract_tar(&archive_path, &extract_dir)?;
+
+        let extracted_file = extract_dir.join("test.txt");
+        assert!(extracted_file.exists());
+
+        let extracted_content = fs::read(&extracted_file)?;
+        assert_eq!(original_content, &extracted_content[..]);
+
+        Ok(())
+    }
 }
@@ -1,8 +1,13 @@
 //! Tar extractor implementation
 
-use super::extractor::{ArchiveEntry, ExtractEntryOptions, Extractor};
+use super::detect::{archive_format, tar_compression};
+use super::extractor::{stream_entry, ArchiveEntry, ExtractEntryOptions, Extractor};
+use super::links::create_symlink;
+use super::meta::is_global_header;
+use crate::progress::{NoProgress, ProgressSink};
+use crate::security::validate_symlink;
 use crate::strategy::Algorithm;
-use crate::{Error, Result};
+use crate::{buffer, Error, Result};
 use flate2::read::GzDecoder;
 use std::fs::{self, File};
 use std::io::{self, Read};
@@ -49,6 +54,24 @@ impl TarExtractor {
     }
 }
 
+/// Describe one tar entry from its header
+pub(crate) fn tar_entry<R: Read>(entry: &tar::Entry<'_, R>) -> Result<ArchiveEntry> {
+    let header = entry.header();
+
+    Ok(ArchiveEntry {
+        path: entry.path()?.to_path_buf(),
+        size: header.size()?,
+        compressed_size: None, // Tar doesn't store compressed size per entry
+        mode: Some(header.mode()?),
+        mtime: Some(header.mtime()? as i64),
+        is_dir: header.entry_type().is_dir(),
+        is_symlink: header.entry_type().is_symlink(),
+        link_target: header.link_name()?.map(|p| p.to_path_buf()),
+        uid: header.uid().ok().map(|u| u as u32),
+        gid: header.gid().ok().map(|g| g as u32),
+    })
+}
+
 impl Extractor for TarExtractor {
     fn entries(&self, source: &Path) -> Result<Box<dyn Iterator<Item = Result<ArchiveEntry>>>> {
         let file = File::open(source)?;
@@ -60,23 +83,8 @@ impl Extractor for TarExtractor {
 
         for entry in archive.entries()? {
             match entry {
-                Ok(entry) => {
-                    let path = entry.path()?.to_path_buf();
-                    let header = entry.header();
-
-                    entries.push(Ok(ArchiveEntry {
-                        path,
-                        size: header.size()?,
-                        compressed_size: None, // Tar doesn't store compressed size per entry
-                        mode: Some(header.mode()?),
-                        mtime: Some(header.mtime()? as i64),
-                        is_dir: header.entry_type().is_dir(),
-                        is_symlink: header.entry_type().is_symlink(),
-                        link_target: header.link_name()?.map(|p| p.to_path_buf()),
-                        uid: header.uid().ok().map(|u| u as u32),
-                        gid: header.gid().ok().map(|g| g as u32),
-                    }));
-                }
+                Ok(entry) if is_global_header(entry.header()) => {}
+                Ok(entry) => entries.push(tar_entry(&entry)),
                 Err(e) => entries.push(Err(Error::Io(e))),
             }
         }
@@ -90,6 +98,17 @@ impl Extractor for TarExtractor {
         entry: &ArchiveEntry,
         destination: &Path,
         options: ExtractEntryOptions,
+    ) -> Result<()> {
+        self.extract_entry_with_progress(source, entry, destination, options, &NoProgress)
+    }
+
+    fn extract_entry_with_progress(
+        &self,
+        source: &Path,
+        entry: &ArchiveEntry,
+        destination: &Path,
+        options: ExtractEntryOptions,
+        progress: &dyn ProgressSink,
     ) -> Result<()> {
         let file = File::open(source)?;
         let reader = self.create_reader(file)?;
@@ -101,7 +120,9 @@ impl Extractor for TarExtractor {
             let entry_path = archive_entry.path()?.to_path_buf();
 
             if entry_path == entry.path {
-                let full_path = destination.join(&entry_path);
+                let Some(full_path) = options.output_path(destination, &entry_path)? else {
+                    return Ok(());
+                };
 
                 // Check if file exists and handle according to options
                 if full_path.exists() && !options.overwrite {
@@ -123,23 +144,27 @@ impl Extractor for TarExtractor {
                         fs::create_dir_all(&full_path)?;
                     }
                     tar::EntryType::Regular | tar::EntryType::Continuous => {
-                        archive_entry.unpack(&full_path)?;
+                        let mut output_file = File::create(&full_path)?;
+                        buffer::copy_with_progress(&mut archive_entry, &mut output_file, progress)?;
                     }
                     tar::EntryType::Symlink => {
                         if let Some(link_target) = &entry.link_target {
-                            #[cfg(unix)]
-                            {
-                                use std::os::unix::fs;
-                                if full_path.exists() {
-                                    std::fs::remove_file(&full_path)?;
-                                }
-                                fs::symlink(link_target, &full_path)?;
-                            }
-                            #[cfg(not(unix))]
-                            {
-                                warn!("Symlink extraction not supported on this platform");
-                            }
+                            validate_symlink(destination, &full_path, link_target, false)?;
+                            create_symlink(
+                                destination,
+                                &full_path,
+                                link_target,
+                                options.symlink_fallback,
+                            )?;
                         }
+                        // Permissions and times would reach through to the target
+                        return Ok(());
+                    }
+                    tar::EntryType::Char | tar::EntryType::Block | tar::EntryType::Fifo => {
+                        return Err(Error::UnsupportedOperation(format!(
+                            "Extracting special file {:?}",
+                            entry.path
+                        )));
                     }
                     _ => {
                         warn!("Unsupported entry type: {:?}", header.entry_type());
@@ -180,6 +205,41 @@ impl Extractor for TarExtractor {
         )))
     }
 
+    fn open_entry(&self, source: &Path, entry: &ArchiveEntry) -> Result<Box<dyn Read + Send>> {
+        let source = source.to_path_buf();
+        let wanted = entry.path.clone();
+        let compression = self.compression;
+
+        stream_entry(move |writer| {
+            let extractor = TarExtractor { compression };
+            let reader = extractor.create_reader(File::open(&source)?)?;
+            let mut archive = Archive::new(reader);
+
+            for archive_entry in archive.entries()? {
+                let mut archive_entry = archive_entry?;
+                if archive_entry.path()? != wanted {
+                    continue;
+                }
+                return match archive_entry.header().entry_type() {
+                    tar::EntryType::Regular | tar::EntryType::Continuous => {
+                        buffer::copy(&mut archive_entry, writer)?;
+                        Ok(())
+                    }
+                    other => Err(Error::InvalidPath(format!(
+                        "Not a regular file ({:?}): {}",
+                        other,
+                        wanted.display()
+                    ))),
+                };
+            }
+
+            Err(Error::NotFound(format!(
+                "Entry not found in archive: {:?}",
+                wanted
+            )))
+        })
+    }
+
     fn format_name(&self) -> &'static str {
         match self.compression {
             None => "tar",
@@ -193,21 +253,17 @@ impl Extractor for TarExtractor {
 }
 
 /// Create an extractor for the given tar file based on its extension
+///
+/// A name that is not a tar's, such as `.zip`, is an error; one without a
+/// known extension is read as an uncompressed tar.
 pub fn create_tar_extractor(path: &Path) -> Result<Box<dyn Extractor>> {
-    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
-
-    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-
-    let compression = if stem.ends_with(".tar") {
-        match ext {
-            "gz" => Some(Algorithm::Gzip),
-            "zst" => Some(Algorithm::Zstd),
-            "xz" => Some(Algorithm::Xz),
-            "br" => Some(Algorithm::Brotli),
-            _ => None,
-        }
-    } else {
-        None
+    let compression = match archive_format(path) {
+        Some(format) => match tar_compression(format) {
+            Some(Algorithm::Store) => None,
+            Some(algorithm) => Some(algorithm),
+            None => return Err(Error::UnsupportedFormat(format.to_string())),
+        },
+        None => None,
     };
 
     Ok(Box::new(TarExtractor { compression }))
@@ -0,0 +1,270 @@
+//! Taking an extraction back
+//!
+//! With [`ExtractOptions::undoable`], an extraction keeps a journal in
+//! `.flux-backup/<run>/` inside the output directory. Files it is about to
+//! overwrite are moved there first rather than lost, and every file and
+//! directory it creates is listed. [`undo_extraction`] reads the newest
+//! journal and puts the directory back as it was: what the extraction
+//! created is removed and the originals are moved back into place.
+//!
+//! [`ExtractOptions::undoable`]: crate::archive::ExtractOptions::undoable
+
+use crate::utils::move_path;
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, warn};
+
+/// Directory in the output directory that journals and replaced files are
+/// kept in
+pub const BACKUP_DIR: &str = ".flux-backup";
+
+const JOURNAL_FILE: &str = "journal.json";
+
+/// What one extraction changed, with paths relative to the output directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Journal {
+    /// Milliseconds since the Unix epoch when the extraction started
+    started: u64,
+    /// Files and directories that did not exist before, in creation order
+    created: Vec<PathBuf>,
+    /// Files that were overwritten, kept below the run's directory
+    replaced: Vec<PathBuf>,
+}
+
+/// The journal of an extraction in progress
+///
+/// Nothing is written until the extraction first changes something; the
+/// journal is saved when the extraction finishes, or when it is dropped
+/// after failing part way.
+pub(crate) struct UndoJournal {
+    output_dir: PathBuf,
+    run_dir: PathBuf,
+    journal: Journal,
+    seen: HashSet<PathBuf>,
+    dirty: bool,
+}
+
+impl UndoJournal {
+    /// A journal for extracting into `output_dir`
+    pub(crate) fn new(output_dir: &Path) -> Self {
+        let started = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        // Extractions started within the same millisecond by one process
+        // still get a directory each
+        static RUNS: AtomicU64 = AtomicU64::new(0);
+        let run = format!(
+            "{}-{}-{}",
+            started,
+            std::process::id(),
+            RUNS.fetch_add(1, Ordering::Relaxed)
+        );
+        Self {
+            output_dir: output_dir.to_path_buf(),
+            run_dir: output_dir.join(BACKUP_DIR).join(run),
+            journal: Journal {
+                started,
+                ..Journal::default()
+            },
+            seen: HashSet::new(),
+            dirty: false,
+        }
+    }
+
+    /// Get `dest` ready to be written: note it and any parent directories
+    /// that do not exist yet as created, or move the file already there
+    /// into the backup
+    ///
+    /// A file this extraction wrote itself, as for an entry stored twice, is
+    /// left to be overwritten.
+    pub(crate) fn prepare(&mut self, dest: &Path) -> Result<()> {
+        let Ok(relative) = dest.strip_prefix(&self.output_dir) else {
+            return Ok(());
+        };
+        if relative.as_os_str().is_empty() || self.seen.contains(relative) {
+            return Ok(());
+        }
+
+        let mut missing = Vec::new();
+        for ancestor in relative.ancestors().skip(1) {
+            if ancestor.as_os_str().is_empty()
+                || self.seen.contains(ancestor)
+                || self.output_dir.join(ancestor).symlink_metadata().is_ok()
+            {
+                break;
+            }
+            missing.push(ancestor.to_path_buf());
+        }
+        for directory in missing.into_iter().rev() {
+            self.record_created(directory);
+        }
+
+        match dest.symlink_metadata() {
+            Ok(meta) if meta.is_dir() => {}
+            Ok(_) => {
+                let backup = self.run_dir.join(relative);
+                if let Some(parent) = backup.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                move_path(dest, &backup)?;
+                debug!("Kept {:?} in {:?}", dest, backup);
+                self.seen.insert(relative.to_path_buf());
+                self.journal.replaced.push(relative.to_path_buf());
+                self.dirty = true;
+            }
+            Err(_) => self.record_created(relative.to_path_buf()),
+        }
+        Ok(())
+    }
+
+    fn record_created(&mut self, relative: PathBuf) {
+        self.seen.insert(relative.clone());
+        self.journal.created.push(relative);
+        self.dirty = true;
+    }
+
+    /// Write the journal, if the extraction changed anything
+    pub(crate) fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.run_dir)?;
+        let json = serde_json::to_vec_pretty(&self.journal)
+            .map_err(|e| Error::Other(format!("Failed to write extraction journal: {}", e)))?;
+        fs::write(self.run_dir.join(JOURNAL_FILE), json)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// [`UndoJournal::prepare`], when a journal is kept
+pub(crate) fn prepare(journal: Option<&mut UndoJournal>, dest: &Path) -> Result<()> {
+    journal.map_or(Ok(()), |journal| journal.prepare(dest))
+}
+
+impl Drop for UndoJournal {
+    fn drop(&mut self) {
+        if let Err(e) = self.save() {
+            warn!("Could not save the journal in {:?}: {}", self.run_dir, e);
+        }
+    }
+}
+
+/// What [`undo_extraction`] did
+#[derive(Debug, Default)]
+pub struct UndoReport {
+    /// Files and directories the extraction created, now removed
+    pub removed: usize,
+    /// Overwritten files moved back into place
+    pub restored: usize,
+    /// Directories the extraction created that were left because something
+    /// else has been put in them since
+    pub kept: Vec<PathBuf>,
+}
+
+impl fmt::Display for UndoReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} removed, {} restored", self.removed, self.restored)?;
+        if !self.kept.is_empty() {
+            write!(
+                f,
+                ", {} directories kept as they are not empty",
+                self.kept.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Take back the newest undoable extraction into `output_dir`
+///
+/// Files it created are removed, as are the directories it created once
+/// empty, and the files it overwrote are moved back. Its journal is then
+/// deleted, so undoing again takes back the extraction before it. Fails with
+/// [`Error::NotFound`] when there is nothing left to undo.
+pub fn undo_extraction<P: AsRef<Path>>(output_dir: P) -> Result<UndoReport> {
+    let output_dir = output_dir.as_ref();
+    let backup_dir = output_dir.join(BACKUP_DIR);
+    let (run_dir, journal) = latest_run(&backup_dir)?.ok_or_else(|| {
+        Error::NotFound(format!(
+            "No undoable extraction in {} (extract with --backup to keep one)",
+            output_dir.display()
+        ))
+    })?;
+    info!("Undoing the extraction journaled in {:?}", run_dir);
+
+    let mut report = UndoReport::default();
+    for relative in journal.created.iter().rev() {
+        let path = output_dir.join(relative);
+        let removed = match path.symlink_metadata() {
+            Ok(meta) if meta.is_dir() => match fs::remove_dir(&path) {
+                Ok(()) => true,
+                Err(_) => {
+                    report.kept.push(relative.clone());
+                    false
+                }
+            },
+            Ok(_) => {
+                fs::remove_file(&path)?;
+                true
+            }
+            Err(_) => false,
+        };
+        if removed {
+            report.removed += 1;
+        }
+    }
+
+    for relative in &journal.replaced {
+        let path = output_dir.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if path.symlink_metadata().is_ok_and(|meta| !meta.is_dir()) {
+            fs::remove_file(&path)?;
+        }
+        move_path(&run_dir.join(relative), &path)?;
+        report.restored += 1;
+    }
+
+    fs::remove_dir_all(&run_dir)?;
+    // Only goes when no other journal is left in it
+    let _ = fs::remove_dir(&backup_dir);
+
+    info!("Undo complete: {}", report);
+    Ok(report)
+}
+
+/// The directory and journal of the newest run in `backup_dir`
+fn latest_run(backup_dir: &Path) -> Result<Option<(PathBuf, Journal)>> {
+    let entries = match fs::read_dir(backup_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut latest: Option<(PathBuf, Journal)> = None;
+    for entry in entries {
+        let run_dir = entry?.path();
+        let Ok(json) = fs::read(run_dir.join(JOURNAL_FILE)) else {
+            continue;
+        };
+        let journal: Journal = serde_json::from_slice(&json).map_err(|e| {
+            Error::ArchiveError(format!("Cannot read the journal in {:?}: {}", run_dir, e))
+        })?;
+        let newer = latest
+            .as_ref()
+            .is_none_or(|(dir, newest)| (journal.started, &run_dir) > (newest.started, dir));
+        if newer {
+            latest = Some((run_dir, journal));
+        }
+    }
+    Ok(latest)
+}
@@ -0,0 +1,332 @@
+//! Integrity check of an archive's entries
+//!
+//! [`verify`] reads every entry without writing anything and compares it
+//! with the checksum the format stores. Zip and 7z keep a CRC per entry.
+//! Compressed tars only have one for the whole stream (gzip, xz, and zstd
+//! when the frame carries one), which is checked once the stream ends;
+//! plain and brotli tars have none.
+
+use super::detect::detect_format;
+use super::meta::is_global_header;
+use crate::{buffer, Error, Result};
+use sevenz_rust::{Password, SevenZReader};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+use zip::ZipArchive;
+
+/// Zstandard frame magic number, little endian
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Outcome of checking one entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EntryStatus {
+    /// The data matches its checksum
+    Ok,
+    /// The data could not be read or does not match its checksum
+    Corrupt(String),
+    /// The data was read, but the archive stores nothing to compare it with
+    NoChecksum,
+    /// The entry needs a password to read
+    Encrypted,
+}
+
+impl EntryStatus {
+    /// Short upper-case name used in reports
+    pub fn label(&self) -> &'static str {
+        match self {
+            EntryStatus::Ok => "OK",
+            EntryStatus::Corrupt(_) => "CORRUPT",
+            EntryStatus::NoChecksum => "NO-CHECKSUM",
+            EntryStatus::Encrypted => "ENCRYPTED",
+        }
+    }
+}
+
+/// One checked entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryCheck {
+    /// Path within the archive
+    pub path: PathBuf,
+    /// Uncompressed size
+    pub size: u64,
+    /// What the check found
+    pub status: EntryStatus,
+}
+
+/// Result of verifying a whole archive
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VerifyReport {
+    /// The archive that was checked
+    pub archive: PathBuf,
+    /// Format such as `tar.zst`, `zip` or `7z`
+    pub format: String,
+    /// Every file and symlink, in archive order; directories hold no data
+    /// and are left out
+    pub entries: Vec<EntryCheck>,
+    /// Problem with the archive as a whole, such as a stream checksum
+    /// mismatch or entries that could not be reached after damage
+    pub error: Option<String>,
+}
+
+impl VerifyReport {
+    /// Entries whose data matched a checksum
+    pub fn ok(&self) -> usize {
+        self.count(|status| *status == EntryStatus::Ok)
+    }
+
+    /// Entries that are damaged
+    pub fn corrupt(&self) -> usize {
+        self.count(|status| matches!(status, EntryStatus::Corrupt(_)))
+    }
+
+    /// Entries that were read but could not be checked
+    pub fn unchecked(&self) -> usize {
+        self.count(|status| matches!(status, EntryStatus::NoChecksum | EntryStatus::Encrypted))
+    }
+
+    /// Whether nothing damaged was found
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none() && self.corrupt() == 0
+    }
+
+    fn count(&self, pred: impl Fn(&EntryStatus) -> bool) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| pred(&entry.status))
+            .count()
+    }
+}
+
+/// Plain-text report, one line per entry followed by a summary
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Archive: {}", self.archive.display())?;
+        writeln!(f, "Format: {}", self.format)?;
+        writeln!(f)?;
+        for entry in &self.entries {
+            write!(f, "{:<12} {}", entry.status.label(), entry.path.display())?;
+            if let EntryStatus::Corrupt(reason) = &entry.status {
+                write!(f, ": {}", reason)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f)?;
+        if let Some(error) = &self.error {
+            writeln!(f, "Error: {}", error)?;
+        }
+        writeln!(
+            f,
+            "{} entries: {} ok, {} corrupt, {} unchecked",
+            self.entries.len(),
+            self.ok(),
+            self.corrupt(),
+            self.unchecked()
+        )
+    }
+}
+
+/// Check every entry of `archive` against its stored checksum
+pub fn verify<P: AsRef<Path>>(archive: P) -> Result<VerifyReport> {
+    verify_with_progress(archive, |_| {})
+}
+
+/// Like [`verify`], calling `progress` after each entry is read
+///
+/// Entries of a compressed tar are reported before the stream checksum
+/// at the end has been compared, so their final status is only in the
+/// returned report.
+pub fn verify_with_progress<P, F>(archive: P, mut progress: F) -> Result<VerifyReport>
+where
+    P: AsRef<Path>,
+    F: FnMut(&EntryCheck),
+{
+    let archive = archive.as_ref();
+    debug!("Verifying {:?}", archive);
+
+    let format = detect_format(archive)?;
+
+    let mut report = VerifyReport {
+        archive: archive.to_path_buf(),
+        format: format.to_string(),
+        ..VerifyReport::default()
+    };
+    match format {
+        "zip" => verify_zip(archive, &mut report, &mut progress)?,
+        "7z" => verify_7z(archive, &mut report, &mut progress)?,
+        _ => verify_tar(archive, format, &mut report, &mut progress)?,
+    }
+
+    debug!(
+        "Verified {:?}: {} ok, {} corrupt, {} unchecked",
+        archive,
+        report.ok(),
+        report.corrupt(),
+        report.unchecked()
+    );
+    Ok(report)
+}
+
+fn verify_zip(
+    archive: &Path,
+    report: &mut VerifyReport,
+    progress: &mut dyn FnMut(&EntryCheck),
+) -> Result<()> {
+    let mut zip = ZipArchive::new(File::open(archive)?)?;
+
+    for i in 0..zip.len() {
+        let (path, size, encrypted) = {
+            let entry = zip.by_index_raw(i)?;
+            if entry.is_dir() {
+                continue;
+            }
+            (PathBuf::from(entry.name()), entry.size(), entry.encrypted())
+        };
+
+        let status = if encrypted {
+            EntryStatus::Encrypted
+        } else {
+            // The reader compares the CRC once the entry is fully read
+            match zip
+                .by_index(i)
+                .map_err(|e| e.to_string())
+                .and_then(|mut entry| {
+                    buffer::copy(&mut entry, &mut io::sink()).map_err(|e| e.to_string())
+                }) {
+                Ok(_) => EntryStatus::Ok,
+                Err(e) => EntryStatus::Corrupt(e),
+            }
+        };
+        report.entries.push(EntryCheck { path, size, status });
+        progress(report.entries.last().unwrap());
+    }
+
+    Ok(())
+}
+
+fn verify_7z(
+    archive: &Path,
+    report: &mut VerifyReport,
+    progress: &mut dyn FnMut(&EntryCheck),
+) -> Result<()> {
+    let mut reader = match SevenZReader::open(archive, Password::empty()) {
+        Ok(reader) => reader,
+        Err(sevenz_rust::Error::PasswordRequired) => {
+            report.error = Some("The archive is encrypted".to_string());
+            return Ok(());
+        }
+        Err(e) => {
+            return Err(Error::ArchiveError(format!(
+                "Failed to read 7z archive: {}",
+                e
+            )))
+        }
+    };
+
+    // The reader checks each entry's CRC as its last byte is read
+    let result = reader.for_each_entries(|entry, data| {
+        if entry.is_directory || entry.is_anti_item {
+            return Ok(true);
+        }
+        let status = match buffer::copy(data, &mut io::sink()) {
+            Ok(_) if entry.has_crc => EntryStatus::Ok,
+            Ok(_) => EntryStatus::NoChecksum,
+            Err(e) => EntryStatus::Corrupt(e.to_string()),
+        };
+        report.entries.push(EntryCheck {
+            path: PathBuf::from(&entry.name),
+            size: entry.size,
+            status,
+        });
+        progress(report.entries.last().unwrap());
+        Ok(true)
+    });
+    if let Err(e) = result {
+        report.error = Some(e.to_string());
+    }
+
+    Ok(())
+}
+
+fn verify_tar(
+    archive: &Path,
+    format: &str,
+    report: &mut VerifyReport,
+    progress: &mut dyn FnMut(&EntryCheck),
+) -> Result<()> {
+    let file = File::open(archive)?;
+    let (reader, checksummed): (Box<dyn Read>, bool) = match format {
+        "tar.gz" => (Box::new(flate2::read::GzDecoder::new(file)), true),
+        "tar.xz" => (Box::new(xz2::read::XzDecoder::new(file)), true),
+        "tar.zst" => (
+            Box::new(zstd::stream::read::Decoder::new(file)?),
+            zstd_has_checksum(archive)?,
+        ),
+        "tar.br" => (Box::new(brotli::Decompressor::new(file, 4096)), false),
+        _ => (Box::new(file), false),
+    };
+    let mut tar = tar::Archive::new(reader);
+
+    // Entries that read fine are settled once the stream checksum is known
+    let mut damaged = false;
+    for entry in tar.entries()? {
+        let mut entry = match entry {
+            Ok(entry) => entry,
+            Err(e) => {
+                report.error = Some(format!("Could not read the next entry: {}", e));
+                damaged = true;
+                break;
+            }
+        };
+        if entry.header().entry_type().is_dir() || is_global_header(entry.header()) {
+            continue;
+        }
+
+        let path = entry
+            .path()
+            .map(|path| path.into_owned())
+            .unwrap_or_else(|_| {
+                PathBuf::from(String::from_utf8_lossy(&entry.path_bytes()).as_ref())
+            });
+        let size = entry.size();
+        let status = match buffer::copy(&mut entry, &mut io::sink()) {
+            Ok(_) => EntryStatus::NoChecksum,
+            Err(e) => {
+                damaged = true;
+                EntryStatus::Corrupt(e.to_string())
+            }
+        };
+        report.entries.push(EntryCheck { path, size, status });
+        progress(report.entries.last().unwrap());
+        if damaged {
+            report.error = Some("Entries after the damaged one could not be read".to_string());
+            break;
+        }
+    }
+    if damaged {
+        return Ok(());
+    }
+
+    // The decoder compares the stream checksum when it reaches the end
+    let mut reader = tar.into_inner();
+    if let Err(e) = buffer::copy(&mut reader, &mut io::sink()) {
+        report.error = Some(format!("The compressed stream is damaged: {}", e));
+        return Ok(());
+    }
+    if checksummed {
+        for entry in &mut report.entries {
+            entry.status = EntryStatus::Ok;
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a zstd file's first frame stores a content checksum
+fn zstd_has_checksum(path: &Path) -> Result<bool> {
+    let mut header = [0u8; 5];
+    File::open(path)?.read_exact(&mut header)?;
+    Ok(header[..4] == ZSTD_MAGIC && header[4] & 0x04 != 0)
+}
@@ -0,0 +1,63 @@
+//! Limits on the directory walks of packing
+//!
+//! With symlinks followed, the same directory can be reached through more
+//! than one path: a link back to an ancestor makes a cycle, and two links
+//! to one directory would pack its contents twice. Walks remember each
+//! directory they enter by its device and inode (its canonical path where
+//! those are not available) and leave out any directory seen before.
+
+use crate::archive::SkipReason;
+use std::collections::HashSet;
+#[cfg(not(unix))]
+use std::path::PathBuf;
+use walkdir::DirEntry;
+
+#[cfg(unix)]
+type DirId = (u64, u64);
+#[cfg(not(unix))]
+type DirId = PathBuf;
+
+/// Decides which directories a walk leaves out
+#[derive(Debug, Default)]
+pub(crate) struct WalkLimits {
+    max_depth: Option<usize>,
+    /// Directories entered so far, tracked only when following symlinks
+    visited: Option<HashSet<DirId>>,
+}
+
+impl WalkLimits {
+    pub(crate) fn new(follow_symlinks: bool, max_depth: Option<usize>) -> Self {
+        Self {
+            max_depth,
+            visited: follow_symlinks.then(HashSet::new),
+        }
+    }
+
+    /// Why the walk should leave out `entry` and everything in it, if it
+    /// should; entries other than directories are never left out
+    pub(crate) fn leave_out(&mut self, entry: &DirEntry) -> Option<SkipReason> {
+        if !entry.file_type().is_dir() {
+            return None;
+        }
+        if self.max_depth.is_some_and(|depth| entry.depth() > depth) {
+            return Some(SkipReason::TooDeep);
+        }
+        let visited = self.visited.as_mut()?;
+        match dir_id(entry) {
+            Some(id) if !visited.insert(id) => Some(SkipReason::Revisited),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(unix)]
+fn dir_id(entry: &DirEntry) -> Option<DirId> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = entry.metadata().ok()?;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn dir_id(entry: &DirEntry) -> Option<DirId> {
+    std::fs::canonicalize(entry.path()).ok()
+}
@@ -1,9 +1,29 @@
 //! Zip archive operations
 
-use crate::archive::{ArchiveEntry, ExtractOptions};
-use crate::{Error, Result};
+use crate::archive::case_fold::CaseFolder;
+use crate::archive::estimate::input_size;
+use crate::archive::mapped::InputData;
+use crate::archive::order::extraction_order;
+use crate::archive::paths;
+#[cfg(unix)]
+use crate::archive::permissions;
+use crate::archive::resume::{self, Prior, Resume};
+use crate::archive::spanned::{self, SpannedReader};
+use crate::archive::tamper::HashLookup;
+use crate::archive::undo::{self, UndoJournal};
+use crate::archive::walk::WalkLimits;
+use crate::archive::zip_extra::{parse_extra_fields, ExtraField};
+use crate::archive::zip_extractor::zip_entry;
+use crate::archive::{
+    ArchiveEntry, ExtractOptions, OperationOutcome, PackEntry, PackFilter, PackOptions, PackRoot,
+    SkipReason,
+};
+use crate::durability::Syncer;
+use crate::progress::{NoProgress, ProgressSink, ProgressWrite};
+use crate::{buffer, Error, Result};
+use std::borrow::Cow;
 use std::fs::{self, File};
-use std::io::{Read, Write};
+use std::io::{Read, Seek, Write};
 use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 use walkdir::WalkDir;
@@ -11,9 +31,27 @@ use zip::write::FileOptions;
 use zip::CompressionMethod;
 use zip::{ZipArchive, ZipWriter};
 
+/// Something a zip archive can be read from
+pub(crate) trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+/// Open the zip archive at `path`, reading the volumes of a split archive
+/// as one
+pub(crate) fn open_zip(path: &Path) -> Result<ZipArchive<Box<dyn ReadSeek>>> {
+    let reader: Box<dyn ReadSeek> = match spanned::volumes(path) {
+        Some(volumes) => {
+            debug!("Reading {:?} as {} volumes", path, volumes.len());
+            Box::new(SpannedReader::open(&volumes)?)
+        }
+        None => Box::new(File::open(path)?),
+    };
+    Ok(ZipArchive::new(reader)?)
+}
+
 /// Pack files into a zip archive
 pub fn pack_zip<P: AsRef<Path>, Q: AsRef<Path>>(input: P, output: Q) -> Result<()> {
-    pack_zip_with_options(input, output, false)
+    pack_zip_with_options(input, output, false, &PackFilter::default())
 }
 
 /// Pack files into a zip archive with options
@@ -21,34 +59,70 @@ pub fn pack_zip_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
     input: P,
     output: Q,
     follow_symlinks: bool,
+    filter: &PackFilter,
 ) -> Result<()> {
+    let options = PackOptions {
+        follow_symlinks,
+        filter: filter.clone(),
+        ..PackOptions::default()
+    };
+    pack_zip_with_progress(input, output, &options, &NoProgress)?.into_result()
+}
+
+/// Pack files into a zip archive with options, reporting progress to
+/// `progress`
+///
+/// A directory's contents are stored at the top of the archive, where a
+/// tar keeps them below the directory's name. The metadata record of
+/// `options`, if any, is kept in the archive comment.
+pub fn pack_zip_with_progress<P: AsRef<Path>, Q: AsRef<Path>>(
+    input: P,
+    output: Q,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
     let input = input.as_ref();
     let output = output.as_ref();
-
     info!(
         "Packing {:?} into ZIP {:?} (follow_symlinks: {})",
-        input, output, follow_symlinks
+        input, output, options.follow_symlinks
     );
+    progress.start(input_size(&[input], options));
 
     // Create output directory if it doesn't exist
     if let Some(parent) = output.parent() {
         fs::create_dir_all(parent)?;
     }
 
-    let file = File::create(output)?;
+    let file = ProgressWrite::new(File::create(output)?, progress);
     let mut zip = ZipWriter::new(file);
 
-    let options = FileOptions::default()
+    let file_options = FileOptions::default()
         .compression_method(CompressionMethod::Deflated)
         .unix_permissions(0o755);
+    let mut outcome = OperationOutcome::default();
 
     if input.is_file() {
         // Pack single file
         let file_name = input.file_name().unwrap().to_string_lossy();
-        pack_file_to_zip(&mut zip, input, &file_name, options)?;
+        pack_file_to_zip(
+            &mut zip,
+            input,
+            &file_name,
+            file_options,
+            options,
+            progress,
+            &mut outcome,
+        )?;
     } else if input.is_dir() {
         // Pack directory recursively
-        pack_directory_to_zip(&mut zip, input, follow_symlinks)?;
+        pack_directory_to_zip(
+            &mut zip,
+            &PackRoot::new(input, ""),
+            options,
+            progress,
+            &mut outcome,
+        )?;
     } else {
         return Err(Error::InvalidPath(format!(
             "{:?} is neither a file nor a directory",
@@ -56,100 +130,359 @@ pub fn pack_zip_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
         )));
     }
 
+    if let Some(metadata) = options.stored_metadata() {
+        zip.set_comment(metadata.to_comment());
+    }
     zip.finish()?;
-    info!("Successfully packed ZIP archive: {:?}", output);
+    info!("Packed ZIP archive {:?}: {}", output, outcome);
 
-    Ok(())
+    Ok(outcome)
+}
+
+/// Pack multiple files into a zip archive, reporting progress to `progress`
+///
+/// Files are named relative to `base_dir`, or by their file name without
+/// one; directories keep their own name as the top folder.
+pub fn pack_multiple_zip<P: AsRef<Path>, Q: AsRef<Path>>(
+    files: &[P],
+    output: Q,
+    base_dir: Option<&Path>,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
+    let roots: Vec<_> = files
+        .iter()
+        .map(|file| PackRoot::named_in(file.as_ref(), base_dir))
+        .collect();
+    pack_roots_zip(&roots, output, options, progress)
+}
+
+/// Pack each of `roots` under its name into a zip archive, reporting
+/// progress to `progress`
+pub fn pack_roots_zip<Q: AsRef<Path>>(
+    roots: &[PackRoot],
+    output: Q,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+) -> Result<OperationOutcome> {
+    let output = output.as_ref();
+    let files = PackRoot::read_paths(roots);
+
+    info!("Packing {} inputs into ZIP {:?}", roots.len(), output);
+    progress.start(input_size(&files, options));
+
+    // Create output directory if it doesn't exist
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = ProgressWrite::new(File::create(output)?, progress);
+    let mut zip = ZipWriter::new(file);
+    let mut outcome = OperationOutcome::default();
+
+    for root in roots {
+        let file_path = root.path.as_path();
+        let name = paths::entry_key(&root.name);
+
+        if file_path.is_file() {
+            let file_options = FileOptions::<'static, ()>::default()
+                .compression_method(CompressionMethod::Deflated);
+            pack_file_to_zip(
+                &mut zip,
+                file_path,
+                &name,
+                file_options,
+                options,
+                progress,
+                &mut outcome,
+            )?;
+        } else if file_path.is_dir() && !root.recursive {
+            match fs::metadata(file_path) {
+                Ok(metadata) => add_directory_to_zip(
+                    &mut zip,
+                    file_path,
+                    &name,
+                    &metadata,
+                    options,
+                    &mut outcome,
+                )?,
+                Err(e) => outcome.fail(name, Error::from(e).with_path(file_path)),
+            }
+        } else if file_path.is_dir() {
+            pack_directory_to_zip(&mut zip, root, options, progress, &mut outcome)?;
+        } else {
+            let error =
+                Error::NotFound(format!("{:?} is neither a file nor a directory", file_path));
+            outcome.fail(name, error.with_path(file_path));
+        }
+    }
+
+    if let Some(metadata) = options.stored_metadata() {
+        zip.set_comment(metadata.to_comment());
+    }
+    zip.finish()?;
+    info!("Packed {} inputs: {}", roots.len(), outcome);
+
+    Ok(outcome)
 }
 
 /// Pack a single file into the zip
+///
+/// A file that cannot be opened is recorded as failed and nothing is
+/// written for it; only errors writing the archive itself are returned.
 fn pack_file_to_zip<W: Write + std::io::Seek>(
     zip: &mut ZipWriter<W>,
     path: &Path,
     name: &str,
-    options: FileOptions<'static, ()>,
+    file_options: FileOptions<'static, ()>,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+    outcome: &mut OperationOutcome,
 ) -> Result<()> {
+    let Some(name) = options.stored_name(Path::new(name), false) else {
+        return Ok(());
+    };
+    let name = &*paths::entry_key(&name);
     debug!("Adding file to ZIP: {:?} as {}", path, name);
 
-    let mut file = File::open(path)?;
-    let metadata = file.metadata()?;
+    let opened = File::open(path).and_then(|file| Ok((file.metadata()?, file)));
+    let (metadata, file) = match opened {
+        Ok(opened) => opened,
+        Err(e) => {
+            outcome.fail(name, Error::from(e).with_path(path));
+            return Ok(());
+        }
+    };
 
+    let (name, file_options) = entry_options(path, name, file_options, &metadata, options);
+
+    progress.file(path);
+    zip.start_file(&*name, file_options)?;
+    InputData::new(file, path, Path::new(&*name), options).copy_to(zip, progress)?;
+    outcome.done(&*name);
+
+    Ok(())
+}
+
+/// The name and `file_options` of the entry `name` for `path`, with the
+/// modification time, clamped as `options` asks, and the permissions of
+/// `metadata`, after the entry transform of `options`
+///
+/// Zip timestamps carry no time zone; they are written in UTC so the same
+/// inputs give the same archive everywhere. Zip stores no owner, so one
+/// set by the transform is dropped.
+pub(super) fn entry_options<'a>(
+    path: &Path,
+    name: &'a str,
+    file_options: FileOptions<'static, ()>,
+    metadata: &fs::Metadata,
+    options: &PackOptions,
+) -> (Cow<'a, str>, FileOptions<'static, ()>) {
+    let mut entry = PackEntry::new(path, PathBuf::from(name), metadata.is_dir(), false);
+    entry.mtime = metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|modified| options.stored_mtime(modified.as_secs()));
     #[cfg(unix)]
-    let options = {
+    {
         use std::os::unix::fs::PermissionsExt;
-        options.unix_permissions(metadata.permissions().mode())
-    };
+        entry.mode = Some(metadata.permissions().mode());
+    }
 
-    // Note: zip crate's FileOptions handles last modified time automatically from file metadata
+    let name = match &options.transform {
+        Some(transform) => {
+            transform.apply(&mut entry);
+            Cow::Owned(paths::entry_key(&entry.path))
+        }
+        None => Cow::Borrowed(name),
+    };
 
-    zip.start_file(name, options)?;
-    let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)?;
-    zip.write_all(&buffer)?;
+    let mut file_options = file_options;
+    if let Some(mtime) = entry.mtime {
+        file_options = file_options.last_modified_time(zip_datetime(mtime));
+    }
+    if let Some(mode) = entry.mode {
+        file_options = file_options.unix_permissions(mode);
+    }
+    (name, file_options)
+}
 
-    Ok(())
+/// The zip timestamp of `secs` seconds after the Unix epoch, limited to the
+/// years zip can store (1980-2107)
+fn zip_datetime(secs: u64) -> zip::DateTime {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil date from days since the epoch (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    if year > 2107 {
+        return zip::DateTime::from_date_and_time(2107, 12, 31, 23, 59, 58).unwrap_or_default();
+    }
+    zip::DateTime::from_date_and_time(
+        year as u16,
+        month,
+        day,
+        (rem / 3_600) as u8,
+        ((rem % 3_600) / 60) as u8,
+        (rem % 60) as u8,
+    )
+    .unwrap_or_default()
 }
 
-/// Pack a directory into the zip
+/// Pack a directory into the zip, skipping entries excluded by the filter
+/// and directories left out by [`WalkLimits`]
+///
+/// Entries are named below the name of `root`. Entries that cannot be read
+/// are recorded as failed.
 fn pack_directory_to_zip<W: Write + std::io::Seek>(
     zip: &mut ZipWriter<W>,
-    dir: &Path,
-    follow_symlinks: bool,
+    root: &PackRoot,
+    options: &PackOptions,
+    progress: &dyn ProgressSink,
+    outcome: &mut OperationOutcome,
 ) -> Result<()> {
-    let base_path = dir.canonicalize()?;
+    let dir = root.path.as_path();
+    let mut limits = WalkLimits::new(options.follow_symlinks, options.max_depth);
+    // Get relative path
+    let entry_name = |path: &Path| {
+        paths::entry_key(&root.entry_name(path)) // Ensure forward slashes in ZIP
+    };
 
-    for entry in WalkDir::new(dir).follow_links(follow_symlinks) {
-        let entry = entry.map_err(|e| Error::Other(e.to_string()))?;
+    let mut left_out = Vec::new();
+    for entry in WalkDir::new(dir)
+        .follow_links(options.follow_symlinks)
+        .into_iter()
+        .filter_entry(|entry| {
+            let reason = if options.filter.excludes_entry(dir, entry) {
+                Some(SkipReason::Excluded)
+            } else {
+                limits.leave_out(entry)
+            };
+            if let Some(reason) = reason {
+                left_out.push((entry.path().to_path_buf(), reason));
+            }
+            reason.is_none()
+        })
+    {
+        let entry = match entry {
+            Ok(entry) => entry,
+            // A link back to a directory being packed
+            Err(e) if e.loop_ancestor().is_some() => {
+                let path = e.path().unwrap_or(dir);
+                warn!("Skipping {:?}: {}", path, SkipReason::Revisited);
+                outcome.skip(entry_name(path), SkipReason::Revisited);
+                continue;
+            }
+            Err(e) => {
+                outcome.fail(entry_name(e.path().unwrap_or(dir)), e.into());
+                continue;
+            }
+        };
         let path = entry.path();
-        let metadata = entry.metadata().map_err(|e| Error::Other(e.to_string()))?;
-
-        // Get relative path
-        let relative_path = path
-            .strip_prefix(&base_path)
-            .unwrap_or(path)
-            .to_string_lossy()
-            .replace('\\', "/"); // Ensure forward slashes in ZIP
+        let relative_path = entry_name(path);
+        // The directory itself, when entries are named relative to it
+        if relative_path.is_empty() {
+            continue;
+        }
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                outcome.fail(relative_path, e.into());
+                continue;
+            }
+        };
 
         if metadata.is_dir() {
-            // Add directory entry
-            let dir_name = format!("{}/", relative_path);
-            debug!("Adding directory to ZIP: {}", dir_name);
-
-            let options =
-                FileOptions::<'static, ()>::default().compression_method(CompressionMethod::Stored);
-
-            #[cfg(unix)]
-            let options = {
-                use std::os::unix::fs::PermissionsExt;
-                options.unix_permissions(metadata.permissions().mode())
-            };
-
-            zip.add_directory(&dir_name, options)?;
+            add_directory_to_zip(zip, path, &relative_path, &metadata, options, outcome)?;
         } else if metadata.is_file() {
             // Add file
-            let options = FileOptions::<'static, ()>::default()
+            let file_options = FileOptions::<'static, ()>::default()
                 .compression_method(CompressionMethod::Deflated);
 
-            pack_file_to_zip(zip, path, &relative_path, options)?;
-        } else if metadata.is_symlink() && !follow_symlinks {
-            warn!("ZIP format does not support symlinks, skipping: {:?}", path);
+            pack_file_to_zip(
+                zip,
+                path,
+                &relative_path,
+                file_options,
+                options,
+                progress,
+                outcome,
+            )?;
+        } else {
+            if metadata.is_symlink() {
+                warn!("ZIP format does not support symlinks, skipping: {:?}", path);
+            } else {
+                warn!("Skipping special file: {:?}", path);
+            }
+            outcome.skip(relative_path, SkipReason::Unsupported);
+        }
+    }
+
+    for (path, reason) in left_out {
+        if reason != SkipReason::Excluded {
+            warn!("Skipping {:?}: {}", path, reason);
         }
+        outcome.skip(entry_name(&path), reason);
     }
 
     Ok(())
 }
 
+/// Add the entry of the directory `path`, without its contents, named
+/// `name`
+///
+/// Nothing is added for the folder `strip_prefix` takes off.
+fn add_directory_to_zip<W: Write + std::io::Seek>(
+    zip: &mut ZipWriter<W>,
+    path: &Path,
+    name: &str,
+    metadata: &fs::Metadata,
+    options: &PackOptions,
+    outcome: &mut OperationOutcome,
+) -> Result<()> {
+    let Some(name) = options.stored_name(Path::new(name), true) else {
+        return Ok(());
+    };
+    let name = paths::entry_key(&name);
+    let (name, file_options) = entry_options(
+        path,
+        &name,
+        FileOptions::<'static, ()>::default().compression_method(CompressionMethod::Stored),
+        metadata,
+        options,
+    );
+    let dir_name = format!("{}/", name.trim_end_matches('/'));
+    debug!("Adding directory to ZIP: {}", dir_name);
+
+    zip.add_directory(&dir_name, file_options)?;
+    outcome.done(dir_name);
+    Ok(())
+}
+
 /// Extract files from a zip archive
 pub fn extract_zip<P: AsRef<Path>, Q: AsRef<Path>>(archive_path: P, output_dir: Q) -> Result<()> {
-    extract_zip_with_options(archive_path, output_dir, ExtractOptions::default())
+    extract_zip_with_options(archive_path, output_dir, ExtractOptions::default())?.into_result()
 }
 
 /// Extract files from a zip archive with options
+///
+/// Entries that cannot be written are recorded in the outcome and the rest
+/// are still extracted; an unreadable entry stops extraction.
 pub fn extract_zip_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
     archive_path: P,
     output_dir: Q,
     options: ExtractOptions,
-) -> Result<()> {
+) -> Result<OperationOutcome> {
     let archive_path = archive_path.as_ref();
     let output_dir = output_dir.as_ref();
 
@@ -161,78 +494,163 @@ pub fn extract_zip_with_options<P: AsRef<Path>, Q: AsRef<Path>>(
     // Create output directory if it doesn't exist
     fs::create_dir_all(output_dir)?;
 
-    let file = File::open(archive_path)?;
-    let mut archive = ZipArchive::new(file)?;
+    let mut archive = open_zip(archive_path)?;
+    let mut outcome = OperationOutcome::default();
+    let mut case_folder = CaseFolder::new(options.case_collisions, options.fold_case, output_dir);
+    let hashes = HashLookup::new(options.manifest_check.as_ref());
+    let mut journal = options.undoable.then(|| UndoJournal::new(output_dir));
+    let mut resume = options
+        .resume
+        .then(|| Resume::open(archive_path, output_dir))
+        .transpose()?;
+    let mut syncer = Syncer::new(options.durability);
+
+    let order = if options.is_ordered() {
+        let entries = (0..archive.len())
+            .map(|i| Ok(zip_entry(&archive.by_index_raw(i)?).into()))
+            .collect::<Result<Vec<ArchiveEntry>>>()?;
+        extraction_order(&entries, &options)
+    } else {
+        (0..archive.len()).collect()
+    };
 
-    for i in 0..archive.len() {
+    for i in order {
         let mut file = archive.by_index(i)?;
         let outpath = match file.enclosed_name() {
             Some(path) => path.to_owned(),
-            None => continue,
+            None => {
+                warn!("Skipping entry with unsafe path: {:?}", file.name());
+                outcome.skip(file.name(), SkipReason::UnsafePath);
+                continue;
+            }
         };
+        if options.entry_filter.is_some() && !options.accepts(&zip_entry(&file).into()) {
+            outcome.skip(outpath, SkipReason::Excluded);
+            continue;
+        }
+        let entry_path = outpath.clone();
 
         // Apply strip components
-        let outpath = if let Some(strip) = options.strip_components {
-            let components: Vec<_> = outpath.components().collect();
-            if components.len() <= strip {
+        let outpath = match options.strip_components {
+            Some(strip) => match paths::strip_components(&outpath, strip) {
+                Some(stripped) => stripped,
                 // Skip this entry if we're stripping more components than it has
-                continue;
-            }
-            PathBuf::from_iter(components.into_iter().skip(strip))
-        } else {
-            outpath
+                None => {
+                    outcome.skip(outpath, SkipReason::Stripped);
+                    continue;
+                }
+            },
+            None => outpath,
         };
+        let outpath = options.normalize_names.apply_path(&outpath).into_owned();
 
-        let dest_path = output_dir.join(&outpath);
+        let mut dest_path = output_dir.join(&outpath);
 
         debug!("Extracting: {:?}", outpath);
 
-        if file.name().ends_with('/') {
+        let result = if file.name().ends_with('/') {
             // Directory
-            fs::create_dir_all(&dest_path)?;
+            undo::prepare(journal.as_mut(), &dest_path)
+                .and_then(|()| fs::create_dir_all(&dest_path).map_err(Error::from))
         } else {
-            // File
-            // Create parent directories if needed
-            if let Some(parent) = dest_path.parent() {
-                fs::create_dir_all(parent)?;
+            let expected = match hashes.expected(&entry_path, &outpath, &mut outcome) {
+                Ok(expected) => expected,
+                Err(e) => {
+                    outcome.fail(outpath, e);
+                    continue;
+                }
+            };
+            match case_folder.place(&outpath, output_dir, &mut outcome) {
+                Ok(name) => dest_path = output_dir.join(name),
+                Err(e) => {
+                    outcome.fail(outpath, e);
+                    continue;
+                }
+            }
+            let prior = resume::prior(resume.as_ref(), &dest_path);
+            if prior == Prior::Done {
+                outcome.skip(outpath, SkipReason::Resumed);
+                continue;
             }
-
             // Handle existing files
-            if dest_path.exists() {
-                if options.skip {
-                    info!("Skipping existing file: {:?}", dest_path);
-                    continue;
-                } else if options.rename {
-                    let dest_path = get_unique_filename(&dest_path);
+            if dest_path.exists() && prior != Prior::Leftover {
+                if options.rename && !options.skip {
+                    dest_path = get_unique_filename(&dest_path);
                     info!("Renaming to avoid conflict: {:?}", dest_path);
-                    extract_zip_file(&mut file, &dest_path)?;
-                } else if options.overwrite {
+                } else if options.overwrite && !options.skip {
                     info!("Overwriting existing file: {:?}", dest_path);
-                    extract_zip_file(&mut file, &dest_path)?;
+                } else {
+                    info!("Skipping existing file: {:?}", dest_path);
+                    outcome.skip(outpath, SkipReason::Exists);
+                    continue;
                 }
-            } else {
-                extract_zip_file(&mut file, &dest_path)?;
             }
-        }
+            // What the interrupted run left is its own, not the user's
+            if prior != Prior::Leftover {
+                if let Err(e) = undo::prepare(journal.as_mut(), &dest_path) {
+                    outcome.fail(outpath, e);
+                    continue;
+                }
+            }
+            match expected {
+                None => extract_zip_file(&mut file, &dest_path, resume.as_mut()),
+                Some(expected) => {
+                    let mut reader = hashes.hashing(&mut file);
+                    extract_zip_file(&mut reader, &dest_path, resume.as_mut()).and_then(|()| {
+                        let actual = reader.hash();
+                        hashes.verify(&outpath, &dest_path, expected, &actual, &mut outcome)
+                    })
+                }
+            }
+        };
 
         // Set permissions on Unix
         #[cfg(unix)]
-        {
+        let result = result.and_then(|()| {
             use std::os::unix::fs::PermissionsExt;
             if let Some(mode) = file.unix_mode() {
+                let mode = permissions::extracted_mode(mode, file.is_dir(), options.apply_umask);
                 fs::set_permissions(&dest_path, fs::Permissions::from_mode(mode))?;
             }
+            Ok(())
+        });
+        let result = result.and_then(|()| syncer.written(&dest_path));
+
+        match result {
+            Ok(()) => {
+                resume::done(resume.as_mut(), &dest_path);
+                outcome.done(outpath)
+            }
+            Err(e) => outcome.fail(outpath, e),
         }
     }
 
-    info!("Successfully extracted ZIP archive");
-    Ok(())
+    syncer.finish()?;
+    if let Some(journal) = &mut journal {
+        journal.save()?;
+    }
+    if let Some(resume) = resume {
+        resume.finish(!outcome.failed.is_empty())?;
+    }
+    info!("Extracted ZIP archive: {}", outcome);
+    Ok(outcome)
 }
 
-/// Extract a single file from zip
-fn extract_zip_file<R: Read>(file: &mut R, dest_path: &Path) -> Result<()> {
+/// Extract a single file from zip, creating its parent directories,
+/// through the resume journal when one is kept
+fn extract_zip_file<R: Read>(
+    file: &mut R,
+    dest_path: &Path,
+    resume: Option<&mut Resume>,
+) -> Result<()> {
+    if let Some(resume) = resume {
+        return resume.write(file, dest_path);
+    }
+    if let Some(parent) = dest_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
     let mut outfile = File::create(dest_path)?;
-    std::io::copy(file, &mut outfile)?;
+    buffer::copy(file, &mut outfile)?;
     Ok(())
 }
 
@@ -268,8 +686,7 @@ pub fn inspect_zip<P: AsRef<Path>>(archive_path: P) -> Result<Vec<ArchiveEntry>>
     let archive_path = archive_path.as_ref();
     info!("Inspecting ZIP archive: {:?}", archive_path);
 
-    let file = File::open(archive_path)?;
-    let mut archive = ZipArchive::new(file)?;
+    let mut archive = open_zip(archive_path)?;
     let mut entries = Vec::new();
 
     for i in 0..archive.len() {
@@ -288,6 +705,10 @@ pub fn inspect_zip<P: AsRef<Path>>(archive_path: P) -> Result<Vec<ArchiveEntry>>
             is_dir: file.is_dir(),
             is_symlink: false, // ZIP doesn't support symlinks
             link_target: None,
+            extra_fields: parse_extra_fields(file.extra_data().unwrap_or_default())
+                .iter()
+                .map(ExtraField::describe)
+                .collect(),
         };
 
         entries.push(entry);
@@ -0,0 +1,415 @@
+//! Extra fields of zip entries
+//!
+//! Besides its name and sizes, a zip entry can carry extra fields: Info-ZIP
+//! timestamps (`UT`) and ownership (`ux`), NTFS times, or data only some
+//! vendor's tools understand. The zip crate reads a few of them and drops
+//! every one when it copies an entry, so archives rewritten in place are
+//! copied here record by record instead, with the extra fields of both the
+//! local header and the central directory kept byte for byte.
+
+use crate::{Error, Result};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use zip::ZipArchive;
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const DATA_DESCRIPTOR: u32 = 0x0807_4b50;
+const ZIP64_END: u32 = 0x0606_4b50;
+const ZIP64_LOCATOR: u32 = 0x0706_4b50;
+const END: u32 = 0x0605_4b50;
+
+const ZIP64: u16 = 0x0001;
+const UNICODE_PATH: u16 = 0x7075;
+
+/// General purpose flag: sizes and CRC follow the data
+const HAS_DESCRIPTOR: u16 = 1 << 3;
+/// General purpose flag: the name is UTF-8
+const UTF8_NAME: u16 = 1 << 11;
+
+/// One extra field of a zip entry, as stored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtraField {
+    /// Header ID naming the kind of field
+    pub id: u16,
+    /// The field's data, without ID and length
+    pub data: Vec<u8>,
+}
+
+impl ExtraField {
+    /// Short name of a field kind this crate knows, as `zipinfo` shows it
+    pub fn name(&self) -> Option<&'static str> {
+        Some(match self.id {
+            0x0001 => "zip64",
+            0x000a => "NTFS",
+            0x000d => "unix",
+            0x5455 => "UT",
+            0x5855 => "UX",
+            0x6375 => "uc",
+            0x7075 => "up",
+            0x7875 => "ux",
+            0x9901 => "AES",
+            _ => return None,
+        })
+    }
+
+    /// The field's name with what it holds, when that is short, such as
+    /// `UT mtime=1700000000` or `ux uid=1000 gid=1000`; fields this crate
+    /// does not know are shown by their ID
+    pub fn describe(&self) -> String {
+        let Some(name) = self.name() else {
+            return format!("{:#06x}", self.id);
+        };
+        match (self.id, self.data.as_slice()) {
+            // Flags, then the times they announce, modification time first
+            (0x5455, [flags, mtime @ ..]) if flags & 1 != 0 && mtime.len() >= 4 => {
+                let mtime = i32::from_le_bytes([mtime[0], mtime[1], mtime[2], mtime[3]]);
+                format!("{} mtime={}", name, mtime)
+            }
+            // Version, then each ID after its size
+            (0x7875, [1, rest @ ..]) => match owner_ids(rest) {
+                Some((uid, gid)) => format!("{} uid={} gid={}", name, uid, gid),
+                None => name.to_string(),
+            },
+            _ => name.to_string(),
+        }
+    }
+}
+
+fn owner_ids(data: &[u8]) -> Option<(u64, u64)> {
+    let (uid, rest) = sized_id(data)?;
+    let (gid, _) = sized_id(rest)?;
+    Some((uid, gid))
+}
+
+fn sized_id(data: &[u8]) -> Option<(u64, &[u8])> {
+    let (&size, rest) = data.split_first()?;
+    let size = size as usize;
+    if size > 8 || rest.len() < size {
+        return None;
+    }
+    let mut bytes = [0u8; 8];
+    bytes[..size].copy_from_slice(&rest[..size]);
+    Some((u64::from_le_bytes(bytes), &rest[size..]))
+}
+
+/// Split a block of extra data into its fields; a truncated field at the
+/// end is left out
+pub fn parse_extra_fields(mut block: &[u8]) -> Vec<ExtraField> {
+    let mut fields = Vec::new();
+    while block.len() >= 4 {
+        let id = u16::from_le_bytes([block[0], block[1]]);
+        let len = u16::from_le_bytes([block[2], block[3]]) as usize;
+        let Some(data) = block.get(4..4 + len) else {
+            break;
+        };
+        fields.push(ExtraField {
+            id,
+            data: data.to_vec(),
+        });
+        block = &block[4 + len..];
+    }
+    fields
+}
+
+fn encode_extra_fields(fields: &[ExtraField]) -> Vec<u8> {
+    let mut block = Vec::new();
+    for field in fields {
+        block.extend_from_slice(&field.id.to_le_bytes());
+        block.extend_from_slice(&(field.data.len() as u16).to_le_bytes());
+        block.extend_from_slice(&field.data);
+    }
+    block
+}
+
+/// Copy the entries of the zip `archive` into `output`, each kept, renamed
+/// or left out as `copy` returns for its name
+///
+/// Compressed data, descriptors and extra fields are copied as they are.
+/// A renamed entry loses its Info-ZIP Unicode path field, which would
+/// otherwise name it as before.
+pub(crate) fn copy_zip<F>(archive: &Path, output: &Path, copy: F) -> Result<()>
+where
+    F: Fn(&str) -> EntryCopy,
+{
+    let mut source = ZipArchive::new(BufReader::new(File::open(archive)?))?;
+    let mut input = BufReader::new(File::open(archive)?);
+    let mut writer = Counting::new(BufWriter::new(File::create(output)?));
+
+    let mut central = Vec::new();
+    for index in 0..source.len() {
+        let file = source.by_index_raw(index)?;
+        let new_name = match copy(file.name()) {
+            EntryCopy::Keep => None,
+            EntryCopy::Rename(name) => Some(name),
+            EntryCopy::Drop => continue,
+        };
+        let compressed_size = file.compressed_size();
+        let data_start = file.data_start();
+        let header_start = file.header_start();
+        let central_start = file.central_header_start();
+        drop(file);
+
+        let mut local = read_record(&mut input, header_start, LOCAL_HEADER, 30, &[26, 28])?;
+        let mut record = read_record(&mut input, central_start, CENTRAL_HEADER, 46, &[28, 30, 32])?;
+        if let Some(name) = &new_name {
+            local.rename(name);
+            record.rename(name);
+        }
+        record.set_local_offset(writer.written)?;
+        local.write(&mut writer)?;
+
+        // The data, and a descriptor after it when the flags say so
+        input.seek(SeekFrom::Start(data_start))?;
+        let copied = std::io::copy(&mut (&mut input).take(compressed_size), &mut writer)?;
+        if copied != compressed_size {
+            return Err(Error::Zip(format!(
+                "Data of {} ends early",
+                String::from_utf8_lossy(&local.name)
+            )));
+        }
+        if local.flags() & HAS_DESCRIPTOR != 0 {
+            let zip64 = local.extra.iter().any(|field| field.id == ZIP64);
+            let mut descriptor = vec![0u8; if zip64 { 20 } else { 12 }];
+            input.read_exact(&mut descriptor[..4])?;
+            if u32::from_le_bytes(descriptor[..4].try_into().unwrap()) == DATA_DESCRIPTOR {
+                descriptor.extend_from_slice(&[0; 4]);
+            }
+            input.read_exact(&mut descriptor[4..])?;
+            writer.write_all(&descriptor)?;
+        }
+        central.push(record);
+    }
+
+    let central_start = writer.written;
+    for record in &central {
+        record.write(&mut writer)?;
+    }
+    let central_size = writer.written - central_start;
+    write_end(
+        &mut writer,
+        central.len() as u64,
+        central_size,
+        central_start,
+        source.comment(),
+    )?;
+    writer.inner.flush()?;
+    Ok(())
+}
+
+/// What [`copy_zip`] does with an entry
+pub(crate) enum EntryCopy {
+    Keep,
+    Rename(String),
+    /// Leave the entry out
+    Drop,
+}
+
+/// A local or central header with its name, extra fields and comment
+struct Record {
+    /// Fixed part, signature included
+    fixed: Vec<u8>,
+    name: Vec<u8>,
+    extra: Vec<ExtraField>,
+    comment: Vec<u8>,
+    /// Offset of the name length in `fixed`
+    name_len_at: usize,
+}
+
+impl Record {
+    /// Offset of the general purpose flags in `fixed`, which central
+    /// records put after the version they were made by
+    fn flags_at(&self) -> usize {
+        if self.fixed.len() == 46 {
+            8
+        } else {
+            6
+        }
+    }
+
+    fn flags(&self) -> u16 {
+        let at = self.flags_at();
+        u16::from_le_bytes([self.fixed[at], self.fixed[at + 1]])
+    }
+
+    fn rename(&mut self, name: &str) {
+        self.name = name.as_bytes().to_vec();
+        self.extra.retain(|field| field.id != UNICODE_PATH);
+        if !name.is_ascii() {
+            let flags = self.flags() | UTF8_NAME;
+            let at = self.flags_at();
+            self.fixed[at..at + 2].copy_from_slice(&flags.to_le_bytes());
+        }
+    }
+
+    /// Point a central record at its local header, moving the offset into
+    /// the zip64 field when it no longer fits in 32 bits
+    fn set_local_offset(&mut self, offset: u64) -> Result<()> {
+        let stored = u32::from_le_bytes(self.fixed[42..46].try_into().unwrap());
+        // Values in the zip64 field appear in this order, each only when its
+        // 32-bit slot is saturated
+        let saturated = [
+            u32::from_le_bytes(self.fixed[24..28].try_into().unwrap()) == u32::MAX,
+            u32::from_le_bytes(self.fixed[20..24].try_into().unwrap()) == u32::MAX,
+        ];
+        let skip = saturated.iter().filter(|&&full| full).count() * 8;
+
+        if stored != u32::MAX && offset < u32::MAX as u64 {
+            self.fixed[42..46].copy_from_slice(&(offset as u32).to_le_bytes());
+            return Ok(());
+        }
+
+        self.fixed[42..46].copy_from_slice(&u32::MAX.to_le_bytes());
+        let index = match self.extra.iter().position(|field| field.id == ZIP64) {
+            Some(index) => index,
+            None => {
+                self.extra.push(ExtraField {
+                    id: ZIP64,
+                    data: Vec::new(),
+                });
+                self.extra.len() - 1
+            }
+        };
+        let data = &mut self.extra[index].data;
+        if data.len() < skip {
+            return Err(Error::Zip(
+                "Zip64 field is shorter than its header announces".to_string(),
+            ));
+        }
+        if stored == u32::MAX {
+            let slot = data
+                .get_mut(skip..skip + 8)
+                .ok_or_else(|| Error::Zip("Zip64 field lacks the header offset".to_string()))?;
+            slot.copy_from_slice(&offset.to_le_bytes());
+        } else {
+            data.splice(skip..skip, offset.to_le_bytes());
+        }
+        Ok(())
+    }
+
+    fn write(&self, writer: &mut impl Write) -> Result<()> {
+        let extra = encode_extra_fields(&self.extra);
+        let mut fixed = self.fixed.clone();
+        let at = self.name_len_at;
+        fixed[at..at + 2].copy_from_slice(&length(self.name.len())?.to_le_bytes());
+        fixed[at + 2..at + 4].copy_from_slice(&length(extra.len())?.to_le_bytes());
+        writer.write_all(&fixed)?;
+        writer.write_all(&self.name)?;
+        writer.write_all(&extra)?;
+        writer.write_all(&self.comment)?;
+        Ok(())
+    }
+}
+
+fn length(len: usize) -> Result<u16> {
+    u16::try_from(len).map_err(|_| Error::Zip("Zip header field is too long".to_string()))
+}
+
+/// Read the record at `start`, whose name, extra and (for central records)
+/// comment lengths are at `lengths` in its fixed part of `size` bytes
+fn read_record<R: Read + Seek>(
+    input: &mut R,
+    start: u64,
+    signature: u32,
+    size: usize,
+    lengths: &[usize],
+) -> Result<Record> {
+    input.seek(SeekFrom::Start(start))?;
+    let mut fixed = vec![0u8; size];
+    input.read_exact(&mut fixed)?;
+    if u32::from_le_bytes(fixed[..4].try_into().unwrap()) != signature {
+        return Err(Error::Zip(format!("No zip header at offset {}", start)));
+    }
+    let len = |at: usize| u16::from_le_bytes([fixed[at], fixed[at + 1]]) as usize;
+    let mut read = |len: usize| -> Result<Vec<u8>> {
+        let mut bytes = vec![0u8; len];
+        input.read_exact(&mut bytes)?;
+        Ok(bytes)
+    };
+    let name = read(len(lengths[0]))?;
+    let extra = read(len(lengths[1]))?;
+    let comment = match lengths.get(2) {
+        Some(&at) => read(len(at))?,
+        None => Vec::new(),
+    };
+    Ok(Record {
+        name,
+        extra: parse_extra_fields(&extra),
+        comment,
+        name_len_at: lengths[0],
+        fixed,
+    })
+}
+
+/// Write the end of central directory record, preceded by its zip64
+/// version when a count or offset does not fit
+fn write_end<W: Write>(
+    writer: &mut Counting<W>,
+    entries: u64,
+    central_size: u64,
+    central_start: u64,
+    comment: &[u8],
+) -> Result<()> {
+    let zip64 = entries >= u16::MAX as u64
+        || central_size >= u32::MAX as u64
+        || central_start >= u32::MAX as u64;
+    if zip64 {
+        let end_start = writer.written;
+        let mut record = Vec::with_capacity(56);
+        record.extend_from_slice(&ZIP64_END.to_le_bytes());
+        record.extend_from_slice(&44u64.to_le_bytes());
+        record.extend_from_slice(&45u16.to_le_bytes());
+        record.extend_from_slice(&45u16.to_le_bytes());
+        record.extend_from_slice(&0u32.to_le_bytes());
+        record.extend_from_slice(&0u32.to_le_bytes());
+        record.extend_from_slice(&entries.to_le_bytes());
+        record.extend_from_slice(&entries.to_le_bytes());
+        record.extend_from_slice(&central_size.to_le_bytes());
+        record.extend_from_slice(&central_start.to_le_bytes());
+        record.extend_from_slice(&ZIP64_LOCATOR.to_le_bytes());
+        record.extend_from_slice(&0u32.to_le_bytes());
+        record.extend_from_slice(&end_start.to_le_bytes());
+        record.extend_from_slice(&1u32.to_le_bytes());
+        writer.write_all(&record)?;
+    }
+
+    let entries = entries.min(u16::MAX as u64) as u16;
+    let mut record = Vec::with_capacity(22 + comment.len());
+    record.extend_from_slice(&END.to_le_bytes());
+    record.extend_from_slice(&0u16.to_le_bytes());
+    record.extend_from_slice(&0u16.to_le_bytes());
+    record.extend_from_slice(&entries.to_le_bytes());
+    record.extend_from_slice(&entries.to_le_bytes());
+    record.extend_from_slice(&(central_size.min(u32::MAX as u64) as u32).to_le_bytes());
+    record.extend_from_slice(&(central_start.min(u32::MAX as u64) as u32).to_le_bytes());
+    record.extend_from_slice(&length(comment.len())?.to_le_bytes());
+    record.extend_from_slice(comment);
+    writer.write_all(&record)?;
+    Ok(())
+}
+
+/// Writer keeping count of the bytes written, which are the offsets the
+/// central directory needs
+struct Counting<W> {
+    inner: W,
+    written: u64,
+}
+
+impl<W> Counting<W> {
+    fn new(inner: W) -> Self {
+        Self { inner, written: 0 }
+    }
+}
+
+impl<W: Write> Write for Counting<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
@@ -1,12 +1,15 @@
 //! Zip extractor implementation
 
-use super::extractor::{ArchiveEntry, ExtractEntryOptions, Extractor};
-use crate::{Error, Result};
+use super::extractor::{stream_entry, ArchiveEntry, ExtractEntryOptions, Extractor};
+use super::zip::open_zip;
+use crate::progress::{NoProgress, ProgressSink};
+use crate::{buffer, Error, Result};
 use std::fs::{self, File};
-use std::io;
+use std::io::{self, Read};
 use std::path::{Path, PathBuf};
 // use tracing::{debug, info, warn};
-use zip::ZipArchive;
+use zip::read::ZipFile;
+use zip::result::ZipError;
 
 /// Zip extractor
 pub struct ZipExtractor;
@@ -24,45 +27,45 @@ impl ZipExtractor {
     }
 }
 
+/// Describe one zip entry from its central directory record
+pub(crate) fn zip_entry(file: &ZipFile<'_>) -> ArchiveEntry {
+    ArchiveEntry {
+        path: PathBuf::from(file.name()),
+        size: file.size(),
+        compressed_size: Some(file.compressed_size()),
+        mode: file.unix_mode(),
+        mtime: file.last_modified().map(|dt| {
+            // Convert from DOS datetime to Unix timestamp
+            let year = dt.year() as i64;
+            let month = dt.month() as i64;
+            let day = dt.day() as i64;
+            let hour = dt.hour() as i64;
+            let minute = dt.minute() as i64;
+            let second = dt.second() as i64;
+
+            // Simple conversion - may not be perfectly accurate
+            let days_since_epoch = (year - 1970) * 365 + (month - 1) * 30 + day;
+
+            days_since_epoch * 86400 + (hour * 3600 + minute * 60 + second)
+        }),
+        is_dir: file.is_dir(),
+        is_symlink: false, // ZIP doesn't directly support symlinks
+        link_target: None,
+        uid: None,
+        gid: None,
+    }
+}
+
 impl Extractor for ZipExtractor {
     fn entries(&self, source: &Path) -> Result<Box<dyn Iterator<Item = Result<ArchiveEntry>>>> {
-        let file = File::open(source)?;
-        let mut archive = ZipArchive::new(file)?;
+        let mut archive = open_zip(source)?;
 
         let mut entries = Vec::new();
 
         for i in 0..archive.len() {
-            match archive.by_index(i) {
-                Ok(file) => {
-                    let path = PathBuf::from(file.name());
-                    let _comment = file.comment();
-
-                    entries.push(Ok(ArchiveEntry {
-                        path,
-                        size: file.size(),
-                        compressed_size: Some(file.compressed_size()),
-                        mode: file.unix_mode(),
-                        mtime: file.last_modified().map(|dt| {
-                            // Convert from DOS datetime to Unix timestamp
-                            let year = dt.year() as i64;
-                            let month = dt.month() as i64;
-                            let day = dt.day() as i64;
-                            let hour = dt.hour() as i64;
-                            let minute = dt.minute() as i64;
-                            let second = dt.second() as i64;
-
-                            // Simple conversion - may not be perfectly accurate
-                            let days_since_epoch = (year - 1970) * 365 + (month - 1) * 30 + day;
-
-                            days_since_epoch * 86400 + (hour * 3600 + minute * 60 + second)
-                        }),
-                        is_dir: file.is_dir(),
-                        is_symlink: false, // ZIP doesn't directly support symlinks
-                        link_target: None,
-                        uid: None,
-                        gid: None,
-                    }));
-                }
+            // Raw access reads metadata without decrypting encrypted entries
+            match archive.by_index_raw(i) {
+                Ok(file) => entries.push(Ok(zip_entry(&file))),
                 Err(e) => entries.push(Err(Error::Zip(e.to_string()))),
             }
         }
@@ -77,16 +80,45 @@ impl Extractor for ZipExtractor {
         destination: &Path,
         options: ExtractEntryOptions,
     ) -> Result<()> {
-        let file = File::open(source)?;
-        let mut archive = ZipArchive::new(file)?;
+        self.extract_entry_with_progress(source, entry, destination, options, &NoProgress)
+    }
+
+    fn extract_entry_with_progress(
+        &self,
+        source: &Path,
+        entry: &ArchiveEntry,
+        destination: &Path,
+        options: ExtractEntryOptions,
+        progress: &dyn ProgressSink,
+    ) -> Result<()> {
+        let mut archive = open_zip(source)?;
 
         // Find the entry by path
         for i in 0..archive.len() {
-            let mut zip_file = archive.by_index(i)?;
-            let zip_path = PathBuf::from(zip_file.name());
+            let (zip_path, encrypted) = {
+                let raw = archive.by_index_raw(i)?;
+                (PathBuf::from(raw.name()), raw.encrypted())
+            };
 
             if zip_path == entry.path {
-                let full_path = destination.join(&entry.path);
+                let mut zip_file = match (encrypted, options.password.as_deref()) {
+                    (false, _) => archive.by_index(i)?,
+                    (true, Some(password)) => archive
+                        .by_index_decrypt(i, password.as_bytes())
+                        .map_err(|e| match e {
+                            ZipError::InvalidPassword => {
+                                Error::InvalidPassword(entry.path.display().to_string())
+                            }
+                            other => other.into(),
+                        })?,
+                    (true, None) => {
+                        return Err(Error::PasswordRequired(entry.path.display().to_string()))
+                    }
+                };
+
+                let Some(full_path) = options.output_path(destination, &entry.path)? else {
+                    return Ok(());
+                };
 
                 // Check if file exists and handle according to options
                 if full_path.exists() && !options.overwrite {
@@ -106,7 +138,7 @@ impl Extractor for ZipExtractor {
                     fs::create_dir_all(&full_path)?;
                 } else {
                     let mut output_file = File::create(&full_path)?;
-                    io::copy(&mut zip_file, &mut output_file)?;
+                    buffer::copy_with_progress(&mut zip_file, &mut output_file, progress)?;
                 }
 
                 // Set permissions if requested and available
@@ -142,6 +174,41 @@ impl Extractor for ZipExtractor {
         )))
     }
 
+    fn open_entry(&self, source: &Path, entry: &ArchiveEntry) -> Result<Box<dyn Read + Send>> {
+        let source = source.to_path_buf();
+        let wanted = entry.path.clone();
+
+        stream_entry(move |writer| {
+            let mut archive = open_zip(&source)?;
+
+            for i in 0..archive.len() {
+                let (zip_path, encrypted, is_dir) = {
+                    let raw = archive.by_index_raw(i)?;
+                    (PathBuf::from(raw.name()), raw.encrypted(), raw.is_dir())
+                };
+                if zip_path != wanted {
+                    continue;
+                }
+                if is_dir {
+                    return Err(Error::InvalidPath(format!(
+                        "Not a regular file: {}",
+                        wanted.display()
+                    )));
+                }
+                if encrypted {
+                    return Err(Error::PasswordRequired(wanted.display().to_string()));
+                }
+                buffer::copy(&mut archive.by_index(i)?, writer)?;
+                return Ok(());
+            }
+
+            Err(Error::NotFound(format!(
+                "Entry not found in archive: {:?}",
+                wanted
+            )))
+        })
+    }
+
     fn format_name(&self) -> &'static str {
         "zip"
     }
@@ -0,0 +1,190 @@
+//! Reusable I/O buffers
+//!
+//! Copying an entry in or out of an archive needs a scratch buffer, and a
+//! fresh one per file adds up when an archive holds many small files.
+//! [`BufferPool`] hands out buffers of one chunk size and takes them back
+//! when they are dropped. The pool is shared between threads, so rayon
+//! workers draw from the same buffers as the thread that started them.
+
+use crate::progress::{ProgressRead, ProgressSink};
+use rayon::current_num_threads;
+use std::io::{self, ErrorKind, Read, Write};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// Chunk size of the global pool unless configured, matching the default
+/// `performance.buffer_size`
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Smallest chunk size a pool uses
+pub const MIN_CHUNK_SIZE: usize = 4 * 1024;
+
+/// A pool of equally sized buffers
+#[derive(Debug)]
+pub struct BufferPool {
+    chunk_size: AtomicUsize,
+    idle: Mutex<Vec<Box<[u8]>>>,
+}
+
+impl BufferPool {
+    /// An empty pool of `chunk_size` buffers, at least [`MIN_CHUNK_SIZE`]
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            chunk_size: AtomicUsize::new(chunk_size.max(MIN_CHUNK_SIZE)),
+            idle: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The pool used by packing, extraction and hashing
+    pub fn global() -> &'static BufferPool {
+        static GLOBAL: OnceLock<BufferPool> = OnceLock::new();
+        GLOBAL.get_or_init(|| BufferPool::new(DEFAULT_CHUNK_SIZE))
+    }
+
+    /// Size of the buffers handed out
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size.load(Ordering::Relaxed)
+    }
+
+    /// Hand out buffers of `chunk_size` from now on, at least
+    /// [`MIN_CHUNK_SIZE`]
+    ///
+    /// Idle buffers of the old size are freed; those in use are freed when
+    /// they come back.
+    pub fn set_chunk_size(&self, chunk_size: usize) {
+        self.chunk_size
+            .store(chunk_size.max(MIN_CHUNK_SIZE), Ordering::Relaxed);
+        self.lock().clear();
+    }
+
+    /// Number of buffers waiting to be reused
+    pub fn idle(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// A buffer of [`chunk_size`](Self::chunk_size) bytes, reused if one is
+    /// idle; its contents are whatever the last user left
+    pub fn get(&self) -> PooledBuffer<'_> {
+        let chunk_size = self.chunk_size();
+        let reused = self
+            .lock()
+            .pop()
+            .filter(|buffer| buffer.len() == chunk_size);
+        PooledBuffer {
+            buffer: Some(reused.unwrap_or_else(|| vec![0; chunk_size].into_boxed_slice())),
+            pool: self,
+        }
+    }
+
+    /// Copy everything from `reader` to `writer` through a pooled buffer,
+    /// like [`io::copy`]
+    pub fn copy<R, W>(&self, reader: &mut R, writer: &mut W) -> io::Result<u64>
+    where
+        R: Read + ?Sized,
+        W: Write + ?Sized,
+    {
+        let mut buffer = self.get();
+        let mut copied = 0;
+        loop {
+            let n = match reader.read(&mut buffer) {
+                Ok(0) => return Ok(copied),
+                Ok(n) => n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            writer.write_all(&buffer[..n])?;
+            copied += n as u64;
+        }
+    }
+
+    /// [`BufferPool::copy`], reporting each chunk read to `sink` and
+    /// stopping with an error once it is cancelled
+    pub fn copy_with_progress<R, W>(
+        &self,
+        reader: &mut R,
+        writer: &mut W,
+        sink: &dyn ProgressSink,
+    ) -> io::Result<u64>
+    where
+        R: Read + ?Sized,
+        W: Write + ?Sized,
+    {
+        self.copy(&mut ProgressRead::new(reader, sink), writer)
+    }
+
+    fn give_back(&self, buffer: Box<[u8]>) {
+        if buffer.len() != self.chunk_size() {
+            return;
+        }
+        let mut idle = self.lock();
+        // Enough for every worker to hold a couple at once
+        if idle.len() < current_num_threads() * 2 {
+            idle.push(buffer);
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<Box<[u8]>>> {
+        // Buffers hold no invariants, so a panic elsewhere leaves them usable
+        self.idle
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for BufferPool {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHUNK_SIZE)
+    }
+}
+
+/// A buffer borrowed from a [`BufferPool`], returned to it on drop
+#[derive(Debug)]
+pub struct PooledBuffer<'a> {
+    buffer: Option<Box<[u8]>>,
+    pool: &'a BufferPool,
+}
+
+impl Deref for PooledBuffer<'_> {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.buffer.as_deref().unwrap_or_default()
+    }
+}
+
+impl DerefMut for PooledBuffer<'_> {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        self.buffer.as_deref_mut().unwrap_or_default()
+    }
+}
+
+impl Drop for PooledBuffer<'_> {
+    fn drop(&mut self) {
+        if let Some(buffer) = self.buffer.take() {
+            self.pool.give_back(buffer);
+        }
+    }
+}
+
+/// [`BufferPool::copy`] through the global pool
+pub fn copy<R, W>(reader: &mut R, writer: &mut W) -> io::Result<u64>
+where
+    R: Read + ?Sized,
+    W: Write + ?Sized,
+{
+    BufferPool::global().copy(reader, writer)
+}
+
+/// [`BufferPool::copy_with_progress`] through the global pool
+pub fn copy_with_progress<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    sink: &dyn ProgressSink,
+) -> io::Result<u64>
+where
+    R: Read + ?Sized,
+    W: Write + ?Sized,
+{
+    BufferPool::global().copy_with_progress(reader, writer, sink)
+}
@@ -1,14 +1,67 @@
 //! Configuration module
 
+use crate::archive::checksum::HashAlgorithm;
+use crate::archive::filter::DEFAULT_VCS_PATTERNS;
 use crate::{Error, Result};
 use dirs::config_dir;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// `key=value` overrides applied by every [`Config::load`] in this process
+static OVERRIDES: RwLock<Vec<(String, String)>> = RwLock::new(Vec::new());
+
+/// Override configuration values for the rest of the process
+///
+/// Each pair is a dotted key such as `compression.default_level` and its
+/// value; see [`Config::set`]. Every pair is checked against the default
+/// configuration first, so an unknown key or a value of the wrong type is
+/// reported here rather than when the configuration is next loaded.
+pub fn set_overrides(overrides: Vec<(String, String)>) -> Result<()> {
+    let mut config = Config::default();
+    for (key, value) in &overrides {
+        config.set(key, value)?;
+    }
+    *OVERRIDES.write().unwrap_or_else(|e| e.into_inner()) = overrides;
+    Ok(())
+}
+
+/// Split a `key=value` override at its first `=`
+pub fn parse_override(assignment: &str) -> Result<(String, String)> {
+    match assignment.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(Error::ConfigError(format!(
+            "Expected key=value, got: {}",
+            assignment
+        ))),
+    }
+}
 
 /// Main configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Directory for temporary files instead of the system's; see
+    /// [`crate::space::temp_dir`]
+    // Ahead of the sections, as TOML needs plain values before tables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temp_dir: Option<PathBuf>,
+    /// Most bytes one operation may keep in its scratch directory, in bytes
+    /// or as a size like `"2GiB"`; see [`crate::space::TempResource`]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "deserialize_optional_size"
+    )]
+    pub scratch_quota: Option<u64>,
+    /// Key from the key store that encrypts manifests, instead of a
+    /// password; see [`crate::keys`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest_key: Option<String>,
     /// Default compression settings
     pub compression: CompressionConfig,
     /// Archive format preferences
@@ -18,6 +71,15 @@ pub struct Config {
     /// Strategy settings
     #[serde(default)]
     pub strategy: StrategyConfig,
+    /// Exclusion settings
+    #[serde(default)]
+    pub exclude: ExcludeConfig,
+    /// Hash functions, by what they are used for
+    #[serde(default)]
+    pub hashing: HashingConfig,
+    /// Cloud storage settings
+    #[serde(default)]
+    pub cloud: CloudConfig,
     /// Custom compression rules
     #[serde(default)]
     pub rules: Vec<CompressionRule>,
@@ -54,7 +116,8 @@ pub struct PerformanceConfig {
     pub threads: usize,
     /// Memory limit in MB (0 = unlimited)
     pub memory_limit: u64,
-    /// Buffer size in KB
+    /// Size in KB of the I/O buffers in the shared
+    /// [`BufferPool`](crate::buffer::BufferPool)
     pub buffer_size: u32,
 }
 
@@ -72,6 +135,26 @@ pub struct StrategyConfig {
     pub size_rules: Vec<SizeRule>,
 }
 
+/// Exclusion configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExcludeConfig {
+    /// Glob patterns skipped by `--exclude-vcs`
+    #[serde(default = "default_vcs_patterns")]
+    pub vcs_patterns: Vec<String>,
+}
+
+impl Default for ExcludeConfig {
+    fn default() -> Self {
+        Self {
+            vcs_patterns: default_vcs_patterns(),
+        }
+    }
+}
+
+fn default_vcs_patterns() -> Vec<String> {
+    DEFAULT_VCS_PATTERNS.iter().map(|s| s.to_string()).collect()
+}
+
 /// Size rule with string threshold support
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -113,6 +196,29 @@ where
         .collect()
 }
 
+/// Deserialize an optional size given in bytes or as a string like "2GiB"
+fn deserialize_optional_size<'de, D>(deserializer: D) -> std::result::Result<Option<u64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Size {
+        Bytes(u64),
+        Text(String),
+    }
+
+    match Option::<Size>::deserialize(deserializer)? {
+        None => Ok(None),
+        Some(Size::Bytes(bytes)) => Ok(Some(bytes)),
+        Some(Size::Text(text)) => parse_size(&text)
+            .map(Some)
+            .map_err(|e| D::Error::custom(format!("Failed to parse size: {}", e))),
+    }
+}
+
 impl Default for StrategyConfig {
     fn default() -> Self {
         Self {
@@ -136,6 +242,83 @@ impl Default for StrategyConfig {
     }
 }
 
+/// Hash function for each use
+///
+/// A manifest records the hash it was made with, so changing these leaves
+/// existing manifests readable; see [`crate::manifest::Manifest`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HashingConfig {
+    /// Hash of the files listed in new manifests, which extraction checks
+    /// files against
+    #[serde(default)]
+    pub manifest: HashAlgorithm,
+    /// Hash that tells duplicate files apart
+    #[serde(default)]
+    pub dedupe: HashAlgorithm,
+}
+
+/// Cloud storage configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CloudConfig {
+    /// Named locations, such as `[cloud.profiles.work]`
+    #[serde(default)]
+    pub profiles: BTreeMap<String, CloudProfileConfig>,
+}
+
+impl CloudConfig {
+    /// The profile whose URL `url` falls under, the longest match winning
+    ///
+    /// `url` falls under a profile when it is the profile's URL or lies
+    /// below it, so `s3://backups/work` does not cover
+    /// `s3://backups/workshop`.
+    pub fn profile_for(&self, url: &str) -> Option<&CloudProfileConfig> {
+        self.profiles
+            .values()
+            .filter(|profile| profile.covers(url))
+            .max_by_key(|profile| profile.url.len())
+    }
+
+    /// Endpoint of the profile `url` falls under, if it sets one
+    pub fn endpoint_for(&self, url: &str) -> Option<&str> {
+        self.profile_for(url)
+            .and_then(|profile| profile.endpoint.as_deref())
+    }
+
+    /// Key of the profile `url` falls under, if it names one
+    pub fn encryption_key_for(&self, url: &str) -> Option<&str> {
+        self.profile_for(url)
+            .and_then(|profile| profile.encryption_key.as_deref())
+    }
+}
+
+/// A named cloud storage location
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CloudProfileConfig {
+    /// Base URL, e.g. `s3://bucket/backups`
+    pub url: String,
+    /// Custom endpoint for S3-compatible services and Azure emulators
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// Name of a key in the key store to encrypt archives with before they
+    /// are uploaded here, and to read encrypted ones downloaded from here
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub encryption_key: Option<String>,
+}
+
+impl CloudProfileConfig {
+    /// Whether `url` is this profile's URL or lies below it
+    fn covers(&self, url: &str) -> bool {
+        if self.url.is_empty() {
+            return false;
+        }
+        match url.strip_prefix(&self.url) {
+            Some(rest) => rest.is_empty() || rest.starts_with('/') || self.url.ends_with('/'),
+            None => false,
+        }
+    }
+}
+
 /// Size-based compression rule
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SizeRule {
@@ -195,6 +378,50 @@ pub fn parse_size(size_str: &str) -> Result<u64> {
     Ok((number * multiplier as f64) as u64)
 }
 
+/// Parse duration string like "5m" or "1h30m"; a plain number is seconds
+pub fn parse_duration(duration_str: &str) -> Result<Duration> {
+    let duration_str = duration_str.trim();
+
+    if let Ok(secs) = duration_str.parse::<f64>() {
+        return Duration::try_from_secs_f64(secs)
+            .map_err(|_| Error::ConfigError(format!("Invalid duration: {}", duration_str)));
+    }
+
+    let mut rest = duration_str;
+    let mut total = 0.0;
+    while !rest.is_empty() {
+        // Each part is a number followed by its unit
+        let unit_pos = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let unit_end = rest[unit_pos..]
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .map_or(rest.len(), |end| unit_pos + end);
+        let (number_part, unit_part) = rest[..unit_end].split_at(unit_pos);
+        let number: f64 = number_part.parse().map_err(|_| {
+            Error::ConfigError(format!("Invalid duration format: {}", duration_str))
+        })?;
+
+        let multiplier = match unit_part.trim().to_lowercase().as_str() {
+            "ms" => 0.001,
+            "s" | "sec" => 1.0,
+            "m" | "min" => 60.0,
+            "h" => 3_600.0,
+            _ => {
+                return Err(Error::ConfigError(format!(
+                    "Unknown duration unit: {}",
+                    unit_part
+                )))
+            }
+        };
+        total += number * multiplier;
+        rest = &rest[unit_end..];
+    }
+
+    Duration::try_from_secs_f64(total)
+        .map_err(|_| Error::ConfigError(format!("Invalid duration: {}", duration_str)))
+}
+
 /// Custom compression rule
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompressionRule {
@@ -219,6 +446,9 @@ pub struct CompressionRule {
 impl Default for Config {
     fn default() -> Self {
         Self {
+            temp_dir: None,
+            scratch_quota: None,
+            manifest_key: None,
             compression: CompressionConfig {
                 default_algorithm: "zstd".to_string(),
                 default_level: 3,
@@ -236,6 +466,9 @@ impl Default for Config {
                 buffer_size: 64, // 64KB
             },
             strategy: StrategyConfig::default(),
+            exclude: ExcludeConfig::default(),
+            hashing: HashingConfig::default(),
+            cloud: CloudConfig::default(),
             rules: vec![
                 // Example rule: Use brotli for HTML/CSS/JS files
                 CompressionRule {
@@ -299,6 +532,17 @@ impl Config {
         r#"# Flux Configuration File
 # This file configures the behavior of the flux archiver
 
+# Directory for temporary files, such as archives staged for the cloud or
+# stdout; the system's temp directory when unset
+# temp_dir = "/var/tmp/flux"
+
+# Most each operation may stage in the temp directory, such as an archive
+# downloaded to extract; unlimited when unset
+# scratch_quota = "20GiB"
+
+# Key that encrypts manifests instead of a password (see flux key generate)
+# manifest_key = "offsite"
+
 [compression]
 # Default compression algorithm: zstd, xz, brotli, gzip, store
 default_algorithm = "zstd"
@@ -322,7 +566,7 @@ follow_symlinks = false
 threads = 0
 # Memory limit in MB (0 = unlimited)
 memory_limit = 0
-# Buffer size in KB
+# Size in KB of the reusable I/O buffers
 buffer_size = 64
 
 [strategy]
@@ -349,6 +593,24 @@ level = 7
 # algorithm = "zstd"
 # level = 1
 
+[exclude]
+# Directories and files skipped by --exclude-vcs (glob patterns, matched
+# against each path component)
+vcs_patterns = [".git", ".hg", ".svn", ".bzr", "_darcs", "CVS", "node_modules", "bower_components", "__pycache__", ".venv", ".tox"]
+
+[hashing]
+# Hash of the files in new manifests: blake3, sha256 or xxh3 (fastest, but
+# no protection against tampering). Existing manifests keep their own.
+manifest = "blake3"
+# Hash that finds duplicate files in an archive
+dedupe = "blake3"
+
+# Cloud locations; URLs under a profile's url use its endpoint
+# [cloud.profiles.work]
+# url = "s3://backups/work"
+# endpoint = "https://minio.example.com:9000"
+# encryption_key = "offsite"
+
 # Custom compression rules based on file patterns
 [[rules]]
 name = "web_assets"
@@ -384,14 +646,14 @@ priority = 95
             // Create default config with detailed examples
             let default_content = Self::default_config_content();
             fs::write(&path, default_content)?;
-            return Ok(Self::default());
+            return Self::default().with_overrides();
         }
 
         let contents = fs::read_to_string(&path)?;
         let config: Config = toml::from_str(&contents)
             .map_err(|e| Error::ConfigError(format!("Failed to parse config: {}", e)))?;
 
-        Ok(config)
+        config.with_overrides()
     }
 
     /// Save configuration to file
@@ -406,10 +668,105 @@ priority = 95
 
     /// Load configuration or use defaults if loading fails
     pub fn load_or_default() -> Self {
-        Self::load().unwrap_or_default()
+        Self::load().unwrap_or_else(|_| {
+            let config = Self::default();
+            config.clone().with_overrides().unwrap_or(config)
+        })
+    }
+
+    /// Set the value at a dotted `key`, such as `compression.default_level`
+    /// or `rules.0.level`
+    ///
+    /// `value` is read as a TOML value, so numbers, booleans and arrays like
+    /// `[".git", "target"]` work; anything that does not parse, or any value
+    /// for a key that holds a string, is taken as a plain string. Keys that
+    /// are not part of the configuration are rejected, except that a new
+    /// entry is started for a map such as `cloud.profiles.work.endpoint`.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let unknown = || Error::ConfigError(format!("Unknown config key: {}", key));
+        let serialize = |config: &Config| {
+            toml::Value::try_from(config)
+                .map_err(|e| Error::ConfigError(format!("Failed to serialize config: {}", e)))
+        };
+
+        let parts: Vec<&str> = key.split('.').collect();
+        if parts.iter().any(|part| part.is_empty()) {
+            return Err(unknown());
+        }
+        let (last, parents) = parts.split_last().ok_or_else(unknown)?;
+
+        let mut root = serialize(self)?;
+        let mut node = &mut root;
+        for part in parents {
+            // A missing table may be a new map entry; the check below
+            // rejects it if serde does not keep it
+            if let toml::Value::Table(table) = node {
+                if !table.contains_key(*part) {
+                    table.insert(part.to_string(), toml::Value::Table(toml::Table::new()));
+                }
+            }
+            node = child_mut(node, part).ok_or_else(unknown)?;
+        }
+        let value = match child_mut(node, last) {
+            Some(toml::Value::String(_)) => toml::Value::String(value.to_string()),
+            _ => parse_value(value),
+        };
+        match node {
+            toml::Value::Table(table) => {
+                table.insert(last.to_string(), value);
+            }
+            toml::Value::Array(items) => {
+                let slot = last
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| items.get_mut(index))
+                    .ok_or_else(unknown)?;
+                *slot = value;
+            }
+            _ => return Err(unknown()),
+        }
+
+        let updated: Config = root
+            .try_into()
+            .map_err(|e| Error::ConfigError(format!("Invalid value for {}: {}", key, e)))?;
+        // Serde drops keys it does not know, so they are missing afterwards
+        let mut check = serialize(&updated)?;
+        let mut node = &mut check;
+        for part in &parts {
+            node = child_mut(node, part).ok_or_else(unknown)?;
+        }
+
+        *self = updated;
+        Ok(())
+    }
+
+    /// Apply the overrides from [`set_overrides`]
+    fn with_overrides(mut self) -> Result<Self> {
+        let overrides = OVERRIDES.read().unwrap_or_else(|e| e.into_inner());
+        for (key, value) in overrides.iter() {
+            self.set(key, value)?;
+        }
+        Ok(self)
+    }
+}
+
+/// The table entry or array element named `part`
+fn child_mut<'a>(node: &'a mut toml::Value, part: &str) -> Option<&'a mut toml::Value> {
+    match node {
+        toml::Value::Table(table) => table.get_mut(part),
+        toml::Value::Array(items) => items.get_mut(part.parse::<usize>().ok()?),
+        _ => None,
     }
 }
 
+/// Read an override value as TOML, falling back to a plain string
+fn parse_value(value: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {}", value))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(value.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -448,6 +805,19 @@ mod tests {
         assert!(parse_size("invalid").is_err());
     }
 
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5_400));
+        assert_eq!(parse_duration("1.5min").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert!(parse_duration("5 days").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("-5").is_err());
+    }
+
     #[test]
     fn test_size_rules_deserialization() {
         let toml_str = r#"
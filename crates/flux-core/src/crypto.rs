@@ -0,0 +1,149 @@
+//! Password-based encryption for small files such as manifests
+//!
+//! AES-256-CTR with an HMAC-SHA256 over everything before the tag, both
+//! keys derived from the password with PBKDF2-HMAC-SHA256. The layout is
+//!
+//! ```text
+//! magic (8) | rounds (4, big endian) | salt (16) | iv (16) | ciphertext | tag (32)
+//! ```
+//!
+//! The round count is stored so it can be raised without breaking files
+//! written before. It is read before the tag can be checked, so counts
+//! outside [`ROUND_RANGE`] are refused rather than spent time on.
+
+use crate::{Error, Result};
+use aes::Aes256;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type Aes256Ctr = ctr::Ctr128BE<Aes256>;
+type HmacSha256 = Hmac<Sha256>;
+
+const MAGIC: &[u8; 8] = b"FLUXENC1";
+const ROUNDS: u32 = 200_000;
+/// Round counts [`open`] accepts
+const ROUND_RANGE: std::ops::RangeInclusive<u32> = 1_000..=10_000_000;
+const SALT_LEN: usize = 16;
+const IV_LEN: usize = 16;
+const TAG_LEN: usize = 32;
+const HEADER_LEN: usize = MAGIC.len() + 4 + SALT_LEN + IV_LEN;
+
+/// Whether `data` was written by [`seal`]
+pub(crate) fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Encrypt `plaintext` with `password`
+pub(crate) fn seal(password: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+    seal_with_rounds(password, plaintext, ROUNDS)
+}
+
+fn seal_with_rounds(password: &str, plaintext: &[u8], rounds: u32) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut iv = [0u8; IV_LEN];
+    getrandom::fill(&mut salt)
+        .and_then(|_| getrandom::fill(&mut iv))
+        .map_err(|e| Error::Other(format!("No randomness for encryption: {}", e)))?;
+    let (key, mac_key) = derive_keys(password, &salt, rounds);
+
+    let mut sealed = Vec::with_capacity(HEADER_LEN + plaintext.len() + TAG_LEN);
+    sealed.extend_from_slice(MAGIC);
+    sealed.extend_from_slice(&rounds.to_be_bytes());
+    sealed.extend_from_slice(&salt);
+    sealed.extend_from_slice(&iv);
+    sealed.extend_from_slice(plaintext);
+    Aes256Ctr::new(&key.into(), &iv.into()).apply_keystream(&mut sealed[HEADER_LEN..]);
+
+    let mut mac = HmacSha256::new_from_slice(&mac_key).expect("HMAC takes any key length");
+    mac.update(&sealed);
+    sealed.extend_from_slice(&mac.finalize().into_bytes());
+    Ok(sealed)
+}
+
+/// Decrypt what [`seal`] wrote; `Error::InvalidPassword` if `password` is
+/// wrong or the data was changed
+pub(crate) fn open(password: &str, sealed: &[u8]) -> Result<Vec<u8>> {
+    if !is_sealed(sealed) || sealed.len() < HEADER_LEN + TAG_LEN {
+        return Err(Error::Archive("Not an encrypted file".to_string()));
+    }
+    let rounds = u32::from_be_bytes(sealed[8..12].try_into().unwrap());
+    if !ROUND_RANGE.contains(&rounds) {
+        return Err(Error::Archive(format!(
+            "Unsupported key derivation round count: {}",
+            rounds
+        )));
+    }
+    let salt = &sealed[12..12 + SALT_LEN];
+    let iv: [u8; IV_LEN] = sealed[12 + SALT_LEN..HEADER_LEN].try_into().unwrap();
+    let (body, tag) = sealed.split_at(sealed.len() - TAG_LEN);
+    let (key, mac_key) = derive_keys(password, salt, rounds);
+
+    let mut mac = HmacSha256::new_from_slice(&mac_key).expect("HMAC takes any key length");
+    mac.update(body);
+    mac.verify_slice(tag).map_err(|_| {
+        Error::InvalidPassword("wrong password, or the file was modified".to_string())
+    })?;
+
+    let mut plaintext = body[HEADER_LEN..].to_vec();
+    Aes256Ctr::new(&key.into(), &iv.into()).apply_keystream(&mut plaintext);
+    Ok(plaintext)
+}
+
+/// The cipher key and the MAC key for `password`
+fn derive_keys(password: &str, salt: &[u8], rounds: u32) -> ([u8; 32], [u8; 32]) {
+    let mut keys = [0u8; 64];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, rounds, &mut keys);
+    let (key, mac_key) = keys.split_at(32);
+    (key.try_into().unwrap(), mac_key.try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Enough to exercise the format without the full cost of the real count
+    fn seal(password: &str, plaintext: &[u8]) -> Result<Vec<u8>> {
+        seal_with_rounds(password, plaintext, 1000)
+    }
+
+    #[test]
+    fn test_seal_and_open() {
+        let sealed = seal("secret", b"listing").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.windows(7).any(|w| w == b"listing"));
+        assert_eq!(open("secret", &sealed).unwrap(), b"listing");
+
+        // Fresh salt and IV every time
+        assert_ne!(seal("secret", b"listing").unwrap(), sealed);
+    }
+
+    #[test]
+    fn test_open_rejects_wrong_password_and_tampering() {
+        let mut sealed = seal("secret", b"listing").unwrap();
+        assert!(matches!(
+            open("guess", &sealed),
+            Err(Error::InvalidPassword(_))
+        ));
+
+        sealed[HEADER_LEN] ^= 1;
+        assert!(matches!(
+            open("secret", &sealed),
+            Err(Error::InvalidPassword(_))
+        ));
+        assert!(open("secret", &sealed[..HEADER_LEN]).is_err());
+    }
+
+    #[test]
+    fn test_open_rejects_round_counts_out_of_range() {
+        let mut sealed = seal("secret", b"listing").unwrap();
+        for rounds in [0, 999, 10_000_001, u32::MAX] {
+            sealed[8..12].copy_from_slice(&rounds.to_be_bytes());
+            assert!(
+                matches!(open("secret", &sealed), Err(Error::Archive(_))),
+                "{}",
+                rounds
+            );
+        }
+    }
+}
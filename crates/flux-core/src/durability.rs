@@ -0,0 +1,151 @@
+//! How far written files are pushed to disk
+//!
+//! Written data sits in the operating system's cache until it gets round
+//! to writing it out, so a crash or power cut soon after an extraction or
+//! a backup can lose files that were reported as written. A [`Durability`]
+//! policy syncs them explicitly: each file as soon as it is written, or
+//! everything at the end of the operation, which costs less and still
+//! leaves nothing unsynced once the operation returns. Archives and
+//! manifests are synced under either.
+
+use crate::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// When written files are synced to disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Durability {
+    /// Leave it to the operating system
+    #[default]
+    None,
+    /// Sync each file before moving on to the next, so a crash loses at
+    /// most the file being written
+    PerFile,
+    /// Sync everything once the operation is done
+    Final,
+}
+
+impl Durability {
+    /// Lower-case name, as accepted by [`FromStr`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            Durability::None => "none",
+            Durability::PerFile => "per-file",
+            Durability::Final => "final",
+        }
+    }
+
+    /// Sync the file at `path` and the directory holding it, unless
+    /// nothing is to be synced
+    ///
+    /// For single outputs such as an archive or a manifest, for which
+    /// `per-file` and `final` are the same.
+    pub fn sync_output(&self, path: &Path) -> Result<()> {
+        if *self == Durability::None {
+            return Ok(());
+        }
+        let synced = sync_file(path).and_then(|()| match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => sync_dir(parent),
+            _ => Ok(()),
+        });
+        synced.map_err(|e| Error::from(e).with_path(path))
+    }
+}
+
+impl FromStr for Durability {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "none" => Ok(Durability::None),
+            "per-file" => Ok(Durability::PerFile),
+            "final" => Ok(Durability::Final),
+            _ => Err(Error::InvalidArgument(format!(
+                "Unknown durability: {} (expected none, per-file or final)",
+                s
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Durability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Files one operation wrote, synced as its [`Durability`] asks
+#[derive(Debug)]
+pub(crate) struct Syncer {
+    policy: Durability,
+    /// Files left to sync under `Final`
+    files: Vec<PathBuf>,
+    /// Directories new names went into
+    dirs: BTreeSet<PathBuf>,
+}
+
+impl Syncer {
+    pub(crate) fn new(policy: Durability) -> Self {
+        Self {
+            policy,
+            files: Vec::new(),
+            dirs: BTreeSet::new(),
+        }
+    }
+
+    /// Note `path` as written, syncing it straight away under `PerFile`;
+    /// directories and symlinks only have their names synced
+    pub(crate) fn written(&mut self, path: &Path) -> Result<()> {
+        if self.policy == Durability::None {
+            return Ok(());
+        }
+        if fs::symlink_metadata(path)?.is_file() {
+            match self.policy {
+                Durability::PerFile => sync_file(path)?,
+                _ => self.files.push(path.to_path_buf()),
+            }
+        }
+        if let Some(parent) = path.parent() {
+            self.dirs.insert(parent.to_path_buf());
+        }
+        Ok(())
+    }
+
+    /// Sync the files left, then the directories holding everything
+    /// written
+    pub(crate) fn finish(self) -> Result<()> {
+        for file in &self.files {
+            sync_file(file).map_err(|e| Error::from(e).with_path(file))?;
+        }
+        for dir in &self.dirs {
+            sync_dir(dir).map_err(|e| Error::from(e).with_path(dir))?;
+        }
+        Ok(())
+    }
+}
+
+/// Sync the data of the file at `path`
+pub(crate) fn sync_file(path: &Path) -> io::Result<()> {
+    // Windows only flushes handles open for writing
+    #[cfg(windows)]
+    let file = fs::OpenOptions::new().write(true).open(path)?;
+    #[cfg(not(windows))]
+    let file = File::open(path)?;
+    file.sync_all()
+}
+
+/// Make the names in `dir` durable; directories cannot be synced on
+/// Windows, where renames are durable once done
+pub(crate) fn sync_dir(dir: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    File::open(dir)?.sync_all()?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
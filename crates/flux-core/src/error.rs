@@ -1,5 +1,8 @@
 //! Error types for flux-lib
 
+use serde::Serialize;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -48,6 +51,237 @@ pub enum Error {
 
     #[error("Security error: {0}")]
     SecurityError(String),
+
+    #[error("Password required: {0}")]
+    PasswordRequired(String),
+
+    #[error("Invalid password: {0}")]
+    InvalidPassword(String),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error(
+        "Not enough free space in {}: about {} needed, {} available",
+        path.display(),
+        mebibytes(*required),
+        mebibytes(*available)
+    )]
+    InsufficientSpace {
+        path: PathBuf,
+        required: u64,
+        available: u64,
+    },
+
+    /// Another error with the file or archive entry it concerns; see
+    /// [`Error::with_path`] and [`Error::with_entry`]
+    #[error("{error}")]
+    Context {
+        path: Option<PathBuf>,
+        entry: Option<PathBuf>,
+        error: Box<Error>,
+    },
+}
+
+/// Stable identifier for the kind of an [`Error`]
+///
+/// Messages may be reworded between releases; codes are not, so scripts and
+/// the CLI's exit status are based on them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    Io,
+    NotFound,
+    PermissionDenied,
+    InsufficientSpace,
+    Archive,
+    Compression,
+    InvalidPath,
+    InvalidArgument,
+    UnsupportedFormat,
+    UnsupportedOperation,
+    Config,
+    FileExists,
+    PartialFailure,
+    Security,
+    PasswordRequired,
+    InvalidPassword,
+    Other,
+}
+
+impl ErrorCode {
+    /// The code as written in reports, such as `not_found`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::Io => "io",
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::PermissionDenied => "permission_denied",
+            ErrorCode::InsufficientSpace => "insufficient_space",
+            ErrorCode::Archive => "archive",
+            ErrorCode::Compression => "compression",
+            ErrorCode::InvalidPath => "invalid_path",
+            ErrorCode::InvalidArgument => "invalid_argument",
+            ErrorCode::UnsupportedFormat => "unsupported_format",
+            ErrorCode::UnsupportedOperation => "unsupported_operation",
+            ErrorCode::Config => "config",
+            ErrorCode::FileExists => "file_exists",
+            ErrorCode::PartialFailure => "partial_failure",
+            ErrorCode::Security => "security",
+            ErrorCode::PasswordRequired => "password_required",
+            ErrorCode::InvalidPassword => "invalid_password",
+            ErrorCode::Other => "other",
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// An [`Error`] as data, for machine-readable output
+#[derive(Debug, Clone, Serialize)]
+pub struct ErrorReport {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry: Option<PathBuf>,
+    /// Whether the same operation may succeed if tried again
+    pub retryable: bool,
+}
+
+impl Error {
+    /// Record the file this error concerns, unless one is already known
+    pub fn with_path(self, path: impl AsRef<Path>) -> Self {
+        match self {
+            Error::Context {
+                path: None,
+                entry,
+                error,
+            } => Error::Context {
+                path: Some(path.as_ref().to_path_buf()),
+                entry,
+                error,
+            },
+            Error::Context { .. } => self,
+            error => Error::Context {
+                path: Some(path.as_ref().to_path_buf()),
+                entry: None,
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// Record the archive entry this error concerns, unless one is already
+    /// known
+    pub fn with_entry(self, entry: impl AsRef<Path>) -> Self {
+        match self {
+            Error::Context {
+                path,
+                entry: None,
+                error,
+            } => Error::Context {
+                path,
+                entry: Some(entry.as_ref().to_path_buf()),
+                error,
+            },
+            Error::Context { .. } => self,
+            error => Error::Context {
+                path: None,
+                entry: Some(entry.as_ref().to_path_buf()),
+                error: Box::new(error),
+            },
+        }
+    }
+
+    /// This error without the path and entry recorded around it
+    pub fn inner(&self) -> &Error {
+        match self {
+            Error::Context { error, .. } => error.inner(),
+            error => error,
+        }
+    }
+
+    /// The file this error concerns, if known
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Error::Context {
+                path: Some(path), ..
+            } => Some(path),
+            Error::Context { error, .. } => error.path(),
+            Error::FileExists(path) | Error::InsufficientSpace { path, .. } => Some(path),
+            _ => None,
+        }
+    }
+
+    /// The archive entry this error concerns, if known
+    pub fn entry(&self) -> Option<&Path> {
+        match self {
+            Error::Context {
+                entry: Some(entry), ..
+            } => Some(entry),
+            Error::Context { error, .. } => error.entry(),
+            _ => None,
+        }
+    }
+
+    /// Stable code for the kind of this error
+    pub fn code(&self) -> ErrorCode {
+        match self.inner() {
+            Error::Io(error) => match error.kind() {
+                ErrorKind::NotFound => ErrorCode::NotFound,
+                ErrorKind::PermissionDenied => ErrorCode::PermissionDenied,
+                _ => ErrorCode::Io,
+            },
+            Error::Archive(_) | Error::ArchiveError(_) | Error::Zip(_) => ErrorCode::Archive,
+            Error::Compression(_) => ErrorCode::Compression,
+            Error::InvalidPath(_) => ErrorCode::InvalidPath,
+            Error::InvalidArgument(_) => ErrorCode::InvalidArgument,
+            Error::InsufficientSpace { .. } => ErrorCode::InsufficientSpace,
+            Error::UnsupportedFormat(_) => ErrorCode::UnsupportedFormat,
+            Error::UnsupportedOperation(_) => ErrorCode::UnsupportedOperation,
+            Error::Config(_) | Error::ConfigError(_) => ErrorCode::Config,
+            Error::FileExists(_) => ErrorCode::FileExists,
+            Error::PartialFailure { .. } => ErrorCode::PartialFailure,
+            Error::NotFound(_) => ErrorCode::NotFound,
+            Error::SecurityError(_) => ErrorCode::Security,
+            Error::PasswordRequired(_) => ErrorCode::PasswordRequired,
+            Error::InvalidPassword(_) => ErrorCode::InvalidPassword,
+            Error::Other(_) => ErrorCode::Other,
+            Error::Context { .. } => unreachable!("inner() removes context"),
+        }
+    }
+
+    /// Whether the failure may be temporary, such as an interrupted read,
+    /// rather than something that will fail the same way again
+    pub fn is_retryable(&self) -> bool {
+        match self.inner() {
+            Error::Io(error) => matches!(
+                error.kind(),
+                ErrorKind::Interrupted
+                    | ErrorKind::TimedOut
+                    | ErrorKind::WouldBlock
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::BrokenPipe
+            ),
+            _ => false,
+        }
+    }
+
+    /// Everything known about this error, ready to serialize
+    pub fn report(&self) -> ErrorReport {
+        ErrorReport {
+            code: self.code(),
+            message: self.to_string(),
+            path: self.path().map(Path::to_path_buf),
+            entry: self.entry().map(Path::to_path_buf),
+            retryable: self.is_retryable(),
+        }
+    }
 }
 
 impl From<zip::result::ZipError> for Error {
@@ -58,8 +292,18 @@ impl From<zip::result::ZipError> for Error {
 
 impl From<walkdir::Error> for Error {
     fn from(err: walkdir::Error) -> Self {
-        Error::Io(err.into())
+        let path = err.path().map(Path::to_path_buf);
+        let error = Error::Io(err.into());
+        match path {
+            Some(path) => error.with_path(path),
+            None => error,
+        }
     }
 }
 
+/// `bytes` in MiB with one decimal, for messages
+fn mebibytes(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
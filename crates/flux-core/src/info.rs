@@ -0,0 +1,160 @@
+//! What this build of Flux can do, for `flux info` and bug reports
+//!
+//! [`BuildInfo::collect`] reads the tables the rest of the crate works from
+//! rather than a list of its own: the formats and aliases in
+//! [`EXTENSIONS`] and [`COMPRESSION_NAMES`] with their
+//! [`FormatCapabilities`], every [`Algorithm`], and the library versions
+//! the build script took from the lock file. A format or algorithm added
+//! there shows up here without anyone remembering to.
+
+use crate::archive::detect::{COMPRESSION_NAMES, EXTENSIONS};
+use crate::archive::FormatCapabilities;
+use crate::buffer::BufferPool;
+use crate::config::Config;
+use crate::strategy::{Algorithm, CompressionStrategy};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// `name=version` of each library, as the build script recorded them
+const LIBRARY_VERSIONS: &str = env!("FLUX_LIBRARY_VERSIONS");
+
+/// A library Flux reads or writes a format with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Library {
+    /// Crate name
+    pub name: &'static str,
+    /// Version the build locked, `None` when built without a lock file
+    pub version: Option<&'static str>,
+}
+
+impl Library {
+    fn new(name: &'static str) -> Self {
+        let version = LIBRARY_VERSIONS.split(',').find_map(|pair| {
+            pair.split_once('=')
+                .filter(|(library, _)| *library == name)
+                .map(|(_, version)| version)
+        });
+        Self { name, version }
+    }
+}
+
+/// An archive format Flux knows
+#[derive(Debug, Clone, Serialize)]
+pub struct FormatSupport {
+    /// Name as `--format` takes it, such as `tar.zst`
+    pub name: &'static str,
+    /// Other extensions and names standing for it
+    pub aliases: Vec<&'static str>,
+    /// Whether Flux writes the format as well as reads it
+    pub pack: bool,
+    pub capabilities: FormatCapabilities,
+    /// Library holding the entries; compressed tars also need the one for
+    /// their [`AlgorithmSupport`]
+    pub library: Library,
+}
+
+/// A compression algorithm Flux knows
+#[derive(Debug, Clone, Serialize)]
+pub struct AlgorithmSupport {
+    /// Name as `--algo` takes it
+    pub name: String,
+    /// Library compressing with it; `None` for storing
+    pub library: Option<Library>,
+    /// Version of the C library behind it, where it reports one
+    pub native_version: Option<&'static str>,
+}
+
+/// This build and the environment it runs in
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInfo {
+    /// Version of flux-core
+    pub version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    /// Whether phase timings can be recorded
+    pub telemetry: bool,
+    pub formats: Vec<FormatSupport>,
+    pub algorithms: Vec<AlgorithmSupport>,
+    /// Threads compression uses unless told otherwise
+    pub default_threads: usize,
+    /// `performance.threads` from the configuration, 0 for automatic
+    pub configured_threads: usize,
+    /// Size of the shared I/O buffers in bytes
+    pub buffer_size: usize,
+    /// Where the configuration is read from; `None` when the platform has
+    /// no configuration directory
+    pub config_path: Option<PathBuf>,
+    /// Where temporary files go; `None` when it cannot be created
+    pub temp_dir: Option<PathBuf>,
+    /// Most bytes one operation may stage there; `None` for no limit
+    pub scratch_quota: Option<u64>,
+}
+
+impl BuildInfo {
+    /// Gather what this build supports and how it is set up
+    pub fn collect() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            telemetry: cfg!(feature = "telemetry"),
+            formats: formats(),
+            algorithms: Algorithm::ALL.into_iter().map(algorithm).collect(),
+            default_threads: CompressionStrategy::default().threads,
+            configured_threads: Config::load_or_default().performance.threads,
+            buffer_size: BufferPool::global().chunk_size(),
+            config_path: Config::config_path().ok(),
+            temp_dir: crate::space::temp_dir().ok(),
+            scratch_quota: crate::space::scratch_quota(),
+        }
+    }
+
+    /// The format `name`, if this build knows it
+    pub fn format(&self, name: &str) -> Option<&FormatSupport> {
+        self.formats.iter().find(|format| format.name == name)
+    }
+}
+
+/// Every format named in [`EXTENSIONS`], in the order first named there
+fn formats() -> Vec<FormatSupport> {
+    let mut formats: Vec<FormatSupport> = Vec::new();
+    for &(alias, name) in EXTENSIONS.iter().chain(COMPRESSION_NAMES) {
+        if let Some(format) = formats.iter_mut().find(|format| format.name == name) {
+            if alias != name {
+                format.aliases.push(alias);
+            }
+            continue;
+        }
+        let Some(capabilities) = FormatCapabilities::of(name) else {
+            continue;
+        };
+        formats.push(FormatSupport {
+            name,
+            aliases: (alias != name).then_some(alias).into_iter().collect(),
+            // `sevenz::pack_7z` refuses; 7z archives are only read
+            pack: name != "7z",
+            capabilities,
+            library: Library::new(match name {
+                "zip" => "zip",
+                "7z" => "sevenz-rust",
+                _ => "tar",
+            }),
+        });
+    }
+    formats
+}
+
+fn algorithm(algorithm: Algorithm) -> AlgorithmSupport {
+    let (library, native_version) = match algorithm {
+        Algorithm::Store => (None, None),
+        Algorithm::Gzip => (Some("flate2"), None),
+        Algorithm::Zstd => (Some("zstd"), Some(zstd::zstd_safe::version_string())),
+        Algorithm::Xz => (Some("xz2"), None),
+        Algorithm::Brotli => (Some("brotli"), None),
+    };
+    AlgorithmSupport {
+        name: algorithm.to_string(),
+        library: library.map(Library::new),
+        native_version,
+    }
+}
@@ -0,0 +1,341 @@
+//! Named encryption keys
+//!
+//! Rather than a password typed for every backup, encrypted manifests can
+//! use a random 256-bit key kept in the key store, a `keys` directory next
+//! to the configuration file, and named by `manifest_key` in the
+//! configuration. Each key is a small TOML file:
+//!
+//! ```toml
+//! name = "offsite"
+//! created = 1760000000
+//! protected = true
+//! secret = "464c5558454e4331..."
+//! ```
+//!
+//! A protected key is stored sealed with a passphrase, in the format of
+//! [`crate::crypto`], and has to be unlocked with it before use. Exporting
+//! copies the file as stored, so a protected key stays protected on its way
+//! to another machine, where importing checks it and adds it to the store.
+
+use crate::config::Config;
+use crate::{crypto, Error, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::info;
+
+/// Length of a key in bytes
+pub const KEY_LEN: usize = 32;
+
+/// Extension of key files in the store
+const EXTENSION: &str = "fluxkey";
+
+/// A key, unlocked
+pub struct Key {
+    pub name: String,
+    /// When the key was generated (Unix timestamp)
+    pub created: i64,
+    secret: [u8; KEY_LEN],
+}
+
+impl Key {
+    /// The key as a password, for what takes one, such as
+    /// [`crate::manifest::Manifest::save_with_password`]
+    pub fn password(&self) -> String {
+        to_hex(&self.secret)
+    }
+
+    /// The key itself, for ciphers that take one directly
+    pub fn secret(&self) -> &[u8; KEY_LEN] {
+        &self.secret
+    }
+}
+
+impl std::fmt::Debug for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never the secret, which ends up in logs otherwise
+        f.debug_struct("Key")
+            .field("name", &self.name)
+            .field("created", &self.created)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A key as stored, without its secret unlocked
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyInfo {
+    pub name: String,
+    /// When the key was generated (Unix timestamp)
+    pub created: i64,
+    /// Whether the key is sealed with a passphrase
+    pub protected: bool,
+}
+
+/// What a key file holds
+#[derive(Serialize, Deserialize)]
+struct KeyFile {
+    name: String,
+    created: i64,
+    protected: bool,
+    /// The key in hex, sealed with the passphrase when protected
+    secret: String,
+}
+
+impl KeyFile {
+    fn info(&self) -> KeyInfo {
+        KeyInfo {
+            name: self.name.clone(),
+            created: self.created,
+            protected: self.protected,
+        }
+    }
+
+    fn read(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| Error::from(e).with_path(path))?;
+        let file: KeyFile = toml::from_str(&content).map_err(|e| {
+            Error::InvalidArgument(format!("{} is not a key file: {}", path.display(), e))
+        })?;
+        validate_name(&file.name)?;
+        let secret = from_hex(&file.secret).ok_or_else(|| {
+            Error::InvalidArgument(format!("{} holds a damaged key", path.display()))
+        })?;
+        if !file.protected && secret.len() != KEY_LEN {
+            return Err(Error::InvalidArgument(format!(
+                "{} holds a damaged key",
+                path.display()
+            )));
+        }
+        Ok(file)
+    }
+
+    fn unlock(&self, passphrase: Option<&str>) -> Result<Key> {
+        let stored = from_hex(&self.secret)
+            .ok_or_else(|| Error::InvalidArgument(format!("Key {} is damaged", self.name)))?;
+        let secret = match (self.protected, passphrase) {
+            (false, _) => stored,
+            (true, Some(passphrase)) => crypto::open(passphrase, &stored).map_err(|e| match e {
+                Error::InvalidPassword(_) => {
+                    Error::InvalidPassword(format!("wrong passphrase for key {}", self.name))
+                }
+                other => other,
+            })?,
+            (true, None) => {
+                return Err(Error::PasswordRequired(format!(
+                    "key {} is protected by a passphrase",
+                    self.name
+                )))
+            }
+        };
+        let secret = secret
+            .try_into()
+            .map_err(|_| Error::InvalidArgument(format!("Key {} is damaged", self.name)))?;
+        Ok(Key {
+            name: self.name.clone(),
+            created: self.created,
+            secret,
+        })
+    }
+}
+
+/// The directory of named keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyStore {
+    dir: PathBuf,
+}
+
+impl KeyStore {
+    /// The store in `dir`, created when a key is first added
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The store next to the configuration file
+    pub fn open_default() -> Result<Self> {
+        let config = Config::config_path()?;
+        Ok(Self::new(
+            config.parent().unwrap_or(Path::new(".")).join("keys"),
+        ))
+    }
+
+    /// File of the key `name`
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.{}", name, EXTENSION))
+    }
+
+    /// Generate a key named `name`, sealed with `passphrase` if one is
+    /// given; a key by that name already there is an error
+    pub fn generate(&self, name: &str, passphrase: Option<&str>) -> Result<KeyInfo> {
+        validate_name(name)?;
+        let path = self.path(name);
+        if path.exists() {
+            return Err(Error::FileExists(path));
+        }
+
+        let mut secret = [0u8; KEY_LEN];
+        getrandom::fill(&mut secret)
+            .map_err(|e| Error::Other(format!("No randomness for a key: {}", e)))?;
+        let stored = match passphrase {
+            Some(passphrase) => crypto::seal(passphrase, &secret)?,
+            None => secret.to_vec(),
+        };
+        let file = KeyFile {
+            name: name.to_string(),
+            created: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs() as i64,
+            protected: passphrase.is_some(),
+            secret: to_hex(&stored),
+        };
+        self.write(&path, &file)?;
+        info!("Generated key {} in {:?}", name, path);
+        Ok(file.info())
+    }
+
+    /// Keys in the store, by name
+    pub fn list(&self) -> Result<Vec<KeyInfo>> {
+        if !self.dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut keys = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == EXTENSION) {
+                keys.push(KeyFile::read(&path)?.info());
+            }
+        }
+        keys.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(keys)
+    }
+
+    /// How the key `name` is stored
+    pub fn info(&self, name: &str) -> Result<KeyInfo> {
+        Ok(self.read(name)?.info())
+    }
+
+    /// Unlock the key `name`, with `passphrase` if it is protected
+    pub fn load(&self, name: &str, passphrase: Option<&str>) -> Result<Key> {
+        self.read(name)?.unlock(passphrase)
+    }
+
+    /// Write the key `name` to `output` as stored, still protected if it is
+    pub fn export(&self, name: &str, output: &Path) -> Result<()> {
+        let file = self.read(name)?;
+        self.write(output, &file)
+    }
+
+    /// The key `name` as stored, in the text [`KeyStore::export`] writes
+    pub fn export_string(&self, name: &str) -> Result<String> {
+        let file = self.read(name)?;
+        toml::to_string(&file).map_err(|e| Error::Other(format!("Failed to write key: {}", e)))
+    }
+
+    /// Add the key exported to `input` to the store, under `name` or the
+    /// name it was exported with, replacing a key by that name only if
+    /// `overwrite`
+    pub fn import(&self, input: &Path, name: Option<&str>, overwrite: bool) -> Result<KeyInfo> {
+        let mut file = KeyFile::read(input)?;
+        if let Some(name) = name {
+            validate_name(name)?;
+            file.name = name.to_string();
+        }
+        let path = self.path(&file.name);
+        if path.exists() && !overwrite {
+            return Err(Error::FileExists(path));
+        }
+        self.write(&path, &file)?;
+        info!("Imported key {} from {:?}", file.name, input);
+        Ok(file.info())
+    }
+
+    fn read(&self, name: &str) -> Result<KeyFile> {
+        validate_name(name)?;
+        let path = self.path(name);
+        if !path.is_file() {
+            return Err(Error::NotFound(format!(
+                "No key named {} (generate one with flux key generate {})",
+                name, name
+            )));
+        }
+        KeyFile::read(&path)
+    }
+
+    /// Write `file` to `path`, readable by its owner alone
+    fn write(&self, path: &Path, file: &KeyFile) -> Result<()> {
+        let content = toml::to_string(file)
+            .map_err(|e| Error::Other(format!("Failed to write key: {}", e)))?;
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut output = options
+            .open(path)
+            .map_err(|e| Error::from(e).with_path(path))?;
+        std::io::Write::write_all(&mut output, content.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Names become file names, so they are kept to letters, digits, `-`,
+/// `_` and `.`, and do not start with a dot
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidArgument(format!(
+            "Invalid key name: {:?} (use letters, digits, '-', '_' and '.')",
+            name
+        )))
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_round_trip() {
+        let bytes = [0u8, 1, 0xab, 0xff];
+        assert_eq!(to_hex(&bytes), "0001abff");
+        assert_eq!(from_hex("0001abff").unwrap(), bytes);
+        assert!(from_hex("abc").is_none());
+        assert!(from_hex("zz").is_none());
+    }
+
+    #[test]
+    fn test_key_names() {
+        for name in ["offsite", "home-nas", "2024_keys.v2"] {
+            assert!(validate_name(name).is_ok(), "{}", name);
+        }
+        for name in ["", ".hidden", "../escape", "with space", "a/b"] {
+            assert!(validate_name(name).is_err(), "{}", name);
+        }
+    }
+}
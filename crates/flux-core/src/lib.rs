@@ -4,20 +4,33 @@
 //! and compressing files with intelligent compression strategies.
 
 pub mod archive;
+pub mod buffer;
 pub mod config;
+mod crypto;
+pub mod durability;
 pub mod error;
+pub mod info;
 pub mod interactive;
+pub mod keys;
 pub mod manifest;
 pub mod metadata;
+pub mod ops;
+pub mod priority;
 pub mod progress;
 pub mod security;
+pub mod shell_integration;
+pub mod snapshot;
+pub mod space;
 pub mod strategy;
+#[cfg(feature = "telemetry")]
+pub mod telemetry;
+pub mod throttle;
 pub mod utils;
 
-pub use error::{Error, Result};
+pub use error::{Error, ErrorCode, ErrorReport, Result};
 
 // Re-export commonly used types
 pub use archive::{
-    create_extractor, extract, extract_with_options, inspect, pack, pack_with_strategy,
-    ArchiveEntry, ExtractOptions, PackOptions,
+    create_extractor, extract, extract_with_options, inspect, inspect_stats, pack,
+    pack_with_strategy, ArchiveEntry, ArchiveStats, ExtractOptions, PackOptions,
 };
@@ -1,9 +1,30 @@
 //! Manifest handling for incremental backups
-
+//!
+//! A manifest lists every path and size in a backup, so it can be saved
+//! encrypted with a password; see [`Manifest::save_with_password`].
+//!
+//! Entries can also carry tags and a note of the user's, such as
+//! `retention=7y` on a tax return, for tools that restore or prune backups
+//! to go by. A manifest made from a directory has none; incremental backups
+//! carry them over from the manifest before, see
+//! [`Manifest::keep_annotations`].
+//!
+//! Files are hashed with [`HashAlgorithm::for_manifests`] unless a manifest
+//! before says otherwise, and the header records which, so a manifest keeps
+//! being read the same way whatever the configuration says later. One
+//! without it, written before the choice existed, was hashed with Blake3.
+
+use crate::archive::checksum::HashAlgorithm;
+use crate::archive::normalize::comparable;
+use crate::archive::{PackFilter, PackHashes};
+use crate::buffer::BufferPool;
+use crate::crypto;
+use crate::progress::{NoProgress, ProgressRead, ProgressSink};
 use crate::{Error, Result};
-use blake3::Hasher;
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
@@ -18,13 +39,20 @@ pub struct FileEntry {
     pub path: PathBuf,
     /// File size in bytes
     pub size: u64,
-    /// Blake3 hash of file contents
+    /// Hash of file contents, taken with the manifest's
+    /// [`Manifest::hash_algorithm`]
     pub hash: String,
     /// Modified time (Unix timestamp)
     pub mtime: i64,
     /// Unix permissions (if available)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mode: Option<u32>,
+    /// Inode number, on Unix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inode: Option<u64>,
+    /// Last status change time (Unix timestamp), on Unix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ctime: Option<i64>,
     /// Whether this is a directory
     pub is_dir: bool,
     /// Whether this is a symlink
@@ -32,6 +60,50 @@ pub struct FileEntry {
     /// Link target (for symlinks)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub link_target: Option<PathBuf>,
+    /// Tags set by the user, by key
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+    /// Note set by the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl FileEntry {
+    /// Whether this entry, from an earlier scan, describes the same
+    /// unchanged regular file as `current` does, judging by its metadata
+    ///
+    /// Size and modification time must match. Tools that restore or copy a
+    /// tree can set the modification time back, though, so where both scans
+    /// recorded them the inode and status change time must match as well;
+    /// those cannot be set back.
+    fn looks_unchanged(&self, current: &FileEntry) -> bool {
+        let same_identity = match (self.inode, self.ctime, current.inode, current.ctime) {
+            (Some(inode), Some(ctime), Some(current_inode), Some(current_ctime)) => {
+                inode == current_inode && ctime == current_ctime
+            }
+            _ => true,
+        };
+        !self.is_dir
+            && !self.is_symlink
+            && self.size == current.size
+            && self.mtime == current.mtime
+            && same_identity
+    }
+
+    /// Whether `current` differs from this entry in kind, size,
+    /// modification time or link target; a directory only in kind
+    fn metadata_differs(&self, current: &FileEntry) -> bool {
+        if self.is_dir != current.is_dir || self.is_symlink != current.is_symlink {
+            return true;
+        }
+        if self.is_dir {
+            return false;
+        }
+        if self.is_symlink {
+            return self.link_target != current.link_target;
+        }
+        self.size != current.size || self.mtime != current.mtime
+    }
 }
 
 /// Backup manifest
@@ -39,6 +111,9 @@ pub struct FileEntry {
 pub struct Manifest {
     /// Version of the manifest format
     pub version: u32,
+    /// Hash function the files' hashes were taken with
+    #[serde(default)]
+    pub hash_algorithm: HashAlgorithm,
     /// Creation timestamp
     pub created: i64,
     /// Base directory (for reference)
@@ -57,102 +132,158 @@ impl Manifest {
 
     /// Create a new manifest for a directory
     pub fn from_directory<P: AsRef<Path>>(base_dir: P) -> Result<Self> {
-        let base_dir = base_dir.as_ref();
-        let mut files = HashMap::new();
-        let mut total_size = 0u64;
-        let mut file_count = 0u32;
+        Self::from_directory_filtered(base_dir, &PackFilter::default())
+    }
+
+    /// Create a manifest for a directory, leaving out what `filter` excludes
+    /// so that it matches an archive packed with the same filter
+    pub fn from_directory_filtered<P: AsRef<Path>>(
+        base_dir: P,
+        filter: &PackFilter,
+    ) -> Result<Self> {
+        Self::scan(base_dir, filter, None, &NoProgress)
+    }
+
+    /// Create a manifest for a directory as [`Manifest::from_directory_filtered`]
+    /// does, taking the hash of each file that `previous` describes as it
+    /// still is (same size, modification time, and on Unix inode and status
+    /// change time) from there instead of reading the file
+    ///
+    /// The directory is walked first; the files found are then looked at and
+    /// hashed in parallel. `progress` is started with the bytes that need
+    /// hashing, told of each file as it is read and advanced as it is hashed,
+    /// and cancelling it stops the scan with an error. Pass no `previous`
+    /// manifest to hash every file.
+    ///
+    /// Files are hashed the way `previous` was, so the two can be compared,
+    /// or else with [`HashAlgorithm::for_manifests`].
+    pub fn scan<P: AsRef<Path>>(
+        base_dir: P,
+        filter: &PackFilter,
+        previous: Option<&Manifest>,
+        progress: &dyn ProgressSink,
+    ) -> Result<Self> {
+        let algorithm = previous.map_or_else(HashAlgorithm::for_manifests, |previous| {
+            previous.hash_algorithm
+        });
+        Self::scan_with_algorithm(base_dir, filter, previous, algorithm, progress)
+    }
 
+    /// Create a manifest for a directory as [`Manifest::scan`] does, hashing
+    /// files with `algorithm`
+    ///
+    /// Hashes are only taken from `previous` when it was made with the same
+    /// algorithm.
+    pub fn scan_with_algorithm<P: AsRef<Path>>(
+        base_dir: P,
+        filter: &PackFilter,
+        previous: Option<&Manifest>,
+        algorithm: HashAlgorithm,
+        progress: &dyn ProgressSink,
+    ) -> Result<Self> {
+        let previous = previous.filter(|previous| previous.hash_algorithm == algorithm);
+        Self::scan_with(
+            base_dir.as_ref(),
+            filter,
+            previous,
+            None,
+            algorithm,
+            progress,
+        )
+    }
+
+    /// Create a manifest for a directory just packed with `filter` and with
+    /// `packed` as [`PackOptions::hashes`](crate::archive::PackOptions::hashes),
+    /// taking the hash of each file from what the pack read
+    ///
+    /// Only files the pack did not read whole, or that changed size or
+    /// modification time since, are read again. The manifest uses the hash
+    /// `packed` was taken with.
+    pub fn from_packed<P: AsRef<Path>>(
+        base_dir: P,
+        filter: &PackFilter,
+        packed: &PackHashes,
+    ) -> Result<Self> {
+        Self::scan_with(
+            base_dir.as_ref(),
+            filter,
+            None,
+            Some(packed),
+            packed.algorithm(),
+            &NoProgress,
+        )
+    }
+
+    fn scan_with(
+        base_dir: &Path,
+        filter: &PackFilter,
+        previous: Option<&Manifest>,
+        packed: Option<&PackHashes>,
+        algorithm: HashAlgorithm,
+        progress: &dyn ProgressSink,
+    ) -> Result<Self> {
         info!("Creating manifest for directory: {:?}", base_dir);
 
-        for entry in WalkDir::new(base_dir)
-            .follow_links(false)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path();
-            let relative_path = path
-                .strip_prefix(base_dir)
-                .map_err(|_| Error::InvalidPath("Failed to compute relative path".to_string()))?;
-
-            // Skip empty relative paths (the base directory itself)
-            if relative_path.as_os_str().is_empty() {
+        let mut entries = scan_entries(base_dir, filter)?;
+
+        // Hashes taken while packing, and those of files that look the
+        // same as before, are kept
+        let known = previous.map(|previous| by_comparable_path(&previous.files));
+        let mut to_hash = Vec::new();
+        for (index, entry) in entries.iter_mut().enumerate() {
+            if entry.is_dir || entry.is_symlink {
                 continue;
             }
+            let from_pack = packed
+                .and_then(|packed| packed.get(&base_dir.join(&entry.path)))
+                .filter(|hash| hash.size == entry.size && hash.mtime == entry.mtime)
+                .map(|hash| hash.hash);
+            let cached = from_pack.or_else(|| {
+                known
+                    .as_ref()
+                    .and_then(|known| known.get(&*comparable(&entry.path)))
+                    .filter(|old| old.looks_unchanged(entry))
+                    .map(|old| old.hash.clone())
+            });
+            match cached {
+                Some(hash) => entry.hash = hash,
+                None => to_hash.push(index),
+            }
+        }
+        debug!(
+            "Hashing {} of {} entries, the rest unchanged",
+            to_hash.len(),
+            entries.len()
+        );
+
+        progress.start(to_hash.iter().map(|&index| entries[index].size).sum());
+        let hashes = to_hash
+            .par_iter()
+            .map(|&index| {
+                let path = base_dir.join(&entries[index].path);
+                progress.file(&path);
+                compute_file_hash(&path, algorithm, progress)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        for (index, hash) in to_hash.into_iter().zip(hashes) {
+            entries[index].hash = hash;
+        }
 
-            let metadata = entry.metadata()?;
-            let is_dir = metadata.is_dir();
-            let is_symlink = metadata.is_symlink();
-
-            let entry = if is_symlink {
-                let link_target = fs::read_link(path)?;
-                FileEntry {
-                    path: relative_path.to_path_buf(),
-                    size: 0,
-                    hash: String::new(),
-                    mtime: metadata
-                        .modified()
-                        .map(|t| {
-                            t.duration_since(SystemTime::UNIX_EPOCH)
-                                .unwrap_or_default()
-                                .as_secs() as i64
-                        })
-                        .unwrap_or(0),
-                    mode: get_file_mode(&metadata),
-                    is_dir: false,
-                    is_symlink: true,
-                    link_target: Some(link_target),
-                }
-            } else if is_dir {
-                FileEntry {
-                    path: relative_path.to_path_buf(),
-                    size: 0,
-                    hash: String::new(),
-                    mtime: metadata
-                        .modified()
-                        .map(|t| {
-                            t.duration_since(SystemTime::UNIX_EPOCH)
-                                .unwrap_or_default()
-                                .as_secs() as i64
-                        })
-                        .unwrap_or(0),
-                    mode: get_file_mode(&metadata),
-                    is_dir: true,
-                    is_symlink: false,
-                    link_target: None,
-                }
-            } else {
-                // Regular file - compute hash
-                let size = metadata.len();
-                let hash = compute_file_hash(path)?;
-
-                total_size += size;
+        let mut files = HashMap::with_capacity(entries.len());
+        let mut total_size = 0u64;
+        let mut file_count = 0u32;
+        for entry in entries {
+            if !entry.is_dir && !entry.is_symlink {
+                total_size += entry.size;
                 file_count += 1;
-
-                FileEntry {
-                    path: relative_path.to_path_buf(),
-                    size,
-                    hash,
-                    mtime: metadata
-                        .modified()
-                        .map(|t| {
-                            t.duration_since(SystemTime::UNIX_EPOCH)
-                                .unwrap_or_default()
-                                .as_secs() as i64
-                        })
-                        .unwrap_or(0),
-                    mode: get_file_mode(&metadata),
-                    is_dir: false,
-                    is_symlink: false,
-                    link_target: None,
-                }
-            };
-
+            }
             debug!("Added to manifest: {:?}", entry.path);
-            files.insert(relative_path.to_path_buf(), entry);
+            files.insert(entry.path.clone(), entry);
         }
 
         Ok(Self {
             version: Self::VERSION,
+            hash_algorithm: algorithm,
             created: SystemTime::now()
                 .duration_since(SystemTime::UNIX_EPOCH)
                 .unwrap_or_default()
@@ -166,6 +297,15 @@ impl Manifest {
 
     /// Save manifest to file
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        self.save_with_password(path, None)
+    }
+
+    /// Save manifest to file, encrypted with `password` if one is given
+    pub fn save_with_password<P: AsRef<Path>>(
+        &self,
+        path: P,
+        password: Option<&str>,
+    ) -> Result<()> {
         let path = path.as_ref();
 
         // Create parent directory if needed
@@ -176,22 +316,40 @@ impl Manifest {
         let json = serde_json::to_string_pretty(self)
             .map_err(|e| Error::Other(format!("Failed to serialize manifest: {}", e)))?;
 
+        let contents = match password {
+            Some(password) => crypto::seal(password, json.as_bytes())?,
+            None => json.into_bytes(),
+        };
+
         let mut file = File::create(path)?;
-        file.write_all(json.as_bytes())?;
+        file.write_all(&contents)?;
 
         info!("Saved manifest to: {:?}", path);
         Ok(())
     }
 
     /// Load manifest from file
+    ///
+    /// Fails with `Error::PasswordRequired` if the manifest is encrypted.
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::load_with_password(path, None)
+    }
+
+    /// Load manifest from file, decrypting it with `password` if it is
+    /// encrypted; a plain manifest loads whatever the password
+    pub fn load_with_password<P: AsRef<Path>>(path: P, password: Option<&str>) -> Result<Self> {
         let path = path.as_ref();
 
-        let mut file = File::open(path)?;
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
+        let mut contents = fs::read(path)?;
+        if crypto::is_sealed(&contents) {
+            contents = match password {
+                Some(password) => crypto::open(password, &contents),
+                None => Err(Error::PasswordRequired("manifest is encrypted".to_string())),
+            }
+            .map_err(|e| e.with_path(path))?;
+        }
 
-        let manifest: Self = serde_json::from_str(&contents)
+        let manifest: Self = serde_json::from_slice(&contents)
             .map_err(|e| Error::Other(format!("Failed to parse manifest: {}", e)))?;
 
         if manifest.version != Self::VERSION {
@@ -206,18 +364,125 @@ impl Manifest {
         Ok(manifest)
     }
 
+    /// Whether the manifest at `path` was saved encrypted
+    pub fn is_encrypted<P: AsRef<Path>>(path: P) -> Result<bool> {
+        let mut magic = Vec::new();
+        File::open(path)?.take(8).read_to_end(&mut magic)?;
+        Ok(crypto::is_sealed(&magic))
+    }
+
+    /// Entry for `path`, as stored or in another Unicode normalization;
+    /// a leading `./` and a trailing `/` are ignored
+    pub fn entry(&self, path: &Path) -> Option<&FileEntry> {
+        let path = comparable(trim_path(path)).into_owned();
+        self.files
+            .iter()
+            .find(|(stored, _)| comparable(stored) == path)
+            .map(|(_, entry)| entry)
+    }
+
+    /// Mutable entry for `path`, found as by [`Manifest::entry`]
+    fn entry_mut(&mut self, path: &Path) -> Result<&mut FileEntry> {
+        let wanted = comparable(trim_path(path)).into_owned();
+        self.files
+            .iter_mut()
+            .find(|(stored, _)| comparable(stored) == wanted)
+            .map(|(_, entry)| entry)
+            .ok_or_else(|| Error::NotFound(format!("{} is not in the manifest", path.display())))
+    }
+
+    /// Tag the entry for `path` with `key`, replacing any value it had
+    pub fn set_tag(&mut self, path: &Path, key: &str, value: &str) -> Result<()> {
+        if key.is_empty() || key.contains('=') {
+            return Err(Error::InvalidArgument(format!(
+                "Invalid tag name: {:?}",
+                key
+            )));
+        }
+        self.entry_mut(path)?
+            .tags
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    /// Remove tag `key` from the entry for `path`; `false` if it had none
+    pub fn remove_tag(&mut self, path: &Path, key: &str) -> Result<bool> {
+        Ok(self.entry_mut(path)?.tags.remove(key).is_some())
+    }
+
+    /// Set or, with `None`, clear the note on the entry for `path`
+    pub fn set_note(&mut self, path: &Path, note: Option<String>) -> Result<()> {
+        self.entry_mut(path)?.note = note;
+        Ok(())
+    }
+
+    /// Paths of the entries tagged `key`, only those with `value` when one
+    /// is given, sorted
+    pub fn tagged(&self, key: &str, value: Option<&str>) -> Vec<&Path> {
+        let mut paths: Vec<&Path> = self
+            .files
+            .values()
+            .filter(|entry| {
+                entry
+                    .tags
+                    .get(key)
+                    .is_some_and(|tag| value.is_none_or(|value| tag == value))
+            })
+            .map(|entry| entry.path.as_path())
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    /// Copy the tags and notes of `previous` to the entries for the same
+    /// paths, for a manifest made anew of a directory backed up before
+    ///
+    /// Tags set on this manifest win over those of `previous`.
+    pub fn keep_annotations(&mut self, previous: &Manifest) {
+        let previous = by_comparable_path(&previous.files);
+        for (path, entry) in &mut self.files {
+            let Some(old) = previous.get(&*comparable(path)) else {
+                continue;
+            };
+            for (key, value) in &old.tags {
+                entry
+                    .tags
+                    .entry(key.clone())
+                    .or_insert_with(|| value.clone());
+            }
+            if entry.note.is_none() {
+                entry.note = old.note.clone();
+            }
+        }
+    }
+
     /// Compare with another manifest to find changes
+    ///
+    /// Paths are matched regardless of their Unicode normalization, so a
+    /// tree copied between macOS and Linux is not seen as renamed. Added and
+    /// modified paths are spelled as in `other`, deleted ones as in `self`.
+    /// Manifests hashed with different algorithms are compared by size and
+    /// modification time instead of hashes.
     pub fn diff(&self, other: &Manifest) -> ManifestDiff {
+        let same_hash = self.hash_algorithm == other.hash_algorithm;
         let mut added = Vec::new();
         let mut modified = Vec::new();
         let mut deleted = Vec::new();
 
+        let old_files = by_comparable_path(&self.files);
+        let new_files = by_comparable_path(&other.files);
+
         // Find added and modified files
         for (path, entry) in &other.files {
-            match self.files.get(path) {
+            match old_files.get(&*comparable(path)) {
                 Some(old_entry) => {
                     // Check if modified
-                    if entry.hash != old_entry.hash || entry.mtime != old_entry.mtime {
+                    let content_differs = if same_hash {
+                        entry.hash != old_entry.hash
+                    } else {
+                        old_entry.metadata_differs(entry)
+                    };
+                    if content_differs || entry.mtime != old_entry.mtime {
                         modified.push(path.clone());
                     }
                 }
@@ -230,7 +495,7 @@ impl Manifest {
 
         // Find deleted files
         for path in self.files.keys() {
-            if !other.files.contains_key(path) {
+            if !new_files.contains_key(&*comparable(path)) {
                 deleted.push(path.clone());
             }
         }
@@ -241,10 +506,85 @@ impl Manifest {
             deleted,
         }
     }
+
+    /// Compare `base_dir`, walked with `filter`, against this manifest by
+    /// size and modification time only, without reading any file
+    ///
+    /// Meant for checks run often, such as from cron, that a backup is not
+    /// falling behind; [`Manifest::scan`] and [`Manifest::diff`] also catch
+    /// files changed with their metadata kept. Directories are not listed
+    /// as modified for a newer modification time alone. A change dated
+    /// before the manifest, such as a file restored with its old time, and
+    /// a deleted file, which leaves no time behind, count from when the
+    /// manifest was made.
+    pub fn quick_check<P: AsRef<Path>>(&self, base_dir: P, filter: &PackFilter) -> Result<Drift> {
+        let current = scan_entries(base_dir.as_ref(), filter)?;
+        let known = by_comparable_path(&self.files);
+        let mut seen = HashSet::with_capacity(current.len());
+        let mut diff = ManifestDiff {
+            added: Vec::new(),
+            modified: Vec::new(),
+            deleted: Vec::new(),
+        };
+        let mut oldest_change: Option<i64> = None;
+        let mut changed_at = |mtime: i64| {
+            let at = mtime.max(self.created);
+            oldest_change = Some(oldest_change.map_or(at, |oldest| oldest.min(at)));
+        };
+
+        for entry in current {
+            let key = comparable(&entry.path).into_owned();
+            match known.get(&*key) {
+                Some(old) if !old.metadata_differs(&entry) => {}
+                Some(_) => {
+                    changed_at(entry.mtime);
+                    diff.modified.push(entry.path);
+                }
+                None => {
+                    changed_at(entry.mtime);
+                    diff.added.push(entry.path);
+                }
+            }
+            seen.insert(key);
+        }
+        for (key, old) in &known {
+            if !seen.contains(&**key) {
+                changed_at(self.created);
+                diff.deleted.push(old.path.clone());
+            }
+        }
+        diff.added.sort();
+        diff.modified.sort();
+        diff.deleted.sort();
+
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs() as i64;
+        Ok(Drift {
+            diff,
+            drift_secs: oldest_change.map_or(0, |oldest| now.saturating_sub(oldest).max(0) as u64),
+            manifest_age_secs: now.saturating_sub(self.created).max(0) as u64,
+        })
+    }
+}
+
+/// How far a directory has drifted from a manifest of it, judged by size
+/// and modification time alone; see [`Manifest::quick_check`]
+#[derive(Debug, Clone, Serialize)]
+pub struct Drift {
+    /// Paths added, modified and deleted since the manifest
+    #[serde(flatten)]
+    pub diff: ManifestDiff,
+    /// Seconds since the oldest change the manifest does not have, or 0
+    /// when there is none
+    pub drift_secs: u64,
+    /// Seconds since the manifest was made
+    pub manifest_age_secs: u64,
 }
 
 /// Differences between two manifests
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ManifestDiff {
     /// Files added in the new manifest
     pub added: Vec<PathBuf>,
@@ -266,11 +606,103 @@ impl ManifestDiff {
     }
 }
 
-/// Compute Blake3 hash of a file
-fn compute_file_hash<P: AsRef<Path>>(path: P) -> Result<String> {
-    let mut file = File::open(path)?;
-    let mut hasher = Hasher::new();
-    let mut buffer = vec![0u8; 8192];
+/// Entries for everything below `base_dir` that `filter` keeps, with the
+/// hashes of regular files still to be filled in
+fn scan_entries(base_dir: &Path, filter: &PackFilter) -> Result<Vec<FileEntry>> {
+    let mut paths = Vec::new();
+    for entry in WalkDir::new(base_dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| !filter.excludes_entry(base_dir, e))
+        .filter_map(|e| e.ok())
+    {
+        // Sockets, devices and FIFOs are not packed, so not listed
+        let file_type = entry.file_type();
+        if !(file_type.is_file() || file_type.is_dir() || file_type.is_symlink()) {
+            continue;
+        }
+        let relative_path = entry
+            .path()
+            .strip_prefix(base_dir)
+            .map_err(|_| Error::InvalidPath("Failed to compute relative path".to_string()))?;
+
+        // Skip empty relative paths (the base directory itself)
+        if !relative_path.as_os_str().is_empty() {
+            paths.push(relative_path.to_path_buf());
+        }
+    }
+
+    paths
+        .into_par_iter()
+        .map(|relative_path| scan_entry(base_dir, relative_path))
+        .collect()
+}
+
+/// `files` keyed by their paths in the form used for comparison
+fn by_comparable_path(files: &HashMap<PathBuf, FileEntry>) -> HashMap<Cow<'_, Path>, &FileEntry> {
+    files
+        .iter()
+        .map(|(path, entry)| (comparable(path), entry))
+        .collect()
+}
+
+/// `path` without a leading `./` or trailing separator, as given on a
+/// command line
+fn trim_path(path: &Path) -> &Path {
+    let path = path.strip_prefix(".").unwrap_or(path);
+    // Components drop a trailing separator
+    path.components().as_path()
+}
+
+/// Entry for `relative_path` below `base_dir`, with the hash of a regular
+/// file still to be filled in
+fn scan_entry(base_dir: &Path, relative_path: PathBuf) -> Result<FileEntry> {
+    let path = base_dir.join(&relative_path);
+    let metadata = fs::symlink_metadata(&path)?;
+    let is_symlink = metadata.is_symlink();
+    let is_dir = metadata.is_dir();
+    let link_target = if is_symlink {
+        Some(fs::read_link(&path)?)
+    } else {
+        None
+    };
+
+    Ok(FileEntry {
+        path: relative_path,
+        size: if is_dir || is_symlink {
+            0
+        } else {
+            metadata.len()
+        },
+        hash: String::new(),
+        mtime: metadata
+            .modified()
+            .map(|t| {
+                t.duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs() as i64
+            })
+            .unwrap_or(0),
+        mode: get_file_mode(&metadata),
+        inode: get_inode(&metadata),
+        ctime: get_ctime(&metadata),
+        is_dir,
+        is_symlink,
+        link_target,
+        tags: BTreeMap::new(),
+        note: None,
+    })
+}
+
+/// Hash a file with `algorithm`, reporting the bytes read to `progress`
+fn compute_file_hash(
+    path: &Path,
+    algorithm: HashAlgorithm,
+    progress: &dyn ProgressSink,
+) -> Result<String> {
+    let mut file = ProgressRead::new(File::open(path)?, progress);
+    let mut hasher = algorithm.hasher();
+    let mut buffer = BufferPool::global().get();
 
     loop {
         let n = file.read(&mut buffer)?;
@@ -280,7 +712,7 @@ fn compute_file_hash<P: AsRef<Path>>(path: P) -> Result<String> {
         hasher.update(&buffer[..n]);
     }
 
-    Ok(hasher.finalize().to_hex().to_string())
+    Ok(hasher.hex())
 }
 
 /// Get file mode (Unix permissions)
@@ -295,6 +727,30 @@ fn get_file_mode(_metadata: &std::fs::Metadata) -> Option<u32> {
     None
 }
 
+/// Get the inode number
+#[cfg(unix)]
+fn get_inode(metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn get_inode(_metadata: &std::fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// Get the status change time (Unix timestamp)
+#[cfg(unix)]
+fn get_ctime(metadata: &std::fs::Metadata) -> Option<i64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ctime())
+}
+
+#[cfg(not(unix))]
+fn get_ctime(_metadata: &std::fs::Metadata) -> Option<i64> {
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -342,4 +798,159 @@ mod tests {
         assert_eq!(diff.modified.len(), 1);
         assert_eq!(diff.deleted.len(), 0);
     }
+
+    #[test]
+    fn test_scan_reuses_hashes_of_unchanged_files() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        struct Hashed(AtomicU64);
+        impl ProgressSink for Hashed {
+            fn advance(&self, bytes: u64) {
+                self.0.fetch_add(bytes, Ordering::Relaxed);
+            }
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("same.txt"), "unchanged").unwrap();
+        fs::write(temp_dir.path().join("grown.txt"), "short").unwrap();
+        let previous = Manifest::from_directory(temp_dir.path()).unwrap();
+
+        fs::write(temp_dir.path().join("grown.txt"), "much longer").unwrap();
+        let hashed = Hashed(AtomicU64::new(0));
+        let current = Manifest::scan(
+            temp_dir.path(),
+            &PackFilter::default(),
+            Some(&previous),
+            &hashed,
+        )
+        .unwrap();
+
+        // Only the file whose size changed was read again
+        assert_eq!(hashed.0.load(Ordering::Relaxed), "much longer".len() as u64);
+        let diff = previous.diff(&current);
+        assert_eq!(diff.modified, [PathBuf::from("grown.txt")]);
+        assert_eq!(
+            current.files[Path::new("same.txt")].hash,
+            previous.files[Path::new("same.txt")].hash
+        );
+        assert_eq!(current.file_count, 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_sees_replaced_files_with_restored_mtime() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("report.txt");
+        fs::write(&file, "draft 1").unwrap();
+        let previous = Manifest::from_directory(temp_dir.path()).unwrap();
+        let mtime = filetime::FileTime::from_last_modification_time(&fs::metadata(&file).unwrap());
+
+        // Replaced by a file of the same size, its time set back as a
+        // restore would
+        let replacement = temp_dir.path().join("replacement.tmp");
+        fs::write(&replacement, "draft 2").unwrap();
+        filetime::set_file_mtime(&replacement, mtime).unwrap();
+        fs::rename(&replacement, &file).unwrap();
+
+        let current = Manifest::scan(
+            temp_dir.path(),
+            &PackFilter::default(),
+            Some(&previous),
+            &NoProgress,
+        )
+        .unwrap();
+        assert_eq!(
+            previous.diff(&current).modified,
+            [PathBuf::from("report.txt")]
+        );
+    }
+
+    #[test]
+    fn test_encrypted_manifest() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source");
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("secret-plans.txt"), "content").unwrap();
+        let manifest = Manifest::from_directory(&source).unwrap();
+
+        let path = temp_dir.path().join("backup.manifest.json");
+        manifest.save_with_password(&path, Some("hunter2")).unwrap();
+        assert!(Manifest::is_encrypted(&path).unwrap());
+        let saved = fs::read(&path).unwrap();
+        assert!(!saved.windows(12).any(|w| w == b"secret-plans"));
+
+        let error = Manifest::load(&path).unwrap_err();
+        assert_eq!(error.code(), crate::ErrorCode::PasswordRequired);
+        let error = Manifest::load_with_password(&path, Some("guess")).unwrap_err();
+        assert_eq!(error.code(), crate::ErrorCode::InvalidPassword);
+
+        let loaded = Manifest::load_with_password(&path, Some("hunter2")).unwrap();
+        assert!(loaded.files.contains_key(Path::new("secret-plans.txt")));
+
+        // A plain manifest loads whether or not a password is given
+        manifest.save(&path).unwrap();
+        assert!(!Manifest::is_encrypted(&path).unwrap());
+        assert!(Manifest::load_with_password(&path, Some("hunter2")).is_ok());
+    }
+
+    #[test]
+    fn test_tags_and_notes() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("taxes")).unwrap();
+        fs::write(temp_dir.path().join("taxes/2024.pdf"), "return").unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "notes").unwrap();
+        let mut manifest = Manifest::from_directory(temp_dir.path()).unwrap();
+
+        manifest
+            .set_tag(Path::new("./taxes/2024.pdf"), "retention", "7y")
+            .unwrap();
+        manifest
+            .set_note(Path::new("taxes/"), Some("Filed in April".to_string()))
+            .unwrap();
+        assert!(manifest
+            .set_tag(Path::new("missing.txt"), "retention", "1y")
+            .is_err());
+        assert!(manifest
+            .set_tag(Path::new("notes.txt"), "a=b", "c")
+            .is_err());
+
+        let path = temp_dir.path().join("backup.manifest.json");
+        manifest.save(&path).unwrap();
+        let mut loaded = Manifest::load(&path).unwrap();
+        assert_eq!(
+            loaded.tagged("retention", Some("7y")),
+            [Path::new("taxes/2024.pdf")]
+        );
+        assert!(loaded.tagged("retention", Some("1y")).is_empty());
+        assert_eq!(
+            loaded.entry(Path::new("taxes")).unwrap().note.as_deref(),
+            Some("Filed in April")
+        );
+
+        assert!(loaded
+            .remove_tag(Path::new("taxes/2024.pdf"), "retention")
+            .unwrap());
+        assert!(loaded.tagged("retention", None).is_empty());
+    }
+
+    #[test]
+    fn test_annotations_carry_over() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("kept.txt"), "v1").unwrap();
+        fs::write(temp_dir.path().join("gone.txt"), "gone").unwrap();
+        let mut previous = Manifest::from_directory(temp_dir.path()).unwrap();
+        previous
+            .set_tag(Path::new("kept.txt"), "owner", "finance")
+            .unwrap();
+        previous
+            .set_tag(Path::new("gone.txt"), "owner", "legal")
+            .unwrap();
+
+        fs::write(temp_dir.path().join("kept.txt"), "v2").unwrap();
+        fs::remove_file(temp_dir.path().join("gone.txt")).unwrap();
+        let mut current = Manifest::from_directory(temp_dir.path()).unwrap();
+        current.keep_annotations(&previous);
+
+        assert_eq!(current.tagged("owner", None), [Path::new("kept.txt")]);
+    }
 }
@@ -0,0 +1,325 @@
+//! Pack and extract jobs shared by the command line and the GUI
+//!
+//! A job holds what one pack or extraction needs, and running it takes
+//! care of the steps each front end used to repeat: picking the format,
+//! laying out inputs from several folders, listing entries, asking for
+//! passwords and hoisting a single top folder. Progress and cancellation go
+//! through the [`ProgressSink`] the job is run with, so a front end only
+//! decides how to show them.
+
+use crate::archive::detect::output_format;
+use crate::archive::extractor::{ArchiveEntry, ExtractEntryOptions};
+use crate::archive::resume::{self, Prior, Resume};
+use crate::archive::{
+    create_secure_extractor, pack_roots, pack_with_progress, top_directory, OperationOutcome,
+    PackOptions, PackRoot, SkipReason,
+};
+use crate::durability::{Durability, Syncer};
+use crate::progress::ProgressSink;
+use crate::{Error, ErrorCode, Result};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Packing of one or more inputs into an archive
+#[derive(Default)]
+pub struct PackJob {
+    /// Files and directories to pack
+    pub inputs: Vec<PathBuf>,
+    /// Inputs with the names to pack them under, as from a
+    /// [file list](crate::archive::file_list); packed instead of `inputs`
+    /// when there are any
+    pub roots: Vec<PackRoot>,
+    /// Archive to write
+    pub output: PathBuf,
+    /// Format to write, such as `tar.zst` or `zip`; `None` for the one
+    /// `output` is named for
+    pub format: Option<String>,
+    pub options: PackOptions,
+    /// Whether the finished archive is synced to disk; `per-file` and
+    /// `final` both sync it once written
+    pub durability: Durability,
+}
+
+impl PackJob {
+    /// Pack `inputs` into `output` with `options`, in the format `output`
+    /// is named for
+    pub fn new(inputs: Vec<PathBuf>, output: impl Into<PathBuf>, options: PackOptions) -> Self {
+        Self {
+            inputs,
+            roots: Vec::new(),
+            output: output.into(),
+            format: None,
+            options,
+            durability: Durability::default(),
+        }
+    }
+
+    /// Write the archive, reporting progress to `progress`
+    ///
+    /// A single input is packed as by [`pack_with_progress`], under its own
+    /// name. Several are named by their paths below the folder they share,
+    /// as by [`PackRoot::below_common_base`], and need a format other than
+    /// 7z, as do `roots`. Files that cannot be read are listed in the
+    /// outcome. When the
+    /// job fails or is cancelled, an archive it created is removed rather
+    /// than left half written.
+    pub fn run(self, progress: &dyn ProgressSink) -> Result<OperationOutcome> {
+        let existed = self.output.exists();
+        let output = self.output.clone();
+        let durability = self.durability;
+        let result = self
+            .pack(progress)
+            .and_then(|outcome| durability.sync_output(&output).map(|()| outcome));
+        if result.is_err() && !existed && output.is_file() {
+            let _ = fs::remove_file(&output);
+        }
+        result
+    }
+
+    fn pack(self, progress: &dyn ProgressSink) -> Result<OperationOutcome> {
+        let Self {
+            inputs,
+            roots,
+            output,
+            format,
+            options,
+            ..
+        } = self;
+        let roots = match inputs.as_slice() {
+            _ if !roots.is_empty() => roots,
+            [] => {
+                return Err(Error::InvalidArgument(
+                    "No input files provided".to_string(),
+                ))
+            }
+            [input] => {
+                return pack_with_progress(input, &output, format.as_deref(), options, progress)
+            }
+            inputs => PackRoot::below_common_base(inputs),
+        };
+
+        let format = match format.as_deref() {
+            Some(format) => format,
+            None => output_format(&output).ok_or_else(|| {
+                Error::UnsupportedFormat(format!(
+                    "{} is not named for an archive format",
+                    output.display()
+                ))
+            })?,
+        };
+        if format == "7z" {
+            return Err(Error::UnsupportedOperation(
+                "7z archives can only be packed from a single input".to_string(),
+            ));
+        }
+        info!(
+            "Packing {} inputs into {:?} as {}",
+            roots.len(),
+            output,
+            format
+        );
+        pack_roots(&roots, &output, format, &options, progress)
+    }
+}
+
+/// Extraction of an archive, or some of its entries, one entry at a time
+///
+/// Unlike [`crate::archive::extract_with_options`], each entry is written
+/// on its own, so encrypted entries can be retried with a password and
+/// progress is reported entry by entry.
+#[derive(Debug, Default)]
+pub struct ExtractJob {
+    /// Archive to extract
+    pub archive: PathBuf,
+    /// Directory to extract into, created if needed
+    pub output_dir: PathBuf,
+    /// Entries to extract, as the archive's extractor lists them; `None`
+    /// for all of them
+    pub entries: Option<Vec<ArchiveEntry>>,
+    /// How entries are written; without `overwrite`, those already on
+    /// disk are skipped
+    pub options: ExtractEntryOptions,
+    /// Write the contents of a single top folder straight into
+    /// `output_dir`, stripping the folder from their names as
+    /// [`ExtractOptions::hoist`](crate::archive::ExtractOptions::hoist) does
+    pub hoist: bool,
+    /// When extracted files are synced to disk
+    pub durability: Durability,
+    /// Keep a resume journal, and skip the entries finished by an earlier
+    /// run of the same job that was interrupted; see
+    /// [`resume`](crate::archive::resume)
+    pub resume: bool,
+}
+
+impl ExtractJob {
+    /// Extract all of `archive` into `output_dir` with `options`
+    pub fn new(
+        archive: impl Into<PathBuf>,
+        output_dir: impl Into<PathBuf>,
+        options: ExtractEntryOptions,
+    ) -> Self {
+        Self {
+            archive: archive.into(),
+            output_dir: output_dir.into(),
+            options,
+            ..Self::default()
+        }
+    }
+
+    /// Extract the entries, reporting progress to `progress`
+    ///
+    /// `password` is asked for the password of an encrypted entry, with
+    /// whether a given one was wrong, until it works; once it returns
+    /// `None`, the job fails with the entry's password error. The password
+    /// that worked is used for the entries after it. Entries that cannot be
+    /// written, and devices and FIFOs, which are not, are listed in the
+    /// outcome and the rest still extracted;
+    /// cancelling through `progress` stops the job with an I/O error.
+    pub fn run(
+        self,
+        progress: &dyn ProgressSink,
+        mut password: impl FnMut(&Path, bool) -> Option<String>,
+    ) -> Result<OperationOutcome> {
+        let Self {
+            archive,
+            output_dir,
+            entries,
+            mut options,
+            hoist,
+            durability,
+            resume,
+        } = self;
+
+        // The path checks of the secure extractor need an absolute directory
+        fs::create_dir_all(&output_dir).map_err(|e| Error::from(e).with_path(&output_dir))?;
+        let output_dir = output_dir.canonicalize()?;
+        let extractor = create_secure_extractor(&archive)?;
+        let entries = match entries {
+            Some(entries) => entries,
+            None => extractor
+                .entries(&archive)?
+                .collect::<Result<Vec<_>>>()
+                .map_err(|e| e.with_path(&archive))?,
+        };
+
+        progress.entries(entries.len());
+        progress.start(
+            entries
+                .iter()
+                .filter(|entry| !entry.is_dir && !entry.is_symlink)
+                .map(|entry| entry.size)
+                .sum(),
+        );
+
+        let strip = options.strip_components.unwrap_or(0);
+        let hoisted = hoist
+            .then(|| {
+                top_directory(
+                    entries
+                        .iter()
+                        .map(|entry| (entry.path.as_path(), entry.is_dir)),
+                    strip,
+                )
+            })
+            .flatten();
+        if let Some(top) = &hoisted {
+            info!("Hoisting the contents of {:?}", top);
+            options.strip_components = Some(strip + 1);
+        }
+
+        let mut outcome = OperationOutcome::default();
+        let mut syncer = Syncer::new(durability);
+        let mut resume = resume
+            .then(|| Resume::open(&archive, &output_dir))
+            .transpose()?;
+        for entry in &entries {
+            if progress.is_cancelled() {
+                return Err(io::Error::other("Operation cancelled").into());
+            }
+            let dest = match options.output_path(&output_dir, &entry.path) {
+                Ok(Some(dest)) => dest,
+                Ok(None) => {
+                    // The folder hoisted out of is not an entry left out
+                    if !hoisted
+                        .as_ref()
+                        .is_some_and(|top| entry.path.ends_with(top))
+                    {
+                        outcome.skip(&entry.path, SkipReason::Stripped);
+                    }
+                    continue;
+                }
+                Err(e) => {
+                    outcome.fail(&entry.path, e);
+                    continue;
+                }
+            };
+            let prior = if entry.is_dir {
+                Prior::Unknown
+            } else {
+                resume::prior(resume.as_ref(), &dest)
+            };
+            if prior == Prior::Done {
+                outcome.skip(&entry.path, SkipReason::Resumed);
+                continue;
+            }
+            if !options.overwrite && prior != Prior::Leftover && dest.exists() {
+                outcome.skip(&entry.path, SkipReason::Exists);
+                continue;
+            }
+            progress.file(&entry.path);
+
+            loop {
+                let mut entry_options = options.clone();
+                entry_options.overwrite |= prior == Prior::Leftover;
+                match extractor.extract_entry_with_progress(
+                    &archive,
+                    entry,
+                    &output_dir,
+                    entry_options,
+                    progress,
+                ) {
+                    Ok(()) => match syncer.written(&dest) {
+                        Ok(()) => {
+                            resume::done(resume.as_mut(), &dest);
+                            outcome.done(&entry.path)
+                        }
+                        Err(e) => outcome.fail(&entry.path, e),
+                    },
+                    Err(e)
+                        if matches!(
+                            e.code(),
+                            ErrorCode::PasswordRequired | ErrorCode::InvalidPassword
+                        ) =>
+                    {
+                        let retry = e.code() == ErrorCode::InvalidPassword;
+                        info!(path = %entry.path.display(), retry, "Entry is encrypted, asking for password");
+                        match password(&entry.path, retry) {
+                            Some(given) => {
+                                options.password = Some(given);
+                                continue;
+                            }
+                            None => return Err(e),
+                        }
+                    }
+                    // A device or FIFO, which is not written
+                    Err(e) if e.code() == ErrorCode::UnsupportedOperation => {
+                        warn!(path = %entry.path.display(), "Skipping: {}", e);
+                        outcome.skip(&entry.path, SkipReason::Unsupported);
+                    }
+                    Err(e) => outcome.fail(&entry.path, e),
+                }
+                break;
+            }
+        }
+
+        syncer.finish()?;
+        if let Some(resume) = resume {
+            resume.finish(!outcome.failed.is_empty())?;
+        }
+
+        info!("Extracted {:?}: {}", archive, outcome);
+        Ok(outcome.with_path(&archive))
+    }
+}
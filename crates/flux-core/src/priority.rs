@@ -0,0 +1,162 @@
+//! Lowering the CPU and I/O priority of the running process
+//!
+//! Threads inherit the priority of the thread that creates them, and on
+//! Linux niceness and I/O class are per thread, so [`lower_priority`] has to
+//! run on the main thread before any worker pool is started.
+//!
+//! A program running several jobs at once, like the GUI, lowers only the
+//! thread of one job with [`lower_thread_priority`] instead.
+
+use crate::{Error, Result};
+use tracing::debug;
+
+/// Niceness added by a plain `--nice`, as with nice(1)
+pub const DEFAULT_NICE: u8 = 10;
+
+/// How far to step back for other programs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priority {
+    /// Add this much to the niceness, from 1 to 19
+    Nice(u8),
+    /// Lowest CPU priority, plus the idle I/O class on Linux and background
+    /// mode on Windows, so disk access only happens when nothing else needs
+    /// the disk
+    Background,
+}
+
+/// Lower the priority of the calling thread and every thread it starts
+/// afterwards
+///
+/// As with nice(1), the increment is added to the current niceness, so a
+/// process started under `nice` ends up lower still.
+pub fn lower_priority(priority: Priority) -> Result<()> {
+    check(priority)?;
+    debug!("Lowering process priority: {:?}", priority);
+    apply(priority)
+}
+
+/// Lower the priority of the calling thread alone, leaving the rest of the
+/// process as it is
+///
+/// Supported on Linux, where threads it starts afterwards inherit it, and
+/// on Windows, where background mode also lowers the thread's I/O priority.
+/// Elsewhere priority belongs to the whole process and this fails.
+pub fn lower_thread_priority(priority: Priority) -> Result<()> {
+    check(priority)?;
+    debug!("Lowering thread priority: {:?}", priority);
+    apply_to_thread(priority)
+}
+
+fn check(priority: Priority) -> Result<()> {
+    if let Priority::Nice(increment) = priority {
+        if !(1..=19).contains(&increment) {
+            return Err(Error::Other(format!(
+                "Nice level must be between 1 and 19, got {}",
+                increment
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn apply_to_thread(priority: Priority) -> Result<()> {
+    apply(priority)
+}
+
+#[cfg(windows)]
+fn apply_to_thread(priority: Priority) -> Result<()> {
+    use winapi::um::processthreadsapi::{GetCurrentThread, SetThreadPriority};
+    use winapi::um::winbase::{
+        THREAD_MODE_BACKGROUND_BEGIN, THREAD_PRIORITY_BELOW_NORMAL, THREAD_PRIORITY_LOWEST,
+    };
+
+    let level = match priority {
+        Priority::Nice(increment) if increment < DEFAULT_NICE => THREAD_PRIORITY_BELOW_NORMAL,
+        Priority::Nice(_) => THREAD_PRIORITY_LOWEST,
+        Priority::Background => THREAD_MODE_BACKGROUND_BEGIN,
+    };
+    if unsafe { SetThreadPriority(GetCurrentThread(), level as i32) } == 0 {
+        return Err(Error::Io(std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn apply_to_thread(_priority: Priority) -> Result<()> {
+    Err(Error::UnsupportedOperation(
+        "Changing the priority of one thread".to_string(),
+    ))
+}
+
+#[cfg(unix)]
+fn apply(priority: Priority) -> Result<()> {
+    let increment = match priority {
+        Priority::Nice(increment) => increment,
+        // The kernel caps niceness at 19
+        Priority::Background => 19,
+    };
+    // -1 is also a valid new niceness, so only errno tells a failure apart
+    let result = unsafe { libc::nice(i32::from(increment)) };
+    if result == -1 {
+        let error = std::io::Error::last_os_error();
+        if error.raw_os_error() == Some(libc::EPERM) {
+            return Err(Error::Io(error));
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    if priority == Priority::Background {
+        set_idle_io_class()?;
+    }
+
+    Ok(())
+}
+
+/// Put the calling thread in the idle I/O scheduling class
+#[cfg(target_os = "linux")]
+fn set_idle_io_class() -> Result<()> {
+    // From linux/ioprio.h, which libc does not wrap
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_IDLE: libc::c_int = 3;
+    const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_ioprio_set,
+            IOPRIO_WHO_PROCESS,
+            0,
+            IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT,
+        )
+    };
+    if result != 0 {
+        return Err(Error::Io(std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn apply(priority: Priority) -> Result<()> {
+    use winapi::um::processthreadsapi::{GetCurrentProcess, SetPriorityClass};
+    use winapi::um::winbase::{
+        BELOW_NORMAL_PRIORITY_CLASS, IDLE_PRIORITY_CLASS, PROCESS_MODE_BACKGROUND_BEGIN,
+    };
+
+    let class = match priority {
+        Priority::Nice(increment) if increment < DEFAULT_NICE => BELOW_NORMAL_PRIORITY_CLASS,
+        Priority::Nice(_) => IDLE_PRIORITY_CLASS,
+        // Also lowers I/O and memory priority
+        Priority::Background => PROCESS_MODE_BACKGROUND_BEGIN,
+    };
+    if unsafe { SetPriorityClass(GetCurrentProcess(), class) } == 0 {
+        return Err(Error::Io(std::io::Error::last_os_error()));
+    }
+    Ok(())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn apply(_priority: Priority) -> Result<()> {
+    Err(Error::UnsupportedOperation(
+        "Changing process priority".to_string(),
+    ))
+}
@@ -1,6 +1,8 @@
 //! Progress reporting module
 
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -136,6 +138,112 @@ impl Drop for ProgressReporter {
     }
 }
 
+/// Receiver of byte-level progress from [`crate::archive::pack_multiple`]
+/// and [`crate::archive::extractor::Extractor::extract_entry_with_progress`]
+///
+/// Every method has an empty default, so a sink only implements what it
+/// shows. Calls come from the thread doing the work, between chunks of
+/// file data, so a sink that blocks holds the operation there.
+pub trait ProgressSink: Send + Sync {
+    /// Extraction of `count` entries starts, ahead of [`ProgressSink::start`]
+    fn entries(&self, _count: usize) {}
+
+    /// Packing or extraction starts; `total` bytes of file data will be
+    /// read
+    fn start(&self, _total: u64) {}
+
+    /// Reading of `path` starts
+    fn file(&self, _path: &Path) {}
+
+    /// `bytes` more file data were read and passed to the compressor
+    fn advance(&self, _bytes: u64) {}
+
+    /// The archive has grown to `bytes`
+    fn written(&self, _bytes: u64) {}
+
+    /// Whether the operation should stop
+    ///
+    /// Checked before every chunk; a stop fails the operation with an
+    /// I/O error.
+    fn is_cancelled(&self) -> bool {
+        false
+    }
+}
+
+/// Sink that ignores all progress
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {}
+
+/// Reader that reports what passes through it to a sink
+pub(crate) struct ProgressRead<'a, R> {
+    inner: R,
+    sink: &'a dyn ProgressSink,
+}
+
+impl<'a, R> ProgressRead<'a, R> {
+    pub(crate) fn new(inner: R, sink: &'a dyn ProgressSink) -> Self {
+        Self { inner, sink }
+    }
+}
+
+impl<R: Read> Read for ProgressRead<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.sink.is_cancelled() {
+            // Not `Interrupted`, which `io::copy` would retry forever
+            return Err(io::Error::other("Operation cancelled"));
+        }
+        let n = self.inner.read(buf)?;
+        self.sink.advance(n as u64);
+        Ok(n)
+    }
+}
+
+/// Writer that reports the size of what was written to a sink
+///
+/// Zip writers seek back to fill in headers, so the size is the furthest
+/// position reached rather than the sum of all writes.
+pub(crate) struct ProgressWrite<'a, W> {
+    inner: W,
+    position: u64,
+    size: u64,
+    sink: &'a dyn ProgressSink,
+}
+
+impl<'a, W> ProgressWrite<'a, W> {
+    pub(crate) fn new(inner: W, sink: &'a dyn ProgressSink) -> Self {
+        Self {
+            inner,
+            position: 0,
+            size: 0,
+            sink,
+        }
+    }
+}
+
+impl<W: Write> Write for ProgressWrite<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.position += n as u64;
+        if self.position > self.size {
+            self.size = self.position;
+            self.sink.written(self.size);
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<W: Seek> Seek for ProgressWrite<'_, W> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+}
+
 /// Simple progress callback for operations
 pub trait ProgressCallback: Send + Sync {
     /// Called when progress is made
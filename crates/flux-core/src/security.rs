@@ -1,6 +1,7 @@
 //! Security utilities for safe archive operations
 
 use crate::{Error, Result};
+use std::fs;
 use std::path::{Component, Path, PathBuf};
 use tracing::{error, warn};
 
@@ -10,6 +11,9 @@ pub const DEFAULT_MAX_EXTRACTION_SIZE: u64 = 10 * 1024 * 1024 * 1024;
 /// Maximum compression ratio to detect potential zip bombs
 pub const DEFAULT_MAX_COMPRESSION_RATIO: f64 = 100.0;
 
+/// Maximum number of entries extracted from a single archive
+pub const DEFAULT_MAX_ENTRIES: u64 = 1_000_000;
+
 /// Security options for archive operations
 #[derive(Debug, Clone)]
 pub struct SecurityOptions {
@@ -17,6 +21,8 @@ pub struct SecurityOptions {
     pub max_extraction_size: u64,
     /// Maximum compression ratio allowed (uncompressed/compressed)
     pub max_compression_ratio: f64,
+    /// Maximum number of entries allowed in one archive
+    pub max_entries: u64,
     /// Whether to allow symlinks that point outside the extraction directory
     pub allow_external_symlinks: bool,
     /// Whether to check available disk space before extraction
@@ -28,6 +34,7 @@ impl Default for SecurityOptions {
         Self {
             max_extraction_size: DEFAULT_MAX_EXTRACTION_SIZE,
             max_compression_ratio: DEFAULT_MAX_COMPRESSION_RATIO,
+            max_entries: DEFAULT_MAX_ENTRIES,
             allow_external_symlinks: false,
             check_disk_space: true,
         }
@@ -74,21 +81,46 @@ pub fn sanitize_path(base: &Path, untrusted: &Path) -> Result<PathBuf> {
         }
     }
 
-    // Verify the final path is still within the base directory
-    let canonical_base = base
-        .canonicalize()
-        .map_err(|e| Error::InvalidPath(format!("Cannot canonicalize base path: {}", e)))?;
+    // Only normal components were added, so the result is under `base` as
+    // written, whether or not that is relative or exists yet
+    Ok(result)
+}
 
-    // Check if result starts with base (without canonicalizing result since it may not exist yet)
-    if !result.starts_with(&canonical_base) {
-        error!(base = ?base, path = ?untrusted, result = ?result, "Path escapes base directory");
-        return Err(Error::InvalidPath(format!(
-            "Path would escape extraction directory: {:?}",
-            untrusted
-        )));
+/// Where an archive entry named `untrusted` is written under `base`
+///
+/// Like [`sanitize_path`], and also refuses paths that pass through a
+/// symlink already on disk, such as one extracted earlier, that leads out
+/// of `base`.
+pub fn entry_output_path(base: &Path, untrusted: &Path) -> Result<PathBuf> {
+    let path = sanitize_path(base, untrusted)?;
+    let Ok(canonical_base) = base.canonicalize() else {
+        // Nothing below a missing base exists yet either
+        return Ok(path);
+    };
+
+    let mut current = base.to_path_buf();
+    for component in path.strip_prefix(base).unwrap_or(&path).components() {
+        current.push(component);
+        match fs::symlink_metadata(&current) {
+            Ok(metadata) if metadata.file_type().is_symlink() => {
+                // A dangling link would be followed to wherever it points
+                let inside = current
+                    .canonicalize()
+                    .is_ok_and(|resolved| resolved.starts_with(&canonical_base));
+                if !inside {
+                    error!(base = ?base, path = ?untrusted, link = ?current, "Path leaves base directory through a symlink");
+                    return Err(Error::InvalidPath(format!(
+                        "Path would escape extraction directory through a symlink: {:?}",
+                        untrusted
+                    )));
+                }
+            }
+            Ok(_) => {}
+            Err(_) => break,
+        }
     }
 
-    Ok(result)
+    Ok(path)
 }
 
 /// Validate symlink target to prevent escaping extraction directory
@@ -206,92 +238,29 @@ pub fn check_extraction_size(current_total: u64, entry_size: u64, max_size: u64)
     Ok(())
 }
 
-/// Check available disk space
-pub fn check_disk_space(path: &Path, required_bytes: u64) -> Result<()> {
-    #[cfg(unix)]
-    {
-        use std::fs;
-
-        let _metadata = fs::metadata(path).or_else(|_| {
-            // If path doesn't exist, check parent directory
-            path.parent()
-                .ok_or_else(|| Error::InvalidPath("No parent directory".to_string()))
-                .and_then(|p| fs::metadata(p).map_err(Error::Io))
-        })?;
-
-        // Get filesystem statistics
-        let stat = unsafe {
-            let mut stat: libc::statvfs = std::mem::zeroed();
-            let path_cstr = std::ffi::CString::new(path.to_string_lossy().as_bytes())
-                .map_err(|_| Error::InvalidPath("Invalid path for statvfs".to_string()))?;
-
-            if libc::statvfs(path_cstr.as_ptr(), &mut stat) != 0 {
-                return Err(Error::Io(std::io::Error::last_os_error()));
-            }
-            stat
-        };
-
-        let available = stat.f_bavail as u64 * stat.f_frsize as u64;
-        if available < required_bytes {
-            error!(
-                available,
-                required_bytes,
-                path = ?path,
-                "Insufficient disk space"
-            );
-            return Err(Error::SecurityError(format!(
-                "Insufficient disk space: {} bytes available, {} bytes required",
-                available, required_bytes
-            )));
-        }
-    }
-
-    #[cfg(windows)]
-    {
-        use std::ffi::OsStr;
-        use std::os::windows::ffi::OsStrExt;
-        use winapi::um::fileapi::GetDiskFreeSpaceExW;
-        use winapi::um::winnt::ULARGE_INTEGER;
-
-        let path_wide: Vec<u16> = OsStr::new(&path.to_string_lossy())
-            .encode_wide()
-            .chain(std::iter::once(0))
-            .collect();
-
-        let mut available = ULARGE_INTEGER::default();
-        let mut total = ULARGE_INTEGER::default();
-        let mut free = ULARGE_INTEGER::default();
-
-        unsafe {
-            if GetDiskFreeSpaceExW(path_wide.as_ptr(), &mut available, &mut total, &mut free) == 0 {
-                return Err(Error::Io(std::io::Error::last_os_error().into()));
-            }
-        }
-
-        let available_bytes = unsafe { *available.QuadPart() } as u64;
-        if available_bytes < required_bytes {
-            error!(
-                available_bytes,
-                required_bytes,
-                path = ?path,
-                "Insufficient disk space"
-            );
-            return Err(Error::SecurityError(format!(
-                "Insufficient disk space: {} bytes available, {} bytes required",
-                available_bytes, required_bytes
-            )));
-        }
-    }
-
-    #[cfg(not(any(unix, windows)))]
-    {
-        // On other platforms, skip disk space check
-        warn!("Disk space check not implemented for this platform");
+/// Check that extracting one more entry stays within the entry limit
+pub fn check_entry_count(current_count: u64, max_entries: u64) -> Result<()> {
+    if current_count >= max_entries {
+        error!(current_count, max_entries, "Archive has too many entries");
+        return Err(Error::SecurityError(format!(
+            "Archive exceeds maximum of {} entries",
+            max_entries
+        )));
     }
 
     Ok(())
 }
 
+/// Check available disk space
+///
+/// Fails with [`Error::InsufficientSpace`] when the disk holding `path` has
+/// less than `required_bytes` free; see [`crate::space::ensure_space`].
+pub fn check_disk_space(path: &Path, required_bytes: u64) -> Result<()> {
+    crate::space::ensure_space(path, required_bytes).inspect_err(|_| {
+        error!(required_bytes, path = ?path, "Insufficient disk space");
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -322,6 +291,34 @@ mod tests {
         assert!(sanitize_path(base, path).is_err());
     }
 
+    #[test]
+    fn test_sanitize_path_relative_base() {
+        let path = Path::new("subdir/file.txt");
+        let result = sanitize_path(Path::new("out"), path).unwrap();
+        assert_eq!(result, Path::new("out/subdir/file.txt"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_entry_output_path_refuses_escaping_symlinks() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().join("out");
+        let outside = temp_dir.path().join("outside");
+        fs::create_dir_all(base.join("inner")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, base.join("escape")).unwrap();
+        std::os::unix::fs::symlink("inner", base.join("alias")).unwrap();
+        std::os::unix::fs::symlink(outside.join("missing"), base.join("dangling")).unwrap();
+
+        assert!(entry_output_path(&base, Path::new("escape/file.txt")).is_err());
+        assert!(entry_output_path(&base, Path::new("dangling")).is_err());
+        assert_eq!(
+            entry_output_path(&base, Path::new("alias/file.txt")).unwrap(),
+            base.join("alias/file.txt")
+        );
+        assert!(entry_output_path(&base, Path::new("new/file.txt")).is_ok());
+    }
+
     #[test]
     fn test_compression_ratio_normal() {
         assert!(check_compression_ratio(1000, 5000, 100.0).is_ok());
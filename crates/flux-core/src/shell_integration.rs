@@ -0,0 +1,559 @@
+//! File manager context menu integration
+//!
+//! Registers "Extract with Flux" and "Compress with Flux" entries that start
+//! the GUI with [`ShellAction::flag`] followed by the selected paths:
+//!
+//! - Windows: per-user registry verbs under `HKCU\Software\Classes`
+//! - macOS: Finder quick actions in `~/Library/Services`
+//! - Linux: Nautilus scripts and KDE service menu actions in the XDG data
+//!   directory
+//!
+//! Everything is installed for the current user only, so no elevated
+//! privileges are needed.
+
+use crate::{Error, Result};
+use std::path::Path;
+
+/// Archive extensions offered the extract entry where the platform allows
+/// filtering by type
+pub const ARCHIVE_EXTENSIONS: &[&str] = crate::archive::detect::FILE_EXTENSIONS;
+
+/// A context menu entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellAction {
+    /// Extract the selected archives
+    Extract,
+    /// Pack the selected files and folders
+    Compress,
+}
+
+impl ShellAction {
+    /// Every entry that gets registered
+    pub const ALL: [ShellAction; 2] = [ShellAction::Extract, ShellAction::Compress];
+
+    /// Menu label
+    pub fn label(self) -> &'static str {
+        match self {
+            ShellAction::Extract => "Extract with Flux",
+            ShellAction::Compress => "Compress with Flux",
+        }
+    }
+
+    /// Command line flag passed to the GUI before the selected paths
+    pub fn flag(self) -> &'static str {
+        match self {
+            ShellAction::Extract => "--extract",
+            ShellAction::Compress => "--compress",
+        }
+    }
+
+    /// Parse a launch flag produced by [`ShellAction::flag`]
+    pub fn from_flag(flag: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|action| action.flag() == flag)
+    }
+
+    fn id(self) -> &'static str {
+        match self {
+            ShellAction::Extract => "extract",
+            ShellAction::Compress => "compress",
+        }
+    }
+}
+
+/// Register the context menu entries to launch `gui_exe`
+///
+/// Existing entries are replaced. Returns the locations that were written.
+pub fn install(gui_exe: &Path) -> Result<Vec<String>> {
+    if !gui_exe.is_file() {
+        return Err(Error::NotFound(format!(
+            "Flux GUI executable not found at {}",
+            gui_exe.display()
+        )));
+    }
+    let gui_exe = gui_exe.canonicalize()?;
+    platform::install(&gui_exe)
+}
+
+/// Remove the context menu entries
+///
+/// Returns the locations that were removed.
+pub fn uninstall() -> Result<Vec<String>> {
+    platform::uninstall()
+}
+
+/// Whether the context menu entries are registered
+pub fn is_installed() -> bool {
+    platform::is_installed()
+}
+
+/// Quote `value` for a POSIX shell
+#[cfg(unix)]
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use super::{shell_quote, ShellAction, ARCHIVE_EXTENSIONS};
+    use crate::{Error, Result};
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+    use std::path::{Path, PathBuf};
+
+    /// MIME types of the archives handled by the extract action
+    const ARCHIVE_MIME_TYPES: &[&str] = &[
+        "application/zip",
+        "application/x-tar",
+        "application/x-compressed-tar",
+        "application/gzip",
+        "application/zstd",
+        "application/x-zstd-compressed-tar",
+        "application/x-xz",
+        "application/x-xz-compressed-tar",
+        "application/x-brotli",
+        "application/x-7z-compressed",
+    ];
+
+    fn data_dir() -> Result<PathBuf> {
+        dirs::data_dir()
+            .ok_or_else(|| Error::Config("Could not determine the data directory".to_string()))
+    }
+
+    /// Nautilus shows every executable in this directory under "Scripts"
+    fn nautilus_script(data_dir: &Path, action: ShellAction) -> PathBuf {
+        data_dir.join("nautilus/scripts").join(action.label())
+    }
+
+    /// Dolphin and other KDE file managers read service menus from here
+    fn service_menu(data_dir: &Path, action: ShellAction) -> PathBuf {
+        data_dir
+            .join("kio/servicemenus")
+            .join(format!("flux-{}.desktop", action.id()))
+    }
+
+    fn files(data_dir: &Path) -> Vec<PathBuf> {
+        ShellAction::ALL
+            .into_iter()
+            .flat_map(|action| {
+                [
+                    nautilus_script(data_dir, action),
+                    service_menu(data_dir, action),
+                ]
+            })
+            .collect()
+    }
+
+    fn script_contents(gui_exe: &Path, action: ShellAction) -> String {
+        let mut script =
+            "#!/bin/sh\n# Installed by Flux; remove with `flux integrate --uninstall`\n"
+                .to_string();
+        if action == ShellAction::Extract {
+            // Nautilus cannot limit scripts to a file type, so drop anything
+            // that is not an archive from the arguments
+            let patterns = ARCHIVE_EXTENSIONS
+                .iter()
+                .map(|ext| format!("*.{}", ext))
+                .collect::<Vec<_>>()
+                .join("|");
+            script.push_str(&format!(
+                "for f in \"$@\"; do\n    shift\n    case \"$f\" in\n        {}) set -- \"$@\" \"$f\" ;;\n    esac\ndone\n[ $# -gt 0 ] || exit 0\n",
+                patterns
+            ));
+        }
+        script.push_str(&format!(
+            "exec {} {} \"$@\"\n",
+            shell_quote(&gui_exe.to_string_lossy()),
+            action.flag()
+        ));
+        script
+    }
+
+    fn service_menu_contents(gui_exe: &Path, action: ShellAction) -> String {
+        let mime_types = match action {
+            ShellAction::Extract => ARCHIVE_MIME_TYPES.join(";"),
+            ShellAction::Compress => "all/all".to_string(),
+        };
+        let icon = match action {
+            ShellAction::Extract => "archive-extract",
+            ShellAction::Compress => "archive-insert",
+        };
+        // Desktop entry Exec values escape these characters inside quotes
+        let exe = gui_exe
+            .to_string_lossy()
+            .chars()
+            .fold(String::new(), |mut escaped, c| {
+                if matches!(c, '"' | '`' | '$' | '\\') {
+                    escaped.push('\\');
+                }
+                escaped.push(c);
+                escaped
+            });
+
+        format!(
+            "[Desktop Entry]\nType=Service\nMimeType={mime};\nActions={id};\nX-KDE-Priority=TopLevel\n\n\
+             [Desktop Action {id}]\nName={label}\nIcon={icon}\nExec=\"{exe}\" {flag} %F\n",
+            mime = mime_types,
+            id = action.id(),
+            label = action.label(),
+            icon = icon,
+            exe = exe,
+            flag = action.flag(),
+        )
+    }
+
+    fn write_executable(path: &Path, contents: &str) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, contents)?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o755))?;
+        Ok(())
+    }
+
+    pub fn install(gui_exe: &Path) -> Result<Vec<String>> {
+        let data_dir = data_dir()?;
+        let mut written = Vec::new();
+
+        for action in ShellAction::ALL {
+            let script = nautilus_script(&data_dir, action);
+            write_executable(&script, &script_contents(gui_exe, action))?;
+            written.push(script.display().to_string());
+
+            // Recent KDE releases only run service menus marked executable
+            let menu = service_menu(&data_dir, action);
+            write_executable(&menu, &service_menu_contents(gui_exe, action))?;
+            written.push(menu.display().to_string());
+        }
+
+        Ok(written)
+    }
+
+    pub fn uninstall() -> Result<Vec<String>> {
+        let mut removed = Vec::new();
+        for file in files(&data_dir()?) {
+            if file.exists() {
+                fs::remove_file(&file)?;
+                removed.push(file.display().to_string());
+            }
+        }
+        Ok(removed)
+    }
+
+    pub fn is_installed() -> bool {
+        data_dir()
+            .map(|dir| files(&dir).iter().all(|file| file.exists()))
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{shell_quote, ShellAction};
+    use crate::{Error, Result};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    fn services_dir() -> Result<PathBuf> {
+        dirs::home_dir()
+            .map(|home| home.join("Library/Services"))
+            .ok_or_else(|| Error::Config("Could not determine the home directory".to_string()))
+    }
+
+    fn workflow(services_dir: &Path, action: ShellAction) -> PathBuf {
+        services_dir.join(format!("{}.workflow", action.label()))
+    }
+
+    fn xml_escape(value: &str) -> String {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+
+    fn info_plist(action: ShellAction) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>NSServices</key>
+	<array>
+		<dict>
+			<key>NSMenuItem</key>
+			<dict>
+				<key>default</key>
+				<string>{label}</string>
+			</dict>
+			<key>NSMessage</key>
+			<string>runWorkflowAsService</string>
+			<key>NSRequiredContext</key>
+			<dict>
+				<key>NSApplicationIdentifier</key>
+				<string>com.apple.finder</string>
+			</dict>
+			<key>NSSendFileTypes</key>
+			<array>
+				<string>public.item</string>
+			</array>
+		</dict>
+	</array>
+</dict>
+</plist>
+"#,
+            label = action.label()
+        )
+    }
+
+    /// Automator document running a shell script with the selected files
+    fn document(gui_exe: &Path, action: ShellAction) -> String {
+        let command = format!(
+            "exec {} {} \"$@\"",
+            shell_quote(&gui_exe.to_string_lossy()),
+            action.flag()
+        );
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+	<key>AMApplicationBuild</key>
+	<string>521</string>
+	<key>AMApplicationVersion</key>
+	<string>2.10</string>
+	<key>AMDocumentVersion</key>
+	<string>2</string>
+	<key>actions</key>
+	<array>
+		<dict>
+			<key>action</key>
+			<dict>
+				<key>AMAccepts</key>
+				<dict>
+					<key>Container</key>
+					<string>List</string>
+					<key>Optional</key>
+					<true/>
+					<key>Types</key>
+					<array>
+						<string>com.apple.cocoa.path</string>
+					</array>
+				</dict>
+				<key>AMActionVersion</key>
+				<string>2.0.3</string>
+				<key>AMParameterProperties</key>
+				<dict>
+					<key>COMMAND_STRING</key>
+					<dict/>
+					<key>inputMethod</key>
+					<dict/>
+					<key>shell</key>
+					<dict/>
+				</dict>
+				<key>ActionBundlePath</key>
+				<string>/System/Library/Automator/Run Shell Script.action</string>
+				<key>ActionName</key>
+				<string>Run Shell Script</string>
+				<key>ActionParameters</key>
+				<dict>
+					<key>COMMAND_STRING</key>
+					<string>{command}</string>
+					<key>CheckedForUserDefaultShell</key>
+					<true/>
+					<key>inputMethod</key>
+					<integer>1</integer>
+					<key>shell</key>
+					<string>/bin/sh</string>
+					<key>source</key>
+					<string></string>
+				</dict>
+				<key>BundleIdentifier</key>
+				<string>com.apple.RunShellScript</string>
+				<key>CFBundleVersion</key>
+				<string>2.0.3</string>
+				<key>Class Name</key>
+				<string>RunShellScriptAction</string>
+			</dict>
+		</dict>
+	</array>
+	<key>workflowMetaData</key>
+	<dict>
+		<key>serviceInputTypeIdentifier</key>
+		<string>com.apple.Automator.fileSystemObject</string>
+		<key>serviceApplicationBundleID</key>
+		<string>com.apple.finder</string>
+		<key>workflowTypeIdentifier</key>
+		<string>com.apple.Automator.servicesMenu</string>
+	</dict>
+</dict>
+</plist>
+"#,
+            command = xml_escape(&command)
+        )
+    }
+
+    /// Ask macOS to pick up changed services without logging out
+    fn refresh_services() {
+        let _ = std::process::Command::new("/System/Library/CoreServices/pbs")
+            .arg("-update")
+            .status();
+    }
+
+    pub fn install(gui_exe: &Path) -> Result<Vec<String>> {
+        let services_dir = services_dir()?;
+        let mut written = Vec::new();
+
+        for action in ShellAction::ALL {
+            let workflow = workflow(&services_dir, action);
+            let contents = workflow.join("Contents");
+            fs::create_dir_all(&contents)?;
+            fs::write(contents.join("Info.plist"), info_plist(action))?;
+            fs::write(contents.join("document.wflow"), document(gui_exe, action))?;
+            written.push(workflow.display().to_string());
+        }
+
+        refresh_services();
+        Ok(written)
+    }
+
+    pub fn uninstall() -> Result<Vec<String>> {
+        let services_dir = services_dir()?;
+        let mut removed = Vec::new();
+
+        for action in ShellAction::ALL {
+            let workflow = workflow(&services_dir, action);
+            if workflow.exists() {
+                fs::remove_dir_all(&workflow)?;
+                removed.push(workflow.display().to_string());
+            }
+        }
+
+        refresh_services();
+        Ok(removed)
+    }
+
+    pub fn is_installed() -> bool {
+        services_dir()
+            .map(|dir| {
+                ShellAction::ALL
+                    .into_iter()
+                    .all(|action| workflow(&dir, action).exists())
+            })
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(windows)]
+mod platform {
+    use super::{ShellAction, ARCHIVE_EXTENSIONS};
+    use crate::{Error, Result};
+    use std::path::Path;
+    use std::process::Command;
+
+    const CLASSES: &str = r"HKCU\Software\Classes";
+
+    /// Registry keys of the verb for `action`
+    ///
+    /// Compress is offered on folders as well as on files.
+    fn keys(action: ShellAction) -> Vec<String> {
+        let verb = match action {
+            ShellAction::Extract => "FluxExtract",
+            ShellAction::Compress => "FluxCompress",
+        };
+        let mut keys = vec![format!(r"{}\*\shell\{}", CLASSES, verb)];
+        if action == ShellAction::Compress {
+            keys.push(format!(r"{}\Directory\shell\{}", CLASSES, verb));
+        }
+        keys
+    }
+
+    fn reg(args: &[&str]) -> Result<bool> {
+        let output = Command::new("reg").args(args).output()?;
+        Ok(output.status.success())
+    }
+
+    fn reg_add(key: &str, value: Option<&str>, data: &str) -> Result<()> {
+        let mut args = vec!["add", key];
+        match value {
+            Some(name) => args.extend(["/v", name]),
+            None => args.push("/ve"),
+        }
+        args.extend(["/d", data, "/f"]);
+
+        if reg(&args)? {
+            Ok(())
+        } else {
+            Err(Error::Other(format!(
+                "Failed to write registry key {}",
+                key
+            )))
+        }
+    }
+
+    pub fn install(gui_exe: &Path) -> Result<Vec<String>> {
+        let exe = gui_exe.to_string_lossy();
+        let applies_to = ARCHIVE_EXTENSIONS
+            .iter()
+            .map(|ext| format!("System.FileExtension:=.{}", ext))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let mut written = Vec::new();
+
+        for action in ShellAction::ALL {
+            let command = format!("\"{}\" {} \"%1\"", exe, action.flag());
+            for key in keys(action) {
+                reg_add(&key, None, action.label())?;
+                reg_add(&key, Some("Icon"), &exe)?;
+                if action == ShellAction::Extract {
+                    reg_add(&key, Some("AppliesTo"), &applies_to)?;
+                }
+                reg_add(&format!(r"{}\command", key), None, &command)?;
+                written.push(key);
+            }
+        }
+
+        Ok(written)
+    }
+
+    pub fn uninstall() -> Result<Vec<String>> {
+        let mut removed = Vec::new();
+        for key in ShellAction::ALL.into_iter().flat_map(keys) {
+            if reg(&["query", &key])? {
+                if !reg(&["delete", &key, "/f"])? {
+                    return Err(Error::Other(format!(
+                        "Failed to remove registry key {}",
+                        key
+                    )));
+                }
+                removed.push(key);
+            }
+        }
+        Ok(removed)
+    }
+
+    pub fn is_installed() -> bool {
+        ShellAction::ALL
+            .into_iter()
+            .flat_map(keys)
+            .all(|key| reg(&["query", &key]).unwrap_or(false))
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod platform {
+    use crate::{Error, Result};
+    use std::path::Path;
+
+    pub fn install(_gui_exe: &Path) -> Result<Vec<String>> {
+        Err(Error::UnsupportedOperation(
+            "Context menu integration is not available on this platform".to_string(),
+        ))
+    }
+
+    pub fn uninstall() -> Result<Vec<String>> {
+        install(Path::new(""))
+    }
+
+    pub fn is_installed() -> bool {
+        false
+    }
+}
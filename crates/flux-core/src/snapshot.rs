@@ -0,0 +1,309 @@
+//! Snapshot generations of a sync target
+//!
+//! Every sync overwrites its target archive: the first run with a full
+//! backup, later runs with only what changed. To keep what each run backed
+//! up, its archive and manifest are also recorded as a numbered generation
+//! next to the target, named like the links of a cloud backup chain:
+//!
+//! ```text
+//! backups/photos.0001.tar.gz        backups/photos.0001.fluxmanifest
+//! backups/photos.0002.tar.gz        backups/photos.0002.fluxmanifest
+//! ```
+//!
+//! The manifest of a generation describes the whole source directory as it
+//! was at that run, so any snapshot can be browsed in full. Restoring a file
+//! reads it from the newest generation up to the snapshot in which the file
+//! changed, which is the one that packed it.
+
+use crate::archive::{
+    extract_with_options, inspect, EntryFilter, ExtractOptions, OperationOutcome, SkipReason,
+};
+use crate::manifest::{FileEntry, Manifest};
+use crate::{Error, Result};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Extension of the manifest recorded with each generation
+pub const MANIFEST_EXTENSION: &str = "fluxmanifest";
+
+/// One recorded sync of a target
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// Position among the target's snapshots, starting at 1
+    pub sequence: u32,
+    pub archive: PathBuf,
+    pub manifest: PathBuf,
+    /// When the manifest was made, in seconds since the Unix epoch
+    pub created: i64,
+    /// Files in the source directory at the time
+    pub file_count: u32,
+    /// Total size of those files
+    pub total_size: u64,
+    /// Size of the generation's archive on disk
+    pub archive_size: u64,
+    /// Paths added, modified or deleted since the snapshot before; every
+    /// path for the first one
+    pub changes: usize,
+}
+
+/// The generations kept for one sync target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotHistory {
+    /// Directory holding the target
+    dir: PathBuf,
+    /// File name of the target up to its archive extension
+    stem: String,
+    /// Archive extension without the leading dot, such as `tar.gz`
+    extension: String,
+}
+
+impl SnapshotHistory {
+    /// The history of backups into `target`, an archive path such as
+    /// `backups/photos.tar.gz`
+    pub fn new(target: &Path) -> Result<Self> {
+        let name = target
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        let split = name
+            .find(".tar.")
+            .or_else(|| name.rfind('.'))
+            .filter(|&dot| dot > 0 && dot + 1 < name.len());
+        let Some(dot) = split else {
+            return Err(Error::InvalidPath(format!(
+                "{} needs an archive name with an extension, such as photos.tar.gz",
+                target.display()
+            )));
+        };
+
+        Ok(Self {
+            dir: target.parent().unwrap_or(Path::new("")).to_path_buf(),
+            stem: name[..dot].to_string(),
+            extension: name[dot + 1..].to_string(),
+        })
+    }
+
+    /// Archive and manifest of the generation numbered `sequence`
+    pub fn paths(&self, sequence: u32) -> (PathBuf, PathBuf) {
+        (
+            self.file(sequence, &self.extension),
+            self.file(sequence, MANIFEST_EXTENSION),
+        )
+    }
+
+    /// Recorded snapshots, oldest first, reading manifests encrypted with
+    /// `password` if there are any
+    ///
+    /// A generation counts once its manifest exists, so an archive left
+    /// behind by a failed recording is passed over.
+    pub fn snapshots(&self, password: Option<&str>) -> Result<Vec<Snapshot>> {
+        let mut snapshots = Vec::new();
+        let mut previous: Option<Manifest> = None;
+        for sequence in self.sequences(MANIFEST_EXTENSION)? {
+            let (archive, manifest_path) = self.paths(sequence);
+            let manifest = Manifest::load_with_password(&manifest_path, password)?;
+            let changes = match &previous {
+                Some(previous) => previous.diff(&manifest).change_count(),
+                None => manifest.files.len(),
+            };
+            snapshots.push(Snapshot {
+                sequence,
+                archive_size: fs::metadata(&archive).map_or(0, |meta| meta.len()),
+                archive,
+                manifest: manifest_path,
+                created: manifest.created,
+                file_count: manifest.file_count,
+                total_size: manifest.total_size,
+                changes,
+            });
+            previous = Some(manifest);
+        }
+        Ok(snapshots)
+    }
+
+    /// Record a sync that wrote `archive` and `manifest` as the next
+    /// generation, copying both, and return its sequence number
+    ///
+    /// The manifest is copied last, so the generation only counts once both
+    /// are in place.
+    pub fn record(&self, archive: &Path, manifest: &Path) -> Result<u32> {
+        // Numbered past every file of the history, complete or not
+        let sequence = self
+            .sequences(&self.extension)?
+            .into_iter()
+            .chain(self.sequences(MANIFEST_EXTENSION)?)
+            .max()
+            .unwrap_or(0)
+            + 1;
+        let (archive_copy, manifest_copy) = self.paths(sequence);
+        fs::copy(archive, &archive_copy)?;
+        fs::copy(manifest, &manifest_copy)?;
+        info!("Recorded snapshot {} of {:?}", sequence, archive);
+        Ok(sequence)
+    }
+
+    /// Extract `paths` as they were in snapshot `sequence` into
+    /// `output_dir`, overwriting what is there; a directory brings
+    /// everything below it along, and no paths restore the whole snapshot
+    ///
+    /// Entries the snapshot does not need from each generation are left out
+    /// without being listed in the outcome.
+    pub fn restore(
+        &self,
+        sequence: u32,
+        paths: &[PathBuf],
+        output_dir: &Path,
+        password: Option<&str>,
+    ) -> Result<OperationOutcome> {
+        let manifests = self.manifests_up_to(sequence, password)?;
+        let Some((_, snapshot)) = manifests.last() else {
+            return Err(Error::NotFound(format!(
+                "No snapshot {} of {}.{}",
+                sequence, self.stem, self.extension
+            )));
+        };
+
+        let mut wanted: BTreeMap<u32, HashSet<PathBuf>> = BTreeMap::new();
+        for (path, entry) in &snapshot.files {
+            if !paths.is_empty() && !paths.iter().any(|p| path.starts_with(p)) {
+                continue;
+            }
+            let source = source_generation(&manifests, path, entry);
+            wanted.entry(source).or_default().insert(path.clone());
+        }
+
+        let mut outcome = OperationOutcome::default();
+        for (source, wanted) in wanted {
+            let (archive, _) = self.paths(source);
+            info!("Restoring {} paths from snapshot {}", wanted.len(), source);
+            // Full backups store the source directory's name in front of
+            // every path, incremental ones do not
+            let strip = usize::from(
+                !inspect(&archive)?
+                    .iter()
+                    .any(|entry| wanted.contains(&entry.path)),
+            );
+            let filter = EntryFilter::new(move |entry| {
+                let stored: PathBuf = entry.path.components().skip(strip).collect();
+                wanted.contains(&stored)
+            });
+            let options = ExtractOptions {
+                overwrite: true,
+                skip: false,
+                strip_components: Some(strip),
+                entry_filter: Some(filter),
+                ..ExtractOptions::default()
+            };
+            let extracted = extract_with_options(&archive, output_dir, options)?;
+            outcome.done.extend(extracted.done);
+            outcome.skipped.extend(
+                extracted
+                    .skipped
+                    .into_iter()
+                    .filter(|skipped| skipped.reason != SkipReason::Excluded),
+            );
+            outcome.failed.extend(extracted.failed);
+        }
+        Ok(outcome)
+    }
+
+    /// Manifests of the snapshots up to and including `sequence`, oldest
+    /// first, or none if there is no snapshot `sequence`
+    fn manifests_up_to(
+        &self,
+        sequence: u32,
+        password: Option<&str>,
+    ) -> Result<Vec<(u32, Manifest)>> {
+        let sequences = self.sequences(MANIFEST_EXTENSION)?;
+        if !sequences.contains(&sequence) {
+            return Ok(Vec::new());
+        }
+        sequences
+            .into_iter()
+            .take_while(|&s| s <= sequence)
+            .map(|s| Ok((s, Manifest::load_with_password(self.paths(s).1, password)?)))
+            .collect()
+    }
+
+    fn file(&self, sequence: u32, extension: &str) -> PathBuf {
+        self.dir
+            .join(format!("{}.{:04}.{}", self.stem, sequence, extension))
+    }
+
+    /// Sorted sequence numbers of the history's files with `extension`
+    fn sequences(&self, extension: &str) -> Result<Vec<u32>> {
+        let dir = if self.dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            &self.dir
+        };
+        let mut sequences = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let name = entry?.file_name();
+            if let Some(sequence) = name.to_str().and_then(|name| self.parse(name, extension)) {
+                sequences.push(sequence);
+            }
+        }
+        sequences.sort_unstable();
+        Ok(sequences)
+    }
+
+    /// Sequence number of `name` if it is a file of this history with
+    /// `extension`
+    fn parse(&self, name: &str, extension: &str) -> Option<u32> {
+        let rest = name.strip_prefix(&self.stem)?.strip_prefix('.')?;
+        let digits = rest.strip_suffix(extension)?.strip_suffix('.')?;
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        digits.parse().ok()
+    }
+}
+
+/// Sequence of the newest generation in `manifests` that packed `path`, as
+/// `entry` describes it in the last one: the one where it last changed
+fn source_generation(manifests: &[(u32, Manifest)], path: &Path, entry: &FileEntry) -> u32 {
+    for window in manifests.windows(2).rev() {
+        let unchanged = window[0]
+            .1
+            .files
+            .get(path)
+            .is_some_and(|before| before.hash == entry.hash && before.mtime == entry.mtime);
+        if !unchanged {
+            return window[1].0;
+        }
+    }
+    manifests[0].0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generation_names() {
+        let history = SnapshotHistory::new(Path::new("backups/photos.tar.gz")).unwrap();
+        let (archive, manifest) = history.paths(12);
+        assert_eq!(archive, Path::new("backups/photos.0012.tar.gz"));
+        assert_eq!(manifest, Path::new("backups/photos.0012.fluxmanifest"));
+
+        let history = SnapshotHistory::new(Path::new("my.photos.zip")).unwrap();
+        assert_eq!(history.paths(1).0, Path::new("my.photos.0001.zip"));
+        assert!(SnapshotHistory::new(Path::new("backups/photos")).is_err());
+    }
+
+    #[test]
+    fn test_parse_only_matches_the_history() {
+        let history = SnapshotHistory::new(Path::new("photos.tar.gz")).unwrap();
+        assert_eq!(
+            history.parse("photos.0003.fluxmanifest", "fluxmanifest"),
+            Some(3)
+        );
+        assert_eq!(history.parse("photos.0003.tar.gz", "tar.gz"), Some(3));
+        assert_eq!(history.parse("photos.tar.gz", "tar.gz"), None);
+        assert_eq!(history.parse("photos-old.0003.tar.gz", "tar.gz"), None);
+        assert_eq!(history.parse("photos.x003.tar.gz", "tar.gz"), None);
+    }
+}
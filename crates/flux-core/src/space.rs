@@ -0,0 +1,409 @@
+//! Temporary files and free disk space
+//!
+//! Archives are staged in a temporary directory when they are packed for
+//! stdout or the cloud and when they are downloaded to be extracted. That
+//! directory is the one given to [`set_temp_dir`], as by `--temp-dir`, or
+//! `temp_dir` from the configuration, as the system one is often a small
+//! tmpfs; see [`temp_dir`].
+//!
+//! Each operation stages its files in a [`TempResource`], a scratch
+//! directory of its own there that is removed when the guard is dropped,
+//! whether the operation finished, failed, was cancelled or panicked. A
+//! guard holds at most the quota from [`set_scratch_quota`] or
+//! `scratch_quota` in the configuration, checked with
+//! [`TempResource::reserve`] before a known amount is written and
+//! [`TempResource::check`] after. Scratch directories left by a process
+//! that was killed are removed by the next one to make a guard.
+//!
+//! [`check_pack`] and [`check_extract`] estimate what a job will write and
+//! fail with [`Error::InsufficientSpace`] before anything is written when
+//! the disk it goes to has less room than that. Both only do the expensive
+//! part of the estimate, compressing a sample or listing the archive, when
+//! the free space is too tight to tell without it.
+
+use crate::archive::detect::output_format;
+use crate::archive::estimate::{estimate_pack, input_size};
+use crate::archive::{detect, Archive, PackOptions};
+use crate::config::Config;
+use crate::security::DEFAULT_MAX_COMPRESSION_RATIO;
+use crate::{Error, ErrorCode, Result};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use tracing::{debug, warn};
+use walkdir::WalkDir;
+
+/// Directory from [`set_temp_dir`], ahead of the configuration
+static TEMP_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// Quota from [`set_scratch_quota`], ahead of the configuration
+static SCRATCH_QUOTA: RwLock<Option<u64>> = RwLock::new(None);
+
+/// Number of the next scratch directory made by this process
+static NEXT_SCRATCH: AtomicU64 = AtomicU64::new(0);
+
+/// Start of the names of scratch directories, followed by the process id
+/// and a number
+const SCRATCH_PREFIX: &str = "flux-scratch-";
+
+/// Format packed when neither the caller nor the output's name picks one,
+/// as in [`crate::archive::pack_with_progress`]
+const DEFAULT_FORMAT: &str = "tar.zst";
+
+/// Use `dir` for temporary files for the rest of the process, whatever
+/// the configuration says
+pub fn set_temp_dir(dir: PathBuf) {
+    *TEMP_DIR.write().unwrap_or_else(|e| e.into_inner()) = Some(dir);
+}
+
+/// Directory for temporary files, created if needed: the one from
+/// [`set_temp_dir`], `temp_dir` from the configuration, or else the
+/// system's
+pub fn temp_dir() -> Result<PathBuf> {
+    let chosen = TEMP_DIR.read().unwrap_or_else(|e| e.into_inner()).clone();
+    let Some(dir) = chosen.or_else(|| Config::load_or_default().temp_dir) else {
+        return Ok(std::env::temp_dir());
+    };
+    fs::create_dir_all(&dir).map_err(|e| Error::from(e).with_path(&dir))?;
+    Ok(dir)
+}
+
+/// Limit each [`TempResource`] to `bytes` for the rest of the process,
+/// whatever the configuration says
+pub fn set_scratch_quota(bytes: u64) {
+    *SCRATCH_QUOTA.write().unwrap_or_else(|e| e.into_inner()) = Some(bytes);
+}
+
+/// Most bytes a [`TempResource`] may hold: the quota from
+/// [`set_scratch_quota`], `scratch_quota` from the configuration, or else
+/// `None` for no limit
+pub fn scratch_quota() -> Option<u64> {
+    let chosen = *SCRATCH_QUOTA.read().unwrap_or_else(|e| e.into_inner());
+    chosen.or_else(|| Config::load_or_default().scratch_quota)
+}
+
+/// Scratch directory of one operation, removed with everything in it when
+/// the guard is dropped
+///
+/// Dropping happens on every way out of the operation, an error, a
+/// cancelled progress sink or a panic unwinding included, so nothing is
+/// left behind in the temp directory.
+#[derive(Debug)]
+pub struct TempResource {
+    path: PathBuf,
+    quota: Option<u64>,
+}
+
+impl TempResource {
+    /// New scratch directory under [`temp_dir`], limited to
+    /// [`scratch_quota`]
+    pub fn new() -> Result<Self> {
+        Self::new_in(&temp_dir()?, scratch_quota())
+    }
+
+    /// New scratch directory under `dir` holding at most `quota` bytes, or
+    /// any amount with no quota
+    ///
+    /// Scratch directories in `dir` left by processes no longer running
+    /// are removed first.
+    pub fn new_in(dir: &Path, quota: Option<u64>) -> Result<Self> {
+        remove_stale_scratch(dir);
+        loop {
+            let path = dir.join(format!(
+                "{}{}-{}",
+                SCRATCH_PREFIX,
+                std::process::id(),
+                NEXT_SCRATCH.fetch_add(1, Ordering::Relaxed)
+            ));
+            match fs::create_dir(&path) {
+                Ok(()) => {
+                    debug!("Created scratch directory {:?}", path);
+                    return Ok(Self { path, quota });
+                }
+                // Left by an earlier process with the same id
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(Error::from(e).with_path(dir)),
+            }
+        }
+    }
+
+    /// The scratch directory
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Most bytes the directory may hold, if limited
+    pub fn quota(&self) -> Option<u64> {
+        self.quota
+    }
+
+    /// Bytes in the files now in the directory
+    pub fn used(&self) -> u64 {
+        WalkDir::new(&self.path)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .filter_map(|entry| entry.metadata().ok())
+            .map(|metadata| metadata.len())
+            .sum()
+    }
+
+    /// Fail with [`Error::InsufficientSpace`] unless `bytes` more fit in
+    /// the quota, and on the disk, before they are written
+    pub fn reserve(&self, bytes: u64) -> Result<()> {
+        if let Some(quota) = self.quota {
+            let used = self.used();
+            if used.saturating_add(bytes) > quota {
+                return Err(Error::InsufficientSpace {
+                    path: self.path.clone(),
+                    required: bytes,
+                    available: quota.saturating_sub(used),
+                });
+            }
+        }
+        ensure_space(&self.path, bytes)
+    }
+
+    /// Fail with [`Error::InsufficientSpace`] if the directory holds more
+    /// than its quota
+    pub fn check(&self) -> Result<()> {
+        match self.quota {
+            Some(quota) => {
+                let used = self.used();
+                if used > quota {
+                    return Err(Error::InsufficientSpace {
+                        path: self.path.clone(),
+                        required: used,
+                        available: quota,
+                    });
+                }
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for TempResource {
+    fn drop(&mut self) {
+        match fs::remove_dir_all(&self.path) {
+            Ok(()) => debug!("Removed scratch directory {:?}", self.path),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Failed to remove scratch directory {:?}: {}", self.path, e),
+        }
+    }
+}
+
+/// Remove the scratch directories in `dir` whose process is gone, as when
+/// it was killed before its guards were dropped
+fn remove_stale_scratch(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name();
+        let Some(pid) = name
+            .to_str()
+            .and_then(|name| name.strip_prefix(SCRATCH_PREFIX))
+            .and_then(|rest| rest.split_once('-'))
+            .and_then(|(pid, _)| pid.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if pid != std::process::id() && !process_running(pid) {
+            debug!("Removing stale scratch directory {:?}", entry.path());
+            let _ = fs::remove_dir_all(entry.path());
+        }
+    }
+}
+
+#[cfg(unix)]
+fn process_running(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return true;
+    };
+    // Signal 0 only checks that the process exists; EPERM means it does
+    // but belongs to someone else
+    let signalled = unsafe { libc::kill(pid, 0) } == 0;
+    signalled || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+// Without a cheap way to tell, scratch directories are left alone
+#[cfg(not(unix))]
+fn process_running(_pid: u32) -> bool {
+    true
+}
+
+/// Bytes free for this user on the disk holding `path`, which need not
+/// exist yet; `None` on platforms where it cannot be told
+pub fn available_space(path: &Path) -> Result<Option<u64>> {
+    // The nearest existing directory is on the same disk
+    let existing = path
+        .ancestors()
+        .find(|dir| !dir.as_os_str().is_empty() && dir.exists())
+        .unwrap_or(Path::new("."));
+    available_space_at(existing).map_err(|e| e.with_path(existing))
+}
+
+#[cfg(unix)]
+fn available_space_at(path: &Path) -> Result<Option<u64>> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = std::ffi::CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| Error::InvalidPath("Invalid path for statvfs".to_string()))?;
+    let stat = unsafe {
+        let mut stat: libc::statvfs = std::mem::zeroed();
+        if libc::statvfs(path.as_ptr(), &mut stat) != 0 {
+            return Err(Error::Io(std::io::Error::last_os_error()));
+        }
+        stat
+    };
+    #[allow(clippy::unnecessary_cast)] // The field types differ between platforms
+    Ok(Some(stat.f_bavail as u64 * stat.f_frsize as u64))
+}
+
+#[cfg(windows)]
+fn available_space_at(path: &Path) -> Result<Option<u64>> {
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::fileapi::GetDiskFreeSpaceExW;
+    use winapi::um::winnt::ULARGE_INTEGER;
+
+    let path: Vec<u16> = path
+        .as_os_str()
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect();
+    let mut available = ULARGE_INTEGER::default();
+    let mut total = ULARGE_INTEGER::default();
+    let mut free = ULARGE_INTEGER::default();
+    unsafe {
+        if GetDiskFreeSpaceExW(path.as_ptr(), &mut available, &mut total, &mut free) == 0 {
+            return Err(Error::Io(std::io::Error::last_os_error()));
+        }
+        Ok(Some(*available.QuadPart()))
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn available_space_at(_path: &Path) -> Result<Option<u64>> {
+    Ok(None)
+}
+
+/// Fail with [`Error::InsufficientSpace`] unless the disk holding `path`
+/// has `required` bytes free
+pub fn ensure_space(path: &Path, required: u64) -> Result<()> {
+    match available_space(path)? {
+        Some(available) if available < required => Err(Error::InsufficientSpace {
+            path: path.to_path_buf(),
+            required,
+            available,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Check there is room for the archive packing `inputs` into `output`
+/// would write
+///
+/// `format` and `options` are those given to the packing; without a
+/// format, the one `output` is named for is assumed. Pass the staging file
+/// as `output` when the archive is written to a temporary file first. The
+/// inputs are only sampled to estimate the archive's size when the disk
+/// has less room than the inputs themselves take.
+pub fn check_pack(
+    inputs: &[PathBuf],
+    output: &Path,
+    format: Option<&str>,
+    options: &PackOptions,
+) -> Result<()> {
+    let Some(available) = available_space(output)? else {
+        return Ok(());
+    };
+    let input_bytes = input_size(inputs, options);
+    if input_bytes <= available {
+        return Ok(());
+    }
+
+    let format = format
+        .and_then(detect::parse_format)
+        .or_else(|| output_format(output))
+        .unwrap_or(DEFAULT_FORMAT);
+    // 7z is not estimated; its archive is taken to be as large as the input
+    let required = match estimate_pack(inputs, format, options) {
+        Ok(estimate) => estimate.archive_size,
+        Err(Error::UnsupportedFormat(_)) => input_bytes,
+        Err(e) => return Err(e),
+    };
+    debug!(
+        "Packing {:?} needs about {} bytes, {} are free",
+        inputs, required, available
+    );
+    ensure_space(output, required)
+}
+
+/// Check there is room in `output_dir` for the files of `archive`
+///
+/// The archive is only listed when the free space is less than
+/// [`DEFAULT_MAX_COMPRESSION_RATIO`] times its size, as hardly anything
+/// but a zip bomb expands more; the listing stays with `archive` for the
+/// extraction that follows.
+pub fn check_extract(archive: &Archive, output_dir: &Path) -> Result<()> {
+    let Some(available) = available_space(output_dir)? else {
+        return Ok(());
+    };
+    let path = archive.path();
+    let archive_size = fs::metadata(path)
+        .map_err(|e| Error::from(e).with_path(path))?
+        .len();
+    if archive_size as f64 * DEFAULT_MAX_COMPRESSION_RATIO <= available as f64 {
+        return Ok(());
+    }
+
+    let entries = match archive.entries() {
+        Ok(entries) => entries,
+        Err(e) if e.code() == ErrorCode::UnsupportedOperation => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let required = entries
+        .iter()
+        .filter(|entry| !entry.is_dir && !entry.is_symlink)
+        .map(|entry| entry.size)
+        .sum();
+    debug!(
+        "Extracting {:?} needs {} bytes, {} are free",
+        path, required, available
+    );
+    ensure_space(output_dir, required)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_available_space_of_a_missing_path() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let missing = dir.path().join("not/yet/made");
+        assert!(available_space(&missing).unwrap().is_some());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_ensure_space_reports_the_shortfall() {
+        let dir = tempfile::TempDir::new().unwrap();
+        ensure_space(dir.path(), 0).unwrap();
+
+        let error = ensure_space(dir.path(), u64::MAX).unwrap_err();
+        assert_eq!(error.code(), ErrorCode::InsufficientSpace);
+        assert!(matches!(
+            error,
+            Error::InsufficientSpace {
+                required: u64::MAX,
+                ..
+            }
+        ));
+    }
+}
@@ -41,6 +41,17 @@ pub enum Algorithm {
     Brotli,
 }
 
+impl Algorithm {
+    /// Every algorithm, in the order they are listed
+    pub const ALL: [Algorithm; 5] = [
+        Algorithm::Store,
+        Algorithm::Gzip,
+        Algorithm::Zstd,
+        Algorithm::Xz,
+        Algorithm::Brotli,
+    ];
+}
+
 impl std::str::FromStr for Algorithm {
     type Err = ();
 
@@ -187,7 +198,7 @@ fn apply_custom_rules(path: &Path, config: &Config) -> Option<CompressionStrateg
 
     // Sort rules by priority (descending)
     let mut rules = config.rules.clone();
-    rules.sort_by(|a, b| b.priority.cmp(&a.priority));
+    rules.sort_by_key(|rule| std::cmp::Reverse(rule.priority));
 
     for rule in rules {
         // Check if any pattern matches
@@ -0,0 +1,201 @@
+//! Timing and byte counts for the phases of an operation
+//!
+//! Each phase is a `flux.phase` tracing span whose `bytes_in`, `bytes_out`
+//! and `duration_ms` fields are filled in when the phase ends, so a layer
+//! such as `tracing-opentelemetry` can forward them as they are. The same
+//! figures go to the exporter installed with [`set_exporter`], for
+//! collecting them without a tracing pipeline.
+
+use serde::Serialize;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Instant;
+use tracing::field::Empty;
+use tracing::Span;
+
+/// A stage of packing, extracting or transferring an archive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Measuring the inputs
+    Scan,
+    /// Reading and compressing the inputs into the archive
+    Compress,
+    /// Copying a finished archive to where it goes, such as stdout
+    Write,
+    Upload,
+    Download,
+    Extract,
+}
+
+impl Phase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Phase::Scan => "scan",
+            Phase::Compress => "compress",
+            Phase::Write => "write",
+            Phase::Upload => "upload",
+            Phase::Download => "download",
+            Phase::Extract => "extract",
+        }
+    }
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What one phase took
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PhaseMetrics {
+    pub phase: Phase,
+    pub duration_ms: u64,
+    /// Bytes read, such as input file data or a download
+    pub bytes_in: u64,
+    /// Bytes produced, such as the archive or an upload
+    pub bytes_out: u64,
+}
+
+/// Receiver of the metrics of every phase that ends
+///
+/// Called on the thread that ended the phase.
+pub trait Exporter: Send + Sync {
+    fn export(&self, metrics: &PhaseMetrics);
+}
+
+static EXPORTER: RwLock<Option<Arc<dyn Exporter>>> = RwLock::new(None);
+
+/// Send the metrics of every phase that ends from now on to `exporter`,
+/// replacing any exporter set before
+pub fn set_exporter(exporter: Arc<dyn Exporter>) {
+    *EXPORTER.write().unwrap_or_else(|e| e.into_inner()) = Some(exporter);
+}
+
+/// Stop exporting metrics
+pub fn clear_exporter() {
+    *EXPORTER.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Exporter that appends one JSON object per phase to a file
+pub struct JsonLinesExporter {
+    file: Mutex<File>,
+}
+
+impl JsonLinesExporter {
+    /// Append to the file at `path`, creating it if needed
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+}
+
+impl Exporter for JsonLinesExporter {
+    fn export(&self, metrics: &PhaseMetrics) {
+        let Ok(line) = serde_json::to_string(metrics) else {
+            return;
+        };
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(file, "{}", line) {
+            tracing::warn!("Failed to write metrics: {}", e);
+        }
+    }
+}
+
+/// A phase in progress, reported when finished or dropped
+pub struct PhaseTimer {
+    phase: Phase,
+    span: Span,
+    start: Instant,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    finished: bool,
+}
+
+impl PhaseTimer {
+    /// Start timing `phase`
+    pub fn start(phase: Phase) -> Self {
+        let span = tracing::info_span!(
+            "flux.phase",
+            phase = phase.as_str(),
+            bytes_in = Empty,
+            bytes_out = Empty,
+            duration_ms = Empty
+        );
+        Self {
+            phase,
+            span,
+            start: Instant::now(),
+            bytes_in: AtomicU64::new(0),
+            bytes_out: AtomicU64::new(0),
+            finished: false,
+        }
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Count `bytes` more read
+    pub fn add_in(&self, bytes: u64) {
+        self.bytes_in.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Count `bytes` more produced
+    pub fn add_out(&self, bytes: u64) {
+        self.bytes_out.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    /// Record that the output has grown to `bytes` in all
+    pub fn set_out(&self, bytes: u64) {
+        self.bytes_out.fetch_max(bytes, Ordering::Relaxed);
+    }
+
+    /// End the phase, closing its span and exporting its metrics
+    pub fn finish(mut self) -> PhaseMetrics {
+        self.report()
+    }
+
+    fn report(&mut self) -> PhaseMetrics {
+        self.finished = true;
+        let metrics = PhaseMetrics {
+            phase: self.phase,
+            duration_ms: self.start.elapsed().as_millis() as u64,
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+        };
+
+        self.span.record("bytes_in", metrics.bytes_in);
+        self.span.record("bytes_out", metrics.bytes_out);
+        self.span.record("duration_ms", metrics.duration_ms);
+        tracing::debug!(
+            parent: &self.span,
+            "{} took {} ms ({} bytes in, {} bytes out)",
+            metrics.phase,
+            metrics.duration_ms,
+            metrics.bytes_in,
+            metrics.bytes_out
+        );
+
+        let exporter = EXPORTER.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Some(exporter) = exporter {
+            exporter.export(&metrics);
+        }
+        metrics
+    }
+}
+
+impl Drop for PhaseTimer {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.report();
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Capping how fast an operation reads or writes
+//!
+//! A [`RateLimiter`] is told of every chunk an operation moves and makes
+//! the caller sleep long enough to keep to its rate. It allows a burst of
+//! one second's worth after being idle, so a paused task does not race to
+//! catch up once it resumes. One limiter can be shared by the threads of
+//! an operation, which then keep to the rate together.
+
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Keeps the bytes passed through it to a rate
+#[derive(Debug)]
+pub struct RateLimiter {
+    bytes_per_second: u64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Bytes that may pass without waiting; negative once they are owed
+    available: f64,
+    refilled: Instant,
+}
+
+impl RateLimiter {
+    /// A limiter allowing `bytes_per_second`, at least one byte a second
+    pub fn new(bytes_per_second: u64) -> Self {
+        let bytes_per_second = bytes_per_second.max(1);
+        Self {
+            bytes_per_second,
+            bucket: Mutex::new(Bucket {
+                available: bytes_per_second as f64,
+                refilled: Instant::now(),
+            }),
+        }
+    }
+
+    /// The rate kept to, in bytes per second
+    pub fn bytes_per_second(&self) -> u64 {
+        self.bytes_per_second
+    }
+
+    /// Count `bytes` moved, sleeping if they put the caller ahead of the
+    /// rate
+    ///
+    /// Returns how long it slept.
+    pub fn consume(&self, bytes: u64) -> Duration {
+        let wait = self.debit(bytes);
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+        wait
+    }
+
+    /// Take `bytes` out of the bucket, returning how long until the debt
+    /// is paid
+    fn debit(&self, bytes: u64) -> Duration {
+        let rate = self.bytes_per_second as f64;
+        let mut bucket = self.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        let earned = now.duration_since(bucket.refilled).as_secs_f64() * rate;
+        bucket.available = (bucket.available + earned).min(rate) - bytes as f64;
+        bucket.refilled = now;
+        if bucket.available < 0.0 {
+            Duration::from_secs_f64(-bucket.available / rate)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
@@ -1,7 +1,9 @@
 //! Utility functions for flux-core
 
-use std::fs;
-use std::path::Path;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use tracing::debug;
 
 /// Calculate the total size of a path (file or directory) in bytes
 ///
@@ -28,6 +30,130 @@ pub fn calculate_path_size<P: AsRef<Path>>(path: P) -> u64 {
     }
 }
 
+/// Whether `error` is a rename failing because its two paths are on
+/// different file systems
+pub fn is_cross_device(error: &io::Error) -> bool {
+    error.kind() == io::ErrorKind::CrossesDevices
+}
+
+/// Whether `a` and `b`, which need not exist yet, are on the same file
+/// system; `None` where that cannot be told
+///
+/// Each path is judged by its nearest existing ancestor.
+pub fn same_file_system(a: &Path, b: &Path) -> Option<bool> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let device = |path: &Path| {
+            let existing = path
+                .ancestors()
+                .find(|dir| !dir.as_os_str().is_empty() && dir.exists())
+                .unwrap_or(Path::new("."));
+            fs::metadata(existing).ok().map(|metadata| metadata.dev())
+        };
+        Some(device(a)? == device(b)?)
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (a, b);
+        None
+    }
+}
+
+/// Move the file or directory `from` to `to`, across file systems if need
+/// be
+///
+/// A rename where it works. Across file systems each file is copied to a
+/// temporary name beside its destination, synced to disk and renamed into
+/// place, so no file is ever seen half written under its own name; `from`
+/// is only removed once everything is copied. Permissions, modification
+/// times and symlinks are kept.
+pub fn move_path(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Ok(()) => Ok(()),
+        Err(e) if is_cross_device(&e) => {
+            debug!(
+                "{:?} and {:?} are on different file systems, copying",
+                from, to
+            );
+            move_across(from, to)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// The copying half of [`move_path`]
+pub(crate) fn move_across(from: &Path, to: &Path) -> io::Result<()> {
+    copy_across(from, to)?;
+    if fs::symlink_metadata(from)?.is_dir() {
+        fs::remove_dir_all(from)
+    } else {
+        fs::remove_file(from)
+    }
+}
+
+fn copy_across(from: &Path, to: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(from)?;
+    let file_type = metadata.file_type();
+    if file_type.is_dir() {
+        match fs::create_dir(to) {
+            Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e),
+            _ => {}
+        }
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_across(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        fs::set_permissions(to, metadata.permissions())?;
+        crate::durability::sync_dir(to)
+    } else if file_type.is_symlink() {
+        let target = fs::read_link(from)?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&target, to)?;
+        #[cfg(windows)]
+        if from.is_dir() {
+            std::os::windows::fs::symlink_dir(&target, to)?;
+        } else {
+            std::os::windows::fs::symlink_file(&target, to)?;
+        }
+        Ok(())
+    } else {
+        copy_file_synced(from, to, &metadata)
+    }
+}
+
+fn copy_file_synced(from: &Path, to: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+    let partial = partial_path(to);
+    let result = (|| {
+        let mut output = File::create(&partial)?;
+        io::copy(&mut File::open(from)?, &mut output)?;
+        if let Ok(modified) = metadata.modified() {
+            output.set_modified(modified)?;
+        }
+        output.set_permissions(metadata.permissions())?;
+        output.sync_all()?;
+        drop(output);
+        fs::rename(&partial, to)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&partial);
+    }
+    result?;
+    match to.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => crate::durability::sync_dir(parent),
+        _ => Ok(()),
+    }
+}
+
+/// Where a file is copied before it is renamed to `path`
+fn partial_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.partial", name, std::process::id()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,4 +193,36 @@ mod tests {
     fn test_nonexistent_path() {
         assert_eq!(calculate_path_size("/nonexistent/path"), 0);
     }
+
+    #[test]
+    fn test_move_across_copies_tree() {
+        let temp_dir = TempDir::new().unwrap();
+        let from = temp_dir.path().join("from");
+        fs::create_dir_all(from.join("sub")).unwrap();
+        fs::write(from.join("sub/file.txt"), b"moved").unwrap();
+        let mtime = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        File::options()
+            .write(true)
+            .open(from.join("sub/file.txt"))
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("sub/file.txt", from.join("link")).unwrap();
+
+        let to = temp_dir.path().join("to");
+        move_across(&from, &to).unwrap();
+
+        assert!(!from.exists());
+        assert_eq!(fs::read(to.join("sub/file.txt")).unwrap(), b"moved");
+        let metadata = fs::metadata(to.join("sub/file.txt")).unwrap();
+        assert_eq!(metadata.modified().unwrap(), mtime);
+        #[cfg(unix)]
+        assert_eq!(
+            fs::read_link(to.join("link")).unwrap(),
+            Path::new("sub/file.txt")
+        );
+        // No partial copies are left behind
+        assert_eq!(fs::read_dir(to.join("sub")).unwrap().count(), 1);
+    }
 }
@@ -0,0 +1,150 @@
+//! Tests for reading an archive through one opened handle
+
+use flux_core::archive::extractor::{ArchiveEntry, ExtractEntryOptions};
+use flux_core::archive::{Archive, ExtractOptions};
+use flux_core::progress::NoProgress;
+use flux_core::ErrorCode;
+use flux_testing::fixtures::{ArchiveBuilder, ArchiveKind};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// An archive of a folder holding two files
+fn create_archive(dir: &Path, kind: ArchiveKind) -> PathBuf {
+    let archive = dir.join(format!("project.{}", kind.extension()));
+    ArchiveBuilder::new(kind)
+        .dir("project")
+        .dir("project/docs")
+        .file("project/docs/notes.txt", "notes")
+        .file("project/readme.txt", "read me")
+        .build(&archive)
+        .unwrap();
+    archive
+}
+
+#[test]
+fn test_open_finds_format_and_capabilities() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), ArchiveKind::TarZst);
+
+    let opened = Archive::open(&archive).unwrap();
+    assert_eq!(opened.path(), archive);
+    assert_eq!(opened.format(), "tar.zst");
+    assert!(opened.capabilities().symlinks);
+
+    let zip = create_archive(temp_dir.path(), ArchiveKind::Zip);
+    let opened = Archive::open_as(&zip, Some("zip")).unwrap();
+    assert_eq!(opened.format(), "zip");
+    assert!(opened.capabilities().encryption);
+
+    let missing = Archive::open(temp_dir.path().join("missing.tar")).unwrap_err();
+    assert_eq!(missing.code(), ErrorCode::NotFound);
+}
+
+#[test]
+fn test_entries_are_listed_once() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), ArchiveKind::TarGz);
+
+    let opened = Archive::open(&archive).unwrap();
+    let listed = opened.entries().unwrap().len();
+    let mut paths: Vec<_> = opened
+        .entries()
+        .unwrap()
+        .iter()
+        .filter(|entry| !entry.is_dir)
+        .map(|entry| entry.path.clone())
+        .collect();
+    paths.sort();
+    assert_eq!(
+        paths,
+        [
+            Path::new("project/docs/notes.txt"),
+            Path::new("project/readme.txt")
+        ]
+    );
+
+    // Kept from the first listing, with the file no longer there to read
+    let moved = temp_dir.path().join("moved.tar.gz");
+    fs::rename(&archive, &moved).unwrap();
+    assert_eq!(opened.entries().unwrap().len(), listed);
+}
+
+#[test]
+fn test_open_entry_and_extract_all() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), ArchiveKind::Zip);
+    let opened = Archive::open(&archive).unwrap();
+
+    let readme = opened
+        .entries()
+        .unwrap()
+        .iter()
+        .find(|entry| entry.path == Path::new("project/readme.txt"))
+        .unwrap();
+    let mut content = String::new();
+    opened
+        .open_entry(readme)
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "read me");
+
+    // The single top folder is hoisted using the listing already made
+    let output = temp_dir.path().join("output");
+    let options = ExtractOptions {
+        hoist: true,
+        ..Default::default()
+    };
+    let outcome = opened.extract_all(&output, options).unwrap();
+    assert!(outcome.is_complete());
+    assert_eq!(
+        fs::read_to_string(output.join("docs/notes.txt")).unwrap(),
+        "notes"
+    );
+    assert!(!output.join("project").exists());
+}
+
+#[test]
+fn test_entries_outside_the_destination_are_refused() {
+    let temp_dir = TempDir::new().unwrap();
+    let output = temp_dir.path().join("output");
+
+    for kind in [ArchiveKind::Tar, ArchiveKind::Zip, ArchiveKind::SevenZ] {
+        let archive = temp_dir.path().join(format!("evil.{}", kind.extension()));
+        ArchiveBuilder::new(kind)
+            .file("ok.txt", "ok")
+            .file("../evil.txt", "evil")
+            .build(&archive)
+            .unwrap();
+        let opened = Archive::open(&archive).unwrap();
+        assert!(opened.entries().is_err(), "{:?}", kind);
+
+        let entry = |path: &str| ArchiveEntry {
+            path: PathBuf::from(path),
+            size: 4,
+            compressed_size: None,
+            mode: None,
+            mtime: None,
+            is_dir: false,
+            is_symlink: false,
+            link_target: None,
+            uid: None,
+            gid: None,
+        };
+        let options = ExtractEntryOptions {
+            overwrite: true,
+            ..Default::default()
+        };
+        opened
+            .extract_entry(&entry("ok.txt"), &output, options.clone(), &NoProgress)
+            .unwrap();
+        let refused = opened
+            .extract_entry(&entry("../evil.txt"), &output, options, &NoProgress)
+            .unwrap_err();
+        assert_eq!(refused.code(), ErrorCode::InvalidPath, "{:?}", kind);
+        assert!(!temp_dir.path().join("evil.txt").exists(), "{:?}", kind);
+    }
+    assert_eq!(fs::read_to_string(output.join("ok.txt")).unwrap(), "ok");
+}
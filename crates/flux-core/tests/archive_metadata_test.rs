@@ -0,0 +1,98 @@
+//! Tests for the archive-level metadata record
+
+use flux_core::archive::{
+    extract, inspect, inspect_stats, pack_with_strategy, read_metadata, verify, ArchiveMetadata,
+    PackOptions,
+};
+use flux_testing::fixtures::SourceTree;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn create_source(dir: &Path) -> PathBuf {
+    SourceTree::new()
+        .file("a.txt", "hello")
+        .file("docs/b.txt", "world")
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+fn metadata() -> ArchiveMetadata {
+    ArchiveMetadata {
+        creator: Some("alice".to_string()),
+        hostname: Some("build-01".to_string()),
+        created: Some(1_700_000_000),
+        flux_version: Some("1.2.3".to_string()),
+        ..ArchiveMetadata::default()
+    }
+    .with_value("ticket", "OPS-12")
+    .with_value("note", "two words = fine")
+}
+
+fn pack(source: &Path, archive: &Path, format: &str, metadata: Option<ArchiveMetadata>) {
+    let options = PackOptions {
+        metadata,
+        ..PackOptions::default()
+    };
+    pack_with_strategy(source, archive, Some(format), options).unwrap();
+}
+
+#[test]
+fn test_metadata_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    for format in ["tar", "tar.gz", "tar.zst", "zip"] {
+        let archive = temp_dir.path().join(format!("out.{}", format));
+        pack(&source, &archive, format, Some(metadata()));
+
+        assert_eq!(
+            read_metadata(&archive).unwrap(),
+            Some(metadata()),
+            "{}",
+            format
+        );
+        let stats = inspect_stats(&archive).unwrap();
+        assert_eq!(stats.metadata, Some(metadata()), "{}", format);
+        assert_eq!(stats.comment, None, "{}", format);
+    }
+}
+
+#[test]
+fn test_no_metadata_by_default() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    for format in ["tar.gz", "zip"] {
+        let archive = temp_dir.path().join(format!("plain.{}", format));
+        pack(&source, &archive, format, None);
+        assert_eq!(read_metadata(&archive).unwrap(), None, "{}", format);
+    }
+}
+
+#[test]
+fn test_metadata_header_is_not_an_entry() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let archive = temp_dir.path().join("out.tar.gz");
+    pack(&source, &archive, "tar.gz", Some(metadata()));
+
+    let entries = inspect(&archive).unwrap();
+    assert!(entries
+        .iter()
+        .all(|entry| !entry.path.to_string_lossy().contains("pax_global_header")));
+    assert_eq!(inspect_stats(&archive).unwrap().files, 2);
+    assert!(verify(&archive).unwrap().is_ok());
+
+    let output = temp_dir.path().join("output");
+    extract(&archive, &output).unwrap();
+    assert_eq!(
+        fs::read_to_string(output.join("source/a.txt")).unwrap(),
+        "hello"
+    );
+    assert_eq!(
+        fs::read_to_string(output.join("source/docs/b.txt")).unwrap(),
+        "world"
+    );
+    assert!(!output.join("pax_global_header").exists());
+}
@@ -0,0 +1,90 @@
+//! Tests for archive statistics
+
+use flux_core::archive::{inspect_stats, pack_with_strategy, PackOptions};
+use flux_testing::fixtures::SourceTree;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use tempfile::TempDir;
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, CompressionMethod, ZipWriter};
+
+fn create_source(dir: &Path) -> std::path::PathBuf {
+    SourceTree::new()
+        .file("a.txt", "a".repeat(1000))
+        .file("docs/b.txt", "b".repeat(500))
+        .symlink("link", "a.txt")
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+#[test]
+fn test_tar_stats() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let archive = temp_dir.path().join("out.tar.zst");
+    pack_with_strategy(&source, &archive, Some("tar.zst"), PackOptions::default()).unwrap();
+
+    let stats = inspect_stats(&archive).unwrap();
+    assert_eq!(stats.format, "tar.zst");
+    assert_eq!(stats.methods, vec!["Zstd"]);
+    assert_eq!(stats.files, 2);
+    assert_eq!(stats.total_size, 1500);
+    #[cfg(unix)]
+    assert_eq!(stats.symlinks, 1);
+    assert!(stats.directories >= 1);
+    assert_eq!(stats.archive_size, fs::metadata(&archive).unwrap().len());
+    assert!(stats.ratio().unwrap() < 1.0);
+    assert!(!stats.is_encrypted());
+    assert_eq!(stats.comment, None);
+    assert_eq!(stats.signature, None);
+}
+
+#[test]
+fn test_zip_stats_with_comment_and_encryption() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("out.zip");
+
+    let mut writer = ZipWriter::new(File::create(&archive).unwrap());
+    writer.set_comment("release build");
+    writer
+        .start_file(
+            "secret.txt",
+            SimpleFileOptions::default().with_aes_encryption(AesMode::Aes256, "pw"),
+        )
+        .unwrap();
+    writer.write_all(b"top secret").unwrap();
+    writer
+        .add_directory("docs/", SimpleFileOptions::default())
+        .unwrap();
+    writer
+        .start_file(
+            "docs/plain.txt",
+            SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+        )
+        .unwrap();
+    writer.write_all(b"nothing to hide").unwrap();
+    writer.finish().unwrap();
+
+    fs::write(temp_dir.path().join("out.zip.sig"), "signature").unwrap();
+
+    let stats = inspect_stats(&archive).unwrap();
+    assert_eq!(stats.format, "zip");
+    assert_eq!(stats.files, 2);
+    assert_eq!(stats.directories, 1);
+    assert_eq!(stats.entries(), 3);
+    assert_eq!(stats.total_size, 10 + 15);
+    assert_eq!(stats.encrypted_entries, 1);
+    assert!(stats.is_encrypted());
+    assert!(stats.methods.contains(&"Stored".to_string()));
+    assert_eq!(stats.comment.as_deref(), Some("release build"));
+    assert_eq!(stats.signature, Some(temp_dir.path().join("out.zip.sig")));
+}
+
+#[test]
+fn test_unknown_format_is_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("notes.txt");
+    fs::write(&file, "text").unwrap();
+    assert!(inspect_stats(&file).is_err());
+}
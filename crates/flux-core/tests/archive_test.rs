@@ -103,6 +103,7 @@ fn test_extract_with_skip_option() {
         rename: false,
         strip_components: None,
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, skip_options).unwrap();
 
@@ -147,6 +148,7 @@ fn test_extract_with_overwrite_option() {
         rename: false,
         strip_components: None,
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, overwrite_options).unwrap();
 
@@ -188,12 +190,15 @@ fn test_extract_with_rename_option() {
         rename: true,
         strip_components: None,
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, rename_options).unwrap();
 
     // Both files should exist - check both with and without directory structure
-    let file_exists = extract_dir.join("file.txt").exists() || extract_dir.join("source/file.txt").exists();
-    let renamed_exists = extract_dir.join("file (1).txt").exists() || extract_dir.join("source/file (1).txt").exists();
+    let file_exists =
+        extract_dir.join("file.txt").exists() || extract_dir.join("source/file.txt").exists();
+    let renamed_exists = extract_dir.join("file (1).txt").exists()
+        || extract_dir.join("source/file (1).txt").exists();
     assert!(file_exists, "Original file should exist");
     assert!(renamed_exists, "Renamed file should exist");
 }
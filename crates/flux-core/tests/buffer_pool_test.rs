@@ -0,0 +1,71 @@
+//! Tests for the shared I/O buffer pool
+
+use flux_core::buffer::{BufferPool, MIN_CHUNK_SIZE};
+use rayon::prelude::*;
+use std::io::Cursor;
+
+#[test]
+fn test_buffers_are_reused() {
+    let pool = BufferPool::new(16 * 1024);
+    {
+        let mut buffer = pool.get();
+        assert_eq!(buffer.len(), 16 * 1024);
+        buffer[0] = 42;
+    }
+    assert_eq!(pool.idle(), 1);
+
+    let buffer = pool.get();
+    assert_eq!(pool.idle(), 0);
+    // The same allocation comes back, contents and all
+    assert_eq!(buffer[0], 42);
+}
+
+#[test]
+fn test_chunk_size_change_drops_old_buffers() {
+    let pool = BufferPool::new(8 * 1024);
+    let in_use = pool.get();
+    drop(pool.get());
+    assert_eq!(pool.idle(), 1);
+
+    pool.set_chunk_size(32 * 1024);
+    assert_eq!(pool.idle(), 0);
+    assert_eq!(pool.get().len(), 32 * 1024);
+
+    // A buffer of the old size is not taken back
+    drop(in_use);
+    assert_eq!(pool.idle(), 1);
+
+    pool.set_chunk_size(1);
+    assert_eq!(pool.chunk_size(), MIN_CHUNK_SIZE);
+}
+
+#[test]
+fn test_copy_crosses_chunk_boundaries() {
+    let pool = BufferPool::new(MIN_CHUNK_SIZE);
+    let data: Vec<u8> = (0..MIN_CHUNK_SIZE * 3 + 17).map(|i| i as u8).collect();
+    let mut output = Vec::new();
+
+    let copied = pool.copy(&mut Cursor::new(&data), &mut output).unwrap();
+    assert_eq!(copied, data.len() as u64);
+    assert_eq!(output, data);
+    assert_eq!(pool.idle(), 1);
+}
+
+#[test]
+fn test_pool_is_shared_by_rayon_workers() {
+    let pool = BufferPool::new(MIN_CHUNK_SIZE);
+    let total: u64 = (0..256u32)
+        .into_par_iter()
+        .map(|i| {
+            let data = vec![i as u8; (i as usize) * 97];
+            let mut output = Vec::new();
+            pool.copy(&mut data.as_slice(), &mut output).unwrap();
+            assert_eq!(output, data);
+            output.len() as u64
+        })
+        .sum();
+
+    assert_eq!(total, (0..256u64).map(|i| i * 97).sum::<u64>());
+    let idle = pool.idle();
+    assert!(idle >= 1 && idle <= rayon::current_num_threads() * 2);
+}
@@ -0,0 +1,99 @@
+//! Tests for format capabilities and the metadata of pack inputs
+
+use flux_core::archive::{
+    metadata_loss, scan_metadata, Capability, FormatCapabilities, PackFilter, PackOptions,
+};
+use flux_testing::fixtures::SourceTree;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// A directory with a symlink, an executable, a name outside ASCII and a
+/// plain file
+fn create_source(dir: &Path) -> PathBuf {
+    SourceTree::new()
+        .file("plain.txt", "plain")
+        .file_with_mode("bin/run.sh", "#!/bin/sh\n", 0o755)
+        .file("bin/café.txt", "unicode")
+        .symlink("latest", "plain.txt")
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+fn capabilities(found: &[flux_core::archive::FoundMetadata]) -> Vec<Capability> {
+    found.iter().map(|found| found.capability).collect()
+}
+
+#[test]
+fn test_every_tar_keeps_links_and_permissions() {
+    for format in ["tar", "tar.gz", "tar.zst", "tar.xz", "tar.br"] {
+        let tar = FormatCapabilities::of(format).unwrap();
+        assert!(
+            tar.symlinks && tar.permissions && tar.large_files,
+            "{}",
+            format
+        );
+        assert!(!tar.encryption, "{}", format);
+    }
+    let sevenz = FormatCapabilities::of("7z").unwrap();
+    assert!(sevenz.encryption && !sevenz.permissions);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_scan_finds_metadata() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    let found = scan_metadata(std::slice::from_ref(&source), &PackOptions::default());
+    assert_eq!(
+        capabilities(&found),
+        [
+            Capability::Symlinks,
+            Capability::Permissions,
+            Capability::UnicodeNames
+        ]
+    );
+    assert_eq!(found[0].count, 1);
+    assert_eq!(found[0].example, source.join("latest"));
+    assert_eq!(found[1].example, source.join("bin/run.sh"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_zip_drops_symlinks_unless_followed() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let inputs = [source];
+
+    let dropped = metadata_loss(&inputs, "zip", &PackOptions::default()).unwrap();
+    assert_eq!(capabilities(&dropped), [Capability::Symlinks]);
+    assert!(metadata_loss(&inputs, "tar.zst", &PackOptions::default())
+        .unwrap()
+        .is_empty());
+
+    let followed = PackOptions {
+        follow_symlinks: true,
+        ..PackOptions::default()
+    };
+    assert!(metadata_loss(&inputs, "zip", &followed).unwrap().is_empty());
+}
+
+#[test]
+fn test_scan_leaves_out_excluded_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let options = PackOptions {
+        filter: PackFilter::new(&["bin", "latest"]).unwrap(),
+        ..PackOptions::default()
+    };
+
+    assert!(scan_metadata(&[source], &options).is_empty());
+}
+
+#[test]
+fn test_unknown_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    assert!(metadata_loss(&[source], "rar", &PackOptions::default()).is_err());
+}
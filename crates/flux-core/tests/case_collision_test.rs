@@ -0,0 +1,171 @@
+//! Tests for entries whose names differ only in letter case
+//!
+//! The output directory is declared case-insensitive through `fold_case`,
+//! so the collisions are found on any file system.
+
+use flux_core::archive::{
+    extract_with_options, find_case_collisions, inspect, CaseCollisions, ExtractOptions, SkipReason,
+};
+use flux_testing::fixtures::{ArchiveBuilder, ArchiveKind};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+const FILES: [(&str, &str); 3] = [
+    ("docs/README.md", "upper"),
+    ("docs/notes.txt", "notes"),
+    ("docs/readme.md", "lower"),
+];
+
+/// A tar and a zip archive holding [`FILES`]
+fn create_archives(dir: &Path) -> [std::path::PathBuf; 2] {
+    let tar = dir.join("names.tar");
+    let zip = dir.join("names.zip");
+    let mut tar_builder = ArchiveBuilder::new(ArchiveKind::Tar);
+    let mut zip_builder = ArchiveBuilder::new(ArchiveKind::Zip).dir("docs");
+    for (name, content) in FILES {
+        tar_builder = tar_builder.file(name, content);
+        zip_builder = zip_builder.file(name, content);
+    }
+    tar_builder.build(&tar).unwrap();
+    zip_builder.build(&zip).unwrap();
+    [tar, zip]
+}
+
+fn folding(case_collisions: CaseCollisions) -> ExtractOptions {
+    ExtractOptions {
+        case_collisions,
+        fold_case: Some(true),
+        ..ExtractOptions::default()
+    }
+}
+
+#[test]
+fn test_later_file_is_renamed_by_default() {
+    let temp_dir = TempDir::new().unwrap();
+    for archive in create_archives(temp_dir.path()) {
+        let output = temp_dir.path().join("renamed");
+        let _ = fs::remove_dir_all(&output);
+        let outcome =
+            extract_with_options(&archive, &output, folding(CaseCollisions::default())).unwrap();
+
+        assert!(outcome.is_complete(), "{:?}", archive);
+        assert_eq!(outcome.case_collisions.len(), 1, "{:?}", archive);
+        let collision = &outcome.case_collisions[0];
+        assert_eq!(collision.path, Path::new("docs/readme.md"));
+        assert_eq!(collision.earlier, Path::new("docs/README.md"));
+        assert_eq!(
+            collision.renamed_to.as_deref(),
+            Some(Path::new("docs/readme (1).md"))
+        );
+        assert_eq!(
+            fs::read_to_string(output.join("docs/README.md")).unwrap(),
+            "upper"
+        );
+        assert_eq!(
+            fs::read_to_string(output.join("docs/readme (1).md")).unwrap(),
+            "lower"
+        );
+        assert!(outcome.to_string().ends_with(", 1 case collisions"));
+    }
+}
+
+#[test]
+fn test_last_wins_replaces_earlier_file() {
+    let temp_dir = TempDir::new().unwrap();
+    for archive in create_archives(temp_dir.path()) {
+        let output = temp_dir.path().join("last");
+        let _ = fs::remove_dir_all(&output);
+        let outcome =
+            extract_with_options(&archive, &output, folding(CaseCollisions::LastWins)).unwrap();
+
+        assert!(outcome.is_complete(), "{:?}", archive);
+        assert!(!output.join("docs/README.md").exists());
+        assert_eq!(
+            fs::read_to_string(output.join("docs/readme.md")).unwrap(),
+            "lower"
+        );
+        assert!(!outcome
+            .done
+            .iter()
+            .any(|path| path == Path::new("docs/README.md")));
+        assert_eq!(outcome.skipped.len(), 1);
+        assert_eq!(outcome.skipped[0].reason, SkipReason::CaseCollision);
+        assert_eq!(outcome.case_collisions[0].renamed_to, None);
+    }
+}
+
+#[test]
+fn test_error_policy_writes_nothing() {
+    let temp_dir = TempDir::new().unwrap();
+    for archive in create_archives(temp_dir.path()) {
+        let output = temp_dir.path().join("refused");
+        let _ = fs::remove_dir_all(&output);
+        let result = extract_with_options(&archive, &output, folding(CaseCollisions::Error));
+
+        assert!(result.is_err(), "{:?}", archive);
+        assert!(!output.join("docs").exists(), "{:?}", archive);
+    }
+}
+
+#[test]
+fn test_collisions_created_by_strip_components_are_found() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("stripped.tar");
+    ArchiveBuilder::new(ArchiveKind::Tar)
+        .file("a/Makefile", "ok")
+        .file("b/makefile", "ok")
+        .build(&archive)
+        .unwrap();
+
+    let output = temp_dir.path().join("output");
+    let options = ExtractOptions {
+        strip_components: Some(1),
+        ..folding(CaseCollisions::Error)
+    };
+    assert!(extract_with_options(&archive, &output, options).is_err());
+    assert!(!output.join("Makefile").exists());
+}
+
+#[test]
+fn test_case_sensitive_output_keeps_both_names() {
+    let temp_dir = TempDir::new().unwrap();
+    let [tar, _] = create_archives(temp_dir.path());
+    let output = temp_dir.path().join("output");
+    let options = ExtractOptions {
+        case_collisions: CaseCollisions::Error,
+        fold_case: Some(false),
+        ..ExtractOptions::default()
+    };
+    let outcome = extract_with_options(&tar, &output, options).unwrap();
+
+    assert!(outcome.case_collisions.is_empty());
+    assert_eq!(outcome.done.len(), 3);
+}
+
+#[test]
+fn test_find_case_collisions_ignores_directories() {
+    let temp_dir = TempDir::new().unwrap();
+    let [_, zip] = create_archives(temp_dir.path());
+    let entries = inspect(&zip).unwrap();
+
+    let collisions = find_case_collisions(&entries);
+    assert_eq!(collisions.len(), 1);
+    assert_eq!(collisions[0].path, Path::new("docs/readme.md"));
+}
+
+#[test]
+fn test_policy_names() {
+    for policy in [
+        CaseCollisions::Error,
+        CaseCollisions::Rename,
+        CaseCollisions::LastWins,
+    ] {
+        assert_eq!(policy.name().parse::<CaseCollisions>().unwrap(), policy);
+    }
+    assert_eq!(
+        "Last_Wins".parse::<CaseCollisions>().unwrap(),
+        CaseCollisions::LastWins
+    );
+    assert!("first-wins".parse::<CaseCollisions>().is_err());
+}
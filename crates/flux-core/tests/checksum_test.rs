@@ -0,0 +1,105 @@
+//! Tests for checksums of archive entries
+
+use flux_core::archive::{checksums, pack_multiple, EntryChecksum, HashAlgorithm, PackOptions};
+use flux_core::progress::NoProgress;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn pack(dir: &Path, format: &str) -> PathBuf {
+    let source = dir.join("source");
+    fs::create_dir_all(source.join("docs/empty")).unwrap();
+    fs::write(source.join("readme.txt"), "hello").unwrap();
+    let noise: Vec<u8> = (0..200_000u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect();
+    fs::write(source.join("docs/data.bin"), noise).unwrap();
+
+    let archive = dir.join(format!("out.{}", format));
+    pack_multiple(
+        &[source.join("readme.txt"), source.join("docs")],
+        &archive,
+        format,
+        Some(&source),
+        &PackOptions::default(),
+        &NoProgress,
+    )
+    .unwrap();
+    archive
+}
+
+fn sha256(path: &Path) -> String {
+    format!("{:x}", Sha256::digest(fs::read(path).unwrap()))
+}
+
+#[test]
+fn test_checksums_match_source_files() {
+    for format in ["zip", "tar", "tar.gz", "tar.zst", "tar.xz", "tar.br"] {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = pack(temp_dir.path(), format);
+        let source = temp_dir.path().join("source");
+
+        let mut sums = checksums(&archive, HashAlgorithm::Sha256).unwrap();
+        sums.sort_by(|a, b| a.path.cmp(&b.path));
+
+        // Directories have no checksum
+        let paths: Vec<_> = sums.iter().map(|sum| sum.path.clone()).collect();
+        assert_eq!(
+            paths,
+            [PathBuf::from("docs/data.bin"), PathBuf::from("readme.txt")],
+            "{}",
+            format
+        );
+        for sum in &sums {
+            assert_eq!(sum.digest, sha256(&source.join(&sum.path)), "{}", format);
+        }
+        assert_eq!(sums[0].size, 200_000, "{}", format);
+    }
+}
+
+#[test]
+fn test_algorithms() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = pack(temp_dir.path(), "tar.zst");
+
+    let digest = |algorithm: &str| {
+        checksums(&archive, algorithm.parse().unwrap())
+            .unwrap()
+            .into_iter()
+            .find(|sum| sum.path == Path::new("readme.txt"))
+            .unwrap()
+            .digest
+    };
+    assert_eq!(digest("md5"), "5d41402abc4b2a76b9719d911017c592");
+    assert_eq!(digest("SHA1"), "aaf4c61ddcc5e8a2dabede0f3b482cd9aea9434d");
+    assert_eq!(
+        digest("sha-256"),
+        "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+    );
+    assert_eq!(digest("sha512").len(), 128);
+    assert_eq!(
+        digest("blake3"),
+        blake3::hash(b"hello").to_hex().to_string()
+    );
+    assert_eq!(
+        digest("xxh3"),
+        format!("{:016x}", xxhash_rust::xxh3::xxh3_64(b"hello"))
+    );
+
+    assert!("crc32".parse::<HashAlgorithm>().is_err());
+}
+
+#[test]
+fn test_checksum_file_lines() {
+    let entry = |path: &str| EntryChecksum {
+        path: PathBuf::from(path),
+        size: 0,
+        digest: "abc123".to_string(),
+    };
+
+    assert_eq!(entry("docs/a.txt").line(), "abc123  docs/a.txt");
+    // Escaped the way sha256sum writes such names
+    assert_eq!(entry("two\nlines").line(), "\\abc123  two\\nlines");
+    assert_eq!(entry("back\\slash").line(), "\\abc123  back\\\\slash");
+}
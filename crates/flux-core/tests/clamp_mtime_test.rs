@@ -0,0 +1,95 @@
+//! Tests for clamping stored timestamps, as with `SOURCE_DATE_EPOCH`
+
+use filetime::FileTime;
+use flux_core::archive::{inspect, pack_with_strategy, ArchiveMetadata, PackOptions};
+use flux_testing::fixtures::SourceTree;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use zip::ZipArchive;
+
+/// 2023-11-14 22:13:20 UTC
+const EPOCH: u64 = 1_700_000_000;
+/// Well before `EPOCH`
+const OLD: i64 = 1_500_000_000;
+
+fn create_source(dir: &Path) -> PathBuf {
+    SourceTree::new()
+        .file("new.txt", "new")
+        .file("old.txt", "old")
+        .mtime("old.txt", OLD as u64)
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+fn clamped() -> PackOptions {
+    PackOptions {
+        clamp_mtime: Some(EPOCH),
+        ..PackOptions::default()
+    }
+}
+
+#[test]
+fn test_tar_timestamps_are_clamped() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let archive = temp_dir.path().join("out.tar.gz");
+    pack_with_strategy(&source, &archive, Some("tar.gz"), clamped()).unwrap();
+
+    let entries = inspect(&archive).unwrap();
+    let mtime = |name: &str| {
+        entries
+            .iter()
+            .find(|entry| entry.path.ends_with(name))
+            .and_then(|entry| entry.mtime)
+            .unwrap()
+    };
+    assert_eq!(mtime("new.txt"), EPOCH as i64);
+    assert_eq!(mtime("old.txt"), OLD);
+}
+
+#[test]
+fn test_zip_timestamps_are_clamped() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let archive = temp_dir.path().join("out.zip");
+    pack_with_strategy(&source, &archive, Some("zip"), clamped()).unwrap();
+
+    let mut zip = ZipArchive::new(File::open(&archive).unwrap()).unwrap();
+    let mut modified = |name: &str| {
+        let time = zip.by_name(name).unwrap().last_modified().unwrap();
+        (time.year(), time.month(), time.day(), time.hour())
+    };
+    assert_eq!(modified("new.txt"), (2023, 11, 14, 22));
+    assert_eq!(modified("old.txt"), (2017, 7, 14, 2));
+}
+
+#[test]
+fn test_clamped_archives_are_reproducible() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    for format in ["tar.gz", "tar.zst", "zip"] {
+        let options = || PackOptions {
+            metadata: Some(ArchiveMetadata {
+                created: Some(i64::MAX),
+                ..ArchiveMetadata::default()
+            }),
+            ..clamped()
+        };
+        let first = temp_dir.path().join(format!("first.{}", format));
+        pack_with_strategy(&source, &first, Some(format), options()).unwrap();
+
+        // Touching the inputs changes nothing once their times are clamped
+        filetime::set_file_mtime(source.join("new.txt"), FileTime::now()).unwrap();
+        let second = temp_dir.path().join(format!("second.{}", format));
+        pack_with_strategy(&source, &second, Some(format), options()).unwrap();
+
+        assert_eq!(
+            fs::read(&first).unwrap(),
+            fs::read(&second).unwrap(),
+            "{}",
+            format
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use flux_core::archive::HashAlgorithm;
 use flux_core::config::{CompressionRule, Config};
 use std::fs;
 use tempfile::TempDir;
@@ -104,7 +105,7 @@ fn test_rule_priority_sorting() {
     ];
 
     let mut sorted_rules = rules.clone();
-    sorted_rules.sort_by(|a, b| b.priority.cmp(&a.priority));
+    sorted_rules.sort_by_key(|rule| std::cmp::Reverse(rule.priority));
 
     assert_eq!(sorted_rules[0].name, "high");
     assert_eq!(sorted_rules[1].name, "medium");
@@ -132,3 +133,182 @@ fn test_config_load_save() {
     // Clean up
     std::env::remove_var("XDG_CONFIG_HOME");
 }
+
+#[test]
+fn test_exclude_patterns() {
+    // The commented default file lists the same patterns as the built-in defaults
+    let config: Config = toml::from_str(&Config::default_config_content()).unwrap();
+    assert_eq!(
+        config.exclude.vcs_patterns,
+        Config::default().exclude.vcs_patterns
+    );
+    assert!(config.exclude.vcs_patterns.contains(&".git".to_string()));
+
+    // Older config files without the section fall back to the defaults
+    let mut value: toml::Value = toml::from_str(&Config::default_config_content()).unwrap();
+    value.as_table_mut().unwrap().remove("exclude");
+    let config: Config = toml::from_str(&toml::to_string(&value).unwrap()).unwrap();
+    assert!(config
+        .exclude
+        .vcs_patterns
+        .contains(&"node_modules".to_string()));
+}
+
+#[test]
+fn test_set_overrides_values() {
+    let mut config = Config::default();
+
+    config.set("compression.default_level", "19").unwrap();
+    config.set("compression.default_algorithm", "xz").unwrap();
+    config.set("archive.follow_symlinks", "true").unwrap();
+    config.set("strategy.large_file_threshold", "1024").unwrap();
+    config.set("rules.0.level", "5").unwrap();
+    config
+        .set("exclude.vcs_patterns", r#"[".git", "target"]"#)
+        .unwrap();
+    // String fields keep the text as given, even when it looks like a number
+    config.set("archive.default_format", "7").unwrap();
+
+    assert_eq!(config.compression.default_level, 19);
+    assert_eq!(config.compression.default_algorithm, "xz");
+    assert!(config.archive.follow_symlinks);
+    assert_eq!(config.strategy.large_file_threshold, Some(1024));
+    assert_eq!(config.rules[0].level, Some(5));
+    assert_eq!(config.exclude.vcs_patterns, [".git", "target"]);
+    assert_eq!(config.archive.default_format, "7");
+}
+
+#[test]
+fn test_set_adds_cloud_profiles() {
+    let mut config = Config::default();
+    assert!(config.cloud.profiles.is_empty());
+
+    config
+        .set("cloud.profiles.work.endpoint", "http://localhost:9000")
+        .unwrap();
+    config
+        .set("cloud.profiles.work.url", "s3://backups/work")
+        .unwrap();
+    let work = &config.cloud.profiles["work"];
+    assert_eq!(work.url, "s3://backups/work");
+    assert_eq!(work.endpoint.as_deref(), Some("http://localhost:9000"));
+
+    assert_eq!(
+        config.cloud.endpoint_for("s3://backups/work/2024.tar.zst"),
+        Some("http://localhost:9000")
+    );
+    assert_eq!(config.cloud.endpoint_for("s3://backups/home.tar.zst"), None);
+    assert_eq!(
+        config.cloud.endpoint_for("s3://backups/work"),
+        Some("http://localhost:9000")
+    );
+    // A sibling whose name starts the same is not below the profile
+    assert_eq!(
+        config
+            .cloud
+            .endpoint_for("s3://backups/workshop/2024.tar.zst"),
+        None
+    );
+
+    assert_eq!(config.cloud.encryption_key_for("s3://backups/work"), None);
+    config
+        .set("cloud.profiles.work.encryption_key", "offsite")
+        .unwrap();
+    assert_eq!(
+        config
+            .cloud
+            .encryption_key_for("s3://backups/work/2024.tar.zst"),
+        Some("offsite")
+    );
+
+    let reloaded: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+    assert_eq!(reloaded.cloud.profiles["work"].url, "s3://backups/work");
+    assert_eq!(
+        reloaded.cloud.profiles["work"].encryption_key.as_deref(),
+        Some("offsite")
+    );
+}
+
+#[test]
+fn test_hashing_takes_algorithm_names() {
+    let mut config = Config::default();
+    assert_eq!(config.hashing.manifest, HashAlgorithm::Blake3);
+
+    config.set("hashing.manifest", "xxh3").unwrap();
+    config.set("hashing.dedupe", "b3").unwrap();
+    assert_eq!(config.hashing.manifest, HashAlgorithm::Xxh3);
+    assert_eq!(config.hashing.dedupe, HashAlgorithm::Blake3);
+    assert!(config.set("hashing.manifest", "crc32").is_err());
+}
+
+#[test]
+fn test_temp_dir_survives_a_round_trip() {
+    let mut config = Config::default();
+    assert!(!toml::to_string(&config).unwrap().contains("temp_dir"));
+
+    config.set("temp_dir", "/var/tmp/flux").unwrap();
+    let reloaded: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+    assert_eq!(
+        reloaded.temp_dir.as_deref(),
+        Some(std::path::Path::new("/var/tmp/flux"))
+    );
+}
+
+#[test]
+fn test_scratch_quota_takes_sizes() {
+    let mut config = Config::default();
+    assert_eq!(config.scratch_quota, None);
+
+    config.set("scratch_quota", "2GiB").unwrap();
+    assert_eq!(config.scratch_quota, Some(2 * 1024 * 1024 * 1024));
+    config.set("scratch_quota", "4096").unwrap();
+    assert_eq!(config.scratch_quota, Some(4096));
+    assert!(config.set("scratch_quota", "lots").is_err());
+
+    // As written in a configuration file
+    let file = format!(
+        "scratch_quota = \"1MiB\"\n{}",
+        toml::to_string(&Config::default()).unwrap()
+    );
+    let loaded: Config = toml::from_str(&file).unwrap();
+    assert_eq!(loaded.scratch_quota, Some(1024 * 1024));
+}
+
+#[test]
+fn test_set_rejects_bad_overrides() {
+    let mut config = Config::default();
+
+    for key in [
+        "strategy.default_level",
+        "compression",
+        "compression.default_level.x",
+        "compression.extra.level",
+        "cloud.profiles.work.bucket",
+        "rules.99.level",
+        "",
+        "compression..default_level",
+    ] {
+        assert!(config.set(key, "1").is_err(), "{}", key);
+    }
+    assert!(config.set("compression.default_level", "high").is_err());
+    assert!(config.set("performance.threads", "-1").is_err());
+
+    // A rejected override leaves the configuration untouched
+    assert_eq!(config.compression.default_level, 3);
+}
+
+#[test]
+fn test_parse_override() {
+    use flux_core::config::parse_override;
+
+    assert_eq!(
+        parse_override("compression.default_level=19").unwrap(),
+        ("compression.default_level".to_string(), "19".to_string())
+    );
+    assert_eq!(
+        parse_override("archive.default_format=a=b").unwrap(),
+        ("archive.default_format".to_string(), "a=b".to_string())
+    );
+    assert!(parse_override("compression.default_level").is_err());
+    assert!(parse_override("=19").is_err());
+}
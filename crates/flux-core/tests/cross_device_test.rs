@@ -0,0 +1,57 @@
+//! Tests for moving files between file systems
+
+use flux_core::utils::{move_path, same_file_system};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// A directory on another file system than the default temporary one,
+/// where the machine has one
+fn other_file_system(base: &Path) -> Option<TempDir> {
+    let shm = Path::new("/dev/shm");
+    if !shm.is_dir() || same_file_system(shm, base) != Some(false) {
+        return None;
+    }
+    TempDir::new_in(shm).ok()
+}
+
+#[test]
+fn test_same_file_system_of_missing_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    let missing = temp_dir.path().join("not/yet/there.txt");
+    assert_ne!(same_file_system(temp_dir.path(), &missing), Some(false));
+}
+
+#[test]
+fn test_move_path_across_file_systems() {
+    let temp_dir = TempDir::new().unwrap();
+    let Some(other) = other_file_system(temp_dir.path()) else {
+        eprintln!("No second file system to move to, skipping");
+        return;
+    };
+
+    let from = other.path().join("staged");
+    fs::create_dir_all(from.join("docs")).unwrap();
+    fs::write(from.join("docs/notes.txt"), "notes").unwrap();
+    fs::write(from.join("readme.txt"), "read me").unwrap();
+
+    let to = temp_dir.path().join("output");
+    move_path(&from, &to).unwrap();
+
+    assert!(!from.exists());
+    assert_eq!(
+        fs::read_to_string(to.join("docs/notes.txt")).unwrap(),
+        "notes"
+    );
+    assert_eq!(
+        fs::read_to_string(to.join("readme.txt")).unwrap(),
+        "read me"
+    );
+
+    // A file onto one already there replaces it
+    let file = other.path().join("new.txt");
+    fs::write(&file, "new").unwrap();
+    move_path(&file, &to.join("readme.txt")).unwrap();
+    assert_eq!(fs::read_to_string(to.join("readme.txt")).unwrap(), "new");
+    assert!(!file.exists());
+}
@@ -0,0 +1,128 @@
+//! Tests for finding and storing once the duplicate files of an archive
+
+use flux_core::archive::{
+    dedupe_report, deduplicate, extract, extract_with_options, pack_with_strategy, ExtractOptions,
+    PackOptions,
+};
+use flux_core::Error;
+use flux_testing::fixtures::SourceTree;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// A directory with one file copied twice, another copied once, a unique
+/// file and two empty ones
+fn create_source(dir: &Path) -> PathBuf {
+    let photo: Vec<u8> = (0..50_000u32).map(|i| (i * 7 % 251) as u8).collect();
+    let notes = b"the same notes, twice".repeat(100);
+    SourceTree::new()
+        .file("photo.jpg", &photo)
+        .file("copies/photo.jpg", &photo)
+        .file("copies/deeper/photo-2.jpg", &photo)
+        .file("notes.txt", &notes)
+        .file("copies/notes.txt", &notes)
+        .file("unique.txt", "only once")
+        .file("empty", "")
+        .file("copies/empty", "")
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+#[test]
+fn test_report_groups_duplicates() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    for format in ["tar.zst", "zip"] {
+        let archive = temp_dir.path().join(format!("dupes.{}", format));
+        pack_with_strategy(&source, &archive, Some(format), PackOptions::default()).unwrap();
+
+        let report = dedupe_report(&archive).unwrap();
+        assert_eq!(report.files, 8, "{}", format);
+        assert_eq!(report.groups.len(), 2, "{}", format);
+        assert_eq!(report.groups[0].paths.len(), 3, "{}", format);
+        assert_eq!(report.groups[0].size, 50_000);
+        assert_eq!(report.duplicates(), 3);
+        assert_eq!(report.savings(), 2 * 50_000 + 2_100);
+    }
+}
+
+#[test]
+fn test_rewrite_stores_duplicates_once() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    for format in ["tar", "tar.gz"] {
+        let archive = temp_dir.path().join(format!("dupes.{}", format));
+        pack_with_strategy(&source, &archive, Some(format), PackOptions::default()).unwrap();
+        let deduped = temp_dir.path().join(format!("deduped.{}", format));
+
+        let report = deduplicate(&archive, Some(&deduped)).unwrap();
+        assert_eq!(report.duplicates(), 3, "{}", format);
+        assert!(
+            fs::metadata(&deduped).unwrap().len() < fs::metadata(&archive).unwrap().len(),
+            "{}",
+            format
+        );
+        assert!(dedupe_report(&deduped).unwrap().groups.is_empty());
+
+        let output = temp_dir.path().join(format!("out-{}", format));
+        extract(&deduped, &output).unwrap();
+        for file in [
+            "photo.jpg",
+            "copies/photo.jpg",
+            "copies/deeper/photo-2.jpg",
+            "copies/notes.txt",
+            "unique.txt",
+            "copies/empty",
+        ] {
+            assert_eq!(
+                fs::read(output.join("source").join(file)).unwrap(),
+                fs::read(source.join(file)).unwrap(),
+                "{} in {}",
+                file,
+                format
+            );
+        }
+    }
+}
+
+#[test]
+fn test_rewrite_in_place() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let archive = temp_dir.path().join("dupes.tar.zst");
+    pack_with_strategy(&source, &archive, Some("tar.zst"), PackOptions::default()).unwrap();
+
+    deduplicate(&archive, None).unwrap();
+    let report = deduplicate(&archive, None).unwrap();
+    assert!(report.groups.is_empty());
+    assert_eq!(report.files, 5);
+
+    // Links are found under the names their targets are extracted to
+    let output = temp_dir.path().join("out");
+    let options = ExtractOptions {
+        strip_components: Some(1),
+        ..ExtractOptions::default()
+    };
+    extract_with_options(&archive, &output, options).unwrap();
+    assert_eq!(
+        fs::read(output.join("copies/deeper/photo-2.jpg")).unwrap(),
+        fs::read(source.join("photo.jpg")).unwrap()
+    );
+}
+
+#[test]
+fn test_rewrite_refuses_zip() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let archive = temp_dir.path().join("dupes.zip");
+    pack_with_strategy(&source, &archive, Some("zip"), PackOptions::default()).unwrap();
+    let before = fs::read(&archive).unwrap();
+
+    assert!(matches!(
+        deduplicate(&archive, None),
+        Err(Error::UnsupportedOperation(_))
+    ));
+    assert_eq!(fs::read(&archive).unwrap(), before);
+}
@@ -0,0 +1,109 @@
+//! Tests for telling archive formats from their content
+
+use flux_core::archive::{
+    checksums, detect_format, extract, extract_with_options, inspect, inspect_as,
+    pack_with_strategy, sniff_format, ExtractOptions, HashAlgorithm, PackOptions,
+};
+use flux_core::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Pack a small directory as `format`, then rename the archive to `name`
+fn create_misnamed(dir: &Path, format: &str, name: &str) -> PathBuf {
+    let source = dir.join("source");
+    fs::create_dir_all(source.join("docs")).unwrap();
+    fs::write(source.join("hello.txt"), "hello").unwrap();
+    fs::write(source.join("docs/guide.md"), "# Guide").unwrap();
+
+    let packed = dir.join(format!("packed.{}", format));
+    pack_with_strategy(&source, &packed, Some(format), PackOptions::default()).unwrap();
+    let archive = dir.join(name);
+    fs::rename(&packed, &archive).unwrap();
+    archive
+}
+
+#[test]
+fn test_extract_archives_without_their_extension() {
+    for (format, name) in [
+        ("tar.gz", "download(1)"),
+        ("zip", "release.tar.gz"),
+        ("tar.zst", "backup.zip"),
+        ("tar.xz", "data.bin"),
+        ("tar", "bundle.7z"),
+    ] {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = create_misnamed(temp_dir.path(), format, name);
+        assert_eq!(detect_format(&archive).unwrap(), format, "{}", name);
+
+        let output = temp_dir.path().join("out");
+        extract(&archive, &output).unwrap();
+        // Zip archives are packed without the folder itself
+        let root = if format == "zip" {
+            output.clone()
+        } else {
+            output.join("source")
+        };
+        assert_eq!(
+            fs::read_to_string(root.join("docs/guide.md")).unwrap(),
+            "# Guide",
+            "{} as {}",
+            format,
+            name
+        );
+        assert!(
+            inspect(&archive)
+                .unwrap()
+                .iter()
+                .any(|entry| entry.path.ends_with("docs/guide.md")),
+            "{}",
+            format
+        );
+        assert_eq!(
+            checksums(&archive, HashAlgorithm::Blake3).unwrap().len(),
+            2,
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_extension_is_the_fallback() {
+    let temp_dir = TempDir::new().unwrap();
+    // Brotli streams have no signature
+    let archive = create_misnamed(temp_dir.path(), "tar.br", "notes.tar.br");
+    assert_eq!(sniff_format(&archive), None);
+    assert_eq!(detect_format(&archive).unwrap(), "tar.br");
+
+    let text = temp_dir.path().join("notes.txt");
+    fs::write(&text, "not an archive").unwrap();
+    assert!(matches!(
+        detect_format(&text),
+        Err(Error::UnsupportedFormat(_))
+    ));
+}
+
+#[test]
+fn test_forced_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_misnamed(temp_dir.path(), "tar.br", "notes");
+    assert!(inspect(&archive).is_err());
+    assert!(inspect_as(&archive, Some("tar.br"))
+        .unwrap()
+        .iter()
+        .any(|entry| entry.path.ends_with("docs/guide.md")));
+
+    let output = temp_dir.path().join("out");
+    let options = ExtractOptions {
+        format: Some("br".to_string()),
+        ..ExtractOptions::default()
+    };
+    extract_with_options(&archive, &output, options).unwrap();
+    assert!(output.join("source/hello.txt").is_file());
+
+    assert!(matches!(
+        inspect_as(&archive, Some("rar")).unwrap_err().inner(),
+        Error::UnsupportedFormat(_)
+    ));
+}
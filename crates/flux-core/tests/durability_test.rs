@@ -0,0 +1,94 @@
+//! Tests for syncing extracted files and written archives to disk
+
+use flux_core::archive::extractor::ExtractEntryOptions;
+use flux_core::archive::{extract_with_options, ExtractOptions, PackOptions};
+use flux_core::durability::Durability;
+use flux_core::ops::{ExtractJob, PackJob};
+use flux_core::progress::NoProgress;
+use flux_core::ErrorCode;
+use flux_testing::fixtures::SourceTree;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn create_source(dir: &Path) -> std::path::PathBuf {
+    SourceTree::new()
+        .file("docs/notes.txt", "notes")
+        .file("readme.txt", "read me")
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+#[test]
+fn test_durability_names() {
+    for policy in [Durability::None, Durability::PerFile, Durability::Final] {
+        assert_eq!(policy.name().parse::<Durability>().unwrap(), policy);
+        assert_eq!(policy.to_string(), policy.name());
+    }
+    assert_eq!(
+        "PER_FILE".parse::<Durability>().unwrap(),
+        Durability::PerFile
+    );
+    assert_eq!(Durability::default(), Durability::None);
+
+    let err = "always".parse::<Durability>().unwrap_err();
+    assert_eq!(err.code(), ErrorCode::InvalidArgument);
+}
+
+#[test]
+fn test_extract_with_each_durability() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    for format in ["tar.gz", "zip"] {
+        let archive = temp_dir.path().join(format!("source.{}", format));
+        PackJob {
+            durability: Durability::Final,
+            ..PackJob::new(vec![source.clone()], &archive, PackOptions::default())
+        }
+        .run(&NoProgress)
+        .unwrap();
+        assert!(archive.is_file());
+
+        for policy in [Durability::None, Durability::PerFile, Durability::Final] {
+            let output = temp_dir.path().join(format!("{}-{}", format, policy));
+            let options = ExtractOptions {
+                durability: policy,
+                ..Default::default()
+            };
+            let outcome = extract_with_options(&archive, &output, options).unwrap();
+            assert!(outcome.is_complete(), "{} with {}", format, policy);
+            assert_eq!(
+                fs::read_to_string(output.join("source/docs/notes.txt")).unwrap(),
+                "notes"
+            );
+            assert_eq!(
+                fs::read_to_string(output.join("source/readme.txt")).unwrap(),
+                "read me"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_extract_job_with_durability() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let archive = temp_dir.path().join("source.tar");
+    PackJob::new(vec![source], &archive, PackOptions::default())
+        .run(&NoProgress)
+        .unwrap();
+
+    let output = temp_dir.path().join("output");
+    let outcome = ExtractJob {
+        durability: Durability::PerFile,
+        ..ExtractJob::new(&archive, &output, ExtractEntryOptions::default())
+    }
+    .run(&NoProgress, |_, _| None)
+    .unwrap();
+    assert!(outcome.is_complete());
+    assert_eq!(
+        fs::read_to_string(output.join("source/readme.txt")).unwrap(),
+        "read me"
+    );
+}
@@ -0,0 +1,119 @@
+//! Tests for extracting password-protected zip entries
+
+use flux_core::archive::extractor::{ExtractEntryOptions, Extractor};
+use flux_core::archive::zip_extractor::ZipExtractor;
+use flux_core::Error;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+use tempfile::TempDir;
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, ZipWriter};
+
+const PASSWORD: &str = "correct horse";
+
+/// Write a zip with one AES-encrypted and one plain entry
+fn create_encrypted_zip(path: &Path) {
+    let mut writer = ZipWriter::new(File::create(path).unwrap());
+
+    writer
+        .start_file(
+            "secret.txt",
+            SimpleFileOptions::default().with_aes_encryption(AesMode::Aes256, PASSWORD),
+        )
+        .unwrap();
+    writer.write_all(b"top secret").unwrap();
+
+    writer
+        .start_file("plain.txt", SimpleFileOptions::default())
+        .unwrap();
+    writer.write_all(b"nothing to hide").unwrap();
+
+    writer.finish().unwrap();
+}
+
+fn options(password: Option<&str>) -> ExtractEntryOptions {
+    ExtractEntryOptions {
+        overwrite: true,
+        password: password.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_encrypted_entries_are_listed() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("encrypted.zip");
+    create_encrypted_zip(&archive);
+
+    let entries: Vec<_> = ZipExtractor::new()
+        .entries(&archive)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].path, Path::new("secret.txt"));
+    assert_eq!(entries[0].size, 10);
+}
+
+#[test]
+fn test_encrypted_entry_requires_password() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("encrypted.zip");
+    let output = temp_dir.path().join("out");
+    create_encrypted_zip(&archive);
+
+    let extractor = ZipExtractor::new();
+    let entries: Vec<_> = extractor.entries(&archive).unwrap().flatten().collect();
+
+    let result = extractor.extract_entry(&archive, &entries[0], &output, options(None));
+    assert!(matches!(result, Err(Error::PasswordRequired(_))));
+
+    // Plain entries still extract without a password
+    extractor
+        .extract_entry(&archive, &entries[1], &output, options(None))
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(output.join("plain.txt")).unwrap(),
+        "nothing to hide"
+    );
+}
+
+#[test]
+fn test_encrypted_entry_with_wrong_password() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("encrypted.zip");
+    let output = temp_dir.path().join("out");
+    create_encrypted_zip(&archive);
+
+    let extractor = ZipExtractor::new();
+    let entries: Vec<_> = extractor.entries(&archive).unwrap().flatten().collect();
+
+    let result = extractor.extract_entry(&archive, &entries[0], &output, options(Some("wrong")));
+    assert!(matches!(result, Err(Error::InvalidPassword(_))));
+}
+
+#[test]
+fn test_encrypted_entry_with_password() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("encrypted.zip");
+    let output = temp_dir.path().join("out");
+    create_encrypted_zip(&archive);
+
+    let extractor = ZipExtractor::new();
+    for entry in extractor.entries(&archive).unwrap() {
+        extractor
+            .extract_entry(&archive, &entry.unwrap(), &output, options(Some(PASSWORD)))
+            .unwrap();
+    }
+
+    assert_eq!(
+        fs::read_to_string(output.join("secret.txt")).unwrap(),
+        "top secret"
+    );
+    assert_eq!(
+        fs::read_to_string(output.join("plain.txt")).unwrap(),
+        "nothing to hide"
+    );
+}
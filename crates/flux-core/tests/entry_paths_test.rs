@@ -0,0 +1,182 @@
+//! Tests for how entry names are worked out and moved with a prefix
+
+use flux_core::archive::incremental::pack_incremental;
+use flux_core::archive::paths::{check_prefix, rebase};
+use flux_core::archive::{inspect, pack_with_strategy, PackOptions};
+use flux_core::manifest::Manifest;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// A folder `project` with a file at the top and one in `src`
+fn create_project(dir: &Path) -> PathBuf {
+    let project = dir.join("project");
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::write(project.join("README.md"), "readme").unwrap();
+    fs::write(project.join("src/lib.rs"), "pub fn new() {}").unwrap();
+    project
+}
+
+/// Names in `archive`, directories with a trailing `/`, sorted
+fn names(archive: &Path) -> Vec<String> {
+    let mut names: Vec<String> = inspect(archive)
+        .unwrap()
+        .into_iter()
+        .map(|entry| {
+            let name = entry
+                .path
+                .to_string_lossy()
+                .trim_end_matches('/')
+                .to_string();
+            if entry.is_dir {
+                format!("{}/", name)
+            } else {
+                name
+            }
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+fn rebased(name: &str, strip_prefix: Option<&str>, prefix: Option<&str>) -> Option<PathBuf> {
+    rebase(
+        Path::new(name),
+        strip_prefix.map(Path::new),
+        prefix.map(Path::new),
+    )
+}
+
+#[test]
+fn test_rebase() {
+    assert_eq!(
+        rebased("project/src/lib.rs", Some("project"), None),
+        Some(PathBuf::from("src/lib.rs"))
+    );
+    // Whole components only, however the prefix is written
+    assert_eq!(
+        rebased("project2/lib.rs", Some("project"), None),
+        Some(PathBuf::from("project2/lib.rs"))
+    );
+    assert_eq!(
+        rebased("project/lib.rs", Some("./project/"), None),
+        Some(PathBuf::from("lib.rs"))
+    );
+    assert_eq!(
+        rebased("project/lib.rs", Some("project"), Some("release/v2")),
+        Some(PathBuf::from("release/v2/lib.rs"))
+    );
+    assert_eq!(
+        rebased("other/lib.rs", Some("project"), Some("release")),
+        Some(PathBuf::from("release/other/lib.rs"))
+    );
+    // The stripped folder itself
+    assert_eq!(rebased("project", Some("project"), None), None);
+    assert_eq!(
+        rebased("project", Some("project"), Some("release")),
+        Some(PathBuf::from("release"))
+    );
+}
+
+#[test]
+fn test_prefix_must_stay_inside() {
+    assert!(check_prefix(Path::new("release/v2")).is_ok());
+    assert!(check_prefix(Path::new("../release")).is_err());
+    assert!(check_prefix(Path::new("/release")).is_err());
+    assert!(check_prefix(Path::new("")).is_err());
+    assert!(check_prefix(Path::new(".")).is_err());
+}
+
+#[test]
+fn test_pack_strips_prefix() {
+    for format in ["tar.gz", "zip"] {
+        let temp_dir = TempDir::new().unwrap();
+        let project = create_project(temp_dir.path());
+        let archive = temp_dir.path().join(format!("project.{}", format));
+        let options = PackOptions {
+            strip_prefix: Some(PathBuf::from("project")),
+            ..PackOptions::default()
+        };
+        pack_with_strategy(&project, &archive, Some(format), options).unwrap();
+
+        assert_eq!(
+            names(&archive),
+            ["README.md", "src/", "src/lib.rs"],
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_pack_moves_entries_below_prefix() {
+    for format in ["tar.zst", "zip"] {
+        let temp_dir = TempDir::new().unwrap();
+        let project = create_project(temp_dir.path());
+        let archive = temp_dir.path().join(format!("project.{}", format));
+        let options = PackOptions {
+            strip_prefix: Some(PathBuf::from("project")),
+            prefix: Some(PathBuf::from("release/v2")),
+            ..PackOptions::default()
+        };
+        pack_with_strategy(&project, &archive, Some(format), options).unwrap();
+
+        assert_eq!(
+            names(&archive),
+            [
+                "release/v2/README.md",
+                "release/v2/src/",
+                "release/v2/src/lib.rs"
+            ],
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_single_file_named_the_prefix_keeps_its_name() {
+    let temp_dir = TempDir::new().unwrap();
+    let file = temp_dir.path().join("notes.txt");
+    fs::write(&file, "notes").unwrap();
+    let archive = temp_dir.path().join("notes.tar");
+    let options = PackOptions {
+        strip_prefix: Some(PathBuf::from("notes.txt")),
+        ..PackOptions::default()
+    };
+    pack_with_strategy(&file, &archive, Some("tar"), options).unwrap();
+    assert_eq!(names(&archive), ["notes.txt"]);
+}
+
+#[test]
+fn test_escaping_prefix_is_refused() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = create_project(temp_dir.path());
+    let archive = temp_dir.path().join("project.tar");
+    let options = PackOptions {
+        prefix: Some(PathBuf::from("../outside")),
+        ..PackOptions::default()
+    };
+    assert!(pack_with_strategy(&project, &archive, Some("tar"), options).is_err());
+    assert!(!archive.exists());
+}
+
+#[test]
+fn test_incremental_pack_uses_prefix() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = create_project(temp_dir.path());
+    let manifest = temp_dir.path().join("base.manifest.json");
+    Manifest::from_directory(&project)
+        .unwrap()
+        .save(&manifest)
+        .unwrap();
+    fs::write(project.join("src/lib.rs"), "pub fn changed() {}").unwrap();
+
+    let archive = temp_dir.path().join("changes.tar");
+    let options = PackOptions {
+        prefix: Some(PathBuf::from("changes")),
+        ..PackOptions::default()
+    };
+    pack_incremental(&project, &archive, &manifest, options).unwrap();
+    assert_eq!(names(&archive), ["changes/src/lib.rs"]);
+}
@@ -0,0 +1,165 @@
+//! Tests for rewriting entries with an entry transform while packing
+
+use flux_core::archive::{
+    create_extractor, inspect, pack_with_progress, EntryTransform, PackOptions,
+};
+use flux_core::progress::NoProgress;
+use flux_testing::fixtures::SourceTree;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+use zip::ZipArchive;
+
+const FORMATS: [&str; 2] = ["tar.zst", "zip"];
+
+fn create_source(dir: &Path) -> PathBuf {
+    SourceTree::new()
+        .file("bin/run.sh", "#!/bin/sh")
+        .file("readme.txt", "read me")
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+fn transformed(transform: EntryTransform) -> PackOptions {
+    PackOptions {
+        transform: Some(transform),
+        ..PackOptions::default()
+    }
+}
+
+#[test]
+fn test_paths_are_rewritten() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    for format in FORMATS {
+        let options = transformed(EntryTransform::new(|entry| {
+            entry.path = Path::new("release").join(&entry.path);
+        }));
+        let archive = temp_dir.path().join(format!("release.{}", format));
+        let outcome =
+            pack_with_progress(&source, &archive, Some(format), options, &NoProgress).unwrap();
+        assert!(outcome.is_complete(), "{}: {:?}", format, outcome.failed);
+        assert!(outcome.done.iter().all(|path| path.starts_with("release")));
+
+        let entries = inspect(&archive).unwrap();
+        assert!(!entries.is_empty());
+        for entry in &entries {
+            assert!(
+                entry.path.starts_with("release"),
+                "{}: {:?}",
+                format,
+                entry.path
+            );
+        }
+        assert!(entries
+            .iter()
+            .any(|entry| entry.path.ends_with("bin/run.sh")));
+        assert!(entries
+            .iter()
+            .any(|entry| entry.is_dir && entry.path.ends_with("bin")));
+    }
+}
+
+/// 2001-09-09 01:46:40 UTC
+const MTIME: u64 = 1_000_000_000;
+
+#[test]
+fn test_mode_and_mtime_are_rewritten() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let options = || {
+        transformed(EntryTransform::new(|entry| {
+            entry.mtime = Some(MTIME);
+            if !entry.is_dir() {
+                entry.mode = Some(0o600);
+            }
+        }))
+    };
+
+    for format in FORMATS {
+        let archive = temp_dir.path().join(format!("modes.{}", format));
+        pack_with_progress(&source, &archive, Some(format), options(), &NoProgress).unwrap();
+
+        for entry in inspect(&archive).unwrap() {
+            if !entry.is_dir {
+                assert_eq!(
+                    entry.mode.map(|mode| mode & 0o7777),
+                    Some(0o600),
+                    "{}: {:?}",
+                    format,
+                    entry.path
+                );
+            }
+        }
+    }
+
+    for entry in inspect(temp_dir.path().join("modes.tar.zst")).unwrap() {
+        assert_eq!(entry.mtime, Some(MTIME as i64), "{:?}", entry.path);
+    }
+    let mut zip = ZipArchive::new(File::open(temp_dir.path().join("modes.zip")).unwrap()).unwrap();
+    for i in 0..zip.len() {
+        let time = zip.by_index(i).unwrap().last_modified().unwrap();
+        assert_eq!(
+            (
+                time.year(),
+                time.month(),
+                time.day(),
+                time.hour(),
+                time.minute()
+            ),
+            (2001, 9, 9, 1, 46)
+        );
+    }
+}
+
+#[test]
+fn test_owner_is_rewritten_in_tar() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let archive = temp_dir.path().join("owned.tar");
+    let options = transformed(EntryTransform::new(|entry| {
+        entry.uid = Some(4321);
+        entry.gid = Some(8765);
+    }));
+    pack_with_progress(&source, &archive, Some("tar"), options, &NoProgress).unwrap();
+
+    let extractor = create_extractor(&archive).unwrap();
+    for entry in extractor.entries(&archive).unwrap() {
+        let entry = entry.unwrap();
+        assert_eq!(entry.uid, Some(4321), "{:?}", entry.path);
+        assert_eq!(entry.gid, Some(8765), "{:?}", entry.path);
+    }
+}
+
+#[test]
+fn test_transform_sees_every_entry_once() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    for format in FORMATS {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let record = Arc::clone(&seen);
+        let options = transformed(EntryTransform::new(move |entry| {
+            record.lock().unwrap().push((
+                entry.path.clone(),
+                entry.source().to_path_buf(),
+                entry.is_dir(),
+            ));
+        }));
+        let archive = temp_dir.path().join(format!("seen.{}", format));
+        pack_with_progress(&source, &archive, Some(format), options, &NoProgress).unwrap();
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), inspect(&archive).unwrap().len(), "{}", format);
+        let (_, run_source, run_is_dir) = seen
+            .iter()
+            .find(|(path, _, _)| path.ends_with("bin/run.sh"))
+            .unwrap();
+        assert_eq!(run_source, &source.join("bin/run.sh"));
+        assert!(!run_is_dir);
+        assert!(seen
+            .iter()
+            .any(|(path, _, is_dir)| *is_dir && path.ends_with("bin")));
+    }
+}
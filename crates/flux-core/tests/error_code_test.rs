@@ -0,0 +1,112 @@
+//! Tests for error codes and the context carried by errors
+
+use flux_core::archive::{extract_with_options, inspect, pack_with_strategy, PackOptions};
+use flux_core::{Error, ErrorCode, ExtractOptions};
+use std::fs;
+use std::io;
+use std::path::Path;
+use tempfile::TempDir;
+
+#[test]
+fn test_codes() {
+    let cases = [
+        (
+            Error::Io(io::Error::from(io::ErrorKind::NotFound)),
+            ErrorCode::NotFound,
+        ),
+        (
+            Error::Io(io::Error::from(io::ErrorKind::PermissionDenied)),
+            ErrorCode::PermissionDenied,
+        ),
+        (
+            Error::Io(io::Error::from(io::ErrorKind::UnexpectedEof)),
+            ErrorCode::Io,
+        ),
+        (Error::Zip("bad".to_string()), ErrorCode::Archive),
+        (Error::ConfigError("bad".to_string()), ErrorCode::Config),
+        (
+            Error::InvalidArgument("bad".to_string()),
+            ErrorCode::InvalidArgument,
+        ),
+        (
+            Error::PartialFailure { count: 2 },
+            ErrorCode::PartialFailure,
+        ),
+    ];
+    for (error, code) in cases {
+        assert_eq!(error.code(), code, "{}", error);
+    }
+    assert_eq!(ErrorCode::PermissionDenied.as_str(), "permission_denied");
+}
+
+#[test]
+fn test_retryable() {
+    assert!(Error::Io(io::Error::from(io::ErrorKind::Interrupted)).is_retryable());
+    assert!(Error::Io(io::Error::from(io::ErrorKind::TimedOut))
+        .with_path("a")
+        .is_retryable());
+    assert!(!Error::Io(io::Error::from(io::ErrorKind::NotFound)).is_retryable());
+    assert!(!Error::Archive("corrupt".to_string()).is_retryable());
+}
+
+#[test]
+fn test_context_keeps_code_and_message() {
+    let error = Error::NotFound("x".to_string())
+        .with_entry("dir/x")
+        .with_path("a.zip")
+        .with_path("ignored.zip");
+
+    assert_eq!(error.code(), ErrorCode::NotFound);
+    assert_eq!(error.to_string(), "Not found: x");
+    assert_eq!(error.path(), Some(Path::new("a.zip")));
+    assert_eq!(error.entry(), Some(Path::new("dir/x")));
+    assert!(matches!(error.inner(), Error::NotFound(_)));
+
+    let report = serde_json::to_value(error.report()).unwrap();
+    assert_eq!(report["code"], "not_found");
+    assert_eq!(report["message"], "Not found: x");
+    assert_eq!(report["path"], "a.zip");
+    assert_eq!(report["entry"], "dir/x");
+    assert_eq!(report["retryable"], false);
+
+    let report = serde_json::to_value(Error::Other("x".to_string()).report()).unwrap();
+    assert!(report.get("path").is_none());
+    assert!(report.get("entry").is_none());
+}
+
+#[test]
+fn test_missing_archive_reports_path() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("missing.tar.gz");
+
+    let error = inspect(&archive).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::NotFound);
+    assert_eq!(error.path(), Some(archive.as_path()));
+}
+
+#[test]
+fn test_failed_entry_is_reported() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("source");
+    fs::create_dir_all(source.join("docs")).unwrap();
+    fs::write(source.join("docs/readme.txt"), "hello").unwrap();
+    let archive = temp_dir.path().join("docs.tar");
+    pack_with_strategy(&source, &archive, Some("tar"), PackOptions::default()).unwrap();
+
+    let readme = inspect(&archive)
+        .unwrap()
+        .into_iter()
+        .find(|entry| entry.path.ends_with("readme.txt"))
+        .unwrap()
+        .path;
+
+    // A file where the entry's folder should go, which fails on the folder
+    let output = temp_dir.path().join("out");
+    fs::create_dir_all(output.join(readme.parent().unwrap().parent().unwrap())).unwrap();
+    fs::write(output.join(readme.parent().unwrap()), "in the way").unwrap();
+
+    let outcome = extract_with_options(&archive, &output, ExtractOptions::default()).unwrap();
+    let error = outcome.into_result().unwrap_err();
+    assert_eq!(error.path(), Some(archive.as_path()));
+    assert_eq!(error.entry(), readme.parent());
+}
@@ -0,0 +1,132 @@
+//! Tests for pack size estimates
+
+use flux_core::archive::{estimate_pack, pack_with_strategy, PackFilter, PackOptions};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn create_text_source(dir: &Path) -> PathBuf {
+    let source = dir.join("source");
+    fs::create_dir_all(source.join("logs")).unwrap();
+    for i in 0..20 {
+        fs::write(
+            source.join(format!("file{}.txt", i)),
+            "the quick brown fox jumps over the lazy dog\n".repeat(200),
+        )
+        .unwrap();
+    }
+    fs::write(source.join("logs/app.log"), "log line\n".repeat(1000)).unwrap();
+    source
+}
+
+fn options(format: &str) -> PackOptions {
+    PackOptions {
+        smart: false,
+        algorithm: format.strip_prefix("tar.").map(str::to_string),
+        ..PackOptions::default()
+    }
+}
+
+#[test]
+fn test_estimate_counts_inputs() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_text_source(temp_dir.path());
+
+    let estimate = estimate_pack(&[source], "tar.zst", &options("tar.zst")).unwrap();
+    assert_eq!(estimate.files, 21);
+    assert_eq!(estimate.input_bytes, 20 * 44 * 200 + 9 * 1000);
+    assert!(estimate.is_exact());
+}
+
+#[test]
+fn test_estimate_compressible_input_is_smaller() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_text_source(temp_dir.path());
+
+    for format in ["tar.gz", "tar.zst", "tar.xz", "tar.br", "zip"] {
+        let estimate =
+            estimate_pack(std::slice::from_ref(&source), format, &options(format)).unwrap();
+        assert!(
+            estimate.ratio().unwrap() < 0.5,
+            "{} estimate {:?}",
+            format,
+            estimate
+        );
+    }
+}
+
+#[test]
+fn test_estimate_is_close_to_actual_size() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_text_source(temp_dir.path());
+
+    for format in ["tar", "tar.zst", "tar.gz"] {
+        let archive = temp_dir.path().join(format!("out.{}", format));
+        pack_with_strategy(&source, &archive, Some(format), options(format)).unwrap();
+        let actual = fs::metadata(&archive).unwrap().len() as f64;
+
+        let estimate =
+            estimate_pack(std::slice::from_ref(&source), format, &options(format)).unwrap();
+        let error = (estimate.archive_size as f64 - actual).abs() / actual;
+        assert!(
+            error < 0.5,
+            "{}: estimated {} but packed {}",
+            format,
+            estimate.archive_size,
+            actual
+        );
+    }
+}
+
+#[test]
+fn test_estimate_store_is_not_smaller_than_input() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_text_source(temp_dir.path());
+
+    let estimate = estimate_pack(&[source], "tar", &options("tar")).unwrap();
+    assert!(estimate.archive_size >= estimate.input_bytes);
+}
+
+#[test]
+fn test_estimate_respects_filter() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_text_source(temp_dir.path());
+
+    let options = PackOptions {
+        filter: PackFilter::new(&["logs"]).unwrap(),
+        ..options("tar.zst")
+    };
+    let estimate = estimate_pack(&[source], "tar.zst", &options).unwrap();
+    assert_eq!(estimate.files, 20);
+    assert_eq!(estimate.input_bytes, 20 * 44 * 200);
+}
+
+#[test]
+fn test_estimate_samples_large_inputs() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("many");
+    fs::create_dir_all(&source).unwrap();
+    for i in 0..200 {
+        fs::write(source.join(format!("{}.txt", i)), "x".repeat(100 * 1024)).unwrap();
+    }
+
+    let estimate = estimate_pack(&[source], "tar.zst", &options("tar.zst")).unwrap();
+    assert_eq!(estimate.files, 200);
+    assert!(!estimate.is_exact());
+    assert!(estimate.sampled_bytes < estimate.input_bytes);
+    assert!(estimate.ratio().unwrap() < 0.1);
+}
+
+#[test]
+fn test_estimate_empty_and_unsupported() {
+    let temp_dir = TempDir::new().unwrap();
+    let empty = temp_dir.path().join("empty");
+    fs::create_dir_all(&empty).unwrap();
+
+    let estimate =
+        estimate_pack(std::slice::from_ref(&empty), "tar.zst", &options("tar.zst")).unwrap();
+    assert_eq!(estimate.files, 0);
+    assert_eq!(estimate.ratio(), None);
+
+    assert!(estimate_pack(&[empty], "rar", &PackOptions::default()).is_err());
+}
@@ -1,5 +1,6 @@
 //! Tests for extract options functionality
 
+use flux_core::archive::{pack_with_strategy, EntryFilter, PackOptions, SkipReason};
 use flux_core::{extract_with_options, pack, ExtractOptions};
 use std::fs;
 use tempfile::TempDir;
@@ -28,6 +29,7 @@ fn test_overwrite_option() {
         rename: false,
         strip_components: None,
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
 
@@ -42,6 +44,7 @@ fn test_overwrite_option() {
         rename: false,
         strip_components: None,
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
 
@@ -77,6 +80,7 @@ fn test_skip_option() {
         rename: false,
         strip_components: None,
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
 
@@ -91,6 +95,7 @@ fn test_skip_option() {
         rename: false,
         strip_components: None,
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
 
@@ -122,6 +127,7 @@ fn test_rename_option() {
         rename: false,
         strip_components: None,
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
 
@@ -132,6 +138,7 @@ fn test_rename_option() {
         rename: true,
         strip_components: None,
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
 
@@ -169,6 +176,7 @@ fn test_strip_components() {
         rename: false,
         strip_components: Some(1),
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
 
@@ -215,6 +223,7 @@ fn test_strip_components_deep() {
         rename: false,
         strip_components: Some(3),
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
 
@@ -232,3 +241,164 @@ fn test_strip_components_deep() {
     assert!(!extract_dir.join("source").exists());
     assert!(!extract_dir.join("level1").exists());
 }
+
+#[test]
+fn test_entry_filter() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("source");
+    fs::create_dir_all(source_dir.join("logs")).unwrap();
+    fs::write(source_dir.join("small.txt"), "small").unwrap();
+    fs::write(source_dir.join("large.bin"), vec![0u8; 4096]).unwrap();
+    fs::write(source_dir.join("logs/app.log"), "log line").unwrap();
+
+    for format in ["tar.gz", "zip"] {
+        let archive_path = temp_dir.path().join(format!("test.{}", format));
+        let extract_dir = temp_dir.path().join(format!("extracted-{}", format));
+        pack_with_strategy(
+            &source_dir,
+            &archive_path,
+            Some(format),
+            PackOptions::default(),
+        )
+        .unwrap();
+
+        // Small files outside logs/, whatever the archive calls the folder
+        let extract_opts = ExtractOptions {
+            entry_filter: Some(EntryFilter::new(|entry| {
+                !entry
+                    .path
+                    .components()
+                    .any(|part| part.as_os_str() == "logs")
+                    && entry.size < 1024
+            })),
+            ..ExtractOptions::default()
+        };
+        let outcome = extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
+
+        let root = if format == "zip" {
+            extract_dir.clone()
+        } else {
+            extract_dir.join("source")
+        };
+        assert!(root.join("small.txt").exists(), "{}", format);
+        assert!(!root.join("large.bin").exists(), "{}", format);
+        assert!(!root.join("logs").exists(), "{}", format);
+        assert!(outcome
+            .skipped
+            .iter()
+            .any(|skipped| skipped.path.ends_with("large.bin")
+                && skipped.reason == SkipReason::Excluded));
+    }
+}
+
+#[test]
+fn test_entry_filter_sees_archive_paths() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("source");
+    fs::create_dir_all(&source_dir).unwrap();
+    fs::write(source_dir.join("keep.txt"), "keep").unwrap();
+    fs::write(source_dir.join("drop.txt"), "drop").unwrap();
+    let archive_path = temp_dir.path().join("test.tar");
+    let extract_dir = temp_dir.path().join("extracted");
+    pack(&source_dir, &archive_path, None).unwrap();
+
+    // The path still has the component that is stripped
+    let extract_opts = ExtractOptions {
+        strip_components: Some(1),
+        entry_filter: Some(EntryFilter::new(|entry| {
+            entry.is_dir || entry.path == std::path::Path::new("source/keep.txt")
+        })),
+        ..ExtractOptions::default()
+    };
+    extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
+
+    assert!(extract_dir.join("keep.txt").exists());
+    assert!(!extract_dir.join("drop.txt").exists());
+}
+
+#[test]
+fn test_hoist_strips_the_top_folder_while_extracting() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("source");
+    fs::create_dir_all(source_dir.join("nested")).unwrap();
+    fs::write(source_dir.join("top.txt"), "top").unwrap();
+    fs::write(source_dir.join("nested/deep.txt"), "deep").unwrap();
+    let archive_path = temp_dir.path().join("test.tar.gz");
+    pack_with_strategy(
+        &source_dir,
+        &archive_path,
+        Some("tar.gz"),
+        PackOptions::default(),
+    )
+    .unwrap();
+
+    // Files already in the output directory do not stop the hoist
+    let extract_dir = temp_dir.path().join("extracted");
+    fs::create_dir_all(&extract_dir).unwrap();
+    fs::write(extract_dir.join("existing.txt"), "mine").unwrap();
+    let extract_opts = ExtractOptions {
+        hoist: true,
+        ..ExtractOptions::default()
+    };
+    let outcome = extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
+
+    assert!(extract_dir.join("top.txt").exists());
+    assert!(extract_dir.join("nested/deep.txt").exists());
+    assert!(extract_dir.join("existing.txt").exists());
+    assert!(!extract_dir.join("source").exists());
+    assert!(outcome.skipped.is_empty(), "{:?}", outcome.skipped);
+}
+
+#[test]
+fn test_hoist_keeps_archives_with_several_top_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("source");
+    fs::create_dir_all(source_dir.join("folder")).unwrap();
+    fs::write(source_dir.join("folder/inside.txt"), "inside").unwrap();
+    fs::write(source_dir.join("beside.txt"), "beside").unwrap();
+    let archive_path = temp_dir.path().join("test.tar");
+    let extract_dir = temp_dir.path().join("extracted");
+    pack(&source_dir, &archive_path, None).unwrap();
+
+    // Below the stripped folder there is a file next to `folder`
+    let extract_opts = ExtractOptions {
+        strip_components: Some(1),
+        hoist: true,
+        ..ExtractOptions::default()
+    };
+    extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
+
+    assert!(extract_dir.join("folder/inside.txt").exists());
+    assert!(extract_dir.join("beside.txt").exists());
+}
+
+#[test]
+fn test_hoist_lists_highly_compressible_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("source");
+    fs::create_dir_all(&source_dir).unwrap();
+    fs::write(source_dir.join("zeros.bin"), vec![0u8; 4 * 1024 * 1024]).unwrap();
+    let archive_path = temp_dir.path().join("test.zip");
+    pack_with_strategy(
+        &source_dir,
+        &archive_path,
+        Some("zip"),
+        PackOptions::default(),
+    )
+    .unwrap();
+
+    // Far beyond the ratio refused for untrusted data, but listing it for
+    // the hoist reads no data
+    let extract_dir = temp_dir.path().join("extracted");
+    let extract_opts = ExtractOptions {
+        hoist: true,
+        ..ExtractOptions::default()
+    };
+    extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
+
+    assert_eq!(
+        fs::metadata(extract_dir.join("zeros.bin")).unwrap().len(),
+        4 * 1024 * 1024
+    );
+    assert!(!extract_dir.join("source").exists());
+}
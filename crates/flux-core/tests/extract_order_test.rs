@@ -0,0 +1,150 @@
+//! Tests for extracting entries in a chosen order
+
+use flux_core::archive::{
+    extract_with_options, pack_with_strategy, ExtractOptions, ExtractOrder, PackOptions,
+};
+use flux_testing::fixtures::SourceTree;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn create_source(dir: &Path) -> PathBuf {
+    SourceTree::new()
+        .file("big.bin", vec![7u8; 40_000])
+        .file("docs/guide.md", "a longer guide to everything")
+        .file("docs/index.md", "index")
+        .file("tiny.txt", "hi")
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+/// The names extracted from `format`, in the order they were written
+fn extracted_order(temp_dir: &Path, format: &str, options: ExtractOptions) -> Vec<String> {
+    let source = temp_dir.join("source");
+    let archive = temp_dir.join(format!("ordered.{}", format));
+    if !archive.exists() {
+        pack_with_strategy(&source, &archive, Some(format), PackOptions::default()).unwrap();
+    }
+
+    let output = temp_dir.join(format!("out-{}", format));
+    let _ = fs::remove_dir_all(&output);
+    let outcome = extract_with_options(&archive, &output, options).unwrap();
+    assert!(outcome.is_complete(), "{}: {:?}", format, outcome.failed);
+    outcome
+        .done
+        .iter()
+        .map(|path| {
+            // Tars keep the name of the packed folder, zips do not
+            let path = path.strip_prefix("source").unwrap_or(path);
+            path.to_string_lossy().trim_end_matches('/').to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn test_smallest_first() {
+    let temp_dir = TempDir::new().unwrap();
+    create_source(temp_dir.path());
+
+    for format in ["tar", "zip"] {
+        let options = ExtractOptions {
+            order: ExtractOrder::SmallestFirst,
+            ..ExtractOptions::default()
+        };
+        let order = extracted_order(temp_dir.path(), format, options);
+        let files: Vec<_> = order.iter().filter(|name| name.contains('.')).collect();
+        assert_eq!(
+            files,
+            ["tiny.txt", "docs/index.md", "docs/guide.md", "big.bin"],
+            "{}",
+            format
+        );
+        let first_file = order.iter().position(|name| name.contains('.')).unwrap();
+        assert!(
+            order[..first_file].iter().any(|name| name == "docs"),
+            "{}: {:?}",
+            format,
+            order
+        );
+    }
+}
+
+#[test]
+fn test_priority_entries_come_first() {
+    let temp_dir = TempDir::new().unwrap();
+    create_source(temp_dir.path());
+
+    for format in ["tar", "zip"] {
+        let prefix = if format == "zip" { "" } else { "source/" };
+        let options = ExtractOptions {
+            priority: vec![
+                PathBuf::from(format!("{}big.bin", prefix)),
+                PathBuf::from(format!("{}docs", prefix)),
+            ],
+            ..ExtractOptions::default()
+        };
+        let order = extracted_order(temp_dir.path(), format, options);
+        assert_eq!(order[0], "big.bin", "{}: {:?}", format, order);
+        let docs: Vec<_> = order[1..4].iter().map(String::as_str).collect();
+        assert!(
+            docs.iter().all(|name| name.starts_with("docs")),
+            "{:?}",
+            order
+        );
+        assert!(order[4..].contains(&"tiny.txt".to_string()), "{:?}", order);
+    }
+}
+
+#[test]
+fn test_ordered_tar_keeps_long_names() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("long.tar");
+    let long_name = format!("{}/{}.txt", "a".repeat(60), "b".repeat(120));
+
+    // GNU long name headers, as other tar tools write for such names
+    let mut builder = tar::Builder::new(fs::File::create(&archive).unwrap());
+    for (name, data) in [
+        (long_name.as_str(), &b"a long name"[..]),
+        ("small.txt", &b"s"[..]),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        builder.append_data(&mut header, name, data).unwrap();
+    }
+    builder.finish().unwrap();
+
+    let output = temp_dir.path().join("out");
+    let options = ExtractOptions {
+        order: ExtractOrder::SmallestFirst,
+        ..ExtractOptions::default()
+    };
+    let outcome = extract_with_options(&archive, &output, options).unwrap();
+    assert!(outcome.is_complete(), "{:?}", outcome.failed);
+    assert_eq!(outcome.done[0], Path::new("small.txt"));
+    assert_eq!(
+        fs::read_to_string(output.join(&long_name)).unwrap(),
+        "a long name"
+    );
+}
+
+#[test]
+fn test_compressed_tar_keeps_stored_order() {
+    let temp_dir = TempDir::new().unwrap();
+    create_source(temp_dir.path());
+
+    let stored = extracted_order(temp_dir.path(), "tar.zst", ExtractOptions::default());
+    let options = ExtractOptions {
+        order: ExtractOrder::SmallestFirst,
+        ..ExtractOptions::default()
+    };
+    assert_eq!(extracted_order(temp_dir.path(), "tar.zst", options), stored);
+}
+
+#[test]
+fn test_order_names() {
+    for order in [ExtractOrder::Archive, ExtractOrder::SmallestFirst] {
+        assert_eq!(order.name().parse::<ExtractOrder>().unwrap(), order);
+    }
+    assert!("largest-first".parse::<ExtractOrder>().is_err());
+}
@@ -0,0 +1,127 @@
+//! Tests for packing exactly the paths a list names
+
+use flux_core::archive::file_list::{listed_roots, read_file_list};
+use flux_core::archive::{inspect, PackOptions};
+use flux_core::ops::PackJob;
+use flux_core::progress::NoProgress;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// A folder `project` with a file at the top and two in `src`
+fn create_project(dir: &Path) -> PathBuf {
+    let project = dir.join("project");
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::write(project.join("README.md"), "readme").unwrap();
+    fs::write(project.join("src/lib.rs"), "pub fn new() {}").unwrap();
+    fs::write(project.join("src/main.rs"), "fn main() {}").unwrap();
+    project
+}
+
+/// Names in `archive`, directories with a trailing `/`, sorted
+fn names(archive: &Path) -> Vec<String> {
+    let mut names: Vec<String> = inspect(archive)
+        .unwrap()
+        .into_iter()
+        .map(|entry| {
+            let name = entry
+                .path
+                .to_string_lossy()
+                .trim_end_matches('/')
+                .to_string();
+            if entry.is_dir {
+                format!("{}/", name)
+            } else {
+                name
+            }
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_read_file_list() {
+    let lines = read_file_list("src/lib.rs\r\n\nREADME.md\n".as_bytes(), false).unwrap();
+    assert_eq!(
+        lines,
+        [PathBuf::from("src/lib.rs"), PathBuf::from("README.md")]
+    );
+
+    let nul = read_file_list("odd\nname.txt\0README.md\0".as_bytes(), true).unwrap();
+    assert_eq!(
+        nul,
+        [PathBuf::from("odd\nname.txt"), PathBuf::from("README.md")]
+    );
+}
+
+#[test]
+fn test_listed_roots_are_named_below_base() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = create_project(temp_dir.path());
+    let listed = [
+        PathBuf::from("."),
+        PathBuf::from("./src"),
+        PathBuf::from("./src/lib.rs"),
+        PathBuf::from("src/lib.rs"),
+        project.join("README.md"),
+    ];
+    let roots = listed_roots(&project, &listed).unwrap();
+    let named: Vec<&Path> = roots.iter().map(|root| root.name.as_path()).collect();
+    assert_eq!(
+        named,
+        [
+            Path::new("src"),
+            Path::new("src/lib.rs"),
+            Path::new("README.md")
+        ]
+    );
+    assert!(roots.iter().all(|root| !root.recursive));
+    assert_eq!(roots[1].path, project.join("src/lib.rs"));
+
+    let outside = temp_dir.path().join("elsewhere.txt");
+    assert!(listed_roots(&project, &[outside]).is_err());
+}
+
+#[test]
+fn test_pack_holds_exactly_the_listed_paths() {
+    for format in ["tar.gz", "zip"] {
+        let temp_dir = TempDir::new().unwrap();
+        let project = create_project(temp_dir.path());
+        // The directory is listed without everything in it
+        let list = read_file_list("src\nsrc/lib.rs\nREADME.md\n".as_bytes(), false).unwrap();
+        let archive = temp_dir.path().join(format!("listed.{}", format));
+
+        let outcome = PackJob {
+            roots: listed_roots(&project, &list).unwrap(),
+            ..PackJob::new(Vec::new(), &archive, PackOptions::default())
+        }
+        .run(&NoProgress)
+        .unwrap();
+        assert!(outcome.is_complete(), "{}: {:?}", format, outcome.failed);
+
+        assert_eq!(
+            names(&archive),
+            ["README.md", "src/", "src/lib.rs"],
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_missing_listed_path_is_reported() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = create_project(temp_dir.path());
+    let archive = temp_dir.path().join("listed.tar");
+    let list = [PathBuf::from("README.md"), PathBuf::from("gone.txt")];
+
+    let outcome = PackJob {
+        roots: listed_roots(&project, &list).unwrap(),
+        ..PackJob::new(Vec::new(), &archive, PackOptions::default())
+    }
+    .run(&NoProgress)
+    .unwrap();
+    assert_eq!(outcome.failed.len(), 1);
+    assert_eq!(names(&archive), ["README.md"]);
+}
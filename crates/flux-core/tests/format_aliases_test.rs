@@ -0,0 +1,133 @@
+//! Tests for the short extensions and other names of archive formats
+
+use flux_core::archive::tar_extractor::create_tar_extractor;
+use flux_core::archive::{
+    archive_format, detect_format, extract, pack_multiple, pack_with_strategy, parse_format,
+    resolve_format, FormatCapabilities, PackOptions,
+};
+use flux_core::progress::NoProgress;
+use flux_core::Error;
+use flux_testing::fixtures::SourceTree;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn create_source(dir: &Path) -> PathBuf {
+    SourceTree::new()
+        .file("docs/guide.md", "# Guide")
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+#[test]
+fn test_pack_and_extract_by_alias() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    for (name, format) in [
+        ("a.tgz", "tar.gz"),
+        ("b.TGZ", "tar.gz"),
+        ("c.tzst", "tar.zst"),
+        ("d.tar.zstd", "tar.zst"),
+        ("e.txz", "tar.xz"),
+        // A bare compression is a tar compressed so when packing
+        ("f.gz", "tar.gz"),
+    ] {
+        let archive = temp_dir.path().join(name);
+        pack_with_strategy(&source, &archive, None, PackOptions::default()).unwrap();
+        assert_eq!(detect_format(&archive).unwrap(), format, "{}", name);
+
+        let output = temp_dir.path().join(format!("out-{}", name));
+        extract(&archive, &output).unwrap();
+        assert_eq!(
+            fs::read_to_string(output.join("source/docs/guide.md")).unwrap(),
+            "# Guide",
+            "{}",
+            name
+        );
+    }
+
+    let archive = temp_dir.path().join("several.txz");
+    pack_multiple(
+        &[&source],
+        &archive,
+        "txz",
+        None,
+        &PackOptions::default(),
+        &NoProgress,
+    )
+    .unwrap();
+    assert_eq!(detect_format(&archive).unwrap(), "tar.xz");
+}
+
+#[test]
+fn test_aliases_agree_everywhere() {
+    for (name, format) in [
+        ("x.tgz", "tar.gz"),
+        ("x.tzst", "tar.zst"),
+        ("x.tar.zstd", "tar.zst"),
+        ("x.txz", "tar.xz"),
+        ("x.tar.br", "tar.br"),
+    ] {
+        assert_eq!(archive_format(Path::new(name)), Some(format));
+        let alias = name.trim_start_matches("x.");
+        assert_eq!(parse_format(alias), Some(format));
+        assert_eq!(
+            FormatCapabilities::for_path(Path::new(name)),
+            FormatCapabilities::of(format)
+        );
+        assert_eq!(
+            FormatCapabilities::of(alias),
+            FormatCapabilities::of(format)
+        );
+    }
+    // Single compressed files are not taken for archives by name
+    assert_eq!(archive_format(Path::new("access.log.gz")), None);
+}
+
+#[test]
+fn test_tar_extractor_by_alias() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let archive = temp_dir.path().join("source.tgz");
+    pack_with_strategy(&source, &archive, None, PackOptions::default()).unwrap();
+
+    let extractor = create_tar_extractor(&archive).unwrap();
+    let entries: Vec<_> = extractor
+        .entries(&archive)
+        .unwrap()
+        .collect::<flux_core::Result<_>>()
+        .unwrap();
+    assert!(entries
+        .iter()
+        .any(|entry| entry.path.ends_with("docs/guide.md")));
+
+    assert!(create_tar_extractor(Path::new("source.zip")).is_err());
+}
+
+#[test]
+fn test_bzip2_is_named_as_unsupported() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    let archive = temp_dir.path().join("old.tbz2");
+    fs::write(&archive, b"BZh91AY&SY not really compressed").unwrap();
+    let unsupported = |err: Error| match err.inner() {
+        Error::UnsupportedFormat(message) => message.contains("bzip2"),
+        _ => false,
+    };
+    assert!(unsupported(detect_format(&archive).unwrap_err()));
+    assert!(unsupported(
+        resolve_format(&archive, Some("tbz2")).unwrap_err()
+    ));
+
+    // Without the signature a bzip2 extension still says so
+    let renamed = temp_dir.path().join("download");
+    fs::rename(&archive, &renamed).unwrap();
+    assert!(unsupported(detect_format(&renamed).unwrap_err()));
+
+    let output = temp_dir.path().join("new.tar.bz2");
+    let err = pack_with_strategy(&source, &output, None, PackOptions::default()).unwrap_err();
+    assert!(unsupported(err));
+    assert!(!output.exists());
+}
@@ -0,0 +1,158 @@
+//! Tests for choosing the hash manifests are made with
+
+use flux_core::archive::{
+    extract_with_options, pack_with_strategy, ExtractOptions, HashAlgorithm, ManifestCheck,
+    PackFilter, PackHashes, PackOptions, TamperPolicy,
+};
+use flux_core::manifest::Manifest;
+use flux_core::progress::NoProgress;
+use flux_testing::fixtures::SourceTree;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn create_source(dir: &Path) -> PathBuf {
+    SourceTree::new()
+        .file("docs/report.txt", "quarterly numbers")
+        .file("notes.txt", "notes")
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+fn scan(source: &Path, previous: Option<&Manifest>, algorithm: HashAlgorithm) -> Manifest {
+    Manifest::scan_with_algorithm(
+        source,
+        &PackFilter::default(),
+        previous,
+        algorithm,
+        &NoProgress,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_algorithm_names() {
+    assert_eq!(
+        "xxh3".parse::<HashAlgorithm>().unwrap(),
+        HashAlgorithm::Xxh3
+    );
+    assert_eq!(HashAlgorithm::default(), HashAlgorithm::Blake3);
+    assert_eq!(
+        serde_json::to_string(&HashAlgorithm::Sha256).unwrap(),
+        "\"sha256\""
+    );
+    assert_eq!(
+        serde_json::from_str::<HashAlgorithm>("\"b3\"").unwrap(),
+        HashAlgorithm::Blake3
+    );
+}
+
+#[test]
+fn test_manifest_records_its_algorithm() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let manifest = scan(&source, None, HashAlgorithm::Sha256);
+
+    assert_eq!(manifest.hash_algorithm, HashAlgorithm::Sha256);
+    let notes = manifest.entry(Path::new("notes.txt")).unwrap();
+    assert_eq!(notes.hash, format!("{:x}", Sha256::digest(b"notes")));
+
+    let path = temp_dir.path().join("manifest.json");
+    manifest.save(&path).unwrap();
+    assert!(fs::read_to_string(&path)
+        .unwrap()
+        .contains("\"hash_algorithm\": \"sha256\""));
+    assert_eq!(
+        Manifest::load(&path).unwrap().hash_algorithm,
+        HashAlgorithm::Sha256
+    );
+}
+
+#[test]
+fn test_manifest_without_algorithm_is_blake3() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let manifest = scan(&source, None, HashAlgorithm::Blake3);
+
+    // Manifests written before the header had the field
+    let mut json = serde_json::to_value(&manifest).unwrap();
+    json.as_object_mut().unwrap().remove("hash_algorithm");
+    let path = temp_dir.path().join("old.json");
+    fs::write(&path, serde_json::to_string(&json).unwrap()).unwrap();
+
+    let loaded = Manifest::load(&path).unwrap();
+    assert_eq!(loaded.hash_algorithm, HashAlgorithm::Blake3);
+    assert!(!loaded.diff(&manifest).has_changes());
+}
+
+#[test]
+fn test_scan_keeps_the_previous_algorithm() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let previous = scan(&source, None, HashAlgorithm::Xxh3);
+
+    fs::write(source.join("notes.txt"), "more notes").unwrap();
+    let current = Manifest::scan(
+        &source,
+        &PackFilter::default(),
+        Some(&previous),
+        &NoProgress,
+    )
+    .unwrap();
+    assert_eq!(current.hash_algorithm, HashAlgorithm::Xxh3);
+    assert_eq!(
+        current.entry(Path::new("notes.txt")).unwrap().hash,
+        format!("{:016x}", xxhash_rust::xxh3::xxh3_64(b"more notes"))
+    );
+    assert_eq!(
+        previous.diff(&current).modified,
+        [PathBuf::from("notes.txt")]
+    );
+}
+
+#[test]
+fn test_diff_across_algorithms_compares_metadata() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let blake3 = scan(&source, None, HashAlgorithm::Blake3);
+    let xxh3 = scan(&source, Some(&blake3), HashAlgorithm::Xxh3);
+
+    assert_ne!(
+        blake3.entry(Path::new("notes.txt")).unwrap().hash,
+        xxh3.entry(Path::new("notes.txt")).unwrap().hash
+    );
+    assert!(
+        !blake3.diff(&xxh3).has_changes(),
+        "{:?}",
+        blake3.diff(&xxh3)
+    );
+}
+
+#[test]
+fn test_extraction_checks_with_the_manifest_algorithm() {
+    for format in ["tar.zst", "zip"] {
+        let temp_dir = TempDir::new().unwrap();
+        let source = create_source(temp_dir.path());
+        let hashes = PackHashes::with_algorithm(HashAlgorithm::Xxh3);
+        let archive = temp_dir.path().join(format!("backup.{}", format));
+        let options = PackOptions {
+            hashes: Some(hashes.clone()),
+            ..PackOptions::default()
+        };
+        pack_with_strategy(&source, &archive, Some(format), options).unwrap();
+
+        let manifest = Manifest::from_packed(&source, &PackFilter::default(), &hashes).unwrap();
+        assert_eq!(manifest.hash_algorithm, HashAlgorithm::Xxh3, "{}", format);
+
+        let options = ExtractOptions {
+            manifest_check: Some(ManifestCheck::new(manifest, TamperPolicy::Fail)),
+            fold_case: Some(false),
+            ..ExtractOptions::default()
+        };
+        let output = temp_dir.path().join("out");
+        let outcome = extract_with_options(&archive, &output, options).unwrap();
+        assert!(outcome.is_complete(), "{}: {:?}", format, outcome.failed);
+        assert!(outcome.tampered.is_empty(), "{}", format);
+    }
+}
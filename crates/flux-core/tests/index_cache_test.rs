@@ -0,0 +1,74 @@
+//! Tests for the on-disk cache of archive listings
+
+use flux_core::archive::extractor::ArchiveEntry;
+use flux_core::archive::{create_extractor, pack_with_strategy, IndexCache, PackOptions};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn create_archive(dir: &Path, name: &str) -> PathBuf {
+    let source = dir.join("source");
+    fs::create_dir_all(source.join("nested")).unwrap();
+    fs::write(source.join("a.txt"), "first file").unwrap();
+    fs::write(source.join("nested/b.txt"), "second file").unwrap();
+
+    let archive = dir.join(name);
+    pack_with_strategy(&source, &archive, None, PackOptions::default()).unwrap();
+    archive
+}
+
+/// Entries of `archive` as the extractor lists them
+fn list(archive: &Path) -> Vec<ArchiveEntry> {
+    create_extractor(archive)
+        .unwrap()
+        .entries(archive)
+        .unwrap()
+        .collect::<flux_core::Result<_>>()
+        .unwrap()
+}
+
+#[test]
+fn test_stored_listing_loads_back() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), "out.tar.zst");
+    let cache = IndexCache::new(temp_dir.path().join("cache"));
+    assert!(cache.load(&archive).is_none());
+
+    let entries = list(&archive);
+    cache.store(&archive, &entries).unwrap();
+    let cached = cache.load(&archive).unwrap();
+    let paths =
+        |entries: &[ArchiveEntry]| entries.iter().map(|e| e.path.clone()).collect::<Vec<_>>();
+    assert_eq!(paths(&cached), paths(&entries));
+}
+
+#[test]
+fn test_changed_archive_is_not_served_from_cache() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), "out.zip");
+    let cache = IndexCache::new(temp_dir.path().join("cache"));
+    cache.store(&archive, &list(&archive)).unwrap();
+
+    // Same size and modification time, different content
+    let metadata = fs::metadata(&archive).unwrap();
+    let mut data = fs::read(&archive).unwrap();
+    data[0] ^= 0xff;
+    fs::write(&archive, &data).unwrap();
+    filetime::set_file_mtime(
+        &archive,
+        filetime::FileTime::from_last_modification_time(&metadata),
+    )
+    .unwrap();
+    assert!(cache.load(&archive).is_none());
+}
+
+#[test]
+fn test_clear_removes_listings() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), "out.tar");
+    let cache = IndexCache::new(temp_dir.path().join("cache"));
+    cache.store(&archive, &list(&archive)).unwrap();
+
+    cache.clear().unwrap();
+    assert!(cache.load(&archive).is_none());
+}
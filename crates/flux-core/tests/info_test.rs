@@ -0,0 +1,45 @@
+//! Tests for the build and environment report
+
+use flux_core::archive::detect::{parse_format, EXTENSIONS};
+use flux_core::archive::FormatCapabilities;
+use flux_core::info::BuildInfo;
+use flux_core::strategy::Algorithm;
+
+#[test]
+fn test_every_known_format_is_listed_with_its_aliases() {
+    let info = BuildInfo::collect();
+    for (alias, name) in EXTENSIONS {
+        let format = info.format(name).unwrap();
+        assert!(*alias == format.name || format.aliases.contains(alias));
+        assert_eq!(FormatCapabilities::of(name), Some(format.capabilities));
+    }
+    for format in &info.formats {
+        for alias in &format.aliases {
+            assert_eq!(parse_format(alias), Some(format.name));
+        }
+    }
+}
+
+#[test]
+fn test_sevenz_is_read_only() {
+    let info = BuildInfo::collect();
+    assert!(!info.format("7z").unwrap().pack);
+    assert!(info.format("zip").unwrap().pack);
+}
+
+#[test]
+fn test_algorithms_name_their_libraries() {
+    let info = BuildInfo::collect();
+    assert_eq!(info.algorithms.len(), Algorithm::ALL.len());
+    for algorithm in &info.algorithms {
+        if algorithm.name == "store" {
+            assert!(algorithm.library.is_none());
+            continue;
+        }
+        // Built in the workspace, so the lock file was there to read
+        let library = algorithm.library.as_ref().unwrap();
+        assert!(library.version.is_some(), "{}", library.name);
+    }
+    let zstd = info.algorithms.iter().find(|a| a.name == "zstd").unwrap();
+    assert!(zstd.native_version.is_some());
+}
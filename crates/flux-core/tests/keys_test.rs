@@ -0,0 +1,125 @@
+//! Tests for the key store
+
+use flux_core::keys::KeyStore;
+use flux_core::manifest::Manifest;
+use flux_core::ErrorCode;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_generate_and_load_keys() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = KeyStore::new(temp_dir.path().join("keys"));
+
+    let open = store.generate("open", None).unwrap();
+    assert!(!open.protected);
+    let protected = store.generate("protected", Some("passphrase")).unwrap();
+    assert!(protected.protected);
+
+    let key = store.load("open", None).unwrap();
+    assert_eq!(key.password().len(), 64);
+    assert_eq!(key.password(), store.load("open", None).unwrap().password());
+    assert_ne!(
+        key.password(),
+        store
+            .load("protected", Some("passphrase"))
+            .unwrap()
+            .password()
+    );
+
+    let names: Vec<_> = store.list().unwrap().into_iter().map(|k| k.name).collect();
+    assert_eq!(names, ["open", "protected"]);
+    assert_eq!(
+        store.generate("open", None).unwrap_err().code(),
+        ErrorCode::FileExists
+    );
+}
+
+#[test]
+fn test_protected_keys_need_their_passphrase() {
+    let temp_dir = TempDir::new().unwrap();
+    let store = KeyStore::new(temp_dir.path());
+    store.generate("offsite", Some("right")).unwrap();
+
+    assert_eq!(
+        store.load("offsite", None).unwrap_err().code(),
+        ErrorCode::PasswordRequired
+    );
+    assert_eq!(
+        store.load("offsite", Some("wrong")).unwrap_err().code(),
+        ErrorCode::InvalidPassword
+    );
+    assert_eq!(
+        store.load("missing", None).unwrap_err().code(),
+        ErrorCode::NotFound
+    );
+    // The secret is not stored in the clear
+    let stored = fs::read_to_string(store.path("offsite")).unwrap();
+    let key = store.load("offsite", Some("right")).unwrap();
+    assert!(!stored.contains(&key.password()));
+}
+
+#[test]
+fn test_exported_keys_import_elsewhere() {
+    let temp_dir = TempDir::new().unwrap();
+    let here = KeyStore::new(temp_dir.path().join("here"));
+    let there = KeyStore::new(temp_dir.path().join("there"));
+    here.generate("offsite", Some("passphrase")).unwrap();
+
+    let exported = temp_dir.path().join("offsite.fluxkey");
+    here.export("offsite", &exported).unwrap();
+    let info = there.import(&exported, Some("copy"), false).unwrap();
+    assert_eq!(info.name, "copy");
+    assert!(info.protected);
+    assert_eq!(
+        there
+            .import(&exported, Some("copy"), false)
+            .unwrap_err()
+            .code(),
+        ErrorCode::FileExists
+    );
+    there.import(&exported, Some("copy"), true).unwrap();
+
+    let original = here.load("offsite", Some("passphrase")).unwrap();
+    let copy = there.load("copy", Some("passphrase")).unwrap();
+    assert_eq!(original.password(), copy.password());
+
+    fs::write(&exported, "not a key").unwrap();
+    assert!(there.import(&exported, None, true).is_err());
+}
+
+#[test]
+fn test_keys_encrypt_manifests() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("source");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join("file.txt"), "content").unwrap();
+
+    let store = KeyStore::new(temp_dir.path().join("keys"));
+    store.generate("manifests", None).unwrap();
+    let password = store.load("manifests", None).unwrap().password();
+
+    let path = temp_dir.path().join("backup.fluxmanifest");
+    Manifest::from_directory(&source)
+        .unwrap()
+        .save_with_password(&path, Some(&password))
+        .unwrap();
+    assert!(Manifest::is_encrypted(&path).unwrap());
+    let loaded = Manifest::load_with_password(&path, Some(&password)).unwrap();
+    assert_eq!(loaded.files.len(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_key_files_are_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let store = KeyStore::new(temp_dir.path());
+    store.generate("private", None).unwrap();
+    let mode = fs::metadata(store.path("private"))
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
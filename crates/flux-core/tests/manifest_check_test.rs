@@ -0,0 +1,163 @@
+//! Tests for checking extracted files against manifest hashes
+
+use flux_core::archive::{
+    extract_with_options, pack_with_strategy, ExtractOptions, ManifestCheck, PackOptions,
+    TamperKind, TamperPolicy,
+};
+use flux_core::manifest::Manifest;
+use flux_testing::fixtures::SourceTree;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const FORMATS: [&str; 2] = ["tar.zst", "zip"];
+
+/// A source folder and the manifest made of it before anything changed
+fn create_source(dir: &Path) -> (PathBuf, Manifest) {
+    let source = SourceTree::new()
+        .file("docs/report.txt", "quarterly numbers")
+        .file("notes.txt", "notes")
+        .create_in(dir, "source")
+        .unwrap();
+    let manifest = Manifest::from_directory(&source).unwrap();
+    (source, manifest)
+}
+
+fn checked(manifest: &Manifest, policy: TamperPolicy) -> ExtractOptions {
+    ExtractOptions {
+        manifest_check: Some(ManifestCheck::new(manifest.clone(), policy)),
+        fold_case: Some(false),
+        ..ExtractOptions::default()
+    }
+}
+
+/// The folder the files of `format` end up in below `output`; tars keep
+/// the name of the packed folder, zips do not
+fn extracted(output: &Path, format: &str) -> PathBuf {
+    if format == "zip" {
+        output.to_path_buf()
+    } else {
+        output.join("source")
+    }
+}
+
+#[test]
+fn test_untouched_archive_passes() {
+    let temp_dir = TempDir::new().unwrap();
+    let (source, manifest) = create_source(temp_dir.path());
+
+    for format in FORMATS {
+        let archive = temp_dir.path().join(format!("backup.{}", format));
+        pack_with_strategy(&source, &archive, Some(format), PackOptions::default()).unwrap();
+
+        let output = temp_dir.path().join(format!("out-{}", format));
+        let outcome =
+            extract_with_options(&archive, &output, checked(&manifest, TamperPolicy::Fail))
+                .unwrap();
+        assert!(outcome.is_complete(), "{}: {:?}", format, outcome.failed);
+        assert!(outcome.tampered.is_empty(), "{}", format);
+        assert_eq!(
+            fs::read_to_string(extracted(&output, format).join("docs/report.txt")).unwrap(),
+            "quarterly numbers"
+        );
+    }
+}
+
+#[test]
+fn test_modified_file_fails_and_is_removed() {
+    let temp_dir = TempDir::new().unwrap();
+    let (source, manifest) = create_source(temp_dir.path());
+    fs::write(source.join("docs/report.txt"), "doctored numbers").unwrap();
+
+    for format in FORMATS {
+        let archive = temp_dir.path().join(format!("tampered.{}", format));
+        pack_with_strategy(&source, &archive, Some(format), PackOptions::default()).unwrap();
+
+        let output = temp_dir.path().join(format!("fail-{}", format));
+        let outcome =
+            extract_with_options(&archive, &output, checked(&manifest, TamperPolicy::Fail))
+                .unwrap();
+
+        assert_eq!(outcome.failed.len(), 1, "{}", format);
+        assert_eq!(outcome.tampered.len(), 1, "{}", format);
+        assert_eq!(outcome.tampered[0].kind, TamperKind::Modified);
+        assert!(outcome.tampered[0].path.ends_with("docs/report.txt"));
+        assert!(outcome.to_string().ends_with(", 1 tampered"));
+
+        let files = extracted(&output, format);
+        assert!(!files.join("docs/report.txt").exists(), "{}", format);
+        assert!(files.join("notes.txt").exists(), "{}", format);
+    }
+}
+
+#[test]
+fn test_flag_policy_keeps_modified_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let (source, manifest) = create_source(temp_dir.path());
+    fs::write(source.join("notes.txt"), "other notes").unwrap();
+
+    for format in FORMATS {
+        let archive = temp_dir.path().join(format!("flagged.{}", format));
+        pack_with_strategy(&source, &archive, Some(format), PackOptions::default()).unwrap();
+
+        let output = temp_dir.path().join(format!("flag-{}", format));
+        let outcome =
+            extract_with_options(&archive, &output, checked(&manifest, TamperPolicy::Flag))
+                .unwrap();
+
+        assert!(outcome.is_complete(), "{}", format);
+        assert_eq!(outcome.tampered.len(), 1, "{}", format);
+        assert_eq!(
+            fs::read_to_string(extracted(&output, format).join("notes.txt")).unwrap(),
+            "other notes"
+        );
+    }
+}
+
+#[test]
+fn test_unlisted_file_is_not_written() {
+    let temp_dir = TempDir::new().unwrap();
+    let (source, manifest) = create_source(temp_dir.path());
+    fs::write(source.join("docs/extra.sh"), "#!/bin/sh").unwrap();
+
+    for format in FORMATS {
+        let archive = temp_dir.path().join(format!("extra.{}", format));
+        pack_with_strategy(&source, &archive, Some(format), PackOptions::default()).unwrap();
+
+        let output = temp_dir.path().join(format!("extra-{}", format));
+        let outcome =
+            extract_with_options(&archive, &output, checked(&manifest, TamperPolicy::Fail))
+                .unwrap();
+
+        assert_eq!(outcome.failed.len(), 1, "{}", format);
+        assert_eq!(outcome.tampered[0].kind, TamperKind::Unlisted);
+        assert!(!extracted(&output, format).join("docs/extra.sh").exists());
+    }
+}
+
+#[test]
+fn test_entries_are_matched_before_stripping() {
+    let temp_dir = TempDir::new().unwrap();
+    let (source, manifest) = create_source(temp_dir.path());
+    let archive = temp_dir.path().join("backup.tar.gz");
+    pack_with_strategy(&source, &archive, Some("tar.gz"), PackOptions::default()).unwrap();
+
+    let output = temp_dir.path().join("stripped");
+    let options = ExtractOptions {
+        strip_components: Some(1),
+        ..checked(&manifest, TamperPolicy::Fail)
+    };
+    let outcome = extract_with_options(&archive, &output, options).unwrap();
+
+    assert!(outcome.is_complete(), "{:?}", outcome.failed);
+    assert!(outcome.tampered.is_empty());
+    assert!(output.join("docs/report.txt").exists());
+}
+
+#[test]
+fn test_policy_names() {
+    for policy in [TamperPolicy::Fail, TamperPolicy::Flag] {
+        assert_eq!(policy.name().parse::<TamperPolicy>().unwrap(), policy);
+    }
+    assert!("ignore".parse::<TamperPolicy>().is_err());
+}
@@ -43,6 +43,7 @@ fn test_unix_permissions_preserved() {
         rename: false,
         strip_components: None,
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
 
@@ -82,6 +83,44 @@ fn test_unix_permissions_preserved() {
     );
 }
 
+#[test]
+#[cfg(unix)]
+fn test_apply_umask_ignores_stored_modes() {
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("source");
+    fs::create_dir_all(&source_dir).unwrap();
+
+    let setuid = source_dir.join("setuid.sh");
+    fs::write(&setuid, "#!/bin/sh\n").unwrap();
+    fs::set_permissions(&setuid, fs::Permissions::from_mode(0o4755)).unwrap();
+    let private = source_dir.join("private.txt");
+    fs::write(&private, "secret").unwrap();
+    fs::set_permissions(&private, fs::Permissions::from_mode(0o600)).unwrap();
+
+    let umask = flux_core::archive::umask();
+    for format in ["tar.gz", "zip"] {
+        let archive_path = temp_dir.path().join(format!("test.{}", format));
+        pack_with_strategy(&source_dir, &archive_path, None, PackOptions::default()).unwrap();
+
+        let extract_dir = temp_dir.path().join(format!("out-{}", format));
+        let options = ExtractOptions {
+            apply_umask: true,
+            ..ExtractOptions::default()
+        };
+        extract_with_options(&archive_path, &extract_dir, options).unwrap();
+
+        let mode = |name: &str| {
+            fs::metadata(extract_dir.join("source").join(name))
+                .unwrap()
+                .permissions()
+                .mode()
+                & 0o7777
+        };
+        assert_eq!(mode("setuid.sh"), 0o777 & !umask, "{}", format);
+        assert_eq!(mode("private.txt"), 0o666 & !umask, "{}", format);
+    }
+}
+
 #[test]
 fn test_modification_time_preserved() {
     let temp_dir = TempDir::new().unwrap();
@@ -115,6 +154,7 @@ fn test_modification_time_preserved() {
         rename: false,
         strip_components: None,
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
 
@@ -173,6 +213,7 @@ fn test_symlink_preserved() {
         rename: false,
         strip_components: None,
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
 
@@ -216,6 +257,7 @@ fn test_directory_structure_preserved() {
         rename: false,
         strip_components: None,
         hoist: true,
+        ..ExtractOptions::default()
     };
     extract_with_options(&archive_path, &extract_dir, extract_opts).unwrap();
 
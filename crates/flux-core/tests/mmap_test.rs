@@ -0,0 +1,126 @@
+//! Tests for packing with memory-mapped input files
+
+use flux_core::archive::{extract, pack_multiple, pack_with_strategy, PackOptions, MMAP_MIN_SIZE};
+use flux_core::progress::ProgressSink;
+use flux_testing::fixtures::SourceTree;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tempfile::TempDir;
+
+#[derive(Default)]
+struct Counter {
+    advanced: AtomicU64,
+    cancelled: AtomicBool,
+}
+
+impl ProgressSink for Counter {
+    fn advance(&self, bytes: u64) {
+        self.advanced.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+fn mapped() -> PackOptions {
+    PackOptions {
+        use_mmap: true,
+        ..PackOptions::default()
+    }
+}
+
+/// A folder with one file large enough to be mapped and one too small,
+/// returning the size of both together
+fn create_source(dir: &Path) -> u64 {
+    let large: Vec<u8> = (0..MMAP_MIN_SIZE + 12_345)
+        .map(|i| (i % 251) as u8)
+        .collect();
+    SourceTree::new()
+        .file("large.bin", &large)
+        .file("small.txt", "small")
+        .create_in(dir, "source")
+        .unwrap();
+    large.len() as u64 + 5
+}
+
+#[test]
+fn test_mapped_inputs_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    create_source(temp_dir.path());
+    let source = temp_dir.path().join("source");
+
+    for format in ["tar", "tar.zst", "zip"] {
+        let archive = temp_dir.path().join(format!("mapped.{}", format));
+        pack_with_strategy(&source, &archive, Some(format), mapped()).unwrap();
+
+        let output = temp_dir.path().join(format!("out-{}", format));
+        extract(&archive, &output).unwrap();
+        // Tar keeps the folder name, zip does not
+        let extracted = if output.join("source").exists() {
+            output.join("source")
+        } else {
+            output
+        };
+        for name in ["large.bin", "small.txt"] {
+            assert_eq!(
+                fs::read(extracted.join(name)).unwrap(),
+                fs::read(source.join(name)).unwrap(),
+                "{}: {}",
+                format,
+                name
+            );
+        }
+    }
+}
+
+#[test]
+fn test_mapped_inputs_report_progress() {
+    let temp_dir = TempDir::new().unwrap();
+    let total = create_source(temp_dir.path());
+    let inputs = [temp_dir.path().join("source")];
+
+    for format in ["tar", "zip"] {
+        let counter = Counter::default();
+        let output = temp_dir.path().join(format!("progress.{}", format));
+        pack_multiple(
+            &inputs,
+            &output,
+            format,
+            Some(temp_dir.path()),
+            &mapped(),
+            &counter,
+        )
+        .unwrap();
+        assert_eq!(
+            counter.advanced.load(Ordering::Relaxed),
+            total,
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_mapped_inputs_can_be_cancelled() {
+    let temp_dir = TempDir::new().unwrap();
+    create_source(temp_dir.path());
+    let inputs = [temp_dir.path().join("source")];
+
+    for format in ["tar", "zip"] {
+        let counter = Counter::default();
+        counter.cancelled.store(true, Ordering::Relaxed);
+        let output = temp_dir.path().join(format!("cancelled.{}", format));
+        let result = pack_multiple(
+            &inputs,
+            &output,
+            format,
+            Some(temp_dir.path()),
+            &mapped(),
+            &counter,
+        );
+        assert!(result.is_err(), "{}", format);
+        assert_eq!(counter.advanced.load(Ordering::Relaxed), 0, "{}", format);
+    }
+}
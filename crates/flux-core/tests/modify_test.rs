@@ -0,0 +1,323 @@
+//! Tests for renaming, deleting and adding entries inside archives
+
+use flux_core::archive::{
+    extract_with_options, inspect, pack_multiple, rename_entry, ExtractOptions, Modifier,
+    PackOptions,
+};
+use flux_core::progress::NoProgress;
+use flux_core::Error;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use zip::ZipArchive;
+
+/// A name long enough to need a GNU long-name header in tar
+const LONG_NAME: &str =
+    "a-file-name-that-is-well-beyond-the-one-hundred-bytes-a-plain-ustar-header-can-hold-on-its-own.txt";
+
+fn pack(dir: &Path, format: &str) -> PathBuf {
+    let source = dir.join("source");
+    fs::create_dir_all(source.join("docs/old")).unwrap();
+    fs::write(source.join("readme.txt"), "read me").unwrap();
+    fs::write(source.join("docs/old/a.txt"), "a".repeat(5000)).unwrap();
+    fs::write(source.join("docs/old").join(LONG_NAME), "long").unwrap();
+    fs::write(source.join("docs/keep.txt"), "keep").unwrap();
+
+    let archive = dir.join(format!("out.{}", format));
+    pack_multiple(
+        &[source.join("readme.txt"), source.join("docs")],
+        &archive,
+        format,
+        Some(&source),
+        &PackOptions::default(),
+        &NoProgress,
+    )
+    .unwrap();
+    archive
+}
+
+fn file_names(archive: &Path) -> Vec<String> {
+    let mut names: Vec<String> = inspect(archive)
+        .unwrap()
+        .into_iter()
+        .filter(|entry| !entry.is_dir)
+        .map(|entry| entry.path.to_string_lossy().replace('\\', "/"))
+        .collect();
+    names.sort();
+    names
+}
+
+fn extract(archive: &Path, dir: &Path) -> PathBuf {
+    let output = dir.join("extracted");
+    extract_with_options(archive, &output, ExtractOptions::default()).unwrap();
+    output
+}
+
+#[test]
+fn test_move_directory() {
+    for format in ["zip", "tar", "tar.gz", "tar.zst", "tar.xz", "tar.br"] {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = pack(temp_dir.path(), format);
+
+        let renamed = rename_entry(&archive, "docs/old", "docs/new").unwrap();
+        assert!(renamed >= 2, "{}", format);
+
+        let names = file_names(&archive);
+        assert!(names.contains(&"docs/new/a.txt".to_string()), "{}", format);
+        assert!(
+            names.contains(&format!("docs/new/{}", LONG_NAME)),
+            "{}",
+            format
+        );
+        assert!(names.contains(&"docs/keep.txt".to_string()), "{}", format);
+        assert!(
+            !names.iter().any(|name| name.starts_with("docs/old")),
+            "{}",
+            format
+        );
+
+        let output = extract(&archive, temp_dir.path());
+        assert_eq!(
+            fs::read_to_string(output.join("docs/new/a.txt")).unwrap(),
+            "a".repeat(5000),
+            "{}",
+            format
+        );
+        assert_eq!(
+            fs::read_to_string(output.join("docs/new").join(LONG_NAME)).unwrap(),
+            "long",
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_zip_rename_keeps_compressed_data() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = pack(temp_dir.path(), "zip");
+    let before = {
+        let mut zip = ZipArchive::new(File::open(&archive).unwrap()).unwrap();
+        let file = zip.by_name("docs/old/a.txt").unwrap();
+        (file.compressed_size(), file.crc32(), file.compression())
+    };
+
+    Modifier::new(&archive)
+        .rename("docs/old/a.txt", "a.txt")
+        .rename("readme.txt", "README")
+        .apply()
+        .unwrap();
+
+    let mut zip = ZipArchive::new(File::open(&archive).unwrap()).unwrap();
+    assert!(zip.by_name("docs/old/a.txt").is_err());
+    assert!(zip.by_name("README").is_ok());
+    let file = zip.by_name("a.txt").unwrap();
+    assert_eq!(
+        (file.compressed_size(), file.crc32(), file.compression()),
+        before
+    );
+}
+
+#[test]
+fn test_missing_source_leaves_archive_alone() {
+    let temp_dir = TempDir::new().unwrap();
+    for format in ["zip", "tar.zst"] {
+        let archive = pack(temp_dir.path(), format);
+        let original = fs::read(&archive).unwrap();
+
+        let result = rename_entry(&archive, "docs/missing", "docs/new");
+        assert!(matches!(result, Err(Error::NotFound(_))), "{}", format);
+        // A prefix of a name is not a directory
+        let result = rename_entry(&archive, "docs/ol", "docs/new");
+        assert!(matches!(result, Err(Error::NotFound(_))), "{}", format);
+
+        assert_eq!(fs::read(&archive).unwrap(), original, "{}", format);
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_rewritten_archive_keeps_its_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    for format in ["zip", "tar"] {
+        let archive = pack(temp_dir.path(), format);
+        fs::set_permissions(&archive, fs::Permissions::from_mode(0o664)).unwrap();
+
+        rename_entry(&archive, "readme.txt", "README.txt").unwrap();
+
+        let mode = fs::metadata(&archive).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o664, "{}", format);
+    }
+}
+
+#[test]
+fn test_rename_onto_existing_entry_fails() {
+    let temp_dir = TempDir::new().unwrap();
+    for format in ["zip", "tar"] {
+        let archive = pack(temp_dir.path(), format);
+        let original = fs::read(&archive).unwrap();
+
+        let result = rename_entry(&archive, "readme.txt", "docs/keep.txt");
+        assert!(matches!(result, Err(Error::FileExists(_))), "{}", format);
+        assert_eq!(fs::read(&archive).unwrap(), original, "{}", format);
+    }
+}
+
+#[test]
+fn test_renames_onto_one_name_fail() {
+    let temp_dir = TempDir::new().unwrap();
+    for format in ["zip", "tar"] {
+        let archive = pack(temp_dir.path(), format);
+        let original = fs::read(&archive).unwrap();
+
+        let result = Modifier::new(&archive)
+            .rename("readme.txt", "notes.txt")
+            .rename("docs/keep.txt", "notes.txt")
+            .apply();
+        assert!(matches!(result, Err(Error::FileExists(_))), "{}", format);
+        // A folder moved onto a renamed file clashes on its own name
+        let result = Modifier::new(&archive)
+            .rename("docs/old", "moved")
+            .rename("docs/keep.txt", "moved/a.txt")
+            .apply();
+        assert!(matches!(result, Err(Error::FileExists(_))), "{}", format);
+        assert_eq!(fs::read(&archive).unwrap(), original, "{}", format);
+    }
+}
+
+#[test]
+fn test_rename_keeps_pax_records() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("pax.tar");
+    let mut builder = tar::Builder::new(File::create(&archive).unwrap());
+    let records = b"30 mtime=1700000000.123456789\n27 SCHILY.xattr.user.tag=x\n";
+    let mut pax = tar::Header::new_ustar();
+    pax.set_entry_type(tar::EntryType::XHeader);
+    pax.set_size(records.len() as u64);
+    builder
+        .append_data(&mut pax, "PaxHeader", &records[..])
+        .unwrap();
+    let mut header = tar::Header::new_ustar();
+    header.set_size(4);
+    header.set_mode(0o644);
+    builder
+        .append_data(&mut header, "old.txt", &b"data"[..])
+        .unwrap();
+    builder.finish().unwrap();
+    drop(builder);
+
+    rename_entry(&archive, "old.txt", "new.txt").unwrap();
+
+    let mut reader = tar::Archive::new(File::open(&archive).unwrap());
+    let mut entries = reader.entries().unwrap();
+    let mut entry = entries.next().unwrap().unwrap();
+    assert_eq!(entry.path().unwrap(), Path::new("new.txt"));
+    let records: Vec<(String, Vec<u8>)> = entry
+        .pax_extensions()
+        .unwrap()
+        .unwrap()
+        .map(|record| {
+            let record = record.unwrap();
+            (
+                record.key().unwrap().to_string(),
+                record.value_bytes().to_vec(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        records,
+        [
+            ("mtime".to_string(), b"1700000000.123456789".to_vec()),
+            ("SCHILY.xattr.user.tag".to_string(), b"x".to_vec()),
+        ]
+    );
+    assert!(entries.next().is_none());
+}
+
+#[test]
+fn test_rename_rejects_unsafe_names() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = pack(temp_dir.path(), "tar");
+
+    for target in ["../escape.txt", "/etc/passwd", ""] {
+        let result = rename_entry(&archive, "readme.txt", target);
+        assert!(matches!(result, Err(Error::InvalidPath(_))), "{}", target);
+    }
+}
+
+#[test]
+fn test_rename_in_7z_is_unsupported() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("out.7z");
+    fs::write(&archive, "not checked").unwrap();
+
+    let result = rename_entry(&archive, "readme.txt", "README");
+    assert!(matches!(result, Err(Error::UnsupportedOperation(_))));
+}
+
+#[test]
+fn test_delete_and_add_entries() {
+    for format in ["zip", "tar", "tar.zst"] {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = pack(temp_dir.path(), format);
+        let notes = temp_dir.path().join("notes.txt");
+        fs::write(&notes, "notes").unwrap();
+        let extra = temp_dir.path().join("extra");
+        fs::create_dir_all(extra.join("nested")).unwrap();
+        fs::write(extra.join("nested/b.txt"), "b").unwrap();
+
+        let changed = Modifier::new(&archive)
+            .delete("docs/old")
+            .rename("readme.txt", "README")
+            .add(&notes, "docs/notes.txt")
+            .add(&extra, "more")
+            .apply()
+            .unwrap();
+        assert!(changed >= 5, "{}", format);
+
+        assert_eq!(
+            file_names(&archive),
+            [
+                "README",
+                "docs/keep.txt",
+                "docs/notes.txt",
+                "more/nested/b.txt"
+            ],
+            "{}",
+            format
+        );
+        let output = extract(&archive, temp_dir.path());
+        assert_eq!(
+            fs::read_to_string(output.join("docs/notes.txt")).unwrap(),
+            "notes"
+        );
+        assert_eq!(
+            fs::read_to_string(output.join("more/nested/b.txt")).unwrap(),
+            "b"
+        );
+    }
+}
+
+#[test]
+fn test_failed_delete_or_add_leaves_archive_alone() {
+    let temp_dir = TempDir::new().unwrap();
+    let notes = temp_dir.path().join("notes.txt");
+    fs::write(&notes, "notes").unwrap();
+    for format in ["zip", "tar"] {
+        let archive = pack(temp_dir.path(), format);
+        let original = fs::read(&archive).unwrap();
+
+        let result = Modifier::new(&archive).delete("docs/missing").apply();
+        assert!(matches!(result, Err(Error::NotFound(_))), "{}", format);
+        let result = Modifier::new(&archive).add(&notes, "readme.txt").apply();
+        assert!(matches!(result, Err(Error::FileExists(_))), "{}", format);
+        let result = Modifier::new(&archive)
+            .add(temp_dir.path().join("gone.txt"), "gone.txt")
+            .apply();
+        assert!(matches!(result, Err(Error::NotFound(_))), "{}", format);
+
+        assert_eq!(fs::read(&archive).unwrap(), original, "{}", format);
+    }
+}
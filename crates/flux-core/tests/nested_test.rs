@@ -0,0 +1,153 @@
+//! Tests for extracting archives found inside archives
+
+use flux_core::archive::{
+    extract_recursive, pack_with_strategy, ExtractOptions, NestedSkipReason, PackOptions,
+    RecursiveOptions,
+};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Pack the directory `dir/name` as `name.format` next to it
+fn pack(dir: &Path, name: &str, format: &str) -> PathBuf {
+    let archive = dir.join(format!("{}.{}", name, format));
+    pack_with_strategy(
+        dir.join(name),
+        &archive,
+        Some(format),
+        PackOptions::default(),
+    )
+    .unwrap();
+    archive
+}
+
+/// `release.tar.gz` holding `libs.zip`, which holds `deep.tar` with `core.txt`
+fn create_nested(dir: &Path) -> PathBuf {
+    fs::create_dir_all(dir.join("deep")).unwrap();
+    fs::write(dir.join("deep/core.txt"), "at the bottom").unwrap();
+    let deep = pack(dir, "deep", "tar");
+
+    fs::create_dir_all(dir.join("libs")).unwrap();
+    fs::rename(&deep, dir.join("libs/deep.tar")).unwrap();
+    fs::write(dir.join("libs/lib.txt"), "a library").unwrap();
+    let libs = pack(dir, "libs", "zip");
+
+    fs::create_dir_all(dir.join("release")).unwrap();
+    fs::rename(&libs, dir.join("release/libs.zip")).unwrap();
+    fs::write(dir.join("release/README"), "read me").unwrap();
+    pack(dir, "release", "tar.gz")
+}
+
+#[test]
+fn test_extracts_every_level_in_place() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_nested(temp_dir.path());
+    let output = temp_dir.path().join("out");
+
+    let report = extract_recursive(
+        &archive,
+        &output,
+        ExtractOptions::default(),
+        &RecursiveOptions::default(),
+    )
+    .unwrap();
+    assert!(report.is_complete());
+    assert!(report.skipped.is_empty());
+    let extracted: Vec<_> = report
+        .extracted
+        .iter()
+        .map(|nested| (nested.path.clone(), nested.depth))
+        .collect();
+    assert_eq!(
+        extracted,
+        [
+            (PathBuf::from("release/libs.zip"), 1),
+            (PathBuf::from("release/libs/deep.tar"), 2)
+        ]
+    );
+
+    // Zip archives are packed without the folder itself
+    assert_eq!(
+        fs::read_to_string(output.join("release/libs/deep/deep/core.txt")).unwrap(),
+        "at the bottom"
+    );
+    assert!(output.join("release/libs/lib.txt").is_file());
+    assert!(!output.join("release/libs.zip").exists());
+    assert!(!output.join("release/libs/deep.tar").exists());
+}
+
+#[test]
+fn test_depth_limit_leaves_deeper_archives() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_nested(temp_dir.path());
+    let output = temp_dir.path().join("out");
+
+    let limits = RecursiveOptions {
+        max_depth: 1,
+        ..RecursiveOptions::default()
+    };
+    let report = extract_recursive(&archive, &output, ExtractOptions::default(), &limits).unwrap();
+    assert_eq!(report.extracted.len(), 1);
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].reason, NestedSkipReason::TooDeep);
+    assert_eq!(report.skipped[0].depth, 2);
+    assert!(report.is_complete());
+    assert!(output.join("release/libs/deep.tar").is_file());
+}
+
+#[test]
+fn test_bomb_limits() {
+    let temp_dir = TempDir::new().unwrap();
+    let dir = temp_dir.path();
+    fs::create_dir_all(dir.join("zeros")).unwrap();
+    fs::write(dir.join("zeros/zeros.bin"), vec![0u8; 4 * 1024 * 1024]).unwrap();
+    let zeros = pack(dir, "zeros", "tar.zst");
+    fs::create_dir_all(dir.join("outer")).unwrap();
+    fs::rename(&zeros, dir.join("outer/zeros.tar.zst")).unwrap();
+    let archive = pack(dir, "outer", "tar");
+
+    let output = dir.join("out");
+    let report = extract_recursive(
+        &archive,
+        &output,
+        ExtractOptions::default(),
+        &RecursiveOptions::default(),
+    )
+    .unwrap();
+    assert!(report.extracted.is_empty());
+    assert!(matches!(
+        report.skipped[0].reason,
+        NestedSkipReason::Ratio(ratio) if ratio > 100.0
+    ));
+    assert!(output.join("outer/zeros.tar.zst").is_file());
+
+    let limits = RecursiveOptions {
+        max_ratio: f64::INFINITY,
+        max_total_size: 1024 * 1024,
+        ..RecursiveOptions::default()
+    };
+    let output = dir.join("out-limited");
+    let report = extract_recursive(&archive, &output, ExtractOptions::default(), &limits).unwrap();
+    assert_eq!(report.skipped[0].reason, NestedSkipReason::OverLimit);
+    assert!(!output.join("outer/zeros").exists());
+}
+
+#[test]
+fn test_leaves_archives_it_did_not_write() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_nested(temp_dir.path());
+    let output = temp_dir.path().join("out");
+    fs::create_dir_all(&output).unwrap();
+    fs::copy(&archive, output.join("mine.tar.gz")).unwrap();
+
+    let report = extract_recursive(
+        &archive,
+        &output,
+        ExtractOptions::default(),
+        &RecursiveOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(report.extracted.len(), 2);
+    assert!(output.join("mine.tar.gz").is_file());
+    assert!(!output.join("mine").exists());
+}
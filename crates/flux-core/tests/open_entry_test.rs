@@ -0,0 +1,96 @@
+//! Tests for reading single entries without extracting
+
+use flux_core::archive::extractor::ArchiveEntry;
+use flux_core::archive::{create_extractor, pack_with_strategy, PackOptions};
+use flux_core::Error;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Pack a small tree with one large file into `archive`
+fn create_archive(dir: &Path, archive: &Path, format: &str) {
+    let source = dir.join("source");
+    fs::create_dir_all(source.join("nested")).unwrap();
+    fs::write(source.join("hello.txt"), "Hello, preview!").unwrap();
+    fs::write(source.join("nested/empty.txt"), "").unwrap();
+    let large: Vec<u8> = (0..4 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    fs::write(source.join("large.bin"), large).unwrap();
+
+    pack_with_strategy(&source, archive, Some(format), PackOptions::default()).unwrap();
+}
+
+fn find(entries: &[ArchiveEntry], name: &str) -> ArchiveEntry {
+    entries
+        .iter()
+        .find(|e| e.path.file_name().is_some_and(|n| n == name))
+        .unwrap_or_else(|| panic!("{} not in archive", name))
+        .clone()
+}
+
+fn check_open_entry(format: &str) {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join(format!("test.{}", format));
+    create_archive(temp_dir.path(), &archive, format);
+
+    let extractor = create_extractor(&archive).unwrap();
+    let entries: Vec<_> = extractor.entries(&archive).unwrap().flatten().collect();
+
+    let mut text = String::new();
+    extractor
+        .open_entry(&archive, &find(&entries, "hello.txt"))
+        .unwrap()
+        .read_to_string(&mut text)
+        .unwrap();
+    assert_eq!(text, "Hello, preview!");
+
+    let mut empty = Vec::new();
+    extractor
+        .open_entry(&archive, &find(&entries, "empty.txt"))
+        .unwrap()
+        .read_to_end(&mut empty)
+        .unwrap();
+    assert!(empty.is_empty());
+
+    // Reading only the start and dropping the reader must not block
+    let mut head = [0u8; 16];
+    let mut reader = extractor
+        .open_entry(&archive, &find(&entries, "large.bin"))
+        .unwrap();
+    reader.read_exact(&mut head).unwrap();
+    assert_eq!(head[..4], [0, 1, 2, 3]);
+    drop(reader);
+
+    // Nothing was written next to the archive
+    let extracted: Vec<_> = fs::read_dir(temp_dir.path()).unwrap().collect();
+    assert_eq!(extracted.len(), 2); // source/ and the archive
+}
+
+#[test]
+fn test_open_entry_tar_zst() {
+    check_open_entry("tar.zst");
+}
+
+#[test]
+fn test_open_entry_zip() {
+    check_open_entry("zip");
+}
+
+#[test]
+fn test_open_entry_missing() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("test.tar.gz");
+    create_archive(temp_dir.path(), &archive, "tar.gz");
+
+    let extractor = create_extractor(&archive).unwrap();
+    let mut entry = extractor
+        .entries(&archive)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    entry.path = "does/not/exist.txt".into();
+
+    let result = extractor.open_entry(&archive, &entry);
+    assert!(matches!(result, Err(Error::NotFound(_))));
+}
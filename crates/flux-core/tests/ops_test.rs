@@ -0,0 +1,221 @@
+//! Tests for the pack and extract jobs both front ends run
+
+use flux_core::archive::create_extractor;
+use flux_core::archive::extractor::{ArchiveEntry, ExtractEntryOptions};
+use flux_core::archive::{PackOptions, SkipReason};
+use flux_core::ops::{ExtractJob, PackJob};
+use flux_core::progress::{NoProgress, ProgressSink};
+use flux_core::{Error, ErrorCode};
+use flux_testing::fixtures::{ArchiveBuilder, ArchiveKind};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use tempfile::TempDir;
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, ZipWriter};
+
+const PASSWORD: &str = "correct horse";
+
+/// A `notes.txt` in each of two folders
+fn create_inputs(dir: &Path) -> Vec<PathBuf> {
+    ["home/work", "home/play"]
+        .into_iter()
+        .map(|folder| {
+            fs::create_dir_all(dir.join(folder)).unwrap();
+            let path = dir.join(folder).join("notes.txt");
+            fs::write(&path, format!("{} notes", folder)).unwrap();
+            path
+        })
+        .collect()
+}
+
+fn overwrite() -> ExtractEntryOptions {
+    ExtractEntryOptions {
+        overwrite: true,
+        ..Default::default()
+    }
+}
+
+/// Counts the entries it is told about and cancels once asked to
+#[derive(Default)]
+struct Counting {
+    entries: AtomicUsize,
+    cancel: AtomicBool,
+}
+
+impl ProgressSink for Counting {
+    fn entries(&self, count: usize) {
+        self.entries.store(count, Ordering::Relaxed);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+#[test]
+fn test_pack_job_names_inputs_below_common_base() {
+    let temp_dir = TempDir::new().unwrap();
+    let inputs = create_inputs(temp_dir.path());
+    let archive = temp_dir.path().join("notes.zip");
+    let outcome = PackJob::new(inputs, &archive, PackOptions::default())
+        .run(&NoProgress)
+        .unwrap();
+    assert!(outcome.is_complete());
+
+    let output = temp_dir.path().join("out");
+    let progress = Counting::default();
+    let outcome = ExtractJob::new(&archive, &output, overwrite())
+        .run(&progress, |_, _| None)
+        .unwrap();
+    assert_eq!(outcome.done.len(), 2);
+    assert_eq!(progress.entries.load(Ordering::Relaxed), 2);
+    assert_eq!(
+        fs::read_to_string(output.join("play/notes.txt")).unwrap(),
+        "home/play notes"
+    );
+}
+
+#[test]
+fn test_failed_pack_job_removes_its_archive() {
+    let temp_dir = TempDir::new().unwrap();
+    let inputs = create_inputs(temp_dir.path());
+    let archive = temp_dir.path().join("notes.7z");
+    let result = PackJob::new(inputs.clone(), &archive, PackOptions::default()).run(&NoProgress);
+    assert!(matches!(result, Err(Error::UnsupportedOperation(_))));
+
+    let progress = Counting::default();
+    progress.cancel.store(true, Ordering::Relaxed);
+    let archive = temp_dir.path().join("notes.tar.gz");
+    let result = PackJob::new(inputs, &archive, PackOptions::default()).run(&progress);
+    assert!(result.is_err());
+    assert!(!archive.exists());
+}
+
+#[test]
+fn test_extract_job_skips_existing_files_without_overwrite() {
+    let temp_dir = TempDir::new().unwrap();
+    let inputs = create_inputs(temp_dir.path());
+    let archive = temp_dir.path().join("notes.tar");
+    PackJob::new(inputs, &archive, PackOptions::default())
+        .run(&NoProgress)
+        .unwrap();
+
+    let output = temp_dir.path().join("out");
+    fs::create_dir_all(output.join("work")).unwrap();
+    fs::write(output.join("work/notes.txt"), "kept").unwrap();
+    let outcome = ExtractJob::new(&archive, &output, ExtractEntryOptions::default())
+        .run(&NoProgress, |_, _| None)
+        .unwrap();
+    assert_eq!(outcome.skipped.len(), 1);
+    assert_eq!(outcome.skipped[0].reason, SkipReason::Exists);
+    assert_eq!(
+        fs::read_to_string(output.join("work/notes.txt")).unwrap(),
+        "kept"
+    );
+    assert!(output.join("play/notes.txt").exists());
+}
+
+/// Hoisting strips the top folder as entries are written, so files
+/// already in the output are found under the names they are written to
+#[test]
+fn test_extract_job_hoists_while_extracting() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("project.tar");
+    ArchiveBuilder::new(ArchiveKind::Tar)
+        .dir("project")
+        .file("project/a.txt", "new a")
+        .file("project/b.txt", "new b")
+        .build(&archive)
+        .unwrap();
+
+    let output = temp_dir.path().join("out");
+    fs::create_dir_all(&output).unwrap();
+    fs::write(output.join("a.txt"), "kept").unwrap();
+    let mut job = ExtractJob::new(&archive, &output, ExtractEntryOptions::default());
+    job.hoist = true;
+    let outcome = job.run(&NoProgress, |_, _| None).unwrap();
+
+    assert_eq!(outcome.done, [PathBuf::from("project/b.txt")]);
+    assert_eq!(outcome.skipped.len(), 1);
+    assert_eq!(outcome.skipped[0].reason, SkipReason::Exists);
+    assert_eq!(fs::read_to_string(output.join("a.txt")).unwrap(), "kept");
+    assert_eq!(fs::read_to_string(output.join("b.txt")).unwrap(), "new b");
+    assert!(!output.join("project").exists());
+}
+
+/// An entry naming a path outside the output fails, even when something
+/// is already there
+#[test]
+fn test_extract_job_refuses_paths_outside_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("notes.tar");
+    ArchiveBuilder::new(ArchiveKind::Tar)
+        .file("notes.txt", "notes")
+        .build(&archive)
+        .unwrap();
+    fs::write(temp_dir.path().join("x"), "outside").unwrap();
+
+    let listed: ArchiveEntry = create_extractor(&archive)
+        .unwrap()
+        .entries(&archive)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    let mut job = ExtractJob::new(&archive, temp_dir.path().join("out"), overwrite());
+    job.entries = Some(vec![ArchiveEntry {
+        path: PathBuf::from("../x"),
+        ..listed
+    }]);
+    let outcome = job.run(&NoProgress, |_, _| None).unwrap();
+
+    assert!(outcome.skipped.is_empty());
+    assert_eq!(outcome.failed.len(), 1);
+    assert!(matches!(
+        outcome.failed[0].error.inner(),
+        Error::InvalidPath(_)
+    ));
+    assert_eq!(
+        fs::read_to_string(temp_dir.path().join("x")).unwrap(),
+        "outside"
+    );
+}
+
+#[test]
+fn test_extract_job_asks_for_password_until_it_works() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("secret.zip");
+    let mut writer = ZipWriter::new(File::create(&archive).unwrap());
+    writer
+        .start_file(
+            "secret.txt",
+            SimpleFileOptions::default().with_aes_encryption(AesMode::Aes256, PASSWORD),
+        )
+        .unwrap();
+    writer.write_all(b"top secret").unwrap();
+    writer.finish().unwrap();
+
+    // Refusing to give one fails the job with the password error
+    let output = temp_dir.path().join("refused");
+    let error = ExtractJob::new(&archive, &output, overwrite())
+        .run(&NoProgress, |_, _| None)
+        .unwrap_err();
+    assert_eq!(error.code(), ErrorCode::PasswordRequired);
+
+    let mut asked = Vec::new();
+    let output = temp_dir.path().join("out");
+    let outcome = ExtractJob::new(&archive, &output, overwrite())
+        .run(&NoProgress, |_, retry| {
+            asked.push(retry);
+            Some(if retry { PASSWORD } else { "wrong" }.to_string())
+        })
+        .unwrap();
+    assert!(outcome.is_complete());
+    assert_eq!(asked, [false, true]);
+    assert_eq!(
+        fs::read_to_string(output.join("secret.txt")).unwrap(),
+        "top secret"
+    );
+}
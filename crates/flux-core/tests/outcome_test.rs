@@ -0,0 +1,268 @@
+//! Tests for the per-entry outcome of packing and extracting
+
+use flux_core::archive::extractor::ExtractEntryOptions;
+use flux_core::archive::{
+    extract_with_options, inspect, pack_multiple, pack_with_progress, ExtractOptions,
+    OperationOutcome, PackFilter, PackOptions, SkipReason,
+};
+use flux_core::ops::ExtractJob;
+use flux_core::progress::NoProgress;
+use flux_core::ErrorCode;
+use flux_testing::fixtures::SourceTree;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// A directory with two files, one of them a log
+fn create_source(dir: &Path) -> PathBuf {
+    SourceTree::new()
+        .file("docs/readme.txt", "hello")
+        .file("debug.log", "log line")
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+fn skipped(outcome: &OperationOutcome, reason: SkipReason) -> Vec<PathBuf> {
+    outcome
+        .skipped
+        .iter()
+        .filter(|skipped| skipped.reason == reason)
+        .map(|skipped| skipped.path.clone())
+        .collect()
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pack_records_each_entry() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    // Followed, a dangling link has nothing to pack
+    std::os::unix::fs::symlink("missing.txt", source.join("dangling")).unwrap();
+    let archive = temp_dir.path().join("out.tar.gz");
+
+    let options = PackOptions {
+        follow_symlinks: true,
+        filter: PackFilter::new(&["*.log"]).unwrap(),
+        ..Default::default()
+    };
+    let outcome = pack_with_progress(&source, &archive, None, options, &NoProgress).unwrap();
+
+    assert!(outcome.done.contains(&PathBuf::from("source/docs")));
+    assert!(outcome
+        .done
+        .contains(&PathBuf::from("source/docs/readme.txt")));
+    assert_eq!(
+        skipped(&outcome, SkipReason::Excluded),
+        [PathBuf::from("source/debug.log")]
+    );
+    assert_eq!(outcome.failed.len(), 1);
+    assert_eq!(outcome.failed[0].path, Path::new("source/dangling"));
+    assert_eq!(outcome.failed[0].error.code(), ErrorCode::NotFound);
+    assert_eq!(outcome.to_string(), "2 done, 1 skipped, 1 failed");
+
+    // Everything that could be read was still packed
+    let names: Vec<_> = inspect(&archive)
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.path)
+        .collect();
+    assert!(names.contains(&PathBuf::from("source/docs/readme.txt")));
+    assert!(!names.contains(&PathBuf::from("source/dangling")));
+}
+
+#[test]
+fn test_pack_multiple_records_missing_input() {
+    for format in ["tar.zst", "zip"] {
+        let temp_dir = TempDir::new().unwrap();
+        let source = create_source(temp_dir.path());
+        let archive = temp_dir.path().join(format!("out.{}", format));
+        let inputs = [source.join("docs/readme.txt"), source.join("gone.txt")];
+
+        let outcome = pack_multiple(
+            &inputs,
+            &archive,
+            format,
+            Some(&source),
+            &PackOptions::default(),
+            &NoProgress,
+        )
+        .unwrap();
+
+        assert_eq!(
+            outcome.done,
+            [PathBuf::from("docs/readme.txt")],
+            "{}",
+            format
+        );
+        assert_eq!(outcome.failed.len(), 1, "{}", format);
+        assert_eq!(outcome.failed[0].path, Path::new("gone.txt"));
+        assert_eq!(inspect(&archive).unwrap().len(), 1, "{}", format);
+
+        let error = outcome.into_result().unwrap_err();
+        assert_eq!(error.entry(), Some(Path::new("gone.txt")));
+    }
+}
+
+#[test]
+fn test_extract_records_existing_and_stripped() {
+    for format in ["tar.gz", "zip"] {
+        let temp_dir = TempDir::new().unwrap();
+        let source = create_source(temp_dir.path());
+        let archive = temp_dir.path().join(format!("out.{}", format));
+        let options = PackOptions {
+            smart: false,
+            ..Default::default()
+        };
+        flux_core::archive::pack_with_strategy(&source, &archive, Some(format), options).unwrap();
+        let entries = inspect(&archive).unwrap();
+        let output = temp_dir.path().join("out");
+
+        let first = extract_with_options(&archive, &output, ExtractOptions::default()).unwrap();
+        assert!(first.is_complete(), "{}", format);
+        assert!(first.skipped.is_empty(), "{}", format);
+        assert_eq!(first.done.len(), entries.len(), "{}", format);
+
+        // Again, with every file already there
+        let second = extract_with_options(&archive, &output, ExtractOptions::default()).unwrap();
+        let files: Vec<_> = entries
+            .iter()
+            .filter(|entry| !entry.is_dir)
+            .map(|entry| entry.path.clone())
+            .collect();
+        assert_eq!(skipped(&second, SkipReason::Exists), files, "{}", format);
+
+        let options = ExtractOptions {
+            strip_components: Some(2),
+            ..Default::default()
+        };
+        let stripped =
+            extract_with_options(&archive, temp_dir.path().join("stripped"), options).unwrap();
+        let short: Vec<_> = entries
+            .iter()
+            .filter(|entry| entry.path.components().count() <= 2)
+            .map(|entry| entry.path.clone())
+            .collect();
+        assert!(!short.is_empty(), "{}", format);
+        assert_eq!(
+            skipped(&stripped, SkipReason::Stripped),
+            short,
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_extract_goes_on_after_failed_entry() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let archive = temp_dir.path().join("out.tar");
+    flux_core::archive::pack_with_strategy(&source, &archive, Some("tar"), PackOptions::default())
+        .unwrap();
+
+    // A file where the docs folder should go
+    let output = temp_dir.path().join("out");
+    fs::create_dir_all(output.join("source")).unwrap();
+    fs::write(output.join("source/docs"), "in the way").unwrap();
+
+    let outcome = extract_with_options(&archive, &output, ExtractOptions::default()).unwrap();
+
+    let failed: Vec<_> = outcome.failed.iter().map(|f| f.path.clone()).collect();
+    assert!(failed.contains(&PathBuf::from("source/docs/readme.txt")));
+    assert!(outcome.done.contains(&PathBuf::from("source/debug.log")));
+    assert_eq!(
+        fs::read_to_string(output.join("source/debug.log")).unwrap(),
+        "log line"
+    );
+
+    let report = serde_json::to_value(&outcome).unwrap();
+    let failure = report["failed"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|failure| failure["path"] == "source/docs/readme.txt")
+        .unwrap();
+    assert_eq!(failure["error"]["entry"], "source/docs/readme.txt");
+    assert_eq!(failure["error"]["path"], archive.to_str().unwrap());
+}
+
+#[test]
+fn test_extract_skips_special_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("special.tar");
+    let mut builder = tar::Builder::new(fs::File::create(&archive).unwrap());
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Fifo);
+    header.set_size(0);
+    header.set_mode(0o644);
+    builder
+        .append_data(&mut header, "pipe", std::io::empty())
+        .unwrap();
+    let mut header = tar::Header::new_gnu();
+    header.set_size(5);
+    header.set_mode(0o644);
+    builder
+        .append_data(&mut header, "notes.txt", &b"notes"[..])
+        .unwrap();
+    builder.finish().unwrap();
+    drop(builder);
+
+    let output = temp_dir.path().join("out");
+    let outcome = extract_with_options(&archive, &output, ExtractOptions::default()).unwrap();
+    assert!(outcome.is_complete());
+    assert_eq!(
+        skipped(&outcome, SkipReason::Unsupported),
+        [Path::new("pipe")]
+    );
+    assert_eq!(
+        outcome.unsupported().collect::<Vec<_>>(),
+        [Path::new("pipe")]
+    );
+    assert!(!output.join("pipe").exists());
+    assert!(output.join("notes.txt").exists());
+
+    // Entry by entry, as the GUI extracts
+    let output = temp_dir.path().join("job");
+    let outcome = ExtractJob::new(&archive, &output, ExtractEntryOptions::default())
+        .run(&NoProgress, |_, _| None)
+        .unwrap();
+    assert!(outcome.is_complete());
+    assert_eq!(
+        skipped(&outcome, SkipReason::Unsupported),
+        [Path::new("pipe")]
+    );
+    assert!(!output.join("pipe").exists());
+    assert!(output.join("notes.txt").exists());
+}
+
+#[test]
+fn test_report_counts_outcome() {
+    let mut outcome = OperationOutcome::default();
+    outcome.done("docs/readme.txt");
+    outcome.skip("debug.log", SkipReason::Excluded);
+    outcome.skip("trace.log", SkipReason::Excluded);
+    outcome.skip("socket", SkipReason::Unsupported);
+    outcome.fail(
+        "locked.txt",
+        std::io::Error::from(std::io::ErrorKind::PermissionDenied).into(),
+    );
+
+    let report = outcome.report();
+    assert_eq!(report.done, 1);
+    assert_eq!(report.skipped, 3);
+    assert_eq!(
+        report.skipped_by_reason,
+        [(SkipReason::Excluded, 2), (SkipReason::Unsupported, 1)]
+    );
+    assert!(!report.is_complete());
+    assert_eq!(report.failed[0].code, ErrorCode::PermissionDenied);
+    assert_eq!(
+        report.failed[0].entry.as_deref(),
+        Some(Path::new("locked.txt"))
+    );
+    assert_eq!(report.to_string(), outcome.to_string());
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["done"], 1);
+    assert_eq!(json["failed"][0]["code"], "permission_denied");
+}
@@ -0,0 +1,131 @@
+//! Tests for exclusion filters when packing
+
+use flux_core::archive::filter::DEFAULT_VCS_PATTERNS;
+use flux_core::archive::{inspect, pack_with_strategy, FilterStats, PackFilter, PackOptions};
+use flux_core::manifest::Manifest;
+use flux_testing::fixtures::SourceTree;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Create a project tree with VCS metadata, logs and one large file
+fn create_source(dir: &Path) -> PathBuf {
+    SourceTree::new()
+        .file(".git/HEAD", "ref: refs/heads/main")
+        .file(".git/objects/abc", "object")
+        .file("src/main.rs", "fn main() {}")
+        .file("logs/app.log", "log line")
+        .file("README.md", "readme")
+        .file("data.bin", vec![0u8; 64 * 1024])
+        .create_in(dir, "project")
+        .unwrap()
+}
+
+fn filter() -> PackFilter {
+    PackFilter::new(&["*.log"])
+        .unwrap()
+        .ignore_vcs(true)
+        .max_file_size(Some(1024))
+}
+
+fn packed_names(format: &str) -> Vec<String> {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let archive = temp_dir.path().join(format!("out.{}", format));
+
+    let options = PackOptions {
+        filter: filter(),
+        ..Default::default()
+    };
+    pack_with_strategy(&source, &archive, Some(format), options).unwrap();
+
+    inspect(&archive)
+        .unwrap()
+        .into_iter()
+        .filter(|entry| !entry.is_dir)
+        .map(|entry| {
+            entry
+                .path
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect()
+}
+
+#[test]
+fn test_filter_excludes_from_tar() {
+    let mut names = packed_names("tar.zst");
+    names.sort();
+    assert_eq!(names, vec!["README.md", "main.rs"]);
+}
+
+#[test]
+fn test_filter_excludes_from_zip() {
+    let mut names = packed_names("zip");
+    names.sort();
+    assert_eq!(names, vec!["README.md", "main.rs"]);
+}
+
+#[test]
+fn test_scan_counts_skipped_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    let inputs = [source];
+    let stats = filter().scan(&inputs, false);
+    assert_eq!(
+        stats,
+        FilterStats {
+            included_files: 2,
+            included_bytes: 12 + 6,
+            skipped_files: 4,
+            skipped_bytes: 20 + 6 + 8 + 64 * 1024,
+        }
+    );
+
+    // An empty filter skips nothing
+    let stats = PackFilter::default().scan(&inputs, false);
+    assert_eq!(stats.included_files, 6);
+    assert_eq!(stats.skipped_files, 0);
+}
+
+#[test]
+fn test_patterns_match_paths_and_components() {
+    let filter = PackFilter::new(&["build/*.o", "node_modules/"]).unwrap();
+    assert!(filter.excludes(Path::new("build/main.o"), false, 0));
+    assert!(!filter.excludes(Path::new("src/main.o"), false, 0));
+    assert!(filter.excludes(Path::new("web/node_modules"), true, 0));
+    assert!(!filter.excludes(Path::new("src/lib.rs"), false, 0));
+}
+
+#[test]
+fn test_invalid_pattern_is_rejected() {
+    assert!(PackFilter::new(&["[unclosed"]).is_err());
+    assert!(PackFilter::new(&["", "  "]).unwrap().is_empty());
+}
+
+#[test]
+fn test_default_vcs_patterns() {
+    let filter = PackFilter::default().exclude(DEFAULT_VCS_PATTERNS).unwrap();
+    assert!(filter.excludes(Path::new(".git"), true, 0));
+    assert!(filter.excludes(Path::new("web/node_modules/react/index.js"), false, 10));
+    assert!(filter.excludes(Path::new("pkg/__pycache__"), true, 0));
+    assert!(!filter.excludes(Path::new("src/git.rs"), false, 10));
+}
+
+#[test]
+fn test_manifest_follows_filter() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    let manifest = Manifest::from_directory_filtered(&source, &filter()).unwrap();
+    let mut names: Vec<_> = manifest
+        .files
+        .values()
+        .filter(|entry| !entry.is_dir)
+        .map(|entry| entry.path.to_string_lossy().replace('\\', "/"))
+        .collect();
+    names.sort();
+    assert_eq!(names, vec!["README.md", "src/main.rs"]);
+}
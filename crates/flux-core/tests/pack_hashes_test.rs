@@ -0,0 +1,77 @@
+//! Tests for hashing files while they are packed
+
+use flux_core::archive::{checksums, pack_with_strategy, HashAlgorithm, PackHashes, PackOptions};
+use flux_core::manifest::Manifest;
+use flux_testing::fixtures::SourceTree;
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn create_source(dir: &Path) -> std::path::PathBuf {
+    SourceTree::new()
+        .file("a.txt", "first file")
+        .file("nested/b.txt", "b".repeat(100_000))
+        .file("empty.txt", "")
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+fn pack_hashed(source: &Path, output: &Path, format: &str) -> PackHashes {
+    let hashes = PackHashes::new();
+    let options = PackOptions {
+        hashes: Some(hashes.clone()),
+        ..PackOptions::default()
+    };
+    pack_with_strategy(source, output, Some(format), options).unwrap();
+    hashes
+}
+
+#[test]
+fn test_manifest_from_packed_matches_a_fresh_scan() {
+    for format in ["tar", "tar.zst", "zip"] {
+        let temp_dir = TempDir::new().unwrap();
+        let source = create_source(temp_dir.path());
+        let output = temp_dir.path().join(format!("out.{}", format));
+        let hashes = pack_hashed(&source, &output, format);
+        assert_eq!(hashes.len(), 3, "{}", format);
+
+        let packed = Manifest::from_packed(&source, &Default::default(), &hashes).unwrap();
+        let scanned = Manifest::from_directory(&source).unwrap();
+        assert_eq!(packed.files.len(), scanned.files.len(), "{}", format);
+        for (path, entry) in &scanned.files {
+            assert_eq!(packed.files[path].hash, entry.hash, "{} {:?}", format, path);
+        }
+    }
+}
+
+#[test]
+fn test_packed_checksums_match_the_archive() {
+    for format in ["tar.gz", "zip"] {
+        let temp_dir = TempDir::new().unwrap();
+        let source = create_source(temp_dir.path());
+        let output = temp_dir.path().join(format!("out.{}", format));
+        let hashes = pack_hashed(&source, &output, format);
+
+        let mut from_archive = checksums(&output, HashAlgorithm::Blake3).unwrap();
+        from_archive.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(hashes.checksums(), from_archive, "{}", format);
+    }
+}
+
+#[test]
+fn test_files_changed_after_packing_are_hashed_again() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let output = temp_dir.path().join("out.tar.zst");
+    let hashes = pack_hashed(&source, &output, "tar.zst");
+
+    fs::write(source.join("a.txt"), "changed after packing").unwrap();
+    let packed = Manifest::from_packed(&source, &Default::default(), &hashes).unwrap();
+    let scanned = Manifest::from_directory(&source).unwrap();
+    let path = Path::new("a.txt");
+    assert_eq!(packed.files[path].hash, scanned.files[path].hash);
+    assert_ne!(
+        packed.files[path].hash,
+        hashes.get(&source.join("a.txt")).unwrap().hash
+    );
+}
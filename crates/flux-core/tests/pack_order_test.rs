@@ -0,0 +1,116 @@
+//! Tests for the order packed entries are stored in
+
+use flux_core::archive::{inspect, pack_with_strategy, PackOptions, PackOrder};
+use flux_testing::fixtures::SourceTree;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+fn create_source(dir: &Path) -> PathBuf {
+    SourceTree::new()
+        .file("app/src/main.rs", "fn main() {}")
+        .file("app/README.md", "# App")
+        .file("lib/lib.rs", "pub fn lib() {}")
+        .file("lib/README.md", "# Lib")
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+fn pack(source: &Path, archive: &Path, order: PackOrder) {
+    let options = PackOptions {
+        entry_order: order,
+        clamp_mtime: Some(1_700_000_000),
+        ..PackOptions::default()
+    };
+    pack_with_strategy(source, archive, Some("tar.zst"), options).unwrap();
+}
+
+/// Names of the archive's entries, as stored
+fn stored(archive: &Path) -> Vec<String> {
+    inspect(archive)
+        .unwrap()
+        .iter()
+        .map(|entry| {
+            let path = entry.path.to_string_lossy().replace('\\', "/");
+            path.trim_end_matches('/').to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn test_extension_order() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let archive = temp_dir.path().join("sorted.tar.zst");
+    pack(&source, &archive, PackOrder::Extension);
+
+    assert_eq!(
+        stored(&archive),
+        [
+            "source/app",
+            "source/app/src",
+            "source/lib",
+            "source/app/README.md",
+            "source/lib/README.md",
+            "source/app/src/main.rs",
+            "source/lib/lib.rs",
+        ]
+    );
+}
+
+#[test]
+fn test_sorted_orders_keep_every_entry() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let walk = temp_dir.path().join("walk.tar.zst");
+    pack(&source, &walk, PackOrder::Walk);
+
+    let mut expected = stored(&walk);
+    expected.sort();
+    for order in [PackOrder::Extension, PackOrder::Similar] {
+        let archive = temp_dir.path().join(format!("{}.tar.zst", order));
+        pack(&source, &archive, order);
+        let mut names = stored(&archive);
+        names.sort();
+        assert_eq!(names, expected, "{}", order);
+    }
+}
+
+#[test]
+fn test_sorted_order_is_reproducible() {
+    // The same tree created in another order packs into the same bytes
+    let temp_dir = TempDir::new().unwrap();
+    let first = create_source(&temp_dir.path().join("one"));
+    let second = temp_dir.path().join("two/source");
+    fs::create_dir_all(second.join("lib")).unwrap();
+    fs::create_dir_all(second.join("app/src")).unwrap();
+    for file in [
+        "lib/README.md",
+        "lib/lib.rs",
+        "app/README.md",
+        "app/src/main.rs",
+    ] {
+        fs::copy(first.join(file), second.join(file)).unwrap();
+    }
+    for path in [&first, &second] {
+        for entry in walkdir::WalkDir::new(path) {
+            let entry = entry.unwrap();
+            let mtime = filetime::FileTime::from_unix_time(1_600_000_000, 0);
+            filetime::set_file_mtime(entry.path(), mtime).unwrap();
+        }
+    }
+
+    let one = temp_dir.path().join("one.tar.zst");
+    let two = temp_dir.path().join("two.tar.zst");
+    pack(&first, &one, PackOrder::Extension);
+    pack(&second, &two, PackOrder::Extension);
+    assert_eq!(fs::read(&one).unwrap(), fs::read(&two).unwrap());
+}
+
+#[test]
+fn test_order_names() {
+    for order in [PackOrder::Walk, PackOrder::Extension, PackOrder::Similar] {
+        assert_eq!(order.name().parse::<PackOrder>().unwrap(), order);
+    }
+    assert!("size".parse::<PackOrder>().is_err());
+}
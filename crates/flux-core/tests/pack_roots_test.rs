@@ -0,0 +1,119 @@
+//! Tests for packing several inputs under names of their own
+
+use flux_core::archive::{extract, pack_roots, PackOptions, PackRoot};
+use flux_core::progress::NoProgress;
+use flux_core::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Two folders that each hold a `notes.txt`, and a project directory
+fn create_inputs(dir: &Path) -> Vec<PathBuf> {
+    for (folder, text) in [("home/work", "work notes"), ("home/play", "play notes")] {
+        fs::create_dir_all(dir.join(folder)).unwrap();
+        fs::write(dir.join(folder).join("notes.txt"), text).unwrap();
+    }
+    let project = dir.join("home/work/project");
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::write(project.join("src/main.rs"), "fn main() {}").unwrap();
+
+    vec![
+        dir.join("home/work/notes.txt"),
+        dir.join("home/play/notes.txt"),
+        project,
+    ]
+}
+
+#[test]
+fn test_inputs_keep_folders_below_common_base() {
+    let temp_dir = TempDir::new().unwrap();
+    let inputs = create_inputs(temp_dir.path());
+    let roots = PackRoot::below_common_base(&inputs);
+    assert_eq!(roots[1].name, Path::new("play/notes.txt"));
+
+    for format in ["tar.zst", "zip"] {
+        let archive = temp_dir.path().join(format!("roots.{}", format));
+        let outcome = pack_roots(
+            &roots,
+            &archive,
+            format,
+            &PackOptions::default(),
+            &NoProgress,
+        )
+        .unwrap();
+        assert!(outcome.is_complete(), "{}: {:?}", format, outcome.failed);
+
+        let output = temp_dir.path().join(format!("out-{}", format));
+        extract(&archive, &output).unwrap();
+        assert_eq!(
+            fs::read_to_string(output.join("work/notes.txt")).unwrap(),
+            "work notes",
+            "{}",
+            format
+        );
+        assert_eq!(
+            fs::read_to_string(output.join("play/notes.txt")).unwrap(),
+            "play notes",
+            "{}",
+            format
+        );
+        assert!(
+            output.join("work/project/src/main.rs").is_file(),
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_explicit_names() {
+    let temp_dir = TempDir::new().unwrap();
+    let inputs = create_inputs(temp_dir.path());
+    let roots = [
+        PackRoot::new(&inputs[0], "docs/work.txt"),
+        PackRoot::new(&inputs[1], "docs/play.txt"),
+        // The project's contents at the top of the archive
+        PackRoot::new(&inputs[2], ""),
+    ];
+
+    for format in ["tar", "zip"] {
+        let archive = temp_dir.path().join(format!("named.{}", format));
+        pack_roots(
+            &roots,
+            &archive,
+            format,
+            &PackOptions::default(),
+            &NoProgress,
+        )
+        .unwrap();
+
+        let output = temp_dir.path().join(format!("out-{}", format));
+        extract(&archive, &output).unwrap();
+        assert_eq!(
+            fs::read_to_string(output.join("docs/play.txt")).unwrap(),
+            "play notes"
+        );
+        assert!(output.join("docs/work.txt").is_file(), "{}", format);
+        assert!(output.join("src/main.rs").is_file(), "{}", format);
+    }
+}
+
+#[test]
+fn test_escaping_names_are_refused() {
+    let temp_dir = TempDir::new().unwrap();
+    let inputs = create_inputs(temp_dir.path());
+    let archive = temp_dir.path().join("escape.tar");
+
+    for name in ["../notes.txt", "/etc/notes.txt"] {
+        let roots = [PackRoot::new(&inputs[0], name)];
+        let result = pack_roots(
+            &roots,
+            &archive,
+            "tar",
+            &PackOptions::default(),
+            &NoProgress,
+        );
+        assert!(matches!(result, Err(Error::InvalidPath(_))), "{}", name);
+    }
+    assert!(!archive.exists());
+}
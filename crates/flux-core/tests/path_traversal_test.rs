@@ -1,8 +1,12 @@
 //! Integration tests for path traversal security
 
-use flux_core::archive::{create_secure_extractor, extractor::ExtractEntryOptions};
+use flux_core::archive::{
+    create_extractor, create_secure_extractor, extract_with_options,
+    extractor::ExtractEntryOptions, ExtractOptions,
+};
 use flux_core::security::{sanitize_path, validate_symlink};
 use flux_core::Error;
+use flux_testing::fixtures::{ArchiveBuilder, ArchiveKind};
 use std::fs::{self, File};
 use std::io::Write;
 use std::path::Path;
@@ -117,6 +121,9 @@ fn test_zip_path_traversal_protection() {
         preserve_permissions: true,
         preserve_timestamps: true,
         follow_symlinks: false,
+        password: None,
+        symlink_fallback: Default::default(),
+        strip_components: None,
     };
 
     let mut extracted_count = 0;
@@ -227,17 +234,18 @@ fn test_tar_secure_extraction() {
         preserve_permissions: true,
         preserve_timestamps: true,
         follow_symlinks: false,
+        password: None,
+        symlink_fallback: Default::default(),
+        strip_components: None,
     };
 
     let mut extracted_count = 0;
-    for entry_result in extractor.entries(&archive_path).unwrap() {
-        if let Ok(entry) = entry_result {
-            if extractor
-                .extract_entry(&archive_path, &entry, &extract_dir, options.clone())
-                .is_ok()
-            {
-                extracted_count += 1;
-            }
+    for entry in extractor.entries(&archive_path).unwrap().flatten() {
+        if extractor
+            .extract_entry(&archive_path, &entry, &extract_dir, options.clone())
+            .is_ok()
+        {
+            extracted_count += 1;
         }
     }
 
@@ -246,6 +254,192 @@ fn test_tar_secure_extraction() {
     assert!(extract_dir.join("subdir/file.txt").exists());
 }
 
+/// Each format's own extractor refuses entries that would land outside the
+/// destination, as `flux extract --interactive` extracts through them
+#[test]
+fn test_extract_entry_stays_in_destination() {
+    let temp_dir = TempDir::new().unwrap();
+    let outside = temp_dir.path().join("outside");
+    fs::create_dir(&outside).unwrap();
+    let options = ExtractEntryOptions {
+        overwrite: true,
+        preserve_permissions: true,
+        preserve_timestamps: true,
+        follow_symlinks: false,
+        password: None,
+        symlink_fallback: Default::default(),
+        strip_components: None,
+    };
+
+    for kind in [ArchiveKind::Tar, ArchiveKind::Zip, ArchiveKind::SevenZ] {
+        let archive_path = temp_dir
+            .path()
+            .join(format!("malicious.{}", kind.extension()));
+        ArchiveBuilder::new(kind)
+            .file("normal.txt", "normal")
+            .file("../evil.txt", "evil")
+            .file("escape/evil.txt", "evil")
+            .build(&archive_path)
+            .unwrap();
+        let extract_dir = temp_dir
+            .path()
+            .join(format!("extract_{}", kind.extension()));
+        fs::create_dir(&extract_dir).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&outside, extract_dir.join("escape")).unwrap();
+
+        let extractor = create_extractor(&archive_path).unwrap();
+        for entry in extractor.entries(&archive_path).unwrap() {
+            let entry = entry.unwrap();
+            let result =
+                extractor.extract_entry(&archive_path, &entry, &extract_dir, options.clone());
+            if entry.path == Path::new("normal.txt") {
+                result.unwrap();
+            } else {
+                assert!(
+                    matches!(result, Err(Error::InvalidPath(_))),
+                    "{:?} {:?}: {:?}",
+                    kind,
+                    entry.path,
+                    result
+                );
+            }
+        }
+
+        assert!(extract_dir.join("normal.txt").exists(), "{:?}", kind);
+        assert!(!temp_dir.path().join("evil.txt").exists(), "{:?}", kind);
+        assert!(!outside.join("evil.txt").exists(), "{:?}", kind);
+    }
+
+    // Nor may a symlink entry point out of it
+    let archive_path = temp_dir.path().join("links.tar");
+    ArchiveBuilder::new(ArchiveKind::Tar)
+        .symlink("passwd", "/etc/passwd")
+        .symlink("up", "../outside")
+        .build(&archive_path)
+        .unwrap();
+    let extract_dir = temp_dir.path().join("extract_links");
+    let extractor = create_extractor(&archive_path).unwrap();
+    for entry in extractor.entries(&archive_path).unwrap() {
+        let entry = entry.unwrap();
+        let result = extractor.extract_entry(&archive_path, &entry, &extract_dir, options.clone());
+        assert!(
+            matches!(result, Err(Error::InvalidPath(_))),
+            "{:?}",
+            entry.path
+        );
+    }
+    assert!(fs::symlink_metadata(extract_dir.join("passwd")).is_err());
+    assert!(fs::symlink_metadata(extract_dir.join("up")).is_err());
+}
+
+/// A hard link entry can neither link to nor be placed through a symlink
+/// that leads out of the destination
+#[cfg(unix)]
+#[test]
+fn test_tar_hard_link_stays_in_destination() {
+    let temp_dir = TempDir::new().unwrap();
+    let outside = temp_dir.path().join("outside");
+    fs::create_dir(&outside).unwrap();
+    fs::write(outside.join("secret.txt"), "secret").unwrap();
+
+    let archive_path = temp_dir.path().join("links.tar");
+    ArchiveBuilder::new(ArchiveKind::Tar)
+        .file("real.txt", "real")
+        .hard_link("stolen.txt", "escape/secret.txt")
+        .hard_link("escape/planted.txt", "real.txt")
+        .build(&archive_path)
+        .unwrap();
+    let extract_dir = temp_dir.path().join("extract");
+    fs::create_dir(&extract_dir).unwrap();
+    std::os::unix::fs::symlink(&outside, extract_dir.join("escape")).unwrap();
+
+    let outcome =
+        extract_with_options(&archive_path, &extract_dir, ExtractOptions::default()).unwrap();
+
+    assert_eq!(outcome.failed.len(), 2, "{}", outcome);
+    assert!(outcome
+        .failed
+        .iter()
+        .all(|failure| matches!(failure.error.inner(), Error::InvalidPath(_))));
+    assert!(fs::symlink_metadata(extract_dir.join("stolen.txt")).is_err());
+    assert!(!outside.join("planted.txt").exists());
+    assert_eq!(
+        fs::read_to_string(extract_dir.join("real.txt")).unwrap(),
+        "real"
+    );
+}
+
+/// Symlinks with absolute targets or targets above the destination are
+/// not created
+#[cfg(unix)]
+#[test]
+fn test_tar_symlinks_stay_in_destination() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive_path = temp_dir.path().join("links.tar");
+    ArchiveBuilder::new(ArchiveKind::Tar)
+        .symlink("abs_link", "/etc/passwd")
+        .symlink("escape", "../../outside")
+        .symlink("nested/sibling", "../kept.txt")
+        .file("kept.txt", "kept")
+        .build(&archive_path)
+        .unwrap();
+    let extract_dir = temp_dir.path().join("nested/extract");
+
+    let outcome =
+        extract_with_options(&archive_path, &extract_dir, ExtractOptions::default()).unwrap();
+
+    assert_eq!(outcome.failed.len(), 2, "{}", outcome);
+    assert!(outcome
+        .failed
+        .iter()
+        .all(|failure| matches!(failure.error.inner(), Error::InvalidPath(_))));
+    assert!(fs::symlink_metadata(extract_dir.join("abs_link")).is_err());
+    assert!(fs::symlink_metadata(extract_dir.join("escape")).is_err());
+    assert_eq!(
+        fs::read_to_string(extract_dir.join("nested/sibling")).unwrap(),
+        "kept"
+    );
+}
+
+/// A file is not written through a symlink the archive extracted earlier
+/// that leads out of the destination
+#[cfg(unix)]
+#[test]
+fn test_tar_file_through_extracted_symlink_stays_in_destination() {
+    let temp_dir = TempDir::new().unwrap();
+    let outside = temp_dir.path().join("outside");
+    fs::create_dir(&outside).unwrap();
+
+    // The second link looks like it stays inside as long as the first is
+    // taken for a directory two levels down, which it is not
+    let archive_path = temp_dir.path().join("escape.tar");
+    ArchiveBuilder::new(ArchiveKind::Tar)
+        .symlink("escape", "../../outside")
+        .file("escape/pwned.txt", "pwned")
+        .dir("a/b")
+        .symlink("a/b/top", "../..")
+        .symlink("a/b/top/up", "../../outside")
+        .file("a/b/top/up/pwned.txt", "pwned")
+        .file("kept.txt", "kept")
+        .build(&archive_path)
+        .unwrap();
+    let extract_dir = temp_dir.path().join("nested/extract");
+
+    let outcome =
+        extract_with_options(&archive_path, &extract_dir, ExtractOptions::default()).unwrap();
+
+    assert!(outcome
+        .failed
+        .iter()
+        .any(|failure| failure.path == Path::new("a/b/top/up/pwned.txt")));
+    assert!(!outside.join("pwned.txt").exists());
+    assert_eq!(
+        fs::read_to_string(extract_dir.join("kept.txt")).unwrap(),
+        "kept"
+    );
+}
+
 /// Test compression bomb detection
 #[test]
 fn test_compression_bomb_detection() {
@@ -0,0 +1,72 @@
+//! Tests for lowering process priority
+
+use flux_core::priority::{lower_priority, lower_thread_priority, Priority};
+
+/// Niceness of the calling thread
+#[cfg(target_os = "linux")]
+fn thread_niceness() -> i64 {
+    let stat = std::fs::read_to_string("/proc/thread-self/stat").unwrap();
+    // Fields after the command name, which is in parentheses
+    let fields: Vec<&str> = stat
+        .rsplit_once(')')
+        .unwrap()
+        .1
+        .split_whitespace()
+        .collect();
+    fields[16].parse().unwrap()
+}
+
+#[test]
+fn test_rejects_out_of_range_levels() {
+    assert!(lower_priority(Priority::Nice(0)).is_err());
+    assert!(lower_priority(Priority::Nice(20)).is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_nice_is_inherited_by_new_threads() {
+    // Niceness is per thread on Linux, so the test's own thread is left alone
+    std::thread::spawn(|| {
+        let before = thread_niceness();
+        lower_priority(Priority::Nice(5)).unwrap();
+        let after = thread_niceness();
+        assert_eq!(after, (before + 5).min(19));
+
+        let worker = std::thread::spawn(thread_niceness).join().unwrap();
+        assert_eq!(worker, after);
+    })
+    .join()
+    .unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_background_uses_lowest_cpu_priority() {
+    std::thread::spawn(|| {
+        // Some sandboxes refuse to change the I/O class
+        let _ = lower_priority(Priority::Background);
+        assert_eq!(thread_niceness(), 19);
+    })
+    .join()
+    .unwrap();
+}
+
+#[test]
+fn test_thread_priority_rejects_out_of_range_levels() {
+    assert!(lower_thread_priority(Priority::Nice(0)).is_err());
+    assert!(lower_thread_priority(Priority::Nice(20)).is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_thread_priority_leaves_other_threads_alone() {
+    let before = thread_niceness();
+    std::thread::spawn(|| {
+        let before = thread_niceness();
+        lower_thread_priority(Priority::Nice(3)).unwrap();
+        assert_eq!(thread_niceness(), (before + 3).min(19));
+    })
+    .join()
+    .unwrap();
+    assert_eq!(thread_niceness(), before);
+}
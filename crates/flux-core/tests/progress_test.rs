@@ -0,0 +1,280 @@
+//! Tests for progress reporting while packing and extracting
+
+use flux_core::archive::extractor::ExtractEntryOptions;
+use flux_core::archive::{
+    create_extractor, inspect, pack_multiple, pack_with_progress, PackOptions,
+};
+use flux_core::progress::ProgressSink;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use tempfile::TempDir;
+
+#[derive(Default)]
+struct Recorder {
+    total: AtomicU64,
+    advanced: AtomicU64,
+    written: AtomicU64,
+    files: Mutex<Vec<PathBuf>>,
+    cancelled: AtomicBool,
+}
+
+impl ProgressSink for Recorder {
+    fn start(&self, total: u64) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    fn file(&self, path: &Path) {
+        self.files.lock().unwrap().push(path.to_path_buf());
+    }
+
+    fn advance(&self, bytes: u64) {
+        self.advanced.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    fn written(&self, bytes: u64) {
+        self.written.store(bytes, Ordering::Relaxed);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// A folder with two files and a loose file next to it, 35 000 bytes in all
+fn create_inputs(dir: &Path) -> Vec<PathBuf> {
+    let folder = dir.join("folder");
+    fs::create_dir_all(folder.join("sub")).unwrap();
+    fs::write(folder.join("a.txt"), "a".repeat(10_000)).unwrap();
+    fs::write(folder.join("sub/b.txt"), "b".repeat(20_000)).unwrap();
+    let loose = dir.join("loose.txt");
+    fs::write(&loose, "c".repeat(5_000)).unwrap();
+    vec![folder, loose]
+}
+
+fn entry_names(archive: &Path) -> Vec<String> {
+    let mut names: Vec<String> = inspect(archive)
+        .unwrap()
+        .into_iter()
+        .filter(|entry| !entry.is_dir)
+        .map(|entry| entry.path.to_string_lossy().replace('\\', "/"))
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_pack_multiple_reports_bytes() {
+    for format in ["tar", "tar.gz", "tar.zst", "tar.xz", "tar.br", "zip"] {
+        let temp_dir = TempDir::new().unwrap();
+        let inputs = create_inputs(temp_dir.path());
+        let output = temp_dir.path().join(format!("out.{}", format));
+        let recorder = Recorder::default();
+
+        pack_multiple(
+            &inputs,
+            &output,
+            format,
+            Some(temp_dir.path()),
+            &PackOptions::default(),
+            &recorder,
+        )
+        .unwrap();
+
+        assert_eq!(recorder.total.load(Ordering::Relaxed), 35_000, "{}", format);
+        assert_eq!(
+            recorder.advanced.load(Ordering::Relaxed),
+            35_000,
+            "{}",
+            format
+        );
+        assert_eq!(recorder.files.lock().unwrap().len(), 3, "{}", format);
+        assert_eq!(
+            recorder.written.load(Ordering::Relaxed),
+            fs::metadata(&output).unwrap().len(),
+            "{}",
+            format
+        );
+        assert_eq!(
+            entry_names(&output),
+            ["folder/a.txt", "folder/sub/b.txt", "loose.txt"],
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_pack_with_progress_reports_bytes() {
+    for format in ["tar", "tar.zst", "zip"] {
+        let temp_dir = TempDir::new().unwrap();
+        let folder = create_inputs(temp_dir.path()).remove(0);
+        let output = temp_dir.path().join(format!("out.{}", format));
+        let recorder = Recorder::default();
+
+        pack_with_progress(
+            &folder,
+            &output,
+            Some(format),
+            PackOptions::default(),
+            &recorder,
+        )
+        .unwrap();
+
+        assert_eq!(recorder.total.load(Ordering::Relaxed), 30_000, "{}", format);
+        assert_eq!(
+            recorder.advanced.load(Ordering::Relaxed),
+            30_000,
+            "{}",
+            format
+        );
+        assert_eq!(recorder.files.lock().unwrap().len(), 2, "{}", format);
+        assert_eq!(
+            recorder.written.load(Ordering::Relaxed),
+            fs::metadata(&output).unwrap().len(),
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_pack_multiple_without_base_dir() {
+    let temp_dir = TempDir::new().unwrap();
+    let inputs = create_inputs(temp_dir.path());
+    for format in ["tar", "zip"] {
+        let output = temp_dir.path().join(format!("out.{}", format));
+
+        pack_multiple(
+            &inputs,
+            &output,
+            format,
+            None,
+            &PackOptions::default(),
+            &Recorder::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            entry_names(&output),
+            ["folder/a.txt", "folder/sub/b.txt", "loose.txt"],
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_pack_multiple_stops_when_cancelled() {
+    for format in ["tar.zst", "zip"] {
+        let temp_dir = TempDir::new().unwrap();
+        let inputs = create_inputs(temp_dir.path());
+        let output = temp_dir.path().join(format!("out.{}", format));
+        let recorder = Recorder::default();
+        recorder.cancelled.store(true, Ordering::Relaxed);
+
+        let result = pack_multiple(
+            &inputs,
+            &output,
+            format,
+            Some(temp_dir.path()),
+            &PackOptions::default(),
+            &recorder,
+        );
+        assert!(result.is_err(), "{}", format);
+        assert_eq!(recorder.advanced.load(Ordering::Relaxed), 0, "{}", format);
+    }
+}
+
+#[test]
+fn test_extract_entry_with_progress_reports_bytes() {
+    for format in ["tar", "tar.zst", "zip"] {
+        let temp_dir = TempDir::new().unwrap();
+        let folder = create_inputs(temp_dir.path()).remove(0);
+        let archive = temp_dir.path().join(format!("out.{}", format));
+        pack_with_progress(
+            &folder,
+            &archive,
+            Some(format),
+            PackOptions::default(),
+            &Recorder::default(),
+        )
+        .unwrap();
+
+        let output = temp_dir.path().join("extracted");
+        let extractor = create_extractor(&archive).unwrap();
+        let recorder = Recorder::default();
+        for entry in extractor.entries(&archive).unwrap() {
+            extractor
+                .extract_entry_with_progress(
+                    &archive,
+                    &entry.unwrap(),
+                    &output,
+                    ExtractEntryOptions::default(),
+                    &recorder,
+                )
+                .unwrap();
+        }
+        assert_eq!(
+            recorder.advanced.load(Ordering::Relaxed),
+            30_000,
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_extract_entry_stops_when_cancelled() {
+    for format in ["tar.zst", "zip"] {
+        let temp_dir = TempDir::new().unwrap();
+        let folder = create_inputs(temp_dir.path()).remove(0);
+        let archive = temp_dir.path().join(format!("out.{}", format));
+        pack_with_progress(
+            &folder,
+            &archive,
+            Some(format),
+            PackOptions::default(),
+            &Recorder::default(),
+        )
+        .unwrap();
+
+        let extractor = create_extractor(&archive).unwrap();
+        let entry = extractor
+            .entries(&archive)
+            .unwrap()
+            .map(Result::unwrap)
+            .find(|entry| !entry.is_dir)
+            .unwrap();
+        let recorder = Recorder::default();
+        recorder.cancelled.store(true, Ordering::Relaxed);
+
+        let result = extractor.extract_entry_with_progress(
+            &archive,
+            &entry,
+            &temp_dir.path().join("extracted"),
+            ExtractEntryOptions::default(),
+            &recorder,
+        );
+        assert!(result.is_err(), "{}", format);
+        assert_eq!(recorder.advanced.load(Ordering::Relaxed), 0, "{}", format);
+    }
+}
+
+#[test]
+fn test_pack_multiple_unsupported_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let inputs = create_inputs(temp_dir.path());
+    let output = temp_dir.path().join("out.7z");
+
+    let result = pack_multiple(
+        &inputs,
+        &output,
+        "7z",
+        None,
+        &PackOptions::default(),
+        &Recorder::default(),
+    );
+    assert!(result.is_err());
+}
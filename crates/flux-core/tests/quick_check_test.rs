@@ -0,0 +1,114 @@
+//! Tests for checking a directory against its manifest by metadata alone
+
+use filetime::{set_file_mtime, FileTime};
+use flux_core::archive::PackFilter;
+use flux_core::manifest::Manifest;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tempfile::TempDir;
+
+/// A folder `project` with a file at the top and two in `src`
+fn create_project(dir: &Path) -> PathBuf {
+    let project = dir.join("project");
+    fs::create_dir_all(project.join("src")).unwrap();
+    fs::write(project.join("README.md"), "readme").unwrap();
+    fs::write(project.join("src/lib.rs"), "pub fn new() {}").unwrap();
+    fs::write(project.join("src/main.rs"), "fn main() {}").unwrap();
+    project
+}
+
+fn ago(secs: u64) -> FileTime {
+    FileTime::from_system_time(SystemTime::now() - Duration::from_secs(secs))
+}
+
+#[test]
+fn test_unchanged_directory_has_no_drift() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = create_project(temp_dir.path());
+    let manifest = Manifest::from_directory(&project).unwrap();
+
+    let drift = manifest
+        .quick_check(&project, &PackFilter::default())
+        .unwrap();
+    assert!(!drift.diff.has_changes(), "{:?}", drift.diff);
+    assert_eq!(drift.drift_secs, 0);
+}
+
+#[test]
+fn test_changes_are_found_by_size_and_mtime() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = create_project(temp_dir.path());
+    let mut manifest = Manifest::from_directory(&project).unwrap();
+    manifest.created -= 3600;
+
+    fs::write(project.join("src/lib.rs"), "pub fn changed() {}").unwrap();
+    fs::write(project.join("NEW.md"), "new").unwrap();
+    fs::remove_file(project.join("src/main.rs")).unwrap();
+
+    let drift = manifest
+        .quick_check(&project, &PackFilter::default())
+        .unwrap();
+    assert_eq!(drift.diff.added, [PathBuf::from("NEW.md")]);
+    assert_eq!(drift.diff.modified, [PathBuf::from("src/lib.rs")]);
+    assert_eq!(drift.diff.deleted, [PathBuf::from("src/main.rs")]);
+    // The deletion counts from when the manifest was made
+    assert!(
+        (3600..3700).contains(&drift.drift_secs),
+        "{}",
+        drift.drift_secs
+    );
+    assert!(drift.manifest_age_secs >= 3600);
+}
+
+#[test]
+fn test_drift_counts_from_oldest_change() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = create_project(temp_dir.path());
+    let mut manifest = Manifest::from_directory(&project).unwrap();
+    manifest.created -= 3600;
+
+    fs::write(project.join("NEW.md"), "new").unwrap();
+    set_file_mtime(project.join("NEW.md"), ago(1800)).unwrap();
+    fs::write(project.join("README.md"), "readme, longer").unwrap();
+
+    let drift = manifest
+        .quick_check(&project, &PackFilter::default())
+        .unwrap();
+    assert_eq!(drift.diff.change_count(), 2);
+    assert!(
+        (1800..1900).contains(&drift.drift_secs),
+        "{}",
+        drift.drift_secs
+    );
+}
+
+#[test]
+fn test_contents_are_not_read() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = create_project(temp_dir.path());
+    let lib = project.join("src/lib.rs");
+    let mtime = FileTime::from_last_modification_time(&fs::metadata(&lib).unwrap());
+    let manifest = Manifest::from_directory(&project).unwrap();
+
+    // Same size and modification time: only a hashing scan can tell
+    fs::write(&lib, "pub fn old() {}").unwrap();
+    set_file_mtime(&lib, mtime).unwrap();
+
+    let drift = manifest
+        .quick_check(&project, &PackFilter::default())
+        .unwrap();
+    assert!(!drift.diff.has_changes(), "{:?}", drift.diff);
+}
+
+#[test]
+fn test_excluded_paths_are_skipped() {
+    let temp_dir = TempDir::new().unwrap();
+    let project = create_project(temp_dir.path());
+    let filter = PackFilter::new(&["*.log"]).unwrap();
+    let manifest = Manifest::from_directory_filtered(&project, &filter).unwrap();
+
+    fs::write(project.join("debug.log"), "noise").unwrap();
+    let drift = manifest.quick_check(&project, &filter).unwrap();
+    assert!(!drift.diff.has_changes(), "{:?}", drift.diff);
+}
@@ -0,0 +1,319 @@
+//! Tests for carrying on interrupted extractions from their journal
+
+use flux_core::archive::extractor::ExtractEntryOptions;
+use flux_core::archive::resume::{ArchiveIdentity, PartialFile, RESUME_VERSION};
+use flux_core::archive::{
+    extract_with_options, pack_roots, resumable, ExtractOptions, PackOptions, PackRoot,
+    ResumeJournal, SkipReason, RESUME_FILE,
+};
+use flux_core::ops::ExtractJob;
+use flux_core::progress::{NoProgress, ProgressSink};
+use flux_testing::fixtures::{ArchiveBuilder, ArchiveKind};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// An archive holding a folder `project` with a file at the top and one
+/// further down
+fn create_archive(dir: &Path, kind: ArchiveKind) -> PathBuf {
+    let archive = dir.join(format!("project.{}", kind.extension()));
+    ArchiveBuilder::new(kind)
+        .dir("project")
+        .file("project/README.md", "from the archive")
+        .dir("project/src")
+        .dir("project/src/deep")
+        .file("project/src/deep/lib.rs", "pub fn new() {}")
+        .build(&archive)
+        .unwrap();
+    archive
+}
+
+/// Leave the journal of a run over `archive` that started at `started`
+/// and finished `completed`
+fn write_journal(
+    archive: &Path,
+    output: &Path,
+    started: u64,
+    completed: &[&str],
+    partial: Option<PartialFile>,
+) {
+    let journal = ResumeJournal {
+        version: RESUME_VERSION,
+        archive: ArchiveIdentity::of(archive).unwrap(),
+        started,
+        completed: completed
+            .iter()
+            .map(|path| {
+                let size = fs::metadata(output.join(path)).unwrap().len();
+                (PathBuf::from(path), size)
+            })
+            .collect::<BTreeMap<_, _>>(),
+        partial,
+    };
+    fs::create_dir_all(output).unwrap();
+    fs::write(
+        output.join(RESUME_FILE),
+        serde_json::to_vec(&journal).unwrap(),
+    )
+    .unwrap();
+}
+
+fn resuming() -> ExtractOptions {
+    ExtractOptions {
+        overwrite: true,
+        skip: false,
+        resume: true,
+        ..ExtractOptions::default()
+    }
+}
+
+#[test]
+fn test_resume_skips_finished_files() {
+    for kind in [ArchiveKind::TarGz, ArchiveKind::Zip, ArchiveKind::SevenZ] {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = create_archive(temp_dir.path(), kind);
+        let output = temp_dir.path().join("out");
+        fs::create_dir_all(output.join("project")).unwrap();
+        fs::write(output.join("project/README.md"), "written earlier").unwrap();
+        write_journal(&archive, &output, u64::MAX, &["project/README.md"], None);
+
+        let outcome = extract_with_options(&archive, &output, resuming()).unwrap();
+        assert_eq!(
+            fs::read_to_string(output.join("project/README.md")).unwrap(),
+            "written earlier",
+            "{:?}",
+            kind
+        );
+        assert_eq!(
+            fs::read_to_string(output.join("project/src/deep/lib.rs")).unwrap(),
+            "pub fn new() {}"
+        );
+        assert!(
+            outcome
+                .skipped
+                .iter()
+                .any(|skipped| skipped.reason == SkipReason::Resumed),
+            "{:?}",
+            kind
+        );
+        assert!(!output.join(RESUME_FILE).exists(), "{:?}", kind);
+    }
+}
+
+#[test]
+fn test_resume_rewrites_files_left_unfinished() {
+    for kind in [ArchiveKind::Tar, ArchiveKind::Zip, ArchiveKind::SevenZ] {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = create_archive(temp_dir.path(), kind);
+        let output = temp_dir.path().join("out");
+        fs::create_dir_all(output.join("project/src/deep")).unwrap();
+        fs::write(output.join("project/src/deep/lib.rs"), "pub fn").unwrap();
+        write_journal(&archive, &output, 0, &[], None);
+
+        // Skipping existing files still replaces what the earlier run cut off
+        let options = ExtractOptions {
+            resume: true,
+            ..ExtractOptions::default()
+        };
+        extract_with_options(&archive, &output, options).unwrap();
+        assert_eq!(
+            fs::read_to_string(output.join("project/src/deep/lib.rs")).unwrap(),
+            "pub fn new() {}",
+            "{:?}",
+            kind
+        );
+    }
+}
+
+#[test]
+fn test_resume_carries_on_from_checkpoint() {
+    for kind in [ArchiveKind::Zip, ArchiveKind::SevenZ, ArchiveKind::TarZst] {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = create_archive(temp_dir.path(), kind);
+        let output = temp_dir.path().join("out");
+        fs::create_dir_all(output.join("project")).unwrap();
+        // Only the checkpointed bytes are kept, and they are not written
+        // again
+        fs::write(output.join("project/README.md"), "FROM the").unwrap();
+        let partial = PartialFile {
+            path: PathBuf::from("project/README.md"),
+            offset: 4,
+            hash: blake3::hash(b"FROM").to_hex().to_string(),
+        };
+        write_journal(&archive, &output, 0, &[], Some(partial));
+
+        extract_with_options(&archive, &output, resuming()).unwrap();
+        assert_eq!(
+            fs::read_to_string(output.join("project/README.md")).unwrap(),
+            "FROM the archive",
+            "{:?}",
+            kind
+        );
+    }
+}
+
+#[test]
+fn test_resume_restarts_file_whose_checkpoint_does_not_match() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), ArchiveKind::Zip);
+    let output = temp_dir.path().join("out");
+    fs::create_dir_all(output.join("project")).unwrap();
+    fs::write(output.join("project/README.md"), "FROM the").unwrap();
+    let partial = PartialFile {
+        path: PathBuf::from("project/README.md"),
+        offset: 4,
+        hash: blake3::hash(b"from").to_hex().to_string(),
+    };
+    write_journal(&archive, &output, 0, &[], Some(partial));
+
+    extract_with_options(&archive, &output, resuming()).unwrap();
+    assert_eq!(
+        fs::read_to_string(output.join("project/README.md")).unwrap(),
+        "from the archive"
+    );
+}
+
+#[test]
+fn test_resume_ignores_journal_of_another_archive() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), ArchiveKind::TarGz);
+    let other = temp_dir.path().join("other.bin");
+    fs::write(&other, "not the archive").unwrap();
+    let output = temp_dir.path().join("out");
+    fs::create_dir_all(output.join("project")).unwrap();
+    fs::write(output.join("project/README.md"), "written earlier").unwrap();
+    write_journal(&other, &output, u64::MAX, &["project/README.md"], None);
+    assert!(resumable(&archive, &output).unwrap().is_none());
+
+    let outcome = extract_with_options(&archive, &output, resuming()).unwrap();
+    assert_eq!(
+        fs::read_to_string(output.join("project/README.md")).unwrap(),
+        "from the archive"
+    );
+    assert!(outcome.skipped.is_empty(), "{:?}", outcome.skipped);
+}
+
+#[test]
+fn test_journal_is_found_for_its_archive() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), ArchiveKind::Zip);
+    let output = temp_dir.path().join("out");
+    fs::create_dir_all(output.join("project")).unwrap();
+    fs::write(output.join("project/README.md"), "from the archive").unwrap();
+    write_journal(&archive, &output, 0, &["project/README.md"], None);
+
+    // A copy elsewhere, as a fresh download of the same archive would be
+    let copy = temp_dir.path().join("download.tmp");
+    fs::copy(&archive, &copy).unwrap();
+    let journal = resumable(&copy, &output).unwrap().unwrap();
+    assert_eq!(journal.completed.len(), 1);
+}
+
+#[test]
+fn test_extract_job_resumes() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), ArchiveKind::Zip);
+    let output = temp_dir.path().join("out");
+    fs::create_dir_all(output.join("project")).unwrap();
+    fs::write(output.join("project/README.md"), "written earlier").unwrap();
+    write_journal(&archive, &output, u64::MAX, &["project/README.md"], None);
+
+    let job = ExtractJob {
+        resume: true,
+        ..ExtractJob::new(
+            &archive,
+            &output,
+            ExtractEntryOptions {
+                overwrite: true,
+                ..Default::default()
+            },
+        )
+    };
+    let outcome = job.run(&NoProgress, |_, _| None).unwrap();
+    assert_eq!(
+        fs::read_to_string(output.join("project/README.md")).unwrap(),
+        "written earlier"
+    );
+    assert_eq!(
+        fs::read_to_string(output.join("project/src/deep/lib.rs")).unwrap(),
+        "pub fn new() {}"
+    );
+    assert_eq!(outcome.skipped.len(), 1);
+    assert_eq!(outcome.skipped[0].reason, SkipReason::Resumed);
+    assert!(!output.join(RESUME_FILE).exists());
+}
+
+/// Set, to the directory to work in, in the copy of this test binary that
+/// is killed part way
+const KILLED_RUN_ENV: &str = "FLUX_TEST_KILLED_RUN";
+
+/// Aborts the process once the first bytes of an entry are written
+struct AbortMidWrite;
+
+impl ProgressSink for AbortMidWrite {
+    fn advance(&self, _bytes: u64) {
+        std::process::abort();
+    }
+}
+
+#[test]
+fn test_resume_after_kill_before_first_save() {
+    if let Some(dir) = std::env::var_os(KILLED_RUN_ENV) {
+        // Nothing is saved on the way out of an abort, as with SIGKILL
+        let dir = PathBuf::from(dir);
+        let job = ExtractJob {
+            resume: true,
+            ..ExtractJob::new(
+                dir.join("big.zip"),
+                dir.join("out"),
+                ExtractEntryOptions::default(),
+            )
+        };
+        let _ = job.run(&AbortMidWrite, |_, _| None);
+        unreachable!("the extraction is aborted");
+    }
+
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("big.bin");
+    // Incompressible, so the write takes more than one read
+    let mut state = 0x2545_f491_u32;
+    let data: Vec<u8> = (0..4 * 1024 * 1024)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect();
+    fs::write(&source, &data).unwrap();
+    let archive = temp_dir.path().join("big.zip");
+    pack_roots(
+        &[PackRoot::new(&source, "big.bin")],
+        &archive,
+        "zip",
+        &PackOptions::default(),
+        &NoProgress,
+    )
+    .unwrap();
+    let output = temp_dir.path().join("out");
+
+    let status = std::process::Command::new(std::env::current_exe().unwrap())
+        .args(["--exact", "test_resume_after_kill_before_first_save"])
+        .env(KILLED_RUN_ENV, temp_dir.path())
+        .status()
+        .unwrap();
+    assert!(!status.success());
+    assert!(output.join(RESUME_FILE).exists());
+    assert_ne!(fs::read(output.join("big.bin")).unwrap(), data);
+
+    // Even keeping existing files, the one cut off is written again
+    let options = ExtractOptions {
+        resume: true,
+        ..ExtractOptions::default()
+    };
+    let outcome = extract_with_options(&archive, &output, options).unwrap();
+    assert!(outcome.skipped.is_empty(), "{:?}", outcome.skipped);
+    assert_eq!(fs::read(output.join("big.bin")).unwrap(), data);
+    assert!(!output.join(RESUME_FILE).exists());
+}
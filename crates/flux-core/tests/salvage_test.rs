@@ -0,0 +1,305 @@
+//! Tests for recovering data from damaged archives
+
+use flux_core::archive::{pack_multiple, salvage, ExtractOptions, PackOptions};
+use flux_core::progress::NoProgress;
+use flux_core::Error;
+use flux_testing::fixtures::{ArchiveBuilder, ArchiveKind};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use zip::CompressionMethod;
+
+fn position(haystack: &[u8], needle: &[u8]) -> usize {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .unwrap()
+}
+
+/// A zip holding `a.txt`, `b.txt` and `c.txt`, with `b.txt` stored
+/// uncompressed so its data is easy to find
+fn write_zip(path: &Path, streamed: bool) {
+    let entries = [
+        ("a.txt", "a".repeat(3000), CompressionMethod::Deflated),
+        ("b.txt", "B".repeat(3000), CompressionMethod::Stored),
+        ("c.txt", "c".repeat(3000), CompressionMethod::Deflated),
+    ];
+    if streamed {
+        fs::write(path, streamed_zip(&entries)).unwrap();
+        return;
+    }
+    entries
+        .iter()
+        .fold(
+            ArchiveBuilder::new(ArchiveKind::Zip).stored(),
+            |builder, (name, content, _)| builder.file(name, content),
+        )
+        .build(path)
+        .unwrap();
+}
+
+/// Local entries the way a streaming writer lays them out: zero sizes in
+/// the header and the real ones in a data descriptor after the data, with
+/// no central directory
+fn streamed_zip(entries: &[(&str, String, CompressionMethod)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (name, content, method) in entries {
+        let data = match method {
+            CompressionMethod::Stored => content.as_bytes().to_vec(),
+            _ => {
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(content.as_bytes()).unwrap();
+                encoder.finish().unwrap()
+            }
+        };
+        let (method, compressed_size) = match method {
+            // A stored entry cannot be found without its size
+            CompressionMethod::Stored => (0u16, data.len() as u32),
+            _ => (8u16, 0),
+        };
+        let crc = crc32fast::hash(content.as_bytes());
+
+        out.extend_from_slice(&[0x50, 0x4b, 0x03, 0x04, 20, 0]);
+        out.extend_from_slice(&(1u16 << 3).to_le_bytes());
+        out.extend_from_slice(&method.to_le_bytes());
+        out.extend_from_slice(&[0; 8]);
+        out.extend_from_slice(&compressed_size.to_le_bytes());
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(&[0; 2]);
+        out.extend_from_slice(name.as_bytes());
+        out.extend_from_slice(&data);
+        out.extend_from_slice(&[0x50, 0x4b, 0x07, 0x08]);
+        out.extend_from_slice(&crc.to_le_bytes());
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    }
+    out
+}
+
+fn sorted(paths: &[PathBuf]) -> Vec<String> {
+    let mut names: Vec<String> = paths
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_zip_skips_corrupt_entry() {
+    for streamed in [false, true] {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = temp_dir.path().join("damaged.zip");
+        write_zip(&archive, streamed);
+
+        let mut bytes = fs::read(&archive).unwrap();
+        let data = position(&bytes, &[b'B'; 64]);
+        bytes[data + 1000..data + 1100].fill(0xff);
+        fs::write(&archive, &bytes).unwrap();
+
+        let output = temp_dir.path().join("out");
+        let report = salvage(&archive, &output, &ExtractOptions::default()).unwrap();
+
+        assert_eq!(sorted(&report.recovered), ["a.txt", "c.txt"]);
+        assert_eq!(report.lost.len(), 1);
+        assert_eq!(report.lost[0].path, Some(PathBuf::from("b.txt")));
+        assert!(report.damaged_bytes > 3000);
+        assert!(!report.is_complete());
+
+        assert_eq!(
+            fs::read_to_string(output.join("c.txt")).unwrap(),
+            "c".repeat(3000)
+        );
+        assert!(!output.join("b.txt").exists());
+    }
+}
+
+#[test]
+fn test_zip_without_central_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("cut.zip");
+    write_zip(&archive, false);
+
+    let bytes = fs::read(&archive).unwrap();
+    let central_directory = position(&bytes, &[0x50, 0x4b, 0x01, 0x02]);
+    fs::write(&archive, &bytes[..central_directory]).unwrap();
+
+    let output = temp_dir.path().join("out");
+    let report = salvage(&archive, &output, &ExtractOptions::default()).unwrap();
+
+    assert_eq!(sorted(&report.recovered), ["a.txt", "b.txt", "c.txt"]);
+    assert!(report.is_complete());
+    assert_eq!(
+        fs::read_to_string(output.join("b.txt")).unwrap(),
+        "B".repeat(3000)
+    );
+}
+
+/// A plain tar of three 1000-byte files, each taking one header block and
+/// two data blocks
+fn write_tar(path: &Path) {
+    ArchiveBuilder::new(ArchiveKind::Tar)
+        .file("a.txt", [b'a'; 1000])
+        .file("b.txt", [b'b'; 1000])
+        .file("c.txt", [b'c'; 1000])
+        .build(path)
+        .unwrap();
+}
+
+#[test]
+fn test_tar_resynchronizes_after_corrupt_header() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("damaged.tar");
+    write_tar(&archive);
+
+    let mut bytes = fs::read(&archive).unwrap();
+    // The header of b.txt follows a.txt's header and data
+    bytes[1536..1546].copy_from_slice(b"garbage!!!");
+    fs::write(&archive, &bytes).unwrap();
+
+    let output = temp_dir.path().join("out");
+    let report = salvage(&archive, &output, &ExtractOptions::default()).unwrap();
+
+    assert_eq!(sorted(&report.recovered), ["a.txt", "c.txt"]);
+    assert_eq!(report.damaged_bytes, 1536);
+    assert!(report.error.is_none());
+    assert!(!report.is_complete());
+    assert_eq!(fs::read(output.join("c.txt")).unwrap(), vec![b'c'; 1000]);
+}
+
+#[test]
+fn test_truncated_compressed_tar() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("source");
+    fs::create_dir(&source).unwrap();
+    // Pseudo-random content so the compressed stream stays large
+    let mut state = 0x2545_f491_u32;
+    for i in 0..20 {
+        let data: Vec<u8> = (0..20_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                state as u8
+            })
+            .collect();
+        fs::write(source.join(format!("file{:02}.bin", i)), data).unwrap();
+    }
+
+    let archive = temp_dir.path().join("cut.tar.gz");
+    pack_multiple(
+        std::slice::from_ref(&source),
+        &archive,
+        "tar.gz",
+        Some(&source),
+        &PackOptions::default(),
+        &NoProgress,
+    )
+    .unwrap();
+    let bytes = fs::read(&archive).unwrap();
+    fs::write(&archive, &bytes[..bytes.len() / 2]).unwrap();
+
+    let output = temp_dir.path().join("out");
+    let report = salvage(&archive, &output, &ExtractOptions::default()).unwrap();
+
+    assert!(report.error.is_some());
+    assert!(!report.is_complete());
+    let files = report
+        .recovered
+        .iter()
+        .filter(|path| path.extension().is_some_and(|ext| ext == "bin"))
+        .count();
+    assert!(files > 0 && files < 20, "recovered {} files", files);
+    // The file cut off mid-way is reported, not left half-written
+    assert_eq!(report.lost.len(), 1);
+    let lost = report.lost[0].path.as_ref().unwrap();
+    assert!(!output.join(lost).exists());
+}
+
+#[test]
+fn test_intact_archive_is_complete() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("source");
+    fs::create_dir_all(source.join("docs")).unwrap();
+    fs::write(source.join("readme.txt"), "read me").unwrap();
+    fs::write(source.join("docs/guide.txt"), "guide").unwrap();
+
+    for format in ["tar.zst", "zip"] {
+        let archive = temp_dir.path().join(format!("intact.{}", format));
+        pack_multiple(
+            &[source.join("readme.txt"), source.join("docs")],
+            &archive,
+            format,
+            Some(&source),
+            &PackOptions::default(),
+            &NoProgress,
+        )
+        .unwrap();
+
+        let output = temp_dir.path().join(format!("out-{}", format));
+        let report = salvage(&archive, &output, &ExtractOptions::default()).unwrap();
+        assert!(report.is_complete(), "{}: {}", format, report);
+        assert_eq!(
+            fs::read_to_string(output.join("docs/guide.txt")).unwrap(),
+            "guide",
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_salvage_7z_is_unsupported() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("damaged.7z");
+    fs::write(&archive, "not checked").unwrap();
+
+    let result = salvage(
+        &archive,
+        temp_dir.path().join("out"),
+        &ExtractOptions::default(),
+    );
+    assert!(matches!(result, Err(Error::UnsupportedOperation(_))));
+}
+
+/// A chain of symlinks that each look harmless on their own cannot carry
+/// a later entry out of the output directory
+#[cfg(unix)]
+#[test]
+fn test_symlink_chain_stays_in_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("chain.tar");
+    ArchiveBuilder::new(ArchiveKind::Tar)
+        .symlink("a", ".")
+        .symlink("a/b", "..")
+        .file("b/evil", "evil")
+        .build(&archive)
+        .unwrap();
+
+    let output = temp_dir.path().join("out");
+    let report = salvage(&archive, &output, &ExtractOptions::default()).unwrap();
+
+    // `a/b` is really `b` beside `a`, so `..` leads out of the output
+    assert_eq!(report.lost.len(), 1);
+    assert_eq!(report.lost[0].path.as_deref(), Some(Path::new("a/b")));
+    assert_eq!(sorted(&report.recovered), ["a", "b/evil"]);
+    assert!(!temp_dir.path().join("evil").exists());
+    assert!(output.join("b").symlink_metadata().unwrap().is_dir());
+    assert_eq!(fs::read_to_string(output.join("b/evil")).unwrap(), "evil");
+
+    // Nor is a link already in the output followed out of it
+    let outside = temp_dir.path().join("outside");
+    fs::create_dir(&outside).unwrap();
+    std::os::unix::fs::symlink(&outside, output.join("escape")).unwrap();
+    let archive = temp_dir.path().join("escape.tar");
+    ArchiveBuilder::new(ArchiveKind::Tar)
+        .file("escape/evil", "evil")
+        .build(&archive)
+        .unwrap();
+    let report = salvage(&archive, &output, &ExtractOptions::default()).unwrap();
+    assert!(report.recovered.is_empty());
+    assert!(!outside.join("evil").exists());
+}
@@ -0,0 +1,98 @@
+//! Tests for the scratch directories operations stage their files in
+
+use flux_core::space::TempResource;
+use flux_core::{Error, ErrorCode};
+use std::fs;
+use std::panic;
+use tempfile::TempDir;
+
+#[test]
+fn test_scratch_is_removed_when_dropped() {
+    let temp_dir = TempDir::new().unwrap();
+    let scratch = TempResource::new_in(temp_dir.path(), None).unwrap();
+    let path = scratch.path().to_path_buf();
+    assert!(path.starts_with(temp_dir.path()));
+    fs::create_dir(path.join("nested")).unwrap();
+    fs::write(path.join("nested/archive.tar"), "data").unwrap();
+
+    drop(scratch);
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_scratch_is_removed_on_panic() {
+    let temp_dir = TempDir::new().unwrap();
+    let base = temp_dir.path().to_path_buf();
+
+    let result = panic::catch_unwind(move || {
+        let scratch = TempResource::new_in(&base, None).unwrap();
+        fs::write(scratch.path().join("partial"), "half written").unwrap();
+        panic!("interrupted");
+    });
+    assert!(result.is_err());
+    assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+}
+
+#[test]
+fn test_each_guard_has_its_own_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let first = TempResource::new_in(temp_dir.path(), None).unwrap();
+    let second = TempResource::new_in(temp_dir.path(), None).unwrap();
+    assert_ne!(first.path(), second.path());
+
+    // Making the second did not take the first for a stale one
+    assert!(first.path().is_dir());
+}
+
+#[test]
+fn test_quota_is_enforced() {
+    let temp_dir = TempDir::new().unwrap();
+    let scratch = TempResource::new_in(temp_dir.path(), Some(100)).unwrap();
+    assert_eq!(scratch.quota(), Some(100));
+
+    fs::write(scratch.path().join("part"), vec![0u8; 60]).unwrap();
+    assert_eq!(scratch.used(), 60);
+    scratch.reserve(40).unwrap();
+    scratch.check().unwrap();
+
+    let error = scratch.reserve(41).unwrap_err();
+    assert_eq!(error.code(), ErrorCode::InsufficientSpace);
+    assert!(matches!(
+        error,
+        Error::InsufficientSpace {
+            required: 41,
+            available: 40,
+            ..
+        }
+    ));
+
+    fs::write(scratch.path().join("more"), vec![0u8; 60]).unwrap();
+    assert_eq!(
+        scratch.check().unwrap_err().code(),
+        ErrorCode::InsufficientSpace
+    );
+}
+
+#[test]
+fn test_no_quota_allows_anything() {
+    let temp_dir = TempDir::new().unwrap();
+    let scratch = TempResource::new_in(temp_dir.path(), None).unwrap();
+    fs::write(scratch.path().join("part"), vec![0u8; 4096]).unwrap();
+    scratch.check().unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn test_scratch_of_a_killed_process_is_removed() {
+    let temp_dir = TempDir::new().unwrap();
+    // Above the largest process id Linux and macOS hand out
+    let stale = temp_dir.path().join("flux-scratch-999999999-0");
+    fs::create_dir(&stale).unwrap();
+    fs::write(stale.join("archive.tar"), "left behind").unwrap();
+    let unrelated = temp_dir.path().join("flux-scratch-notes");
+    fs::create_dir(&unrelated).unwrap();
+
+    let _scratch = TempResource::new_in(temp_dir.path(), None).unwrap();
+    assert!(!stale.exists());
+    assert!(unrelated.exists());
+}
@@ -0,0 +1,206 @@
+//! Tests for extracting and reading single entries of 7z archives
+
+use flux_core::archive::create_extractor;
+use flux_core::archive::extractor::{ArchiveEntry, ExtractEntryOptions};
+use flux_core::Error;
+use sevenz_rust::{SeqReader, SevenZArchiveEntry, SevenZWriter, SourceReader};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use tempfile::TempDir;
+
+fn file_entry(name: &str) -> SevenZArchiveEntry {
+    let mut entry = SevenZArchiveEntry::new();
+    entry.name = name.to_string();
+    entry.has_stream = true;
+    entry
+}
+
+/// Write a 7z archive with a folder, a solid block of `solid` files and one
+/// file in a block of its own
+fn create_archive(archive: &Path, solid: &[(&str, &[u8])]) {
+    let mut folder = SevenZArchiveEntry::new();
+    folder.name = "docs".to_string();
+    folder.is_directory = true;
+
+    let mut writer = SevenZWriter::create(archive).unwrap();
+    writer.push_archive_entry::<&[u8]>(folder, None).unwrap();
+    writer
+        .push_archive_entries(
+            solid.iter().map(|(name, _)| file_entry(name)).collect(),
+            SeqReader::new(
+                solid
+                    .iter()
+                    .map(|(_, data)| SourceReader::new(*data))
+                    .collect(),
+            ),
+        )
+        .unwrap();
+    writer
+        .push_archive_entry(file_entry("alone.txt"), Some(&b"a block of my own"[..]))
+        .unwrap();
+    writer.finish().unwrap();
+}
+
+const SOLID: [(&str, &[u8]); 3] = [
+    ("docs/first.txt", b"first in the block"),
+    ("docs/second.txt", b"second in the block"),
+    ("docs/third.txt", b"third and last"),
+];
+
+fn find(entries: &[ArchiveEntry], name: &str) -> ArchiveEntry {
+    entries
+        .iter()
+        .find(|e| e.path == Path::new(name))
+        .unwrap_or_else(|| panic!("{} not in archive", name))
+        .clone()
+}
+
+#[test]
+fn test_sevenz_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("test.7z");
+    create_archive(&archive, &SOLID);
+
+    let extractor = create_extractor(&archive).unwrap();
+    let entries: Vec<_> = extractor.entries(&archive).unwrap().flatten().collect();
+    assert_eq!(entries.len(), 5);
+    assert!(find(&entries, "docs").is_dir);
+    assert_eq!(find(&entries, "docs/second.txt").size, 19);
+}
+
+#[test]
+fn test_sevenz_extract_selected_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("test.7z");
+    create_archive(&archive, &SOLID);
+    let output = temp_dir.path().join("out");
+
+    let extractor = create_extractor(&archive).unwrap();
+    let entries: Vec<_> = extractor.entries(&archive).unwrap().flatten().collect();
+    // Out of block order, and across blocks
+    for name in ["docs/third.txt", "alone.txt", "docs/first.txt"] {
+        extractor
+            .extract_entry(
+                &archive,
+                &find(&entries, name),
+                &output,
+                ExtractEntryOptions::default(),
+            )
+            .unwrap();
+    }
+
+    assert_eq!(
+        fs::read(output.join("docs/third.txt")).unwrap(),
+        b"third and last"
+    );
+    assert_eq!(
+        fs::read(output.join("docs/first.txt")).unwrap(),
+        b"first in the block"
+    );
+    assert_eq!(
+        fs::read(output.join("alone.txt")).unwrap(),
+        b"a block of my own"
+    );
+    assert!(!output.join("docs/second.txt").exists());
+}
+
+#[test]
+fn test_sevenz_extract_entry_keeps_existing_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("test.7z");
+    create_archive(&archive, &SOLID);
+    let output = temp_dir.path().join("out");
+    fs::create_dir_all(output.join("docs")).unwrap();
+    fs::write(output.join("docs/first.txt"), "mine").unwrap();
+
+    let extractor = create_extractor(&archive).unwrap();
+    let entry = find(
+        &extractor
+            .entries(&archive)
+            .unwrap()
+            .flatten()
+            .collect::<Vec<_>>(),
+        "docs/first.txt",
+    );
+    assert!(extractor
+        .extract_entry(&archive, &entry, &output, ExtractEntryOptions::default())
+        .is_err());
+    assert_eq!(fs::read(output.join("docs/first.txt")).unwrap(), b"mine");
+
+    let options = ExtractEntryOptions {
+        overwrite: true,
+        ..ExtractEntryOptions::default()
+    };
+    extractor
+        .extract_entry(&archive, &entry, &output, options)
+        .unwrap();
+    assert_eq!(
+        fs::read(output.join("docs/first.txt")).unwrap(),
+        b"first in the block"
+    );
+}
+
+#[test]
+fn test_sevenz_open_entry() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("test.7z");
+    create_archive(&archive, &SOLID);
+
+    let extractor = create_extractor(&archive).unwrap();
+    let entries: Vec<_> = extractor.entries(&archive).unwrap().flatten().collect();
+    for (name, data) in SOLID.iter().rev() {
+        let mut read = Vec::new();
+        extractor
+            .open_entry(&archive, &find(&entries, name))
+            .unwrap()
+            .read_to_end(&mut read)
+            .unwrap();
+        assert_eq!(&read, data, "{}", name);
+    }
+
+    assert!(matches!(
+        extractor.open_entry(&archive, &find(&entries, "docs")),
+        Err(Error::InvalidPath(_))
+    ));
+    let mut missing = find(&entries, "alone.txt");
+    missing.path = "not/there.txt".into();
+    assert!(matches!(
+        extractor.open_entry(&archive, &missing),
+        Err(Error::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_sevenz_rewritten_archive_is_decoded_again() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("test.7z");
+    create_archive(&archive, &SOLID);
+
+    let extractor = create_extractor(&archive).unwrap();
+    let entries: Vec<_> = extractor.entries(&archive).unwrap().flatten().collect();
+    let entry = find(&entries, "docs/second.txt");
+    let mut read = Vec::new();
+    extractor
+        .open_entry(&archive, &entry)
+        .unwrap()
+        .read_to_end(&mut read)
+        .unwrap();
+    assert_eq!(read, b"second in the block");
+
+    // The same extractor must not serve the block it decoded before
+    create_archive(
+        &archive,
+        &[
+            ("docs/first.txt", b"changed"),
+            ("docs/second.txt", b"changed as well, and longer"),
+        ],
+    );
+    let mut read = Vec::new();
+    extractor
+        .open_entry(&archive, &entry)
+        .unwrap()
+        .read_to_end(&mut read)
+        .unwrap();
+    assert_eq!(read, b"changed as well, and longer");
+}
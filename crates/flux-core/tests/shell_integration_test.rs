@@ -0,0 +1,61 @@
+//! Tests for the file manager context menu integration
+
+use flux_core::shell_integration::{self, ShellAction};
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_launch_flags_round_trip() {
+    for action in ShellAction::ALL {
+        assert_eq!(ShellAction::from_flag(action.flag()), Some(action));
+    }
+    assert_eq!(ShellAction::from_flag("--verbose"), None);
+}
+
+#[test]
+fn test_install_requires_existing_gui() {
+    let temp_dir = TempDir::new().unwrap();
+    assert!(shell_integration::install(&temp_dir.path().join("missing")).is_err());
+}
+
+/// Install and remove the Nautilus scripts and KDE service menus
+///
+/// Kept as a single test because it points `XDG_DATA_HOME` at a temporary
+/// directory for the whole process.
+#[cfg(all(unix, not(target_os = "macos")))]
+#[test]
+fn test_install_and_uninstall_in_data_dir() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let data_dir = temp_dir.path().join("data");
+    let gui = temp_dir.path().join("it's flux");
+    fs::write(&gui, "").unwrap();
+    std::env::set_var("XDG_DATA_HOME", &data_dir);
+
+    assert!(!shell_integration::is_installed());
+    let written = shell_integration::install(&gui).unwrap();
+    assert_eq!(written.len(), 4);
+    assert!(shell_integration::is_installed());
+
+    let script_path = data_dir.join("nautilus/scripts/Extract with Flux");
+    let script = fs::read_to_string(&script_path).unwrap();
+    assert!(script.starts_with("#!/bin/sh\n"));
+    assert!(script.contains(r"'\''s flux' --extract"));
+    assert!(script.contains("*.zip|"));
+    let mode = fs::metadata(&script_path).unwrap().permissions().mode();
+    assert_ne!(mode & 0o111, 0);
+
+    let menu = fs::read_to_string(data_dir.join("kio/servicemenus/flux-compress.desktop")).unwrap();
+    assert!(menu.contains("MimeType=all/all;"));
+    assert!(menu.contains("Name=Compress with Flux"));
+    assert!(menu.contains("--compress %F"));
+
+    // Installing again replaces the entries
+    assert_eq!(shell_integration::install(&gui).unwrap().len(), 4);
+
+    let removed = shell_integration::uninstall().unwrap();
+    assert_eq!(removed.len(), 4);
+    assert!(!shell_integration::is_installed());
+    assert!(shell_integration::uninstall().unwrap().is_empty());
+}
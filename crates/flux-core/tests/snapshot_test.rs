@@ -0,0 +1,91 @@
+//! Tests for browsing and restoring recorded sync snapshots
+
+use flux_core::archive::incremental::pack_incremental;
+use flux_core::archive::{pack_with_strategy, PackOptions};
+use flux_core::manifest::Manifest;
+use flux_core::snapshot::SnapshotHistory;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Two snapshots of `source`: a full backup, then one after `notes.txt`
+/// changed and `new.txt` was added
+fn create_history(dir: &Path) -> SnapshotHistory {
+    let source = dir.join("source");
+    fs::create_dir_all(source.join("docs")).unwrap();
+    fs::write(source.join("notes.txt"), "first").unwrap();
+    fs::write(source.join("docs/readme.md"), "unchanged").unwrap();
+
+    let target = dir.join("backup.tar.gz");
+    let manifest = dir.join("backup.tar.manifest.json");
+    let history = SnapshotHistory::new(&target).unwrap();
+
+    pack_with_strategy(&source, &target, Some("tar.gz"), PackOptions::default()).unwrap();
+    Manifest::from_directory(&source)
+        .unwrap()
+        .save(&manifest)
+        .unwrap();
+    assert_eq!(history.record(&target, &manifest).unwrap(), 1);
+
+    // Far enough apart for the modification time to change
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    fs::write(source.join("notes.txt"), "second").unwrap();
+    fs::write(source.join("new.txt"), "added").unwrap();
+    pack_incremental(&source, &target, &manifest, PackOptions::default()).unwrap();
+    assert_eq!(history.record(&target, &manifest).unwrap(), 2);
+
+    history
+}
+
+#[test]
+fn test_snapshots_are_listed_with_their_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let history = create_history(temp_dir.path());
+
+    let snapshots = history.snapshots(None).unwrap();
+    assert_eq!(
+        snapshots.iter().map(|s| s.sequence).collect::<Vec<_>>(),
+        [1, 2]
+    );
+    assert!(snapshots[1].file_count > snapshots[0].file_count);
+    assert!(snapshots[1].changes >= 2);
+    assert!(snapshots.iter().all(|s| s.archive_size > 0));
+}
+
+#[test]
+fn test_restore_reads_each_file_from_where_it_changed() {
+    let temp_dir = TempDir::new().unwrap();
+    let history = create_history(temp_dir.path());
+
+    let latest = temp_dir.path().join("latest");
+    history.restore(2, &[], &latest, None).unwrap();
+    assert_eq!(
+        fs::read_to_string(latest.join("notes.txt")).unwrap(),
+        "second"
+    );
+    assert_eq!(fs::read_to_string(latest.join("new.txt")).unwrap(), "added");
+    assert_eq!(
+        fs::read_to_string(latest.join("docs/readme.md")).unwrap(),
+        "unchanged"
+    );
+
+    let first = temp_dir.path().join("first");
+    history
+        .restore(1, &[PathBuf::from("notes.txt")], &first, None)
+        .unwrap();
+    assert_eq!(
+        fs::read_to_string(first.join("notes.txt")).unwrap(),
+        "first"
+    );
+    assert!(!first.join("docs").exists());
+    assert!(!first.join("new.txt").exists());
+}
+
+#[test]
+fn test_unknown_snapshot_is_not_found() {
+    let temp_dir = TempDir::new().unwrap();
+    let history = create_history(temp_dir.path());
+
+    let output = temp_dir.path().join("out");
+    assert!(history.restore(3, &[], &output, None).is_err());
+}
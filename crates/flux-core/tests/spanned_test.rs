@@ -0,0 +1,138 @@
+//! Tests for split zip volumes
+
+use flux_core::archive::spanned::{self, MIN_VOLUME_SIZE};
+use flux_core::archive::{
+    create_extractor, extract_with_options, inspect, pack_with_strategy, split_zip, ExtractOptions,
+    PackOptions,
+};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Bytes that do not compress, so the archive is about as large as the input
+fn noise(len: usize, seed: u32) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect()
+}
+
+/// A zip of a folder with two large files and a small one
+fn packed_zip(temp_dir: &TempDir) -> (PathBuf, PathBuf) {
+    let source = temp_dir.path().join("source");
+    fs::create_dir_all(source.join("sub")).unwrap();
+    fs::write(source.join("big.bin"), noise(200_000, 0x2545_f491)).unwrap();
+    fs::write(source.join("sub/other.bin"), noise(90_000, 0x1234_5678)).unwrap();
+    fs::write(source.join("small.txt"), "small").unwrap();
+
+    let archive = temp_dir.path().join("split.zip");
+    pack_with_strategy(&source, &archive, Some("zip"), PackOptions::default()).unwrap();
+    (source, archive)
+}
+
+fn assert_same_file(a: &Path, b: &Path) {
+    assert_eq!(fs::read(a).unwrap(), fs::read(b).unwrap(), "{:?}", b);
+}
+
+#[test]
+fn test_split_and_read_back() {
+    let temp_dir = TempDir::new().unwrap();
+    let (source, archive) = packed_zip(&temp_dir);
+    let before = inspect(&archive).unwrap();
+
+    let paths = split_zip(&archive, MIN_VOLUME_SIZE).unwrap();
+    assert!(paths.len() >= 5, "{:?}", paths);
+    assert_eq!(paths.last().unwrap(), &archive);
+    assert_eq!(paths[0], temp_dir.path().join("split.z01"));
+    for path in &paths {
+        assert!(fs::metadata(path).unwrap().len() <= MIN_VOLUME_SIZE);
+    }
+    assert_eq!(spanned::volumes(&archive).unwrap(), paths);
+
+    // Only the first volume carries the split marker
+    let first = fs::read(&paths[0]).unwrap();
+    assert_eq!(&first[..4], b"PK\x07\x08");
+
+    let after = inspect(&archive).unwrap();
+    assert_eq!(before.len(), after.len());
+    for (before, after) in before.iter().zip(&after) {
+        assert_eq!(before.path, after.path);
+        assert_eq!(before.size, after.size);
+    }
+
+    let output = temp_dir.path().join("out");
+    extract_with_options(&archive, &output, ExtractOptions::default()).unwrap();
+    for name in ["big.bin", "sub/other.bin", "small.txt"] {
+        assert_same_file(&source.join(name), &output.join(name));
+    }
+
+    let extractor = create_extractor(&archive).unwrap();
+    let entry = extractor
+        .entries(&archive)
+        .unwrap()
+        .map(|entry| entry.unwrap())
+        .find(|entry| entry.path == Path::new("sub/other.bin"))
+        .unwrap();
+    let mut data = Vec::new();
+    extractor
+        .open_entry(&archive, &entry)
+        .unwrap()
+        .read_to_end(&mut data)
+        .unwrap();
+    assert_eq!(data, fs::read(source.join("sub/other.bin")).unwrap());
+}
+
+#[test]
+fn test_small_archive_is_not_split() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("source");
+    fs::create_dir_all(&source).unwrap();
+    fs::write(source.join("a.txt"), "hello").unwrap();
+    let archive = temp_dir.path().join("small.zip");
+    pack_with_strategy(&source, &archive, Some("zip"), PackOptions::default()).unwrap();
+    let original = fs::read(&archive).unwrap();
+
+    let paths = split_zip(&archive, MIN_VOLUME_SIZE).unwrap();
+    assert_eq!(paths, vec![archive.clone()]);
+    assert_eq!(fs::read(&archive).unwrap(), original);
+    assert!(spanned::volumes(&archive).is_none());
+}
+
+#[test]
+fn test_resplit_removes_stale_volumes() {
+    let temp_dir = TempDir::new().unwrap();
+    let (_, archive) = packed_zip(&temp_dir);
+    let stale = temp_dir.path().join("split.z09");
+    let many = split_zip(&archive, MIN_VOLUME_SIZE).unwrap();
+    for number in many.len()..=9 {
+        fs::write(temp_dir.path().join(format!("split.z{:02}", number)), "old").unwrap();
+    }
+
+    // A plain zip written over the split one ignores the old volumes
+    let (_, archive) = packed_zip(&temp_dir);
+    assert!(spanned::volumes(&archive).is_none());
+    let entries = inspect(&archive).unwrap().len();
+    // Split again into fewer, larger volumes
+    let fewer = split_zip(&archive, 4 * MIN_VOLUME_SIZE).unwrap();
+    assert!(fewer.len() < many.len());
+    assert!(!stale.exists());
+    assert_eq!(spanned::volumes(&archive).unwrap(), fewer);
+    assert_eq!(inspect(&archive).unwrap().len(), entries);
+}
+
+#[test]
+fn test_split_rejects_bad_arguments() {
+    let temp_dir = TempDir::new().unwrap();
+    let (_, archive) = packed_zip(&temp_dir);
+    assert!(split_zip(&archive, MIN_VOLUME_SIZE - 1).is_err());
+
+    let renamed = temp_dir.path().join("split.bin");
+    fs::rename(&archive, &renamed).unwrap();
+    assert!(split_zip(&renamed, MIN_VOLUME_SIZE).is_err());
+}
@@ -1,13 +1,47 @@
 #[cfg(unix)]
-#[ignore = "Symlink functionality needs work"]
 mod symlink_tests {
     use flux_core::archive::{
         extract_with_options, pack_with_strategy, ExtractOptions, PackOptions,
     };
+    use flux_testing::fixtures::{ArchiveBuilder, ArchiveKind};
     use std::fs;
     use std::os::unix::fs as unix_fs;
     use tempfile::TempDir;
 
+    /// A symlink entry replaces an empty directory of the same name, but
+    /// never one with files in it, even when overwriting
+    #[test]
+    fn test_symlink_does_not_replace_directory_with_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive_path = temp_dir.path().join("links.tar");
+        ArchiveBuilder::new(ArchiveKind::Tar)
+            .file("elsewhere/readme.txt", "archived")
+            .symlink("data", "elsewhere")
+            .symlink("empty", "elsewhere")
+            .build(&archive_path)
+            .unwrap();
+
+        let extract_dir = temp_dir.path().join("extracted");
+        fs::create_dir_all(extract_dir.join("data")).unwrap();
+        fs::write(extract_dir.join("data/mine.txt"), "user file").unwrap();
+        fs::create_dir_all(extract_dir.join("empty")).unwrap();
+
+        let options = ExtractOptions {
+            overwrite: true,
+            skip: false,
+            ..ExtractOptions::default()
+        };
+        let outcome = extract_with_options(&archive_path, &extract_dir, options).unwrap();
+
+        assert_eq!(outcome.failed.len(), 1);
+        assert!(outcome.failed[0].path.ends_with("data"));
+        assert_eq!(
+            fs::read_to_string(extract_dir.join("data/mine.txt")).unwrap(),
+            "user file"
+        );
+        assert!(extract_dir.join("empty").is_symlink());
+    }
+
     #[test]
     #[ignore = "Symlink functionality needs work"]
     fn test_pack_symlinks_follow() {
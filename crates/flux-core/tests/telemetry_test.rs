@@ -0,0 +1,73 @@
+//! Tests for phase telemetry
+
+#![cfg(feature = "telemetry")]
+
+use flux_core::telemetry::{
+    clear_exporter, set_exporter, Exporter, JsonLinesExporter, Phase, PhaseMetrics, PhaseTimer,
+};
+use std::fs;
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+// The exporter is global, so the tests take turns
+static EXPORTER_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Default)]
+struct Collect(Mutex<Vec<PhaseMetrics>>);
+
+impl Exporter for Collect {
+    fn export(&self, metrics: &PhaseMetrics) {
+        self.0.lock().unwrap().push(metrics.clone());
+    }
+}
+
+#[test]
+fn test_timer_exports_counts() {
+    let _lock = EXPORTER_LOCK.lock().unwrap();
+    let collect = Arc::new(Collect::default());
+    set_exporter(collect.clone());
+
+    let timer = PhaseTimer::start(Phase::Compress);
+    timer.add_in(100);
+    timer.add_in(50);
+    timer.set_out(40);
+    // A smaller total than seen before does not shrink the output
+    timer.set_out(30);
+    let metrics = timer.finish();
+
+    // Dropping an unfinished timer reports it too
+    drop(PhaseTimer::start(Phase::Upload));
+    clear_exporter();
+    drop(PhaseTimer::start(Phase::Extract));
+
+    assert_eq!(metrics.phase, Phase::Compress);
+    assert_eq!(metrics.bytes_in, 150);
+    assert_eq!(metrics.bytes_out, 40);
+    let exported = collect.0.lock().unwrap();
+    let phases: Vec<_> = exported.iter().map(|metrics| metrics.phase).collect();
+    assert_eq!(phases, [Phase::Compress, Phase::Upload]);
+    assert_eq!(exported[0], metrics);
+}
+
+#[test]
+fn test_json_lines_exporter_appends() {
+    let _lock = EXPORTER_LOCK.lock().unwrap();
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("metrics.jsonl");
+    fs::write(&path, "{\"earlier\":true}\n").unwrap();
+
+    set_exporter(Arc::new(JsonLinesExporter::open(&path).unwrap()));
+    let timer = PhaseTimer::start(Phase::Write);
+    timer.add_out(7);
+    timer.finish();
+    clear_exporter();
+
+    let content = fs::read_to_string(&path).unwrap();
+    let lines: Vec<_> = content.lines().collect();
+    assert_eq!(lines.len(), 2);
+    let record: serde_json::Value = serde_json::from_str(lines[1]).unwrap();
+    assert_eq!(record["phase"], "write");
+    assert_eq!(record["bytes_in"], 0);
+    assert_eq!(record["bytes_out"], 7);
+    assert!(record["duration_ms"].is_u64());
+}
@@ -0,0 +1,46 @@
+//! Tests for keeping operations to a rate
+
+use flux_core::throttle::RateLimiter;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_first_second_passes_without_waiting() {
+    let limiter = RateLimiter::new(1000);
+    assert_eq!(limiter.consume(600), Duration::ZERO);
+    assert_eq!(limiter.consume(400), Duration::ZERO);
+}
+
+#[test]
+fn test_keeps_to_the_rate() {
+    let limiter = RateLimiter::new(10_000);
+    let started = Instant::now();
+    for _ in 0..30 {
+        limiter.consume(1000);
+    }
+    // A second's burst, then two more seconds' worth at the rate
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(1900), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(4), "{:?}", elapsed);
+}
+
+#[test]
+fn test_shared_between_threads() {
+    let limiter = RateLimiter::new(20_000);
+    let started = Instant::now();
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for _ in 0..10 {
+                    limiter.consume(1000);
+                }
+            });
+        }
+    });
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(900), "{:?}", elapsed);
+}
+
+#[test]
+fn test_zero_rate_still_moves() {
+    assert_eq!(RateLimiter::new(0).bytes_per_second(), 1);
+}
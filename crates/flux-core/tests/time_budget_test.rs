@@ -0,0 +1,122 @@
+//! Tests for choosing the compression level to fit a time budget
+
+use flux_core::archive::{extract, pack_multiple, pack_with_strategy, PackOptions};
+use flux_core::progress::NoProgress;
+use flux_testing::fixtures::SourceTree;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tempfile::TempDir;
+
+fn create_source(dir: &Path) -> PathBuf {
+    let words = [
+        "error", "warning", "request", "served", "in", "ms", "user", "cache",
+    ];
+    let mut seed: u64 = 42;
+    let mut tree = SourceTree::new();
+    for i in 0..8 {
+        let mut text = String::new();
+        for _ in 0..20_000 {
+            seed = seed.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1);
+            text.push_str(words[(seed >> 61) as usize]);
+            text.push(if seed & 0xf == 0 { '\n' } else { ' ' });
+        }
+        tree = tree.file(&format!("log{}.txt", i), text);
+    }
+    tree.create_in(dir, "source").unwrap()
+}
+
+fn budgeted(budget: Duration) -> PackOptions {
+    PackOptions {
+        time_budget: Some(budget),
+        ..PackOptions::default()
+    }
+}
+
+#[test]
+fn test_budgeted_archives_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    for format in ["tar.gz", "tar.zst", "tar.xz"] {
+        let archive = temp_dir.path().join(format!("budget.{}", format));
+        pack_with_strategy(
+            &source,
+            &archive,
+            Some(format),
+            budgeted(Duration::from_secs(60)),
+        )
+        .unwrap();
+
+        let output = temp_dir.path().join(format!("out-{}", format));
+        extract(&archive, &output).unwrap();
+        assert_eq!(
+            fs::read(output.join("source/log3.txt")).unwrap(),
+            fs::read(source.join("log3.txt")).unwrap(),
+            "{}",
+            format
+        );
+    }
+}
+
+#[test]
+fn test_tight_budget_uses_the_fastest_level() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+    let inputs = [source];
+
+    let fastest = temp_dir.path().join("fastest.tar.zst");
+    let options = PackOptions {
+        level: Some(1),
+        ..PackOptions::default()
+    };
+    pack_multiple(
+        &inputs,
+        &fastest,
+        "tar.zst",
+        Some(temp_dir.path()),
+        &options,
+        &NoProgress,
+    )
+    .unwrap();
+
+    let tight = temp_dir.path().join("tight.tar.zst");
+    pack_multiple(
+        &inputs,
+        &tight,
+        "tar.zst",
+        Some(temp_dir.path()),
+        &budgeted(Duration::from_nanos(1)),
+        &NoProgress,
+    )
+    .unwrap();
+
+    assert_eq!(fs::read(&tight).unwrap(), fs::read(&fastest).unwrap());
+}
+
+#[test]
+fn test_generous_budget_compresses_harder() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path());
+
+    let fastest = temp_dir.path().join("fastest.tar.zst");
+    let options = PackOptions {
+        level: Some(1),
+        ..PackOptions::default()
+    };
+    pack_with_strategy(&source, &fastest, Some("tar.zst"), options).unwrap();
+
+    let generous = temp_dir.path().join("generous.tar.zst");
+    pack_with_strategy(
+        &source,
+        &generous,
+        Some("tar.zst"),
+        budgeted(Duration::from_secs(3_600)),
+    )
+    .unwrap();
+
+    assert!(
+        fs::metadata(&generous).unwrap().len() < fs::metadata(&fastest).unwrap().len(),
+        "a budget of an hour should allow more than the fastest level"
+    );
+}
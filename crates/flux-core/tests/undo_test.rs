@@ -0,0 +1,128 @@
+//! Tests for undoing extractions that overwrote files
+
+use flux_core::archive::{extract_with_options, undo_extraction, ExtractOptions, BACKUP_DIR};
+use flux_core::Error;
+use flux_testing::fixtures::{ArchiveBuilder, ArchiveKind};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// An archive holding a folder `project` with a file at the top and one
+/// further down
+fn create_archive(dir: &Path, kind: ArchiveKind) -> PathBuf {
+    let archive = dir.join(format!("project.{}", kind.extension()));
+    ArchiveBuilder::new(kind)
+        .dir("project")
+        .file("project/README.md", "from the archive")
+        .dir("project/src")
+        .dir("project/src/deep")
+        .file("project/src/deep/lib.rs", "pub fn new() {}")
+        .build(&archive)
+        .unwrap();
+    archive
+}
+
+fn undoable() -> ExtractOptions {
+    ExtractOptions {
+        overwrite: true,
+        skip: false,
+        undoable: true,
+        ..ExtractOptions::default()
+    }
+}
+
+#[test]
+fn test_undo_restores_overwritten_files() {
+    for kind in [ArchiveKind::TarGz, ArchiveKind::Zip, ArchiveKind::SevenZ] {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = create_archive(temp_dir.path(), kind);
+        let output = temp_dir.path().join("out");
+        fs::create_dir_all(output.join("project")).unwrap();
+        fs::write(output.join("project/README.md"), "mine").unwrap();
+        fs::write(output.join("project/notes.txt"), "untouched").unwrap();
+
+        extract_with_options(&archive, &output, undoable()).unwrap();
+        assert_eq!(
+            fs::read_to_string(output.join("project/README.md")).unwrap(),
+            "from the archive",
+            "{:?}",
+            kind
+        );
+        assert!(output.join(BACKUP_DIR).is_dir(), "{:?}", kind);
+
+        let report = undo_extraction(&output).unwrap();
+        assert_eq!(report.restored, 1, "{:?}", kind);
+        assert_eq!(
+            fs::read_to_string(output.join("project/README.md")).unwrap(),
+            "mine",
+            "{:?}",
+            kind
+        );
+        assert_eq!(
+            fs::read_to_string(output.join("project/notes.txt")).unwrap(),
+            "untouched"
+        );
+        assert!(!output.join("project/src").exists(), "{:?}", kind);
+        assert!(!output.join(BACKUP_DIR).exists(), "{:?}", kind);
+    }
+}
+
+#[test]
+fn test_undo_into_wrong_directory() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), ArchiveKind::TarZst);
+    let output = temp_dir.path().join("home");
+    fs::create_dir_all(&output).unwrap();
+    fs::write(output.join("keep.txt"), "keep").unwrap();
+
+    extract_with_options(&archive, &output, undoable()).unwrap();
+    // Something the user put there after extracting stays
+    fs::write(output.join("project/later.txt"), "later").unwrap();
+
+    let report = undo_extraction(&output).unwrap();
+    assert_eq!(report.restored, 0);
+    assert_eq!(report.kept, [PathBuf::from("project")]);
+    assert!(!output.join("project/README.md").exists());
+    assert!(output.join("project/later.txt").is_file());
+    assert!(output.join("keep.txt").is_file());
+}
+
+#[test]
+fn test_undo_goes_back_one_extraction_at_a_time() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), ArchiveKind::Zip);
+    let output = temp_dir.path().join("out");
+    fs::create_dir_all(output.join("project")).unwrap();
+    fs::write(output.join("project/README.md"), "first").unwrap();
+
+    extract_with_options(&archive, &output, undoable()).unwrap();
+    fs::write(output.join("project/README.md"), "edited").unwrap();
+    extract_with_options(&archive, &output, undoable()).unwrap();
+
+    undo_extraction(&output).unwrap();
+    assert_eq!(
+        fs::read_to_string(output.join("project/README.md")).unwrap(),
+        "edited"
+    );
+    undo_extraction(&output).unwrap();
+    assert_eq!(
+        fs::read_to_string(output.join("project/README.md")).unwrap(),
+        "first"
+    );
+    assert!(matches!(undo_extraction(&output), Err(Error::NotFound(_))));
+}
+
+#[test]
+fn test_no_journal_without_undoable() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = create_archive(temp_dir.path(), ArchiveKind::Tar);
+    let output = temp_dir.path().join("out");
+
+    let options = ExtractOptions {
+        undoable: false,
+        ..undoable()
+    };
+    extract_with_options(&archive, &output, options).unwrap();
+    assert!(!output.join(BACKUP_DIR).exists());
+    assert!(matches!(undo_extraction(&output), Err(Error::NotFound(_))));
+}
@@ -0,0 +1,179 @@
+//! Tests for Unicode normalization of entry names
+
+use flux_core::archive::{
+    extract_with_options, inspect, pack_with_strategy, CaseCollisions, ExtractOptions,
+    NameNormalization, PackOptions,
+};
+use flux_core::manifest::Manifest;
+use flux_testing::fixtures::SourceTree;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// `café` with a precomposed `é`
+const NFC: &str = "caf\u{e9}";
+/// `café` with `e` and a combining acute accent
+const NFD: &str = "cafe\u{301}";
+
+fn create_source(dir: &Path, name: &str) -> PathBuf {
+    SourceTree::new()
+        .file(&format!("{}/{}.txt", name, name), "menu")
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+fn names(archive: &Path) -> Vec<String> {
+    inspect(archive)
+        .unwrap()
+        .into_iter()
+        .map(|entry| {
+            entry
+                .path
+                .to_string_lossy()
+                .trim_end_matches('/')
+                .to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn test_pack_normalizes_names() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path(), NFD);
+
+    for format in ["tar", "zip"] {
+        let archive = temp_dir.path().join(format!("nfc.{}", format));
+        let options = PackOptions {
+            normalize_names: NameNormalization::Nfc,
+            ..PackOptions::default()
+        };
+        pack_with_strategy(&source, &archive, Some(format), options).unwrap();
+
+        let names = names(&archive);
+        assert!(
+            names
+                .iter()
+                .any(|name| name.ends_with(&format!("{}/{}.txt", NFC, NFC))),
+            "{}: {:?}",
+            format,
+            names
+        );
+        assert!(!names.iter().any(|name| name.contains(NFD)), "{}", format);
+    }
+}
+
+#[test]
+fn test_names_are_kept_by_default() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path(), NFD);
+    let archive = temp_dir.path().join("kept.tar");
+    pack_with_strategy(&source, &archive, Some("tar"), PackOptions::default()).unwrap();
+
+    assert!(names(&archive).iter().any(|name| name.contains(NFD)));
+}
+
+#[test]
+fn test_extract_normalizes_names() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path(), NFD);
+
+    for format in ["tar.gz", "zip"] {
+        let archive = temp_dir.path().join(format!("nfd.{}", format));
+        pack_with_strategy(&source, &archive, Some(format), PackOptions::default()).unwrap();
+
+        let output = temp_dir.path().join(format!("out-{}", format));
+        let options = ExtractOptions {
+            normalize_names: NameNormalization::Nfc,
+            fold_case: Some(false),
+            ..ExtractOptions::default()
+        };
+        let outcome = extract_with_options(&archive, &output, options).unwrap();
+        assert!(outcome.is_complete(), "{}", format);
+
+        let written = fs::read_dir(&output)
+            .unwrap()
+            .chain(fs::read_dir(output.join("source")).into_iter().flatten())
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        assert!(
+            written.iter().any(|name| name == NFC),
+            "{}: {:?}",
+            format,
+            written
+        );
+        assert!(!written.iter().any(|name| name == NFD), "{}", format);
+    }
+}
+
+#[test]
+fn test_differently_normalized_names_collide() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("both.tar");
+    let mut builder = tar::Builder::new(File::create(&archive).unwrap());
+    for name in [NFC, NFD] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(4);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, name, &b"menu"[..])
+            .unwrap();
+    }
+    builder.finish().unwrap();
+
+    let output = temp_dir.path().join("output");
+    let options = ExtractOptions {
+        case_collisions: CaseCollisions::Error,
+        fold_case: Some(true),
+        ..ExtractOptions::default()
+    };
+    assert!(extract_with_options(&archive, &output, options).is_err());
+}
+
+#[test]
+fn test_manifest_diff_ignores_normalization() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = create_source(temp_dir.path(), NFD);
+    let old = Manifest::from_directory(&source).unwrap();
+
+    // The same tree as another system would list it
+    let mut new = old.clone();
+    new.files = old
+        .files
+        .iter()
+        .map(|(path, entry)| {
+            let path = PathBuf::from(path.to_string_lossy().replace(NFD, NFC));
+            (
+                path.clone(),
+                flux_core::manifest::FileEntry {
+                    path,
+                    ..entry.clone()
+                },
+            )
+        })
+        .collect();
+
+    let diff = old.diff(&new);
+    assert!(!diff.has_changes(), "{:?}", diff);
+
+    // A real change is still found, under the new spelling
+    let changed = new.files.values_mut().find(|entry| !entry.is_dir).unwrap();
+    changed.hash = "changed".to_string();
+    let diff = old.diff(&new);
+    assert_eq!(diff.modified.len(), 1);
+    assert!(diff.modified[0].to_string_lossy().contains(NFC));
+}
+
+#[test]
+fn test_normalization_names() {
+    for form in [
+        NameNormalization::Keep,
+        NameNormalization::Nfc,
+        NameNormalization::Nfd,
+    ] {
+        assert_eq!(form.name().parse::<NameNormalization>().unwrap(), form);
+    }
+    assert_eq!(NameNormalization::Nfd.apply(NFC), NFD);
+    assert_eq!(NameNormalization::Nfc.apply(NFD), NFC);
+    assert!("nfkc".parse::<NameNormalization>().is_err());
+}
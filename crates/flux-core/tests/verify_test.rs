@@ -0,0 +1,142 @@
+//! Tests for archive verification
+
+use flux_core::archive::{
+    pack_with_strategy, verify, verify_with_progress, EntryStatus, PackOptions,
+};
+use flux_testing::fixtures::SourceTree;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+use zip::write::SimpleFileOptions;
+use zip::{AesMode, CompressionMethod, ZipWriter};
+
+fn create_source(dir: &Path) -> PathBuf {
+    // Varied bytes, so damage lands in real compressed data
+    let noise: Vec<u8> = (0..20_000u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect();
+    SourceTree::new()
+        .file("a.txt", "a".repeat(1000))
+        .file("docs/b.bin", noise)
+        .create_in(dir, "source")
+        .unwrap()
+}
+
+fn pack(dir: &Path, format: &str) -> PathBuf {
+    let source = create_source(dir);
+    let archive = dir.join(format!("out.{}", format));
+    pack_with_strategy(&source, &archive, Some(format), PackOptions::default()).unwrap();
+    archive
+}
+
+/// Flip a byte in the middle of the file
+fn damage(path: &Path) {
+    let mut bytes = fs::read(path).unwrap();
+    let middle = bytes.len() / 2;
+    bytes[middle] ^= 0xff;
+    fs::write(path, bytes).unwrap();
+}
+
+#[test]
+fn test_verify_checksummed_tars() {
+    for format in ["tar.gz", "tar.zst", "tar.xz"] {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = pack(temp_dir.path(), format);
+
+        let report = verify(&archive).unwrap();
+        assert_eq!(report.format, format);
+        assert_eq!(report.entries.len(), 2, "{}", format);
+        assert_eq!(report.ok(), 2, "{}", format);
+        assert!(report.is_ok());
+    }
+}
+
+#[test]
+fn test_verify_plain_tar_has_no_checksum() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = pack(temp_dir.path(), "tar");
+
+    let report = verify(&archive).unwrap();
+    assert_eq!(report.entries.len(), 2);
+    assert!(report
+        .entries
+        .iter()
+        .all(|entry| entry.status == EntryStatus::NoChecksum));
+    assert_eq!(report.unchecked(), 2);
+    assert!(report.is_ok());
+}
+
+#[test]
+fn test_verify_detects_damaged_tar() {
+    for format in ["tar.gz", "tar.zst", "tar.xz"] {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = pack(temp_dir.path(), format);
+        damage(&archive);
+
+        let report = verify(&archive).unwrap();
+        assert!(!report.is_ok(), "{}: {:?}", format, report);
+        assert_eq!(report.ok(), 0, "{}", format);
+    }
+}
+
+#[test]
+fn test_verify_zip() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("out.zip");
+
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut writer = ZipWriter::new(File::create(&archive).unwrap());
+    writer.add_directory("docs/", stored).unwrap();
+    writer.start_file("docs/plain.txt", stored).unwrap();
+    writer.write_all(&[b'x'; 4096]).unwrap();
+    writer
+        .start_file(
+            "secret.txt",
+            SimpleFileOptions::default().with_aes_encryption(AesMode::Aes256, "pw"),
+        )
+        .unwrap();
+    writer.write_all(b"top secret").unwrap();
+    writer.finish().unwrap();
+
+    let report = verify(&archive).unwrap();
+    assert_eq!(report.entries.len(), 2);
+    assert_eq!(report.entries[0].path, PathBuf::from("docs/plain.txt"));
+    assert_eq!(report.entries[0].status, EntryStatus::Ok);
+    assert_eq!(report.entries[1].status, EntryStatus::Encrypted);
+    assert!(report.is_ok());
+
+    // The stored entry's data sits in the first half of the file
+    let mut bytes = fs::read(&archive).unwrap();
+    let offset = bytes.iter().position(|&b| b == b'x').unwrap() + 100;
+    bytes[offset] = b'y';
+    fs::write(&archive, bytes).unwrap();
+
+    let report = verify(&archive).unwrap();
+    assert!(matches!(report.entries[0].status, EntryStatus::Corrupt(_)));
+    assert_eq!(report.corrupt(), 1);
+    assert!(!report.is_ok());
+}
+
+#[test]
+fn test_verify_progress_and_report_text() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = pack(temp_dir.path(), "tar.gz");
+
+    let mut seen = Vec::new();
+    let report = verify_with_progress(&archive, |entry| seen.push(entry.path.clone())).unwrap();
+    assert_eq!(seen.len(), report.entries.len());
+
+    let text = report.to_string();
+    assert!(text.contains("Format: tar.gz"));
+    assert!(text.contains("OK           source/a.txt"));
+    assert!(text.contains("2 entries: 2 ok, 0 corrupt, 0 unchecked"));
+}
+
+#[test]
+fn test_verify_unsupported_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("notes.txt");
+    fs::write(&path, "not an archive").unwrap();
+    assert!(verify(&path).is_err());
+}
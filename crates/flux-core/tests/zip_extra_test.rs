@@ -0,0 +1,96 @@
+//! Tests for keeping and showing the extra fields of zip entries
+
+use flux_core::archive::zip_extra::{parse_extra_fields, ExtraField};
+use flux_core::archive::{inspect, Modifier};
+use flux_testing::fixtures::{ArchiveBuilder, ArchiveKind};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use tempfile::TempDir;
+use zip::ZipArchive;
+
+const VENDOR: u16 = 0xcafe;
+
+/// Entries with an extended timestamp holding a modification time, uid and
+/// gid 1000, and two vendor fields
+fn create_zip(path: &Path) {
+    let mut timestamp = vec![1u8];
+    timestamp.extend_from_slice(&1_700_000_000i32.to_le_bytes());
+    ArchiveBuilder::new(ArchiveKind::Zip)
+        .zip_extra_field(0x5455, timestamp, false)
+        .zip_extra_field(0x7875, [1, 2, 0xe8, 0x03, 2, 0xe8, 0x03], false)
+        .zip_extra_field(VENDOR, b"vendor data", false)
+        .zip_extra_field(VENDOR + 1, b"central only", true)
+        .zip_comment("kept comment")
+        .file("docs/notes.txt", "notes")
+        .file("readme.txt", "read me")
+        .build(path)
+        .unwrap();
+}
+
+/// Extra fields of the local header, then of the central directory
+fn extra_fields(archive: &Path, name: &str) -> (Vec<ExtraField>, Vec<ExtraField>) {
+    let mut zip = ZipArchive::new(File::open(archive).unwrap()).unwrap();
+    let file = zip.by_name(name).unwrap();
+    let central = parse_extra_fields(file.extra_data().unwrap_or_default());
+    let (header_start, data_start) = (file.header_start(), file.data_start());
+    drop(file);
+
+    let bytes = fs::read(archive).unwrap();
+    let header = &bytes[header_start as usize..data_start as usize];
+    let name_len = u16::from_le_bytes([header[26], header[27]]) as usize;
+    (parse_extra_fields(&header[30 + name_len..]), central)
+}
+
+#[test]
+fn test_rename_keeps_extra_fields() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("extra.zip");
+    create_zip(&archive);
+    let (local_before, central_before) = extra_fields(&archive, "docs/notes.txt");
+    assert!(local_before.iter().any(|field| field.id == VENDOR));
+    assert!(central_before.iter().any(|field| field.id == VENDOR + 1));
+
+    let renamed = Modifier::new(&archive)
+        .rename("docs", "papers")
+        .apply()
+        .unwrap();
+    assert_eq!(renamed, 1);
+
+    assert_eq!(
+        extra_fields(&archive, "papers/notes.txt"),
+        (local_before, central_before)
+    );
+    assert_eq!(extra_fields(&archive, "readme.txt").0.len(), 3);
+
+    let mut zip = ZipArchive::new(File::open(&archive).unwrap()).unwrap();
+    assert_eq!(zip.comment(), b"kept comment");
+    let mut content = String::new();
+    zip.by_name("papers/notes.txt")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "notes");
+}
+
+#[test]
+fn test_inspect_shows_extra_fields() {
+    let temp_dir = TempDir::new().unwrap();
+    let archive = temp_dir.path().join("extra.zip");
+    create_zip(&archive);
+
+    let entries = inspect(&archive).unwrap();
+    let readme = entries
+        .iter()
+        .find(|entry| entry.path == Path::new("readme.txt"))
+        .unwrap();
+    assert_eq!(
+        readme.extra_fields,
+        [
+            "UT mtime=1700000000",
+            "ux uid=1000 gid=1000",
+            "0xcafe",
+            "0xcaff"
+        ]
+    );
+}
@@ -0,0 +1,759 @@
+# English messages for the Flux GUI
+#
+# Each line is `key = value`. Arguments are written as { $name } and
+# indented lines continue the previous message.
+
+## Archive formats
+
+format-smart = Smart
+format-smart-desc = Picked from your files
+format-zip = ZIP
+format-zip-desc = Universal compatibility
+format-tar-gz = TAR.GZ
+format-tar-gz-desc = Good compression
+format-tar-zst = TAR.ZST
+format-tar-zst-desc = Best performance
+format-tar-xz = TAR.XZ
+format-tar-xz-desc = Best compression
+
+## Settings
+
+settings-title = Settings
+settings-reset = Reset to Defaults
+settings-reset-done = Settings restored to defaults
+settings-compression = Compression
+settings-default-format = Default format:
+settings-compression-level = Compression level:
+settings-automatic = Automatic
+settings-threads = Threads:
+settings-all-cores = All cores
+settings-concurrent-tasks = Concurrent tasks:
+settings-concurrent-tasks-hint = Further tasks wait in the queue
+limits-priority = Priority:
+limits-background = Background priority
+limits-background-hint = Run at the lowest CPU and disk priority, compressing on one thread unless a thread count is set
+limits-disk = Disk speed:
+limits-network = Network speed:
+limits-cap = Limit to
+settings-general = General
+settings-language = Language:
+settings-theme = Theme:
+settings-theme-light = Light
+settings-theme-dark = Dark
+settings-confirm-overwrite = Ask before overwriting existing files
+settings-save-task-logs = Save the log of each task to a file
+settings-appearance = Appearance
+settings-appearance-reset = Reset Appearance
+settings-accent = Accent color:
+settings-accent-default = Default
+settings-accent-custom = Pick any color
+settings-font-size = Text size:
+settings-density = Density:
+settings-density-compact = Compact
+settings-density-comfortable = Comfortable
+settings-density-spacious = Spacious
+settings-integration = Integration
+settings-integration-description = Adds "{ $extract }" and "{ $compress }" to the context menu of your file manager.
+settings-integration-installed = Installed
+settings-integration-remove = Remove
+settings-integration-add = Add to File Manager
+settings-cloud-profiles = Cloud Profiles
+settings-add-profile = Add Profile
+settings-no-profiles = No cloud profiles configured
+settings-profile-name = Name
+settings-profile-endpoint = Custom endpoint (optional)
+settings-remove-profile = Remove profile
+
+## Common
+
+common-cancel = Cancel
+common-close = Close
+dialog-archives = Archives
+dialog-archive = Archive
+dialog-format-archive = { $format } Archive
+dialog-tar-archives = Tar Archives
+dialog-text-files = Text files
+
+## Window titles
+
+window-title-packing = Flux - Pack Files
+window-title-extracting = Flux - Extract Archive
+window-title-syncing = Flux - Incremental Backup
+window-title-browsing = Flux - Archive Browser
+window-title-welcome = Flux - File Archiver
+window-title-cloud = Flux - Cloud Storage
+window-title-schedules = Flux - Scheduled Backups
+window-title-settings = Flux - Settings
+window-title-one-active = { $title } (1 task active)
+window-title-active = { $title } ({ $count } tasks active)
+
+## Welcome view
+
+welcome-title = Flux Archive Manager
+welcome-subtitle = Modern, fast, and intelligent file compression
+welcome-drop = Drop files or folders here
+welcome-drop-browse = or click to browse
+welcome-create-archive = Create Archive
+welcome-extract-archive = Extract Archive
+welcome-incremental-sync = Incremental Sync
+welcome-feature-fast = Lightning Fast
+welcome-feature-fast-desc =
+    Multi-threaded compression
+    with real-time progress
+welcome-feature-smart = Smart Selection
+welcome-feature-smart-desc =
+    Automatic format detection
+    and optimal compression
+welcome-feature-secure = Secure & Reliable
+welcome-feature-secure-desc =
+    Safe extraction with
+    path traversal protection
+welcome-tips = Quick Tips:
+welcome-tip-combine = Drag multiple files/folders to create a combined archive
+welcome-tip-extract = Drop an archive file to extract it automatically
+welcome-tip-sync = Use Incremental Sync for efficient backups
+welcome-tip-logs = Check the logs panel for detailed operation info
+welcome-tip-palette = Press { $shortcut } to open the command palette
+
+## Task results
+
+task-pack-succeeded = Archive created successfully!
+task-extract-succeeded = Files extracted successfully!
+task-sync-succeeded = Backup completed successfully!
+task-failed-toast = Operation failed - click for details
+task-pack-failed = Failed to create archive
+task-extract-failed = Failed to extract files
+task-sync-failed = Backup failed
+task-failed-details =
+    Error Details:
+
+    { $error }
+
+    Please check:
+    • File permissions
+    • Available disk space
+    • File paths are correct
+    • Archive format is supported
+task-cancelled = Cancelled: { $label }
+tasks-cancelling-all = Cancelling all tasks...
+tasks-paused-all = Paused running tasks
+tasks-resumed-all = Resumed paused tasks
+tasks-nothing-to-pause = No running task can be paused
+
+## Password dialog
+
+password-title = Password required
+password-encrypted-files = This archive contains encrypted files:
+password-incorrect = Incorrect password, please try again.
+password-hint = Password
+password-unlock = Unlock
+
+## Access denied dialog
+
+access-denied-title = Access denied
+access-denied-message = Flux is not allowed to write to this folder:
+access-denied-elevate = Run as administrator
+access-denied-elevate-hint = Windows will ask for permission, then the extraction runs in a new window.
+access-denied-elevated = Extraction continues in an elevated window
+access-denied-elevate-failed = Could not run as administrator: { $error }
+access-denied-fallback = Extract to { $folder }
+access-denied-choose = Choose another folder…
+
+## Status bar and log panel
+
+status-show-logs = Show Logs
+status-show-tasks = Show Tasks
+status-show-tasks-count = Show Tasks ({ $count })
+status-log-total = Total: { $count }
+status-log-errors = Errors: { $count }
+status-log-warnings = Warnings: { $count }
+status-clear-logs = Clear All
+status-toggle-theme = Toggle theme
+status-running-queued = { $running } running, { $queued } queued
+status-running = { $running } running
+status-paused = { $count } paused
+status-ready = Ready
+logs-title = Logs
+logs-filter-hint = Filter logs...
+logs-level = Level:
+logs-level-all = All
+logs-level-error = Error
+logs-level-warn = Warn
+logs-level-info = Info
+logs-level-debug = Debug
+logs-export = Export
+logs-export-failed = Failed to export logs: { $error }
+logs-exported = Logs exported successfully
+logs-no-match = No logs match the current filter
+
+## Dialogs
+
+error-details-title = Error Details
+error-details-copy = Copy to Clipboard
+error-details-copied = Error details copied to clipboard
+overwrite-title = Overwrite existing files?
+overwrite-directory = The output directory is not empty. Existing files may be replaced:
+overwrite-file = The output file already exists and will be replaced:
+overwrite-confirm = Overwrite
+about-title = About Flux
+about-version = Version { $version }
+about-description = A fast, modern file archiver with GUI
+about-features = Features:
+about-feature-formats = Multiple archive formats (ZIP, TAR, 7Z)
+about-feature-smart = Smart compression selection
+about-feature-incremental = Incremental backups
+about-feature-secure = Secure extraction
+about-feature-cross-platform = Cross-platform support
+about-documentation = Documentation
+
+## Navigation
+
+nav-home = Home
+nav-home-tooltip = Start screen
+nav-pack = Pack
+nav-pack-tooltip = Create archives
+nav-extract = Extract
+nav-extract-tooltip = Extract archives
+nav-browse = Browse
+nav-browse-tooltip = Browse archive contents
+nav-sync = Sync
+nav-sync-tooltip = Incremental backup
+nav-cloud = Cloud
+nav-cloud-tooltip = Browse cloud storage
+nav-schedules = Schedules
+nav-schedules-tooltip = Recurring backups
+nav-settings = Settings
+nav-settings-tooltip = Application settings
+common-unknown = Unknown
+drop-zone-text = Drop files here
+common-browse = Browse
+
+## Pack view
+
+pack-start = Start Packing
+pack-clear-all = Clear All
+pack-drop = Drop files or folders to pack
+pack-drop-multiple = You can add multiple items
+pack-archive-settings = Archive Settings
+pack-format = Format:
+pack-output = Output:
+pack-no-output = No output selected
+pack-select-output = Select output location
+pack-files-to-pack = Files to Pack ({ $count })
+pack-add-more = Add More
+pack-remove-from-list = Remove from list
+pack-level = Level:
+pack-symlinks = Symlinks:
+pack-follow-symlinks = Follow symlinks
+pack-follow-symlinks-hint = Archive the files links point to instead of the links
+pack-already-compressed = Already compressed:
+pack-force-compression = Force compression
+pack-force-compression-hint = Compress even when smart packing would store the files as-is
+pack-exclusions = Exclusions
+pack-pattern-hint = e.g. *.log or node_modules
+pack-remove-pattern = Remove pattern
+pack-add-pattern = Add Pattern
+pack-ignore-vcs = Ignore version control folders
+pack-ignore-vcs-hint = .git, .hg, .svn and similar
+pack-skip-larger = Skip files larger than
+pack-counting = Counting files...
+pack-scan-included = Packing { $count } files ({ $size })
+pack-scan-skipped = Skipping { $count } files ({ $size })
+pack-scan-nothing-skipped = Nothing is skipped
+pack-analyzing = Analyzing files to choose a compression method...
+pack-analysis-stopped = Analysis stopped unexpectedly
+pack-analysis-failed = Could not analyze the files: { $error }
+pack-still-analyzing = Still choosing a compression method; try again in a moment
+pack-smart-choice = Smart choice: { $algorithm } ({ $format })
+pack-smart-reason = { $reason }, level { $level }.
+pack-smart-forced = Smart packing would store the files, but compression is forced; using zstd level { $level }.
+pack-smart-basis = Based on { $basis }
+pack-smart-empty = Add files to see what smart packing will choose.
+pack-estimate = Estimated size: { $size } ({ $ratio }% of { $input })
+pack-estimate-duration = about { $duration } to compress
+pack-estimate-empty = Nothing to pack with the current exclusions
+pack-estimate-failed = Could not estimate the archive size: { $error }
+pack-estimate-waiting = The size estimate appears once the options are complete
+pack-estimating = Estimating archive size...
+pack-drops-symlinks = { $format } archives cannot hold symlinks; { $count } will be left out, such as { $example }
+pack-drops-permissions = { $format } archives do not keep permissions; { $count } executable files will lose them, such as { $example }
+pack-drops-xattrs = { $format } archives do not keep extended attributes; { $count } files will lose them, such as { $example }
+pack-drops-large-files = { $format } archives cannot hold files over 4 GiB; { $count } are that large, such as { $example }
+pack-drops-unicode-names = { $format } archives may garble { $count } names outside ASCII, such as { $example }
+
+## Smart packing
+
+smart-nothing-to-analyze = Nothing to analyze
+smart-reason-store = Most files are already compressed (media, archives), so they are stored as-is
+smart-reason-text = Mostly text, which compresses well at a higher zstd level
+smart-reason-small-files = Many small files: fast zstd keeps packing quick
+smart-reason-mixed = Mixed content: zstd at a balanced level
+smart-reason-large = Very large input: xz at a low level keeps memory use down
+smart-reason-custom-rule = Matched a custom rule in your Flux configuration
+common-browse-ellipsis = Browse...
+common-clear = Clear
+common-no-archive-selected = No archive selected
+common-no-directory-selected = No directory selected
+properties = Properties
+
+## Extract view
+
+extract-archive-to-extract = Archive to extract:
+extract-size = Size:
+extract-type = Type:
+extract-output-directory = Output directory:
+extract-select-output-tip = Select where to extract the files
+extract-note = Files will be extracted to the selected directory
+extract-smart-folder = Smart folder extraction
+extract-smart-folder-hint = If the archive contains a single folder at the root level, extract its contents directly to the output directory, eliminating redundant nested folders.
+extract-start = Start Extracting
+extract-browse-archive = Browse Archive
+extract-view-contents = View Contents
+extract-view-contents-hint = Browse archive contents without extracting
+extract-batch-title = { $count } archives to extract
+extract-batch-remove = Remove from batch
+extract-batch-next-to-archives = Next to each archive
+extract-batch-use-archive-folders = Use Archive Folders
+extract-batch-destination = Destination:
+extract-batch-subfolder = Subfolder named after archive
+extract-batch-subfolder-hint = Extract each archive into its own folder, e.g. photos.zip into photos/
+extract-batch-same-folder = Same folder
+extract-batch-same-folder-hint = Extract every archive straight into the output directory
+extract-batch-start = Extract All ({ $count })
+archive-type-zip = ZIP Archive
+archive-type-tar-gz = TAR.GZ Archive
+archive-type-tar-zst = TAR.ZST Archive (Zstandard)
+archive-type-tar-xz = TAR.XZ Archive
+archive-type-7z = 7-Zip Archive
+archive-type-tar = TAR Archive
+
+## Destination checks
+
+preflight-checking = Checking the destination…
+preflight-blocked = Fix the problems shown with the destination first
+preflight-missing = { $dir } does not exist
+preflight-not-a-folder = { $dir } is a file, not a folder
+preflight-unwritable = Cannot write to { $dir }: { $error }
+preflight-no-space = Not enough room in { $dir }: about { $required } needed, { $available } free
+preflight-overlap = The archive { $output } would be written inside { $input }, which is being packed
+
+## Sync view
+
+sync-title = Incremental Backup / Sync
+sync-description = Create incremental backups that only include changed files since the last backup.
+sync-source-directory = Source Directory:
+sync-target-archive = Target Archive:
+sync-manifest-found = Existing manifest found:
+sync-incremental-note = This will be an incremental sync - only changed files will be backed up.
+sync-no-manifest = No existing manifest found
+sync-full-note = This will be a full backup - all files will be included.
+sync-advanced = Advanced Options
+sync-follow-symlinks = Follow symbolic links
+sync-include-permissions = Include file permissions
+sync-deleted-list = Generate deleted files list
+sync-start-incremental = Start Incremental Sync
+sync-start-full = Start Full Backup
+sync-view-manifest = View Manifest
+sync-manifest-at = Manifest at: { $path }
+sync-preview = Preview Changes
+sync-preview-hint = List what the next sync would back up, without running it
+sync-preview-running = Comparing the source directory with the manifest...
+sync-preview-failed = Could not preview the sync: { $error }
+sync-preview-stopped = The preview stopped before it finished
+sync-preview-no-changes = Nothing has changed since the last backup.
+sync-preview-summary = { $added } added, { $modified } modified, { $deleted } deleted, { $size } to back up
+sync-preview-change = Change
+sync-change-added = Added
+sync-change-modified = Modified
+sync-change-deleted = Deleted
+sync-how-it-works = How incremental backup works:
+sync-how-first = First backup creates a full archive and manifest
+sync-how-subsequent = Subsequent backups only include changed/new files
+sync-how-manifest = Each backup updates the manifest with current state
+sync-how-deleted = Deleted files are tracked in a separate list
+
+## Snapshots
+
+snapshot-title = Snapshots
+snapshot-loading = Reading snapshots...
+snapshot-load-failed = Could not read the snapshots: { $error }
+snapshot-none = No snapshots yet. Each sync is recorded as one.
+snapshot-date = Date
+snapshot-changes = Changes
+snapshot-files = Files
+snapshot-size-hint = Backed up { $total } in total
+snapshot-browse = Browse
+snapshot-files-of = Files of snapshot { $sequence }
+snapshot-restore-all = Restore All...
+snapshot-restore-selected = Restore { $count } Selected...
+snapshot-restoring = Restoring...
+snapshot-restore-done = Snapshot restored: { $summary }
+snapshot-restore-failed = Could not restore the snapshot: { $error }
+snapshot-restore-stopped = The restore stopped before it finished
+
+## Archive browser
+
+browser-title = Archive Browser
+browser-open-failed = Failed to open browser: { $error }
+browser-unknown-archive = Unknown Archive
+browser-file-count = { $count } files
+browser-folder-count = { $count } folders
+browser-total-size = Total: { $size }
+browser-reading = Reading entries…
+browser-extract-all = Extract All
+browser-extract-selected = Extract { $count } Selected
+browser-search-hint = Search by name
+browser-show-hidden = Show hidden files
+browser-tree = Tree
+browser-table = Table
+browser-clear-selection = Clear Selection
+browser-copy-to-clipboard = { $count } item(s) - release outside the window to copy to the clipboard
+browser-preview = Preview
+browser-details = Details
+browser-directory = Directory
+browser-compressed = Compressed:
+browser-modified = Modified:
+browser-permissions = Permissions:
+browser-path = Path:
+browser-select-for-details = Select an item to view details
+browser-items-selected = { $count } items selected
+browser-files = Files:
+browser-directories = Directories:
+browser-total-size-label = Total size:
+browser-remote-no-preview = Previews are not available for archives in cloud storage
+browser-edit = Edit
+browser-edit-hint = Rename, delete and add entries, then save them into the archive
+browser-edit-unavailable = Only local zip and tar archives can be edited, once all their entries are read
+browser-edit-add-files = Add Files
+browser-edit-add-folder = Add Folder
+browser-edit-add-hint = Added into the highlighted folder, or the top of the archive
+browser-edit-delete = Delete { $count }
+browser-edit-rename-hint = New path of the highlighted entry
+browser-edit-rename = Rename
+browser-edit-save = Save { $count } Changes
+browser-edit-discard = Discard
+browser-edit-saving = Saving changes into the archive…
+browser-edit-save-stopped = Saving stopped unexpectedly
+browser-edit-no-changes = No changes yet
+browser-edit-changes = { $count } unsaved changes
+browser-edit-undo = Undo this change
+file-type-gzip = Gzip Compressed
+file-type-text = Text Document
+file-type-markdown = Markdown Document
+file-type-pdf = PDF Document
+file-type-jpeg = JPEG Image
+file-type-png = PNG Image
+file-type-gif = GIF Image
+file-type-mp3 = MP3 Audio
+file-type-mp4 = MP4 Video
+file-type-rust = Rust Source
+file-type-python = Python Script
+file-type-javascript = JavaScript
+file-type-file = File
+preview-loading = Loading preview...
+preview-unavailable = No preview: { $error }
+preview-truncated = Showing the first { $size } KB
+
+## Browser table
+
+table-showing = Showing { $shown } of { $total } entries
+table-name = Name
+table-size = Size
+table-type = Type
+table-modified = Modified
+table-compressed = Compressed
+table-show-all-types = Show all types
+table-sort-hint = Sort; click again to reverse, right-click for archive order
+table-type-text = Text
+table-type-image = Image
+table-type-audio = Audio
+table-type-video = Video
+table-type-document = Document
+table-type-spreadsheet = Spreadsheet
+table-type-executable = Executable
+table-type-code = Source Code
+file-kind-folders = Folders
+file-kind-text = Text
+file-kind-images = Images
+file-kind-audio = Audio
+file-kind-video = Video
+file-kind-archives = Archives
+file-kind-documents = Documents
+file-kind-code = Source code
+file-kind-executables = Executables
+file-kind-other = Other files
+
+## Properties
+
+properties-reading = Reading archive...
+properties-read-failed = Could not read archive: { $error }
+properties-format = Format:
+properties-compression = Compression:
+properties-archive-size = Archive size:
+properties-extracted-size = Extracted size:
+properties-ratio = Ratio:
+properties-ratio-value = { $ratio }% ({ $saved }% saved)
+properties-not-available = n/a
+properties-entries = Entries:
+properties-entries-value = { $files } files, { $folders } folders, { $symlinks } symlinks
+properties-encrypted-headers = The file list is encrypted; a password is needed to see the contents
+properties-encrypted-entries = { $count } of { $total } files are encrypted
+properties-not-encrypted = Not encrypted
+properties-signature = Detached signature: { $name }
+properties-signature-hint = The signature file was found but has not been verified
+properties-no-signature = No signature
+properties-metadata = Origin
+properties-creator = Created by:
+properties-hostname = Host:
+properties-created = Created:
+properties-flux-version = Flux version:
+properties-comment = Comment
+
+## Verification
+
+verify = Verify
+verify-hint = Check every entry against the checksums stored in the archive
+verify-running = Checking entries... { $count } read
+verify-stopped = Verification stopped unexpectedly
+verify-failed = Could not verify the archive: { $error }
+verify-passed = No damaged entries found
+verify-problems = { $count } damaged entries found
+verify-counts = { $total } entries: { $ok } verified, { $corrupt } damaged, { $unchecked } without a checksum
+verify-unchecked-hint = Entries without a checksum were read without errors, but their contents could not be confirmed.
+verify-problems-only = Hide verified entries
+verify-nothing-to-show = Every entry was verified
+verify-status-ok = OK
+verify-status-corrupt = Damaged
+verify-status-no-checksum = No checksum
+verify-status-encrypted = Encrypted
+verify-export = Export Report
+verify-exported = Verification report saved
+verify-export-failed = Could not save the report: { $error }
+
+## Cloud storage
+
+common-loading = Loading...
+cloud-title = Cloud Storage
+cloud-pack-here = Pack Here
+cloud-pack-here-hint = Create an archive in this location
+cloud-refresh = Refresh
+cloud-profiles = Profiles
+cloud-open = Open
+cloud-credentials-note = Credentials are read from the environment, as with the CLI.
+cloud-empty-prompt = Enter a bucket URL or pick a profile to browse its contents.
+cloud-parent-folder = Parent folder
+cloud-location-empty = This location is empty.
+cloud-extract-hint = Download and extract to a local folder
+cloud-invalid-location = Invalid location: { $error }
+cloud-packing-to = Packing to { $url }
+cloud-connect-failed = Failed to connect to { $location }: { $error }
+cloud-list-failed = Failed to list { $location }: { $error }
+cloud-unsupported-format = Unsupported archive format: { $url }
+cloud-open-failed = Failed to open { $url }: { $error }
+cloud-extract-failed = Failed to extract { $url }: { $error }
+cloud-invalid-destination = Invalid destination { $url }: { $error }
+cloud-no-object-name = Destination has no object name: { $url }
+cloud-upload-failed = Failed to upload { $url }: { $error }
+cloud-downloading = Downloading...
+cloud-uploading = Uploading...
+cloud-open-archive = Open from cloud
+cloud-open-archive-hint = Browse an archive in S3, Google Cloud Storage or Azure without downloading it
+cloud-reading-archive = Reading archive... { $count } entries
+cloud-list-archive-failed = Failed to read { $url }: { $error }
+cloud-list-stopped = Reading the archive stopped unexpectedly
+cloud-hint-missing-credentials = No { $provider } credentials were found. Set { $vars } and restart Flux.
+cloud-hint-invalid-credentials = { $provider } rejected the credentials from { $vars }.
+cloud-hint-permission-denied = The credentials do not allow access to this location.
+cloud-hint-not-found = The bucket or object does not exist.
+
+## Schedules
+
+cadence-hourly = Hourly
+cadence-daily = Daily
+cadence-weekly = Weekly
+schedules-title = Scheduled Backups
+schedules-description = Schedules run while Flux is open. A backup missed while it was closed runs at the next start.
+schedules-new = New Schedule
+schedules-name = Name:
+schedules-name-hint = Defaults to the source folder name
+schedules-source-directory = Source directory:
+schedules-target-archive = Target archive:
+schedules-runs = Runs:
+schedules-add = Add Schedule
+schedules-empty = No schedules yet.
+schedules-enabled-hint = Run on schedule
+schedules-delete = Delete
+schedules-run-now = Run Now
+schedules-source = Source:
+schedules-target = Target:
+schedules-last-result = Last result:
+schedules-never-run = Never run
+schedules-next-run = Next run:
+schedules-running-now = Running now
+schedules-due = Due
+schedules-disabled = Disabled
+schedules-history = History ({ $count })
+schedules-no-log = No log messages
+schedules-outcome-running = Running
+schedules-outcome-success = Success
+schedules-outcome-failed = Failed: { $error }
+schedules-outcome-cancelled = Cancelled
+schedules-outcome-duration = { $outcome } in { $seconds }s
+
+## Previous session
+
+session-title = Restore Previous Session
+session-intro = Flux closed while work was still set up or running.
+session-draft = Unstarted job
+session-draft-pack = Packing { $count } selected items
+session-draft-extract = Extracting { $count } selected archives
+session-draft-sync = Syncing { $source }
+session-tasks = Interrupted tasks
+session-restart-hint = Checked tasks start again from the beginning.
+session-restore = Restore
+session-discard = Discard
+
+## Recent archives
+
+recent-title = Recent
+recent-opened = Opened { $time }
+recent-opened-hint = Browse this archive
+recent-created = Created { $time }
+recent-created-hint = Pack the same files again with the same settings
+recent-destination = Extracted to { $time }
+recent-destination-hint = Extract another archive here
+recent-remove = Remove from history
+recent-pin = Pin to keep it at the top
+recent-unpin = Unpin
+
+## Task queue
+
+tasks-title = Tasks
+tasks-clear-finished = Clear Finished
+tasks-cancel-all = Cancel All
+tasks-pause-all = Pause All
+tasks-resume-all = Resume All
+tasks-empty = No tasks yet. Packing, extracting and syncing will show up here.
+tasks-cancel = Cancel task
+tasks-pause = Pause task
+tasks-resume = Resume task
+tasks-status-queued = Queued
+tasks-status-running = Running
+tasks-status-done = Done
+tasks-status-failed = Failed
+tasks-status-cancelled = Cancelled
+tasks-status-paused = Paused
+tasks-waiting = Waiting...
+tasks-workers-stopped = Background workers stopped
+tasks-starting = Starting...
+tasks-progress = { $processed } / { $total } MB ({ $percent }%) - { $speed }
+tasks-progress-eta = { $processed } / { $total } MB ({ $percent }%) - { $speed } - ETA: { $eta }
+tasks-processing = Processing...
+tasks-completed = Completed
+tasks-error = Error: { $error }
+tasks-waiting-for-password = Waiting for password...
+tasks-cancelling = Cancelling...
+task-label-pack = Pack { $name }
+task-label-extract = Extract { $name }
+task-label-sync = Sync { $name }
+
+## Task history
+
+task-history-tab-queue = Queue
+task-history-tab-history = History
+task-history-title = Finished Tasks
+task-history-clear = Clear History
+task-history-empty = Finished tasks will show up here with their logs and reports.
+task-history-details = Show log and report
+task-history-report = Report
+task-history-done = Done
+task-history-skipped = Skipped
+task-history-failed = Failed
+task-history-no-report = This task has no entry report.
+task-history-log = Log
+task-history-no-log = Nothing was logged.
+task-history-open-output = Open Output Folder
+task-history-reveal-manifest = Reveal Manifest
+task-history-copy-report = Copy Report JSON
+task-history-report-copied = Report copied to clipboard
+task-history-open-failed = Could not open the file manager: { $error }
+
+## Notifications
+
+toast-ready-to-extract = Ready to extract: { $name }
+toast-ready-to-extract-many = Ready to extract { $count } archives
+toast-ready-to-pack-one = Ready to pack 1 file
+toast-ready-to-pack = Ready to pack { $count } files
+toast-shell-installed = Flux was added to the file manager menu
+toast-shell-removed = Flux was removed from the file manager menu
+toast-shell-failed = Could not update the file manager menu: { $error }
+toast-task-started = Started: { $task }
+toast-task-queued = Queued: { $task } ({ $position } waiting)
+toast-workers-not-responding = Failed to start task: background workers not responding
+toast-input-missing = { $path } no longer exists
+toast-session-restarted = Restarted { $count } interrupted tasks
+toast-session-input-missing = Cannot restart { $task }: { $path } no longer exists
+toast-output-dir-missing = Output directory does not exist
+toast-select-output = Please select an output path first
+toast-archive-missing = Archive file not found
+toast-batch-archive-missing = Archive not found: { $path }; nothing was extracted
+toast-create-dir-failed = Could not create { $path }
+toast-select-archive-and-output = Please select an archive and output directory first
+toast-source-dir-missing = Source directory does not exist
+toast-target-dir-missing = Target directory does not exist
+toast-select-source-and-target = Please select source directory and target archive first
+toast-scheduled = Scheduled: { $name }
+toast-open-archive-failed = Failed to open archive: { $error }
+toast-read-entries-failed = Failed to read archive entries: { $error }
+toast-browsing = Browsing: { $name }
+toast-archive-saved = Saved { $count } changed entries into the archive
+toast-archive-save-failed = Failed to save the changes: { $error }
+toast-staging-failed = Failed to create staging directory: { $error }
+toast-clipboard-failed = Failed to open clipboard: { $error }
+toast-clipboard-copy-failed = Failed to copy files to clipboard: { $error }
+toast-copied-to-clipboard = { $count } items copied to the clipboard - paste them into your file manager
+
+## Background work
+
+worker-preparing-pack = Preparing to pack...
+worker-pack-complete = Packing complete
+worker-adding = Adding: { $path }
+worker-opening-archive = Opening archive...
+worker-extracting-count = Extracting { $count } files...
+worker-extracting-entry = Extracting ({ $current }/{ $total }): { $name }
+worker-extracted-count = Successfully extracted { $count } files
+worker-entry-failed = Failed on { $path }: { $error }
+worker-entries-failed = { $failed } of { $total } entries failed
+worker-backup-changes = Backup complete - { $count } changes
+worker-creating-full-backup = Creating full backup...
+worker-full-backup-complete = Full backup complete
+
+## Commands and shortcuts
+
+command-open-archive = Open Archive
+command-pack-files = Pack Files
+command-start-task = Start Task
+command-cancel-tasks = Cancel All Tasks
+command-pause-tasks = Pause or Resume All Tasks
+command-toggle-logs = Show/Hide Logs
+command-toggle-tasks = Show/Hide Tasks
+command-palette = Command Palette
+command-toggle-theme = Toggle Dark Mode
+command-show-welcome = Go to Home
+command-show-pack = Go to Pack
+command-show-extract = Go to Extract
+command-show-sync = Go to Sync
+command-show-cloud = Go to Cloud
+command-show-schedules = Go to Schedules
+command-show-settings = Go to Settings
+command-nothing-to-start = Nothing to start in this view
+palette-hint = Type a command...
+palette-no-matches = No matching commands
+settings-shortcuts = Keyboard Shortcuts
+settings-shortcuts-description = Click a shortcut and press the new key combination. Escape cancels.
+settings-shortcuts-reset = Reset Shortcuts
+settings-shortcut-press = Press keys...
+settings-shortcut-none = None
+settings-shortcut-change = Click to record a new shortcut
+settings-shortcut-remove = Remove shortcut
+settings-shortcut-moved = The shortcut was taken from "{ $command }"
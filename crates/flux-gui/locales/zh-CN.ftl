@@ -0,0 +1,759 @@
+# Flux 图形界面的简体中文消息
+#
+# Simplified Chinese messages for the Flux GUI. Keys must match en.ftl;
+# missing keys fall back to English.
+
+## Archive formats
+
+format-smart = 智能
+format-smart-desc = 根据文件自动选择
+format-zip = ZIP
+format-zip-desc = 兼容性最好
+format-tar-gz = TAR.GZ
+format-tar-gz-desc = 压缩率良好
+format-tar-zst = TAR.ZST
+format-tar-zst-desc = 速度最快
+format-tar-xz = TAR.XZ
+format-tar-xz-desc = 压缩率最高
+
+## Settings
+
+settings-title = 设置
+settings-reset = 恢复默认设置
+settings-reset-done = 已恢复默认设置
+settings-compression = 压缩
+settings-default-format = 默认格式：
+settings-compression-level = 压缩级别：
+settings-automatic = 自动
+settings-threads = 线程数：
+settings-all-cores = 使用全部核心
+settings-concurrent-tasks = 同时运行的任务：
+settings-concurrent-tasks-hint = 其余任务将在队列中等待
+limits-priority = 优先级：
+limits-background = 后台优先级
+limits-background-hint = 以最低的 CPU 和磁盘优先级运行，未设置线程数时只用一个线程压缩
+limits-disk = 磁盘速度：
+limits-network = 网络速度：
+limits-cap = 限制为
+settings-general = 常规
+settings-language = 语言：
+settings-theme = 主题：
+settings-theme-light = 浅色
+settings-theme-dark = 深色
+settings-confirm-overwrite = 覆盖已有文件前询问
+settings-save-task-logs = 将每个任务的日志保存到文件
+settings-appearance = 外观
+settings-appearance-reset = 重置外观
+settings-accent = 强调色：
+settings-accent-default = 默认
+settings-accent-custom = 选择任意颜色
+settings-font-size = 文字大小：
+settings-density = 密度：
+settings-density-compact = 紧凑
+settings-density-comfortable = 适中
+settings-density-spacious = 宽松
+settings-integration = 系统集成
+settings-integration-description = 在文件管理器的右键菜单中添加“{ $extract }”和“{ $compress }”。
+settings-integration-installed = 已安装
+settings-integration-remove = 移除
+settings-integration-add = 添加到文件管理器
+settings-cloud-profiles = 云存储配置
+settings-add-profile = 添加配置
+settings-no-profiles = 尚未配置云存储
+settings-profile-name = 名称
+settings-profile-endpoint = 自定义端点（可选）
+settings-remove-profile = 删除配置
+
+## Common
+
+common-cancel = 取消
+common-close = 关闭
+dialog-archives = 压缩包
+dialog-archive = 压缩包
+dialog-format-archive = { $format } 压缩包
+dialog-tar-archives = Tar 压缩包
+dialog-text-files = 文本文件
+
+## Window titles
+
+window-title-packing = Flux - 打包文件
+window-title-extracting = Flux - 解压压缩包
+window-title-syncing = Flux - 增量备份
+window-title-browsing = Flux - 压缩包浏览器
+window-title-welcome = Flux - 文件压缩工具
+window-title-cloud = Flux - 云存储
+window-title-schedules = Flux - 定时备份
+window-title-settings = Flux - 设置
+window-title-one-active = { $title }（1 个任务进行中）
+window-title-active = { $title }（{ $count } 个任务进行中）
+
+## Welcome view
+
+welcome-title = Flux 压缩管理器
+welcome-subtitle = 现代、快速、智能的文件压缩
+welcome-drop = 将文件或文件夹拖放到这里
+welcome-drop-browse = 或点击浏览
+welcome-create-archive = 创建压缩包
+welcome-extract-archive = 解压压缩包
+welcome-incremental-sync = 增量同步
+welcome-feature-fast = 极速
+welcome-feature-fast-desc =
+    多线程压缩
+    实时显示进度
+welcome-feature-smart = 智能选择
+welcome-feature-smart-desc =
+    自动识别格式
+    选择最佳压缩方式
+welcome-feature-secure = 安全可靠
+welcome-feature-secure-desc =
+    安全解压
+    防止路径穿越
+welcome-tips = 小提示：
+welcome-tip-combine = 拖入多个文件或文件夹可合并为一个压缩包
+welcome-tip-extract = 拖入压缩包即可自动解压
+welcome-tip-sync = 使用增量同步高效备份
+welcome-tip-logs = 在日志面板中查看详细的操作信息
+welcome-tip-palette = 按 { $shortcut } 打开命令面板
+
+## Task results
+
+task-pack-succeeded = 压缩包创建成功！
+task-extract-succeeded = 文件解压成功！
+task-sync-succeeded = 备份完成！
+task-failed-toast = 操作失败，点击查看详情
+task-pack-failed = 创建压缩包失败
+task-extract-failed = 解压文件失败
+task-sync-failed = 备份失败
+task-failed-details =
+    错误详情：
+
+    { $error }
+
+    请检查：
+    • 文件权限
+    • 可用磁盘空间
+    • 文件路径是否正确
+    • 压缩格式是否受支持
+task-cancelled = 已取消：{ $label }
+tasks-cancelling-all = 正在取消所有任务...
+tasks-paused-all = 已暂停运行中的任务
+tasks-resumed-all = 已继续暂停的任务
+tasks-nothing-to-pause = 没有可以暂停的运行中任务
+
+## Password dialog
+
+password-title = 需要密码
+password-encrypted-files = 此压缩包包含加密文件：
+password-incorrect = 密码错误，请重试。
+password-hint = 密码
+password-unlock = 解锁
+
+## Access denied dialog
+
+access-denied-title = 拒绝访问
+access-denied-message = Flux 无权写入此文件夹：
+access-denied-elevate = 以管理员身份运行
+access-denied-elevate-hint = Windows 会请求权限，随后在新窗口中进行解压。
+access-denied-elevated = 解压将在具有管理员权限的窗口中继续
+access-denied-elevate-failed = 无法以管理员身份运行：{ $error }
+access-denied-fallback = 解压到 { $folder }
+access-denied-choose = 选择其他文件夹…
+
+## Status bar and log panel
+
+status-show-logs = 显示日志
+status-show-tasks = 显示任务
+status-show-tasks-count = 显示任务（{ $count }）
+status-log-total = 共 { $count } 条
+status-log-errors = 错误：{ $count }
+status-log-warnings = 警告：{ $count }
+status-clear-logs = 全部清除
+status-toggle-theme = 切换主题
+status-running-queued = { $running } 个运行中，{ $queued } 个排队中
+status-running = { $running } 个运行中
+status-paused = { $count } 个已暂停
+status-ready = 就绪
+logs-title = 日志
+logs-filter-hint = 筛选日志...
+logs-level = 级别：
+logs-level-all = 全部
+logs-level-error = 错误
+logs-level-warn = 警告
+logs-level-info = 信息
+logs-level-debug = 调试
+logs-export = 导出
+logs-export-failed = 导出日志失败：{ $error }
+logs-exported = 日志已导出
+logs-no-match = 没有符合筛选条件的日志
+
+## Dialogs
+
+error-details-title = 错误详情
+error-details-copy = 复制到剪贴板
+error-details-copied = 错误详情已复制到剪贴板
+overwrite-title = 覆盖已有文件？
+overwrite-directory = 输出目录不为空，已有文件可能会被替换：
+overwrite-file = 输出文件已存在，将被替换：
+overwrite-confirm = 覆盖
+about-title = 关于 Flux
+about-version = 版本 { $version }
+about-description = 快速、现代的图形化文件压缩工具
+about-features = 功能：
+about-feature-formats = 支持多种压缩格式（ZIP、TAR、7Z）
+about-feature-smart = 智能选择压缩方式
+about-feature-incremental = 增量备份
+about-feature-secure = 安全解压
+about-feature-cross-platform = 跨平台
+about-documentation = 文档
+
+## Navigation
+
+nav-home = 主页
+nav-home-tooltip = 开始页面
+nav-pack = 打包
+nav-pack-tooltip = 创建压缩包
+nav-extract = 解压
+nav-extract-tooltip = 解压压缩包
+nav-browse = 浏览
+nav-browse-tooltip = 浏览压缩包内容
+nav-sync = 同步
+nav-sync-tooltip = 增量备份
+nav-cloud = 云存储
+nav-cloud-tooltip = 浏览云存储
+nav-schedules = 定时任务
+nav-schedules-tooltip = 定期备份
+nav-settings = 设置
+nav-settings-tooltip = 应用设置
+common-unknown = 未知
+drop-zone-text = 将文件拖放到这里
+common-browse = 浏览
+
+## Pack view
+
+pack-start = 开始打包
+pack-clear-all = 全部清除
+pack-drop = 将要打包的文件或文件夹拖放到这里
+pack-drop-multiple = 可以添加多个项目
+pack-archive-settings = 压缩包设置
+pack-format = 格式：
+pack-output = 输出：
+pack-no-output = 尚未选择输出位置
+pack-select-output = 选择输出位置
+pack-files-to-pack = 待打包文件（{ $count }）
+pack-add-more = 继续添加
+pack-remove-from-list = 从列表中移除
+pack-level = 级别：
+pack-symlinks = 符号链接：
+pack-follow-symlinks = 跟随符号链接
+pack-follow-symlinks-hint = 打包链接指向的文件，而不是链接本身
+pack-already-compressed = 已压缩的文件：
+pack-force-compression = 强制压缩
+pack-force-compression-hint = 即使智能打包会直接存储这些文件，也进行压缩
+pack-exclusions = 排除规则
+pack-pattern-hint = 例如 *.log 或 node_modules
+pack-remove-pattern = 删除规则
+pack-add-pattern = 添加规则
+pack-ignore-vcs = 忽略版本控制文件夹
+pack-ignore-vcs-hint = .git、.hg、.svn 等
+pack-skip-larger = 跳过大于此大小的文件
+pack-counting = 正在统计文件...
+pack-scan-included = 将打包 { $count } 个文件（{ $size }）
+pack-scan-skipped = 将跳过 { $count } 个文件（{ $size }）
+pack-scan-nothing-skipped = 没有文件被跳过
+pack-analyzing = 正在分析文件以选择压缩方式...
+pack-analysis-stopped = 分析意外中止
+pack-analysis-failed = 无法分析文件：{ $error }
+pack-still-analyzing = 仍在选择压缩方式，请稍后再试
+pack-smart-choice = 智能选择：{ $algorithm }（{ $format }）
+pack-smart-reason = { $reason }，级别 { $level }。
+pack-smart-forced = 智能打包本会直接存储这些文件，但已启用强制压缩；使用 zstd 级别 { $level }。
+pack-smart-basis = 依据：{ $basis }
+pack-smart-empty = 添加文件后即可查看智能打包的选择。
+pack-estimate = 预计大小：{ $size }（{ $input } 的 { $ratio }%）
+pack-estimate-duration = 压缩约需 { $duration }
+pack-estimate-empty = 按当前排除规则没有要打包的文件
+pack-estimate-failed = 无法估算归档大小：{ $error }
+pack-estimate-waiting = 选项完整后将显示预计大小
+pack-estimating = 正在估算归档大小...
+pack-drops-symlinks = { $format } 压缩包无法保存符号链接；将略过 { $count } 个，例如 { $example }
+pack-drops-permissions = { $format } 压缩包不保存权限；{ $count } 个可执行文件将失去权限，例如 { $example }
+pack-drops-xattrs = { $format } 压缩包不保存扩展属性；{ $count } 个文件将丢失扩展属性，例如 { $example }
+pack-drops-large-files = { $format } 压缩包无法保存超过 4 GiB 的文件；共有 { $count } 个，例如 { $example }
+pack-drops-unicode-names = { $format } 压缩包可能损坏 { $count } 个非 ASCII 名称，例如 { $example }
+
+## Smart packing
+
+smart-nothing-to-analyze = 没有可分析的内容
+smart-reason-store = 大部分文件已经压缩过（媒体、压缩包），因此直接存储
+smart-reason-text = 以文本为主，使用较高的 zstd 级别压缩效果好
+smart-reason-small-files = 小文件较多：使用快速 zstd 加快打包
+smart-reason-mixed = 内容混合：使用均衡级别的 zstd
+smart-reason-large = 输入非常大：使用低级别 xz 以减少内存占用
+smart-reason-custom-rule = 匹配了 Flux 配置中的自定义规则
+common-browse-ellipsis = 浏览...
+common-clear = 清除
+common-no-archive-selected = 尚未选择压缩包
+common-no-directory-selected = 尚未选择目录
+properties = 属性
+
+## Extract view
+
+extract-archive-to-extract = 要解压的压缩包：
+extract-size = 大小：
+extract-type = 类型：
+extract-output-directory = 输出目录：
+extract-select-output-tip = 选择解压文件的保存位置
+extract-note = 文件将被解压到所选目录
+extract-smart-folder = 智能文件夹解压
+extract-smart-folder-hint = 如果压缩包根目录只有一个文件夹，则将其内容直接解压到输出目录，避免多余的嵌套文件夹。
+extract-start = 开始解压
+extract-browse-archive = 选择压缩包
+extract-view-contents = 查看内容
+extract-view-contents-hint = 无需解压即可浏览压缩包内容
+extract-batch-title = 待解压的归档：{ $count } 个
+extract-batch-remove = 从批量任务中移除
+extract-batch-next-to-archives = 各归档所在的文件夹
+extract-batch-use-archive-folders = 使用归档所在文件夹
+extract-batch-destination = 目标位置：
+extract-batch-subfolder = 以归档命名的子文件夹
+extract-batch-subfolder-hint = 将每个归档解压到各自的文件夹，例如 photos.zip 解压到 photos/
+extract-batch-same-folder = 同一文件夹
+extract-batch-same-folder-hint = 将所有归档直接解压到输出目录
+extract-batch-start = 全部解压（{ $count }）
+archive-type-zip = ZIP 压缩包
+archive-type-tar-gz = TAR.GZ 压缩包
+archive-type-tar-zst = TAR.ZST 压缩包（Zstandard）
+archive-type-tar-xz = TAR.XZ 压缩包
+archive-type-7z = 7-Zip 压缩包
+archive-type-tar = TAR 压缩包
+
+## Destination checks
+
+preflight-checking = 正在检查目标位置…
+preflight-blocked = 请先解决目标位置的问题
+preflight-missing = { $dir } 不存在
+preflight-not-a-folder = { $dir } 是文件，不是文件夹
+preflight-unwritable = 无法写入 { $dir }：{ $error }
+preflight-no-space = { $dir } 空间不足：约需 { $required }，可用 { $available }
+preflight-overlap = 归档 { $output } 将被写入正在打包的 { $input } 中
+
+## Sync view
+
+sync-title = 增量备份 / 同步
+sync-description = 创建增量备份，只包含自上次备份以来发生变化的文件。
+sync-source-directory = 源目录：
+sync-target-archive = 目标压缩包：
+sync-manifest-found = 找到已有清单：
+sync-incremental-note = 这将是一次增量同步，只备份有变化的文件。
+sync-no-manifest = 未找到已有清单
+sync-full-note = 这将是一次完整备份，包含所有文件。
+sync-advanced = 高级选项
+sync-follow-symlinks = 跟随符号链接
+sync-include-permissions = 包含文件权限
+sync-deleted-list = 生成已删除文件列表
+sync-start-incremental = 开始增量同步
+sync-start-full = 开始完整备份
+sync-view-manifest = 查看清单
+sync-manifest-at = 清单位置：{ $path }
+sync-preview = 预览更改
+sync-preview-hint = 列出下次同步将备份的内容，但不实际运行
+sync-preview-running = 正在将源目录与清单进行比较...
+sync-preview-failed = 无法预览同步：{ $error }
+sync-preview-stopped = 预览在完成前已停止
+sync-preview-no-changes = 自上次备份以来没有任何更改。
+sync-preview-summary = 新增 { $added } 个，修改 { $modified } 个，删除 { $deleted } 个，需备份 { $size }
+sync-preview-change = 更改
+sync-change-added = 新增
+sync-change-modified = 已修改
+sync-change-deleted = 已删除
+sync-how-it-works = 增量备份的工作方式：
+sync-how-first = 首次备份会创建完整的压缩包和清单
+sync-how-subsequent = 之后的备份只包含新增或修改的文件
+sync-how-manifest = 每次备份都会用当前状态更新清单
+sync-how-deleted = 已删除的文件会记录在单独的列表中
+
+## Snapshots
+
+snapshot-title = 快照
+snapshot-loading = 正在读取快照...
+snapshot-load-failed = 无法读取快照：{ $error }
+snapshot-none = 还没有快照。每次同步都会记录为一个快照。
+snapshot-date = 日期
+snapshot-changes = 更改
+snapshot-files = 文件
+snapshot-size-hint = 共备份 { $total }
+snapshot-browse = 浏览
+snapshot-files-of = 快照 { $sequence } 的文件
+snapshot-restore-all = 全部恢复...
+snapshot-restore-selected = 恢复所选的 { $count } 项...
+snapshot-restoring = 正在恢复...
+snapshot-restore-done = 快照已恢复：{ $summary }
+snapshot-restore-failed = 无法恢复快照：{ $error }
+snapshot-restore-stopped = 恢复在完成前已停止
+
+## Archive browser
+
+browser-title = 压缩包浏览器
+browser-open-failed = 无法打开浏览器：{ $error }
+browser-unknown-archive = 未知压缩包
+browser-file-count = { $count } 个文件
+browser-folder-count = { $count } 个文件夹
+browser-total-size = 总计：{ $size }
+browser-reading = 正在读取条目…
+browser-extract-all = 全部解压
+browser-extract-selected = 解压选中的 { $count } 项
+browser-search-hint = 按名称搜索
+browser-show-hidden = 显示隐藏文件
+browser-tree = 树状
+browser-table = 表格
+browser-clear-selection = 取消选择
+browser-copy-to-clipboard = { $count } 项 - 拖放到窗口外即可复制到剪贴板
+browser-preview = 预览
+browser-details = 详细信息
+browser-directory = 文件夹
+browser-compressed = 压缩后：
+browser-modified = 修改时间：
+browser-permissions = 权限：
+browser-path = 路径：
+browser-select-for-details = 选择一个项目以查看详细信息
+browser-items-selected = 已选择 { $count } 项
+browser-files = 文件：
+browser-directories = 文件夹：
+browser-total-size-label = 总大小：
+browser-remote-no-preview = 云存储中的归档无法预览
+browser-edit = 编辑
+browser-edit-hint = 重命名、删除和添加条目，然后保存到归档中
+browser-edit-unavailable = 只能编辑本地的 zip 和 tar 归档，且需读取完所有条目
+browser-edit-add-files = 添加文件
+browser-edit-add-folder = 添加文件夹
+browser-edit-add-hint = 添加到高亮的文件夹，或归档的顶层
+browser-edit-delete = 删除 { $count } 项
+browser-edit-rename-hint = 高亮条目的新路径
+browser-edit-rename = 重命名
+browser-edit-save = 保存 { $count } 项更改
+browser-edit-discard = 放弃
+browser-edit-saving = 正在将更改保存到归档…
+browser-edit-save-stopped = 保存意外中止
+browser-edit-no-changes = 尚无更改
+browser-edit-changes = { $count } 项未保存的更改
+browser-edit-undo = 撤销此更改
+file-type-gzip = Gzip 压缩文件
+file-type-text = 文本文档
+file-type-markdown = Markdown 文档
+file-type-pdf = PDF 文档
+file-type-jpeg = JPEG 图像
+file-type-png = PNG 图像
+file-type-gif = GIF 图像
+file-type-mp3 = MP3 音频
+file-type-mp4 = MP4 视频
+file-type-rust = Rust 源代码
+file-type-python = Python 脚本
+file-type-javascript = JavaScript
+file-type-file = 文件
+preview-loading = 正在加载预览...
+preview-unavailable = 无法预览：{ $error }
+preview-truncated = 仅显示前 { $size } KB
+
+## Browser table
+
+table-showing = 显示 { $shown } / { $total } 项
+table-name = 名称
+table-size = 大小
+table-type = 类型
+table-modified = 修改时间
+table-compressed = 压缩后
+table-show-all-types = 显示所有类型
+table-sort-hint = 点击排序，再次点击反向，右键恢复压缩包顺序
+table-type-text = 文本
+table-type-image = 图像
+table-type-audio = 音频
+table-type-video = 视频
+table-type-document = 文档
+table-type-spreadsheet = 电子表格
+table-type-executable = 可执行文件
+table-type-code = 源代码
+file-kind-folders = 文件夹
+file-kind-text = 文本
+file-kind-images = 图像
+file-kind-audio = 音频
+file-kind-video = 视频
+file-kind-archives = 压缩包
+file-kind-documents = 文档
+file-kind-code = 源代码
+file-kind-executables = 可执行文件
+file-kind-other = 其他文件
+
+## Properties
+
+properties-reading = 正在读取压缩包...
+properties-read-failed = 无法读取压缩包：{ $error }
+properties-format = 格式：
+properties-compression = 压缩方式：
+properties-archive-size = 压缩包大小：
+properties-extracted-size = 解压后大小：
+properties-ratio = 压缩率：
+properties-ratio-value = { $ratio }%（节省 { $saved }%）
+properties-not-available = 不适用
+properties-entries = 条目：
+properties-entries-value = { $files } 个文件，{ $folders } 个文件夹，{ $symlinks } 个符号链接
+properties-encrypted-headers = 文件列表已加密，需要密码才能查看内容
+properties-encrypted-entries = { $total } 个文件中有 { $count } 个已加密
+properties-not-encrypted = 未加密
+properties-signature = 独立签名：{ $name }
+properties-signature-hint = 已找到签名文件，但尚未验证
+properties-no-signature = 无签名
+properties-metadata = 来源
+properties-creator = 创建者：
+properties-hostname = 主机：
+properties-created = 创建时间：
+properties-flux-version = Flux 版本：
+properties-comment = 注释
+
+## Verification
+
+verify = 校验
+verify-hint = 根据归档中保存的校验和检查每个条目
+verify-running = 正在检查条目...已读取 { $count } 个
+verify-stopped = 校验意外中止
+verify-failed = 无法校验归档：{ $error }
+verify-passed = 未发现损坏的条目
+verify-problems = 发现 { $count } 个损坏的条目
+verify-counts = 共 { $total } 个条目：{ $ok } 个已校验，{ $corrupt } 个损坏，{ $unchecked } 个无校验和
+verify-unchecked-hint = 无校验和的条目读取时没有出错，但无法确认其内容是否完整。
+verify-problems-only = 隐藏已校验的条目
+verify-nothing-to-show = 所有条目均已通过校验
+verify-status-ok = 正常
+verify-status-corrupt = 已损坏
+verify-status-no-checksum = 无校验和
+verify-status-encrypted = 已加密
+verify-export = 导出报告
+verify-exported = 校验报告已保存
+verify-export-failed = 无法保存报告：{ $error }
+
+## Cloud storage
+
+common-loading = 正在加载...
+cloud-title = 云存储
+cloud-pack-here = 在此打包
+cloud-pack-here-hint = 在此位置创建压缩包
+cloud-refresh = 刷新
+cloud-profiles = 配置
+cloud-open = 打开
+cloud-credentials-note = 凭据从环境变量读取，与命令行工具相同。
+cloud-empty-prompt = 输入存储桶 URL 或选择一个配置以浏览其内容。
+cloud-parent-folder = 上级文件夹
+cloud-location-empty = 此位置为空。
+cloud-extract-hint = 下载并解压到本地文件夹
+cloud-invalid-location = 无效的位置：{ $error }
+cloud-packing-to = 正在打包到 { $url }
+cloud-connect-failed = 无法连接到 { $location }：{ $error }
+cloud-list-failed = 无法列出 { $location }：{ $error }
+cloud-unsupported-format = 不支持的压缩格式：{ $url }
+cloud-open-failed = 无法打开 { $url }：{ $error }
+cloud-extract-failed = 无法解压 { $url }：{ $error }
+cloud-invalid-destination = 无效的目标 { $url }：{ $error }
+cloud-no-object-name = 目标缺少对象名称：{ $url }
+cloud-upload-failed = 无法上传 { $url }：{ $error }
+cloud-downloading = 正在下载...
+cloud-uploading = 正在上传...
+cloud-open-archive = 从云端打开
+cloud-open-archive-hint = 无需下载即可浏览 S3、Google Cloud Storage 或 Azure 中的归档
+cloud-reading-archive = 正在读取归档... 已找到 { $count } 个条目
+cloud-list-archive-failed = 读取 { $url } 失败：{ $error }
+cloud-list-stopped = 读取归档意外中止
+cloud-hint-missing-credentials = 未找到 { $provider } 凭据。请设置 { $vars } 后重启 Flux。
+cloud-hint-invalid-credentials = { $provider } 拒绝了来自 { $vars } 的凭据。
+cloud-hint-permission-denied = 当前凭据无权访问此位置。
+cloud-hint-not-found = 存储桶或对象不存在。
+
+## Schedules
+
+cadence-hourly = 每小时
+cadence-daily = 每天
+cadence-weekly = 每周
+schedules-title = 定时备份
+schedules-description = 定时任务在 Flux 运行时执行。Flux 关闭期间错过的备份会在下次启动时运行。
+schedules-new = 新建定时任务
+schedules-name = 名称：
+schedules-name-hint = 默认使用源文件夹名称
+schedules-source-directory = 源目录：
+schedules-target-archive = 目标压缩包：
+schedules-runs = 运行频率：
+schedules-add = 添加定时任务
+schedules-empty = 暂无定时任务。
+schedules-enabled-hint = 按计划运行
+schedules-delete = 删除
+schedules-run-now = 立即运行
+schedules-source = 源：
+schedules-target = 目标：
+schedules-last-result = 上次结果：
+schedules-never-run = 从未运行
+schedules-next-run = 下次运行：
+schedules-running-now = 正在运行
+schedules-due = 待运行
+schedules-disabled = 已停用
+schedules-history = 历史记录（{ $count }）
+schedules-no-log = 无日志消息
+schedules-outcome-running = 运行中
+schedules-outcome-success = 成功
+schedules-outcome-failed = 失败：{ $error }
+schedules-outcome-cancelled = 已取消
+schedules-outcome-duration = { $outcome }，用时 { $seconds } 秒
+
+## Previous session
+
+session-title = 恢复上次会话
+session-intro = Flux 关闭时仍有已设置或正在运行的工作。
+session-draft = 未开始的任务
+session-draft-pack = 打包 { $count } 个选中的项目
+session-draft-extract = 解压 { $count } 个选中的归档
+session-draft-sync = 同步 { $source }
+session-tasks = 被中断的任务
+session-restart-hint = 勾选的任务将从头重新开始。
+session-restore = 恢复
+session-discard = 放弃
+
+## Recent archives
+
+recent-title = 最近使用
+recent-opened = 打开于 { $time }
+recent-opened-hint = 浏览此压缩包
+recent-created = 创建于 { $time }
+recent-created-hint = 使用相同设置再次打包相同的文件
+recent-destination = 解压于 { $time }
+recent-destination-hint = 将其他压缩包解压到此处
+recent-remove = 从历史记录中移除
+recent-pin = 固定到顶部
+recent-unpin = 取消固定
+
+## Task queue
+
+tasks-title = 任务
+tasks-clear-finished = 清除已完成
+tasks-cancel-all = 全部取消
+tasks-pause-all = 全部暂停
+tasks-resume-all = 全部继续
+tasks-empty = 暂无任务。打包、解压和同步任务会显示在这里。
+tasks-cancel = 取消任务
+tasks-pause = 暂停任务
+tasks-resume = 继续任务
+tasks-status-queued = 排队中
+tasks-status-running = 运行中
+tasks-status-done = 已完成
+tasks-status-failed = 失败
+tasks-status-cancelled = 已取消
+tasks-status-paused = 已暂停
+tasks-waiting = 等待中...
+tasks-workers-stopped = 后台工作线程已停止
+tasks-starting = 正在启动...
+tasks-progress = { $processed } / { $total } MB（{ $percent }%）- { $speed }
+tasks-progress-eta = { $processed } / { $total } MB（{ $percent }%）- { $speed } - 剩余时间：{ $eta }
+tasks-processing = 正在处理...
+tasks-completed = 已完成
+tasks-error = 错误：{ $error }
+tasks-waiting-for-password = 等待输入密码...
+tasks-cancelling = 正在取消...
+task-label-pack = 打包 { $name }
+task-label-extract = 解压 { $name }
+task-label-sync = 同步 { $name }
+
+## Task history
+
+task-history-tab-queue = 队列
+task-history-tab-history = 历史
+task-history-title = 已结束的任务
+task-history-clear = 清除历史
+task-history-empty = 已结束的任务及其日志和报告会显示在这里。
+task-history-details = 查看日志和报告
+task-history-report = 报告
+task-history-done = 已完成
+task-history-skipped = 已跳过
+task-history-failed = 失败
+task-history-no-report = 此任务没有条目报告。
+task-history-log = 日志
+task-history-no-log = 没有日志记录。
+task-history-open-output = 打开输出文件夹
+task-history-reveal-manifest = 显示清单文件
+task-history-copy-report = 复制报告 JSON
+task-history-report-copied = 报告已复制到剪贴板
+task-history-open-failed = 无法打开文件管理器：{ $error }
+
+## Notifications
+
+toast-ready-to-extract = 准备解压：{ $name }
+toast-ready-to-extract-many = 已准备解压 { $count } 个归档
+toast-ready-to-pack-one = 准备打包 1 个文件
+toast-ready-to-pack = 准备打包 { $count } 个文件
+toast-shell-installed = 已将 Flux 添加到文件管理器菜单
+toast-shell-removed = 已从文件管理器菜单中移除 Flux
+toast-shell-failed = 无法更新文件管理器菜单：{ $error }
+toast-task-started = 已开始：{ $task }
+toast-task-queued = 已排队：{ $task }（前方 { $position } 个）
+toast-workers-not-responding = 无法启动任务：后台工作线程无响应
+toast-input-missing = { $path } 已不存在
+toast-session-restarted = 已重新开始 { $count } 个被中断的任务
+toast-session-input-missing = 无法重新开始 { $task }：{ $path } 已不存在
+toast-output-dir-missing = 输出目录不存在
+toast-select-output = 请先选择输出路径
+toast-archive-missing = 找不到压缩包文件
+toast-batch-archive-missing = 找不到归档：{ $path }，未解压任何文件
+toast-create-dir-failed = 无法创建 { $path }
+toast-select-archive-and-output = 请先选择压缩包和输出目录
+toast-source-dir-missing = 源目录不存在
+toast-target-dir-missing = 目标目录不存在
+toast-select-source-and-target = 请先选择源目录和目标压缩包
+toast-scheduled = 已添加定时任务：{ $name }
+toast-open-archive-failed = 无法打开压缩包：{ $error }
+toast-read-entries-failed = 无法读取压缩包条目：{ $error }
+toast-browsing = 正在浏览：{ $name }
+toast-archive-saved = 已将 { $count } 个更改的条目保存到归档
+toast-archive-save-failed = 保存更改失败：{ $error }
+toast-staging-failed = 无法创建临时目录：{ $error }
+toast-clipboard-failed = 无法打开剪贴板：{ $error }
+toast-clipboard-copy-failed = 无法将文件复制到剪贴板：{ $error }
+toast-copied-to-clipboard = 已将 { $count } 个项目复制到剪贴板，可粘贴到文件管理器中
+
+## Background work
+
+worker-preparing-pack = 正在准备打包...
+worker-pack-complete = 打包完成
+worker-adding = 正在添加：{ $path }
+worker-opening-archive = 正在打开压缩包...
+worker-extracting-count = 正在解压 { $count } 个文件...
+worker-extracting-entry = 正在解压（{ $current }/{ $total }）：{ $name }
+worker-extracted-count = 已成功解压 { $count } 个文件
+worker-entry-failed = 处理 { $path } 失败：{ $error }
+worker-entries-failed = { $total } 个条目中有 { $failed } 个失败
+worker-backup-changes = 备份完成 - { $count } 处更改
+worker-creating-full-backup = 正在创建完整备份...
+worker-full-backup-complete = 完整备份完成
+
+## Commands and shortcuts
+
+command-open-archive = 打开压缩包
+command-pack-files = 打包文件
+command-start-task = 开始任务
+command-cancel-tasks = 取消所有任务
+command-pause-tasks = 暂停或继续所有任务
+command-toggle-logs = 显示/隐藏日志
+command-toggle-tasks = 显示/隐藏任务
+command-palette = 命令面板
+command-toggle-theme = 切换深色模式
+command-show-welcome = 转到主页
+command-show-pack = 转到打包
+command-show-extract = 转到解压
+command-show-sync = 转到同步
+command-show-cloud = 转到云存储
+command-show-schedules = 转到定时备份
+command-show-settings = 转到设置
+command-nothing-to-start = 当前视图没有可开始的任务
+palette-hint = 输入命令...
+palette-no-matches = 没有匹配的命令
+settings-shortcuts = 键盘快捷键
+settings-shortcuts-description = 点击快捷键后按下新的组合键，按 Esc 取消。
+settings-shortcuts-reset = 恢复默认快捷键
+settings-shortcut-press = 请按键...
+settings-shortcut-none = 无
+settings-shortcut-change = 点击以录制新的快捷键
+settings-shortcut-remove = 移除快捷键
+settings-shortcut-moved = 该快捷键已从“{ $command }”移除
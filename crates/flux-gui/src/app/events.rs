@@ -1,13 +1,28 @@
 //! Event handling for the Flux GUI application
 
+use super::state::ClipboardCopy;
 use super::{AppView, FluxApp};
-use crate::task::TaskCommand;
-use crate::views::BrowserState;
-use std::path::PathBuf;
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use crate::archive_index::{IndexEvent, IndexLoader};
+use crate::cloud::RemoteListing;
+use crate::history::{PackRecipe, RecentKind};
+use crate::launch::LaunchRequest;
+use crate::session::{SavedTask, Session, SessionDraft};
+use crate::settings::SMART_FORMAT;
+use crate::shortcuts::Command;
+use crate::t;
+use crate::task::{TaskCommand, TaskControl, TaskId};
+use crate::task_history::TaskRecord;
+use crate::views::packing_view_modern::with_format;
+use crate::views::{
+    BrowserState, CommandPalette, PackJobOptions, SessionRestore, SnapshotAction, SnapshotBrowser,
+    SyncPreview,
 };
+use chrono::Utc;
+use flux_core::archive::detect::FILE_EXTENSIONS;
+use flux_core::archive::PackOptions;
+use flux_core::shell_integration;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tracing::{debug, info, warn};
 
 impl FluxApp {
@@ -22,142 +37,760 @@ impl FluxApp {
             let file = &files[0];
             let file_name = file.file_name().map(|n| n.to_string_lossy().to_string());
 
-            // Check for common archive extensions
-            if let Some(ext) = file.extension() {
-                let ext_str = ext.to_string_lossy().to_lowercase();
-                if matches!(
-                    ext_str.as_str(),
-                    "zip" | "tar" | "gz" | "zst" | "xz" | "7z" | "br"
-                ) {
-                    // Switch to browser view to explore the archive
-                    if let Err(e) = self.open_archive_browser(file.clone()) {
-                        // Fall back to extraction view if browser fails
-                        warn!("Failed to open archive browser: {}", e);
-                        self.view = AppView::Extracting;
-                        self.input_files = files;
-                        info!(file = ?file_name, "Ready to extract archive");
-                        self.toasts.info(format!(
-                            "Ready to extract: {}",
-                            file_name.as_deref().unwrap_or("archive")
-                        ));
-                    }
+            // Common archive extensions, or without any the signature of an
+            // archive, as for a download saved as `download(1)`; zip-based
+            // documents such as .docx keep being packed
+            let is_archive = match file.extension() {
+                Some(ext) => {
+                    FILE_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str())
+                }
+                None => flux_core::archive::sniff_format(file).is_some(),
+            };
+            if is_archive {
+                // Switch to browser view to explore the archive
+                if let Err(e) = self.open_archive_browser(file.clone()) {
+                    // Fall back to extraction view if browser fails
+                    warn!("Failed to open archive browser: {}", e);
+                    self.view = AppView::Extracting;
+                    self.input_files = files;
+                    info!(file = ?file_name, "Ready to extract archive");
+                    self.toasts.info(t!(
+                        "toast-ready-to-extract",
+                        name = file_name.as_deref().unwrap_or("archive")
+                    ));
+                }
+                return;
+            }
+        }
+
+        // Several archives and nothing else are extracted as a batch
+        if files.len() > 1
+            && files.iter().all(|file| {
+                file.is_file()
+                    && file
+                        .file_name()
+                        .and_then(|name| crate::cloud::archive_format(&name.to_string_lossy()))
+                        .is_some()
+            })
+        {
+            let count = files.len();
+            self.view = AppView::Extracting;
+            self.input_files = files;
+            self.output_path = None;
+            info!(archives = count, "Ready to extract archives");
+            self.toasts
+                .info(t!("toast-ready-to-extract-many", count = count));
+            return;
+        }
+
+        // Multiple files, single non-archive file, or directories - switch to packing view
+        self.view = AppView::Packing;
+        let count = files.len();
+        self.input_files = files;
+        info!(files = count, "Ready to pack files");
+        self.toasts.info(if count == 1 {
+            t!("toast-ready-to-pack-one").to_string()
+        } else {
+            t!("toast-ready-to-pack", count = count)
+        });
+    }
+
+    /// Act on the paths the application was started with
+    pub(super) fn handle_launch(&mut self, launch: LaunchRequest) {
+        match launch {
+            LaunchRequest::Open(paths) => self.analyze_dropped_files(paths),
+            LaunchRequest::Compress(paths) => {
+                let name = match paths.as_slice() {
+                    [single] => single
+                        .file_name()
+                        .map(|name| name.to_string_lossy().into_owned()),
+                    _ => None,
+                }
+                .unwrap_or_else(|| "archive".to_string());
+                let format = self
+                    .pack_job
+                    .effective_format(&self.compression_format)
+                    .unwrap_or("tar.zst");
+                self.output_path = paths
+                    .first()
+                    .and_then(|path| path.parent())
+                    .map(|dir| with_format(&dir.join(name), format));
+
+                info!(files = paths.len(), "Launched to pack files");
+                self.input_files = paths;
+                self.cloud_pack_destination = None;
+                self.view = AppView::Packing;
+            }
+            LaunchRequest::ExtractTo {
+                archive,
+                output_dir,
+            } => {
+                info!(archive = ?archive, output_dir = ?output_dir, "Launched to extract into a folder");
+                self.submit_extract(archive, output_dir, self.extract_hoist);
+            }
+            LaunchRequest::Extract(archives) => {
+                if let [archive] = archives.as_slice() {
+                    // Confirm the destination in the extract view first
+                    info!(archive = ?archive, "Launched to extract archive");
+                    self.output_path = archive.parent().map(Path::to_path_buf);
+                    self.input_files = archives;
+                    self.view = AppView::Extracting;
                     return;
                 }
+
+                // Several archives were picked at once; extract each one
+                // next to itself
+                for archive in archives {
+                    let Some(output_dir) = archive.parent().map(Path::to_path_buf) else {
+                        continue;
+                    };
+                    self.submit_extract(archive, output_dir, self.extract_hoist);
+                }
+            }
+        }
+    }
+
+    /// Add or remove the file manager context menu entries
+    pub(super) fn set_shell_integration(&mut self, install: bool) {
+        let result = if install {
+            std::env::current_exe()
+                .map_err(flux_core::Error::from)
+                .and_then(|exe| shell_integration::install(&exe))
+        } else {
+            shell_integration::uninstall()
+        };
+
+        match result {
+            Ok(locations) => {
+                for location in &locations {
+                    info!(
+                        "{} {}",
+                        if install { "Registered" } else { "Removed" },
+                        location
+                    );
+                }
+                self.toasts.success(if install {
+                    t!("toast-shell-installed")
+                } else {
+                    t!("toast-shell-removed")
+                });
+            }
+            Err(e) => {
+                warn!("Context menu integration failed: {}", e);
+                self.toasts.error(t!("toast-shell-failed", error = e));
             }
+        }
+        self.shell_integrated = shell_integration::is_installed();
+    }
 
-            // Also check for compound extensions like .tar.gz
-            if let Some(name) = &file_name {
-                let name_lower = name.to_lowercase();
-                if name_lower.ends_with(".tar.gz")
-                    || name_lower.ends_with(".tar.zst")
-                    || name_lower.ends_with(".tar.xz")
-                    || name_lower.ends_with(".tar.br")
+    /// Run a command picked from the palette or triggered by its shortcut
+    pub(super) fn run_command(&mut self, command: Command) {
+        debug!(?command, "Running command");
+        match command {
+            Command::OpenArchive => {
+                if let Some(file) = rfd::FileDialog::new()
+                    .add_filter(t!("dialog-archives"), FILE_EXTENSIONS)
+                    .pick_file()
                 {
-                    // Switch to browser view to explore the archive
-                    if let Err(e) = self.open_archive_browser(file.clone()) {
-                        // Fall back to extraction view if browser fails
-                        warn!("Failed to open archive browser: {}", e);
-                        self.view = AppView::Extracting;
-                        self.input_files = files;
-                        info!(file = name, "Ready to extract compressed tar archive");
-                        self.toasts.info(format!("Ready to extract: {}", name));
+                    self.analyze_dropped_files(vec![file]);
+                }
+            }
+            Command::PackFiles => {
+                if let Some(files) = rfd::FileDialog::new().pick_files() {
+                    self.analyze_dropped_files(files);
+                }
+            }
+            Command::StartTask => match self.view {
+                AppView::Packing | AppView::Extracting => {
+                    // The views keep their start buttons disabled meanwhile
+                    match self.preflight.problems().first() {
+                        Some(problem) => {
+                            self.toasts.warning(problem.message());
+                        }
+                        None => self.start_task(),
                     }
-                    return;
+                }
+                AppView::Syncing => self.start_sync_task(),
+                _ => {
+                    self.toasts.info(t!("command-nothing-to-start"));
+                }
+            },
+            Command::CancelTasks => {
+                if self.task_queue.active_count() > 0 {
+                    self.task_queue.cancel_all();
+                    self.toasts.info(t!("tasks-cancelling-all"));
                 }
             }
+            Command::PauseTasks => self.toggle_pause_all(),
+            Command::ToggleLogs => self.show_log_panel = !self.show_log_panel,
+            Command::ToggleTasks => self.show_task_panel = !self.show_task_panel,
+            Command::OpenPalette => self.command_palette = Some(CommandPalette::default()),
+            Command::ToggleTheme => self.toggle_theme(),
+            Command::ShowWelcome => self.view = AppView::Welcome,
+            Command::ShowPack => self.view = AppView::Packing,
+            Command::ShowExtract => self.view = AppView::Extracting,
+            Command::ShowSync => self.view = AppView::Syncing,
+            Command::ShowCloud => self.view = AppView::Cloud,
+            Command::ShowSchedules => self.view = AppView::Schedules,
+            Command::ShowSettings => self.view = AppView::Settings,
         }
+    }
 
-        // Multiple files, single non-archive file, or directories - switch to packing view
+    /// Cancel a queued or running task
+    pub(super) fn cancel_task(&mut self, id: TaskId) {
+        if self.task_queue.cancel(id) {
+            info!(task = id, "Cancelling task");
+            self.toasts.info(t!("tasks-cancelling"));
+        }
+    }
+
+    /// Park a running task until the user resumes it
+    pub(super) fn pause_task(&mut self, id: TaskId) {
+        if self.task_queue.pause(id) {
+            info!(task = id, "Pausing task");
+        }
+    }
+
+    /// Let a paused task continue
+    pub(super) fn resume_task(&mut self, id: TaskId) {
+        if self.task_queue.resume(id) {
+            info!(task = id, "Resuming task");
+        }
+    }
+
+    /// Resume all tasks if any is paused, otherwise pause all that can be
+    pub(super) fn toggle_pause_all(&mut self) {
+        if self.task_queue.paused_count() > 0 {
+            self.task_queue.resume_all();
+            self.toasts.info(t!("tasks-resumed-all"));
+        } else if self.task_queue.pause_all() > 0 {
+            self.toasts.info(t!("tasks-paused-all"));
+        } else {
+            self.toasts.info(t!("tasks-nothing-to-pause"));
+        }
+    }
+
+    /// Add a task to the queue and start it if a worker is free
+    fn submit_task(&mut self, command: TaskCommand) -> TaskId {
+        let label = command.describe();
+        let record = TaskRecord::new(&command);
+        let id = self.task_queue.submit(command);
+        self.task_history.submitted(id, record);
+        self.show_task_panel = true;
+
+        if self.task_queue.dispatch(&self.task_sender) {
+            let position = self.task_queue.active_count() - self.task_queue.running_count();
+            info!(task = id, "Submitted task: {}", label);
+            if position == 0 {
+                self.toasts.info(t!("toast-task-started", task = label));
+            } else {
+                self.toasts
+                    .info(t!("toast-task-queued", task = label, position = position));
+            }
+        } else {
+            warn!("Failed to send task to background workers");
+            self.toasts.error(t!("toast-workers-not-responding"));
+        }
+        id
+    }
+
+    /// Control for a task that keeps to the limits of the settings
+    fn task_control(&self) -> TaskControl {
+        TaskControl::with_limits(self.settings.limits.clone())
+    }
+
+    /// Like [`Self::submit_task`], keeping what is needed to start the task
+    /// again if the app closes before it finishes
+    fn submit_restartable(&mut self, command: TaskCommand, recipe: Option<PackRecipe>) -> TaskId {
+        let saved = SavedTask::from_command(&command, recipe);
+        let id = self.submit_task(command);
+        if let Some(saved) = saved {
+            self.restartable.insert(id, saved);
+        }
+        id
+    }
+
+    /// Queue extracting all of `archive` into `output_dir`, remembering the
+    /// destination once it succeeds
+    fn submit_extract(&mut self, archive: PathBuf, output_dir: PathBuf, hoist: bool) {
+        let command = TaskCommand::Extract {
+            archive,
+            output_dir: output_dir.clone(),
+            entries: None,
+            hoist,
+            control: self.task_control(),
+        };
+        let task_id = self.submit_restartable(command, None);
+        self.history
+            .record_on_success(task_id, output_dir, RecentKind::Destination, None);
+    }
+
+    /// Queue a refused extraction again, into `output_dir` instead
+    pub(super) fn retry_extract_in(&mut self, task: SavedTask, output_dir: PathBuf) {
+        let SavedTask::Extract {
+            archive,
+            entries,
+            hoist,
+            ..
+        } = task
+        else {
+            return;
+        };
+        info!(archive = ?archive, output_dir = ?output_dir, "Retrying extraction elsewhere");
+        match entries {
+            None => self.submit_extract(archive, output_dir, hoist),
+            Some(entries) => {
+                let command = TaskCommand::Extract {
+                    archive,
+                    output_dir,
+                    entries: Some(entries),
+                    hoist,
+                    control: self.task_control(),
+                };
+                self.submit_restartable(command, None);
+            }
+        }
+    }
+
+    /// Queue every archive of the extract view, each going where the batch
+    /// destination rule puts it
+    ///
+    /// Nothing is queued when an archive is missing, so a batch is never
+    /// half started.
+    fn extract_all(&mut self) {
+        let base = self.output_path.clone();
+        if base.as_ref().is_some_and(|base| !base.exists()) {
+            warn!("Output directory does not exist: {:?}", base);
+            self.toasts.error(t!("toast-output-dir-missing"));
+            return;
+        }
+
+        let mut targets = Vec::new();
+        for archive in &self.input_files {
+            if !archive.exists() {
+                warn!("Archive file not found: {:?}", archive);
+                self.toasts
+                    .error(t!("toast-batch-archive-missing", path = archive.display()));
+                return;
+            }
+            if let Some(output_dir) = self.batch_destination.output_dir(archive, base.as_deref()) {
+                targets.push((archive.clone(), output_dir));
+            }
+        }
+
+        if let Some((_, output_dir)) = targets
+            .iter()
+            .find(|(_, output_dir)| self.needs_overwrite_confirmation(output_dir))
+        {
+            self.pending_overwrite = Some(output_dir.clone());
+            return;
+        }
+
+        info!(archives = targets.len(), "Extracting archives as a batch");
+        for (archive, output_dir) in targets {
+            if let Err(e) = std::fs::create_dir_all(&output_dir) {
+                warn!("Failed to create {:?}: {}", output_dir, e);
+                self.toasts
+                    .error(t!("toast-create-dir-failed", path = output_dir.display()));
+                continue;
+            }
+            self.submit_extract(archive, output_dir, self.extract_hoist);
+        }
+    }
+
+    /// Settings of the pack being started, for packing it again later
+    fn pack_recipe(&self, options: &PackOptions) -> PackRecipe {
+        // The concrete format keeps a re-run from waiting on a new smart
+        // analysis and matches the archive's extension
+        let format = self
+            .pack_job
+            .effective_format(&self.compression_format)
+            .map(str::to_string)
+            .unwrap_or_else(|| self.compression_format.clone());
+
+        PackRecipe {
+            inputs: self.input_files.clone(),
+            format,
+            level: options.level,
+            threads: options.threads,
+            follow_symlinks: options.follow_symlinks,
+            force_compress: options.force_compress,
+            exclude_patterns: self.pack_job.exclude_patterns.clone(),
+            ignore_vcs: self.pack_job.ignore_vcs,
+            max_file_mb: self.pack_job.max_file_mb,
+        }
+    }
+
+    /// Pack view options set up as in `recipe`
+    fn pack_job_for(&self, recipe: &PackRecipe) -> PackJobOptions {
+        let mut job = PackJobOptions::new(&self.settings);
+        job.level = recipe.level;
+        job.threads = recipe.threads;
+        job.follow_symlinks = recipe.follow_symlinks;
+        job.force_compress = recipe.force_compress;
+        job.exclude_patterns = recipe.exclude_patterns.clone();
+        job.ignore_vcs = recipe.ignore_vcs;
+        job.max_file_mb = recipe.max_file_mb;
+        job
+    }
+
+    /// Pack a recently created archive again with the settings it was
+    /// created with
+    pub(super) fn rerun_pack(&mut self, archive: &Path) {
+        let Some(recipe) = self
+            .history
+            .get(archive, RecentKind::Created)
+            .and_then(|item| item.recipe.clone())
+        else {
+            return;
+        };
+
+        if let Some(missing) = recipe.inputs.iter().find(|path| !path.exists()) {
+            self.toasts
+                .error(t!("toast-input-missing", path = missing.display()));
+            return;
+        }
+
+        self.pack_job = self.pack_job_for(&recipe);
+        self.input_files = recipe.inputs;
+        self.compression_format = recipe.format;
+        self.output_path = Some(archive.to_path_buf());
+        self.cloud_pack_destination = None;
         self.view = AppView::Packing;
-        let count = files.len();
-        self.input_files = files;
-        info!(files = count, "Ready to pack files");
-        self.toasts.info(format!(
-            "Ready to pack {} file{}",
-            count,
-            if count == 1 { "" } else { "s" }
-        ));
+        self.start_task();
     }
 
-    /// Cancel the current task
-    pub(super) fn cancel_task(&mut self) {
-        if let Some(flag) = &self.cancel_flag {
-            flag.store(true, Ordering::SeqCst);
-            info!("Cancelling current task");
-            self.toasts.info("Cancelling task...");
+    /// The unstarted job and the unfinished tasks, for the next launch
+    pub(super) fn session(&self) -> Session {
+        let draft = match self.view {
+            AppView::Packing if !self.input_files.is_empty() => Some(SessionDraft::Pack {
+                recipe: PackRecipe {
+                    inputs: self.input_files.clone(),
+                    format: self.compression_format.clone(),
+                    level: self.pack_job.level,
+                    threads: self.pack_job.threads,
+                    follow_symlinks: self.pack_job.follow_symlinks,
+                    force_compress: self.pack_job.force_compress,
+                    exclude_patterns: self.pack_job.exclude_patterns.clone(),
+                    ignore_vcs: self.pack_job.ignore_vcs,
+                    max_file_mb: self.pack_job.max_file_mb,
+                },
+                output: self.output_path.clone(),
+                cloud_destination: self.cloud_pack_destination.clone(),
+            }),
+            AppView::Extracting if !self.input_files.is_empty() => Some(SessionDraft::Extract {
+                archives: self.input_files.clone(),
+                output_dir: self.output_path.clone(),
+                hoist: self.extract_hoist,
+            }),
+            AppView::Syncing
+                if self.sync_source_dir.is_some() || self.sync_target_archive.is_some() =>
+            {
+                Some(SessionDraft::Sync {
+                    source_dir: self.sync_source_dir.clone(),
+                    target_archive: self.sync_target_archive.clone(),
+                    manifest: self.sync_manifest_path.clone(),
+                })
+            }
+            _ => None,
+        };
+
+        let active = self
+            .task_queue
+            .tasks()
+            .iter()
+            .filter(|task| task.status.is_active())
+            .map(|task| task.id);
+        Session {
+            draft,
+            tasks: self.restartable.saved(active),
+        }
+    }
+
+    /// Bring back the previous session's job and start the tasks the user
+    /// kept checked
+    pub(super) fn restore_session(&mut self, restore: SessionRestore) {
+        let SessionRestore { session, selected } = restore;
+        if let Some(draft) = session.draft {
+            self.restore_draft(draft);
+        }
+
+        let mut restarted = 0;
+        for (task, keep) in session.tasks.into_iter().zip(selected) {
+            if keep && self.restart_task(task) {
+                restarted += 1;
+            }
+        }
+        info!(tasks = restarted, "Restored previous session");
+        if restarted > 0 {
+            self.toasts
+                .info(t!("toast-session-restarted", count = restarted));
+        }
+    }
+
+    /// Fill a task view in again, leaving out inputs that no longer exist
+    fn restore_draft(&mut self, draft: SessionDraft) {
+        match draft {
+            SessionDraft::Pack {
+                mut recipe,
+                output,
+                cloud_destination,
+            } => {
+                recipe.inputs.retain(|path| path.exists());
+                self.pack_job = self.pack_job_for(&recipe);
+                self.input_files = recipe.inputs;
+                self.compression_format = recipe.format;
+                self.output_path = output;
+                self.cloud_pack_destination = cloud_destination;
+                self.view = AppView::Packing;
+            }
+            SessionDraft::Extract {
+                mut archives,
+                output_dir,
+                hoist,
+            } => {
+                archives.retain(|path| path.exists());
+                self.input_files = archives;
+                self.output_path = output_dir;
+                self.extract_hoist = hoist;
+                self.view = AppView::Extracting;
+            }
+            SessionDraft::Sync {
+                source_dir,
+                target_archive,
+                manifest,
+            } => {
+                self.sync_source_dir = source_dir;
+                self.sync_target_archive = target_archive;
+                self.sync_manifest_path = manifest;
+                self.refresh_snapshots();
+                self.view = AppView::Syncing;
+            }
+        }
+    }
+
+    /// Pack options for `recipe`, telling the user if they are invalid
+    fn recipe_options(&mut self, recipe: &PackRecipe) -> Option<PackOptions> {
+        match self.pack_job_for(recipe).pack_options(&recipe.format) {
+            Ok(options) => Some(options),
+            Err(e) => {
+                self.toasts.warning(e);
+                None
+            }
+        }
+    }
+
+    /// Queue an interrupted task again from the start
+    ///
+    /// Returns false, after telling the user, if its inputs are gone.
+    fn restart_task(&mut self, task: SavedTask) -> bool {
+        let label = task.describe();
+        let missing = match &task {
+            SavedTask::Pack { recipe, .. } | SavedTask::CloudPack { recipe, .. } => {
+                recipe.inputs.iter().find(|path| !path.exists())
+            }
+            SavedTask::Extract { archive, .. } => Some(archive).filter(|path| !path.exists()),
+            SavedTask::Sync { source_dir, .. } => Some(source_dir).filter(|path| !path.exists()),
+            SavedTask::CloudExtract { .. } => None,
+        };
+        if let Some(path) = missing {
+            warn!("Not restarting {}: {:?} is missing", label, path);
+            self.toasts.error(t!(
+                "toast-session-input-missing",
+                task = label,
+                path = path.display()
+            ));
+            return false;
+        }
+
+        let command = match task.clone() {
+            SavedTask::Pack { output, recipe } => {
+                let Some(options) = self.recipe_options(&recipe) else {
+                    return false;
+                };
+                TaskCommand::Pack {
+                    inputs: recipe.inputs,
+                    output,
+                    options,
+                    control: self.task_control(),
+                }
+            }
+            SavedTask::CloudPack { url, recipe } => {
+                let Some(options) = self.recipe_options(&recipe) else {
+                    return false;
+                };
+                TaskCommand::CloudPack {
+                    inputs: recipe.inputs,
+                    url,
+                    options,
+                    control: self.task_control(),
+                }
+            }
+            SavedTask::Extract {
+                archive,
+                output_dir,
+                entries,
+                hoist,
+            } => TaskCommand::Extract {
+                archive,
+                output_dir,
+                entries,
+                hoist,
+                control: self.task_control(),
+            },
+            SavedTask::CloudExtract { url, output_dir } => TaskCommand::CloudExtract {
+                url,
+                output_dir,
+                control: self.task_control(),
+            },
+            SavedTask::Sync {
+                source_dir,
+                target_archive,
+                old_manifest,
+            } => {
+                let file_name = target_archive
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("");
+                TaskCommand::Sync {
+                    options: self.settings.sync_options(file_name),
+                    source_dir,
+                    target_archive,
+                    old_manifest,
+                    control: self.task_control(),
+                }
+            }
+        };
+
+        info!("Restarting interrupted task: {}", label);
+        let task_id = self.submit_task(command);
+        match &task {
+            SavedTask::Pack { output, recipe } => self.history.record_on_success(
+                task_id,
+                output.clone(),
+                RecentKind::Created,
+                Some(recipe.clone()),
+            ),
+            SavedTask::Extract {
+                output_dir,
+                entries: None,
+                ..
+            } => self.history.record_on_success(
+                task_id,
+                output_dir.clone(),
+                RecentKind::Destination,
+                None,
+            ),
+            _ => {}
         }
+        self.restartable.insert(task_id, task);
+        true
     }
 
     /// Reset to welcome view
+    #[allow(dead_code)]
     pub(super) fn reset_to_welcome(&mut self) {
         debug!("Resetting to welcome view");
         self.view = AppView::Welcome;
         self.input_files.clear();
         self.output_path = None;
-        self.current_progress = 0.0;
-        self.status_text = "Ready".to_string();
-        self.cancel_flag = None;
+    }
+
+    /// Cloud URL the next pack uploads to, named after the first input
+    ///
+    /// With smart packing the extension is only known once the inputs have
+    /// been analyzed; until then the format value itself is shown.
+    pub(super) fn cloud_pack_url(&self) -> Option<String> {
+        let prefix = self.cloud_pack_destination.as_ref()?;
+        let format = self
+            .pack_job
+            .effective_format(&self.compression_format)
+            .unwrap_or(&self.compression_format);
+        let stem = self
+            .input_files
+            .first()
+            .and_then(|path| path.file_stem())
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "archive".to_string());
+        Some(format!(
+            "{}/{}.{}",
+            prefix.trim_end_matches('/'),
+            stem,
+            format
+        ))
     }
 
     /// Start the task based on current view and inputs
     pub(super) fn start_task(&mut self) {
         match self.view {
             AppView::Packing => {
-                if let Some(output) = &self.output_path {
+                let options = match self.pack_job.pack_options(&self.compression_format) {
+                    Ok(options) => options,
+                    Err(e) => {
+                        self.toasts.warning(e);
+                        return;
+                    }
+                };
+
+                if let Some(url) = self.cloud_pack_url() {
+                    let recipe = self.pack_recipe(&options);
+                    let command = TaskCommand::CloudPack {
+                        inputs: self.input_files.clone(),
+                        url,
+                        options,
+                        control: TaskControl::with_limits(self.pack_job.limits.clone()),
+                    };
+                    self.submit_restartable(command, Some(recipe));
+                } else if let Some(mut output) = self.output_path.clone() {
+                    // Smart packing may pick a different format than the
+                    // output was named for
+                    if self.compression_format == SMART_FORMAT {
+                        if let Some(format) =
+                            self.pack_job.effective_format(&self.compression_format)
+                        {
+                            let renamed = with_format(&output, format);
+                            if renamed != output {
+                                info!("Renaming output to {:?} to match {}", renamed, format);
+                                output = renamed;
+                                self.output_path = Some(output.clone());
+                            }
+                        }
+                    }
+
                     // Validate output path
                     if let Some(parent) = output.parent() {
                         if !parent.exists() {
                             warn!("Output directory does not exist: {:?}", parent);
-                            self.toasts.error("Output directory does not exist");
+                            self.toasts.error(t!("toast-output-dir-missing"));
                             return;
                         }
                     }
 
-                    // Determine the algorithm based on the selected compression format
-                    let algorithm = match self.compression_format.as_str() {
-                        "tar.gz" => Some("gz".to_string()),
-                        "tar.zst" => Some("zst".to_string()),
-                        "tar.xz" => Some("xz".to_string()),
-                        "zip" => Some("zip".to_string()),
-                        _ => None,
-                    };
-
-                    let options = flux_core::archive::PackOptions {
-                        smart: false, // Disable smart mode since user explicitly selected format
-                        algorithm,
-                        level: None,
-                        threads: None,
-                        force_compress: false,
-                        follow_symlinks: false,
-                    };
-
-                    // Create cancel flag
-                    let cancel_flag = Arc::new(AtomicBool::new(false));
-                    self.cancel_flag = Some(cancel_flag.clone());
+                    if self.needs_overwrite_confirmation(&output) {
+                        self.pending_overwrite = Some(output);
+                        return;
+                    }
 
+                    let recipe = self.pack_recipe(&options);
                     let command = TaskCommand::Pack {
                         inputs: self.input_files.clone(),
                         output: output.clone(),
                         options,
-                        cancel_flag,
+                        control: TaskControl::with_limits(self.pack_job.limits.clone()),
                     };
-
-                    if self.task_sender.send(command).is_ok() {
-                        self.is_busy = true;
-                        self.current_progress = 0.0;
-                        self.status_text = "Starting pack operation...".to_string();
-                        info!("Starting pack operation");
-                        self.toasts.info("Starting to create archive...");
-                    } else {
-                        warn!("Failed to send pack command to background thread");
-                        self.toasts
-                            .error("Failed to start task: background thread not responding");
-                    }
+                    let task_id = self.submit_restartable(command, Some(recipe.clone()));
+                    self.history.record_on_success(
+                        task_id,
+                        output,
+                        RecentKind::Created,
+                        Some(recipe),
+                    );
                 } else {
                     warn!("No output path selected");
-                    self.toasts.error("Please select an output path first");
+                    self.toasts.error(t!("toast-select-output"));
                 }
             }
+            AppView::Extracting if self.input_files.len() > 1 => self.extract_all(),
             AppView::Extracting => {
                 if let (Some(archive), Some(output_dir)) =
                     (self.input_files.first(), &self.output_path)
@@ -165,43 +798,26 @@ impl FluxApp {
                     // Validate archive exists
                     if !archive.exists() {
                         warn!("Archive file not found: {:?}", archive);
-                        self.toasts.error("Archive file not found");
+                        self.toasts.error(t!("toast-archive-missing"));
                         return;
                     }
 
                     // Validate output directory exists
                     if !output_dir.exists() {
                         warn!("Output directory does not exist: {:?}", output_dir);
-                        self.toasts.error("Output directory does not exist");
+                        self.toasts.error(t!("toast-output-dir-missing"));
                         return;
                     }
 
-                    // Create cancel flag
-                    let cancel_flag = Arc::new(AtomicBool::new(false));
-                    self.cancel_flag = Some(cancel_flag.clone());
-
-                    let command = TaskCommand::Extract {
-                        archive: archive.clone(),
-                        output_dir: output_dir.clone(),
-                        hoist: self.extract_hoist,
-                        cancel_flag,
-                    };
-
-                    if self.task_sender.send(command).is_ok() {
-                        self.is_busy = true;
-                        self.current_progress = 0.0;
-                        self.status_text = "Starting extraction...".to_string();
-                        info!("Starting extraction operation");
-                        self.toasts.info("Starting extraction...");
-                    } else {
-                        warn!("Failed to send extract command to background thread");
-                        self.toasts
-                            .error("Failed to start task: background thread not responding");
+                    if self.needs_overwrite_confirmation(output_dir) {
+                        self.pending_overwrite = Some(output_dir.clone());
+                        return;
                     }
+
+                    self.submit_extract(archive.clone(), output_dir.clone(), self.extract_hoist);
                 } else {
                     warn!("Missing archive or output directory");
-                    self.toasts
-                        .error("Please select an archive and output directory first");
+                    self.toasts.error(t!("toast-select-archive-and-output"));
                 }
             }
             AppView::Welcome => {}
@@ -209,10 +825,53 @@ impl FluxApp {
                 // Should use start_sync_task instead
                 warn!("start_task called in Syncing view, use start_sync_task instead");
             }
-            AppView::Browsing => {
-                // Browser view doesn't use start_task
-                warn!("start_task called in Browsing view");
+            // The browser only starts tasks here for archives in cloud storage
+            AppView::Cloud | AppView::Browsing => {
+                if let (Some(url), Some(output_dir)) = (&self.cloud_extract_url, &self.output_path)
+                {
+                    if self.needs_overwrite_confirmation(output_dir) {
+                        self.pending_overwrite = Some(output_dir.clone());
+                        return;
+                    }
+
+                    let command = TaskCommand::CloudExtract {
+                        url: url.clone(),
+                        output_dir: output_dir.clone(),
+                        control: self.task_control(),
+                    };
+                    self.cloud_extract_url = None;
+                    self.submit_restartable(command, None);
+                } else if self.view == AppView::Browsing {
+                    warn!("start_task called in Browsing view");
+                }
             }
+            AppView::Schedules | AppView::Settings => {}
+        }
+    }
+
+    /// Start the task whose overwrite the user just confirmed
+    pub(super) fn confirm_overwrite(&mut self) {
+        self.pending_overwrite = None;
+        self.overwrite_confirmed = true;
+        self.start_task();
+        self.overwrite_confirmed = false;
+    }
+
+    /// Check whether starting a task would replace existing files without
+    /// the user having agreed to it
+    ///
+    /// For archives this means the file exists; for extraction it means the
+    /// output directory already has contents.
+    fn needs_overwrite_confirmation(&self, path: &Path) -> bool {
+        if !self.settings.confirm_overwrite || self.overwrite_confirmed {
+            return false;
+        }
+        if path.is_dir() {
+            std::fs::read_dir(path)
+                .map(|mut entries| entries.next().is_some())
+                .unwrap_or(false)
+        } else {
+            path.exists()
         }
     }
 
@@ -224,7 +883,7 @@ impl FluxApp {
             // Validate source directory exists
             if !source_dir.exists() {
                 warn!("Source directory does not exist: {:?}", source_dir);
-                self.toasts.error("Source directory does not exist");
+                self.toasts.error(t!("toast-source-dir-missing"));
                 return;
             }
 
@@ -232,7 +891,7 @@ impl FluxApp {
             if let Some(parent) = target_archive.parent() {
                 if !parent.exists() {
                     warn!("Target directory does not exist: {:?}", parent);
-                    self.toasts.error("Target directory does not exist");
+                    self.toasts.error(t!("toast-target-dir-missing"));
                     return;
                 }
             }
@@ -242,96 +901,172 @@ impl FluxApp {
                 .file_name()
                 .and_then(|n| n.to_str())
                 .unwrap_or("");
+            let options = self.settings.sync_options(filename);
 
-            let algorithm = if filename.ends_with(".tar.gz") {
-                Some("gz".to_string())
-            } else if filename.ends_with(".tar.zst") {
-                Some("zst".to_string())
-            } else if filename.ends_with(".tar.xz") {
-                Some("xz".to_string())
+            let task_type = if self.sync_manifest_path.is_some() {
+                "incremental backup"
             } else {
-                None
+                "full backup"
             };
-
-            let options = flux_core::archive::PackOptions {
-                smart: false,
-                algorithm,
-                level: Some(6), // Default compression level
-                threads: None,
-                force_compress: false,
-                follow_symlinks: false,
-            };
-
-            // Create cancel flag
-            let cancel_flag = Arc::new(AtomicBool::new(false));
-            self.cancel_flag = Some(cancel_flag.clone());
+            info!("Queueing sync operation: {}", task_type);
 
             let command = TaskCommand::Sync {
                 source_dir: source_dir.clone(),
                 target_archive: target_archive.clone(),
                 old_manifest: self.sync_manifest_path.clone(),
                 options,
-                cancel_flag,
+                control: self.task_control(),
             };
+            self.submit_restartable(command, None);
+        } else {
+            warn!("Missing source directory or target archive");
+            self.toasts.error(t!("toast-select-source-and-target"));
+        }
+    }
 
-            if self.task_sender.send(command).is_ok() {
-                self.is_busy = true;
-                self.current_progress = 0.0;
+    /// Work out what syncing the chosen directory would back up
+    pub(super) fn start_sync_preview(&mut self) {
+        let (Some(source_dir), Some(manifest)) = (&self.sync_source_dir, &self.sync_manifest_path)
+        else {
+            return;
+        };
+        let filename = self
+            .sync_target_archive
+            .as_ref()
+            .and_then(|target| target.file_name())
+            .and_then(|name| name.to_str())
+            .unwrap_or("");
+        // The same filter the sync would use
+        let filter = self.settings.sync_options(filename).filter;
+        info!("Previewing sync of {:?} against {:?}", source_dir, manifest);
+        self.sync_preview = Some(SyncPreview::start(
+            source_dir.clone(),
+            manifest.clone(),
+            filter,
+        ));
+    }
 
-                let task_type = if self.sync_manifest_path.is_some() {
-                    "incremental backup"
-                } else {
-                    "full backup"
-                };
+    /// Read the snapshots recorded for the sync target again
+    pub(super) fn refresh_snapshots(&mut self) {
+        self.snapshot_browser = self
+            .sync_target_archive
+            .as_deref()
+            .map(SnapshotBrowser::start);
+    }
 
-                self.status_text = format!("Starting {}...", task_type);
-                info!("Starting sync operation: {}", task_type);
-                self.toasts.info(format!("Starting {}...", task_type));
-            } else {
-                warn!("Failed to send sync command to background thread");
-                self.toasts
-                    .error("Failed to start task: background thread not responding");
+    /// Carry out what was asked for in the snapshot list
+    pub(super) fn handle_snapshot_action(&mut self, action: SnapshotAction) {
+        let Some(browser) = &mut self.snapshot_browser else {
+            return;
+        };
+        match action {
+            SnapshotAction::Open(sequence) => {
+                if let Err(e) = browser.open(sequence) {
+                    self.toasts.error(t!("snapshot-load-failed", error = e));
+                }
+            }
+            SnapshotAction::Close => browser.close(),
+            SnapshotAction::Restore { sequence, paths } => {
+                if let Some(dest) = rfd::FileDialog::new().pick_folder() {
+                    info!(
+                        "Restoring {} paths of snapshot {} to {:?}",
+                        paths.len(),
+                        sequence,
+                        dest
+                    );
+                    browser.restore(sequence, paths, dest);
+                }
             }
-        } else {
-            warn!("Missing source directory or target archive");
-            self.toasts
-                .error("Please select source directory and target archive first");
         }
     }
 
-    /// Open the archive browser for a given archive file
-    pub(super) fn open_archive_browser(&mut self, archive_path: PathBuf) -> Result<(), String> {
-        use flux_core::archive;
+    /// Queue a sync for schedule `id`
+    ///
+    /// Like the sync view, a manifest next to the archive makes the run
+    /// incremental.
+    pub(super) fn run_schedule(&mut self, id: u64) {
+        let Some(schedule) = self.scheduler.schedules().iter().find(|s| s.id == id) else {
+            return;
+        };
+        info!("Running schedule: {}", schedule.name);
 
-        // Create an extractor for the archive
-        let extractor = archive::create_extractor(&archive_path)
-            .map_err(|e| format!("Failed to open archive: {}", e))?;
+        let target_archive = schedule.target_archive.clone();
+        let manifest = target_archive.with_extension("manifest.json");
+        let file_name = target_archive
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("");
 
-        // Get all entries from the archive
-        let entries_iter = extractor
-            .entries(&archive_path)
-            .map_err(|e| format!("Failed to read archive entries: {}", e))?;
+        let command = TaskCommand::Sync {
+            source_dir: schedule.source_dir.clone(),
+            options: self.settings.sync_options(file_name),
+            old_manifest: manifest.exists().then_some(manifest),
+            target_archive,
+            control: self.task_control(),
+        };
+        let task_id = self.submit_task(command);
+        self.scheduler.start_run(id, task_id, Utc::now());
+    }
 
-        // Collect entries into a vector
-        let mut entries = Vec::new();
-        for entry_result in entries_iter {
-            match entry_result {
-                Ok(entry) => entries.push(entry),
-                Err(e) => warn!("Failed to read entry: {}", e),
-            }
+    /// Queue every enabled schedule that has come due
+    pub(super) fn run_due_schedules(&mut self) {
+        for id in self.scheduler.due(Utc::now()) {
+            self.run_schedule(id);
         }
+    }
+
+    /// Create a schedule from the schedules view form
+    pub(super) fn add_schedule(&mut self) {
+        let form = &mut self.schedule_form;
+        let (Some(source_dir), Some(target_archive)) =
+            (form.source_dir.take(), form.target_archive.take())
+        else {
+            self.toasts.error(t!("toast-select-source-and-target"));
+            return;
+        };
 
-        // Create browser state
-        let browser_state = BrowserState::new(archive_path.clone(), entries);
+        let name = match form.name.trim() {
+            "" => source_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| source_dir.display().to_string()),
+            name => name.to_string(),
+        };
+        let cadence = form.cadence;
+        form.name.clear();
+
+        info!("Adding {} schedule: {}", cadence.label(), name);
+        self.toasts.success(t!("toast-scheduled", name = name));
+        self.scheduler
+            .add(name, source_dir, target_archive, cadence);
+    }
+
+    /// Open the archive browser for a given archive file
+    ///
+    /// The browser opens empty and fills in as [`IndexLoader`] reads the
+    /// entries; see [`Self::poll_index_loader`].
+    pub(super) fn open_archive_browser(&mut self, archive_path: PathBuf) -> Result<(), String> {
+        // Fail early on files no extractor can read
+        let archive = flux_core::archive::Archive::open(&archive_path)
+            .map_err(|e| t!("toast-open-archive-failed", error = e))?;
+
+        self.index_loader = Some(IndexLoader::start(archive_path.clone()));
+        self.history
+            .record(archive_path.clone(), RecentKind::Opened, None);
 
         // Switch to browser view
         self.view = AppView::Browsing;
+        let mut browser_state = BrowserState::loading(archive_path.clone());
+        browser_state.archive = Some(Arc::new(archive));
+        // Entries of 7z archives cannot be changed in place
+        browser_state.editable =
+            flux_core::archive::detect_format(&archive_path).is_ok_and(|format| format != "7z");
         self.browser_state = Some(browser_state);
 
         info!("Opened archive browser for: {:?}", archive_path);
-        self.toasts.info(format!(
-            "Browsing: {}",
-            archive_path
+        self.toasts.info(t!(
+            "toast-browsing",
+            name = archive_path
                 .file_name()
                 .and_then(|n| n.to_str())
                 .unwrap_or("archive")
@@ -340,6 +1075,124 @@ impl FluxApp {
         Ok(())
     }
 
+    /// Hand the entries read so far to the browser
+    pub(super) fn poll_index_loader(&mut self) {
+        let Some(loader) = &self.index_loader else {
+            return;
+        };
+        // The browser was closed, or moved on to another archive
+        let Some(browser) = self
+            .browser_state
+            .as_mut()
+            .filter(|browser| !browser.is_remote() && browser.archive_path == loader.archive())
+        else {
+            self.index_loader = None;
+            return;
+        };
+
+        for event in loader.poll() {
+            match event {
+                IndexEvent::Entries(entries) => browser.extend(entries),
+                IndexEvent::Done { cached } => {
+                    info!(
+                        "Read {} entries of {:?}{}",
+                        loader.found(),
+                        browser.archive_path,
+                        if cached { " from the index cache" } else { "" }
+                    );
+                    browser.loading = false;
+                    self.index_loader = None;
+                    return;
+                }
+                IndexEvent::Failed(e) => {
+                    warn!("Failed to read {:?}: {}", browser.archive_path, e);
+                    browser.loading = false;
+                    self.toasts.error(e);
+                    self.index_loader = None;
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Reopen the browser once the changes made in edit mode are saved
+    pub(super) fn poll_browser_save(&mut self) {
+        let Some(browser) = &mut self.browser_state else {
+            return;
+        };
+        let Some(result) = browser.poll_save() else {
+            return;
+        };
+        let archive_path = browser.archive_path.clone();
+
+        match result {
+            Ok(changed) => {
+                info!("Changed {} entries of {:?}", changed, archive_path);
+                self.toasts
+                    .success(t!("toast-archive-saved", count = changed));
+                if let Err(e) = self.open_archive_browser(archive_path) {
+                    self.toasts.error(e);
+                    self.view = AppView::Welcome;
+                    self.browser_state = None;
+                }
+            }
+            Err(e) => {
+                warn!("Failed to change entries of {:?}: {}", archive_path, e);
+                self.toasts
+                    .error(t!("toast-archive-save-failed", error = e));
+            }
+        }
+    }
+
+    /// Start reading the cloud archive at `url` for the browser
+    pub(super) fn open_cloud_archive(&mut self, url: &str) {
+        match RemoteListing::start(url) {
+            Ok(listing) => {
+                info!("Reading cloud archive: {}", listing.url());
+                self.remote_listing = Some(listing);
+            }
+            Err(e) => {
+                self.toasts.error(e);
+            }
+        }
+    }
+
+    /// Open the browser once a cloud archive has been read
+    pub(super) fn poll_remote_listing(&mut self) {
+        let Some(result) = self.remote_listing.as_ref().and_then(RemoteListing::poll) else {
+            return;
+        };
+        let Some(listing) = self.remote_listing.take() else {
+            return;
+        };
+
+        match result {
+            Ok(entries) => {
+                let url = listing.url().to_string();
+                info!(
+                    "Opened cloud archive {} with {} entries",
+                    url,
+                    entries.len()
+                );
+                let browser_state = BrowserState::new_remote(url, entries);
+                self.toasts.info(t!(
+                    "toast-browsing",
+                    name = browser_state
+                        .archive_path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("archive")
+                ));
+                self.browser_state = Some(browser_state);
+                self.view = AppView::Browsing;
+            }
+            Err(e) => {
+                warn!("Failed to read cloud archive {}: {}", listing.url(), e);
+                self.toasts.error(e);
+            }
+        }
+    }
+
     /// Extract selected entries from an archive
     pub(super) fn extract_selected_entries(
         &mut self,
@@ -347,32 +1200,143 @@ impl FluxApp {
         archive_path: PathBuf,
         output_dir: PathBuf,
     ) {
-        self.toasts
-            .info(format!("Extracting {} selected items...", entries.len()));
+        info!(
+            "Extracting {} selected entries from {:?}",
+            entries.len(),
+            archive_path
+        );
+        let command = TaskCommand::Extract {
+            archive: archive_path,
+            output_dir,
+            entries: Some(entries),
+            hoist: false,
+            control: self.task_control(),
+        };
+        self.submit_restartable(command, None);
+    }
+
+    /// Extract entries dragged out of the browser into a staging directory
+    /// and copy them to the clipboard once they are on disk
+    ///
+    /// winit cannot start an OS drag, so the user pastes the files into the
+    /// file manager instead of dropping them there.
+    pub(super) fn copy_entries_to_clipboard(
+        &mut self,
+        entries: Vec<flux_core::archive::extractor::ArchiveEntry>,
+        archive_path: PathBuf,
+    ) {
+        let staging = match flux_core::space::TempResource::new() {
+            Ok(dir) => dir,
+            Err(e) => {
+                warn!("Failed to create staging directory: {}", e);
+                self.toasts.error(t!("toast-staging-failed", error = e));
+                return;
+            }
+        };
 
-        // Store the paths of selected entries
-        let entry_count = entries.len();
-        let entry_names: Vec<String> = entries
+        // Only hand over the outermost items; their children come along
+        let copied: Vec<&Path> = entries.iter().map(|e| e.path.as_path()).collect();
+        let paths = copied
             .iter()
-            .map(|e| e.path.file_name()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string())
+            .filter(|path| {
+                !copied
+                    .iter()
+                    .any(|other| other != *path && path.starts_with(other))
+            })
+            .map(|path| staging.path().join(path))
             .collect();
 
-        // For now, show a detailed message about what would be extracted
-        let message = if entry_count <= 3 {
-            format!("Would extract: {}", entry_names.join(", "))
-        } else {
-            format!("Would extract {} items including: {}, ...", 
-                entry_count, 
-                entry_names.iter().take(3).cloned().collect::<Vec<_>>().join(", "))
+        let command = TaskCommand::Extract {
+            archive: archive_path,
+            output_dir: staging.path().to_path_buf(),
+            entries: Some(entries),
+            hoist: false,
+            control: self.task_control(),
+        };
+        let task_id = self.submit_task(command);
+
+        self.clipboard_copies.push(ClipboardCopy {
+            task_id,
+            staging,
+            paths,
+            on_clipboard: false,
+        });
+    }
+
+    /// Put the staged files of the copy extracted by `task_id`, if any, on
+    /// the clipboard
+    ///
+    /// The files of earlier copies are no longer on the clipboard then, so
+    /// their staging directories are removed.
+    pub(super) fn publish_clipboard_copy(&mut self, task_id: TaskId) {
+        let Some(index) = self
+            .clipboard_copies
+            .iter()
+            .position(|copy| copy.task_id == task_id)
+        else {
+            return;
+        };
+
+        if self.clipboard.is_none() {
+            match arboard::Clipboard::new() {
+                Ok(clipboard) => self.clipboard = Some(clipboard),
+                Err(e) => {
+                    warn!("Failed to open clipboard: {}", e);
+                    self.toasts.error(t!("toast-clipboard-failed", error = e));
+                    self.clipboard_copies.remove(index);
+                    return;
+                }
+            }
+        }
+        let Some(clipboard) = &mut self.clipboard else {
+            return;
         };
-        
-        self.toasts.info(message);
-        self.toasts.warning("Partial extraction feature is coming soon!");
-        
-        // TODO: Implement partial extraction in flux-core
-        // This requires extending the extractor API to support extracting specific entries
+
+        let copy = &mut self.clipboard_copies[index];
+        match clipboard.set().file_list(&copy.paths) {
+            Ok(()) => {
+                info!(
+                    "Copied {} items from {:?} to the clipboard",
+                    copy.paths.len(),
+                    copy.staging.path()
+                );
+                copy.on_clipboard = true;
+                self.toasts
+                    .success(t!("toast-copied-to-clipboard", count = copy.paths.len()));
+                // Copies still being extracted are published when they finish
+                self.clipboard_copies
+                    .retain(|copy| copy.task_id == task_id || !copy.on_clipboard);
+            }
+            Err(e) => {
+                warn!("Failed to copy files to clipboard: {}", e);
+                self.toasts
+                    .error(t!("toast-clipboard-copy-failed", error = e));
+                self.clipboard_copies.remove(index);
+            }
+        }
+    }
+
+    /// Leave the staging directory whose files are still on the clipboard
+    /// in place when the app closes, so they can be pasted after it
+    ///
+    /// The next scratch directory flux creates removes it, as one left by
+    /// a process that is gone.
+    pub(super) fn keep_clipboard_copies(&mut self) {
+        let listed = self
+            .clipboard
+            .as_mut()
+            .and_then(|clipboard| clipboard.get().file_list().ok())
+            .unwrap_or_default();
+        for copy in self.clipboard_copies.drain(..) {
+            let pasteable = copy.on_clipboard
+                && copy
+                    .paths
+                    .iter()
+                    .any(|path| listed.iter().any(|listed| listed == path));
+            if pasteable {
+                info!("Keeping {:?} for the clipboard", copy.staging.path());
+                std::mem::forget(copy.staging);
+            }
+        }
     }
 }
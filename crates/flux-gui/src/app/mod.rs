@@ -7,16 +7,22 @@ mod ui;
 pub use state::{AppView, FluxApp};
 
 use egui_notify::Toasts;
+use std::collections::{HashSet, VecDeque};
 use std::thread;
+use tracing::warn;
 
-use crate::task::{TaskCommand, ToUi};
+use crate::launch::LaunchRequest;
+use crate::log_store::LogStore;
+use crate::queue::{TaskQueue, MAX_WORKERS};
+use crate::task::{TaskCommand, TaskEvent, TaskId, TaskReporter, ToUi};
 
 impl FluxApp {
-    /// Create a new application instance
-    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+    /// Create a new application instance, acting on the paths it was
+    /// launched with
+    pub fn new(cc: &eframe::CreationContext<'_>, launch: Option<LaunchRequest>) -> Self {
         // Create channels for communication
-        let (task_sender, task_receiver) = crossbeam_channel::unbounded::<TaskCommand>();
-        let (ui_sender, ui_receiver) = crossbeam_channel::unbounded::<ToUi>();
+        let (task_sender, task_receiver) = crossbeam_channel::unbounded::<(TaskId, TaskCommand)>();
+        let (ui_sender, ui_receiver) = crossbeam_channel::unbounded::<TaskEvent>();
 
         // Create channel for log messages
         let (log_sender, log_receiver) = crossbeam_channel::unbounded::<(tracing::Level, String)>();
@@ -27,95 +33,53 @@ impl FluxApp {
         // Load persistent state
         let persistence = Self::load_persistence(cc.storage);
 
+        let settings = persistence.settings();
+        crate::i18n::set_language(settings.language);
+        crate::i18n::install_fonts(&cc.egui_ctx, settings.language);
+
         // Create theme based on saved preference
-        let theme = if persistence.dark_mode {
-            crate::theme::FluxTheme::dark()
+        let mode = if settings.dark_mode {
+            crate::theme::ThemeMode::Dark
         } else {
-            crate::theme::FluxTheme::light()
+            crate::theme::ThemeMode::Light
         };
+        let theme = crate::theme::FluxTheme::new(mode, settings.appearance.clone());
 
-        // Spawn background thread
-        let task_handle = thread::spawn(move || {
-            // Background thread main loop
-            loop {
-                match task_receiver.recv() {
-                    Ok(command) => match command {
-                        TaskCommand::Pack {
-                            inputs,
-                            output,
-                            options,
-                            cancel_flag,
-                        } => {
-                            crate::handle_pack_task(
-                                inputs,
-                                output,
-                                options,
-                                cancel_flag,
-                                &ui_sender,
-                            );
-                        }
-                        TaskCommand::Extract {
-                            archive,
-                            output_dir,
-                            hoist,
-                            cancel_flag,
-                        } => {
-                            crate::handle_extract_task(
-                                archive,
-                                output_dir,
-                                hoist,
-                                cancel_flag,
-                                &ui_sender,
-                            );
-                        }
-                        TaskCommand::Sync {
-                            source_dir,
-                            target_archive,
-                            old_manifest,
-                            options,
-                            cancel_flag,
-                        } => {
-                            crate::handle_sync_task(
-                                source_dir,
-                                target_archive,
-                                old_manifest,
-                                options,
-                                cancel_flag,
-                                &ui_sender,
-                            );
-                        }
-                    },
-                    Err(_) => {
-                        // Channel closed, exit thread
-                        break;
+        // Spawn the worker pool; the queue decides how many of them are busy
+        let task_handles = (0..MAX_WORKERS)
+            .map(|_| {
+                let task_receiver = task_receiver.clone();
+                let ui_sender = ui_sender.clone();
+                let throughput = persistence.throughput.clone();
+                thread::spawn(move || {
+                    // Worker main loop; exits once the channel is closed
+                    while let Ok((id, command)) = task_receiver.recv() {
+                        let reporter = TaskReporter::new(id, ui_sender.clone(), throughput.clone());
+                        run_task(command, &reporter);
                     }
-                }
-            }
-        });
+                })
+            })
+            .collect();
 
-        Self {
+        let mut app = Self {
             view: AppView::Welcome,
             task_sender,
             ui_receiver,
-            _task_handle: Some(task_handle),
-            current_progress: 0.0,
-            status_text: "Ready".to_string(),
-            current_file: String::new(),
-            processed_bytes: 0,
-            total_bytes: 0,
+            _task_handles: task_handles,
+            task_queue: TaskQueue::new(settings.max_concurrent_tasks),
+            show_task_panel: false,
+            show_task_history: false,
+            task_history: crate::task_history::TaskHistory::new(),
+            task_details: None,
             input_files: Vec::new(),
             output_path: None,
-            compression_format: persistence
-                .preferred_format
-                .unwrap_or_else(|| "tar.zst".to_string()),
-            is_busy: false,
+            compression_format: settings.default_format.clone(),
+            pack_job: crate::views::PackJobOptions::new(&settings),
+            preflight: crate::preflight::Preflight::new(),
             toasts: Toasts::default(),
-            cancel_flag: None,
-            logs: Vec::new(),
+            logs: LogStore::new(),
             show_log_panel: persistence.show_log_panel,
             log_receiver: Some(log_receiver),
-            current_speed_bps: 0.0,
-            eta_seconds: None,
             log_filter: String::new(),
             log_level_filter: None,
             error_details: None,
@@ -127,7 +91,142 @@ impl FluxApp {
             show_about_dialog: false,
             sidebar: crate::layout::Sidebar::default(),
             browser_state: None,
+            index_loader: None,
             extract_hoist: false,
+            batch_destination: crate::views::BatchDestination::default(),
+            cloud_browser: crate::views::CloudBrowserState::default(),
+            cloud_pack_destination: None,
+            cloud_extract_url: None,
+            archive_url_input: String::new(),
+            remote_listing: None,
+            settings,
+            pending_overwrite: None,
+            overwrite_confirmed: false,
+            password_prompts: VecDeque::new(),
+            access_denied: HashSet::new(),
+            access_denied_prompt: None,
+            clipboard_copies: Vec::new(),
+            clipboard: None,
+            scheduler: crate::scheduler::Scheduler::new(persistence.schedules),
+            schedule_form: crate::views::ScheduleForm::default(),
+            history: crate::history::History::new(persistence.recent),
+            shell_integrated: flux_core::shell_integration::is_installed(),
+            properties: None,
+            sync_preview: None,
+            snapshot_browser: None,
+            verification: None,
+            command_palette: None,
+            recording_shortcut: None,
+            restartable: crate::session::RestartableTasks::default(),
+            session_restore: None,
+            throughput: persistence.throughput.clone(),
+        };
+
+        // Paths the app was launched with replace the unstarted job, but
+        // interrupted tasks are still offered
+        let mut session = persistence.session;
+        if launch.is_some() {
+            session.draft = None;
+        }
+        if !session.is_empty() {
+            app.session_restore = Some(crate::views::SessionRestore::new(session));
+        }
+
+        if let Some(launch) = launch {
+            app.handle_launch(launch);
+        }
+        app
+    }
+}
+
+/// Run one command on the current worker thread, or on a thread of its own
+/// when it is to run in the background
+fn run_task(command: TaskCommand, reporter: &TaskReporter) {
+    if !command.control().limits().background {
+        run_command(command, reporter);
+        return;
+    }
+
+    // A lowered priority cannot always be raised again, so the task gets a
+    // thread that ends with it rather than lowering the worker for good
+    let reporter = reporter.clone();
+    let task = thread::spawn(move || {
+        let priority = flux_core::priority::Priority::Background;
+        if let Err(e) = flux_core::priority::lower_thread_priority(priority) {
+            warn!("Could not lower the priority of a background task: {}", e);
+            let _ = reporter.send(ToUi::Log(format!("Running at normal priority: {}", e)));
+        }
+        run_command(command, &reporter);
+    });
+    if task.join().is_err() {
+        warn!("Background task panicked");
+    }
+}
+
+/// Run one command on the current thread, with the limits of its control
+/// applied to its pack options
+fn run_command(mut command: TaskCommand, reporter: &TaskReporter) {
+    match &mut command {
+        TaskCommand::Pack {
+            options, control, ..
+        }
+        | TaskCommand::CloudPack {
+            options, control, ..
+        }
+        | TaskCommand::Sync {
+            options, control, ..
+        } => control.limits().apply(options),
+        TaskCommand::Extract { .. } | TaskCommand::CloudExtract { .. } => {}
+    }
+
+    match command {
+        TaskCommand::Pack {
+            inputs,
+            output,
+            options,
+            control,
+        } => {
+            crate::handle_pack_task(inputs, output, options, control, reporter);
+        }
+        TaskCommand::Extract {
+            archive,
+            output_dir,
+            entries,
+            hoist,
+            control,
+        } => {
+            crate::handle_extract_task(archive, output_dir, entries, hoist, control, reporter);
+        }
+        TaskCommand::CloudExtract {
+            url,
+            output_dir,
+            control,
+        } => {
+            crate::cloud::handle_cloud_extract_task(url, output_dir, control, reporter);
+        }
+        TaskCommand::CloudPack {
+            inputs,
+            url,
+            options,
+            control,
+        } => {
+            crate::cloud::handle_cloud_pack_task(inputs, url, options, control, reporter);
+        }
+        TaskCommand::Sync {
+            source_dir,
+            target_archive,
+            old_manifest,
+            options,
+            control,
+        } => {
+            crate::handle_sync_task(
+                source_dir,
+                target_archive,
+                old_manifest,
+                options,
+                control,
+                reporter,
+            );
         }
     }
 }
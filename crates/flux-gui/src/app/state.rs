@@ -3,16 +3,30 @@
 use crossbeam_channel::{Receiver, Sender};
 use egui_notify::Toasts;
 use std::{
+    collections::{HashSet, VecDeque},
     path::PathBuf,
-    sync::{atomic::AtomicBool, Arc},
     thread,
 };
 
+use crate::archive_index::IndexLoader;
+use crate::cloud::RemoteListing;
+use crate::history::{History, RecentItem};
 use crate::layout::Sidebar;
-use crate::task::TaskCommand;
-use crate::task::ToUi;
+use crate::log_store::LogStore;
+use crate::preflight::Preflight;
+use crate::progress_tracker::ThroughputHistory;
+use crate::queue::TaskQueue;
+use crate::scheduler::{Schedule, Scheduler};
+use crate::session::{RestartableTasks, SavedTask, Session};
+use crate::settings::Settings;
+use crate::shortcuts::Command;
+use crate::task::{PasswordRequest, TaskCommand, TaskEvent, TaskId};
+use crate::task_history::TaskHistory;
 use crate::theme::FluxTheme;
-use crate::views::BrowserState;
+use crate::views::{
+    ArchiveProperties, ArchiveVerification, BatchDestination, BrowserState, CloudBrowserState,
+    CommandPalette, PackJobOptions, ScheduleForm, SessionRestore, SnapshotBrowser, SyncPreview,
+};
 use serde::{Deserialize, Serialize};
 
 /// Application view states
@@ -28,50 +42,84 @@ pub enum AppView {
     Syncing,
     /// Browsing archive contents
     Browsing,
+    /// Browsing cloud storage
+    Cloud,
+    /// Recurring backups
+    Schedules,
+    /// Application settings
+    Settings,
+}
+
+/// Entries of the archive browser copied to the clipboard as files,
+/// extracted to a staging directory first
+pub struct ClipboardCopy {
+    /// Extraction task filling the staging directory
+    pub task_id: TaskId,
+    /// Staging directory, kept while the clipboard may list its files
+    pub staging: flux_core::space::TempResource,
+    /// Top-level items the user copied, inside `staging`
+    pub paths: Vec<PathBuf>,
+    /// Whether the files were put on the clipboard
+    pub on_clipboard: bool,
+}
+
+/// A worker waiting for the user to enter a password
+pub struct PasswordPrompt {
+    /// Task that asked for the password
+    pub task_id: TaskId,
+    /// The request from the worker, including the reply channel
+    pub request: PasswordRequest,
+    /// Text typed into the dialog so far
+    pub input: String,
+}
+
+/// An extraction the destination refused, waiting for the user to retry it
+/// elevated, elsewhere, or not at all
+pub struct AccessDeniedPrompt {
+    /// The extraction as submitted
+    pub task: SavedTask,
+    /// Error the worker reported
+    pub error: String,
 }
 
 /// Main application structure
 pub struct FluxApp {
     /// Current view
     pub(super) view: AppView,
-    /// Sender for commands to background thread
-    pub(super) task_sender: Sender<TaskCommand>,
-    /// Receiver for messages from background thread
-    pub(super) ui_receiver: Receiver<ToUi>,
-    /// Handle to the background thread
-    pub(super) _task_handle: Option<thread::JoinHandle<()>>,
-    /// Current progress (0.0 to 1.0)
-    pub(super) current_progress: f32,
-    /// Status text to display
-    pub(super) status_text: String,
-    /// Current file being processed
-    pub(super) current_file: String,
-    /// Bytes processed
-    pub(super) processed_bytes: u64,
-    /// Total bytes to process
-    pub(super) total_bytes: u64,
+    /// Sender for commands to the worker pool
+    pub(super) task_sender: Sender<(TaskId, TaskCommand)>,
+    /// Receiver for messages from the worker pool
+    pub(super) ui_receiver: Receiver<TaskEvent>,
+    /// Handles to the worker threads
+    pub(super) _task_handles: Vec<thread::JoinHandle<()>>,
+    /// Submitted tasks and their progress
+    pub(super) task_queue: TaskQueue,
+    /// Show the task list panel
+    pub(super) show_task_panel: bool,
+    /// Show finished tasks in the task panel instead of the queue
+    pub(super) show_task_history: bool,
+    /// Logs and reports of finished tasks
+    pub(super) task_history: TaskHistory,
+    /// Finished task whose details are open
+    pub(super) task_details: Option<TaskId>,
     /// Files to process
     pub(super) input_files: Vec<PathBuf>,
     /// Output path
     pub(super) output_path: Option<PathBuf>,
     /// Selected compression format for packing
     pub(super) compression_format: String,
-    /// Is task running
-    pub(super) is_busy: bool,
+    /// Level, threads and other options for the archive being packed
+    pub(super) pack_job: PackJobOptions,
+    /// Destination checks of the job set up in the pack or extract view
+    pub(super) preflight: Preflight,
     /// Toast notifications
     pub(super) toasts: Toasts,
-    /// Cancel flag for current task
-    pub(super) cancel_flag: Option<Arc<AtomicBool>>,
     /// Log messages with level
-    pub(super) logs: Vec<(tracing::Level, String)>,
+    pub(super) logs: LogStore,
     /// Show log panel
     pub(super) show_log_panel: bool,
     /// Receiver for log messages from tracing
     pub(super) log_receiver: Option<Receiver<(tracing::Level, String)>>,
-    /// Current processing speed in bytes per second
-    pub(super) current_speed_bps: f64,
-    /// Estimated time remaining in seconds
-    pub(super) eta_seconds: Option<f64>,
     /// Log search filter
     pub(super) log_filter: String,
     /// Selected log level filter
@@ -94,8 +142,66 @@ pub struct FluxApp {
     pub(super) sidebar: Sidebar,
     /// Browser state when viewing archive contents
     pub(super) browser_state: Option<BrowserState>,
+    /// Entries of the local archive in the browser still being read
+    pub(super) index_loader: Option<IndexLoader>,
     /// Extract with directory hoisting enabled
     pub(super) extract_hoist: bool,
+    /// Where each archive goes when several are extracted at once
+    pub(super) batch_destination: BatchDestination,
+    /// Cloud storage browser
+    pub(super) cloud_browser: CloudBrowserState,
+    /// Cloud prefix to upload the next archive to instead of `output_path`
+    pub(super) cloud_pack_destination: Option<String>,
+    /// Cloud archive chosen for extraction from the cloud view or browser
+    pub(super) cloud_extract_url: Option<String>,
+    /// Text in the field for opening an archive by URL
+    pub(super) archive_url_input: String,
+    /// Cloud archive being read for the browser
+    pub(super) remote_listing: Option<RemoteListing>,
+    /// User preferences used as defaults for pack/extract/sync
+    pub(super) settings: Settings,
+    /// Path awaiting confirmation before it is overwritten
+    pub(super) pending_overwrite: Option<PathBuf>,
+    /// The user agreed to overwrite the current output path
+    pub(super) overwrite_confirmed: bool,
+    /// Password requests from workers, answered one at a time
+    pub(super) password_prompts: VecDeque<PasswordPrompt>,
+    /// Running extractions whose destination refused them
+    pub(super) access_denied: HashSet<TaskId>,
+    /// Refused extraction the user is asked what to do about
+    pub(super) access_denied_prompt: Option<AccessDeniedPrompt>,
+    /// Copies to the clipboard being extracted or whose files may still be
+    /// pasted
+    pub(super) clipboard_copies: Vec<ClipboardCopy>,
+    /// System clipboard, opened on first use and kept so its contents stay
+    /// available on platforms where the owner must stay alive
+    pub(super) clipboard: Option<arboard::Clipboard>,
+    /// Recurring sync jobs
+    pub(super) scheduler: Scheduler,
+    /// Schedule being set up in the schedules view
+    pub(super) schedule_form: ScheduleForm,
+    /// Recent archives and extraction destinations
+    pub(super) history: History,
+    /// Whether the file manager context menu entries are installed
+    pub(super) shell_integrated: bool,
+    /// Open archive properties dialog
+    pub(super) properties: Option<ArchiveProperties>,
+    /// Changes the next sync would back up, shown in the sync view
+    pub(super) sync_preview: Option<SyncPreview>,
+    /// Snapshots recorded for the sync target
+    pub(super) snapshot_browser: Option<SnapshotBrowser>,
+    /// Running or finished archive verification
+    pub(super) verification: Option<ArchiveVerification>,
+    /// Open command palette
+    pub(super) command_palette: Option<CommandPalette>,
+    /// Command whose new shortcut is being recorded in Settings
+    pub(super) recording_shortcut: Option<Command>,
+    /// How to start the unfinished tasks again after a restart
+    pub(super) restartable: RestartableTasks,
+    /// Previous session waiting for the user to restore or discard it
+    pub(super) session_restore: Option<SessionRestore>,
+    /// Speeds of past tasks, shared with the workers
+    pub(super) throughput: ThroughputHistory,
 }
 
 /// Persistent application state
@@ -113,6 +219,37 @@ pub struct AppPersistence {
     pub dark_mode: bool,
     /// Last used output directory
     pub last_output_dir: Option<PathBuf>,
+    /// Settings edited in the Settings view
+    ///
+    /// Absent in state saved by older versions, which only stored
+    /// `preferred_format` and `dark_mode`.
+    pub settings: Option<Settings>,
+    /// Recurring sync jobs and their run history
+    #[serde(default)]
+    pub schedules: Vec<Schedule>,
+    /// Recent and pinned archives and destinations
+    #[serde(default)]
+    pub recent: Vec<RecentItem>,
+    /// Unstarted job and unfinished tasks from the last run
+    #[serde(default)]
+    pub session: Session,
+    /// Speed of each task phase, for the first ETA of the next task
+    #[serde(default)]
+    pub throughput: ThroughputHistory,
+}
+
+impl AppPersistence {
+    /// Settings from storage, falling back to the older individual fields
+    pub fn settings(&self) -> Settings {
+        self.settings.clone().unwrap_or_else(|| Settings {
+            default_format: self
+                .preferred_format
+                .clone()
+                .unwrap_or_else(|| Settings::default().default_format),
+            dark_mode: self.dark_mode,
+            ..Settings::default()
+        })
+    }
 }
 
 impl FluxApp {
@@ -141,6 +278,18 @@ impl FluxApp {
                 .output_path
                 .as_ref()
                 .and_then(|p| p.parent().map(|p| p.to_path_buf())),
+            settings: Some(Settings {
+                dark_mode: self.theme.is_dark_mode(),
+                ..self.settings.clone()
+            }),
+            schedules: self.scheduler.schedules().to_vec(),
+            recent: self.history.to_vec(),
+            // Keep an undecided previous session until the user answers
+            session: match &self.session_restore {
+                Some(restore) => restore.session.clone(),
+                None => self.session(),
+            },
+            throughput: self.throughput.clone(),
         };
 
         if let Ok(data) = serde_json::to_string(&persistence) {
@@ -1,19 +1,260 @@
 //! UI rendering and update logic for the Flux GUI application
 
+use chrono::Utc;
 use eframe::egui;
+use flux_core::archive::detect::FILE_EXTENSIONS;
 use std::time::SystemTime;
-use tracing::{info, Level};
+use tracing::{info, warn, Level};
 
+use super::state::{AccessDeniedPrompt, PasswordPrompt};
 use super::{AppView, FluxApp};
 use crate::components::{set_theme_in_context, DropZone, FluxButton};
+use crate::i18n::Language;
 use crate::layout::NavItem;
+use crate::log_store::log_dir;
+use crate::queue::{TaskKind, TaskStatus};
+use crate::session::SavedTask;
+use crate::settings::{CloudProfile, Settings, SMART_FORMAT};
+use crate::shortcuts::Command;
+use crate::t;
 use crate::task::{TaskResult, ToUi};
 use crate::views::{
-    draw_browser_view, draw_extracting_view, draw_packing_view_modern, draw_sync_view,
-    BrowserAction, ExtractingAction, PackingAction, SyncAction,
+    draw_archive_url_bar, draw_browser_view, draw_cloud_view, draw_command_palette,
+    draw_extracting_view, draw_packing_view_modern, draw_properties_dialog, draw_recent_list,
+    draw_schedules_view, draw_session_dialog, draw_settings_view, draw_snapshots, draw_sync_view,
+    draw_task_details_dialog, draw_task_history_view, draw_task_queue_view, draw_verify_dialog,
+    ArchiveProperties, ArchiveUrlAction, ArchiveVerification, BrowserAction, CloudAction,
+    ExtractingAction, PackJobOptions, PackingAction, PaletteAction, RecentAction, ScheduleAction,
+    SessionAction, SettingsAction, SnapshotBrowser, SyncAction, TaskHistoryAction, TaskQueueAction,
+    VerifyAction,
 };
 
 impl FluxApp {
+    /// Switch between light and dark theme, keeping the setting in sync
+    pub(super) fn toggle_theme(&mut self) {
+        self.theme.toggle();
+        self.settings.dark_mode = self.theme.is_dark_mode();
+    }
+
+    /// Show the interface in `language` from the next frame on
+    fn set_language(&mut self, ctx: &egui::Context, language: Language) {
+        if language.needs_cjk_font() != self.settings.language.needs_cjk_font() {
+            crate::i18n::install_fonts(ctx, language);
+        }
+        crate::i18n::set_language(language);
+        self.settings.language = language;
+    }
+
+    /// Ask for the password requested by the oldest waiting worker
+    fn draw_password_dialog(&mut self, ctx: &egui::Context) {
+        // Drop requests from tasks that were cancelled while waiting
+        let queue = &self.task_queue;
+        self.password_prompts.retain(|prompt| {
+            queue
+                .tasks()
+                .iter()
+                .any(|task| task.id == prompt.task_id && task.status.is_active())
+        });
+
+        let Some(prompt) = self.password_prompts.front_mut() else {
+            return;
+        };
+
+        let mut answer = None;
+
+        egui::Window::new(format!(
+            "{} {}",
+            egui_phosphor::regular::LOCK,
+            t!("password-title")
+        ))
+        .id(egui::Id::new("password_prompt"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(t!("password-encrypted-files"));
+            ui.add_space(5.0);
+            ui.monospace(prompt.request.archive.display().to_string());
+            ui.weak(prompt.request.entry.display().to_string());
+            ui.add_space(10.0);
+
+            if prompt.request.retry {
+                ui.colored_label(self.theme.colors.error, t!("password-incorrect"));
+            }
+
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut prompt.input)
+                    .password(true)
+                    .hint_text(t!("password-hint")),
+            );
+            response.request_focus();
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+            ui.add_space(10.0);
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let can_unlock = !prompt.input.is_empty();
+                if ui
+                    .add_enabled(can_unlock, FluxButton::new(t!("password-unlock")).primary())
+                    .clicked()
+                    || (submitted && can_unlock)
+                {
+                    answer = Some(Some(prompt.input.clone()));
+                }
+                if ui.button(t!("common-cancel")).clicked() {
+                    answer = Some(None);
+                }
+            });
+        });
+
+        if let Some(password) = answer {
+            if let Some(prompt) = self.password_prompts.pop_front() {
+                // The worker may have given up already; nothing to do then
+                let _ = prompt.request.reply.send(password);
+            }
+        }
+    }
+
+    /// Offer to retry an extraction its destination refused, elevated or
+    /// into a folder the user can write to
+    fn draw_access_denied_dialog(&mut self, ctx: &egui::Context) {
+        let Some(prompt) = &self.access_denied_prompt else {
+            return;
+        };
+        let SavedTask::Extract {
+            archive,
+            output_dir,
+            entries,
+            ..
+        } = &prompt.task
+        else {
+            self.access_denied_prompt = None;
+            return;
+        };
+        // Only whole archives can be handed to the elevated instance
+        let can_elevate = crate::elevation::can_elevate() && entries.is_none();
+        let fallback = crate::elevation::fallback_dir(output_dir);
+
+        let mut elevate = false;
+        let mut retry_in = None;
+        let mut close = false;
+
+        egui::Window::new(format!(
+            "{} {}",
+            egui_phosphor::regular::SHIELD_WARNING,
+            t!("access-denied-title")
+        ))
+        .id(egui::Id::new("access_denied"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label(t!("access-denied-message"));
+            ui.add_space(5.0);
+            ui.monospace(output_dir.display().to_string());
+            ui.weak(&prompt.error);
+            ui.add_space(10.0);
+
+            if can_elevate {
+                if ui
+                    .add(FluxButton::new(t!("access-denied-elevate")).primary())
+                    .clicked()
+                {
+                    elevate = true;
+                }
+                ui.weak(t!("access-denied-elevate-hint"));
+                ui.add_space(5.0);
+            }
+            if let Some(dir) = &fallback {
+                if ui
+                    .button(t!("access-denied-fallback", folder = dir.display()))
+                    .clicked()
+                {
+                    retry_in = Some(dir.clone());
+                }
+            }
+            if ui.button(t!("access-denied-choose")).clicked() {
+                retry_in = rfd::FileDialog::new().pick_folder();
+            }
+
+            ui.add_space(10.0);
+            ui.separator();
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button(t!("common-close")).clicked() {
+                    close = true;
+                }
+            });
+        });
+
+        if elevate {
+            match crate::elevation::relaunch_elevated(archive, output_dir) {
+                Ok(()) => {
+                    info!(archive = ?archive, "Relaunched elevated to extract");
+                    self.toasts.info(t!("access-denied-elevated"));
+                }
+                Err(e) => {
+                    warn!(error = %e, "Could not relaunch elevated");
+                    self.toasts
+                        .error(t!("access-denied-elevate-failed", error = e));
+                }
+            }
+            self.access_denied_prompt = None;
+        } else if let Some(dir) = retry_in {
+            if let Some(prompt) = self.access_denied_prompt.take() {
+                self.retry_extract_in(prompt.task, dir);
+            }
+        } else if close {
+            self.access_denied_prompt = None;
+        }
+    }
+
+    /// Act on the history list or the details of a finished task
+    fn handle_task_history_action(&mut self, ctx: &egui::Context, action: TaskHistoryAction) {
+        match action {
+            TaskHistoryAction::ShowDetails(id) => self.task_details = Some(id),
+            TaskHistoryAction::CloseDetails => self.task_details = None,
+            TaskHistoryAction::OpenOutput(id) => {
+                let dir = self
+                    .task_history
+                    .get(id)
+                    .and_then(|record| record.output_dir.clone());
+                if let Some(dir) = dir {
+                    if let Err(e) = crate::file_manager::open_folder(&dir) {
+                        warn!(error = %e, "Could not open {:?}", dir);
+                        self.toasts.error(t!("task-history-open-failed", error = e));
+                    }
+                }
+            }
+            TaskHistoryAction::RevealManifest(id) => {
+                let manifest = self
+                    .task_history
+                    .get(id)
+                    .and_then(|record| record.manifest.clone());
+                if let Some(manifest) = manifest {
+                    if let Err(e) = crate::file_manager::reveal(&manifest) {
+                        warn!(error = %e, "Could not show {:?}", manifest);
+                        self.toasts.error(t!("task-history-open-failed", error = e));
+                    }
+                }
+            }
+            TaskHistoryAction::CopyReport(id) => {
+                let json = self
+                    .task_history
+                    .get(id)
+                    .and_then(|record| record.report_json());
+                if let Some(json) = json {
+                    ctx.output_mut(|o| o.copied_text = json);
+                    self.toasts.info(t!("task-history-report-copied"));
+                }
+            }
+            TaskHistoryAction::Clear => {
+                self.task_history.clear();
+                self.task_details = None;
+            }
+        }
+    }
+
     /// Export logs to a file
     fn export_logs(&self, path: &std::path::Path) -> Result<(), std::io::Error> {
         use std::io::Write;
@@ -28,27 +269,63 @@ impl FluxApp {
         writeln!(file, "=")?;
         writeln!(file)?;
 
-        for (level, log) in &self.logs {
+        for (level, log) in self.logs.iter() {
             writeln!(file, "[{}] {}", level, log)?;
         }
 
         Ok(())
     }
 
+    /// Save the finished verification report where the user picks
+    fn export_verify_report(&mut self) {
+        let Some(verification) = &self.verification else {
+            return;
+        };
+        let Some(report) = verification.report() else {
+            return;
+        };
+        let name = verification
+            .path()
+            .file_name()
+            .map(|name| format!("{}.verify.txt", name.to_string_lossy()))
+            .unwrap_or_else(|| "verify.txt".to_string());
+
+        if let Some(path) = rfd::FileDialog::new()
+            .set_file_name(name)
+            .add_filter(t!("dialog-text-files"), &["txt"])
+            .save_file()
+        {
+            match std::fs::write(&path, report.to_string()) {
+                Ok(()) => self.toasts.success(t!("verify-exported")),
+                Err(e) => self.toasts.error(t!("verify-export-failed", error = e)),
+            };
+        }
+    }
+
+    /// Draw the field for opening an archive in cloud storage
+    fn draw_archive_url_field(&mut self, ui: &mut egui::Ui, width: f32) {
+        let reading = self.remote_listing.as_ref().map(|listing| listing.found());
+        match draw_archive_url_bar(ui, &mut self.archive_url_input, reading, width) {
+            Some(ArchiveUrlAction::Open(url)) => self.open_cloud_archive(&url),
+            Some(ArchiveUrlAction::Cancel) => self.remote_listing = None,
+            None => {}
+        }
+    }
+
     /// Draw the welcome view
-    pub(super) fn draw_welcome_view(&mut self, _ctx: &egui::Context, ui: &mut egui::Ui) {
+    pub(super) fn draw_welcome_view(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
         ui.vertical_centered(|ui| {
             ui.add_space(40.0);
 
             // Stylish header with gradient-like effect
             ui.heading(
-                egui::RichText::new("Flux Archive Manager")
+                egui::RichText::new(t!("welcome-title"))
                     .size(32.0)
                     .color(self.theme.colors.primary),
             );
             ui.add_space(10.0);
             ui.label(
-                egui::RichText::new("Modern, fast, and intelligent file compression")
+                egui::RichText::new(t!("welcome-subtitle"))
                     .size(16.0)
                     .color(self.theme.colors.text_weak),
             );
@@ -57,8 +334,8 @@ impl FluxApp {
             // Modern drop zone
             let drop_response = ui.add(
                 DropZone::new("main_drop")
-                    .text("Drop files or folders here")
-                    .subtext("or click to browse"),
+                    .text(t!("welcome-drop"))
+                    .subtext(t!("welcome-drop-browse")),
             );
 
             if drop_response.clicked() {
@@ -76,7 +353,7 @@ impl FluxApp {
                 // Create Archive button
                 if ui
                     .add(
-                        FluxButton::new("Create Archive")
+                        FluxButton::new(t!("welcome-create-archive"))
                             .primary()
                             .icon(egui_phosphor::regular::PACKAGE)
                             .min_size(egui::vec2(150.0, 40.0)),
@@ -93,14 +370,14 @@ impl FluxApp {
                 // Extract Archive button
                 if ui
                     .add(
-                        FluxButton::new("Extract Archive")
+                        FluxButton::new(t!("welcome-extract-archive"))
                             .icon(egui_phosphor::regular::FOLDER_OPEN)
                             .min_size(egui::vec2(150.0, 40.0)),
                     )
                     .clicked()
                 {
                     if let Some(file) = rfd::FileDialog::new()
-                        .add_filter("Archives", &["zip", "tar", "gz", "zst", "xz", "7z", "br"])
+                        .add_filter(t!("dialog-archives"), FILE_EXTENSIONS)
                         .pick_file()
                     {
                         self.analyze_dropped_files(vec![file]);
@@ -112,7 +389,7 @@ impl FluxApp {
                 // Incremental Backup button
                 if ui
                     .add(
-                        FluxButton::new("Incremental Sync")
+                        FluxButton::new(t!("welcome-incremental-sync"))
                             .icon(egui_phosphor::regular::ARROW_SQUARE_OUT)
                             .min_size(egui::vec2(150.0, 40.0)),
                     )
@@ -122,8 +399,46 @@ impl FluxApp {
                 }
             });
 
+            ui.add_space(20.0);
+
+            // Archive in cloud storage
+            ui.horizontal(|ui| {
+                ui.add_space((ui.available_width() - 530.0) / 2.0);
+                self.draw_archive_url_field(ui, 530.0);
+            });
+
             ui.add_space(40.0);
 
+            // Recent archives and destinations
+            if let Some(action) = draw_recent_list(ui, &self.history, &self.theme) {
+                match action {
+                    RecentAction::Reopen(path) => {
+                        if let Err(e) = self.open_archive_browser(path) {
+                            self.toasts.error(e);
+                        }
+                    }
+                    RecentAction::Rerun(path) => self.rerun_pack(&path),
+                    RecentAction::ExtractTo(dir) => {
+                        if let Some(file) = rfd::FileDialog::new()
+                            .add_filter(t!("dialog-archives"), FILE_EXTENSIONS)
+                            .pick_file()
+                        {
+                            self.input_files = vec![file];
+                            self.output_path = Some(dir);
+                            self.view = AppView::Extracting;
+                        }
+                    }
+                    RecentAction::TogglePin(path, kind) => self.history.toggle_pin(&path, kind),
+                    RecentAction::Remove(path, kind) => self.history.remove(&path, kind),
+                    RecentAction::Properties(path) => {
+                        self.properties = Some(ArchiveProperties::open(path));
+                    }
+                }
+            }
+            if !self.history.items().is_empty() {
+                ui.add_space(30.0);
+            }
+
             // Feature highlights
             egui::Frame::none()
                 .fill(self.theme.colors.panel_bg)
@@ -133,40 +448,46 @@ impl FluxApp {
                     ui.columns(3, |columns| {
                         columns[0].vertical_centered(|ui| {
                             ui.label(egui::RichText::new("⚡").size(32.0));
-                            ui.label(egui::RichText::new("Lightning Fast").size(16.0).strong());
+                            ui.label(
+                                egui::RichText::new(t!("welcome-feature-fast"))
+                                    .size(16.0)
+                                    .strong(),
+                            );
                             ui.add_space(5.0);
                             ui.label(
-                                egui::RichText::new(
-                                    "Multi-threaded compression\nwith real-time progress",
-                                )
-                                .size(12.0)
-                                .color(self.theme.colors.text_weak),
+                                egui::RichText::new(t!("welcome-feature-fast-desc"))
+                                    .size(12.0)
+                                    .color(self.theme.colors.text_weak),
                             );
                         });
 
                         columns[1].vertical_centered(|ui| {
                             ui.label(egui::RichText::new("🎯").size(32.0));
-                            ui.label(egui::RichText::new("Smart Selection").size(16.0).strong());
+                            ui.label(
+                                egui::RichText::new(t!("welcome-feature-smart"))
+                                    .size(16.0)
+                                    .strong(),
+                            );
                             ui.add_space(5.0);
                             ui.label(
-                                egui::RichText::new(
-                                    "Automatic format detection\nand optimal compression",
-                                )
-                                .size(12.0)
-                                .color(self.theme.colors.text_weak),
+                                egui::RichText::new(t!("welcome-feature-smart-desc"))
+                                    .size(12.0)
+                                    .color(self.theme.colors.text_weak),
                             );
                         });
 
                         columns[2].vertical_centered(|ui| {
                             ui.label(egui::RichText::new("🔒").size(32.0));
-                            ui.label(egui::RichText::new("Secure & Reliable").size(16.0).strong());
+                            ui.label(
+                                egui::RichText::new(t!("welcome-feature-secure"))
+                                    .size(16.0)
+                                    .strong(),
+                            );
                             ui.add_space(5.0);
                             ui.label(
-                                egui::RichText::new(
-                                    "Safe extraction with\npath traversal protection",
-                                )
-                                .size(12.0)
-                                .color(self.theme.colors.text_weak),
+                                egui::RichText::new(t!("welcome-feature-secure-desc"))
+                                    .size(12.0)
+                                    .color(self.theme.colors.text_weak),
                             );
                         });
                     });
@@ -180,7 +501,7 @@ impl FluxApp {
 
             ui.horizontal(|ui| {
                 ui.label(egui::RichText::new("💡").size(16.0));
-                ui.label(egui::RichText::new("Quick Tips:").size(14.0).strong());
+                ui.label(egui::RichText::new(t!("welcome-tips")).size(14.0).strong());
             });
 
             ui.add_space(5.0);
@@ -192,22 +513,29 @@ impl FluxApp {
                     .color(self.theme.colors.text_weak)
             };
             ui.indent("tips", |ui| {
-                ui.label(tip_style(
-                    "• Drag multiple files/folders to create a combined archive",
-                ));
-                ui.label(tip_style(
-                    "• Drop an archive file to extract it automatically",
-                ));
-                ui.label(tip_style("• Use Incremental Sync for efficient backups"));
-                ui.label(tip_style(
-                    "• Check the logs panel for detailed operation info",
-                ));
+                for tip in [
+                    t!("welcome-tip-combine"),
+                    t!("welcome-tip-extract"),
+                    t!("welcome-tip-sync"),
+                    t!("welcome-tip-logs"),
+                ] {
+                    ui.label(tip_style(&format!("• {}", tip)));
+                }
+                if let Some(shortcut) = self.settings.shortcuts.get(Command::OpenPalette) {
+                    ui.label(tip_style(&format!(
+                        "• {}",
+                        t!("welcome-tip-palette", shortcut = shortcut.display(ctx))
+                    )));
+                }
             });
         });
     }
 
     /// Process incoming messages and update UI state
     pub(super) fn process_messages(&mut self) {
+        self.logs
+            .set_spill_dir(self.settings.save_task_logs.then(log_dir).flatten());
+
         // Process log messages from tracing
         if let Some(log_receiver) = &self.log_receiver {
             while let Ok((level, log_msg)) = log_receiver.try_recv() {
@@ -225,100 +553,113 @@ impl FluxApp {
                     "[{:02}:{:02}:{:02}.{:03}] {}",
                     hours, mins, secs, millis, log_msg
                 );
-                self.logs.push((level, timestamped_msg));
-
-                // Keep log size reasonable (max 1000 entries)
-                if self.logs.len() > 1000 {
-                    self.logs.drain(0..100); // Remove oldest 100 entries
-                }
+                self.logs.push(level, timestamped_msg);
             }
         }
 
         // Process all pending UI messages
-        while let Ok(msg) = self.ui_receiver.try_recv() {
-            match msg {
-                ToUi::Progress(update) => {
-                    self.current_progress =
-                        update.processed_bytes as f32 / update.total_bytes.max(1) as f32;
-                    self.current_file = update.current_file.clone();
-                    self.processed_bytes = update.processed_bytes;
-                    self.total_bytes = update.total_bytes;
-                    self.current_speed_bps = update.speed_bps;
-                    self.eta_seconds = update.eta_seconds;
-
-                    // Format status text with size information
-                    let processed_mb = update.processed_bytes as f64 / (1024.0 * 1024.0);
-                    let total_mb = update.total_bytes as f64 / (1024.0 * 1024.0);
-
-                    if update.total_bytes > 0 {
-                        let percent = (self.current_progress * 100.0) as u32;
-                        let speed_str = crate::progress_tracker::format_speed(update.speed_bps);
-
-                        if let Some(eta_seconds) = update.eta_seconds {
-                            let eta_str = crate::progress_tracker::format_duration(eta_seconds);
-                            self.status_text = format!(
-                                "{:.1} / {:.1} MB ({}%) - {} - ETA: {}",
-                                processed_mb, total_mb, percent, speed_str, eta_str
-                            );
-                        } else {
-                            self.status_text = format!(
-                                "{:.1} / {:.1} MB ({}%) - {}",
-                                processed_mb, total_mb, percent, speed_str
-                            );
-                        }
-                    } else {
-                        self.status_text = "Processing...".to_string();
-                    }
+        while let Ok(event) = self.ui_receiver.try_recv() {
+            match &event.message {
+                ToUi::Progress(_) => {
+                    self.task_queue.apply(&event);
                 }
                 ToUi::Finished(result) => {
-                    self.is_busy = false;
-                    self.cancel_flag = None; // Clear cancel flag
-                    match result {
-                        TaskResult::Success => {
-                            self.status_text = "Task completed successfully!".to_string();
-                            self.current_progress = 1.0;
-                            info!("Task completed successfully");
+                    if let Some(name) = self.scheduler.finish_run(event.id, result, Utc::now()) {
+                        info!("Scheduled run finished: {}", name);
+                    }
+                    self.history.finish_task(event.id, result);
+                    let saved = self.restartable.finish(event.id);
+                    let refused = self.access_denied.remove(&event.id);
+                    self.logs.finish_task(event.id);
+
+                    let Some(task) = self.task_queue.apply(&event) else {
+                        continue;
+                    };
+                    let (kind, label, status) =
+                        (task.kind, task.label.clone(), task.status.clone());
+                    self.task_history.finish(event.id, &status);
+
+                    match status {
+                        TaskStatus::Completed => {
+                            info!("Task completed successfully: {}", label);
 
                             // Add success notification
-                            let message = match self.view {
-                                AppView::Packing => "Archive created successfully!",
-                                AppView::Extracting => "Files extracted successfully!",
-                                _ => "Operation completed successfully!",
+                            // The run was recorded as a new snapshot
+                            if kind == TaskKind::Sync {
+                                self.refresh_snapshots();
+                            }
+
+                            let message = match kind {
+                                TaskKind::Pack => t!("task-pack-succeeded"),
+                                TaskKind::Extract => t!("task-extract-succeeded"),
+                                TaskKind::Sync => t!("task-sync-succeeded"),
                             };
-                            self.toasts.success(message);
+                            self.toasts.success(format!("{} ({})", message, label));
+
+                            self.publish_clipboard_copy(event.id);
                         }
-                        TaskResult::Error(err) => {
-                            self.status_text = format!("Error: {}", err);
-                            self.current_progress = 0.0;
-                            info!("Task failed: {}", err);
+                        TaskStatus::Failed(err) => {
+                            info!("Task failed: {}: {}", label, err);
+                            self.clipboard_copies
+                                .retain(|copy| copy.task_id != event.id);
 
                             // Add error notification
-                            self.toasts.error("Operation failed - click for details");
+                            self.toasts.error(t!("task-failed-toast"));
 
                             // Store error details for modal
-                            let summary = match self.view {
-                                AppView::Packing => "Failed to create archive",
-                                AppView::Extracting => "Failed to extract files",
-                                _ => "Operation failed",
+                            let summary = match kind {
+                                TaskKind::Pack => t!("task-pack-failed"),
+                                TaskKind::Extract => t!("task-extract-failed"),
+                                TaskKind::Sync => t!("task-sync-failed"),
                             };
 
+                            // A refused extraction can be tried again elevated or
+                            // somewhere else
+                            if let Some(task @ SavedTask::Extract { .. }) =
+                                saved.filter(|_| refused)
+                            {
+                                self.access_denied_prompt =
+                                    Some(AccessDeniedPrompt { task, error: err });
+                                continue;
+                            }
+
                             // Parse error for better formatting
-                            let details = format!("Error Details:\n\n{}\n\nPlease check:\n• File permissions\n• Available disk space\n• File paths are correct\n• Archive format is supported", err);
+                            let details = t!("task-failed-details", error = err);
 
                             self.error_details = Some((summary.to_string(), details));
                             self.show_error_modal = true;
                         }
-                        TaskResult::Cancelled => {
-                            self.status_text = "Operation cancelled".to_string();
-                            self.current_progress = 0.0;
-                            info!("Task cancelled");
+                        TaskStatus::Cancelled => {
+                            info!("Task cancelled: {}", label);
+                            self.clipboard_copies
+                                .retain(|copy| copy.task_id != event.id);
 
                             // Add info notification
-                            self.toasts.info("Operation cancelled by user");
+                            self.toasts.info(t!("task-cancelled", label = label));
                         }
+                        TaskStatus::Queued | TaskStatus::Running => {}
                     }
                 }
+                ToUi::PasswordRequest(request) => {
+                    self.task_queue.apply(&event);
+                    info!(
+                        "Password requested for {} in {}",
+                        request.entry.display(),
+                        request.archive.display()
+                    );
+                    self.password_prompts.push_back(PasswordPrompt {
+                        task_id: event.id,
+                        request: request.clone(),
+                        input: String::new(),
+                    });
+                }
+                ToUi::AccessDenied => {
+                    self.access_denied.insert(event.id);
+                }
+                ToUi::Report(_) | ToUi::Manifest(_) => self.task_history.apply(&event),
                 ToUi::Log(message) => {
+                    self.scheduler.record_log(event.id, message);
+
                     // Add timestamp to log message (simple format for now)
                     let now = SystemTime::now()
                         .duration_since(SystemTime::UNIX_EPOCH)
@@ -333,16 +674,101 @@ impl FluxApp {
                         "[{:02}:{:02}:{:02}.{:03}] {}",
                         hours, mins, secs, millis, message
                     );
+                    self.task_history.record_log(event.id, &timestamped_msg);
                     // For messages from ToUi::Log, default to INFO level
-                    self.logs.push((tracing::Level::INFO, timestamped_msg));
-
-                    // Keep log size reasonable (max 1000 entries)
-                    if self.logs.len() > 1000 {
-                        self.logs.drain(0..100); // Remove oldest 100 entries
-                    }
+                    self.logs
+                        .push_task(event.id, tracing::Level::INFO, timestamped_msg);
                 }
             }
         }
+
+        // Pick up a finished cloud listing
+        self.cloud_browser.poll();
+
+        // Smart packing explains its choice before the job starts, the
+        // exclusions show what they skip and the estimate what to expect
+        if self.view == AppView::Packing {
+            if self.compression_format == SMART_FORMAT {
+                self.pack_job.analyze(&self.input_files);
+            }
+            self.pack_job.scan(&self.input_files);
+            self.pack_job
+                .estimate(&self.input_files, &self.compression_format);
+        }
+        self.pack_job.poll();
+
+        // Destinations are checked as soon as they are chosen, so the
+        // views can tell what is wrong before the job starts
+        match self.view {
+            AppView::Packing if self.cloud_pack_destination.is_none() => match &self.output_path {
+                Some(output) => self.preflight.check_pack(
+                    &self.input_files,
+                    output,
+                    self.pack_job.archive_size(),
+                ),
+                None => self.preflight.clear(),
+            },
+            AppView::Extracting if self.input_files.len() > 1 => {
+                let (archives, output_dirs): (Vec<_>, Vec<_>) = self
+                    .input_files
+                    .iter()
+                    .filter_map(|archive| {
+                        let output_dir = self
+                            .batch_destination
+                            .output_dir(archive, self.output_path.as_deref())?;
+                        Some((archive.clone(), output_dir))
+                    })
+                    .unzip();
+                self.preflight.check_extract(&archives, &output_dirs, true);
+            }
+            AppView::Extracting => match &self.output_path {
+                Some(output_dir) => self.preflight.check_extract(
+                    &self.input_files,
+                    std::slice::from_ref(output_dir),
+                    false,
+                ),
+                None => self.preflight.clear(),
+            },
+            _ => {}
+        }
+        self.preflight.poll();
+        if let Some(preview) = &mut self.sync_preview {
+            preview.poll();
+        }
+        match self
+            .snapshot_browser
+            .as_mut()
+            .and_then(SnapshotBrowser::poll)
+        {
+            Some(Ok(summary)) => {
+                self.toasts
+                    .success(t!("snapshot-restore-done", summary = summary));
+            }
+            Some(Err(e)) => {
+                self.toasts.error(t!("snapshot-restore-failed", error = e));
+            }
+            None => {}
+        }
+
+        // Tasks cancelled while still queued never report back
+        for task_id in self.scheduler.running_tasks() {
+            let active = self
+                .task_queue
+                .tasks()
+                .iter()
+                .any(|task| task.id == task_id && task.status.is_active());
+            if !active {
+                self.scheduler
+                    .finish_run(task_id, &TaskResult::Cancelled, Utc::now());
+            }
+        }
+
+        // Queue schedules that came due
+        self.run_due_schedules();
+
+        // Start queued tasks on any workers that became free
+        self.task_queue.max_concurrent = self.settings.max_concurrent_tasks;
+        self.task_queue.dispatch(&self.task_sender);
     }
 }
 
@@ -353,17 +779,22 @@ impl eframe::App for FluxApp {
         set_theme_in_context(ctx, &self.theme);
 
         // Update window title based on current state
-        let title = match (self.view, self.is_busy) {
-            (AppView::Packing, true) => "Flux - Packing...",
-            (AppView::Packing, false) => "Flux - Pack Files",
-            (AppView::Extracting, true) => "Flux - Extracting...",
-            (AppView::Extracting, false) => "Flux - Extract Archive",
-            (AppView::Syncing, true) => "Flux - Syncing...",
-            (AppView::Syncing, false) => "Flux - Incremental Backup",
-            (AppView::Browsing, _) => "Flux - Archive Browser",
-            (AppView::Welcome, _) => "Flux - File Archiver",
+        let view_title = match self.view {
+            AppView::Packing => t!("window-title-packing"),
+            AppView::Extracting => t!("window-title-extracting"),
+            AppView::Syncing => t!("window-title-syncing"),
+            AppView::Browsing => t!("window-title-browsing"),
+            AppView::Welcome => t!("window-title-welcome"),
+            AppView::Cloud => t!("window-title-cloud"),
+            AppView::Schedules => t!("window-title-schedules"),
+            AppView::Settings => t!("window-title-settings"),
         };
-        ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.to_string()));
+        let title = match self.task_queue.active_count() {
+            0 => view_title.to_string(),
+            1 => t!("window-title-one-active", title = view_title),
+            n => t!("window-title-active", title = view_title, count = n),
+        };
+        ctx.send_viewport_cmd(egui::ViewportCommand::Title(title));
 
         // Check for dropped files
         ctx.input(|i| {
@@ -380,6 +811,36 @@ impl eframe::App for FluxApp {
 
         // Process incoming messages
         self.process_messages();
+        self.poll_remote_listing();
+        self.poll_index_loader();
+        self.poll_browser_save();
+        if self.remote_listing.is_some()
+            || self.index_loader.is_some()
+            || self.browser_state.as_ref().is_some_and(|b| b.is_saving())
+        {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+
+        // Keyboard shortcuts, unless the palette or a shortcut being
+        // recorded needs the keys
+        if self.view != AppView::Settings {
+            self.recording_shortcut = None;
+        }
+        if self.command_palette.is_none() && self.recording_shortcut.is_none() {
+            if let Some(command) = self.settings.shortcuts.pressed(ctx) {
+                self.run_command(command);
+            }
+        }
+
+        // Wake up now and then so schedules run without user input
+        if self
+            .scheduler
+            .schedules()
+            .iter()
+            .any(|schedule| schedule.enabled)
+        {
+            ctx.request_repaint_after(std::time::Duration::from_secs(30));
+        }
 
         // Navigation items
         let nav_items = NavItem::default_items();
@@ -408,16 +869,17 @@ impl eframe::App for FluxApp {
                         }
                         AppView::Packing => {
                             // Handle packing view actions
+                            let cloud_output = self.cloud_pack_url();
                             if let Some(action) = draw_packing_view_modern(
                                 ctx,
                                 ui,
                                 &self.input_files,
                                 &self.output_path,
+                                cloud_output.as_deref(),
                                 &mut self.compression_format,
-                                self.is_busy,
+                                &mut self.pack_job,
+                                &self.preflight,
                                 &self.theme,
-                                self.current_progress,
-                                &self.status_text,
                             ) {
                                 match action {
                                     PackingAction::RemoveFile(idx) => {
@@ -430,22 +892,33 @@ impl eframe::App for FluxApp {
                                         }
                                     }
                                     PackingAction::SelectOutput => {
-                                        // Determine file extension and filter based on compression format
-                                        let (extension, filter_name) =
-                                            match self.compression_format.as_str() {
-                                                "tar.gz" => ("tar.gz", "TAR GZ Archive"),
-                                                "tar.zst" => ("tar.zst", "TAR ZST Archive"),
-                                                "tar.xz" => ("tar.xz", "TAR XZ Archive"),
-                                                "zip" => ("zip", "ZIP Archive"),
-                                                _ => ("tar.gz", "Archive"),
-                                            };
+                                        // Determine file extension and filter based on compression format;
+                                        // smart packing renames the output once its pick is known
+                                        let (extension, filter_name) = match self
+                                            .pack_job
+                                            .effective_format(&self.compression_format)
+                                        {
+                                            Some("tar.gz") => ("tar.gz", "TAR GZ"),
+                                            Some("tar.zst") => ("tar.zst", "TAR ZST"),
+                                            Some("tar.xz") => ("tar.xz", "TAR XZ"),
+                                            Some("tar.br") => ("tar.br", "TAR BR"),
+                                            Some("tar") => ("tar", "TAR"),
+                                            Some("zip") => ("zip", "ZIP"),
+                                            _ => ("tar.zst", ""),
+                                        };
+                                        let filter_name = if filter_name.is_empty() {
+                                            t!("dialog-archive").to_string()
+                                        } else {
+                                            t!("dialog-format-archive", format = filter_name)
+                                        };
 
                                         if let Some(path) = rfd::FileDialog::new()
                                             .set_file_name(format!("archive.{}", extension))
-                                            .add_filter(filter_name, &[extension])
+                                            .add_filter(&filter_name, &[extension])
                                             .save_file()
                                         {
                                             self.output_path = Some(path);
+                                            self.cloud_pack_destination = None;
                                         }
                                     }
                                     PackingAction::AddMoreFiles => {
@@ -458,13 +931,10 @@ impl eframe::App for FluxApp {
                                     }
                                     PackingAction::ClearAll => {
                                         self.input_files.clear();
+                                        self.pack_job = PackJobOptions::new(&self.settings);
                                         self.output_path = None;
+                                        self.cloud_pack_destination = None;
                                         self.view = AppView::Welcome;
-                                        self.current_progress = 0.0;
-                                        self.status_text = "Ready".to_string();
-                                    }
-                                    PackingAction::Cancel => {
-                                        self.cancel_task();
                                     }
                                 }
                             }
@@ -477,10 +947,11 @@ impl eframe::App for FluxApp {
                             if let Some(action) = draw_extracting_view(
                                 ctx,
                                 ui,
-                                &archive_path,
+                                &self.input_files,
                                 &self.output_path,
-                                self.is_busy,
                                 &mut self.extract_hoist,
+                                &mut self.batch_destination,
+                                &self.preflight,
                             ) {
                                 match action {
                                     ExtractingAction::SelectOutputDir => {
@@ -492,38 +963,53 @@ impl eframe::App for FluxApp {
                                         self.start_task();
                                     }
                                     ExtractingAction::BrowseArchive => {
-                                        if let Some(path) = rfd::FileDialog::new()
-                                            .add_filter(
-                                                "Archives",
-                                                &["zip", "tar", "gz", "zst", "xz", "7z"],
-                                            )
-                                            .pick_file()
+                                        if let Some(paths) = rfd::FileDialog::new()
+                                            .add_filter(t!("dialog-archives"), FILE_EXTENSIONS)
+                                            .pick_files()
                                         {
-                                            self.input_files = vec![path];
+                                            self.input_files = paths;
                                         }
                                     }
+                                    ExtractingAction::RemoveArchive(idx) => {
+                                        if idx < self.input_files.len() {
+                                            self.input_files.remove(idx);
+                                        }
+                                    }
+                                    ExtractingAction::UseArchiveFolders => {
+                                        self.output_path = None;
+                                    }
                                     ExtractingAction::Clear => {
                                         self.input_files.clear();
                                         self.output_path = None;
                                         self.view = AppView::Welcome;
-                                        self.current_progress = 0.0;
-                                        self.status_text = "Ready".to_string();
                                     }
-                                    ExtractingAction::Cancel => {
-                                        self.cancel_task();
+                                    ExtractingAction::Verify => {
+                                        if let Some(archive) = archive_path {
+                                            self.verification =
+                                                Some(ArchiveVerification::start(archive));
+                                        }
+                                    }
+                                    ExtractingAction::ShowProperties => {
+                                        if let Some(archive) = archive_path {
+                                            self.properties =
+                                                Some(ArchiveProperties::open(archive));
+                                        }
                                     }
                                     ExtractingAction::OpenBrowser => {
                                         if let Some(archive) = archive_path {
                                             if let Err(e) = self.open_archive_browser(archive) {
-                                                self.toasts.error(format!(
-                                                    "Failed to open browser: {}",
-                                                    e
-                                                ));
+                                                self.toasts
+                                                    .error(t!("browser-open-failed", error = e));
                                             }
                                         }
                                     }
                                 }
                             }
+
+                            ui.add_space(20.0);
+                            ui.separator();
+                            ui.add_space(10.0);
+                            self.draw_archive_url_field(ui, ui.available_width().min(600.0));
                         }
                         AppView::Syncing => {
                             // Handle sync view actions
@@ -533,24 +1019,27 @@ impl eframe::App for FluxApp {
                                 &self.sync_source_dir,
                                 &self.sync_target_archive,
                                 &self.sync_manifest_path,
-                                self.is_busy,
+                                self.sync_preview.as_ref(),
                             ) {
                                 match action {
                                     SyncAction::SelectSourceDir => {
                                         if let Some(dir) = rfd::FileDialog::new().pick_folder() {
                                             self.sync_source_dir = Some(dir);
+                                            self.sync_preview = None;
                                         }
                                     }
                                     SyncAction::SelectTargetArchive => {
                                         if let Some(file) = rfd::FileDialog::new()
                                             .set_file_name("backup.tar.zst")
                                             .add_filter(
-                                                "Tar Archives",
+                                                t!("dialog-tar-archives"),
                                                 &["tar", "tar.gz", "tar.zst", "tar.xz"],
                                             )
                                             .save_file()
                                         {
                                             self.sync_target_archive = Some(file.clone());
+                                            self.sync_preview = None;
+                                            self.refresh_snapshots();
                                             // Check for existing manifest
                                             let manifest_path =
                                                 file.with_extension("manifest.json");
@@ -564,12 +1053,15 @@ impl eframe::App for FluxApp {
                                     SyncAction::StartSync => {
                                         self.start_sync_task();
                                     }
+                                    SyncAction::PreviewChanges => {
+                                        self.start_sync_preview();
+                                    }
                                     SyncAction::ViewManifest => {
                                         if let Some(manifest_path) = &self.sync_manifest_path {
                                             // Show manifest details (could open in external editor or show in modal)
-                                            self.toasts.info(format!(
-                                                "Manifest at: {}",
-                                                manifest_path.display()
+                                            self.toasts.info(t!(
+                                                "sync-manifest-at",
+                                                path = manifest_path.display()
                                             ));
                                         }
                                     }
@@ -577,15 +1069,20 @@ impl eframe::App for FluxApp {
                                         self.sync_source_dir = None;
                                         self.sync_target_archive = None;
                                         self.sync_manifest_path = None;
+                                        self.sync_preview = None;
+                                        self.snapshot_browser = None;
                                         self.view = AppView::Welcome;
-                                        self.current_progress = 0.0;
-                                        self.status_text = "Ready".to_string();
-                                    }
-                                    SyncAction::Cancel => {
-                                        self.cancel_task();
                                     }
                                 }
                             }
+
+                            if let Some(browser) = &mut self.snapshot_browser {
+                                ui.add_space(20.0);
+                                ui.separator();
+                                if let Some(action) = draw_snapshots(ui, browser) {
+                                    self.handle_snapshot_action(action);
+                                }
+                            }
                         }
                         AppView::Browsing => {
                             // Handle browser view
@@ -604,6 +1101,14 @@ impl eframe::App for FluxApp {
                                                 dest,
                                             );
                                         }
+                                        BrowserAction::ExtractAll(dest)
+                                            if browser_state.is_remote() =>
+                                        {
+                                            self.cloud_extract_url =
+                                                browser_state.source_url.clone();
+                                            self.output_path = Some(dest);
+                                            self.start_task();
+                                        }
                                         BrowserAction::ExtractAll(dest) => {
                                             // Switch to extracting view with the archive
                                             self.view = AppView::Extracting;
@@ -613,18 +1118,54 @@ impl eframe::App for FluxApp {
                                             self.browser_state = None;
                                             self.start_task();
                                         }
+                                        BrowserAction::CopyToClipboard(entries) => {
+                                            let archive_path = browser_state.archive_path.clone();
+                                            self.copy_entries_to_clipboard(entries, archive_path);
+                                        }
+                                        BrowserAction::ShowProperties => {
+                                            self.properties = Some(ArchiveProperties::open(
+                                                browser_state.archive_path.clone(),
+                                            ));
+                                        }
+                                        BrowserAction::Verify => {
+                                            self.verification = Some(ArchiveVerification::start(
+                                                browser_state.archive_path.clone(),
+                                            ));
+                                        }
+                                        BrowserAction::AddFiles => {
+                                            if let Some(files) = rfd::FileDialog::new().pick_files()
+                                            {
+                                                browser_state.add_sources(files);
+                                            }
+                                        }
+                                        BrowserAction::AddFolder => {
+                                            if let Some(dir) = rfd::FileDialog::new().pick_folder()
+                                            {
+                                                browser_state.add_sources(vec![dir]);
+                                            }
+                                        }
+                                        BrowserAction::SaveEdits => {
+                                            // The loader is done once editing is allowed,
+                                            // but must not read the archive as it changes
+                                            self.index_loader = None;
+                                            browser_state.start_save();
+                                        }
                                         BrowserAction::Close => {
                                             // Return to welcome view
                                             self.view = AppView::Welcome;
                                             self.browser_state = None;
-                                            self.current_progress = 0.0;
-                                            self.status_text = "Ready".to_string();
                                         }
                                         BrowserAction::ChooseDestination => {
                                             if let Some(dir) = rfd::FileDialog::new().pick_folder()
                                             {
-                                                // Check if we're extracting all or selected
-                                                if browser_state.selected.is_empty() {
+                                                // Check if we're extracting all or selected;
+                                                // cloud archives are always extracted whole
+                                                if browser_state.is_remote() {
+                                                    self.cloud_extract_url =
+                                                        browser_state.source_url.clone();
+                                                    self.output_path = Some(dir);
+                                                    self.start_task();
+                                                } else if browser_state.selected.is_empty() {
                                                     // Extract all
                                                     self.view = AppView::Extracting;
                                                     self.input_files =
@@ -653,6 +1194,174 @@ impl eframe::App for FluxApp {
                                 self.view = AppView::Welcome;
                             }
                         }
+                        AppView::Cloud => {
+                            let action = draw_cloud_view(
+                                ui,
+                                &mut self.cloud_browser,
+                                &self.settings.cloud_profiles,
+                                &self.theme,
+                            );
+
+                            if let Some(action) = action {
+                                match action {
+                                    CloudAction::Open(url) => {
+                                        if let Err(e) = self.cloud_browser.open(&url) {
+                                            self.toasts
+                                                .error(t!("cloud-invalid-location", error = e));
+                                        }
+                                    }
+                                    CloudAction::Enter(key) => self.cloud_browser.enter(&key),
+                                    CloudAction::Up => self.cloud_browser.up(),
+                                    CloudAction::Refresh => self.cloud_browser.refresh(),
+                                    CloudAction::Extract(key) => {
+                                        if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                                            self.cloud_extract_url =
+                                                self.cloud_browser.object_url(&key);
+                                            self.output_path = Some(dir);
+                                            self.start_task();
+                                        }
+                                    }
+                                    CloudAction::PackHere => {
+                                        if let Some(url) = self.cloud_browser.current_url() {
+                                            self.toasts.info(t!("cloud-packing-to", url = url));
+                                            self.cloud_pack_destination = Some(url);
+                                            self.view = AppView::Packing;
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        AppView::Settings => {
+                            let previous_format = self.settings.default_format.clone();
+                            let previous_level = self.settings.compression_level;
+                            let previous_threads = self.settings.threads;
+                            let previous_limits = self.settings.limits.clone();
+                            let action = draw_settings_view(
+                                ctx,
+                                ui,
+                                &mut self.settings,
+                                self.shell_integrated,
+                                self.recording_shortcut,
+                                &self.theme,
+                            );
+
+                            // A new default format applies to the next pack as well
+                            if self.settings.default_format != previous_format {
+                                self.compression_format = self.settings.default_format.clone();
+                            }
+                            if self.settings.compression_level != previous_level
+                                || self.settings.threads != previous_threads
+                            {
+                                self.pack_job.level = self.settings.compression_level;
+                                self.pack_job.threads = self.settings.threads;
+                            }
+                            if self.settings.limits != previous_limits {
+                                self.pack_job.limits = self.settings.limits.clone();
+                            }
+
+                            if let Some(action) = action {
+                                match action {
+                                    SettingsAction::SetDarkMode(dark) => {
+                                        if self.theme.is_dark_mode() != dark {
+                                            self.toggle_theme();
+                                        }
+                                    }
+                                    SettingsAction::AddCloudProfile => {
+                                        self.settings.cloud_profiles.push(CloudProfile::default());
+                                    }
+                                    SettingsAction::RemoveCloudProfile(idx) => {
+                                        if idx < self.settings.cloud_profiles.len() {
+                                            self.settings.cloud_profiles.remove(idx);
+                                        }
+                                    }
+                                    SettingsAction::ResetDefaults => {
+                                        // Keep the language so the user can still read
+                                        // the interface after the reset
+                                        self.settings = Settings {
+                                            language: self.settings.language,
+                                            ..Settings::default()
+                                        };
+                                        self.compression_format =
+                                            self.settings.default_format.clone();
+                                        self.pack_job = PackJobOptions::new(&self.settings);
+                                        if self.theme.is_dark_mode() {
+                                            self.toggle_theme();
+                                        }
+                                        self.toasts.info(t!("settings-reset-done"));
+                                    }
+                                    SettingsAction::SetShellIntegration(install) => {
+                                        self.set_shell_integration(install);
+                                    }
+                                    SettingsAction::SetLanguage(language) => {
+                                        self.set_language(ctx, language);
+                                    }
+                                    SettingsAction::RecordShortcut(command) => {
+                                        self.recording_shortcut = command;
+                                    }
+                                    SettingsAction::SetShortcut(command, shortcut) => {
+                                        self.recording_shortcut = None;
+                                        if let Some(other) =
+                                            self.settings.shortcuts.set(command, shortcut)
+                                        {
+                                            self.toasts.info(t!(
+                                                "settings-shortcut-moved",
+                                                command = other.label()
+                                            ));
+                                        }
+                                    }
+                                    SettingsAction::ResetShortcuts => {
+                                        self.recording_shortcut = None;
+                                        self.settings.shortcuts.reset();
+                                    }
+                                }
+                            }
+
+                            // Appearance edits show up while the user drags
+                            if self.settings.appearance != self.theme.appearance {
+                                self.theme = crate::theme::FluxTheme::new(
+                                    self.theme.mode,
+                                    self.settings.appearance.clone(),
+                                );
+                            }
+                        }
+                        AppView::Schedules => {
+                            let action = draw_schedules_view(
+                                ui,
+                                &self.scheduler,
+                                &mut self.schedule_form,
+                                &self.theme,
+                            );
+
+                            if let Some(action) = action {
+                                match action {
+                                    ScheduleAction::SelectSource => {
+                                        if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                                            self.schedule_form.source_dir = Some(dir);
+                                        }
+                                    }
+                                    ScheduleAction::SelectTarget => {
+                                        if let Some(file) = rfd::FileDialog::new()
+                                            .set_file_name("backup.tar.zst")
+                                            .add_filter(
+                                                t!("dialog-tar-archives"),
+                                                &["tar", "tar.gz", "tar.zst", "tar.xz"],
+                                            )
+                                            .save_file()
+                                        {
+                                            self.schedule_form.target_archive = Some(file);
+                                        }
+                                    }
+                                    ScheduleAction::Add => self.add_schedule(),
+                                    ScheduleAction::SetEnabled(id, enabled) => {
+                                        if let Some(schedule) = self.scheduler.get_mut(id) {
+                                            schedule.enabled = enabled;
+                                        }
+                                    }
+                                    ScheduleAction::RunNow(id) => self.run_schedule(id),
+                                    ScheduleAction::Remove(id) => self.scheduler.remove(id),
+                                }
+                            }
+                        }
                     }
                 });
         });
@@ -663,35 +1372,32 @@ impl eframe::App for FluxApp {
             .show(ctx, |ui| {
                 ui.horizontal(|ui| {
                     // Log panel toggle
-                    ui.checkbox(&mut self.show_log_panel, "📋 Show Logs");
+                    ui.checkbox(
+                        &mut self.show_log_panel,
+                        format!("📋 {}", t!("status-show-logs")),
+                    );
+
+                    // Task panel toggle
+                    let task_label = match self.task_queue.active_count() {
+                        0 => t!("status-show-tasks").to_string(),
+                        n => t!("status-show-tasks-count", count = n),
+                    };
+                    ui.checkbox(&mut self.show_task_panel, task_label);
 
                     // Log count with level breakdown
                     if !self.logs.is_empty() {
                         ui.separator();
 
-                        let error_count = self
-                            .logs
-                            .iter()
-                            .filter(|(l, _)| matches!(l, &Level::ERROR))
-                            .count();
-                        let warn_count = self
-                            .logs
-                            .iter()
-                            .filter(|(l, _)| matches!(l, &Level::WARN))
-                            .count();
-                        let _info_count = self
-                            .logs
-                            .iter()
-                            .filter(|(l, _)| matches!(l, &Level::INFO))
-                            .count();
-
-                        ui.weak(format!("Total: {}", self.logs.len()));
+                        let error_count = self.logs.count(Level::ERROR);
+                        let warn_count = self.logs.count(Level::WARN);
+
+                        ui.weak(t!("status-log-total", count = self.logs.len()));
 
                         if error_count > 0 {
                             ui.separator();
                             ui.colored_label(
                                 egui::Color32::from_rgb(255, 100, 100),
-                                format!("Errors: {}", error_count),
+                                t!("status-log-errors", count = error_count),
                             );
                         }
 
@@ -699,13 +1405,16 @@ impl eframe::App for FluxApp {
                             ui.separator();
                             ui.colored_label(
                                 egui::Color32::from_rgb(255, 200, 100),
-                                format!("Warnings: {}", warn_count),
+                                t!("status-log-warnings", count = warn_count),
                             );
                         }
 
                         if self.show_log_panel {
                             ui.separator();
-                            if ui.button("🗑 Clear All").clicked() {
+                            if ui
+                                .button(format!("🗑 {}", t!("status-clear-logs")))
+                                .clicked()
+                            {
                                 self.logs.clear();
                             }
                         }
@@ -720,24 +1429,87 @@ impl eframe::App for FluxApp {
                         };
                         if ui
                             .button(theme_icon)
-                            .on_hover_text("Toggle theme")
+                            .on_hover_text(t!("status-toggle-theme"))
                             .clicked()
                         {
-                            self.theme.toggle();
+                            self.toggle_theme();
                         }
 
                         ui.separator();
 
-                        if self.is_busy {
+                        if self.task_queue.is_busy() {
+                            let running = self.task_queue.running_count();
+                            let queued = self.task_queue.active_count() - running;
                             ui.spinner();
-                            ui.label("Working...");
+                            if queued > 0 {
+                                ui.label(t!(
+                                    "status-running-queued",
+                                    running = running,
+                                    queued = queued
+                                ));
+                            } else {
+                                ui.label(t!("status-running", running = running));
+                            }
+                            let paused = self.task_queue.paused_count();
+                            if paused > 0 {
+                                ui.colored_label(
+                                    self.theme.colors.warning,
+                                    t!("status-paused", count = paused),
+                                );
+                            }
                         } else {
-                            ui.weak("Ready");
+                            ui.weak(t!("status-ready"));
                         }
                     });
                 });
             });
 
+        // Task panel
+        if self.show_task_panel {
+            egui::TopBottomPanel::bottom("task_panel")
+                .resizable(true)
+                .default_height(180.0)
+                .min_height(80.0)
+                .max_height(400.0)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.selectable_value(
+                            &mut self.show_task_history,
+                            false,
+                            t!("task-history-tab-queue"),
+                        );
+                        ui.selectable_value(
+                            &mut self.show_task_history,
+                            true,
+                            t!("task-history-tab-history"),
+                        );
+                    });
+                    if self.show_task_history {
+                        if let Some(action) =
+                            draw_task_history_view(ui, &self.task_history, &self.theme)
+                        {
+                            self.handle_task_history_action(ctx, action);
+                        }
+                    } else if let Some(action) =
+                        draw_task_queue_view(ui, self.task_queue.tasks(), &self.theme)
+                    {
+                        match action {
+                            TaskQueueAction::Cancel(id) => self.cancel_task(id),
+                            TaskQueueAction::CancelAll => {
+                                self.task_queue.cancel_all();
+                                self.toasts.info(t!("tasks-cancelling-all"));
+                            }
+                            TaskQueueAction::Pause(id) => self.pause_task(id),
+                            TaskQueueAction::Resume(id) => self.resume_task(id),
+                            TaskQueueAction::PauseAll | TaskQueueAction::ResumeAll => {
+                                self.toggle_pause_all()
+                            }
+                            TaskQueueAction::ClearFinished => self.task_queue.clear_finished(),
+                        }
+                    }
+                });
+        }
+
         // Log panel
         if self.show_log_panel {
             egui::TopBottomPanel::bottom("log_panel")
@@ -748,7 +1520,7 @@ impl eframe::App for FluxApp {
                 .show(ctx, |ui| {
                     // Header with controls
                     ui.horizontal(|ui| {
-                        ui.heading("📋 Logs");
+                        ui.heading(format!("📋 {}", t!("logs-title")));
 
                         ui.separator();
 
@@ -757,16 +1529,19 @@ impl eframe::App for FluxApp {
                         ui.add(
                             egui::TextEdit::singleline(&mut self.log_filter)
                                 .desired_width(200.0)
-                                .hint_text("Filter logs..."),
+                                .hint_text(t!("logs-filter-hint")),
                         );
 
                         ui.separator();
 
                         // Level filter buttons
-                        ui.label("Level:");
+                        ui.label(t!("logs-level"));
 
                         let all_selected = self.log_level_filter.is_none();
-                        if ui.selectable_label(all_selected, "All").clicked() {
+                        if ui
+                            .selectable_label(all_selected, t!("logs-level-all"))
+                            .clicked()
+                        {
                             self.log_level_filter = None;
                         }
 
@@ -774,7 +1549,7 @@ impl eframe::App for FluxApp {
                         if ui
                             .selectable_label(
                                 error_selected,
-                                egui::RichText::new("Error")
+                                egui::RichText::new(t!("logs-level-error"))
                                     .color(egui::Color32::from_rgb(255, 100, 100)),
                             )
                             .clicked()
@@ -786,7 +1561,7 @@ impl eframe::App for FluxApp {
                         if ui
                             .selectable_label(
                                 warn_selected,
-                                egui::RichText::new("Warn")
+                                egui::RichText::new(t!("logs-level-warn"))
                                     .color(egui::Color32::from_rgb(255, 200, 100)),
                             )
                             .clicked()
@@ -795,7 +1570,10 @@ impl eframe::App for FluxApp {
                         }
 
                         let info_selected = matches!(self.log_level_filter, Some(Level::INFO));
-                        if ui.selectable_label(info_selected, "Info").clicked() {
+                        if ui
+                            .selectable_label(info_selected, t!("logs-level-info"))
+                            .clicked()
+                        {
                             self.log_level_filter = Some(Level::INFO);
                         }
 
@@ -803,7 +1581,7 @@ impl eframe::App for FluxApp {
                         if ui
                             .selectable_label(
                                 debug_selected,
-                                egui::RichText::new("Debug")
+                                egui::RichText::new(t!("logs-level-debug"))
                                     .color(egui::Color32::from_rgb(150, 150, 150)),
                             )
                             .clicked()
@@ -813,16 +1591,16 @@ impl eframe::App for FluxApp {
 
                         // Right-aligned export button
                         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            if ui.button("💾 Export").clicked() {
+                            if ui.button(format!("💾 {}", t!("logs-export"))).clicked() {
                                 if let Some(path) = rfd::FileDialog::new()
                                     .set_file_name("flux_logs.txt")
-                                    .add_filter("Text files", &["txt"])
+                                    .add_filter(t!("dialog-text-files"), &["txt"])
                                     .save_file()
                                 {
                                     if let Err(e) = self.export_logs(&path) {
-                                        self.toasts.error(format!("Failed to export logs: {}", e));
+                                        self.toasts.error(t!("logs-export-failed", error = e));
                                     } else {
-                                        self.toasts.success("Logs exported successfully");
+                                        self.toasts.success(t!("logs-exported"));
                                     }
                                 }
                             }
@@ -831,59 +1609,35 @@ impl eframe::App for FluxApp {
 
                     ui.separator();
 
-                    // Log content area
+                    // Log content area; only the rows in view are laid out
+                    let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                    let total = self.logs.len();
+                    self.logs.filter(&self.log_filter, self.log_level_filter);
+                    let logs = &self.logs;
+                    let matching = logs.matches();
+                    if matching.is_empty() && total > 0 {
+                        ui.weak(t!("logs-no-match"));
+                    }
                     egui::ScrollArea::vertical()
                         .auto_shrink([false, false])
                         .stick_to_bottom(true)
-                        .show(ui, |ui| {
+                        .show_rows(ui, row_height, matching.len(), |ui, rows| {
                             ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend);
 
-                            let filter_lower = self.log_filter.to_lowercase();
-                            let filtered_logs: Vec<_> = self
-                                .logs
-                                .iter()
-                                .filter(|(level, log)| {
-                                    // Apply level filter
-                                    if let Some(filter_level) = &self.log_level_filter {
-                                        if level != filter_level {
-                                            return false;
-                                        }
-                                    }
-
-                                    // Apply text filter
-                                    if !filter_lower.is_empty()
-                                        && !log.to_lowercase().contains(&filter_lower)
-                                    {
-                                        return false;
-                                    }
-
-                                    true
-                                })
-                                .collect();
-
-                            if filtered_logs.is_empty() && !self.logs.is_empty() {
-                                ui.weak("No logs match the current filter");
-                            } else {
-                                for (level, log) in filtered_logs {
-                                    // Color code based on log level
-                                    let color = match *level {
-                                        tracing::Level::ERROR => {
-                                            egui::Color32::from_rgb(255, 100, 100)
-                                        }
-                                        tracing::Level::WARN => {
-                                            egui::Color32::from_rgb(255, 200, 100)
-                                        }
-                                        tracing::Level::INFO => ui.style().visuals.text_color(),
-                                        tracing::Level::DEBUG => {
-                                            egui::Color32::from_rgb(150, 150, 150)
-                                        }
-                                        tracing::Level::TRACE => {
-                                            egui::Color32::from_rgb(100, 100, 100)
-                                        }
-                                    };
-
-                                    ui.colored_label(color, egui::RichText::new(log).monospace());
-                                }
+                            for seq in &matching[rows] {
+                                let Some((level, log)) = logs.get(*seq) else {
+                                    continue;
+                                };
+                                // Color code based on log level
+                                let color = match *level {
+                                    tracing::Level::ERROR => egui::Color32::from_rgb(255, 100, 100),
+                                    tracing::Level::WARN => egui::Color32::from_rgb(255, 200, 100),
+                                    tracing::Level::INFO => ui.style().visuals.text_color(),
+                                    tracing::Level::DEBUG => egui::Color32::from_rgb(150, 150, 150),
+                                    tracing::Level::TRACE => egui::Color32::from_rgb(100, 100, 100),
+                                };
+
+                                ui.colored_label(color, egui::RichText::new(log).monospace());
                             }
                         });
                 });
@@ -898,7 +1652,8 @@ impl eframe::App for FluxApp {
             if let Some((summary, details)) = error_details_clone {
                 let mut close_modal = false;
 
-                egui::Window::new("❌ Error Details")
+                egui::Window::new(format!("❌ {}", t!("error-details-title")))
+                    .id(egui::Id::new("error_details"))
                     .collapsible(false)
                     .resizable(false)
                     .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
@@ -927,17 +1682,20 @@ impl eframe::App for FluxApp {
 
                             // Action buttons
                             ui.horizontal(|ui| {
-                                if ui.button("📋 Copy to Clipboard").clicked() {
+                                if ui
+                                    .button(format!("📋 {}", t!("error-details-copy")))
+                                    .clicked()
+                                {
                                     ui.output_mut(|o| {
                                         o.copied_text = format!("{}\n\n{}", summary, details)
                                     });
-                                    self.toasts.info("Error details copied to clipboard");
+                                    self.toasts.info(t!("error-details-copied"));
                                 }
 
                                 ui.with_layout(
                                     egui::Layout::right_to_left(egui::Align::Center),
                                     |ui| {
-                                        if ui.button("Close").clicked() {
+                                        if ui.button(t!("common-close")).clicked() {
                                             close_modal = true;
                                         }
                                     },
@@ -953,11 +1711,109 @@ impl eframe::App for FluxApp {
             }
         }
 
+        // Overwrite confirmation dialog
+        if let Some(path) = self.pending_overwrite.clone() {
+            let mut confirmed = false;
+            let mut cancelled = false;
+
+            egui::Window::new(t!("overwrite-title"))
+                .id(egui::Id::new("overwrite_confirmation"))
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    let message = if path.is_dir() {
+                        t!("overwrite-directory")
+                    } else {
+                        t!("overwrite-file")
+                    };
+                    ui.label(message);
+                    ui.add_space(5.0);
+                    ui.monospace(path.display().to_string());
+                    ui.add_space(10.0);
+                    ui.separator();
+
+                    ui.horizontal(|ui| {
+                        if ui.button(t!("overwrite-confirm")).clicked() {
+                            confirmed = true;
+                        }
+                        if ui.button(t!("common-cancel")).clicked() {
+                            cancelled = true;
+                        }
+                    });
+                });
+
+            if confirmed {
+                self.confirm_overwrite();
+            } else if cancelled {
+                self.pending_overwrite = None;
+            }
+        }
+
+        // Password dialog for encrypted archives
+        self.draw_password_dialog(ctx);
+
+        // Extractions the destination refused
+        self.draw_access_denied_dialog(ctx);
+
+        // Log and report of a finished task
+        if let Some(record) = self.task_details.and_then(|id| self.task_history.get(id)) {
+            if let Some(action) = draw_task_details_dialog(ctx, record, &self.theme) {
+                self.handle_task_history_action(ctx, action);
+            }
+        }
+
+        if let Some(palette) = &mut self.command_palette {
+            match draw_command_palette(ctx, palette, &self.settings.shortcuts, &self.theme) {
+                Some(PaletteAction::Run(command)) => {
+                    self.command_palette = None;
+                    self.run_command(command);
+                }
+                Some(PaletteAction::Close) => self.command_palette = None,
+                None => {}
+            }
+        }
+
+        // Archive properties
+        if let Some(properties) = &mut self.properties {
+            properties.poll();
+            if draw_properties_dialog(ctx, properties, &self.theme) {
+                self.properties = None;
+            }
+        }
+
+        // Previous session
+        if let Some(restore) = &mut self.session_restore {
+            match draw_session_dialog(ctx, restore, &self.theme) {
+                Some(SessionAction::Restore) => {
+                    if let Some(restore) = self.session_restore.take() {
+                        self.restore_session(restore);
+                    }
+                }
+                Some(SessionAction::Discard) => {
+                    info!("Discarded previous session");
+                    self.session_restore = None;
+                }
+                None => {}
+            }
+        }
+
+        // Archive verification
+        if let Some(verification) = &mut self.verification {
+            verification.poll();
+            match draw_verify_dialog(ctx, verification, &self.theme) {
+                Some(VerifyAction::Export) => self.export_verify_report(),
+                Some(VerifyAction::Close) => self.verification = None,
+                None => {}
+            }
+        }
+
         // About dialog
         if self.show_about_dialog {
             let mut close_dialog = false;
 
-            egui::Window::new("About Flux")
+            egui::Window::new(t!("about-title"))
+                .id(egui::Id::new("about"))
                 .collapsible(false)
                 .resizable(false)
                 .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
@@ -969,21 +1825,25 @@ impl eframe::App for FluxApp {
                         ui.add_space(5.0);
 
                         // Version
-                        ui.label(format!("Version {}", env!("CARGO_PKG_VERSION")));
+                        ui.label(t!("about-version", version = env!("CARGO_PKG_VERSION")));
                         ui.add_space(10.0);
 
                         // Description
-                        ui.label("A fast, modern file archiver with GUI");
+                        ui.label(t!("about-description"));
                         ui.add_space(20.0);
 
                         // Features
-                        ui.label("Features:");
+                        ui.label(t!("about-features"));
                         ui.indent("features", |ui| {
-                            ui.label("• Multiple archive formats (ZIP, TAR, 7Z)");
-                            ui.label("• Smart compression selection");
-                            ui.label("• Incremental backups");
-                            ui.label("• Secure extraction");
-                            ui.label("• Cross-platform support");
+                            for feature in [
+                                t!("about-feature-formats"),
+                                t!("about-feature-smart"),
+                                t!("about-feature-incremental"),
+                                t!("about-feature-secure"),
+                                t!("about-feature-cross-platform"),
+                            ] {
+                                ui.label(format!("• {}", feature));
+                            }
                         });
 
                         ui.add_space(20.0);
@@ -993,7 +1853,7 @@ impl eframe::App for FluxApp {
                             ui.hyperlink_to("GitHub", "https://github.com/your-username/flux");
                             ui.label("|");
                             ui.hyperlink_to(
-                                "Documentation",
+                                t!("about-documentation"),
                                 "https://github.com/your-username/flux/wiki",
                             );
                         });
@@ -1003,7 +1863,7 @@ impl eframe::App for FluxApp {
                         ui.add_space(10.0);
 
                         // Close button
-                        if ui.button("Close").clicked() {
+                        if ui.button(t!("common-close")).clicked() {
                             close_dialog = true;
                         }
                     });
@@ -1014,8 +1874,17 @@ impl eframe::App for FluxApp {
             }
         }
 
-        // Request repaint if busy
-        if self.is_busy {
+        // Request repaint while tasks, cloud listings, pack job previews,
+        // archive properties, verification or destination checks are in
+        // progress
+        if self.task_queue.is_busy()
+            || self.cloud_browser.is_loading()
+            || self.pack_job.is_busy()
+            || self.properties.as_ref().is_some_and(|p| p.is_loading())
+            || self.verification.as_ref().is_some_and(|v| v.is_running())
+            || self.sync_preview.as_ref().is_some_and(|p| p.is_running())
+            || self.preflight.is_checking()
+        {
             ctx.request_repaint();
         }
     }
@@ -1023,4 +1892,8 @@ impl eframe::App for FluxApp {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
         self.save_persistence(storage);
     }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.keep_clipboard_copies();
+    }
 }
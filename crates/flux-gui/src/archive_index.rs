@@ -0,0 +1,180 @@
+//! Reading an archive's entries for the browser without blocking the UI
+//!
+//! [`IndexLoader`] lists the archive on a background thread and hands the
+//! entries over in batches, so the browser fills in while a large tar is
+//! still being read. Listings of archives with many entries are kept in the
+//! on-disk [`IndexCache`], and opening the same archive again shows them at
+//! once instead of reading it through.
+
+use crate::cloud::StoppableReader;
+use crate::t;
+use crossbeam_channel::Receiver;
+use flux_core::archive::extractor::ArchiveEntry;
+use flux_core::archive::{create_extractor, detect_format, list_from_reader, IndexCache};
+use flux_core::Error;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Entries handed to the browser at a time
+const BATCH_SIZE: usize = 5_000;
+
+/// Longest a found entry waits before its batch is handed over
+const BATCH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Listings shorter than this are quick enough to read again, and are not
+/// cached
+const CACHE_MIN_ENTRIES: usize = 1_000;
+
+/// What the loader has found since it was last polled
+pub enum IndexEvent {
+    /// More entries, in archive order
+    Entries(Vec<ArchiveEntry>),
+    /// Every entry has been handed over; `cached` if they came from the
+    /// index cache
+    Done { cached: bool },
+    /// Reading stopped early; the entries handed over so far are all there is
+    Failed(String),
+}
+
+/// Background listing of a local archive
+pub struct IndexLoader {
+    archive: PathBuf,
+    /// Entries found so far
+    found: Arc<AtomicUsize>,
+    stop: Arc<AtomicBool>,
+    events: Receiver<IndexEvent>,
+}
+
+impl IndexLoader {
+    /// Start listing `archive`
+    pub fn start(archive: PathBuf) -> Self {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let found = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (counter, stopped, source) = (found.clone(), stop.clone(), archive.clone());
+
+        std::thread::spawn(move || {
+            let cache = IndexCache::open_default()
+                .map_err(|e| debug!("No index cache: {}", e))
+                .ok();
+            if let Some(entries) = cache.as_ref().and_then(|cache| cache.load(&source)) {
+                counter.store(entries.len(), Ordering::Relaxed);
+                let _ = sender.send(IndexEvent::Entries(entries));
+                let _ = sender.send(IndexEvent::Done { cached: true });
+                return;
+            }
+
+            let mut batch = Vec::new();
+            let mut sent = Instant::now();
+            let result = list(&source, stopped.clone(), |entry| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                batch.push(entry);
+                if batch.len() >= BATCH_SIZE || sent.elapsed() >= BATCH_INTERVAL {
+                    let _ = sender.send(IndexEvent::Entries(std::mem::take(&mut batch)));
+                    sent = Instant::now();
+                }
+            });
+            if !batch.is_empty() {
+                let _ = sender.send(IndexEvent::Entries(batch));
+            }
+
+            match result {
+                Ok(entries) => {
+                    if let Some(cache) = cache.filter(|_| entries.len() >= CACHE_MIN_ENTRIES) {
+                        if let Err(e) = cache.store(&source, &entries) {
+                            warn!("Failed to cache the index of {:?}: {}", source, e);
+                        }
+                    }
+                    let _ = sender.send(IndexEvent::Done { cached: false });
+                }
+                Err(_) if stopped.load(Ordering::Relaxed) => {}
+                Err(e) => {
+                    let _ = sender.send(IndexEvent::Failed(
+                        t!("toast-read-entries-failed", error = e).to_string(),
+                    ));
+                }
+            }
+        });
+
+        Self {
+            archive,
+            found,
+            stop,
+            events: receiver,
+        }
+    }
+
+    /// The archive being listed
+    pub fn archive(&self) -> &Path {
+        &self.archive
+    }
+
+    /// Number of entries found so far
+    pub fn found(&self) -> usize {
+        self.found.load(Ordering::Relaxed)
+    }
+
+    /// Everything that happened since the last poll
+    pub fn poll(&self) -> Vec<IndexEvent> {
+        let mut events: Vec<IndexEvent> = self.events.try_iter().collect();
+        // The thread went away without saying it was done
+        if events.is_empty()
+            && matches!(
+                self.events.try_recv(),
+                Err(crossbeam_channel::TryRecvError::Disconnected)
+            )
+        {
+            events.push(IndexEvent::Failed(t!("cloud-list-stopped").to_string()));
+        }
+        events
+    }
+}
+
+impl Drop for IndexLoader {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// List `archive`, calling `on_entry` with each entry as it is found
+///
+/// Formats [`list_from_reader`] reads are listed as the archive is read;
+/// anything else goes through its extractor, which hands the entries over
+/// once it has them all.
+fn list(
+    archive: &Path,
+    stop: Arc<AtomicBool>,
+    mut on_entry: impl FnMut(ArchiveEntry),
+) -> flux_core::Result<Vec<ArchiveEntry>> {
+    if let Ok(format) = detect_format(archive) {
+        let file = File::open(archive).map_err(|e| Error::from(e).with_path(archive))?;
+        let reader = StoppableReader::new(BufReader::new(file), stop);
+        match list_from_reader(reader, format, |entry| on_entry(entry.clone())) {
+            Err(Error::UnsupportedFormat(_)) => {}
+            result => {
+                if let Ok(entries) = &result {
+                    info!("Listed {} entries of {:?}", entries.len(), archive);
+                }
+                return result;
+            }
+        }
+    }
+
+    let extractor = create_extractor(archive)?;
+    let mut entries = Vec::new();
+    for entry in extractor.entries(archive)? {
+        match entry {
+            Ok(entry) => {
+                on_entry(entry.clone());
+                entries.push(entry);
+            }
+            Err(e) => warn!("Failed to read entry: {}", e),
+        }
+    }
+    Ok(entries)
+}
@@ -0,0 +1,500 @@
+//! Cloud storage support for the GUI
+//!
+//! Listing runs on a short-lived thread so a slow bucket never blocks the
+//! UI. Archives are listed and extracted straight from a [`CloudReader`]
+//! without a local copy; packing writes to a staging file first and then
+//! uploads it, since the archive writers need a seekable local file.
+//!
+//! Failed requests are passed through [`flux_cloud::diagnose`], so a missing
+//! or rejected credential comes with advice on what to set.
+
+use crate::progress_tracker::{Phase, ProgressTracker};
+use crate::t;
+use crate::task::{ProgressUpdate, TaskControl, TaskReporter, TaskResult, ToUi};
+use crossbeam_channel::Receiver;
+use flux_cloud::diagnostics::diagnose_io;
+use flux_cloud::{
+    diagnose, Cause, CloudError, CloudPath, CloudReader, CloudStore, CloudWriter, Diagnosis,
+};
+use flux_core::archive::extractor::ArchiveEntry;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{error, info, instrument};
+
+/// Interval between progress messages while streaming
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A "directory" or object below the current prefix
+#[derive(Debug, Clone)]
+pub struct CloudEntry {
+    /// Last path segment, shown in the list
+    pub name: String,
+    /// Full object key or prefix within the bucket
+    pub key: String,
+    /// Whether this is a common prefix rather than an object
+    pub is_dir: bool,
+    /// Object size in bytes (0 for prefixes)
+    pub size: u64,
+    /// Last modification time of the object
+    pub last_modified: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Result of listing one prefix
+pub struct CloudListing {
+    /// Store the listing came from, reused for the next request
+    pub store: CloudStore,
+    /// Location that was listed
+    pub location: CloudPath,
+    /// Prefixes first, then objects, each sorted by name
+    pub entries: Vec<CloudEntry>,
+}
+
+/// Connect to the bucket of `location`, through the endpoint of the
+/// configured profile it falls under
+fn connect(location: &CloudPath) -> flux_cloud::Result<CloudStore> {
+    let config = flux_core::config::Config::load_or_default();
+    CloudStore::with_endpoint(location, config.cloud.endpoint_for(&location.to_string()))
+}
+
+/// List `location` on a background thread
+///
+/// `store` is reused when it belongs to the same bucket; otherwise a new
+/// connection is made from the environment's credentials.
+pub fn list_location(
+    store: Option<CloudStore>,
+    location: CloudPath,
+) -> Receiver<Result<CloudListing, String>> {
+    let (sender, receiver) = crossbeam_channel::bounded(1);
+
+    std::thread::spawn(move || {
+        let result = (|| {
+            let store = match store {
+                Some(store) => store,
+                None => connect(&location).map_err(|e| {
+                    let message = t!("cloud-connect-failed", location = location, error = e);
+                    explain_cloud(message, &e, &location.scheme)
+                })?,
+            };
+
+            let prefix = (!location.path.as_ref().is_empty()).then_some(&location.path);
+            let listing = store.list(prefix).map_err(|e| {
+                let message = t!("cloud-list-failed", location = location, error = e);
+                explain_cloud(message, &e, &location.scheme)
+            })?;
+
+            let mut entries: Vec<CloudEntry> = listing
+                .common_prefixes
+                .iter()
+                .map(|prefix| CloudEntry {
+                    name: prefix.filename().unwrap_or_default().to_string(),
+                    key: prefix.to_string(),
+                    is_dir: true,
+                    size: 0,
+                    last_modified: None,
+                })
+                .collect();
+            entries.extend(listing.objects.iter().map(|object| CloudEntry {
+                name: object.location.filename().unwrap_or_default().to_string(),
+                key: object.location.to_string(),
+                is_dir: false,
+                size: object.size as u64,
+                last_modified: Some(object.last_modified),
+            }));
+            entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then(a.name.cmp(&b.name)));
+
+            Ok(CloudListing {
+                store,
+                location,
+                entries,
+            })
+        })();
+
+        let _ = sender.send(result);
+    });
+
+    receiver
+}
+
+/// Listing of an archive in cloud storage, running in the background
+///
+/// Dropping it stops the listing at the next read.
+pub struct RemoteListing {
+    url: String,
+    /// Entries found so far
+    found: Arc<AtomicUsize>,
+    stop: Arc<AtomicBool>,
+    pending: Receiver<Result<Vec<ArchiveEntry>, String>>,
+}
+
+impl RemoteListing {
+    /// Start listing the archive at `url`
+    pub fn start(url: &str) -> Result<Self, String> {
+        let url = url.trim().to_string();
+        let location =
+            CloudPath::parse(&url).map_err(|e| t!("cloud-invalid-location", error = e))?;
+        let format =
+            archive_format(&url).ok_or_else(|| t!("cloud-unsupported-format", url = url))?;
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let found = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(AtomicBool::new(false));
+        let (counter, stopped, source) = (found.clone(), stop.clone(), url.clone());
+
+        std::thread::spawn(move || {
+            let result = (|| {
+                let reader = connect(&location)
+                    .and_then(|store| CloudReader::from_store(store, location.path.clone()))
+                    .map_err(|e| {
+                        let message = t!("cloud-open-failed", url = source, error = e);
+                        explain_cloud(message, &e, &location.scheme)
+                    })?;
+                let reader = StoppableReader::new(reader, stopped);
+                flux_core::archive::list_from_reader(reader, format, |_| {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                })
+                .map_err(|e| {
+                    let message = t!("cloud-list-archive-failed", url = source, error = e);
+                    explain_core(message, &e, &location.scheme)
+                })
+            })();
+            let _ = sender.send(result);
+        });
+
+        Ok(Self {
+            url,
+            found,
+            stop,
+            pending: receiver,
+        })
+    }
+
+    /// The archive being listed
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Number of entries found so far
+    pub fn found(&self) -> usize {
+        self.found.load(Ordering::Relaxed)
+    }
+
+    /// The entries, once the listing has finished or failed
+    pub fn poll(&self) -> Option<Result<Vec<ArchiveEntry>, String>> {
+        match self.pending.try_recv() {
+            Ok(result) => Some(result),
+            Err(crossbeam_channel::TryRecvError::Empty) => None,
+            Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                Some(Err(t!("cloud-list-stopped").to_string()))
+            }
+        }
+    }
+}
+
+impl Drop for RemoteListing {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Reader that fails once `stop` is set, ending a listing nobody waits for
+pub(crate) struct StoppableReader<R> {
+    inner: R,
+    stop: Arc<AtomicBool>,
+}
+
+impl<R> StoppableReader<R> {
+    pub(crate) fn new(inner: R, stop: Arc<AtomicBool>) -> Self {
+        Self { inner, stop }
+    }
+}
+
+impl<R: Read> Read for StoppableReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.stop.load(Ordering::Relaxed) {
+            return Err(io::Error::other("Listing stopped"));
+        }
+        self.inner.read(buf)
+    }
+}
+
+impl<R: Seek> Seek for StoppableReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+/// `message` followed by advice for what `diagnosis` found, if any
+fn explain(message: String, diagnosis: &Diagnosis) -> String {
+    let provider = match diagnosis.scheme.as_str() {
+        "s3" => "Amazon S3",
+        "gs" => "Google Cloud Storage",
+        "az" | "azblob" => "Azure Blob Storage",
+        other => other,
+    };
+    let vars = diagnosis.credential_vars.join(", ");
+    let hint = match diagnosis.cause {
+        Cause::MissingCredentials => {
+            t!(
+                "cloud-hint-missing-credentials",
+                provider = provider,
+                vars = vars
+            )
+        }
+        Cause::InvalidCredentials => {
+            t!(
+                "cloud-hint-invalid-credentials",
+                provider = provider,
+                vars = vars
+            )
+        }
+        Cause::PermissionDenied => t!("cloud-hint-permission-denied").to_string(),
+        Cause::NotFound => t!("cloud-hint-not-found").to_string(),
+        Cause::InvalidPath | Cause::Other => return message,
+    };
+    format!("{}\n{}", message, hint)
+}
+
+/// Like [`explain`], for an error from flux-cloud
+fn explain_cloud(message: String, error: &CloudError, scheme: &str) -> String {
+    explain(message, &diagnose(error, scheme))
+}
+
+/// Like [`explain`], for an error from flux-core reading a cloud object
+fn explain_core(message: String, error: &flux_core::Error, scheme: &str) -> String {
+    match error.inner() {
+        flux_core::Error::Io(error) => explain(message, &diagnose_io(error, scheme)),
+        _ => message,
+    }
+}
+
+/// Archive format of an object key, as understood by
+/// [`flux_core::archive::stream::extract_from_reader`]
+pub fn archive_format(key: &str) -> Option<&'static str> {
+    flux_core::archive::archive_format(Path::new(key))
+}
+
+/// Stream an archive from cloud storage into `output_dir`
+#[instrument(skip(control, ui_sender))]
+pub fn handle_cloud_extract_task(
+    url: String,
+    output_dir: PathBuf,
+    control: TaskControl,
+    ui_sender: &TaskReporter,
+) {
+    let fail = |message: String| {
+        error!("{}", message);
+        let _ = ui_sender.send(ToUi::Log(message.clone()));
+        let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(message)));
+    };
+
+    let Some(format) = archive_format(&url) else {
+        fail(t!("cloud-unsupported-format", url = url));
+        return;
+    };
+
+    info!(url = %url, output_dir = %output_dir.display(), "Starting cloud extraction");
+    let _ = ui_sender.send(ToUi::Log(format!(
+        "Extracting {} to {}",
+        url,
+        output_dir.display()
+    )));
+
+    let scheme = url.split("://").next().unwrap_or_default();
+    let reader = match CloudPath::parse(&url)
+        .and_then(|path| CloudReader::from_store(connect(&path)?, path.path))
+    {
+        Ok(reader) => reader,
+        Err(e) => {
+            fail(explain_cloud(
+                t!("cloud-open-failed", url = url, error = e),
+                &e,
+                scheme,
+            ));
+            return;
+        }
+    };
+
+    let total = reader.size();
+    let reader = ProgressReader::new(
+        reader,
+        total,
+        t!("cloud-downloading"),
+        Phase::Download,
+        &control,
+        ui_sender,
+    );
+    let options = flux_core::security::SecurityOptions::default();
+
+    match flux_core::archive::stream::extract_from_reader(reader, format, &output_dir, &options) {
+        Ok(()) => {
+            info!("Cloud extraction completed");
+            let _ = ui_sender.send(ToUi::Log(format!("Extracted {}", url)));
+            let _ = ui_sender.send(ToUi::Finished(TaskResult::Success));
+        }
+        Err(_) if control.is_cancelled() => {
+            let _ = ui_sender.send(ToUi::Finished(TaskResult::Cancelled));
+        }
+        Err(e) => fail(explain_core(
+            t!("cloud-extract-failed", url = url, error = e),
+            &e,
+            scheme,
+        )),
+    }
+}
+
+/// Pack `inputs` into a staging file and upload it to `url`
+#[instrument(skip(options, control, ui_sender))]
+pub fn handle_cloud_pack_task(
+    inputs: Vec<PathBuf>,
+    url: String,
+    options: flux_core::archive::PackOptions,
+    control: TaskControl,
+    ui_sender: &TaskReporter,
+) {
+    let fail = |message: String| {
+        error!("{}", message);
+        let _ = ui_sender.send(ToUi::Log(message.clone()));
+        let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(message)));
+    };
+
+    let cloud_path = match CloudPath::parse(&url) {
+        Ok(path) => path,
+        Err(e) => {
+            fail(t!("cloud-invalid-destination", url = url, error = e));
+            return;
+        }
+    };
+    let Some(file_name) = cloud_path.path.filename().map(str::to_string) else {
+        fail(t!("cloud-no-object-name", url = url));
+        return;
+    };
+
+    let staging = match flux_core::space::TempResource::new() {
+        Ok(dir) => dir,
+        Err(e) => {
+            fail(t!("toast-staging-failed", error = e));
+            return;
+        }
+    };
+    let local = staging.path().join(&file_name);
+
+    if !crate::pack_archive(inputs, local.clone(), options, &control, ui_sender) {
+        return;
+    }
+    if let Err(e) = staging.check() {
+        fail(e.to_string());
+        return;
+    }
+
+    info!(url = %url, "Uploading archive");
+    let _ = ui_sender.send(ToUi::Log(format!("Uploading to {}", url)));
+
+    let result = (|| -> Result<(), String> {
+        let file = std::fs::File::open(&local).map_err(|e| e.to_string())?;
+        let total = file.metadata().map_err(|e| e.to_string())?.len();
+        let mut reader = ProgressReader::new(
+            file,
+            total,
+            t!("cloud-uploading"),
+            Phase::Upload,
+            &control,
+            ui_sender,
+        );
+
+        let store = connect(&cloud_path)
+            .map_err(|e| explain_cloud(e.to_string(), &e, &cloud_path.scheme))?;
+        let writer =
+            CloudWriter::from_store(store, cloud_path.path.clone()).map_err(|e| e.to_string())?;
+        let mut writer = flux_cloud::writer::CloudWriterGuard::new(writer);
+
+        io::copy(&mut reader, &mut writer)
+            .map_err(|e| explain(e.to_string(), &diagnose_io(&e, &cloud_path.scheme)))?;
+        writer.finish().map_err(|e| e.to_string())
+    })();
+
+    match result {
+        Ok(()) => {
+            info!("Upload completed");
+            let _ = ui_sender.send(ToUi::Log(format!("Uploaded {}", url)));
+            let _ = ui_sender.send(ToUi::Finished(TaskResult::Success));
+        }
+        Err(_) if control.is_cancelled() => {
+            let _ = ui_sender.send(ToUi::Finished(TaskResult::Cancelled));
+        }
+        Err(e) => fail(t!("cloud-upload-failed", url = url, error = e)),
+    }
+}
+
+/// Reader that reports its position as task progress, waits while the task
+/// is paused, keeps to its network limit and stops when it is cancelled
+struct ProgressReader<'a, R> {
+    inner: R,
+    position: u64,
+    total: u64,
+    label: &'static str,
+    control: &'a TaskControl,
+    ui_sender: &'a TaskReporter,
+    tracker: ProgressTracker,
+    last_update: Instant,
+}
+
+impl<'a, R> ProgressReader<'a, R> {
+    fn new(
+        inner: R,
+        total: u64,
+        label: &'static str,
+        phase: Phase,
+        control: &'a TaskControl,
+        ui_sender: &'a TaskReporter,
+    ) -> Self {
+        Self {
+            inner,
+            position: 0,
+            total,
+            label,
+            control,
+            ui_sender,
+            tracker: ui_sender.tracker(phase),
+            last_update: Instant::now(),
+        }
+    }
+
+    fn report(&mut self) {
+        if self.last_update.elapsed() < PROGRESS_INTERVAL && self.position < self.total {
+            return;
+        }
+        let (speed, eta) = self.tracker.update(self.position, self.total);
+        let _ = self.ui_sender.send(ToUi::Progress(ProgressUpdate {
+            processed_bytes: self.position,
+            total_bytes: self.total,
+            current_file: self.label.to_string(),
+            speed_bps: speed,
+            eta_seconds: eta,
+        }));
+        self.last_update = Instant::now();
+    }
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.tracker.exclude(self.control.wait_while_paused());
+        if self.control.is_cancelled() {
+            return Err(io::Error::other("Operation cancelled"));
+        }
+        let n = self.inner.read(buf)?;
+        self.control.throttle_network(n as u64);
+        self.position += n as u64;
+        self.report();
+        Ok(n)
+    }
+}
+
+impl<R: Seek> Seek for ProgressReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = self.inner.seek(pos)?;
+        Ok(self.position)
+    }
+}
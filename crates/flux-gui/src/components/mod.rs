@@ -1,5 +1,6 @@
 //! Custom UI components for Flux GUI
 
+use crate::t;
 use crate::theme::FluxTheme;
 use egui::{vec2, Color32, Context, Id, Rect, Response, Sense, Ui, Widget};
 use egui_phosphor::regular;
@@ -64,16 +65,13 @@ impl Widget for FluxButton {
 
         let padding = vec2(16.0, 8.0);
         let icon_spacing = 8.0;
+        let font = egui::FontId::proportional(14.0 * theme.appearance.font_scale);
 
         // Calculate size
         let text_size = ui.fonts(|f| {
-            f.layout_no_wrap(
-                self.text.clone(),
-                egui::FontId::proportional(14.0),
-                Color32::WHITE,
-            )
-            .rect
-            .size()
+            f.layout_no_wrap(self.text.clone(), font.clone(), Color32::WHITE)
+                .rect
+                .size()
         });
 
         let icon_size = if self.icon.is_some() { 16.0 } else { 0.0 };
@@ -170,7 +168,7 @@ impl Widget for FluxButton {
                     ),
                 egui::Align2::CENTER_CENTER,
                 &self.text,
-                egui::FontId::proportional(14.0),
+                font,
                 text_color,
             );
         }
@@ -184,6 +182,7 @@ pub struct DropZone {
     id: Id,
     text: String,
     subtext: String,
+    #[allow(dead_code)]
     accepts_multiple: bool,
 }
 
@@ -191,8 +190,8 @@ impl DropZone {
     pub fn new(id: impl Into<Id>) -> Self {
         Self {
             id: id.into(),
-            text: "Drop files here".to_string(),
-            subtext: "or click to browse".to_string(),
+            text: t!("drop-zone-text").to_string(),
+            subtext: t!("welcome-drop-browse").to_string(),
             accepts_multiple: true,
         }
     }
@@ -207,6 +206,7 @@ impl DropZone {
         self
     }
 
+    #[allow(dead_code)]
     pub fn single_file(mut self) -> Self {
         self.accepts_multiple = false;
         self
@@ -328,6 +328,7 @@ impl FluxProgress {
         self
     }
 
+    #[allow(dead_code)]
     pub fn show_percentage(mut self, show: bool) -> Self {
         self.show_percentage = show;
         self
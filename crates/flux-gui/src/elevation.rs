@@ -0,0 +1,88 @@
+//! Recovering from extractions a destination refused
+//!
+//! Extracting into a protected folder, such as `C:\Program Files`, fails
+//! with access denied. Instead of only showing the error, the user is
+//! offered to run the extraction again elevated, on Windows through the UAC
+//! prompt, or to extract into a folder of their own instead.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Whether [`relaunch_elevated`] can work on this platform
+pub fn can_elevate() -> bool {
+    cfg!(windows)
+}
+
+/// A folder the user can write to, to extract into instead of
+/// `refused`
+///
+/// Downloads first, then Documents, then the home folder, skipping the one
+/// that refused.
+pub fn fallback_dir(refused: &Path) -> Option<PathBuf> {
+    [dirs::download_dir(), dirs::document_dir(), dirs::home_dir()]
+        .into_iter()
+        .flatten()
+        .find(|dir| dir != refused && dir.is_dir())
+}
+
+/// Start another instance of the GUI as administrator extracting `archive`
+/// into `output_dir`
+///
+/// Returns once the instance is asked for; the UAC prompt and the
+/// extraction happen in it. Declining the prompt leaves nothing running.
+#[cfg(windows)]
+pub fn relaunch_elevated(archive: &Path, output_dir: &Path) -> io::Result<()> {
+    use crate::launch::EXTRACT_TO_FLAG;
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+
+    /// Keep the PowerShell window that asks for elevation from flashing up
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let exe = std::env::current_exe()?;
+    let arguments = [
+        EXTRACT_TO_FLAG.to_string(),
+        output_dir.display().to_string(),
+        archive.display().to_string(),
+    ]
+    .iter()
+    .map(|arg| windows_quote(arg))
+    .collect::<Vec<_>>()
+    .join(" ");
+    let script = format!(
+        "Start-Process -FilePath {} -Verb RunAs -ArgumentList {}",
+        powershell_quote(&exe.display().to_string()),
+        powershell_quote(&arguments)
+    );
+
+    Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn()
+        .map(|_| ())
+}
+
+/// Elevation is only offered on Windows
+#[cfg(not(windows))]
+pub fn relaunch_elevated(_archive: &Path, _output_dir: &Path) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Relaunching elevated is only supported on Windows",
+    ))
+}
+
+/// `arg` quoted for a Windows command line
+///
+/// Paths cannot contain `"`, but backslashes before the closing quote
+/// would escape it, so those are doubled.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn windows_quote(arg: &str) -> String {
+    let trailing = arg.len() - arg.trim_end_matches('\\').len();
+    format!("\"{}{}\"", arg, "\\".repeat(trailing))
+}
+
+/// `value` as a single-quoted PowerShell string
+#[cfg_attr(not(windows), allow(dead_code))]
+fn powershell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
@@ -0,0 +1,31 @@
+//! Showing files and folders in the system file manager
+
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+/// Open `dir` in the file manager
+pub fn open_folder(dir: &Path) -> io::Result<()> {
+    let program = if cfg!(windows) {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+    Command::new(program).arg(dir).spawn().map(|_| ())
+}
+
+/// Open the folder holding `file` with `file` selected, where the file
+/// manager can select; elsewhere just the folder
+pub fn reveal(file: &Path) -> io::Result<()> {
+    if cfg!(windows) {
+        let mut select = std::ffi::OsString::from("/select,");
+        select.push(file);
+        Command::new("explorer").arg(select).spawn().map(|_| ())
+    } else if cfg!(target_os = "macos") {
+        Command::new("open").arg("-R").arg(file).spawn().map(|_| ())
+    } else {
+        open_folder(file.parent().unwrap_or(file))
+    }
+}
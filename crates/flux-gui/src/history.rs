@@ -0,0 +1,186 @@
+//! Recently used archives and extraction destinations
+//!
+//! Items are recorded when a task succeeds (or an archive is opened in the
+//! browser) and shown on the Welcome view. Pinned items are never dropped.
+
+use crate::task::{TaskId, TaskResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Unpinned items kept in the history
+const MAX_RECENT: usize = 15;
+
+/// How a recent path was used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecentKind {
+    /// Archive opened in the browser
+    Opened,
+    /// Archive written by a pack task
+    Created,
+    /// Directory an archive was extracted into
+    Destination,
+}
+
+/// Everything needed to pack the same archive again
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PackRecipe {
+    /// Files and folders that were packed
+    pub inputs: Vec<PathBuf>,
+    /// Format value from the pack view, e.g. `smart` or `tar.zst`
+    pub format: String,
+    /// Compression level, if one was set
+    pub level: Option<u32>,
+    /// Worker threads, if limited
+    pub threads: Option<usize>,
+    /// Symlink targets were archived
+    pub follow_symlinks: bool,
+    /// Compression was forced for smart packing
+    pub force_compress: bool,
+    /// Exclude patterns
+    pub exclude_patterns: Vec<String>,
+    /// Version control folders were left out
+    pub ignore_vcs: bool,
+    /// Size limit for packed files in megabytes
+    pub max_file_mb: Option<u64>,
+}
+
+/// One entry in the history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentItem {
+    /// Archive or directory
+    pub path: PathBuf,
+    /// How it was used
+    pub kind: RecentKind,
+    /// When it was last used
+    pub used: DateTime<Utc>,
+    /// Kept at the top and never dropped
+    #[serde(default)]
+    pub pinned: bool,
+    /// Settings of the pack that created the archive
+    #[serde(default)]
+    pub recipe: Option<PackRecipe>,
+}
+
+/// Recent items plus the tasks that will add to them
+#[derive(Debug, Default)]
+pub struct History {
+    items: Vec<RecentItem>,
+    /// Items recorded once their task succeeds
+    pending: HashMap<TaskId, RecentItem>,
+}
+
+impl History {
+    /// Restore saved items
+    pub fn new(items: Vec<RecentItem>) -> Self {
+        let mut history = Self {
+            items,
+            pending: HashMap::new(),
+        };
+        history.trim();
+        history
+    }
+
+    /// Pinned items first, then the most recently used
+    pub fn items(&self) -> Vec<&RecentItem> {
+        let mut items: Vec<_> = self.items.iter().collect();
+        items.sort_by_key(|item| !item.pinned);
+        items
+    }
+
+    /// Saved form of the history
+    pub fn to_vec(&self) -> Vec<RecentItem> {
+        self.items.clone()
+    }
+
+    /// Look up an item
+    pub fn get(&self, path: &Path, kind: RecentKind) -> Option<&RecentItem> {
+        self.items
+            .iter()
+            .find(|item| item.path == path && item.kind == kind)
+    }
+
+    /// Record a use of `path` now, keeping its pin
+    pub fn record(&mut self, path: PathBuf, kind: RecentKind, recipe: Option<PackRecipe>) {
+        self.insert(RecentItem {
+            path,
+            kind,
+            used: Utc::now(),
+            pinned: false,
+            recipe,
+        });
+    }
+
+    /// Record `path` once `task_id` succeeds
+    pub fn record_on_success(
+        &mut self,
+        task_id: TaskId,
+        path: PathBuf,
+        kind: RecentKind,
+        recipe: Option<PackRecipe>,
+    ) {
+        self.pending.insert(
+            task_id,
+            RecentItem {
+                path,
+                kind,
+                used: Utc::now(),
+                pinned: false,
+                recipe,
+            },
+        );
+    }
+
+    /// Add the pending item of a finished task if it succeeded
+    pub fn finish_task(&mut self, task_id: TaskId, result: &TaskResult) {
+        if let Some(mut item) = self.pending.remove(&task_id) {
+            if matches!(result, TaskResult::Success) {
+                item.used = Utc::now();
+                self.insert(item);
+            }
+        }
+    }
+
+    /// Pin or unpin an item
+    pub fn toggle_pin(&mut self, path: &Path, kind: RecentKind) {
+        if let Some(item) = self
+            .items
+            .iter_mut()
+            .find(|item| item.path == path && item.kind == kind)
+        {
+            item.pinned = !item.pinned;
+        }
+    }
+
+    /// Forget an item
+    pub fn remove(&mut self, path: &Path, kind: RecentKind) {
+        self.items
+            .retain(|item| !(item.path == path && item.kind == kind));
+    }
+
+    fn insert(&mut self, mut item: RecentItem) {
+        if let Some(idx) = self
+            .items
+            .iter()
+            .position(|existing| existing.path == item.path && existing.kind == item.kind)
+        {
+            item.pinned = self.items.remove(idx).pinned;
+        }
+        self.items.push(item);
+        self.trim();
+    }
+
+    /// Drop the oldest unpinned items beyond [`MAX_RECENT`]
+    fn trim(&mut self) {
+        self.items.sort_by_key(|item| std::cmp::Reverse(item.used));
+        let mut unpinned = 0;
+        self.items.retain(|item| {
+            if !item.pinned {
+                unpinned += 1;
+            }
+            item.pinned || unpinned <= MAX_RECENT
+        });
+    }
+}
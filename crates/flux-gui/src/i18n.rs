@@ -0,0 +1,248 @@
+//! Translations of user-facing strings
+//!
+//! Messages live in Fluent-style resource files under `locales/`, one
+//! `key = value` pair per line. Values may reference arguments as
+//! `{ $name }` and continue on following lines that start with whitespace.
+//! Lookups fall back to English, then to the key itself, so a missing
+//! translation shows up as readable text instead of a blank label.
+//!
+//! Only what the user reads in the interface is translated. The log panel
+//! and tracing output stay in English so they can be searched and pasted
+//! into bug reports.
+//!
+//! Use the [`t!`](crate::t) macro to look up a message:
+//!
+//! ```ignore
+//! ui.label(t!("settings-title"));
+//! ui.label(t!("window-title-active", title = "Flux", count = 2));
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+/// Languages the interface is translated into
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Chinese,
+}
+
+impl Language {
+    /// Every supported language, in the order shown in Settings
+    pub const ALL: [Language; 2] = [Language::English, Language::Chinese];
+
+    /// Name of the language written in that language
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Chinese => "简体中文",
+        }
+    }
+
+    /// Whether the language needs a CJK font to render
+    pub fn needs_cjk_font(self) -> bool {
+        matches!(self, Language::Chinese)
+    }
+
+    /// Language picked from the environment's locale, used on first start
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .filter(|value| value.to_lowercase().starts_with("zh"))
+            .map_or(Language::English, |_| Language::Chinese)
+    }
+
+    fn resource(self) -> &'static str {
+        match self {
+            Language::English => include_str!("../locales/en.ftl"),
+            Language::Chinese => include_str!("../locales/zh-CN.ftl"),
+        }
+    }
+
+    fn index(self) -> u8 {
+        match self {
+            Language::English => 0,
+            Language::Chinese => 1,
+        }
+    }
+}
+
+/// Messages of every language, indexed like [`Language::ALL`]
+type Bundles = Vec<HashMap<&'static str, String>>;
+
+static BUNDLES: OnceLock<Bundles> = OnceLock::new();
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+fn bundles() -> &'static Bundles {
+    BUNDLES.get_or_init(|| {
+        Language::ALL
+            .iter()
+            .map(|language| parse(language.resource()))
+            .collect()
+    })
+}
+
+/// Parse a resource file into its messages
+fn parse(source: &'static str) -> HashMap<&'static str, String> {
+    let mut messages: HashMap<&'static str, String> = HashMap::new();
+    let mut current: Option<&'static str> = None;
+    // Blank lines inside a multiline value are kept, trailing ones are not
+    let mut blank_lines = 0;
+
+    for line in source.lines() {
+        if line.trim_start().starts_with('#') {
+            continue;
+        }
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if line.starts_with([' ', '\t']) {
+            // Continuation of the previous message
+            if let Some(value) = current.and_then(|key| messages.get_mut(key)) {
+                if !value.is_empty() {
+                    value.push_str(&"\n".repeat(blank_lines + 1));
+                }
+                value.push_str(line.trim());
+            }
+            blank_lines = 0;
+            continue;
+        }
+
+        current = None;
+        blank_lines = 0;
+        if let Some((key, value)) = line.split_once('=') {
+            let key = key.trim();
+            if !key.is_empty() {
+                messages.insert(key, value.trim().to_string());
+                current = Some(key);
+            }
+        }
+    }
+
+    messages
+}
+
+/// Switch the interface to `language`
+pub fn set_language(language: Language) {
+    CURRENT.store(language.index(), Ordering::Relaxed);
+}
+
+/// Language the interface is currently shown in
+pub fn language() -> Language {
+    Language::ALL
+        .into_iter()
+        .find(|language| language.index() == CURRENT.load(Ordering::Relaxed))
+        .unwrap_or_default()
+}
+
+/// Message `key` in the current language
+pub fn tr(key: &'static str) -> &'static str {
+    let bundles = bundles();
+    [language(), Language::English]
+        .iter()
+        .find_map(|language| bundles[language.index() as usize].get(key))
+        .map_or(key, String::as_str)
+}
+
+/// Message `key` in the current language with its `{ $name }` arguments
+/// replaced
+pub fn tr_args(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    let template = tr(key);
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        result.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let placeholder = &rest[start + 1..start + end];
+        let name = placeholder.trim().trim_start_matches('$');
+        match args.iter().find(|(arg, _)| *arg == name) {
+            Some((_, value)) => result.push_str(&value.to_string()),
+            None => result.push_str(&rest[start..=start + end]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    result.push_str(rest);
+
+    result
+}
+
+/// Look up a translated message, optionally filling in arguments
+///
+/// `t!("key")` returns a `&'static str`; `t!("key", name = value, ...)`
+/// returns a `String`.
+#[macro_export]
+macro_rules! t {
+    ($key:literal) => {
+        $crate::i18n::tr($key)
+    };
+    ($key:literal, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::i18n::tr_args(
+            $key,
+            &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),+],
+        )
+    };
+}
+
+/// Font files that cover Chinese, looked for in order
+const CJK_FONTS: &[&str] = &[
+    // Linux
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/wqy/wqy-microhei.ttc",
+    "/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+    // macOS
+    "/System/Library/Fonts/PingFang.ttc",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/System/Library/Fonts/STHeiti Light.ttc",
+    // Windows
+    "C:\\Windows\\Fonts\\msyh.ttc",
+    "C:\\Windows\\Fonts\\simhei.ttf",
+    "C:\\Windows\\Fonts\\simsun.ttc",
+];
+
+/// Install the application fonts, adding a system CJK font as a fallback
+/// when `language` needs one
+///
+/// egui's bundled fonts have no Chinese glyphs, and shipping one would add
+/// many megabytes to the binary, so the font is borrowed from the system.
+pub fn install_fonts(ctx: &egui::Context, language: Language) {
+    let mut fonts = egui::FontDefinitions::default();
+    egui_phosphor::add_to_fonts(&mut fonts, egui_phosphor::Variant::Regular);
+
+    if language.needs_cjk_font() {
+        match CJK_FONTS
+            .iter()
+            .find_map(|path| std::fs::read(path).ok().map(|data| (path, data)))
+        {
+            Some((path, data)) => {
+                tracing::debug!("Using {} for CJK text", path);
+                fonts
+                    .font_data
+                    .insert("cjk".to_string(), egui::FontData::from_owned(data));
+                for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+                    fonts
+                        .families
+                        .entry(family)
+                        .or_default()
+                        .push("cjk".to_string());
+                }
+            }
+            None => tracing::warn!("No CJK font found; Chinese text may not render"),
+        }
+    }
+
+    ctx.set_fonts(fonts);
+}
@@ -0,0 +1,70 @@
+//! Command line arguments the GUI is started with
+//!
+//! File manager context menu entries (see `flux integrate`) start the GUI
+//! with `--extract` or `--compress` followed by the selected paths. Paths
+//! without a flag are treated like files dropped on the window.
+//!
+//! `--extract-to DIR ARCHIVE` extracts straight into DIR without asking.
+//! The GUI starts itself that way, elevated, when a destination refused
+//! an extraction (see [`crate::elevation`]).
+
+use flux_core::shell_integration::ShellAction;
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Flag naming the directory to extract into, followed by the archive
+pub const EXTRACT_TO_FLAG: &str = "--extract-to";
+
+/// What the GUI was asked to do on startup
+#[derive(Debug, Clone, PartialEq)]
+pub enum LaunchRequest {
+    /// Extract these archives
+    Extract(Vec<PathBuf>),
+    /// Extract an archive into a directory chosen already
+    ExtractTo {
+        archive: PathBuf,
+        output_dir: PathBuf,
+    },
+    /// Pack these files and folders
+    Compress(Vec<PathBuf>),
+    /// Open these paths as if they were dropped on the window
+    Open(Vec<PathBuf>),
+}
+
+impl LaunchRequest {
+    /// Parse the arguments after the program name
+    ///
+    /// Returns `None` when no paths were given.
+    pub fn parse<I: IntoIterator<Item = OsString>>(args: I) -> Option<Self> {
+        let mut action = None;
+        let mut paths = Vec::new();
+        let mut args = args.into_iter();
+        let mut output_dir = None;
+
+        while let Some(arg) = args.next() {
+            if arg.to_str() == Some(EXTRACT_TO_FLAG) {
+                output_dir = args.next().map(PathBuf::from);
+                continue;
+            }
+            match arg.to_str().and_then(ShellAction::from_flag) {
+                Some(flag) => action = Some(flag),
+                None => paths.push(PathBuf::from(arg)),
+            }
+        }
+
+        if paths.is_empty() {
+            return None;
+        }
+        if let (Some(output_dir), [archive]) = (output_dir, paths.as_slice()) {
+            return Some(LaunchRequest::ExtractTo {
+                archive: archive.clone(),
+                output_dir,
+            });
+        }
+        Some(match action {
+            Some(ShellAction::Extract) => LaunchRequest::Extract(paths),
+            Some(ShellAction::Compress) => LaunchRequest::Compress(paths),
+            None => LaunchRequest::Open(paths),
+        })
+    }
+}
@@ -1,6 +1,7 @@
 //! Modern layout system for Flux GUI with sidebar navigation
 
 use crate::app::AppView;
+use crate::t;
 use crate::theme::FluxTheme;
 use egui::{vec2, Color32, Context, Id, Rect, Response, Sense, Ui};
 use egui_phosphor::regular;
@@ -22,37 +23,51 @@ impl NavItem {
             NavItem {
                 id: "welcome",
                 icon: regular::HOUSE,
-                label: "Home",
+                label: t!("nav-home"),
                 view: AppView::Welcome,
-                tooltip: "Start screen",
+                tooltip: t!("nav-home-tooltip"),
             },
             NavItem {
                 id: "pack",
                 icon: regular::PACKAGE,
-                label: "Pack",
+                label: t!("nav-pack"),
                 view: AppView::Packing,
-                tooltip: "Create archives",
+                tooltip: t!("nav-pack-tooltip"),
             },
             NavItem {
                 id: "extract",
                 icon: regular::FOLDER_OPEN,
-                label: "Extract",
+                label: t!("nav-extract"),
                 view: AppView::Extracting,
-                tooltip: "Extract archives",
+                tooltip: t!("nav-extract-tooltip"),
             },
             NavItem {
                 id: "browse",
                 icon: regular::BINOCULARS,
-                label: "Browse",
+                label: t!("nav-browse"),
                 view: AppView::Browsing,
-                tooltip: "Browse archive contents",
+                tooltip: t!("nav-browse-tooltip"),
             },
             NavItem {
                 id: "sync",
                 icon: regular::ARROW_SQUARE_OUT,
-                label: "Sync",
+                label: t!("nav-sync"),
                 view: AppView::Syncing,
-                tooltip: "Incremental backup",
+                tooltip: t!("nav-sync-tooltip"),
+            },
+            NavItem {
+                id: "cloud",
+                icon: regular::CLOUD,
+                label: t!("nav-cloud"),
+                view: AppView::Cloud,
+                tooltip: t!("nav-cloud-tooltip"),
+            },
+            NavItem {
+                id: "schedules",
+                icon: regular::CALENDAR_CHECK,
+                label: t!("nav-schedules"),
+                view: AppView::Schedules,
+                tooltip: t!("nav-schedules-tooltip"),
             },
         ]
     }
@@ -177,16 +192,16 @@ impl Sidebar {
                             &NavItem {
                                 id: "settings",
                                 icon: regular::GEAR,
-                                label: "Settings",
-                                view: AppView::Welcome, // Will implement Settings view later
-                                tooltip: "Application settings",
+                                label: t!("nav-settings"),
+                                view: AppView::Settings,
+                                tooltip: t!("nav-settings-tooltip"),
                             },
-                            false,
+                            *current_view == AppView::Settings,
                             theme,
                         );
 
                         if settings_response.clicked() {
-                            // TODO: Open settings dialog
+                            *current_view = AppView::Settings;
                         }
                     });
                 });
@@ -378,7 +393,7 @@ pub fn draw_file_card(
                     egui::RichText::new(
                         path.file_name()
                             .and_then(|n| n.to_str())
-                            .unwrap_or("Unknown"),
+                            .unwrap_or(t!("common-unknown")),
                     )
                     .strong(),
                 );
@@ -407,7 +422,7 @@ pub fn draw_file_card(
                     on_remove();
                 }
 
-                remove_btn.on_hover_text("Remove from list");
+                remove_btn.on_hover_text(t!("pack-remove-from-list"));
             });
         });
     });
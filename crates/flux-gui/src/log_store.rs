@@ -0,0 +1,195 @@
+//! Log lines shown in the log panel
+//!
+//! Lines are kept in a ring buffer, so a long job pushes out the oldest
+//! ones instead of the panel growing without bound. Every line has a
+//! sequence number that never changes, which lets the panel keep the
+//! lines matching its filter and only look at new ones each frame. Lines
+//! a task logs can also be appended to a file of their own, so nothing of
+//! a long job is lost to the buffer.
+
+use crate::task::TaskId;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use tracing::Level;
+
+/// Most lines kept in memory
+pub const CAPACITY: usize = 100_000;
+
+/// Directory task logs are written to
+pub fn log_dir() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|dir| dir.join("flux").join("logs"))
+}
+
+/// Lines matching the panel's filter, as sequence numbers
+#[derive(Debug, Default)]
+struct Matches {
+    text: String,
+    level: Option<Level>,
+    /// Sequence number of the first line not looked at yet
+    scanned: u64,
+    lines: Vec<u64>,
+}
+
+/// Log lines with their levels, oldest first
+#[derive(Default)]
+pub struct LogStore {
+    lines: VecDeque<(Level, String)>,
+    /// Sequence number of the oldest line kept
+    first: u64,
+    /// Lines kept at each level, indexed by [`level_index`]
+    counts: [usize; 5],
+    matches: Matches,
+    /// Where task logs are written, when they are
+    spill_dir: Option<PathBuf>,
+    /// Open task log files; `None` once one could not be written
+    files: HashMap<TaskId, Option<BufWriter<File>>>,
+}
+
+impl LogStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a line, dropping the oldest once [`CAPACITY`] is reached
+    pub fn push(&mut self, level: Level, text: String) {
+        if self.lines.len() == CAPACITY {
+            if let Some((dropped, _)) = self.lines.pop_front() {
+                self.counts[level_index(dropped)] -= 1;
+                self.first += 1;
+            }
+        }
+        self.counts[level_index(level)] += 1;
+        self.lines.push_back((level, text));
+    }
+
+    /// Add a line logged by `task`, appending it to the task's file when
+    /// task logs are saved
+    pub fn push_task(&mut self, task: TaskId, level: Level, text: String) {
+        if let Some(dir) = &self.spill_dir {
+            let file = self
+                .files
+                .entry(task)
+                .or_insert_with(|| open_task_log(dir, task));
+            if let Some(writer) = file {
+                if let Err(e) = writeln!(writer, "[{}] {}", level, text) {
+                    tracing::warn!("Stopped saving the log of task {}: {}", task, e);
+                    *file = None;
+                }
+            }
+        }
+        self.push(level, text);
+    }
+
+    /// Save task logs to `dir` from now on, or stop saving them
+    pub fn set_spill_dir(&mut self, dir: Option<PathBuf>) {
+        if self.spill_dir != dir {
+            self.spill_dir = dir;
+            self.files.clear();
+        }
+    }
+
+    /// Close the log file of `task`, which logs nothing more
+    pub fn finish_task(&mut self, task: TaskId) {
+        if let Some(Some(mut writer)) = self.files.remove(&task) {
+            let _ = writer.flush();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// Lines kept at `level`
+    pub fn count(&self, level: Level) -> usize {
+        self.counts[level_index(level)]
+    }
+
+    /// Drop every line kept in memory; task log files are left as they
+    /// are
+    pub fn clear(&mut self) {
+        self.first += self.lines.len() as u64;
+        self.lines.clear();
+        self.counts = [0; 5];
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(Level, String)> {
+        self.lines.iter()
+    }
+
+    /// Line with sequence number `seq`, unless it was dropped
+    pub fn get(&self, seq: u64) -> Option<&(Level, String)> {
+        let index = seq.checked_sub(self.first)?;
+        self.lines.get(usize::try_from(index).ok()?)
+    }
+
+    /// Match the lines at `level` containing `text`, ignoring case; an
+    /// empty `text` and a `None` level match everything
+    ///
+    /// Only lines added since the last call are looked at, unless the
+    /// filter changed.
+    pub fn filter(&mut self, text: &str, level: Option<Level>) {
+        let text = text.to_lowercase();
+        let matches = &mut self.matches;
+        if matches.text != text || matches.level != level {
+            *matches = Matches {
+                text,
+                level,
+                ..Matches::default()
+            };
+        }
+
+        let dropped = matches.lines.partition_point(|&seq| seq < self.first);
+        matches.lines.drain(..dropped);
+
+        let start = matches.scanned.max(self.first);
+        let skip = usize::try_from(start - self.first).unwrap_or(usize::MAX);
+        for (seq, (line_level, line)) in (start..).zip(self.lines.iter().skip(skip)) {
+            if matches.level.is_some_and(|wanted| wanted != *line_level) {
+                continue;
+            }
+            if matches.text.is_empty() || line.to_lowercase().contains(&matches.text) {
+                matches.lines.push(seq);
+            }
+        }
+        matches.scanned = self.first + self.lines.len() as u64;
+    }
+
+    /// Sequence numbers of the lines matched by the last [`LogStore::filter`]
+    pub fn matches(&self) -> &[u64] {
+        &self.matches.lines
+    }
+}
+
+/// Position of `level` in [`LogStore::counts`]
+fn level_index(level: Level) -> usize {
+    match level {
+        Level::ERROR => 0,
+        Level::WARN => 1,
+        Level::INFO => 2,
+        Level::DEBUG => 3,
+        Level::TRACE => 4,
+    }
+}
+
+/// Create the log file of `task` in `dir`, named after when it started
+fn open_task_log(dir: &std::path::Path, task: TaskId) -> Option<BufWriter<File>> {
+    let name = format!(
+        "{}-task-{}.log",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        task
+    );
+    let opened = fs::create_dir_all(dir).and_then(|()| File::create(dir.join(&name)));
+    match opened {
+        Ok(file) => Some(BufWriter::new(file)),
+        Err(e) => {
+            tracing::warn!("Cannot save the log of task {} in {:?}: {}", task, dir, e);
+            None
+        }
+    }
+}
@@ -1,45 +1,69 @@
 //! Flux GUI - A modern graphical interface for the Flux archiver
 
-use crossbeam_channel::Sender;
-use flux_core::utils::calculate_path_size;
-use std::path::PathBuf;
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
-};
+use flux_core::archive::OperationOutcome;
+use flux_core::ops::{ExtractJob, PackJob};
+use flux_core::progress::{NoProgress, ProgressSink};
+use flux_core::ErrorCode;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tracing::{debug, error, info, instrument, warn};
 
 mod app;
+mod archive_index;
+mod cloud;
 mod components;
+mod elevation;
+mod file_manager;
+mod history;
+mod i18n;
+mod launch;
 mod layout;
+mod log_store;
 mod logging;
+mod preflight;
 mod progress_tracker;
+mod queue;
+mod scheduler;
+mod session;
+mod settings;
+mod shortcuts;
+mod smart;
 mod task;
+mod task_history;
 mod theme;
 mod views;
 
 use app::FluxApp;
-use progress_tracker::ProgressTracker;
-use task::{ProgressUpdate, TaskResult, ToUi};
+use progress_tracker::{Phase, ProgressTracker};
+use task::{PasswordRequest, ProgressUpdate, TaskControl, TaskReporter, TaskResult, ToUi};
 
 /// Handle pack task in background thread
-#[instrument(skip(ui_sender, cancel_flag, options))]
+#[instrument(skip(ui_sender, control, options))]
 pub fn handle_pack_task(
     inputs: Vec<PathBuf>,
     output: PathBuf,
     options: flux_core::archive::PackOptions,
-    cancel_flag: Arc<AtomicBool>,
-    ui_sender: &Sender<ToUi>,
+    control: TaskControl,
+    ui_sender: &TaskReporter,
 ) {
-    if inputs.is_empty() {
-        error!("No input files provided");
-        let _ = ui_sender.send(ToUi::Log("Error: No input files provided".to_string()));
-        let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(
-            "No input files".to_string(),
-        )));
-        return;
+    if pack_archive(inputs, output, options, &control, ui_sender) {
+        let _ = ui_sender.send(ToUi::Finished(TaskResult::Success));
     }
+}
 
+/// Pack `inputs` into `output`, reporting progress to the UI
+///
+/// Returns false if packing failed; the error has already been sent as the
+/// task result.
+pub fn pack_archive(
+    inputs: Vec<PathBuf>,
+    output: PathBuf,
+    options: flux_core::archive::PackOptions,
+    control: &TaskControl,
+    ui_sender: &TaskReporter,
+) -> bool {
     info!(files = inputs.len(), output = %output.display(), "Starting pack operation");
     let _ = ui_sender.send(ToUi::Log(format!(
         "Starting pack operation: {} files to {}",
@@ -47,139 +71,28 @@ pub fn handle_pack_task(
         output.display()
     )));
 
-    // Calculate total size of all input files for progress tracking
-    let mut total_size: u64 = 0;
-    let mut file_sizes: Vec<(PathBuf, u64)> = Vec::new();
-
-    for input in &inputs {
-        let size = calculate_path_size(input);
-        total_size += size;
-        file_sizes.push((input.clone(), size));
-        debug!(path = %input.display(), size_mb = size as f64 / (1024.0 * 1024.0), "Input file");
-        let _ = ui_sender.send(ToUi::Log(format!(
-            "Input: {} ({:.2} MB)",
-            input.display(),
-            size as f64 / (1024.0 * 1024.0)
-        )));
-    }
-
-    info!(
-        total_size_mb = total_size as f64 / (1024.0 * 1024.0),
-        "Total size calculated"
-    );
-    let _ = ui_sender.send(ToUi::Log(format!(
-        "Total size: {:.2} MB",
-        total_size as f64 / (1024.0 * 1024.0)
-    )));
-
-    let mut processed_size: u64 = 0;
-    let mut progress_tracker = ProgressTracker::new();
-
-    // Send initial progress
-    let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
-        processed_bytes: 0,
-        total_bytes: total_size,
-        current_file: "Preparing to pack...".to_string(),
-        speed_bps: 0.0,
-        eta_seconds: None,
-    }));
-
-    // Handle different compression formats
-    match output.extension().and_then(|e| e.to_str()) {
-        Some("zip") => {
-            // For ZIP files, we'll pack each file individually
-            info!("Creating ZIP archive");
-            let _ = ui_sender.send(ToUi::Log("Creating ZIP archive...".to_string()));
-            if let Err(e) = pack_multiple_zip(
-                &inputs,
-                &output,
-                ui_sender,
-                &mut processed_size,
-                total_size,
-                options.follow_symlinks,
-                &cancel_flag,
-                &mut progress_tracker,
-            ) {
-                error!(error = %e, "Error creating ZIP");
-                let _ = ui_sender.send(ToUi::Log(format!("Error creating ZIP: {}", e)));
-                let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(e.to_string())));
-                return;
-            }
-        }
-        Some(ext) => {
-            // For tar-based formats, check if it's a compound extension
-            let filename = output.file_name().and_then(|n| n.to_str()).unwrap_or("");
-
-            if filename.ends_with(".tar.gz")
-                || filename.ends_with(".tar.zst")
-                || filename.ends_with(".tar.xz")
-                || filename.ends_with(".tar.br")
-            {
-                // Pack to compressed tar
-                if let Err(e) = pack_multiple_tar_compressed(
-                    &inputs,
-                    &output,
-                    ui_sender,
-                    &mut processed_size,
-                    total_size,
-                    options,
-                    &cancel_flag,
-                    &mut progress_tracker,
-                ) {
-                    let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(e.to_string())));
-                    return;
-                }
-            } else if ext == "tar" {
-                // Pack to uncompressed tar
-                if let Err(e) = pack_multiple_tar(
-                    &inputs,
-                    &output,
-                    ui_sender,
-                    &mut processed_size,
-                    total_size,
-                    options.follow_symlinks,
-                    &cancel_flag,
-                    &mut progress_tracker,
-                ) {
-                    let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(e.to_string())));
-                    return;
-                }
+    let progress = TaskProgress::new(TaskKind::Pack, control, ui_sender);
+    progress.send(true);
+    // Packed as what the output is named for, as the CLI does
+    let job = PackJob {
+        format: flux_core::archive::detect::output_format(&output).map(str::to_string),
+        ..PackJob::new(inputs, &output, options)
+    };
+    let outcome = match job.run(&progress) {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            if control.is_cancelled() {
+                let _ = ui_sender.send(ToUi::Finished(TaskResult::Cancelled));
             } else {
-                // Fallback to single file packing for other formats
-                if inputs.len() == 1 {
-                    match flux_core::archive::pack_with_strategy(&inputs[0], &output, None, options)
-                    {
-                        Ok(_) => {
-                            let (speed, _) = progress_tracker.update(total_size, total_size);
-                            let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
-                                processed_bytes: total_size,
-                                total_bytes: total_size,
-                                current_file: "Packing complete".to_string(),
-                                speed_bps: speed,
-                                eta_seconds: None,
-                            }));
-                        }
-                        Err(e) => {
-                            let _ =
-                                ui_sender.send(ToUi::Finished(TaskResult::Error(e.to_string())));
-                            return;
-                        }
-                    }
-                } else {
-                    let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(
-                        "Multiple files can only be packed into tar or zip archives".to_string(),
-                    )));
-                    return;
-                }
+                error!(error = %e, "Error creating archive");
+                let _ = ui_sender.send(ToUi::Log(format!("Error creating archive: {}", e)));
+                let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(e.to_string())));
             }
+            return false;
         }
-        None => {
-            let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(
-                "Output file must have an extension".to_string(),
-            )));
-            return;
-        }
-    }
+    };
+    progress.finish();
+    log_outcome(&outcome, ui_sender);
 
     // Get final file size
     if let Ok(metadata) = std::fs::metadata(&output) {
@@ -191,397 +104,344 @@ pub fn handle_pack_task(
         )));
     }
 
-    let _ = ui_sender.send(ToUi::Finished(TaskResult::Success));
-}
-
-/// Pack multiple files into a tar archive
-#[instrument(skip(ui_sender, cancel_flag, progress_tracker))]
-fn pack_multiple_tar(
-    inputs: &[PathBuf],
-    output: &PathBuf,
-    ui_sender: &Sender<ToUi>,
-    processed_size: &mut u64,
-    total_size: u64,
-    follow_symlinks: bool,
-    cancel_flag: &Arc<AtomicBool>,
-    progress_tracker: &mut ProgressTracker,
-) -> Result<(), Box<dyn std::error::Error>> {
-    use flux_core::archive::tar;
-
-    // Find common base directory for relative paths
-    let base_dir = find_common_base_dir(inputs);
-
-    // Send progress updates periodically
-    for input in inputs {
-        // Check for cancellation
-        if cancel_flag.load(Ordering::SeqCst) {
-            let _ = ui_sender.send(ToUi::Finished(TaskResult::Cancelled));
-            return Err("Operation cancelled".into());
+    match outcome_result(&outcome) {
+        TaskResult::Success => true,
+        result => {
+            let _ = ui_sender.send(ToUi::Finished(result));
+            false
         }
+    }
+}
 
-        let (speed, eta) = progress_tracker.update(*processed_size, total_size);
-        let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
-            processed_bytes: *processed_size,
-            total_bytes: total_size,
-            current_file: format!("Adding: {}", input.display()),
-            speed_bps: speed,
-            eta_seconds: eta,
-        }));
+/// Send the entries that failed, and a count of all of them, to the log,
+/// and the report of the outcome to the task history
+fn log_outcome(outcome: &OperationOutcome, ui_sender: &TaskReporter) {
+    for failure in &outcome.failed {
+        let _ = ui_sender.send(ToUi::Log(format!(
+            "Failed on {}: {}",
+            failure.path.display(),
+            failure.error
+        )));
+    }
+    let _ = ui_sender.send(ToUi::Log(format!("Entries: {}", outcome)));
+    let _ = ui_sender.send(ToUi::Report(outcome.report()));
+}
 
-        *processed_size += calculate_path_size(input);
+/// The task result for an outcome: the error itself when one entry failed,
+/// otherwise how many did
+fn outcome_result(outcome: &OperationOutcome) -> TaskResult {
+    match outcome.failed.as_slice() {
+        [] => TaskResult::Success,
+        [failure] => TaskResult::Error(t!(
+            "worker-entry-failed",
+            path = failure.path.display(),
+            error = failure.error
+        )),
+        failed => TaskResult::Error(t!(
+            "worker-entries-failed",
+            failed = failed.len(),
+            total = outcome.done.len() + failed.len()
+        )),
     }
+}
 
-    // Pack all files
-    tar::pack_multiple_files(inputs, output, base_dir.as_deref(), follow_symlinks)?;
+/// Minimum time between progress updates sent while a job runs
+const PROGRESS_UPDATE_INTERVAL: Duration = Duration::from_millis(100);
 
-    Ok(())
+/// Which job a [`TaskProgress`] reports on, for its labels and phases
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TaskKind {
+    Pack,
+    Extract,
 }
 
-/// Pack multiple files into a compressed tar archive
-#[instrument(skip(ui_sender, cancel_flag, progress_tracker, options))]
-fn pack_multiple_tar_compressed(
-    inputs: &[PathBuf],
-    output: &PathBuf,
-    ui_sender: &Sender<ToUi>,
-    processed_size: &mut u64,
-    total_size: u64,
-    options: flux_core::archive::PackOptions,
-    cancel_flag: &Arc<AtomicBool>,
-    progress_tracker: &mut ProgressTracker,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // First create uncompressed tar in memory or temp file
-    let temp_tar = output.with_extension("tar.tmp");
-
-    // Pack to temporary tar file
-    pack_multiple_tar(
-        inputs,
-        &temp_tar,
-        ui_sender,
-        processed_size,
-        total_size,
-        options.follow_symlinks,
-        cancel_flag,
-        progress_tracker,
-    )?;
-
-    // Now compress the tar file
-    let (speed, eta) = progress_tracker.update(*processed_size, total_size);
-    let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
-        processed_bytes: *processed_size,
-        total_bytes: total_size,
-        current_file: "Compressing archive...".to_string(),
-        speed_bps: speed,
-        eta_seconds: eta,
-    }));
-
-    // Use pack_with_strategy to compress the tar file
-    match flux_core::archive::pack_with_strategy(&temp_tar, output, None, options) {
-        Ok(_) => {
-            // Clean up temp file
-            let _ = std::fs::remove_file(&temp_tar);
-            Ok(())
-        }
-        Err(e) => {
-            // Clean up temp file
-            let _ = std::fs::remove_file(&temp_tar);
-            Err(e.into())
-        }
-    }
+/// Passes progress from the core jobs on to the UI
+///
+/// Core calls in from the worker thread between chunks of file data, so
+/// this is also where a paused task is held and a cancelled one stopped.
+struct TaskProgress<'a> {
+    kind: TaskKind,
+    control: &'a TaskControl,
+    ui_sender: &'a TaskReporter,
+    total: AtomicU64,
+    processed: AtomicU64,
+    state: Mutex<TaskProgressState>,
 }
 
-/// Pack multiple files into a ZIP archive
-#[instrument(skip(ui_sender, cancel_flag, progress_tracker))]
-fn pack_multiple_zip(
-    inputs: &[PathBuf],
-    output: &PathBuf,
-    ui_sender: &Sender<ToUi>,
-    processed_size: &mut u64,
-    total_size: u64,
-    follow_symlinks: bool,
-    cancel_flag: &Arc<AtomicBool>,
-    progress_tracker: &mut ProgressTracker,
-) -> Result<(), Box<dyn std::error::Error>> {
-    // For ZIP, we'll create a temporary directory and copy all files there,
-    // then use flux_core to pack them
-    use std::fs;
-    use tempfile::TempDir;
-
-    // Create a temporary directory
-    let temp_dir = TempDir::new()?;
-    let temp_path = temp_dir.path();
-
-    // Copy all input files to the temp directory
-    for (idx, input) in inputs.iter().enumerate() {
-        // Check for cancellation
-        if cancel_flag.load(Ordering::SeqCst) {
-            let _ = ui_sender.send(ToUi::Finished(TaskResult::Cancelled));
-            return Err("Operation cancelled".into());
+struct TaskProgressState {
+    tracker: ProgressTracker,
+    current_file: String,
+    last_sent: Option<Instant>,
+    /// Entries an extraction will write, and how many it has started
+    entries: usize,
+    entry: usize,
+}
+
+impl<'a> TaskProgress<'a> {
+    fn new(kind: TaskKind, control: &'a TaskControl, ui_sender: &'a TaskReporter) -> Self {
+        let (phase, label) = match kind {
+            TaskKind::Pack => (Phase::Scan, t!("worker-preparing-pack")),
+            TaskKind::Extract => (Phase::Extract, t!("worker-opening-archive")),
+        };
+        Self {
+            kind,
+            control,
+            ui_sender,
+            total: AtomicU64::new(0),
+            processed: AtomicU64::new(0),
+            state: Mutex::new(TaskProgressState {
+                tracker: ui_sender.tracker(phase),
+                current_file: label.to_string(),
+                last_sent: None,
+                entries: 0,
+                entry: 0,
+            }),
         }
+    }
 
-        let (speed, eta) = progress_tracker.update(*processed_size, total_size);
-        let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
-            processed_bytes: *processed_size,
-            total_bytes: total_size,
-            current_file: format!("Preparing: {}", input.display()),
+    /// Send the current progress, at most once per interval unless `force`
+    fn send(&self, force: bool) {
+        let mut state = self.state.lock().unwrap();
+        if !force
+            && state
+                .last_sent
+                .is_some_and(|sent| sent.elapsed() < PROGRESS_UPDATE_INTERVAL)
+        {
+            return;
+        }
+        state.last_sent = Some(Instant::now());
+
+        let processed = self.processed.load(Ordering::Relaxed);
+        let total = self.total.load(Ordering::Relaxed);
+        let (speed, eta) = state.tracker.update(processed, total);
+        let _ = self.ui_sender.send(ToUi::Progress(ProgressUpdate {
+            processed_bytes: processed,
+            total_bytes: total,
+            current_file: state.current_file.clone(),
             speed_bps: speed,
             eta_seconds: eta,
         }));
+    }
 
-        let dest_name = input
-            .file_name()
-            .map(|n| n.to_owned())
-            .unwrap_or_else(|| std::ffi::OsString::from(format!("file_{}", idx)));
-        let dest_path = temp_path.join(&dest_name);
-
-        if input.is_file() {
-            fs::copy(input, &dest_path)?;
-        } else if input.is_dir() {
-            copy_dir_recursive(input, &dest_path)?;
+    /// Show the bar full once the job is complete
+    fn finish(&self) {
+        self.processed
+            .store(self.total.load(Ordering::Relaxed), Ordering::Relaxed);
+        {
+            let mut state = self.state.lock().unwrap();
+            state.current_file = match self.kind {
+                TaskKind::Pack => t!("worker-pack-complete").to_string(),
+                TaskKind::Extract => t!("worker-extracted-count", count = state.entries),
+            };
         }
-
-        *processed_size += calculate_path_size(input);
+        self.send(true);
     }
-
-    // Now use flux_core to pack the temp directory
-    let (speed, eta) = progress_tracker.update(*processed_size, total_size);
-    let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
-        processed_bytes: *processed_size,
-        total_bytes: total_size,
-        current_file: "Creating ZIP archive...".to_string(),
-        speed_bps: speed,
-        eta_seconds: eta,
-    }));
-
-    flux_core::archive::zip::pack_zip_with_options(temp_path, output, follow_symlinks)?;
-
-    Ok(())
 }
 
-/// Recursively copy a directory
-#[instrument]
-fn copy_dir_recursive(src: &PathBuf, dst: &PathBuf) -> Result<(), Box<dyn std::error::Error>> {
-    use std::fs;
-
-    fs::create_dir_all(dst)?;
-
-    for entry in fs::read_dir(src)? {
-        let entry = entry?;
-        let src_path = entry.path();
-        let dst_path = dst.join(entry.file_name());
+impl ProgressSink for TaskProgress<'_> {
+    fn entries(&self, count: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.entries = count;
+        state.current_file = t!("worker-extracting-count", count = count);
+    }
 
-        if src_path.is_dir() {
-            copy_dir_recursive(&src_path, &dst_path)?;
-        } else {
-            fs::copy(&src_path, &dst_path)?;
+    fn start(&self, total: u64) {
+        debug!(
+            total_size_mb = total as f64 / (1024.0 * 1024.0),
+            "Total size calculated"
+        );
+        let _ = self.ui_sender.send(ToUi::Log(format!(
+            "Total size: {:.2} MB",
+            total as f64 / (1024.0 * 1024.0)
+        )));
+        self.total.store(total, Ordering::Relaxed);
+        if self.kind == TaskKind::Pack {
+            self.state
+                .lock()
+                .unwrap()
+                .tracker
+                .set_phase(Phase::Compress);
         }
+        self.send(true);
     }
 
-    Ok(())
-}
-
-/// Find the common base directory for a set of paths
-#[instrument]
-fn find_common_base_dir(paths: &[PathBuf]) -> Option<PathBuf> {
-    if paths.is_empty() {
-        return None;
+    fn file(&self, path: &Path) {
+        {
+            let mut state = self.state.lock().unwrap();
+            state.current_file = match self.kind {
+                TaskKind::Pack => t!("worker-adding", path = path.display()),
+                TaskKind::Extract => {
+                    state.entry += 1;
+                    t!(
+                        "worker-extracting-entry",
+                        current = state.entry,
+                        total = state.entries,
+                        name = path
+                            .file_name()
+                            .unwrap_or(path.as_os_str())
+                            .to_string_lossy()
+                    )
+                }
+            };
+        }
+        self.send(false);
     }
 
-    // If all paths have the same parent, use that as base
-    let first_parent = paths[0].parent();
-    if let Some(parent) = first_parent {
-        if paths.iter().all(|p| p.parent() == first_parent) {
-            return Some(parent.to_path_buf());
+    fn advance(&self, bytes: u64) {
+        let paused = self.control.wait_while_paused();
+        if !paused.is_zero() {
+            self.state.lock().unwrap().tracker.exclude(paused);
         }
+        self.control.throttle_disk(bytes);
+        self.processed.fetch_add(bytes, Ordering::Relaxed);
+        self.send(false);
     }
 
-    None
+    fn is_cancelled(&self) -> bool {
+        // Entries without data report none, so a paused task is also held
+        // between them
+        let paused = self.control.wait_while_paused();
+        if !paused.is_zero() {
+            self.state.lock().unwrap().tracker.exclude(paused);
+        }
+        self.control.is_cancelled()
+    }
 }
 
 /// Handle extract task in background thread
-#[instrument(skip(ui_sender, cancel_flag))]
+#[instrument(skip(selection, ui_sender, control))]
 pub fn handle_extract_task(
     archive: PathBuf,
     output_dir: PathBuf,
+    selection: Option<Vec<flux_core::archive::extractor::ArchiveEntry>>,
     hoist: bool,
-    cancel_flag: Arc<AtomicBool>,
-    ui_sender: &Sender<ToUi>,
+    control: TaskControl,
+    ui_sender: &TaskReporter,
 ) {
     use flux_core::archive::extractor::ExtractEntryOptions;
-    use std::time::Instant;
 
-    // Send initial status
     info!(archive = %archive.display(), output_dir = %output_dir.display(), "Starting extraction");
     let _ = ui_sender.send(ToUi::Log(format!(
         "Starting extraction: {} to {}",
         archive.display(),
         output_dir.display()
     )));
-    let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
-        processed_bytes: 0,
-        total_bytes: 0,
-        current_file: "Opening archive...".to_string(),
-        speed_bps: 0.0,
-        eta_seconds: None,
-    }));
-
-    // Create secure extractor
-    let extractor = match flux_core::archive::create_secure_extractor(&archive) {
-        Ok(ex) => ex,
-        Err(e) => {
-            error!(error = %e, "Failed to create extractor");
-            let _ = ui_sender.send(ToUi::Log(format!("Failed to create extractor: {}", e)));
-            let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(e.to_string())));
-            return;
-        }
-    };
-
-    // Get entries to calculate total size
-    let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
-        processed_bytes: 0,
-        total_bytes: 0,
-        current_file: "Reading archive contents...".to_string(),
-        speed_bps: 0.0,
-        eta_seconds: None,
-    }));
-
-    let entries: Vec<_> = match extractor.entries(&archive) {
-        Ok(entries) => {
-            // Collect entries first to calculate total size
-            entries.filter_map(|e| e.ok()).collect()
-        }
-        Err(e) => {
-            let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(e.to_string())));
-            return;
-        }
-    };
 
-    // Calculate total size and count
-    let total_size: u64 = entries.iter().map(|e| e.size).sum();
-    let total_count = entries.len();
-    let mut processed_size: u64 = 0;
-    let mut processed_count = 0;
-    let mut progress_tracker = ProgressTracker::new();
-
-    // Send initial progress with total info
-    let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
-        processed_bytes: 0,
-        total_bytes: total_size,
-        current_file: format!("Extracting {} files...", total_count),
-        speed_bps: 0.0,
-        eta_seconds: None,
-    }));
-
-    // Extract options
-    let extract_options = ExtractEntryOptions {
-        overwrite: true,
-        preserve_permissions: true,
-        preserve_timestamps: true,
-        follow_symlinks: false,
+    let progress = TaskProgress::new(TaskKind::Extract, &control, ui_sender);
+    progress.send(true);
+    let job = ExtractJob {
+        archive: archive.clone(),
+        output_dir,
+        // Entries picked in the archive browser
+        entries: selection,
+        options: ExtractEntryOptions {
+            overwrite: true,
+            preserve_permissions: true,
+            preserve_timestamps: true,
+            follow_symlinks: false,
+            password: None,
+            symlink_fallback: Default::default(),
+            strip_components: None,
+        },
+        hoist,
+        durability: Default::default(),
+        // A task restored after Flux was closed mid-way, or run again after
+        // failing, skips what it already extracted
+        resume: true,
     };
-
-    // Track time for periodic updates
-    let mut last_update = Instant::now();
-    let update_interval = std::time::Duration::from_millis(100); // Update every 100ms
-
-    // Extract each entry
-    for entry in &entries {
-        // Check for cancellation
-        if cancel_flag.load(Ordering::SeqCst) {
-            let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(
-                "Operation cancelled".to_string(),
-            )));
-            return;
-        }
-
-        processed_count += 1;
-
-        // Send progress update if enough time has passed or for every file if there are few files
-        if last_update.elapsed() > update_interval || total_count < 50 {
-            let (speed, eta) = progress_tracker.update(processed_size, total_size);
-            let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
-                processed_bytes: processed_size,
-                total_bytes: total_size,
-                current_file: format!(
-                    "Extracting ({}/{}): {}",
-                    processed_count,
-                    total_count,
-                    entry
-                        .path
-                        .file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or_else(|| entry.path.to_str().unwrap_or("..."))
-                ),
-                speed_bps: speed,
-                eta_seconds: eta,
-            }));
-            last_update = Instant::now();
+    // Asked until a password works or the user gives up
+    let mut declined = None;
+    let result = job.run(&progress, |entry, retry| {
+        let password = request_password(&archive, entry, retry, &control, ui_sender);
+        if password.is_none() {
+            declined = Some(entry.to_path_buf());
         }
+        password
+    });
 
-        // Extract the entry
-        if let Err(e) =
-            extractor.extract_entry(&archive, entry, &output_dir, extract_options.clone())
-        {
-            error!(path = %entry.path.display(), error = %e, "Failed to extract file");
-            let _ = ui_sender.send(ToUi::Log(format!(
-                "Failed to extract {}: {}",
-                entry.path.display(),
-                e
-            )));
-            let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(format!(
-                "Failed to extract {}: {}",
-                entry.path.display(),
-                e
-            ))));
+    let outcome = match result {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            if control.is_cancelled() {
+                let _ = ui_sender.send(ToUi::Finished(TaskResult::Cancelled));
+            } else if let Some(entry) = declined {
+                let _ = ui_sender.send(ToUi::Log(format!(
+                    "No password given for {}, extraction cancelled",
+                    entry.display()
+                )));
+                let _ = ui_sender.send(ToUi::Finished(TaskResult::Cancelled));
+            } else {
+                error!(error = %e, "Extraction failed");
+                let _ = ui_sender.send(ToUi::Log(format!("Extraction failed: {}", e)));
+                if e.code() == ErrorCode::PermissionDenied {
+                    let _ = ui_sender.send(ToUi::AccessDenied);
+                }
+                let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(e.to_string())));
+            }
             return;
         }
+    };
 
-        processed_size += entry.size;
+    progress.finish();
+    info!(
+        files = outcome.done.len(),
+        failed = outcome.failed.len(),
+        "Extraction completed"
+    );
+    log_outcome(&outcome, ui_sender);
+    let refused = !outcome.failed.is_empty()
+        && outcome
+            .failed
+            .iter()
+            .all(|failure| failure.error.code() == ErrorCode::PermissionDenied);
+    if refused {
+        let _ = ui_sender.send(ToUi::AccessDenied);
     }
+    let _ = ui_sender.send(ToUi::Finished(outcome_result(&outcome)));
+}
 
-    // Perform directory hoisting if requested
-    if hoist {
-        info!("Checking for single directory to hoist...");
-        let _ = ui_sender.send(ToUi::Log(
-            "Checking for single directory to hoist...".to_string(),
-        ));
-        if let Err(e) = flux_core::archive::hoist_single_directory(&output_dir) {
-            info!("Directory hoisting failed: {}", e);
-            let _ = ui_sender.send(ToUi::Log(format!("Directory hoisting failed: {}", e)));
-            // We don't fail the entire operation if hoisting fails
-        } else {
-            info!("Directory hoisting completed");
-            let _ = ui_sender.send(ToUi::Log(
-                "Directory hoisting completed successfully".to_string(),
-            ));
+/// Ask the UI for the password of an encrypted entry and wait for the answer
+///
+/// Returns `None` if the user dismissed the dialog or cancelled the task.
+fn request_password(
+    archive: &std::path::Path,
+    entry: &std::path::Path,
+    retry: bool,
+    control: &TaskControl,
+    ui_sender: &TaskReporter,
+) -> Option<String> {
+    use crossbeam_channel::RecvTimeoutError;
+    use std::time::Duration;
+
+    let (reply, answer) = crossbeam_channel::bounded(1);
+    ui_sender
+        .send(ToUi::PasswordRequest(PasswordRequest {
+            archive: archive.to_path_buf(),
+            entry: entry.to_path_buf(),
+            retry,
+            reply,
+        }))
+        .ok()?;
+
+    // Poll so a cancel from the task list is noticed while the dialog is open
+    loop {
+        match answer.recv_timeout(Duration::from_millis(200)) {
+            Ok(password) => return password,
+            Err(RecvTimeoutError::Timeout) if !control.is_cancelled() => {}
+            Err(_) => return None,
         }
     }
-
-    // Send completion
-    let (speed, _) = progress_tracker.update(total_size, total_size);
-    let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
-        processed_bytes: total_size,
-        total_bytes: total_size,
-        current_file: format!("Successfully extracted {} files", total_count),
-        speed_bps: speed,
-        eta_seconds: None,
-    }));
-    info!(files = total_count, "Extraction completed");
-    let _ = ui_sender.send(ToUi::Log(format!(
-        "Extraction completed: {} files extracted",
-        total_count
-    )));
-    let _ = ui_sender.send(ToUi::Finished(TaskResult::Success));
 }
 
 /// Handle sync/incremental backup task in background thread
-#[instrument(skip(ui_sender, _cancel_flag, options))]
+#[instrument(skip(ui_sender, _control, options))]
 pub fn handle_sync_task(
     source_dir: PathBuf,
     target_archive: PathBuf,
     old_manifest: Option<PathBuf>,
     options: flux_core::archive::PackOptions,
-    _cancel_flag: Arc<AtomicBool>,
-    ui_sender: &Sender<ToUi>,
+    _control: TaskControl,
+    ui_sender: &TaskReporter,
 ) {
     info!(
         source = %source_dir.display(),
@@ -611,7 +471,11 @@ pub fn handle_sync_task(
             &old_manifest_path,
             options,
         ) {
-            Ok((_new_manifest_path, diff)) => {
+            Ok((new_manifest_path, diff)) => {
+                let _ = ui_sender.send(ToUi::Manifest(new_manifest_path.clone()));
+                if diff.has_changes() {
+                    record_snapshot(&target_archive, &new_manifest_path, ui_sender);
+                }
                 info!(
                     added = diff.added.len(),
                     modified = diff.modified.len(),
@@ -630,7 +494,7 @@ pub fn handle_sync_task(
                 let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
                     processed_bytes: 100,
                     total_bytes: 100,
-                    current_file: format!("Backup complete - {} changes", diff.change_count()),
+                    current_file: t!("worker-backup-changes", count = diff.change_count()),
                     speed_bps: 0.0,
                     eta_seconds: None,
                 }));
@@ -644,87 +508,101 @@ pub fn handle_sync_task(
             }
         }
     } else {
-        // Full backup - first create the manifest
-        info!("Creating initial manifest for full backup");
-        let _ = ui_sender.send(ToUi::Log(
-            "Creating manifest for source directory...".to_string(),
-        ));
-
-        match flux_core::manifest::Manifest::from_directory(&source_dir) {
-            Ok(manifest) => {
-                let file_count = manifest.file_count;
-                let total_size = manifest.total_size;
-
-                let _ = ui_sender.send(ToUi::Log(format!(
-                    "Manifest created: {} files, {:.2} MB total",
-                    file_count,
-                    total_size as f64 / (1024.0 * 1024.0)
-                )));
+        // Full backup, then the manifest from the hashes taken while packing
+        let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
+            processed_bytes: 0,
+            total_bytes: 0,
+            current_file: t!("worker-creating-full-backup").to_string(),
+            speed_bps: 0.0,
+            eta_seconds: None,
+        }));
 
-                // Create the full backup using regular pack
-                let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
-                    processed_bytes: 0,
-                    total_bytes: total_size,
-                    current_file: "Creating full backup...".to_string(),
-                    speed_bps: 0.0,
-                    eta_seconds: None,
-                }));
+        let filter = options.filter.clone();
+        let hashes = flux_core::archive::PackHashes::new();
+        let options = flux_core::archive::PackOptions {
+            hashes: Some(hashes.clone()),
+            ..options
+        };
+        if let Err(e) =
+            PackJob::new(vec![source_dir.clone()], &target_archive, options).run(&NoProgress)
+        {
+            error!(error = %e, "Full backup failed");
+            let _ = ui_sender.send(ToUi::Log(format!("Full backup failed: {}", e)));
+            let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(e.to_string())));
+            return;
+        }
 
-                match flux_core::archive::pack_with_strategy(
-                    &source_dir,
-                    &target_archive,
-                    None,
-                    options,
-                ) {
-                    Ok(_) => {
-                        // Save the manifest
-                        let manifest_path = target_archive.with_extension("manifest.json");
-                        if let Err(e) = manifest.save(&manifest_path) {
-                            warn!(error = %e, "Failed to save manifest");
-                            let _ = ui_sender.send(ToUi::Log(format!(
-                                "Warning: Failed to save manifest: {}",
-                                e
-                            )));
-                        } else {
-                            info!("Manifest saved to {:?}", manifest_path);
-                            let _ = ui_sender.send(ToUi::Log(format!(
-                                "Manifest saved to {}",
-                                manifest_path.display()
-                            )));
-                        }
-
-                        let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
-                            processed_bytes: total_size,
-                            total_bytes: total_size,
-                            current_file: "Full backup complete".to_string(),
-                            speed_bps: 0.0,
-                            eta_seconds: None,
-                        }));
-
-                        let _ = ui_sender.send(ToUi::Finished(TaskResult::Success));
-                    }
-                    Err(e) => {
-                        error!(error = %e, "Full backup failed");
-                        let _ = ui_sender.send(ToUi::Log(format!("Full backup failed: {}", e)));
-                        let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(e.to_string())));
-                    }
+        info!("Creating manifest for full backup");
+        let manifest =
+            match flux_core::manifest::Manifest::from_packed(&source_dir, &filter, &hashes) {
+                Ok(manifest) => manifest,
+                Err(e) => {
+                    error!(error = %e, "Failed to create manifest");
+                    let _ = ui_sender.send(ToUi::Log(format!("Failed to create manifest: {}", e)));
+                    let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(e.to_string())));
+                    return;
                 }
-            }
-            Err(e) => {
-                error!(error = %e, "Failed to create manifest");
-                let _ = ui_sender.send(ToUi::Log(format!("Failed to create manifest: {}", e)));
-                let _ = ui_sender.send(ToUi::Finished(TaskResult::Error(e.to_string())));
-            }
+            };
+        let total_size = manifest.total_size;
+        let _ = ui_sender.send(ToUi::Log(format!(
+            "Manifest created: {} files, {:.2} MB total",
+            manifest.file_count,
+            total_size as f64 / (1024.0 * 1024.0)
+        )));
+
+        // Save the manifest
+        let manifest_path = target_archive.with_extension("manifest.json");
+        if let Err(e) = manifest.save(&manifest_path) {
+            warn!(error = %e, "Failed to save manifest");
+            let _ = ui_sender.send(ToUi::Log(format!(
+                "Warning: Failed to save manifest: {}",
+                e
+            )));
+        } else {
+            info!("Manifest saved to {:?}", manifest_path);
+            let _ = ui_sender.send(ToUi::Log(format!(
+                "Manifest saved to {}",
+                manifest_path.display()
+            )));
+            let _ = ui_sender.send(ToUi::Manifest(manifest_path.clone()));
+            record_snapshot(&target_archive, &manifest_path, ui_sender);
         }
+
+        let _ = ui_sender.send(ToUi::Progress(ProgressUpdate {
+            processed_bytes: total_size,
+            total_bytes: total_size,
+            current_file: t!("worker-full-backup-complete").to_string(),
+            speed_bps: 0.0,
+            eta_seconds: None,
+        }));
+
+        let _ = ui_sender.send(ToUi::Finished(TaskResult::Success));
     }
 }
 
+/// Keep the archive and manifest a sync just wrote as the target's next
+/// snapshot, so it can still be restored after later syncs overwrite them
+fn record_snapshot(target_archive: &Path, manifest: &Path, ui_sender: &TaskReporter) {
+    let recorded = flux_core::snapshot::SnapshotHistory::new(target_archive)
+        .and_then(|history| history.record(target_archive, manifest));
+    let message = match recorded {
+        Ok(sequence) => format!("Recorded as snapshot {}", sequence),
+        Err(e) => {
+            warn!(error = %e, "Failed to record snapshot");
+            format!("Warning: Failed to record snapshot: {}", e)
+        }
+    };
+    let _ = ui_sender.send(ToUi::Log(message));
+}
+
 fn main() -> Result<(), eframe::Error> {
     // Initialize tracing without GUI integration first (will be updated when app starts)
     crate::logging::init_tracing(None);
 
     info!("Starting Flux GUI application");
 
+    let launch = launch::LaunchRequest::parse(std::env::args_os().skip(1));
+
     // Set up eframe options with icon
     let icon_bytes = include_bytes!("../assets/icon.png");
     let icon = eframe::icon_data::from_png_bytes(icon_bytes).unwrap_or_else(|e| {
@@ -748,6 +626,6 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "Flux - File Archiver",
         options,
-        Box::new(|cc| Ok(Box::new(FluxApp::new(cc)))),
+        Box::new(|cc| Ok(Box::new(FluxApp::new(cc, launch)))),
     )
 }
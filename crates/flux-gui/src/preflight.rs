@@ -0,0 +1,263 @@
+//! Checks of a job's destination before it starts
+//!
+//! A pack or extraction into a folder that cannot be written, onto a disk
+//! too small for it, or an archive written into a folder being packed
+//! would only fail partway through. The pack and extract views check the
+//! destination as soon as it is chosen instead, on a background thread,
+//! and show what is wrong next to it; the job cannot be started until
+//! it is fixed. Free space is checked against the pack estimate, and for
+//! extraction with [`flux_core::space::check_extract`].
+
+use crate::t;
+use crossbeam_channel::Receiver;
+use flux_core::archive::Archive;
+use flux_core::Error;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// Inputs and destinations a check was run for, with the expected archive
+/// size of a pack
+type PreflightKey = (Vec<PathBuf>, Vec<PathBuf>, Option<u64>);
+
+/// Something that would make a job fail once started
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// The destination folder does not exist, and the job does not make it
+    Missing(PathBuf),
+    /// The destination is a file rather than a folder
+    NotAFolder(PathBuf),
+    /// Creating a file in the destination failed
+    Unwritable { dir: PathBuf, error: String },
+    /// The disk holding the destination has less room than the job needs
+    NoSpace {
+        dir: PathBuf,
+        required: u64,
+        available: u64,
+    },
+    /// The archive would be written inside one of the inputs it packs
+    Overlap { input: PathBuf, output: PathBuf },
+}
+
+impl Problem {
+    /// What is wrong, for the warning shown next to the destination
+    pub fn message(&self) -> String {
+        match self {
+            Problem::Missing(dir) => t!("preflight-missing", dir = dir.display()),
+            Problem::NotAFolder(dir) => t!("preflight-not-a-folder", dir = dir.display()),
+            Problem::Unwritable { dir, error } => {
+                t!("preflight-unwritable", dir = dir.display(), error = error)
+            }
+            Problem::NoSpace {
+                dir,
+                required,
+                available,
+            } => t!(
+                "preflight-no-space",
+                dir = dir.display(),
+                required = crate::views::browser_view::format_size(*required),
+                available = crate::views::browser_view::format_size(*available)
+            ),
+            Problem::Overlap { input, output } => t!(
+                "preflight-overlap",
+                input = input.display(),
+                output = output.display()
+            ),
+        }
+    }
+}
+
+/// Destination checks of the job set up in the pack or extract view
+#[derive(Debug, Default)]
+pub struct Preflight {
+    /// What the latest check was run for
+    checked: Option<PreflightKey>,
+    problems: Vec<Problem>,
+    pending: Option<Receiver<Vec<Problem>>>,
+}
+
+impl Preflight {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check packing `inputs` into `output`, an archive expected to take
+    /// `archive_size` bytes once estimated, unless already checked
+    pub fn check_pack(&mut self, inputs: &[PathBuf], output: &Path, archive_size: Option<u64>) {
+        let key = (inputs.to_vec(), vec![output.to_path_buf()], archive_size);
+        self.start(key, move |(inputs, outputs, archive_size)| {
+            let output = &outputs[0];
+            let dir = match output.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                _ => PathBuf::from("."),
+            };
+            let mut problems = Vec::new();
+            problems.extend(check_dir(&dir, true));
+            problems.extend(check_overlap(&inputs, output));
+            if let (true, Some(required)) = (problems.is_empty(), archive_size) {
+                problems.extend(check_space(flux_core::space::ensure_space(
+                    output, required,
+                )));
+            }
+            problems
+        });
+    }
+
+    /// Check extracting each of `archives` into the folder at the same
+    /// position in `output_dirs`, unless already checked
+    ///
+    /// `create` tells whether the extraction makes missing folders, as a
+    /// batch does for its subfolders.
+    pub fn check_extract(&mut self, archives: &[PathBuf], output_dirs: &[PathBuf], create: bool) {
+        let key = (archives.to_vec(), output_dirs.to_vec(), None);
+        self.start(key, move |(archives, output_dirs, _)| {
+            let mut problems = Vec::new();
+            for (archive, dir) in archives.iter().zip(&output_dirs) {
+                let mut found = check_dir(dir, !create);
+                // The archive's own errors are for the extraction to report
+                if found.is_empty() {
+                    if let Ok(opened) = Archive::open(archive) {
+                        found = check_space(flux_core::space::check_extract(&opened, dir));
+                    }
+                }
+                // Batches often extract many archives into one folder
+                for problem in found {
+                    if !problems.contains(&problem) {
+                        problems.push(problem);
+                    }
+                }
+            }
+            problems
+        });
+    }
+
+    /// Run `check` for `key` in the background, unless `key` was already
+    /// checked
+    fn start<F>(&mut self, key: PreflightKey, check: F)
+    where
+        F: FnOnce(PreflightKey) -> Vec<Problem> + Send + 'static,
+    {
+        if self.checked.as_ref() == Some(&key) {
+            return;
+        }
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let job = key.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(check(job));
+        });
+        self.checked = Some(key);
+        self.problems.clear();
+        self.pending = Some(receiver);
+    }
+
+    /// Pick up the problems once the check is done
+    pub fn poll(&mut self) {
+        if let Some(receiver) = &self.pending {
+            match receiver.try_recv() {
+                Ok(problems) => {
+                    self.problems = problems;
+                    self.pending = None;
+                }
+                Err(crossbeam_channel::TryRecvError::Empty) => {}
+                Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                    self.pending = None;
+                }
+            }
+        }
+    }
+
+    /// Forget the last check, as when the job is cleared
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Whether a check is running
+    pub fn is_checking(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Problems found by the last finished check
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+}
+
+/// Check `dir` is a folder a file can be created in
+///
+/// A folder that does not exist yet is judged by the nearest one above it
+/// that does, which the job would create it in, unless `must_exist`.
+fn check_dir(dir: &Path, must_exist: bool) -> Vec<Problem> {
+    if dir.exists() && !dir.is_dir() {
+        return vec![Problem::NotAFolder(dir.to_path_buf())];
+    }
+    if !dir.exists() && must_exist {
+        return vec![Problem::Missing(dir.to_path_buf())];
+    }
+    let Some(existing) = dir.ancestors().find(|ancestor| ancestor.is_dir()) else {
+        return vec![Problem::Missing(dir.to_path_buf())];
+    };
+    match tempfile::Builder::new()
+        .prefix(".flux-preflight")
+        .tempfile_in(existing)
+    {
+        Ok(_) => Vec::new(),
+        Err(e) => vec![Problem::Unwritable {
+            dir: dir.to_path_buf(),
+            error: e.to_string(),
+        }],
+    }
+}
+
+/// Check `output` is not one of `inputs` or inside one of them
+fn check_overlap(inputs: &[PathBuf], output: &Path) -> Vec<Problem> {
+    let output_path = resolved(output);
+    inputs
+        .iter()
+        .filter(|input| output_path.starts_with(resolved(input)))
+        .map(|input| Problem::Overlap {
+            input: input.clone(),
+            output: output.to_path_buf(),
+        })
+        .collect()
+}
+
+/// `path` made absolute with links resolved, as far as it exists
+fn resolved(path: &Path) -> PathBuf {
+    let mut rest = Vec::new();
+    let mut existing = path;
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return rest
+                .iter()
+                .rev()
+                .fold(canonical, |path, name| path.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+/// The problem an [`Error::InsufficientSpace`] from flux-core stands for;
+/// other errors are left for the job itself to report
+fn check_space(result: flux_core::Result<()>) -> Vec<Problem> {
+    match result {
+        Ok(()) => Vec::new(),
+        Err(Error::InsufficientSpace {
+            path,
+            required,
+            available,
+        }) => vec![Problem::NoSpace {
+            dir: path,
+            required,
+            available,
+        }],
+        Err(e) => {
+            debug!("Free space could not be checked: {}", e);
+            Vec::new()
+        }
+    }
+}
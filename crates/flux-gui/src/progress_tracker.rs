@@ -1,57 +1,141 @@
 //! Progress tracking with speed and ETA calculation
+//!
+//! Speed is an exponential moving average of samples taken at least
+//! [`SAMPLE_INTERVAL`] apart. A stretch without progress is not sampled on
+//! its own but folded into the sample that ends it, so a stall slows the
+//! estimate once instead of dropping it to nothing. Each [`Phase`] of a
+//! task keeps its own pace, and the last pace of every phase is remembered
+//! in a [`ThroughputHistory`] so the next task, or one restored from an
+//! earlier session, shows an ETA from its first update.
 
-use std::collections::VecDeque;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+/// Shortest time a speed sample covers
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Weight of a new sample in the moving average
+const SMOOTHING: f64 = 0.2;
+
+/// Weight of a finished run in the remembered speed of its phase
+const HISTORY_WEIGHT: f64 = 0.5;
+
+/// Stage of a task, each going at a pace of its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Phase {
+    /// Walking the inputs to find out how much there is
+    Scan,
+    /// Writing an archive
+    Compress,
+    /// Writing the entries of an archive out
+    Extract,
+    /// Sending an archive to cloud storage
+    Upload,
+    /// Reading an archive from cloud storage
+    Download,
+}
+
+/// Speed of each phase as last measured, shared by all tasks and saved
+/// with the app state
+#[derive(Debug, Clone, Default)]
+pub struct ThroughputHistory(Arc<Mutex<HashMap<Phase, f64>>>);
+
+impl ThroughputHistory {
+    /// Remembered speed of `phase` in bytes per second
+    pub fn speed(&self, phase: Phase) -> Option<f64> {
+        self.0.lock().unwrap().get(&phase).copied()
+    }
+
+    /// Blend the speed a run of `phase` ended with into what is remembered
+    pub fn record(&self, phase: Phase, speed: f64) {
+        if !speed.is_finite() || speed <= 0.0 {
+            return;
+        }
+        self.0
+            .lock()
+            .unwrap()
+            .entry(phase)
+            .and_modify(|old| *old += HISTORY_WEIGHT * (speed - *old))
+            .or_insert(speed);
+    }
+}
+
+impl Serialize for ThroughputHistory {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.lock().unwrap().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ThroughputHistory {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let speeds = HashMap::deserialize(deserializer)?;
+        Ok(Self(Arc::new(Mutex::new(speeds))))
+    }
+}
+
 /// A progress tracker that calculates speed and ETA
+///
+/// The speed it ends with is recorded in its history when it is dropped
+/// or moves to another phase.
 pub struct ProgressTracker {
-    /// Start time of the operation
+    /// Phase being tracked
+    phase: Phase,
+    /// Where the speed of finished phases goes
+    history: ThroughputHistory,
+    /// Start time of the phase
     start_time: Instant,
-    /// Last update time
-    last_update: Instant,
-    /// History of speed measurements for smoothing
-    speed_history: VecDeque<f64>,
-    /// Maximum number of speed samples to keep
-    max_samples: usize,
-    /// Last processed bytes count
-    last_bytes: u64,
+    /// Smoothed speed, remembered from earlier runs until measured
+    speed: Option<f64>,
+    /// Whether `speed` has been measured in this phase
+    measured: bool,
+    /// Start of the sample being taken
+    sample_start: Instant,
+    /// Processed bytes count at the start of the sample
+    sample_bytes: u64,
 }
 
 impl ProgressTracker {
-    /// Create a new progress tracker
-    pub fn new() -> Self {
+    /// Create a tracker for `phase`, starting from its speed in `history`
+    pub fn new(phase: Phase, history: ThroughputHistory) -> Self {
+        let now = Instant::now();
         Self {
-            start_time: Instant::now(),
-            last_update: Instant::now(),
-            speed_history: VecDeque::with_capacity(10),
-            max_samples: 10,
-            last_bytes: 0,
+            phase,
+            speed: history.speed(phase),
+            history,
+            start_time: now,
+            measured: false,
+            sample_start: now,
+            sample_bytes: 0,
         }
     }
 
     /// Update progress and calculate speed/ETA
     pub fn update(&mut self, processed_bytes: u64, total_bytes: u64) -> (f64, Option<f64>) {
         let now = Instant::now();
-        let elapsed = now.duration_since(self.last_update);
-
-        // Calculate instantaneous speed
-        if elapsed.as_secs_f64() > 0.1 {
-            // Only update if enough time has passed
-            let bytes_delta = processed_bytes.saturating_sub(self.last_bytes) as f64;
-            let speed = bytes_delta / elapsed.as_secs_f64();
-
-            // Add to history
-            self.speed_history.push_back(speed);
-            if self.speed_history.len() > self.max_samples {
-                self.speed_history.pop_front();
-            }
+        let elapsed = now.duration_since(self.sample_start);
 
-            self.last_update = now;
-            self.last_bytes = processed_bytes;
+        // Take a sample once enough time has passed and something moved;
+        // until then a stall keeps stretching the sample in progress
+        if elapsed >= SAMPLE_INTERVAL && processed_bytes > self.sample_bytes {
+            let bytes_delta = (processed_bytes - self.sample_bytes) as f64;
+            let sample = bytes_delta / elapsed.as_secs_f64();
+            self.speed = Some(match self.speed {
+                Some(speed) => speed + SMOOTHING * (sample - speed),
+                None => sample,
+            });
+            self.measured = true;
+            self.sample_start = now;
+            self.sample_bytes = processed_bytes;
+        } else if processed_bytes < self.sample_bytes {
+            // Progress went back, as when a reader seeks
+            self.sample_start = now;
+            self.sample_bytes = processed_bytes;
         }
 
-        // Calculate average speed
-        let avg_speed = if self.speed_history.is_empty() {
+        let speed = self.speed.unwrap_or_else(|| {
             // Fallback to overall average if no samples yet
             let total_elapsed = now.duration_since(self.start_time).as_secs_f64();
             if total_elapsed > 0.0 {
@@ -59,27 +143,52 @@ impl ProgressTracker {
             } else {
                 0.0
             }
-        } else {
-            self.speed_history.iter().sum::<f64>() / self.speed_history.len() as f64
-        };
+        });
 
         // Calculate ETA
-        let eta = if avg_speed > 0.0 && processed_bytes < total_bytes {
+        let eta = if speed > 0.0 && processed_bytes < total_bytes {
             let remaining_bytes = (total_bytes - processed_bytes) as f64;
-            Some(remaining_bytes / avg_speed)
+            Some(remaining_bytes / speed)
         } else {
             None
         };
 
-        (avg_speed, eta)
+        (speed, eta)
+    }
+
+    /// Move on to `phase`, whose bytes are counted from zero again
+    pub fn set_phase(&mut self, phase: Phase) {
+        if phase != self.phase {
+            self.record();
+            *self = Self::new(phase, self.history.clone());
+        }
+    }
+
+    /// Leave a stretch of time, such as a pause, out of the speed
+    pub fn exclude(&mut self, duration: Duration) {
+        self.start_time += duration;
+        self.sample_start += duration;
     }
 
     /// Reset the tracker
+    #[allow(dead_code)]
     pub fn reset(&mut self) {
-        self.start_time = Instant::now();
-        self.last_update = Instant::now();
-        self.speed_history.clear();
-        self.last_bytes = 0;
+        self.record();
+        *self = Self::new(self.phase, self.history.clone());
+    }
+
+    /// Remember the measured speed of the phase for later runs
+    fn record(&mut self) {
+        if let (true, Some(speed)) = (self.measured, self.speed) {
+            self.history.record(self.phase, speed);
+        }
+        self.measured = false;
+    }
+}
+
+impl Drop for ProgressTracker {
+    fn drop(&mut self) {
+        self.record();
     }
 }
 
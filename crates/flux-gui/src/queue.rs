@@ -0,0 +1,347 @@
+//! Task queue for running several archive operations at once
+//!
+//! The UI submits [`TaskCommand`]s to a [`TaskQueue`]. The queue hands them
+//! to the worker pool as long as fewer than `max_concurrent` are running and
+//! keeps the rest waiting. Worker messages are routed back to the matching
+//! task by id, so every task has its own progress, status and
+//! [`TaskControl`] for cancelling and pausing it.
+
+use crate::progress_tracker::{format_duration, format_speed};
+use crate::t;
+use crate::task::{TaskCommand, TaskControl, TaskEvent, TaskId, TaskResult, ToUi};
+use crossbeam_channel::Sender;
+
+/// Number of worker threads, and the upper bound for `max_concurrent`
+pub const MAX_WORKERS: usize = 8;
+
+/// What a task does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    Pack,
+    Extract,
+    Sync,
+}
+
+impl From<&TaskCommand> for TaskKind {
+    fn from(command: &TaskCommand) -> Self {
+        match command {
+            TaskCommand::Pack { .. } | TaskCommand::CloudPack { .. } => TaskKind::Pack,
+            TaskCommand::Extract { .. } | TaskCommand::CloudExtract { .. } => TaskKind::Extract,
+            TaskCommand::Sync { .. } => TaskKind::Sync,
+        }
+    }
+}
+
+/// Lifecycle of a queued task
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskStatus {
+    /// Waiting for a free worker
+    Queued,
+    /// Running on a worker
+    Running,
+    /// Finished successfully
+    Completed,
+    /// Finished with an error
+    Failed(String),
+    /// Cancelled before or while running
+    Cancelled,
+}
+
+impl TaskStatus {
+    /// Whether the task is still waiting or running
+    pub fn is_active(&self) -> bool {
+        matches!(self, TaskStatus::Queued | TaskStatus::Running)
+    }
+}
+
+/// A task in the queue together with its latest progress
+pub struct QueuedTask {
+    pub id: TaskId,
+    pub kind: TaskKind,
+    pub label: String,
+    pub status: TaskStatus,
+    /// Progress from 0.0 to 1.0
+    pub progress: f32,
+    /// Formatted size, speed and ETA
+    pub status_text: String,
+    /// File currently being processed
+    pub current_file: String,
+    control: TaskControl,
+    /// The command, until it is handed to a worker
+    command: Option<TaskCommand>,
+}
+
+impl QueuedTask {
+    /// Whether the user asked to cancel this task
+    pub fn cancel_requested(&self) -> bool {
+        self.control.is_cancelled()
+    }
+
+    /// Whether the task is running but parked by the user
+    pub fn is_paused(&self) -> bool {
+        self.status == TaskStatus::Running && self.control.is_paused()
+    }
+
+    /// Whether the task can be paused
+    ///
+    /// Sync runs as a single step, so there is nowhere for it to park.
+    pub fn can_pause(&self) -> bool {
+        self.status == TaskStatus::Running
+            && self.kind != TaskKind::Sync
+            && !self.cancel_requested()
+    }
+}
+
+/// Queue of submitted tasks with a concurrency limit
+pub struct TaskQueue {
+    tasks: Vec<QueuedTask>,
+    next_id: TaskId,
+    /// How many tasks may run at the same time
+    pub max_concurrent: usize,
+}
+
+impl TaskQueue {
+    /// Create an empty queue
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            tasks: Vec::new(),
+            next_id: 1,
+            max_concurrent: max_concurrent.clamp(1, MAX_WORKERS),
+        }
+    }
+
+    /// Add a command to the queue, returning its id
+    pub fn submit(&mut self, command: TaskCommand) -> TaskId {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        self.tasks.push(QueuedTask {
+            id,
+            kind: TaskKind::from(&command),
+            label: command.describe(),
+            status: TaskStatus::Queued,
+            progress: 0.0,
+            status_text: t!("tasks-waiting").to_string(),
+            current_file: String::new(),
+            control: command.control().clone(),
+            command: Some(command),
+        });
+        id
+    }
+
+    /// Hand queued tasks to the workers while there is capacity
+    ///
+    /// Returns false if the worker pool has shut down.
+    pub fn dispatch(&mut self, sender: &Sender<(TaskId, TaskCommand)>) -> bool {
+        let limit = self.max_concurrent.clamp(1, MAX_WORKERS);
+        let mut running = self.running_count();
+
+        for task in &mut self.tasks {
+            if running >= limit {
+                break;
+            }
+            if task.status != TaskStatus::Queued {
+                continue;
+            }
+            if let Some(command) = task.command.take() {
+                if sender.send((task.id, command)).is_err() {
+                    task.status = TaskStatus::Failed(t!("tasks-workers-stopped").to_string());
+                    return false;
+                }
+                task.status = TaskStatus::Running;
+                task.status_text = t!("tasks-starting").to_string();
+                running += 1;
+            }
+        }
+        true
+    }
+
+    /// Apply a worker message to its task
+    ///
+    /// Returns the task when the message finished it. Log messages,
+    /// password requests, access denials, reports and manifests are left
+    /// to the caller.
+    pub fn apply(&mut self, event: &TaskEvent) -> Option<&QueuedTask> {
+        let task = self.tasks.iter_mut().find(|task| task.id == event.id)?;
+
+        match &event.message {
+            ToUi::Progress(update) => {
+                task.progress = update.processed_bytes as f32 / update.total_bytes.max(1) as f32;
+                task.current_file = update.current_file.clone();
+                task.status_text = if update.total_bytes > 0 {
+                    let processed_mb = update.processed_bytes as f64 / (1024.0 * 1024.0);
+                    let total_mb = update.total_bytes as f64 / (1024.0 * 1024.0);
+                    let percent = (task.progress * 100.0) as u32;
+                    let speed = format_speed(update.speed_bps);
+                    match update.eta_seconds {
+                        Some(eta) => t!(
+                            "tasks-progress-eta",
+                            processed = format!("{:.1}", processed_mb),
+                            total = format!("{:.1}", total_mb),
+                            percent = percent,
+                            speed = speed,
+                            eta = format_duration(eta)
+                        ),
+                        None => t!(
+                            "tasks-progress",
+                            processed = format!("{:.1}", processed_mb),
+                            total = format!("{:.1}", total_mb),
+                            percent = percent,
+                            speed = speed
+                        ),
+                    }
+                } else {
+                    t!("tasks-processing").to_string()
+                };
+                None
+            }
+            ToUi::Finished(result) => {
+                task.status = match result {
+                    TaskResult::Success => {
+                        task.progress = 1.0;
+                        task.status_text = t!("tasks-completed").to_string();
+                        TaskStatus::Completed
+                    }
+                    // Workers report cancellation as an error
+                    TaskResult::Cancelled | TaskResult::Error(_) if task.cancel_requested() => {
+                        task.status_text = t!("tasks-status-cancelled").to_string();
+                        TaskStatus::Cancelled
+                    }
+                    TaskResult::Cancelled => {
+                        task.status_text = t!("tasks-status-cancelled").to_string();
+                        TaskStatus::Cancelled
+                    }
+                    TaskResult::Error(err) => {
+                        task.status_text = t!("tasks-error", error = err);
+                        TaskStatus::Failed(err.clone())
+                    }
+                };
+                Some(task)
+            }
+            ToUi::PasswordRequest(_) => {
+                task.status_text = t!("tasks-waiting-for-password").to_string();
+                None
+            }
+            ToUi::Log(_) | ToUi::AccessDenied | ToUi::Report(_) | ToUi::Manifest(_) => None,
+        }
+    }
+
+    /// Cancel a task, returning true if it was still active
+    ///
+    /// Queued tasks are dropped right away; running tasks stop at the next
+    /// cancellation check in the worker.
+    pub fn cancel(&mut self, id: TaskId) -> bool {
+        let Some(task) = self.tasks.iter_mut().find(|task| task.id == id) else {
+            return false;
+        };
+
+        match task.status {
+            TaskStatus::Queued => {
+                task.command = None;
+                task.status = TaskStatus::Cancelled;
+                task.status_text = t!("tasks-status-cancelled").to_string();
+                true
+            }
+            TaskStatus::Running => {
+                task.control.cancel();
+                task.status_text = t!("tasks-cancelling").to_string();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Pause a running task, returning true if it can be paused
+    ///
+    /// The worker parks at its next check and keeps its slot, so queued
+    /// tasks do not start in its place.
+    pub fn pause(&mut self, id: TaskId) -> bool {
+        match self.tasks.iter().find(|task| task.id == id) {
+            Some(task) if task.can_pause() => {
+                task.control.pause();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Resume a paused task, returning true if it was paused
+    pub fn resume(&mut self, id: TaskId) -> bool {
+        match self.tasks.iter().find(|task| task.id == id) {
+            Some(task) if task.is_paused() => {
+                task.control.resume();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Pause every running task that supports it, returning how many were
+    /// paused
+    pub fn pause_all(&mut self) -> usize {
+        let mut paused = 0;
+        for task in &self.tasks {
+            if task.can_pause() && !task.control.is_paused() {
+                task.control.pause();
+                paused += 1;
+            }
+        }
+        paused
+    }
+
+    /// Resume every paused task
+    pub fn resume_all(&mut self) {
+        for task in self.tasks.iter().filter(|task| task.is_paused()) {
+            task.control.resume();
+        }
+    }
+
+    /// Number of running tasks that are paused
+    pub fn paused_count(&self) -> usize {
+        self.tasks.iter().filter(|task| task.is_paused()).count()
+    }
+
+    /// Cancel every active task
+    pub fn cancel_all(&mut self) {
+        let ids: Vec<_> = self
+            .tasks
+            .iter()
+            .filter(|task| task.status.is_active())
+            .map(|task| task.id)
+            .collect();
+        for id in ids {
+            self.cancel(id);
+        }
+    }
+
+    /// Remove finished, failed and cancelled tasks from the list
+    pub fn clear_finished(&mut self) {
+        self.tasks.retain(|task| task.status.is_active());
+    }
+
+    /// All tasks in submission order
+    pub fn tasks(&self) -> &[QueuedTask] {
+        &self.tasks
+    }
+
+    /// Number of tasks currently on a worker
+    pub fn running_count(&self) -> usize {
+        self.tasks
+            .iter()
+            .filter(|task| task.status == TaskStatus::Running)
+            .count()
+    }
+
+    /// Number of tasks queued or running
+    pub fn active_count(&self) -> usize {
+        self.tasks
+            .iter()
+            .filter(|task| task.status.is_active())
+            .count()
+    }
+
+    /// Whether any task is queued or running
+    pub fn is_busy(&self) -> bool {
+        self.active_count() > 0
+    }
+}
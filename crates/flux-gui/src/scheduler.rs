@@ -0,0 +1,254 @@
+//! Recurring sync jobs
+//!
+//! Schedules are checked while the GUI is open; a job that fell due while
+//! Flux was closed runs once at the next start instead of catching up on
+//! every missed run.
+
+use crate::task::{TaskId, TaskResult};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Runs kept in each schedule's history
+const MAX_HISTORY: usize = 20;
+
+/// Log lines kept for each run
+const MAX_RUN_LOG: usize = 200;
+
+/// How often a schedule runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Cadence {
+    /// Once an hour
+    Hourly,
+    /// Once a day
+    #[default]
+    Daily,
+    /// Once a week
+    Weekly,
+}
+
+impl Cadence {
+    /// Every cadence, in the order shown in the UI
+    pub const ALL: [Cadence; 3] = [Cadence::Hourly, Cadence::Daily, Cadence::Weekly];
+
+    /// Name shown in the UI
+    pub fn label(self) -> &'static str {
+        match self {
+            Cadence::Hourly => crate::t!("cadence-hourly"),
+            Cadence::Daily => crate::t!("cadence-daily"),
+            Cadence::Weekly => crate::t!("cadence-weekly"),
+        }
+    }
+
+    /// Time between two runs
+    pub fn interval(self) -> Duration {
+        match self {
+            Cadence::Hourly => Duration::hours(1),
+            Cadence::Daily => Duration::days(1),
+            Cadence::Weekly => Duration::weeks(1),
+        }
+    }
+}
+
+/// Outcome of one run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RunOutcome {
+    /// The sync task has not finished yet
+    Running,
+    /// The sync completed
+    Success,
+    /// The sync failed with this error
+    Failed(String),
+    /// The task was cancelled from the task list
+    Cancelled,
+}
+
+/// One run of a schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    /// When the sync task was submitted
+    pub started: DateTime<Utc>,
+    /// When the task finished
+    pub finished: Option<DateTime<Utc>>,
+    /// How it ended
+    pub outcome: RunOutcome,
+    /// Log messages reported by the task
+    pub log: Vec<String>,
+}
+
+/// A recurring sync from a directory into an archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    /// Stable identifier
+    pub id: u64,
+    /// Display name
+    pub name: String,
+    /// Directory to back up
+    pub source_dir: PathBuf,
+    /// Archive the sync writes
+    pub target_archive: PathBuf,
+    /// How often to run
+    pub cadence: Cadence,
+    /// Disabled schedules only run on demand
+    pub enabled: bool,
+    /// Past runs, newest first
+    pub history: Vec<RunRecord>,
+}
+
+impl Schedule {
+    /// The most recent run
+    pub fn last_run(&self) -> Option<&RunRecord> {
+        self.history.first()
+    }
+
+    /// When the schedule runs next, or `None` if it is disabled
+    ///
+    /// A schedule that has never run is due immediately.
+    pub fn next_run(&self) -> Option<DateTime<Utc>> {
+        if !self.enabled {
+            return None;
+        }
+        Some(match self.last_run() {
+            Some(run) => run.started + self.cadence.interval(),
+            None => DateTime::<Utc>::MIN_UTC,
+        })
+    }
+}
+
+/// All schedules plus the runs currently in progress
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    schedules: Vec<Schedule>,
+    /// Sync task started for each running schedule
+    running: HashMap<TaskId, u64>,
+}
+
+impl Scheduler {
+    /// Restore saved schedules
+    ///
+    /// Runs that were in progress when Flux closed are marked as failed.
+    pub fn new(mut schedules: Vec<Schedule>) -> Self {
+        for run in schedules.iter_mut().flat_map(|s| s.history.iter_mut()) {
+            if run.outcome == RunOutcome::Running {
+                run.outcome = RunOutcome::Failed("Interrupted when Flux closed".to_string());
+            }
+        }
+        Self {
+            schedules,
+            running: HashMap::new(),
+        }
+    }
+
+    /// Schedules in the order they were added
+    pub fn schedules(&self) -> &[Schedule] {
+        &self.schedules
+    }
+
+    /// Look up a schedule for editing
+    pub fn get_mut(&mut self, id: u64) -> Option<&mut Schedule> {
+        self.schedules.iter_mut().find(|s| s.id == id)
+    }
+
+    /// Add an enabled schedule and return its id
+    pub fn add(
+        &mut self,
+        name: String,
+        source_dir: PathBuf,
+        target_archive: PathBuf,
+        cadence: Cadence,
+    ) -> u64 {
+        let id = self.schedules.iter().map(|s| s.id + 1).max().unwrap_or(1);
+        self.schedules.push(Schedule {
+            id,
+            name,
+            source_dir,
+            target_archive,
+            cadence,
+            enabled: true,
+            history: Vec::new(),
+        });
+        id
+    }
+
+    /// Delete a schedule and its history
+    pub fn remove(&mut self, id: u64) {
+        self.schedules.retain(|s| s.id != id);
+        self.running.retain(|_, schedule| *schedule != id);
+    }
+
+    /// Whether a run of `id` is in progress
+    pub fn is_running(&self, id: u64) -> bool {
+        self.running.values().any(|&schedule| schedule == id)
+    }
+
+    /// Tasks of the runs in progress
+    pub fn running_tasks(&self) -> Vec<TaskId> {
+        self.running.keys().copied().collect()
+    }
+
+    /// Enabled schedules that are due at `now` and not already running
+    pub fn due(&self, now: DateTime<Utc>) -> Vec<u64> {
+        self.schedules
+            .iter()
+            .filter(|s| s.next_run().is_some_and(|next| next <= now))
+            .filter(|s| !self.is_running(s.id))
+            .map(|s| s.id)
+            .collect()
+    }
+
+    /// Record that `task_id` is a run of schedule `id`
+    pub fn start_run(&mut self, id: u64, task_id: TaskId, now: DateTime<Utc>) {
+        let Some(schedule) = self.get_mut(id) else {
+            return;
+        };
+        schedule.history.insert(
+            0,
+            RunRecord {
+                started: now,
+                finished: None,
+                outcome: RunOutcome::Running,
+                log: Vec::new(),
+            },
+        );
+        schedule.history.truncate(MAX_HISTORY);
+        self.running.insert(task_id, id);
+    }
+
+    /// Add a log message from a running task
+    pub fn record_log(&mut self, task_id: TaskId, message: &str) {
+        if let Some(run) = self.current_run(task_id) {
+            if run.log.len() < MAX_RUN_LOG {
+                run.log.push(message.to_string());
+            }
+        }
+    }
+
+    /// Close the run belonging to `task_id`; returns the schedule name if
+    /// the task was a scheduled run
+    pub fn finish_run(
+        &mut self,
+        task_id: TaskId,
+        result: &TaskResult,
+        now: DateTime<Utc>,
+    ) -> Option<String> {
+        let run = self.current_run(task_id)?;
+        run.finished = Some(now);
+        run.outcome = match result {
+            TaskResult::Success => RunOutcome::Success,
+            TaskResult::Error(e) => RunOutcome::Failed(e.clone()),
+            TaskResult::Cancelled => RunOutcome::Cancelled,
+        };
+
+        let id = self.running.remove(&task_id)?;
+        self.schedules
+            .iter()
+            .find(|s| s.id == id)
+            .map(|s| s.name.clone())
+    }
+
+    fn current_run(&mut self, task_id: TaskId) -> Option<&mut RunRecord> {
+        let id = *self.running.get(&task_id)?;
+        self.get_mut(id)?.history.first_mut()
+    }
+}
@@ -0,0 +1,179 @@
+//! Work left unfinished when the app closed
+//!
+//! The job being set up in the pack, extract or sync view and every task
+//! still queued or running are saved with the rest of the app state. On the
+//! next launch the user is asked whether to bring them back. Interrupted
+//! tasks start again from the beginning.
+
+use crate::history::PackRecipe;
+use crate::t;
+use crate::task::{TaskCommand, TaskId};
+use flux_core::archive::extractor::ArchiveEntry;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A job set up in one of the task views but not started
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SessionDraft {
+    /// Files chosen in the pack view; `recipe.format` is the format value
+    /// from the view, which may be `smart`
+    Pack {
+        recipe: PackRecipe,
+        output: Option<PathBuf>,
+        /// Cloud prefix the archive would be uploaded to
+        cloud_destination: Option<String>,
+    },
+    /// Archives chosen in the extract view
+    Extract {
+        archives: Vec<PathBuf>,
+        output_dir: Option<PathBuf>,
+        hoist: bool,
+    },
+    /// Folders chosen in the sync view
+    Sync {
+        source_dir: Option<PathBuf>,
+        target_archive: Option<PathBuf>,
+        manifest: Option<PathBuf>,
+    },
+}
+
+/// A queued or running task, as needed to start it again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SavedTask {
+    /// Pack into a local archive
+    Pack { output: PathBuf, recipe: PackRecipe },
+    /// Pack and upload to cloud storage
+    CloudPack { url: String, recipe: PackRecipe },
+    /// Extract an archive, or only some of its entries
+    Extract {
+        archive: PathBuf,
+        output_dir: PathBuf,
+        entries: Option<Vec<ArchiveEntry>>,
+        hoist: bool,
+    },
+    /// Extract an archive streamed from cloud storage
+    CloudExtract { url: String, output_dir: PathBuf },
+    /// Sync a folder into an archive
+    Sync {
+        source_dir: PathBuf,
+        target_archive: PathBuf,
+        old_manifest: Option<PathBuf>,
+    },
+}
+
+impl SavedTask {
+    /// What is needed to run `command` again
+    ///
+    /// Pack options cannot be saved as they are, so packs need the recipe
+    /// they were started from and are skipped without one.
+    pub fn from_command(command: &TaskCommand, recipe: Option<PackRecipe>) -> Option<Self> {
+        Some(match command {
+            TaskCommand::Pack { output, .. } => SavedTask::Pack {
+                output: output.clone(),
+                recipe: recipe?,
+            },
+            TaskCommand::CloudPack { url, .. } => SavedTask::CloudPack {
+                url: url.clone(),
+                recipe: recipe?,
+            },
+            TaskCommand::Extract {
+                archive,
+                output_dir,
+                entries,
+                hoist,
+                ..
+            } => SavedTask::Extract {
+                archive: archive.clone(),
+                output_dir: output_dir.clone(),
+                entries: entries.clone(),
+                hoist: *hoist,
+            },
+            TaskCommand::CloudExtract {
+                url, output_dir, ..
+            } => SavedTask::CloudExtract {
+                url: url.clone(),
+                output_dir: output_dir.clone(),
+            },
+            TaskCommand::Sync {
+                source_dir,
+                target_archive,
+                old_manifest,
+                ..
+            } => SavedTask::Sync {
+                source_dir: source_dir.clone(),
+                target_archive: target_archive.clone(),
+                old_manifest: old_manifest.clone(),
+            },
+        })
+    }
+
+    /// Label for the restore dialog, as the task list shows it
+    pub fn describe(&self) -> String {
+        fn name(path: &Path) -> String {
+            path.file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string())
+        }
+        fn cloud_name(url: &str) -> &str {
+            url.rsplit('/').next().unwrap_or(url)
+        }
+
+        match self {
+            SavedTask::Pack { output, .. } => t!("task-label-pack", name = name(output)),
+            SavedTask::CloudPack { url, .. } => t!("task-label-pack", name = cloud_name(url)),
+            SavedTask::Extract { archive, .. } => t!("task-label-extract", name = name(archive)),
+            SavedTask::CloudExtract { url, .. } => {
+                t!("task-label-extract", name = cloud_name(url))
+            }
+            SavedTask::Sync { target_archive, .. } => {
+                t!("task-label-sync", name = name(target_archive))
+            }
+        }
+    }
+}
+
+/// Everything saved for the next launch
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// Job being set up
+    pub draft: Option<SessionDraft>,
+    /// Tasks that had not finished, in submission order
+    pub tasks: Vec<SavedTask>,
+}
+
+impl Session {
+    /// Whether there is nothing to restore
+    pub fn is_empty(&self) -> bool {
+        self.draft.is_none() && self.tasks.is_empty()
+    }
+}
+
+/// Saved forms of the tasks submitted in this session, by task id
+///
+/// Entries are dropped once their task finishes, so whatever is left when
+/// the app closes was interrupted.
+#[derive(Debug, Default)]
+pub struct RestartableTasks {
+    tasks: HashMap<TaskId, SavedTask>,
+}
+
+impl RestartableTasks {
+    /// Remember how to start task `id` again
+    pub fn insert(&mut self, id: TaskId, task: SavedTask) {
+        self.tasks.insert(id, task);
+    }
+
+    /// Forget a task that finished, returning how it was started
+    pub fn finish(&mut self, id: TaskId) -> Option<SavedTask> {
+        self.tasks.remove(&id)
+    }
+
+    /// Saved forms of the tasks in `ids`, in that order
+    pub fn saved(&self, ids: impl IntoIterator<Item = TaskId>) -> Vec<SavedTask> {
+        ids.into_iter()
+            .filter_map(|id| self.tasks.get(&id).cloned())
+            .collect()
+    }
+}
@@ -0,0 +1,149 @@
+//! User preferences shared by the pack, extract and sync views
+
+use crate::i18n::Language;
+use crate::shortcuts::Shortcuts;
+use crate::task::ResourceLimits;
+use crate::theme::Appearance;
+use flux_core::archive::{archive_format, parse_format};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Format value that lets flux-core pick the algorithm from the inputs
+pub const SMART_FORMAT: &str = "smart";
+
+/// Archive formats offered when packing, as (value, label, description)
+///
+/// Labels and descriptions are message keys for [`crate::i18n::tr`].
+pub const PACK_FORMATS: &[(&str, &str, &str)] = &[
+    (SMART_FORMAT, "format-smart", "format-smart-desc"),
+    ("zip", "format-zip", "format-zip-desc"),
+    ("tar.gz", "format-tar-gz", "format-tar-gz-desc"),
+    ("tar.zst", "format-tar-zst", "format-tar-zst-desc"),
+    ("tar.xz", "format-tar-xz", "format-tar-xz-desc"),
+];
+
+/// Compression level used by sync when the user has not picked one
+const DEFAULT_SYNC_LEVEL: u32 = 6;
+
+/// A named cloud storage location
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CloudProfile {
+    /// Display name
+    pub name: String,
+    /// Base URL, e.g. `s3://bucket/backups`
+    pub url: String,
+    /// Custom endpoint for S3-compatible services
+    pub endpoint: Option<String>,
+}
+
+/// Application settings edited in the Settings view
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Format preselected in the pack view
+    pub default_format: String,
+    /// Compression level; `None` lets flux-core choose
+    pub compression_level: Option<u32>,
+    /// Worker threads; `None` uses every available core
+    pub threads: Option<usize>,
+    /// Use the dark theme
+    pub dark_mode: bool,
+    /// Accent color, text size and density
+    pub appearance: Appearance,
+    /// Ask before replacing an existing archive or extracting into a
+    /// non-empty directory
+    pub confirm_overwrite: bool,
+    /// Append what each task logs to a file of its own, kept after the
+    /// log panel drops old lines
+    pub save_task_logs: bool,
+    /// Saved cloud storage locations
+    pub cloud_profiles: Vec<CloudProfile>,
+    /// How many tasks may run at the same time; the rest wait in the queue
+    pub max_concurrent_tasks: usize,
+    /// Priority and rate caps of new tasks; the pack view can change them
+    /// for one archive
+    pub limits: ResourceLimits,
+    /// Language of the interface
+    pub language: Language,
+    /// Keyboard shortcuts the user changed
+    pub shortcuts: Shortcuts,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            default_format: SMART_FORMAT.to_string(),
+            compression_level: None,
+            threads: None,
+            dark_mode: false,
+            appearance: Appearance::default(),
+            confirm_overwrite: true,
+            save_task_logs: false,
+            cloud_profiles: Vec::new(),
+            max_concurrent_tasks: 2,
+            limits: ResourceLimits::default(),
+            language: Language::from_env(),
+            shortcuts: Shortcuts::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Highest level accepted by the compressor behind `format`
+    ///
+    /// Smart packing usually picks zstd, so it offers the zstd range; the
+    /// level is clamped once the actual algorithm is known.
+    pub fn max_level(format: &str) -> u32 {
+        match format {
+            "tar.zst" | SMART_FORMAT => 22,
+            "tar.br" => 11,
+            _ => 9,
+        }
+    }
+
+    /// Pack options for `format` using the configured level and threads
+    pub fn pack_options(&self, format: &str) -> flux_core::archive::PackOptions {
+        flux_core::archive::PackOptions {
+            smart: false, // The user explicitly selected a format
+            algorithm: algorithm_for_format(format),
+            level: self
+                .compression_level
+                .map(|level| level.min(Self::max_level(format))),
+            threads: self.threads,
+            force_compress: false,
+            follow_symlinks: false,
+            max_depth: None,
+            filter: flux_core::archive::PackFilter::default(),
+            metadata: None,
+            clamp_mtime: None,
+            normalize_names: flux_core::archive::NameNormalization::Keep,
+            strip_prefix: None,
+            prefix: None,
+            use_mmap: false,
+            transform: None,
+            time_budget: None,
+            paranoid: false,
+            hashes: None,
+            entry_order: flux_core::archive::PackOrder::Walk,
+        }
+    }
+
+    /// Pack options for a sync into `format`, which always sets a level
+    pub fn sync_options(&self, format: &str) -> flux_core::archive::PackOptions {
+        let mut options = self.pack_options(format);
+        options.level = options.level.or(Some(DEFAULT_SYNC_LEVEL));
+        options
+    }
+}
+
+/// Maps a format name or archive file name to a flux-core algorithm
+pub fn algorithm_for_format(format: &str) -> Option<String> {
+    let format = parse_format(format).or_else(|| archive_format(Path::new(format)))?;
+    match format {
+        "tar.gz" => Some("gz".to_string()),
+        "tar.zst" => Some("zst".to_string()),
+        "tar.xz" => Some("xz".to_string()),
+        "zip" => Some("zip".to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,310 @@
+//! Keyboard shortcuts and the commands they trigger
+//!
+//! Every command can be run from the command palette; most also have a
+//! default shortcut. Shortcuts the user changed in Settings are stored as
+//! overrides, so commands added later still get their defaults.
+
+use crate::t;
+use eframe::egui::{self, Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Something the user can do from the keyboard or the command palette
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Command {
+    /// Pick an archive and open it in the browser
+    OpenArchive,
+    /// Pick files to pack
+    PackFiles,
+    /// Start packing, extracting or syncing in the current view
+    StartTask,
+    /// Cancel every queued and running task
+    CancelTasks,
+    /// Pause every running task, or resume them if any is paused
+    PauseTasks,
+    /// Show or hide the log panel
+    ToggleLogs,
+    /// Show or hide the task panel
+    ToggleTasks,
+    /// Open the command palette
+    OpenPalette,
+    /// Switch between the light and dark theme
+    ToggleTheme,
+    /// Go to the welcome view
+    ShowWelcome,
+    /// Go to the pack view
+    ShowPack,
+    /// Go to the extract view
+    ShowExtract,
+    /// Go to the sync view
+    ShowSync,
+    /// Go to the cloud view
+    ShowCloud,
+    /// Go to the schedules view
+    ShowSchedules,
+    /// Go to the settings view
+    ShowSettings,
+}
+
+impl Command {
+    /// Every command, in the order listed in the palette and Settings
+    pub const ALL: [Command; 16] = [
+        Command::OpenPalette,
+        Command::OpenArchive,
+        Command::PackFiles,
+        Command::StartTask,
+        Command::CancelTasks,
+        Command::PauseTasks,
+        Command::ToggleLogs,
+        Command::ToggleTasks,
+        Command::ToggleTheme,
+        Command::ShowWelcome,
+        Command::ShowPack,
+        Command::ShowExtract,
+        Command::ShowSync,
+        Command::ShowCloud,
+        Command::ShowSchedules,
+        Command::ShowSettings,
+    ];
+
+    /// Name shown in the palette and Settings
+    pub fn label(self) -> &'static str {
+        match self {
+            Command::OpenArchive => t!("command-open-archive"),
+            Command::PackFiles => t!("command-pack-files"),
+            Command::StartTask => t!("command-start-task"),
+            Command::CancelTasks => t!("command-cancel-tasks"),
+            Command::PauseTasks => t!("command-pause-tasks"),
+            Command::ToggleLogs => t!("command-toggle-logs"),
+            Command::ToggleTasks => t!("command-toggle-tasks"),
+            Command::OpenPalette => t!("command-palette"),
+            Command::ToggleTheme => t!("command-toggle-theme"),
+            Command::ShowWelcome => t!("command-show-welcome"),
+            Command::ShowPack => t!("command-show-pack"),
+            Command::ShowExtract => t!("command-show-extract"),
+            Command::ShowSync => t!("command-show-sync"),
+            Command::ShowCloud => t!("command-show-cloud"),
+            Command::ShowSchedules => t!("command-show-schedules"),
+            Command::ShowSettings => t!("command-show-settings"),
+        }
+    }
+
+    /// Shortcut the command has until the user changes it
+    pub fn default_shortcut(self) -> Option<Shortcut> {
+        let key = match self {
+            Command::OpenArchive => Key::O,
+            Command::PackFiles => Key::N,
+            Command::StartTask => Key::Enter,
+            Command::CancelTasks => Key::Period,
+            Command::ToggleLogs => Key::L,
+            Command::ToggleTasks => Key::T,
+            Command::OpenPalette => Key::K,
+            Command::PauseTasks | Command::ToggleTheme => return None,
+            Command::ShowWelcome => Key::Num1,
+            Command::ShowPack => Key::Num2,
+            Command::ShowExtract => Key::Num3,
+            Command::ShowSync => Key::Num4,
+            Command::ShowCloud => Key::Num5,
+            Command::ShowSchedules => Key::Num6,
+            Command::ShowSettings => Key::Comma,
+        };
+        Some(Shortcut::new(Modifiers::COMMAND, key))
+    }
+}
+
+/// A key together with its modifiers
+///
+/// Stored as text such as `Ctrl+Shift+K`, where `Ctrl` means Cmd on macOS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Shortcut(KeyboardShortcut);
+
+impl Shortcut {
+    /// Shortcut for `key` pressed together with `modifiers`
+    pub fn new(modifiers: Modifiers, key: Key) -> Self {
+        Self(KeyboardShortcut::new(modifiers, key))
+    }
+
+    /// The shortcut as shown to the user, using the platform's names
+    pub fn display(&self, ctx: &egui::Context) -> String {
+        ctx.format_shortcut(&self.0)
+    }
+
+    /// Whether pressing the shortcut would also type into a text field
+    fn types_text(&self) -> bool {
+        let modifiers = self.0.modifiers;
+        let function_key = matches!(
+            self.0.logical_key,
+            Key::F1
+                | Key::F2
+                | Key::F3
+                | Key::F4
+                | Key::F5
+                | Key::F6
+                | Key::F7
+                | Key::F8
+                | Key::F9
+                | Key::F10
+                | Key::F11
+                | Key::F12
+        );
+        !(modifiers.command || modifiers.ctrl || modifiers.alt || modifiers.mac_cmd || function_key)
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let modifiers = self.0.modifiers;
+        if modifiers.command || modifiers.ctrl || modifiers.mac_cmd {
+            f.write_str("Ctrl+")?;
+        }
+        if modifiers.alt {
+            f.write_str("Alt+")?;
+        }
+        if modifiers.shift {
+            f.write_str("Shift+")?;
+        }
+        f.write_str(self.0.logical_key.name())
+    }
+}
+
+impl From<Shortcut> for String {
+    fn from(shortcut: Shortcut) -> Self {
+        shortcut.to_string()
+    }
+}
+
+impl TryFrom<String> for Shortcut {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        // The plus key itself is written as `Ctrl++`
+        let (prefix, key) = match text.strip_suffix("++") {
+            Some(prefix) => (Some(prefix), "+"),
+            None => match text.rsplit_once('+') {
+                Some((prefix, key)) => (Some(prefix), key),
+                None => (None, text.as_str()),
+            },
+        };
+
+        let mut modifiers = Modifiers::NONE;
+        for part in prefix.into_iter().flat_map(|prefix| prefix.split('+')) {
+            match part.trim().to_lowercase().as_str() {
+                "ctrl" | "cmd" => modifiers = modifiers | Modifiers::COMMAND,
+                "alt" => modifiers = modifiers | Modifiers::ALT,
+                "shift" => modifiers = modifiers | Modifiers::SHIFT,
+                _ => return Err(format!("Unknown modifier {:?} in {:?}", part, text)),
+            }
+        }
+        let key = Key::from_name(key.trim()).ok_or_else(|| format!("Unknown key in {:?}", text))?;
+
+        Ok(Shortcut::new(modifiers, key))
+    }
+}
+
+/// Shortcuts of every command, with the user's changes applied
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Shortcuts {
+    /// Shortcuts the user changed; `None` removes a default one
+    overrides: BTreeMap<Command, Option<Shortcut>>,
+}
+
+impl Shortcuts {
+    /// Current shortcut of `command`
+    pub fn get(&self, command: Command) -> Option<Shortcut> {
+        match self.overrides.get(&command) {
+            Some(shortcut) => *shortcut,
+            None => command.default_shortcut(),
+        }
+    }
+
+    /// Assign `shortcut` to `command`, returning the command that had it
+    /// before, which is left without one
+    pub fn set(&mut self, command: Command, shortcut: Option<Shortcut>) -> Option<Command> {
+        let previous = shortcut.and_then(|shortcut| {
+            Command::ALL
+                .into_iter()
+                .find(|&other| other != command && self.get(other) == Some(shortcut))
+        });
+        if let Some(other) = previous {
+            self.assign(other, None);
+        }
+        self.assign(command, shortcut);
+        previous
+    }
+
+    fn assign(&mut self, command: Command, shortcut: Option<Shortcut>) {
+        if shortcut == command.default_shortcut() {
+            self.overrides.remove(&command);
+        } else {
+            self.overrides.insert(command, shortcut);
+        }
+    }
+
+    /// Whether any shortcut differs from its default
+    pub fn is_customized(&self) -> bool {
+        !self.overrides.is_empty()
+    }
+
+    /// Restore every default shortcut
+    pub fn reset(&mut self) {
+        self.overrides.clear();
+    }
+
+    /// Command whose shortcut was pressed this frame, consuming the key
+    ///
+    /// Shortcuts without a modifier are ignored while a text field has
+    /// focus so they can still be typed.
+    pub fn pressed(&self, ctx: &egui::Context) -> Option<Command> {
+        let typing = ctx.wants_keyboard_input();
+        // Modifiers must match exactly so Ctrl+K does not fire for Ctrl+Shift+K
+        let (command, shortcut) = ctx.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    repeat: false,
+                    modifiers,
+                    ..
+                } => Command::ALL.into_iter().find_map(|command| {
+                    let shortcut = self.get(command)?;
+                    (shortcut.0.logical_key == *key
+                        && modifiers.matches_exact(shortcut.0.modifiers)
+                        && !(typing && shortcut.types_text()))
+                    .then_some((command, shortcut))
+                }),
+                _ => None,
+            })
+        })?;
+        ctx.input_mut(|i| i.consume_shortcut(&shortcut.0));
+        Some(command)
+    }
+
+    /// Shortcut pressed this frame, for recording a new one
+    ///
+    /// Modifier keys alone and Escape, which cancels recording, are not
+    /// returned.
+    pub fn capture(ctx: &egui::Context) -> Option<Shortcut> {
+        ctx.input(|i| {
+            i.events.iter().find_map(|event| match event {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } if *key != Key::Escape => Some(Shortcut::new(
+                    Modifiers {
+                        command: modifiers.command || modifiers.ctrl,
+                        alt: modifiers.alt,
+                        shift: modifiers.shift,
+                        ..Modifiers::NONE
+                    },
+                    *key,
+                )),
+                _ => None,
+            })
+        })
+    }
+}
@@ -0,0 +1,108 @@
+//! Preview of the smart compression strategy for the pack view
+//!
+//! flux-core decides per input; the GUI runs the same analysis up front so
+//! the user can see the choice, and then packs with exactly that choice.
+
+use crossbeam_channel::Receiver;
+use flux_core::strategy::{Algorithm, CompressionStrategy};
+use std::path::{Path, PathBuf};
+
+/// What the smart strategy chose for a set of inputs
+#[derive(Debug, Clone)]
+pub struct SmartPick {
+    /// Compression algorithm
+    pub algorithm: Algorithm,
+    /// Compression level
+    pub level: u32,
+    /// Message key explaining why this algorithm suits the inputs
+    pub reason: &'static str,
+    /// Input the decision was based on
+    pub basis: String,
+}
+
+/// Analyze `inputs` on a background thread
+///
+/// With several inputs the largest one stands in for the whole job, as it
+/// dominates both the archive size and the packing time.
+pub fn analyze(inputs: Vec<PathBuf>) -> Receiver<Result<SmartPick, String>> {
+    let (sender, receiver) = crossbeam_channel::bounded(1);
+
+    std::thread::spawn(move || {
+        let result = (|| {
+            let input = inputs
+                .iter()
+                .max_by_key(|path| path_size(path))
+                .ok_or_else(|| crate::t!("smart-nothing-to-analyze").to_string())?;
+
+            let strategy = if input.is_dir() {
+                CompressionStrategy::smart_for_directory(input, None, None)
+                    .map_err(|e| e.to_string())?
+            } else {
+                CompressionStrategy::smart(input, None, None)
+            };
+
+            Ok(SmartPick {
+                algorithm: strategy.algorithm,
+                level: strategy.level,
+                reason: reason(&strategy),
+                basis: input
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| input.display().to_string()),
+            })
+        })();
+
+        let _ = sender.send(result);
+    });
+
+    receiver
+}
+
+/// Archive format that uses `algorithm`
+pub fn format_for(algorithm: Algorithm) -> &'static str {
+    match algorithm {
+        Algorithm::Store => "tar",
+        Algorithm::Gzip => "tar.gz",
+        Algorithm::Zstd => "tar.zst",
+        Algorithm::Xz => "tar.xz",
+        Algorithm::Brotli => "tar.br",
+    }
+}
+
+/// Message key explaining a choice of the smart strategy
+///
+/// Mirrors the rules in `flux_core::strategy`; gzip and brotli are only
+/// chosen by custom rules in the user's configuration.
+fn reason(strategy: &CompressionStrategy) -> &'static str {
+    match strategy.algorithm {
+        Algorithm::Store => "smart-reason-store",
+        Algorithm::Zstd if strategy.level >= 6 => "smart-reason-text",
+        Algorithm::Zstd if strategy.level <= 1 => "smart-reason-small-files",
+        Algorithm::Zstd => "smart-reason-mixed",
+        Algorithm::Xz => "smart-reason-large",
+        Algorithm::Gzip | Algorithm::Brotli => "smart-reason-custom-rule",
+    }
+}
+
+fn path_size(path: &Path) -> u64 {
+    if path.is_dir() {
+        walkdir_size(path)
+    } else {
+        path.metadata().map(|m| m.len()).unwrap_or(0)
+    }
+}
+
+fn walkdir_size(path: &Path) -> u64 {
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| match entry.file_type() {
+                    Ok(kind) if kind.is_dir() => walkdir_size(&entry.path()),
+                    Ok(kind) if kind.is_file() => entry.metadata().map(|m| m.len()).unwrap_or(0),
+                    _ => 0,
+                })
+                .sum()
+        })
+        .unwrap_or(0)
+}
@@ -1,7 +1,154 @@
 //! Background task handling for flux-gui
 
+use crate::progress_tracker::{Phase, ProgressTracker, ThroughputHistory};
+use crate::t;
+use crossbeam_channel::{SendError, Sender};
+use flux_core::throttle::RateLimiter;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
-use std::sync::{atomic::AtomicBool, Arc};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Condvar, Mutex, MutexGuard, PoisonError,
+};
+use std::time::{Duration, Instant};
+
+/// Identifier assigned to every submitted task
+pub type TaskId = u64;
+
+/// How much of the machine a task may take, so it can run alongside other
+/// work
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// Run at the lowest CPU and disk priority, compressing on one thread
+    /// unless a thread count was chosen
+    pub background: bool,
+    /// Most megabytes a second to read or write on disk
+    pub disk_mb_per_sec: Option<u32>,
+    /// Most megabytes a second to upload or download
+    pub network_mb_per_sec: Option<u32>,
+}
+
+impl ResourceLimits {
+    /// Apply the limits that are pack options
+    pub fn apply(&self, options: &mut flux_core::archive::PackOptions) {
+        if self.background && options.threads.is_none() {
+            options.threads = Some(1);
+        }
+    }
+}
+
+/// Rate in bytes a second of a cap in megabytes a second
+fn limiter(mb_per_sec: Option<u32>) -> Option<RateLimiter> {
+    mb_per_sec.map(|mb| RateLimiter::new(u64::from(mb) * 1024 * 1024))
+}
+
+/// Cancel and pause requests for a running task, and its resource limits
+///
+/// The UI keeps one clone and the worker another. Workers check it between
+/// files, entries or chunks: they stop when it is cancelled and park in
+/// [`TaskControl::wait_while_paused`] while it is paused. They pass the
+/// bytes of each chunk to [`TaskControl::throttle_disk`] or
+/// [`TaskControl::throttle_network`] to keep to the limits.
+#[derive(Debug, Clone, Default)]
+pub struct TaskControl(Arc<ControlState>);
+
+#[derive(Debug, Default)]
+struct ControlState {
+    cancelled: AtomicBool,
+    paused: Mutex<bool>,
+    wake: Condvar,
+    limits: ResourceLimits,
+    disk: Option<RateLimiter>,
+    network: Option<RateLimiter>,
+}
+
+impl TaskControl {
+    /// Create a control for a new task
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a control for a new task kept to `limits`
+    pub fn with_limits(limits: ResourceLimits) -> Self {
+        Self(Arc::new(ControlState {
+            disk: limiter(limits.disk_mb_per_sec),
+            network: limiter(limits.network_mb_per_sec),
+            limits,
+            ..ControlState::default()
+        }))
+    }
+
+    /// The limits the task runs with
+    pub fn limits(&self) -> &ResourceLimits {
+        &self.0.limits
+    }
+
+    /// Count `bytes` read or written on disk, sleeping as long as the disk
+    /// limit asks
+    pub fn throttle_disk(&self, bytes: u64) {
+        if let Some(limiter) = &self.0.disk {
+            limiter.consume(bytes);
+        }
+    }
+
+    /// Count `bytes` uploaded or downloaded, sleeping as long as the
+    /// network limit asks
+    pub fn throttle_network(&self, bytes: u64) {
+        if let Some(limiter) = &self.0.network {
+            limiter.consume(bytes);
+        }
+    }
+
+    /// Ask the worker to stop, waking it if it is paused
+    pub fn cancel(&self) {
+        self.0.cancelled.store(true, Ordering::SeqCst);
+        let _paused = self.lock_paused();
+        self.0.wake.notify_all();
+    }
+
+    /// Whether the task was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Ask the worker to park at its next check
+    pub fn pause(&self) {
+        *self.lock_paused() = true;
+    }
+
+    /// Let a paused worker continue
+    pub fn resume(&self) {
+        *self.lock_paused() = false;
+        self.0.wake.notify_all();
+    }
+
+    /// Whether the task is paused
+    pub fn is_paused(&self) -> bool {
+        *self.lock_paused()
+    }
+
+    /// Block while the task is paused and not cancelled
+    ///
+    /// Returns the time spent waiting, so it can be left out of speed and
+    /// ETA figures.
+    pub fn wait_while_paused(&self) -> Duration {
+        let started = Instant::now();
+        let mut paused = self.lock_paused();
+        while *paused && !self.is_cancelled() {
+            paused = self
+                .0
+                .wake
+                .wait(paused)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        started.elapsed()
+    }
+
+    fn lock_paused(&self) -> MutexGuard<'_, bool> {
+        self.0.paused.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
 
 /// Commands sent from UI to background thread
 pub enum TaskCommand {
@@ -13,8 +160,8 @@ pub enum TaskCommand {
         output: PathBuf,
         /// Packing options
         options: flux_core::archive::PackOptions,
-        /// Cancel flag
-        cancel_flag: Arc<AtomicBool>,
+        /// Cancel and pause requests
+        control: TaskControl,
     },
     /// Extract an archive
     Extract {
@@ -22,10 +169,32 @@ pub enum TaskCommand {
         archive: PathBuf,
         /// Directory to extract to
         output_dir: PathBuf,
+        /// Only extract these entries; the whole archive when `None`
+        entries: Option<Vec<flux_core::archive::extractor::ArchiveEntry>>,
         /// Enable smart directory hoisting
         hoist: bool,
-        /// Cancel flag
-        cancel_flag: Arc<AtomicBool>,
+        /// Cancel and pause requests
+        control: TaskControl,
+    },
+    /// Stream an archive from cloud storage and extract it
+    CloudExtract {
+        /// Cloud URL of the archive, e.g. `s3://bucket/backup.tar.zst`
+        url: String,
+        /// Directory to extract to
+        output_dir: PathBuf,
+        /// Cancel and pause requests
+        control: TaskControl,
+    },
+    /// Pack files and upload the archive to cloud storage
+    CloudPack {
+        /// Input files/directories to pack
+        inputs: Vec<PathBuf>,
+        /// Cloud URL of the archive to create
+        url: String,
+        /// Packing options
+        options: flux_core::archive::PackOptions,
+        /// Cancel and pause requests
+        control: TaskControl,
     },
     /// Sync/incremental backup
     Sync {
@@ -37,11 +206,48 @@ pub enum TaskCommand {
         old_manifest: Option<PathBuf>,
         /// Pack options
         options: flux_core::archive::PackOptions,
-        /// Cancel flag
-        cancel_flag: Arc<AtomicBool>,
+        /// Cancel and pause requests
+        control: TaskControl,
     },
 }
 
+impl TaskCommand {
+    /// Control shared with the worker running this command
+    pub fn control(&self) -> &TaskControl {
+        match self {
+            TaskCommand::Pack { control, .. }
+            | TaskCommand::Extract { control, .. }
+            | TaskCommand::CloudExtract { control, .. }
+            | TaskCommand::CloudPack { control, .. }
+            | TaskCommand::Sync { control, .. } => control,
+        }
+    }
+
+    /// Short human-readable description for the task list
+    pub fn describe(&self) -> String {
+        fn name(path: &std::path::Path) -> String {
+            path.file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.display().to_string())
+        }
+        fn cloud_name(url: &str) -> &str {
+            url.rsplit('/').next().unwrap_or(url)
+        }
+
+        match self {
+            TaskCommand::Pack { output, .. } => t!("task-label-pack", name = name(output)),
+            TaskCommand::Extract { archive, .. } => t!("task-label-extract", name = name(archive)),
+            TaskCommand::CloudExtract { url, .. } => {
+                t!("task-label-extract", name = cloud_name(url))
+            }
+            TaskCommand::CloudPack { url, .. } => t!("task-label-pack", name = cloud_name(url)),
+            TaskCommand::Sync { target_archive, .. } => {
+                t!("task-label-sync", name = name(target_archive))
+            }
+        }
+    }
+}
+
 /// Progress update from background thread
 #[derive(Debug, Clone)]
 pub struct ProgressUpdate {
@@ -68,6 +274,19 @@ pub enum TaskResult {
     Cancelled,
 }
 
+/// Request for the password of an encrypted archive entry
+#[derive(Debug, Clone)]
+pub struct PasswordRequest {
+    /// Archive being extracted
+    pub archive: PathBuf,
+    /// Entry that needs the password
+    pub entry: PathBuf,
+    /// The previous password was wrong
+    pub retry: bool,
+    /// Where to send the password, or `None` to give up
+    pub reply: Sender<Option<String>>,
+}
+
 /// Messages sent from background thread to UI
 #[derive(Debug, Clone)]
 pub enum ToUi {
@@ -77,4 +296,58 @@ pub enum ToUi {
     Finished(TaskResult),
     /// Log message
     Log(String),
+    /// The worker is blocked until the user enters a password
+    PasswordRequest(PasswordRequest),
+    /// The destination refused the extraction; sent before the task fails
+    AccessDenied,
+    /// What a pack or extraction did to its entries; sent before the task
+    /// finishes
+    Report(flux_core::archive::OperationReport),
+    /// A sync wrote this manifest
+    Manifest(PathBuf),
+}
+
+/// A message from a worker, tagged with the task it belongs to
+#[derive(Debug, Clone)]
+pub struct TaskEvent {
+    /// Task that produced the message
+    pub id: TaskId,
+    /// The message itself
+    pub message: ToUi,
+}
+
+/// Sends messages for one task back to the UI
+///
+/// Handlers use this like a plain `Sender<ToUi>`; every message is tagged
+/// with the task id so the UI can route it to the right row. It also
+/// carries the throughput history their progress trackers start from.
+#[derive(Debug, Clone)]
+pub struct TaskReporter {
+    id: TaskId,
+    sender: Sender<TaskEvent>,
+    throughput: ThroughputHistory,
+}
+
+impl TaskReporter {
+    /// Create a reporter for task `id`
+    pub fn new(id: TaskId, sender: Sender<TaskEvent>, throughput: ThroughputHistory) -> Self {
+        Self {
+            id,
+            sender,
+            throughput,
+        }
+    }
+
+    /// A progress tracker for `phase` of the task
+    pub fn tracker(&self, phase: Phase) -> ProgressTracker {
+        ProgressTracker::new(phase, self.throughput.clone())
+    }
+
+    /// Send a message to the UI
+    pub fn send(&self, message: ToUi) -> Result<(), SendError<TaskEvent>> {
+        self.sender.send(TaskEvent {
+            id: self.id,
+            message,
+        })
+    }
 }
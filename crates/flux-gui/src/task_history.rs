@@ -0,0 +1,142 @@
+//! Finished tasks with their logs and reports
+//!
+//! The task list only shows a one-line status, and the log panel mixes the
+//! lines of every task. Each task is also followed here from submission:
+//! the last lines it logged, the report of what it did to its entries and
+//! the manifest a sync wrote are kept with it once it finishes, so the
+//! history list can show them task by task.
+
+use crate::queue::{TaskKind, TaskStatus};
+use crate::task::{TaskCommand, TaskEvent, TaskId, ToUi};
+use chrono::{DateTime, Local};
+use flux_core::archive::OperationReport;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+
+/// Finished tasks kept, the oldest dropped first
+pub const CAPACITY: usize = 100;
+
+/// Log lines kept for each task, the last ones it logged
+pub const LOG_EXCERPT_LINES: usize = 200;
+
+/// A task followed from submission
+#[derive(Debug, Clone)]
+pub struct TaskRecord {
+    pub id: TaskId,
+    pub kind: TaskKind,
+    pub label: String,
+    /// `None` until the task finishes
+    pub finished: Option<DateTime<Local>>,
+    pub status: TaskStatus,
+    /// Folder the task writes into: the extraction folder, or the folder
+    /// of the archive being written. `None` for uploads.
+    pub output_dir: Option<PathBuf>,
+    /// Manifest written by a sync
+    pub manifest: Option<PathBuf>,
+    /// What a pack or extraction did to its entries
+    pub report: Option<OperationReport>,
+    /// Last [`LOG_EXCERPT_LINES`] lines the task logged
+    pub log: VecDeque<String>,
+}
+
+impl TaskRecord {
+    /// Record of a task about to run `command`
+    pub fn new(command: &TaskCommand) -> Self {
+        let output_dir = match command {
+            TaskCommand::Pack { output, .. } => output.parent().map(PathBuf::from),
+            TaskCommand::Extract { output_dir, .. }
+            | TaskCommand::CloudExtract { output_dir, .. } => Some(output_dir.clone()),
+            TaskCommand::CloudPack { .. } => None,
+            TaskCommand::Sync { target_archive, .. } => target_archive.parent().map(PathBuf::from),
+        };
+        Self {
+            id: 0,
+            kind: TaskKind::from(command),
+            label: command.describe(),
+            finished: None,
+            status: TaskStatus::Queued,
+            output_dir,
+            manifest: None,
+            report: None,
+            log: VecDeque::new(),
+        }
+    }
+
+    /// The report as pretty-printed JSON
+    pub fn report_json(&self) -> Option<String> {
+        self.report
+            .as_ref()
+            .and_then(|report| serde_json::to_string_pretty(report).ok())
+    }
+}
+
+/// Tasks not finished yet, and the finished ones newest last
+#[derive(Debug, Default)]
+pub struct TaskHistory {
+    /// Tasks cancelled before they start never finish, but have nothing
+    /// worth keeping either
+    pending: HashMap<TaskId, TaskRecord>,
+    finished: VecDeque<TaskRecord>,
+}
+
+impl TaskHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start following task `id`, made from [`TaskRecord::new`] before
+    /// its command was submitted
+    pub fn submitted(&mut self, id: TaskId, record: TaskRecord) {
+        self.pending.insert(id, TaskRecord { id, ..record });
+    }
+
+    /// Keep a log line of task `id`
+    pub fn record_log(&mut self, id: TaskId, line: &str) {
+        if let Some(record) = self.pending.get_mut(&id) {
+            if record.log.len() == LOG_EXCERPT_LINES {
+                record.log.pop_front();
+            }
+            record.log.push_back(line.to_string());
+        }
+    }
+
+    /// Keep the report or manifest a worker sent
+    pub fn apply(&mut self, event: &TaskEvent) {
+        let Some(record) = self.pending.get_mut(&event.id) else {
+            return;
+        };
+        match &event.message {
+            ToUi::Report(report) => record.report = Some(report.clone()),
+            ToUi::Manifest(path) => record.manifest = Some(path.clone()),
+            _ => {}
+        }
+    }
+
+    /// Move task `id` to the finished tasks with its final `status`
+    pub fn finish(&mut self, id: TaskId, status: &TaskStatus) {
+        let Some(mut record) = self.pending.remove(&id) else {
+            return;
+        };
+        record.status = status.clone();
+        record.finished = Some(Local::now());
+        if self.finished.len() == CAPACITY {
+            self.finished.pop_front();
+        }
+        self.finished.push_back(record);
+    }
+
+    /// Finished tasks, newest first
+    pub fn finished(&self) -> impl Iterator<Item = &TaskRecord> {
+        self.finished.iter().rev()
+    }
+
+    /// The finished task `id`
+    pub fn get(&self, id: TaskId) -> Option<&TaskRecord> {
+        self.finished.iter().find(|record| record.id == id)
+    }
+
+    /// Forget the finished tasks
+    pub fn clear(&mut self) {
+        self.finished.clear();
+    }
+}
@@ -1,7 +1,22 @@
 //! Theme system for Flux GUI
 
 use egui::{Color32, FontId, Rounding, Stroke, Style, TextStyle, Visuals};
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// Accent colors offered next to the free color picker
+pub const ACCENT_PRESETS: [[u8; 3]; 6] = [
+    [0, 120, 212],
+    [0, 150, 136],
+    [46, 160, 67],
+    [128, 90, 213],
+    [230, 126, 34],
+    [214, 51, 132],
+];
+
+/// Text scale the Settings view allows
+pub const FONT_SCALE_RANGE: RangeInclusive<f32> = 0.8..=1.5;
 
 /// Theme mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -10,6 +25,51 @@ pub enum ThemeMode {
     Dark,
 }
 
+/// How tightly widgets are packed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Density {
+    Compact,
+    #[default]
+    Comfortable,
+    Spacious,
+}
+
+impl Density {
+    /// Every density, from tightest to loosest
+    pub const ALL: [Density; 3] = [Density::Compact, Density::Comfortable, Density::Spacious];
+
+    /// Base spacing between widgets, in points
+    pub fn spacing(self) -> f32 {
+        match self {
+            Density::Compact => 4.0,
+            Density::Comfortable => 8.0,
+            Density::Spacious => 12.0,
+        }
+    }
+}
+
+/// User adjustments applied on top of the light and dark palettes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Appearance {
+    /// Accent color as RGB; `None` keeps the palette's own
+    pub accent: Option<[u8; 3]>,
+    /// Multiplier for every text size
+    pub font_scale: f32,
+    /// Spacing between widgets
+    pub density: Density,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        Self {
+            accent: None,
+            font_scale: 1.0,
+            density: Density::Comfortable,
+        }
+    }
+}
+
 /// Color scheme for the application
 #[derive(Debug, Clone)]
 pub struct ColorScheme {
@@ -44,9 +104,38 @@ pub struct FluxTheme {
     pub colors: ColorScheme,
     pub rounding: f32,
     pub spacing: f32,
+    /// Customizations this theme was built with
+    pub appearance: Appearance,
 }
 
 impl FluxTheme {
+    /// Theme in `mode` with the user's accent, text size and density
+    pub fn new(mode: ThemeMode, appearance: Appearance) -> Self {
+        let mut theme = match mode {
+            ThemeMode::Light => Self::light(),
+            ThemeMode::Dark => Self::dark(),
+        };
+
+        if let Some([r, g, b]) = appearance.accent {
+            let accent = Color32::from_rgb(r, g, b);
+            theme.colors.primary = accent;
+            theme.colors.primary_hover = accent.lerp_to_gamma(Color32::WHITE, 0.15);
+            theme.colors.hyperlink = match mode {
+                ThemeMode::Light => accent,
+                // Plain accents can be too dark to read on the dark panels
+                ThemeMode::Dark => accent.lerp_to_gamma(Color32::WHITE, 0.3),
+            };
+        }
+        theme.spacing = appearance.density.spacing();
+        theme.appearance = Appearance {
+            font_scale: appearance
+                .font_scale
+                .clamp(*FONT_SCALE_RANGE.start(), *FONT_SCALE_RANGE.end()),
+            ..appearance
+        };
+        theme
+    }
+
     /// Check if the theme is in dark mode
     pub fn is_dark_mode(&self) -> bool {
         matches!(self.mode, ThemeMode::Dark)
@@ -71,6 +160,7 @@ impl FluxTheme {
             },
             rounding: 4.0,
             spacing: 8.0,
+            appearance: Appearance::default(),
         }
     }
 
@@ -93,15 +183,17 @@ impl FluxTheme {
             },
             rounding: 4.0,
             spacing: 8.0,
+            appearance: Appearance::default(),
         }
     }
 
-    /// Toggle between light and dark mode
+    /// Toggle between light and dark mode, keeping the customizations
     pub fn toggle(&mut self) {
-        *self = match self.mode {
-            ThemeMode::Light => Self::dark(),
-            ThemeMode::Dark => Self::light(),
+        let mode = match self.mode {
+            ThemeMode::Light => ThemeMode::Dark,
+            ThemeMode::Dark => ThemeMode::Light,
         };
+        *self = Self::new(mode, self.appearance.clone());
     }
 
     /// Apply the theme to egui context
@@ -167,11 +259,12 @@ impl FluxTheme {
         let mut text_styles = BTreeMap::new();
 
         // Base font sizes
-        let small_size = 12.0;
-        let body_size = 14.0;
-        let button_size = 14.0;
-        let heading_size = 20.0;
-        let monospace_size = 13.0;
+        let scale = self.appearance.font_scale;
+        let small_size = 12.0 * scale;
+        let body_size = 14.0 * scale;
+        let button_size = 14.0 * scale;
+        let heading_size = 20.0 * scale;
+        let monospace_size = 13.0 * scale;
 
         text_styles.insert(TextStyle::Small, FontId::proportional(small_size));
         text_styles.insert(TextStyle::Body, FontId::proportional(body_size));
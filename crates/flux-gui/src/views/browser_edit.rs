@@ -0,0 +1,291 @@
+//! Edit mode of the archive browser
+//!
+//! Entries of a local zip or tar archive can be renamed, deleted and added
+//! to while it is browsed. Changes are only collected as they are made;
+//! saving hands them all to [`Modifier`] at once, which rewrites the
+//! archive on a background thread, and the browser then reopens on the
+//! rewritten archive.
+
+use super::browser_view::{BrowserAction, BrowserState};
+use crate::t;
+use crate::theme::FluxTheme;
+use crossbeam_channel::Receiver;
+use eframe::egui;
+use egui_phosphor::regular;
+use flux_core::archive::Modifier;
+use std::path::{Path, PathBuf};
+
+/// One change to an entry, not saved yet
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    /// Rename an entry, or move a folder with its contents
+    Rename { from: PathBuf, to: PathBuf },
+    /// Remove an entry, or a folder with its contents
+    Delete(PathBuf),
+    /// Add a file or folder from disk as the entry `name`
+    Add { source: PathBuf, name: PathBuf },
+}
+
+/// Changes made in edit mode, in the order they were made
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveEdits {
+    changes: Vec<Edit>,
+}
+
+impl ArchiveEdits {
+    /// Record `edit`, unless the same change is already recorded
+    pub fn push(&mut self, edit: Edit) {
+        if !self.changes.contains(&edit) {
+            self.changes.push(edit);
+        }
+    }
+
+    /// Forget the change at `index`
+    pub fn remove(&mut self, index: usize) {
+        if index < self.changes.len() {
+            self.changes.remove(index);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Edit> {
+        self.changes.iter()
+    }
+
+    /// A [`Modifier`] making every change to `archive`
+    pub fn modifier(&self, archive: &Path) -> Modifier {
+        self.changes
+            .iter()
+            .fold(Modifier::new(archive), |modifier, edit| match edit {
+                Edit::Rename { from, to } => modifier.rename(from, to),
+                Edit::Delete(path) => modifier.delete(path),
+                Edit::Add { source, name } => modifier.add(source.clone(), name),
+            })
+    }
+}
+
+/// Edits being written into an archive, in the background
+pub struct EditSave {
+    pending: Receiver<Result<usize, String>>,
+}
+
+impl EditSave {
+    /// Start writing `edits` into `archive`
+    pub fn start(archive: &Path, edits: &ArchiveEdits) -> Self {
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let modifier = edits.modifier(archive);
+        std::thread::spawn(move || {
+            let _ = sender.send(modifier.apply().map_err(|e| e.to_string()));
+        });
+        Self { pending: receiver }
+    }
+
+    /// How many entries changed, or why saving failed, once it is done
+    pub fn poll(&self) -> Option<Result<usize, String>> {
+        match self.pending.try_recv() {
+            Ok(result) => Some(result),
+            Err(crossbeam_channel::TryRecvError::Empty) => None,
+            Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                Some(Err(t!("browser-edit-save-stopped").to_string()))
+            }
+        }
+    }
+}
+
+/// Draw the edit toolbar and the list of unsaved changes
+pub fn draw_edit_toolbar(
+    ui: &mut egui::Ui,
+    state: &mut BrowserState,
+    theme: &FluxTheme,
+) -> Option<BrowserAction> {
+    let mut action = None;
+    let mut discard = false;
+    let saving = state.is_saving();
+    let Some(edits) = &mut state.edits else {
+        return None;
+    };
+
+    ui.add_enabled_ui(!saving, |ui| {
+        ui.horizontal(|ui| {
+            if ui
+                .button(format!(
+                    "{} {}",
+                    regular::FILE_PLUS,
+                    t!("browser-edit-add-files")
+                ))
+                .on_hover_text(t!("browser-edit-add-hint"))
+                .clicked()
+            {
+                action = Some(BrowserAction::AddFiles);
+            }
+            if ui
+                .button(format!(
+                    "{} {}",
+                    regular::FOLDER_PLUS,
+                    t!("browser-edit-add-folder")
+                ))
+                .on_hover_text(t!("browser-edit-add-hint"))
+                .clicked()
+            {
+                action = Some(BrowserAction::AddFolder);
+            }
+
+            ui.separator();
+
+            // The whole selection, or else the highlighted entry
+            let doomed: Vec<PathBuf> = if state.selected.is_empty() {
+                state.highlighted.iter().cloned().collect()
+            } else {
+                state.selected.iter().cloned().collect()
+            };
+            if ui
+                .add_enabled(
+                    !doomed.is_empty(),
+                    egui::Button::new(format!(
+                        "{} {}",
+                        regular::TRASH,
+                        t!("browser-edit-delete", count = doomed.len())
+                    )),
+                )
+                .clicked()
+            {
+                for path in doomed {
+                    edits.push(Edit::Delete(path));
+                }
+                state.selected.clear();
+            }
+
+            ui.separator();
+
+            // Start from the highlighted entry's path whenever it changes
+            if state.rename_from != state.highlighted {
+                state.rename_from = state.highlighted.clone();
+                state.rename_to = state
+                    .highlighted
+                    .as_ref()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+            }
+            ui.add_enabled(
+                state.highlighted.is_some(),
+                egui::TextEdit::singleline(&mut state.rename_to)
+                    .hint_text(t!("browser-edit-rename-hint"))
+                    .desired_width(220.0),
+            );
+            let target = state.rename_to.trim().trim_matches('/');
+            let rename = state
+                .rename_from
+                .as_ref()
+                .filter(|from| !target.is_empty() && Path::new(target) != from.as_path());
+            if ui
+                .add_enabled(
+                    rename.is_some(),
+                    egui::Button::new(format!(
+                        "{} {}",
+                        regular::PENCIL_SIMPLE,
+                        t!("browser-edit-rename")
+                    )),
+                )
+                .clicked()
+            {
+                if let Some(from) = rename {
+                    edits.push(Edit::Rename {
+                        from: from.clone(),
+                        to: PathBuf::from(target),
+                    });
+                }
+            }
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui
+                    .add_enabled(
+                        !edits.is_empty(),
+                        egui::Button::new(format!(
+                            "{} {}",
+                            regular::FLOPPY_DISK,
+                            t!("browser-edit-save", count = edits.len())
+                        )),
+                    )
+                    .clicked()
+                {
+                    action = Some(BrowserAction::SaveEdits);
+                }
+                if ui.button(t!("browser-edit-discard")).clicked() {
+                    discard = true;
+                }
+            });
+        });
+    });
+
+    if discard {
+        state.edits = None;
+        return action;
+    }
+
+    if saving {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.weak(t!("browser-edit-saving"));
+        });
+    }
+
+    let Some(edits) = &mut state.edits else {
+        return action;
+    };
+    if edits.is_empty() {
+        ui.weak(t!("browser-edit-no-changes"));
+        return action;
+    }
+
+    egui::CollapsingHeader::new(t!("browser-edit-changes", count = edits.len()))
+        .default_open(true)
+        .show(ui, |ui| {
+            egui::ScrollArea::vertical()
+                .max_height(120.0)
+                .show(ui, |ui| {
+                    let mut undone = None;
+                    for (index, edit) in edits.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            if !saving
+                                && ui
+                                    .small_button(regular::ARROW_COUNTER_CLOCKWISE)
+                                    .on_hover_text(t!("browser-edit-undo"))
+                                    .clicked()
+                            {
+                                undone = Some(index);
+                            }
+                            match edit {
+                                Edit::Rename { from, to } => ui.label(format!(
+                                    "{} {} → {}",
+                                    regular::PENCIL_SIMPLE,
+                                    from.display(),
+                                    to.display()
+                                )),
+                                Edit::Delete(path) => ui.colored_label(
+                                    theme.colors.error,
+                                    format!("{} {}", regular::TRASH, path.display()),
+                                ),
+                                Edit::Add { source, name } => ui
+                                    .colored_label(
+                                        theme.colors.success,
+                                        format!("{} {}", regular::PLUS, name.display()),
+                                    )
+                                    .on_hover_text(source.display().to_string()),
+                            };
+                        });
+                    }
+                    if let Some(index) = undone {
+                        edits.remove(index);
+                    }
+                });
+        });
+
+    action
+}
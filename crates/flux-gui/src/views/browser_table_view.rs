@@ -1,25 +1,287 @@
 //! Table-based browser view for better performance with large archives
 //! This module provides a virtual scrolling table view using egui_extras::Table
+//!
+//! The tree is flattened once into a [`TableIndex`]. Searching, sorting and
+//! type filters only rebuild the list of visible row numbers, and only when
+//! one of them changes, so large archives stay responsive while scrolling.
 
-use super::browser_view::{format_size, get_file_icon, BrowserState};
+use super::browser_view::{format_size, get_file_icon, BrowserState, TreeNode};
+use crate::t;
 use crate::theme::FluxTheme;
 use egui::Ui;
 use egui_extras::{Column, TableBuilder};
 use egui_phosphor::regular;
+use flux_core::archive::detect::FILE_EXTENSIONS;
 use flux_core::archive::extractor::ArchiveEntry;
-use std::path::PathBuf;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Column the table is sorted by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortColumn {
+    /// Order of the archive, indented like the tree
+    #[default]
+    Archive,
+    /// File name
+    Name,
+    /// Uncompressed size
+    Size,
+    /// Modification time
+    Modified,
+}
+
+/// Kind of entry used by the type filter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FileKind {
+    Folder,
+    Text,
+    Image,
+    Audio,
+    Video,
+    Archive,
+    Document,
+    Code,
+    Executable,
+    Other,
+}
+
+impl FileKind {
+    /// Every kind, in the order shown in the filter menu
+    pub const ALL: [FileKind; 10] = [
+        FileKind::Folder,
+        FileKind::Text,
+        FileKind::Image,
+        FileKind::Audio,
+        FileKind::Video,
+        FileKind::Archive,
+        FileKind::Document,
+        FileKind::Code,
+        FileKind::Executable,
+        FileKind::Other,
+    ];
+
+    /// Name shown in the filter menu
+    pub fn label(self) -> &'static str {
+        match self {
+            FileKind::Folder => t!("file-kind-folders"),
+            FileKind::Text => t!("file-kind-text"),
+            FileKind::Image => t!("file-kind-images"),
+            FileKind::Audio => t!("file-kind-audio"),
+            FileKind::Video => t!("file-kind-video"),
+            FileKind::Archive => t!("file-kind-archives"),
+            FileKind::Document => t!("file-kind-documents"),
+            FileKind::Code => t!("file-kind-code"),
+            FileKind::Executable => t!("file-kind-executables"),
+            FileKind::Other => t!("file-kind-other"),
+        }
+    }
+
+    fn of(path: &Path, is_dir: bool) -> Self {
+        if is_dir {
+            return FileKind::Folder;
+        }
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+
+        match ext.as_str() {
+            "txt" | "md" | "log" | "csv" | "json" | "toml" | "yaml" | "yml" | "xml" | "ini" => {
+                FileKind::Text
+            }
+            "jpg" | "jpeg" | "png" | "gif" | "bmp" | "svg" | "webp" | "tiff" => FileKind::Image,
+            "mp3" | "wav" | "flac" | "ogg" | "m4a" | "aac" => FileKind::Audio,
+            "mp4" | "avi" | "mkv" | "mov" | "webm" => FileKind::Video,
+            // The archives Flux reads, and rars, which it does not
+            ext if ext == "rar" || FILE_EXTENSIONS.contains(&ext) => FileKind::Archive,
+            "pdf" | "doc" | "docx" | "odt" | "xls" | "xlsx" | "ods" | "ppt" | "pptx" | "odp" => {
+                FileKind::Document
+            }
+            "rs" | "py" | "js" | "ts" | "c" | "h" | "cpp" | "hpp" | "java" | "go" | "rb" | "sh" => {
+                FileKind::Code
+            }
+            "exe" | "msi" | "dll" | "so" | "dylib" | "bin" => FileKind::Executable,
+            _ => FileKind::Other,
+        }
+    }
+}
+
+/// One flattened tree node
+struct Row {
+    path: PathBuf,
+    entry: Option<ArchiveEntry>,
+    depth: usize,
+    name: String,
+    name_lower: String,
+    kind: FileKind,
+    /// The entry or one of its parents is a dot file
+    hidden: bool,
+}
+
+impl Row {
+    fn is_dir(&self) -> bool {
+        self.entry.as_ref().is_none_or(|e| e.is_dir)
+    }
+
+    fn size(&self) -> u64 {
+        self.entry.as_ref().map_or(0, |e| e.size)
+    }
+
+    fn mtime(&self) -> i64 {
+        self.entry
+            .as_ref()
+            .and_then(|e| e.mtime)
+            .unwrap_or(i64::MIN)
+    }
+}
+
+/// Everything that decides which rows are visible, and in which order
+#[derive(Debug, Clone, PartialEq)]
+struct Query {
+    search: String,
+    show_hidden: bool,
+    sort: SortColumn,
+    descending: bool,
+    kinds: BTreeSet<FileKind>,
+}
+
+/// Searchable, sortable view over the flattened archive tree
+#[derive(Default)]
+pub struct TableIndex {
+    rows: Vec<Row>,
+    /// Row numbers that pass the current query, in display order
+    visible: Vec<usize>,
+    query: Option<Query>,
+    /// Bumped whenever `visible` is rebuilt
+    generation: u64,
+    /// Whether every visible file is selected, keyed by generation and
+    /// selection size
+    all_selected: Option<(u64, usize, bool)>,
+    /// Sort column
+    pub sort: SortColumn,
+    /// Sort from largest to smallest
+    pub descending: bool,
+    /// Kinds to show; empty shows everything
+    pub kinds: BTreeSet<FileKind>,
+}
+
+impl TableIndex {
+    /// Number of rows that pass the current filters
+    pub fn visible_len(&self) -> usize {
+        self.visible.len()
+    }
+
+    /// Number of rows in the archive
+    pub fn total_len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Flatten the tree again on the next refresh, as after entries were
+    /// added to it
+    pub fn invalidate(&mut self) {
+        self.rows.clear();
+        self.query = None;
+    }
+
+    /// Sort by `column`, or flip the direction if it is already sorted by it
+    pub fn toggle_sort(&mut self, column: SortColumn) {
+        if self.sort == column {
+            self.descending = !self.descending;
+        } else {
+            self.sort = column;
+            self.descending = false;
+        }
+    }
+
+    /// Bring the visible rows up to date with `search` and `show_hidden`
+    fn refresh(&mut self, tree: &TreeNode, search: &str, show_hidden: bool) {
+        if self.rows.is_empty() && !tree.children.is_empty() {
+            for child in &tree.children {
+                flatten_tree(child, &mut self.rows, 0, false);
+            }
+        }
+
+        let query = Query {
+            search: search.trim().to_lowercase(),
+            show_hidden,
+            sort: self.sort,
+            descending: self.descending,
+            kinds: self.kinds.clone(),
+        };
+        if self.query.as_ref() == Some(&query) {
+            return;
+        }
+
+        let rows = &self.rows;
+        self.visible = (0..rows.len())
+            .filter(|&i| {
+                let row = &rows[i];
+                (query.show_hidden || !row.hidden)
+                    && (query.kinds.is_empty() || query.kinds.contains(&row.kind))
+                    && (query.search.is_empty() || row.name_lower.contains(&query.search))
+            })
+            .collect();
+
+        let compare: Option<fn(&Row, &Row) -> Ordering> = match query.sort {
+            SortColumn::Archive => None,
+            SortColumn::Name => Some(|a, b| a.name_lower.cmp(&b.name_lower)),
+            SortColumn::Size => Some(|a, b| a.size().cmp(&b.size())),
+            SortColumn::Modified => Some(|a, b| a.mtime().cmp(&b.mtime())),
+        };
+        if let Some(compare) = compare {
+            self.visible.sort_by(|&a, &b| {
+                let ordering =
+                    compare(&rows[a], &rows[b]).then_with(|| rows[a].path.cmp(&rows[b].path));
+                if query.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            });
+        }
+
+        self.query = Some(query);
+        self.generation += 1;
+        self.all_selected = None;
+    }
+
+    /// Whether every visible file is in `selected`
+    fn all_selected(&mut self, selected: &HashSet<PathBuf>) -> bool {
+        if let Some((generation, count, all)) = self.all_selected {
+            if generation == self.generation && count == selected.len() {
+                return all;
+            }
+        }
+
+        let mut files = self
+            .visible
+            .iter()
+            .map(|&i| &self.rows[i])
+            .filter(|row| !row.is_dir())
+            .peekable();
+        let all = files.peek().is_some() && files.all(|row| selected.contains(&row.path));
+        self.all_selected = Some((self.generation, selected.len(), all));
+        all
+    }
+}
 
 /// Draw the table-based browser view with virtual scrolling
 pub fn draw_table_view(ui: &mut Ui, state: &mut BrowserState, theme: &FluxTheme) {
-    // Flatten the tree into a list for table display
-    let mut flat_entries = Vec::new();
-    flatten_tree(
-        &state.tree,
-        &mut flat_entries,
-        &state.search_filter,
-        state.show_hidden,
-        0,
-    );
+    let index = &mut state.table;
+    index.refresh(&state.tree, &state.search_filter, state.show_hidden);
+
+    if index.visible_len() != index.total_len() {
+        ui.weak(t!(
+            "table-showing",
+            shown = index.visible_len(),
+            total = index.total_len()
+        ));
+    }
+
+    // Indentation only makes sense in archive order
+    let indented = index.sort == SortColumn::Archive;
 
     // Calculate available height for the table
     let available_height = ui.available_height();
@@ -44,22 +306,14 @@ pub fn draw_table_view(ui: &mut Ui, state: &mut BrowserState, theme: &FluxTheme)
         .header(20.0, |mut header| {
             header.col(|ui| {
                 // Select all checkbox
-                let all_selected = !flat_entries.is_empty()
-                    && flat_entries.iter().all(|(_, entry, _)| {
-                        entry
-                            .as_ref()
-                            .is_some_and(|e| !e.is_dir && state.selected.contains(&e.path))
-                    });
-                let mut checkbox_state = all_selected;
+                let mut checkbox_state = index.all_selected(&state.selected);
                 if ui.checkbox(&mut checkbox_state, "").changed() {
-                    for (path, entry, _) in &flat_entries {
-                        if let Some(e) = entry {
-                            if !e.is_dir {
-                                if checkbox_state {
-                                    state.selected.insert(path.clone());
-                                } else {
-                                    state.selected.remove(path);
-                                }
+                    for row in index.visible.iter().map(|&i| &index.rows[i]) {
+                        if !row.is_dir() {
+                            if checkbox_state {
+                                state.selected.insert(row.path.clone());
+                            } else {
+                                state.selected.remove(&row.path);
                             }
                         }
                     }
@@ -67,169 +321,220 @@ pub fn draw_table_view(ui: &mut Ui, state: &mut BrowserState, theme: &FluxTheme)
             });
             header.col(|_| {}); // Icon column
             header.col(|ui| {
-                ui.strong("Name");
+                sort_header(ui, index, t!("table-name"), SortColumn::Name);
             });
             header.col(|ui| {
-                ui.strong("Size");
+                sort_header(ui, index, t!("table-size"), SortColumn::Size);
             });
             header.col(|ui| {
-                ui.strong("Type");
+                let label = if index.kinds.is_empty() {
+                    format!("{} {}", t!("table-type"), regular::FUNNEL_SIMPLE)
+                } else {
+                    format!(
+                        "{} {} ({})",
+                        t!("table-type"),
+                        regular::FUNNEL,
+                        index.kinds.len()
+                    )
+                };
+                ui.menu_button(egui::RichText::new(label).strong(), |ui| {
+                    for kind in FileKind::ALL {
+                        let mut shown = index.kinds.contains(&kind);
+                        if ui.checkbox(&mut shown, kind.label()).changed() {
+                            if shown {
+                                index.kinds.insert(kind);
+                            } else {
+                                index.kinds.remove(&kind);
+                            }
+                        }
+                    }
+                    ui.separator();
+                    if ui.button(t!("table-show-all-types")).clicked() {
+                        index.kinds.clear();
+                        ui.close_menu();
+                    }
+                });
             });
             header.col(|ui| {
-                ui.strong("Modified");
+                sort_header(ui, index, t!("table-modified"), SortColumn::Modified);
             });
             header.col(|ui| {
-                ui.strong("Compressed");
+                ui.strong(t!("table-compressed"));
             });
         })
         .body(|body| {
-            // Virtual scrolling body
-            body.rows(20.0, flat_entries.len(), |mut row| {
-                let row_index = row.index();
-                if let Some((path, entry_opt, indent)) = flat_entries.get(row_index) {
-                    let is_selected = state.selected.contains(path);
-                    let is_highlighted = state.highlighted.as_ref() == Some(path);
-
-                    // Checkbox column
-                    row.col(|ui| {
-                        ui.add_space(*indent as f32 * 10.0);
-                        if let Some(entry) = entry_opt {
-                            if !entry.is_dir {
-                                let mut checkbox_state = is_selected;
-                                if ui.checkbox(&mut checkbox_state, "").changed() {
-                                    if checkbox_state {
-                                        state.selected.insert(path.clone());
-                                    } else {
-                                        state.selected.remove(path);
-                                    }
+            // Virtual scrolling body: only the rows on screen are laid out
+            body.rows(20.0, index.visible.len(), |mut row| {
+                let Some(item) = index
+                    .visible
+                    .get(row.index())
+                    .and_then(|&i| index.rows.get(i))
+                else {
+                    return;
+                };
+                let path = &item.path;
+                let entry_opt = &item.entry;
+                let is_selected = state.selected.contains(path);
+                let is_highlighted = state.highlighted.as_ref() == Some(path);
+
+                // Checkbox column
+                row.col(|ui| {
+                    if indented {
+                        ui.add_space(item.depth as f32 * 10.0);
+                    }
+                    if let Some(entry) = entry_opt {
+                        if !entry.is_dir {
+                            let mut checkbox_state = is_selected;
+                            if ui.checkbox(&mut checkbox_state, "").changed() {
+                                if checkbox_state {
+                                    state.selected.insert(path.clone());
+                                } else {
+                                    state.selected.remove(path);
                                 }
                             }
                         }
-                    });
+                    }
+                });
 
-                    // Icon column
-                    row.col(|ui| {
-                        let icon = if let Some(entry) = entry_opt {
-                            if entry.is_dir {
-                                regular::FOLDER
-                            } else {
-                                get_file_icon(path)
-                            }
+                // Icon column
+                row.col(|ui| {
+                    let icon = if item.is_dir() {
+                        regular::FOLDER
+                    } else {
+                        get_file_icon(path)
+                    };
+                    ui.label(
+                        egui::RichText::new(icon)
+                            .size(16.0)
+                            .color(theme.colors.primary),
+                    );
+                });
+
+                // Name column
+                row.col(|ui| {
+                    let response = ui.selectable_label(
+                        is_highlighted,
+                        egui::RichText::new(&item.name).color(if is_selected {
+                            theme.colors.primary
                         } else {
-                            regular::FOLDER
-                        };
-                        ui.label(
-                            egui::RichText::new(icon)
-                                .size(16.0)
-                                .color(theme.colors.primary),
-                        );
-                    });
-
-                    // Name column
-                    row.col(|ui| {
-                        let name = path
-                            .file_name()
-                            .and_then(|n| n.to_str())
-                            .unwrap_or_else(|| path.to_str().unwrap_or("Unknown"));
-
-                        let response = ui.selectable_label(
-                            is_highlighted,
-                            egui::RichText::new(name).color(if is_selected {
-                                theme.colors.primary
-                            } else {
-                                theme.colors.text
-                            }),
-                        );
+                            theme.colors.text
+                        }),
+                    );
+                    // Flat orders lose the tree, so show where the entry lives
+                    let response = if indented {
+                        response
+                    } else {
+                        response.on_hover_text(path.display().to_string())
+                    };
 
-                        if response.clicked() {
-                            state.highlighted = Some(path.clone());
-                        }
-                    });
+                    if response.clicked() {
+                        state.highlighted = Some(path.clone());
+                    }
 
-                    // Size column
-                    row.col(|ui| {
-                        if let Some(entry) = entry_opt {
-                            if !entry.is_dir {
-                                ui.label(format_size(entry.size));
-                            }
+                    if response.interact(egui::Sense::drag()).drag_started() {
+                        state.drag_source = Some(path.clone());
+                    }
+                });
+
+                // Size column
+                row.col(|ui| {
+                    if let Some(entry) = entry_opt {
+                        if !entry.is_dir {
+                            ui.label(format_size(entry.size));
                         }
-                    });
+                    }
+                });
 
-                    // Type column
-                    row.col(|ui| {
-                        if let Some(entry) = entry_opt {
-                            if entry.is_dir {
-                                ui.label("Directory");
-                            } else {
-                                ui.label(get_file_type(path));
-                            }
+                // Type column
+                row.col(|ui| {
+                    if let Some(entry) = entry_opt {
+                        if entry.is_dir {
+                            ui.label(t!("browser-directory"));
+                        } else {
+                            ui.label(get_file_type(path));
                         }
-                    });
-
-                    // Modified column
-                    row.col(|ui| {
-                        if let Some(entry) = entry_opt {
-                            if let Some(mtime) = entry.mtime {
-                                let datetime =
-                                    chrono::DateTime::<chrono::Utc>::from_timestamp(mtime, 0)
-                                        .unwrap_or_default();
-                                ui.label(datetime.format("%Y-%m-%d %H:%M").to_string());
-                            }
+                    }
+                });
+
+                // Modified column
+                row.col(|ui| {
+                    if let Some(entry) = entry_opt {
+                        if let Some(mtime) = entry.mtime {
+                            let datetime =
+                                chrono::DateTime::<chrono::Utc>::from_timestamp(mtime, 0)
+                                    .unwrap_or_default();
+                            ui.label(datetime.format("%Y-%m-%d %H:%M").to_string());
                         }
-                    });
+                    }
+                });
 
-                    // Compressed column
-                    row.col(|ui| {
-                        if let Some(entry) = entry_opt {
-                            if let Some(compressed) = entry.compressed_size {
+                // Compressed column
+                row.col(|ui| {
+                    if let Some(entry) = entry_opt {
+                        if let Some(compressed) = entry.compressed_size {
+                            if entry.size > 0 {
                                 ui.label(format!(
                                     "{} ({:.1}%)",
                                     format_size(compressed),
                                     (compressed as f64 / entry.size as f64) * 100.0
                                 ));
+                            } else {
+                                ui.label(format_size(compressed));
                             }
                         }
-                    });
-                }
+                    }
+                });
             });
         });
 }
 
-/// Flatten the tree structure into a list of entries for table display
-fn flatten_tree(
-    node: &super::browser_view::TreeNode,
-    flat_list: &mut Vec<(PathBuf, Option<ArchiveEntry>, usize)>,
-    search_filter: &str,
-    show_hidden: bool,
-    depth: usize,
-) {
-    // Skip root node
-    if depth > 0 {
-        // Apply filters
-        if !search_filter.is_empty()
-            && !node
-                .name
-                .to_lowercase()
-                .contains(&search_filter.to_lowercase())
-        {
-            return;
-        }
+/// Clickable column header that sorts by `column`
+fn sort_header(ui: &mut Ui, index: &mut TableIndex, label: &str, column: SortColumn) {
+    let text = if index.sort == column {
+        let arrow = if index.descending {
+            regular::CARET_DOWN
+        } else {
+            regular::CARET_UP
+        };
+        format!("{} {}", label, arrow)
+    } else {
+        label.to_string()
+    };
 
-        if !show_hidden && node.name.starts_with('.') {
-            return;
-        }
-
-        flat_list.push((node.path.clone(), node.entry.clone(), depth - 1));
+    let response = ui
+        .add(egui::Button::new(egui::RichText::new(text).strong()).frame(false))
+        .on_hover_text(t!("table-sort-hint"));
+    if response.clicked() {
+        index.toggle_sort(column);
+    }
+    if response.secondary_clicked() {
+        index.sort = SortColumn::Archive;
+        index.descending = false;
     }
+}
+
+/// Flatten the tree structure into rows in archive order
+fn flatten_tree(node: &TreeNode, rows: &mut Vec<Row>, depth: usize, parent_hidden: bool) {
+    let hidden = parent_hidden || node.name.starts_with('.');
+    let is_dir = node.entry.as_ref().is_none_or(|e| e.is_dir);
+
+    rows.push(Row {
+        path: node.path.clone(),
+        entry: node.entry.clone(),
+        depth,
+        name_lower: node.name.to_lowercase(),
+        name: node.name.clone(),
+        kind: FileKind::of(&node.path, is_dir),
+        hidden,
+    });
 
-    // Recursively add children if it's a directory
     for child in &node.children {
-        flatten_tree(child, flat_list, search_filter, show_hidden, depth + 1);
+        flatten_tree(child, rows, depth + 1, hidden);
     }
 }
 
 /// Get file type string from path
-fn get_file_type(path: &PathBuf) -> &'static str {
+fn get_file_type(path: &Path) -> &'static str {
     let ext = path
         .extension()
         .and_then(|e| e.to_str())
@@ -237,16 +542,16 @@ fn get_file_type(path: &PathBuf) -> &'static str {
         .to_lowercase();
 
     match ext.as_str() {
-        "txt" | "md" | "log" => "Text",
-        "jpg" | "jpeg" | "png" | "gif" | "bmp" => "Image",
-        "mp3" | "wav" | "flac" | "ogg" => "Audio",
-        "mp4" | "avi" | "mkv" | "mov" => "Video",
-        "zip" | "tar" | "gz" | "7z" | "rar" => "Archive",
+        "txt" | "md" | "log" => t!("table-type-text"),
+        "jpg" | "jpeg" | "png" | "gif" | "bmp" => t!("table-type-image"),
+        "mp3" | "wav" | "flac" | "ogg" => t!("table-type-audio"),
+        "mp4" | "avi" | "mkv" | "mov" => t!("table-type-video"),
+        ext if ext == "rar" || FILE_EXTENSIONS.contains(&ext) => t!("dialog-archive"),
         "pdf" => "PDF",
-        "doc" | "docx" => "Document",
-        "xls" | "xlsx" => "Spreadsheet",
-        "exe" | "msi" => "Executable",
-        "rs" | "py" | "js" | "cpp" | "java" => "Source Code",
-        _ => "File",
+        "doc" | "docx" => t!("table-type-document"),
+        "xls" | "xlsx" => t!("table-type-spreadsheet"),
+        "exe" | "msi" => t!("table-type-executable"),
+        "rs" | "py" | "js" | "cpp" | "java" => t!("table-type-code"),
+        _ => t!("file-type-file"),
     }
 }
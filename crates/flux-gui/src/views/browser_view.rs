@@ -1,13 +1,19 @@
 //! Archive browser view for exploring and extracting archive contents
 
+use super::browser_edit::{draw_edit_toolbar, ArchiveEdits, Edit, EditSave};
+use super::browser_table_view::TableIndex;
+use super::preview_pane::{draw_preview, PreviewState};
 use crate::components::{set_theme_in_context, FluxButton};
 use crate::layout::Card;
+use crate::t;
 use crate::theme::FluxTheme;
 use egui::{vec2, Context, Ui, Widget};
 use egui_phosphor::regular;
 use flux_core::archive::extractor::ArchiveEntry;
-use std::collections::HashSet;
+use flux_core::archive::Archive;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 /// Tree node for file hierarchy
 #[derive(Debug, Clone)]
@@ -31,37 +37,37 @@ impl TreeNode {
         }
     }
 
-    /// Build tree structure from flat list of entries
-    pub fn build_tree(entries: Vec<ArchiveEntry>) -> TreeNode {
-        let mut root = TreeNode::new("Archive Root".to_string(), PathBuf::new(), None);
-
-        for entry in entries {
-            let components: Vec<_> = entry.path.components().collect();
-            let mut current = &mut root;
-
-            for (i, component) in components.iter().enumerate() {
-                let name = component.as_os_str().to_string_lossy().to_string();
-                let path = components[..=i].iter().collect::<PathBuf>();
-
-                // Find or create child node
-                let child_idx = current.children.iter().position(|c| c.name == name);
-
-                if let Some(idx) = child_idx {
-                    current = &mut current.children[idx];
-                } else {
+    /// Add `entry` below this root node, creating its parent folders
+    ///
+    /// `positions` holds the position of each node among its parent's
+    /// children, and is kept up to date for the next insert. Nodes are
+    /// looked up there rather than by scanning their siblings, so flat
+    /// archives with many thousands of entries build in linear time.
+    pub fn insert(&mut self, entry: ArchiveEntry, positions: &mut HashMap<PathBuf, usize>) {
+        let components: Vec<_> = entry.path.components().collect();
+        let mut current = self;
+
+        for (i, component) in components.iter().enumerate() {
+            let path = components[..=i].iter().collect::<PathBuf>();
+
+            // Find or create child node
+            let child_idx = match positions.get(&path) {
+                Some(&idx) => idx,
+                None => {
+                    let name = component.as_os_str().to_string_lossy().to_string();
                     let is_last = i == components.len() - 1;
                     let node_entry = if is_last { Some(entry.clone()) } else { None };
 
-                    current.children.push(TreeNode::new(name, path, node_entry));
-                    current = current.children.last_mut().unwrap();
+                    let idx = current.children.len();
+                    current
+                        .children
+                        .push(TreeNode::new(name, path.clone(), node_entry));
+                    positions.insert(path, idx);
+                    idx
                 }
-            }
+            };
+            current = &mut current.children[child_idx];
         }
-
-        // Auto-expand root
-        root.is_expanded = true;
-
-        root
     }
 
     /// Check if this node or any descendant is selected
@@ -93,8 +99,16 @@ impl TreeNode {
 
 /// Archive browser state
 pub struct BrowserState {
-    /// The archive file being browsed
+    /// The archive file being browsed; for a cloud archive, its URL
     pub archive_path: PathBuf,
+    /// URL of an archive read from cloud storage
+    ///
+    /// Entries of a cloud archive can't be previewed, dragged out or
+    /// extracted one by one, so only extracting everything is offered.
+    pub source_url: Option<String>,
+    /// The local archive, opened once for the previews to read from;
+    /// `None` for a cloud archive
+    pub archive: Option<Arc<Archive>>,
     /// Tree structure of archive contents
     pub tree: TreeNode,
     /// Selected items (paths)
@@ -115,42 +129,164 @@ pub struct BrowserState {
     pub dir_count: usize,
     /// Use table view instead of tree view
     pub use_table_view: bool,
+    /// Preview of the highlighted entry
+    pub preview: PreviewState,
+    /// Item being dragged, if a drag started in the tree or table
+    pub drag_source: Option<PathBuf>,
+    /// Search, sort and type filter state of the table view
+    pub table: TableIndex,
+    /// Entries are still being read, and more may be added
+    pub loading: bool,
+    /// Position of each tree node among its parent's children
+    positions: HashMap<PathBuf, usize>,
+    /// The archive is local and in a format whose entries can be changed
+    pub editable: bool,
+    /// Unsaved changes, while in edit mode
+    pub edits: Option<ArchiveEdits>,
+    /// Entry the rename field was filled in from
+    pub rename_from: Option<PathBuf>,
+    /// New path typed for the highlighted entry
+    pub rename_to: String,
+    /// Changes being written into the archive
+    saving: Option<EditSave>,
 }
 
 impl BrowserState {
     /// Create a new browser state from entries
     pub fn new(archive_path: PathBuf, entries: Vec<ArchiveEntry>) -> Self {
-        let mut total_size = 0u64;
-        let mut file_count = 0;
-        let mut dir_count = 0;
-
-        for entry in &entries {
-            if entry.is_dir {
-                dir_count += 1;
-            } else {
-                file_count += 1;
-                total_size += entry.size;
-            }
-        }
+        let mut state = Self::loading(archive_path);
+        state.extend(entries);
+        state.loading = false;
+        state
+    }
 
-        let tree = TreeNode::build_tree(entries);
+    /// Create an empty browser state for an archive whose entries are
+    /// still being read, to [`BrowserState::extend`] as they arrive
+    pub fn loading(archive_path: PathBuf) -> Self {
+        let mut tree = TreeNode::new("Archive Root".to_string(), PathBuf::new(), None);
+        tree.is_expanded = true;
 
         Self {
             archive_path,
+            source_url: None,
+            archive: None,
             tree,
             selected: HashSet::new(),
             highlighted: None,
             search_filter: String::new(),
             show_hidden: true,
             info_panel_width: 300.0,
-            total_size,
-            file_count,
-            dir_count,
+            total_size: 0,
+            file_count: 0,
+            dir_count: 0,
             use_table_view: false,
+            preview: PreviewState::default(),
+            drag_source: None,
+            table: TableIndex::default(),
+            loading: true,
+            positions: HashMap::new(),
+            editable: false,
+            edits: None,
+            rename_from: None,
+            rename_to: String::new(),
+            saving: None,
+        }
+    }
+
+    /// Add entries read after the browser opened
+    pub fn extend(&mut self, entries: Vec<ArchiveEntry>) {
+        if entries.is_empty() {
+            return;
+        }
+        for entry in entries {
+            if entry.is_dir {
+                self.dir_count += 1;
+            } else {
+                self.file_count += 1;
+                self.total_size += entry.size;
+            }
+            self.tree.insert(entry, &mut self.positions);
+        }
+        self.table.invalidate();
+    }
+
+    /// Create a browser state for an archive in cloud storage
+    pub fn new_remote(url: String, entries: Vec<ArchiveEntry>) -> Self {
+        Self {
+            source_url: Some(url.clone()),
+            ..Self::new(PathBuf::from(url), entries)
         }
     }
 
+    /// Whether the archive was read from cloud storage
+    pub fn is_remote(&self) -> bool {
+        self.source_url.is_some()
+    }
+
+    /// Whether edit mode can be entered: the archive is local, its format
+    /// allows changes and all its entries have been read
+    pub fn can_edit(&self) -> bool {
+        self.editable && !self.is_remote() && !self.loading
+    }
+
+    /// Whether changes are being written into the archive
+    pub fn is_saving(&self) -> bool {
+        self.saving.is_some()
+    }
+
+    /// Queue `sources` from disk to be added into the highlighted folder,
+    /// the folder of the highlighted file, or else the top of the archive
+    pub fn add_sources(&mut self, sources: Vec<PathBuf>) {
+        let folder = self
+            .highlighted
+            .as_ref()
+            .and_then(|path| {
+                let entry = find_entry_by_path(&self.tree, path)?;
+                Some(if entry.is_dir {
+                    path.clone()
+                } else {
+                    path.parent().map(PathBuf::from).unwrap_or_default()
+                })
+            })
+            .unwrap_or_default();
+        let Some(edits) = &mut self.edits else {
+            return;
+        };
+        for source in sources {
+            if let Some(name) = source.file_name() {
+                let name = folder.join(name);
+                edits.push(Edit::Add { source, name });
+            }
+        }
+    }
+
+    /// Start writing the changes into the archive
+    ///
+    /// The archive handle is let go first, so that the rewritten archive
+    /// can replace it on platforms that keep open files in place.
+    pub fn start_save(&mut self) {
+        let Some(edits) = self.edits.as_ref().filter(|edits| !edits.is_empty()) else {
+            return;
+        };
+        self.saving = Some(EditSave::start(&self.archive_path, edits));
+        self.archive = None;
+        self.preview.clear();
+    }
+
+    /// How many entries changed, or why saving failed, once saving is done
+    ///
+    /// A failed save keeps the changes so they can be corrected.
+    pub fn poll_save(&mut self) -> Option<Result<usize, String>> {
+        let result = self.saving.as_ref()?.poll()?;
+        self.saving = None;
+        if result.is_err() {
+            self.archive = Archive::open(&self.archive_path).ok().map(Arc::new);
+        }
+        Some(result)
+    }
+
     /// Toggle selection of an item
+    #[allow(dead_code)]
     pub fn toggle_selection(&mut self, path: PathBuf) {
         if self.selected.contains(&path) {
             self.selected.remove(&path);
@@ -160,6 +296,7 @@ impl BrowserState {
     }
 
     /// Select all items under a node
+    #[allow(dead_code)]
     pub fn select_node_recursive(&mut self, node: &TreeNode) {
         let mut paths = Vec::new();
         node.get_all_entry_paths(&mut paths);
@@ -181,6 +318,23 @@ impl BrowserState {
         entries
     }
 
+    /// Entries carried by a drag starting at `path`
+    ///
+    /// Dragging a selected item takes the whole selection along; otherwise
+    /// only the item itself (and everything below it) is dragged.
+    pub fn drag_entries(&self, path: &Path) -> Vec<ArchiveEntry> {
+        if self.selected.contains(path) {
+            return self.get_selected_entries();
+        }
+
+        let Some(node) = find_node_by_path(&self.tree, path) else {
+            return Vec::new();
+        };
+        let mut entries = Vec::new();
+        collect_entries(node, &mut entries);
+        entries
+    }
+
     fn collect_selected_entries(&self, node: &TreeNode, entries: &mut Vec<ArchiveEntry>) {
         if let Some(entry) = &node.entry {
             if self.selected.contains(&node.path) {
@@ -196,6 +350,7 @@ impl BrowserState {
 
 /// Actions that can be triggered from the browser view
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub enum BrowserAction {
     /// Extract selected items to a directory
     ExtractSelected(PathBuf),
@@ -205,6 +360,19 @@ pub enum BrowserAction {
     Close,
     /// Open file dialog to choose extraction destination
     ChooseDestination,
+    /// Entries were dragged out of the window, to copy them to the
+    /// clipboard
+    CopyToClipboard(Vec<ArchiveEntry>),
+    /// Show the properties of the archive
+    ShowProperties,
+    /// Check every entry against its checksum
+    Verify,
+    /// Pick files to add to the archive in edit mode
+    AddFiles,
+    /// Pick a folder to add to the archive in edit mode
+    AddFolder,
+    /// Write the changes made in edit mode into the archive
+    SaveEdits,
 }
 
 /// Draw the archive browser view
@@ -217,10 +385,11 @@ pub fn draw_browser_view(
     set_theme_in_context(ctx, theme);
 
     let mut action = None;
+    let remote = state.is_remote();
 
     // Header
     ui.horizontal(|ui| {
-        ui.heading("Archive Browser");
+        ui.heading(t!("browser-title"));
 
         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
             if ui.button("✕").clicked() {
@@ -235,30 +404,42 @@ pub fn draw_browser_view(
     Card::show(ui, theme, |ui| {
         ui.horizontal(|ui| {
             ui.label(
-                egui::RichText::new(regular::ARCHIVE)
-                    .size(20.0)
-                    .color(theme.colors.primary),
-            );
+                egui::RichText::new(if remote {
+                    regular::CLOUD
+                } else {
+                    regular::ARCHIVE
+                })
+                .size(20.0)
+                .color(theme.colors.primary),
+            )
+            .on_hover_text(state.source_url.as_deref().unwrap_or_default());
             ui.label(
                 egui::RichText::new(
                     state
                         .archive_path
                         .file_name()
                         .and_then(|n| n.to_str())
-                        .unwrap_or("Unknown Archive"),
+                        .unwrap_or(t!("browser-unknown-archive")),
                 )
                 .strong(),
             );
 
             ui.separator();
 
-            ui.label(format!("{} files", state.file_count));
-            ui.label(format!("{} folders", state.dir_count));
-            ui.label(format!("Total: {}", format_size(state.total_size)));
+            ui.label(t!("browser-file-count", count = state.file_count));
+            ui.label(t!("browser-folder-count", count = state.dir_count));
+            ui.label(t!(
+                "browser-total-size",
+                size = format_size(state.total_size)
+            ));
+            if state.loading {
+                ui.spinner();
+                ui.weak(t!("browser-reading"));
+            }
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 // Action buttons
-                let extract_all_btn = FluxButton::new("Extract All")
+                let extract_all_btn = FluxButton::new(t!("browser-extract-all"))
                     .icon(regular::DOWNLOAD_SIMPLE)
                     .primary();
 
@@ -266,10 +447,49 @@ pub fn draw_browser_view(
                     action = Some(BrowserAction::ChooseDestination);
                 }
 
+                if remote {
+                    return;
+                }
+
+                if state.edits.is_none()
+                    && FluxButton::new(t!("browser-edit"))
+                        .ghost()
+                        .icon(regular::PENCIL_SIMPLE)
+                        .ui(ui)
+                        .on_hover_text(if state.can_edit() {
+                            t!("browser-edit-hint")
+                        } else {
+                            t!("browser-edit-unavailable")
+                        })
+                        .clicked()
+                    && state.can_edit()
+                {
+                    state.edits = Some(ArchiveEdits::default());
+                }
+
+                if FluxButton::new(t!("properties"))
+                    .ghost()
+                    .icon(regular::INFO)
+                    .ui(ui)
+                    .clicked()
+                {
+                    action = Some(BrowserAction::ShowProperties);
+                }
+
+                if FluxButton::new(t!("verify"))
+                    .ghost()
+                    .icon(regular::SHIELD_CHECK)
+                    .ui(ui)
+                    .on_hover_text(t!("verify-hint"))
+                    .clicked()
+                {
+                    action = Some(BrowserAction::Verify);
+                }
+
                 let selected_count = state.selected.len();
                 if selected_count > 0 {
                     let extract_selected_btn =
-                        FluxButton::new(format!("Extract {} Selected", selected_count))
+                        FluxButton::new(t!("browser-extract-selected", count = selected_count))
                             .icon(regular::DOWNLOAD);
 
                     if extract_selected_btn.ui(ui).clicked() {
@@ -280,28 +500,44 @@ pub fn draw_browser_view(
         });
     });
 
+    if state.edits.is_some() {
+        ui.add_space(8.0);
+        Card::show(ui, theme, |ui| {
+            if let Some(edit_action) = draw_edit_toolbar(ui, state, theme) {
+                action = Some(edit_action);
+            }
+        });
+    }
+
     ui.add_space(8.0);
 
     // Search and filters
     ui.horizontal(|ui| {
         ui.label(regular::MAGNIFYING_GLASS);
-        ui.text_edit_singleline(&mut state.search_filter);
+        ui.add(
+            egui::TextEdit::singleline(&mut state.search_filter)
+                .hint_text(t!("browser-search-hint"))
+                .desired_width(200.0),
+        );
+        if !state.search_filter.is_empty() && ui.small_button(regular::X).clicked() {
+            state.search_filter.clear();
+        }
 
         ui.separator();
 
-        ui.checkbox(&mut state.show_hidden, "Show hidden files");
+        ui.checkbox(&mut state.show_hidden, t!("browser-show-hidden"));
 
         ui.separator();
 
         // View mode toggle
         if ui
-            .selectable_label(!state.use_table_view, "🌳 Tree")
+            .selectable_label(!state.use_table_view, format!("🌳 {}", t!("browser-tree")))
             .clicked()
         {
             state.use_table_view = false;
         }
         if ui
-            .selectable_label(state.use_table_view, "📊 Table")
+            .selectable_label(state.use_table_view, format!("📊 {}", t!("browser-table")))
             .clicked()
         {
             state.use_table_view = true;
@@ -309,7 +545,7 @@ pub fn draw_browser_view(
 
         if !state.selected.is_empty() {
             ui.separator();
-            if ui.button("Clear Selection").clicked() {
+            if ui.button(t!("browser-clear-selection")).clicked() {
                 state.clear_selection();
             }
         }
@@ -336,7 +572,7 @@ pub fn draw_browser_view(
                     let search_filter = &state.search_filter;
                     let show_hidden = state.show_hidden;
 
-                    let (new_highlighted, selection_changes) = draw_tree_node(
+                    let (new_highlighted, selection_changes, drag_started) = draw_tree_node(
                         ui,
                         &mut state.tree,
                         selected,
@@ -352,6 +588,10 @@ pub fn draw_browser_view(
                         state.highlighted = Some(path);
                     }
 
+                    if drag_started.is_some() {
+                        state.drag_source = drag_started;
+                    }
+
                     for (path, selected) in selection_changes {
                         if selected {
                             state.selected.insert(path);
@@ -365,9 +605,78 @@ pub fn draw_browser_view(
 
         ui.separator();
 
+        // Cloud entries have no local copy to drop
+        if remote {
+            state.drag_source = None;
+        }
+
+        // A drag released outside the window is a drop onto the desktop
+        if let Some(source) = &state.drag_source {
+            let (released, outside) = ctx.input(|i| {
+                let outside = i
+                    .pointer
+                    .latest_pos()
+                    .is_none_or(|pos| !i.screen_rect().contains(pos));
+                (i.pointer.any_released(), outside)
+            });
+
+            if released {
+                if outside {
+                    let entries = state.drag_entries(source);
+                    if !entries.is_empty() {
+                        action = Some(BrowserAction::CopyToClipboard(entries));
+                    }
+                }
+                state.drag_source = None;
+            } else {
+                let count = if state.selected.contains(source) {
+                    state.selected.len()
+                } else {
+                    1
+                };
+                egui::show_tooltip_at_pointer(
+                    ctx,
+                    ui.layer_id(),
+                    egui::Id::new("browser_copy_to_clipboard"),
+                    |ui| {
+                        ui.label(format!(
+                            "{} {}",
+                            regular::DOWNLOAD_SIMPLE,
+                            t!("browser-copy-to-clipboard", count = count)
+                        ));
+                    },
+                );
+            }
+        }
+
+        // Keep the preview in step with the highlighted entry
+        let highlighted = state
+            .highlighted
+            .as_ref()
+            .and_then(|path| find_entry_by_path(&state.tree, path));
+        match (highlighted, &state.archive) {
+            (Some(entry), Some(archive)) if !entry.is_dir && !remote => {
+                state.preview.request(archive, entry)
+            }
+            _ => state.preview.clear(),
+        }
+
         // Info panel
         ui.allocate_ui(vec2(state.info_panel_width, ui.available_height()), |ui| {
-            draw_info_panel(ui, state, theme);
+            ui.vertical(|ui| {
+                draw_info_panel(ui, state, theme);
+
+                if state.highlighted.is_some() {
+                    ui.add_space(8.0);
+                    ui.heading(t!("browser-preview"));
+                    ui.separator();
+                    if remote {
+                        ui.weak(t!("browser-remote-no-preview"));
+                    } else {
+                        draw_preview(ui, &mut state.preview, theme);
+                    }
+                }
+            });
         });
     });
 
@@ -375,6 +684,7 @@ pub fn draw_browser_view(
 }
 
 /// Draw a tree node and its children
+#[allow(clippy::too_many_arguments)]
 fn draw_tree_node(
     ui: &mut Ui,
     node: &mut TreeNode,
@@ -384,9 +694,10 @@ fn draw_tree_node(
     show_hidden: bool,
     theme: &FluxTheme,
     depth: usize,
-) -> (Option<PathBuf>, Vec<(PathBuf, bool)>) {
+) -> (Option<PathBuf>, Vec<(PathBuf, bool)>, Option<PathBuf>) {
     let mut new_highlighted = None;
     let mut selection_changes = Vec::new();
+    let mut drag_started = None;
 
     // Skip if filtered
     if !search_filter.is_empty()
@@ -399,12 +710,12 @@ fn draw_tree_node(
             .iter()
             .any(|c| contains_filter(c, search_filter))
     {
-        return (new_highlighted, selection_changes);
+        return (new_highlighted, selection_changes, drag_started);
     }
 
     // Skip hidden files if needed
     if !show_hidden && node.name.starts_with('.') {
-        return (new_highlighted, selection_changes);
+        return (new_highlighted, selection_changes, drag_started);
     }
 
     let indent = depth as f32 * 20.0;
@@ -473,6 +784,10 @@ fn draw_tree_node(
             new_highlighted = Some(node.path.clone());
         }
 
+        if name_response.interact(egui::Sense::drag()).drag_started() {
+            drag_started = Some(node.path.clone());
+        }
+
         if name_response.double_clicked() && has_children {
             node.is_expanded = !node.is_expanded;
         }
@@ -494,7 +809,7 @@ fn draw_tree_node(
     // Draw children if expanded
     if node.is_expanded {
         for child in &mut node.children {
-            let (child_highlighted, child_changes) = draw_tree_node(
+            let (child_highlighted, child_changes, child_drag) = draw_tree_node(
                 ui,
                 child,
                 selected,
@@ -508,15 +823,18 @@ fn draw_tree_node(
                 new_highlighted = child_highlighted;
             }
             selection_changes.extend(child_changes);
+            if child_drag.is_some() {
+                drag_started = child_drag;
+            }
         }
     }
 
-    (new_highlighted, selection_changes)
+    (new_highlighted, selection_changes, drag_started)
 }
 
 /// Draw the info panel showing details about selected item
 fn draw_info_panel(ui: &mut Ui, state: &BrowserState, theme: &FluxTheme) {
-    ui.heading("Details");
+    ui.heading(t!("browser-details"));
     ui.separator();
 
     if let Some(highlighted_path) = &state.highlighted {
@@ -529,7 +847,7 @@ fn draw_info_panel(ui: &mut Ui, state: &BrowserState, theme: &FluxTheme) {
                             highlighted_path
                                 .file_name()
                                 .and_then(|n| n.to_str())
-                                .unwrap_or("Unknown"),
+                                .unwrap_or(t!("common-unknown")),
                         )
                         .strong()
                         .size(16.0),
@@ -542,20 +860,20 @@ fn draw_info_panel(ui: &mut Ui, state: &BrowserState, theme: &FluxTheme) {
                         .num_columns(2)
                         .spacing([12.0, 4.0])
                         .show(ui, |ui| {
-                            ui.label("Type:");
+                            ui.label(t!("extract-type"));
                             if entry.is_dir {
-                                ui.label("Directory");
+                                ui.label(t!("browser-directory"));
                             } else {
                                 ui.label(get_file_type(highlighted_path));
                             }
                             ui.end_row();
 
-                            ui.label("Size:");
+                            ui.label(t!("extract-size"));
                             ui.label(format_size(entry.size));
                             ui.end_row();
 
                             if let Some(compressed_size) = entry.compressed_size {
-                                ui.label("Compressed:");
+                                ui.label(t!("browser-compressed"));
                                 ui.label(format!(
                                     "{} ({:.1}%)",
                                     format_size(compressed_size),
@@ -565,18 +883,18 @@ fn draw_info_panel(ui: &mut Ui, state: &BrowserState, theme: &FluxTheme) {
                             }
 
                             if let Some(mtime) = entry.mtime {
-                                ui.label("Modified:");
+                                ui.label(t!("browser-modified"));
                                 ui.label(format_timestamp(mtime));
                                 ui.end_row();
                             }
 
                             if let Some(mode) = entry.mode {
-                                ui.label("Permissions:");
+                                ui.label(t!("browser-permissions"));
                                 ui.label(format_permissions(mode));
                                 ui.end_row();
                             }
 
-                            ui.label("Path:");
+                            ui.label(t!("browser-path"));
                             ui.label(highlighted_path.to_string_lossy().to_string());
                             ui.end_row();
                         });
@@ -585,7 +903,7 @@ fn draw_info_panel(ui: &mut Ui, state: &BrowserState, theme: &FluxTheme) {
         }
     } else if state.selected.is_empty() {
         ui.label(
-            egui::RichText::new("Select an item to view details")
+            egui::RichText::new(t!("browser-select-for-details"))
                 .color(theme.colors.text_weak)
                 .italics(),
         );
@@ -594,7 +912,7 @@ fn draw_info_panel(ui: &mut Ui, state: &BrowserState, theme: &FluxTheme) {
         Card::show(ui, theme, |ui| {
             ui.vertical(|ui| {
                 ui.label(
-                    egui::RichText::new(format!("{} items selected", state.selected.len()))
+                    egui::RichText::new(t!("browser-items-selected", count = state.selected.len()))
                         .strong(),
                 );
 
@@ -614,15 +932,15 @@ fn draw_info_panel(ui: &mut Ui, state: &BrowserState, theme: &FluxTheme) {
                     .num_columns(2)
                     .spacing([12.0, 4.0])
                     .show(ui, |ui| {
-                        ui.label("Files:");
+                        ui.label(t!("browser-files"));
                         ui.label(file_count.to_string());
                         ui.end_row();
 
-                        ui.label("Directories:");
+                        ui.label(t!("browser-directories"));
                         ui.label(dir_count.to_string());
                         ui.end_row();
 
-                        ui.label("Total size:");
+                        ui.label(t!("browser-total-size-label"));
                         ui.label(format_size(total_size));
                         ui.end_row();
                     });
@@ -648,6 +966,25 @@ fn contains_filter(node: &TreeNode, filter: &str) -> bool {
     false
 }
 
+/// Find a node by path in the tree
+fn find_node_by_path<'a>(node: &'a TreeNode, path: &Path) -> Option<&'a TreeNode> {
+    if node.path == path {
+        return Some(node);
+    }
+
+    node.children
+        .iter()
+        .find_map(|child| find_node_by_path(child, path))
+}
+
+/// Collect the entries of a node and all its descendants
+fn collect_entries(node: &TreeNode, entries: &mut Vec<ArchiveEntry>) {
+    entries.extend(node.entry.clone());
+    for child in &node.children {
+        collect_entries(child, entries);
+    }
+}
+
 /// Find an entry by path in the tree
 fn find_entry_by_path<'a>(node: &'a TreeNode, path: &Path) -> Option<&'a ArchiveEntry> {
     if node.path == path {
@@ -683,23 +1020,23 @@ pub fn get_file_icon(path: &Path) -> &'static str {
 /// Get human-readable file type
 fn get_file_type(path: &Path) -> String {
     match path.extension().and_then(|e| e.to_str()) {
-        Some("zip") => "ZIP Archive".to_string(),
-        Some("tar") => "TAR Archive".to_string(),
-        Some("gz") => "Gzip Compressed".to_string(),
-        Some("7z") => "7-Zip Archive".to_string(),
-        Some("txt") => "Text Document".to_string(),
-        Some("md") => "Markdown Document".to_string(),
-        Some("pdf") => "PDF Document".to_string(),
-        Some("jpg") | Some("jpeg") => "JPEG Image".to_string(),
-        Some("png") => "PNG Image".to_string(),
-        Some("gif") => "GIF Image".to_string(),
-        Some("mp3") => "MP3 Audio".to_string(),
-        Some("mp4") => "MP4 Video".to_string(),
-        Some("rs") => "Rust Source".to_string(),
-        Some("py") => "Python Script".to_string(),
-        Some("js") => "JavaScript".to_string(),
+        Some("zip") => t!("archive-type-zip").to_string(),
+        Some("tar") => t!("archive-type-tar").to_string(),
+        Some("gz") => t!("file-type-gzip").to_string(),
+        Some("7z") => t!("archive-type-7z").to_string(),
+        Some("txt") => t!("file-type-text").to_string(),
+        Some("md") => t!("file-type-markdown").to_string(),
+        Some("pdf") => t!("file-type-pdf").to_string(),
+        Some("jpg") | Some("jpeg") => t!("file-type-jpeg").to_string(),
+        Some("png") => t!("file-type-png").to_string(),
+        Some("gif") => t!("file-type-gif").to_string(),
+        Some("mp3") => t!("file-type-mp3").to_string(),
+        Some("mp4") => t!("file-type-mp4").to_string(),
+        Some("rs") => t!("file-type-rust").to_string(),
+        Some("py") => t!("file-type-python").to_string(),
+        Some("js") => t!("file-type-javascript").to_string(),
         Some(ext) => ext.to_uppercase(),
-        None => "File".to_string(),
+        None => t!("file-type-file").to_string(),
     }
 }
 
@@ -722,13 +1059,13 @@ pub fn format_size(bytes: u64) -> String {
 }
 
 /// Format Unix timestamp
-fn format_timestamp(timestamp: i64) -> String {
+pub(crate) fn format_timestamp(timestamp: i64) -> String {
     use chrono::{Local, TimeZone};
 
     if let Some(dt) = Local.timestamp_opt(timestamp, 0).single() {
         dt.format("%Y-%m-%d %H:%M").to_string()
     } else {
-        "Unknown".to_string()
+        t!("common-unknown").to_string()
     }
 }
 
@@ -0,0 +1,390 @@
+//! Cloud storage browser for opening and creating archives in buckets
+
+use crate::cloud::{archive_format, list_location, CloudEntry, CloudListing};
+use crate::components::FluxButton;
+use crate::layout::Card;
+use crate::settings::CloudProfile;
+use crate::t;
+use crate::theme::FluxTheme;
+use crossbeam_channel::Receiver;
+use eframe::egui;
+use egui_phosphor::regular;
+use flux_cloud::{CloudPath, CloudStore};
+
+/// Actions that can be triggered from the cloud view
+#[derive(Debug, Clone, PartialEq)]
+pub enum CloudAction {
+    /// Connect to a bucket URL and list it
+    Open(String),
+    /// Descend into a prefix
+    Enter(String),
+    /// Go to the parent prefix
+    Up,
+    /// List the current prefix again
+    Refresh,
+    /// Download and extract the object with this key
+    Extract(String),
+    /// Use the current prefix as the pack destination
+    PackHere,
+}
+
+/// Actions from the field for opening an archive by URL
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArchiveUrlAction {
+    /// Read the archive at this URL into the browser
+    Open(String),
+    /// Stop reading the archive
+    Cancel,
+}
+
+/// Cloud browser state
+#[derive(Default)]
+pub struct CloudBrowserState {
+    /// Text in the location field
+    pub url_input: String,
+    /// Connection to the current bucket
+    store: Option<CloudStore>,
+    /// Bucket and prefix currently shown
+    location: Option<CloudPath>,
+    /// Contents of the current prefix
+    entries: Vec<CloudEntry>,
+    /// Listing in progress
+    pending: Option<Receiver<Result<CloudListing, String>>>,
+    /// Error from the last listing
+    error: Option<String>,
+}
+
+impl CloudBrowserState {
+    /// Start listing `url`, reusing the connection if the bucket is unchanged
+    pub fn open(&mut self, url: &str) -> Result<(), String> {
+        let location = CloudPath::parse(url.trim()).map_err(|e| e.to_string())?;
+        self.list(location);
+        Ok(())
+    }
+
+    /// Start listing a prefix in the current bucket
+    pub fn enter(&mut self, key: &str) {
+        if let Some(location) = &self.location {
+            self.list(CloudPath {
+                path: key.into(),
+                ..location.clone()
+            });
+        }
+    }
+
+    /// Start listing the parent of the current prefix
+    pub fn up(&mut self) {
+        if let Some(location) = &self.location {
+            let parent = location
+                .path
+                .as_ref()
+                .rsplit_once('/')
+                .map(|(parent, _)| parent)
+                .unwrap_or("");
+            self.list(CloudPath {
+                path: parent.into(),
+                ..location.clone()
+            });
+        }
+    }
+
+    /// List the current prefix again
+    pub fn refresh(&mut self) {
+        if let Some(location) = self.location.clone() {
+            self.list(location);
+        }
+    }
+
+    fn list(&mut self, location: CloudPath) {
+        let same_bucket = self.location.as_ref().is_some_and(|current| {
+            current.scheme == location.scheme && current.bucket == location.bucket
+        });
+        let store = if same_bucket {
+            self.store.clone()
+        } else {
+            None
+        };
+
+        self.url_input = location.to_string();
+        self.error = None;
+        self.pending = Some(list_location(store, location));
+    }
+
+    /// Pick up a finished listing; returns true if the state changed
+    pub fn poll(&mut self) -> bool {
+        let Some(result) = self.pending.as_ref().and_then(|rx| rx.try_recv().ok()) else {
+            return false;
+        };
+        self.pending = None;
+
+        match result {
+            Ok(listing) => {
+                self.url_input = listing.location.to_string();
+                self.store = Some(listing.store);
+                self.location = Some(listing.location);
+                self.entries = listing.entries;
+            }
+            Err(e) => self.error = Some(e),
+        }
+        true
+    }
+
+    /// Whether a listing is in progress
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// URL of the prefix being shown
+    pub fn current_url(&self) -> Option<String> {
+        self.location.as_ref().map(|location| location.to_string())
+    }
+
+    /// URL of an object in the current bucket
+    pub fn object_url(&self, key: &str) -> Option<String> {
+        self.location
+            .as_ref()
+            .map(|location| format!("{}://{}/{}", location.scheme, location.bucket, key))
+    }
+}
+
+/// Draw the field for opening an archive by its cloud URL
+///
+/// `reading` is the number of entries found so far while an archive is
+/// being read, during which the field is replaced by a progress line.
+pub fn draw_archive_url_bar(
+    ui: &mut egui::Ui,
+    input: &mut String,
+    reading: Option<usize>,
+    width: f32,
+) -> Option<ArchiveUrlAction> {
+    let mut action = None;
+
+    ui.allocate_ui(egui::vec2(width, 0.0), |ui| {
+        ui.horizontal(|ui| {
+            if let Some(count) = reading {
+                ui.spinner();
+                ui.label(t!("cloud-reading-archive", count = count));
+                if ui.button(t!("common-cancel")).clicked() {
+                    action = Some(ArchiveUrlAction::Cancel);
+                }
+                return;
+            }
+
+            ui.label(regular::CLOUD);
+            let response = ui.add(
+                egui::TextEdit::singleline(input)
+                    .hint_text("s3://bucket/archive.tar.zst")
+                    .desired_width(width - 160.0),
+            );
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+            if ui
+                .add_enabled(
+                    !input.trim().is_empty(),
+                    FluxButton::new(t!("cloud-open-archive")).icon(regular::CLOUD_ARROW_DOWN),
+                )
+                .on_hover_text(t!("cloud-open-archive-hint"))
+                .clicked()
+                || (submitted && !input.trim().is_empty())
+            {
+                action = Some(ArchiveUrlAction::Open(input.trim().to_string()));
+            }
+        });
+    });
+
+    action
+}
+
+/// Draw the cloud storage browser
+pub fn draw_cloud_view(
+    ui: &mut egui::Ui,
+    state: &mut CloudBrowserState,
+    profiles: &[CloudProfile],
+    theme: &FluxTheme,
+) -> Option<CloudAction> {
+    let mut action = None;
+    let connected = state.location.is_some();
+
+    ui.horizontal(|ui| {
+        ui.heading(t!("cloud-title"));
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .add_enabled(
+                    connected,
+                    FluxButton::new(t!("cloud-pack-here"))
+                        .primary()
+                        .icon(regular::UPLOAD),
+                )
+                .on_hover_text(t!("cloud-pack-here-hint"))
+                .clicked()
+            {
+                action = Some(CloudAction::PackHere);
+            }
+
+            ui.add_space(8.0);
+
+            if ui
+                .add_enabled(
+                    connected && !state.is_loading(),
+                    FluxButton::new(t!("cloud-refresh"))
+                        .ghost()
+                        .icon(regular::ARROW_CLOCKWISE),
+                )
+                .clicked()
+            {
+                action = Some(CloudAction::Refresh);
+            }
+        });
+    });
+
+    ui.add_space(20.0);
+
+    Card::show(ui, theme, |ui| {
+        ui.horizontal(|ui| {
+            let usable: Vec<_> = profiles.iter().filter(|p| !p.url.is_empty()).collect();
+            if !usable.is_empty() {
+                egui::ComboBox::from_id_source("cloud_profile")
+                    .selected_text(t!("cloud-profiles"))
+                    .show_ui(ui, |ui| {
+                        for profile in usable {
+                            if ui.selectable_label(false, &profile.name).clicked() {
+                                action = Some(CloudAction::Open(profile.url.clone()));
+                            }
+                        }
+                    });
+            }
+
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut state.url_input)
+                    .hint_text("s3://bucket/prefix")
+                    .desired_width(ui.available_width() - 90.0),
+            );
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+            if ui
+                .add(FluxButton::new(t!("cloud-open")).icon(regular::CLOUD))
+                .clicked()
+                || submitted
+            {
+                action = Some(CloudAction::Open(state.url_input.clone()));
+            }
+        });
+
+        ui.weak(t!("cloud-credentials-note"));
+    });
+
+    ui.add_space(10.0);
+
+    if let Some(error) = &state.error {
+        ui.colored_label(theme.colors.error, error);
+        ui.add_space(10.0);
+    }
+
+    if state.is_loading() {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label(t!("common-loading"));
+        });
+        return action;
+    }
+
+    let Some(location) = &state.location else {
+        ui.weak(t!("cloud-empty-prompt"));
+        return action;
+    };
+
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(
+                !location.path.as_ref().is_empty(),
+                egui::Button::new(regular::ARROW_UP),
+            )
+            .on_hover_text(t!("cloud-parent-folder"))
+            .clicked()
+        {
+            action = Some(CloudAction::Up);
+        }
+        ui.monospace(location.to_string());
+    });
+
+    ui.separator();
+
+    if state.entries.is_empty() {
+        ui.weak(t!("cloud-location-empty"));
+        return action;
+    }
+
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("cloud_entries")
+                .num_columns(4)
+                .striped(true)
+                .spacing([20.0, 6.0])
+                .show(ui, |ui| {
+                    for entry in &state.entries {
+                        if entry.is_dir {
+                            if ui
+                                .link(format!("{} {}", regular::FOLDER, entry.name))
+                                .clicked()
+                            {
+                                action = Some(CloudAction::Enter(entry.key.clone()));
+                            }
+                            ui.label("");
+                            ui.label("");
+                            ui.label("");
+                        } else {
+                            let is_archive = archive_format(&entry.key).is_some();
+                            let icon = if is_archive {
+                                regular::ARCHIVE
+                            } else {
+                                regular::FILE
+                            };
+                            ui.label(format!("{} {}", icon, entry.name));
+                            ui.label(format_size(entry.size));
+                            ui.weak(
+                                entry
+                                    .last_modified
+                                    .map(|time| time.format("%Y-%m-%d %H:%M").to_string())
+                                    .unwrap_or_default(),
+                            );
+                            if is_archive {
+                                if ui
+                                    .small_button(format!(
+                                        "{} {}",
+                                        regular::DOWNLOAD,
+                                        t!("nav-extract")
+                                    ))
+                                    .on_hover_text(t!("cloud-extract-hint"))
+                                    .clicked()
+                                {
+                                    action = Some(CloudAction::Extract(entry.key.clone()));
+                                }
+                            } else {
+                                ui.label("");
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+    action
+}
+
+/// Format an object size for display
+fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
@@ -0,0 +1,139 @@
+//! Command palette listing every command with its shortcut
+//!
+//! Typing filters the list; the arrow keys move the selection and Enter
+//! runs it, so the whole application can be driven without the mouse.
+
+use crate::shortcuts::{Command, Shortcuts};
+use crate::t;
+use crate::theme::FluxTheme;
+use eframe::egui;
+use egui_phosphor::regular;
+
+/// Actions that can be triggered from the command palette
+#[derive(Debug, Clone, PartialEq)]
+pub enum PaletteAction {
+    /// Run a command and close the palette
+    Run(Command),
+    /// Close the palette without running anything
+    Close,
+}
+
+/// Text typed into the open palette and the highlighted command
+#[derive(Debug, Default)]
+pub struct CommandPalette {
+    query: String,
+    selected: usize,
+}
+
+impl CommandPalette {
+    /// Commands whose label contains every word of the query
+    fn matches(&self) -> Vec<Command> {
+        let query = self.query.to_lowercase();
+        Command::ALL
+            .into_iter()
+            .filter(|&command| command != Command::OpenPalette)
+            .filter(|command| {
+                let label = command.label().to_lowercase();
+                query.split_whitespace().all(|word| label.contains(word))
+            })
+            .collect()
+    }
+}
+
+/// Draw the palette at the top of the window
+pub fn draw_command_palette(
+    ctx: &egui::Context,
+    palette: &mut CommandPalette,
+    shortcuts: &Shortcuts,
+    theme: &FluxTheme,
+) -> Option<PaletteAction> {
+    let mut action = None;
+    let matches = palette.matches();
+    palette.selected = palette.selected.min(matches.len().saturating_sub(1));
+
+    let (up, down, enter, escape) = ctx.input_mut(|i| {
+        (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            i.key_pressed(egui::Key::Enter),
+            i.key_pressed(egui::Key::Escape),
+        )
+    });
+    if up {
+        palette.selected = palette.selected.saturating_sub(1);
+    }
+    if down && palette.selected + 1 < matches.len() {
+        palette.selected += 1;
+    }
+
+    let response = egui::Window::new("command_palette")
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .fixed_size([440.0, 0.0])
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 80.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(regular::MAGNIFYING_GLASS).size(18.0));
+                let input = ui.add(
+                    egui::TextEdit::singleline(&mut palette.query)
+                        .hint_text(t!("palette-hint"))
+                        .desired_width(f32::INFINITY)
+                        .frame(false),
+                );
+                input.request_focus();
+                if input.changed() {
+                    palette.selected = 0;
+                }
+            });
+            ui.separator();
+
+            if matches.is_empty() {
+                ui.weak(t!("palette-no-matches"));
+                return;
+            }
+
+            egui::ScrollArea::vertical()
+                .max_height(320.0)
+                .show(ui, |ui| {
+                    for (index, &command) in matches.iter().enumerate() {
+                        let selected = index == palette.selected;
+                        let row = ui.horizontal(|ui| {
+                            let label = ui.selectable_label(selected, command.label());
+                            if let Some(shortcut) = shortcuts.get(command) {
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        ui.label(
+                                            egui::RichText::new(shortcut.display(ctx))
+                                                .color(theme.colors.text_weak)
+                                                .monospace(),
+                                        );
+                                    },
+                                );
+                            }
+                            label
+                        });
+                        if selected {
+                            row.inner.scroll_to_me(None);
+                        }
+                        if row.inner.clicked() {
+                            action = Some(PaletteAction::Run(command));
+                        }
+                    }
+                });
+        });
+
+    if action.is_none() {
+        if enter {
+            action = Some(match matches.get(palette.selected) {
+                Some(&command) => PaletteAction::Run(command),
+                None => PaletteAction::Close,
+            });
+        } else if escape || response.is_some_and(|r| r.response.clicked_elsewhere()) {
+            action = Some(PaletteAction::Close);
+        }
+    }
+
+    action
+}
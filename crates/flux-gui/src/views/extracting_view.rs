@@ -1,31 +1,88 @@
 //! Extracting view for the Flux GUI
 //! This module handles the UI rendering for extracting archives
 
+use crate::preflight::Preflight;
+use crate::t;
+use crate::views::browser_view::format_size;
 use eframe::egui;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Where each archive of a batch is extracted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BatchDestination {
+    /// Straight into the destination folder
+    SameFolder,
+    /// Into a folder named after the archive, inside the destination
+    #[default]
+    Subfolder,
+}
+
+impl BatchDestination {
+    /// Folder `archive` is extracted to, inside `base` or next to the
+    /// archive when no base folder was chosen
+    pub fn output_dir(self, archive: &Path, base: Option<&Path>) -> Option<PathBuf> {
+        let base = match base {
+            Some(base) => base.to_path_buf(),
+            None => archive.parent()?.to_path_buf(),
+        };
+        Some(match self {
+            BatchDestination::SameFolder => base,
+            BatchDestination::Subfolder => base.join(archive_stem(archive)),
+        })
+    }
+}
+
+/// File name of `archive` without its archive extension, so
+/// `photos.tar.gz` becomes `photos`
+pub fn archive_stem(archive: &Path) -> String {
+    let name = archive
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "archive".to_string());
+    match crate::cloud::archive_format(&name) {
+        Some(format) => name[..name.len() - format.len() - 1].to_string(),
+        None => name,
+    }
+}
 
 /// Draw the extracting view UI
+///
+/// With more than one archive the view lists them all and extracts each
+/// one according to `destination`.
 pub fn draw_extracting_view(
     _ctx: &egui::Context,
     ui: &mut egui::Ui,
-    archive_path: &Option<PathBuf>,
+    archives: &[PathBuf],
     output_dir: &Option<PathBuf>,
-    is_busy: bool,
     hoist_enabled: &mut bool,
+    destination: &mut BatchDestination,
+    preflight: &Preflight,
 ) -> Option<ExtractingAction> {
+    if archives.len() > 1 {
+        return draw_batch(
+            ui,
+            archives,
+            output_dir,
+            hoist_enabled,
+            destination,
+            preflight,
+        );
+    }
+
     let mut action = None;
+    let archive_path = archives.first();
 
-    ui.heading("📂 Extract Archive");
+    ui.heading(format!("📂 {}", t!("welcome-extract-archive")));
     ui.separator();
     ui.add_space(10.0);
 
     // Display the archive to extract prominently
     if let Some(archive) = archive_path {
         // Show archive name in a highlighted box
-        ui.group(|ui| {
+        let group = ui.group(|ui| {
             ui.horizontal(|ui| {
                 ui.label("📦");
-                ui.strong("Archive to extract:");
+                ui.strong(t!("extract-archive-to-extract"));
             });
             ui.add_space(5.0);
             ui.horizontal(|ui| {
@@ -33,10 +90,25 @@ pub fn draw_extracting_view(
                     archive
                         .file_name()
                         .and_then(|n| n.to_str())
-                        .unwrap_or_else(|| archive.to_str().unwrap_or("Unknown")),
+                        .unwrap_or_else(|| archive.to_str().unwrap_or(t!("common-unknown"))),
                 );
             });
         });
+        ui.interact(
+            group.response.rect,
+            ui.id().with("archive_card"),
+            egui::Sense::click(),
+        )
+        .context_menu(|ui| {
+            if ui.button(format!("ℹ {}", t!("properties"))).clicked() {
+                action = Some(ExtractingAction::ShowProperties);
+                ui.close_menu();
+            }
+            if ui.button(format!("✔ {}", t!("verify"))).clicked() {
+                action = Some(ExtractingAction::Verify);
+                ui.close_menu();
+            }
+        });
 
         ui.add_space(10.0);
 
@@ -44,61 +116,43 @@ pub fn draw_extracting_view(
         if let Ok(metadata) = std::fs::metadata(archive) {
             let size_mb = metadata.len() as f64 / (1024.0 * 1024.0);
             ui.horizontal(|ui| {
-                ui.label("Size:");
+                ui.label(t!("extract-size"));
                 ui.label(format!("{:.2} MB", size_mb));
             });
         }
 
         // Display archive type
-        if let Some(ext) = archive.extension() {
-            let archive_type = match ext.to_str().unwrap_or("").to_lowercase().as_str() {
-                "zip" => "ZIP Archive",
-                "gz" => {
-                    if archive.to_str().unwrap_or("").ends_with(".tar.gz") {
-                        "TAR.GZ Archive"
-                    } else {
-                        "GZIP Archive"
-                    }
-                }
-                "zst" => {
-                    if archive.to_str().unwrap_or("").ends_with(".tar.zst") {
-                        "TAR.ZST Archive (Zstandard)"
-                    } else {
-                        "Zstandard Archive"
-                    }
-                }
-                "xz" => {
-                    if archive.to_str().unwrap_or("").ends_with(".tar.xz") {
-                        "TAR.XZ Archive"
-                    } else {
-                        "XZ Archive"
-                    }
-                }
-                "7z" => "7-Zip Archive",
-                "tar" => "TAR Archive",
-                _ => "Archive",
+        if archive.extension().is_some() {
+            let archive_type = match flux_core::archive::archive_format(archive) {
+                Some("zip") => t!("archive-type-zip"),
+                Some("tar.gz") => t!("archive-type-tar-gz"),
+                Some("tar.zst") => t!("archive-type-tar-zst"),
+                Some("tar.xz") => t!("archive-type-tar-xz"),
+                Some("7z") => t!("archive-type-7z"),
+                Some("tar") => t!("archive-type-tar"),
+                _ => t!("dialog-archive"),
             };
             ui.horizontal(|ui| {
-                ui.label("Type:");
+                ui.label(t!("extract-type"));
                 ui.label(archive_type);
             });
         }
     } else {
-        ui.label("No archive selected");
+        ui.label(t!("common-no-archive-selected"));
     }
 
     ui.add_space(20.0);
 
     // Output directory selection with visual guidance
     ui.horizontal(|ui| {
-        ui.label("Output directory:");
+        ui.label(t!("extract-output-directory"));
 
         // Display current output directory or placeholder text
         let has_output = output_dir.is_some();
         let output_text = output_dir
             .as_ref()
             .map(|p| p.display().to_string())
-            .unwrap_or_else(|| "⚠️ No directory selected".to_string());
+            .unwrap_or_else(|| format!("⚠️ {}", t!("common-no-directory-selected")));
 
         if has_output {
             ui.label(&output_text);
@@ -107,20 +161,21 @@ pub fn draw_extracting_view(
         }
 
         // Browse button to select output directory
-        if ui
-            .add_enabled(!is_busy, egui::Button::new("Browse..."))
-            .clicked()
-        {
+        if ui.button(t!("common-browse-ellipsis")).clicked() {
             action = Some(ExtractingAction::SelectOutputDir);
         }
     });
 
+    if output_dir.is_some() {
+        draw_preflight(ui, preflight);
+    }
+
     // Show helpful tip if output not selected
-    if output_dir.is_none() && !is_busy {
+    if output_dir.is_none() {
         ui.add_space(5.0);
         ui.indent("extract_tip", |ui| {
             ui.label(
-                egui::RichText::new("💡 Select where to extract the files")
+                egui::RichText::new(format!("💡 {}", t!("extract-select-output-tip")))
                     .size(12.0)
                     .color(ui.style().visuals.weak_text_color()),
             );
@@ -131,21 +186,17 @@ pub fn draw_extracting_view(
     ui.add_space(10.0);
     ui.horizontal(|ui| {
         ui.label("ℹ️");
-        ui.weak("Files will be extracted to the selected directory");
+        ui.weak(t!("extract-note"));
     });
 
     // Smart directory hoisting option
     ui.add_space(10.0);
     ui.horizontal(|ui| {
         ui.checkbox(hoist_enabled, "");
-        ui.label("Smart folder extraction");
+        ui.label(t!("extract-smart-folder"));
         if ui
             .small_button("?")
-            .on_hover_text(
-                "If the archive contains a single folder at the root level, \
-            extract its contents directly to the output directory, \
-            eliminating redundant nested folders.",
-            )
+            .on_hover_text(t!("extract-smart-folder-hint"))
             .clicked()
         {
             // Do nothing, just showing tooltip
@@ -157,35 +208,31 @@ pub fn draw_extracting_view(
     // Action buttons
     ui.horizontal(|ui| {
         // Start extraction button
-        let can_start = !is_busy && archive_path.is_some() && output_dir.is_some();
+        let can_start =
+            archive_path.is_some() && output_dir.is_some() && preflight.problems().is_empty();
         if ui
             .add_enabled(
                 can_start,
-                egui::Button::new("Start Extracting").min_size(egui::vec2(140.0, 35.0)),
+                egui::Button::new(t!("extract-start")).min_size(egui::vec2(140.0, 35.0)),
             )
+            .on_disabled_hover_text(t!("preflight-blocked"))
             .clicked()
         {
             action = Some(ExtractingAction::StartExtracting);
         }
 
-        // Cancel button - enabled when busy (cancels task) or when not busy (clears selection)
-        let cancel_text = if is_busy { "Cancel Task" } else { "Clear" };
         if ui
-            .add(egui::Button::new(cancel_text).min_size(egui::vec2(80.0, 35.0)))
+            .add(egui::Button::new(t!("common-clear")).min_size(egui::vec2(80.0, 35.0)))
             .clicked()
         {
-            action = Some(if is_busy {
-                ExtractingAction::Cancel
-            } else {
-                ExtractingAction::Clear
-            });
+            action = Some(ExtractingAction::Clear);
         }
 
         ui.add_space(20.0);
 
         // Browse for different archive
         if ui
-            .add_enabled(!is_busy, egui::Button::new("📁 Browse Archive"))
+            .button(format!("📁 {}", t!("extract-browse-archive")))
             .clicked()
         {
             action = Some(ExtractingAction::BrowseArchive);
@@ -194,8 +241,8 @@ pub fn draw_extracting_view(
         // Open archive browser
         if archive_path.is_some()
             && ui
-                .add_enabled(!is_busy, egui::Button::new("🔍 View Contents"))
-                .on_hover_text("Browse archive contents without extracting")
+                .button(format!("🔍 {}", t!("extract-view-contents")))
+                .on_hover_text(t!("extract-view-contents-hint"))
                 .clicked()
         {
             action = Some(ExtractingAction::OpenBrowser);
@@ -205,6 +252,161 @@ pub fn draw_extracting_view(
     action
 }
 
+/// Several archives with one destination rule
+fn draw_batch(
+    ui: &mut egui::Ui,
+    archives: &[PathBuf],
+    output_dir: &Option<PathBuf>,
+    hoist_enabled: &mut bool,
+    destination: &mut BatchDestination,
+    preflight: &Preflight,
+) -> Option<ExtractingAction> {
+    let mut action = None;
+
+    ui.heading(format!("📂 {}", t!("welcome-extract-archive")));
+    ui.separator();
+    ui.add_space(10.0);
+
+    ui.strong(t!("extract-batch-title", count = archives.len()));
+    ui.add_space(5.0);
+    egui::ScrollArea::vertical()
+        .max_height(240.0)
+        .auto_shrink([false, true])
+        .show(ui, |ui| {
+            egui::Grid::new("batch_archives")
+                .num_columns(4)
+                .spacing([16.0, 6.0])
+                .striped(true)
+                .show(ui, |ui| {
+                    for (idx, archive) in archives.iter().enumerate() {
+                        let name = archive
+                            .file_name()
+                            .map(|name| name.to_string_lossy().into_owned())
+                            .unwrap_or_else(|| archive.display().to_string());
+                        ui.label(format!("📦 {}", name));
+                        ui.weak(
+                            std::fs::metadata(archive)
+                                .map(|m| format_size(m.len()))
+                                .unwrap_or_default(),
+                        );
+                        let target = destination.output_dir(archive, output_dir.as_deref());
+                        ui.weak(format!(
+                            "→ {}",
+                            target
+                                .map(|path| path.display().to_string())
+                                .unwrap_or_default()
+                        ));
+                        if ui
+                            .small_button("✖")
+                            .on_hover_text(t!("extract-batch-remove"))
+                            .clicked()
+                        {
+                            action = Some(ExtractingAction::RemoveArchive(idx));
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+
+    ui.add_space(15.0);
+
+    ui.horizontal(|ui| {
+        ui.label(t!("extract-output-directory"));
+        match output_dir {
+            Some(dir) => {
+                ui.label(dir.display().to_string());
+            }
+            None => {
+                ui.label(t!("extract-batch-next-to-archives"));
+            }
+        }
+        if ui.button(t!("common-browse-ellipsis")).clicked() {
+            action = Some(ExtractingAction::SelectOutputDir);
+        }
+        if output_dir.is_some() && ui.button(t!("extract-batch-use-archive-folders")).clicked() {
+            action = Some(ExtractingAction::UseArchiveFolders);
+        }
+    });
+
+    ui.add_space(5.0);
+    ui.horizontal(|ui| {
+        ui.label(t!("extract-batch-destination"));
+        ui.radio_value(
+            destination,
+            BatchDestination::Subfolder,
+            t!("extract-batch-subfolder"),
+        )
+        .on_hover_text(t!("extract-batch-subfolder-hint"));
+        ui.radio_value(
+            destination,
+            BatchDestination::SameFolder,
+            t!("extract-batch-same-folder"),
+        )
+        .on_hover_text(t!("extract-batch-same-folder-hint"));
+    });
+
+    draw_preflight(ui, preflight);
+
+    ui.add_space(10.0);
+    ui.horizontal(|ui| {
+        ui.checkbox(hoist_enabled, "");
+        ui.label(t!("extract-smart-folder"));
+        ui.small_button("?")
+            .on_hover_text(t!("extract-smart-folder-hint"));
+    });
+
+    ui.add_space(20.0);
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(
+                preflight.problems().is_empty(),
+                egui::Button::new(t!("extract-batch-start", count = archives.len()))
+                    .min_size(egui::vec2(140.0, 35.0)),
+            )
+            .on_disabled_hover_text(t!("preflight-blocked"))
+            .clicked()
+        {
+            action = Some(ExtractingAction::StartExtracting);
+        }
+
+        if ui
+            .add(egui::Button::new(t!("common-clear")).min_size(egui::vec2(80.0, 35.0)))
+            .clicked()
+        {
+            action = Some(ExtractingAction::Clear);
+        }
+
+        ui.add_space(20.0);
+
+        if ui
+            .button(format!("📁 {}", t!("extract-browse-archive")))
+            .clicked()
+        {
+            action = Some(ExtractingAction::BrowseArchive);
+        }
+    });
+
+    action
+}
+
+/// What is wrong with the destination, or that it is still being checked
+pub fn draw_preflight(ui: &mut egui::Ui, preflight: &Preflight) {
+    if preflight.is_checking() {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.weak(t!("preflight-checking"));
+        });
+        return;
+    }
+    let color = ui.visuals().warn_fg_color;
+    for problem in preflight.problems() {
+        ui.colored_label(
+            color,
+            format!("{} {}", egui_phosphor::regular::WARNING, problem.message()),
+        );
+    }
+}
+
 /// Actions that can be triggered from the extracting view
 #[derive(Debug, Clone)]
 pub enum ExtractingAction {
@@ -216,8 +418,14 @@ pub enum ExtractingAction {
     BrowseArchive,
     /// Clear current selection
     Clear,
-    /// Cancel the current operation
-    Cancel,
     /// Open archive browser to view contents
     OpenBrowser,
+    /// Show the properties of the selected archive
+    ShowProperties,
+    /// Verify the selected archive
+    Verify,
+    /// Drop an archive from the batch
+    RemoveArchive(usize),
+    /// Extract the batch next to each archive instead of into one folder
+    UseArchiveFolders,
 }
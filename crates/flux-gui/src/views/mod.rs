@@ -1,14 +1,40 @@
 //! View modules for Flux GUI
 
+pub mod browser_edit;
 pub mod browser_table_view;
 pub mod browser_view;
+pub mod cloud_view;
+pub mod command_palette;
 pub mod extracting_view;
 pub mod packing_view;
 pub mod packing_view_modern;
+pub mod preview_pane;
+pub mod properties_view;
+pub mod recent_view;
+pub mod schedules_view;
+pub mod session_view;
+pub mod settings_view;
+pub mod snapshot_view;
 pub mod sync_view;
+pub mod task_history_view;
+pub mod task_queue_view;
+pub mod verify_view;
 
 pub use browser_view::{draw_browser_view, BrowserAction, BrowserState};
-pub use extracting_view::{draw_extracting_view, ExtractingAction};
+pub use cloud_view::{
+    draw_archive_url_bar, draw_cloud_view, ArchiveUrlAction, CloudAction, CloudBrowserState,
+};
+pub use command_palette::{draw_command_palette, CommandPalette, PaletteAction};
+pub use extracting_view::{draw_extracting_view, BatchDestination, ExtractingAction};
 pub use packing_view::PackingAction;
-pub use packing_view_modern::draw_packing_view_modern;
-pub use sync_view::{draw_sync_view, SyncAction};
+pub use packing_view_modern::{draw_packing_view_modern, PackJobOptions};
+pub use properties_view::{draw_properties_dialog, ArchiveProperties};
+pub use recent_view::{draw_recent_list, RecentAction};
+pub use schedules_view::{draw_schedules_view, ScheduleAction, ScheduleForm};
+pub use session_view::{draw_session_dialog, SessionAction, SessionRestore};
+pub use settings_view::{draw_settings_view, SettingsAction};
+pub use snapshot_view::{draw_snapshots, SnapshotAction, SnapshotBrowser};
+pub use sync_view::{draw_sync_view, SyncAction, SyncPreview};
+pub use task_history_view::{draw_task_details_dialog, draw_task_history_view, TaskHistoryAction};
+pub use task_queue_view::{draw_task_queue_view, TaskQueueAction};
+pub use verify_view::{draw_verify_dialog, ArchiveVerification, VerifyAction};
@@ -5,13 +5,13 @@ use eframe::egui;
 use std::path::PathBuf;
 
 /// Draw the packing view UI
+#[allow(dead_code)]
 pub fn draw_packing_view(
     _ctx: &egui::Context,
     ui: &mut egui::Ui,
     input_files: &[PathBuf],
     output_path: &Option<PathBuf>,
     compression_format: &mut String,
-    is_busy: bool,
 ) -> Option<PackingAction> {
     let mut action = None;
 
@@ -42,14 +42,11 @@ pub fn draw_packing_view(
                         .unwrap_or_else(|| file.to_str().unwrap_or("Unknown"));
                     ui.label(display_name);
 
-                    // Add remove button if not busy
-                    if !is_busy {
-                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                            if ui.small_button("❌").clicked() {
-                                files_to_remove.push(idx);
-                            }
-                        });
-                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("❌").clicked() {
+                            files_to_remove.push(idx);
+                        }
+                    });
                 });
             }
         });
@@ -64,20 +61,18 @@ pub fn draw_packing_view(
     // Compression format selection using ComboBox
     ui.horizontal(|ui| {
         ui.label("Archive format:");
-        ui.add_enabled_ui(!is_busy, |ui| {
-            egui::ComboBox::from_label("")
-                .selected_text(compression_format.as_str())
-                .show_ui(ui, |ui| {
-                    ui.selectable_value(compression_format, "tar.gz".to_string(), "tar.gz");
-                    ui.selectable_value(
-                        compression_format,
-                        "tar.zst".to_string(),
-                        "tar.zst (recommended)",
-                    );
-                    ui.selectable_value(compression_format, "tar.xz".to_string(), "tar.xz");
-                    ui.selectable_value(compression_format, "zip".to_string(), "zip");
-                });
-        });
+        egui::ComboBox::from_label("")
+            .selected_text(compression_format.as_str())
+            .show_ui(ui, |ui| {
+                ui.selectable_value(compression_format, "tar.gz".to_string(), "tar.gz");
+                ui.selectable_value(
+                    compression_format,
+                    "tar.zst".to_string(),
+                    "tar.zst (recommended)",
+                );
+                ui.selectable_value(compression_format, "tar.xz".to_string(), "tar.xz");
+                ui.selectable_value(compression_format, "zip".to_string(), "zip");
+            });
     });
 
     ui.add_space(10.0);
@@ -100,16 +95,13 @@ pub fn draw_packing_view(
         }
 
         // Browse button to select output location
-        if ui
-            .add_enabled(!is_busy, egui::Button::new("Browse..."))
-            .clicked()
-        {
+        if ui.button("Browse...").clicked() {
             action = Some(PackingAction::SelectOutput);
         }
     });
 
     // Show helpful tip if output not selected
-    if output_path.is_none() && !is_busy {
+    if output_path.is_none() {
         ui.add_space(5.0);
         ui.indent("output_tip", |ui| {
             ui.label(
@@ -125,7 +117,7 @@ pub fn draw_packing_view(
     // Action buttons
     ui.horizontal(|ui| {
         // Start packing button
-        let can_start = !is_busy && !input_files.is_empty() && output_path.is_some();
+        let can_start = !input_files.is_empty() && output_path.is_some();
         if ui
             .add_enabled(
                 can_start,
@@ -136,26 +128,17 @@ pub fn draw_packing_view(
             action = Some(PackingAction::StartPacking);
         }
 
-        // Cancel button - enabled when busy (cancels task) or when not busy (clears selection)
-        let cancel_text = if is_busy { "Cancel Task" } else { "Clear" };
         if ui
-            .add(egui::Button::new(cancel_text).min_size(egui::vec2(80.0, 35.0)))
+            .add(egui::Button::new("Clear").min_size(egui::vec2(80.0, 35.0)))
             .clicked()
         {
-            action = Some(if is_busy {
-                PackingAction::Cancel
-            } else {
-                PackingAction::ClearAll
-            });
+            action = Some(PackingAction::ClearAll);
         }
 
         ui.add_space(20.0);
 
         // Add more files button
-        if ui
-            .add_enabled(!is_busy, egui::Button::new("➕ Add More Files"))
-            .clicked()
-        {
+        if ui.button("➕ Add More Files").clicked() {
             action = Some(PackingAction::AddMoreFiles);
         }
     });
@@ -176,6 +159,4 @@ pub enum PackingAction {
     StartPacking,
     /// Clear all selections
     ClearAll,
-    /// Cancel the current operation
-    Cancel,
 }
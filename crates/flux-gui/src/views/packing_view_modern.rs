@@ -1,49 +1,373 @@
 //! Modern packing view with card-based UI
-use crate::components::{DropZone, FluxButton, FluxProgress};
+use crate::components::{DropZone, FluxButton};
 use crate::layout::{draw_file_card, Card};
+use crate::preflight::Preflight;
+use crate::progress_tracker::format_duration;
+use crate::settings::{algorithm_for_format, Settings, PACK_FORMATS, SMART_FORMAT};
+use crate::smart::{self, SmartPick};
+use crate::t;
+use crate::task::ResourceLimits;
 use crate::theme::FluxTheme;
+use crate::views::browser_view::format_size;
+use crate::views::extracting_view::draw_preflight;
+use crossbeam_channel::Receiver;
 use eframe::egui;
 use egui_phosphor::regular;
-use std::path::PathBuf;
+use flux_core::archive::{
+    estimate_pack, scan_metadata, Capability, FilterStats, FormatCapabilities, FoundMetadata,
+    PackEstimate, PackFilter, PackOptions,
+};
+use flux_core::strategy::Algorithm;
+use std::path::{Path, PathBuf};
+
+/// Level used when a forced compression overrides a smart "store" pick
+const FORCED_ZSTD_LEVEL: u32 = 3;
+
+/// Size limit suggested when the size filter is first turned on
+const DEFAULT_MAX_FILE_MB: u64 = 100;
+
+/// Rate suggested when a disk or network cap is first turned on
+const DEFAULT_RATE_MB: u32 = 20;
+
+/// Inputs and filter a [`FilterStats`] scan was run for
+type ScanKey = (Vec<PathBuf>, PackFilter, bool);
+
+/// Inputs, format, level, filter and symlink handling an estimate was
+/// made for
+type EstimateKey = (Vec<PathBuf>, String, Option<u32>, PackFilter, bool);
+
+/// Compression options for the archive being set up in the pack view
+///
+/// Seeded from [`Settings`] and reset with the job, so changes here never
+/// touch the saved defaults.
+#[derive(Debug)]
+pub struct PackJobOptions {
+    /// Compression level; `None` keeps the format's (or smart) default
+    pub level: Option<u32>,
+    /// Worker threads; `None` uses every available core
+    pub threads: Option<usize>,
+    /// Priority and rate caps of the task
+    pub limits: ResourceLimits,
+    /// Archive the targets of symlinks instead of the links
+    pub follow_symlinks: bool,
+    /// Compress even when smart packing would store the files
+    pub force_compress: bool,
+    /// Glob patterns for files and folders to leave out
+    pub exclude_patterns: Vec<String>,
+    /// Leave out `.git` and other version control folders
+    pub ignore_vcs: bool,
+    /// Leave out files larger than this many megabytes
+    pub max_file_mb: Option<u64>,
+    /// Result of the last smart analysis
+    pick: Option<Result<SmartPick, String>>,
+    /// Analysis in progress
+    pending: Option<Receiver<Result<SmartPick, String>>>,
+    /// Inputs the current pick belongs to
+    analyzed: Vec<PathBuf>,
+    /// What the exclusions skip for the current inputs
+    scan: Option<FilterStats>,
+    /// Metadata of the current inputs that only some formats keep
+    found: Vec<FoundMetadata>,
+    /// Exclusion and metadata scan in progress
+    scan_pending: Option<Receiver<(FilterStats, Vec<FoundMetadata>)>>,
+    /// Inputs and filter of the latest scan
+    scanned: Option<ScanKey>,
+    /// Expected archive size and packing time
+    estimate: Option<Result<PackEstimate, String>>,
+    /// Estimate in progress
+    estimate_pending: Option<Receiver<Result<PackEstimate, String>>>,
+    /// What the latest estimate was made for
+    estimated: Option<EstimateKey>,
+}
+
+impl PackJobOptions {
+    /// Options preset from the user's settings
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            level: settings.compression_level,
+            threads: settings.threads,
+            limits: settings.limits.clone(),
+            follow_symlinks: false,
+            force_compress: false,
+            exclude_patterns: Vec::new(),
+            ignore_vcs: false,
+            max_file_mb: None,
+            pick: None,
+            pending: None,
+            analyzed: Vec::new(),
+            scan: None,
+            found: Vec::new(),
+            scan_pending: None,
+            scanned: None,
+            estimate: None,
+            estimate_pending: None,
+            estimated: None,
+        }
+    }
+
+    /// Start a smart analysis unless `inputs` were already analyzed
+    pub fn analyze(&mut self, inputs: &[PathBuf]) {
+        if inputs.is_empty() || self.analyzed == inputs {
+            return;
+        }
+        self.analyzed = inputs.to_vec();
+        self.pick = None;
+        self.pending = Some(smart::analyze(inputs.to_vec()));
+    }
+
+    /// Count what the exclusions skip and find the metadata of what they
+    /// keep, unless `inputs` were already scanned with the current filter
+    pub fn scan(&mut self, inputs: &[PathBuf]) {
+        let Ok(filter) = self.filter() else {
+            return;
+        };
+        let key = (inputs.to_vec(), filter, self.follow_symlinks);
+        if self.scanned.as_ref() == Some(&key) {
+            return;
+        }
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let (inputs, filter, follow_symlinks) = key.clone();
+        std::thread::spawn(move || {
+            let stats = filter.scan(&inputs, follow_symlinks);
+            let options = PackOptions {
+                follow_symlinks,
+                filter,
+                ..PackOptions::default()
+            };
+            let _ = sender.send((stats, scan_metadata(&inputs, &options)));
+        });
+        self.scanned = Some(key);
+        self.scan_pending = Some(receiver);
+    }
+
+    /// Estimate the archive `format` would produce, unless it was already
+    /// estimated with the current options
+    ///
+    /// Waits for the smart pick, since the estimate depends on it.
+    pub fn estimate(&mut self, inputs: &[PathBuf], format: &str) {
+        if inputs.is_empty() {
+            return;
+        }
+        let (Some(effective), Ok(options)) =
+            (self.effective_format(format), self.pack_options(format))
+        else {
+            return;
+        };
+        let key = (
+            inputs.to_vec(),
+            effective.to_string(),
+            options.level,
+            options.filter.clone(),
+            options.follow_symlinks,
+        );
+        if self.estimated.as_ref() == Some(&key) {
+            return;
+        }
+
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let (inputs, format) = (key.0.clone(), key.1.clone());
+        std::thread::spawn(move || {
+            let result = estimate_pack(&inputs, &format, &options).map_err(|e| e.to_string());
+            let _ = sender.send(result);
+        });
+        self.estimated = Some(key);
+        self.estimate_pending = Some(receiver);
+    }
+
+    /// Collect finished background work
+    pub fn poll(&mut self) {
+        if let Some(receiver) = &self.pending {
+            match receiver.try_recv() {
+                Ok(result) => {
+                    self.pick = Some(result);
+                    self.pending = None;
+                }
+                Err(crossbeam_channel::TryRecvError::Empty) => {}
+                Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                    self.pick = Some(Err(t!("pack-analysis-stopped").to_string()));
+                    self.pending = None;
+                }
+            }
+        }
+
+        if let Some(receiver) = &self.scan_pending {
+            match receiver.try_recv() {
+                Ok((stats, found)) => {
+                    self.scan = Some(stats);
+                    self.found = found;
+                    self.scan_pending = None;
+                }
+                Err(crossbeam_channel::TryRecvError::Empty) => {}
+                Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                    self.scan_pending = None;
+                }
+            }
+        }
+
+        if let Some(receiver) = &self.estimate_pending {
+            match receiver.try_recv() {
+                Ok(result) => {
+                    self.estimate = Some(result);
+                    self.estimate_pending = None;
+                }
+                Err(crossbeam_channel::TryRecvError::Empty) => {}
+                Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                    self.estimate_pending = None;
+                }
+            }
+        }
+    }
+
+    /// Expected size of the archive, once estimated
+    pub fn archive_size(&self) -> Option<u64> {
+        match &self.estimate {
+            Some(Ok(estimate)) => Some(estimate.archive_size),
+            _ => None,
+        }
+    }
+
+    /// Whether a smart analysis is running
+    pub fn is_analyzing(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Whether a smart analysis, exclusion scan or estimate is running
+    pub fn is_busy(&self) -> bool {
+        self.pending.is_some() || self.scan_pending.is_some() || self.estimate_pending.is_some()
+    }
+
+    /// The exclusions as a flux-core filter
+    pub fn filter(&self) -> flux_core::Result<PackFilter> {
+        Ok(PackFilter::new(&self.exclude_patterns)?
+            .ignore_vcs(self.ignore_vcs)
+            .max_file_size(self.max_file_mb.map(|mb| mb * 1024 * 1024)))
+    }
+
+    /// Algorithm smart packing will use, after `force_compress`
+    fn smart_algorithm(&self) -> Option<Algorithm> {
+        let pick = self.pick.as_ref()?.as_ref().ok()?;
+        Some(match pick.algorithm {
+            Algorithm::Store if self.force_compress => Algorithm::Zstd,
+            algorithm => algorithm,
+        })
+    }
+
+    /// Concrete archive format for `format`, resolving smart packing
+    ///
+    /// `None` while the smart analysis has not produced a pick.
+    pub fn effective_format<'a>(&self, format: &'a str) -> Option<&'a str> {
+        if format == SMART_FORMAT {
+            self.smart_algorithm().map(smart::format_for)
+        } else {
+            Some(format)
+        }
+    }
+
+    /// Pack options for `format`
+    ///
+    /// Fails while smart packing is still analyzing the inputs or when an
+    /// exclude pattern is invalid. Smart packing passes the analyzed
+    /// algorithm explicitly, so the archive is written exactly as the view
+    /// described it.
+    pub fn pack_options(&self, format: &str) -> Result<PackOptions, String> {
+        let analyzing = t!("pack-still-analyzing");
+
+        let filter = self.filter().map_err(|e| e.to_string())?;
+        let effective = self.effective_format(format).ok_or(analyzing)?;
+        let max_level = Settings::max_level(effective);
+
+        let (algorithm, level) = if format == SMART_FORMAT {
+            let pick = match &self.pick {
+                Some(Ok(pick)) => pick,
+                _ => return Err(analyzing.to_string()),
+            };
+            let algorithm = self.smart_algorithm().ok_or(analyzing)?;
+            let default_level = if algorithm == pick.algorithm {
+                pick.level
+            } else {
+                FORCED_ZSTD_LEVEL
+            };
+            (
+                Some(algorithm.to_string()),
+                Some(self.level.unwrap_or(default_level).min(max_level)),
+            )
+        } else {
+            (
+                algorithm_for_format(format),
+                self.level.map(|level| level.min(max_level)),
+            )
+        };
+
+        Ok(PackOptions {
+            smart: false,
+            algorithm,
+            level,
+            threads: self.threads,
+            force_compress: self.force_compress,
+            follow_symlinks: self.follow_symlinks,
+            max_depth: None,
+            filter,
+            metadata: None,
+            clamp_mtime: None,
+            normalize_names: flux_core::archive::NameNormalization::Keep,
+            strip_prefix: None,
+            prefix: None,
+            use_mmap: false,
+            transform: None,
+            time_budget: None,
+            paranoid: false,
+            hashes: None,
+            entry_order: flux_core::archive::PackOrder::Walk,
+        })
+    }
+}
+
+/// `path` with its archive extension replaced by `format`
+pub fn with_format(path: &Path, format: &str) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "archive".to_string());
+    let stem = ["tar.gz", "tar.zst", "tar.xz", "tar.br", "tar", "zip"]
+        .iter()
+        .find_map(|ext| name.strip_suffix(&format!(".{}", ext)))
+        .unwrap_or(&name);
+    path.with_file_name(format!("{}.{}", stem, format))
+}
 
 /// Draw the modern packing view
+#[allow(clippy::too_many_arguments)]
 pub fn draw_packing_view_modern(
     ctx: &egui::Context,
     ui: &mut egui::Ui,
     input_files: &[PathBuf],
     output_path: &Option<PathBuf>,
+    cloud_output: Option<&str>,
     compression_format: &mut String,
-    is_busy: bool,
+    job: &mut PackJobOptions,
+    preflight: &Preflight,
     theme: &FluxTheme,
-    current_progress: f32,
-    status_text: &str,
 ) -> Option<super::PackingAction> {
     let mut action = None;
 
     // Header section
     ui.horizontal(|ui| {
-        ui.heading("Create Archive");
+        ui.heading(t!("welcome-create-archive"));
 
         // Action buttons in header
         ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-            if is_busy {
+            if !input_files.is_empty() {
                 if ui
-                    .add(FluxButton::new("Cancel").danger().icon(regular::X_CIRCLE))
-                    .clicked()
-                {
-                    action = Some(super::PackingAction::Cancel);
-                }
-            } else if input_files.is_empty() {
-                // Show nothing
-            } else {
-                if ui
-                    .add(
-                        FluxButton::new("Start Packing")
+                    .add_enabled(
+                        preflight.problems().is_empty(),
+                        FluxButton::new(t!("pack-start"))
                             .primary()
                             .icon(regular::PLAY),
                     )
+                    .on_disabled_hover_text(t!("preflight-blocked"))
                     .clicked()
-                    && output_path.is_some()
+                    && (output_path.is_some() || cloud_output.is_some())
                 {
                     action = Some(super::PackingAction::StartPacking);
                 }
@@ -51,7 +375,11 @@ pub fn draw_packing_view_modern(
                 ui.add_space(8.0);
 
                 if ui
-                    .add(FluxButton::new("Clear All").ghost().icon(regular::TRASH))
+                    .add(
+                        FluxButton::new(t!("pack-clear-all"))
+                            .ghost()
+                            .icon(regular::TRASH),
+                    )
                     .clicked()
                 {
                     action = Some(super::PackingAction::ClearAll);
@@ -62,31 +390,13 @@ pub fn draw_packing_view_modern(
 
     ui.add_space(20.0);
 
-    // Progress section if busy
-    if is_busy {
-        Card::show(ui, theme, |ui| {
-            ui.vertical(|ui| {
-                ui.label(
-                    egui::RichText::new("Packing in progress...")
-                        .size(16.0)
-                        .strong(),
-                );
-                ui.add_space(10.0);
-
-                ui.add(FluxProgress::new(current_progress).text(status_text));
-            });
-        });
-
-        ui.add_space(20.0);
-    }
-
     // Files section
     if input_files.is_empty() {
         // Show drop zone when no files
         let drop_response = ui.add(
             DropZone::new("pack_drop")
-                .text("Drop files or folders to pack")
-                .subtext("You can add multiple items"),
+                .text(t!("pack-drop"))
+                .subtext(t!("pack-drop-multiple")),
         );
 
         if drop_response.clicked() {
@@ -100,29 +410,26 @@ pub fn draw_packing_view_modern(
         // Configuration card
         Card::show(ui, theme, |ui| {
             ui.vertical(|ui| {
-                ui.label(egui::RichText::new("Archive Settings").size(16.0).strong());
+                ui.label(
+                    egui::RichText::new(t!("pack-archive-settings"))
+                        .size(16.0)
+                        .strong(),
+                );
                 ui.add_space(10.0);
 
                 // Format selection
                 ui.horizontal(|ui| {
-                    ui.label("Format:");
+                    ui.label(t!("pack-format"));
                     ui.add_space(10.0);
 
-                    let formats = [
-                        ("zip", "ZIP", "Universal compatibility"),
-                        ("tar.gz", "TAR.GZ", "Good compression"),
-                        ("tar.zst", "TAR.ZST", "Best performance"),
-                        ("tar.xz", "TAR.XZ", "Best compression"),
-                    ];
-
-                    for (value, label, desc) in formats {
+                    for &(value, label, desc) in PACK_FORMATS {
                         let is_selected = compression_format == value;
                         let format_id = ui.make_persistent_id(("format", value));
 
                         let (rect, response) =
                             ui.allocate_exact_size(egui::vec2(120.0, 60.0), egui::Sense::click());
 
-                        if response.clicked() && !is_busy {
+                        if response.clicked() {
                             *compression_format = value.to_string();
                         }
 
@@ -156,7 +463,7 @@ pub fn draw_packing_view_modern(
                         ui.painter().text(
                             rect.center() - egui::vec2(0.0, 10.0),
                             egui::Align2::CENTER_CENTER,
-                            label,
+                            crate::i18n::tr(label),
                             egui::FontId::proportional(14.0),
                             if is_selected {
                                 theme.colors.primary
@@ -168,7 +475,7 @@ pub fn draw_packing_view_modern(
                         ui.painter().text(
                             rect.center() + egui::vec2(0.0, 10.0),
                             egui::Align2::CENTER_CENTER,
-                            desc,
+                            crate::i18n::tr(desc),
                             egui::FontId::proportional(10.0),
                             theme.colors.text_weak,
                         );
@@ -177,15 +484,37 @@ pub fn draw_packing_view_modern(
                     }
                 });
 
+                ui.add_space(10.0);
+
+                if compression_format == SMART_FORMAT {
+                    draw_smart_pick(ui, job, theme);
+                    ui.add_space(10.0);
+                }
+
+                draw_estimate(ui, job, theme);
+                draw_dropped_metadata(ui, compression_format, job, theme);
+                ui.add_space(10.0);
+
+                draw_job_options(ui, compression_format, job);
+
+                ui.add_space(10.0);
+                draw_exclusions(ui, job, theme);
+
                 ui.add_space(20.0);
                 ui.separator();
                 ui.add_space(10.0);
 
                 // Output path
                 ui.horizontal(|ui| {
-                    ui.label(egui::RichText::new("Output:").strong());
+                    ui.label(egui::RichText::new(t!("pack-output")).strong());
 
-                    if let Some(path) = output_path {
+                    if let Some(url) = cloud_output {
+                        ui.label(
+                            egui::RichText::new(format!("{} {}", regular::CLOUD, url))
+                                .monospace()
+                                .color(theme.colors.text_weak),
+                        );
+                    } else if let Some(path) = output_path {
                         ui.label(
                             egui::RichText::new(path.display().to_string())
                                 .monospace()
@@ -193,21 +522,23 @@ pub fn draw_packing_view_modern(
                         );
                     } else {
                         ui.label(
-                            egui::RichText::new("No output selected").color(theme.colors.warning),
+                            egui::RichText::new(t!("pack-no-output")).color(theme.colors.warning),
                         );
                     }
 
                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                         if ui
-                            .add(FluxButton::new("Browse").icon(regular::FOLDER_OPEN))
-                            .on_hover_text("Select output location")
+                            .add(FluxButton::new(t!("common-browse")).icon(regular::FOLDER_OPEN))
+                            .on_hover_text(t!("pack-select-output"))
                             .clicked()
-                            && !is_busy
                         {
                             action = Some(super::PackingAction::SelectOutput);
                         }
                     });
                 });
+                if output_path.is_some() && cloud_output.is_none() {
+                    draw_preflight(ui, preflight);
+                }
             });
         });
 
@@ -216,16 +547,19 @@ pub fn draw_packing_view_modern(
         // Files list header
         ui.horizontal(|ui| {
             ui.label(
-                egui::RichText::new(format!("Files to Pack ({})", input_files.len()))
+                egui::RichText::new(t!("pack-files-to-pack", count = input_files.len()))
                     .size(16.0)
                     .strong(),
             );
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 if ui
-                    .add(FluxButton::new("Add More").ghost().icon(regular::PLUS))
+                    .add(
+                        FluxButton::new(t!("pack-add-more"))
+                            .ghost()
+                            .icon(regular::PLUS),
+                    )
                     .clicked()
-                    && !is_busy
                 {
                     action = Some(super::PackingAction::AddMoreFiles);
                 }
@@ -269,9 +603,7 @@ pub fn draw_packing_view_modern(
 
                                 ui.allocate_ui(egui::vec2(card_width, 80.0), |ui| {
                                     draw_file_card(ui, theme, path, *size, global_idx, || {
-                                        if !is_busy {
-                                            file_to_remove = Some(global_idx);
-                                        }
+                                        file_to_remove = Some(global_idx);
                                     });
                                 });
 
@@ -286,9 +618,7 @@ pub fn draw_packing_view_modern(
                     // Single column layout
                     for (idx, (path, size)) in file_infos.iter().enumerate() {
                         draw_file_card(ui, theme, path, *size, idx, || {
-                            if !is_busy {
-                                file_to_remove = Some(idx);
-                            }
+                            file_to_remove = Some(idx);
                         });
                         ui.add_space(spacing);
                     }
@@ -303,6 +633,319 @@ pub fn draw_packing_view_modern(
     action
 }
 
+/// Explain what smart packing chose for the current inputs
+fn draw_smart_pick(ui: &mut egui::Ui, job: &PackJobOptions, theme: &FluxTheme) {
+    egui::Frame::none()
+        .fill(theme.colors.primary.gamma_multiply(0.08))
+        .rounding(theme.rounding)
+        .inner_margin(egui::Margin::same(10.0))
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width());
+            match &job.pick {
+                _ if job.is_analyzing() => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(t!("pack-analyzing"));
+                    });
+                }
+                Some(Ok(pick)) => {
+                    let algorithm = job.smart_algorithm().unwrap_or(pick.algorithm);
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "{} {}",
+                            regular::SPARKLE,
+                            t!(
+                                "pack-smart-choice",
+                                algorithm = algorithm,
+                                format = smart::format_for(algorithm).to_uppercase()
+                            )
+                        ))
+                        .strong(),
+                    );
+                    if algorithm == pick.algorithm {
+                        ui.label(t!(
+                            "pack-smart-reason",
+                            reason = crate::i18n::tr(pick.reason),
+                            level = pick.level
+                        ));
+                    } else {
+                        ui.label(t!("pack-smart-forced", level = FORCED_ZSTD_LEVEL));
+                    }
+                    ui.weak(t!("pack-smart-basis", basis = pick.basis));
+                }
+                Some(Err(e)) => {
+                    ui.colored_label(
+                        theme.colors.error,
+                        format!(
+                            "{} {}",
+                            regular::WARNING,
+                            t!("pack-analysis-failed", error = e)
+                        ),
+                    );
+                }
+                None => {
+                    ui.weak(t!("pack-smart-empty"));
+                }
+            }
+        });
+}
+
+/// Expected archive size and packing time for the current options
+fn draw_estimate(ui: &mut egui::Ui, job: &PackJobOptions, theme: &FluxTheme) {
+    ui.horizontal(|ui| {
+        ui.label(egui::RichText::new(regular::SCALES).color(theme.colors.text_weak));
+        // The previous estimate stays visible while a new one is made
+        match &job.estimate {
+            Some(Ok(estimate)) if estimate.files > 0 => {
+                let approx = if estimate.is_exact() { "" } else { "~" };
+                ui.label(t!(
+                    "pack-estimate",
+                    size = format!("{}{}", approx, format_size(estimate.archive_size)),
+                    ratio = format!("{:.0}", estimate.ratio().unwrap_or(1.0) * 100.0),
+                    input = format_size(estimate.input_bytes)
+                ));
+                ui.weak(t!(
+                    "pack-estimate-duration",
+                    duration = format_duration(estimate.duration.as_secs_f64().max(1.0))
+                ));
+            }
+            Some(Ok(_)) => {
+                ui.weak(t!("pack-estimate-empty"));
+            }
+            Some(Err(e)) => {
+                ui.colored_label(theme.colors.error, t!("pack-estimate-failed", error = e));
+            }
+            None if job.estimate_pending.is_none() => {
+                ui.weak(t!("pack-estimate-waiting"));
+            }
+            None => {}
+        }
+        if job.estimate_pending.is_some() {
+            ui.spinner();
+            if job.estimate.is_none() {
+                ui.weak(t!("pack-estimating"));
+            }
+        }
+    });
+}
+
+/// What of the inputs the chosen format would not keep
+fn draw_dropped_metadata(ui: &mut egui::Ui, format: &str, job: &PackJobOptions, theme: &FluxTheme) {
+    let Some(format) = job.effective_format(format) else {
+        return;
+    };
+    let Some(capabilities) = FormatCapabilities::of(format) else {
+        return;
+    };
+    let format = format.to_uppercase();
+    for found in capabilities.dropped(&job.found) {
+        let key = match found.capability {
+            Capability::Symlinks => "pack-drops-symlinks",
+            Capability::Permissions => "pack-drops-permissions",
+            Capability::Xattrs => "pack-drops-xattrs",
+            Capability::LargeFiles => "pack-drops-large-files",
+            Capability::UnicodeNames => "pack-drops-unicode-names",
+            Capability::Encryption | Capability::Append => continue,
+        };
+        let text = crate::i18n::tr_args(
+            key,
+            &[
+                ("format", &format),
+                ("count", &found.count),
+                ("example", &found.example.display()),
+            ],
+        );
+        ui.colored_label(
+            theme.colors.warning,
+            format!("{} {}", regular::WARNING, text),
+        );
+    }
+}
+
+/// Level, thread and symlink controls for this job
+fn draw_job_options(ui: &mut egui::Ui, format: &str, job: &mut PackJobOptions) {
+    // Zip archives are written with fixed settings
+    let tunable = format != "zip";
+
+    egui::Grid::new("pack_job_options")
+        .num_columns(2)
+        .spacing([20.0, 8.0])
+        .show(ui, |ui| {
+            ui.label(t!("pack-level"));
+            ui.add_enabled_ui(tunable, |ui| {
+                ui.horizontal(|ui| {
+                    let mut automatic = job.level.is_none();
+                    if ui
+                        .checkbox(&mut automatic, t!("settings-automatic"))
+                        .changed()
+                    {
+                        job.level = if automatic { None } else { Some(6) };
+                    }
+                    let max = Settings::max_level(job.effective_format(format).unwrap_or(format));
+                    if let Some(level) = &mut job.level {
+                        *level = (*level).min(max);
+                        ui.add(egui::Slider::new(level, 1..=max));
+                    }
+                });
+            });
+            ui.end_row();
+
+            ui.label(t!("settings-threads"));
+            ui.add_enabled_ui(tunable, |ui| {
+                ui.horizontal(|ui| {
+                    let mut automatic = job.threads.is_none();
+                    if ui
+                        .checkbox(&mut automatic, t!("settings-all-cores"))
+                        .changed()
+                    {
+                        job.threads = if automatic { None } else { Some(1) };
+                    }
+                    if let Some(threads) = &mut job.threads {
+                        let max = std::thread::available_parallelism()
+                            .map(|n| n.get())
+                            .unwrap_or(1)
+                            .max(*threads);
+                        ui.add(egui::Slider::new(threads, 1..=max));
+                    }
+                });
+            });
+            ui.end_row();
+
+            draw_resource_limits(ui, &mut job.limits);
+
+            ui.label(t!("pack-symlinks"));
+            ui.checkbox(&mut job.follow_symlinks, t!("pack-follow-symlinks"))
+                .on_hover_text(t!("pack-follow-symlinks-hint"));
+            ui.end_row();
+
+            if format == SMART_FORMAT {
+                ui.label(t!("pack-already-compressed"));
+                ui.checkbox(&mut job.force_compress, t!("pack-force-compression"))
+                    .on_hover_text(t!("pack-force-compression-hint"));
+                ui.end_row();
+            }
+        });
+}
+
+/// Priority and rate cap rows of an options grid
+pub fn draw_resource_limits(ui: &mut egui::Ui, limits: &mut ResourceLimits) {
+    ui.label(t!("limits-priority"));
+    ui.checkbox(&mut limits.background, t!("limits-background"))
+        .on_hover_text(t!("limits-background-hint"));
+    ui.end_row();
+
+    ui.label(t!("limits-disk"));
+    draw_rate_cap(ui, &mut limits.disk_mb_per_sec);
+    ui.end_row();
+
+    ui.label(t!("limits-network"));
+    draw_rate_cap(ui, &mut limits.network_mb_per_sec);
+    ui.end_row();
+}
+
+/// A checkbox turning a cap in MB/s on and off, and its value
+fn draw_rate_cap(ui: &mut egui::Ui, cap: &mut Option<u32>) {
+    ui.horizontal(|ui| {
+        let mut limited = cap.is_some();
+        if ui.checkbox(&mut limited, t!("limits-cap")).changed() {
+            *cap = limited.then_some(DEFAULT_RATE_MB);
+        }
+        if let Some(mb) = cap {
+            ui.add(egui::DragValue::new(mb).range(1..=100_000).suffix(" MB/s"));
+        }
+    });
+}
+
+/// Exclude patterns, VCS and size filters, with what they skip
+fn draw_exclusions(ui: &mut egui::Ui, job: &mut PackJobOptions, theme: &FluxTheme) {
+    egui::CollapsingHeader::new(format!("{} {}", regular::FUNNEL, t!("pack-exclusions")))
+        .id_source("pack_exclusions")
+        .default_open(false)
+        .show(ui, |ui| {
+            let mut remove = None;
+            for (idx, pattern) in job.exclude_patterns.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    let valid = PackFilter::new(&[pattern.as_str()]).is_ok();
+                    let mut edit = egui::TextEdit::singleline(pattern)
+                        .hint_text(t!("pack-pattern-hint"))
+                        .desired_width(240.0);
+                    if !valid {
+                        edit = edit.text_color(theme.colors.error);
+                    }
+                    ui.add(edit);
+                    if ui
+                        .small_button(regular::X)
+                        .on_hover_text(t!("pack-remove-pattern"))
+                        .clicked()
+                    {
+                        remove = Some(idx);
+                    }
+                });
+            }
+            if let Some(idx) = remove {
+                job.exclude_patterns.remove(idx);
+            }
+
+            if ui
+                .add(
+                    FluxButton::new(t!("pack-add-pattern"))
+                        .ghost()
+                        .icon(regular::PLUS),
+                )
+                .clicked()
+            {
+                job.exclude_patterns.push(String::new());
+            }
+
+            ui.add_space(6.0);
+            ui.checkbox(&mut job.ignore_vcs, t!("pack-ignore-vcs"))
+                .on_hover_text(t!("pack-ignore-vcs-hint"));
+
+            ui.horizontal(|ui| {
+                let mut limited = job.max_file_mb.is_some();
+                if ui.checkbox(&mut limited, t!("pack-skip-larger")).changed() {
+                    job.max_file_mb = limited.then_some(DEFAULT_MAX_FILE_MB);
+                }
+                if let Some(mb) = &mut job.max_file_mb {
+                    ui.add(egui::DragValue::new(mb).range(1..=1_048_576).suffix(" MB"));
+                }
+            });
+
+            ui.add_space(6.0);
+            match (&job.scan, job.filter()) {
+                (_, Err(e)) => {
+                    ui.colored_label(theme.colors.error, e.to_string());
+                }
+                _ if job.scan_pending.is_some() => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.weak(t!("pack-counting"));
+                    });
+                }
+                (Some(stats), Ok(_)) => {
+                    ui.label(t!(
+                        "pack-scan-included",
+                        count = stats.included_files,
+                        size = format_size(stats.included_bytes)
+                    ));
+                    if stats.skipped_files > 0 {
+                        ui.colored_label(
+                            theme.colors.warning,
+                            t!(
+                                "pack-scan-skipped",
+                                count = stats.skipped_files,
+                                size = format_size(stats.skipped_bytes)
+                            ),
+                        );
+                    } else {
+                        ui.weak(t!("pack-scan-nothing-skipped"));
+                    }
+                }
+                (None, Ok(_)) => {}
+            }
+        });
+}
+
 fn calculate_dir_size(path: &PathBuf) -> u64 {
     let mut size = 0;
     if let Ok(entries) = std::fs::read_dir(path) {
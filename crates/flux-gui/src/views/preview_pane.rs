@@ -0,0 +1,277 @@
+//! Preview of a single archive entry, streamed without extracting to disk
+
+use crate::t;
+use crate::theme::FluxTheme;
+use crossbeam_channel::Receiver;
+use egui::{RichText, Ui};
+use egui_extras::syntax_highlighting::{highlight, CodeTheme};
+use flux_core::archive::extractor::ArchiveEntry;
+use flux_core::archive::Archive;
+use std::io::Read;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Bytes read from an entry for the text and hex views
+const PREVIEW_LIMIT: u64 = 64 * 1024;
+
+/// Bytes read from an entry that looks like an image
+const IMAGE_LIMIT: u64 = 16 * 1024 * 1024;
+
+/// Largest image edge uploaded as a texture
+const MAX_IMAGE_EDGE: u32 = 1024;
+
+/// Bytes per hex dump row
+const HEX_ROW: usize = 16;
+
+/// Decoded preview of an entry
+pub enum PreviewContent {
+    /// UTF-8 text, highlighted by file extension
+    Text {
+        text: String,
+        language: String,
+        truncated: bool,
+    },
+    /// Decoded image, converted to a texture on first draw
+    Image {
+        image: egui::ColorImage,
+        width: u32,
+        height: u32,
+    },
+    /// Anything else, shown as a hex dump
+    Binary { bytes: Vec<u8>, truncated: bool },
+}
+
+/// Preview pane state for the highlighted entry
+#[derive(Default)]
+pub struct PreviewState {
+    /// Entry the preview belongs to
+    path: Option<PathBuf>,
+    /// Load in progress
+    pending: Option<Receiver<Result<PreviewContent, String>>>,
+    /// Result of the last load
+    content: Option<Result<PreviewContent, String>>,
+    /// Texture for image previews
+    texture: Option<egui::TextureHandle>,
+}
+
+impl PreviewState {
+    /// Start loading `entry` unless it is already shown
+    pub fn request(&mut self, archive: &Arc<Archive>, entry: &ArchiveEntry) {
+        if self.path.as_deref() == Some(entry.path.as_path()) {
+            return;
+        }
+
+        self.path = Some(entry.path.clone());
+        self.content = None;
+        self.texture = None;
+        self.pending = Some(load_preview(archive.clone(), entry.clone()));
+    }
+
+    /// Drop the current preview
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Pick up a finished load; returns true if the state changed
+    pub fn poll(&mut self) -> bool {
+        let Some(result) = self.pending.as_ref().and_then(|rx| rx.try_recv().ok()) else {
+            return false;
+        };
+        self.pending = None;
+        self.content = Some(result);
+        true
+    }
+
+    /// Whether a load is in progress
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+/// Read the start of `entry` on a background thread and decode it
+fn load_preview(
+    archive: Arc<Archive>,
+    entry: ArchiveEntry,
+) -> Receiver<Result<PreviewContent, String>> {
+    let (sender, receiver) = crossbeam_channel::bounded(1);
+
+    std::thread::spawn(move || {
+        let result = (|| {
+            let image_format = image::ImageFormat::from_path(&entry.path)
+                .ok()
+                .filter(|format| format.reading_enabled());
+            let limit = if image_format.is_some() {
+                IMAGE_LIMIT
+            } else {
+                PREVIEW_LIMIT
+            };
+
+            let reader = archive.open_entry(&entry).map_err(|e| e.to_string())?;
+
+            let mut bytes = Vec::new();
+            reader
+                .take(limit + 1)
+                .read_to_end(&mut bytes)
+                .map_err(|e| e.to_string())?;
+            let truncated = bytes.len() as u64 > limit;
+            bytes.truncate(limit as usize);
+
+            if let (Some(format), false) = (image_format, truncated) {
+                if let Ok(image) = image::load_from_memory_with_format(&bytes, format) {
+                    let (width, height) = (image.width(), image.height());
+                    let image = image.thumbnail(MAX_IMAGE_EDGE, MAX_IMAGE_EDGE).to_rgba8();
+                    return Ok(PreviewContent::Image {
+                        image: egui::ColorImage::from_rgba_unmultiplied(
+                            [image.width() as usize, image.height() as usize],
+                            image.as_raw(),
+                        ),
+                        width,
+                        height,
+                    });
+                }
+            }
+
+            if let Some(text) = decode_text(&bytes, truncated) {
+                let language = entry
+                    .path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .unwrap_or("txt")
+                    .to_string();
+                return Ok(PreviewContent::Text {
+                    text,
+                    language,
+                    truncated,
+                });
+            }
+
+            Ok(PreviewContent::Binary { bytes, truncated })
+        })();
+
+        let _ = sender.send(result);
+    });
+
+    receiver
+}
+
+/// Interpret `bytes` as text if it is valid UTF-8 without NUL bytes
+///
+/// A truncated read may end in the middle of a character, which is dropped.
+fn decode_text(bytes: &[u8], truncated: bool) -> Option<String> {
+    if bytes.contains(&0) {
+        return None;
+    }
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Some(text.to_string()),
+        Err(e) if truncated && e.error_len().is_none() => {
+            Some(String::from_utf8_lossy(&bytes[..e.valid_up_to()]).into_owned())
+        }
+        Err(_) => None,
+    }
+}
+
+/// Draw the preview for the highlighted entry
+pub fn draw_preview(ui: &mut Ui, state: &mut PreviewState, theme: &FluxTheme) {
+    if state.poll() {
+        ui.ctx().request_repaint();
+    }
+
+    if state.is_loading() {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label(t!("preview-loading"));
+        });
+        ui.ctx().request_repaint();
+        return;
+    }
+
+    match &state.content {
+        None => {}
+        Some(Err(e)) => {
+            ui.label(
+                RichText::new(t!("preview-unavailable", error = e))
+                    .color(theme.colors.text_weak)
+                    .italics(),
+            );
+        }
+        Some(Ok(PreviewContent::Text {
+            text,
+            language,
+            truncated,
+        })) => {
+            if *truncated {
+                truncation_note(ui, theme);
+            }
+            egui::ScrollArea::both()
+                .id_source("preview_text")
+                .show(ui, |ui| {
+                    let code_theme = CodeTheme::from_style(ui.style());
+                    let job = highlight(ui.ctx(), &code_theme, text, language);
+                    ui.add(egui::Label::new(job).selectable(true));
+                });
+        }
+        Some(Ok(PreviewContent::Image {
+            image,
+            width,
+            height,
+        })) => {
+            let texture = state.texture.get_or_insert_with(|| {
+                ui.ctx()
+                    .load_texture("entry_preview", image.clone(), Default::default())
+            });
+            ui.label(
+                RichText::new(format!("{} × {}", width, height)).color(theme.colors.text_weak),
+            );
+            egui::ScrollArea::vertical()
+                .id_source("preview_image")
+                .show(ui, |ui| {
+                    ui.add(egui::Image::new(&*texture).shrink_to_fit());
+                });
+        }
+        Some(Ok(PreviewContent::Binary { bytes, truncated })) => {
+            if *truncated {
+                truncation_note(ui, theme);
+            }
+            egui::ScrollArea::both().id_source("preview_hex").show_rows(
+                ui,
+                ui.text_style_height(&egui::TextStyle::Monospace),
+                bytes.len().div_ceil(HEX_ROW),
+                |ui, rows| {
+                    for row in rows {
+                        let start = row * HEX_ROW;
+                        let end = (start + HEX_ROW).min(bytes.len());
+                        ui.monospace(hex_row(start, &bytes[start..end]));
+                    }
+                },
+            );
+        }
+    }
+}
+
+fn truncation_note(ui: &mut Ui, theme: &FluxTheme) {
+    ui.label(
+        RichText::new(t!("preview-truncated", size = PREVIEW_LIMIT / 1024))
+            .color(theme.colors.text_weak)
+            .small(),
+    );
+}
+
+/// Format one hex dump row: offset, bytes, then printable ASCII
+fn hex_row(offset: usize, bytes: &[u8]) -> String {
+    let mut line = format!("{:08x} ", offset);
+    for i in 0..HEX_ROW {
+        match bytes.get(i) {
+            Some(byte) => line.push_str(&format!(" {:02x}", byte)),
+            None => line.push_str("   "),
+        }
+    }
+    line.push_str("  ");
+    line.extend(bytes.iter().map(|&byte| {
+        if byte.is_ascii_graphic() || byte == b' ' {
+            byte as char
+        } else {
+            '.'
+        }
+    }));
+    line
+}
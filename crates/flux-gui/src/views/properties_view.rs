@@ -0,0 +1,250 @@
+//! Archive properties dialog
+//!
+//! Statistics are collected on a background thread with
+//! [`flux_core::archive::inspect_stats`], which reads every entry header of
+//! tar archives and may take a moment for large ones.
+
+use super::browser_view::{format_size, format_timestamp};
+use crate::t;
+use crate::theme::FluxTheme;
+use crossbeam_channel::Receiver;
+use eframe::egui;
+use egui_phosphor::regular;
+use flux_core::archive::{inspect_stats, ArchiveStats};
+use std::path::PathBuf;
+
+/// Properties of one archive, loaded in the background
+pub struct ArchiveProperties {
+    path: PathBuf,
+    stats: Option<Result<ArchiveStats, String>>,
+    pending: Option<Receiver<Result<ArchiveStats, String>>>,
+}
+
+impl ArchiveProperties {
+    /// Start collecting the properties of `path`
+    pub fn open(path: PathBuf) -> Self {
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let archive = path.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(inspect_stats(&archive).map_err(|e| e.to_string()));
+        });
+
+        Self {
+            path,
+            stats: None,
+            pending: Some(receiver),
+        }
+    }
+
+    /// Pick up the statistics once they are ready
+    pub fn poll(&mut self) {
+        if let Some(receiver) = &self.pending {
+            if let Ok(result) = receiver.try_recv() {
+                self.stats = Some(result);
+                self.pending = None;
+            }
+        }
+    }
+
+    /// Whether the statistics are still being collected
+    pub fn is_loading(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+/// Draw the properties window, returning true once it is closed
+pub fn draw_properties_dialog(
+    ctx: &egui::Context,
+    properties: &ArchiveProperties,
+    theme: &FluxTheme,
+) -> bool {
+    let mut open = true;
+    let mut close = false;
+    let name = properties
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| properties.path.display().to_string());
+
+    egui::Window::new(format!("{} {}", regular::INFO, name))
+        .id(egui::Id::new("archive_properties"))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.set_min_width(360.0);
+            ui.weak(properties.path.display().to_string());
+            ui.add_space(10.0);
+
+            match &properties.stats {
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(t!("properties-reading"));
+                    });
+                }
+                Some(Err(e)) => {
+                    ui.colored_label(theme.colors.error, t!("properties-read-failed", error = e));
+                }
+                Some(Ok(stats)) => draw_stats(ui, stats, theme),
+            }
+
+            ui.add_space(10.0);
+            ui.separator();
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button(t!("common-close")).clicked() {
+                    close = true;
+                }
+            });
+        });
+
+    close || !open
+}
+
+fn draw_stats(ui: &mut egui::Ui, stats: &ArchiveStats, theme: &FluxTheme) {
+    egui::Grid::new("archive_properties_grid")
+        .num_columns(2)
+        .spacing([20.0, 6.0])
+        .show(ui, |ui| {
+            ui.label(t!("properties-format"));
+            ui.label(stats.format.as_str());
+            ui.end_row();
+
+            ui.label(t!("properties-compression"));
+            if stats.methods.is_empty() {
+                ui.weak(t!("common-unknown"));
+            } else {
+                ui.label(stats.methods.join(", "));
+            }
+            ui.end_row();
+
+            ui.label(t!("properties-archive-size"));
+            ui.label(format_size(stats.archive_size));
+            ui.end_row();
+
+            if stats.encrypted_headers {
+                // Nothing below is known without the password
+                return;
+            }
+
+            ui.label(t!("properties-extracted-size"));
+            ui.label(format_size(stats.total_size));
+            ui.end_row();
+
+            ui.label(t!("properties-ratio"));
+            match stats.ratio() {
+                Some(ratio) => ui.label(t!(
+                    "properties-ratio-value",
+                    ratio = format!("{:.1}", ratio * 100.0),
+                    saved = format!("{:.1}", (1.0 - ratio).max(0.0) * 100.0)
+                )),
+                None => ui.weak(t!("properties-not-available")),
+            };
+            ui.end_row();
+
+            ui.label(t!("properties-entries"));
+            ui.label(t!(
+                "properties-entries-value",
+                files = stats.files,
+                folders = stats.directories,
+                symlinks = stats.symlinks
+            ));
+            ui.end_row();
+        });
+
+    ui.add_space(10.0);
+
+    if stats.encrypted_headers {
+        ui.colored_label(
+            theme.colors.warning,
+            format!("{} {}", regular::LOCK, t!("properties-encrypted-headers")),
+        );
+    } else if stats.encrypted_entries > 0 {
+        ui.colored_label(
+            theme.colors.warning,
+            format!(
+                "{} {}",
+                regular::LOCK,
+                t!(
+                    "properties-encrypted-entries",
+                    count = stats.encrypted_entries,
+                    total = stats.files
+                )
+            ),
+        );
+    } else {
+        ui.label(format!(
+            "{} {}",
+            regular::LOCK_OPEN,
+            t!("properties-not-encrypted")
+        ));
+    }
+
+    match &stats.signature {
+        Some(signature) => {
+            ui.label(format!(
+                "{} {}",
+                regular::SEAL_CHECK,
+                t!(
+                    "properties-signature",
+                    name = signature
+                        .file_name()
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .unwrap_or_default()
+                )
+            ))
+            .on_hover_text(t!("properties-signature-hint"));
+        }
+        None => {
+            ui.weak(format!(
+                "{} {}",
+                regular::SEAL,
+                t!("properties-no-signature")
+            ));
+        }
+    }
+
+    if let Some(metadata) = &stats.metadata {
+        ui.add_space(10.0);
+        ui.label(egui::RichText::new(t!("properties-metadata")).strong());
+        egui::Grid::new("archive_metadata_grid")
+            .num_columns(2)
+            .spacing([20.0, 4.0])
+            .show(ui, |ui| {
+                let mut row = |label: String, value: &str| {
+                    ui.label(label);
+                    ui.label(value);
+                    ui.end_row();
+                };
+                if let Some(creator) = &metadata.creator {
+                    row(t!("properties-creator").to_string(), creator);
+                }
+                if let Some(hostname) = &metadata.hostname {
+                    row(t!("properties-hostname").to_string(), hostname);
+                }
+                if let Some(created) = metadata.created {
+                    row(
+                        t!("properties-created").to_string(),
+                        &format_timestamp(created),
+                    );
+                }
+                if let Some(version) = &metadata.flux_version {
+                    row(t!("properties-flux-version").to_string(), version);
+                }
+                for (key, value) in &metadata.values {
+                    row(format!("{}:", key), value);
+                }
+            });
+    }
+
+    if let Some(comment) = &stats.comment {
+        ui.add_space(10.0);
+        ui.label(egui::RichText::new(t!("properties-comment")).strong());
+        egui::ScrollArea::vertical()
+            .max_height(120.0)
+            .show(ui, |ui| {
+                ui.label(comment);
+            });
+    }
+}
@@ -0,0 +1,155 @@
+//! Recent archives and destinations on the Welcome view
+
+use crate::history::{History, RecentItem, RecentKind};
+use crate::t;
+use crate::theme::FluxTheme;
+use chrono::Local;
+use eframe::egui;
+use egui_phosphor::regular;
+use std::path::PathBuf;
+
+/// Actions that can be triggered from the recent list
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecentAction {
+    /// Open an archive in the browser
+    Reopen(PathBuf),
+    /// Pack a created archive again with the same settings
+    Rerun(PathBuf),
+    /// Pick an archive and extract it into this directory
+    ExtractTo(PathBuf),
+    /// Pin or unpin an item
+    TogglePin(PathBuf, RecentKind),
+    /// Forget an item
+    Remove(PathBuf, RecentKind),
+    /// Show the properties of an archive
+    Properties(PathBuf),
+}
+
+/// Draw the recent items, or nothing when the history is empty
+pub fn draw_recent_list(
+    ui: &mut egui::Ui,
+    history: &History,
+    theme: &FluxTheme,
+) -> Option<RecentAction> {
+    let items = history.items();
+    if items.is_empty() {
+        return None;
+    }
+
+    let mut action = None;
+
+    egui::Frame::none()
+        .fill(theme.colors.panel_bg)
+        .rounding(theme.rounding)
+        .inner_margin(egui::Margin::same(16.0))
+        .show(ui, |ui| {
+            ui.set_width(ui.available_width().min(640.0));
+            ui.horizontal(|ui| {
+                ui.label(egui::RichText::new(regular::CLOCK_COUNTER_CLOCKWISE).size(16.0));
+                ui.label(egui::RichText::new(t!("recent-title")).size(16.0).strong());
+            });
+            ui.add_space(8.0);
+
+            for item in items {
+                ui.horizontal(|ui| {
+                    if let Some(a) = draw_item(ui, item, theme) {
+                        action = Some(a);
+                    }
+                });
+            }
+        });
+
+    action
+}
+
+fn draw_item(ui: &mut egui::Ui, item: &RecentItem, theme: &FluxTheme) -> Option<RecentAction> {
+    let mut action = None;
+    let exists = item.path.exists();
+
+    let used = item.used.with_timezone(&Local).format("%Y-%m-%d %H:%M");
+    let (icon, when, hint) = match item.kind {
+        RecentKind::Opened => (
+            regular::FILE_ZIP,
+            t!("recent-opened", time = used),
+            t!("recent-opened-hint"),
+        ),
+        RecentKind::Created => (
+            regular::PACKAGE,
+            t!("recent-created", time = used),
+            t!("recent-created-hint"),
+        ),
+        RecentKind::Destination => (
+            regular::FOLDER_SIMPLE,
+            t!("recent-destination", time = used),
+            t!("recent-destination-hint"),
+        ),
+    };
+    let runnable = match item.kind {
+        RecentKind::Created => item.recipe.is_some(),
+        RecentKind::Opened | RecentKind::Destination => exists,
+    };
+
+    let name = item
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| item.path.display().to_string());
+
+    ui.label(icon);
+    let text = if exists {
+        egui::RichText::new(name)
+    } else {
+        egui::RichText::new(name).strikethrough()
+    };
+    let response = ui
+        .add_enabled(runnable, egui::Link::new(text))
+        .on_hover_text(format!("{}\n{}", item.path.display(), hint));
+    if response.clicked() {
+        action = Some(match item.kind {
+            RecentKind::Opened => RecentAction::Reopen(item.path.clone()),
+            RecentKind::Created => RecentAction::Rerun(item.path.clone()),
+            RecentKind::Destination => RecentAction::ExtractTo(item.path.clone()),
+        });
+    }
+    if exists && item.kind != RecentKind::Destination {
+        response.context_menu(|ui| {
+            if ui
+                .button(format!("{} {}", regular::INFO, t!("properties")))
+                .clicked()
+            {
+                action = Some(RecentAction::Properties(item.path.clone()));
+                ui.close_menu();
+            }
+        });
+    }
+    ui.label(
+        egui::RichText::new(when)
+            .size(12.0)
+            .color(theme.colors.text_weak),
+    );
+
+    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+        if ui
+            .small_button(regular::X)
+            .on_hover_text(t!("recent-remove"))
+            .clicked()
+        {
+            action = Some(RecentAction::Remove(item.path.clone(), item.kind));
+        }
+        let (pin_icon, pin_hint) = if item.pinned {
+            (regular::PUSH_PIN_SLASH, t!("recent-unpin"))
+        } else {
+            (regular::PUSH_PIN, t!("recent-pin"))
+        };
+        let pin = egui::RichText::new(pin_icon).color(if item.pinned {
+            theme.colors.primary
+        } else {
+            theme.colors.text_weak
+        });
+        if ui.small_button(pin).on_hover_text(pin_hint).clicked() {
+            action = Some(RecentAction::TogglePin(item.path.clone(), item.kind));
+        }
+    });
+
+    action
+}
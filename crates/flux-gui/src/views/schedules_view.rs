@@ -0,0 +1,291 @@
+//! Schedules view for recurring sync jobs
+
+use crate::components::FluxButton;
+use crate::layout::Card;
+use crate::scheduler::{Cadence, RunOutcome, RunRecord, Scheduler};
+use crate::t;
+use crate::theme::FluxTheme;
+use chrono::{DateTime, Local, Utc};
+use eframe::egui;
+use egui_phosphor::regular;
+use std::path::PathBuf;
+
+/// Actions that can be triggered from the schedules view
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScheduleAction {
+    /// Pick the directory for a new schedule
+    SelectSource,
+    /// Pick the archive for a new schedule
+    SelectTarget,
+    /// Create a schedule from the form
+    Add,
+    /// Turn a schedule on or off
+    SetEnabled(u64, bool),
+    /// Start a run of a schedule immediately
+    RunNow(u64),
+    /// Delete a schedule and its history
+    Remove(u64),
+}
+
+/// Fields for a schedule that has not been added yet
+#[derive(Debug, Default)]
+pub struct ScheduleForm {
+    /// Display name; the source folder name is used when empty
+    pub name: String,
+    /// Directory to back up
+    pub source_dir: Option<PathBuf>,
+    /// Archive the sync writes
+    pub target_archive: Option<PathBuf>,
+    /// How often to run
+    pub cadence: Cadence,
+}
+
+/// Draw the list of schedules and the form for adding one
+pub fn draw_schedules_view(
+    ui: &mut egui::Ui,
+    scheduler: &Scheduler,
+    form: &mut ScheduleForm,
+    theme: &FluxTheme,
+) -> Option<ScheduleAction> {
+    let mut action = None;
+
+    ui.heading(t!("schedules-title"));
+    ui.add_space(4.0);
+    ui.weak(t!("schedules-description"));
+    ui.add_space(20.0);
+
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            Card::show(ui, theme, |ui| {
+                ui.label(egui::RichText::new(t!("schedules-new")).size(16.0).strong());
+                ui.add_space(10.0);
+
+                egui::Grid::new("new_schedule")
+                    .num_columns(2)
+                    .spacing([20.0, 10.0])
+                    .show(ui, |ui| {
+                        ui.label(t!("schedules-name"));
+                        ui.add(
+                            egui::TextEdit::singleline(&mut form.name)
+                                .hint_text(t!("schedules-name-hint")),
+                        );
+                        ui.end_row();
+
+                        ui.label(t!("schedules-source-directory"));
+                        ui.horizontal(|ui| {
+                            path_label(
+                                ui,
+                                form.source_dir.as_ref(),
+                                t!("common-no-directory-selected"),
+                            );
+                            if ui.button(t!("common-browse-ellipsis")).clicked() {
+                                action = Some(ScheduleAction::SelectSource);
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label(t!("schedules-target-archive"));
+                        ui.horizontal(|ui| {
+                            path_label(
+                                ui,
+                                form.target_archive.as_ref(),
+                                t!("common-no-archive-selected"),
+                            );
+                            if ui.button(t!("common-browse-ellipsis")).clicked() {
+                                action = Some(ScheduleAction::SelectTarget);
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label(t!("schedules-runs"));
+                        egui::ComboBox::from_id_source("schedule_cadence")
+                            .selected_text(form.cadence.label())
+                            .show_ui(ui, |ui| {
+                                for cadence in Cadence::ALL {
+                                    ui.selectable_value(
+                                        &mut form.cadence,
+                                        cadence,
+                                        cadence.label(),
+                                    );
+                                }
+                            });
+                        ui.end_row();
+                    });
+
+                ui.add_space(10.0);
+                let ready = form.source_dir.is_some() && form.target_archive.is_some();
+                if ui
+                    .add_enabled(
+                        ready,
+                        FluxButton::new(t!("schedules-add"))
+                            .primary()
+                            .icon(regular::PLUS),
+                    )
+                    .clicked()
+                {
+                    action = Some(ScheduleAction::Add);
+                }
+            });
+
+            ui.add_space(10.0);
+
+            if scheduler.schedules().is_empty() {
+                ui.weak(t!("schedules-empty"));
+                return;
+            }
+
+            for schedule in scheduler.schedules() {
+                let running = scheduler.is_running(schedule.id);
+
+                Card::show(ui, theme, |ui| {
+                    ui.horizontal(|ui| {
+                        let mut enabled = schedule.enabled;
+                        if ui
+                            .checkbox(&mut enabled, "")
+                            .on_hover_text(t!("schedules-enabled-hint"))
+                            .changed()
+                        {
+                            action = Some(ScheduleAction::SetEnabled(schedule.id, enabled));
+                        }
+                        ui.label(egui::RichText::new(&schedule.name).size(16.0).strong());
+                        ui.weak(schedule.cadence.label());
+
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui
+                                .add(
+                                    FluxButton::new(t!("schedules-delete"))
+                                        .danger()
+                                        .icon(regular::TRASH),
+                                )
+                                .clicked()
+                            {
+                                action = Some(ScheduleAction::Remove(schedule.id));
+                            }
+                            if ui
+                                .add_enabled(
+                                    !running,
+                                    FluxButton::new(t!("schedules-run-now")).icon(regular::PLAY),
+                                )
+                                .clicked()
+                            {
+                                action = Some(ScheduleAction::RunNow(schedule.id));
+                            }
+                        });
+                    });
+
+                    ui.add_space(6.0);
+
+                    egui::Grid::new(("schedule_details", schedule.id))
+                        .num_columns(2)
+                        .spacing([20.0, 4.0])
+                        .show(ui, |ui| {
+                            ui.label(t!("schedules-source"));
+                            ui.label(schedule.source_dir.display().to_string());
+                            ui.end_row();
+
+                            ui.label(t!("schedules-target"));
+                            ui.label(schedule.target_archive.display().to_string());
+                            ui.end_row();
+
+                            ui.label(t!("schedules-last-result"));
+                            match schedule.last_run() {
+                                Some(run) => outcome_label(ui, run, theme),
+                                None => {
+                                    ui.weak(t!("schedules-never-run"));
+                                }
+                            }
+                            ui.end_row();
+
+                            ui.label(t!("schedules-next-run"));
+                            match schedule.next_run() {
+                                _ if running => ui.weak(t!("schedules-running-now")),
+                                Some(next) if next <= Utc::now() => ui.label(t!("schedules-due")),
+                                Some(next) => ui.label(format_time(next)),
+                                None => ui.weak(t!("schedules-disabled")),
+                            };
+                            ui.end_row();
+                        });
+
+                    if !schedule.history.is_empty() {
+                        egui::CollapsingHeader::new(t!(
+                            "schedules-history",
+                            count = schedule.history.len()
+                        ))
+                        .id_source(("schedule_history", schedule.id))
+                        .show(ui, |ui| {
+                            for (idx, run) in schedule.history.iter().enumerate() {
+                                egui::CollapsingHeader::new(format!(
+                                    "{} - {}",
+                                    format_time(run.started),
+                                    outcome_text(run)
+                                ))
+                                .id_source(("schedule_run", schedule.id, idx))
+                                .show(ui, |ui| {
+                                    if run.log.is_empty() {
+                                        ui.weak(t!("schedules-no-log"));
+                                    }
+                                    for line in &run.log {
+                                        ui.monospace(line);
+                                    }
+                                });
+                            }
+                        });
+                    }
+                });
+
+                ui.add_space(10.0);
+            }
+        });
+
+    action
+}
+
+fn path_label(ui: &mut egui::Ui, path: Option<&PathBuf>, placeholder: &str) {
+    match path {
+        Some(path) => ui.label(path.display().to_string()),
+        None => ui.weak(placeholder),
+    };
+}
+
+fn outcome_label(ui: &mut egui::Ui, run: &RunRecord, theme: &FluxTheme) {
+    let (icon, color) = match run.outcome {
+        RunOutcome::Running => (regular::SPINNER, theme.colors.text),
+        RunOutcome::Success => (regular::CHECK_CIRCLE, theme.colors.success),
+        RunOutcome::Failed(_) => (regular::X_CIRCLE, theme.colors.error),
+        RunOutcome::Cancelled => (regular::MINUS_CIRCLE, theme.colors.text_weak),
+    };
+    ui.colored_label(
+        color,
+        format!(
+            "{} {} ({})",
+            icon,
+            outcome_text(run),
+            format_time(run.started)
+        ),
+    );
+}
+
+/// Outcome plus duration, e.g. "Success in 12s"
+fn outcome_text(run: &RunRecord) -> String {
+    let outcome = match &run.outcome {
+        RunOutcome::Running => return t!("schedules-outcome-running").to_string(),
+        RunOutcome::Success => t!("schedules-outcome-success").to_string(),
+        RunOutcome::Failed(e) => t!("schedules-outcome-failed", error = e),
+        RunOutcome::Cancelled => t!("schedules-outcome-cancelled").to_string(),
+    };
+    match run.finished {
+        Some(finished) => t!(
+            "schedules-outcome-duration",
+            outcome = outcome,
+            seconds = (finished - run.started).num_seconds().max(0)
+        ),
+        None => outcome,
+    }
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
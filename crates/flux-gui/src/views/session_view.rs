@@ -0,0 +1,110 @@
+//! Dialog offering to restore the previous session
+
+use crate::session::{Session, SessionDraft};
+use crate::t;
+use crate::theme::FluxTheme;
+use eframe::egui;
+use egui_phosphor::regular;
+
+/// Actions that can be triggered from the restore dialog
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionAction {
+    /// Bring back the draft and the checked tasks
+    Restore,
+    /// Forget the previous session
+    Discard,
+}
+
+/// A saved session waiting for the user's decision
+pub struct SessionRestore {
+    pub session: Session,
+    /// Which interrupted tasks to start again, one per saved task
+    pub selected: Vec<bool>,
+}
+
+impl SessionRestore {
+    /// Offer `session`, with every task checked
+    pub fn new(session: Session) -> Self {
+        let selected = vec![true; session.tasks.len()];
+        Self { session, selected }
+    }
+}
+
+/// Draw the restore dialog
+pub fn draw_session_dialog(
+    ctx: &egui::Context,
+    restore: &mut SessionRestore,
+    theme: &FluxTheme,
+) -> Option<SessionAction> {
+    let mut action = None;
+
+    egui::Window::new(format!(
+        "{} {}",
+        regular::CLOCK_COUNTER_CLOCKWISE,
+        t!("session-title")
+    ))
+    .id(egui::Id::new("session_restore"))
+    .collapsible(false)
+    .resizable(false)
+    .default_width(420.0)
+    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+    .show(ctx, |ui| {
+        ui.label(t!("session-intro"));
+        ui.add_space(8.0);
+
+        if let Some(draft) = &restore.session.draft {
+            ui.label(egui::RichText::new(t!("session-draft")).strong());
+            ui.label(draft_summary(draft));
+            ui.add_space(8.0);
+        }
+
+        if !restore.session.tasks.is_empty() {
+            ui.label(egui::RichText::new(t!("session-tasks")).strong());
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    for (task, selected) in restore
+                        .session
+                        .tasks
+                        .iter()
+                        .zip(restore.selected.iter_mut())
+                    {
+                        ui.checkbox(selected, task.describe());
+                    }
+                });
+            ui.colored_label(theme.colors.text_weak, t!("session-restart-hint"));
+            ui.add_space(8.0);
+        }
+
+        ui.separator();
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui.button(t!("session-restore")).clicked() {
+                action = Some(SessionAction::Restore);
+            }
+            if ui.button(t!("session-discard")).clicked() {
+                action = Some(SessionAction::Discard);
+            }
+        });
+    });
+
+    action
+}
+
+/// One line describing the unstarted job
+fn draft_summary(draft: &SessionDraft) -> String {
+    match draft {
+        SessionDraft::Pack { recipe, .. } => {
+            t!("session-draft-pack", count = recipe.inputs.len())
+        }
+        SessionDraft::Extract { archives, .. } => {
+            t!("session-draft-extract", count = archives.len())
+        }
+        SessionDraft::Sync { source_dir, .. } => t!(
+            "session-draft-sync",
+            source = source_dir
+                .as_ref()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_default()
+        ),
+    }
+}
@@ -0,0 +1,568 @@
+//! Settings view for application-wide defaults
+
+use crate::components::FluxButton;
+use crate::i18n::Language;
+use crate::layout::Card;
+use crate::log_store::log_dir;
+use crate::queue::MAX_WORKERS;
+use crate::settings::{CloudProfile, Settings, PACK_FORMATS};
+use crate::shortcuts::{Command, Shortcut, Shortcuts};
+use crate::t;
+use crate::theme::{Appearance, Density, FluxTheme, ACCENT_PRESETS, FONT_SCALE_RANGE};
+use crate::views::packing_view_modern::draw_resource_limits;
+use eframe::egui;
+use egui_phosphor::regular;
+use flux_core::shell_integration::ShellAction;
+
+/// Actions that can be triggered from the settings view
+#[derive(Debug, Clone, PartialEq)]
+pub enum SettingsAction {
+    /// Switch between the light and dark theme
+    SetDarkMode(bool),
+    /// Add an empty cloud profile
+    AddCloudProfile,
+    /// Remove the cloud profile at the given index
+    RemoveCloudProfile(usize),
+    /// Restore every setting to its default
+    ResetDefaults,
+    /// Add (`true`) or remove the file manager context menu entries
+    SetShellIntegration(bool),
+    /// Switch the interface language
+    SetLanguage(Language),
+    /// Start recording a new shortcut for a command, or stop with `None`
+    RecordShortcut(Option<Command>),
+    /// Give a command a new shortcut, or remove it with `None`
+    SetShortcut(Command, Option<Shortcut>),
+    /// Restore the default shortcuts
+    ResetShortcuts,
+}
+
+/// Draw the settings view
+///
+/// Simple fields are edited in place; changes that affect other parts of the
+/// application are returned as an action.
+pub fn draw_settings_view(
+    ctx: &egui::Context,
+    ui: &mut egui::Ui,
+    settings: &mut Settings,
+    shell_integrated: bool,
+    recording: Option<Command>,
+    theme: &FluxTheme,
+) -> Option<SettingsAction> {
+    let mut action = None;
+
+    ui.horizontal(|ui| {
+        ui.heading(t!("settings-title"));
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .add(
+                    FluxButton::new(t!("settings-reset"))
+                        .ghost()
+                        .icon(regular::ARROW_COUNTER_CLOCKWISE),
+                )
+                .clicked()
+            {
+                action = Some(SettingsAction::ResetDefaults);
+            }
+        });
+    });
+
+    ui.add_space(20.0);
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        Card::show(ui, theme, |ui| {
+            ui.vertical(|ui| {
+                ui.label(
+                    egui::RichText::new(t!("settings-compression"))
+                        .size(16.0)
+                        .strong(),
+                );
+                ui.add_space(10.0);
+
+                egui::Grid::new("compression_settings")
+                    .num_columns(2)
+                    .spacing([20.0, 10.0])
+                    .show(ui, |ui| {
+                        ui.label(t!("settings-default-format"));
+                        egui::ComboBox::from_id_source("default_format")
+                            .selected_text(format_label(&settings.default_format))
+                            .show_ui(ui, |ui| {
+                                for &(value, label, desc) in PACK_FORMATS {
+                                    ui.selectable_value(
+                                        &mut settings.default_format,
+                                        value.to_string(),
+                                        crate::i18n::tr(label),
+                                    )
+                                    .on_hover_text(crate::i18n::tr(desc));
+                                }
+                            });
+                        ui.end_row();
+
+                        ui.label(t!("settings-compression-level"));
+                        ui.horizontal(|ui| {
+                            let mut automatic = settings.compression_level.is_none();
+                            if ui
+                                .checkbox(&mut automatic, t!("settings-automatic"))
+                                .changed()
+                            {
+                                settings.compression_level = if automatic { None } else { Some(6) };
+                            }
+                            if let Some(level) = &mut settings.compression_level {
+                                let max = Settings::max_level(&settings.default_format);
+                                ui.add(egui::Slider::new(level, 1..=max));
+                            }
+                        });
+                        ui.end_row();
+
+                        ui.label(t!("settings-threads"));
+                        ui.horizontal(|ui| {
+                            let mut automatic = settings.threads.is_none();
+                            if ui
+                                .checkbox(&mut automatic, t!("settings-all-cores"))
+                                .changed()
+                            {
+                                settings.threads = if automatic { None } else { Some(1) };
+                            }
+                            if let Some(threads) = &mut settings.threads {
+                                let max = std::thread::available_parallelism()
+                                    .map(|n| n.get())
+                                    .unwrap_or(1)
+                                    .max(*threads);
+                                ui.add(egui::Slider::new(threads, 1..=max));
+                            }
+                        });
+                        ui.end_row();
+
+                        draw_resource_limits(ui, &mut settings.limits);
+
+                        ui.label(t!("settings-concurrent-tasks"));
+                        ui.add(egui::Slider::new(
+                            &mut settings.max_concurrent_tasks,
+                            1..=MAX_WORKERS,
+                        ))
+                        .on_hover_text(t!("settings-concurrent-tasks-hint"));
+                        ui.end_row();
+                    });
+            });
+        });
+
+        ui.add_space(20.0);
+
+        Card::show(ui, theme, |ui| {
+            ui.vertical(|ui| {
+                ui.label(
+                    egui::RichText::new(t!("settings-general"))
+                        .size(16.0)
+                        .strong(),
+                );
+                ui.add_space(10.0);
+
+                ui.horizontal(|ui| {
+                    ui.label(t!("settings-language"));
+                    ui.add_space(10.0);
+                    egui::ComboBox::from_id_source("language")
+                        .selected_text(settings.language.native_name())
+                        .show_ui(ui, |ui| {
+                            for language in Language::ALL {
+                                if ui
+                                    .selectable_label(
+                                        settings.language == language,
+                                        language.native_name(),
+                                    )
+                                    .clicked()
+                                {
+                                    action = Some(SettingsAction::SetLanguage(language));
+                                }
+                            }
+                        });
+                });
+
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    ui.label(t!("settings-theme"));
+                    ui.add_space(10.0);
+                    if ui
+                        .selectable_label(
+                            !settings.dark_mode,
+                            format!("{} {}", regular::SUN, t!("settings-theme-light")),
+                        )
+                        .clicked()
+                    {
+                        action = Some(SettingsAction::SetDarkMode(false));
+                    }
+                    if ui
+                        .selectable_label(
+                            settings.dark_mode,
+                            format!("{} {}", regular::MOON, t!("settings-theme-dark")),
+                        )
+                        .clicked()
+                    {
+                        action = Some(SettingsAction::SetDarkMode(true));
+                    }
+                });
+
+                ui.add_space(5.0);
+                ui.checkbox(
+                    &mut settings.confirm_overwrite,
+                    t!("settings-confirm-overwrite"),
+                );
+                let save_logs =
+                    ui.checkbox(&mut settings.save_task_logs, t!("settings-save-task-logs"));
+                if let Some(dir) = log_dir() {
+                    save_logs.on_hover_text(dir.display().to_string());
+                }
+            });
+        });
+
+        ui.add_space(20.0);
+
+        Card::show(ui, theme, |ui| {
+            ui.vertical(|ui| {
+                draw_appearance(ui, &mut settings.appearance, theme);
+            });
+        });
+
+        ui.add_space(20.0);
+
+        Card::show(ui, theme, |ui| {
+            ui.vertical(|ui| {
+                if let Some(shortcut_action) =
+                    draw_shortcuts(ctx, ui, &settings.shortcuts, recording)
+                {
+                    action = Some(shortcut_action);
+                }
+            });
+        });
+
+        ui.add_space(20.0);
+
+        Card::show(ui, theme, |ui| {
+            ui.vertical(|ui| {
+                ui.label(
+                    egui::RichText::new(t!("settings-integration"))
+                        .size(16.0)
+                        .strong(),
+                );
+                ui.add_space(10.0);
+
+                ui.label(t!(
+                    "settings-integration-description",
+                    extract = ShellAction::Extract.label(),
+                    compress = ShellAction::Compress.label()
+                ));
+                ui.add_space(5.0);
+                ui.horizontal(|ui| {
+                    if shell_integrated {
+                        ui.label(
+                            egui::RichText::new(format!(
+                                "{} {}",
+                                regular::CHECK_CIRCLE,
+                                t!("settings-integration-installed")
+                            ))
+                            .color(theme.colors.success),
+                        );
+                        if ui
+                            .add(
+                                FluxButton::new(t!("settings-integration-remove"))
+                                    .ghost()
+                                    .icon(regular::TRASH),
+                            )
+                            .clicked()
+                        {
+                            action = Some(SettingsAction::SetShellIntegration(false));
+                        }
+                    } else if ui
+                        .add(FluxButton::new(t!("settings-integration-add")).icon(regular::PLUS))
+                        .clicked()
+                    {
+                        action = Some(SettingsAction::SetShellIntegration(true));
+                    }
+                });
+            });
+        });
+
+        ui.add_space(20.0);
+
+        Card::show(ui, theme, |ui| {
+            ui.vertical(|ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        egui::RichText::new(t!("settings-cloud-profiles"))
+                            .size(16.0)
+                            .strong(),
+                    );
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui
+                            .add(
+                                FluxButton::new(t!("settings-add-profile"))
+                                    .ghost()
+                                    .icon(regular::PLUS),
+                            )
+                            .clicked()
+                        {
+                            action = Some(SettingsAction::AddCloudProfile);
+                        }
+                    });
+                });
+                ui.add_space(10.0);
+
+                if settings.cloud_profiles.is_empty() {
+                    ui.weak(t!("settings-no-profiles"));
+                }
+
+                for (index, profile) in settings.cloud_profiles.iter_mut().enumerate() {
+                    if draw_cloud_profile(ui, index, profile) {
+                        action = Some(SettingsAction::RemoveCloudProfile(index));
+                    }
+                    ui.add_space(5.0);
+                }
+            });
+        });
+    });
+
+    action
+}
+
+/// Draw the shortcut of every command with buttons to change them
+///
+/// While a command is being recorded, the next key pressed with its
+/// modifiers becomes its shortcut; Escape stops recording.
+/// Accent color, text size and density, applied as they are edited
+fn draw_appearance(ui: &mut egui::Ui, appearance: &mut Appearance, theme: &FluxTheme) {
+    ui.horizontal(|ui| {
+        ui.label(
+            egui::RichText::new(t!("settings-appearance"))
+                .size(16.0)
+                .strong(),
+        );
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .add_enabled(
+                    *appearance != Appearance::default(),
+                    FluxButton::new(t!("settings-appearance-reset"))
+                        .ghost()
+                        .icon(regular::ARROW_COUNTER_CLOCKWISE),
+                )
+                .clicked()
+            {
+                *appearance = Appearance::default();
+            }
+        });
+    });
+    ui.add_space(10.0);
+
+    egui::Grid::new("settings_appearance")
+        .num_columns(2)
+        .spacing([20.0, 10.0])
+        .show(ui, |ui| {
+            ui.label(t!("settings-accent"));
+            ui.horizontal(|ui| {
+                let selected = appearance.accent;
+                if ui
+                    .selectable_label(selected.is_none(), t!("settings-accent-default"))
+                    .clicked()
+                {
+                    appearance.accent = None;
+                }
+                for preset in ACCENT_PRESETS {
+                    let [r, g, b] = preset;
+                    let (rect, response) =
+                        ui.allocate_exact_size(egui::vec2(20.0, 20.0), egui::Sense::click());
+                    ui.painter().circle_filled(
+                        rect.center(),
+                        9.0,
+                        egui::Color32::from_rgb(r, g, b),
+                    );
+                    if selected == Some(preset) {
+                        ui.painter().circle_stroke(
+                            rect.center(),
+                            10.0,
+                            egui::Stroke::new(2.0, theme.colors.text),
+                        );
+                    }
+                    if response.clicked() {
+                        appearance.accent = Some(preset);
+                    }
+                }
+
+                // Starts from the current accent so small tweaks are easy
+                let primary = theme.colors.primary;
+                let mut custom =
+                    appearance
+                        .accent
+                        .unwrap_or([primary.r(), primary.g(), primary.b()]);
+                if egui::color_picker::color_edit_button_srgb(ui, &mut custom)
+                    .on_hover_text(t!("settings-accent-custom"))
+                    .changed()
+                {
+                    appearance.accent = Some(custom);
+                }
+            });
+            ui.end_row();
+
+            ui.label(t!("settings-font-size"));
+            ui.add(
+                egui::Slider::new(&mut appearance.font_scale, FONT_SCALE_RANGE)
+                    .step_by(0.05)
+                    .custom_formatter(|value, _| format!("{:.0}%", value * 100.0))
+                    .custom_parser(|text| {
+                        text.trim_end_matches('%')
+                            .trim()
+                            .parse::<f64>()
+                            .ok()
+                            .map(|percent| percent / 100.0)
+                    }),
+            );
+            ui.end_row();
+
+            ui.label(t!("settings-density"));
+            ui.horizontal(|ui| {
+                for density in Density::ALL {
+                    let label = match density {
+                        Density::Compact => t!("settings-density-compact"),
+                        Density::Comfortable => t!("settings-density-comfortable"),
+                        Density::Spacious => t!("settings-density-spacious"),
+                    };
+                    ui.selectable_value(&mut appearance.density, density, label);
+                }
+            });
+            ui.end_row();
+        });
+}
+
+fn draw_shortcuts(
+    ctx: &egui::Context,
+    ui: &mut egui::Ui,
+    shortcuts: &Shortcuts,
+    recording: Option<Command>,
+) -> Option<SettingsAction> {
+    let mut action = None;
+
+    if let Some(command) = recording {
+        if ctx.input(|i| i.key_pressed(egui::Key::Escape)) {
+            action = Some(SettingsAction::RecordShortcut(None));
+        } else if let Some(shortcut) = Shortcuts::capture(ctx) {
+            action = Some(SettingsAction::SetShortcut(command, Some(shortcut)));
+        }
+    }
+
+    ui.horizontal(|ui| {
+        ui.label(
+            egui::RichText::new(t!("settings-shortcuts"))
+                .size(16.0)
+                .strong(),
+        );
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .add_enabled(
+                    shortcuts.is_customized(),
+                    FluxButton::new(t!("settings-shortcuts-reset"))
+                        .ghost()
+                        .icon(regular::ARROW_COUNTER_CLOCKWISE),
+                )
+                .clicked()
+            {
+                action = Some(SettingsAction::ResetShortcuts);
+            }
+        });
+    });
+    ui.add_space(5.0);
+    ui.weak(t!("settings-shortcuts-description"));
+    ui.add_space(10.0);
+
+    egui::Grid::new("shortcut_settings")
+        .num_columns(3)
+        .spacing([20.0, 6.0])
+        .show(ui, |ui| {
+            for command in Command::ALL {
+                ui.label(command.label());
+
+                let shortcut = shortcuts.get(command);
+                let text = if recording == Some(command) {
+                    t!("settings-shortcut-press").to_string()
+                } else {
+                    shortcut.map_or_else(
+                        || t!("settings-shortcut-none").to_string(),
+                        |shortcut| shortcut.display(ctx),
+                    )
+                };
+                let button = ui
+                    .add(
+                        egui::Button::new(egui::RichText::new(text).monospace())
+                            .selected(recording == Some(command))
+                            .min_size(egui::vec2(140.0, 0.0)),
+                    )
+                    .on_hover_text(t!("settings-shortcut-change"));
+                if button.clicked() {
+                    action = Some(SettingsAction::RecordShortcut(
+                        (recording != Some(command)).then_some(command),
+                    ));
+                }
+
+                if ui
+                    .add_enabled(shortcut.is_some(), egui::Button::new(regular::X))
+                    .on_hover_text(t!("settings-shortcut-remove"))
+                    .clicked()
+                {
+                    action = Some(SettingsAction::SetShortcut(command, None));
+                }
+                ui.end_row();
+            }
+        });
+
+    action
+}
+
+/// Draw the editor for a single cloud profile, returning true if it should
+/// be removed
+fn draw_cloud_profile(ui: &mut egui::Ui, index: usize, profile: &mut CloudProfile) -> bool {
+    let mut remove = false;
+
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut profile.name)
+                .id_source(("cloud_name", index))
+                .desired_width(120.0)
+                .hint_text(t!("settings-profile-name")),
+        );
+        ui.add(
+            egui::TextEdit::singleline(&mut profile.url)
+                .id_source(("cloud_url", index))
+                .desired_width(240.0)
+                .hint_text("s3://bucket/prefix"),
+        );
+
+        let mut endpoint = profile.endpoint.clone().unwrap_or_default();
+        if ui
+            .add(
+                egui::TextEdit::singleline(&mut endpoint)
+                    .id_source(("cloud_endpoint", index))
+                    .desired_width(180.0)
+                    .hint_text(t!("settings-profile-endpoint")),
+            )
+            .changed()
+        {
+            profile.endpoint = (!endpoint.trim().is_empty()).then_some(endpoint);
+        }
+
+        if ui
+            .button(regular::TRASH)
+            .on_hover_text(t!("settings-remove-profile"))
+            .clicked()
+        {
+            remove = true;
+        }
+    });
+
+    remove
+}
+
+/// Display label for a pack format value
+fn format_label(format: &str) -> &str {
+    PACK_FORMATS
+        .iter()
+        .find(|(value, ..)| *value == format)
+        .map(|(_, label, _)| crate::i18n::tr(label))
+        .unwrap_or(format)
+}
@@ -0,0 +1,342 @@
+//! Snapshots of a sync target, shown below the sync view
+//!
+//! Each sync from the GUI records its archive and manifest as a numbered
+//! generation next to the target; see [`flux_core::snapshot`]. The list is
+//! read on a background thread, a snapshot's files are browsed as a tree
+//! built from its manifest, and the ones ticked are restored in the
+//! background too.
+
+use super::browser_view::{format_size, format_timestamp};
+use crate::t;
+use crossbeam_channel::Receiver;
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+use flux_core::manifest::Manifest;
+use flux_core::snapshot::{Snapshot, SnapshotHistory};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+/// Height of one row in the snapshot table
+const ROW_HEIGHT: f32 = 20.0;
+
+/// Files of one snapshot, by the directory holding them
+struct SnapshotTree {
+    sequence: u32,
+    /// Children of each directory, the top one being the empty path, with
+    /// whether they are directories and their sizes
+    children: BTreeMap<PathBuf, Vec<(PathBuf, bool, u64)>>,
+    /// Paths ticked for restoring; a directory stands for everything in it
+    selected: BTreeSet<PathBuf>,
+}
+
+impl SnapshotTree {
+    fn new(sequence: u32, manifest: &Manifest) -> Self {
+        let mut nodes: BTreeMap<PathBuf, (bool, u64)> = BTreeMap::new();
+        for (path, entry) in &manifest.files {
+            nodes.insert(path.clone(), (entry.is_dir, entry.size));
+            // Parents left out of the manifest still show as folders
+            for ancestor in path.ancestors().skip(1) {
+                if ancestor.as_os_str().is_empty() {
+                    break;
+                }
+                nodes.entry(ancestor.to_path_buf()).or_insert((true, 0));
+            }
+        }
+
+        let mut children: BTreeMap<PathBuf, Vec<(PathBuf, bool, u64)>> = BTreeMap::new();
+        for (path, (is_dir, size)) in nodes {
+            let parent = path.parent().unwrap_or(Path::new("")).to_path_buf();
+            children
+                .entry(parent)
+                .or_default()
+                .push((path, is_dir, size));
+        }
+        // Folders first, each group by name as the map already sorted them
+        for entries in children.values_mut() {
+            entries.sort_by_key(|(_, is_dir, _)| !is_dir);
+        }
+
+        Self {
+            sequence,
+            children,
+            selected: BTreeSet::new(),
+        }
+    }
+}
+
+/// Snapshots recorded for a sync target, and the one being browsed
+pub struct SnapshotBrowser {
+    history: Option<SnapshotHistory>,
+    snapshots: Option<Result<Vec<Snapshot>, String>>,
+    pending: Option<Receiver<Result<Vec<Snapshot>, String>>>,
+    tree: Option<SnapshotTree>,
+    restoring: Option<Receiver<Result<String, String>>>,
+}
+
+impl SnapshotBrowser {
+    /// Start reading the snapshots recorded for `target`
+    pub fn start(target: &Path) -> Self {
+        let history = SnapshotHistory::new(target).ok();
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        if let Some(history) = history.clone() {
+            std::thread::spawn(move || {
+                let _ = sender.send(history.snapshots(None).map_err(|e| e.to_string()));
+            });
+        }
+
+        Self {
+            history,
+            snapshots: None,
+            pending: Some(receiver),
+            tree: None,
+            restoring: None,
+        }
+    }
+
+    /// Pick up the snapshot list once read, and return how a finished
+    /// restore went, as a summary or an error
+    pub fn poll(&mut self) -> Option<Result<String, String>> {
+        if let Some(receiver) = &self.pending {
+            match receiver.try_recv() {
+                Ok(result) => {
+                    self.snapshots = Some(result);
+                    self.pending = None;
+                }
+                Err(crossbeam_channel::TryRecvError::Empty) => {}
+                Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                    // No history for a target without an archive extension
+                    self.snapshots = Some(Ok(Vec::new()));
+                    self.pending = None;
+                }
+            }
+        }
+
+        let receiver = self.restoring.as_ref()?;
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(crossbeam_channel::TryRecvError::Empty) => return None,
+            Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                Err(t!("snapshot-restore-stopped").to_string())
+            }
+        };
+        self.restoring = None;
+        Some(result)
+    }
+
+    /// Show the files of snapshot `sequence`
+    pub fn open(&mut self, sequence: u32) -> Result<(), String> {
+        let history = self
+            .history
+            .as_ref()
+            .ok_or_else(|| t!("snapshot-none").to_string())?;
+        let manifest = Manifest::load(history.paths(sequence).1).map_err(|e| e.to_string())?;
+        self.tree = Some(SnapshotTree::new(sequence, &manifest));
+        Ok(())
+    }
+
+    /// Stop showing the files of a snapshot
+    pub fn close(&mut self) {
+        self.tree = None;
+    }
+
+    /// Restore `paths` of snapshot `sequence` into `output_dir` in the
+    /// background; see [`SnapshotBrowser::poll`]
+    pub fn restore(&mut self, sequence: u32, paths: Vec<PathBuf>, output_dir: PathBuf) {
+        let Some(history) = self.history.clone() else {
+            return;
+        };
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        std::thread::spawn(move || {
+            let result = history
+                .restore(sequence, &paths, &output_dir, None)
+                .map(|outcome| outcome.to_string())
+                .map_err(|e| e.to_string());
+            let _ = sender.send(result);
+        });
+        self.restoring = Some(receiver);
+    }
+
+    /// Whether a restore is still running
+    pub fn is_restoring(&self) -> bool {
+        self.restoring.is_some()
+    }
+}
+
+/// Actions that can be triggered from the snapshot list
+#[derive(Debug, Clone)]
+pub enum SnapshotAction {
+    /// Browse the files of a snapshot
+    Open(u32),
+    /// Close the file tree
+    Close,
+    /// Restore paths of a snapshot, everything if there are none, into a
+    /// folder still to be chosen
+    Restore { sequence: u32, paths: Vec<PathBuf> },
+}
+
+/// Draw the snapshots of the sync target and the files of the open one
+pub fn draw_snapshots(ui: &mut egui::Ui, browser: &mut SnapshotBrowser) -> Option<SnapshotAction> {
+    let mut action = None;
+
+    ui.heading(format!("🕘 {}", t!("snapshot-title")));
+    let snapshots = match &browser.snapshots {
+        None => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(t!("snapshot-loading"));
+            });
+            return None;
+        }
+        Some(Err(e)) => {
+            ui.colored_label(
+                egui::Color32::from_rgb(220, 80, 80),
+                t!("snapshot-load-failed", error = e),
+            );
+            return None;
+        }
+        Some(Ok(snapshots)) => snapshots,
+    };
+    if snapshots.is_empty() {
+        ui.label(t!("snapshot-none"));
+        return None;
+    }
+
+    let open = browser.tree.as_ref().map(|tree| tree.sequence);
+    // Its own id, so it does not clash with the preview table
+    ui.push_id("snapshots", |ui| {
+        TableBuilder::new(ui)
+            .striped(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::auto().at_least(40.0)) // Sequence
+            .column(Column::auto().at_least(130.0)) // Date
+            .column(Column::auto().at_least(70.0)) // Changes
+            .column(Column::auto().at_least(70.0)) // Files
+            .column(Column::auto().at_least(80.0)) // Size
+            .column(Column::remainder()) // Browse
+            .min_scrolled_height(0.0)
+            .max_scroll_height(180.0)
+            .header(ROW_HEIGHT, |mut header| {
+                for title in [
+                    "#",
+                    t!("snapshot-date"),
+                    t!("snapshot-changes"),
+                    t!("snapshot-files"),
+                    t!("table-size"),
+                    "",
+                ] {
+                    header.col(|ui| {
+                        ui.strong(title);
+                    });
+                }
+            })
+            .body(|body| {
+                // Newest first
+                body.rows(ROW_HEIGHT, snapshots.len(), |mut row| {
+                    let snapshot = &snapshots[snapshots.len() - 1 - row.index()];
+                    row.col(|ui| {
+                        ui.label(snapshot.sequence.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(format_timestamp(snapshot.created));
+                    });
+                    row.col(|ui| {
+                        ui.label(snapshot.changes.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.label(snapshot.file_count.to_string());
+                    });
+                    row.col(|ui| {
+                        ui.weak(format_size(snapshot.archive_size))
+                            .on_hover_text(t!(
+                                "snapshot-size-hint",
+                                total = format_size(snapshot.total_size)
+                            ));
+                    });
+                    row.col(|ui| {
+                        if open != Some(snapshot.sequence)
+                            && ui.small_button(t!("snapshot-browse")).clicked()
+                        {
+                            action = Some(SnapshotAction::Open(snapshot.sequence));
+                        }
+                    });
+                });
+            });
+    });
+
+    let restoring = browser.is_restoring();
+    if let Some(tree) = &mut browser.tree {
+        ui.add_space(10.0);
+        ui.horizontal(|ui| {
+            ui.strong(t!("snapshot-files-of", sequence = tree.sequence));
+            if ui.small_button("✖").clicked() {
+                action = Some(SnapshotAction::Close);
+            }
+        });
+        egui::ScrollArea::vertical()
+            .id_source("snapshot_tree")
+            .max_height(260.0)
+            .show(ui, |ui| draw_directory(ui, tree, Path::new("")));
+
+        ui.horizontal(|ui| {
+            let label = if tree.selected.is_empty() {
+                t!("snapshot-restore-all").to_string()
+            } else {
+                t!("snapshot-restore-selected", count = tree.selected.len())
+            };
+            if ui
+                .add_enabled(!restoring, egui::Button::new(format!("↩ {}", label)))
+                .clicked()
+            {
+                action = Some(SnapshotAction::Restore {
+                    sequence: tree.sequence,
+                    paths: tree.selected.iter().cloned().collect(),
+                });
+            }
+            if restoring {
+                ui.spinner();
+                ui.label(t!("snapshot-restoring"));
+            }
+        });
+    }
+
+    action
+}
+
+/// Draw the entries of `dir`, folders collapsed until opened
+fn draw_directory(ui: &mut egui::Ui, tree: &mut SnapshotTree, dir: &Path) {
+    let Some(entries) = tree.children.get(dir).cloned() else {
+        return;
+    };
+    for (path, is_dir, size) in entries {
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |n| n.to_string_lossy().into_owned(),
+        );
+        let mut selected = tree.selected.contains(&path);
+        if is_dir {
+            let id = ui.make_persistent_id(("snapshot", tree.sequence, &path));
+            egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, false)
+                .show_header(ui, |ui| {
+                    if ui.checkbox(&mut selected, format!("📁 {}", name)).changed() {
+                        toggle(&mut tree.selected, &path, selected);
+                    }
+                })
+                .body(|ui| draw_directory(ui, tree, &path));
+        } else {
+            ui.horizontal(|ui| {
+                if ui.checkbox(&mut selected, format!("📄 {}", name)).changed() {
+                    toggle(&mut tree.selected, &path, selected);
+                }
+                ui.weak(format_size(size));
+            });
+        }
+    }
+}
+
+fn toggle(selected: &mut BTreeSet<PathBuf>, path: &Path, on: bool) {
+    if on {
+        selected.insert(path.to_path_buf());
+    } else {
+        selected.remove(path);
+    }
+}
@@ -1,7 +1,128 @@
 //! Sync view for incremental backups
+//!
+//! With a manifest from an earlier backup, the view can preview what the
+//! next sync would pick up. [`SyncPreview`] scans the source directory on a
+//! background thread, hashing every file as the sync itself would, and
+//! compares it with the manifest.
 
+use super::browser_view::format_size;
+use crate::t;
+use crossbeam_channel::Receiver;
 use eframe::egui;
-use std::path::PathBuf;
+use egui_extras::{Column, TableBuilder};
+use flux_core::archive::PackFilter;
+use flux_core::manifest::Manifest;
+use flux_core::progress::NoProgress;
+use std::path::{Path, PathBuf};
+
+/// Height of one row in the preview table
+const ROW_HEIGHT: f32 = 20.0;
+
+/// How a file changed since the last backup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Change {
+    Added,
+    Modified,
+    Deleted,
+}
+
+/// One row of the preview
+#[derive(Debug, Clone)]
+pub struct ChangedFile {
+    pub change: Change,
+    pub path: PathBuf,
+    /// Current size, or the size in the manifest for deleted files
+    pub size: u64,
+}
+
+/// What the next sync of a directory would back up, worked out in the
+/// background
+pub struct SyncPreview {
+    changes: Option<Result<Vec<ChangedFile>, String>>,
+    pending: Option<Receiver<Result<Vec<ChangedFile>, String>>>,
+}
+
+impl SyncPreview {
+    /// Start comparing `source_dir`, leaving out what `filter` excludes,
+    /// with `manifest`
+    pub fn start(source_dir: PathBuf, manifest: PathBuf, filter: PackFilter) -> Self {
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        std::thread::spawn(move || {
+            let result = changed_files(&source_dir, &manifest, &filter);
+            let _ = sender.send(result.map_err(|e| e.to_string()));
+        });
+
+        Self {
+            changes: None,
+            pending: Some(receiver),
+        }
+    }
+
+    /// Pick up the changes once they are known
+    pub fn poll(&mut self) {
+        if let Some(receiver) = &self.pending {
+            match receiver.try_recv() {
+                Ok(result) => {
+                    self.changes = Some(result);
+                    self.pending = None;
+                }
+                Err(crossbeam_channel::TryRecvError::Empty) => {}
+                Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                    self.changes = Some(Err(t!("sync-preview-stopped").to_string()));
+                    self.pending = None;
+                }
+            }
+        }
+    }
+
+    /// Whether the source directory is still being scanned
+    pub fn is_running(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+/// Files added, modified and deleted in `source_dir` since `manifest`,
+/// sorted by change and then path
+///
+/// Directories whose only change is a newer modification time, which they
+/// get whenever a file inside them changes, are left out.
+fn changed_files(
+    source_dir: &Path,
+    manifest: &Path,
+    filter: &PackFilter,
+) -> flux_core::Result<Vec<ChangedFile>> {
+    let previous = Manifest::load(manifest)?;
+    let current = Manifest::scan(source_dir, filter, Some(&previous), &NoProgress)?;
+    let diff = previous.diff(&current);
+
+    let size =
+        |manifest: &Manifest, path: &Path| manifest.files.get(path).map_or(0, |entry| entry.size);
+    let is_dir = |path: &PathBuf| current.files.get(path).is_some_and(|entry| entry.is_dir);
+
+    let mut changes: Vec<ChangedFile> = diff
+        .added
+        .into_iter()
+        .map(|path| (Change::Added, path))
+        .chain(
+            diff.modified
+                .into_iter()
+                .filter(|path| !is_dir(path))
+                .map(|path| (Change::Modified, path)),
+        )
+        .map(|(change, path)| ChangedFile {
+            change,
+            size: size(&current, &path),
+            path,
+        })
+        .chain(diff.deleted.into_iter().map(|path| ChangedFile {
+            change: Change::Deleted,
+            size: size(&previous, &path),
+            path,
+        }))
+        .collect();
+    changes.sort_by(|a, b| (a.change, &a.path).cmp(&(b.change, &b.path)));
+    Ok(changes)
+}
 
 /// Draw the sync/incremental backup view
 pub fn draw_sync_view(
@@ -10,27 +131,27 @@ pub fn draw_sync_view(
     source_dir: &Option<PathBuf>,
     target_archive: &Option<PathBuf>,
     existing_manifest: &Option<PathBuf>,
-    is_busy: bool,
+    preview: Option<&SyncPreview>,
 ) -> Option<SyncAction> {
     let mut action = None;
 
-    ui.heading("🔄 Incremental Backup / Sync");
+    ui.heading(format!("🔄 {}", t!("sync-title")));
     ui.separator();
     ui.add_space(10.0);
 
     // Explanation
-    ui.label("Create incremental backups that only include changed files since the last backup.");
+    ui.label(t!("sync-description"));
     ui.add_space(10.0);
 
     // Source directory selection
     ui.horizontal(|ui| {
-        ui.label("Source Directory:");
+        ui.label(t!("sync-source-directory"));
         ui.add_space(10.0);
 
         let source_text = source_dir
             .as_ref()
             .and_then(|p| p.to_str())
-            .unwrap_or("No directory selected");
+            .unwrap_or(t!("common-no-directory-selected"));
 
         ui.add(
             egui::TextEdit::singleline(&mut source_text.to_string())
@@ -38,10 +159,7 @@ pub fn draw_sync_view(
                 .interactive(false),
         );
 
-        if ui
-            .add_enabled(!is_busy, egui::Button::new("Browse..."))
-            .clicked()
-        {
+        if ui.button(t!("common-browse-ellipsis")).clicked() {
             action = Some(SyncAction::SelectSourceDir);
         }
     });
@@ -50,13 +168,13 @@ pub fn draw_sync_view(
 
     // Target archive selection
     ui.horizontal(|ui| {
-        ui.label("Target Archive:");
+        ui.label(t!("sync-target-archive"));
         ui.add_space(10.0);
 
         let target_text = target_archive
             .as_ref()
             .and_then(|p| p.to_str())
-            .unwrap_or("No archive selected");
+            .unwrap_or(t!("common-no-archive-selected"));
 
         ui.add(
             egui::TextEdit::singleline(&mut target_text.to_string())
@@ -64,10 +182,7 @@ pub fn draw_sync_view(
                 .interactive(false),
         );
 
-        if ui
-            .add_enabled(!is_busy, egui::Button::new("Browse..."))
-            .clicked()
-        {
+        if ui.button(t!("common-browse-ellipsis")).clicked() {
             action = Some(SyncAction::SelectTargetArchive);
         }
     });
@@ -79,7 +194,7 @@ pub fn draw_sync_view(
         ui.horizontal(|ui| {
             ui.colored_label(
                 egui::Color32::from_rgb(90, 198, 90),
-                "✓ Existing manifest found:",
+                format!("✓ {}", t!("sync-manifest-found")),
             );
             ui.weak(
                 manifest_path
@@ -88,29 +203,29 @@ pub fn draw_sync_view(
                     .unwrap_or("manifest.json"),
             );
         });
-        ui.label("This will be an incremental sync - only changed files will be backed up.");
+        ui.label(t!("sync-incremental-note"));
     } else if target_archive.is_some() {
         ui.horizontal(|ui| {
             ui.colored_label(
                 egui::Color32::from_rgb(255, 200, 100),
-                "⚠ No existing manifest found",
+                format!("⚠ {}", t!("sync-no-manifest")),
             );
         });
-        ui.label("This will be a full backup - all files will be included.");
+        ui.label(t!("sync-full-note"));
     }
 
     ui.add_space(20.0);
 
     // Advanced options (collapsible)
-    ui.collapsing("Advanced Options", |ui| {
-        ui.checkbox(&mut true, "Follow symbolic links");
-        ui.checkbox(&mut true, "Include file permissions");
-        ui.checkbox(&mut true, "Generate deleted files list");
+    ui.collapsing(t!("sync-advanced"), |ui| {
+        ui.checkbox(&mut true, t!("sync-follow-symlinks"));
+        ui.checkbox(&mut true, t!("sync-include-permissions"));
+        ui.checkbox(&mut true, t!("sync-deleted-list"));
 
         ui.add_space(10.0);
 
         ui.horizontal(|ui| {
-            ui.label("Compression level:");
+            ui.label(t!("settings-compression-level"));
             ui.add(egui::Slider::new(&mut 6, 1..=9));
         });
     });
@@ -120,12 +235,12 @@ pub fn draw_sync_view(
     // Action buttons
     ui.horizontal(|ui| {
         // Start sync button
-        let can_start = !is_busy && source_dir.is_some() && target_archive.is_some();
+        let can_start = source_dir.is_some() && target_archive.is_some();
 
         let button_text = if existing_manifest.is_some() {
-            "🔄 Start Incremental Sync"
+            format!("🔄 {}", t!("sync-start-incremental"))
         } else {
-            "📦 Start Full Backup"
+            format!("📦 {}", t!("sync-start-full"))
         };
 
         if ui
@@ -138,14 +253,20 @@ pub fn draw_sync_view(
             action = Some(SyncAction::StartSync);
         }
 
-        // Cancel/Clear button
-        let cancel_text = if is_busy { "Cancel" } else { "Clear" };
-        if ui.button(cancel_text).clicked() {
-            action = Some(if is_busy {
-                SyncAction::Cancel
-            } else {
-                SyncAction::Clear
-            });
+        if existing_manifest.is_some()
+            && ui
+                .add_enabled(
+                    source_dir.is_some() && !preview.is_some_and(SyncPreview::is_running),
+                    egui::Button::new(format!("🔍 {}", t!("sync-preview"))),
+                )
+                .on_hover_text(t!("sync-preview-hint"))
+                .clicked()
+        {
+            action = Some(SyncAction::PreviewChanges);
+        }
+
+        if ui.button(t!("common-clear")).clicked() {
+            action = Some(SyncAction::Clear);
         }
 
         ui.add_space(20.0);
@@ -153,13 +274,18 @@ pub fn draw_sync_view(
         // View manifest button (if exists)
         if existing_manifest.is_some()
             && ui
-                .add_enabled(!is_busy, egui::Button::new("📋 View Manifest"))
+                .button(format!("📋 {}", t!("sync-view-manifest")))
                 .clicked()
         {
             action = Some(SyncAction::ViewManifest);
         }
     });
 
+    if let Some(preview) = preview {
+        ui.add_space(10.0);
+        draw_preview(ui, preview);
+    }
+
     ui.add_space(20.0);
 
     // Info box
@@ -168,12 +294,16 @@ pub fn draw_sync_view(
         .inner_margin(10.0)
         .rounding(4.0)
         .show(ui, |ui| {
-            ui.label("ℹ️ How incremental backup works:");
+            ui.label(format!("ℹ️ {}", t!("sync-how-it-works")));
             ui.add_space(5.0);
-            ui.label("• First backup creates a full archive and manifest");
-            ui.label("• Subsequent backups only include changed/new files");
-            ui.label("• Each backup updates the manifest with current state");
-            ui.label("• Deleted files are tracked in a separate list");
+            for step in [
+                t!("sync-how-first"),
+                t!("sync-how-subsequent"),
+                t!("sync-how-manifest"),
+                t!("sync-how-deleted"),
+            ] {
+                ui.label(format!("• {}", step));
+            }
         });
 
     action
@@ -188,10 +318,98 @@ pub enum SyncAction {
     SelectTargetArchive,
     /// Start the sync operation
     StartSync,
+    /// Work out what the sync would back up, without running it
+    PreviewChanges,
     /// View existing manifest
     ViewManifest,
     /// Clear selections
     Clear,
-    /// Cancel operation
-    Cancel,
+}
+
+/// Draw the changes found by `preview`, or how far it got
+fn draw_preview(ui: &mut egui::Ui, preview: &SyncPreview) {
+    let changes = match &preview.changes {
+        None => {
+            ui.horizontal(|ui| {
+                ui.spinner();
+                ui.label(t!("sync-preview-running"));
+            });
+            return;
+        }
+        Some(Err(e)) => {
+            ui.colored_label(
+                egui::Color32::from_rgb(220, 80, 80),
+                t!("sync-preview-failed", error = e),
+            );
+            return;
+        }
+        Some(Ok(changes)) => changes,
+    };
+    if changes.is_empty() {
+        ui.label(t!("sync-preview-no-changes"));
+        return;
+    }
+
+    let count = |kind: Change| changes.iter().filter(|file| file.change == kind).count();
+    let to_back_up: u64 = changes
+        .iter()
+        .filter(|file| file.change != Change::Deleted)
+        .map(|file| file.size)
+        .sum();
+    ui.label(t!(
+        "sync-preview-summary",
+        added = count(Change::Added),
+        modified = count(Change::Modified),
+        deleted = count(Change::Deleted),
+        size = format_size(to_back_up)
+    ));
+    ui.add_space(4.0);
+
+    TableBuilder::new(ui)
+        .striped(true)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(Column::auto().at_least(90.0)) // Change
+        .column(Column::remainder().at_least(200.0)) // Path
+        .column(Column::auto().at_least(80.0)) // Size
+        .min_scrolled_height(0.0)
+        .max_scroll_height(280.0)
+        .header(ROW_HEIGHT, |mut header| {
+            header.col(|ui| {
+                ui.strong(t!("sync-preview-change"));
+            });
+            header.col(|ui| {
+                ui.strong(t!("table-name"));
+            });
+            header.col(|ui| {
+                ui.strong(t!("table-size"));
+            });
+        })
+        .body(|body| {
+            body.rows(ROW_HEIGHT, changes.len(), |mut row| {
+                let file = &changes[row.index()];
+                let (label, color) = match file.change {
+                    Change::Added => (
+                        t!("sync-change-added"),
+                        egui::Color32::from_rgb(90, 198, 90),
+                    ),
+                    Change::Modified => (
+                        t!("sync-change-modified"),
+                        egui::Color32::from_rgb(255, 200, 100),
+                    ),
+                    Change::Deleted => (
+                        t!("sync-change-deleted"),
+                        egui::Color32::from_rgb(220, 80, 80),
+                    ),
+                };
+                row.col(|ui| {
+                    ui.colored_label(color, label);
+                });
+                row.col(|ui| {
+                    ui.label(file.path.display().to_string());
+                });
+                row.col(|ui| {
+                    ui.weak(format_size(file.size));
+                });
+            });
+        });
 }
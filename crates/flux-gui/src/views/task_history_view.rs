@@ -0,0 +1,264 @@
+//! Finished tasks, and the log and report of each one
+//!
+//! The history list sits next to the task list in the task panel. Opening
+//! a task shows what it did to its entries, the entries that failed and the
+//! last lines it logged, with shortcuts to its output folder, its manifest
+//! and its report as JSON.
+
+use super::task_queue_view::status_label;
+use crate::queue::{TaskKind, TaskStatus};
+use crate::t;
+use crate::task::TaskId;
+use crate::task_history::{TaskHistory, TaskRecord};
+use crate::theme::FluxTheme;
+use eframe::egui;
+use egui_phosphor::regular;
+
+/// Actions that can be triggered from the history list or a task's details
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskHistoryAction {
+    /// Show the log and report of a task
+    ShowDetails(TaskId),
+    /// Close the details dialog
+    CloseDetails,
+    /// Open the folder the task wrote into
+    OpenOutput(TaskId),
+    /// Show the manifest a sync wrote in the file manager
+    RevealManifest(TaskId),
+    /// Copy the task's report as JSON
+    CopyReport(TaskId),
+    /// Forget every finished task
+    Clear,
+}
+
+/// Draw one row per finished task, newest first
+pub fn draw_task_history_view(
+    ui: &mut egui::Ui,
+    history: &TaskHistory,
+    theme: &FluxTheme,
+) -> Option<TaskHistoryAction> {
+    let mut action = None;
+
+    ui.horizontal(|ui| {
+        ui.heading(format!(
+            "{} {}",
+            regular::CLOCK_COUNTER_CLOCKWISE,
+            t!("task-history-title")
+        ));
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .add_enabled(
+                    history.finished().next().is_some(),
+                    egui::Button::new(t!("task-history-clear")),
+                )
+                .clicked()
+            {
+                action = Some(TaskHistoryAction::Clear);
+            }
+        });
+    });
+
+    ui.separator();
+
+    if history.finished().next().is_none() {
+        ui.weak(t!("task-history-empty"));
+        return action;
+    }
+
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for record in history.finished() {
+                ui.horizontal(|ui| {
+                    ui.label(egui::RichText::new(kind_icon(record.kind)).size(18.0));
+                    ui.vertical(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(&record.label).strong());
+                            let (text, color) = status_label(&record.status, theme);
+                            ui.colored_label(color, text);
+                        });
+                        let summary = match &record.report {
+                            Some(report) => report.to_string(),
+                            None => String::new(),
+                        };
+                        ui.weak(format!("{}  {}", finished_at(record), summary));
+                    });
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui
+                            .button(regular::ARTICLE)
+                            .on_hover_text(t!("task-history-details"))
+                            .clicked()
+                        {
+                            action = Some(TaskHistoryAction::ShowDetails(record.id));
+                        }
+                        if record.output_dir.is_some()
+                            && ui
+                                .button(regular::FOLDER_OPEN)
+                                .on_hover_text(t!("task-history-open-output"))
+                                .clicked()
+                        {
+                            action = Some(TaskHistoryAction::OpenOutput(record.id));
+                        }
+                    });
+                });
+                ui.separator();
+            }
+        });
+
+    action
+}
+
+/// Draw the log and report of one finished task
+pub fn draw_task_details_dialog(
+    ctx: &egui::Context,
+    record: &TaskRecord,
+    theme: &FluxTheme,
+) -> Option<TaskHistoryAction> {
+    let mut action = None;
+    let mut open = true;
+
+    egui::Window::new(format!("{} {}", kind_icon(record.kind), record.label))
+        .id(egui::Id::new("task_details"))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(520.0)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                let (text, color) = status_label(&record.status, theme);
+                ui.colored_label(color, text);
+                ui.weak(finished_at(record));
+            });
+            if let TaskStatus::Failed(error) = &record.status {
+                ui.colored_label(theme.colors.error, error);
+            }
+            ui.add_space(8.0);
+
+            egui::CollapsingHeader::new(t!("task-history-report"))
+                .default_open(true)
+                .show(ui, |ui| match &record.report {
+                    Some(report) => {
+                        egui::Grid::new("task_report_grid")
+                            .num_columns(2)
+                            .spacing([20.0, 4.0])
+                            .show(ui, |ui| {
+                                ui.label(t!("task-history-done"));
+                                ui.label(report.done.to_string());
+                                ui.end_row();
+                                ui.label(t!("task-history-skipped"));
+                                ui.label(report.skipped.to_string());
+                                ui.end_row();
+                                for (reason, count) in &report.skipped_by_reason {
+                                    ui.weak(format!("  {}", reason));
+                                    ui.weak(count.to_string());
+                                    ui.end_row();
+                                }
+                                ui.label(t!("task-history-failed"));
+                                ui.label(report.failed.len().to_string());
+                                ui.end_row();
+                            });
+                        for failure in &report.failed {
+                            let entry = failure
+                                .entry
+                                .as_ref()
+                                .map(|entry| entry.display().to_string())
+                                .unwrap_or_default();
+                            ui.colored_label(
+                                theme.colors.error,
+                                format!("{}: {}", entry, failure.message),
+                            );
+                        }
+                    }
+                    None => {
+                        ui.weak(t!("task-history-no-report"));
+                    }
+                });
+
+            egui::CollapsingHeader::new(t!("task-history-log"))
+                .default_open(record.report.is_none())
+                .show(ui, |ui| {
+                    if record.log.is_empty() {
+                        ui.weak(t!("task-history-no-log"));
+                        return;
+                    }
+                    egui::ScrollArea::vertical()
+                        .max_height(240.0)
+                        .stick_to_bottom(true)
+                        .show(ui, |ui| {
+                            for line in &record.log {
+                                ui.label(egui::RichText::new(line).monospace());
+                            }
+                        });
+                });
+
+            ui.add_space(8.0);
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(
+                        record.output_dir.is_some(),
+                        egui::Button::new(format!(
+                            "{} {}",
+                            regular::FOLDER_OPEN,
+                            t!("task-history-open-output")
+                        )),
+                    )
+                    .clicked()
+                {
+                    action = Some(TaskHistoryAction::OpenOutput(record.id));
+                }
+                if record.manifest.is_some()
+                    && ui
+                        .button(format!(
+                            "{} {}",
+                            regular::FILE_TEXT,
+                            t!("task-history-reveal-manifest")
+                        ))
+                        .clicked()
+                {
+                    action = Some(TaskHistoryAction::RevealManifest(record.id));
+                }
+                if ui
+                    .add_enabled(
+                        record.report.is_some(),
+                        egui::Button::new(format!(
+                            "{} {}",
+                            regular::COPY,
+                            t!("task-history-copy-report")
+                        )),
+                    )
+                    .clicked()
+                {
+                    action = Some(TaskHistoryAction::CopyReport(record.id));
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.button(t!("common-close")).clicked() {
+                        action = Some(TaskHistoryAction::CloseDetails);
+                    }
+                });
+            });
+        });
+
+    if !open {
+        action = Some(TaskHistoryAction::CloseDetails);
+    }
+    action
+}
+
+fn kind_icon(kind: TaskKind) -> &'static str {
+    match kind {
+        TaskKind::Pack => regular::PACKAGE,
+        TaskKind::Extract => regular::FOLDER_OPEN,
+        TaskKind::Sync => regular::ARROW_SQUARE_OUT,
+    }
+}
+
+/// When the task finished, as shown in the list
+fn finished_at(record: &TaskRecord) -> String {
+    record
+        .finished
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
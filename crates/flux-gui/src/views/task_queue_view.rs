@@ -0,0 +1,178 @@
+//! Task list showing queued, running and finished tasks
+
+use crate::components::{FluxButton, FluxProgress};
+use crate::queue::{QueuedTask, TaskKind, TaskStatus};
+use crate::t;
+use crate::task::TaskId;
+use crate::theme::FluxTheme;
+use eframe::egui;
+use egui_phosphor::regular;
+
+/// Actions that can be triggered from the task list
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskQueueAction {
+    /// Cancel a single task
+    Cancel(TaskId),
+    /// Park a running task until it is resumed
+    Pause(TaskId),
+    /// Let a paused task continue
+    Resume(TaskId),
+    /// Pause every running task
+    PauseAll,
+    /// Resume every paused task
+    ResumeAll,
+    /// Cancel every queued and running task
+    CancelAll,
+    /// Remove finished tasks from the list
+    ClearFinished,
+}
+
+/// Draw one row per task with its progress and pause and cancel buttons
+pub fn draw_task_queue_view(
+    ui: &mut egui::Ui,
+    tasks: &[QueuedTask],
+    theme: &FluxTheme,
+) -> Option<TaskQueueAction> {
+    let mut action = None;
+
+    let active = tasks.iter().filter(|task| task.status.is_active()).count();
+    let finished = tasks.len() - active;
+    let paused = tasks.iter().filter(|task| task.is_paused()).count();
+    let pausable = tasks.iter().filter(|task| task.can_pause()).count();
+
+    ui.horizontal(|ui| {
+        ui.heading(format!("{} {}", regular::LIST_CHECKS, t!("tasks-title")));
+
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+            if ui
+                .add_enabled(finished > 0, egui::Button::new(t!("tasks-clear-finished")))
+                .clicked()
+            {
+                action = Some(TaskQueueAction::ClearFinished);
+            }
+            if ui
+                .add_enabled(
+                    active > 0,
+                    FluxButton::new(t!("tasks-cancel-all"))
+                        .danger()
+                        .icon(regular::X_CIRCLE),
+                )
+                .clicked()
+            {
+                action = Some(TaskQueueAction::CancelAll);
+            }
+            if paused > 0 {
+                if ui
+                    .add(FluxButton::new(t!("tasks-resume-all")).icon(regular::PLAY))
+                    .clicked()
+                {
+                    action = Some(TaskQueueAction::ResumeAll);
+                }
+            } else if ui
+                .add_enabled(
+                    pausable > 0,
+                    FluxButton::new(t!("tasks-pause-all")).icon(regular::PAUSE),
+                )
+                .clicked()
+            {
+                action = Some(TaskQueueAction::PauseAll);
+            }
+        });
+    });
+
+    ui.separator();
+
+    if tasks.is_empty() {
+        ui.weak(t!("tasks-empty"));
+        return action;
+    }
+
+    egui::ScrollArea::vertical()
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            for task in tasks {
+                ui.horizontal(|ui| {
+                    let icon = match task.kind {
+                        TaskKind::Pack => regular::PACKAGE,
+                        TaskKind::Extract => regular::FOLDER_OPEN,
+                        TaskKind::Sync => regular::ARROW_SQUARE_OUT,
+                    };
+                    ui.label(egui::RichText::new(icon).size(18.0));
+
+                    ui.vertical(|ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(egui::RichText::new(&task.label).strong());
+                            let (text, color) = if task.is_paused() {
+                                (t!("tasks-status-paused"), theme.colors.warning)
+                            } else {
+                                status_label(&task.status, theme)
+                            };
+                            ui.colored_label(color, text);
+                        });
+
+                        match task.status {
+                            TaskStatus::Running => {
+                                ui.add(
+                                    FluxProgress::new(task.progress).text(task.status_text.clone()),
+                                );
+                                if !task.current_file.is_empty() {
+                                    ui.weak(&task.current_file);
+                                }
+                            }
+                            TaskStatus::Queued => {}
+                            _ => {
+                                ui.weak(&task.status_text);
+                            }
+                        }
+                    });
+
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if task.status.is_active()
+                            && ui
+                                .add_enabled(
+                                    !task.cancel_requested(),
+                                    egui::Button::new(regular::X_CIRCLE),
+                                )
+                                .on_hover_text(t!("tasks-cancel"))
+                                .clicked()
+                        {
+                            action = Some(TaskQueueAction::Cancel(task.id));
+                        }
+                        if task.is_paused() {
+                            if ui
+                                .button(regular::PLAY)
+                                .on_hover_text(t!("tasks-resume"))
+                                .clicked()
+                            {
+                                action = Some(TaskQueueAction::Resume(task.id));
+                            }
+                        } else if task.can_pause()
+                            && ui
+                                .button(regular::PAUSE)
+                                .on_hover_text(t!("tasks-pause"))
+                                .clicked()
+                        {
+                            action = Some(TaskQueueAction::Pause(task.id));
+                        }
+                    });
+                });
+                ui.separator();
+            }
+        });
+
+    action
+}
+
+/// Short status text and its color
+pub(super) fn status_label(
+    status: &TaskStatus,
+    theme: &FluxTheme,
+) -> (&'static str, egui::Color32) {
+    match status {
+        TaskStatus::Queued => (t!("tasks-status-queued"), theme.colors.text_weak),
+        TaskStatus::Running => (t!("tasks-status-running"), theme.colors.primary),
+        TaskStatus::Completed => (t!("tasks-status-done"), theme.colors.success),
+        TaskStatus::Failed(_) => (t!("tasks-status-failed"), theme.colors.error),
+        TaskStatus::Cancelled => (t!("tasks-status-cancelled"), theme.colors.warning),
+    }
+}
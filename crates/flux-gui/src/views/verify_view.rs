@@ -0,0 +1,258 @@
+//! Archive verification dialog
+//!
+//! [`flux_core::archive::verify_with_progress`] reads every entry on a
+//! background thread, which takes about as long as extracting the archive.
+//! The dialog counts entries while it runs and lists each result at the end.
+
+use super::browser_view::format_size;
+use crate::t;
+use crate::theme::FluxTheme;
+use crossbeam_channel::Receiver;
+use eframe::egui;
+use egui_phosphor::regular;
+use flux_core::archive::{verify_with_progress, EntryCheck, EntryStatus, VerifyReport};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Height of one row in the results list
+const ROW_HEIGHT: f32 = 20.0;
+
+/// Actions that can be triggered from the verification dialog
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerifyAction {
+    /// Save the report as a text file
+    Export,
+    /// Close the dialog
+    Close,
+}
+
+/// Verification of one archive, running in the background
+pub struct ArchiveVerification {
+    path: PathBuf,
+    /// Entries read so far
+    checked: Arc<AtomicUsize>,
+    report: Option<Result<VerifyReport, String>>,
+    pending: Option<Receiver<Result<VerifyReport, String>>>,
+    /// List only entries that are not verified OK
+    problems_only: bool,
+}
+
+impl ArchiveVerification {
+    /// Start verifying `path`
+    pub fn start(path: PathBuf) -> Self {
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        let checked = Arc::new(AtomicUsize::new(0));
+        let archive = path.clone();
+        let counter = checked.clone();
+        std::thread::spawn(move || {
+            let result = verify_with_progress(&archive, |_| {
+                counter.fetch_add(1, Ordering::Relaxed);
+            });
+            let _ = sender.send(result.map_err(|e| e.to_string()));
+        });
+
+        Self {
+            path,
+            checked,
+            report: None,
+            pending: Some(receiver),
+            problems_only: false,
+        }
+    }
+
+    /// Pick up the report once it is ready
+    pub fn poll(&mut self) {
+        if let Some(receiver) = &self.pending {
+            match receiver.try_recv() {
+                Ok(result) => {
+                    self.problems_only = result.as_ref().is_ok_and(|report| !report.is_ok());
+                    self.report = Some(result);
+                    self.pending = None;
+                }
+                Err(crossbeam_channel::TryRecvError::Empty) => {}
+                Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                    self.report = Some(Err(t!("verify-stopped").to_string()));
+                    self.pending = None;
+                }
+            }
+        }
+    }
+
+    /// Whether the archive is still being read
+    pub fn is_running(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// The archive being verified
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The finished report, if verification succeeded
+    pub fn report(&self) -> Option<&VerifyReport> {
+        self.report.as_ref()?.as_ref().ok()
+    }
+}
+
+/// Draw the verification window
+pub fn draw_verify_dialog(
+    ctx: &egui::Context,
+    verification: &mut ArchiveVerification,
+    theme: &FluxTheme,
+) -> Option<VerifyAction> {
+    let mut action = None;
+    let mut open = true;
+    let name = verification
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| verification.path.display().to_string());
+
+    egui::Window::new(format!("{} {}", regular::SHIELD_CHECK, name))
+        .id(egui::Id::new("archive_verification"))
+        .open(&mut open)
+        .collapsible(false)
+        .resizable(true)
+        .default_width(520.0)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.weak(verification.path.display().to_string());
+            ui.add_space(10.0);
+
+            match &verification.report {
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(t!(
+                            "verify-running",
+                            count = verification.checked.load(Ordering::Relaxed)
+                        ));
+                    });
+                }
+                Some(Err(e)) => {
+                    ui.colored_label(theme.colors.error, t!("verify-failed", error = e));
+                }
+                Some(Ok(report)) => {
+                    draw_summary(ui, report, theme);
+                    ui.add_space(8.0);
+                    ui.checkbox(&mut verification.problems_only, t!("verify-problems-only"));
+                    ui.add_space(4.0);
+                    draw_entries(ui, report, verification.problems_only, theme);
+                }
+            }
+
+            ui.add_space(10.0);
+            ui.separator();
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if ui.button(t!("common-close")).clicked() {
+                    action = Some(VerifyAction::Close);
+                }
+                if ui
+                    .add_enabled(
+                        verification.report().is_some(),
+                        egui::Button::new(format!("{} {}", regular::EXPORT, t!("verify-export"))),
+                    )
+                    .clicked()
+                {
+                    action = Some(VerifyAction::Export);
+                }
+            });
+        });
+
+    if !open {
+        action = Some(VerifyAction::Close);
+    }
+    action
+}
+
+fn draw_summary(ui: &mut egui::Ui, report: &VerifyReport, theme: &FluxTheme) {
+    if report.is_ok() {
+        ui.colored_label(
+            theme.colors.success,
+            egui::RichText::new(format!("{} {}", regular::CHECK_CIRCLE, t!("verify-passed")))
+                .strong(),
+        );
+    } else {
+        ui.colored_label(
+            theme.colors.error,
+            egui::RichText::new(format!(
+                "{} {}",
+                regular::WARNING_CIRCLE,
+                t!("verify-problems", count = report.corrupt())
+            ))
+            .strong(),
+        );
+    }
+    if let Some(error) = &report.error {
+        ui.colored_label(theme.colors.error, error);
+    }
+
+    ui.label(t!(
+        "verify-counts",
+        total = report.entries.len(),
+        ok = report.ok(),
+        corrupt = report.corrupt(),
+        unchecked = report.unchecked()
+    ));
+    if report.unchecked() > 0 {
+        ui.weak(t!("verify-unchecked-hint"));
+    }
+}
+
+fn draw_entries(ui: &mut egui::Ui, report: &VerifyReport, problems_only: bool, theme: &FluxTheme) {
+    let rows: Vec<&EntryCheck> = report
+        .entries
+        .iter()
+        .filter(|entry| !problems_only || entry.status != EntryStatus::Ok)
+        .collect();
+    if rows.is_empty() {
+        ui.weak(t!("verify-nothing-to-show"));
+        return;
+    }
+
+    egui::ScrollArea::vertical()
+        .max_height(320.0)
+        .auto_shrink([false, true])
+        .show_rows(ui, ROW_HEIGHT, rows.len(), |ui, range| {
+            for entry in &rows[range] {
+                ui.horizontal(|ui| {
+                    let (icon, label, color) = match &entry.status {
+                        EntryStatus::Ok => (
+                            regular::CHECK_CIRCLE,
+                            t!("verify-status-ok"),
+                            theme.colors.success,
+                        ),
+                        EntryStatus::Corrupt(_) => (
+                            regular::WARNING_CIRCLE,
+                            t!("verify-status-corrupt"),
+                            theme.colors.error,
+                        ),
+                        EntryStatus::NoChecksum => (
+                            regular::QUESTION,
+                            t!("verify-status-no-checksum"),
+                            theme.colors.text_weak,
+                        ),
+                        EntryStatus::Encrypted => (
+                            regular::LOCK_SIMPLE,
+                            t!("verify-status-encrypted"),
+                            theme.colors.warning,
+                        ),
+                    };
+                    ui.add_sized(
+                        [110.0, ROW_HEIGHT],
+                        egui::Label::new(
+                            egui::RichText::new(format!("{} {}", icon, label)).color(color),
+                        ),
+                    );
+                    let path = ui.label(entry.path.display().to_string());
+                    if let EntryStatus::Corrupt(reason) = &entry.status {
+                        path.on_hover_text(reason);
+                    }
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        ui.weak(format_size(entry.size));
+                    });
+                });
+            }
+        });
+}
@@ -1,7 +1,10 @@
 //! Common assertions for flux testing
 
 use anyhow::Result;
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 use walkdir::WalkDir;
 
 /// Asserts that two directory structures are identical
@@ -63,6 +66,192 @@ pub fn assert_file_permissions(path: &Path, expected: u32) -> Result<()> {
     Ok(())
 }
 
+/// What [`assert_tree_matches_with`] compares besides names, types and contents
+#[derive(Debug, Clone)]
+pub struct TreeMatchOptions {
+    /// Compare permission bits; off unix only the read-only flag is compared
+    pub permissions: bool,
+    /// Allowed difference between file mtimes, or `None` to ignore them
+    pub mtime_tolerance: Option<Duration>,
+    /// Compare symlinks and their targets; always skipped off unix
+    pub symlinks: bool,
+}
+
+impl Default for TreeMatchOptions {
+    fn default() -> Self {
+        Self {
+            permissions: true,
+            // Zip stores DOS timestamps with two-second resolution
+            mtime_tolerance: Some(Duration::from_secs(2)),
+            symlinks: true,
+        }
+    }
+}
+
+/// Asserts that two file trees match, including permissions, symlink targets
+/// and file mtimes
+///
+/// See [`assert_tree_matches_with`] for the individual checks.
+pub fn assert_tree_matches(expected: &Path, actual: &Path) -> Result<()> {
+    assert_tree_matches_with(expected, actual, &TreeMatchOptions::default())
+}
+
+/// Asserts that two file trees match according to `options`
+///
+/// Paths are compared with `/` separators and symlinks are never followed.
+/// Directory mtimes are ignored because extraction updates them as entries
+/// are written. All differences are reported together.
+pub fn assert_tree_matches_with(
+    expected: &Path,
+    actual: &Path,
+    options: &TreeMatchOptions,
+) -> Result<()> {
+    let expected_tree = snapshot_tree(expected, options)?;
+    let actual_tree = snapshot_tree(actual, options)?;
+    let mut differences = Vec::new();
+
+    for (path, want) in &expected_tree {
+        match actual_tree.get(path) {
+            None => differences.push(format!("missing: {}", path)),
+            Some(got) => compare_nodes(path, want, got, options, &mut differences),
+        }
+    }
+    for path in actual_tree.keys() {
+        if !expected_tree.contains_key(path) {
+            differences.push(format!("unexpected: {}", path));
+        }
+    }
+
+    assert!(
+        differences.is_empty(),
+        "trees differ between {:?} and {:?}:\n  {}",
+        expected,
+        actual,
+        differences.join("\n  ")
+    );
+    Ok(())
+}
+
+#[derive(Debug)]
+enum NodeKind {
+    File,
+    Dir,
+    Symlink(String),
+}
+
+#[derive(Debug)]
+struct Node {
+    full_path: PathBuf,
+    kind: NodeKind,
+    mode: u32,
+    mtime: SystemTime,
+}
+
+fn compare_nodes(
+    path: &str,
+    want: &Node,
+    got: &Node,
+    options: &TreeMatchOptions,
+    differences: &mut Vec<String>,
+) {
+    match (&want.kind, &got.kind) {
+        (NodeKind::File, NodeKind::File) => {
+            let same_contents = match (fs::read(&want.full_path), fs::read(&got.full_path)) {
+                (Ok(a), Ok(b)) => a == b,
+                _ => false,
+            };
+            if !same_contents {
+                differences.push(format!("contents differ: {}", path));
+            }
+            if let Some(tolerance) = options.mtime_tolerance {
+                let diff = want
+                    .mtime
+                    .duration_since(got.mtime)
+                    .unwrap_or_else(|e| e.duration());
+                if diff > tolerance {
+                    differences.push(format!(
+                        "mtime differs by {:?} (tolerance {:?}): {}",
+                        diff, tolerance, path
+                    ));
+                }
+            }
+        }
+        (NodeKind::Dir, NodeKind::Dir) => {}
+        (NodeKind::Symlink(a), NodeKind::Symlink(b)) => {
+            if a != b {
+                differences.push(format!("symlink target {} != {}: {}", a, b, path));
+            }
+            // Link permissions are meaningless on most platforms
+            return;
+        }
+        (a, b) => {
+            differences.push(format!("type {:?} != {:?}: {}", a, b, path));
+            return;
+        }
+    }
+
+    if options.permissions && want.mode != got.mode {
+        differences.push(format!(
+            "permissions {:o} != {:o}: {}",
+            want.mode, got.mode, path
+        ));
+    }
+}
+
+/// Reads every entry below `root`, keyed by its `/`-separated relative path
+fn snapshot_tree(root: &Path, options: &TreeMatchOptions) -> Result<BTreeMap<String, Node>> {
+    let mut nodes = BTreeMap::new();
+    for entry in WalkDir::new(root).min_depth(1) {
+        let entry = entry?;
+        let metadata = entry.path().symlink_metadata()?;
+        let file_type = metadata.file_type();
+
+        let kind = if file_type.is_symlink() {
+            if !options.symlinks || cfg!(not(unix)) {
+                continue;
+            }
+            let target = fs::read_link(entry.path())?;
+            NodeKind::Symlink(normalize_separators(&target))
+        } else if file_type.is_dir() {
+            NodeKind::Dir
+        } else {
+            NodeKind::File
+        };
+
+        let relative = entry.path().strip_prefix(root)?;
+        nodes.insert(
+            normalize_separators(relative),
+            Node {
+                full_path: entry.path().to_path_buf(),
+                kind,
+                mode: permission_bits(&metadata),
+                mtime: metadata.modified()?,
+            },
+        );
+    }
+    Ok(nodes)
+}
+
+fn normalize_separators(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+#[cfg(unix)]
+fn permission_bits(metadata: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o777
+}
+
+/// Only the read-only flag is portable, so map it onto the unix bits
+#[cfg(not(unix))]
+fn permission_bits(metadata: &fs::Metadata) -> u32 {
+    if metadata.permissions().readonly() {
+        0o444
+    } else {
+        0o644
+    }
+}
+
 fn collect_entries(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
     let mut entries: Vec<_> = WalkDir::new(dir)
         .into_iter()
@@ -73,3 +262,74 @@ fn collect_entries(dir: &Path) -> Result<Vec<std::path::PathBuf>> {
     entries.sort();
     Ok(entries)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestDir;
+
+    fn sample_tree(test_dir: &TestDir, root: &str) -> PathBuf {
+        test_dir
+            .create_file(&format!("{}/a.txt", root), b"alpha")
+            .unwrap();
+        test_dir
+            .create_file(&format!("{}/sub/b.txt", root), b"beta")
+            .unwrap();
+        test_dir.path().join(root)
+    }
+
+    #[test]
+    fn test_identical_trees_match() {
+        let test_dir = TestDir::new().unwrap();
+        let left = sample_tree(&test_dir, "left");
+        let right = sample_tree(&test_dir, "right");
+
+        assert_tree_matches(&left, &right).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "contents differ: sub/b.txt")]
+    fn test_content_difference_reported() {
+        let test_dir = TestDir::new().unwrap();
+        let left = sample_tree(&test_dir, "left");
+        let right = sample_tree(&test_dir, "right");
+        fs::write(right.join("sub/b.txt"), b"changed").unwrap();
+
+        assert_tree_matches(&left, &right).unwrap();
+    }
+
+    #[test]
+    fn test_mtime_tolerance() {
+        let test_dir = TestDir::new().unwrap();
+        let left = sample_tree(&test_dir, "left");
+        let right = sample_tree(&test_dir, "right");
+        let shifted = SystemTime::now() - Duration::from_secs(60);
+        filetime::set_file_mtime(
+            right.join("a.txt"),
+            filetime::FileTime::from_system_time(shifted),
+        )
+        .unwrap();
+
+        let options = TreeMatchOptions {
+            mtime_tolerance: None,
+            ..Default::default()
+        };
+        assert_tree_matches_with(&left, &right, &options).unwrap();
+
+        let result = std::panic::catch_unwind(|| assert_tree_matches(&left, &right));
+        assert!(result.is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[should_panic(expected = "symlink target")]
+    fn test_symlink_target_difference_reported() {
+        let test_dir = TestDir::new().unwrap();
+        let left = sample_tree(&test_dir, "left");
+        let right = sample_tree(&test_dir, "right");
+        std::os::unix::fs::symlink("a.txt", left.join("link")).unwrap();
+        std::os::unix::fs::symlink("sub/b.txt", right.join("link")).unwrap();
+
+        assert_tree_matches(&left, &right).unwrap();
+    }
+}
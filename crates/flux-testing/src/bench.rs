@@ -0,0 +1,419 @@
+//! Dataset generators and timing helpers for benchmarks
+//!
+//! Benchmarks are only comparable across machines if they run on the same
+//! bytes. [`DatasetSpec`] describes a dataset shape (many small files, a few
+//! huge files, a media-heavy mix) and generates it from a fixed seed, so every
+//! machine packs and extracts byte-identical input.
+//!
+//! Set `FLUX_BENCH_SCALE` (e.g. `0.1`) to shrink every preset proportionally
+//! on slow CI runners; file counts and sizes scale, the content does not.
+
+use anyhow::Result;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Environment variable that scales every dataset preset
+pub const SCALE_ENV: &str = "FLUX_BENCH_SCALE";
+
+const WORDS: &[&str] = &[
+    "archive", "buffer", "compress", "data", "entry", "file", "header", "index", "json", "kernel",
+    "length", "manifest", "node", "offset", "path", "queue", "record", "stream", "table", "update",
+    "value", "window", "the", "a", "of", "and", "to", "in", "is", "for",
+];
+
+/// The kind of bytes written into a generated file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentKind {
+    /// Prose-like text built from a small vocabulary; compresses well
+    Text,
+    /// Line-oriented source code with repeated structure
+    Source,
+    /// Uniformly random bytes behind a media file signature; incompressible
+    Media,
+}
+
+impl ContentKind {
+    fn extension(&self) -> &'static str {
+        match self {
+            ContentKind::Text => "txt",
+            ContentKind::Source => "rs",
+            ContentKind::Media => "jpg",
+        }
+    }
+}
+
+/// A group of similar files within a dataset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileGroup {
+    pub count: usize,
+    pub size: usize,
+    pub content: ContentKind,
+}
+
+/// Deterministic description of a benchmark dataset
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatasetSpec {
+    pub name: &'static str,
+    pub groups: Vec<FileGroup>,
+    /// Maximum number of files per directory before a new one is started
+    pub files_per_dir: usize,
+    pub seed: u64,
+}
+
+impl DatasetSpec {
+    /// 10 000 files of about 1 KiB, like a source tree or config directory
+    pub fn many_small_files() -> Self {
+        Self {
+            name: "many_small_files",
+            groups: vec![
+                FileGroup {
+                    count: 6_000,
+                    size: 1024,
+                    content: ContentKind::Source,
+                },
+                FileGroup {
+                    count: 4_000,
+                    size: 768,
+                    content: ContentKind::Text,
+                },
+            ],
+            files_per_dir: 200,
+            seed: 1,
+        }
+        .scaled_from_env()
+    }
+
+    /// Four 256 MiB files, like database dumps or disk images
+    pub fn few_huge_files() -> Self {
+        Self {
+            name: "few_huge_files",
+            groups: vec![
+                FileGroup {
+                    count: 2,
+                    size: 256 * 1024 * 1024,
+                    content: ContentKind::Text,
+                },
+                FileGroup {
+                    count: 2,
+                    size: 256 * 1024 * 1024,
+                    content: ContentKind::Media,
+                },
+            ],
+            files_per_dir: 16,
+            seed: 2,
+        }
+        .scaled_from_env()
+    }
+
+    /// A photo library: mostly incompressible media with some sidecar text
+    pub fn media_mix() -> Self {
+        Self {
+            name: "media_mix",
+            groups: vec![
+                FileGroup {
+                    count: 200,
+                    size: 2 * 1024 * 1024,
+                    content: ContentKind::Media,
+                },
+                FileGroup {
+                    count: 200,
+                    size: 4 * 1024,
+                    content: ContentKind::Text,
+                },
+            ],
+            files_per_dir: 100,
+            seed: 3,
+        }
+        .scaled_from_env()
+    }
+
+    /// Multiplies file counts and sizes by `factor`, keeping at least one
+    /// byte-sized file per group
+    pub fn scaled(mut self, factor: f64) -> Self {
+        for group in &mut self.groups {
+            group.count = ((group.count as f64 * factor).round() as usize).max(1);
+            group.size = ((group.size as f64 * factor).round() as usize).max(1);
+        }
+        self
+    }
+
+    fn scaled_from_env(self) -> Self {
+        match std::env::var(SCALE_ENV)
+            .ok()
+            .and_then(|value| value.parse::<f64>().ok())
+        {
+            Some(factor) if factor > 0.0 => self.scaled(factor),
+            _ => self,
+        }
+    }
+
+    /// Total number of files the dataset will contain
+    pub fn file_count(&self) -> usize {
+        self.groups.iter().map(|group| group.count).sum()
+    }
+
+    /// Total number of bytes the dataset will contain
+    pub fn total_bytes(&self) -> u64 {
+        self.groups
+            .iter()
+            .map(|group| (group.count * group.size) as u64)
+            .sum()
+    }
+
+    /// Writes the dataset into `dir`, which is created if needed
+    pub fn generate(&self, dir: &Path) -> Result<DatasetStats> {
+        fs::create_dir_all(dir)?;
+        let mut rng = SplitMix64::new(self.seed);
+        let mut buffer = Vec::new();
+        let mut index = 0usize;
+
+        for (group_index, group) in self.groups.iter().enumerate() {
+            for _ in 0..group.count {
+                let subdir = dir.join(format!("d{:04}", index / self.files_per_dir.max(1)));
+                if index.is_multiple_of(self.files_per_dir.max(1)) {
+                    fs::create_dir_all(&subdir)?;
+                }
+                let path = subdir.join(format!(
+                    "g{}_{:06}.{}",
+                    group_index,
+                    index,
+                    group.content.extension()
+                ));
+
+                buffer.clear();
+                fill_content(&mut buffer, group.content, group.size, &mut rng);
+                fs::File::create(path)?.write_all(&buffer)?;
+                index += 1;
+            }
+        }
+
+        Ok(DatasetStats {
+            files: self.file_count(),
+            bytes: self.total_bytes(),
+        })
+    }
+}
+
+/// Summary of a generated dataset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatasetStats {
+    pub files: usize,
+    pub bytes: u64,
+}
+
+fn fill_content(out: &mut Vec<u8>, kind: ContentKind, size: usize, rng: &mut SplitMix64) {
+    match kind {
+        ContentKind::Text => {
+            while out.len() < size {
+                out.extend_from_slice(WORDS[rng.below(WORDS.len())].as_bytes());
+                out.push(if rng.below(12) == 0 { b'\n' } else { b' ' });
+            }
+        }
+        ContentKind::Source => {
+            let mut line = 0;
+            while out.len() < size {
+                let word = WORDS[rng.below(WORDS.len())];
+                let _ = writeln!(
+                    out,
+                    "    let {}_{} = {}(&{}, {});",
+                    word,
+                    line,
+                    WORDS[rng.below(WORDS.len())],
+                    word,
+                    rng.below(1000)
+                );
+                line += 1;
+            }
+        }
+        ContentKind::Media => {
+            // JPEG SOI/APP0 marker so format sniffing treats it as media
+            out.extend_from_slice(&[0xFF, 0xD8, 0xFF, 0xE0]);
+            while out.len() < size {
+                out.extend_from_slice(&rng.next_u64().to_le_bytes());
+            }
+        }
+    }
+    out.truncate(size);
+}
+
+/// Small seeded generator; its output is fixed forever, unlike `rand`'s
+/// `StdRng`, whose algorithm may change between releases
+#[derive(Debug, Clone)]
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n.max(1) as u64) as usize
+    }
+}
+
+/// Timing results from [`measure`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timings {
+    pub samples: Vec<Duration>,
+}
+
+impl Timings {
+    pub fn min(&self) -> Duration {
+        self.samples.iter().copied().min().unwrap_or_default()
+    }
+
+    pub fn max(&self) -> Duration {
+        self.samples.iter().copied().max().unwrap_or_default()
+    }
+
+    pub fn median(&self) -> Duration {
+        let mut sorted = self.samples.clone();
+        sorted.sort_unstable();
+        sorted.get(sorted.len() / 2).copied().unwrap_or_default()
+    }
+
+    pub fn mean(&self) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+        self.samples.iter().sum::<Duration>() / self.samples.len() as u32
+    }
+
+    /// Median throughput in MiB/s for an operation that processed `bytes`
+    pub fn throughput_mib_s(&self, bytes: u64) -> f64 {
+        throughput_mib_s(bytes, self.median())
+    }
+}
+
+impl fmt::Display for Timings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "median {:?} (min {:?}, max {:?}, n={})",
+            self.median(),
+            self.min(),
+            self.max(),
+            self.samples.len()
+        )
+    }
+}
+
+/// Runs `setup` then times `routine` on its output, `iterations` times
+///
+/// Only `routine` is timed, mirroring criterion's `iter_with_setup`, so
+/// dataset generation never pollutes a measurement.
+pub fn measure<S, T, R>(iterations: usize, mut setup: S, mut routine: R) -> Result<Timings>
+where
+    S: FnMut() -> Result<T>,
+    R: FnMut(T) -> Result<()>,
+{
+    let mut samples = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        let input = setup()?;
+        let start = Instant::now();
+        routine(input)?;
+        samples.push(start.elapsed());
+    }
+    Ok(Timings { samples })
+}
+
+/// Converts a byte count and duration into MiB/s
+pub fn throughput_mib_s(bytes: u64, elapsed: Duration) -> f64 {
+    if elapsed.is_zero() {
+        return 0.0;
+    }
+    bytes as f64 / (1024.0 * 1024.0) / elapsed.as_secs_f64()
+}
+
+/// One-line description of the machine, printed alongside benchmark results
+/// so numbers from different runs can be put in context
+pub fn machine_summary() -> String {
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let scale = std::env::var(SCALE_ENV).unwrap_or_else(|_| "1".to_string());
+    format!(
+        "{}-{}, {} threads, flux {}, {}={}",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        threads,
+        env!("CARGO_PKG_VERSION"),
+        SCALE_ENV,
+        scale
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestDir;
+    use walkdir::WalkDir;
+
+    fn read_all(dir: &Path) -> Vec<(String, Vec<u8>)> {
+        let mut files: Vec<_> = WalkDir::new(dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| {
+                let name = e.path().strip_prefix(dir).unwrap().display().to_string();
+                (name, fs::read(e.path()).unwrap())
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_generation_is_deterministic() {
+        let spec = DatasetSpec::media_mix().scaled(0.01);
+        let test_dir = TestDir::new().unwrap();
+        let a = test_dir.path().join("a");
+        let b = test_dir.path().join("b");
+
+        let stats = spec.generate(&a).unwrap();
+        spec.generate(&b).unwrap();
+
+        let files = read_all(&a);
+        assert_eq!(files.len(), stats.files);
+        assert_eq!(
+            files.iter().map(|(_, data)| data.len() as u64).sum::<u64>(),
+            stats.bytes
+        );
+        assert_eq!(files, read_all(&b));
+    }
+
+    #[test]
+    fn test_media_is_incompressible_and_text_is_not() {
+        let mut rng = SplitMix64::new(9);
+        let mut text = Vec::new();
+        let mut media = Vec::new();
+        fill_content(&mut text, ContentKind::Text, 64 * 1024, &mut rng);
+        fill_content(&mut media, ContentKind::Media, 64 * 1024, &mut rng);
+
+        let text_ratio = zstd::encode_all(text.as_slice(), 3).unwrap().len() as f64 / 65536.0;
+        let media_ratio = zstd::encode_all(media.as_slice(), 3).unwrap().len() as f64 / 65536.0;
+        assert!(text_ratio < 0.5, "text ratio {}", text_ratio);
+        assert!(media_ratio > 0.95, "media ratio {}", media_ratio);
+    }
+
+    #[test]
+    fn test_measure_collects_samples() {
+        let timings = measure(3, || Ok(()), |_| Ok(())).unwrap();
+        assert_eq!(timings.samples.len(), 3);
+        assert!(timings.min() <= timings.median());
+        assert_eq!(throughput_mib_s(1024 * 1024, Duration::from_secs(1)), 1.0);
+    }
+}
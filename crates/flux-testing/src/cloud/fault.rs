@@ -0,0 +1,335 @@
+//! Fault-injecting object store wrapper
+//!
+//! [`FaultyStore`] forwards every call to an inner store, but can be told to
+//! add latency or fail specific requests. Faults are scheduled explicitly
+//! (the next N requests, or every Nth request) so resilience tests stay
+//! deterministic.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use flux_cloud::CloudStore;
+use futures::stream::{self, BoxStream, StreamExt};
+use object_store::path::Path;
+use object_store::{
+    GetOptions, GetResult, GetResultPayload, ListResult, MultipartUpload, ObjectMeta, ObjectStore,
+    PutMultipartOpts, PutOptions, PutPayload, PutResult, UploadPart,
+};
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const STORE_NAME: &str = "FaultyStore";
+
+/// A failure that can be injected into a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The service rejects the request with a 503 "slow down" response
+    Throttle,
+    /// The request never completes and times out
+    Timeout,
+    /// The connection drops; reads return half of the data before failing
+    Disconnect,
+}
+
+impl Fault {
+    fn to_error(self) -> object_store::Error {
+        let source: Box<dyn std::error::Error + Send + Sync> = match self {
+            Fault::Throttle => "503 Service Unavailable: slow down (injected)".into(),
+            Fault::Timeout => Box::new(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "request timed out (injected)",
+            )),
+            Fault::Disconnect => Box::new(io::Error::new(
+                io::ErrorKind::ConnectionReset,
+                "connection reset by peer (injected)",
+            )),
+        };
+        object_store::Error::Generic {
+            store: STORE_NAME,
+            source,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct FaultState {
+    requests: usize,
+    injected: usize,
+    queued: VecDeque<Fault>,
+    every: Option<(usize, Fault)>,
+}
+
+/// An object store wrapper that injects latency and failures
+#[derive(Debug)]
+pub struct FaultyStore<S: ObjectStore> {
+    inner: Arc<S>,
+    latency: Duration,
+    state: Arc<Mutex<FaultState>>,
+}
+
+impl<S: ObjectStore> Clone for FaultyStore<S> {
+    fn clone(&self) -> Self {
+        FaultyStore {
+            inner: self.inner.clone(),
+            latency: self.latency,
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<S: ObjectStore> FaultyStore<S> {
+    /// Wraps `inner` without any faults configured
+    pub fn new(inner: S) -> Self {
+        FaultyStore {
+            inner: Arc::new(inner),
+            latency: Duration::ZERO,
+            state: Arc::new(Mutex::new(FaultState::default())),
+        }
+    }
+
+    /// Delays every request by `latency`
+    ///
+    /// The delay blocks the calling thread, which keeps timing independent of
+    /// whichever async runtime drives the store.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Injects `fault` into every `n`th request
+    pub fn fail_every(self, n: usize, fault: Fault) -> Self {
+        assert!(n > 0, "fail_every requires n > 0");
+        self.state.lock().unwrap().every = Some((n, fault));
+        self
+    }
+
+    /// Injects `fault` into the next `count` requests
+    pub fn fail_next(&self, fault: Fault, count: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.queued.extend(std::iter::repeat_n(fault, count));
+    }
+
+    /// Number of requests seen so far, including failed ones
+    pub fn requests(&self) -> usize {
+        self.state.lock().unwrap().requests
+    }
+
+    /// Number of faults injected so far
+    pub fn injected(&self) -> usize {
+        self.state.lock().unwrap().injected
+    }
+
+    /// Returns the wrapped store
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Creates a `CloudStore` that talks to this store
+    pub fn cloud_store(&self) -> flux_cloud::Result<CloudStore> {
+        CloudStore::from_object_store(Arc::new(self.clone()))
+    }
+
+    /// Applies latency and decides whether the current request should fail
+    fn next_fault(&self) -> Option<Fault> {
+        if !self.latency.is_zero() {
+            std::thread::sleep(self.latency);
+        }
+
+        let mut state = self.state.lock().unwrap();
+        state.requests += 1;
+        let fault = match state.queued.pop_front() {
+            Some(fault) => Some(fault),
+            None => match state.every {
+                Some((n, fault)) if state.requests.is_multiple_of(n) => Some(fault),
+                _ => None,
+            },
+        };
+        if fault.is_some() {
+            state.injected += 1;
+        }
+        fault
+    }
+
+    fn check(&self) -> object_store::Result<()> {
+        match self.next_fault() {
+            Some(fault) => Err(fault.to_error()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<S: ObjectStore> fmt::Display for FaultyStore<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "FaultyStore({})", self.inner)
+    }
+}
+
+#[async_trait]
+impl<S: ObjectStore> ObjectStore for FaultyStore<S> {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> object_store::Result<PutResult> {
+        self.check()?;
+        self.inner.put_opts(location, payload, opts).await
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOpts,
+    ) -> object_store::Result<Box<dyn MultipartUpload>> {
+        self.check()?;
+        let inner = self.inner.put_multipart_opts(location, opts).await?;
+        Ok(Box::new(FaultyUpload {
+            inner,
+            store: self.clone(),
+        }))
+    }
+
+    async fn get_opts(
+        &self,
+        location: &Path,
+        options: GetOptions,
+    ) -> object_store::Result<GetResult> {
+        match self.next_fault() {
+            Some(Fault::Disconnect) => {
+                let result = self.inner.get_opts(location, options).await?;
+                let meta = result.meta.clone();
+                let range = result.range.clone();
+                let attributes = result.attributes.clone();
+                let data = result.bytes().await?;
+
+                // Deliver the first half, then drop the connection
+                let partial: Bytes = data.slice(..data.len() / 2);
+                let payload = stream::iter(vec![Ok(partial), Err(Fault::Disconnect.to_error())]);
+                Ok(GetResult {
+                    payload: GetResultPayload::Stream(payload.boxed()),
+                    meta,
+                    range,
+                    attributes,
+                })
+            }
+            Some(fault) => Err(fault.to_error()),
+            None => self.inner.get_opts(location, options).await,
+        }
+    }
+
+    async fn delete(&self, location: &Path) -> object_store::Result<()> {
+        self.check()?;
+        self.inner.delete(location).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+        match self.next_fault() {
+            Some(fault) => stream::once(async move { Err(fault.to_error()) }).boxed(),
+            None => self.inner.list(prefix),
+        }
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> object_store::Result<ListResult> {
+        self.check()?;
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        self.check()?;
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        self.check()?;
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
+
+/// Multipart upload whose parts and completion are subject to the same faults
+#[derive(Debug)]
+struct FaultyUpload<S: ObjectStore> {
+    inner: Box<dyn MultipartUpload>,
+    store: FaultyStore<S>,
+}
+
+#[async_trait]
+impl<S: ObjectStore> MultipartUpload for FaultyUpload<S> {
+    fn put_part(&mut self, data: PutPayload) -> UploadPart {
+        match self.store.next_fault() {
+            Some(fault) => Box::pin(async move { Err(fault.to_error()) }),
+            None => self.inner.put_part(data),
+        }
+    }
+
+    async fn complete(&mut self) -> object_store::Result<PutResult> {
+        self.store.check()?;
+        self.inner.complete().await
+    }
+
+    async fn abort(&mut self) -> object_store::Result<()> {
+        self.inner.abort().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cloud::MemoryStore;
+    use futures::executor::block_on;
+    use std::time::Instant;
+
+    #[test]
+    fn test_fail_next() {
+        let store = FaultyStore::new(MemoryStore::new().with_object("a.txt", "abc"));
+        store.fail_next(Fault::Throttle, 2);
+
+        let path = Path::from("a.txt");
+        let err = block_on(store.head(&path)).unwrap_err();
+        assert!(err.to_string().contains("503"));
+        assert!(block_on(store.head(&path)).is_err());
+        assert!(block_on(store.head(&path)).is_ok());
+
+        assert_eq!(store.requests(), 3);
+        assert_eq!(store.injected(), 2);
+    }
+
+    #[test]
+    fn test_fail_every() {
+        let store = FaultyStore::new(MemoryStore::new().with_object("a.txt", "abc"))
+            .fail_every(3, Fault::Timeout);
+        let path = Path::from("a.txt");
+
+        let results: Vec<bool> = (0..6)
+            .map(|_| block_on(store.head(&path)).is_ok())
+            .collect();
+        assert_eq!(results, vec![true, true, false, true, true, false]);
+    }
+
+    #[test]
+    fn test_disconnect_truncates_read() {
+        let store = FaultyStore::new(MemoryStore::new().with_object("a.bin", vec![1u8; 100]));
+        store.fail_next(Fault::Disconnect, 1);
+
+        let path = Path::from("a.bin");
+        let result = block_on(store.get(&path)).unwrap();
+        let mut chunks = block_on_collect(result);
+        assert_eq!(chunks.remove(0).unwrap().len(), 50);
+        assert!(chunks.remove(0).is_err());
+    }
+
+    #[test]
+    fn test_latency() {
+        let store = FaultyStore::new(MemoryStore::new().with_object("a.txt", "abc"))
+            .with_latency(Duration::from_millis(20));
+
+        let start = Instant::now();
+        block_on(store.head(&Path::from("a.txt"))).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    fn block_on_collect(result: GetResult) -> Vec<object_store::Result<Bytes>> {
+        block_on(result.into_stream().collect())
+    }
+}
@@ -0,0 +1,251 @@
+//! In-memory cloud storage for tests
+//!
+//! [`MemoryStore`] is an [`ObjectStore`] backed by `object_store`'s `InMemory`
+//! implementation. It records every completed upload so tests can assert on
+//! what flux-cloud wrote, without network access or credentials. Like the real
+//! providers it supports [`MultipartStore`], so large uploads are tracked with
+//! a marker object while they run.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use flux_cloud::uploads::is_marker;
+use flux_cloud::{CloudReader, CloudStore, CloudWriter};
+use futures::executor::block_on;
+use futures::stream::BoxStream;
+use object_store::memory::InMemory;
+use object_store::multipart::{MultipartStore, PartId};
+use object_store::path::Path;
+use object_store::{
+    GetOptions, GetResult, ListResult, MultipartId, MultipartUpload, ObjectMeta, ObjectStore,
+    PutMultipartOpts, PutOptions, PutPayload, PutResult, UploadPart,
+};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// An in-memory object store that captures uploads
+#[derive(Debug, Clone, Default)]
+pub struct MemoryStore {
+    inner: Arc<InMemory>,
+    uploads: Arc<Mutex<Vec<Path>>>,
+}
+
+impl MemoryStore {
+    /// Creates an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pre-populates an object, returning the store for chaining
+    pub fn with_object(self, path: &str, data: impl Into<Bytes>) -> Self {
+        self.insert(path, data);
+        self
+    }
+
+    /// Pre-populates an object without recording it as an upload
+    pub fn insert(&self, path: &str, data: impl Into<Bytes>) {
+        let payload = PutPayload::from(data.into());
+        block_on(self.inner.put(&Path::from(path), payload)).expect("in-memory put cannot fail");
+    }
+
+    /// Returns the current contents of an object, if it exists
+    pub fn object(&self, path: &str) -> Option<Bytes> {
+        block_on(async {
+            let result = self.inner.get(&Path::from(path)).await.ok()?;
+            result.bytes().await.ok()
+        })
+    }
+
+    /// Returns true if an object exists at `path`
+    pub fn contains(&self, path: &str) -> bool {
+        self.object(path).is_some()
+    }
+
+    /// Returns the paths of all completed uploads, in order
+    ///
+    /// Upload markers written by flux-cloud are not included.
+    pub fn uploads(&self) -> Vec<String> {
+        self.uploads
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|p| p.to_string())
+            .collect()
+    }
+
+    /// Forgets previously captured uploads
+    pub fn clear_uploads(&self) {
+        self.uploads.lock().unwrap().clear();
+    }
+
+    /// Creates a `CloudStore` that talks to this store
+    pub fn cloud_store(&self) -> flux_cloud::Result<CloudStore> {
+        CloudStore::from_multipart_store(Arc::new(self.clone()))
+    }
+
+    /// Creates a `CloudReader` for an object in this store
+    pub fn reader(&self, path: &str) -> flux_cloud::Result<CloudReader> {
+        CloudReader::from_store(self.cloud_store()?, Path::from(path))
+    }
+
+    /// Creates a `CloudWriter` that uploads to this store
+    pub fn writer(&self, path: &str) -> flux_cloud::Result<CloudWriter> {
+        CloudWriter::from_store(self.cloud_store()?, Path::from(path))
+    }
+
+    fn record_upload(&self, path: &Path) {
+        if !is_marker(path) {
+            self.uploads.lock().unwrap().push(path.clone());
+        }
+    }
+}
+
+impl fmt::Display for MemoryStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "MemoryStore")
+    }
+}
+
+#[async_trait]
+impl ObjectStore for MemoryStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> object_store::Result<PutResult> {
+        let result = self.inner.put_opts(location, payload, opts).await?;
+        self.record_upload(location);
+        Ok(result)
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        opts: PutMultipartOpts,
+    ) -> object_store::Result<Box<dyn MultipartUpload>> {
+        let inner = self.inner.put_multipart_opts(location, opts).await?;
+        Ok(Box::new(CapturingUpload {
+            inner,
+            path: location.clone(),
+            store: self.clone(),
+        }))
+    }
+
+    async fn get_opts(
+        &self,
+        location: &Path,
+        options: GetOptions,
+    ) -> object_store::Result<GetResult> {
+        self.inner.get_opts(location, options).await
+    }
+
+    async fn delete(&self, location: &Path) -> object_store::Result<()> {
+        self.inner.delete(location).await
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'_, object_store::Result<ObjectMeta>> {
+        self.inner.list(prefix)
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> object_store::Result<ListResult> {
+        self.inner.list_with_delimiter(prefix).await
+    }
+
+    async fn copy(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        self.inner.copy(from, to).await
+    }
+
+    async fn copy_if_not_exists(&self, from: &Path, to: &Path) -> object_store::Result<()> {
+        self.inner.copy_if_not_exists(from, to).await
+    }
+}
+
+#[async_trait]
+impl MultipartStore for MemoryStore {
+    async fn create_multipart(&self, path: &Path) -> object_store::Result<MultipartId> {
+        self.inner.create_multipart(path).await
+    }
+
+    async fn put_part(
+        &self,
+        path: &Path,
+        id: &MultipartId,
+        part_idx: usize,
+        data: PutPayload,
+    ) -> object_store::Result<PartId> {
+        self.inner.put_part(path, id, part_idx, data).await
+    }
+
+    async fn complete_multipart(
+        &self,
+        path: &Path,
+        id: &MultipartId,
+        parts: Vec<PartId>,
+    ) -> object_store::Result<PutResult> {
+        let result = self.inner.complete_multipart(path, id, parts).await?;
+        self.record_upload(path);
+        Ok(result)
+    }
+
+    async fn abort_multipart(&self, path: &Path, id: &MultipartId) -> object_store::Result<()> {
+        self.inner.abort_multipart(path, id).await
+    }
+}
+
+/// Multipart upload that records its path once completed
+#[derive(Debug)]
+struct CapturingUpload {
+    inner: Box<dyn MultipartUpload>,
+    path: Path,
+    store: MemoryStore,
+}
+
+#[async_trait]
+impl MultipartUpload for CapturingUpload {
+    fn put_part(&mut self, data: PutPayload) -> UploadPart {
+        self.inner.put_part(data)
+    }
+
+    async fn complete(&mut self) -> object_store::Result<PutResult> {
+        let result = self.inner.complete().await?;
+        self.store.record_upload(&self.path);
+        Ok(result)
+    }
+
+    async fn abort(&mut self) -> object_store::Result<()> {
+        self.inner.abort().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+
+    #[test]
+    fn test_prepopulated_read() {
+        let store = MemoryStore::new().with_object("data/file.txt", "hello cloud");
+
+        let mut reader = store.reader("data/file.txt").unwrap();
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+
+        assert_eq!(content, "hello cloud");
+        assert!(store.uploads().is_empty());
+    }
+
+    #[test]
+    fn test_captures_uploads() {
+        let store = MemoryStore::new();
+
+        let mut writer = store.writer("out/archive.tar").unwrap();
+        writer.write_all(b"archive bytes").unwrap();
+        drop(writer);
+
+        assert_eq!(store.uploads(), vec!["out/archive.tar".to_string()]);
+        assert_eq!(
+            store.object("out/archive.tar").unwrap(),
+            Bytes::from_static(b"archive bytes")
+        );
+    }
+}
@@ -0,0 +1,10 @@
+//! Cloud storage test doubles
+//!
+//! These stores implement [`object_store::ObjectStore`] so they can be plugged
+//! into flux-cloud via [`flux_cloud::CloudStore::from_object_store`].
+
+pub mod fault;
+pub mod memory;
+
+pub use fault::{Fault, FaultyStore};
+pub use memory::MemoryStore;
@@ -0,0 +1,422 @@
+//! Compatibility checks against published flux releases
+//!
+//! Archives and manifests written today must stay readable by the releases
+//! people already run, and the other way round, as new features land. The
+//! driver packs a fixed source tree with one binary and extracts it with the
+//! other, in every direction and format, then has the second binary take an
+//! incremental backup against the first one's manifest.
+//!
+//! Release binaries are not downloaded here. Point `FLUX_COMPAT_RELEASES` at
+//! a directory holding one folder per release, each with the `flux` binary
+//! from its release archive:
+//!
+//! ```text
+//! releases/
+//!   v1.5.0/flux
+//!   v1.6.0/flux
+//! ```
+
+use crate::golden::Outcome;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+/// Environment variable naming the directory of release binaries
+pub const RELEASES_ENV: &str = "FLUX_COMPAT_RELEASES";
+
+/// Formats every release is expected to pack and extract
+pub const FORMATS: &[&str] = &["tar", "tar.gz", "tar.zst", "tar.xz", "zip"];
+
+/// A flux binary to check against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    /// Release tag, such as `v1.6.0`
+    pub version: String,
+    pub binary: PathBuf,
+}
+
+impl Release {
+    pub fn new(version: impl Into<String>, binary: impl Into<PathBuf>) -> Self {
+        Self {
+            version: version.into(),
+            binary: binary.into(),
+        }
+    }
+
+    /// Runs the binary with `args`, failing with the end of its stderr
+    fn run(&self, args: &[&std::ffi::OsStr]) -> Result<()> {
+        let output = Command::new(&self.binary)
+            .args(args)
+            .output()
+            .with_context(|| format!("Cannot run {}", self.binary.display()))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let last = stderr.lines().rev().find(|line| !line.trim().is_empty());
+            anyhow::bail!("{} failed: {}", self.version, last.unwrap_or("no output"));
+        }
+        Ok(())
+    }
+}
+
+/// Releases in `dir`, one per folder holding a `flux` binary, oldest first
+pub fn find_releases(dir: &Path) -> Result<Vec<Release>> {
+    let binary_name = format!("flux{}", std::env::consts::EXE_SUFFIX);
+    let mut releases = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("Cannot read {}", dir.display()))? {
+        let entry = entry?;
+        let binary = entry.path().join(&binary_name);
+        if binary.is_file() {
+            let version = entry.file_name().to_string_lossy().into_owned();
+            releases.push(Release::new(version, binary));
+        }
+    }
+    releases.sort_by(|a, b| compare_versions(&a.version, &b.version));
+    Ok(releases)
+}
+
+/// Releases in the directory named by `FLUX_COMPAT_RELEASES`, or none if it
+/// is not set
+pub fn releases_from_env() -> Result<Vec<Release>> {
+    match std::env::var_os(RELEASES_ENV).filter(|dir| !dir.is_empty()) {
+        Some(dir) => find_releases(Path::new(&dir)),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Orders `v1.10.0` after `v1.9.2`, comparing numeric parts as numbers
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |version: &str| -> Vec<(u64, String)> {
+        version
+            .trim_start_matches('v')
+            .split(['.', '-'])
+            .map(|part| (part.parse().unwrap_or(u64::MAX), part.to_string()))
+            .collect()
+    };
+    parts(a).cmp(&parts(b))
+}
+
+/// Which binary writes and which reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Direction {
+    /// The current build writes, the release reads
+    NewToOld,
+    /// The release writes, the current build reads
+    OldToNew,
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Direction::NewToOld => "new -> old",
+            Direction::OldToNew => "old -> new",
+        })
+    }
+}
+
+/// One check between the current build and a release
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompatCheck {
+    pub release: String,
+    pub direction: Direction,
+    /// Archive format, or `manifest` for the incremental backup check
+    pub subject: String,
+    pub result: Outcome<()>,
+}
+
+impl CompatCheck {
+    pub fn passed(&self) -> bool {
+        self.result == Outcome::Ok(())
+    }
+}
+
+impl fmt::Display for CompatCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}: ", self.release, self.direction, self.subject)?;
+        match &self.result {
+            Outcome::Ok(()) => f.write_str("ok"),
+            Outcome::Error(e) => f.write_str(e),
+        }
+    }
+}
+
+/// Writes the tree every check packs into `dir/source`
+///
+/// It has nested and empty directories, an empty file, binary content and
+/// a non-ASCII name, which older readers have each got wrong at some point.
+pub fn write_source(dir: &Path) -> Result<PathBuf> {
+    let source = dir.join("source");
+    fs::create_dir_all(source.join("docs/guide"))?;
+    fs::create_dir_all(source.join("empty"))?;
+    fs::write(
+        source.join("README.md"),
+        "# Compatibility corpus\n".repeat(64),
+    )?;
+    fs::write(source.join("docs/guide/intro.txt"), "flux ".repeat(2000))?;
+    fs::write(source.join("docs/empty.txt"), "")?;
+    fs::write(source.join("docs/héllo wörld.txt"), "unicode\n")?;
+    let binary: Vec<u8> = (0..65_536u32).map(|i| (i * 7919 % 251) as u8).collect();
+    fs::write(source.join("data.bin"), binary)?;
+    Ok(source)
+}
+
+/// Runs every check between `current` and `release` in scratch directories
+/// under `work`
+pub fn check_release(
+    current: &Release,
+    release: &Release,
+    work: &Path,
+) -> Result<Vec<CompatCheck>> {
+    let work = work.join(&release.version);
+    fs::create_dir_all(&work)?;
+    let source = write_source(&work)?;
+
+    let mut checks = Vec::new();
+    for (direction, writer, reader) in [
+        (Direction::NewToOld, current, release),
+        (Direction::OldToNew, release, current),
+    ] {
+        let dir = work.join(direction_dir(direction));
+        for format in FORMATS {
+            let result = round_trip(writer, reader, &source, &dir, format);
+            checks.push(CompatCheck {
+                release: release.version.clone(),
+                direction,
+                subject: format.to_string(),
+                result: outcome(result),
+            });
+        }
+        let result = incremental(writer, reader, &source, &dir.join("manifest"));
+        checks.push(CompatCheck {
+            release: release.version.clone(),
+            direction,
+            subject: "manifest".to_string(),
+            result: outcome(result),
+        });
+    }
+    Ok(checks)
+}
+
+/// Runs [`check_release`] for every release
+pub fn check_releases(
+    current: &Release,
+    releases: &[Release],
+    work: &Path,
+) -> Result<Vec<CompatCheck>> {
+    let mut checks = Vec::new();
+    for release in releases {
+        checks.extend(check_release(current, release, work)?);
+    }
+    Ok(checks)
+}
+
+/// Asserts that every check passed, listing all failures together
+pub fn assert_compatible(checks: &[CompatCheck]) {
+    let failures: Vec<String> = checks
+        .iter()
+        .filter(|check| !check.passed())
+        .map(ToString::to_string)
+        .collect();
+    assert!(
+        failures.is_empty(),
+        "{} of {} compatibility checks failed:\n  {}",
+        failures.len(),
+        checks.len(),
+        failures.join("\n  ")
+    );
+}
+
+fn direction_dir(direction: Direction) -> &'static str {
+    match direction {
+        Direction::NewToOld => "new-to-old",
+        Direction::OldToNew => "old-to-new",
+    }
+}
+
+fn outcome(result: Result<()>) -> Outcome<()> {
+    match result {
+        Ok(()) => Outcome::Ok(()),
+        Err(e) => Outcome::Error(format!("{:#}", e)),
+    }
+}
+
+/// `writer` packs `source` as `format`, `reader` extracts it, and the
+/// extracted tree must hold the same files
+fn round_trip(
+    writer: &Release,
+    reader: &Release,
+    source: &Path,
+    dir: &Path,
+    format: &str,
+) -> Result<()> {
+    let archive = dir.join(format!("source.{}", format));
+    let out = dir.join(format!("out-{}", format));
+    fs::create_dir_all(dir)?;
+    writer.run(&[
+        "pack".as_ref(),
+        source.as_os_str(),
+        "-o".as_ref(),
+        archive.as_os_str(),
+    ])?;
+    reader.run(&[
+        "extract".as_ref(),
+        archive.as_os_str(),
+        "-o".as_ref(),
+        out.as_os_str(),
+    ])?;
+    // Zips hold the folder's contents, tars the folder. Older releases
+    // wrote a tar whatever the output was named
+    let extracted = if flux_core::archive::sniff_format(&archive) == Some("zip") {
+        out
+    } else {
+        out.join("source")
+    };
+    compare_trees(source, &extracted)
+}
+
+/// `writer` takes a full backup of `source` with a manifest, and `reader`
+/// backs up a new file against that manifest; the increment must hold it
+fn incremental(writer: &Release, reader: &Release, source: &Path, dir: &Path) -> Result<()> {
+    let copy = dir.join("source");
+    copy_tree(source, &copy)?;
+    let full = dir.join("full.tar.zst");
+    writer.run(&[
+        "pack".as_ref(),
+        copy.as_os_str(),
+        "-o".as_ref(),
+        full.as_os_str(),
+    ])?;
+    let manifest = full.with_extension("manifest.json");
+    if !manifest.is_file() {
+        anyhow::bail!("{} wrote no manifest", writer.version);
+    }
+
+    fs::write(copy.join("added.txt"), "added after the full backup\n")?;
+    let increment = dir.join("increment.tar.zst");
+    reader.run(&[
+        "pack".as_ref(),
+        copy.as_os_str(),
+        "-o".as_ref(),
+        increment.as_os_str(),
+        "--incremental".as_ref(),
+        manifest.as_os_str(),
+    ])?;
+
+    let out = dir.join("out");
+    reader.run(&[
+        "extract".as_ref(),
+        increment.as_os_str(),
+        "-o".as_ref(),
+        out.as_os_str(),
+    ])?;
+    let added = WalkDir::new(&out)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .any(|entry| entry.file_name() == "added.txt");
+    if !added {
+        anyhow::bail!("the increment is missing added.txt");
+    }
+    Ok(())
+}
+
+/// Fails naming every path that is missing, unexpected or different
+fn compare_trees(expected: &Path, actual: &Path) -> Result<()> {
+    let want = read_tree(expected)?;
+    let got = read_tree(actual).with_context(|| format!("Cannot read {}", actual.display()))?;
+    let mut differences = Vec::new();
+    for (path, content) in &want {
+        match got.get(path) {
+            None => differences.push(format!("missing {}", path)),
+            Some(other) if other != content => differences.push(format!("differs {}", path)),
+            Some(_) => {}
+        }
+    }
+    for path in got.keys().filter(|path| !want.contains_key(*path)) {
+        differences.push(format!("unexpected {}", path));
+    }
+    if !differences.is_empty() {
+        anyhow::bail!("{}", differences.join(", "));
+    }
+    Ok(())
+}
+
+/// Relative paths under `root` with `/` separators, mapped to file contents
+/// or `None` for directories
+fn read_tree(root: &Path) -> Result<BTreeMap<String, Option<Vec<u8>>>> {
+    let mut tree = BTreeMap::new();
+    for entry in WalkDir::new(root).min_depth(1) {
+        let entry = entry?;
+        let path = entry
+            .path()
+            .strip_prefix(root)?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let content = if entry.file_type().is_dir() {
+            None
+        } else {
+            Some(fs::read(entry.path())?)
+        };
+        tree.insert(path, content);
+    }
+    Ok(tree)
+}
+
+fn copy_tree(from: &Path, to: &Path) -> Result<()> {
+    for entry in WalkDir::new(from) {
+        let entry = entry?;
+        let target = to.join(entry.path().strip_prefix(from)?);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_versions_sort_numerically() {
+        let mut versions = vec!["v1.10.0", "v1.9.2", "v1.9.10", "v0.4.0"];
+        versions.sort_by(|a, b| compare_versions(a, b));
+        assert_eq!(versions, ["v0.4.0", "v1.9.2", "v1.9.10", "v1.10.0"]);
+    }
+
+    #[test]
+    fn test_find_releases() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let binary = format!("flux{}", std::env::consts::EXE_SUFFIX);
+        for version in ["v1.6.0", "v1.5.1"] {
+            fs::create_dir_all(dir.path().join(version)).unwrap();
+            fs::write(dir.path().join(version).join(&binary), "").unwrap();
+        }
+        fs::create_dir_all(dir.path().join("notes")).unwrap();
+
+        let releases = find_releases(dir.path()).unwrap();
+        let versions: Vec<_> = releases.iter().map(|r| r.version.as_str()).collect();
+        assert_eq!(versions, ["v1.5.1", "v1.6.0"]);
+    }
+
+    #[test]
+    fn test_compare_trees_lists_differences() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let source = write_source(dir.path()).unwrap();
+        let copy = dir.path().join("copy");
+        copy_tree(&source, &copy).unwrap();
+        compare_trees(&source, &copy).unwrap();
+
+        fs::write(copy.join("data.bin"), "changed").unwrap();
+        fs::remove_file(copy.join("README.md")).unwrap();
+        let error = compare_trees(&source, &copy).unwrap_err().to_string();
+        assert_eq!(error, "missing README.md, differs data.bin");
+    }
+}
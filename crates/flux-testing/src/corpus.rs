@@ -0,0 +1,320 @@
+//! Curated corpus of tricky archives
+//!
+//! Each case reproduces a structure seen in archives from the wild: zip64
+//! records, pax extended headers, legacy cp437 file names, entries that try to
+//! escape the extraction directory, and a zip bomb. The archives are built
+//! byte-for-byte from code rather than checked in, so the corpus stays
+//! reviewable and identical on every machine.
+//!
+//! Use [`build_corpus`] to write all cases into a directory, then compare what
+//! flux does with them against the stored snapshots in [`crate::golden`].
+
+use crate::fixtures::{zip_datetime, ArchiveBuilder, ArchiveKind};
+use anyhow::Result;
+use flate2::Crc;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
+
+/// Modification time stamped on every corpus entry
+const CORPUS_MTIME: u64 = 1_700_000_000;
+
+/// Uncompressed size of the zip bomb payload
+const BOMB_SIZE: usize = 4 * 1024 * 1024;
+
+/// A single archive in the corpus
+#[derive(Debug, Clone)]
+pub struct CorpusCase {
+    /// Stable name, also used for the golden snapshot file
+    pub name: &'static str,
+    /// What makes this archive tricky
+    pub description: &'static str,
+    /// Location of the generated archive
+    pub path: PathBuf,
+}
+
+type Generator = fn(&Path) -> Result<()>;
+
+const CASES: &[(&str, &str, &str, Generator)] = &[
+    (
+        "zip64",
+        "zip64.zip",
+        "entries written with zip64 extra fields",
+        write_zip64,
+    ),
+    (
+        "pax_long_names",
+        "pax_long_names.tar",
+        "pax extended headers carrying long and non-ASCII paths",
+        write_pax_long_names,
+    ),
+    (
+        "cp437_names",
+        "cp437_names.zip",
+        "legacy zip names encoded in cp437 without the UTF-8 flag",
+        write_cp437_names,
+    ),
+    (
+        "symlink_escape",
+        "symlink_escape.tar",
+        "symlinks pointing outside the extraction root, followed by writes through them",
+        write_symlink_escape,
+    ),
+    (
+        "path_traversal",
+        "path_traversal.tar",
+        "entries with parent-directory and absolute paths",
+        write_path_traversal,
+    ),
+    (
+        "zip_bomb",
+        "zip_bomb.zip",
+        "a small zip that inflates to several megabytes of zeros",
+        write_zip_bomb,
+    ),
+];
+
+/// Names of every case in the corpus, in build order
+pub fn case_names() -> Vec<&'static str> {
+    CASES.iter().map(|(name, ..)| *name).collect()
+}
+
+/// Writes every corpus archive into `dir`
+pub fn build_corpus(dir: &Path) -> Result<Vec<CorpusCase>> {
+    fs::create_dir_all(dir)?;
+    CASES
+        .iter()
+        .map(|(name, file_name, description, generate)| {
+            let path = dir.join(file_name);
+            generate(&path)?;
+            Ok(CorpusCase {
+                name,
+                description,
+                path,
+            })
+        })
+        .collect()
+}
+
+/// Writes a single corpus archive by name into `dir`
+pub fn build_case(dir: &Path, name: &str) -> Result<CorpusCase> {
+    let (name, file_name, description, generate) = CASES
+        .iter()
+        .find(|(case, ..)| *case == name)
+        .ok_or_else(|| anyhow::anyhow!("unknown corpus case: {}", name))?;
+    fs::create_dir_all(dir)?;
+    let path = dir.join(file_name);
+    generate(&path)?;
+    Ok(CorpusCase {
+        name,
+        description,
+        path,
+    })
+}
+
+fn write_zip64(path: &Path) -> Result<()> {
+    let mut zip = ZipWriter::new(fs::File::create(path)?);
+    let options = SimpleFileOptions::default()
+        .large_file(true)
+        .unix_permissions(0o644)
+        .last_modified_time(zip_datetime(CORPUS_MTIME));
+
+    zip.add_directory("data/", options.unix_permissions(0o755))?;
+    zip.start_file("data/small.txt", options)?;
+    zip.write_all(b"zip64 records on a tiny file")?;
+    zip.start_file("data/empty.bin", options)?;
+    zip.finish()?;
+    Ok(())
+}
+
+fn write_pax_long_names(path: &Path) -> Result<()> {
+    let long_path = format!(
+        "{}/long_name.txt",
+        ["a_rather_long_directory_name"; 5].join("/")
+    );
+    let unicode_path = "pax/ünïcödé_名前.txt";
+
+    let mut builder = tar::Builder::new(fs::File::create(path)?);
+    for (name, data) in [(long_path.as_str(), "long"), (unicode_path, "unicode")] {
+        let records = pax_record("path", name);
+        let mut pax = tar::Header::new_ustar();
+        pax.set_entry_type(tar::EntryType::XHeader);
+        pax.set_path("PaxHeader/entry")?;
+        pax.set_size(records.len() as u64);
+        pax.set_mode(0o644);
+        pax.set_mtime(CORPUS_MTIME);
+        pax.set_cksum();
+        builder.append(&pax, records.as_slice())?;
+
+        // The ustar name is a truncated placeholder; readers must use the pax path
+        let mut header = tar::Header::new_ustar();
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_path("placeholder.txt")?;
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(CORPUS_MTIME);
+        header.set_cksum();
+        builder.append(&header, data.as_bytes())?;
+    }
+    builder.finish()?;
+    Ok(())
+}
+
+/// Encodes a single pax record; the length prefix counts itself
+fn pax_record(key: &str, value: &str) -> Vec<u8> {
+    let body = format!(" {}={}\n", key, value);
+    let mut len = body.len() + 1;
+    while len.to_string().len() + body.len() != len {
+        len += 1;
+    }
+    format!("{}{}", len, body).into_bytes()
+}
+
+fn write_cp437_names(path: &Path) -> Result<()> {
+    // 0x82 is "é" and 0x8B is "ï" in code page 437
+    let entries: [(&[u8], &[u8]); 2] = [
+        (b"caf\x82.txt", b"cafe"),
+        (b"na\x8bve/readme.txt", b"naive"),
+    ];
+    fs::write(path, raw_stored_zip(&entries))?;
+    Ok(())
+}
+
+/// Builds a stored (uncompressed) zip with raw name bytes and no UTF-8 flag
+///
+/// The `zip` crate always writes UTF-8 names, so legacy encodings have to be
+/// assembled by hand.
+fn raw_stored_zip(entries: &[(&[u8], &[u8])]) -> Vec<u8> {
+    let mtime = zip_datetime(CORPUS_MTIME);
+    let (time, date) = (mtime.timepart(), mtime.datepart());
+
+    let mut out = Vec::new();
+    let mut central = Vec::new();
+    for (name, data) in entries {
+        let mut crc = Crc::new();
+        crc.update(data);
+        let offset = out.len() as u32;
+
+        out.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        out.extend_from_slice(&10u16.to_le_bytes()); // version needed
+        out.extend_from_slice(&0u16.to_le_bytes()); // flags: no UTF-8
+        out.extend_from_slice(&0u16.to_le_bytes()); // stored
+        out.extend_from_slice(&time.to_le_bytes());
+        out.extend_from_slice(&date.to_le_bytes());
+        out.extend_from_slice(&crc.sum().to_le_bytes());
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes()); // extra length
+        out.extend_from_slice(name);
+        out.extend_from_slice(data);
+
+        central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        central.extend_from_slice(&10u16.to_le_bytes()); // version made by (MS-DOS)
+        central.extend_from_slice(&10u16.to_le_bytes());
+        central.extend_from_slice(&0u16.to_le_bytes());
+        central.extend_from_slice(&0u16.to_le_bytes());
+        central.extend_from_slice(&time.to_le_bytes());
+        central.extend_from_slice(&date.to_le_bytes());
+        central.extend_from_slice(&crc.sum().to_le_bytes());
+        central.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        central.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        central.extend_from_slice(&[0; 8]); // extra, comment, disk, internal attrs
+        central.extend_from_slice(&0u32.to_le_bytes()); // external attrs
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name);
+    }
+
+    let central_offset = out.len() as u32;
+    out.extend_from_slice(&central);
+    out.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+    out.extend_from_slice(&[0; 4]); // disk numbers
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    out.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    out.extend_from_slice(&central_offset.to_le_bytes());
+    out.extend_from_slice(&0u16.to_le_bytes()); // comment length
+    out
+}
+
+fn write_symlink_escape(path: &Path) -> Result<()> {
+    ArchiveBuilder::new(ArchiveKind::Tar)
+        .mtime(CORPUS_MTIME)
+        .file("ok.txt", "inside")
+        .symlink("escape", "../../outside")
+        .file("escape/pwned.txt", "written through a symlink")
+        .symlink("abs_link", "/etc/passwd")
+        .build(path)
+}
+
+fn write_path_traversal(path: &Path) -> Result<()> {
+    let mut builder = tar::Builder::new(fs::File::create(path)?);
+    for (name, data) in [
+        ("safe.txt", "safe"),
+        ("../evil.txt", "parent"),
+        ("nested/../../evil2.txt", "nested parent"),
+        ("/abs.txt", "absolute"),
+    ] {
+        // `Header::set_path` refuses these names, so write the raw bytes
+        let mut header = tar::Header::new_old();
+        header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_entry_type(tar::EntryType::Regular);
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(CORPUS_MTIME);
+        header.set_cksum();
+        builder.append(&header, data.as_bytes())?;
+    }
+    builder.finish()?;
+    Ok(())
+}
+
+fn write_zip_bomb(path: &Path) -> Result<()> {
+    let mut zip = ZipWriter::new(fs::File::create(path)?);
+    let options = SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated)
+        .compression_level(Some(9))
+        .unix_permissions(0o644)
+        .last_modified_time(zip_datetime(CORPUS_MTIME));
+
+    zip.start_file("zeros.bin", options)?;
+    zip.write_all(&vec![0u8; BOMB_SIZE])?;
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TestDir;
+
+    #[test]
+    fn test_build_corpus() {
+        let test_dir = TestDir::new().unwrap();
+        let cases = build_corpus(test_dir.path()).unwrap();
+
+        assert_eq!(cases.len(), case_names().len());
+        for case in &cases {
+            assert!(case.path.exists(), "{} was not written", case.name);
+        }
+    }
+
+    #[test]
+    fn test_pax_record_length() {
+        let record = pax_record("path", "a.txt");
+        assert_eq!(record, b"14 path=a.txt\n");
+
+        // Crossing a digit boundary must still count the prefix correctly
+        let record = pax_record("path", &"x".repeat(90));
+        let len: usize = String::from_utf8_lossy(&record)
+            .split(' ')
+            .next()
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert_eq!(len, record.len());
+    }
+}
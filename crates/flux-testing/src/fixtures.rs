@@ -2,6 +2,14 @@
 
 use crate::TestDir;
 use anyhow::Result;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use sevenz_rust::SevenZArchiveEntry;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::{FileOptions, FullFileOptions, SimpleFileOptions};
+use zip::{CompressionMethod, ZipWriter};
 
 /// Creates a standard test file structure
 pub fn create_test_files(test_dir: &TestDir) -> Result<()> {
@@ -58,3 +66,573 @@ pub fn create_symlink_structure(test_dir: &TestDir) -> Result<()> {
 
     Ok(())
 }
+
+/// Fixed modification time used for builder entries, so fixtures are reproducible
+const FIXTURE_MTIME: u64 = 1_700_000_000;
+
+/// Archive formats that [`ArchiveBuilder`] can produce
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Tar,
+    TarGz,
+    TarZst,
+    Zip,
+    SevenZ,
+}
+
+impl ArchiveKind {
+    /// File extension used for this format, without a leading dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveKind::Tar => "tar",
+            ArchiveKind::TarGz => "tar.gz",
+            ArchiveKind::TarZst => "tar.zst",
+            ArchiveKind::Zip => "zip",
+            ArchiveKind::SevenZ => "7z",
+        }
+    }
+}
+
+/// A single entry to be written by [`ArchiveBuilder`]
+#[derive(Debug, Clone)]
+pub enum FixtureEntry {
+    File {
+        path: String,
+        data: Vec<u8>,
+        mode: u32,
+    },
+    Dir {
+        path: String,
+        mode: u32,
+    },
+    Symlink {
+        path: String,
+        target: String,
+    },
+    HardLink {
+        path: String,
+        target: String,
+    },
+}
+
+/// Builds archive files with exactly the entries a test asks for
+///
+/// Entries are written in the order they were added, bypassing flux-core so
+/// tests can describe archives flux itself would never produce.
+#[derive(Debug, Clone)]
+pub struct ArchiveBuilder {
+    kind: ArchiveKind,
+    entries: Vec<FixtureEntry>,
+    mtime: u64,
+    stored: bool,
+    zip_extra: Vec<(u16, Vec<u8>, bool)>,
+    zip_comment: Option<String>,
+}
+
+impl ArchiveBuilder {
+    /// Creates an empty builder for the given format
+    pub fn new(kind: ArchiveKind) -> Self {
+        Self {
+            kind,
+            entries: Vec::new(),
+            mtime: FIXTURE_MTIME,
+            stored: false,
+            zip_extra: Vec::new(),
+            zip_comment: None,
+        }
+    }
+
+    /// Adds a regular file with mode 0644
+    pub fn file(self, path: &str, data: impl AsRef<[u8]>) -> Self {
+        self.file_with_mode(path, data, 0o644)
+    }
+
+    /// Adds a regular file with explicit permissions
+    pub fn file_with_mode(mut self, path: &str, data: impl AsRef<[u8]>, mode: u32) -> Self {
+        self.entries.push(FixtureEntry::File {
+            path: path.to_string(),
+            data: data.as_ref().to_vec(),
+            mode,
+        });
+        self
+    }
+
+    /// Adds a directory entry with mode 0755
+    pub fn dir(mut self, path: &str) -> Self {
+        self.entries.push(FixtureEntry::Dir {
+            path: path.to_string(),
+            mode: 0o755,
+        });
+        self
+    }
+
+    /// Adds a symlink entry; the target is stored verbatim
+    pub fn symlink(mut self, path: &str, target: &str) -> Self {
+        self.entries.push(FixtureEntry::Symlink {
+            path: path.to_string(),
+            target: target.to_string(),
+        });
+        self
+    }
+
+    /// Adds a hard link entry naming another entry; only tar fixtures hold
+    /// them
+    pub fn hard_link(mut self, path: &str, target: &str) -> Self {
+        self.entries.push(FixtureEntry::HardLink {
+            path: path.to_string(),
+            target: target.to_string(),
+        });
+        self
+    }
+
+    /// Adds files whose names use non-ASCII scripts and emoji
+    pub fn unicode_names(self) -> Self {
+        self.file("文档/说明.txt", "chinese")
+            .file("ドキュメント.md", "japanese")
+            .file("café/naïve résumé.txt", "latin accents")
+            .file("emoji_🚀.txt", "emoji")
+    }
+
+    /// Adds a file nested `depth` directories deep
+    pub fn deep_path(self, depth: usize) -> Self {
+        let dirs: Vec<String> = (0..depth).map(|i| format!("level{}", i)).collect();
+        let path = format!("{}/deep.txt", dirs.join("/"));
+        self.file(&path, "deep")
+    }
+
+    /// Overrides the modification time applied to every entry
+    pub fn mtime(mut self, secs: u64) -> Self {
+        self.mtime = secs;
+        self
+    }
+
+    /// Stores zip file entries uncompressed, so their data can be found in
+    /// the archive bytes
+    pub fn stored(mut self) -> Self {
+        self.stored = true;
+        self
+    }
+
+    /// Adds an extra field to every zip file entry, in both its local header
+    /// and the central directory, or only the latter with `central_only`
+    pub fn zip_extra_field(mut self, id: u16, data: impl AsRef<[u8]>, central_only: bool) -> Self {
+        self.zip_extra
+            .push((id, data.as_ref().to_vec(), central_only));
+        self
+    }
+
+    /// Sets the archive comment of a zip
+    pub fn zip_comment(mut self, comment: &str) -> Self {
+        self.zip_comment = Some(comment.to_string());
+        self
+    }
+
+    /// Returns the entries added so far
+    pub fn entries(&self) -> &[FixtureEntry] {
+        &self.entries
+    }
+
+    /// Writes the archive into `test_dir` as `<name>.<ext>` and returns its path
+    pub fn build_in(&self, test_dir: &TestDir, name: &str) -> Result<PathBuf> {
+        let path = test_dir
+            .path()
+            .join(format!("{}.{}", name, self.kind.extension()));
+        self.build(&path)?;
+        Ok(path)
+    }
+
+    /// Writes the archive to `output`
+    pub fn build(&self, output: &Path) -> Result<()> {
+        let file = File::create(output)?;
+        match self.kind {
+            ArchiveKind::Tar => {
+                self.write_tar(file)?;
+            }
+            ArchiveKind::TarGz => {
+                let encoder = self.write_tar(GzEncoder::new(file, Compression::default()))?;
+                encoder.finish()?;
+            }
+            ArchiveKind::TarZst => {
+                let encoder = self.write_tar(zstd::Encoder::new(file, 0)?)?;
+                encoder.finish()?;
+            }
+            ArchiveKind::Zip => self.write_zip(file)?,
+            ArchiveKind::SevenZ => self.write_7z(file)?,
+        }
+        Ok(())
+    }
+
+    fn write_tar<W: Write>(&self, writer: W) -> Result<W> {
+        let mut builder = tar::Builder::new(writer);
+
+        for entry in &self.entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_mtime(self.mtime);
+            match entry {
+                FixtureEntry::File { path, data, mode } => {
+                    header.set_entry_type(tar::EntryType::Regular);
+                    header.set_size(data.len() as u64);
+                    header.set_mode(*mode);
+                    if path.split('/').any(|part| part == "..") {
+                        // `tar` refuses to write names that extraction has to refuse
+                        header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+                        header.set_cksum();
+                        builder.append(&header, data.as_slice())?;
+                    } else {
+                        builder.append_data(&mut header, path, data.as_slice())?;
+                    }
+                }
+                FixtureEntry::Dir { path, mode } => {
+                    header.set_entry_type(tar::EntryType::Directory);
+                    header.set_size(0);
+                    header.set_mode(*mode);
+                    builder.append_data(&mut header, path, std::io::empty())?;
+                }
+                FixtureEntry::Symlink { path, target } => {
+                    header.set_entry_type(tar::EntryType::Symlink);
+                    header.set_size(0);
+                    header.set_mode(0o777);
+                    builder.append_link(&mut header, path, target)?;
+                }
+                FixtureEntry::HardLink { path, target } => {
+                    header.set_entry_type(tar::EntryType::Link);
+                    header.set_size(0);
+                    header.set_mode(0o644);
+                    builder.append_link(&mut header, path, target)?;
+                }
+            }
+        }
+
+        Ok(builder.into_inner()?)
+    }
+
+    fn write_zip(&self, file: File) -> Result<()> {
+        let mut zip = ZipWriter::new(file);
+        let mtime = zip_datetime(self.mtime);
+        let method = if self.stored {
+            CompressionMethod::Stored
+        } else {
+            CompressionMethod::Deflated
+        };
+
+        for entry in &self.entries {
+            match entry {
+                FixtureEntry::File { path, data, mode } => {
+                    let mut options: FullFileOptions = FileOptions::default()
+                        .unix_permissions(*mode)
+                        .last_modified_time(mtime)
+                        .compression_method(method);
+                    for (id, extra, central_only) in &self.zip_extra {
+                        options.add_extra_data(*id, extra.clone().into(), *central_only)?;
+                    }
+                    zip.start_file(path.as_str(), options)?;
+                    zip.write_all(data)?;
+                }
+                FixtureEntry::Dir { path, mode } => {
+                    let options = SimpleFileOptions::default()
+                        .unix_permissions(*mode)
+                        .last_modified_time(mtime);
+                    zip.add_directory(path.as_str(), options)?;
+                }
+                FixtureEntry::Symlink { path, target } => {
+                    let options = SimpleFileOptions::default().last_modified_time(mtime);
+                    zip.add_symlink(path.as_str(), target.as_str(), options)?;
+                }
+                FixtureEntry::HardLink { path, .. } => {
+                    anyhow::bail!("zip fixtures do not support hard links ({})", path);
+                }
+            }
+        }
+
+        if let Some(comment) = &self.zip_comment {
+            zip.set_comment(comment.as_str());
+        }
+        zip.finish()?;
+        Ok(())
+    }
+
+    fn write_7z(&self, file: File) -> Result<()> {
+        let mut writer = sevenz_rust::SevenZWriter::new(file)?;
+
+        for entry in &self.entries {
+            match entry {
+                FixtureEntry::File { path, data, .. } => {
+                    let mut archive_entry = SevenZArchiveEntry::new();
+                    archive_entry.name = path.clone();
+                    archive_entry.has_stream = !data.is_empty();
+                    writer.push_archive_entry(archive_entry, Some(data.as_slice()))?;
+                }
+                FixtureEntry::Dir { path, .. } => {
+                    let mut archive_entry = SevenZArchiveEntry::new();
+                    archive_entry.name = path.clone();
+                    archive_entry.is_directory = true;
+                    writer.push_archive_entry::<&[u8]>(archive_entry, None)?;
+                }
+                FixtureEntry::Symlink { path, .. } => {
+                    anyhow::bail!("7z fixtures do not support symlinks ({})", path);
+                }
+                FixtureEntry::HardLink { path, .. } => {
+                    anyhow::bail!("7z fixtures do not support hard links ({})", path);
+                }
+            }
+        }
+
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+/// Builds a directory tree on disk with exactly the entries a test asks
+/// for, as the input of packing tests
+///
+/// Entries are written in the order they were added, with their parent
+/// directories created as needed. Symlinks are only made on unix, and left
+/// out elsewhere.
+#[derive(Debug, Clone, Default)]
+pub struct SourceTree {
+    entries: Vec<FixtureEntry>,
+    mtimes: Vec<(String, u64)>,
+}
+
+impl SourceTree {
+    /// Creates an empty tree
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a regular file with mode 0644
+    pub fn file(self, path: &str, data: impl AsRef<[u8]>) -> Self {
+        self.file_with_mode(path, data, 0o644)
+    }
+
+    /// Adds a regular file with explicit permissions, which only unix keeps
+    pub fn file_with_mode(mut self, path: &str, data: impl AsRef<[u8]>, mode: u32) -> Self {
+        self.entries.push(FixtureEntry::File {
+            path: path.to_string(),
+            data: data.as_ref().to_vec(),
+            mode,
+        });
+        self
+    }
+
+    /// Adds a directory, empty unless entries below it are added too
+    pub fn dir(mut self, path: &str) -> Self {
+        self.entries.push(FixtureEntry::Dir {
+            path: path.to_string(),
+            mode: 0o755,
+        });
+        self
+    }
+
+    /// Adds a symlink; the target is stored verbatim
+    pub fn symlink(mut self, path: &str, target: &str) -> Self {
+        self.entries.push(FixtureEntry::Symlink {
+            path: path.to_string(),
+            target: target.to_string(),
+        });
+        self
+    }
+
+    /// Adds a hard link to the file `target`, added before it
+    pub fn hard_link(mut self, path: &str, target: &str) -> Self {
+        self.entries.push(FixtureEntry::HardLink {
+            path: path.to_string(),
+            target: target.to_string(),
+        });
+        self
+    }
+
+    /// Sets the modification time of the entry `path` once the tree is
+    /// written
+    pub fn mtime(mut self, path: &str, secs: u64) -> Self {
+        self.mtimes.push((path.to_string(), secs));
+        self
+    }
+
+    /// Returns the entries added so far
+    pub fn entries(&self) -> &[FixtureEntry] {
+        &self.entries
+    }
+
+    /// Writes the tree into the directory `name` in `dir` and returns its
+    /// path
+    pub fn create_in(&self, dir: &Path, name: &str) -> Result<PathBuf> {
+        let root = dir.join(name);
+        self.write_to(&root)?;
+        Ok(root)
+    }
+
+    /// Writes the tree into `root`, creating it if needed
+    pub fn write_to(&self, root: &Path) -> Result<()> {
+        fs::create_dir_all(root)?;
+        for entry in &self.entries {
+            match entry {
+                FixtureEntry::File { path, data, mode } => {
+                    let path = root.join(path);
+                    create_parent(&path)?;
+                    fs::write(&path, data)?;
+                    #[cfg(unix)]
+                    {
+                        use std::os::unix::fs::PermissionsExt;
+                        fs::set_permissions(&path, fs::Permissions::from_mode(*mode))?;
+                    }
+                    #[cfg(not(unix))]
+                    let _ = mode;
+                }
+                FixtureEntry::Dir { path, .. } => fs::create_dir_all(root.join(path))?,
+                FixtureEntry::Symlink { path, target } => {
+                    #[cfg(unix)]
+                    {
+                        let path = root.join(path);
+                        create_parent(&path)?;
+                        std::os::unix::fs::symlink(target, &path)?;
+                    }
+                    #[cfg(not(unix))]
+                    let _ = (path, target);
+                }
+                FixtureEntry::HardLink { path, target } => {
+                    let path = root.join(path);
+                    create_parent(&path)?;
+                    fs::hard_link(root.join(target), &path)?;
+                }
+            }
+        }
+        for (path, secs) in &self.mtimes {
+            let time = filetime::FileTime::from_unix_time(*secs as i64, 0);
+            filetime::set_symlink_file_times(root.join(path), time, time)?;
+        }
+        Ok(())
+    }
+}
+
+fn create_parent(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(())
+}
+
+/// Converts a unix timestamp into a zip DOS timestamp (UTC)
+pub(crate) fn zip_datetime(secs: u64) -> zip::DateTime {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil-from-days conversion (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = (yoe + era * 400 + i64::from(month <= 2)) as u16;
+
+    zip::DateTime::from_date_and_time(
+        year,
+        month,
+        day,
+        (rem / 3_600) as u8,
+        ((rem % 3_600) / 60) as u8,
+        (rem % 60) as u8,
+    )
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_tar_with_entries() {
+        let test_dir = TestDir::new().unwrap();
+        let archive = ArchiveBuilder::new(ArchiveKind::Tar)
+            .dir("docs")
+            .file_with_mode("docs/run.sh", "#!/bin/sh", 0o755)
+            .symlink("latest", "docs/run.sh")
+            .deep_path(3)
+            .build_in(&test_dir, "fixture")
+            .unwrap();
+
+        let mut tar = tar::Archive::new(File::open(&archive).unwrap());
+        let names: Vec<String> = tar
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap().path().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "docs",
+                "docs/run.sh",
+                "latest",
+                "level0/level1/level2/deep.txt"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_all_kinds() {
+        let test_dir = TestDir::new().unwrap();
+        for kind in [
+            ArchiveKind::Tar,
+            ArchiveKind::TarGz,
+            ArchiveKind::TarZst,
+            ArchiveKind::Zip,
+            ArchiveKind::SevenZ,
+        ] {
+            let archive = ArchiveBuilder::new(kind)
+                .unicode_names()
+                .build_in(&test_dir, "unicode")
+                .unwrap();
+            assert!(archive.to_string_lossy().ends_with(kind.extension()));
+
+            let out = test_dir
+                .create_dir(&format!("out_{}", kind.extension()))
+                .unwrap();
+            flux_core::extract(&archive, &out).unwrap();
+            assert_eq!(
+                std::fs::read_to_string(out.join("文档/说明.txt")).unwrap(),
+                "chinese"
+            );
+        }
+    }
+
+    #[test]
+    fn test_source_tree() {
+        let test_dir = TestDir::new().unwrap();
+        let source = SourceTree::new()
+            .file("docs/notes.txt", "notes")
+            .dir("empty")
+            .symlink("latest", "docs/notes.txt")
+            .mtime("docs/notes.txt", FIXTURE_MTIME)
+            .create_in(test_dir.path(), "source")
+            .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(source.join("docs/notes.txt")).unwrap(),
+            "notes"
+        );
+        assert!(source.join("empty").is_dir());
+        let modified = fs::metadata(source.join("docs/notes.txt"))
+            .unwrap()
+            .modified()
+            .unwrap();
+        assert_eq!(
+            modified,
+            std::time::UNIX_EPOCH + std::time::Duration::from_secs(FIXTURE_MTIME)
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            fs::read_link(source.join("latest")).unwrap(),
+            Path::new("docs/notes.txt")
+        );
+    }
+
+    #[test]
+    fn test_zip_datetime() {
+        let dt = zip_datetime(FIXTURE_MTIME);
+        assert_eq!((dt.year(), dt.month(), dt.day()), (2023, 11, 14));
+        assert_eq!((dt.hour(), dt.minute(), dt.second()), (22, 13, 20));
+    }
+}
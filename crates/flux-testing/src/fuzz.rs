@@ -0,0 +1,278 @@
+//! Support code for fuzzing the extractors
+//!
+//! The cargo-fuzz targets under `fuzz/` are thin wrappers around
+//! [`check_inspect`] and [`check_extract`]. Both run flux against arbitrary
+//! bytes inside a scratch directory and panic only on real bugs: a panic from
+//! flux or its parsers, or an extraction that leaves files outside the output
+//! directory. Ordinary errors for malformed input are expected and ignored.
+//!
+//! [`Mutator`] and [`seed_inputs`] make it possible to run a short,
+//! deterministic fuzzing pass as a normal test without libFuzzer.
+
+use crate::corpus;
+use crate::fixtures::{ArchiveBuilder, ArchiveKind};
+use anyhow::Result;
+use flux_core::archive::{extract_from_reader, inspect};
+use flux_core::security::SecurityOptions;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Input formats the fuzz targets exercise
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FuzzFormat {
+    Tar,
+    TarGz,
+    TarZst,
+    Zip,
+    SevenZ,
+}
+
+impl FuzzFormat {
+    pub const ALL: [FuzzFormat; 5] = [
+        FuzzFormat::Tar,
+        FuzzFormat::TarGz,
+        FuzzFormat::TarZst,
+        FuzzFormat::Zip,
+        FuzzFormat::SevenZ,
+    ];
+
+    /// Extension understood by flux-core, without a leading dot
+    pub fn extension(&self) -> &'static str {
+        match self {
+            FuzzFormat::Tar => "tar",
+            FuzzFormat::TarGz => "tar.gz",
+            FuzzFormat::TarZst => "tar.zst",
+            FuzzFormat::Zip => "zip",
+            FuzzFormat::SevenZ => "7z",
+        }
+    }
+}
+
+/// Resource limits applied to every fuzz iteration
+///
+/// Inputs above `max_input_len` are ignored so the fuzzer spends its time on
+/// structure rather than size; the output limits stop decompression bombs
+/// from exhausting the disk.
+#[derive(Debug, Clone)]
+pub struct FuzzLimits {
+    pub max_input_len: usize,
+    pub max_output_bytes: u64,
+    pub max_entries: u64,
+}
+
+impl Default for FuzzLimits {
+    fn default() -> Self {
+        Self {
+            max_input_len: 1024 * 1024,
+            max_output_bytes: 16 * 1024 * 1024,
+            max_entries: 10_000,
+        }
+    }
+}
+
+impl FuzzLimits {
+    fn security_options(&self) -> SecurityOptions {
+        SecurityOptions {
+            max_extraction_size: self.max_output_bytes,
+            max_entries: self.max_entries,
+            allow_external_symlinks: false,
+            check_disk_space: false,
+            ..Default::default()
+        }
+    }
+}
+
+/// Runs `inspect` on `data` as an archive of `format`
+pub fn check_inspect(data: &[u8], format: FuzzFormat, limits: &FuzzLimits) {
+    if data.len() > limits.max_input_len {
+        return;
+    }
+
+    let scratch = tempfile::TempDir::new().expect("failed to create scratch directory");
+    let archive = scratch.path().join(format!("input.{}", format.extension()));
+    fs::write(&archive, data).expect("failed to write fuzz input");
+
+    let _ = inspect(&archive);
+}
+
+/// Extracts `data` as an archive of `format` and asserts nothing escaped
+///
+/// The output directory is nested two levels inside the scratch directory so
+/// an escaping entry lands somewhere it can be detected.
+pub fn check_extract(data: &[u8], format: FuzzFormat, limits: &FuzzLimits) {
+    if data.len() > limits.max_input_len {
+        return;
+    }
+
+    let scratch = tempfile::TempDir::new().expect("failed to create scratch directory");
+    let sandbox = scratch.path().join("sandbox");
+    let output = sandbox.join("jail").join("out");
+    fs::create_dir_all(&output).expect("failed to create output directory");
+
+    let _ = extract_from_reader(
+        Cursor::new(data),
+        format.extension(),
+        &output,
+        &limits.security_options(),
+    );
+
+    let escaped = entries_outside(&sandbox, &output);
+    assert!(
+        escaped.is_empty(),
+        "{} extraction wrote outside the output directory: {:?}",
+        format.extension(),
+        escaped
+    );
+}
+
+/// Lists entries under `sandbox` that are neither `output`, its contents, nor
+/// one of its ancestors
+fn entries_outside(sandbox: &Path, output: &Path) -> Vec<String> {
+    WalkDir::new(sandbox)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| entry.path() != output)
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| !output.starts_with(entry.path()))
+        .map(|entry| entry.path().display().to_string())
+        .collect()
+}
+
+/// Well-formed archives of `format` to start mutating from
+pub fn seed_inputs(format: FuzzFormat) -> Result<Vec<Vec<u8>>> {
+    let scratch = crate::TestDir::new()?;
+    let mut seeds = Vec::new();
+
+    let kind = match format {
+        FuzzFormat::Tar => Some(ArchiveKind::Tar),
+        FuzzFormat::TarGz => Some(ArchiveKind::TarGz),
+        FuzzFormat::Zip => Some(ArchiveKind::Zip),
+        FuzzFormat::SevenZ => Some(ArchiveKind::SevenZ),
+        FuzzFormat::TarZst => None,
+    };
+    if let Some(kind) = kind {
+        let mut builder = ArchiveBuilder::new(kind)
+            .file("a.txt", "hello")
+            .dir("sub")
+            .file_with_mode("sub/run.sh", "#!/bin/sh\n", 0o755)
+            .unicode_names();
+        if kind != ArchiveKind::SevenZ {
+            builder = builder.symlink("link", "a.txt");
+        }
+        seeds.push(fs::read(builder.build_in(&scratch, "seed")?)?);
+    } else {
+        let tar = ArchiveBuilder::new(ArchiveKind::Tar)
+            .file("a.txt", "hello")
+            .symlink("link", "a.txt")
+            .build_in(&scratch, "seed")?;
+        seeds.push(zstd::encode_all(fs::read(tar)?.as_slice(), 3)?);
+    }
+
+    let corpus_dir = scratch.path().join("corpus");
+    for case in corpus::build_corpus(&corpus_dir)? {
+        let name = case.path.to_string_lossy();
+        if name.ends_with(&format!(".{}", format.extension())) {
+            seeds.push(fs::read(&case.path)?);
+        }
+    }
+
+    Ok(seeds)
+}
+
+/// Small deterministic byte mutator
+///
+/// This is no substitute for libFuzzer's coverage-guided search, but it is
+/// enough to shake out crashes near valid inputs in a regular test run.
+#[derive(Debug, Clone)]
+pub struct Mutator {
+    state: u64,
+}
+
+impl Mutator {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed.max(1) }
+    }
+
+    fn next(&mut self) -> u64 {
+        // xorshift64
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+
+    /// Returns a mutated copy of `input`
+    pub fn mutate(&mut self, input: &[u8]) -> Vec<u8> {
+        let mut data = input.to_vec();
+        for _ in 0..=self.below(4) {
+            if data.is_empty() {
+                data.push(self.next() as u8);
+                continue;
+            }
+            let pos = self.below(data.len());
+            match self.below(5) {
+                0 => data[pos] ^= 1 << self.below(8),
+                1 => data[pos] = self.next() as u8,
+                2 => data.truncate(pos),
+                3 => data.insert(pos, self.next() as u8),
+                // Boundary values tend to hit size and offset fields
+                _ => {
+                    let end = (pos + 4).min(data.len());
+                    let fill = if self.below(2) == 0 { 0x00 } else { 0xff };
+                    data[pos..end].fill(fill);
+                }
+            }
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ITERATIONS: usize = 64;
+
+    #[test]
+    fn test_mutated_seeds_do_not_crash_or_escape() {
+        let limits = FuzzLimits::default();
+        for format in FuzzFormat::ALL {
+            let seeds = seed_inputs(format).unwrap();
+            assert!(!seeds.is_empty(), "no seeds for {:?}", format);
+
+            let mut mutator = Mutator::new(0x5eed ^ format as u64);
+            for seed in &seeds {
+                check_extract(seed, format, &limits);
+                for _ in 0..ITERATIONS {
+                    let input = mutator.mutate(seed);
+                    check_inspect(&input, format, &limits);
+                    check_extract(&input, format, &limits);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_corpus_escapes_are_contained() {
+        let test_dir = crate::TestDir::new().unwrap();
+        let limits = FuzzLimits::default();
+        for name in ["symlink_escape", "path_traversal"] {
+            let case = corpus::build_case(test_dir.path(), name).unwrap();
+            check_extract(&fs::read(&case.path).unwrap(), FuzzFormat::Tar, &limits);
+        }
+    }
+
+    #[test]
+    fn test_mutator_is_deterministic() {
+        let input = b"0123456789abcdef";
+        let a = Mutator::new(7).mutate(input);
+        let b = Mutator::new(7).mutate(input);
+        assert_eq!(a, b);
+    }
+}
@@ -0,0 +1,284 @@
+//! Property-based generators for round-trip testing
+//!
+//! The proptest strategies here produce random directory trees (names, sizes,
+//! permissions, symlinks, nesting). A generated tree can be written to disk and
+//! checked with [`assert_round_trip`], which packs it, extracts it again and
+//! compares the result entry by entry.
+//!
+//! Names are lowercase only, so trees never collide on case-insensitive
+//! filesystems.
+
+use crate::assertions::assert_tree_matches;
+use crate::TestDir;
+use anyhow::{Context, Result};
+use flux_core::archive::{extract, pack_with_strategy, PackOptions};
+use proptest::collection::{btree_map, vec};
+use proptest::prelude::*;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// A node in a generated directory tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TreeNode {
+    File {
+        contents: Vec<u8>,
+        mode: u32,
+    },
+    Dir(BTreeMap<String, TreeNode>),
+    /// Relative symlink; the target may or may not exist
+    Symlink(String),
+}
+
+/// A generated directory tree, keyed by entry name
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GeneratedTree {
+    pub root: BTreeMap<String, TreeNode>,
+}
+
+/// Limits for [`arb_tree`]
+#[derive(Debug, Clone)]
+pub struct TreeConfig {
+    /// Maximum directory nesting below the root
+    pub max_depth: u32,
+    /// Maximum number of entries in a single directory
+    pub max_entries: usize,
+    /// Maximum size of a generated file in bytes
+    pub max_file_size: usize,
+    /// Include non-ASCII names
+    pub unicode_names: bool,
+    /// Include symlinks (only honoured on unix)
+    pub symlinks: bool,
+}
+
+impl Default for TreeConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 3,
+            max_entries: 5,
+            max_file_size: 4096,
+            unicode_names: true,
+            symlinks: cfg!(unix),
+        }
+    }
+}
+
+/// Generates a single path component
+pub fn arb_file_name(unicode: bool) -> BoxedStrategy<String> {
+    let ascii = "[a-z0-9_][a-z0-9_.-]{0,11}";
+    if unicode {
+        prop_oneof![3 => ascii, 1 => "[a-z0-9éüß文字ж🚀]{1,8}"].boxed()
+    } else {
+        ascii.boxed()
+    }
+}
+
+/// Generates file contents, mixing random bytes with highly compressible text
+pub fn arb_contents(max_size: usize) -> BoxedStrategy<Vec<u8>> {
+    prop_oneof![
+        vec(any::<u8>(), 0..=max_size),
+        ("[a-z ]{1,16}", 0..=max_size / 16 + 1)
+            .prop_map(move |(pattern, n)| pattern.repeat(n).into_bytes()),
+    ]
+    .boxed()
+}
+
+/// Generates a unix permission mode for a file
+pub fn arb_mode() -> BoxedStrategy<u32> {
+    prop_oneof![Just(0o644), Just(0o600), Just(0o755), Just(0o444)].boxed()
+}
+
+/// Generates a random directory tree within the given limits
+pub fn arb_tree(config: TreeConfig) -> impl Strategy<Value = GeneratedTree> {
+    let name = arb_file_name(config.unicode_names);
+    let file = (arb_contents(config.max_file_size), arb_mode())
+        .prop_map(|(contents, mode)| TreeNode::File { contents, mode });
+
+    let leaf = if config.symlinks && cfg!(unix) {
+        prop_oneof![
+            4 => file,
+            1 => arb_file_name(false).prop_map(TreeNode::Symlink),
+        ]
+        .boxed()
+    } else {
+        file.boxed()
+    };
+
+    let max_entries = config.max_entries;
+    let dir_name = name.clone();
+    let node = leaf.prop_recursive(config.max_depth, 64, max_entries as u32, move |inner| {
+        btree_map(dir_name.clone(), inner, 0..=max_entries).prop_map(TreeNode::Dir)
+    });
+
+    btree_map(name, node, 0..=max_entries).prop_map(|root| GeneratedTree { root })
+}
+
+impl GeneratedTree {
+    /// Materializes the tree inside `dir`, which must already exist
+    pub fn write_to(&self, dir: &Path) -> Result<()> {
+        write_nodes(dir, &self.root)
+    }
+
+    /// Number of regular files in the tree
+    pub fn file_count(&self) -> usize {
+        fn count(nodes: &BTreeMap<String, TreeNode>) -> usize {
+            nodes
+                .values()
+                .map(|node| match node {
+                    TreeNode::File { .. } => 1,
+                    TreeNode::Dir(children) => count(children),
+                    TreeNode::Symlink(_) => 0,
+                })
+                .sum()
+        }
+        count(&self.root)
+    }
+
+    /// Reads a directory from disk back into a tree
+    pub fn read_from(dir: &Path) -> Result<Self> {
+        Ok(Self {
+            root: read_nodes(dir)?,
+        })
+    }
+}
+
+fn write_nodes(dir: &Path, nodes: &BTreeMap<String, TreeNode>) -> Result<()> {
+    for (name, node) in nodes {
+        let path = dir.join(name);
+        match node {
+            TreeNode::File { contents, mode } => {
+                fs::write(&path, contents)?;
+                set_mode(&path, *mode)?;
+            }
+            TreeNode::Dir(children) => {
+                fs::create_dir(&path)?;
+                write_nodes(&path, children)?;
+            }
+            TreeNode::Symlink(target) => {
+                #[cfg(unix)]
+                std::os::unix::fs::symlink(target, &path)?;
+                #[cfg(not(unix))]
+                anyhow::bail!(
+                    "cannot create symlink {:?} -> {} on this platform",
+                    path,
+                    target
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn read_nodes(dir: &Path) -> Result<BTreeMap<String, TreeNode>> {
+    let mut nodes = BTreeMap::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let file_type = entry.file_type()?;
+
+        let node = if file_type.is_symlink() {
+            let target = fs::read_link(&path)?;
+            TreeNode::Symlink(target.to_string_lossy().into_owned())
+        } else if file_type.is_dir() {
+            TreeNode::Dir(read_nodes(&path)?)
+        } else {
+            TreeNode::File {
+                contents: fs::read(&path)?,
+                mode: get_mode(&path)?,
+            }
+        };
+        nodes.insert(name, node);
+    }
+    Ok(nodes)
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: u32) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: u32) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn get_mode(path: &Path) -> Result<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    Ok(fs::metadata(path)?.permissions().mode() & 0o777)
+}
+
+/// Modes are not tracked off unix, so report the generator default
+#[cfg(not(unix))]
+fn get_mode(_path: &Path) -> Result<u32> {
+    Ok(0o644)
+}
+
+/// Packs `tree` as `format` (e.g. "tar", "tar.zst", "zip"), extracts it and
+/// asserts that the extracted tree is identical to the original
+pub fn assert_round_trip(tree: &GeneratedTree, format: &str) -> Result<()> {
+    let test_dir = TestDir::new()?;
+    let source = test_dir.create_dir("src/tree")?;
+    tree.write_to(&source)?;
+
+    let archive = test_dir.path().join(format!("tree.{}", format));
+    let options = PackOptions {
+        smart: false,
+        ..Default::default()
+    };
+    pack_with_strategy(&source, &archive, Some(format), options)
+        .with_context(|| format!("packing {} failed", format))?;
+
+    let output = test_dir.create_dir("out")?;
+    extract(&archive, &output).with_context(|| format!("extracting {} failed", format))?;
+
+    // An empty tree produces no entries, so the root is never created
+    let extracted_root = output.join("tree");
+    if !extracted_root.exists() {
+        fs::create_dir(&extracted_root)?;
+    }
+
+    assert_tree_matches(&source, &extracted_root)
+        .with_context(|| format!("round trip through {} failed", format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(16))]
+
+        #[test]
+        fn test_tar_round_trip(tree in arb_tree(TreeConfig::default())) {
+            assert_round_trip(&tree, "tar").unwrap();
+        }
+
+        #[test]
+        fn test_tar_zst_round_trip(tree in arb_tree(TreeConfig::default())) {
+            assert_round_trip(&tree, "tar.zst").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_write_and_read_back() {
+        let mut root = BTreeMap::new();
+        root.insert(
+            "a.txt".to_string(),
+            TreeNode::File {
+                contents: b"hello".to_vec(),
+                mode: 0o644,
+            },
+        );
+        root.insert("sub".to_string(), TreeNode::Dir(BTreeMap::new()));
+        let tree = GeneratedTree { root };
+
+        let test_dir = TestDir::new().unwrap();
+        tree.write_to(test_dir.path()).unwrap();
+        assert_eq!(GeneratedTree::read_from(test_dir.path()).unwrap(), tree);
+        assert_eq!(tree.file_count(), 1);
+    }
+}
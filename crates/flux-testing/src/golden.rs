@@ -0,0 +1,241 @@
+//! Golden snapshot assertions
+//!
+//! [`snapshot_archive`] runs `inspect`, a plain extraction and a secure
+//! extraction against an archive and records what happened in a serializable
+//! [`ArchiveSnapshot`].
+//! [`assert_golden`] compares any serializable value with a JSON file stored
+//! under `crates/flux-testing/golden/`.
+//!
+//! When behaviour changes on purpose, regenerate the snapshots with
+//! `FLUX_UPDATE_GOLDEN=1 cargo test -p flux-testing` and review the diff.
+
+use anyhow::{Context, Result};
+use flux_core::archive::secure_extractor::extract_archive_secure;
+use flux_core::archive::{create_extractor, extract_with_options, inspect, ExtractOptions};
+use flux_core::security::SecurityOptions;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Environment variable that makes [`assert_golden`] rewrite snapshots
+pub const UPDATE_ENV: &str = "FLUX_UPDATE_GOLDEN";
+
+/// Directory holding the checked-in snapshots
+pub fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("golden")
+}
+
+/// Result of an operation, recorded as either its value or its error
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome<T> {
+    Ok(T),
+    Error(String),
+}
+
+/// An archive entry as reported by `inspect`
+///
+/// Compressed sizes are left out because they depend on the codec version.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EntrySnapshot {
+    pub path: String,
+    pub size: u64,
+    pub kind: &'static str,
+    /// Octal permission bits, if the format stores them
+    pub mode: Option<String>,
+    pub mtime: Option<i64>,
+    pub link_target: Option<String>,
+}
+
+/// What an extraction left on disk
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExtractSnapshot {
+    pub result: Outcome<()>,
+    /// Everything under the output directory, one line per entry
+    pub tree: Vec<String>,
+    /// Anything written next to the output directory instead of inside it
+    pub escaped: Vec<String>,
+}
+
+/// Everything flux reports about one archive
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArchiveSnapshot {
+    pub inspect: Outcome<Vec<EntrySnapshot>>,
+    pub extract: ExtractSnapshot,
+    pub secure_extract: ExtractSnapshot,
+}
+
+/// Inspects and extracts `archive`, recording the results
+///
+/// Extraction happens in a scratch directory, and errors are recorded by
+/// [`error_kind`], so snapshots depend neither on where it lives nor on the
+/// platform's error messages.
+pub fn snapshot_archive(archive: &Path) -> Result<ArchiveSnapshot> {
+    let inspect = match inspect(archive) {
+        Ok(entries) => Outcome::Ok(
+            entries
+                .into_iter()
+                .map(|entry| EntrySnapshot {
+                    path: entry.path.to_string_lossy().into_owned(),
+                    size: entry.size,
+                    kind: if entry.is_symlink {
+                        "symlink"
+                    } else if entry.is_dir {
+                        "dir"
+                    } else {
+                        "file"
+                    },
+                    mode: entry.mode.map(|mode| format!("{:o}", mode)),
+                    mtime: entry.mtime,
+                    link_target: entry
+                        .link_target
+                        .map(|target| target.to_string_lossy().into_owned()),
+                })
+                .collect(),
+        ),
+        Err(e) => Outcome::Error(error_kind(&e)),
+    };
+
+    let extract = snapshot_extraction(|out| {
+        extract_with_options(archive, out, ExtractOptions::default())?.into_result()
+    })?;
+    let secure_extract = snapshot_extraction(|out| {
+        let extractor = create_extractor(archive)?;
+        let options = SecurityOptions {
+            check_disk_space: false,
+            ..Default::default()
+        };
+        extract_archive_secure(archive, out, extractor, options)
+    })?;
+
+    Ok(ArchiveSnapshot {
+        inspect,
+        extract,
+        secure_extract,
+    })
+}
+
+/// Runs `extract` into `<scratch>/sandbox/jail/out` and records the result
+///
+/// The extra directory levels give escaping entries somewhere harmless to
+/// land, where they are reported instead of touching the real filesystem.
+fn snapshot_extraction<F>(extract: F) -> Result<ExtractSnapshot>
+where
+    F: FnOnce(&Path) -> flux_core::Result<()>,
+{
+    let scratch = tempfile::TempDir::new()?;
+    let sandbox = scratch.path().join("sandbox");
+    let out = sandbox.join("jail").join("out");
+    fs::create_dir_all(&out)?;
+
+    let result = match extract(&out) {
+        Ok(()) => Outcome::Ok(()),
+        Err(e) => Outcome::Error(error_kind(&e)),
+    };
+
+    Ok(ExtractSnapshot {
+        result,
+        tree: describe_tree(&out, None)?,
+        escaped: describe_tree(&sandbox, Some(&out))?
+            .into_iter()
+            .filter(|line| line != "dir jail")
+            .collect(),
+    })
+}
+
+/// The code of `error`, with the I/O error kind or the number of failed
+/// operations where there is one, such as `io: AlreadyExists`
+///
+/// Messages are left out: they carry OS error text and numbers that differ
+/// between platforms.
+pub fn error_kind(error: &flux_core::Error) -> String {
+    match error.inner() {
+        flux_core::Error::Io(e) => format!("{}: {:?}", error.code(), e.kind()),
+        flux_core::Error::PartialFailure { count } => format!("{}: {}", error.code(), count),
+        _ => error.code().to_string(),
+    }
+}
+
+/// Lists every entry below `root` as `<kind> <relative path>[ details]`,
+/// leaving out `skip` and its contents
+fn describe_tree(root: &Path, skip: Option<&Path>) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    let walker = WalkDir::new(root)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| Some(entry.path()) != skip);
+    for entry in walker {
+        let entry = entry?;
+        let relative = entry.path().strip_prefix(root)?.to_string_lossy();
+        let file_type = entry.file_type();
+        let line = if file_type.is_symlink() {
+            let target = fs::read_link(entry.path())?;
+            format!("symlink {} -> {}", relative, target.display())
+        } else if file_type.is_dir() {
+            format!("dir {}", relative)
+        } else {
+            format!("file {} ({} bytes)", relative, entry.metadata()?.len())
+        };
+        lines.push(line.replace('\\', "/"));
+    }
+    Ok(lines)
+}
+
+/// Asserts that `value` serializes to the snapshot `golden/<name>.json`
+///
+/// With `FLUX_UPDATE_GOLDEN` set, the snapshot is written instead.
+pub fn assert_golden<T: Serialize>(name: &str, value: &T) -> Result<()> {
+    let path = golden_dir().join(format!("{}.json", name));
+    let actual = serde_json::to_string_pretty(value)? + "\n";
+
+    if std::env::var_os(UPDATE_ENV).is_some() {
+        fs::create_dir_all(golden_dir())?;
+        fs::write(&path, &actual)?;
+        return Ok(());
+    }
+
+    let expected = fs::read_to_string(&path).with_context(|| {
+        format!(
+            "missing golden snapshot {:?}; run with {}=1 to create it",
+            path, UPDATE_ENV
+        )
+    })?;
+    assert_eq!(
+        expected, actual,
+        "golden snapshot {:?} does not match; run with {}=1 to update it",
+        path, UPDATE_ENV
+    );
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::corpus;
+    use crate::TestDir;
+
+    #[test]
+    fn test_corpus_matches_golden() {
+        let test_dir = TestDir::new().unwrap();
+        for case in corpus::build_corpus(test_dir.path()).unwrap() {
+            let snapshot = snapshot_archive(&case.path).unwrap();
+            assert_golden(case.name, &snapshot).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_corpus_never_escapes_secure_extraction() {
+        let test_dir = TestDir::new().unwrap();
+        for case in corpus::build_corpus(test_dir.path()).unwrap() {
+            let snapshot = snapshot_archive(&case.path).unwrap();
+            assert!(
+                snapshot.secure_extract.escaped.is_empty(),
+                "{} escaped: {:?}",
+                case.name,
+                snapshot.secure_extract.escaped
+            );
+        }
+    }
+}
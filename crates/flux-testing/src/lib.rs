@@ -8,7 +8,15 @@ use std::path::{Path, PathBuf};
 use tempfile::TempDir;
 
 pub mod assertions;
+pub mod bench;
+#[cfg(feature = "cloud")]
+pub mod cloud;
+pub mod compat;
+pub mod corpus;
 pub mod fixtures;
+pub mod fuzz;
+pub mod generators;
+pub mod golden;
 pub mod helpers;
 
 /// Creates a temporary test directory with cleanup on drop
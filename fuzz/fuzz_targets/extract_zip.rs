@@ -0,0 +1,8 @@
+#![no_main]
+
+use flux_testing::fuzz::{check_extract, FuzzFormat, FuzzLimits};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    check_extract(data, FuzzFormat::Zip, &FuzzLimits::default());
+});
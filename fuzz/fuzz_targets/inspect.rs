@@ -0,0 +1,12 @@
+#![no_main]
+
+use flux_testing::fuzz::{check_inspect, FuzzFormat, FuzzLimits};
+use libfuzzer_sys::fuzz_target;
+
+// The first byte picks the format so one corpus covers every parser
+fuzz_target!(|data: &[u8]| {
+    if let Some((&selector, input)) = data.split_first() {
+        let format = FuzzFormat::ALL[selector as usize % FuzzFormat::ALL.len()];
+        check_inspect(input, format, &FuzzLimits::default());
+    }
+});